@@ -1,10 +1,18 @@
 use anyhow::{anyhow, Context, Result};
+use base64::Engine;
 use reqwest::blocking::Client;
 use serde::Deserialize;
 use serde_json::json;
 use std::collections::HashSet;
 use std::time::Duration;
 
+use super::error::{parse_retry_after, ProviderError};
+use super::network;
+use super::GenerationOptions;
+
+/// Límite de tokens de cada respuesta de chat.
+pub const MAX_TOKENS: u32 = 512;
+
 #[derive(Debug, Deserialize)]
 struct AnthropicContent {
     #[serde(rename = "type", default)]
@@ -30,6 +38,109 @@ pub struct AnthropicModel {
     pub aliases: Vec<String>,
     #[serde(rename = "type", default)]
     pub r#type: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    /// Acepta imágenes en la entrada. La API no lo publica; se deduce de la familia.
+    #[serde(default)]
+    pub supports_vision: bool,
+}
+
+/// Capacidades de una familia de modelos de Claude. El listado de modelos de la API solo
+/// devuelve identificador y nombre, así que el contexto y la visión se completan aquí.
+struct FamilyCapabilities {
+    prefix: &'static str,
+    context_window: u32,
+    output_token_limit: u32,
+    vision: bool,
+}
+
+/// Familias conocidas; los prefijos más específicos van antes.
+const KNOWN_FAMILIES: &[FamilyCapabilities] = &[
+    FamilyCapabilities {
+        prefix: "claude-opus-4",
+        context_window: 200_000,
+        output_token_limit: 32_000,
+        vision: true,
+    },
+    FamilyCapabilities {
+        prefix: "claude-sonnet-4",
+        context_window: 200_000,
+        output_token_limit: 64_000,
+        vision: true,
+    },
+    FamilyCapabilities {
+        prefix: "claude-haiku-4",
+        context_window: 200_000,
+        output_token_limit: 64_000,
+        vision: true,
+    },
+    FamilyCapabilities {
+        prefix: "claude-3-7-sonnet",
+        context_window: 200_000,
+        output_token_limit: 64_000,
+        vision: true,
+    },
+    FamilyCapabilities {
+        prefix: "claude-3-5-sonnet",
+        context_window: 200_000,
+        output_token_limit: 8_192,
+        vision: true,
+    },
+    FamilyCapabilities {
+        prefix: "claude-3-5-haiku",
+        context_window: 200_000,
+        output_token_limit: 8_192,
+        vision: true,
+    },
+    FamilyCapabilities {
+        prefix: "claude-3-",
+        context_window: 200_000,
+        output_token_limit: 4_096,
+        vision: true,
+    },
+    FamilyCapabilities {
+        prefix: "claude-2.1",
+        context_window: 200_000,
+        output_token_limit: 4_096,
+        vision: false,
+    },
+    FamilyCapabilities {
+        prefix: "claude-2",
+        context_window: 100_000,
+        output_token_limit: 4_096,
+        vision: false,
+    },
+    FamilyCapabilities {
+        prefix: "claude-instant",
+        context_window: 100_000,
+        output_token_limit: 4_096,
+        vision: false,
+    },
+];
+
+impl AnthropicModel {
+    /// Completa contexto, salida máxima y visión con los datos conocidos de su familia,
+    /// sin pisar los que haya devuelto la API.
+    pub fn enrich_capabilities(&mut self) {
+        let Some(family) = KNOWN_FAMILIES
+            .iter()
+            .find(|family| self.id.starts_with(family.prefix))
+        else {
+            return;
+        };
+        self.context_window.get_or_insert(family.context_window);
+        self.output_token_limit
+            .get_or_insert(family.output_token_limit);
+        self.supports_vision |= family.vision;
+    }
+
+    pub fn label(&self) -> &str {
+        self.display_name
+            .as_deref()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.id)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -53,18 +164,64 @@ struct AnthropicErrorResponse {
 
 /// Envía un mensaje a la API de Anthropic Claude y devuelve la primera respuesta textual.
 pub fn send_message(api_key: &str, model: &str, prompt: &str) -> Result<String> {
-    let client = Client::builder()
+    send_message_with(api_key, model, prompt, &GenerationOptions::default())
+}
+
+/// Igual que `send_message`, con las instrucciones y parámetros de la conversación.
+pub fn send_message_with(
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+    options: &GenerationOptions,
+) -> Result<String> {
+    let content = json!([{ "type": "text", "text": prompt }]);
+    send_content(api_key, model, &content, options)
+}
+
+/// Pide a un modelo con visión que responda a `prompt` sobre la imagen indicada.
+/// `media_type` es el tipo MIME de la imagen (`image/png`, `image/jpeg`…).
+pub fn describe_image(
+    api_key: &str,
+    model: &str,
+    image: &[u8],
+    media_type: &str,
+    prompt: &str,
+) -> Result<String> {
+    let content = json!([
+        {
+            "type": "image",
+            "source": {
+                "type": "base64",
+                "media_type": media_type,
+                "data": base64::engine::general_purpose::STANDARD.encode(image),
+            },
+        },
+        { "type": "text", "text": prompt },
+    ]);
+    send_content(api_key, model, &content, &GenerationOptions::default())
+}
+
+/// Envía un mensaje con los bloques de contenido dados, probando las variantes del
+/// identificador del modelo si la API no reconoce el original.
+fn send_content(
+    api_key: &str,
+    model: &str,
+    content: &serde_json::Value,
+    options: &GenerationOptions,
+) -> Result<String> {
+    let client = network::client_builder(Duration::from_secs(45))?
         .connect_timeout(Duration::from_secs(15))
-        .timeout(Duration::from_secs(45))
         .build()
         .context("No se pudo crear el cliente HTTP para Anthropic")?;
 
     let mut last_not_found: Option<(String, String)> = None;
 
     for candidate in build_model_candidates(model) {
-        match send_request(&client, api_key, &candidate, prompt) {
+        match send_request(&client, api_key, &candidate, content, options) {
             Ok(reply) => return Ok(reply),
             Err(RequestError::Api {
+                status,
+                retry_after,
                 error_type,
                 message,
             }) => {
@@ -74,40 +231,82 @@ pub fn send_message(api_key: &str, model: &str, prompt: &str) -> Result<String>
                 }
 
                 let code = error_type.unwrap_or_else(|| "error_desconocido".to_string());
-                return Err(anyhow!("Anthropic devolvió un error ({code}): {message}"));
+                return Err(ProviderError::from_status(
+                    status,
+                    retry_after,
+                    format!("Anthropic devolvió un error ({code}): {message}"),
+                )
+                .into());
             }
-            Err(RequestError::Transport(err)) => return Err(err),
+            Err(RequestError::Transport(err)) => return Err(err.into()),
         }
     }
 
     if let Some((attempted_model, message)) = last_not_found {
-        return Err(anyhow!(
+        return Err(ProviderError::InvalidRequest(format!(
             "Anthropic devolvió un error (not_found_error): {message} (modelo intentado: {attempted_model})"
-        ));
+        ))
+        .into());
     }
 
-    Err(anyhow!(
-        "Anthropic no devolvió una respuesta válida para el modelo especificado."
-    ))
+    Err(ProviderError::InvalidRequest(
+        "Anthropic no devolvió una respuesta válida para el modelo especificado.".to_string(),
+    )
+    .into())
 }
 
 #[derive(Debug, Deserialize)]
 struct ModelListResponse {
     data: Vec<AnthropicModel>,
+    #[serde(default)]
+    has_more: bool,
+    #[serde(default)]
+    last_id: Option<String>,
 }
 
-/// Obtiene el catálogo completo de modelos disponibles para la cuenta de Anthropic.
+/// Modelos por página; el máximo que admite el endpoint.
+const MODELS_PAGE_SIZE: &str = "1000";
+
+/// Obtiene el catálogo completo de modelos disponibles para la cuenta de Anthropic,
+/// recorriendo todas las páginas y completando las capacidades de cada modelo.
 pub fn list_models(api_key: &str) -> Result<Vec<AnthropicModel>> {
-    let client = Client::builder()
+    let client = network::client_builder(Duration::from_secs(45))?
         .connect_timeout(Duration::from_secs(15))
-        .timeout(Duration::from_secs(45))
         .build()
         .context("No se pudo crear el cliente HTTP para Anthropic")?;
 
-    let response = client
+    let mut models = Vec::new();
+    let mut after_id: Option<String> = None;
+    loop {
+        let page = fetch_models_page(&client, api_key, after_id.as_deref())?;
+        models.extend(page.data);
+        match page.last_id {
+            Some(last_id) if page.has_more => after_id = Some(last_id),
+            _ => break,
+        }
+    }
+
+    for model in &mut models {
+        model.enrich_capabilities();
+    }
+    models.sort_by_key(|model| model.id.to_lowercase());
+    Ok(models)
+}
+
+fn fetch_models_page(
+    client: &Client,
+    api_key: &str,
+    after_id: Option<&str>,
+) -> Result<ModelListResponse> {
+    let mut request = client
         .get("https://api.anthropic.com/v1/models")
+        .query(&[("limit", MODELS_PAGE_SIZE)])
         .header("x-api-key", api_key)
-        .header("anthropic-version", "2023-06-01")
+        .header("anthropic-version", "2023-06-01");
+    if let Some(after_id) = after_id {
+        request = request.query(&[("after_id", after_id)]);
+    }
+    let response = request
         .send()
         .map_err(|err| anyhow!("Error solicitando el listado de modelos: {}", err))?;
 
@@ -138,81 +337,74 @@ pub fn list_models(api_key: &str) -> Result<Vec<AnthropicModel>> {
         ));
     }
 
-    let mut response: ModelListResponse = serde_json::from_str(&body).map_err(|err| {
+    serde_json::from_str(&body).map_err(|err| {
         anyhow!(
             "No se pudo interpretar el listado de modelos de Anthropic: {}",
             err
         )
-    })?;
-
-    response
-        .data
-        .sort_by(|a, b| a.id.to_lowercase().cmp(&b.id.to_lowercase()));
-
-    Ok(response.data)
+    })
 }
 
 fn send_request(
     client: &Client,
     api_key: &str,
     model: &str,
-    prompt: &str,
+    content: &serde_json::Value,
+    options: &GenerationOptions,
 ) -> Result<String, RequestError> {
-    let payload = json!({
+    let mut payload = json!({
         "model": model,
-        "max_tokens": 512,
+        "max_tokens": options.max_tokens.unwrap_or(MAX_TOKENS),
         "messages": [
             {
                 "role": "user",
-                "content": [
-                    {
-                        "type": "text",
-                        "text": prompt,
-                    }
-                ],
+                "content": content,
             }
         ],
     });
 
+    if let Some(system) = &options.system {
+        payload["system"] = json!(system);
+    }
+    if let Some(temperature) = options.temperature {
+        // Anthropic admite temperaturas entre 0 y 1.
+        payload["temperature"] = json!(temperature.clamp(0.0, 1.0));
+    }
+
     let response = client
         .post("https://api.anthropic.com/v1/messages")
         .header("x-api-key", api_key)
         .header("anthropic-version", "2023-06-01")
         .json(&payload)
         .send()
-        .map_err(|err| {
-            RequestError::Transport(anyhow!("Error enviando la solicitud a Anthropic: {}", err))
-        })?;
+        .map_err(|err| RequestError::Transport(ProviderError::network("Anthropic", err)))?;
+    network::mark_first_byte();
 
     let status = response.status();
-    let body = response.text().map_err(|err| {
-        RequestError::Transport(anyhow!(
-            "No se pudo leer la respuesta de Anthropic: {}",
-            err
-        ))
-    })?;
-
     if !status.is_success() {
+        let retry_after = parse_retry_after(&response);
+        let body = response.text().unwrap_or_default();
         if let Ok(error) = serde_json::from_str::<AnthropicErrorResponse>(&body) {
             return Err(RequestError::Api {
+                status,
+                retry_after,
                 error_type: error.error.r#type,
                 message: error.error.message,
             });
         }
 
-        return Err(RequestError::Transport(anyhow!(
-            "Anthropic devolvió un estado {}: {}",
+        return Err(RequestError::Transport(ProviderError::from_status(
             status,
-            body
+            retry_after,
+            format!("Anthropic devolvió un estado {}: {}", status, body),
         )));
     }
 
-    let parsed: AnthropicResponse = serde_json::from_str(&body).map_err(|err| {
-        RequestError::Transport(anyhow!(
-            "No se pudo interpretar la respuesta de Anthropic: {}",
-            err
-        ))
-    })?;
+    let body = response
+        .text()
+        .map_err(|err| RequestError::Transport(ProviderError::network("Anthropic", err)))?;
+    let parsed: AnthropicResponse = serde_json::from_str(&body)
+        .map_err(|err| RequestError::Transport(ProviderError::invalid_payload("Anthropic", err)))?;
 
     let reply = parsed
         .content
@@ -313,8 +505,10 @@ fn map_known_alias(model: &str) -> Option<&'static str> {
 
 enum RequestError {
     Api {
+        status: reqwest::StatusCode,
+        retry_after: Option<Duration>,
         error_type: Option<String>,
         message: String,
     },
-    Transport(anyhow::Error),
+    Transport(ProviderError),
 }
@@ -0,0 +1,165 @@
+use std::fmt;
+use std::time::Duration;
+
+use reqwest::blocking::Response;
+use reqwest::StatusCode;
+
+/// Errores tipados de los proveedores remotos, usados para decidir cómo reaccionar
+/// en la interfaz (pedir credenciales, ofrecer un reintento o solo informar).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProviderError {
+    /// Credenciales ausentes, caducadas o sin permisos suficientes.
+    Auth(String),
+    /// El proveedor limitó la tasa de peticiones.
+    RateLimited {
+        retry_after: Option<Duration>,
+        message: String,
+    },
+    /// Fallo de conexión, DNS o tiempo de espera antes de obtener respuesta.
+    Network(String),
+    /// La petición fue rechazada por formato, modelo o parámetros inválidos.
+    InvalidRequest(String),
+    /// El proveedor respondió con un error interno o con una respuesta ilegible.
+    ServerError {
+        status: Option<u16>,
+        message: String,
+    },
+}
+
+impl ProviderError {
+    /// Clasifica un estado HTTP de error.
+    pub fn from_status(
+        status: StatusCode,
+        retry_after: Option<Duration>,
+        message: impl Into<String>,
+    ) -> Self {
+        let message = message.into();
+        match status.as_u16() {
+            401 | 403 => ProviderError::Auth(message),
+            429 => ProviderError::RateLimited {
+                retry_after,
+                message,
+            },
+            400..=499 => ProviderError::InvalidRequest(message),
+            code => ProviderError::ServerError {
+                status: Some(code),
+                message,
+            },
+        }
+    }
+
+    /// Consume una respuesta HTTP fallida y extrae el mensaje de error de su cuerpo.
+    pub fn from_response(provider: &str, response: Response) -> Self {
+        let status = response.status();
+        let retry_after = parse_retry_after(&response);
+        let body = response.text().unwrap_or_default();
+        let detail = serde_json::from_str::<serde_json::Value>(&body)
+            .ok()
+            .and_then(|value| {
+                value
+                    .pointer("/error/message")
+                    .and_then(|message| message.as_str())
+                    .map(str::to_string)
+            })
+            .unwrap_or(body);
+
+        Self::from_status(
+            status,
+            retry_after,
+            format!("{provider} devolvió un estado {status}: {}", detail.trim()),
+        )
+    }
+
+    pub fn network(provider: &str, err: reqwest::Error) -> Self {
+        ProviderError::Network(format!("Error enviando la solicitud a {provider}: {err}"))
+    }
+
+    pub fn invalid_payload(provider: &str, err: impl fmt::Display) -> Self {
+        ProviderError::ServerError {
+            status: None,
+            message: format!("No se pudo interpretar la respuesta de {provider}: {err}"),
+        }
+    }
+
+    /// Recupera el error tipado de un `anyhow::Error`; los errores sin clasificar se
+    /// tratan como fallos de red si provienen de `reqwest` y como errores del servidor
+    /// en otro caso.
+    pub fn from_anyhow(err: anyhow::Error) -> Self {
+        if let Some(provider_error) = err.downcast_ref::<ProviderError>() {
+            return provider_error.clone();
+        }
+
+        let message = format!("{err:#}");
+        if err
+            .chain()
+            .any(|cause| cause.downcast_ref::<reqwest::Error>().is_some())
+        {
+            ProviderError::Network(message)
+        } else {
+            ProviderError::ServerError {
+                status: None,
+                message,
+            }
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ProviderError::Auth(message)
+            | ProviderError::Network(message)
+            | ProviderError::InvalidRequest(message)
+            | ProviderError::RateLimited { message, .. }
+            | ProviderError::ServerError { message, .. } => message,
+        }
+    }
+
+    pub fn kind_label(&self) -> &'static str {
+        match self {
+            ProviderError::Auth(_) => "Autenticación",
+            ProviderError::RateLimited { .. } => "Límite de peticiones",
+            ProviderError::Network(_) => "Red",
+            ProviderError::InvalidRequest(_) => "Petición inválida",
+            ProviderError::ServerError { .. } => "Error del proveedor",
+        }
+    }
+
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            ProviderError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+
+    /// Indica si repetir la misma petición puede tener éxito sin cambiar nada.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ProviderError::RateLimited { .. }
+                | ProviderError::Network(_)
+                | ProviderError::ServerError { .. }
+        )
+    }
+
+    pub fn requires_reauth(&self) -> bool {
+        matches!(self, ProviderError::Auth(_))
+    }
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+/// Lee la cabecera `Retry-After` expresada en segundos.
+pub(crate) fn parse_retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|seconds| *seconds >= 0.0)
+        .map(Duration::from_secs_f64)
+}
@@ -1,6 +1,8 @@
 use anyhow::{anyhow, Context, Result};
-use reqwest::blocking::Client;
 use serde::Deserialize;
+use std::time::Duration;
+
+use super::network;
 
 #[derive(Debug, Deserialize)]
 struct GitHubUser {
@@ -24,7 +26,7 @@ pub fn fetch_user_and_repositories(token: &str) -> Result<GitHubData> {
         return Err(anyhow!("GitHub token is empty"));
     }
 
-    let client = Client::builder()
+    let client = network::client_builder(Duration::from_secs(30))?
         .user_agent("JungleMonkAI/0.1")
         .build()
         .context("Failed to build HTTP client")?;
@@ -62,3 +64,64 @@ pub fn fetch_user_and_repositories(token: &str) -> Result<GitHubData> {
         repositories: repo_names,
     })
 }
+
+#[derive(Debug, Deserialize)]
+struct GitHubNotificationSubject {
+    title: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubNotificationRepository {
+    full_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubNotificationPayload {
+    reason: String,
+    subject: GitHubNotificationSubject,
+    repository: GitHubNotificationRepository,
+}
+
+/// Unread notification of the authenticated user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitHubNotification {
+    pub repository: String,
+    pub title: String,
+    pub kind: String,
+    pub reason: String,
+}
+
+/// Fetch the unread notifications of the authenticated user.
+pub fn fetch_notifications(token: &str) -> Result<Vec<GitHubNotification>> {
+    if token.trim().is_empty() {
+        return Err(anyhow!("GitHub token is empty"));
+    }
+
+    let client = network::client_builder(Duration::from_secs(30))?
+        .user_agent("JungleMonkAI/0.1")
+        .build()
+        .context("Failed to build HTTP client")?;
+
+    let notifications: Vec<GitHubNotificationPayload> = client
+        .get("https://api.github.com/notifications")
+        .query(&[("per_page", "50")])
+        .bearer_auth(token)
+        .send()
+        .context("Failed to request GitHub notifications")?
+        .error_for_status()
+        .context("GitHub returned an error for the notifications request")?
+        .json()
+        .context("Failed to deserialize GitHub notifications")?;
+
+    Ok(notifications
+        .into_iter()
+        .map(|notification| GitHubNotification {
+            repository: notification.repository.full_name,
+            title: notification.subject.title,
+            kind: notification.subject.kind,
+            reason: notification.reason,
+        })
+        .collect())
+}
@@ -1,9 +1,14 @@
 use anyhow::{Context, Result};
-use reqwest::blocking::Client;
 use serde::Deserialize;
 use serde_json::json;
 use std::time::Duration;
 
+use super::{error::ProviderError, network, GenerationOptions};
+
+/// Parámetros de muestreo de cada respuesta de chat.
+pub const MAX_TOKENS: u32 = 256;
+pub const TEMPERATURE: f32 = 0.2;
+
 #[derive(Debug, Deserialize)]
 struct ChatMessage {
     #[serde(default)]
@@ -23,33 +28,79 @@ struct ChatResponse {
 
 /// Envía un mensaje utilizando la API compatible de Groq.
 pub fn send_message(api_key: &str, model: &str, prompt: &str) -> Result<String> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(45))
+    send_message_with(api_key, model, prompt, &GenerationOptions::default())
+}
+
+/// Igual que `send_message`, con las instrucciones y parámetros de la conversación.
+pub fn send_message_with(
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+    options: &GenerationOptions,
+) -> Result<String> {
+    chat_completion(api_key, model, prompt, false, options)
+}
+
+/// Igual que `send_message_with`, pero activa el modo JSON nativo (`response_format`)
+/// para que la respuesta sea siempre un objeto JSON.
+pub fn send_message_json(
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+    options: &GenerationOptions,
+) -> Result<String> {
+    chat_completion(api_key, model, prompt, true, options)
+}
+
+fn chat_completion(
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+    json_mode: bool,
+    options: &GenerationOptions,
+) -> Result<String> {
+    let client = network::client_builder(Duration::from_secs(45))?
         .build()
         .context("No se pudo crear el cliente HTTP para Groq")?;
 
-    let payload = json!({
+    // La persona de la conversación sustituye a las instrucciones por defecto; el modo
+    // JSON sigue exigiendo un objeto como respuesta.
+    let system = match (&options.system, json_mode) {
+        (Some(persona), true) => {
+            format!("{persona}\n\nResponde únicamente con un objeto JSON válido.")
+        }
+        (None, true) => "Responde únicamente con un objeto JSON válido.".to_string(),
+        (Some(persona), false) => persona.clone(),
+        (None, false) => "Responde brevemente.".to_string(),
+    };
+    let mut payload = json!({
         "model": model,
-        "max_tokens": 256,
-        "temperature": 0.2,
+        "max_tokens": options.max_tokens.unwrap_or(MAX_TOKENS),
+        "temperature": options.temperature.unwrap_or(TEMPERATURE),
         "messages": [
-            {"role": "system", "content": "Responde brevemente."},
+            {"role": "system", "content": system},
             {"role": "user", "content": prompt},
         ],
     });
+    if json_mode {
+        payload["response_format"] = json!({ "type": "json_object" });
+    }
 
     let response = client
         .post("https://api.groq.com/openai/v1/chat/completions")
         .bearer_auth(api_key)
         .json(&payload)
         .send()
-        .context("Error enviando la solicitud a Groq")?
-        .error_for_status()
-        .context("Groq devolvió un estado de error")?;
+        .map_err(|err| ProviderError::network("Groq", err))?;
+    network::mark_first_byte();
+
+    if !response.status().is_success() {
+        return Err(ProviderError::from_response("Groq", response).into());
+    }
 
     let parsed: ChatResponse = response
         .json()
-        .context("No se pudo interpretar la respuesta de Groq")?;
+        .map_err(|err| ProviderError::invalid_payload("Groq", err))?;
 
     let reply = parsed
         .choices
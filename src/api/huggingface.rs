@@ -1,14 +1,18 @@
 use anyhow::{anyhow, Context, Result};
-use hf_hub::api::sync::ApiBuilder;
-use reqwest::blocking::Client;
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
 use serde::Deserialize;
 use serde_json::Value;
-use std::collections::HashSet;
-use std::fs;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
 
+use super::network;
 use crate::local_providers::{LocalModelCard, LocalModelProvider};
 
 #[derive(Debug, Clone, Deserialize)]
@@ -90,8 +94,7 @@ fn huggingface_incompatibility(raw: &RawModelSummary) -> Option<String> {
 
 /// Busca modelos en Hugging Face y devuelve una lista de metadatos resumidos.
 pub fn search_models(query: &str, token: Option<&str>) -> Result<Vec<LocalModelCard>> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(30))
+    let client = network::client_builder(Duration::from_secs(30))?
         .user_agent("JungleMonkAI/0.1")
         .build()
         .context("No se pudo crear el cliente HTTP para Hugging Face")?;
@@ -136,59 +139,134 @@ pub fn search_models(query: &str, token: Option<&str>) -> Result<Vec<LocalModelC
         .collect())
 }
 
-/// Descarga metadatos básicos del modelo y los almacena en disco dentro del directorio indicado.
-pub fn download_model(
-    model: &LocalModelCard,
-    install_dir: &Path,
-    token: Option<&str>,
-) -> Result<PathBuf> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(60))
-        .user_agent("JungleMonkAI/0.1")
-        .build()
-        .context("No se pudo crear el cliente HTTP para Hugging Face")?;
+/// Intentos por archivo antes de dar la descarga por fallida.
+const DOWNLOAD_ATTEMPTS: u32 = 3;
+/// Sufijo de los archivos a medio descargar que se reanudan en el siguiente intento.
+const PARTIAL_SUFFIX: &str = ".part";
+
+/// Mensaje con el que termina una descarga cancelada.
+pub const DOWNLOAD_CANCELLED: &str = "Descarga cancelada";
+
+/// Progreso de una descarga compartido entre los hilos que descargan y la interfaz, que
+/// también lo usa para cancelarla.
+#[derive(Debug, Default)]
+pub struct DownloadProgress {
+    downloaded: AtomicU64,
+    total: AtomicU64,
+    cancelled: AtomicBool,
+}
 
-    let mut request = client.get(format!("https://huggingface.co/api/models/{}", model.id));
-    if let Some(token) = token {
-        if !token.trim().is_empty() {
-            request = request.bearer_auth(token.trim());
+impl DownloadProgress {
+    pub fn downloaded(&self) -> u64 {
+        self.downloaded.load(Ordering::Relaxed)
+    }
+
+    /// Bytes esperados de los archivos conocidos hasta ahora.
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn add_total(&self, bytes: u64) {
+        self.total.fetch_add(bytes, Ordering::Relaxed);
+    }
+}
+
+/// Bytes que un intento de descarga sumó al progreso. Si el intento no termina bien se
+/// descuentan al soltarlo, porque el siguiente vuelve a contar lo que haya en disco.
+struct AttemptBytes<'a> {
+    progress: &'a DownloadProgress,
+    bytes: u64,
+    /// Tamaño anunciado por la respuesta de un archivo sin tamaño publicado.
+    announced: u64,
+    kept: bool,
+}
+
+impl<'a> AttemptBytes<'a> {
+    fn new(progress: &'a DownloadProgress) -> Self {
+        Self {
+            progress,
+            bytes: 0,
+            announced: 0,
+            kept: false,
         }
     }
 
-    let response = request
-        .send()
-        .context("Error descargando metadatos del modelo en Hugging Face")?
-        .error_for_status()
-        .context("Hugging Face devolvió un estado de error al descargar metadatos")?;
+    fn announce(&mut self, bytes: u64) {
+        self.announced += bytes;
+        self.progress.add_total(bytes);
+    }
 
-    let metadata: Value = response
-        .json()
-        .context("No se pudo interpretar los metadatos del modelo de Hugging Face")?;
+    fn add(&mut self, bytes: u64) {
+        self.bytes += bytes;
+        self.progress.downloaded.fetch_add(bytes, Ordering::Relaxed);
+    }
 
-    let available_files: HashSet<String> = metadata
-        .get("siblings")
-        .and_then(|siblings| siblings.as_array())
-        .map(|entries| {
-            entries
-                .iter()
-                .filter_map(|entry| entry.get("rfilename").and_then(|value| value.as_str()))
-                .map(|value| value.to_string())
-                .collect()
-        })
-        .unwrap_or_default();
+    fn discard(&mut self) {
+        self.progress
+            .downloaded
+            .fetch_sub(self.bytes, Ordering::Relaxed);
+        self.bytes = 0;
+    }
+
+    fn keep(mut self) {
+        self.kept = true;
+    }
+}
+
+impl Drop for AttemptBytes<'_> {
+    fn drop(&mut self) {
+        if !self.kept {
+            self.discard();
+            self.progress
+                .total
+                .fetch_sub(self.announced, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Archivo del repositorio que forma parte de la instalación.
+#[derive(Clone, Debug)]
+struct RemoteFile {
+    name: String,
+    /// Tamaño publicado por Hugging Face; si falta se usa el que anuncia la descarga.
+    size: Option<u64>,
+    /// SHA-256 publicado por LFS, con el que se verifica el archivo descargado.
+    sha256: Option<String>,
+    optional: bool,
+}
+
+/// Descarga los metadatos y los pesos del modelo dentro del directorio indicado.
+///
+/// Los archivos se descargan en paralelo (hasta `parallelism` a la vez) en un directorio
+/// temporal que se conserva entre intentos: los fragmentos a medias se reanudan con
+/// peticiones HTTP `Range` y cada archivo se compara con el tamaño y el SHA-256
+/// publicados antes de mover la instalación a su destino final. `progress` recibe los
+/// bytes descargados y permite cancelar; lo descargado se conserva para reanudar.
+pub fn download_model(
+    model: &LocalModelCard,
+    install_dir: &Path,
+    token: Option<&str>,
+    parallelism: usize,
+    progress: &DownloadProgress,
+) -> Result<PathBuf> {
+    let token = token.map(str::trim).filter(|token| !token.is_empty());
+    let metadata = fetch_metadata(&model.id, token)?;
+    let available_files = published_files(&metadata);
+    let checksums = published_checksums(&metadata);
 
     let safe_dir_name = sanitize_id(&model.id);
     let target_dir = install_dir.join(&safe_dir_name);
     let staging_dir = install_dir.join(format!("{}__downloading", safe_dir_name));
 
-    if staging_dir.exists() {
-        fs::remove_dir_all(&staging_dir).with_context(|| {
-            format!(
-                "No se pudo limpiar el directorio temporal de descarga {:?}",
-                staging_dir
-            )
-        })?;
-    }
+    // El directorio temporal se conserva para reanudar lo que quedó a medias.
     fs::create_dir_all(&staging_dir)
         .with_context(|| format!("No se pudo crear el directorio {:?}", staging_dir))?;
 
@@ -196,107 +274,30 @@ pub fn download_model(
     fs::write(&metadata_path, serde_json::to_string_pretty(&metadata)?)
         .with_context(|| format!("No se pudo escribir {:?}", metadata_path))?;
 
-    let mut builder = ApiBuilder::new().with_progress(false);
-    if let Some(token) = token.and_then(|t| {
-        let trimmed = t.trim();
-        if trimmed.is_empty() {
-            None
-        } else {
-            Some(trimmed.to_string())
-        }
-    }) {
-        builder = builder.with_token(Some(token));
-    }
-
-    let api = builder
-        .build()
-        .context("No se pudo inicializar el cliente de Hugging Face Hub")?;
-    let repo = api.model(model.id.to_string());
-
-    let download_file = |remote: &str, optional: bool| -> Result<()> {
-        if !available_files.contains(remote) {
-            if optional {
-                return Ok(());
-            }
-            return Err(anyhow!(
-                "El repositorio de Hugging Face no contiene el archivo obligatorio '{}'",
-                remote
-            ));
-        }
-        let mut last_err = None;
-        for attempt in 1..=3 {
-            match repo.download(remote) {
-                Ok(path) => {
-                    let destination = staging_dir.join(remote);
-                    if let Some(parent) = destination.parent() {
-                        fs::create_dir_all(parent)
-                            .with_context(|| format!("No se pudo crear {:?}", parent))?;
-                    }
-                    fs::copy(&path, &destination).with_context(|| {
-                        format!(
-                            "No se pudo copiar el archivo descargado de Hugging Face {:?} a {:?}",
-                            path, destination
-                        )
-                    })?;
-
-                    let metadata = fs::metadata(&destination).with_context(|| {
-                        format!("No se pudo obtener el tamaño del archivo {:?}", destination)
-                    })?;
-                    if metadata.len() == 0 {
-                        last_err = Some(anyhow!(
-                            "El archivo '{}' descargado está vacío. Inténtalo nuevamente.",
-                            remote
-                        ));
-                        fs::remove_file(&destination).ok();
-                        thread::sleep(Duration::from_millis(250 * attempt as u64));
-                        continue;
-                    }
-
-                    return Ok(());
-                }
-                Err(err) => {
-                    if optional {
-                        return Ok(());
-                    }
-                    last_err = Some(anyhow!(
-                        "No se pudo descargar '{}' desde Hugging Face (intento {} de 3): {}",
-                        remote,
-                        attempt,
-                        err
-                    ));
-                    thread::sleep(Duration::from_millis(250 * attempt as u64));
-                }
-            }
-        }
-
-        if let Some(err) = last_err {
-            Err(err)
-        } else {
-            Err(anyhow!("Error desconocido al descargar '{}'", remote))
-        }
+    let download_client = download_client()?;
+    let downloader = ShardDownloader {
+        client: &download_client,
+        model_id: &model.id,
+        token,
+        staging_dir: &staging_dir,
+        progress,
+    };
+    let remote = |name: &str, optional: bool| RemoteFile {
+        name: name.to_string(),
+        size: available_files.get(name).copied().flatten(),
+        sha256: checksums.get(name).cloned(),
+        optional,
     };
 
-    download_file("config.json", false)?;
-
-    let optional_files = [
-        "tokenizer.json",
-        "tokenizer_config.json",
-        "sentence_bert_config.json",
-        "vocab.txt",
-        "merges.txt",
-        "special_tokens_map.json",
-        "modules.json",
-        "rust_model.ot",
-    ];
-
-    for file in optional_files {
-        download_file(file, true)?;
+    if !available_files.contains_key("config.json") {
+        return Err(anyhow!(
+            "El repositorio de Hugging Face no contiene el archivo obligatorio 'config.json'"
+        ));
     }
 
-    let mut safetensor_files: Vec<_> = available_files
-        .iter()
+    let mut safetensor_files: Vec<&String> = available_files
+        .keys()
         .filter(|name| name.ends_with(".safetensors"))
-        .cloned()
         .collect();
     safetensor_files.sort();
 
@@ -307,9 +308,26 @@ pub fn download_model(
         ));
     }
 
-    for file in safetensor_files {
-        download_file(&file, false)?;
-    }
+    let optional_files = [
+        "tokenizer.json",
+        "tokenizer_config.json",
+        "sentence_bert_config.json",
+        "vocab.txt",
+        "merges.txt",
+        "special_tokens_map.json",
+        "modules.json",
+        "rust_model.ot",
+    ];
+
+    let mut files = vec![remote("config.json", false)];
+    files.extend(
+        optional_files
+            .iter()
+            .filter(|name| available_files.contains_key(**name))
+            .map(|name| remote(name, true)),
+    );
+    files.extend(safetensor_files.iter().map(|name| remote(name, false)));
+    downloader.download_all(files, parallelism)?;
 
     let modules_path = staging_dir.join("modules.json");
     if modules_path.exists() {
@@ -326,17 +344,20 @@ pub fn download_model(
             }
         };
 
-        for module in modules {
-            if let Some(path) = module.get("path").and_then(|value| value.as_str()) {
-                if path.trim().is_empty() {
-                    continue;
-                }
-                let config_path = format!("{}/config.json", path);
-                download_file(&config_path, true)?;
-                let weights_path = format!("{}/rust_model.ot", path);
-                download_file(&weights_path, true)?;
-            }
-        }
+        let module_files: Vec<RemoteFile> = modules
+            .iter()
+            .filter_map(|module| module.get("path").and_then(|value| value.as_str()))
+            .filter(|path| !path.trim().is_empty())
+            .flat_map(|path| {
+                [
+                    format!("{}/config.json", path),
+                    format!("{}/rust_model.ot", path),
+                ]
+            })
+            .filter(|name| available_files.contains_key(name))
+            .map(|name| remote(&name, true))
+            .collect();
+        downloader.download_all(module_files, parallelism)?;
     }
 
     ensure_required_assets(&staging_dir)?;
@@ -360,6 +381,489 @@ pub fn download_model(
     Ok(target_dir)
 }
 
+/// Revisión publicada más reciente de un repositorio.
+#[derive(Clone, Debug)]
+pub struct RemoteRevision {
+    pub sha: String,
+    pub last_modified: Option<String>,
+}
+
+/// Resultado de actualizar una instalación existente.
+#[derive(Clone, Debug)]
+pub struct ModelUpdate {
+    pub revision: String,
+    /// Archivos que cambiaron respecto a la revisión instalada y se descargaron de nuevo.
+    pub changed_files: Vec<String>,
+}
+
+/// Consulta la última revisión publicada del modelo.
+pub fn latest_revision(model_id: &str, token: Option<&str>) -> Result<RemoteRevision> {
+    let token = token.map(str::trim).filter(|token| !token.is_empty());
+    let metadata = fetch_metadata(model_id, token)?;
+    let sha = metadata_revision(&metadata)
+        .ok_or_else(|| anyhow!("Hugging Face no indicó la revisión de '{}'", model_id))?;
+    Ok(RemoteRevision {
+        sha,
+        last_modified: metadata
+            .get("lastModified")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+    })
+}
+
+/// Revisión guardada en `metadata.json` al instalar el modelo.
+pub fn installed_revision(model_dir: &Path) -> Option<String> {
+    let data = fs::read_to_string(model_dir.join("metadata.json")).ok()?;
+    let metadata: Value = serde_json::from_str(&data).ok()?;
+    metadata_revision(&metadata)
+}
+
+/// Historial de cambios del repositorio en la web de Hugging Face.
+pub fn changelog_url(model_id: &str) -> String {
+    format!("https://huggingface.co/{}/commits/main", model_id)
+}
+
+/// Lleva una instalación a la última revisión descargando solo los archivos cuyo
+/// contenido cambió. Los archivos nuevos se preparan aparte y se sustituyen al final,
+/// de modo que un fallo a mitad deja intacta la instalación anterior.
+pub fn update_model(
+    model_id: &str,
+    model_dir: &Path,
+    token: Option<&str>,
+    parallelism: usize,
+) -> Result<ModelUpdate> {
+    let token = token.map(str::trim).filter(|token| !token.is_empty());
+    let previous_path = model_dir.join("metadata.json");
+    let previous: Value = fs::read_to_string(&previous_path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .ok_or_else(|| {
+            anyhow!(
+                "No se encontró {:?}; reinstala el modelo para poder actualizarlo.",
+                previous_path
+            )
+        })?;
+
+    let metadata = fetch_metadata(model_id, token)?;
+    let revision = metadata_revision(&metadata)
+        .ok_or_else(|| anyhow!("Hugging Face no indicó la revisión de '{}'", model_id))?;
+    if metadata_revision(&previous).as_deref() == Some(revision.as_str()) {
+        return Ok(ModelUpdate {
+            revision,
+            changed_files: Vec::new(),
+        });
+    }
+
+    let available_files = published_files(&metadata);
+    let checksums = published_checksums(&metadata);
+    let previous_blobs = published_blobs(&previous);
+    let current_blobs = published_blobs(&metadata);
+
+    let mut local_files = Vec::new();
+    collect_relative_files(model_dir, model_dir, &mut local_files);
+    let mut candidates: Vec<&String> = available_files
+        .keys()
+        .filter(|name| {
+            name.as_str() == "config.json"
+                || name.ends_with(".safetensors")
+                || local_files.contains(*name)
+        })
+        .collect();
+    candidates.sort();
+
+    let changed: Vec<RemoteFile> = candidates
+        .into_iter()
+        .filter(|name| {
+            let path = model_dir.join(name.as_str());
+            previous_blobs.get(*name) != current_blobs.get(*name) || !path.exists()
+        })
+        .map(|name| RemoteFile {
+            name: name.clone(),
+            size: available_files.get(name).copied().flatten(),
+            sha256: checksums.get(name).cloned(),
+            optional: false,
+        })
+        .collect();
+
+    let staging_dir = PathBuf::from(format!("{}__update", model_dir.display()));
+    fs::create_dir_all(&staging_dir)
+        .with_context(|| format!("No se pudo crear el directorio {:?}", staging_dir))?;
+    let download_client = download_client()?;
+    let progress = DownloadProgress::default();
+    let downloader = ShardDownloader {
+        client: &download_client,
+        model_id,
+        token,
+        staging_dir: &staging_dir,
+        progress: &progress,
+    };
+    downloader.download_all(changed.clone(), parallelism)?;
+
+    for file in &changed {
+        let source = staging_dir.join(&file.name);
+        let destination = model_dir.join(&file.name);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("No se pudo crear {:?}", parent))?;
+        }
+        fs::rename(&source, &destination)
+            .with_context(|| format!("No se pudo mover {:?} a {:?}", source, destination))?;
+    }
+
+    // Los fragmentos que ya no se publican pertenecen a la revisión anterior.
+    for stale in local_files
+        .iter()
+        .filter(|name| name.ends_with(".safetensors") && !available_files.contains_key(*name))
+    {
+        fs::remove_file(model_dir.join(stale)).ok();
+    }
+
+    fs::write(&previous_path, serde_json::to_string_pretty(&metadata)?)
+        .with_context(|| format!("No se pudo escribir {:?}", previous_path))?;
+    fs::remove_dir_all(&staging_dir).ok();
+    ensure_required_assets(model_dir)?;
+
+    Ok(ModelUpdate {
+        revision,
+        changed_files: changed.into_iter().map(|file| file.name).collect(),
+    })
+}
+
+fn fetch_metadata(model_id: &str, token: Option<&str>) -> Result<Value> {
+    let client = network::client_builder(Duration::from_secs(60))?
+        .user_agent("JungleMonkAI/0.1")
+        .build()
+        .context("No se pudo crear el cliente HTTP para Hugging Face")?;
+
+    let mut request = client
+        .get(format!("https://huggingface.co/api/models/{}", model_id))
+        .query(&[("blobs", "true")]);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request
+        .send()
+        .context("Error descargando metadatos del modelo en Hugging Face")?
+        .error_for_status()
+        .context("Hugging Face devolvió un estado de error al descargar metadatos")?;
+
+    response
+        .json()
+        .context("No se pudo interpretar los metadatos del modelo de Hugging Face")
+}
+
+/// Cliente para los archivos del modelo.
+fn download_client() -> Result<reqwest::blocking::Client> {
+    // Los pesos pueden tardar mucho más que el timeout global, así que solo se limita
+    // la conexión; si se corta, el siguiente intento reanuda desde donde quedó.
+    network::client_builder(Duration::from_secs(60))?
+        .timeout(None::<Duration>)
+        .connect_timeout(Duration::from_secs(30))
+        .user_agent("JungleMonkAI/0.1")
+        .build()
+        .context("No se pudo crear el cliente HTTP para Hugging Face")
+}
+
+fn metadata_revision(metadata: &Value) -> Option<String> {
+    metadata
+        .get("sha")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn siblings(metadata: &Value) -> impl Iterator<Item = &Value> {
+    metadata
+        .get("siblings")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+}
+
+/// Archivos publicados y su tamaño, si Hugging Face lo indica.
+fn published_files(metadata: &Value) -> HashMap<String, Option<u64>> {
+    siblings(metadata)
+        .filter_map(|entry| {
+            let name = entry.get("rfilename")?.as_str()?;
+            let size = entry.get("size").and_then(Value::as_u64);
+            Some((name.to_string(), size))
+        })
+        .collect()
+}
+
+/// Huella del contenido de cada archivo: el sha256 de LFS o, si no lo hay, el blob de git.
+fn published_blobs(metadata: &Value) -> HashMap<String, String> {
+    siblings(metadata)
+        .filter_map(|entry| {
+            let name = entry.get("rfilename")?.as_str()?;
+            let blob = entry
+                .pointer("/lfs/sha256")
+                .or_else(|| entry.get("blobId"))
+                .and_then(Value::as_str)?;
+            Some((name.to_string(), blob.to_string()))
+        })
+        .collect()
+}
+
+/// SHA-256 de los archivos guardados en LFS; los demás no publican uno comprobable.
+fn published_checksums(metadata: &Value) -> HashMap<String, String> {
+    siblings(metadata)
+        .filter_map(|entry| {
+            let name = entry.get("rfilename")?.as_str()?;
+            let sha256 = entry.pointer("/lfs/sha256").and_then(Value::as_str)?;
+            Some((name.to_string(), sha256.to_lowercase()))
+        })
+        .collect()
+}
+
+fn file_sha256(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).with_context(|| format!("No se pudo abrir {:?}", path))?;
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buffer = vec![0; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("No se pudo leer {:?}", path))?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+    Ok(context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+/// Rutas relativas (con `/`) de los archivos instalados, sin metadatos ni descargas a medias.
+fn collect_relative_files(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_relative_files(root, &path, files);
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let name = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        if name != "metadata.json" && !name.ends_with(PARTIAL_SUFFIX) {
+            files.push(name);
+        }
+    }
+}
+
+/// Descarga archivos de un repositorio al directorio temporal de la instalación.
+struct ShardDownloader<'a> {
+    client: &'a reqwest::blocking::Client,
+    model_id: &'a str,
+    token: Option<&'a str>,
+    staging_dir: &'a Path,
+    progress: &'a DownloadProgress,
+}
+
+impl ShardDownloader<'_> {
+    /// Reparte `files` entre `parallelism` hilos. Un fallo en un archivo obligatorio
+    /// detiene el reparto y se devuelve el primero que se produjo.
+    fn download_all(&self, files: Vec<RemoteFile>, parallelism: usize) -> Result<()> {
+        let workers = parallelism.clamp(1, files.len().max(1));
+        self.progress
+            .add_total(files.iter().filter_map(|file| file.size).sum());
+        let queue = Mutex::new(VecDeque::from(files));
+        let failed = AtomicBool::new(false);
+        let first_error = Mutex::new(None);
+
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| loop {
+                    if failed.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let Some(file) = queue.lock().ok().and_then(|mut queue| queue.pop_front())
+                    else {
+                        break;
+                    };
+                    if let Err(err) = self.download_with_retries(&file) {
+                        if file.optional && !self.progress.is_cancelled() {
+                            eprintln!("Se omite el archivo opcional '{}': {}", file.name, err);
+                            continue;
+                        }
+                        failed.store(true, Ordering::Relaxed);
+                        if let Ok(mut slot) = first_error.lock() {
+                            slot.get_or_insert(err);
+                        }
+                    }
+                });
+            }
+        });
+
+        match first_error.into_inner() {
+            Ok(Some(err)) => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn download_with_retries(&self, file: &RemoteFile) -> Result<()> {
+        let mut last_err = None;
+        for attempt in 1..=DOWNLOAD_ATTEMPTS {
+            match self.download(file) {
+                Ok(()) => return Ok(()),
+                Err(_) if self.progress.is_cancelled() => return Err(anyhow!(DOWNLOAD_CANCELLED)),
+                Err(err) => {
+                    last_err = Some(err.context(format!(
+                        "No se pudo descargar '{}' desde Hugging Face (intento {} de {})",
+                        file.name, attempt, DOWNLOAD_ATTEMPTS
+                    )));
+                    thread::sleep(Duration::from_millis(500 * attempt as u64));
+                }
+            }
+        }
+        Err(last_err.unwrap_or_else(|| anyhow!("Error desconocido al descargar '{}'", file.name)))
+    }
+
+    /// Descarga un archivo reanudando su `.part` si existe y comprueba el tamaño y el
+    /// SHA-256 finales.
+    fn download(&self, file: &RemoteFile) -> Result<()> {
+        let mut attempt = AttemptBytes::new(self.progress);
+        let destination = self.staging_dir.join(&file.name);
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("No se pudo crear {:?}", parent))?;
+        }
+        if let Ok(existing) = fs::metadata(&destination) {
+            if file
+                .size
+                .map_or(existing.len() > 0, |size| existing.len() == size)
+            {
+                attempt.add(existing.len());
+                attempt.keep();
+                return Ok(());
+            }
+            fs::remove_file(&destination).ok();
+        }
+
+        let partial = PathBuf::from(format!("{}{}", destination.display(), PARTIAL_SUFFIX));
+        let mut offset = fs::metadata(&partial).map(|meta| meta.len()).unwrap_or(0);
+        if file.size.is_some_and(|size| offset > size) {
+            fs::remove_file(&partial).ok();
+            offset = 0;
+        }
+        attempt.add(offset);
+
+        let mut expected = file.size;
+        if file.size.is_none_or(|size| offset < size) {
+            let url = format!(
+                "https://huggingface.co/{}/resolve/main/{}",
+                self.model_id, file.name
+            );
+            let mut request = self.client.get(&url);
+            if let Some(token) = self.token {
+                request = request.bearer_auth(token);
+            }
+            if offset > 0 {
+                request = request.header(RANGE, format!("bytes={}-", offset));
+            }
+            let mut response = request
+                .send()
+                .with_context(|| format!("Error al solicitar '{}'", file.name))?;
+
+            let status = response.status();
+            let resumed = status == StatusCode::PARTIAL_CONTENT;
+            if status == StatusCode::RANGE_NOT_SATISFIABLE {
+                // El servidor ya no tiene nada que enviar: el `.part` está completo o no
+                // corresponde a este archivo; el control de tamaño lo decide.
+            } else {
+                if let Err(err) = response.error_for_status_ref() {
+                    return Err(anyhow!("Hugging Face respondió {}: {}", status, err));
+                }
+                if !resumed {
+                    offset = 0;
+                    attempt.discard();
+                }
+                if expected.is_none() {
+                    expected = response.content_length().map(|length| length + offset);
+                    if let Some(size) = expected {
+                        attempt.announce(size);
+                    }
+                }
+                let mut output = OpenOptions::new()
+                    .create(true)
+                    .write(true)
+                    .append(resumed)
+                    .truncate(!resumed)
+                    .open(&partial)
+                    .with_context(|| format!("No se pudo abrir {:?}", partial))?;
+                self.copy_body(&mut response, &mut output, &mut attempt)
+                    .with_context(|| format!("Descarga interrumpida de '{}'", file.name))?;
+            }
+        }
+
+        let downloaded = fs::metadata(&partial).map(|meta| meta.len()).unwrap_or(0);
+        let complete = match expected {
+            Some(size) => downloaded == size,
+            None => downloaded > 0,
+        };
+        if !complete {
+            if expected.is_some_and(|size| downloaded > size) {
+                fs::remove_file(&partial).ok();
+            }
+            return Err(anyhow!(
+                "El archivo '{}' tiene {} bytes y se esperaban {}.",
+                file.name,
+                downloaded,
+                expected.map_or_else(|| "más de 0".to_string(), |size| size.to_string())
+            ));
+        }
+        if let Some(sha256) = &file.sha256 {
+            let actual = file_sha256(&partial)?;
+            if &actual != sha256 {
+                fs::remove_file(&partial).ok();
+                return Err(anyhow!(
+                    "El SHA-256 de '{}' no coincide con el publicado ({} en lugar de {}).",
+                    file.name,
+                    actual,
+                    sha256
+                ));
+            }
+        }
+
+        fs::rename(&partial, &destination)
+            .with_context(|| format!("No se pudo mover {:?} a {:?}", partial, destination))?;
+        attempt.keep();
+        Ok(())
+    }
+
+    /// Copia el cuerpo de la respuesta sumando el progreso y parando si se cancela.
+    fn copy_body(
+        &self,
+        response: &mut impl Read,
+        output: &mut impl Write,
+        attempt: &mut AttemptBytes,
+    ) -> Result<()> {
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            if self.progress.is_cancelled() {
+                return Err(anyhow!(DOWNLOAD_CANCELLED));
+            }
+            let read = match response.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err.into()),
+            };
+            output.write_all(&buffer[..read])?;
+            attempt.add(read as u64);
+        }
+    }
+}
+
 fn sanitize_id(id: &str) -> String {
     id.chars()
         .map(|ch| match ch {
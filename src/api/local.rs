@@ -1,14 +1,22 @@
 use anyhow::{anyhow, bail, Context, Result};
+use candle_core::quantized::gguf_file;
 use candle_core::{DType, Device, Tensor};
 use candle_nn::VarBuilder;
 use candle_transformers::models::bert::{
     BertModel, Config as BertConfig, HiddenAct, PositionEmbeddingType,
 };
-use log::warn;
+use candle_transformers::quantized_var_builder::VarBuilder as QuantizedVarBuilder;
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokenizers::Tokenizer;
+
+use super::quantized_bert::QuantizedBertModel;
 use tokenizers::{
     PaddingParams, PaddingStrategy, TruncationDirection, TruncationParams, TruncationStrategy,
 };
@@ -23,18 +31,33 @@ use tokenizers::{
 pub struct JarvisRuntime {
     model_dir: PathBuf,
     model_id: Option<String>,
+    requested_adapters: Vec<PathBuf>,
     summary: Option<String>,
     pipeline_tag: Option<String>,
     tags: Vec<String>,
-    encoder: JarvisEncoder,
+    encoder: Arc<JarvisEncoder>,
     knowledge: Vec<JarvisKnowledge>,
     encoder_ready: bool,
+    precision: Option<String>,
+    load_duration: Duration,
+}
+
+/// Mediciones de una llamada a `generate_reply_measured`.
+#[derive(Clone, Debug)]
+pub struct JarvisCallMetrics {
+    pub duration: Duration,
+    /// Memoria residente del proceso al terminar la llamada, si el sistema la expone.
+    pub memory_bytes: Option<u64>,
 }
 
 struct JarvisKnowledge {
+    entry_index: usize,
+    label: String,
     embedding: Vec<f32>,
     norm: f32,
-    responder: fn(&JarvisRuntime, &str, f32) -> String,
+    response: String,
+    sources: Vec<(String, Option<String>)>,
+    responder: Option<fn(&JarvisRuntime, &str, f32) -> String>,
 }
 
 struct JarvisPersonaBlueprint {
@@ -43,17 +66,277 @@ struct JarvisPersonaBlueprint {
     responder: fn(&JarvisRuntime, &str, f32) -> String,
 }
 
+/// Similitud mínima para que un prompt se enrute a una entrada de conocimiento.
+pub const JARVIS_ROUTING_THRESHOLD: f32 = 0.18;
+const SOURCE_EXCERPT_CHARS: usize = 600;
+
+/// Entrada editable de la base de conocimientos de Jarvis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JarvisKnowledgeEntry {
+    pub label: String,
+    /// Frases de ejemplo cuya huella semántica identifica la entrada.
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    /// Respuesta fija. Si está vacía responde la persona integrada indicada en `persona`.
+    #[serde(default)]
+    pub response: String,
+    /// Rutas a documentos locales que se citan junto a la respuesta.
+    #[serde(default)]
+    pub sources: Vec<String>,
+    #[serde(default)]
+    pub persona: Option<String>,
+}
+
+impl JarvisKnowledgeEntry {
+    pub fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            triggers: Vec::new(),
+            response: String::new(),
+            sources: Vec::new(),
+            persona: None,
+        }
+    }
+
+    /// Base de conocimientos curada que acompaña a Jarvis de serie.
+    pub fn defaults() -> Vec<Self> {
+        JARVIS_BLUEPRINTS
+            .iter()
+            .map(|blueprint| Self {
+                label: blueprint.label.to_string(),
+                triggers: blueprint
+                    .prompts
+                    .iter()
+                    .map(|prompt| prompt.to_string())
+                    .collect(),
+                response: String::new(),
+                sources: Vec::new(),
+                persona: Some(blueprint.label.to_string()),
+            })
+            .collect()
+    }
+
+    /// Nombres de las personas integradas con respuesta dinámica.
+    pub fn builtin_personas() -> impl Iterator<Item = &'static str> {
+        JARVIS_BLUEPRINTS.iter().map(|blueprint| blueprint.label)
+    }
+
+    /// Frases de activación sin espacios sobrantes ni líneas vacías.
+    pub fn normalized_triggers(&self) -> Vec<&str> {
+        self.triggers
+            .iter()
+            .map(|trigger| trigger.trim())
+            .filter(|trigger| !trigger.is_empty())
+            .collect()
+    }
+}
+
+/// Puntuación de una entrada frente a un prompt de prueba.
+#[derive(Debug, Clone)]
+pub struct KnowledgeMatch {
+    /// Posición de la entrada en la lista recibida por el runtime.
+    pub entry_index: usize,
+    pub label: String,
+    pub score: f32,
+}
+
+impl KnowledgeMatch {
+    pub fn is_routed(&self) -> bool {
+        self.score >= JARVIS_ROUTING_THRESHOLD
+    }
+}
+
 enum JarvisEncoder {
     Bert {
         tokenizer: Tokenizer,
-        model: BertModel,
+        model: EncoderModel,
         device: Device,
         normalize: bool,
         mean_pooling: bool,
+        adapters: Vec<String>,
     },
     Placeholder,
 }
 
+/// Pesos del codificador: los de `safetensors` (o un GGUF con adaptadores fusionados)
+/// en precisión completa, o los de un GGUF manteniendo su cuantización.
+enum EncoderModel {
+    Full(BertModel),
+    Quantized(QuantizedBertModel),
+}
+
+impl EncoderModel {
+    fn forward(
+        &self,
+        input_ids: &Tensor,
+        token_type_ids: &Tensor,
+        attention_mask: &Tensor,
+    ) -> candle_core::Result<Tensor> {
+        match self {
+            EncoderModel::Full(model) => {
+                model.forward(input_ids, token_type_ids, Some(attention_mask))
+            }
+            EncoderModel::Quantized(model) => {
+                model.forward(input_ids, token_type_ids, attention_mask)
+            }
+        }
+    }
+}
+
+/// Codificador compartido con el runtime que lo cargó; sigue siendo válido aunque el
+/// runtime se recargue mientras se usa.
+#[derive(Clone)]
+pub struct JarvisEmbedder(Arc<JarvisEncoder>);
+
+impl JarvisEmbedder {
+    pub fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.0.embed_batch(texts)
+    }
+}
+
+const LORA_CONFIG_FILE: &str = "adapter_config.json";
+const LORA_WEIGHTS_FILE: &str = "adapter_model.safetensors";
+
+/// Adaptador LoRA en formato PEFT (`adapter_config.json` + `adapter_model.safetensors`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoraAdapter {
+    pub name: String,
+    pub path: PathBuf,
+    pub rank: usize,
+    pub alpha: f64,
+    pub base_model: Option<String>,
+}
+
+impl LoraAdapter {
+    pub fn from_dir(dir: &Path) -> Option<Self> {
+        if !dir.join(LORA_WEIGHTS_FILE).is_file() {
+            return None;
+        }
+        let raw = fs::read_to_string(dir.join(LORA_CONFIG_FILE)).ok()?;
+        let config: Value = match serde_json::from_str(&raw) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("Configuración LoRA inválida en {:?}: {}", dir, err);
+                return None;
+            }
+        };
+        let rank = config.get("r").and_then(Value::as_u64).unwrap_or(8) as usize;
+        Some(Self {
+            name: dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("adaptador")
+                .to_string(),
+            path: dir.to_path_buf(),
+            rank,
+            alpha: config
+                .get("lora_alpha")
+                .and_then(Value::as_f64)
+                .unwrap_or(rank as f64),
+            base_model: config
+                .get("base_model_name_or_path")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .filter(|base| !base.trim().is_empty()),
+        })
+    }
+
+    /// Factor `alpha / r` con el que se suma `B·A` a los pesos originales.
+    pub fn scale(&self) -> f64 {
+        if self.rank == 0 {
+            1.0
+        } else {
+            self.alpha / self.rank as f64
+        }
+    }
+
+    /// Indica si el adaptador declara como base el modelo indicado. Los adaptadores sin
+    /// modelo base declarado se consideran compatibles.
+    pub fn targets(&self, model_id: Option<&str>) -> bool {
+        match (self.base_model.as_deref(), model_id) {
+            (Some(base), Some(model)) => {
+                let base = base.trim_end_matches('/');
+                base.eq_ignore_ascii_case(model)
+                    || base
+                        .rsplit('/')
+                        .next()
+                        .zip(model.rsplit('/').next())
+                        .is_some_and(|(base, model)| base.eq_ignore_ascii_case(model))
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Busca adaptadores en `<modelo>/adapters/*` y en los directorios del directorio de
+/// instalación de Jarvis.
+pub fn discover_lora_adapters(model_dir: Option<&Path>, install_dir: &Path) -> Vec<LoraAdapter> {
+    let mut roots = Vec::new();
+    if let Some(model_dir) = model_dir {
+        roots.push(model_dir.join("adapters"));
+    }
+    roots.push(install_dir.to_path_buf());
+
+    let mut adapters: Vec<LoraAdapter> = roots
+        .iter()
+        .filter_map(|root| fs::read_dir(root).ok())
+        .flat_map(|entries| entries.filter_map(|entry| entry.ok()))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && Some(path.as_path()) != model_dir)
+        .filter_map(|path| LoraAdapter::from_dir(&path))
+        .collect();
+    adapters.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.path.cmp(&b.path)));
+    adapters.dedup_by(|a, b| a.path == b.path);
+    adapters
+}
+
+/// Suma `scale · B·A` a cada capa objetivo del adaptador. Los cambios solo se aplican si
+/// todas las capas encajan, para no dejar el modelo a medio fusionar.
+fn merge_lora_adapter(
+    weights: &mut HashMap<String, Tensor>,
+    adapter: &LoraAdapter,
+    device: &Device,
+) -> Result<usize> {
+    let tensors = candle_core::safetensors::load(adapter.path.join(LORA_WEIGHTS_FILE), device)
+        .with_context(|| format!("No se pudieron leer los pesos de {}", adapter.name))?;
+
+    let mut updates = Vec::new();
+    for (name, lora_a) in &tensors {
+        let Some(layer) = name.strip_suffix(".lora_A.weight") else {
+            continue;
+        };
+        let lora_b = tensors
+            .get(&format!("{layer}.lora_B.weight"))
+            .ok_or_else(|| anyhow!("Falta la matriz lora_B de {}", layer))?;
+        let layer = layer.strip_prefix("base_model.model.").unwrap_or(layer);
+        let target = [
+            format!("{layer}.weight"),
+            format!("bert.{layer}.weight"),
+            format!("{}.weight", layer.strip_prefix("bert.").unwrap_or(layer)),
+        ]
+        .into_iter()
+        .find(|candidate| weights.contains_key(candidate))
+        .ok_or_else(|| anyhow!("La capa {} no existe en el modelo base", layer))?;
+
+        let delta = lora_b
+            .to_dtype(DType::F32)?
+            .matmul(&lora_a.to_dtype(DType::F32)?)?
+            .affine(adapter.scale(), 0.0)?;
+        let merged = weights[&target]
+            .to_dtype(DType::F32)?
+            .add(&delta)
+            .with_context(|| format!("Dimensiones incompatibles en {}", target))?;
+        updates.push((target, merged));
+    }
+
+    if updates.is_empty() {
+        bail!("{} no contiene matrices LoRA", adapter.name);
+    }
+    let merged_layers = updates.len();
+    weights.extend(updates);
+    Ok(merged_layers)
+}
+
 const PLACEHOLDER_EMBEDDING_DIM: usize = 32;
 
 fn normalized_keywords(text: &str) -> Vec<String> {
@@ -68,6 +351,75 @@ fn normalized_keywords(text: &str) -> Vec<String> {
     keywords
 }
 
+/// Deduce la precisión de los pesos a partir de `config.json` o, en modelos GGUF, del
+/// sufijo de cuantización del fichero.
+fn detect_precision(model_dir: &Path) -> Option<String> {
+    let from_config = fs::read_to_string(model_dir.join("config.json"))
+        .ok()
+        .and_then(|raw| serde_json::from_str::<Value>(&raw).ok())
+        .and_then(|config| {
+            config
+                .get("torch_dtype")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        });
+    if from_config.is_some() {
+        return from_config;
+    }
+
+    fs::read_dir(model_dir)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.to_lowercase().ends_with(".gguf"))
+        .find_map(|name| {
+            name.trim_end_matches(".gguf")
+                .split(['.', '-'])
+                .map(str::to_lowercase)
+                .find(|part| {
+                    (part.starts_with('q') || part.starts_with("iq"))
+                        && part.chars().any(|ch| ch.is_ascii_digit())
+                })
+        })
+}
+
+/// Archivo GGUF del directorio del modelo, si lo hay.
+fn find_gguf_file(dir: &Path) -> Result<Option<PathBuf>> {
+    Ok(fs::read_dir(dir)
+        .with_context(|| format!("No se pudo listar el directorio del modelo {:?}", dir))?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("gguf"))
+        }))
+}
+
+/// Pesos de un GGUF descuantizados a `f32`. Solo se usa para fusionar adaptadores LoRA,
+/// que necesitan los pesos originales; sin ellos el modelo se carga cuantizado.
+fn dequantize_gguf(path: &Path, device: &Device) -> Result<HashMap<String, Tensor>> {
+    let mut file = fs::File::open(path).with_context(|| format!("No se pudo abrir {:?}", path))?;
+    let content = gguf_file::Content::read(&mut file)
+        .with_context(|| format!("{:?} no es un GGUF válido", path))?;
+    let mut weights = HashMap::new();
+    for name in content.tensor_infos.keys() {
+        let tensor = content
+            .tensor(&mut file, name, device)?
+            .dequantize(device)
+            .with_context(|| format!("No se pudo descuantizar '{}'", name))?;
+        weights.insert(name.clone(), tensor);
+    }
+    Ok(weights)
+}
+
+/// Memoria residente del proceso. Solo disponible en Linux (`/proc/self/statm`).
+pub fn resident_memory_bytes() -> Option<u64> {
+    let statm = fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
+
 fn read_metadata(model_dir: &Path) -> Option<Value> {
     let metadata_path = model_dir.join("metadata.json");
     if !metadata_path.exists() {
@@ -311,7 +663,7 @@ const JARVIS_BLUEPRINTS: &[JarvisPersonaBlueprint] = &[
 ];
 
 impl JarvisEncoder {
-    fn new(model_dir: &Path) -> Result<Self> {
+    fn new(model_dir: &Path, adapters: &[LoraAdapter]) -> Result<Self> {
         let tokenizer_path = model_dir.join("tokenizer.json");
         let mut tokenizer = Tokenizer::from_file(tokenizer_path)
             .map_err(|err| anyhow!("No se pudo cargar el tokenizer: {err}"))?;
@@ -343,17 +695,56 @@ impl JarvisEncoder {
                     .ok_or_else(|| anyhow!("No se pudo parsear {:?}: {}", config_path, primary_err))
             })?;
 
+        let device = Device::Cpu;
         let safetensor_files = collect_safetensor_files(model_dir)?;
-        if safetensor_files.is_empty() {
+        let gguf_file = if safetensor_files.is_empty() {
+            find_gguf_file(model_dir)?
+        } else {
+            None
+        };
+        if safetensor_files.is_empty() && gguf_file.is_none() {
             bail!(
-                "No se encontró ningún archivo '.safetensors' en {:?}. Descarga el modelo completo.",
+                "No se encontró ningún archivo '.safetensors' ni '.gguf' en {:?}. Descarga el modelo completo.",
                 model_dir
             );
         }
 
         let weight_refs: Vec<&Path> = safetensor_files.iter().map(|path| path.as_path()).collect();
 
-        let device = Device::Cpu;
+        // Con adaptadores, los pesos se cargan en memoria para fusionarlos antes de
+        // construir el modelo (los GGUF, descuantizados); sin ellos se mantiene el mapeo
+        // directo de los safetensors o los tensores cuantizados del GGUF.
+        let mut applied_adapters = Vec::new();
+        let merged_weights = if adapters.is_empty() {
+            None
+        } else {
+            let mut weights = match &gguf_file {
+                Some(path) => dequantize_gguf(path, &device)?,
+                None => HashMap::new(),
+            };
+            for file in &safetensor_files {
+                weights.extend(
+                    candle_core::safetensors::load(file, &device)
+                        .with_context(|| format!("No se pudo leer {:?}", file))?,
+                );
+            }
+            for adapter in adapters {
+                match merge_lora_adapter(&mut weights, adapter, &device) {
+                    Ok(layers) => {
+                        applied_adapters.push(adapter.name.clone());
+                        info!(
+                            "Adaptador LoRA '{}' fusionado en {} capas.",
+                            adapter.name, layers
+                        );
+                    }
+                    Err(err) => warn!(
+                        "No se pudo aplicar el adaptador LoRA '{}': {:#}",
+                        adapter.name, err
+                    ),
+                }
+            }
+            Some(weights)
+        };
         let dtype_hint = config_value
             .get("torch_dtype")
             .and_then(|value| value.as_str())
@@ -385,25 +776,42 @@ impl JarvisEncoder {
         let mut model = None;
         let mut last_error: Option<anyhow::Error> = None;
 
-        'outer: for dtype in dtype_candidates {
+        if let (Some(path), None) = (&gguf_file, &merged_weights) {
+            let vb = QuantizedVarBuilder::from_gguf(path, &device)
+                .with_context(|| format!("{:?} no es un GGUF válido", path))?;
             for candidate in &config_candidates {
-                let vb = match unsafe {
-                    VarBuilder::from_mmaped_safetensors(&weight_refs, dtype, &device)
-                } {
-                    Ok(builder) => builder,
-                    Err(err) => {
-                        last_error = Some(anyhow::Error::new(err));
-                        continue;
-                    }
-                };
-
-                match BertModel::load(vb, candidate) {
+                match QuantizedBertModel::load(vb.clone(), candidate) {
                     Ok(loaded) => {
-                        model = Some(loaded);
-                        break 'outer;
+                        model = Some(EncoderModel::Quantized(loaded));
+                        break;
                     }
-                    Err(err) => {
-                        last_error = Some(anyhow::Error::new(err));
+                    Err(err) => last_error = Some(anyhow::Error::new(err)),
+                }
+            }
+        } else {
+            'outer: for dtype in dtype_candidates {
+                for candidate in &config_candidates {
+                    let vb = match &merged_weights {
+                        Some(weights) => VarBuilder::from_tensors(weights.clone(), dtype, &device),
+                        None => match unsafe {
+                            VarBuilder::from_mmaped_safetensors(&weight_refs, dtype, &device)
+                        } {
+                            Ok(builder) => builder,
+                            Err(err) => {
+                                last_error = Some(anyhow::Error::new(err));
+                                continue;
+                            }
+                        },
+                    };
+
+                    match BertModel::load(vb, candidate) {
+                        Ok(loaded) => {
+                            model = Some(EncoderModel::Full(loaded));
+                            break 'outer;
+                        }
+                        Err(err) => {
+                            last_error = Some(anyhow::Error::new(err));
+                        }
                     }
                 }
             }
@@ -465,6 +873,7 @@ impl JarvisEncoder {
             device,
             normalize,
             mean_pooling,
+            adapters: applied_adapters,
         })
     }
 
@@ -472,6 +881,13 @@ impl JarvisEncoder {
         JarvisEncoder::Placeholder
     }
 
+    fn adapters(&self) -> &[String] {
+        match self {
+            JarvisEncoder::Bert { adapters, .. } => adapters,
+            JarvisEncoder::Placeholder => &[],
+        }
+    }
+
     fn embed(&self, text: &str) -> Result<Vec<f32>> {
         match self {
             JarvisEncoder::Bert {
@@ -480,6 +896,7 @@ impl JarvisEncoder {
                 device,
                 normalize,
                 mean_pooling,
+                ..
             } => {
                 let encoding = tokenizer
                     .encode(text, true)
@@ -515,7 +932,7 @@ impl JarvisEncoder {
                 };
 
                 let hidden_states = model
-                    .forward(&input_ids, &token_type_ids, Some(&attention_mask))?
+                    .forward(&input_ids, &token_type_ids, &attention_mask)?
                     .squeeze(0)?
                     .to_vec2::<f32>()?;
                 let attention_mask = attention_mask.squeeze(0)?.to_vec1::<f32>()?;
@@ -600,7 +1017,13 @@ impl JarvisEncoder {
 
 impl JarvisRuntime {
     /// Carga el runtime apuntando al directorio del modelo instalado.
-    pub fn load(model_dir: impl Into<PathBuf>, model_id: Option<String>) -> Result<Self> {
+    pub fn load(
+        model_dir: impl Into<PathBuf>,
+        model_id: Option<String>,
+        knowledge: &[JarvisKnowledgeEntry],
+        adapters: &[LoraAdapter],
+    ) -> Result<Self> {
+        let started = Instant::now();
         let mut model_dir = model_dir.into();
         if model_dir.is_file() {
             if let Some(parent) = model_dir.parent() {
@@ -666,7 +1089,7 @@ impl JarvisRuntime {
             })
             .unwrap_or_default();
 
-        let (encoder, encoder_ready) = match JarvisEncoder::new(&model_dir) {
+        let (encoder, encoder_ready) = match JarvisEncoder::new(&model_dir, adapters) {
             Ok(encoder) => (encoder, true),
             Err(err) => {
                 warn!(
@@ -677,34 +1100,104 @@ impl JarvisRuntime {
             }
         };
 
-        let knowledge = match Self::build_knowledge_base(&encoder) {
-            Ok(knowledge) => knowledge,
-            Err(err) => {
-                warn!(
-                    "No se pudo preparar la base de conocimientos local: {}. Se continuará sin coincidencias semánticas.",
-                    err
-                );
-                Vec::new()
-            }
-        };
+        let knowledge = Self::build_knowledge_base(&encoder, knowledge);
+        let precision = detect_precision(&model_dir);
 
         Ok(Self {
             model_dir,
             model_id,
+            requested_adapters: adapters
+                .iter()
+                .map(|adapter| adapter.path.clone())
+                .collect(),
             summary,
             pipeline_tag,
             tags,
-            encoder,
+            encoder: Arc::new(encoder),
             knowledge,
             encoder_ready,
+            precision,
+            load_duration: started.elapsed(),
         })
     }
 
+    /// Tiempo que tardó `load` en preparar el modelo y la base de conocimientos.
+    pub fn load_duration(&self) -> Duration {
+        self.load_duration
+    }
+
+    /// Precisión o cuantización de los pesos (`float16`, `q4_k_m`…), si se pudo deducir.
+    pub fn precision(&self) -> Option<&str> {
+        self.precision.as_deref()
+    }
+
+    /// Igual que `generate_reply`, midiendo la duración y la memoria del proceso.
+    pub fn generate_reply_measured(&self, prompt: &str) -> (Result<String>, JarvisCallMetrics) {
+        let started = Instant::now();
+        let reply = self.generate_reply(prompt);
+        let metrics = JarvisCallMetrics {
+            duration: started.elapsed(),
+            memory_bytes: resident_memory_bytes(),
+        };
+        (reply, metrics)
+    }
+
+    /// Sustituye la base de conocimientos en caliente y devuelve cuántas entradas quedaron
+    /// indexadas.
+    pub fn reload_knowledge(&mut self, entries: &[JarvisKnowledgeEntry]) -> usize {
+        self.knowledge = Self::build_knowledge_base(&self.encoder, entries);
+        self.knowledge.len()
+    }
+
+    /// Ordena `entries` por similitud con `prompt` sin modificar la base cargada. Sin
+    /// runtime se usa la huella por palabras clave del modo degradado.
+    pub fn preview_routing(
+        runtime: Option<&JarvisRuntime>,
+        entries: &[JarvisKnowledgeEntry],
+        prompt: &str,
+    ) -> Result<Vec<KnowledgeMatch>> {
+        let placeholder = JarvisEncoder::placeholder();
+        let encoder = runtime.map_or(&placeholder, |runtime| runtime.encoder.as_ref());
+        let knowledge = Self::build_knowledge_base(encoder, entries);
+        let prompt_vector = encoder.embed(prompt)?;
+        Ok(Self::rank_knowledge(&knowledge, &prompt_vector))
+    }
+
+    /// Calcula los embeddings de `texts` con el modelo local. En modo degradado se
+    /// devuelve la huella por palabras clave.
+    pub fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        self.encoder.embed_batch(texts)
+    }
+
+    /// Codificador del runtime para calcular embeddings desde otro hilo.
+    pub fn embedder(&self) -> JarvisEmbedder {
+        JarvisEmbedder(Arc::clone(&self.encoder))
+    }
+
+    /// Indica si el runtime dispone de pesos reales para calcular embeddings.
+    pub fn has_semantic_encoder(&self) -> bool {
+        self.encoder_ready
+    }
+
     /// Comprueba si el runtime apunta al mismo directorio indicado.
     pub fn matches(&self, dir: &Path) -> bool {
         self.model_dir == dir
     }
 
+    /// Comprueba si el runtime se cargó con la misma selección de adaptadores.
+    pub fn matches_adapters(&self, adapters: &[LoraAdapter]) -> bool {
+        self.requested_adapters.len() == adapters.len()
+            && adapters
+                .iter()
+                .zip(&self.requested_adapters)
+                .all(|(adapter, path)| &adapter.path == path)
+    }
+
+    /// Adaptadores LoRA fusionados con éxito en los pesos del modelo.
+    pub fn active_adapters(&self) -> &[String] {
+        self.encoder.adapters()
+    }
+
     /// Nombre descriptivo del modelo activo.
     pub fn model_label(&self) -> String {
         if let Some(id) = &self.model_id {
@@ -735,28 +1228,20 @@ impl JarvisRuntime {
             }
         };
 
-        let persona_segment = if prompt_vector.is_empty() || self.knowledge.is_empty() {
-            Self::reflect_prompt(prompt)
-        } else {
-            let prompt_norm = Self::vector_norm(&prompt_vector);
-            let mut best_match: Option<(&JarvisKnowledge, f32)> = None;
-            for entry in &self.knowledge {
-                let score = Self::cosine_similarity(&prompt_vector, prompt_norm, entry);
-                match best_match {
-                    Some((_, current)) if score <= current => {}
-                    _ => best_match = Some((entry, score)),
-                }
-            }
-
-            if let Some((entry, score)) = best_match {
-                if score >= 0.18 {
-                    (entry.responder)(self, prompt, score)
-                } else {
-                    Self::reflect_prompt(prompt)
-                }
-            } else {
-                Self::reflect_prompt(prompt)
+        let best_match = Self::rank_knowledge(&self.knowledge, &prompt_vector)
+            .into_iter()
+            .next()
+            .filter(KnowledgeMatch::is_routed);
+        let persona_segment = match best_match {
+            Some(best) => {
+                let entry = self
+                    .knowledge
+                    .iter()
+                    .find(|entry| entry.entry_index == best.entry_index)
+                    .expect("coincidencia de la base cargada");
+                self.respond_with_knowledge(entry, prompt, best.score)
             }
+            None => Self::reflect_prompt(prompt),
         };
 
         Ok(self.compose_response(persona_segment, prompt))
@@ -777,6 +1262,12 @@ impl JarvisRuntime {
         if let Some(pipeline) = &self.pipeline_tag {
             header.push_str(&format!(" Está optimizado para la tarea '{}'.", pipeline));
         }
+        if !self.active_adapters().is_empty() {
+            header.push_str(&format!(
+                " Adaptadores LoRA activos: {}.",
+                self.active_adapters().join(", ")
+            ));
+        }
         if !self.encoder_ready {
             header.push_str(" Descarga los pesos completos para habilitar respuestas semánticas.");
         }
@@ -819,28 +1310,109 @@ impl JarvisRuntime {
         }
     }
 
-    fn build_knowledge_base(encoder: &JarvisEncoder) -> Result<Vec<JarvisKnowledge>> {
-        let mut knowledge = Vec::with_capacity(JARVIS_BLUEPRINTS.len());
-        for blueprint in JARVIS_BLUEPRINTS {
-            let embeddings = match encoder.embed_batch(blueprint.prompts) {
+    fn build_knowledge_base(
+        encoder: &JarvisEncoder,
+        entries: &[JarvisKnowledgeEntry],
+    ) -> Vec<JarvisKnowledge> {
+        let mut knowledge = Vec::with_capacity(entries.len());
+        for (entry_index, entry) in entries.iter().enumerate() {
+            let triggers = entry.normalized_triggers();
+            if triggers.is_empty() {
+                warn!(
+                    "La entrada '{}' no tiene frases de activación; se omitirá.",
+                    entry.label
+                );
+                continue;
+            }
+            let embeddings = match encoder.embed_batch(&triggers) {
                 Ok(value) => value,
                 Err(err) => {
                     warn!(
                         "No se pudo crear la huella semántica para '{}': {}.",
-                        blueprint.label, err
+                        entry.label, err
                     );
                     continue;
                 }
             };
             let combined = Self::average_embedding(&embeddings);
             let norm = Self::vector_norm(&combined);
+            let responder = entry.persona.as_deref().and_then(|persona| {
+                JARVIS_BLUEPRINTS
+                    .iter()
+                    .find(|blueprint| blueprint.label == persona)
+                    .map(|blueprint| blueprint.responder)
+            });
             knowledge.push(JarvisKnowledge {
+                entry_index,
+                label: entry.label.clone(),
                 embedding: combined,
                 norm,
-                responder: blueprint.responder,
+                response: entry.response.trim().to_string(),
+                sources: entry
+                    .sources
+                    .iter()
+                    .map(|source| source.trim())
+                    .filter(|source| !source.is_empty())
+                    .map(|source| (source.to_string(), Self::source_excerpt(Path::new(source))))
+                    .collect(),
+                responder,
             });
         }
-        Ok(knowledge)
+        knowledge
+    }
+
+    fn source_excerpt(path: &Path) -> Option<String> {
+        match fs::read_to_string(path) {
+            Ok(content) => {
+                let content = content.trim();
+                let mut excerpt: String = content.chars().take(SOURCE_EXCERPT_CHARS).collect();
+                if excerpt.len() < content.len() {
+                    excerpt.push('…');
+                }
+                Some(excerpt).filter(|excerpt| !excerpt.is_empty())
+            }
+            Err(err) => {
+                warn!("No se pudo leer el documento fuente {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    fn rank_knowledge(knowledge: &[JarvisKnowledge], prompt_vector: &[f32]) -> Vec<KnowledgeMatch> {
+        if prompt_vector.is_empty() {
+            return Vec::new();
+        }
+        let prompt_norm = Self::vector_norm(prompt_vector);
+        let mut matches: Vec<KnowledgeMatch> = knowledge
+            .iter()
+            .map(|entry| KnowledgeMatch {
+                entry_index: entry.entry_index,
+                label: entry.label.clone(),
+                score: Self::cosine_similarity(prompt_vector, prompt_norm, entry),
+            })
+            .collect();
+        // Ante un empate gana la primera entrada, como en el recorrido original.
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches
+    }
+
+    fn respond_with_knowledge(&self, entry: &JarvisKnowledge, prompt: &str, score: f32) -> String {
+        let mut sections = Vec::new();
+        if !entry.response.is_empty() {
+            sections.push(entry.response.clone());
+        } else if let Some(responder) = entry.responder {
+            sections.push(responder(self, prompt, score));
+        } else {
+            sections.push(Self::reflect_prompt(prompt));
+        }
+
+        for (path, excerpt) in &entry.sources {
+            match excerpt {
+                Some(excerpt) => sections.push(format!("Fuente: {}\n{}", path, excerpt)),
+                None => sections.push(format!("Fuente: {}", path)),
+            }
+        }
+        sections.join("\n\n")
     }
 
     fn average_embedding(vectors: &[Vec<f32>]) -> Vec<f32> {
@@ -1,10 +1,24 @@
 pub mod claude;
+pub mod error;
 pub mod github;
 pub mod groq;
 pub mod huggingface;
 pub mod local;
+pub mod network;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
+pub mod quantized_bert;
+pub mod web_search;
 
 // Podrías definir un trait común `LLMClient` aquí para unificar las APIs.
+
+/// Ajustes de generación que puede fijar una conversación. Los campos vacíos conservan
+/// los valores propios de cada cliente.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GenerationOptions {
+    /// Instrucciones de sistema que sustituyen a las del cliente.
+    pub system: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+}
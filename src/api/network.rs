@@ -0,0 +1,124 @@
+use anyhow::{anyhow, Context, Result};
+use once_cell::sync::Lazy;
+use reqwest::blocking::ClientBuilder;
+use reqwest::{Certificate, NoProxy, Proxy};
+use std::cell::Cell;
+use std::fs;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use crate::config::NetworkConfig;
+
+/// Ajustes de red vigentes, compartidos por todos los clientes HTTP de `api::*`.
+static SETTINGS: Lazy<RwLock<NetworkConfig>> = Lazy::new(|| RwLock::new(NetworkConfig::default()));
+
+thread_local! {
+    /// Momento en que llegaron las cabeceras de la última respuesta de chat en este hilo.
+    static FIRST_BYTE: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+const PROXY_ENV_VARS: [&str; 3] = ["HTTPS_PROXY", "HTTP_PROXY", "ALL_PROXY"];
+
+/// Publica los ajustes de red para los clientes que se construyan a partir de ahora.
+///
+/// El descargador de Hugging Face Hub utiliza `ureq`, que solo lee el proxy desde las
+/// variables de entorno, por lo que también se exportan allí.
+pub fn apply_settings(config: &NetworkConfig) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        if *settings == *config {
+            return;
+        }
+        *settings = config.clone();
+    }
+
+    let proxy = config.proxy_url.trim();
+    for var in PROXY_ENV_VARS {
+        if proxy.is_empty() {
+            std::env::remove_var(var);
+        } else {
+            std::env::set_var(var, proxy);
+        }
+    }
+    if config.no_proxy.trim().is_empty() {
+        std::env::remove_var("NO_PROXY");
+    } else {
+        std::env::set_var("NO_PROXY", config.no_proxy.trim());
+    }
+}
+
+pub fn current_settings() -> NetworkConfig {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Crea un `ClientBuilder` con el proxy, los certificados y el timeout configurados.
+/// `default_timeout` se usa cuando el usuario no ha fijado un timeout global.
+pub fn client_builder(default_timeout: Duration) -> Result<ClientBuilder> {
+    builder_with(&current_settings(), default_timeout)
+}
+
+/// Anota que acaba de llegar la respuesta de un proveedor. Las peticiones no usan
+/// streaming, así que es el primer byte que se recibe del modelo.
+pub fn mark_first_byte() {
+    FIRST_BYTE.with(|first| first.set(Some(Instant::now())));
+}
+
+/// Devuelve y olvida la última marca de `mark_first_byte` hecha en este hilo.
+pub fn take_first_byte() -> Option<Instant> {
+    FIRST_BYTE.with(Cell::take)
+}
+
+/// Comprueba que los ajustes indicados permiten construir un cliente HTTP.
+pub fn validate(config: &NetworkConfig) -> Result<()> {
+    builder_with(config, Duration::from_secs(30))?
+        .build()
+        .context("No se pudo crear un cliente HTTP con la configuración de red")?;
+    Ok(())
+}
+
+fn builder_with(config: &NetworkConfig, default_timeout: Duration) -> Result<ClientBuilder> {
+    let timeout = if config.request_timeout_secs > 0 {
+        Duration::from_secs(config.request_timeout_secs)
+    } else {
+        default_timeout
+    };
+    let mut builder = reqwest::blocking::Client::builder().timeout(timeout);
+
+    let proxy_url = config.proxy_url.trim();
+    if !proxy_url.is_empty() {
+        // reqwest se compila sin la característica `socks`, así que se rechazan
+        // explícitamente en lugar de conectar directamente sin avisar.
+        if proxy_url.starts_with("socks") {
+            return Err(anyhow!(
+                "El proxy SOCKS '{}' no está soportado en esta compilación; usa un proxy HTTP o HTTPS.",
+                proxy_url
+            ));
+        }
+        let proxy = Proxy::all(proxy_url)
+            .with_context(|| format!("URL de proxy no válida: {}", proxy_url))?
+            .no_proxy(NoProxy::from_string(config.no_proxy.trim()));
+        builder = builder.proxy(proxy);
+    }
+
+    let ca_path = config.ca_bundle_path.trim();
+    if !ca_path.is_empty() {
+        let pem = fs::read(ca_path)
+            .with_context(|| format!("No se pudo leer el bundle de certificados {}", ca_path))?;
+        let certificates = Certificate::from_pem_bundle(&pem).with_context(|| {
+            format!("El bundle {} no contiene certificados PEM válidos", ca_path)
+        })?;
+        if certificates.is_empty() {
+            return Err(anyhow!(
+                "El bundle {} no contiene ningún certificado",
+                ca_path
+            ));
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    Ok(builder)
+}
@@ -2,9 +2,9 @@ use std::process::Command;
 use std::time::Duration;
 
 use anyhow::{anyhow, Context, Result};
-use reqwest::blocking::Client;
 use serde::Deserialize;
 
+use super::network;
 use crate::local_providers::{LocalModelCard, LocalModelProvider};
 
 #[derive(Debug, Deserialize, Default)]
@@ -45,8 +45,7 @@ fn resolve_host(token: Option<&str>) -> String {
 pub fn search_models(query: &str, token: Option<&str>) -> Result<Vec<LocalModelCard>> {
     let host = resolve_host(token);
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(15))
+    let client = network::client_builder(Duration::from_secs(15))?
         .user_agent("JungleMonkAI/0.1")
         .build()
         .context("No se pudo crear el cliente HTTP para Ollama")?;
@@ -1,9 +1,16 @@
-use anyhow::{Context, Result};
-use reqwest::blocking::Client;
+use anyhow::{bail, Context, Result};
+use base64::Engine;
 use serde::Deserialize;
 use serde_json::json;
+use std::path::Path;
 use std::time::Duration;
 
+use super::{error::ProviderError, network, GenerationOptions};
+
+/// Parámetros de muestreo de cada respuesta de chat.
+pub const MAX_TOKENS: u32 = 256;
+pub const TEMPERATURE: f32 = 0.2;
+
 #[derive(Debug, Deserialize)]
 struct ChatMessage {
     #[serde(default)]
@@ -21,35 +28,198 @@ struct ChatResponse {
     choices: Vec<ChatChoice>,
 }
 
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    #[serde(default)]
+    data: Vec<EmbeddingData>,
+}
+
+/// Calcula los embeddings de `texts` con el endpoint de OpenAI, en el mismo orden.
+pub fn embed(api_key: &str, model: &str, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+    if texts.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let client = network::client_builder(Duration::from_secs(45))?
+        .build()
+        .context("No se pudo crear el cliente HTTP para OpenAI")?;
+
+    let response = client
+        .post("https://api.openai.com/v1/embeddings")
+        .bearer_auth(api_key)
+        .json(&json!({ "model": model, "input": texts }))
+        .send()
+        .map_err(|err| ProviderError::network("OpenAI", err))?;
+
+    if !response.status().is_success() {
+        return Err(ProviderError::from_response("OpenAI", response).into());
+    }
+
+    let mut parsed: EmbeddingResponse = response
+        .json()
+        .map_err(|err| ProviderError::invalid_payload("OpenAI", err))?;
+    if parsed.data.len() != texts.len() {
+        bail!(
+            "OpenAI devolvió {} embeddings para {} textos",
+            parsed.data.len(),
+            texts.len()
+        );
+    }
+    parsed.data.sort_by_key(|item| item.index);
+    Ok(parsed.data.into_iter().map(|item| item.embedding).collect())
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptionResponse {
+    #[serde(default)]
+    text: String,
+}
+
+/// Transcribe el archivo de audio con `/v1/audio/transcriptions`. `language` es un código
+/// ISO-639-1 opcional; sin él el servicio detecta el idioma.
+pub fn transcribe_audio(
+    api_key: &str,
+    model: &str,
+    audio: &Path,
+    language: Option<&str>,
+) -> Result<String> {
+    let client = network::client_builder(Duration::from_secs(120))?
+        .build()
+        .context("No se pudo crear el cliente HTTP para OpenAI")?;
+
+    let mut form = reqwest::blocking::multipart::Form::new()
+        .text("model", model.to_string())
+        .text("response_format", "json")
+        .file("file", audio)
+        .with_context(|| format!("No se pudo leer {}", audio.display()))?;
+    if let Some(language) = language.map(str::trim).filter(|code| !code.is_empty()) {
+        form = form.text("language", language.to_string());
+    }
+
+    let response = client
+        .post("https://api.openai.com/v1/audio/transcriptions")
+        .bearer_auth(api_key)
+        .multipart(form)
+        .send()
+        .map_err(|err| ProviderError::network("OpenAI", err))?;
+
+    if !response.status().is_success() {
+        return Err(ProviderError::from_response("OpenAI", response).into());
+    }
+
+    let parsed: TranscriptionResponse = response
+        .json()
+        .map_err(|err| ProviderError::invalid_payload("OpenAI", err))?;
+    Ok(parsed.text.trim().to_string())
+}
+
 /// Envía un mensaje a la API de OpenAI y devuelve la respuesta de chat generada.
 pub fn send_message(api_key: &str, model: &str, prompt: &str) -> Result<String> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(45))
+    send_message_with(api_key, model, prompt, &GenerationOptions::default())
+}
+
+/// Igual que `send_message`, con las instrucciones y parámetros de la conversación.
+pub fn send_message_with(
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+    options: &GenerationOptions,
+) -> Result<String> {
+    chat_completion(api_key, model, json!(prompt), false, options)
+}
+
+/// Igual que `send_message_with`, pero activa el modo JSON nativo (`response_format`)
+/// para que la respuesta sea siempre un objeto JSON.
+pub fn send_message_json(
+    api_key: &str,
+    model: &str,
+    prompt: &str,
+    options: &GenerationOptions,
+) -> Result<String> {
+    chat_completion(api_key, model, json!(prompt), true, options)
+}
+
+/// Pide a un modelo con visión que responda a `prompt` sobre la imagen indicada, enviada
+/// como URL `data:` con el tipo MIME `media_type`.
+pub fn describe_image(
+    api_key: &str,
+    model: &str,
+    image: &[u8],
+    media_type: &str,
+    prompt: &str,
+) -> Result<String> {
+    let url = format!(
+        "data:{};base64,{}",
+        media_type,
+        base64::engine::general_purpose::STANDARD.encode(image)
+    );
+    let content = json!([
+        { "type": "text", "text": prompt },
+        { "type": "image_url", "image_url": { "url": url } },
+    ]);
+    let options = GenerationOptions {
+        max_tokens: Some(MAX_TOKENS * 2),
+        ..GenerationOptions::default()
+    };
+    chat_completion(api_key, model, content, false, &options)
+}
+
+/// `user_content` es el texto del prompt o una lista de partes (texto e imágenes).
+fn chat_completion(
+    api_key: &str,
+    model: &str,
+    user_content: serde_json::Value,
+    json_mode: bool,
+    options: &GenerationOptions,
+) -> Result<String> {
+    let client = network::client_builder(Duration::from_secs(45))?
         .build()
         .context("No se pudo crear el cliente HTTP para OpenAI")?;
 
-    let payload = json!({
+    // La persona de la conversación sustituye a las instrucciones por defecto; el modo
+    // JSON sigue exigiendo un objeto como respuesta.
+    let system = match (&options.system, json_mode) {
+        (Some(persona), true) => {
+            format!("{persona}\n\nResponde únicamente con un objeto JSON válido.")
+        }
+        (None, true) => "Responde únicamente con un objeto JSON válido.".to_string(),
+        (Some(persona), false) => persona.clone(),
+        (None, false) => "Eres un asistente que responde con frases breves.".to_string(),
+    };
+    let mut payload = json!({
         "model": model,
-        "max_tokens": 256,
-        "temperature": 0.2,
+        "max_tokens": options.max_tokens.unwrap_or(MAX_TOKENS),
+        "temperature": options.temperature.unwrap_or(TEMPERATURE),
         "messages": [
-            {"role": "system", "content": "Eres un asistente que responde con frases breves."},
-            {"role": "user", "content": prompt},
+            {"role": "system", "content": system},
+            {"role": "user", "content": user_content},
         ],
     });
+    if json_mode {
+        payload["response_format"] = json!({ "type": "json_object" });
+    }
 
     let response = client
         .post("https://api.openai.com/v1/chat/completions")
         .bearer_auth(api_key)
         .json(&payload)
         .send()
-        .context("Error enviando la solicitud a OpenAI")?
-        .error_for_status()
-        .context("OpenAI devolvió un estado de error")?;
+        .map_err(|err| ProviderError::network("OpenAI", err))?;
+    network::mark_first_byte();
+
+    if !response.status().is_success() {
+        return Err(ProviderError::from_response("OpenAI", response).into());
+    }
 
     let parsed: ChatResponse = response
         .json()
-        .context("No se pudo interpretar la respuesta de OpenAI")?;
+        .map_err(|err| ProviderError::invalid_payload("OpenAI", err))?;
 
     let reply = parsed
         .choices
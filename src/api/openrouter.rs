@@ -1,9 +1,9 @@
 use std::time::Duration;
 
 use anyhow::{Context, Result};
-use reqwest::blocking::Client;
 use serde::Deserialize;
 
+use super::network;
 use crate::local_providers::{LocalModelCard, LocalModelProvider};
 
 #[derive(Debug, Deserialize)]
@@ -35,8 +35,7 @@ struct OpenRouterArchitecture {
 
 /// Fetches the public OpenRouter catalog and filters it using the provided query.
 pub fn search_models(query: &str) -> Result<Vec<LocalModelCard>> {
-    let client = Client::builder()
-        .timeout(Duration::from_secs(30))
+    let client = network::client_builder(Duration::from_secs(30))?
         .user_agent("JungleMonkAI/0.1")
         .build()
         .context("No se pudo crear el cliente HTTP para OpenRouter")?;
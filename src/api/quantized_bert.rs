@@ -0,0 +1,183 @@
+//! Variante de BERT que conserva cuantizados los pesos de un GGUF.
+//!
+//! Sigue la estructura de `candle_transformers::models::bert`, pero las capas lineales
+//! usan `QMatMul` sobre los `QTensor` del archivo, de modo que el modelo ocupa en
+//! memoria lo mismo que en disco. Solo los embeddings y las normalizaciones, que son
+//! pequeños, se descuantizan.
+
+use candle_core::{DType, Module, Result, Tensor, D};
+use candle_nn::{Embedding, LayerNorm};
+use candle_transformers::models::bert::{Config, HiddenAct};
+use candle_transformers::quantized_nn::{layer_norm, linear, Linear};
+use candle_transformers::quantized_var_builder::VarBuilder;
+
+fn embedding(rows: usize, hidden: usize, vb: VarBuilder) -> Result<Embedding> {
+    let weights = vb.get((rows, hidden), "weight")?.dequantize(vb.device())?;
+    Ok(Embedding::new(weights, hidden))
+}
+
+struct Embeddings {
+    word: Embedding,
+    position: Embedding,
+    token_type: Embedding,
+    layer_norm: LayerNorm,
+}
+
+impl Embeddings {
+    fn load(vb: VarBuilder, config: &Config) -> Result<Self> {
+        Ok(Self {
+            word: embedding(
+                config.vocab_size,
+                config.hidden_size,
+                vb.pp("word_embeddings"),
+            )?,
+            position: embedding(
+                config.max_position_embeddings,
+                config.hidden_size,
+                vb.pp("position_embeddings"),
+            )?,
+            token_type: embedding(
+                config.type_vocab_size,
+                config.hidden_size,
+                vb.pp("token_type_embeddings"),
+            )?,
+            layer_norm: layer_norm(
+                config.hidden_size,
+                config.layer_norm_eps,
+                vb.pp("LayerNorm"),
+            )?,
+        })
+    }
+
+    fn forward(&self, input_ids: &Tensor, token_type_ids: &Tensor) -> Result<Tensor> {
+        let (_, seq_len) = input_ids.dims2()?;
+        let positions = Tensor::arange(0u32, seq_len as u32, input_ids.device())?;
+        let embeddings = (self.word.forward(input_ids)?
+            + self.token_type.forward(token_type_ids)?)?
+        .broadcast_add(&self.position.forward(&positions)?)?;
+        self.layer_norm.forward(&embeddings)
+    }
+}
+
+struct Layer {
+    query: Linear,
+    key: Linear,
+    value: Linear,
+    attention_output: Linear,
+    attention_norm: LayerNorm,
+    intermediate: Linear,
+    output: Linear,
+    output_norm: LayerNorm,
+    heads: usize,
+    head_size: usize,
+    activation: HiddenAct,
+}
+
+impl Layer {
+    fn load(vb: VarBuilder, config: &Config) -> Result<Self> {
+        let hidden = config.hidden_size;
+        let attention = vb.pp("attention");
+        Ok(Self {
+            query: linear(hidden, hidden, attention.pp("self.query"))?,
+            key: linear(hidden, hidden, attention.pp("self.key"))?,
+            value: linear(hidden, hidden, attention.pp("self.value"))?,
+            attention_output: linear(hidden, hidden, attention.pp("output.dense"))?,
+            attention_norm: layer_norm(
+                hidden,
+                config.layer_norm_eps,
+                attention.pp("output.LayerNorm"),
+            )?,
+            intermediate: linear(
+                hidden,
+                config.intermediate_size,
+                vb.pp("intermediate.dense"),
+            )?,
+            output: linear(config.intermediate_size, hidden, vb.pp("output.dense"))?,
+            output_norm: layer_norm(hidden, config.layer_norm_eps, vb.pp("output.LayerNorm"))?,
+            heads: config.num_attention_heads,
+            head_size: hidden / config.num_attention_heads,
+            activation: config.hidden_act,
+        })
+    }
+
+    fn split_heads(&self, xs: &Tensor) -> Result<Tensor> {
+        let (batch, seq_len, _) = xs.dims3()?;
+        xs.reshape((batch, seq_len, self.heads, self.head_size))?
+            .transpose(1, 2)?
+            .contiguous()
+    }
+
+    fn forward(&self, hidden_states: &Tensor, attention_mask: &Tensor) -> Result<Tensor> {
+        let query = self.split_heads(&self.query.forward(hidden_states)?)?;
+        let key = self.split_heads(&self.key.forward(hidden_states)?)?;
+        let value = self.split_heads(&self.value.forward(hidden_states)?)?;
+
+        let scores = (query.matmul(&key.t()?)? / (self.head_size as f64).sqrt())?
+            .broadcast_add(attention_mask)?;
+        let probabilities = candle_nn::ops::softmax(&scores, D::Minus1)?;
+        let context = probabilities
+            .matmul(&value)?
+            .transpose(1, 2)?
+            .contiguous()?
+            .flatten_from(D::Minus2)?;
+
+        let attention = self
+            .attention_norm
+            .forward(&(self.attention_output.forward(&context)? + hidden_states)?)?;
+        let intermediate = self.intermediate.forward(&attention)?;
+        let intermediate = match self.activation {
+            HiddenAct::Gelu => intermediate.gelu_erf()?,
+            HiddenAct::GeluApproximate => intermediate.gelu()?,
+            HiddenAct::Relu => intermediate.relu()?,
+        };
+        self.output_norm
+            .forward(&(self.output.forward(&intermediate)? + attention)?)
+    }
+}
+
+/// Codificador BERT construido sobre los tensores cuantizados de un GGUF.
+pub struct QuantizedBertModel {
+    embeddings: Embeddings,
+    layers: Vec<Layer>,
+}
+
+impl QuantizedBertModel {
+    /// Carga el modelo con los nombres de tensor de Hugging Face, con o sin el prefijo
+    /// del tipo de modelo (`bert.embeddings...`), igual que `BertModel::load`.
+    pub fn load(vb: VarBuilder, config: &Config) -> Result<Self> {
+        match Self::load_from(vb.clone(), config) {
+            Ok(model) => Ok(model),
+            Err(err) => match &config.model_type {
+                Some(model_type) => Self::load_from(vb.pp(model_type), config).map_err(|_| err),
+                None => Err(err),
+            },
+        }
+    }
+
+    fn load_from(vb: VarBuilder, config: &Config) -> Result<Self> {
+        let embeddings = Embeddings::load(vb.pp("embeddings"), config)?;
+        let layers = (0..config.num_hidden_layers)
+            .map(|index| Layer::load(vb.pp(format!("encoder.layer.{index}")), config))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { embeddings, layers })
+    }
+
+    pub fn forward(
+        &self,
+        input_ids: &Tensor,
+        token_type_ids: &Tensor,
+        attention_mask: &Tensor,
+    ) -> Result<Tensor> {
+        let mut hidden_states = self.embeddings.forward(input_ids, token_type_ids)?;
+        // Las posiciones de relleno reciben el mínimo representable antes del softmax.
+        let mask = attention_mask
+            .unsqueeze(1)?
+            .unsqueeze(1)?
+            .to_dtype(DType::F32)?;
+        let mask = ((mask.ones_like()? - &mask)? * f64::from(f32::MIN))?;
+        for layer in &self.layers {
+            hidden_states = layer.forward(&hidden_states, &mask)?;
+        }
+        Ok(hidden_states)
+    }
+}
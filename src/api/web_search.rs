@@ -0,0 +1,236 @@
+use anyhow::{bail, Context, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use std::time::Duration;
+
+use super::error::ProviderError;
+use super::network;
+use crate::config::{WebSearchBackend, WebSearchConfig};
+
+const BRAVE_ENDPOINT: &str = "https://api.search.brave.com/res/v1/web/search";
+const BING_ENDPOINT: &str = "https://api.bing.microsoft.com/v7.0/search";
+/// Bytes de una página que se leen como máximo al ampliar un resultado.
+const MAX_PAGE_BYTES: usize = 2 * 1024 * 1024;
+
+static HIDDEN_BLOCKS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?is)<(script|style|noscript|svg|head)\b.*?</(script|style|noscript|svg|head)>")
+        .expect("patrón válido")
+});
+static BLOCK_TAGS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)</?(p|div|br|li|h[1-6]|tr|section|article)\b[^>]*>").expect("patrón válido")
+});
+static TAGS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<[^>]*>").expect("patrón válido"));
+
+/// Resultado tal como lo devuelve el buscador, en su orden.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WebSearchHit {
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+}
+
+#[derive(Deserialize)]
+struct SearxResponse {
+    #[serde(default)]
+    results: Vec<SearxResult>,
+}
+
+#[derive(Deserialize)]
+struct SearxResult {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct BraveResponse {
+    #[serde(default)]
+    web: Option<BraveWeb>,
+}
+
+#[derive(Deserialize)]
+struct BraveWeb {
+    #[serde(default)]
+    results: Vec<BraveResult>,
+}
+
+#[derive(Deserialize)]
+struct BraveResult {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct BingResponse {
+    #[serde(rename = "webPages", default)]
+    web_pages: Option<BingWebPages>,
+}
+
+#[derive(Deserialize)]
+struct BingWebPages {
+    #[serde(default)]
+    value: Vec<BingResult>,
+}
+
+#[derive(Deserialize)]
+struct BingResult {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    snippet: String,
+}
+
+/// Comprueba que el backend elegido tiene lo necesario para buscar.
+pub fn validate(config: &WebSearchConfig) -> Result<()> {
+    match config.backend {
+        WebSearchBackend::SearXng if config.searxng_url.trim().is_empty() => {
+            bail!("Indica la URL de la instancia de SearXNG")
+        }
+        WebSearchBackend::Brave if config.brave_api_key.trim().is_empty() => {
+            bail!("Falta la API key de Brave Search")
+        }
+        WebSearchBackend::Bing if config.bing_api_key.trim().is_empty() => {
+            bail!("Falta la API key de Bing Web Search")
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Consulta el backend configurado y devuelve como mucho `count` resultados.
+pub fn search(config: &WebSearchConfig, query: &str, count: usize) -> Result<Vec<WebSearchHit>> {
+    validate(config)?;
+    let label = config.backend.label();
+    let client = network::client_builder(Duration::from_secs(20))?
+        .user_agent("JungleMonkAI/0.1")
+        .build()
+        .with_context(|| format!("No se pudo crear el cliente HTTP para {}", label))?;
+    let count = count.max(1).to_string();
+
+    let request = match config.backend {
+        WebSearchBackend::SearXng => client
+            .get(format!(
+                "{}/search",
+                config.searxng_url.trim().trim_end_matches('/')
+            ))
+            .query(&[("q", query), ("format", "json")]),
+        WebSearchBackend::Brave => client
+            .get(BRAVE_ENDPOINT)
+            .header("X-Subscription-Token", config.brave_api_key.trim())
+            .header("Accept", "application/json")
+            .query(&[("q", query), ("count", count.as_str())]),
+        WebSearchBackend::Bing => client
+            .get(BING_ENDPOINT)
+            .header("Ocp-Apim-Subscription-Key", config.bing_api_key.trim())
+            .query(&[
+                ("q", query),
+                ("count", count.as_str()),
+                ("textDecorations", "false"),
+            ]),
+    };
+    let response = request
+        .send()
+        .map_err(|err| ProviderError::network(label, err))?;
+    if !response.status().is_success() {
+        return Err(ProviderError::from_response(label, response).into());
+    }
+
+    let hits = match config.backend {
+        WebSearchBackend::SearXng => response
+            .json::<SearxResponse>()
+            .map_err(|err| ProviderError::invalid_payload(label, err))?
+            .results
+            .into_iter()
+            .map(|result| WebSearchHit {
+                title: result.title,
+                url: result.url,
+                snippet: result.content,
+            })
+            .collect(),
+        WebSearchBackend::Brave => response
+            .json::<BraveResponse>()
+            .map_err(|err| ProviderError::invalid_payload(label, err))?
+            .web
+            .map(|web| web.results)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|result| WebSearchHit {
+                title: html_to_text(&result.title),
+                url: result.url,
+                snippet: html_to_text(&result.description),
+            })
+            .collect(),
+        WebSearchBackend::Bing => response
+            .json::<BingResponse>()
+            .map_err(|err| ProviderError::invalid_payload(label, err))?
+            .web_pages
+            .map(|pages| pages.value)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|result| WebSearchHit {
+                title: result.name,
+                url: result.url,
+                snippet: result.snippet,
+            })
+            .collect(),
+    };
+    Ok(hits)
+}
+
+/// Descarga la página de un resultado y devuelve su texto visible.
+pub fn fetch_page_text(url: &str) -> Result<String> {
+    let client = network::client_builder(Duration::from_secs(20))?
+        .user_agent("JungleMonkAI/0.1")
+        .build()
+        .context("No se pudo crear el cliente HTTP")?;
+    let response = client
+        .get(url)
+        .send()
+        .with_context(|| format!("No se pudo abrir {}", url))?
+        .error_for_status()
+        .with_context(|| format!("{} devolvió un error", url))?;
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_none_or(|value| value.contains("html"));
+    let body = response
+        .bytes()
+        .with_context(|| format!("No se pudo leer {}", url))?;
+    let body = String::from_utf8_lossy(&body[..body.len().min(MAX_PAGE_BYTES)]);
+    Ok(if is_html {
+        html_to_text(&body)
+    } else {
+        body.trim().to_string()
+    })
+}
+
+/// Quita etiquetas, scripts y estilos, decodifica las entidades habituales y compacta los
+/// espacios, conservando un salto de línea por bloque.
+pub fn html_to_text(html: &str) -> String {
+    let text = HIDDEN_BLOCKS.replace_all(html, " ");
+    let text = BLOCK_TAGS.replace_all(&text, "\n");
+    let text = TAGS.replace_all(&text, "");
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&amp;", "&");
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,281 @@
+use std::process::ExitCode;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use multimodal_agent_rs::local_providers::{
+    LocalModelCard, LocalModelIdentifier, LocalModelProvider,
+};
+use multimodal_agent_rs::state::{
+    format_bytes, AppState, BatchTarget, ProviderCallDispatch, RemoteProviderKind,
+};
+
+const USAGE: &str = "Uso: junglemonk-cli <comando>
+
+Comandos:
+  chat [--provider anthropic|openai|groq|jarvis] \"prompt\"
+  models list
+  models install <id>
+  workflow run <nombre>
+  eval <prompts.jsonl|csv> [--provider <destino>]... [--output <resultados>]";
+
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(180);
+const INSTALL_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const EVAL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {:#}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<()> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["chat", rest @ ..] => chat(rest),
+        ["models", "list"] => list_models(),
+        ["models", "install", id] => install_model(id),
+        ["workflow", "run", name @ ..] if !name.is_empty() => run_workflow(&name.join(" ")),
+        ["eval", rest @ ..] => evaluate(rest),
+        ["help" | "--help" | "-h"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => bail!("{}", USAGE),
+    }
+}
+
+fn chat(args: &[&str]) -> Result<()> {
+    let mut provider = "jarvis";
+    let mut prompt = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "--provider" | "-p" => {
+                provider = iter
+                    .next()
+                    .ok_or_else(|| anyhow!("Falta el proveedor tras --provider"))?;
+            }
+            other => prompt.push(other),
+        }
+    }
+    let prompt = prompt.join(" ");
+    if prompt.trim().is_empty() {
+        bail!("Indica el prompt a enviar.");
+    }
+
+    let mut state = AppState::default();
+    let kind = match provider {
+        "jarvis" => {
+            let reply = state
+                .generate_local_jarvis_reply(&prompt)
+                .map_err(|err| anyhow!(err))?;
+            println!("{}", reply);
+            return Ok(());
+        }
+        "anthropic" | "claude" => RemoteProviderKind::Anthropic,
+        "openai" | "gpt" => RemoteProviderKind::OpenAi,
+        "groq" => RemoteProviderKind::Groq,
+        other => bail!("Proveedor desconocido: {}", other),
+    };
+
+    let ticket = match state.invoke_provider_kind(kind, prompt) {
+        ProviderCallDispatch::Pending(ticket) => ticket,
+        ProviderCallDispatch::MissingCredentials { provider_name, .. } => {
+            bail!("{} no tiene una API key configurada.", provider_name)
+        }
+        ProviderCallDispatch::Deferred {
+            provider_name,
+            limit,
+            used,
+            ..
+        } => bail!(
+            "Se alcanzó el límite de uso de {} ({}/{}).",
+            provider_name,
+            used,
+            limit
+        ),
+        ProviderCallDispatch::Queued { provider_name, .. }
+        | ProviderCallDispatch::Offline { provider_name, .. } => {
+            bail!("Sin conexión: no se pudo contactar con {}.", provider_name)
+        }
+        ProviderCallDispatch::BudgetExhausted {
+            provider_name,
+            period,
+            spent,
+            limit,
+            ..
+        } => bail!(
+            "Presupuesto {} de {} agotado (${:.2} de ${:.2}).",
+            period.label(),
+            provider_name,
+            spent,
+            limit
+        ),
+        ProviderCallDispatch::LocalOnly {
+            provider_name,
+            projects,
+            ..
+        } => bail!(
+            "No se envió a {}: el contexto incluye {}, marcado como solo local.",
+            provider_name,
+            projects.join(", ")
+        ),
+    };
+
+    let result = state
+        .wait_for_provider_calls(std::slice::from_ref(&ticket), PROVIDER_TIMEOUT)
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("{} no respondió a tiempo.", ticket.provider_name))?;
+    match result.outcome {
+        Ok(reply) => {
+            println!("{}", reply);
+            Ok(())
+        }
+        Err(err) => Err(anyhow!("{}: {}", ticket.provider_name, err.message())),
+    }
+}
+
+fn list_models() -> Result<()> {
+    let state = AppState::default();
+    let active = state.resources.jarvis_active_model.as_ref();
+    if state.resources.installed_local_models.is_empty() {
+        println!("No hay modelos locales instalados.");
+    }
+    for model in &state.resources.installed_local_models {
+        let marker = if active == Some(&model.identifier) {
+            "*"
+        } else {
+            " "
+        };
+        println!(
+            "{} {}\t{}\t{}",
+            marker,
+            model.identifier.serialize(),
+            format_bytes(model.size_bytes),
+            model.install_path
+        );
+    }
+    Ok(())
+}
+
+fn install_model(id: &str) -> Result<()> {
+    let mut state = AppState::default();
+    let provider = LocalModelProvider::HuggingFace;
+    let token = state.config.huggingface.access_token.clone();
+    if !state.queue_huggingface_install(LocalModelCard::placeholder(provider, id), token) {
+        bail!("'{}' ya se está instalando.", id);
+    }
+    eprintln!("Descargando '{}'…", id);
+
+    while state.has_pending_local_installs() {
+        std::thread::sleep(INSTALL_POLL_INTERVAL);
+        state.update_async_tasks();
+    }
+    state.persist_config();
+
+    let status = state
+        .provider_state(provider)
+        .install_status
+        .clone()
+        .unwrap_or_default();
+    if state
+        .installed_model(&LocalModelIdentifier::new(provider, id))
+        .is_some()
+    {
+        println!("{}", status);
+        Ok(())
+    } else {
+        Err(anyhow!(status))
+    }
+}
+
+fn run_workflow(name: &str) -> Result<()> {
+    let mut state = AppState::default();
+    let needle = name.trim().to_lowercase();
+    let workflow_id = state
+        .automation
+        .workflows
+        .workflows
+        .iter()
+        .find(|workflow| {
+            workflow.name.to_lowercase() == needle
+                || workflow
+                    .chat_command
+                    .as_deref()
+                    .is_some_and(|command| command.to_lowercase() == needle)
+        })
+        .map(|workflow| workflow.id)
+        .ok_or_else(|| anyhow!("No existe ningún workflow llamado '{}'.", name))?;
+
+    let message = state
+        .trigger_workflow(workflow_id)
+        .ok_or_else(|| anyhow!("No se pudo lanzar el workflow '{}'.", name))?;
+    println!("{}", message);
+    Ok(())
+}
+
+fn evaluate(args: &[&str]) -> Result<()> {
+    let mut state = AppState::default();
+    let mut prompts_path = None;
+    let mut targets = Vec::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match *arg {
+            "--provider" | "-p" => {
+                let code = iter
+                    .next()
+                    .ok_or_else(|| anyhow!("Falta el destino tras --provider"))?;
+                let target = BatchTarget::from_code(code)
+                    .ok_or_else(|| anyhow!("Destino desconocido: {}", code))?;
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+            "--output" | "-o" => {
+                state.batch_eval.output_path = iter
+                    .next()
+                    .ok_or_else(|| anyhow!("Falta la ruta tras --output"))?
+                    .to_string();
+            }
+            path => prompts_path = Some(path.to_string()),
+        }
+    }
+
+    state.batch_eval.prompts_path =
+        prompts_path.ok_or_else(|| anyhow!("Indica el fichero de prompts."))?;
+    if !targets.is_empty() {
+        state.batch_eval.targets = targets;
+    }
+
+    let expected = state.start_batch_evaluation().map_err(|err| anyhow!(err))?;
+    eprintln!("Evaluando {} combinaciones…", expected);
+    while state.batch_eval.is_running() {
+        if !state.update_async_tasks() {
+            std::thread::sleep(EVAL_POLL_INTERVAL);
+        }
+    }
+
+    println!("destino\tprompts\terrores\tlatencia_media_ms\tp95_ms\ttokens_entrada\ttokens_salida");
+    for summary in state.batch_eval.summary() {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            summary.target,
+            summary.total,
+            summary.failures,
+            summary.average_latency_ms,
+            summary.p95_latency_ms,
+            summary.prompt_tokens,
+            summary.response_tokens
+        );
+    }
+    if let Some(status) = &state.batch_eval.status {
+        eprintln!("{}", status);
+    }
+    Ok(())
+}
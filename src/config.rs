@@ -1,14 +1,43 @@
+use crate::api::local::JarvisKnowledgeEntry;
 use anyhow::Context;
 use chrono::{DateTime, Utc};
 use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
 
+/// Copias de `config.json` que se conservan, de la más reciente (`.1`) a la más antigua.
+const CONFIG_BACKUPS: usize = 3;
+
+/// Secciones de los proveedores cuya API key se guarda en el llavero del sistema.
+const KEYCHAIN_PROVIDERS: [&str; 3] = ["anthropic", "openai", "groq"];
+
+/// Entrada del llavero con la API key del proveedor `name`.
+pub fn provider_key_account(name: &str) -> String {
+    format!("provider:{}", name)
+}
+
+/// Qué se hizo al arrancar porque `config.json` no se podía interpretar.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigRecovery {
+    /// Se cargó la copia `backup`; el archivo dañado se conserva en `damaged`.
+    Restored { backup: PathBuf, damaged: PathBuf },
+    /// Ninguna copia era válida y se arrancó con la configuración por defecto.
+    Defaults { damaged: PathBuf },
+}
+
 /// Datos de configuración específicos de un proveedor de modelos.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderConfig {
     /// API key opcional (algunos proveedores permiten tokens vacíos para cuentas gratuitas).
+    /// Solo vive en memoria: `config.json` guarda `api_key_account`. Se sigue leyendo del
+    /// fichero para migrar las que guardaban las versiones anteriores.
+    #[serde(default, skip_serializing)]
     pub api_key: Option<String>,
+    /// Entrada del llavero del sistema que guarda la API key.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_account: Option<String>,
     /// Modelo por defecto con el que se realizarán las peticiones.
     pub default_model: String,
     /// Alias que el usuario utilizará dentro del chat para invocar al proveedor.
@@ -16,15 +45,61 @@ pub struct ProviderConfig {
     /// Límite máximo de invocaciones por día que Jarvis puede realizar automáticamente.
     #[serde(default)]
     pub daily_limit: Option<u32>,
+    #[serde(default)]
+    pub budget: ProviderBudget,
+    /// Gasto acumulado, persistido para que los presupuestos sobrevivan a los reinicios.
+    #[serde(default)]
+    pub spend: ProviderSpend,
+}
+
+/// Presupuesto de gasto de un proveedor remoto, en dólares.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProviderBudget {
+    pub daily_usd: Option<f32>,
+    pub monthly_usd: Option<f32>,
+    /// Porcentajes del presupuesto en los que se avisa, de menor a mayor.
+    pub alert_percents: Vec<u8>,
+    /// Rechaza las llamadas mientras el presupuesto esté agotado.
+    pub hard_block: bool,
+}
+
+impl Default for ProviderBudget {
+    fn default() -> Self {
+        Self {
+            daily_usd: None,
+            monthly_usd: None,
+            alert_percents: vec![50, 80, 100],
+            hard_block: false,
+        }
+    }
+}
+
+/// Gasto estimado del día y del mes en curso según los precios del catálogo.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProviderSpend {
+    /// Día (`%Y-%m-%d`) al que corresponde `daily_usd`.
+    pub day: String,
+    pub daily_usd: f32,
+    /// Mes (`%Y-%m`) al que corresponde `monthly_usd`.
+    pub month: String,
+    pub monthly_usd: f32,
+    /// Mayor porcentaje ya avisado en el periodo, para no repetir alertas.
+    pub daily_alerted: u8,
+    pub monthly_alerted: u8,
 }
 
 impl Default for ProviderConfig {
     fn default() -> Self {
         Self {
             api_key: None,
+            api_key_account: None,
             default_model: String::new(),
             alias: String::new(),
             daily_limit: None,
+            budget: ProviderBudget::default(),
+            spend: ProviderSpend::default(),
         }
     }
 }
@@ -41,6 +116,27 @@ pub struct InstalledModelConfig {
         default = "default_installed_timestamp"
     )]
     pub installed_at: DateTime<Utc>,
+    /// Revisión del repositorio de origen descargada.
+    #[serde(default)]
+    pub revision: Option<String>,
+    /// Modelo del que se generó esta variante cuantizada, codificado como
+    /// `proveedor::identificador`.
+    #[serde(default)]
+    pub quantized_from: Option<String>,
+}
+
+/// Modelo desinstalado cuyos archivos esperan en la papelera hasta que caduca el plazo.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedModelConfig {
+    pub identifier: String,
+    pub original_path: String,
+    pub trash_path: String,
+    #[serde(default)]
+    pub size_bytes: u64,
+    #[serde(default)]
+    pub revision: Option<String>,
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub trashed_at: DateTime<Utc>,
 }
 
 fn default_installed_timestamp() -> DateTime<Utc> {
@@ -67,6 +163,8 @@ where
                                 install_path: String::new(),
                                 size_bytes: 0,
                                 installed_at: Utc::now(),
+                                revision: None,
+                                quantized_from: None,
                             });
                         } else {
                             return Err(D::Error::custom("Formato inválido en installed_models"));
@@ -97,6 +195,28 @@ pub struct JarvisConfig {
     pub active_model: Option<String>,
     #[serde(default = "JarvisConfig::default_alias")]
     pub chat_alias: String,
+    /// Entradas de conocimiento que enrutan los prompts hacia respuestas curadas.
+    #[serde(default = "JarvisKnowledgeEntry::defaults")]
+    pub knowledge_base: Vec<JarvisKnowledgeEntry>,
+    /// Archivos del modelo que se descargan a la vez desde Hugging Face.
+    #[serde(default = "JarvisConfig::default_download_parallelism")]
+    pub download_parallelism: usize,
+    /// Días que los modelos desinstalados permanecen en la papelera; 0 los borra al momento.
+    #[serde(default = "JarvisConfig::default_trash_retention_days")]
+    pub trash_retention_days: u32,
+    #[serde(default)]
+    pub trashed_models: Vec<TrashedModelConfig>,
+    /// Alias del chat que responden con un modelo instalado distinto del activo.
+    #[serde(default)]
+    pub model_aliases: Vec<JarvisModelAlias>,
+}
+
+/// Alias como `@coder` que envía el prompt a un modelo local concreto.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct JarvisModelAlias {
+    pub alias: String,
+    /// Modelo instalado codificado como `proveedor::identificador`.
+    pub model: String,
 }
 
 impl Default for JarvisConfig {
@@ -108,6 +228,11 @@ impl Default for JarvisConfig {
             installed_models: Vec::new(),
             active_model: None,
             chat_alias: Self::default_alias(),
+            knowledge_base: JarvisKnowledgeEntry::defaults(),
+            download_parallelism: Self::default_download_parallelism(),
+            trash_retention_days: Self::default_trash_retention_days(),
+            trashed_models: Vec::new(),
+            model_aliases: Vec::new(),
         }
     }
 }
@@ -116,6 +241,1338 @@ impl JarvisConfig {
     fn default_alias() -> String {
         "jarvis".to_string()
     }
+
+    fn default_download_parallelism() -> usize {
+        4
+    }
+
+    fn default_trash_retention_days() -> u32 {
+        7
+    }
+}
+
+/// Estrategias disponibles para adjuntar el historial de la conversación a cada prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextStrategy {
+    Disabled,
+    #[default]
+    LastMessages,
+    TokenBudget,
+    SummarizeThenInclude,
+}
+
+impl ContextStrategy {
+    pub const ALL: [ContextStrategy; 4] = [
+        ContextStrategy::Disabled,
+        ContextStrategy::LastMessages,
+        ContextStrategy::TokenBudget,
+        ContextStrategy::SummarizeThenInclude,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ContextStrategy::Disabled => "Sin historial",
+            ContextStrategy::LastMessages => "Últimos N mensajes",
+            ContextStrategy::TokenBudget => "Presupuesto de tokens",
+            ContextStrategy::SummarizeThenInclude => "Resumir y luego incluir",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            ContextStrategy::Disabled => "Solo se envía el mensaje actual, sin contexto previo.",
+            ContextStrategy::LastMessages => {
+                "Adjunta los mensajes más recientes hasta el máximo configurado."
+            }
+            ContextStrategy::TokenBudget => {
+                "Conserva el inicio y el final de la conversación y recorta la parte central para respetar el presupuesto."
+            }
+            ContextStrategy::SummarizeThenInclude => {
+                "Adjunta un resumen de los mensajes antiguos, hecho por el modelo y guardado por mensaje, y los recientes completos."
+            }
+        }
+    }
+}
+
+/// Ajustes de la ventana de contexto enviada a los proveedores remotos.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    pub strategy: ContextStrategy,
+    /// Número máximo de mensajes adjuntos con la estrategia de últimos mensajes.
+    pub max_messages: usize,
+    /// Tokens disponibles para el historial con la estrategia de presupuesto.
+    pub token_budget: usize,
+    /// Mensajes recientes que se adjuntan completos; el resto va resumido.
+    pub summary_recent_messages: usize,
+    /// Código del proveedor remoto que resume los mensajes antiguos; `None` usa el de la
+    /// conversación.
+    pub summary_provider: Option<String>,
+    /// Modelo del proveedor que resume; vacío usa el predeterminado.
+    pub summary_model: String,
+    /// Coste estimado (USD) a partir del cual se pide confirmación antes de enviar.
+    /// Con 0 no se pide nunca.
+    pub confirm_cost_above: f32,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            strategy: ContextStrategy::default(),
+            max_messages: 8,
+            token_budget: 2048,
+            summary_recent_messages: 4,
+            summary_provider: None,
+            summary_model: String::new(),
+            confirm_cost_above: 0.05,
+        }
+    }
+}
+
+/// Ajustes de red aplicados a todas las peticiones salientes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// URL del proxy HTTP o HTTPS. Vacío para conexión directa.
+    pub proxy_url: String,
+    /// Hosts que no deben atravesar el proxy, separados por comas.
+    pub no_proxy: String,
+    /// Ruta a un bundle PEM con certificados raíz adicionales.
+    pub ca_bundle_path: String,
+    /// Tiempo máximo por petición en segundos; 0 conserva el valor de cada cliente.
+    pub request_timeout_secs: u64,
+    /// Fuerza el modo sin conexión aunque la red esté disponible.
+    pub offline_mode: bool,
+    /// Sin conexión, retiene las llamadas remotas para reenviarlas en lugar de rechazarlas.
+    pub queue_offline_requests: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            proxy_url: String::new(),
+            no_proxy: String::new(),
+            ca_bundle_path: String::new(),
+            request_timeout_secs: 0,
+            offline_mode: false,
+            queue_offline_requests: true,
+        }
+    }
+}
+
+/// Servicio que atiende las búsquedas web del chat y de los proveedores.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebSearchBackend {
+    /// Instancia de SearXNG con la salida JSON habilitada.
+    #[default]
+    SearXng,
+    /// Brave Search API.
+    Brave,
+    /// Bing Web Search API.
+    Bing,
+}
+
+impl WebSearchBackend {
+    pub const ALL: [WebSearchBackend; 3] = [
+        WebSearchBackend::SearXng,
+        WebSearchBackend::Brave,
+        WebSearchBackend::Bing,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WebSearchBackend::SearXng => "SearXNG",
+            WebSearchBackend::Brave => "Brave Search",
+            WebSearchBackend::Bing => "Bing Web Search",
+        }
+    }
+}
+
+/// Ajustes de la búsqueda web.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct WebSearchConfig {
+    pub backend: WebSearchBackend,
+    /// URL base de la instancia de SearXNG.
+    pub searxng_url: String,
+    pub brave_api_key: String,
+    pub bing_api_key: String,
+    pub max_results: usize,
+    /// Ofrece la búsqueda a los proveedores remotos como herramienta.
+    pub expose_to_providers: bool,
+}
+
+impl Default for WebSearchConfig {
+    fn default() -> Self {
+        Self {
+            backend: WebSearchBackend::default(),
+            searxng_url: String::new(),
+            brave_api_key: String::new(),
+            bing_api_key: String::new(),
+            max_results: 5,
+            expose_to_providers: true,
+        }
+    }
+}
+
+/// Cifrado de la conexión con el servidor SMTP.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Conexión en claro que se cifra con STARTTLS (puerto 587).
+    #[default]
+    StartTls,
+    /// TLS desde el principio (puerto 465).
+    Tls,
+    /// Sin cifrar; solo para servidores locales.
+    None,
+}
+
+impl SmtpSecurity {
+    pub const ALL: [SmtpSecurity; 3] = [
+        SmtpSecurity::StartTls,
+        SmtpSecurity::Tls,
+        SmtpSecurity::None,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SmtpSecurity::StartTls => "STARTTLS",
+            SmtpSecurity::Tls => "TLS",
+            SmtpSecurity::None => "Sin cifrar",
+        }
+    }
+
+    pub fn default_port(self) -> u16 {
+        match self {
+            SmtpSecurity::StartTls => 587,
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::None => 25,
+        }
+    }
+}
+
+/// Servidor SMTP y plantillas con las que se envían informes, resúmenes y
+/// conversaciones por correo.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: String,
+    pub password: String,
+    pub from_address: String,
+    /// Destinatarios propuestos al preparar un correo, separados por comas.
+    pub default_recipients: String,
+    /// Plantillas con `{titulo}`, `{fecha}` y `{contenido}`.
+    pub subject_template: String,
+    pub body_template: String,
+    /// Ejecutable de curl, que hace el envío; se busca en el `PATH` si no es una ruta.
+    pub curl_command: String,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: SmtpSecurity::default().default_port(),
+            security: SmtpSecurity::default(),
+            username: String::new(),
+            password: String::new(),
+            from_address: String::new(),
+            default_recipients: String::new(),
+            subject_template: "{titulo}".to_string(),
+            body_template: "Hola:\n\n{contenido}\n\n—\nEnviado desde JungleMonkAI el {fecha}."
+                .to_string(),
+            curl_command: "curl".to_string(),
+        }
+    }
+}
+
+/// Servicio que recibe los mensajes de un conector de webhook saliente.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    #[default]
+    Slack,
+    Discord,
+}
+
+impl WebhookKind {
+    pub const ALL: [WebhookKind; 2] = [WebhookKind::Slack, WebhookKind::Discord];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            WebhookKind::Slack => "Slack",
+            WebhookKind::Discord => "Discord",
+        }
+    }
+}
+
+/// Conector que publica en un canal de Slack o Discord. La URL del webhook es un
+/// secreto y se guarda en el llavero del sistema, no en la configuración.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct WebhookConnectorConfig {
+    /// Nombre único; los pasos de sincronización que lo mencionan publican en él.
+    pub name: String,
+    pub kind: WebhookKind,
+    pub enabled: bool,
+    /// Reenvía los avisos que la política de notificaciones no deja solo en el registro.
+    pub forward_notifications: bool,
+    /// Publica el resumen diario cuando se genera.
+    pub post_digest: bool,
+}
+
+impl Default for WebhookConnectorConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            kind: WebhookKind::default(),
+            enabled: true,
+            forward_notifications: false,
+            post_digest: false,
+        }
+    }
+}
+
+/// Puente con un bot de Telegram para conversar con el agente desde el móvil.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TelegramBridgeConfig {
+    pub enabled: bool,
+    /// Token que entrega @BotFather al crear el bot.
+    pub bot_token: String,
+    /// Chats que pueden hablar con el agente; los demás se ignoran.
+    pub allowed_chat_ids: Vec<i64>,
+    /// Título del hilo de JungleMonkAI donde se vuelcan los mensajes del bot.
+    pub thread_title: String,
+}
+
+impl Default for TelegramBridgeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bot_token: String::new(),
+            allowed_chat_ids: Vec::new(),
+            thread_title: "Telegram".to_string(),
+        }
+    }
+}
+
+/// Broker MQTT con el que el agente participa en la domótica: escucha temas como
+/// disparadores de listeners y publica el resultado de los workflows.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub client_id: String,
+    /// Filtros de tema a los que se suscribe, con los comodines `+` y `#`.
+    pub subscriptions: Vec<String>,
+    /// Tema donde se publica un resumen de cada workflow lanzado; vacío para no publicar.
+    pub results_topic: String,
+    pub keep_alive_secs: u16,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 1883,
+            username: String::new(),
+            password: String::new(),
+            client_id: "junglemonkai".to_string(),
+            subscriptions: Vec::new(),
+            results_topic: "junglemonkai/workflows".to_string(),
+            keep_alive_secs: 60,
+        }
+    }
+}
+
+/// Límites de un carril de la cola de llamadas salientes: un proveedor remoto o una
+/// integración como los webhooks.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct JobLaneLimit {
+    pub lane: String,
+    /// Llamadas simultáneas permitidas en el carril.
+    pub max_concurrent: u32,
+    /// Llamadas que pueden empezar por minuto; 0 no limita.
+    pub per_minute: u32,
+}
+
+/// Cola de llamadas salientes con límites por carril.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct JobQueueConfig {
+    pub lanes: Vec<JobLaneLimit>,
+    /// Llamadas simultáneas de los carriles sin límites propios.
+    pub default_max_concurrent: u32,
+}
+
+impl Default for JobQueueConfig {
+    fn default() -> Self {
+        let lane = |lane: &str, max_concurrent, per_minute| JobLaneLimit {
+            lane: lane.to_string(),
+            max_concurrent,
+            per_minute,
+        };
+        Self {
+            lanes: vec![
+                lane("anthropic", 2, 50),
+                lane("openai", 4, 60),
+                lane("groq", 4, 30),
+                lane("webhooks", 2, 30),
+            ],
+            default_max_concurrent: 4,
+        }
+    }
+}
+
+/// Tamaño y visibilidad de los paneles del shell.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LayoutSnapshot {
+    pub show_navigation: bool,
+    pub show_resource_panel: bool,
+    pub navigation_collapsed: bool,
+    pub resource_collapsed: bool,
+    pub navigation_width: f32,
+    pub resource_width: f32,
+    /// Altura del compositor del chat; 0 deja que se ajuste a su contenido.
+    pub composer_height: f32,
+}
+
+impl Default for LayoutSnapshot {
+    fn default() -> Self {
+        Self {
+            show_navigation: true,
+            show_resource_panel: true,
+            navigation_collapsed: false,
+            resource_collapsed: false,
+            navigation_width: 280.0,
+            resource_width: 320.0,
+            composer_height: 0.0,
+        }
+    }
+}
+
+/// Distribución de paneles guardada con un nombre.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LayoutPreset {
+    pub name: String,
+    pub layout: LayoutSnapshot,
+}
+
+/// Distribución restaurada al arrancar y presets disponibles.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutPreferences {
+    pub current: LayoutSnapshot,
+    pub active_view: crate::state::MainView,
+    pub presets: Vec<LayoutPreset>,
+    /// Vistas abiertas en ventanas propias, que se vuelven a abrir al arrancar.
+    pub popouts: Vec<PopoutWindow>,
+}
+
+/// Vista sacada de la ventana principal y geometría con la que se restaura.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PopoutWindow {
+    pub view: crate::state::MainView,
+    /// Esquina superior izquierda en puntos; `None` deja que el sistema la coloque.
+    pub position: Option<[f32; 2]>,
+    pub size: [f32; 2],
+}
+
+impl Default for LayoutPreferences {
+    fn default() -> Self {
+        Self {
+            current: LayoutSnapshot::default(),
+            active_view: crate::state::MainView::default(),
+            presets: default_layout_presets(),
+            popouts: Vec::new(),
+        }
+    }
+}
+
+fn default_layout_presets() -> Vec<LayoutPreset> {
+    vec![
+        LayoutPreset {
+            name: "Predeterminada".to_string(),
+            layout: LayoutSnapshot::default(),
+        },
+        LayoutPreset {
+            name: "Enfoque en chat".to_string(),
+            layout: LayoutSnapshot {
+                navigation_collapsed: true,
+                resource_collapsed: true,
+                ..LayoutSnapshot::default()
+            },
+        },
+        LayoutPreset {
+            name: "Exploración".to_string(),
+            layout: LayoutSnapshot {
+                navigation_width: 340.0,
+                resource_width: 440.0,
+                ..LayoutSnapshot::default()
+            },
+        },
+    ]
+}
+
+/// Opciones de accesibilidad de la interfaz.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    /// Factor de escala aplicado a toda la interfaz.
+    pub ui_scale: f32,
+    /// Desactiva animaciones y transiciones.
+    pub reduced_motion: bool,
+    /// Permite recorrer la navegación y el historial del chat con el teclado.
+    pub keyboard_navigation: bool,
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.0,
+            reduced_motion: false,
+            keyboard_navigation: true,
+        }
+    }
+}
+
+/// Atajos de teclado personalizados.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct KeybindingsConfig {
+    /// Combinación elegida para cada acción, por su identificador. Las acciones que no
+    /// aparecen usan la combinación predeterminada.
+    pub overrides: BTreeMap<String, String>,
+    /// Intro envía el mensaje; si se desactiva, solo lo envía el atajo de envío.
+    pub enter_sends: bool,
+}
+
+impl Default for KeybindingsConfig {
+    fn default() -> Self {
+        Self {
+            overrides: BTreeMap::new(),
+            enter_sends: true,
+        }
+    }
+}
+
+/// Servidor WebSocket que difunde los eventos de la aplicación a herramientas externas.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct EventStreamConfig {
+    pub enabled: bool,
+    /// Dirección `host:puerto` en la que escucha el servidor.
+    pub bind_address: String,
+    /// Token que deben presentar los observadores; vacío acepta cualquiera del equipo.
+    pub token: String,
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: "127.0.0.1:8765".to_string(),
+            token: String::new(),
+        }
+    }
+}
+
+/// Ruta vigilada y patrón glob de los ficheros que interesan dentro de ella.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct WatchedPath {
+    pub path: String,
+    /// Glob relativo a `path`, por ejemplo `**/*.md`; vacío acepta cualquier fichero.
+    pub pattern: String,
+}
+
+/// Vigilante de ficheros cuyos cambios llegan a los listeners de automatización.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct FileWatcherConfig {
+    pub enabled: bool,
+    /// Tiempo sin eventos que se espera antes de notificar el cambio de un fichero.
+    pub debounce_millis: u64,
+    pub paths: Vec<WatchedPath>,
+}
+
+impl Default for FileWatcherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            debounce_millis: 500,
+            paths: Vec::new(),
+        }
+    }
+}
+
+/// Origen de los vectores de embeddings usados por la búsqueda semántica y la memoria.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbeddingBackend {
+    /// Modelo Candle cargado por Jarvis.
+    #[default]
+    Local,
+    /// Endpoint `/v1/embeddings` de OpenAI.
+    OpenAi,
+}
+
+impl EmbeddingBackend {
+    pub const ALL: [EmbeddingBackend; 2] = [EmbeddingBackend::Local, EmbeddingBackend::OpenAi];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            EmbeddingBackend::Local => "Modelo local (Jarvis)",
+            EmbeddingBackend::OpenAi => "OpenAI embeddings",
+        }
+    }
+}
+
+/// Modelo de embeddings y subsistemas que lo aprovechan.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct EmbeddingConfig {
+    pub backend: EmbeddingBackend,
+    pub openai_model: String,
+    /// Añade coincidencias por significado a la búsqueda global.
+    pub semantic_search: bool,
+    /// Mensajes antiguos más parecidos al prompt que se recuerdan al consultar a un
+    /// proveedor; 0 desactiva la memoria semántica.
+    pub recall_messages: usize,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            backend: EmbeddingBackend::default(),
+            openai_model: "text-embedding-3-small".to_string(),
+            semantic_search: true,
+            recall_messages: 0,
+        }
+    }
+}
+
+/// Servicio que convierte notas de voz y audios adjuntos en texto.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TranscriptionBackend {
+    /// CLI de Whisper instalada en el equipo.
+    #[default]
+    LocalWhisper,
+    /// Endpoint `/v1/audio/transcriptions` de OpenAI.
+    OpenAi,
+}
+
+impl TranscriptionBackend {
+    pub const ALL: [TranscriptionBackend; 2] = [
+        TranscriptionBackend::LocalWhisper,
+        TranscriptionBackend::OpenAi,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TranscriptionBackend::LocalWhisper => "Whisper local",
+            TranscriptionBackend::OpenAi => "OpenAI transcriptions",
+        }
+    }
+}
+
+/// Ajustes de la entrada de voz.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct VoiceInputConfig {
+    pub backend: TranscriptionBackend,
+    pub openai_model: String,
+    /// Ejecutable de Whisper; se busca en el `PATH` si no es una ruta.
+    pub whisper_command: String,
+    pub whisper_model: String,
+    /// Código ISO-639-1 del idioma hablado. Vacío para detectarlo automáticamente.
+    pub language: String,
+}
+
+impl Default for VoiceInputConfig {
+    fn default() -> Self {
+        Self {
+            backend: TranscriptionBackend::default(),
+            openai_model: "whisper-1".to_string(),
+            whisper_command: "whisper".to_string(),
+            whisper_model: "small".to_string(),
+            language: String::new(),
+        }
+    }
+}
+
+/// Reconocimiento de texto (OCR) en imágenes con Tesseract.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct OcrConfig {
+    pub enabled: bool,
+    /// Ejecutable de Tesseract; se busca en el `PATH` si no es una ruta.
+    pub tesseract_command: String,
+    /// Paquetes de idioma de Tesseract (`spa`, `eng`…) que se combinan al reconocer.
+    pub languages: Vec<String>,
+    /// Incorpora también las imágenes de los proyectos a la base de conocimiento.
+    pub index_project_images: bool,
+}
+
+impl Default for OcrConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tesseract_command: "tesseract".to_string(),
+            languages: vec!["spa".to_string(), "eng".to_string()],
+            index_project_images: false,
+        }
+    }
+}
+
+/// Patrón que se sustituye en los prompts antes de enviarlos a un proveedor remoto.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct RedactionRule {
+    pub name: String,
+    /// Expresión regular con la sintaxis del crate `regex`.
+    pub pattern: String,
+    pub replacement: String,
+    pub enabled: bool,
+}
+
+impl RedactionRule {
+    fn new(name: &str, pattern: &str, replacement: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            enabled: true,
+        }
+    }
+}
+
+/// Registro local de métricas de uso. Desactivado hasta que se activa a mano.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct UsageMetricsConfig {
+    pub enabled: bool,
+    pub retention_days: u32,
+}
+
+impl Default for UsageMetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_days: 90,
+        }
+    }
+}
+
+/// Por dónde se entrega un aviso, de menos a más intrusivo. Cada canal incluye los
+/// anteriores: todo aviso queda siempre en el registro de actividad.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NotificationChannel {
+    LogOnly,
+    Toast,
+    Desktop,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum NotificationSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Canal de un origen de avisos y gravedad mínima para usarlo; lo que no llega al
+/// umbral se queda en el registro.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NotificationRoute {
+    pub channel: NotificationChannel,
+    pub min_severity: NotificationSeverity,
+}
+
+/// Política de avisos: horas de silencio y ruta de cada origen.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct NotificationPolicyConfig {
+    pub quiet_hours_enabled: bool,
+    /// Inicio y fin de las horas de silencio en formato `HH:MM`; pueden cruzar la
+    /// medianoche.
+    pub quiet_start: String,
+    pub quiet_end: String,
+    /// Deja que los errores se muestren en pantalla durante las horas de silencio.
+    pub errors_break_quiet_hours: bool,
+    pub scheduler: NotificationRoute,
+    pub providers: NotificationRoute,
+    pub integrations: NotificationRoute,
+}
+
+impl Default for NotificationPolicyConfig {
+    fn default() -> Self {
+        Self {
+            quiet_hours_enabled: false,
+            quiet_start: "22:00".to_string(),
+            quiet_end: "08:00".to_string(),
+            errors_break_quiet_hours: true,
+            scheduler: NotificationRoute {
+                channel: NotificationChannel::Toast,
+                min_severity: NotificationSeverity::Warning,
+            },
+            providers: NotificationRoute {
+                channel: NotificationChannel::Toast,
+                min_severity: NotificationSeverity::Error,
+            },
+            integrations: NotificationRoute {
+                channel: NotificationChannel::Toast,
+                min_severity: NotificationSeverity::Error,
+            },
+        }
+    }
+}
+
+/// Qué se hace con un texto de Jarvis que coincide con un filtro de seguridad.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SafetyAction {
+    /// Se entrega igual, pero queda registrado y se avisa en pantalla.
+    #[default]
+    Warn,
+    /// Se sustituye el fragmento que coincide.
+    Redact,
+    /// Se descarta el texto entero.
+    Block,
+}
+
+/// Regla de los filtros de seguridad de Jarvis.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct SafetyFilterRule {
+    pub name: String,
+    /// Expresión regular o, si `regex` es `false`, palabras separadas por comas que se
+    /// buscan completas y sin distinguir mayúsculas.
+    pub pattern: String,
+    pub regex: bool,
+    pub action: SafetyAction,
+    pub enabled: bool,
+}
+
+impl SafetyFilterRule {
+    fn keywords(name: &str, keywords: &str, action: SafetyAction) -> Self {
+        Self {
+            name: name.to_string(),
+            pattern: keywords.to_string(),
+            regex: false,
+            action,
+            enabled: true,
+        }
+    }
+}
+
+/// Clasificador opcional: compara el embedding del texto con el de cada tema vetado.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SafetyClassifierConfig {
+    pub enabled: bool,
+    /// Descripciones breves de los temas que no deben aparecer en una demo.
+    pub topics: Vec<String>,
+    /// Similitud a partir de la que un texto se considera del tema.
+    pub threshold: f32,
+    /// Sin fragmento concreto que sustituir, `Redact` se trata como `Block`.
+    pub action: SafetyAction,
+}
+
+impl Default for SafetyClassifierConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            topics: vec![
+                "Insultos, lenguaje ofensivo o contenido sexual".to_string(),
+                "Instrucciones para hacer daño a personas o sistemas".to_string(),
+            ],
+            threshold: 0.6,
+            action: SafetyAction::Block,
+        }
+    }
+}
+
+/// Filtros que se aplican a los prompts y a las respuestas de Jarvis y de los modelos
+/// locales. Cada conversación puede activarlos o desactivarlos por su cuenta.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SafetyFilterConfig {
+    pub enabled: bool,
+    pub rules: Vec<SafetyFilterRule>,
+    pub classifier: SafetyClassifierConfig,
+    /// Texto que sustituye a los fragmentos con la acción `Redact`.
+    pub replacement: String,
+}
+
+impl Default for SafetyFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            rules: vec![
+                SafetyFilterRule::keywords(
+                    "Lenguaje malsonante",
+                    "mierda, joder, gilipollas, fuck, shit",
+                    SafetyAction::Redact,
+                ),
+                SafetyFilterRule {
+                    name: "Credenciales".to_string(),
+                    pattern: r"(?i)\b(?:password|contraseña|token)\s*[:=]\s*\S+".to_string(),
+                    regex: true,
+                    action: SafetyAction::Redact,
+                    enabled: true,
+                },
+            ],
+            classifier: SafetyClassifierConfig::default(),
+            replacement: "[FILTRADO]".to_string(),
+        }
+    }
+}
+
+/// Contexto que el chat aplica mientras su proyecto está seleccionado. Los ajustes de
+/// cada conversación tienen prioridad sobre los del perfil.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ProjectContextProfile {
+    /// Instrucciones de sistema para las conversaciones sin persona propia.
+    pub persona: String,
+    /// Código corto del proveedor de los mensajes sin mención; `None` deja a Jarvis.
+    pub provider: Option<String>,
+    /// Proyectos conectados cuyos README y documentos se recuperan como contexto.
+    pub knowledge_sources: Vec<String>,
+    /// Reglas de redacción que se aplican, por nombre; `None` usa las de preferencias.
+    pub redaction_rules: Option<Vec<String>>,
+}
+
+/// Filtros de redacción de los prompts salientes. Jarvis y los modelos locales no los usan
+/// porque el texto no sale del equipo.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    pub rules: Vec<RedactionRule>,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: vec![
+                RedactionRule::new(
+                    "API keys",
+                    r"\b(?:sk-(?:ant-|proj-)?[A-Za-z0-9_-]{20,}|gh[pousr]_[A-Za-z0-9]{36,}|gsk_[A-Za-z0-9]{20,}|hf_[A-Za-z0-9]{30,}|AKIA[0-9A-Z]{16})\b",
+                    "[API_KEY]",
+                ),
+                RedactionRule::new(
+                    "Correos electrónicos",
+                    r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b",
+                    "[EMAIL]",
+                ),
+                RedactionRule::new(
+                    "Hosts internos",
+                    r"\b(?:[A-Za-z0-9-]+\.)+(?:internal|intranet|corp|lan|local)\b",
+                    "[HOST]",
+                ),
+            ],
+        }
+    }
+}
+
+/// Límites de la caché de respuestas de los proveedores remotos, que se guarda en
+/// `responses/` dentro del directorio de caché.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ResponseCacheConfig {
+    pub enabled: bool,
+    pub ttl_hours: u32,
+    pub max_entries: usize,
+    pub max_size_mb: u32,
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ttl_hours: 24,
+            max_entries: 500,
+            max_size_mb: 64,
+        }
+    }
+}
+
+/// Acción rápida definida por el usuario para un modelo remoto concreto.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ModelQuickAction {
+    /// Código corto del proveedor (`anthropic`, `openai` o `groq`).
+    pub provider: String,
+    pub model_id: String,
+    pub label: String,
+    /// Texto con el que se rellena el editor del chat al lanzar la acción.
+    pub template: String,
+}
+
+/// Reindexado programado de los documentos de un proyecto en la base de conocimiento.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct KnowledgeReindexSchedule {
+    /// Nombre del proyecto tal como aparece en los recursos conectados.
+    pub project: String,
+    pub cron_expression: String,
+    pub enabled: bool,
+    pub last_run: Option<String>,
+}
+
+/// Resumen diario que redacta un proveedor con la actividad de la jornada.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct DailyDigestConfig {
+    pub enabled: bool,
+    /// Hora local de envío en formato `HH:MM`.
+    pub time: String,
+    /// Código corto del proveedor que redacta el resumen; `None` se lo encarga a Jarvis.
+    pub provider: Option<String>,
+    pub include_activity: bool,
+    pub include_workflows: bool,
+    pub include_github: bool,
+    pub include_calendar: bool,
+    /// Calendario `.ics` del que se leen los eventos del día.
+    pub calendar_file: String,
+    pub desktop_notification: bool,
+    pub last_run: Option<String>,
+}
+
+impl Default for DailyDigestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            time: "08:00".to_string(),
+            provider: None,
+            include_activity: true,
+            include_workflows: true,
+            include_github: true,
+            include_calendar: true,
+            calendar_file: String::new(),
+            desktop_notification: true,
+            last_run: None,
+        }
+    }
+}
+
+/// Transformación rápida del menú contextual de los mensajes del chat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct TextActionTemplate {
+    pub label: String,
+    /// Prompt con los marcadores `{texto}` e `{idioma}`.
+    pub prompt: String,
+}
+
+impl TextActionTemplate {
+    fn new(label: &str, prompt: &str) -> Self {
+        Self {
+            label: label.to_string(),
+            prompt: prompt.to_string(),
+        }
+    }
+
+    /// Prompt con el texto del mensaje y el idioma de destino sustituidos. Si la plantilla
+    /// no usa `{texto}`, el texto se añade al final.
+    pub fn render(&self, text: &str, language: &str) -> String {
+        let prompt = self.prompt.replace("{idioma}", language);
+        if prompt.contains("{texto}") {
+            prompt.replace("{texto}", text)
+        } else {
+            format!("{}\n\n{}", prompt.trim_end(), text)
+        }
+    }
+}
+
+/// Acciones del menú contextual de las burbujas del chat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TextActionsConfig {
+    /// Idioma al que traducen las plantillas con `{idioma}`.
+    pub target_language: String,
+    pub actions: Vec<TextActionTemplate>,
+}
+
+impl Default for TextActionsConfig {
+    fn default() -> Self {
+        Self {
+            target_language: "inglés".to_string(),
+            actions: vec![
+                TextActionTemplate::new(
+                    "Explicar",
+                    "Explica con palabras sencillas el siguiente texto:\n\n{texto}",
+                ),
+                TextActionTemplate::new(
+                    "Traducir",
+                    "Traduce al {idioma} el siguiente texto. Responde solo con la traducción:\n\n{texto}",
+                ),
+                TextActionTemplate::new(
+                    "Reescribir en tono formal",
+                    "Reescribe el siguiente texto en un registro formal, sin cambiar su significado:\n\n{texto}",
+                ),
+                TextActionTemplate::new(
+                    "Extraer tareas",
+                    "Extrae del siguiente texto las tareas pendientes como una lista de casillas Markdown, con responsable y fecha si aparecen:\n\n{texto}",
+                ),
+            ],
+        }
+    }
+}
+
+/// Vigilancia del portapapeles para ofrecer acciones sobre lo que se copia.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ClipboardWatcherConfig {
+    pub enabled: bool,
+    pub poll_interval_secs: u64,
+    pub detect_stack_traces: bool,
+    pub detect_code: bool,
+    pub detect_urls: bool,
+}
+
+impl Default for ClipboardWatcherConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_secs: 2,
+            detect_stack_traces: true,
+            detect_code: true,
+            detect_urls: true,
+        }
+    }
+}
+
+/// Plantilla Markdown de un informe con secciones que redacta un proveedor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct ReportTemplate {
+    pub name: String,
+    /// Markdown con marcadores `{{modelo: instrucción}}`, `{{fecha}}` y `{{hora}}`.
+    pub body: String,
+    /// Código corto del proveedor que redacta las secciones; `None` usa Jarvis.
+    pub provider: Option<String>,
+    /// Directorio donde se escriben los informes; vacío usa el de datos de la aplicación.
+    pub output_directory: String,
+    /// Expresión cron para generarlo automáticamente; vacía si solo se lanza a mano.
+    pub cron_expression: String,
+    /// Crea un commit con cada informe si el directorio está dentro de un repositorio Git.
+    pub commit_to_git: bool,
+    pub last_run: Option<String>,
+}
+
+/// Tipo de comprobación de una prueba rápida.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum QuickTestAssertionKind {
+    /// La respuesta contiene el texto, sin distinguir mayúsculas.
+    #[default]
+    Contains,
+    Regex,
+    /// La respuesta incluye JSON que cumple el esquema.
+    JsonSchema,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct QuickTestAssertion {
+    pub kind: QuickTestAssertionKind,
+    pub value: String,
+}
+
+/// Prompt con las comprobaciones que debe superar la respuesta de cada modelo.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct QuickTestCase {
+    pub name: String,
+    pub prompt: String,
+    pub assertions: Vec<QuickTestAssertion>,
+}
+
+impl QuickTestCase {
+    pub fn examples() -> Vec<QuickTestCase> {
+        let assertion = |kind, value: &str| QuickTestAssertion {
+            kind,
+            value: value.to_string(),
+        };
+        vec![
+            QuickTestCase {
+                name: "Capital de Francia".to_string(),
+                prompt: "¿Cuál es la capital de Francia? Responde con una palabra.".to_string(),
+                assertions: vec![assertion(QuickTestAssertionKind::Contains, "París")],
+            },
+            QuickTestCase {
+                name: "JSON de contacto".to_string(),
+                prompt: "Devuelve solo un objeto JSON con los campos nombre (texto) y edad (entero) de una persona inventada.".to_string(),
+                assertions: vec![assertion(
+                    QuickTestAssertionKind::JsonSchema,
+                    r#"{"type": "object", "required": ["nombre", "edad"], "properties": {"nombre": {"type": "string"}, "edad": {"type": "integer"}}}"#,
+                )],
+            },
+            QuickTestCase {
+                name: "Fecha ISO".to_string(),
+                prompt: "Escribe la fecha del primer alunizaje tripulado en formato AAAA-MM-DD, sin nada más.".to_string(),
+                assertions: vec![assertion(QuickTestAssertionKind::Regex, r"1969-07-2[01]")],
+            },
+        ]
+    }
+}
+
+/// Sugerencias de seguimiento que aparecen bajo la última respuesta del chat.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct FollowUpConfig {
+    pub enabled: bool,
+    /// Código del proveedor remoto que las propone; `None` las pide a Jarvis.
+    pub provider: Option<String>,
+    /// Modelo del proveedor, normalmente uno barato; vacío usa el predeterminado.
+    pub model: String,
+    pub count: usize,
+    /// Enviar la sugerencia al pulsarla en lugar de dejarla en el compositor.
+    pub send_immediately: bool,
+}
+
+impl Default for FollowUpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            provider: None,
+            model: String::new(),
+            count: 3,
+            send_immediately: false,
+        }
+    }
+}
+
+/// Efecto con consecuencias fuera de la app que puede provocar un workflow o un listener.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum AutomationSideEffect {
+    FileDeletion,
+    GitPush,
+    HttpRequest,
+    ShellExec,
+}
+
+/// Permiso recordado para un efecto, de una automatización concreta o de todas.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AutomationPermissionGrant {
+    pub effect: AutomationSideEffect,
+    /// `workflow:<nombre>` o `listener:<nombre>`; `None` vale para cualquiera.
+    #[serde(default)]
+    pub automation: Option<String>,
+    #[serde(default)]
+    pub granted_at: String,
+}
+
+/// Paso de un playbook: un prompt que se envía al proveedor enrutado de la conversación.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct PlaybookStep {
+    pub title: String,
+    /// Admite `{{campo}}`, que se pide al lanzar el playbook, `{{anterior}}` con la
+    /// respuesta del paso previo y `{{paso: N}}` con la del paso N.
+    pub prompt: String,
+}
+
+/// Flujo de conversación predefinido cuyas respuestas se reúnen en un documento.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct Playbook {
+    pub name: String,
+    pub description: String,
+    pub steps: Vec<PlaybookStep>,
+}
+
+impl Playbook {
+    fn new(name: &str, description: &str, steps: &[(&str, &str)]) -> Self {
+        Self {
+            name: name.to_string(),
+            description: description.to_string(),
+            steps: steps
+                .iter()
+                .map(|(title, prompt)| PlaybookStep {
+                    title: title.to_string(),
+                    prompt: prompt.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn examples() -> Vec<Playbook> {
+        vec![
+            Playbook::new(
+                "Triaje de bug",
+                "Resume un informe de bug, busca sus causas y propone un plan de corrección.",
+                &[
+                    (
+                        "Resumen y gravedad",
+                        "Resume este informe de bug en tres frases y clasifica su gravedad (crítica, alta, media o baja):\n\n{{informe}}",
+                    ),
+                    (
+                        "Causas probables",
+                        "Enumera las causas más probables de este bug en el componente {{componente}} y cómo confirmar cada una:\n\n{{anterior}}",
+                    ),
+                    (
+                        "Plan de corrección",
+                        "Propón un plan de corrección con pasos, pruebas y riesgos.\n\nResumen:\n{{paso: 1}}\n\nCausas:\n{{paso: 2}}",
+                    ),
+                ],
+            ),
+            Playbook::new(
+                "Borrador de PRD",
+                "Redacta el problema, los requisitos y las métricas de un documento de producto.",
+                &[
+                    (
+                        "Problema y usuarios",
+                        "Describe el problema que resuelve {{producto}} para {{usuarios}} y sus necesidades principales.",
+                    ),
+                    (
+                        "Requisitos",
+                        "A partir de este contexto, escribe los requisitos funcionales y no funcionales en listas numeradas:\n\n{{anterior}}",
+                    ),
+                    (
+                        "Métricas y riesgos",
+                        "Propón métricas de éxito y riesgos de lanzamiento para este producto.\n\nProblema:\n{{paso: 1}}\n\nRequisitos:\n{{paso: 2}}",
+                    ),
+                ],
+            ),
+        ]
+    }
+}
+
+/// De dónde sale el secreto con el que se cifra el historial guardado.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageKeySource {
+    /// Secreto aleatorio guardado en el llavero del sistema; se desbloquea solo.
+    #[default]
+    Keychain,
+    /// Frase de paso que el usuario escribe en cada arranque.
+    Passphrase,
+}
+
+/// Cifrado en disco de las conversaciones, los marcadores y la sesión.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageEncryptionConfig {
+    pub enabled: bool,
+    pub key_source: StorageKeySource,
 }
 
 /// Preferencias relacionadas con catálogos de modelos descargables.
@@ -159,6 +1616,91 @@ pub struct AppConfig {
     pub modelscope: ModelProviderConfig,
     #[serde(default)]
     pub theme: crate::ui::theme::ThemePreset,
+    #[serde(default)]
+    pub context: ContextConfig,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub layout: LayoutPreferences,
+    #[serde(default)]
+    pub language: crate::i18n::Language,
+    #[serde(default)]
+    pub accessibility: AccessibilityConfig,
+    #[serde(default)]
+    pub keybindings: KeybindingsConfig,
+    #[serde(default)]
+    pub event_stream: EventStreamConfig,
+    #[serde(default)]
+    pub embeddings: EmbeddingConfig,
+    #[serde(default)]
+    pub model_quick_actions: Vec<ModelQuickAction>,
+    #[serde(default)]
+    pub response_cache: ResponseCacheConfig,
+    #[serde(default)]
+    pub file_watcher: FileWatcherConfig,
+    #[serde(default)]
+    pub knowledge_reindex: Vec<KnowledgeReindexSchedule>,
+    #[serde(default)]
+    pub voice_input: VoiceInputConfig,
+    #[serde(default)]
+    pub ocr: OcrConfig,
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+    /// Proyectos conectados cuyo contenido solo puede enviarse a Jarvis.
+    #[serde(default)]
+    pub local_only_projects: Vec<String>,
+    #[serde(default)]
+    pub daily_digest: DailyDigestConfig,
+    #[serde(default)]
+    pub report_templates: Vec<ReportTemplate>,
+    #[serde(default)]
+    pub text_actions: TextActionsConfig,
+    /// Lee en voz alta las puestas al día de la actividad.
+    #[serde(default)]
+    pub speak_briefings: bool,
+    #[serde(default)]
+    pub follow_ups: FollowUpConfig,
+    #[serde(default)]
+    pub usage_metrics: UsageMetricsConfig,
+    /// Dependencias entre tareas del planificador: nombre de la tarea → tareas previas.
+    #[serde(default)]
+    pub cron_dependencies: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub notifications: NotificationPolicyConfig,
+    #[serde(default)]
+    pub safety_filters: SafetyFilterConfig,
+    #[serde(default = "Playbook::examples")]
+    pub playbooks: Vec<Playbook>,
+    /// Perfil de contexto de cada proyecto de `projects`, por nombre.
+    #[serde(default)]
+    pub project_profiles: BTreeMap<String, ProjectContextProfile>,
+    /// Paquetes de workflows importados, tal como quedaron instalados; se vuelven a
+    /// aplicar al arrancar.
+    #[serde(default)]
+    pub imported_workflows: Vec<crate::state::workflow_bundle::WorkflowBundle>,
+    /// Efectos que las automatizaciones pueden provocar sin volver a preguntar.
+    #[serde(default)]
+    pub automation_permissions: Vec<AutomationPermissionGrant>,
+    #[serde(default)]
+    pub storage_encryption: StorageEncryptionConfig,
+    /// Pruebas con aserciones para validar modelos antes de hacerlos predeterminados.
+    #[serde(default = "QuickTestCase::examples")]
+    pub quick_tests: Vec<QuickTestCase>,
+    #[serde(default)]
+    pub web_search: WebSearchConfig,
+    #[serde(default)]
+    pub smtp: SmtpConfig,
+    /// Conectores de Slack y Discord para workflows y notificaciones.
+    #[serde(default)]
+    pub webhook_connectors: Vec<WebhookConnectorConfig>,
+    #[serde(default)]
+    pub telegram: TelegramBridgeConfig,
+    #[serde(default)]
+    pub mqtt: MqttConfig,
+    #[serde(default)]
+    pub job_queue: JobQueueConfig,
+    #[serde(default)]
+    pub clipboard_watcher: ClipboardWatcherConfig,
 }
 
 impl Default for AppConfig {
@@ -169,18 +1711,21 @@ impl Default for AppConfig {
                 default_model: "claude-3-opus-20240229".to_string(),
                 alias: "claude".to_string(),
                 daily_limit: Some(120),
+                ..ProviderConfig::default()
             },
             openai: ProviderConfig {
                 api_key: None,
                 default_model: "gpt-4.1-mini".to_string(),
                 alias: "gpt".to_string(),
                 daily_limit: Some(120),
+                ..ProviderConfig::default()
             },
             groq: ProviderConfig {
                 api_key: None,
                 default_model: "llama3-70b-8192".to_string(),
                 alias: "groq".to_string(),
                 daily_limit: Some(120),
+                ..ProviderConfig::default()
             },
             github_token: None,
             cache_directory: "/var/tmp/jungle/cache".to_string(),
@@ -208,35 +1753,306 @@ impl Default for AppConfig {
             openrouter: ModelProviderConfig::default(),
             modelscope: ModelProviderConfig::default(),
             theme: crate::ui::theme::ThemePreset::default(),
+            context: ContextConfig::default(),
+            network: NetworkConfig::default(),
+            layout: LayoutPreferences::default(),
+            language: crate::i18n::Language::default(),
+            accessibility: AccessibilityConfig::default(),
+            keybindings: KeybindingsConfig::default(),
+            event_stream: EventStreamConfig::default(),
+            embeddings: EmbeddingConfig::default(),
+            model_quick_actions: Vec::new(),
+            response_cache: ResponseCacheConfig::default(),
+            file_watcher: FileWatcherConfig::default(),
+            knowledge_reindex: Vec::new(),
+            voice_input: VoiceInputConfig::default(),
+            ocr: OcrConfig::default(),
+            redaction: RedactionConfig::default(),
+            local_only_projects: Vec::new(),
+            daily_digest: DailyDigestConfig::default(),
+            report_templates: Vec::new(),
+            text_actions: TextActionsConfig::default(),
+            speak_briefings: false,
+            follow_ups: FollowUpConfig::default(),
+            usage_metrics: UsageMetricsConfig::default(),
+            cron_dependencies: BTreeMap::new(),
+            notifications: NotificationPolicyConfig::default(),
+            safety_filters: SafetyFilterConfig::default(),
+            playbooks: Playbook::examples(),
+            project_profiles: BTreeMap::new(),
+            imported_workflows: Vec::new(),
+            automation_permissions: Vec::new(),
+            storage_encryption: StorageEncryptionConfig::default(),
+            quick_tests: QuickTestCase::examples(),
+            web_search: WebSearchConfig::default(),
+            smtp: SmtpConfig::default(),
+            webhook_connectors: Vec::new(),
+            telegram: TelegramBridgeConfig::default(),
+            mqtt: MqttConfig::default(),
+            job_queue: JobQueueConfig::default(),
+            clipboard_watcher: ClipboardWatcherConfig::default(),
         }
     }
 }
 
 impl AppConfig {
-    fn config_path() -> anyhow::Result<PathBuf> {
+    /// Ruta de un fichero dentro del directorio de datos de la aplicación.
+    pub fn data_file(name: &str) -> anyhow::Result<PathBuf> {
         let base = dirs::config_dir().unwrap_or_else(|| Path::new(".").to_path_buf());
         let dir = base.join("JungleMonkAI");
         fs::create_dir_all(&dir).with_context(|| format!("No se pudo crear {:?}", dir))?;
-        Ok(dir.join("config.json"))
+        Ok(dir.join(name))
+    }
+
+    fn providers_mut(&mut self) -> [(&'static str, &mut ProviderConfig); 3] {
+        [
+            (KEYCHAIN_PROVIDERS[0], &mut self.anthropic),
+            (KEYCHAIN_PROVIDERS[1], &mut self.openai),
+            (KEYCHAIN_PROVIDERS[2], &mut self.groq),
+        ]
+    }
+
+    /// API keys no vacías de los proveedores, por sección.
+    pub fn provider_keys(&self) -> BTreeMap<String, String> {
+        [&self.anthropic, &self.openai, &self.groq]
+            .into_iter()
+            .zip(KEYCHAIN_PROVIDERS)
+            .filter_map(|(provider, name)| {
+                let key = provider.api_key.as_deref()?.trim();
+                (!key.is_empty()).then(|| (name.to_string(), key.to_string()))
+            })
+            .collect()
+    }
+
+    /// Anota en cada proveedor la entrada del llavero de su API key, o ninguna si no tiene.
+    pub fn link_provider_keys(&mut self) {
+        for (name, provider) in self.providers_mut() {
+            let has_key = provider
+                .api_key
+                .as_deref()
+                .is_some_and(|key| !key.trim().is_empty());
+            provider.api_key_account = has_key.then(|| provider_key_account(name));
+        }
+    }
+
+    /// Lee del llavero las API keys de los proveedores. Las que aún estaban en claro en
+    /// `config.json` se quedan en memoria y se devuelven sus secciones, para que el
+    /// siguiente guardado las mueva al llavero.
+    pub fn load_provider_keys(&mut self) -> (Vec<&'static str>, Vec<String>) {
+        let mut plaintext = Vec::new();
+        let mut errors = Vec::new();
+        for (name, provider) in self.providers_mut() {
+            if provider.api_key.is_some() {
+                plaintext.push(name);
+                continue;
+            }
+            let Some(account) = provider.api_key_account.clone() else {
+                continue;
+            };
+            match crate::state::vault::keychain_entry(&account) {
+                Ok(Some(key)) => provider.api_key = Some(key),
+                Ok(None) => errors.push(format!("El llavero no tiene la API key de {}", name)),
+                Err(err) => errors.push(format!("{:#}", err)),
+            }
+        }
+        self.link_provider_keys();
+        (plaintext, errors)
+    }
+
+    /// Devuelve las API keys `keys` a la configuración reconstruida desde JSON, que no las
+    /// contiene. Solo en los proveedores que siguen apuntando al llavero.
+    pub fn keep_provider_keys(&mut self, keys: &BTreeMap<String, String>) {
+        for (name, provider) in self.providers_mut() {
+            if provider.api_key.is_none() && provider.api_key_account.is_some() {
+                provider.api_key = keys.get(name).cloned();
+            }
+        }
+    }
+
+    /// Guarda en el llavero las API keys que difieren de `stored` y borra las que ya no
+    /// están en la configuración.
+    pub fn store_provider_keys(&self, stored: &BTreeMap<String, String>) -> anyhow::Result<()> {
+        let keys = self.provider_keys();
+        for (name, key) in &keys {
+            if stored.get(name) != Some(key) {
+                crate::state::vault::store_keychain_entry(&provider_key_account(name), key)
+                    .with_context(|| {
+                        format!("No se pudo guardar la API key de {} en el llavero", name)
+                    })?;
+            }
+        }
+        for name in stored.keys().filter(|name| !keys.contains_key(*name)) {
+            crate::state::vault::delete_keychain_entry(&provider_key_account(name))
+                .with_context(|| format!("No se pudo borrar la API key de {} del llavero", name))?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn config_path() -> anyhow::Result<PathBuf> {
+        Self::data_file("config.json")
     }
 
     pub fn load_or_default() -> Self {
-        let path = match Self::config_path() {
-            Ok(path) => path,
-            Err(_) => return Self::default(),
-        };
+        Self::load_with_recovery().0
+    }
+
+    /// Carga la configuración y, si `config.json` está dañado, recurre a la copia válida
+    /// más reciente. El archivo dañado se aparta para poder revisarlo.
+    pub fn load_with_recovery() -> (Self, Option<ConfigRecovery>) {
+        match Self::config_path() {
+            Ok(path) => Self::load_from(&path),
+            Err(_) => (Self::default(), None),
+        }
+    }
 
-        let data = match fs::read_to_string(&path) {
-            Ok(content) => content,
-            Err(_) => return Self::default(),
+    fn load_from(path: &Path) -> (Self, Option<ConfigRecovery>) {
+        let Ok(data) = fs::read(path) else {
+            return (Self::default(), None);
         };
+        if let Ok(config) = serde_json::from_slice(&data) {
+            return (config, None);
+        }
 
-        serde_json::from_str(&data).unwrap_or_else(|_| Self::default())
+        let damaged = path.with_extension("json.damaged");
+        let _ = fs::copy(path, &damaged);
+        for backup in (1..=CONFIG_BACKUPS).map(|number| backup_path(path, number)) {
+            let restored = fs::read(&backup)
+                .ok()
+                .and_then(|data| serde_json::from_slice(&data).ok());
+            if let Some(config) = restored {
+                return (config, Some(ConfigRecovery::Restored { backup, damaged }));
+            }
+        }
+        (Self::default(), Some(ConfigRecovery::Defaults { damaged }))
     }
 
     pub fn save(&self) -> anyhow::Result<()> {
-        let path = Self::config_path()?;
+        self.save_to(&Self::config_path()?)
+    }
+
+    fn save_to(&self, path: &Path) -> anyhow::Result<()> {
         let json = serde_json::to_string_pretty(self)?;
-        fs::write(&path, json).with_context(|| format!("No se pudo guardar {:?}", path))
+        // Escribe en un temporal sincronizado con el disco y lo renombra, para no dejar el
+        // fichero a medias si la aplicación se cierra o el sistema cae durante el guardado.
+        let temporary = path.with_extension("json.tmp");
+        let mut file = fs::File::create(&temporary)
+            .with_context(|| format!("No se pudo guardar {:?}", temporary))?;
+        file.write_all(json.as_bytes())
+            .and_then(|()| file.sync_all())
+            .with_context(|| format!("No se pudo guardar {:?}", temporary))?;
+        rotate_backups(path);
+        fs::rename(&temporary, path).with_context(|| format!("No se pudo guardar {:?}", path))
+    }
+}
+
+fn backup_path(path: &Path, number: usize) -> PathBuf {
+    path.with_extension(format!("json.{}", number))
+}
+
+/// Desplaza las copias (`.1` pasa a `.2`…) y guarda el archivo actual como `.1`. Solo se
+/// copia si es JSON válido: una copia dañada no serviría para recuperar nada.
+fn rotate_backups(path: &Path) {
+    let valid = fs::read(path)
+        .ok()
+        .is_some_and(|data| serde_json::from_slice::<serde_json::Value>(&data).is_ok());
+    if !valid {
+        return;
+    }
+    for number in (1..CONFIG_BACKUPS).rev() {
+        let _ = fs::rename(backup_path(path, number), backup_path(path, number + 1));
+    }
+    let _ = fs::copy(path, backup_path(path, 1));
+    for number in 1..=CONFIG_BACKUPS {
+        strip_plaintext_keys(&backup_path(path, number));
+    }
+}
+
+/// Quita de una copia de `config.json` las API keys en claro que guardaban las versiones
+/// anteriores; ahora están en el llavero.
+fn strip_plaintext_keys(path: &Path) {
+    let Some(mut value) = fs::read(path)
+        .ok()
+        .and_then(|data| serde_json::from_slice::<serde_json::Value>(&data).ok())
+    else {
+        return;
+    };
+    let mut stripped = false;
+    for name in KEYCHAIN_PROVIDERS {
+        if let Some(section) = value
+            .get_mut(name)
+            .and_then(|section| section.as_object_mut())
+        {
+            stripped |= section.remove("api_key").is_some();
+        }
+    }
+    if stripped {
+        if let Ok(data) = serde_json::to_vec_pretty(&value) {
+            let _ = fs::write(path, data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_backups_and_recovers_from_a_damaged_file() {
+        let dir = std::env::temp_dir().join(format!("jmk-config-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+
+        let mut config = AppConfig {
+            projects: vec!["Primero".to_string()],
+            ..AppConfig::default()
+        };
+        config.save_to(&path).unwrap();
+        config.projects = vec!["Segundo".to_string()];
+        config.save_to(&path).unwrap();
+        assert!(backup_path(&path, 1).exists());
+
+        fs::write(&path, "{\"projects\": [").unwrap();
+        let (loaded, recovery) = AppConfig::load_from(&path);
+        assert_eq!(loaded.projects, vec!["Primero".to_string()]);
+        assert_eq!(
+            recovery,
+            Some(ConfigRecovery::Restored {
+                backup: backup_path(&path, 1),
+                damaged: path.with_extension("json.damaged"),
+            })
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn api_keys_stay_out_of_the_file_and_its_backups() {
+        let dir = std::env::temp_dir().join(format!("jmk-config-keys-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        fs::write(&path, r#"{"openai": {"api_key": "sk-antigua"}}"#).unwrap();
+
+        let mut config = AppConfig::default();
+        config.openai.api_key = Some("sk-nueva".to_string());
+        config.link_provider_keys();
+        config.save_to(&path).unwrap();
+        config.save_to(&path).unwrap();
+
+        for file in [path.clone(), backup_path(&path, 1), backup_path(&path, 2)] {
+            let data = fs::read_to_string(&file).unwrap();
+            assert!(
+                !data.contains("sk-antigua") && !data.contains("sk-nueva"),
+                "{:?} guarda una API key",
+                file
+            );
+        }
+        let (loaded, _) = AppConfig::load_from(&path);
+        assert_eq!(loaded.openai.api_key, None);
+        assert_eq!(
+            loaded.openai.api_key_account,
+            Some(provider_key_account("openai"))
+        );
+        let _ = fs::remove_dir_all(&dir);
     }
 }
@@ -0,0 +1,1560 @@
+/// Traducciones al inglés de los textos escritos en español.
+pub(super) const ENTRIES: &[(&str, &str)] = &[
+    // Cabecera y shell
+    ("Preferencias", "Preferences"),
+    ("Funciones", "Functions"),
+    ("Modo zen", "Zen mode"),
+    ("Salir del modo zen", "Exit zen mode"),
+    ("Volver a conectar", "Go back online"),
+    ("Modo sin conexión", "Offline mode"),
+    ("Sin conexión", "Offline"),
+    ("Sin conexión · {0} en cola", "Offline · {0} queued"),
+    (
+        "Cmd/Ctrl+K · Buscar modelos, conversaciones y documentos",
+        "Cmd/Ctrl+K · Search models, conversations and documents",
+    ),
+    ("Conversación multimodal", "Multimodal conversation"),
+    ("Planificador de tareas", "Task scheduler"),
+    ("Actividad reciente", "Recent activity"),
+    ("Consola de depuración", "Debug console"),
+    ("Preferencias avanzadas", "Advanced preferences"),
+    ("Explorador de recursos", "Resource browser"),
+    ("Navegación", "Navigation"),
+    ("Ocultar navegación", "Hide navigation"),
+    ("Mostrar navegación", "Show navigation"),
+    ("Mostrar recursos", "Show resources"),
+    ("Ocultar recursos", "Hide resources"),
+    // Panel de recursos
+    ("Recursos", "Resources"),
+    ("Ocultar panel de recursos", "Hide resource panel"),
+    ("Jarvis listo para iniciar", "Jarvis ready to start"),
+    ("Sin modelo seleccionado", "No model selected"),
+    ("Resumen del entorno local", "Local environment summary"),
+    ("Inicio automático: {0}", "Auto start: {0}"),
+    ("activado", "enabled"),
+    ("Modelo configurado: {0}", "Configured model: {0}"),
+    ("Abrir preferencias", "Open preferences"),
+    (
+        "Configura proveedores y automatizaciones",
+        "Configure providers and automations",
+    ),
+    ("Explorar funciones", "Browse functions"),
+    ("{0} funciones personalizables", "{0} customizable functions"),
+    ("Copiar conversación", "Copy conversation"),
+    (
+        "Guarda el historial actual en el portapapeles",
+        "Copy the current history to the clipboard",
+    ),
+    ("Acciones rápidas", "Quick actions"),
+    ("Atajos frecuentes durante la sesión", "Frequent shortcuts during the session"),
+    ("Catálogos destacados", "Featured catalogs"),
+    ("Explora proveedores conectados", "Browse connected providers"),
+    // Secciones de navegación
+    ("Principal", "Main"),
+    (
+        "Accesos directos a las vistas principales",
+        "Shortcuts to the main views",
+    ),
+    ("Sistema", "System"),
+    (
+        "Configura integraciones y recursos del sistema",
+        "Configure integrations and system resources",
+    ),
+    ("Personalización", "Customization"),
+    ("Ajusta la experiencia de JungleMonkAI", "Tailor the JungleMonkAI experience"),
+    ("Proveedores", "Providers"),
+    (
+        "Gestiona credenciales y catálogos remotos",
+        "Manage credentials and remote catalogs",
+    ),
+    ("Modelos locales", "Local models"),
+    (
+        "Controla el runtime y las instalaciones locales",
+        "Control the runtime and local installations",
+    ),
+    ("Catálogos remotos", "Remote catalogs"),
+    (
+        "Explora modelos disponibles en la nube",
+        "Browse models available in the cloud",
+    ),
+    ("Galerías locales", "Local galleries"),
+    (
+        "Instala modelos optimizados para ejecución local",
+        "Install models optimized for local execution",
+    ),
+    ("Espacios conectados", "Connected spaces"),
+    (
+        "Gestiona los recursos ya integrados",
+        "Manage the resources already integrated",
+    ),
+    ("Chat multimodal", "Multimodal chat"),
+    (
+        "Conversa con JungleMonkAI en modo multimodal.",
+        "Chat with JungleMonkAI in multimodal mode.",
+    ),
+    ("Rutas por alias", "Alias routes"),
+    (
+        "Divide tus mensajes entre proveedores remotos usando menciones @alias.",
+        "Split your messages across remote providers using @alias mentions.",
+    ),
+    (
+        "Programa y supervisa tareas automáticas.",
+        "Schedule and monitor automated tasks.",
+    ),
+    ("Actividad", "Activity"),
+    (
+        "Consulta los eventos recientes del sistema.",
+        "Review recent system events.",
+    ),
+    (
+        "Accede a diagnósticos y registros de depuración.",
+        "Access diagnostics and debug logs.",
+    ),
+    (
+        "Modelos y recursos ya disponibles en Jarvis",
+        "Models and resources already available in Jarvis",
+    ),
+    (
+        "Proyectos conectados y su estado de sincronización",
+        "Connected projects and their sync status",
+    ),
+    (
+        "Repositorios disponibles desde GitHub",
+        "Repositories available from GitHub",
+    ),
+    // Metadatos de paneles
+    ("Integración con GitHub", "GitHub integration"),
+    (
+        "Administra las credenciales de GitHub y el repositorio sincronizado con JungleMonkAI.",
+        "Manage GitHub credentials and the repository synced with JungleMonkAI.",
+    ),
+    ("Caché", "Cache"),
+    (
+        "Configura el directorio de caché, límites de espacio y automatizaciones de limpieza.",
+        "Configure the cache directory, space limits and cleanup automations.",
+    ),
+    (
+        "Delimita el uso permitido de memoria y almacenamiento para la ejecución local.",
+        "Limit the memory and storage allowed for local execution.",
+    ),
+    ("Red", "Network"),
+    (
+        "Define el proxy, los certificados raíz adicionales y el timeout de las peticiones salientes.",
+        "Set the proxy, additional root certificates and the timeout for outgoing requests.",
+    ),
+    ("Comandos", "Commands"),
+    (
+        "Crea y gestiona accesos rápidos disponibles como slash-commands en el chat.",
+        "Create and manage shortcuts available as slash commands in the chat.",
+    ),
+    ("Apariencia", "Appearance"),
+    (
+        "Selecciona el tema claro u oscuro inspirado en la estética de VSCode.",
+        "Choose the light or dark theme inspired by the VSCode look.",
+    ),
+    ("Memoria", "Memory"),
+    (
+        "Ajusta la retención de memoria contextual y la persistencia entre sesiones.",
+        "Adjust contextual memory retention and persistence across sessions.",
+    ),
+    ("Perfiles", "Profiles"),
+    (
+        "Selecciona, crea y renombra perfiles de configuración para la experiencia diaria.",
+        "Select, create and rename configuration profiles for everyday use.",
+    ),
+    ("Proyectos", "Projects"),
+    (
+        "Organiza los proyectos que JungleMonkAI sigue y prioriza dentro del espacio de trabajo.",
+        "Organize the projects JungleMonkAI tracks and prioritizes in the workspace.",
+    ),
+    (
+        "Introduce credenciales de Anthropic, alias de invocación y prueba la conectividad de Claude.",
+        "Enter Anthropic credentials and the invocation alias, and test Claude connectivity.",
+    ),
+    (
+        "Define la API key de OpenAI, alias de chat y el modelo predeterminado para peticiones.",
+        "Set the OpenAI API key, chat alias and default model for requests.",
+    ),
+    (
+        "Configura las credenciales de Groq y valida la disponibilidad de su endpoint.",
+        "Configure Groq credentials and check that its endpoint is available.",
+    ),
+    ("Configuración", "Settings"),
+    (
+        "Controla la ruta, instalación y comportamiento de arranque del runtime Jarvis.",
+        "Control the path, installation and startup behavior of the Jarvis runtime.",
+    ),
+    (
+        "Explora modelos publicados en Hugging Face listos para instalar en Jarvis.",
+        "Browse models published on Hugging Face ready to install in Jarvis.",
+    ),
+    (
+        "Consulta colecciones de GitHub Models y prepara su exportación para uso offline.",
+        "Browse GitHub Models collections and prepare them for offline use.",
+    ),
+    (
+        "Busca modelos de la comunidad de Replicate compatibles con el runtime local.",
+        "Search Replicate community models compatible with the local runtime.",
+    ),
+    (
+        "Conecta con tu servidor Ollama y descarga modelos optimizados para CPU/GPU.",
+        "Connect to your Ollama server and download models optimized for CPU/GPU.",
+    ),
+    (
+        "Lista modelos disponibles en OpenRouter y sincronízalos con el entorno local.",
+        "List models available on OpenRouter and sync them with the local environment.",
+    ),
+    (
+        "Revisa checkpoints publicados en ModelScope para incorporarlos a Jarvis.",
+        "Review checkpoints published on ModelScope to add them to Jarvis.",
+    ),
+    (
+        "Explora el catálogo actualizado de modelos Claude disponibles vía Anthropic.",
+        "Browse the up-to-date catalog of Claude models available through Anthropic.",
+    ),
+    (
+        "Revisa la disponibilidad planificada de modelos GPT y sus capacidades.",
+        "Review the planned availability of GPT models and their capabilities.",
+    ),
+    (
+        "Consulta los modelos acelerados por Groq y su estado de compatibilidad.",
+        "Browse Groq-accelerated models and their compatibility status.",
+    ),
+    ("Modelos instalados", "Installed models"),
+    (
+        "Gestiona los modelos locales ya descargados, su tamaño y fecha de instalación.",
+        "Manage downloaded local models, their size and installation date.",
+    ),
+    ("Instalados", "Installed"),
+    ("Proyectos locales conectados", "Connected local projects"),
+    (
+        "Navega proyectos locales indexados como recursos con resúmenes y estado de sincronización.",
+        "Browse local projects indexed as resources with summaries and sync status.",
+    ),
+    ("Productividad", "Productivity"),
+    ("Repositorios GitHub conectados", "Connected GitHub repositories"),
+    (
+        "Consulta repositorios enlazados con previews de README y sincronización bidireccional.",
+        "Browse linked repositories with README previews and two-way sync.",
+    ),
+    (
+        "Selecciona un recurso en el panel izquierdo para explorar su contenido.",
+        "Select a resource in the left panel to explore its content.",
+    ),
+    // Pestañas y vistas
+    (
+        "Define y gestiona comandos personalizados",
+        "Define and manage custom commands",
+    ),
+    (
+        "Consulta referencias y ejemplos de comandos",
+        "Browse command references and examples",
+    ),
+    (
+        "Revisa la actividad reciente de los comandos",
+        "Review recent command activity",
+    ),
+    (
+        "Ajusta la memoria contextual y su retención",
+        "Adjust contextual memory and its retention",
+    ),
+    ("Contexto", "Context"),
+    (
+        "Define cuánto historial acompaña a cada prompt",
+        "Choose how much history goes with each prompt",
+    ),
+    (
+        "Configura credenciales y alias de Anthropic",
+        "Configure Anthropic credentials and alias",
+    ),
+    ("Modelos", "Models"),
+    (
+        "Gestiona el catálogo de modelos Claude",
+        "Manage the Claude model catalog",
+    ),
+    (
+        "Supervisa consumo y límites de Anthropic",
+        "Monitor Anthropic usage and limits",
+    ),
+    (
+        "Configura credenciales y alias de OpenAI",
+        "Configure OpenAI credentials and alias",
+    ),
+    (
+        "Selecciona modelos y parámetros de OpenAI",
+        "Choose OpenAI models and parameters",
+    ),
+    (
+        "Controla el consumo de tokens en OpenAI",
+        "Track token usage on OpenAI",
+    ),
+    (
+        "Configura credenciales y alias de Groq",
+        "Configure Groq credentials and alias",
+    ),
+    ("Explora modelos acelerados por Groq", "Browse Groq-accelerated models"),
+    ("Supervisa uso y límites de Groq", "Monitor Groq usage and limits"),
+    ("Conversación principal", "Main conversation"),
+    (
+        "Tareas programadas y cron jobs",
+        "Scheduled tasks and cron jobs",
+    ),
+    ("Actividad reciente del sistema", "Recent system activity"),
+    ("Herramientas de diagnóstico", "Diagnostic tools"),
+    (
+        "Coordina agentes, herramientas y documentos",
+        "Coordinate agents, tools and documents",
+    ),
+    (
+        "Gestiona tareas automatizadas y cron jobs",
+        "Manage automated tasks and cron jobs",
+    ),
+    (
+        "Audita eventos recientes del sistema",
+        "Audit recent system events",
+    ),
+    (
+        "Monitorea registros y diagnósticos",
+        "Monitor logs and diagnostics",
+    ),
+    (
+        "Configura integraciones y flujos de trabajo",
+        "Configure integrations and workflows",
+    ),
+    (
+        "Explora catálogos locales y remotos",
+        "Browse local and remote catalogs",
+    ),
+    // Apariencia
+    ("Tema de la interfaz", "Interface theme"),
+    (
+        "Alterna entre presets claro y oscuro inspirados en los esquemas de VSCode.",
+        "Switch between light and dark presets inspired by VSCode color schemes.",
+    ),
+    ("Tema oscuro", "Dark theme"),
+    (
+        "Contraste alto con paneles profundos y resaltes eléctricos.",
+        "High contrast with deep panels and electric highlights.",
+    ),
+    ("Tema claro", "Light theme"),
+    (
+        "Fondo luminoso con bordes suaves para entornos bien iluminados.",
+        "Bright background with soft borders for well-lit rooms.",
+    ),
+    ("Idioma de la interfaz", "Interface language"),
+    (
+        "El cambio se aplica al instante; los textos sin traducir se muestran en su idioma original.",
+        "The change applies immediately; untranslated text is shown in its original language.",
+    ),
+    ("Idioma de la interfaz: {0}", "Interface language: {0}"),
+    ("Distribución de paneles", "Panel layout"),
+    (
+        "Arrastra los bordes de los paneles para redimensionarlos; la distribución se restaura al volver a abrir la aplicación.",
+        "Drag panel edges to resize them; the layout is restored when the app is reopened.",
+    ),
+    ("activa", "active"),
+    ("Aplicar", "Apply"),
+    ("Eliminar distribución", "Delete layout"),
+    ("Nombre de la distribución", "Layout name"),
+    ("Guardar distribución actual", "Save current layout"),
+    (
+        "Oculta cabecera y paneles laterales (F11)",
+        "Hide the header and side panels (F11)",
+    ),
+    ("oculto", "hidden"),
+    ("plegado", "collapsed"),
+    ("Navegación {0} · Recursos {1}", "Navigation {0} · Resources {1}"),
+    ("Distribución '{0}' guardada.", "Layout '{0}' saved."),
+    ("Distribución '{0}' aplicada.", "Layout '{0}' applied."),
+    ("Distribución '{0}' eliminada.", "Layout '{0}' deleted."),
+    // Ventanas modales y ayuda de comandos
+    (
+        "Configura aquí tus claves de API y otros ajustes.",
+        "Configure your API keys and other settings here.",
+    ),
+    (
+        "Consulta la documentación ampliada de cada comando y función disponible.",
+        "Read the extended documentation for every available command and function.",
+    ),
+    ("Comandos integrados", "Built-in commands"),
+    ("Funciones personalizables", "Customizable functions"),
+    ("Ejemplos:", "Examples:"),
+    ("Parámetros:", "Parameters:"),
+    (
+        "Ejecuta comandos condicionalmente. La condición admite &&, ||, !, paréntesis, comparaciones de números y textos y los métodos contains, starts_with y ends_with.",
+        "Runs commands conditionally. Conditions support &&, ||, !, parentheses, number and text comparisons and the contains, starts_with and ends_with methods.",
+    ),
+    (
+        "Sincroniza la configuración y admite la bandera --force para reiniciar credenciales.",
+        "Syncs the configuration and accepts the --force flag to reset credentials.",
+    ),
+    (
+        "Muestra la hora actual con distintos formatos de salida.",
+        "Shows the current time in several output formats.",
+    ),
+    (
+        "Resume el estado del sistema y permite profundizar en recursos concretos.",
+        "Summarizes system status and can drill down into specific resources.",
+    ),
+    (
+        "Genera un informe de depuración con estados detallados de cada componente.",
+        "Generates a debug report with the detailed status of each component.",
+    ),
+    (
+        "Entrega estadísticas de uso y métricas de comandos.",
+        "Reports usage statistics and command metrics.",
+    ),
+    (
+        "Lista los proyectos activos y permite limitar la salida.",
+        "Lists active projects and can limit the output.",
+    ),
+    (
+        "Muestra los perfiles configurados con orden opcional.",
+        "Shows the configured profiles with optional sorting.",
+    ),
+    (
+        "Describe la configuración actual de la caché del agente.",
+        "Describes the agent's current cache configuration.",
+    ),
+    (
+        "Lista los modelos disponibles filtrando por proveedor si se desea.",
+        "Lists available models, optionally filtered by provider.",
+    ),
+    (
+        "Entrega un resumen de la conexión con GitHub y opcionalmente los repositorios.",
+        "Summarizes the GitHub connection and optionally the repositories.",
+    ),
+    (
+        "Explica la configuración de memoria contextual.",
+        "Explains the contextual memory configuration.",
+    ),
+    (
+        "Lista los proveedores activos con información opcional de modelos.",
+        "Lists active providers with optional model details.",
+    ),
+    (
+        "Describe el estado del runtime local Jarvis con posibilidad de ver rutas y logs.",
+        "Describes the status of the local Jarvis runtime, optionally with paths and logs.",
+    ),
+    (
+        "Lista todos los comandos disponibles y su propósito.",
+        "Lists every available command and its purpose.",
+    ),
+    // Registros
+    ("Registros y tareas", "Logs and tasks"),
+    ("Estado", "Status"),
+    ("Origen", "Source"),
+    ("Detalle", "Details"),
+    ("Hora", "Time"),
+    ("Idioma", "Language"),
+    (
+        "Sincronización de proveedores completada",
+        "Provider sync completed",
+    ),
+    ("Indexando embeddings locales", "Indexing local embeddings"),
+    (
+        "Timeout al consultar métricas externas",
+        "Timeout while querying external metrics",
+    ),
+    ("Accesibilidad", "Accessibility"),
+    ("Ajusta la escala de la interfaz, reduce las animaciones y configura la navegación por teclado.", "Adjust the interface scale, reduce animations and configure keyboard navigation."),
+    ("Escala de la interfaz", "Interface scale"),
+    ("Restablecer", "Reset"),
+    ("Reducir animaciones", "Reduce motion"),
+    ("Desactiva las transiciones de paneles, menús y desplazamientos.", "Disables panel, menu and scrolling transitions."),
+    ("Navegación por teclado", "Keyboard navigation"),
+    ("Permite recorrer la barra lateral y el historial del chat con las flechas.", "Lets you move through the sidebar and chat history with the arrow keys."),
+    ("Atajos de teclado", "Keyboard shortcuts"),
+    ("Elemento anterior o siguiente de la barra lateral", "Previous or next sidebar item"),
+    ("Mensaje anterior o siguiente del historial", "Previous or next message in the history"),
+    ("Primer o último mensaje del historial", "First or last message in the history"),
+    ("Responder al mensaje resaltado", "Reply to the highlighted message"),
+    ("Quitar el resaltado del historial", "Clear the history highlight"),
+    ("Recorrer los controles de la vista", "Cycle through the view controls"),
+    ("Aumentar o reducir la escala", "Increase or decrease the scale"),
+    ("Copiar mensaje al portapapeles", "Copy message to clipboard"),
+    ("Citar mensaje en el input", "Quote message in the input"),
+    ("Responder a este mensaje", "Reply to this message"),
+    ("Reutilizar este mensaje", "Reuse this message"),
+    ("Editar y reenviar desde aquí", "Edit and resend from here"),
+    ("Regenerar respuesta", "Regenerate response"),
+    ("Regenerar con otro proveedor", "Regenerate with another provider"),
+    ("Copiar bloque de código", "Copy code block"),
+    ("Evaluación por lotes", "Batch evaluation"),
+    ("Evaluación", "Evaluation"),
+    ("Lanza lotes de prompts contra varios proveedores y compara resultados.", "Run prompt batches against several providers and compare the results."),
+    ("Envía un fichero JSONL o CSV de prompts a varios destinos y guarda las respuestas con sus métricas.", "Send a JSONL or CSV prompt file to several targets and save the responses with their metrics."),
+    ("Fichero de prompts", "Prompts file"),
+    ("Fichero de resultados", "Results file"),
+    ("Destinos", "Targets"),
+    ("Ejecutar evaluación", "Run evaluation"),
+    ("Cancelar", "Cancel"),
+    ("Destino", "Target"),
+    ("Errores", "Errors"),
+    ("Latencia media", "Average latency"),
+    ("Tokens de entrada", "Input tokens"),
+    ("Tokens de salida", "Output tokens"),
+    ("{0} tokens de entrada · {1} tokens de salida", "{0} input tokens · {1} output tokens"),
+    // Compartir conversación
+    ("Compartir conversación", "Share conversation"),
+    ("Cifra la conversación con una frase de paso para enviarla a otra instancia de JungleMonkAI sin pasar por un servidor.", "Encrypt the conversation with a passphrase to send it to another JungleMonkAI instance without a server."),
+    ("Frase de paso", "Passphrase"),
+    ("Confirmación", "Confirmation"),
+    ("Solo al exportar", "Only when exporting"),
+    ("Exportar", "Export"),
+    ("Exportar cifrada", "Export encrypted"),
+    ("Importar", "Import"),
+    ("La frase de paso necesita al menos {0} caracteres.", "The passphrase needs at least {0} characters."),
+    ("{0} mensajes exportados.", "{0} messages exported."),
+    ("{0} mensajes añadidos al historial.", "{0} messages added to the history."),
+    ("Exporta o importa un archivo cifrado con frase de paso", "Export or import a passphrase-encrypted file"),
+    // Base de conocimientos de Jarvis
+    ("Preferencias › Modelos locales › Base de conocimientos", "Preferences › Local models › Knowledge base"),
+    ("Edita las entradas que enrutan los prompts de Jarvis, prueba el enrutado y recárgalas en caliente.", "Edit the entries that route Jarvis prompts, test the routing and hot-reload them."),
+    ("Conocimiento", "Knowledge"),
+    ("Cada entrada agrupa frases de activación: Jarvis compara el prompt con su huella semántica y responde con la entrada más parecida.", "Each entry groups trigger phrases: Jarvis compares the prompt with their semantic fingerprint and answers with the closest entry."),
+    ("Añadir entrada", "Add entry"),
+    ("Nueva entrada", "New entry"),
+    ("Restaurar predeterminadas", "Restore defaults"),
+    ("Sustituye las entradas por la base curada de Jarvis.", "Replace the entries with the curated Jarvis knowledge base."),
+    ("{0} frases", "{0} phrases"),
+    ("Nombre", "Name"),
+    ("Persona integrada", "Built-in persona"),
+    ("Ninguna", "None"),
+    ("Frases de activación (una por línea)", "Trigger phrases (one per line)"),
+    ("hola jarvis", "hello jarvis"),
+    ("Respuesta", "Response"),
+    ("Vacía para usar la persona integrada", "Leave empty to use the built-in persona"),
+    ("Documentos fuente (una ruta por línea)", "Source documents (one path per line)"),
+    ("Eliminar entrada", "Delete entry"),
+    ("Probar enrutado", "Test routing"),
+    ("Escribe un prompt de prueba", "Type a test prompt"),
+    ("Probar", "Test"),
+    ("Aplicar y recargar", "Apply and reload"),
+    ("Descartar cambios", "Discard changes"),
+    // Adaptadores LoRA
+    ("Adaptadores LoRA", "LoRA adapters"),
+    ("Buscar adaptadores", "Scan for adapters"),
+    ("Coloca adaptadores PEFT (adapter_config.json y adapter_model.safetensors) en la carpeta 'adapters' del modelo o en el directorio de instalación.", "Place PEFT adapters (adapter_config.json and adapter_model.safetensors) in the model's 'adapters' folder or in the install directory."),
+    ("Entrenado sobre {0}", "Trained on {0}"),
+    ("Sin modelo base declarado", "No base model declared"),
+    ("⚠ otro modelo base", "⚠ different base model"),
+    ("La selección es propia de esta conversación y se fusiona con los pesos al recargar el modelo.", "The selection belongs to this conversation and is merged into the weights when the model reloads."),
+    ("Activos en el runtime: {0}", "Active in the runtime: {0}"),
+    ("Activos en el runtime: ninguno", "Active in the runtime: none"),
+    // Embeddings
+    ("Modelo de embeddings", "Embedding model"),
+    ("Los embeddings alimentan la búsqueda semántica de la cabecera y los recuerdos que se adjuntan a los prompts.", "Embeddings power the header's semantic search and the memories attached to prompts."),
+    ("Modelo local (Jarvis)", "Local model (Jarvis)"),
+    ("Modelo", "Model"),
+    ("Búsqueda semántica en la conversación", "Semantic search in the conversation"),
+    ("Recuerdos por prompt", "Memories per prompt"),
+    ("Mensajes antiguos más parecidos al prompt que se envían como memoria. Requiere la memoria contextual activa; 0 la desactiva.", "Older messages most similar to the prompt that are sent as memory. Requires contextual memory; 0 disables it."),
+    ("Probar embeddings", "Test embeddings"),
+    ("{0} textos indexados", "{0} texts indexed"),
+    ("Coincidencias semánticas", "Semantic matches"),
+    ("Ir a la conversación", "Go to conversation"),
+    // Semantic search mode
+    ("Cmd/Ctrl+K · Buscar por significado en chats, README y conocimiento", "Cmd/Ctrl+K · Search by meaning across chats, READMEs and knowledge"),
+    ("Búsqueda por palabras", "Keyword search"),
+    ("Búsqueda semántica", "Semantic search"),
+    ("Mensajes", "Messages"),
+    ("Documentos de proyectos", "Project documents"),
+    ("Tarjetas de conocimiento", "Knowledge cards"),
+    ("Sin coincidencias por significado", "No matches by meaning"),
+    ("Desactiva el modo semántico para buscar por palabras", "Turn off semantic mode to search by keyword"),
+    ("Escribe al menos tres caracteres", "Type at least three characters"),
+    ("Buscando por significado…", "Searching by meaning…"),
+    ("Esperando", "Waiting"),
+    ("Abrir recurso", "Open resource"),
+    ("Recientes", "Recent"),
+    ("Conversaciones", "Conversations"),
+    ("Documentos y recursos", "Documents and resources"),
+    ("Pulsa Enter para repetir", "Press Enter to repeat"),
+    ("Ir al historial de chat", "Go to chat history"),
+    ("Iniciar workflow", "Start workflow"),
+    // Model quick actions
+    ("El próximo mensaje a {0} usará {1}", "The next message to {0} will use {1}"),
+    ("Usar modelo por defecto", "Use default model"),
+    ("Acciones rápidas personalizadas", "Custom quick actions"),
+    ("Aparecen en la tarjeta del modelo dentro del catálogo y abren el chat con la plantilla dirigida a ese modelo.", "They appear on the model's catalog card and open the chat with the template routed to that model."),
+    ("Eliminar", "Remove"),
+    ("Elige un modelo", "Choose a model"),
+    ("Nombre de la acción", "Action name"),
+    ("Plantilla del prompt", "Prompt template"),
+    ("Añadir acción", "Add action"),
+    ("Acción rápida añadida.", "Quick action added."),
+    // Composer history
+    ("Historial", "History"),
+    ("Busca entre los mensajes enviados", "Search your sent messages"),
+    ("Historial de mensajes", "Message history"),
+    ("Buscar en el historial", "Search history"),
+    ("Ningún mensaje coincide con la búsqueda.", "No message matches the search."),
+    // Bookmarks
+    ("Mensaje fijado", "Pinned message"),
+    ("Fijar mensaje", "Pin message"),
+    ("Soltar mensaje", "Unpin message"),
+    ("Mensajes fijados ({0})", "Pinned messages ({0})"),
+    ("Ir al mensaje", "Go to message"),
+    ("Soltar", "Unpin"),
+    ("Marcadores", "Bookmarks"),
+    ("Mensajes fijados en todas las conversaciones. Los de la conversación abierta te llevan de vuelta al historial.", "Pinned messages from every conversation. Those from the open conversation take you back to the history."),
+    ("Filtrar marcadores", "Filter bookmarks"),
+    ("Exportar a Markdown", "Export to Markdown"),
+    ("{0} marcadores exportados.", "{0} bookmarks exported."),
+    ("Todavía no hay mensajes fijados. Usa el icono de chincheta de un mensaje para guardarlo aquí.", "No pinned messages yet. Use a message's pin icon to keep it here."),
+    ("Conversación actual", "Current conversation"),
+    ("Conversación del {0}", "Conversation from {0}"),
+    ("fijado el {0}", "pinned on {0}"),
+    ("Recursos › Marcadores", "Resources › Bookmarks"),
+    ("Reúne los mensajes fijados en todas las conversaciones para volver a ellos o exportarlos.", "Collects pinned messages from every conversation so you can return to them or export them."),
+    ("Mensajes fijados en todas las conversaciones", "Pinned messages across conversations"),
+    // Response cache
+    ("Caché de respuestas", "Response cache"),
+    ("Las consultas idénticas a un proveedor (mismo modelo, prompt y parámetros) se sirven desde disco sin volver a llamar a la API.", "Identical provider queries (same model, prompt and parameters) are served from disk without calling the API again."),
+    ("Reutilizar respuestas en caché", "Reuse cached responses"),
+    ("Caducidad", "Expiry"),
+    ("Máximo de entradas", "Maximum entries"),
+    ("Tamaño máximo", "Maximum size"),
+    ("{0} respuestas guardadas · {1}", "{0} stored responses · {1}"),
+    ("Aciertos: {0} · Fallos: {1} · Tasa de acierto: {2}%", "Hits: {0} · Misses: {1} · Hit rate: {2}%"),
+    ("Actualizar", "Refresh"),
+    ("Vaciar caché", "Clear cache"),
+    ("Sin caché", "No cache"),
+    ("El próximo mensaje se enviará al proveedor aunque haya una respuesta en caché", "The next message is sent to the provider even if a cached response exists"),
+    ("Ignorar respuestas en caché", "Ignore cached responses"),
+    // File watcher
+    ("Vigilante de ficheros", "File watcher"),
+    ("Activo", "Active"),
+    ("Los cambios en las rutas vigiladas llegan al flujo de eventos y disparan los listeners de tipo «Cambio de fichero». Usa la acción workflow:<id> para lanzar un workflow.", "Changes in watched paths reach the event stream and fire “File change” listeners. Use the workflow:<id> action to launch a workflow."),
+    ("Vigilar", "Watch"),
+    ("Agrupar cambios durante", "Group changes within"),
+    ("Cambios recientes ({0})", "Recent changes ({0})"),
+    ("Creado", "Created"),
+    ("Modificado", "Modified"),
+    ("Eliminado", "Removed"),
+    ("Cambio de fichero", "File change"),
+    ("Quitar", "Remove"),
+    // Knowledge re-indexing
+    ("Reindexar base de conocimiento", "Re-index knowledge base"),
+    ("Programa por proyecto la actualización del índice semántico. Solo se releen los documentos que han cambiado desde la última pasada.", "Schedule semantic index updates per project. Only documents changed since the last pass are read again."),
+    ("Elige un proyecto", "Choose a project"),
+    ("Programar", "Schedule"),
+    // Conversation threads
+    ("Bifurcar la conversación aquí", "Fork the conversation here"),
+    ("Hilo: {0} ({1} hilos)", "Thread: {0} ({1} threads)"),
+    ("Hilos y puntos de control", "Threads and checkpoints"),
+    ("{0} mensajes", "{0} messages"),
+    ("Restaurar", "Restore"),
+    ("Nombre del punto de control", "Checkpoint name"),
+    ("Guardar punto de control", "Save checkpoint"),
+    // Structured output
+    ("Pide respuestas JSON que cumplan un esquema", "Ask for JSON replies that match a schema"),
+    ("Salida JSON estructurada", "Structured JSON output"),
+    ("Los mensajes enviados a un proveedor incluyen este JSON Schema. La respuesta se valida y, si no lo cumple, se pide al modelo que la corrija.", "Messages sent to a provider include this JSON Schema. The reply is validated and, if it does not match, the model is asked to fix it."),
+    ("Activar", "Enable"),
+    ("Desactivar", "Disable"),
+    ("Usar ejemplo", "Use example"),
+    ("JSON válido según el esquema", "JSON valid against the schema"),
+    ("Copiar JSON", "Copy JSON"),
+    // Data tables
+    ("Gráfico", "Chart"),
+    ("Barras", "Bars"),
+    ("Líneas", "Lines"),
+    ("Ordenar por esta columna", "Sort by this column"),
+    // Cost estimates
+    ("Envío ≈${0}", "Send ≈${0}"),
+    ("Este envío costará ≈${0} (umbral ${1}).", "This send will cost ≈${0} (threshold ${1})."),
+    ("Enviar igualmente", "Send anyway"),
+    ("Confirmar envíos que cuesten más de", "Confirm sends costing more than"),
+    ("Con 0 los envíos nunca piden confirmación.", "With 0, sends never ask for confirmation."),
+    // Provider budgets
+    ("Presupuesto de gasto", "Spending budget"),
+    ("El gasto se estima con los precios del catálogo para cada respuesta recibida.", "Spend is estimated from catalog prices for every reply received."),
+    ("Diario", "Daily"),
+    ("Mensual", "Monthly"),
+    ("Avisar al", "Alert at"),
+    ("Bloquear las llamadas cuando se agote el presupuesto", "Block calls when the budget runs out"),
+    // Jarvis performance
+    ("Aún no hay mediciones. Envía mensajes a Jarvis para registrar su rendimiento.", "No measurements yet. Send messages to Jarvis to record its performance."),
+    ("Comparativa por modelo", "Comparison by model"),
+    ("Precisión", "Precision"),
+    ("Llamadas", "Calls"),
+    ("Carga", "Load"),
+    ("Tokens/s", "Tokens/s"),
+    ("Prompt / respuesta", "Prompt / reply"),
+    ("Memoria máx.", "Peak memory"),
+    ("Todos los modelos", "All models"),
+    ("Borrar mediciones", "Clear measurements"),
+    ("Se necesitan al menos dos llamadas para dibujar el historial.", "At least two calls are needed to draw the history."),
+    ("Velocidad (tokens/s)", "Speed (tokens/s)"),
+    ("Latencia (ms)", "Latency (ms)"),
+    ("Longitud en tokens", "Length in tokens"),
+    ("Memoria residente (MB)", "Resident memory (MB)"),
+    // Parallel model downloads
+    ("Descargas simultáneas", "Simultaneous downloads"),
+    (
+        "Archivos del modelo que se descargan a la vez. Las descargas interrumpidas se reanudan al reintentar la instalación.",
+        "Model files downloaded at the same time. Interrupted downloads resume when the installation is retried.",
+    ),
+    // Model updates
+    ("Buscar actualizaciones", "Check for updates"),
+    (
+        "Compara la revisión instalada de cada modelo de Hugging Face con la última publicada.",
+        "Compares the installed revision of each Hugging Face model with the latest published one.",
+    ),
+    ("No hay modelos de Hugging Face que comprobar.", "There are no Hugging Face models to check."),
+    ("Última comprobación: {0}", "Last checked: {0}"),
+    (
+        "Última comprobación: {0} · {1} con actualizaciones",
+        "Last checked: {0} · {1} with updates",
+    ),
+    (
+        "Descarga solo los archivos que cambiaron en la última revisión.",
+        "Downloads only the files that changed in the latest revision.",
+    ),
+    ("Comprobando revisión…", "Checking revision…"),
+    ("Descargando archivos modificados…", "Downloading changed files…"),
+    ("Al día · revisión {0}", "Up to date · revision {0}"),
+    ("Desactualizado · {0} → {1}", "Outdated · {0} → {1}"),
+    ("Ver cambios", "View changes"),
+    ("No se pudo comprobar: {0}", "Could not check: {0}"),
+    ("Revisión {0}", "Revision {0}"),
+    // Model trash
+    ("Mover a la papelera ({0} días)", "Move to trash ({0} days)"),
+    ("Mover a la papelera", "Move to trash"),
+    (
+        "La papelera está desactivada (plazo de 0 días).",
+        "The trash is disabled (0-day retention).",
+    ),
+    ("Borrar del disco ({0})", "Delete from disk ({0})"),
+    ("Papelera", "Trash"),
+    ("Conservar durante", "Keep for"),
+    (" días", " days"),
+    (
+        "Con 0 días los modelos se borran del disco al desinstalarlos.",
+        "With 0 days, models are deleted from disk when uninstalled.",
+    ),
+    ("Vaciar papelera", "Empty trash"),
+    ("La papelera está vacía.", "The trash is empty."),
+    ("{0} · se borra el {1}", "{0} · deleted on {1}"),
+    ("Borrar", "Delete"),
+    ("Carpetas sin registrar", "Unregistered folders"),
+    (
+        "Estas carpetas contienen modelos que no figuran en la biblioteca.",
+        "These folders contain models that are not in the library.",
+    ),
+    ("Registrar", "Register"),
+    ("Buscar carpetas sin registrar", "Find unregistered folders"),
+    (
+        "Todas las carpetas de modelos están registradas.",
+        "All model folders are registered.",
+    ),
+    // Command engine
+    (
+        "Estado del sistema; con 'debug' muestra el diagnóstico completo.",
+        "System status; with 'debug' shows the full diagnostics.",
+    ),
+    // Command pipelines
+    (
+        "Lanza un workflow de automatización. Los argumentos clave=valor sustituyen los {clave} de los pasos y la salida de un pipeline llega como {input}.",
+        "Runs an automation workflow. key=value arguments replace the {key} markers in its steps and pipeline output arrives as {input}.",
+    ),
+    (
+        "Resume con Jarvis la salida del comando anterior o el texto indicado.",
+        "Summarizes the previous command's output, or the given text, with Jarvis.",
+    ),
+    // Custom command editor
+    ("Paleta de comandos", "Command palette"),
+    (
+        "Asocia comandos de barra a funciones predefinidas, plantillas de prompt, workflows o guiones de comandos.",
+        "Link slash commands to built-in functions, prompt templates, workflows or command scripts.",
+    ),
+    ("Editar", "Edit"),
+    ("Workflow inexistente", "Missing workflow"),
+    ("Funciones disponibles", "Available functions"),
+    ("Editar comando", "Edit command"),
+    ("Nuevo comando", "New command"),
+    ("Disparador", "Trigger"),
+    ("Tipo", "Type"),
+    ("Función predefinida", "Built-in function"),
+    ("Plantilla de prompt", "Prompt template"),
+    ("Workflow", "Workflow"),
+    ("Guion de comandos", "Command script"),
+    ("Función", "Function"),
+    ("Proveedor", "Provider"),
+    ("Plantilla", "Template"),
+    (
+        "Marcadores: {args}, {0}, {1}…, {input} (salida de un pipeline) y {clave} para argumentos clave=valor.",
+        "Placeholders: {args}, {0}, {1}…, {input} (pipeline output) and {key} for key=value arguments.",
+    ),
+    ("Argumentos de prueba", "Test arguments"),
+    ("Vista previa", "Preview"),
+    ("Faltan valores para: {0}", "Missing values for: {0}"),
+    ("Guardar cambios", "Save changes"),
+    ("Añadir comando", "Add command"),
+    // Conversation settings
+    ("Sin mención: {0}", "Without mention: {0}"),
+    ("Ajustes de la conversación", "Conversation settings"),
+    (
+        "Se guardan con la conversación y se aplican a los mensajes sin @mención.",
+        "Saved with the conversation and applied to messages without an @mention.",
+    ),
+    ("Jarvis (local)", "Jarvis (local)"),
+    ("Temperatura", "Temperature"),
+    ("Por defecto del proveedor", "Provider default"),
+    ("Máx. tokens", "Max tokens"),
+    ("Persona", "Persona"),
+    (
+        "Instrucciones de sistema, p. ej. «Eres un revisor de código exigente».",
+        "System instructions, e.g. “You are a demanding code reviewer”.",
+    ),
+    ("Guardar", "Save"),
+    // Keyboard shortcuts
+    ("Chat", "Chat"),
+    ("Vista", "View"),
+    (
+        "Reasigna los atajos del chat y la navegación y detecta combinaciones en conflicto.",
+        "Reassign chat and navigation shortcuts and detect conflicting combinations.",
+    ),
+    ("Enviar el mensaje", "Send the message"),
+    ("Abrir la paleta de búsqueda", "Open the search palette"),
+    ("Saltar al mensaje anterior", "Jump to the previous message"),
+    ("Saltar al mensaje siguiente", "Jump to the next message"),
+    ("Pestaña Chat", "Chat tab"),
+    ("Pestaña Cron", "Cron tab"),
+    ("Pestaña Actividad", "Activity tab"),
+    ("Pestaña Depuración", "Debug tab"),
+    ("Cancelar la llamada en curso", "Cancel the pending call"),
+    ("Elemento anterior de la barra lateral", "Previous sidebar item"),
+    ("Elemento siguiente de la barra lateral", "Next sidebar item"),
+    ("Mostrar los atajos de teclado", "Show keyboard shortcuts"),
+    ("Mensaje anterior del historial resaltado", "Previous message in the highlighted history"),
+    ("Mensaje siguiente del historial resaltado", "Next message in the highlighted history"),
+    ("Primer mensaje del historial", "First message in the history"),
+    ("Último mensaje del historial", "Last message in the history"),
+    ("Enviar o responder al mensaje resaltado", "Send, or reply to the highlighted message"),
+    ("Salto de línea en el editor", "Line break in the editor"),
+    ("Siguiente control de la vista", "Next control in the view"),
+    ("Control anterior de la vista", "Previous control in the view"),
+    ("Aumentar la escala", "Increase scale"),
+    ("Reducir la escala", "Decrease scale"),
+    ("Copiar", "Copy"),
+    ("Pegar", "Paste"),
+    ("Cortar", "Cut"),
+    ("Deshacer", "Undo"),
+    ("Seleccionar todo", "Select all"),
+    ("Atajos fijos", "Fixed shortcuts"),
+    ("Configurar atajos", "Configure shortcuts"),
+    ("Reasignación cancelada.", "Reassignment cancelled."),
+    ("Llamada al proveedor cancelada.", "Provider call cancelled."),
+    ("Intro envía el mensaje", "Enter sends the message"),
+    (
+        "Si se desactiva, Intro añade un salto de línea y el mensaje se envía con el atajo de envío.",
+        "When disabled, Enter adds a line break and the message is sent with the send shortcut.",
+    ),
+    ("Acción", "Action"),
+    ("Categoría", "Category"),
+    ("Atajo", "Shortcut"),
+    ("Pulsa una combinación… (Esc cancela)", "Press a combination… (Esc cancels)"),
+    ("Haz clic para reasignar", "Click to reassign"),
+    ("Predeterminado", "Default"),
+    (
+        "{0} combinaciones en conflicto. Solo se ejecuta la primera acción de la lista.",
+        "{0} conflicting combinations. Only the first action in the list runs.",
+    ),
+    ("Restablecer todos", "Reset all"),
+    ("Ver chuleta", "Show cheat sheet"),
+    ("También asignado a: {0}", "Also assigned to: {0}"),
+    ("Coincide con un atajo fijo: {0}", "Matches a fixed shortcut: {0}"),
+    (
+        "Escribe tu mensaje o comando. Usa Shift+Enter para saltos de línea.",
+        "Type your message or command. Use Shift+Enter for line breaks.",
+    ),
+    (
+        "Escribe tu mensaje o comando. Envíalo con {0}.",
+        "Type your message or command. Send it with {0}.",
+    ),
+    // Session recovery
+    ("Recuperar la sesión anterior", "Recover previous session"),
+    (
+        "JungleMonkAI no se cerró correctamente. Puedes recuperar la conversación, el borrador y la vista en la que estabas.",
+        "JungleMonkAI did not close properly. You can recover the conversation, the draft and the view you were in.",
+    ),
+    ("Última copia: {0} · {1} mensajes.", "Last snapshot: {0} · {1} messages."),
+    ("Informe del fallo: {0}", "Crash report: {0}"),
+    ("Descartar", "Discard"),
+    ("Sesión restaurada con {0} mensajes.", "Session restored with {0} messages."),
+    // System monitor
+    ("Uso de recursos en tiempo real", "Live resource usage"),
+    ("Media de los últimos {0} s: {1} %", "Average over the last {0} s: {1} %"),
+    ("JungleMonkAI: {0} de {1} permitidos", "JungleMonkAI: {0} of {1} allowed"),
+    ("VRAM {0} / {1}", "VRAM {0} / {1}"),
+    ("Sin datos: no se encontró nvidia-smi", "No data: nvidia-smi not found"),
+    ("{0} Disco {1} libres", "{0} Disk {1} free"),
+    ("{0} de {1} en {2}", "{0} of {1} on {2}"),
+    ("⏸ Generación local en pausa", "⏸ Local generation paused"),
+    (
+        "Se reanudará cuando la memoria baje del límite configurado",
+        "It resumes once memory drops below the configured limit",
+    ),
+    ("Midiendo recursos…", "Measuring resources…"),
+    // Catálogo de Anthropic
+    ("Actualizar lista", "Refresh list"),
+    (
+        "Consulta los modelos disponibles en la API de Anthropic.",
+        "Queries the models available in the Anthropic API.",
+    ),
+    // Entrada de voz
+    ("Backend de transcripción", "Transcription backend"),
+    ("Whisper local", "Local Whisper"),
+    ("OpenAI transcriptions", "OpenAI transcriptions"),
+    ("Ejecutable", "Executable"),
+    ("es, en… vacío para detectarlo", "es, en… empty to detect it"),
+    (
+        "Configura la API key de OpenAI en Proveedores › OpenAI para usar este backend.",
+        "Set the OpenAI API key in Providers › OpenAI to use this backend.",
+    ),
+    (
+        "Requiere la CLI de Whisper instalada (pip install openai-whisper). Si no la tienes, elige OpenAI.",
+        "Requires the Whisper CLI (pip install openai-whisper). If you don't have it, choose OpenAI.",
+    ),
+    ("Transcribir un audio", "Transcribe an audio file"),
+    ("Ruta de la nota de voz o del audio", "Path to the voice note or audio file"),
+    ("Transcribir", "Transcribe"),
+    (
+        "El texto se añade al compositor del chat. También puedes usar /transcribe <ruta>.",
+        "The text is added to the chat composer. You can also use /transcribe <path>.",
+    ),
+    ("Entrada de voz", "Voice input"),
+    (
+        "Elige cómo se transcriben las notas de voz y los audios adjuntos: Whisper local u OpenAI.",
+        "Choose how voice notes and attached audio are transcribed: local Whisper or OpenAI.",
+    ),
+    // Adjuntos
+    ("Describir imagen", "Describe image"),
+    ("Quitar adjunto", "Remove attachment"),
+    ("Imagen enviada a {0}.", "Image sent to {0}."),
+    // Auditoría
+    ("Auditoría", "Audit"),
+    (
+        "Efectos externos registrados en cada conversación",
+        "External side effects recorded for each conversation",
+    ),
+    (
+        "Cada llamada a un proveedor, comando, workflow y archivo escrito queda en un registro de solo anexado encadenado por hashes.",
+        "Every provider call, command, workflow and written file goes into an append-only, hash-chained log.",
+    ),
+    ("Llamada a proveedor", "Provider call"),
+    ("Archivo escrito", "File written"),
+    ("Comando ejecutado", "Command executed"),
+    ("Workflow lanzado", "Workflow triggered"),
+    ("Conversación", "Conversation"),
+    ("Todos los eventos", "All events"),
+    ("Buscar en el registro", "Search the log"),
+    ("Verificar integridad", "Verify integrity"),
+    (
+        "Cadena íntegra: {0} entradas verificadas.",
+        "Chain intact: {0} entries verified.",
+    ),
+    ("Cadena rota en la entrada #{0}: {1}.", "Chain broken at entry #{0}: {1}."),
+    (
+        "falta una entrada o está fuera de orden",
+        "an entry is missing or out of order",
+    ),
+    (
+        "no enlaza con la entrada anterior",
+        "it does not link to the previous entry",
+    ),
+    (
+        "el contenido no coincide con su hash",
+        "the content does not match its hash",
+    ),
+    (
+        "No hay eventos registrados en esta conversación.",
+        "No events recorded in this conversation.",
+    ),
+    (
+        "Revisa las llamadas, comandos, workflows y archivos escritos en cada conversación.",
+        "Review the calls, commands, workflows and files written in each conversation.",
+    ),
+    // Redacción
+    ("Redacción", "Redaction"),
+    (
+        "Oculta claves, correos y hosts internos de los prompts antes de enviarlos a proveedores remotos.",
+        "Hide keys, emails and internal hosts from prompts before sending them to remote providers.",
+    ),
+    ("Prompt redactado", "Prompt redacted"),
+    (
+        "Redactar los prompts enviados a proveedores remotos",
+        "Redact prompts sent to remote providers",
+    ),
+    (
+        "Las reglas usan la sintaxis de expresiones regulares del crate regex. Jarvis y los modelos locales reciben el texto original.",
+        "Rules use the regex crate's regular expression syntax. Jarvis and local models receive the original text.",
+    ),
+    ("Patrón", "Pattern"),
+    ("Reemplazo", "Replacement"),
+    ("Eliminar regla", "Delete rule"),
+    ("Añadir regla", "Add rule"),
+    ("Nueva regla", "New rule"),
+    ("La regla '{0}' no es válida: {1}", "Rule '{0}' is not valid: {1}"),
+    ("Probar las reglas", "Try the rules"),
+    (
+        "Pega un prompt para ver qué se redactaría",
+        "Paste a prompt to see what would be redacted",
+    ),
+    (
+        "{0} fragmentos se redactarán al enviar a proveedores remotos",
+        "{0} fragments will be redacted when sent to remote providers",
+    ),
+    // Proyectos solo locales
+    ("Solo local", "Local only"),
+    (
+        "El contenido de este proyecto solo podrá enviarse a Jarvis; los envíos a proveedores remotos que lo incluyan pedirán confirmación.",
+        "This project's content can only be sent to Jarvis; remote sends that include it will ask for confirmation.",
+    ),
+    (
+        "El mensaje incluye contenido de {0}, marcado como solo local. Pregunta a Jarvis o confirma que quieres enviarlo a un proveedor remoto.",
+        "The message includes content from {0}, marked as local only. Ask Jarvis or confirm you want to send it to a remote provider.",
+    ),
+    ("Enviar a remoto igualmente", "Send to remote anyway"),
+    // Explorador de código
+    ("Explorador de código", "Code explorer"),
+    ("Explorar código", "Browse code"),
+    (
+        "Selecciona líneas de un archivo (Mayús+clic para ampliar) y pregunta por ellas: la respuesta queda anclada al rango.",
+        "Select lines in a file (Shift+click to extend) and ask about them: the reply stays anchored to the range.",
+    ),
+    ("No hay proyectos locales conectados.", "No local projects connected."),
+    ("Proyecto", "Project"),
+    ("Filtrar archivos", "Filter files"),
+    ("Elige un archivo de la lista.", "Pick a file from the list."),
+    ("Selección: {0}", "Selection: {0}"),
+    ("Sin selección", "No selection"),
+    (
+        "¿Qué quieres saber de estas líneas?",
+        "What do you want to know about these lines?",
+    ),
+    ("Pedir la respuesta como parche", "Ask for the reply as a patch"),
+    ("Preguntar", "Ask"),
+    ("Parche sugerido para {0}", "Suggested patch for {0}"),
+    ("Guardar .patch", "Save .patch"),
+    (
+        "Abrir {0} en el explorador de código",
+        "Open {0} in the code explorer",
+    ),
+    ("Ver como parche", "View as patch"),
+    (
+        "Muestra el diff de la respuesta junto al archivo",
+        "Shows the reply's diff next to the file",
+    ),
+    // Resumen diario
+    ("Generar el resumen cada día", "Generate the digest every day"),
+    ("Redacta", "Written by"),
+    ("Calendario (.ics)", "Calendar (.ics)"),
+    ("Ruta del calendario exportado", "Path to the exported calendar"),
+    ("Incluir", "Include"),
+    ("Workflows ejecutados", "Workflows run"),
+    ("Notificaciones de GitHub", "GitHub notifications"),
+    ("Agenda", "Agenda"),
+    (
+        "Mostrar también una notificación de escritorio",
+        "Also show a desktop notification",
+    ),
+    ("Último resumen: {0}", "Last digest: {0}"),
+    ("Generar ahora", "Generate now"),
+    ("Abrir conversación", "Open conversation"),
+    ("Resumen diario", "Daily digest"),
+    // Informes
+    ("Informes", "Reports"),
+    ("Las plantillas son Markdown: cada {{modelo: instrucción}} lo redacta el proveedor elegido. También puedes usar {{fecha}}, {{hora}} y {{plantilla}}.", "Templates are Markdown: each {{modelo: instruction}} is written by the chosen provider. You can also use {{fecha}}, {{hora}} and {{plantilla}}."),
+    ("Plantillas", "Templates"),
+    ("Nueva plantilla", "New template"),
+    ("Todavía no hay plantillas. Crea una a partir del ejemplo.", "No templates yet. Create one from the example."),
+    ("bajo demanda", "on demand"),
+    ("último: {0}", "last: {0}"),
+    ("Directorio de salida", "Output directory"),
+    ("Carpeta de informes de la aplicación", "App reports folder"),
+    ("Programación (cron)", "Schedule (cron)"),
+    ("Vacío: solo bajo demanda", "Empty: on demand only"),
+    ("Confirmar cada informe en el repositorio Git del directorio", "Commit each report to the directory's Git repository"),
+    ("Guardar plantilla", "Save template"),
+    ("Informes generados", "Generated reports"),
+    ("Aún no se ha generado ningún informe.", "No report has been generated yet."),
+    ("Selecciona un informe para verlo.", "Select a report to preview it."),
+    // Acciones de texto
+    ("Transformar mensaje", "Transform message"),
+    ("Editar acciones…", "Edit actions…"),
+    ("Acción aplicada: {0}.", "Action applied: {0}."),
+    ("Haz clic derecho sobre un mensaje para aplicarle una de estas acciones. La respuesta llega como un mensaje nuevo del proveedor de la conversación.", "Right-click a message to apply one of these actions. The result arrives as a new message from the conversation's provider."),
+    ("Idioma de traducción", "Translation language"),
+    ("Prompt con {texto} e {idioma}", "Prompt with {texto} and {idioma}"),
+    ("Nueva acción", "New action"),
+    ("Restablecer predeterminadas", "Restore defaults"),
+    ("Explicar", "Explain"),
+    ("Traducir", "Translate"),
+    ("Reescribir en tono formal", "Rewrite formally"),
+    ("Extraer tareas", "Extract action items"),
+    // Idioma de respuesta
+    ("Auto ({0})", "Auto ({0})"),
+    ("Auto", "Auto"),
+    ("Idioma de respuesta", "Reply language"),
+    ("Automático (idioma del mensaje)", "Automatic (message language)"),
+    ("Idioma en que responden los proveedores en esta conversación", "Language providers reply in for this conversation"),
+    ("Automático", "Automatic"),
+    // Asistente de commits
+    ("Asistente de commits", "Commit assistant"),
+    ("Archivo abierto", "Open file"),
+    ("Todo el árbol de trabajo", "Whole working tree"),
+    ("Mensaje de commit", "Commit message"),
+    ("Descripción de PR", "PR description"),
+    // Historial de Git
+    ("¿Quién escribió esto?", "Who wrote this?"),
+    ("Historial del archivo", "File history"),
+    ("Cargar historial", "Load history"),
+    ("El archivo no tiene commits.", "The file has no commits."),
+    ("Explicar este cambio", "Explain this change"),
+    // Conflictos de fusión
+    ("Conflictos de fusión", "Merge conflicts"),
+    ("Buscar conflictos", "Find conflicts"),
+    ("Bloque en la línea {0}", "Hunk at line {0}"),
+    ("Usar la nuestra", "Use ours"),
+    ("Usar la suya", "Use theirs"),
+    ("Ambas", "Both"),
+    ("Proponer fusión", "Propose merge"),
+    ("Propuesta del modelo", "Model proposal"),
+    ("Aceptar propuesta", "Accept proposal"),
+    ("Escribir resolución ({0} de {1} bloques)", "Write resolution ({0} of {1} hunks)"),
+    // Contenido de archivos
+    ("Solo el principio de {0}", "Only the beginning of {0}"),
+    // Estado de los remotos
+    ("Traer remotos", "Fetch remotes"),
+    (
+        "Ejecuta git fetch y compara cada rama con su remota sin tocar el árbol de trabajo.",
+        "Runs git fetch and compares each branch with its remote without touching the working tree.",
+    ),
+    ("Actualizado a las {0}", "Updated at {0}"),
+    ("Commits entrantes ({0})", "Incoming commits ({0})"),
+    ("Las ramas divergieron desde {0}", "The branches diverged at {0}"),
+    // Progreso de descargas
+    ("{0}: {1} de {2} · {3}/s", "{0}: {1} of {2} · {3}/s"),
+    ("{0}: preparando la descarga…", "{0}: preparing the download…"),
+    (
+        "Lo ya descargado se conserva y se reanuda al volver a instalar el modelo.",
+        "What was already downloaded is kept and resumed when the model is installed again.",
+    ),
+    // Uso de disco de los modelos
+    ("Uso de disco", "Disk usage"),
+    ("Medir", "Measure"),
+    ("Simular limpieza", "Preview cleanup"),
+    (
+        "Muestra qué descargas a medias y modelos caducados de la papelera se borrarían.",
+        "Shows which partial downloads and expired trashed models would be deleted.",
+    ),
+    ("Limpiar", "Clean up"),
+    (
+        "{0} en total · instalados {1} · papelera {2} · sin registrar {3} · descargas a medias {4}",
+        "{0} in total · installed {1} · trash {2} · unregistered {3} · partial downloads {4}",
+    ),
+    // Ediciones externas de la configuración
+    ("Conflicto en la configuración", "Configuration conflict"),
+    (
+        "config.json se editó fuera de la aplicación y estas secciones también cambiaron aquí. Los guardados quedan en pausa hasta que elijas.",
+        "config.json was edited outside the app and these sections also changed here. Saving is paused until you choose.",
+    ),
+    ("Secciones: {0}", "Sections: {0}"),
+    ("Usar el fichero", "Use the file"),
+    ("Conservar los de la aplicación", "Keep the app's values"),
+    // Ventanas aparte
+    ("Abrir en ventana aparte", "Open in separate window"),
+    ("Traer a la ventana principal", "Move back to main window"),
+    ("Esta vista está abierta en otra ventana.", "This view is open in another window."),
+    // Métricas de uso
+    ("Preferencias › Sistema › Uso", "Preferences › System › Usage"),
+    ("Uso", "Usage"),
+    ("Consulta tus propias métricas de uso: comandos, proveedores, tokens y latencias. Se guardan solo en este equipo.", "Review your own usage metrics: commands, providers, tokens and latencies. They are stored only on this computer."),
+    ("Registrar mis métricas de uso", "Record my usage metrics"),
+    ("Las métricas se guardan solo en este equipo y nunca se envían a ningún servidor.", "Metrics are stored only on this computer and are never sent to any server."),
+    ("Conservar", "Keep"),
+    ("{0} días", "{0} days"),
+    ("Borrar métricas", "Clear metrics"),
+    ("Aún no hay métricas registradas.", "No metrics recorded yet."),
+    ("Llamadas por día", "Calls per day"),
+    ("Tokens por día", "Tokens per day"),
+    ("Comandos más usados", "Most used commands"),
+    ("Ejecuciones", "Runs"),
+    ("Registro local de métricas de uso activado.", "Local usage metrics enabled."),
+    ("Registro local de métricas de uso desactivado.", "Local usage metrics disabled."),
+    ("Métricas de uso borradas.", "Usage metrics cleared."),
+    // Cadenas de tareas
+    ("Tras {0}", "After {0}"),
+    ("Omitida: {0}", "Skipped: {0}"),
+    ("Cadena de dependencias", "Dependency chain"),
+    ("Tarea no disponible", "Task not available"),
+    ("Esta tarea no forma parte de ninguna cadena.", "This task is not part of any chain."),
+    ("Se lanza cuando terminan bien:", "Runs when these finish successfully:"),
+    ("Quitar dependencia", "Remove dependency"),
+    ("Elige una tarea previa", "Choose a prerequisite task"),
+    ("Añadir dependencia", "Add dependency"),
+    ("Omitido", "Skipped"),
+    // Calendario del planificador
+    ("Semana", "Week"),
+    ("Mes", "Month"),
+    ("Calendario de ejecuciones", "Run calendar"),
+    ("Hoy", "Today"),
+    ("⚠ {0} ejecuciones coinciden con otras en la misma media hora.", "⚠ {0} runs collide with others in the same half hour."),
+    ("Lun", "Mon"),
+    ("Mar", "Tue"),
+    ("Mié", "Wed"),
+    ("Jue", "Thu"),
+    ("Vie", "Fri"),
+    ("Sáb", "Sat"),
+    ("Dom", "Sun"),
+    ("Abrir la tarea en el detalle", "Open the task in the detail panel"),
+    ("Recordatorio", "Reminder"),
+    ("+{0} más", "+{0} more"),
+    ("No hay ejecuciones previstas en este periodo.", "No runs planned in this period."),
+    // Notificaciones
+    ("Preferencias › Sistema › Notificaciones", "Preferences › System › Notifications"),
+    ("Notificaciones", "Notifications"),
+    ("Decide qué avisos del planificador, los proveedores y las integraciones llegan a la pantalla o al sistema, y cuándo guardar silencio.", "Choose which scheduler, provider and integration alerts reach the screen or the system, and when to stay quiet."),
+    ("Horas de silencio", "Quiet hours"),
+    ("Desde", "From"),
+    ("hasta", "to"),
+    ("Mostrar los errores en pantalla aunque sea hora de silencio", "Show errors on screen even during quiet hours"),
+    ("Ahora mismo es hora de silencio: los avisos solo quedan en el registro.", "Quiet hours are on right now: alerts only go to the log."),
+    ("Todos los avisos quedan siempre en el registro de actividad.", "Every alert is always kept in the activity log."),
+    ("Canal", "Channel"),
+    ("Gravedad mínima", "Minimum severity"),
+    ("Planificador", "Scheduler"),
+    ("Integraciones", "Integrations"),
+    ("Solo registro", "Log only"),
+    ("Aviso en pantalla", "On-screen toast"),
+    ("Notificación del sistema", "System notification"),
+    ("Información", "Info"),
+    ("Advertencia", "Warning"),
+    ("Error", "Error"),
+    // Modelos por alias
+    ("Modelos por alias", "Models by alias"),
+    ("Cada alias responde con su modelo; los menos usados se descargan para respetar el límite de memoria.", "Each alias answers with its own model; the least used ones are unloaded to stay within the memory limit."),
+    ("en memoria", "loaded"),
+    ("no instalado", "not installed"),
+    ("Quitar alias", "Remove alias"),
+    ("Selecciona un modelo instalado", "Pick an installed model"),
+    ("Añadir alias", "Add alias"),
+    ("{0} modelos por alias en memoria ({1}).", "{0} alias models loaded ({1})."),
+    ("Descargar", "Unload"),
+    // Cuantización de modelos
+    ("Cuantizar", "Quantize"),
+    ("8 bits", "8-bit"),
+    ("4 bits", "4-bit"),
+    ("Crea una variante GGUF más ligera y la añade a la biblioteca.", "Creates a lighter GGUF variant and adds it to the library."),
+    ("Variante cuantizada de {0}: {1}% del original ({2}).", "Quantized variant of {0}: {1}% of the original ({2})."),
+    ("Variante cuantizada de {0}.", "Quantized variant of {0}."),
+    ("A {0}: {1}/{2} tensores", "To {0}: {1}/{2} tensors"),
+    // Filtros de seguridad
+    ("Filtros de seguridad", "Safety filters"),
+    ("Según preferencias", "Follow preferences"),
+    ("Activados", "On"),
+    ("Desactivados", "Off"),
+    ("Avisar", "Warn"),
+    ("Redactar", "Redact"),
+    ("Bloquear", "Block"),
+    ("Prompt", "Prompt"),
+    ("Filtrar los prompts y las respuestas de Jarvis y los modelos locales", "Filter prompts and replies of Jarvis and local models"),
+    ("Cada conversación puede activarlos o desactivarlos desde sus ajustes. Las palabras se separan por comas; las expresiones regulares usan la sintaxis del crate regex.", "Each conversation can turn them on or off from its settings. Keywords are comma-separated; regular expressions use the regex crate syntax."),
+    ("Texto de reemplazo", "Replacement text"),
+    ("Regex", "Regex"),
+    ("Clasificador por similitud con temas vetados", "Classifier by similarity to banned topics"),
+    ("Usa el backend de embeddings configurado y compara cada texto con la descripción de cada tema.", "Uses the configured embeddings backend and compares each text with every topic description."),
+    ("Similitud mínima", "Minimum similarity"),
+    ("Eliminar tema", "Delete topic"),
+    ("Añadir tema", "Add topic"),
+    ("Pega una respuesta para ver cómo quedaría (sin el clasificador)", "Paste a reply to see how it would look (without the classifier)"),
+    ("Ninguna regla coincide.", "No rule matches."),
+    ("Bloqueado por '{0}'.", "Blocked by '{0}'."),
+    ("{0}: {1} coincidencias.", "{0}: {1} matches."),
+    ("Coincidencias de la sesión", "Session matches"),
+    ("Vaciar", "Clear"),
+    ("Sin coincidencias. También quedan en el registro de auditoría.", "No matches. They are also kept in the audit log."),
+    ("Filtro de seguridad", "Safety filter"),
+    ("Preferencias › Modelos locales › Filtros de seguridad", "Preferences › Local models › Safety filters"),
+    ("Bloquea, avisa o redacta lo que Jarvis recibe y responde con listas de palabras, expresiones regulares y un clasificador opcional.", "Block, warn about or redact what Jarvis receives and answers using keyword lists, regular expressions and an optional classifier."),
+    ("Filtros", "Filters"),
+    // Perfiles de contexto por proyecto
+    ("Perfil de contexto de «{0}»", "Context profile for “{0}”"),
+    ("Se aplica al seleccionar el proyecto. Los ajustes propios de cada conversación tienen prioridad.", "Applied when the project is selected. Each conversation's own settings take precedence."),
+    ("Instrucciones de sistema para este proyecto", "System instructions for this project"),
+    ("Fuentes de conocimiento", "Knowledge sources"),
+    ("Conecta proyectos o repositorios para usarlos como fuente.", "Connect projects or repositories to use them as sources."),
+    ("Usar las reglas de preferencias", "Use the rules from preferences"),
+    ("Contexto de {0}: {1}", "{0} context: {1}"),
+    ("Ocultar", "Hide"),
+    // Playbooks
+    ("Playbooks", "Playbooks"),
+    ("Cada paso es un prompt: {{campo}} se pide al lanzar, {{anterior}} es la respuesta del paso previo y {{paso: N}} la del paso N.", "Each step is a prompt: {{campo}} is asked for at launch, {{anterior}} is the previous step's answer and {{paso: N}} is step N's answer."),
+    ("Los pasos se envían a {0}, con la persona y los parámetros de la conversación abierta.", "Steps are sent to {0} with the open conversation's persona and parameters."),
+    ("Playbooks guardados", "Saved playbooks"),
+    ("Nuevo playbook", "New playbook"),
+    ("Todavía no hay playbooks. Crea uno con sus pasos.", "There are no playbooks yet. Create one with its steps."),
+    ("{0} pasos", "{0} steps"),
+    ("Descripción", "Description"),
+    ("Paso {0}", "Step {0}"),
+    ("Título", "Title"),
+    ("Eliminar paso", "Remove step"),
+    ("Añadir paso", "Add step"),
+    ("Guardar playbook", "Save playbook"),
+    ("Lanzar '{0}'", "Launch '{0}'"),
+    ("Lanzar", "Launch"),
+    ("{0}/{1} pasos", "{0}/{1} steps"),
+    ("En curso: {0}", "In progress: {0}"),
+    ("Documento de resultados", "Result document"),
+    // Sugerencias de seguimiento
+    ("Buscando sugerencias…", "Looking for suggestions…"),
+    ("Enviar esta pregunta", "Send this question"),
+    ("Llevar al compositor", "Move to the composer"),
+    ("Sugerencias", "Suggestions"),
+    ("Activadas", "Enabled"),
+    ("Desactivadas", "Disabled"),
+    ("Sugerencias de seguimiento", "Follow-up suggestions"),
+    ("Preguntas propuestas bajo la última respuesta. Cada conversación puede desactivarlas desde sus ajustes.", "Questions suggested under the latest reply. Each conversation can turn them off from its settings."),
+    ("Proponer sugerencias", "Suggest follow-ups"),
+    ("Las propone", "Suggested by"),
+    ("Predeterminado del proveedor", "Provider default"),
+    ("Cantidad", "Count"),
+    ("Enviar la sugerencia al pulsarla", "Send the suggestion when clicked"),
+    ("Jarvis solo las propone si ya tiene un modelo cargado.", "Jarvis only suggests them when it already has a model loaded."),
+    // Puesta al día de la actividad
+    ("Ponme al día", "Brief me"),
+    ("Resume en el chat la actividad desde la última puesta al día con el proveedor de la conversación", "Summarizes in the chat the activity since the last briefing using the conversation's provider"),
+    ("Leer en voz alta", "Read aloud"),
+    // Paquetes de workflows
+    ("Compartir workflows", "Share workflows"),
+    ("Los paquetes JSON incluyen los pasos, las programaciones vinculadas y los listeners que lanzan cada workflow. Los secretos que detectan las reglas de redacción se exportan como marcadores y se piden al importar.", "JSON bundles include the steps, linked schedules and listeners that launch each workflow. Secrets caught by the redaction rules are exported as placeholders and requested on import."),
+    ("Vacío: carpeta workflows de la app", "Empty: the app's workflows folder"),
+    ("Exportar los visibles", "Export visible ones"),
+    ("Paquete exportado a {0}.", "Bundle exported to {0}."),
+    ("Leer paquete", "Read bundle"),
+    ("Exportado el {0} · {1} workflows · {2} programaciones · {3} listeners", "Exported on {0} · {1} workflows · {2} schedules · {3} listeners"),
+    ("Ya existen con el mismo nombre: {0}", "Already present with the same name: {0}"),
+    ("Conservar las existentes", "Keep existing ones"),
+    ("Reemplazar las existentes", "Replace existing ones"),
+    ("Importar con otro nombre", "Import under another name"),
+    ("Secretos que necesita el paquete", "Secrets required by the bundle"),
+    ("Importado: {0} añadidas, {1} reemplazadas y {2} omitidas.", "Imported: {0} added, {1} replaced and {2} skipped."),
+    ("Guarda este workflow como paquete para otra instalación", "Saves this workflow as a bundle for another install"),
+    // Permisos de las automatizaciones
+    ("Permiso de automatización", "Automation permission"),
+    ("Borrar ficheros", "Delete files"),
+    ("Publicar con git push", "Publish with git push"),
+    ("Enviar peticiones a servicios externos", "Send requests to external services"),
+    ("Ejecutar comandos locales", "Run local commands"),
+    ("Solo esta vez", "Just this once"),
+    ("Siempre para esta automatización", "Always for this automation"),
+    ("Siempre para cualquier automatización", "Always for any automation"),
+    ("'{0}' quiere:", "'{0}' wants to:"),
+    ("{0} automatizaciones más esperan después.", "{0} more automations are waiting after this one."),
+    ("Permitir", "Allow"),
+    ("Denegar", "Deny"),
+    ("Antes de que un workflow o un listener borre ficheros, haga git push, llame a un servicio externo o ejecute un comando se pide permiso. Aquí quedan los que se concedieron para siempre.", "Workflows and listeners ask for permission before deleting files, running git push, calling an external service or running a command. Permissions granted for good are listed here."),
+    ("{0} automatizaciones esperan permiso.", "{0} automations are waiting for permission."),
+    ("No hay permisos recordados: cada efecto se pregunta al ejecutarse.", "No remembered permissions: every effect is asked about when it runs."),
+    ("Efecto", "Effect"),
+    ("Automatización", "Automation"),
+    ("Concedido", "Granted"),
+    ("Cualquier automatización", "Any automation"),
+    ("Retirar", "Revoke"),
+    // Cifrado del historial
+    ("Las conversaciones, los borradores, los marcadores y la sesión guardada se cifran con AES-256-GCM. Lo que ya estaba en claro se migra al activarlo y se sigue leyendo sin cambios hasta entonces.", "Conversations, drafts, bookmarks and the saved session are encrypted with AES-256-GCM. Anything stored in plain text is migrated when you turn it on and keeps loading unchanged until then."),
+    ("Sin cifrar", "Not encrypted"),
+    ("Cifrado · bloqueado", "Encrypted · locked"),
+    ("Cifrado · desbloqueado", "Encrypted · unlocked"),
+    ("Llavero del sistema", "System keychain"),
+    ("Clave: {0}", "Key: {0}"),
+    ("Desbloquear", "Unlock"),
+    ("Historial desbloqueado.", "History unlocked."),
+    ("Cambiar la clave", "Change the key"),
+    ("Activar el cifrado", "Turn on encryption"),
+    ("Secreto guardado en el llavero del sistema", "Secret stored in the system keychain"),
+    ("Frase de paso en cada arranque", "Passphrase on every start"),
+    ("Si olvidas la frase de paso no podrás recuperar el historial.", "If you forget the passphrase the history cannot be recovered."),
+    ("Cifrar con la nueva clave", "Encrypt with the new key"),
+    ("Cifrar el historial", "Encrypt history"),
+    ("Historial cifrado; {0} archivos migrados.", "History encrypted; {0} files migrated."),
+    ("Desactivar el cifrado", "Turn off encryption"),
+    ("Cifrado desactivado; {0} archivos guardados en claro.", "Encryption turned off; {0} files saved in plain text."),
+    ("Las frases de paso no coinciden.", "The passphrases do not match."),
+    ("Historial cifrado", "Encrypted history"),
+    ("Escribe la frase de paso para cargar las conversaciones y la sesión guardadas.", "Enter the passphrase to load the saved conversations and session."),
+    ("No se pudo leer la clave del llavero del sistema. Desbloquéalo y vuelve a intentarlo.", "The key could not be read from the system keychain. Unlock it and try again."),
+    ("Reintentar", "Retry"),
+    // Diario de cambios de estado
+    ("Cambios de estado ({0})", "State changes ({0})"),
+    ("Enrutado, modelos y preferencias que cambiaron en esta sesión, con lo que los provocó. Revierte un campo para devolverlo a su valor anterior.", "Routing, models and preferences changed during this session, with what caused them. Revert a field to restore its previous value."),
+    ("Sin cambios registrados.", "No changes recorded."),
+    ("Enrutado", "Routing"),
+    ("Revertido", "Reverted"),
+    ("Revertir", "Revert"),
+    // Pruebas rápidas con aserciones
+    ("Pruebas con aserciones", "Tests with assertions"),
+    ("Comprueba que los modelos de la comparativa siguen respondiendo como esperas antes de hacerlos predeterminados.", "Check that the models in the comparison still answer as expected before making them the default."),
+    ("Editar prueba", "Edit test"),
+    ("Nueva prueba", "New test"),
+    ("Añade modelos a la comparativa con el botón 'Comparar'.", "Add models to the comparison with the 'Comparar' button."),
+    ("Incluir Jarvis", "Include Jarvis"),
+    ("Ejecutar pruebas", "Run tests"),
+    ("Aserciones", "Assertions"),
+    ("Contiene", "Contains"),
+    ("Expresión regular", "Regular expression"),
+    ("Esquema JSON", "JSON schema"),
+    ("Añadir aserción", "Add assertion"),
+    ("Guardar prueba", "Save test"),
+    ("Prueba", "Test"),
+    ("Superadas", "Passed"),
+    ("Hacer predeterminado", "Make default"),
+    ("Superó todas las pruebas: úsalo como modelo predeterminado del proveedor.", "It passed every test: use it as the provider's default model."),
+    ("{0} es ahora el modelo predeterminado.", "{0} is now the default model."),
+    // Benchmark de latencia
+    ("Benchmark de latencia", "Latency benchmark"),
+    ("Envía prompts corto, medio y largo a cada destino, mide TTFB, latencia y tokens/s y actualiza la latencia de las fichas del catálogo. También disponible con /benchmark.", "Sends short, medium and long prompts to each target, measures TTFB, latency and tokens/s and updates the latency on catalog cards. Also available with /benchmark."),
+    ("Iteraciones", "Iterations"),
+    ("Ejecutar benchmark", "Run benchmark"),
+    ("Ejecución del {0} · {1} iteraciones por prompt", "Run of {0} · {1} iterations per prompt"),
+    ("Latencia", "Latency"),
+    ("Todos", "All"),
+    ("Corto", "Short"),
+    ("Medio", "Medium"),
+    ("Largo", "Long"),
+    // Documentos PDF y DOCX
+    ("{0} páginas · {1} fragmentos", "{0} pages · {1} chunks"),
+    // Búsqueda web
+    ("Búsqueda web", "Web search"),
+    ("Elige el buscador (SearXNG, Brave o Bing) que usan el comando /search y los proveedores remotos cuando necesitan información actual.", "Choose the search engine (SearXNG, Brave or Bing) used by the /search command and by remote providers when they need current information."),
+    ("Búsqueda web: «{0}» ({1})", "Web search: “{0}” ({1})"),
+    ("Contraer", "Collapse"),
+    ("Ampliar", "Expand"),
+    ("Descarga el texto de la página", "Downloads the page text"),
+    ("Quitar del contexto", "Remove from context"),
+    ("Adjuntar como contexto", "Attach as context"),
+    ("Buscador", "Search engine"),
+    ("URL de la instancia", "Instance URL"),
+    ("Resultados", "Results"),
+    ("Ofrecer la búsqueda a los proveedores remotos como herramienta", "Offer search to remote providers as a tool"),
+    ("El proveedor puede pedir una búsqueda en lugar de responder; se le vuelve a llamar con los resultados.", "The provider can ask for a search instead of answering; it is called again with the results."),
+    ("La instancia debe tener habilitado el formato JSON (search.formats en settings.yml).", "The instance must have the JSON format enabled (search.formats in settings.yml)."),
+    ("Probar una búsqueda", "Try a search"),
+    ("Consulta", "Query"),
+    ("Buscar", "Search"),
+    ("Los resultados aparecen en el chat, donde puedes ampliarlos y adjuntarlos. También puedes usar /search <consulta>.", "Results appear in the chat, where you can expand and attach them. You can also use /search <query>."),
+    // Vigilante del portapapeles
+    ("Portapapeles", "Clipboard"),
+    ("Detecta trazas de error, código o URL al copiarlos y ofrece en un aviso acciones que los llevan a un mensaje nuevo.", "Detects stack traces, code or URLs when you copy them and offers actions in a toast that bring them into a new message."),
+    ("Vigilar el portapapeles", "Watch the clipboard"),
+    ("Consultar cada", "Check every"),
+    ("Trazas de error", "Stack traces"),
+    ("Código", "Code"),
+    ("Explicar este traceback", "Explain this traceback"),
+    ("Proponer un arreglo", "Suggest a fix"),
+    ("Explicar este código", "Explain this code"),
+    ("Revisar este código", "Review this code"),
+    ("Resumir esta URL", "Summarize this URL"),
+    // OCR
+    ("Preferencias › Modelos locales › OCR", "Preferences › Local models › OCR"),
+    ("Reconoce con Tesseract el texto de las imágenes adjuntas y de los proyectos para usarlo como contexto y en la búsqueda, y elige los paquetes de idioma.", "Recognizes text in attached and project images with Tesseract to use it as context and in search, and lets you pick the language packs."),
+    ("Reconocer el texto de las imágenes adjuntas", "Recognize text in attached images"),
+    ("Idiomas", "Languages"),
+    ("Ninguno: Tesseract usará inglés.", "None: Tesseract will use English."),
+    ("Añadir", "Add"),
+    ("Detectar paquetes instalados", "Detect installed packs"),
+    ("Paquetes instalados", "Installed packs"),
+    ("Sin instalar: {0}. Tesseract fallará hasta que instales sus paquetes.", "Not installed: {0}. Tesseract will fail until you install their packs."),
+    ("Incluir las imágenes de los proyectos al reindexar la base de conocimiento", "Include project images when reindexing the knowledge base"),
+    ("Requiere Tesseract instalado con los paquetes de idioma elegidos (p. ej. tesseract-ocr-spa). El texto reconocido acompaña a tus preguntas como el de los documentos adjuntos.", "Requires Tesseract installed with the chosen language packs (e.g. tesseract-ocr-spa). Recognized text accompanies your questions like attached documents do."),
+    ("Texto reconocido · {0} fragmentos", "Recognized text · {0} chunks"),
+    ("Reconociendo texto…", "Recognizing text…"),
+    ("Reconocer texto (OCR)", "Recognize text (OCR)"),
+    // Correo
+    ("Preferencias › Sistema › Correo", "Preferences › System › Email"),
+    ("Configura el servidor SMTP y las plantillas con las que se envían por correo los informes, los resúmenes diarios y las conversaciones exportadas.", "Configure the SMTP server and the templates used to email reports, daily digests and exported conversations."),
+    ("Correo", "Email"),
+    ("Servidor", "Server"),
+    ("Cifrado", "Encryption"),
+    ("Usuario", "User"),
+    ("Contraseña", "Password"),
+    ("Remitente", "Sender"),
+    ("Destinatarios habituales", "Usual recipients"),
+    ("Separados por comas", "Comma separated"),
+    ("{titulo} es el nombre del informe o resumen, {fecha} la fecha del envío y {contenido} su texto.", "{titulo} is the report or digest name, {fecha} the sending date and {contenido} its text."),
+    ("Asunto", "Subject"),
+    ("Cuerpo", "Body"),
+    ("El envío usa curl, incluido en macOS, Windows 10 y la mayoría de distribuciones Linux.", "Sending uses curl, included in macOS, Windows 10 and most Linux distributions."),
+    ("Enviar un correo de prueba", "Send a test email"),
+    ("Enviar por correo", "Send by email"),
+    ("Adjunta el archivo exportado; la frase de paso no se envía.", "Attaches the exported file; the passphrase is not sent."),
+    ("Para", "To"),
+    ("Enviar", "Send"),
+    // Webhooks
+    ("Webhooks salientes", "Outgoing webhooks"),
+    ("Los pasos de sincronización de los workflows que nombran un conector, o su servicio, publican en su canal. La URL de cada webhook se guarda en el llavero del sistema.", "Workflow sync steps that name a connector, or its service, post to its channel. Each webhook URL is stored in the system keychain."),
+    ("Reenviar avisos", "Forward notifications"),
+    ("Publicar el resumen diario", "Post the daily digest"),
+    ("Enviar prueba", "Send test"),
+    ("URL del webhook", "Webhook URL"),
+    ("Añadir conector", "Add connector"),
+    // Telegram
+    ("Preferencias › Sistema › Telegram", "Preferences › System › Telegram"),
+    ("Conecta un bot de Telegram para conversar con tu agente desde el móvil. Los mensajes de los chats autorizados llegan a un hilo propio y reciben la respuesta de la ruta habitual.", "Connect a Telegram bot to talk to your agent from your phone. Messages from authorized chats land in their own thread and get the answer from the usual route."),
+    ("Activar el puente con Telegram", "Enable the Telegram bridge"),
+    ("Token del bot", "Bot token"),
+    ("Hilo de la conversación", "Conversation thread"),
+    ("Crea el bot con @BotFather y pega aquí su token. Escribe al bot desde el móvil: si el chat no está autorizado, su identificador aparece en la actividad.", "Create the bot with @BotFather and paste its token here. Message the bot from your phone: if the chat is not authorized, its ID shows up in the activity log."),
+    ("Chats autorizados", "Authorized chats"),
+    ("Autorizar", "Authorize"),
+    ("Reconectar", "Reconnect"),
+    ("{0} mensajes en curso", "{0} messages in progress"),
+    // MQTT
+    ("Preferencias › Sistema › MQTT", "Preferences › System › MQTT"),
+    ("Conecta con un broker MQTT, como el de Home Assistant, para publicar los resultados de los workflows y usar los temas suscritos como disparadores de eventos.", "Connect to an MQTT broker, such as Home Assistant's, to publish workflow results and use subscribed topics as event triggers."),
+    ("Mensaje MQTT", "MQTT message"),
+    ("Conectar con el broker MQTT", "Connect to the MQTT broker"),
+    ("Broker", "Broker"),
+    ("Identificador de cliente", "Client ID"),
+    ("Keep-alive", "Keep-alive"),
+    ("Tema de resultados", "Results topic"),
+    ("Cada workflow ejecutado publica un resumen JSON en el tema de resultados. Los mensajes de los temas suscritos disparan los listeners de tipo «Mensaje MQTT» cuyo filtro encaja.", "Every workflow run publishes a JSON summary to the results topic. Messages on subscribed topics fire the “MQTT message” listeners whose filter matches."),
+    ("Suscripciones", "Subscriptions"),
+    ("Suscribir", "Subscribe"),
+    ("Publicar prueba", "Publish test"),
+    ("Mensajes recibidos", "Received messages"),
+    ("Se conservan los últimos {0} mensajes.", "The last {0} messages are kept."),
+    // Cola de llamadas
+    ("Preferencias › Sistema › Cola de llamadas", "Preferences › System › Call queue"),
+    ("Limita las llamadas simultáneas y por minuto a cada proveedor e integración, y revisa o cancela las que esperan turno. Las del chat se atienden antes que las de segundo plano.", "Limit concurrent and per-minute calls to each provider and integration, and review or cancel the ones waiting their turn. Chat calls are served before background ones."),
+    ("Límites por carril", "Limits per lane"),
+    ("Carril", "Lane"),
+    ("Simultáneas", "Concurrent"),
+    ("Por minuto", "Per minute"),
+    ("Añadir carril", "Add lane"),
+    ("Resto de carriles", "Other lanes"),
+    ("llamadas simultáneas, sin límite por minuto", "concurrent calls, no per-minute limit"),
+    ("Los carriles de proveedores se llaman anthropic, openai y groq; las integraciones usan webhooks y github. Un límite por minuto de 0 no limita.", "Provider lanes are named anthropic, openai and groq; integrations use webhooks and github. A per-minute limit of 0 means no limit."),
+    ("Llamadas activas: {0} en curso · {1} en cola", "Active calls: {0} running · {1} queued"),
+    ("No hay llamadas esperando ni en curso.", "No calls are waiting or running."),
+    ("Terminadas recientemente", "Recently finished"),
+    ("Llamada", "Call"),
+    ("Prioridad", "Priority"),
+    ("Encolada", "Queued"),
+    ("Espera", "Wait"),
+    ("Interactiva", "Interactive"),
+    ("Segundo plano", "Background"),
+    ("En cola", "Queued"),
+    ("En curso", "Running"),
+    ("Terminado", "Finished"),
+    ("Cancelado", "Cancelled"),
+    ("Descarta la llamada; si ya está en curso se ignora su respuesta", "Discards the call; if it is already running its response is ignored"),
+    ("Esta llamada ya está en curso y no se puede interrumpir", "This call is already running and cannot be interrupted"),
+    ("Los resume", "Summarized by"),
+    ("Proveedor de la conversación", "Conversation provider"),
+    ("Resumiendo mensajes antiguos; mientras tanto van completos.", "Summarizing older messages; until then they are sent in full."),
+];
@@ -0,0 +1,73 @@
+/// Traducciones al español de los textos que se escribieron en inglés.
+pub(super) const ENTRIES: &[(&str, &str)] = &[
+    // Ventanas modales
+    ("Settings", "Ajustes"),
+    ("API Configuration", "Configuración de API"),
+    ("Close", "Cerrar"),
+    ("Available Functions", "Funciones disponibles"),
+    // Pestañas y vistas
+    ("Activity", "Actividad"),
+    ("Debug console", "Consola de depuración"),
+    ("Custom commands", "Comandos personalizados"),
+    ("Documentation", "Documentación"),
+    ("Configuration", "Configuración"),
+    ("Usage", "Uso"),
+    ("Cron Scheduler", "Planificador cron"),
+    ("Activity feed", "Actividad reciente"),
+    // Ayuda de comandos
+    (
+        "Display the current local time.",
+        "Muestra la hora local actual.",
+    ),
+    (
+        "Summarize the system health of the agent.",
+        "Resume el estado de salud del agente.",
+    ),
+    (
+        "Provide an in-depth diagnostic report including providers, Jarvis runtime, and commands.",
+        "Genera un diagnóstico detallado de proveedores, runtime Jarvis y comandos.",
+    ),
+    (
+        "Provide placeholder usage statistics.",
+        "Muestra estadísticas de uso de ejemplo.",
+    ),
+    (
+        "List the projects tracked by the agent.",
+        "Lista los proyectos que sigue el agente.",
+    ),
+    (
+        "List configured user profiles.",
+        "Lista los perfiles de usuario configurados.",
+    ),
+    (
+        "Describe the current cache directory and limits.",
+        "Describe el directorio de caché actual y sus límites.",
+    ),
+    (
+        "List the models configured across providers and local runtime.",
+        "Lista los modelos configurados en los proveedores y el runtime local.",
+    ),
+    (
+        "Summarize the authenticated GitHub account and repositories.",
+        "Resume la cuenta de GitHub autenticada y sus repositorios.",
+    ),
+    (
+        "Explain the current contextual memory configuration.",
+        "Explica la configuración actual de memoria contextual.",
+    ),
+    (
+        "List all providers that are currently configured.",
+        "Lista todos los proveedores configurados actualmente.",
+    ),
+    (
+        "Display the status of the local Jarvis runtime.",
+        "Muestra el estado del runtime local Jarvis.",
+    ),
+    (
+        "List every available slash command in the chat.",
+        "Lista todos los slash-commands disponibles en el chat.",
+    ),
+    // Registros
+    ("Scheduler", "Planificador"),
+    ("Automation", "Automatización"),
+];
@@ -0,0 +1,94 @@
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+mod en;
+mod es;
+
+/// Idiomas disponibles para la interfaz.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    Spanish,
+    English,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::Spanish, Language::English];
+
+    /// Nombre del idioma escrito en ese mismo idioma.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::Spanish => "Español",
+            Language::English => "English",
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Spanish => "es",
+            Language::English => "en",
+        }
+    }
+
+    fn from_index(index: u8) -> Self {
+        match index {
+            1 => Language::English,
+            _ => Language::Spanish,
+        }
+    }
+
+    fn index(self) -> u8 {
+        match self {
+            Language::Spanish => 0,
+            Language::English => 1,
+        }
+    }
+}
+
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+static ENGLISH: Lazy<HashMap<&'static str, &'static str>> =
+    Lazy::new(|| en::ENTRIES.iter().copied().collect());
+static SPANISH: Lazy<HashMap<&'static str, &'static str>> =
+    Lazy::new(|| es::ENTRIES.iter().copied().collect());
+
+/// Cambia el idioma activo; el siguiente frame ya se pinta traducido.
+pub fn set_language(language: Language) {
+    CURRENT.store(language.index(), Ordering::Relaxed);
+}
+
+pub fn current_language() -> Language {
+    Language::from_index(CURRENT.load(Ordering::Relaxed))
+}
+
+/// Traduce `text` al idioma activo.
+///
+/// Los textos se escriben en su idioma original y actúan como clave: cada bundle
+/// traduce las cadenas escritas en el otro idioma. Si una cadena no figura en el
+/// bundle activo se devuelve sin cambios.
+pub fn t(text: &str) -> &str {
+    let bundle = match current_language() {
+        Language::Spanish => &SPANISH,
+        Language::English => &ENGLISH,
+    };
+    bundle.get(text).copied().unwrap_or(text)
+}
+
+/// Traduce por separado cada segmento de una ruta como `Preferencias › Sistema`.
+pub fn t_path(path: &str) -> String {
+    path.split(" › ")
+        .map(|segment| segment.split(" · ").map(t).collect::<Vec<_>>().join(" · "))
+        .collect::<Vec<_>>()
+        .join(" › ")
+}
+
+/// Traduce una plantilla y sustituye `{0}`, `{1}`… por los argumentos indicados.
+pub fn tf(template: &str, args: &[&str]) -> String {
+    let mut text = t(template).to_string();
+    for (index, arg) in args.iter().enumerate() {
+        text = text.replace(&format!("{{{}}}", index), arg);
+    }
+    text
+}
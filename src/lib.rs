@@ -0,0 +1,7 @@
+pub mod api;
+pub mod config;
+pub mod i18n;
+pub mod local_providers;
+pub mod state;
+pub mod tokenizer;
+pub mod ui;
@@ -1,14 +1,13 @@
-mod api;
-mod config;
-mod local_providers;
-mod state;
-mod ui;
-
-use state::AppState;
+use multimodal_agent_rs::state::{session, AppState};
 
 fn main() -> anyhow::Result<()> {
-    vscode_shell::run(|| Box::new(AppState::default()))
-        .map_err(|e| anyhow::anyhow!("Eframe error: {}", e))?;
+    session::install_panic_hook();
+    vscode_shell::run(|| {
+        let mut state = AppState::default();
+        state.start_services();
+        Box::new(state)
+    })
+    .map_err(|e| anyhow::anyhow!("Eframe error: {}", e))?;
 
     Ok(())
 }
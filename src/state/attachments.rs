@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::documents::{self, DocumentChunk};
+use super::transcription;
+
+/// Tamaño máximo de una imagen enviada a un modelo con visión; es el límite de Anthropic.
+pub const MAX_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+/// Instrucción con la que se pide la descripción de una imagen.
+pub const DESCRIBE_PROMPT: &str = "Describe esta imagen en español con detalle: elementos principales, texto visible y cualquier dato relevante para la conversación.";
+
+/// Tipo MIME de las imágenes que aceptan los modelos con visión.
+pub fn image_media_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    match extension.as_str() {
+        "png" => Some("image/png"),
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttachmentKind {
+    Image,
+    Audio,
+    /// PDF o DOCX cuyo texto acompaña a los prompts.
+    Document,
+    Other,
+}
+
+impl AttachmentKind {
+    pub fn of(path: &Path) -> Self {
+        if image_media_type(path).is_some() {
+            AttachmentKind::Image
+        } else if transcription::is_audio_file(path) {
+            AttachmentKind::Audio
+        } else if documents::is_document(path) {
+            AttachmentKind::Document
+        } else {
+            AttachmentKind::Other
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            AttachmentKind::Image => "🖼",
+            AttachmentKind::Audio => "🎙",
+            AttachmentKind::Document => "📑",
+            AttachmentKind::Other => "📄",
+        }
+    }
+}
+
+/// Archivo adjunto al compositor del chat.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComposerAttachment {
+    pub path: PathBuf,
+    pub kind: AttachmentKind,
+}
+
+impl ComposerAttachment {
+    pub fn new(path: PathBuf) -> Self {
+        let kind = AttachmentKind::of(&path);
+        Self { path, kind }
+    }
+
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| self.path.display().to_string())
+    }
+}
+
+/// Adjuntos del compositor, añadidos al soltar archivos sobre la ventana.
+#[derive(Default)]
+pub struct AttachmentState {
+    pub items: Vec<ComposerAttachment>,
+    /// Texto troceado de los documentos adjuntos y su número de páginas. El de las
+    /// imágenes, reconocido por OCR, cuenta como una sola página.
+    pub documents: HashMap<PathBuf, (usize, Vec<DocumentChunk>)>,
+}
+
+impl AttachmentState {
+    /// Añade el archivo si no estaba ya adjunto. Devuelve `true` si se añadió.
+    pub fn add(&mut self, path: PathBuf) -> bool {
+        if self.items.iter().any(|item| item.path == path) {
+            return false;
+        }
+        self.items.push(ComposerAttachment::new(path));
+        true
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.items.retain(|item| item.path != path);
+        self.documents.remove(path);
+    }
+
+    /// Fragmentos de los documentos adjuntos más relacionados con `prompt`, precedidos
+    /// del archivo y las páginas para que el modelo pueda citarlos.
+    pub fn document_context(&self, prompt: &str, limit: usize) -> Vec<String> {
+        let candidates = self.items.iter().flat_map(|item| {
+            let chunks = self
+                .documents
+                .get(&item.path)
+                .map_or(&[][..], |(_, chunks)| chunks.as_slice());
+            chunks.iter().map(move |chunk| (item, chunk))
+        });
+        documents::rank_chunks(prompt, candidates, limit)
+            .into_iter()
+            .map(|(item, chunk)| match chunk.citation() {
+                Some(citation) => format!("{} · {}\n{}", item.file_name(), citation, chunk.text),
+                None => format!("{}\n{}", item.file_name(), chunk.text),
+            })
+            .collect()
+    }
+
+    /// Imagen adjuntada más recientemente, la que describe `/describe` sin argumentos.
+    pub fn last_image(&self) -> Option<&ComposerAttachment> {
+        self.items
+            .iter()
+            .rev()
+            .find(|item| item.kind == AttachmentKind::Image)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attachments_are_classified_and_deduplicated() {
+        let mut attachments = AttachmentState::default();
+        assert!(attachments.add(PathBuf::from("captura.PNG")));
+        assert!(attachments.add(PathBuf::from("nota.ogg")));
+        assert!(attachments.add(PathBuf::from("diagrama.webp")));
+        assert!(!attachments.add(PathBuf::from("nota.ogg")));
+
+        assert_eq!(attachments.items[0].kind, AttachmentKind::Image);
+        assert_eq!(attachments.items[1].kind, AttachmentKind::Audio);
+        assert_eq!(
+            attachments.last_image().map(ComposerAttachment::file_name),
+            Some("diagrama.webp".to_string())
+        );
+        assert_eq!(image_media_type(Path::new("foto.jpg")), Some("image/jpeg"));
+        assert_eq!(
+            AttachmentKind::of(Path::new("informe.pdf")),
+            AttachmentKind::Document
+        );
+        assert_eq!(
+            AttachmentKind::of(Path::new("datos.csv")),
+            AttachmentKind::Other
+        );
+
+        let contract = PathBuf::from("contrato.docx");
+        assert!(attachments.add(contract.clone()));
+        attachments.documents.insert(
+            contract.clone(),
+            (
+                3,
+                vec![DocumentChunk {
+                    text: "La penalización por retraso es del 2 %.".to_string(),
+                    pages: Some(documents::PageRange { first: 3, last: 3 }),
+                }],
+            ),
+        );
+        assert_eq!(
+            attachments.document_context("¿Cuál es la penalización?", 2),
+            vec!["contrato.docx · p. 3\nLa penalización por retraso es del 2 %.".to_string()]
+        );
+        attachments.remove(&contract);
+        assert!(attachments.documents.is_empty());
+
+        attachments.remove(Path::new("diagrama.webp"));
+        assert_eq!(
+            attachments.last_image().map(|item| item.path.clone()),
+            Some(PathBuf::from("captura.PNG"))
+        );
+    }
+}
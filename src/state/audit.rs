@@ -0,0 +1,622 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use ring::{digest, hmac};
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+
+use super::{
+    feature::{FeatureModule, WorkbenchRegistry},
+    navigation::NavigationNode,
+    vault, MainView, NavigationRegistry, NavigationTarget, SECTION_PRIMARY,
+};
+
+pub(crate) const AUDIT_DIR: &str = "audit";
+/// Última secuencia y hash de cada conversación, guardados fuera de los registros para
+/// que truncarlos no pase desapercibido.
+pub(crate) const AUDIT_HEADS_FILE: &str = "audit_heads.json";
+/// Clave de firma de respaldo cuando no hay llavero del sistema.
+pub(crate) const AUDIT_KEY_FILE: &str = "audit.key";
+/// Cuenta del llavero con la clave que firma las entradas.
+const AUDIT_KEYCHAIN_ACCOUNT: &str = "auditoria";
+/// `previous_hash` de la primera entrada de cada conversación.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Efectos externos que quedan registrados en la auditoría.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditEventKind {
+    ProviderCall,
+    FileWritten,
+    CommandExecuted,
+    WorkflowTriggered,
+    PromptRedacted,
+    SafetyFilter,
+    AutomationPermission,
+}
+
+impl AuditEventKind {
+    pub const ALL: [AuditEventKind; 7] = [
+        AuditEventKind::ProviderCall,
+        AuditEventKind::FileWritten,
+        AuditEventKind::CommandExecuted,
+        AuditEventKind::WorkflowTriggered,
+        AuditEventKind::PromptRedacted,
+        AuditEventKind::SafetyFilter,
+        AuditEventKind::AutomationPermission,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AuditEventKind::ProviderCall => "Llamada a proveedor",
+            AuditEventKind::FileWritten => "Archivo escrito",
+            AuditEventKind::CommandExecuted => "Comando ejecutado",
+            AuditEventKind::WorkflowTriggered => "Workflow lanzado",
+            AuditEventKind::PromptRedacted => "Prompt redactado",
+            AuditEventKind::SafetyFilter => "Filtro de seguridad",
+            AuditEventKind::AutomationPermission => "Permiso de automatización",
+        }
+    }
+}
+
+/// Entrada del registro. Cada una incluye el hash de la anterior, de modo que modificar
+/// o borrar una entrada rompe la cadena a partir de ese punto. Los hashes son HMAC con
+/// una clave del llavero, así que no se pueden recalcular sin ella.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct AuditEntry {
+    pub sequence: u64,
+    pub timestamp: String,
+    pub conversation: String,
+    pub kind: AuditEventKind,
+    pub summary: String,
+    #[serde(default)]
+    pub details: BTreeMap<String, String>,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+/// Campos que cubre el hash, en el mismo orden en que se serializan.
+#[derive(Serialize)]
+struct HashedFields<'a> {
+    sequence: u64,
+    timestamp: &'a str,
+    conversation: &'a str,
+    kind: AuditEventKind,
+    summary: &'a str,
+    details: &'a BTreeMap<String, String>,
+    previous_hash: &'a str,
+}
+
+impl AuditEntry {
+    pub fn compute_hash(&self, key: &hmac::Key) -> String {
+        let fields = HashedFields {
+            sequence: self.sequence,
+            timestamp: &self.timestamp,
+            conversation: &self.conversation,
+            kind: self.kind,
+            summary: &self.summary,
+            details: &self.details,
+            previous_hash: &self.previous_hash,
+        };
+        let bytes = serde_json::to_vec(&fields).unwrap_or_default();
+        hmac::sign(key, &bytes)
+            .as_ref()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+/// Resultado de comprobar la cadena de hashes de una conversación.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AuditVerification {
+    Intact(usize),
+    Broken { sequence: u64, reason: String },
+}
+
+/// Última entrada conocida de una conversación.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditHead {
+    pub sequence: u64,
+    pub hash: String,
+}
+
+/// Comprueba la cadena de `entries` y, con `head`, que el registro llega hasta la última
+/// entrada anotada al escribir.
+pub fn verify_chain(
+    entries: &[AuditEntry],
+    key: &hmac::Key,
+    head: Option<&AuditHead>,
+) -> AuditVerification {
+    let mut previous = GENESIS_HASH.to_string();
+    for (index, entry) in entries.iter().enumerate() {
+        let broken = |reason: &str| AuditVerification::Broken {
+            sequence: entry.sequence,
+            reason: reason.to_string(),
+        };
+        if entry.sequence != index as u64 + 1 {
+            return broken("falta una entrada o está fuera de orden");
+        }
+        if entry.previous_hash != previous {
+            return broken("no enlaza con la entrada anterior");
+        }
+        if entry.compute_hash(key) != entry.hash {
+            return broken("el contenido no coincide con su hash");
+        }
+        previous = entry.hash.clone();
+    }
+    if let Some(head) = head {
+        let last = entries
+            .last()
+            .map(|entry| (entry.sequence, entry.hash.as_str()));
+        if last != Some((head.sequence, head.hash.as_str())) {
+            return AuditVerification::Broken {
+                sequence: head.sequence,
+                reason: "el registro no llega hasta la última entrada anotada".to_string(),
+            };
+        }
+    }
+    AuditVerification::Intact(entries.len())
+}
+
+/// Estado del panel de auditoría.
+#[derive(Default)]
+pub struct AuditViewer {
+    /// Conversación mostrada; vacía para la conversación activa.
+    pub conversation: String,
+    pub entries: Vec<AuditEntry>,
+    pub verification: Option<AuditVerification>,
+    pub kind_filter: Option<AuditEventKind>,
+    pub search: String,
+    pub status: Option<String>,
+}
+
+/// Registro de auditoría: un fichero JSONL de solo anexado por conversación.
+#[derive(Default)]
+pub struct AuditState {
+    pub viewer: AuditViewer,
+    /// Última secuencia y hash de cada conversación, para encadenar sin releer el fichero.
+    heads: HashMap<String, (u64, String)>,
+    /// Clave de firma, cargada del llavero la primera vez que hace falta.
+    key: Option<hmac::Key>,
+    /// Directorio alternativo, usado por las pruebas.
+    directory: Option<PathBuf>,
+}
+
+impl AuditState {
+    /// Registro guardado en `directory`, con `audit_heads.json` y `audit.key` a su lado.
+    /// No usa el llavero del sistema.
+    pub fn in_directory(directory: PathBuf) -> Self {
+        Self {
+            directory: Some(directory),
+            ..Self::default()
+        }
+    }
+
+    fn directory(&self) -> Result<PathBuf> {
+        match &self.directory {
+            Some(dir) => Ok(dir.clone()),
+            None => AppConfig::data_file(AUDIT_DIR),
+        }
+    }
+
+    fn file_for(&self, conversation: &str) -> Result<PathBuf> {
+        Ok(self.directory()?.join(file_name(conversation)))
+    }
+
+    /// `audit_heads.json` junto al directorio de registros, no dentro de él.
+    fn heads_path(&self) -> Result<PathBuf> {
+        match &self.directory {
+            Some(dir) => Ok(dir.with_file_name(AUDIT_HEADS_FILE)),
+            None => AppConfig::data_file(AUDIT_HEADS_FILE),
+        }
+    }
+
+    fn key_path(&self) -> Result<PathBuf> {
+        match &self.directory {
+            Some(dir) => Ok(dir.with_file_name(AUDIT_KEY_FILE)),
+            None => AppConfig::data_file(AUDIT_KEY_FILE),
+        }
+    }
+
+    fn key(&mut self) -> Result<hmac::Key> {
+        if let Some(key) = &self.key {
+            return Ok(key.clone());
+        }
+        let secret = signing_secret(&self.key_path()?, self.directory.is_none())?;
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+        self.key = Some(key.clone());
+        Ok(key)
+    }
+
+    fn stored_heads(&self) -> Result<BTreeMap<String, AuditHead>> {
+        let path = self.heads_path()?;
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let data = vault::read_to_string(&path)?;
+        serde_json::from_str(&data).with_context(|| format!("{} ilegible", path.display()))
+    }
+
+    fn store_head(&self, conversation: &str, head: AuditHead) -> Result<()> {
+        let mut heads = self.stored_heads()?;
+        heads.insert(conversation.to_string(), head);
+        let path = self.heads_path()?;
+        vault::write(&path, &serde_json::to_string_pretty(&heads)?)
+            .with_context(|| format!("No se pudo guardar {}", path.display()))
+    }
+
+    /// Añade una entrada al registro de `conversation` y la devuelve.
+    pub fn record(
+        &mut self,
+        conversation: &str,
+        kind: AuditEventKind,
+        summary: impl Into<String>,
+        details: BTreeMap<String, String>,
+    ) -> Result<AuditEntry> {
+        let path = self.file_for(conversation)?;
+        let key = self.key()?;
+        let (last_sequence, previous_hash) = match self.heads.get(conversation) {
+            Some(head) => head.clone(),
+            None => read_head(&path)?,
+        };
+
+        let mut entry = AuditEntry {
+            sequence: last_sequence + 1,
+            timestamp: Local::now().to_rfc3339(),
+            conversation: conversation.to_string(),
+            kind,
+            summary: summary.into(),
+            details,
+            previous_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash(&key);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("No se pudo crear {}", parent.display()))?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("No se pudo abrir {}", path.display()))?;
+        writeln!(
+            file,
+            "{}",
+            vault::seal_line(&serde_json::to_string(&entry)?)?
+        )
+        .with_context(|| format!("No se pudo escribir {}", path.display()))?;
+        self.store_head(
+            conversation,
+            AuditHead {
+                sequence: entry.sequence,
+                hash: entry.hash.clone(),
+            },
+        )?;
+
+        self.heads.insert(
+            conversation.to_string(),
+            (entry.sequence, entry.hash.clone()),
+        );
+        if self.viewer.conversation == conversation {
+            self.viewer.entries.push(entry.clone());
+        }
+        Ok(entry)
+    }
+
+    pub fn load(&self, conversation: &str) -> Result<Vec<AuditEntry>> {
+        read_entries(&self.file_for(conversation)?)
+    }
+
+    /// Comprueba la cadena de `entries` con la clave de firma y la última entrada anotada.
+    pub fn verify(
+        &mut self,
+        conversation: &str,
+        entries: &[AuditEntry],
+    ) -> Result<AuditVerification> {
+        let key = self.key()?;
+        let heads = self.stored_heads()?;
+        Ok(verify_chain(entries, &key, heads.get(conversation)))
+    }
+
+    /// Carga en el panel el registro de `conversation` y comprueba su cadena.
+    pub fn open(&mut self, conversation: &str) {
+        self.viewer.conversation = conversation.to_string();
+        match self
+            .load(conversation)
+            .and_then(|entries| Ok((self.verify(conversation, &entries)?, entries)))
+        {
+            Ok((verification, entries)) => {
+                self.viewer.verification = Some(verification);
+                self.viewer.status = None;
+                self.viewer.entries = entries;
+            }
+            Err(err) => {
+                self.viewer.entries.clear();
+                self.viewer.verification = None;
+                self.viewer.status = Some(format!("{:#}", err));
+            }
+        }
+    }
+
+    /// Conversaciones con registro, de la modificada más recientemente a la más antigua.
+    pub fn conversations(&self) -> Vec<String> {
+        let Ok(dir) = self.directory() else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut found: Vec<(std::time::SystemTime, String)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let modified = entry.metadata().ok()?.modified().ok()?;
+                let first = read_entries(&entry.path()).ok()?.into_iter().next()?;
+                Some((modified, first.conversation))
+            })
+            .collect();
+        found.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+        found
+            .into_iter()
+            .map(|(_, conversation)| conversation)
+            .collect()
+    }
+
+    /// Entradas del panel que pasan el filtro de tipo y la búsqueda.
+    pub fn visible_entries(&self) -> Vec<&AuditEntry> {
+        let query = self.viewer.search.trim().to_lowercase();
+        self.viewer
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| {
+                self.viewer
+                    .kind_filter
+                    .is_none_or(|kind| entry.kind == kind)
+            })
+            .filter(|entry| {
+                query.is_empty()
+                    || entry.summary.to_lowercase().contains(&query)
+                    || entry
+                        .details
+                        .values()
+                        .any(|value| value.to_lowercase().contains(&query))
+            })
+            .collect()
+    }
+}
+
+impl FeatureModule for AuditState {
+    fn register_navigation(&self, registry: &mut NavigationRegistry) {
+        let target = NavigationTarget::main(MainView::Audit);
+        registry.register_node(NavigationNode {
+            id: target.id(),
+            label: "Auditoría".into(),
+            description: Some(
+                "Revisa las llamadas, comandos, workflows y archivos escritos en cada conversación."
+                    .into(),
+            ),
+            icon: Some("🛡".into()),
+            badge: None,
+            target,
+            order: 5,
+            section_id: SECTION_PRIMARY.to_string(),
+        });
+    }
+
+    fn register_workbench_views(&self, registry: &mut WorkbenchRegistry) {
+        crate::ui::chat::register_audit_workbench_view(registry);
+    }
+}
+
+/// Nombre legible derivado de la clave más un sufijo de su hash, para que dos claves
+/// que se sanean igual no compartan fichero.
+fn file_name(conversation: &str) -> String {
+    let readable: String = conversation
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '_' })
+        .take(48)
+        .collect();
+    let hash = digest::digest(&digest::SHA256, conversation.as_bytes());
+    let suffix: String = hash.as_ref()[..4]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("{}-{}.jsonl", readable, suffix)
+}
+
+/// Secreto de la clave de firma: del llavero del sistema o, si no lo hay o no se usa, de
+/// `path`, que solo puede leer el usuario y se cifra junto al resto de datos cuando el
+/// almacenamiento cifrado está activo.
+fn signing_secret(path: &Path, use_keychain: bool) -> Result<String> {
+    if use_keychain {
+        if let Ok(Some(secret)) = vault::keychain_entry(AUDIT_KEYCHAIN_ACCOUNT) {
+            return Ok(secret);
+        }
+    }
+    if path.exists() {
+        return Ok(vault::read_to_string(path)?.trim().to_string());
+    }
+    let secret = vault::generate_secret()?;
+    if !use_keychain || vault::store_keychain_entry(AUDIT_KEYCHAIN_ACCOUNT, &secret).is_err() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("No se pudo crear {}", parent.display()))?;
+        }
+        vault::write(path, &secret)?;
+    }
+    Ok(secret)
+}
+
+fn read_entries(path: &Path) -> Result<Vec<AuditEntry>> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err).with_context(|| format!("No se pudo leer {}", path.display())),
+    };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            vault::open_line(line)
+                .and_then(|line| serde_json::from_str(&line).map_err(Into::into))
+                .with_context(|| format!("Línea {} ilegible en {}", index + 1, path.display()))
+        })
+        .collect()
+}
+
+fn read_head(path: &Path) -> Result<(u64, String)> {
+    Ok(read_entries(path)?
+        .last()
+        .map(|entry| (entry.sequence, entry.hash.clone()))
+        .unwrap_or_else(|| (0, GENESIS_HASH.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temporary_state(name: &str) -> AuditState {
+        let dir = std::env::temp_dir()
+            .join(format!("jmk-audit-{}-{}", name, std::process::id()))
+            .join(AUDIT_DIR);
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_file(dir.with_file_name(AUDIT_HEADS_FILE));
+        AuditState {
+            key: Some(test_key()),
+            ..AuditState::in_directory(dir)
+        }
+    }
+
+    fn test_key() -> hmac::Key {
+        hmac::Key::new(hmac::HMAC_SHA256, b"clave de pruebas")
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn generated_key_is_private_to_the_user() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir()
+            .join(format!("jmk-audit-key-{}", std::process::id()))
+            .join(AUDIT_DIR);
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+        let mut audit = AuditState::in_directory(dir.clone());
+        audit
+            .record(
+                "conv-1",
+                AuditEventKind::CommandExecuted,
+                "/status",
+                BTreeMap::new(),
+            )
+            .unwrap();
+
+        let key_file = dir.with_file_name(AUDIT_KEY_FILE);
+        let mode = fs::metadata(&key_file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn entries_chain_across_reloads() {
+        let mut audit = temporary_state("chain");
+        let details = BTreeMap::from([("modelo".to_string(), "gpt-4.1".to_string())]);
+        audit
+            .record("conv-1", AuditEventKind::ProviderCall, "OpenAI", details)
+            .unwrap();
+        audit
+            .record(
+                "conv-1",
+                AuditEventKind::CommandExecuted,
+                "/status",
+                BTreeMap::new(),
+            )
+            .unwrap();
+
+        // Una instancia nueva retoma la cadena desde el fichero.
+        let mut reopened = AuditState {
+            key: Some(test_key()),
+            directory: audit.directory.clone(),
+            ..AuditState::default()
+        };
+        let third = reopened
+            .record(
+                "conv-1",
+                AuditEventKind::FileWritten,
+                "export.md",
+                BTreeMap::new(),
+            )
+            .unwrap();
+        assert_eq!(third.sequence, 3);
+
+        let entries = reopened.load("conv-1").unwrap();
+        assert_eq!(
+            reopened.verify("conv-1", &entries).unwrap(),
+            AuditVerification::Intact(3)
+        );
+        assert!(reopened.load("otra").unwrap().is_empty());
+        assert_eq!(reopened.conversations(), vec!["conv-1".to_string()]);
+
+        fs::remove_dir_all(audit.directory.unwrap().parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn tampering_breaks_the_chain() {
+        let mut audit = temporary_state("tamper");
+        for command in ["/status", "/models", "/help"] {
+            audit
+                .record(
+                    "conv",
+                    AuditEventKind::CommandExecuted,
+                    command,
+                    BTreeMap::new(),
+                )
+                .unwrap();
+        }
+        let mut entries = audit.load("conv").unwrap();
+
+        // Sin la clave no se puede recalcular el hash de una entrada editada.
+        let mut edited = entries.clone();
+        edited[1].summary = "/time".to_string();
+        let forged_key = hmac::Key::new(hmac::HMAC_SHA256, b"otra clave");
+        edited[1].hash = edited[1].compute_hash(&forged_key);
+        assert_eq!(
+            audit.verify("conv", &edited).unwrap(),
+            AuditVerification::Broken {
+                sequence: 2,
+                reason: "el contenido no coincide con su hash".to_string(),
+            }
+        );
+
+        entries.remove(1);
+        assert!(matches!(
+            audit.verify("conv", &entries).unwrap(),
+            AuditVerification::Broken { sequence: 3, .. }
+        ));
+
+        // Quitar las últimas líneas deja una cadena válida, pero más corta que la anotada.
+        let path = audit.file_for("conv").unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let truncated: Vec<&str> = content.lines().take(2).collect();
+        fs::write(&path, truncated.join("\n") + "\n").unwrap();
+        let entries = audit.load("conv").unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(
+            audit.verify("conv", &entries).unwrap(),
+            AuditVerification::Broken {
+                sequence: 3,
+                reason: "el registro no llega hasta la última entrada anotada".to_string(),
+            }
+        );
+
+        fs::remove_dir_all(audit.directory.unwrap().parent().unwrap()).unwrap();
+    }
+}
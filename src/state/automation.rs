@@ -1,10 +1,14 @@
 use super::{
+    commands::WorkflowCommand,
     feature::{CommandRegistry, FeatureModule, WorkbenchRegistry},
+    workflow_bundle::{self, ConflictResolution},
     AutomationWorkflowBoard, CronBoardState, EventAutomationState, ExternalIntegrationsState,
     LogEntry, LogStatus, NavigationNode, NavigationRegistry, NavigationTarget, ScheduledReminder,
+    ScheduledTask,
 };
 use crate::config::AppConfig;
 use chrono::Local;
+use std::collections::BTreeMap;
 
 pub struct AutomationState {
     pub cron_board: CronBoardState,
@@ -16,7 +20,7 @@ pub struct AutomationState {
 }
 
 impl AutomationState {
-    pub fn from_config(_config: &AppConfig) -> Self {
+    pub fn from_config(config: &AppConfig) -> Self {
         let mut state = Self {
             cron_board: CronBoardState::with_tasks(super::default_scheduled_tasks()),
             workflows: AutomationWorkflowBoard::with_workflows(
@@ -27,6 +31,36 @@ impl AutomationState {
             external_integrations: ExternalIntegrationsState::default(),
             activity_logs: super::default_logs(),
         };
+        for schedule in &config.knowledge_reindex {
+            let id = state.cron_board.next_task_id();
+            state
+                .cron_board
+                .tasks
+                .push(ScheduledTask::knowledge_reindex(id, schedule));
+        }
+        for template in &config.report_templates {
+            if !template.cron_expression.trim().is_empty() {
+                let id = state.cron_board.next_task_id();
+                state
+                    .cron_board
+                    .tasks
+                    .push(ScheduledTask::report(id, template));
+            }
+        }
+        if config.daily_digest.enabled {
+            let id = state.cron_board.next_task_id();
+            if let Some(task) = ScheduledTask::daily_digest(id, &config.daily_digest) {
+                state.cron_board.tasks.push(task);
+            }
+        }
+        for bundle in &config.imported_workflows {
+            workflow_bundle::apply(
+                bundle,
+                ConflictResolution::Replace,
+                &BTreeMap::new(),
+                &mut state,
+            );
+        }
 
         let summary = LogEntry {
             status: LogStatus::Ok,
@@ -99,6 +133,19 @@ impl FeatureModule for AutomationState {
             super::CustomCommandAction::ShowSystemDiagnostics,
             super::CustomCommandAction::ShowUsageStatistics,
         ]);
+        for workflow in &self.workflows.workflows {
+            if let Some(trigger) = workflow
+                .chat_command
+                .as_deref()
+                .filter(|trigger| trigger.trim().starts_with('/'))
+            {
+                registry.register_command(WorkflowCommand::new(
+                    trigger,
+                    workflow.id,
+                    &workflow.name,
+                ));
+            }
+        }
     }
 
     fn register_workbench_views(&self, registry: &mut WorkbenchRegistry) {
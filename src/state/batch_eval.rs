@@ -0,0 +1,384 @@
+use std::collections::VecDeque;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::tokenizer::TokenCounter;
+
+use super::{
+    feature::{FeatureModule, WorkbenchRegistry},
+    navigation::NavigationNode,
+    MainView, NavigationRegistry, NavigationTarget, RemoteProviderKind, SECTION_PRIMARY,
+};
+
+/// Destino al que se envía cada prompt de una evaluación por lotes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BatchTarget {
+    Remote(RemoteProviderKind),
+    Jarvis,
+}
+
+impl BatchTarget {
+    pub const ALL: [BatchTarget; 4] = [
+        BatchTarget::Remote(RemoteProviderKind::Anthropic),
+        BatchTarget::Remote(RemoteProviderKind::OpenAi),
+        BatchTarget::Remote(RemoteProviderKind::Groq),
+        BatchTarget::Jarvis,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BatchTarget::Remote(provider) => provider.display_name(),
+            BatchTarget::Jarvis => "Jarvis (local)",
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            BatchTarget::Remote(provider) => provider.short_code(),
+            BatchTarget::Jarvis => "jarvis",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|target| target.code().eq_ignore_ascii_case(code.trim()))
+    }
+}
+
+/// Prompt leído del fichero de entrada.
+#[derive(Clone, Debug, Deserialize)]
+pub struct BatchPrompt {
+    #[serde(default)]
+    pub id: String,
+    pub prompt: String,
+}
+
+/// Resultado de enviar un prompt a un destino concreto.
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchResult {
+    pub prompt_id: String,
+    pub target: String,
+    pub prompt: String,
+    pub response: Option<String>,
+    pub error: Option<String>,
+    pub latency_ms: u64,
+    pub prompt_tokens: usize,
+    pub response_tokens: usize,
+}
+
+impl BatchResult {
+    pub(crate) fn from_outcome(
+        target: BatchTarget,
+        prompt: BatchPrompt,
+        outcome: std::result::Result<String, String>,
+        latency: Duration,
+        counter: &dyn TokenCounter,
+    ) -> Self {
+        let prompt_tokens = counter.count(&prompt.prompt);
+        let (response, error) = match outcome {
+            Ok(response) => (Some(response), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            prompt_id: prompt.id,
+            target: target.code().to_string(),
+            prompt: prompt.prompt,
+            response_tokens: response.as_deref().map_or(0, |text| counter.count(text)),
+            response,
+            error,
+            latency_ms: latency.as_millis() as u64,
+            prompt_tokens,
+        }
+    }
+}
+
+/// Métricas agregadas por destino para el informe.
+#[derive(Clone, Debug)]
+pub struct BatchTargetSummary {
+    pub target: String,
+    pub total: usize,
+    pub failures: usize,
+    pub average_latency_ms: u64,
+    pub p95_latency_ms: u64,
+    pub prompt_tokens: usize,
+    pub response_tokens: usize,
+}
+
+/// Respuesta de un proveedor remoto recibida desde su hilo de trabajo.
+pub(crate) struct RemoteBatchOutcome {
+    pub prompt: BatchPrompt,
+    pub provider: RemoteProviderKind,
+    pub outcome: std::result::Result<String, String>,
+    pub latency: Duration,
+}
+
+/// Evaluación en curso.
+pub(crate) struct BatchRun {
+    pub remote_rx: Receiver<RemoteBatchOutcome>,
+    pub jarvis_queue: VecDeque<BatchPrompt>,
+    pub expected: usize,
+    pub started_at: Instant,
+}
+
+pub struct BatchEvalState {
+    /// Fichero JSONL o CSV con los prompts a evaluar.
+    pub prompts_path: String,
+    /// Fichero de resultados; la extensión `.csv` elige CSV y cualquier otra JSONL.
+    pub output_path: String,
+    pub targets: Vec<BatchTarget>,
+    pub results: Vec<BatchResult>,
+    /// Consulta siempre a los proveedores en lugar de reutilizar respuestas en caché.
+    pub bypass_cache: bool,
+    pub status: Option<String>,
+    pub(crate) run: Option<BatchRun>,
+}
+
+impl Default for BatchEvalState {
+    fn default() -> Self {
+        Self {
+            prompts_path: String::new(),
+            output_path: "batch_results.jsonl".to_string(),
+            targets: vec![BatchTarget::Jarvis],
+            results: Vec::new(),
+            bypass_cache: false,
+            status: None,
+            run: None,
+        }
+    }
+}
+
+impl BatchEvalState {
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    pub fn toggle_target(&mut self, target: BatchTarget) {
+        if let Some(position) = self.targets.iter().position(|entry| *entry == target) {
+            self.targets.remove(position);
+        } else {
+            self.targets.push(target);
+        }
+    }
+
+    /// Fracción completada de la evaluación en curso.
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.run
+            .as_ref()
+            .map(|run| (self.results.len(), run.expected))
+    }
+
+    pub fn summary(&self) -> Vec<BatchTargetSummary> {
+        summarize(&self.results)
+    }
+}
+
+impl FeatureModule for BatchEvalState {
+    fn register_navigation(&self, registry: &mut NavigationRegistry) {
+        let target = NavigationTarget::main(MainView::BatchEvaluation);
+        registry.register_node(NavigationNode {
+            id: target.id(),
+            label: "Evaluación".into(),
+            description: Some(
+                "Lanza lotes de prompts contra varios proveedores y compara resultados.".into(),
+            ),
+            icon: Some("🧪".into()),
+            badge: None,
+            target,
+            order: 4,
+            section_id: SECTION_PRIMARY.to_string(),
+        });
+    }
+
+    fn register_workbench_views(&self, registry: &mut WorkbenchRegistry) {
+        crate::ui::chat::register_batch_eval_workbench_view(registry);
+    }
+}
+
+/// Lee los prompts de un fichero JSONL (`{"id": …, "prompt": …}` por línea) o CSV con
+/// una columna `prompt` y, opcionalmente, `id`.
+pub fn load_prompts(path: &Path) -> Result<Vec<BatchPrompt>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("No se pudo leer {}", path.display()))?;
+    let mut prompts = if is_csv(path) {
+        parse_csv_prompts(&content)?
+    } else {
+        let mut prompts = Vec::new();
+        for (line_number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let prompt: BatchPrompt = serde_json::from_str(line)
+                .with_context(|| format!("Línea {} no válida", line_number + 1))?;
+            prompts.push(prompt);
+        }
+        prompts
+    };
+
+    prompts.retain(|prompt| !prompt.prompt.trim().is_empty());
+    for (index, prompt) in prompts.iter_mut().enumerate() {
+        if prompt.id.trim().is_empty() {
+            prompt.id = (index + 1).to_string();
+        }
+    }
+    if prompts.is_empty() {
+        bail!("{} no contiene prompts", path.display());
+    }
+    Ok(prompts)
+}
+
+pub fn write_results(path: &Path, results: &[BatchResult]) -> Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file =
+        fs::File::create(path).with_context(|| format!("No se pudo crear {}", path.display()))?;
+
+    if is_csv(path) {
+        writeln!(
+            file,
+            "prompt_id,target,latency_ms,prompt_tokens,response_tokens,error,response"
+        )?;
+        for result in results {
+            writeln!(
+                file,
+                "{},{},{},{},{},{},{}",
+                csv_field(&result.prompt_id),
+                csv_field(&result.target),
+                result.latency_ms,
+                result.prompt_tokens,
+                result.response_tokens,
+                csv_field(result.error.as_deref().unwrap_or("")),
+                csv_field(result.response.as_deref().unwrap_or("")),
+            )?;
+        }
+    } else {
+        for result in results {
+            writeln!(file, "{}", serde_json::to_string(result)?)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn summarize(results: &[BatchResult]) -> Vec<BatchTargetSummary> {
+    let mut summaries: Vec<BatchTargetSummary> = Vec::new();
+    let mut latencies: Vec<Vec<u64>> = Vec::new();
+
+    for result in results {
+        let position = match summaries
+            .iter()
+            .position(|summary| summary.target == result.target)
+        {
+            Some(position) => position,
+            None => {
+                summaries.push(BatchTargetSummary {
+                    target: result.target.clone(),
+                    total: 0,
+                    failures: 0,
+                    average_latency_ms: 0,
+                    p95_latency_ms: 0,
+                    prompt_tokens: 0,
+                    response_tokens: 0,
+                });
+                latencies.push(Vec::new());
+                summaries.len() - 1
+            }
+        };
+        let summary = &mut summaries[position];
+        summary.total += 1;
+        if result.error.is_some() {
+            summary.failures += 1;
+        }
+        summary.prompt_tokens += result.prompt_tokens;
+        summary.response_tokens += result.response_tokens;
+        latencies[position].push(result.latency_ms);
+    }
+
+    for (summary, mut values) in summaries.iter_mut().zip(latencies) {
+        values.sort_unstable();
+        summary.average_latency_ms = values.iter().sum::<u64>() / values.len().max(1) as u64;
+        let p95_index = ((values.len() as f32 * 0.95).ceil() as usize).saturating_sub(1);
+        summary.p95_latency_ms = values.get(p95_index).copied().unwrap_or_default();
+    }
+    summaries
+}
+
+fn is_csv(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+}
+
+fn parse_csv_prompts(content: &str) -> Result<Vec<BatchPrompt>> {
+    let mut records = parse_csv(content).into_iter();
+    let header = records.next().ok_or_else(|| anyhow!("El CSV está vacío"))?;
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|field| field.trim().eq_ignore_ascii_case(name))
+    };
+    let prompt_column =
+        column("prompt").ok_or_else(|| anyhow!("El CSV necesita una columna 'prompt'"))?;
+    let id_column = column("id");
+
+    Ok(records
+        .map(|record| BatchPrompt {
+            id: id_column
+                .and_then(|index| record.get(index).cloned())
+                .unwrap_or_default(),
+            prompt: record.get(prompt_column).cloned().unwrap_or_default(),
+        })
+        .collect())
+}
+
+/// Analiza CSV con comillas dobles, incluidos saltos de línea dentro de un campo.
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|value| !value.is_empty()) {
+                    records.push(std::mem::take(&mut record));
+                } else {
+                    record.clear();
+                }
+            }
+            other => field.push(other),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,415 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+use crate::tokenizer::TokenCounter;
+
+use super::batch_eval::BatchTarget;
+use super::{vault, RemoteProviderKind};
+
+pub(crate) const LAST_RUN_FILE: &str = "benchmark.json";
+/// Directorio de datos donde se guardan los informes.
+pub const REPORTS_DIR: &str = "benchmarks";
+pub const DEFAULT_ITERATIONS: usize = 3;
+pub const MAX_ITERATIONS: usize = 20;
+
+const SAMPLE_PARAGRAPH: &str = "Los equipos que operan servicios en producción revisan cada semana las alertas, los despliegues y las incidencias abiertas para decidir qué mejorar primero.";
+
+/// Longitud de los prompts estandarizados del benchmark.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BenchmarkPromptSize {
+    Short,
+    Medium,
+    Long,
+}
+
+impl BenchmarkPromptSize {
+    pub const ALL: [BenchmarkPromptSize; 3] = [
+        BenchmarkPromptSize::Short,
+        BenchmarkPromptSize::Medium,
+        BenchmarkPromptSize::Long,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            BenchmarkPromptSize::Short => "Corto",
+            BenchmarkPromptSize::Medium => "Medio",
+            BenchmarkPromptSize::Long => "Largo",
+        }
+    }
+
+    /// Prompt fijo de cada tamaño, para que las cifras sean comparables entre ejecuciones.
+    pub fn prompt(self) -> String {
+        match self {
+            BenchmarkPromptSize::Short => {
+                "Responde en una frase: ¿qué es la latencia de red?".to_string()
+            }
+            BenchmarkPromptSize::Medium => format!(
+                "{}\n\nResume el párrafo anterior en tres viñetas.",
+                SAMPLE_PARAGRAPH
+            ),
+            BenchmarkPromptSize::Long => format!(
+                "{}\n\nEscribe un plan de mejora de unas 200 palabras a partir del texto anterior.",
+                [SAMPLE_PARAGRAPH; 12].join("\n")
+            ),
+        }
+    }
+}
+
+/// Medida de una llamada.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BenchmarkSample {
+    /// Código del destino (`anthropic`, `jarvis`…).
+    pub target: String,
+    pub size: BenchmarkPromptSize,
+    pub iteration: usize,
+    /// Hasta la llegada de la respuesta del proveedor. Jarvis genera en el propio
+    /// proceso, así que no tiene.
+    pub ttfb_ms: Option<u64>,
+    pub total_ms: u64,
+    pub response_tokens: usize,
+    pub error: Option<String>,
+}
+
+impl BenchmarkSample {
+    pub(crate) fn measure(call: BenchmarkCall, counter: &dyn TokenCounter) -> Self {
+        let (response_tokens, error) = match call.outcome {
+            Ok(response) => (counter.count(&response), None),
+            Err(error) => (0, Some(error)),
+        };
+        Self {
+            target: call.target.code().to_string(),
+            size: call.size,
+            iteration: call.iteration,
+            ttfb_ms: call.ttfb.map(|ttfb| ttfb.as_millis() as u64),
+            total_ms: call.total.as_millis() as u64,
+            response_tokens,
+            error,
+        }
+    }
+
+    pub fn tokens_per_second(&self) -> f32 {
+        if self.total_ms == 0 {
+            return 0.0;
+        }
+        self.response_tokens as f32 * 1000.0 / self.total_ms as f32
+    }
+}
+
+/// Cifras agregadas de un destino para un tamaño de prompt, o para todos si `size` es
+/// `None`. Las latencias son medianas de las llamadas sin error.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkSummary {
+    pub target: String,
+    pub size: Option<BenchmarkPromptSize>,
+    pub runs: usize,
+    pub failures: usize,
+    pub ttfb_ms: Option<u64>,
+    pub total_ms: Option<u64>,
+    pub p90_total_ms: Option<u64>,
+    pub tokens_per_second: Option<f32>,
+}
+
+fn percentile(values: &mut [u64], fraction: f32) -> Option<u64> {
+    values.sort_unstable();
+    let index = ((values.len() as f32 * fraction).ceil() as usize).saturating_sub(1);
+    values.get(index).copied()
+}
+
+fn summarize_group(
+    target: &str,
+    size: Option<BenchmarkPromptSize>,
+    samples: &[&BenchmarkSample],
+) -> BenchmarkSummary {
+    let ok: Vec<&&BenchmarkSample> = samples
+        .iter()
+        .filter(|sample| sample.error.is_none())
+        .collect();
+    let mut totals: Vec<u64> = ok.iter().map(|sample| sample.total_ms).collect();
+    let mut ttfbs: Vec<u64> = ok.iter().filter_map(|sample| sample.ttfb_ms).collect();
+    let tokens_per_second = (!ok.is_empty()).then(|| {
+        ok.iter()
+            .map(|sample| sample.tokens_per_second())
+            .sum::<f32>()
+            / ok.len() as f32
+    });
+    BenchmarkSummary {
+        target: target.to_string(),
+        size,
+        runs: samples.len(),
+        failures: samples.len() - ok.len(),
+        ttfb_ms: percentile(&mut ttfbs, 0.5),
+        total_ms: percentile(&mut totals, 0.5),
+        p90_total_ms: percentile(&mut totals, 0.9),
+        tokens_per_second,
+    }
+}
+
+/// Destino y modelo medidos en una ejecución.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BenchmarkModel {
+    pub target: String,
+    pub model: String,
+}
+
+/// Ejecución completa con todas sus medidas.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BenchmarkRecord {
+    pub started_at: String,
+    pub iterations: usize,
+    pub models: Vec<BenchmarkModel>,
+    pub samples: Vec<BenchmarkSample>,
+}
+
+impl BenchmarkRecord {
+    pub fn model_for(&self, target: &str) -> Option<&str> {
+        self.models
+            .iter()
+            .find(|model| model.target == target)
+            .map(|model| model.model.as_str())
+    }
+
+    /// Una fila por destino y tamaño, seguida de la fila con todos los tamaños.
+    pub fn summaries(&self) -> Vec<BenchmarkSummary> {
+        let mut summaries = Vec::new();
+        for model in &self.models {
+            let samples: Vec<&BenchmarkSample> = self
+                .samples
+                .iter()
+                .filter(|sample| sample.target == model.target)
+                .collect();
+            for size in BenchmarkPromptSize::ALL {
+                let group: Vec<&BenchmarkSample> = samples
+                    .iter()
+                    .copied()
+                    .filter(|sample| sample.size == size)
+                    .collect();
+                if !group.is_empty() {
+                    summaries.push(summarize_group(&model.target, Some(size), &group));
+                }
+            }
+            if !samples.is_empty() {
+                summaries.push(summarize_group(&model.target, None, &samples));
+            }
+        }
+        summaries
+    }
+
+    /// Latencia mediana de cada modelo remoto, para las fichas del catálogo.
+    pub fn card_latencies(&self) -> Vec<(RemoteProviderKind, String, u32)> {
+        self.summaries()
+            .into_iter()
+            .filter(|summary| summary.size.is_none())
+            .filter_map(|summary| {
+                let BatchTarget::Remote(provider) = BatchTarget::from_code(&summary.target)? else {
+                    return None;
+                };
+                let model = self.model_for(&summary.target)?;
+                Some((provider, model.to_string(), summary.total_ms? as u32))
+            })
+            .collect()
+    }
+
+    /// Informe en Markdown con la tabla comparativa.
+    pub fn render_report(&self) -> String {
+        let mut report = format!(
+            "# Benchmark de latencia\n\n- Fecha: {}\n- Iteraciones por prompt: {}\n- Prompts: {}\n\n",
+            self.started_at,
+            self.iterations,
+            BenchmarkPromptSize::ALL
+                .iter()
+                .map(|size| size.label())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        report.push_str(
+            "| Destino | Modelo | Prompt | Llamadas | Errores | TTFB | Latencia | p90 | Tokens/s |\n",
+        );
+        report.push_str("|---|---|---|---|---|---|---|---|---|\n");
+        let millis =
+            |value: Option<u64>| value.map_or("—".to_string(), |ms| format!("{} ms", ms));
+        for summary in self.summaries() {
+            let label = BatchTarget::from_code(&summary.target)
+                .map(|target| target.label().to_string())
+                .unwrap_or_else(|| summary.target.clone());
+            report.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {} |\n",
+                label,
+                self.model_for(&summary.target).unwrap_or("—"),
+                summary.size.map_or("Todos", |size| size.label()),
+                summary.runs,
+                summary.failures,
+                millis(summary.ttfb_ms),
+                millis(summary.total_ms),
+                millis(summary.p90_total_ms),
+                summary
+                    .tokens_per_second
+                    .map_or("—".to_string(), |rate| format!("{:.1}", rate)),
+            ));
+        }
+        report.push_str(
+            "\nLas latencias son medianas de las llamadas sin error. Los proveedores responden sin streaming, así que el TTFB mide la llegada de la respuesta; Jarvis genera en el propio proceso y no lo tiene.\n",
+        );
+        report
+    }
+}
+
+pub fn load_last_run() -> Option<BenchmarkRecord> {
+    let path = AppConfig::data_file(LAST_RUN_FILE).ok()?;
+    let data = vault::read_to_string(&path).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn save_last_run(record: &BenchmarkRecord) -> Result<()> {
+    let path = AppConfig::data_file(LAST_RUN_FILE)?;
+    let json = serde_json::to_string_pretty(record)?;
+    vault::write(&path, &json).with_context(|| format!("No se pudo guardar {}", path.display()))
+}
+
+/// Llamada ya hecha, con sus tiempos, que llega desde un hilo de trabajo.
+pub(crate) struct BenchmarkCall {
+    pub target: BatchTarget,
+    pub size: BenchmarkPromptSize,
+    pub iteration: usize,
+    pub outcome: std::result::Result<String, String>,
+    pub ttfb: Option<Duration>,
+    pub total: Duration,
+}
+
+/// Benchmark en curso.
+pub(crate) struct BenchmarkRun {
+    pub remote_rx: Receiver<BenchmarkCall>,
+    /// Llamadas pendientes de Jarvis, que se atienden de una en una.
+    pub jarvis_queue: VecDeque<(BenchmarkPromptSize, usize)>,
+    pub record: BenchmarkRecord,
+    pub expected: usize,
+    pub started_at: Instant,
+}
+
+pub struct BenchmarkState {
+    pub targets: Vec<BatchTarget>,
+    pub iterations: usize,
+    /// Última ejecución completada; sus latencias se aplican al catálogo al arrancar.
+    pub last_run: Option<BenchmarkRecord>,
+    pub status: Option<String>,
+    pub(crate) run: Option<BenchmarkRun>,
+}
+
+impl Default for BenchmarkState {
+    fn default() -> Self {
+        Self {
+            targets: vec![BatchTarget::Jarvis],
+            iterations: DEFAULT_ITERATIONS,
+            last_run: None,
+            status: None,
+            run: None,
+        }
+    }
+}
+
+impl BenchmarkState {
+    pub fn load() -> Self {
+        Self {
+            last_run: load_last_run(),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    pub fn toggle_target(&mut self, target: BatchTarget) {
+        if let Some(position) = self.targets.iter().position(|entry| *entry == target) {
+            self.targets.remove(position);
+        } else {
+            self.targets.push(target);
+        }
+    }
+
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.run
+            .as_ref()
+            .map(|run| (run.record.samples.len(), run.expected))
+    }
+
+    /// Ejecución que se muestra: la que está en curso o la última completada.
+    pub fn shown_run(&self) -> Option<&BenchmarkRecord> {
+        match &self.run {
+            Some(run) => Some(&run.record),
+            None => self.last_run.as_ref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(target: &str, size: BenchmarkPromptSize, total_ms: u64) -> BenchmarkSample {
+        BenchmarkSample {
+            target: target.to_string(),
+            size,
+            iteration: 0,
+            ttfb_ms: (target != "jarvis").then_some(total_ms - 10),
+            total_ms,
+            response_tokens: 50,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn summarizes_medians_and_updates_card_latencies() {
+        let mut failed = sample("openai", BenchmarkPromptSize::Long, 30_000);
+        failed.error = Some("Tiempo agotado".to_string());
+        let record = BenchmarkRecord {
+            started_at: "2026-10-15 10:00:00".to_string(),
+            iterations: 2,
+            models: vec![
+                BenchmarkModel {
+                    target: "openai".to_string(),
+                    model: "gpt-4o-mini".to_string(),
+                },
+                BenchmarkModel {
+                    target: "jarvis".to_string(),
+                    model: "phi-3".to_string(),
+                },
+            ],
+            samples: vec![
+                sample("openai", BenchmarkPromptSize::Short, 400),
+                sample("openai", BenchmarkPromptSize::Short, 600),
+                sample("openai", BenchmarkPromptSize::Long, 1_000),
+                failed,
+                sample("jarvis", BenchmarkPromptSize::Short, 250),
+            ],
+        };
+
+        let summaries = record.summaries();
+        let short = &summaries[0];
+        assert_eq!(short.size, Some(BenchmarkPromptSize::Short));
+        assert_eq!(short.total_ms, Some(400));
+        assert_eq!(short.p90_total_ms, Some(600));
+        assert_eq!(short.ttfb_ms, Some(390));
+        let overall = summaries
+            .iter()
+            .find(|summary| summary.target == "openai" && summary.size.is_none())
+            .unwrap();
+        assert_eq!((overall.runs, overall.failures), (4, 1));
+        assert_eq!(overall.total_ms, Some(600));
+        let jarvis = summaries.last().unwrap();
+        assert_eq!(jarvis.ttfb_ms, None);
+        assert_eq!(jarvis.tokens_per_second, Some(200.0));
+
+        assert_eq!(
+            record.card_latencies(),
+            vec![(RemoteProviderKind::OpenAi, "gpt-4o-mini".to_string(), 600)]
+        );
+        assert!(record
+            .render_report()
+            .contains("| Jarvis (local) | phi-3 | Todos |"));
+    }
+}
@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+
+use super::{vault, ChatMessage};
+
+pub(super) const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+/// Copia de un mensaje fijado. Se guarda aparte del historial para que los marcadores
+/// sobrevivan a la conversación en la que se crearon.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Bookmark {
+    /// Conversación de origen, identificada por su inicio.
+    pub conversation: String,
+    pub sender: String,
+    pub text: String,
+    pub timestamp: String,
+    pub pinned_at: String,
+}
+
+impl Bookmark {
+    pub fn from_message(conversation: &str, message: &ChatMessage) -> Self {
+        Self {
+            conversation: conversation.to_string(),
+            sender: message.sender.clone(),
+            text: message.text.clone(),
+            timestamp: message.timestamp.clone(),
+            pinned_at: Local::now().format("%Y-%m-%d %H:%M").to_string(),
+        }
+    }
+
+    pub fn matches(&self, conversation: &str, message: &ChatMessage) -> bool {
+        self.conversation == conversation
+            && self.sender == message.sender
+            && self.timestamp == message.timestamp
+            && self.text == message.text
+    }
+}
+
+/// Marcadores de todas las conversaciones y estado de la sección de recursos.
+pub struct BookmarkState {
+    pub entries: Vec<Bookmark>,
+    /// Identificador de la conversación abierta en esta sesión.
+    pub conversation: String,
+    pub filter: String,
+    pub export_path: String,
+    pub status: Option<String>,
+}
+
+impl Default for BookmarkState {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            conversation: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            filter: String::new(),
+            export_path: "marcadores.md".to_string(),
+            status: None,
+        }
+    }
+}
+
+impl BookmarkState {
+    pub fn load() -> Self {
+        let entries = AppConfig::data_file(BOOKMARKS_FILE)
+            .ok()
+            .and_then(|path| vault::read_to_string(&path).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            entries,
+            ..Self::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = AppConfig::data_file(BOOKMARKS_FILE)?;
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        vault::write(&path, &json)
+    }
+
+    /// Marcadores que contienen `filter`, agrupados por conversación y con su posición
+    /// en `entries`. Las conversaciones más recientes van primero.
+    pub fn grouped(&self) -> Vec<(String, Vec<(usize, &Bookmark)>)> {
+        let needle = self.filter.trim().to_lowercase();
+        let mut groups: Vec<(String, Vec<(usize, &Bookmark)>)> = Vec::new();
+        for (index, bookmark) in self.entries.iter().enumerate().rev() {
+            if !needle.is_empty()
+                && !format!("{} {}", bookmark.sender, bookmark.text)
+                    .to_lowercase()
+                    .contains(&needle)
+            {
+                continue;
+            }
+            match groups
+                .iter_mut()
+                .find(|(conversation, _)| *conversation == bookmark.conversation)
+            {
+                Some((_, entries)) => entries.push((index, bookmark)),
+                None => groups.push((bookmark.conversation.clone(), vec![(index, bookmark)])),
+            }
+        }
+        groups
+    }
+
+    /// Escribe los marcadores en Markdown, agrupados por conversación.
+    pub fn export_markdown(&self, path: &Path) -> Result<usize> {
+        let mut output = String::from("# Marcadores de JungleMonkAI\n");
+        let mut written = 0;
+        for (conversation, entries) in self.grouped() {
+            output.push_str(&format!("\n## Conversación del {}\n", conversation));
+            for (_, bookmark) in entries {
+                output.push_str(&format!(
+                    "\n### {} · {} (fijado el {})\n\n{}\n",
+                    bookmark.sender, bookmark.timestamp, bookmark.pinned_at, bookmark.text
+                ));
+                written += 1;
+            }
+        }
+
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, output)
+            .with_context(|| format!("No se pudo escribir {}", path.display()))?;
+        Ok(written)
+    }
+}
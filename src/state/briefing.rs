@@ -0,0 +1,146 @@
+use std::io::ErrorKind;
+use std::process::{Command, Stdio};
+use std::sync::mpsc::Receiver;
+
+use anyhow::{bail, Context, Result};
+
+use super::{LogEntry, LogStatus};
+
+/// Origen con el que se registran las puestas al día en la actividad.
+pub const BRIEFING_SOURCE: &str = "Puesta al día";
+/// Entradas más recientes que se resumen como máximo.
+const MAX_ENTRIES: usize = 60;
+
+/// Entradas posteriores a `since`, sin las de puestas al día anteriores. Los registros
+/// usan `%Y-%m-%d %H:%M:%S`, así que se comparan como texto.
+pub fn recent_entries<'a>(entries: &'a [LogEntry], since: Option<&str>) -> Vec<&'a LogEntry> {
+    let recent: Vec<&LogEntry> = entries
+        .iter()
+        .filter(|entry| entry.source != BRIEFING_SOURCE)
+        .filter(|entry| since.is_none_or(|since| entry.timestamp.as_str() > since))
+        .collect();
+    let skip = recent.len().saturating_sub(MAX_ENTRIES);
+    recent.into_iter().skip(skip).collect()
+}
+
+fn status_label(status: LogStatus) -> &'static str {
+    match status {
+        LogStatus::Ok => "ok",
+        LogStatus::Warning => "aviso",
+        LogStatus::Error => "error",
+        LogStatus::Running => "en curso",
+    }
+}
+
+pub fn build_prompt(entries: &[&LogEntry]) -> String {
+    let lines: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "{} [{}] {}: {}",
+                entry.timestamp,
+                status_label(entry.status),
+                entry.source,
+                entry.message
+            )
+        })
+        .collect();
+    format!(
+        "Estos son los registros de actividad de JungleMonkAI mientras el usuario no estaba:\n\n{}\n\nResume en tres a cinco frases qué terminó bien, qué falló y qué sigue en curso, como si se lo contaras en voz alta. No uses Markdown, listas ni marcas de tiempo completas.",
+        lines.join("\n")
+    )
+}
+
+/// Lee `text` con el sintetizador de voz del sistema sin esperar a que termine.
+pub fn speak(text: &str) -> Result<()> {
+    let candidates: Vec<Command> = if cfg!(target_os = "macos") {
+        vec![speech_command("say", &[text])]
+    } else if cfg!(target_os = "windows") {
+        let mut command = speech_command(
+            "powershell",
+            &[
+                "-NoProfile",
+                "-Command",
+                "Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak($env:JUNGLEMONK_SPEECH)",
+            ],
+        );
+        command.env("JUNGLEMONK_SPEECH", text);
+        vec![command]
+    } else {
+        vec![
+            speech_command("spd-say", &[text]),
+            speech_command("espeak-ng", &[text]),
+            speech_command("espeak", &[text]),
+        ]
+    };
+    for mut command in candidates {
+        match command.spawn() {
+            Ok(_) => return Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => continue,
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!(
+                        "No se pudo ejecutar {}",
+                        command.get_program().to_string_lossy()
+                    )
+                })
+            }
+        }
+    }
+    bail!("No hay un sintetizador de voz instalado (say, spd-say o espeak).")
+}
+
+fn speech_command(program: &str, args: &[&str]) -> Command {
+    let mut command = Command::new(program);
+    command
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    command
+}
+
+/// Puesta al día en curso y hasta dónde llegó la última.
+#[derive(Default)]
+pub struct BriefingState {
+    /// Proveedor que redacta y su respuesta en segundo plano.
+    pub run: Option<(String, Receiver<Result<String, String>>)>,
+    /// Marca de tiempo de la última entrada resumida.
+    pub since: Option<String>,
+    pub status: Option<String>,
+}
+
+impl BriefingState {
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(timestamp: &str, source: &str, status: LogStatus) -> LogEntry {
+        LogEntry {
+            status,
+            source: source.to_string(),
+            message: format!("{} terminó", source),
+            timestamp: timestamp.to_string(),
+        }
+    }
+
+    #[test]
+    fn summarizes_only_entries_since_the_last_briefing() {
+        let entries = vec![
+            entry("2024-05-15 08:00:00", "Cron", LogStatus::Ok),
+            entry("2024-05-15 09:30:00", BRIEFING_SOURCE, LogStatus::Ok),
+            entry("2024-05-15 10:15:00", "Workflows", LogStatus::Error),
+        ];
+        assert_eq!(recent_entries(&entries, None).len(), 2);
+        let recent = recent_entries(&entries, Some("2024-05-15 09:00:00"));
+        assert_eq!(recent.len(), 1);
+        assert!(build_prompt(&recent)
+            .contains("2024-05-15 10:15:00 [error] Workflows: Workflows terminó"));
+        assert!(recent_entries(&entries, Some("2024-05-15 10:15:00")).is_empty());
+    }
+}
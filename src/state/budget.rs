@@ -0,0 +1,123 @@
+use chrono::Local;
+
+use crate::config::{ProviderBudget, ProviderSpend};
+
+/// Periodo al que se aplica un presupuesto.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    pub fn label(self) -> &'static str {
+        match self {
+            BudgetPeriod::Daily => "diario",
+            BudgetPeriod::Monthly => "mensual",
+        }
+    }
+}
+
+/// Umbral de presupuesto que se acaba de cruzar.
+#[derive(Clone, Debug)]
+pub struct BudgetAlert {
+    pub period: BudgetPeriod,
+    pub percent: u8,
+    pub spent: f32,
+    pub limit: f32,
+}
+
+/// Presupuesto agotado que impide nuevas llamadas.
+#[derive(Clone, Debug)]
+pub struct BudgetExhausted {
+    pub period: BudgetPeriod,
+    pub spent: f32,
+    pub limit: f32,
+}
+
+/// Reinicia los acumulados cuando cambia el día o el mes.
+pub fn roll_over(spend: &mut ProviderSpend) {
+    let now = Local::now();
+    let day = now.format("%Y-%m-%d").to_string();
+    let month = now.format("%Y-%m").to_string();
+    if spend.day != day {
+        spend.day = day;
+        spend.daily_usd = 0.0;
+        spend.daily_alerted = 0;
+    }
+    if spend.month != month {
+        spend.month = month;
+        spend.monthly_usd = 0.0;
+        spend.monthly_alerted = 0;
+    }
+}
+
+/// Suma `cost` al gasto y devuelve los umbrales de aviso cruzados por primera vez.
+pub fn record_spend(
+    budget: &ProviderBudget,
+    spend: &mut ProviderSpend,
+    cost: f32,
+) -> Vec<BudgetAlert> {
+    roll_over(spend);
+    spend.daily_usd += cost;
+    spend.monthly_usd += cost;
+
+    let mut alerts = Vec::new();
+    let periods = [
+        (
+            BudgetPeriod::Daily,
+            budget.daily_usd,
+            spend.daily_usd,
+            &mut spend.daily_alerted,
+        ),
+        (
+            BudgetPeriod::Monthly,
+            budget.monthly_usd,
+            spend.monthly_usd,
+            &mut spend.monthly_alerted,
+        ),
+    ];
+    for (period, limit, spent, alerted) in periods {
+        let Some(limit) = limit.filter(|limit| *limit > 0.0) else {
+            continue;
+        };
+        let used_percent = spent / limit * 100.0;
+        let crossed = budget
+            .alert_percents
+            .iter()
+            .copied()
+            .filter(|percent| *percent > *alerted && used_percent >= f32::from(*percent))
+            .max();
+        if let Some(percent) = crossed {
+            *alerted = percent;
+            alerts.push(BudgetAlert {
+                period,
+                percent,
+                spent,
+                limit,
+            });
+        }
+    }
+    alerts
+}
+
+/// Primer presupuesto agotado, si lo hay. Solo importa cuando el bloqueo está activo.
+pub fn exhausted(budget: &ProviderBudget, spend: &mut ProviderSpend) -> Option<BudgetExhausted> {
+    if !budget.hard_block {
+        return None;
+    }
+    roll_over(spend);
+    [
+        (BudgetPeriod::Daily, budget.daily_usd, spend.daily_usd),
+        (BudgetPeriod::Monthly, budget.monthly_usd, spend.monthly_usd),
+    ]
+    .into_iter()
+    .find_map(|(period, limit, spent)| {
+        let limit = limit.filter(|limit| *limit > 0.0)?;
+        (spent >= limit).then_some(BudgetExhausted {
+            period,
+            spent,
+            limit,
+        })
+    })
+}
@@ -1,11 +1,15 @@
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
 
 use super::{
+    connectivity::QueuedProviderCall,
     feature::{CommandRegistry, FeatureModule, WorkbenchRegistry},
     navigation::NavigationNode,
-    ChatMessage, ChatRoutingState, CustomCommand, CustomCommandAction, LocalInstallMessage,
-    MainView, NavigationRegistry, NavigationTarget, PendingLocalInstall, PendingProviderCall,
-    ProviderResponse, SECTION_PRIMARY,
+    runtime::{self, UiSender},
+    ChatMessage, ChatRoutingState, CustomCommand, CustomCommandAction, CustomCommandBehavior,
+    LocalInstallMessage, MainView, MessageQuote, NavigationRegistry, NavigationTarget,
+    PendingLocalInstall, PendingProviderCall, ProviderRequest, ProviderResponse, RemoteModelKey,
+    StructuredOutputState, SECTION_PRIMARY,
 };
 use crate::config::AppConfig;
 
@@ -13,25 +17,137 @@ pub struct ChatState {
     pub input: String,
     pub messages: Vec<ChatMessage>,
     pub custom_commands: Vec<CustomCommand>,
-    pub new_command: String,
-    pub new_command_action: CustomCommandAction,
+    /// Formulario del editor de comandos personalizados.
+    pub command_draft: CustomCommandDraft,
     pub command_feedback: Option<String>,
     pub show_functions_modal: bool,
     pub routing: ChatRoutingState,
     pub pending_copy_conversation: bool,
-    pub provider_response_rx: Receiver<ProviderResponse>,
-    pub provider_response_tx: Sender<ProviderResponse>,
-    pub local_install_rx: Receiver<LocalInstallMessage>,
-    pub local_install_tx: Sender<LocalInstallMessage>,
-    pub pending_local_installs: Vec<PendingLocalInstall>,
-    pub pending_provider_calls: Vec<PendingProviderCall>,
+    pub(crate) provider_response_rx: Receiver<ProviderResponse>,
+    pub(crate) provider_response_tx: UiSender<ProviderResponse>,
+    pub(crate) local_install_rx: Receiver<LocalInstallMessage>,
+    pub(crate) local_install_tx: UiSender<LocalInstallMessage>,
+    pub(crate) pending_local_installs: Vec<PendingLocalInstall>,
+    pub(crate) pending_provider_calls: Vec<PendingProviderCall>,
+    /// Llamadas aceptadas que esperan el vector de su prompt para recordar contexto.
+    pub(crate) recall_waiting: Vec<ProviderRequest>,
     pub next_provider_call_id: u64,
+    pub editing_message: Option<usize>,
+    pub pending_reply: Option<MessageQuote>,
+    pub offline_queue: Vec<QueuedProviderCall>,
+    /// Mensaje resaltado al recorrer el historial con el teclado.
+    pub focused_message: Option<usize>,
+    /// Indica que el historial debe desplazarse hasta `focused_message`.
+    pub scroll_to_focused: bool,
+    /// Adaptadores LoRA activados para esta conversación, en orden de fusión.
+    pub lora_adapters: Vec<PathBuf>,
+    /// Modelo del catálogo al que se dirige el siguiente mensaje enrutado, en lugar del
+    /// modelo por defecto de su proveedor.
+    pub model_override: Option<RemoteModelKey>,
+    /// Envía el siguiente mensaje enrutado al proveedor aunque haya una respuesta en caché.
+    pub bypass_response_cache: bool,
+    /// Esquema JSON que se adjunta a los mensajes enrutados mientras el modo está activo.
+    pub structured: StructuredOutputState,
+    /// Envío retenido hasta que se confirme su coste estimado.
+    pub cost_confirmation: Option<CostConfirmation>,
+    /// Envío retenido porque incluye contenido de proyectos marcados como solo local.
+    pub local_only_confirmation: Option<LocalOnlyConfirmation>,
+    /// Permite, solo durante el envío en curso, mandar a proveedores remotos contexto de
+    /// proyectos solo locales. Lo activa la confirmación explícita del usuario.
+    pub local_only_override: bool,
+}
+
+/// Mensaje cuyo coste estimado supera el umbral de confirmación.
+#[derive(Clone, Debug)]
+pub struct CostConfirmation {
+    /// Texto del compositor al que corresponde la estimación.
+    pub input: String,
+    pub total: f32,
+    pub confirmed: bool,
+}
+
+/// Mensaje que enviaría a un proveedor remoto contenido de proyectos solo locales.
+#[derive(Clone, Debug)]
+pub struct LocalOnlyConfirmation {
+    /// Texto del compositor al que corresponde el bloqueo.
+    pub input: String,
+    pub projects: Vec<String>,
+    pub confirmed: bool,
+}
+
+/// Comando personalizado en edición, con los argumentos y la salida de la última prueba.
+#[derive(Clone, Debug)]
+pub struct CustomCommandDraft {
+    /// Índice del comando que se modifica; `None` crea uno nuevo.
+    pub editing: Option<usize>,
+    pub trigger: String,
+    pub action: CustomCommandAction,
+    pub behavior: CustomCommandBehavior,
+    pub test_arguments: String,
+    pub test_output: Vec<String>,
+}
+
+impl Default for CustomCommandDraft {
+    fn default() -> Self {
+        Self {
+            editing: None,
+            trigger: String::new(),
+            action: CustomCommandAction::ShowCurrentTime,
+            behavior: CustomCommandBehavior::Action,
+            test_arguments: String::new(),
+            test_output: Vec::new(),
+        }
+    }
+}
+
+impl CustomCommandDraft {
+    pub fn from_command(index: usize, command: &CustomCommand) -> Self {
+        Self {
+            editing: Some(index),
+            trigger: command.trigger.clone(),
+            action: command.action,
+            behavior: command.behavior.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Comando resultante del formulario, con el disparador normalizado a `/nombre`.
+    pub fn to_command(&self) -> Result<CustomCommand, String> {
+        let trimmed = self.trigger.trim();
+        if trimmed.is_empty() || trimmed == "/" {
+            return Err("El comando necesita un disparador.".to_string());
+        }
+        if trimmed.contains(char::is_whitespace) {
+            return Err("El disparador no puede contener espacios.".to_string());
+        }
+        let trigger = if trimmed.starts_with('/') {
+            trimmed.to_string()
+        } else {
+            format!("/{}", trimmed)
+        };
+
+        match &self.behavior {
+            CustomCommandBehavior::Prompt { template, .. } if template.trim().is_empty() => {
+                return Err("La plantilla del prompt está vacía.".to_string());
+            }
+            CustomCommandBehavior::Script { commands } if commands.trim().is_empty() => {
+                return Err("El guion no contiene comandos.".to_string());
+            }
+            _ => {}
+        }
+
+        Ok(CustomCommand {
+            trigger,
+            action: self.action,
+            behavior: self.behavior.clone(),
+        })
+    }
 }
 
 impl ChatState {
     pub fn from_config(config: &AppConfig) -> Self {
-        let (provider_response_tx, provider_response_rx) = mpsc::channel();
-        let (local_install_tx, local_install_rx) = mpsc::channel();
+        let (provider_response_tx, provider_response_rx) = runtime::ui_channel();
+        let (local_install_tx, local_install_rx) = runtime::ui_channel();
 
         let mut state = Self {
             input: String::new(),
@@ -41,8 +157,7 @@ impl ChatState {
             } else {
                 config.custom_commands.clone()
             },
-            new_command: String::new(),
-            new_command_action: CustomCommandAction::ShowCurrentTime,
+            command_draft: CustomCommandDraft::default(),
             command_feedback: None,
             show_functions_modal: false,
             routing: ChatRoutingState::default(),
@@ -53,7 +168,20 @@ impl ChatState {
             local_install_tx,
             pending_local_installs: Vec::new(),
             pending_provider_calls: Vec::new(),
+            recall_waiting: Vec::new(),
             next_provider_call_id: 0,
+            editing_message: None,
+            pending_reply: None,
+            offline_queue: Vec::new(),
+            focused_message: None,
+            scroll_to_focused: false,
+            lora_adapters: Vec::new(),
+            model_override: None,
+            bypass_response_cache: false,
+            structured: StructuredOutputState::default(),
+            cost_confirmation: None,
+            local_only_confirmation: None,
+            local_only_override: false,
         };
 
         let routing_hint = state.routing.status.clone().unwrap_or_else(|| {
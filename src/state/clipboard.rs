@@ -0,0 +1,324 @@
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::runtime;
+use crate::config::ClipboardWatcherConfig;
+
+/// Caracteres a partir de los cuales lo copiado se ignora.
+const MAX_CLIPBOARD_CHARS: usize = 20_000;
+
+static TRACEBACK_HEADER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?m)Traceback \(most recent call last\):|panicked at |^stack backtrace:|^goroutine \d+ \[|Exception in thread |^\s*File .+, line \d+",
+    )
+    .expect("patrón válido")
+});
+static STACK_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(at [\w$.<>/\\-]+ ?\(.*\)|at .+:\d+:\d+\)?|\d+: [\w:<>]+)\s*$")
+        .expect("patrón válido")
+});
+static CODE_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s*(fn |pub |let |const |impl |use |import |from \S+ import|def |class |function |return\b|if .*[:{]\s*$|for .*[:{]\s*$|#include|//|/\*|\}|\{\s*$)|;\s*$|=>|->",
+    )
+    .expect("patrón válido")
+});
+
+/// Tipo de contenido reconocido en el portapapeles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClipboardContentKind {
+    StackTrace,
+    Code,
+    Url,
+}
+
+impl ClipboardContentKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            ClipboardContentKind::StackTrace => "traza de error",
+            ClipboardContentKind::Code => "código",
+            ClipboardContentKind::Url => "URL",
+        }
+    }
+
+    /// Acciones que se ofrecen en el aviso para este tipo de contenido.
+    pub fn actions(self) -> &'static [ClipboardAction] {
+        match self {
+            ClipboardContentKind::StackTrace => &TRACEBACK_ACTIONS,
+            ClipboardContentKind::Code => &CODE_ACTIONS,
+            ClipboardContentKind::Url => &URL_ACTIONS,
+        }
+    }
+}
+
+/// Acción de un clic sobre lo copiado: una plantilla para un nuevo mensaje del chat.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ClipboardAction {
+    pub label: &'static str,
+    /// Plantilla del mensaje; `{texto}` se sustituye por lo copiado.
+    pub prompt: &'static str,
+    /// Adjunta el texto de la página como contexto, para las URL.
+    pub attach_page: bool,
+}
+
+impl ClipboardAction {
+    pub fn render(&self, text: &str) -> String {
+        self.prompt.replace("{texto}", text.trim())
+    }
+}
+
+const TRACEBACK_ACTIONS: [ClipboardAction; 2] = [
+    ClipboardAction {
+        label: "Explicar este traceback",
+        prompt: "Explica este error: qué lo provoca y en qué parte del código se origina.\n\n```\n{texto}\n```",
+        attach_page: false,
+    },
+    ClipboardAction {
+        label: "Proponer un arreglo",
+        prompt: "Propón un arreglo para el error de esta traza, con el cambio de código concreto.\n\n```\n{texto}\n```",
+        attach_page: false,
+    },
+];
+
+const CODE_ACTIONS: [ClipboardAction; 2] = [
+    ClipboardAction {
+        label: "Explicar este código",
+        prompt: "Explica paso a paso qué hace este código.\n\n```\n{texto}\n```",
+        attach_page: false,
+    },
+    ClipboardAction {
+        label: "Revisar este código",
+        prompt: "Revisa este código: señala errores, casos límite y mejoras.\n\n```\n{texto}\n```",
+        attach_page: false,
+    },
+];
+
+const URL_ACTIONS: [ClipboardAction; 2] = [
+    ClipboardAction {
+        label: "Resumir esta URL",
+        prompt: "Resume el contenido de {texto} en unos pocos puntos.",
+        attach_page: true,
+    },
+    ClipboardAction {
+        label: "Adjuntar como contexto",
+        prompt: "Usa como contexto la página {texto}.\n\n",
+        attach_page: true,
+    },
+];
+
+/// Reconoce trazas de error, fragmentos de código y URL sueltas. Las trazas tienen
+/// prioridad porque también contienen líneas que parecen código.
+pub fn classify(text: &str) -> Option<ClipboardContentKind> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    if !text.contains(char::is_whitespace)
+        && (text.starts_with("https://") || text.starts_with("http://"))
+    {
+        return Some(ClipboardContentKind::Url);
+    }
+
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let frames = lines
+        .iter()
+        .filter(|line| STACK_FRAME.is_match(line))
+        .count();
+    if TRACEBACK_HEADER.is_match(text) || frames >= 2 {
+        return Some(ClipboardContentKind::StackTrace);
+    }
+
+    let code_lines = lines.iter().filter(|line| CODE_LINE.is_match(line)).count();
+    (code_lines >= 2 && code_lines * 10 >= lines.len() * 3).then_some(ClipboardContentKind::Code)
+}
+
+/// Clasifica según los detectores activados en la configuración.
+pub fn classify_enabled(
+    config: &ClipboardWatcherConfig,
+    text: &str,
+) -> Option<ClipboardContentKind> {
+    classify(text).filter(|kind| match kind {
+        ClipboardContentKind::StackTrace => config.detect_stack_traces,
+        ClipboardContentKind::Code => config.detect_code,
+        ClipboardContentKind::Url => config.detect_urls,
+    })
+}
+
+/// Lee el texto del portapapeles con la herramienta del sistema: `pbpaste` en macOS,
+/// PowerShell en Windows y `wl-paste`, `xclip` o `xsel` en Linux.
+pub fn read_clipboard() -> Result<String, String> {
+    let candidates: Vec<(&str, Vec<&str>)> = if cfg!(target_os = "macos") {
+        vec![("pbpaste", vec![])]
+    } else if cfg!(target_os = "windows") {
+        vec![(
+            "powershell",
+            vec!["-NoProfile", "-Command", "Get-Clipboard -Raw"],
+        )]
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        vec![("wl-paste", vec!["--no-newline"])]
+    } else {
+        vec![
+            ("xclip", vec!["-selection", "clipboard", "-o"]),
+            ("xsel", vec!["--clipboard", "--output"]),
+        ]
+    };
+
+    let mut missing = Vec::new();
+    for (program, args) in candidates {
+        match Command::new(program)
+            .args(&args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+        {
+            // Un portapapeles vacío o sin texto también termina con error: no es un fallo.
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+            }
+            Ok(_) => return Ok(String::new()),
+            Err(_) => missing.push(program),
+        }
+    }
+    Err(format!(
+        "No se encontró {} para leer el portapapeles",
+        missing.join(" ni ")
+    ))
+}
+
+/// Vigila el portapapeles en segundo plano y avisa de lo que se copia.
+#[derive(Default)]
+pub struct ClipboardState {
+    /// Último contenido reconocido, pendiente de una acción del aviso.
+    pub capture: Option<(ClipboardContentKind, String)>,
+    pub status: Option<String>,
+    rx: Option<Receiver<Result<String, String>>>,
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl ClipboardState {
+    /// Arranca el hilo que consulta el portapapeles cada `poll_interval_secs`. Lo que
+    /// ya hubiera copiado al arrancar no se ofrece.
+    pub fn start(&mut self, poll_interval_secs: u64) {
+        self.stop();
+        let (tx, rx) = runtime::ui_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let interval = Duration::from_secs(poll_interval_secs.max(1));
+        {
+            let stop = Arc::clone(&stop);
+            let _ = runtime::spawn_service("clipboard", move || {
+                let mut last = match read_clipboard() {
+                    Ok(text) => text,
+                    Err(err) => {
+                        let _ = tx.send(Err(err));
+                        return;
+                    }
+                };
+                while !stop.load(Ordering::Relaxed) {
+                    thread::sleep(interval);
+                    let text = match read_clipboard() {
+                        Ok(text) => text,
+                        Err(err) => {
+                            let _ = tx.send(Err(err));
+                            return;
+                        }
+                    };
+                    if text == last {
+                        continue;
+                    }
+                    last = text.clone();
+                    if text.chars().count() <= MAX_CLIPBOARD_CHARS && tx.send(Ok(text)).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        self.rx = Some(rx);
+        self.stop = Some(stop);
+        self.status = Some("Vigilando el portapapeles.".to_string());
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        self.rx = None;
+        self.capture = None;
+        self.status = None;
+    }
+
+    /// Contenidos nuevos del portapapeles desde la última consulta. Si la herramienta
+    /// del sistema falla, el vigilante se detiene y se devuelve el error.
+    pub fn poll(&mut self) -> Result<Vec<String>, String> {
+        let Some(rx) = self.rx.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let mut texts = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(Ok(text)) => texts.push(text),
+                Ok(Err(err)) => {
+                    self.stop();
+                    self.status = Some(err.clone());
+                    return Err(err);
+                }
+                Err(TryRecvError::Empty) => return Ok(texts),
+                Err(TryRecvError::Disconnected) => {
+                    self.stop();
+                    return Ok(texts);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ClipboardState {
+    fn drop(&mut self) {
+        if let Some(stop) = &self.stop {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_recognises_tracebacks_code_and_urls() {
+        let python = "Traceback (most recent call last):\n  File \"app.py\", line 3, in <module>\n    main()\nValueError: bad";
+        assert_eq!(classify(python), Some(ClipboardContentKind::StackTrace));
+        let js =
+            "TypeError: x is undefined\n    at render (app.js:10:5)\n    at main (app.js:20:3)";
+        assert_eq!(classify(js), Some(ClipboardContentKind::StackTrace));
+        let rust = "fn main() {\n    let x = 1;\n    println!(\"{}\", x);\n}";
+        assert_eq!(classify(rust), Some(ClipboardContentKind::Code));
+        assert_eq!(
+            classify("  https://example.com/doc?id=1 \n"),
+            Some(ClipboardContentKind::Url)
+        );
+        assert_eq!(classify("Nos vemos mañana a las diez."), None);
+        assert_eq!(classify("visita https://example.com hoy"), None);
+
+        let config = ClipboardWatcherConfig {
+            detect_code: false,
+            ..ClipboardWatcherConfig::default()
+        };
+        assert_eq!(classify_enabled(&config, rust), None);
+
+        let action = ClipboardContentKind::Url.actions()[0];
+        assert_eq!(
+            action.render(" https://example.com "),
+            "Resume el contenido de https://example.com en unos pocos puntos."
+        );
+    }
+}
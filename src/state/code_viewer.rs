@@ -0,0 +1,426 @@
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+
+use anyhow::{bail, Result};
+
+use super::{
+    feature::{FeatureModule, WorkbenchRegistry},
+    git_assist::{
+        git_read_file, BlameLine, ConflictFile, ConflictState, DiffDocument, DiffScope,
+        FetchStatus, FileCommit, FileEncoding,
+    },
+    knowledge_index::SKIPPED_DIRECTORIES,
+    navigation::NavigationNode,
+    MainView, NavigationRegistry, NavigationTarget, SECTION_PRIMARY,
+};
+
+/// Líneas de contexto que acompañan a la selección por arriba y por abajo.
+pub const CONTEXT_LINES: usize = 12;
+/// De archivos mayores solo se muestra el principio.
+const MAX_VIEW_BYTES: usize = 1024 * 1024;
+/// Tope de archivos listados por proyecto, para no bloquear la interfaz en monorepos.
+const MAX_LISTED_FILES: usize = 5000;
+
+/// Rango de un archivo al que se refiere un mensaje del chat. Las líneas empiezan en 1.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeAnchor {
+    pub project: String,
+    pub path: PathBuf,
+    /// Ruta relativa a la raíz del proyecto, con `/`.
+    pub relative: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl CodeAnchor {
+    pub fn label(&self) -> String {
+        if self.start == self.end {
+            format!("{}:{}", self.relative, self.start)
+        } else {
+            format!("{}:{}-{}", self.relative, self.start, self.end)
+        }
+    }
+}
+
+/// Archivo abierto en el visor.
+#[derive(Clone, Debug)]
+pub struct OpenFile {
+    pub path: PathBuf,
+    pub relative: String,
+    pub lines: Vec<String>,
+    pub encoding: FileEncoding,
+    /// Tamaño completo cuando solo se cargó el principio del archivo.
+    pub truncated_from: Option<u64>,
+}
+
+impl OpenFile {
+    pub fn load(root: &Path, path: &Path) -> Result<Self> {
+        let content = git_read_file(root, path, MAX_VIEW_BYTES)?;
+        let Some(encoding) = content.encoding else {
+            bail!("{} es un archivo binario", path.display());
+        };
+        Ok(Self {
+            path: path.to_path_buf(),
+            relative: relative_path(root, path),
+            lines: content.text.lines().map(str::to_string).collect(),
+            encoding,
+            truncated_from: content.truncated.then_some(content.total_bytes),
+        })
+    }
+}
+
+/// Consulta de git del explorador que corre en segundo plano, con lo necesario para
+/// terminarla cuando llegue la respuesta.
+#[derive(Clone, Debug)]
+pub enum GitQuery {
+    /// Commits del archivo abierto.
+    History,
+    /// `git blame` del archivo para preguntar quién escribió la selección.
+    Blame {
+        anchor: CodeAnchor,
+        selection_prompt: String,
+    },
+    /// Cambio de un commit sobre el archivo abierto, para explicarlo.
+    CommitDiff { hash: String, relative: String },
+    /// Diff del archivo o del árbol de trabajo para redactar `document`.
+    WorkingDiff {
+        document: DiffDocument,
+        scope: DiffScope,
+    },
+    /// Archivos con conflictos de fusión en el proyecto.
+    Conflicts,
+}
+
+/// Resultado de una `GitQuery`.
+pub enum GitAnswer {
+    History(Vec<FileCommit>),
+    Blame(Vec<BlameLine>),
+    Diff(String),
+    Conflicts(Vec<ConflictFile>),
+}
+
+/// Sugerencia de parche extraída de una respuesta anclada a un archivo.
+#[derive(Clone, Debug)]
+pub struct PatchSuggestion {
+    pub anchor: CodeAnchor,
+    pub diff: String,
+}
+
+/// Explorador ligero de los proyectos locales conectados, con selección de líneas para
+/// preguntar por ellas en el chat.
+#[derive(Default)]
+pub struct CodeViewerState {
+    pub project: Option<String>,
+    pub root: PathBuf,
+    pub files: Vec<PathBuf>,
+    pub filter: String,
+    pub open: Option<OpenFile>,
+    /// Línea donde empezó la selección, para extenderla con Mayús+clic.
+    pub selection_anchor: Option<usize>,
+    pub selection: Option<RangeInclusive<usize>>,
+    pub question: String,
+    pub request_patch: bool,
+    pub patch: Option<PatchSuggestion>,
+    /// El asistente de commits usa el diff de todo el árbol en lugar del archivo abierto.
+    pub diff_whole_tree: bool,
+    /// Commits del archivo abierto, cargados bajo demanda.
+    pub history: Option<Vec<FileCommit>>,
+    pub conflicts: ConflictState,
+    /// Ramas frente a sus remotas tras el último fetch del proyecto.
+    pub fetch: Option<FetchStatus>,
+    pub fetch_rx: Option<Receiver<Result<FetchStatus, String>>>,
+    /// Consulta de git en curso; solo hay una a la vez.
+    pub git_job: Option<(GitQuery, Receiver<Result<GitAnswer, String>>)>,
+    pub status: Option<String>,
+}
+
+impl CodeViewerState {
+    /// Abre un proyecto y lista sus archivos.
+    pub fn open_project(&mut self, project: &str, root: &Path) -> Result<usize> {
+        if !root.is_dir() {
+            bail!("La carpeta {} no existe en este equipo", root.display());
+        }
+        self.project = Some(project.to_string());
+        self.root = root.to_path_buf();
+        self.files = list_files(root);
+        self.open = None;
+        self.fetch = None;
+        self.fetch_rx = None;
+        self.git_job = None;
+        self.selection = None;
+        self.selection_anchor = None;
+        self.status = None;
+        Ok(self.files.len())
+    }
+
+    pub fn open_file(&mut self, path: &Path) -> Result<()> {
+        self.open = Some(OpenFile::load(&self.root, path)?);
+        self.selection = None;
+        self.selection_anchor = None;
+        self.history = None;
+        self.git_job = None;
+        Ok(())
+    }
+
+    /// Selecciona `line`; con `extend` amplía la selección desde la línea inicial.
+    pub fn click_line(&mut self, line: usize, extend: bool) {
+        match (self.selection_anchor, extend) {
+            (Some(anchor), true) => {
+                self.selection = Some(anchor.min(line)..=anchor.max(line));
+            }
+            _ => {
+                self.selection_anchor = Some(line);
+                self.selection = Some(line..=line);
+            }
+        }
+    }
+
+    pub fn is_selected(&self, line: usize) -> bool {
+        self.selection
+            .as_ref()
+            .is_some_and(|selection| selection.contains(&line))
+    }
+
+    pub fn visible_files(&self) -> Vec<&PathBuf> {
+        let filter = self.filter.trim().to_lowercase();
+        self.files
+            .iter()
+            .filter(|path| {
+                filter.is_empty()
+                    || relative_path(&self.root, path)
+                        .to_lowercase()
+                        .contains(&filter)
+            })
+            .collect()
+    }
+
+    /// Ancla de la selección actual.
+    pub fn current_anchor(&self) -> Option<CodeAnchor> {
+        let file = self.open.as_ref()?;
+        let selection = self.selection.as_ref()?;
+        Some(CodeAnchor {
+            project: self.project.clone().unwrap_or_default(),
+            path: file.path.clone(),
+            relative: file.relative.clone(),
+            start: *selection.start(),
+            end: *selection.end(),
+        })
+    }
+
+    /// Prompt con la pregunta, la selección y sus líneas de contexto.
+    pub fn selection_prompt(&self) -> Option<(CodeAnchor, String)> {
+        let file = self.open.as_ref()?;
+        let anchor = self.current_anchor()?;
+        let prompt = build_selection_prompt(
+            file,
+            anchor.start..=anchor.end,
+            &self.question,
+            self.request_patch,
+        );
+        Some((anchor, prompt))
+    }
+}
+
+impl FeatureModule for CodeViewerState {
+    fn register_navigation(&self, registry: &mut NavigationRegistry) {
+        let target = NavigationTarget::main(MainView::CodeViewer);
+        registry.register_node(NavigationNode {
+            id: target.id(),
+            label: "Código".into(),
+            description: Some(
+                "Explora los proyectos locales y pregunta al modelo por las líneas seleccionadas."
+                    .into(),
+            ),
+            icon: Some("📂".into()),
+            badge: None,
+            target,
+            order: 6,
+            section_id: SECTION_PRIMARY.to_string(),
+        });
+    }
+
+    fn register_workbench_views(&self, registry: &mut WorkbenchRegistry) {
+        crate::ui::chat::register_code_viewer_workbench_view(registry);
+    }
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn list_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let skipped = path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| SKIPPED_DIRECTORIES.contains(&name));
+                if !skipped {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                files.push(path);
+                if files.len() >= MAX_LISTED_FILES {
+                    files.sort();
+                    return files;
+                }
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Lenguaje del bloque de código según la extensión, para el resaltado del modelo.
+fn fence_language(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "rs" => "rust",
+        "py" => "python",
+        "js" | "mjs" | "cjs" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "go" => "go",
+        "java" => "java",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "hpp" => "cpp",
+        "sh" => "bash",
+        "toml" => "toml",
+        "json" => "json",
+        "yml" | "yaml" => "yaml",
+        "md" => "markdown",
+        _ => "",
+    }
+}
+
+pub fn build_selection_prompt(
+    file: &OpenFile,
+    selection: RangeInclusive<usize>,
+    question: &str,
+    request_patch: bool,
+) -> String {
+    let total = file.lines.len().max(1);
+    let start = (*selection.start()).clamp(1, total);
+    let end = (*selection.end()).clamp(start, total);
+    let context_start = start.saturating_sub(CONTEXT_LINES).max(1);
+    let context_end = (end + CONTEXT_LINES).min(total);
+
+    let numbered = |from: usize, to: usize| -> String {
+        (from..=to)
+            .filter_map(|number| {
+                let line = file.lines.get(number - 1)?;
+                Some(format!("{:>5} | {}", number, line))
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    let language = fence_language(&file.path);
+    let question = question.trim();
+    let question = if question.is_empty() {
+        "Explica qué hacen estas líneas y señala cualquier problema."
+    } else {
+        question
+    };
+
+    let mut prompt = format!(
+        "{}\n\nArchivo: {} (líneas {}-{})\n\nSelección:\n```{}\n{}\n```\n\nContexto (líneas {}-{}):\n```{}\n{}\n```",
+        question,
+        file.relative,
+        start,
+        end,
+        language,
+        numbered(start, end),
+        context_start,
+        context_end,
+        language,
+        numbered(context_start, context_end),
+    );
+    if request_patch {
+        prompt.push_str(&format!(
+            "\n\nSi propones cambios, inclúyelos como un diff unificado en un bloque ```diff con rutas a/{0} y b/{0}.",
+            file.relative
+        ));
+    }
+    prompt
+}
+
+/// Primer bloque ```diff o ```patch de una respuesta.
+pub fn extract_patch(text: &str) -> Option<String> {
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        let fence = line.trim_start();
+        if fence.starts_with("```diff") || fence.starts_with("```patch") {
+            let body: Vec<&str> = lines
+                .by_ref()
+                .take_while(|line| !line.trim_start().starts_with("```"))
+                .collect();
+            if !body.is_empty() {
+                return Some(body.join("\n") + "\n");
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_file(lines: usize) -> OpenFile {
+        OpenFile {
+            path: PathBuf::from("/proyecto/src/main.rs"),
+            relative: "src/main.rs".to_string(),
+            lines: (1..=lines)
+                .map(|number| format!("linea {}", number))
+                .collect(),
+            encoding: FileEncoding::Utf8,
+            truncated_from: None,
+        }
+    }
+
+    #[test]
+    fn selection_prompt_numbers_lines_and_clamps_context() {
+        let prompt = build_selection_prompt(&sample_file(20), 3..=4, "¿Qué hace?", true);
+        assert!(prompt.starts_with("¿Qué hace?\n\nArchivo: src/main.rs (líneas 3-4)"));
+        assert!(prompt.contains("```rust\n    3 | linea 3\n    4 | linea 4\n```"));
+        assert!(prompt.contains("Contexto (líneas 1-16)"));
+        assert!(prompt.contains("a/src/main.rs y b/src/main.rs"));
+
+        let mut viewer = CodeViewerState {
+            open: Some(sample_file(20)),
+            ..CodeViewerState::default()
+        };
+        viewer.click_line(8, false);
+        viewer.click_line(5, true);
+        assert_eq!(viewer.current_anchor().unwrap().label(), "src/main.rs:5-8");
+    }
+
+    #[test]
+    fn extracts_the_first_diff_block() {
+        let reply = "Cambia esto:\n```diff\n--- a/x\n+++ b/x\n-uno\n+dos\n```\nListo.";
+        assert_eq!(
+            extract_patch(reply).as_deref(),
+            Some("--- a/x\n+++ b/x\n-uno\n+dos\n")
+        );
+        assert_eq!(extract_patch("```rust\nfn main() {}\n```"), None);
+    }
+}
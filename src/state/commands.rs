@@ -0,0 +1,2419 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::rc::Rc;
+
+use chrono::Local;
+
+use super::{
+    batch_eval::BatchTarget, benchmark, conditions, format_bytes, placeholder_names, AppState,
+    AuditEventKind, CustomCommand, CustomCommandAction, CustomCommandBehavior, LogStatus,
+    ProviderCallDispatch, RemoteProviderKind,
+};
+use crate::local_providers::LocalModelProvider;
+
+pub const MAX_COMMAND_DEPTH: usize = 5;
+
+/// Nombre reservado de la instrucción condicional; no se puede redefinir.
+const CONDITIONAL_COMMAND: &str = "/if";
+
+/// Líneas de la entrada que conserva el resumen local cuando Jarvis no está disponible.
+const SUMMARY_FALLBACK_LINES: usize = 5;
+
+/// Divide `/models | /summarize` en etapas. Solo separa por `|` sueltos seguidos de otro
+/// comando, así que el `||` de las condiciones y los textos con barras no se parten.
+pub fn split_pipeline(input: &str) -> Vec<&str> {
+    let bytes = input.as_bytes();
+    let mut stages = Vec::new();
+    let mut start = 0;
+    for (index, byte) in bytes.iter().enumerate() {
+        if *byte != b'|' {
+            continue;
+        }
+        let doubled =
+            (index > 0 && bytes[index - 1] == b'|') || bytes.get(index + 1).copied() == Some(b'|');
+        if !doubled && input[index + 1..].trim_start().starts_with('/') {
+            stages.push(input[start..index].trim());
+            start = index + 1;
+        }
+    }
+    stages.push(input[start..].trim());
+    stages
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CommandInvocation {
+    pub raw: String,
+    pub name: String,
+    pub args: BTreeMap<String, String>,
+    pub flags: BTreeSet<String>,
+    pub positional: Vec<String>,
+    /// Salida de la etapa anterior cuando el comando forma parte de un pipeline.
+    pub input: Option<String>,
+}
+
+impl CommandInvocation {
+    pub fn parse(input: &str) -> Self {
+        let mut invocation = CommandInvocation {
+            raw: input.trim().to_string(),
+            ..Default::default()
+        };
+
+        let mut tokens = input.split_whitespace();
+        if let Some(first) = tokens.next() {
+            invocation.name = first.to_string();
+        } else {
+            return invocation;
+        }
+
+        for token in tokens {
+            if let Some(stripped) = token.strip_prefix("--") {
+                if let Some((key, value)) = stripped.split_once('=') {
+                    invocation.args.insert(key.to_string(), value.to_string());
+                } else {
+                    invocation.flags.insert(stripped.to_string());
+                }
+            } else if let Some((key, value)) = token.split_once('=') {
+                invocation.args.insert(key.to_string(), value.to_string());
+            } else {
+                invocation.positional.push(token.to_string());
+            }
+        }
+
+        invocation
+    }
+
+    pub fn arg(&self, key: &str) -> Option<&str> {
+        self.args.get(key).map(|s| s.as_str())
+    }
+
+    pub fn flag(&self, key: &str) -> bool {
+        self.flags.contains(key)
+    }
+}
+
+pub struct CommandOutcome {
+    pub messages: Vec<String>,
+}
+
+impl CommandOutcome {
+    pub fn single(message: String) -> Self {
+        CommandOutcome {
+            messages: vec![message],
+        }
+    }
+}
+
+pub struct CommandDocumentation {
+    pub signature: &'static str,
+    pub summary: &'static str,
+    pub parameters: &'static [&'static str],
+    pub examples: &'static [&'static str],
+}
+
+/// Estado disponible para un comando en ejecución.
+pub struct CommandContext<'a> {
+    pub state: &'a mut AppState,
+    depth: usize,
+}
+
+impl CommandContext<'_> {
+    /// Ejecuta otro comando como parte de este, respetando el límite de anidado.
+    pub fn run(&mut self, invocation: CommandInvocation) -> CommandOutcome {
+        self.state.resolve_command(invocation, self.depth + 1)
+    }
+}
+
+/// Comando de chat invocable con `/nombre`.
+pub trait Command {
+    /// Disparador completo, con la barra inicial.
+    fn name(&self) -> &str;
+
+    /// Descripción de una línea para la ayuda.
+    fn summary(&self) -> String;
+
+    /// Firma, parámetros y ejemplos, si el comando los documenta.
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        None
+    }
+
+    /// Acción predefinida que ejecuta, si es un envoltorio de una de ellas.
+    fn action(&self) -> Option<CustomCommandAction> {
+        None
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome;
+}
+
+/// Resuelve los comandos del chat. El orden de búsqueda es: la condicional `/if`, los
+/// comandos personalizados del usuario, los registrados por plugins, los que aportan
+/// los módulos (p. ej. los workflows) y, por último, los integrados.
+pub struct CommandEngine {
+    builtins: Vec<Rc<dyn Command>>,
+    registered: Vec<Rc<dyn Command>>,
+    module_commands: Vec<Rc<dyn Command>>,
+}
+
+impl Default for CommandEngine {
+    fn default() -> Self {
+        let mut builtins: Vec<Rc<dyn Command>> = vec![
+            Rc::new(ConditionalCommand),
+            Rc::new(SystemCommand),
+            Rc::new(ReloadCommand),
+            Rc::new(WorkflowRunCommand),
+            Rc::new(SummarizeCommand),
+            Rc::new(TranscribeCommand),
+            Rc::new(DescribeCommand),
+            Rc::new(BenchmarkCommand),
+            Rc::new(SearchCommand),
+        ];
+        builtins.extend(
+            [
+                ("/status", CustomCommandAction::ShowSystemStatus),
+                ("/models", CustomCommandAction::ListAvailableModels),
+                ("/stats", CustomCommandAction::ShowUsageStatistics),
+                ("/help", CustomCommandAction::ShowCommandHelp),
+                ("/time", CustomCommandAction::ShowCurrentTime),
+                ("/projects", CustomCommandAction::ListActiveProjects),
+                ("/profiles", CustomCommandAction::ListConfiguredProfiles),
+                ("/cache", CustomCommandAction::ShowCacheConfiguration),
+                ("/github", CustomCommandAction::ShowGithubSummary),
+                ("/memory", CustomCommandAction::ShowMemorySettings),
+                ("/providers", CustomCommandAction::ShowActiveProviders),
+                ("/jarvis", CustomCommandAction::ShowJarvisStatus),
+            ]
+            .into_iter()
+            .map(|(name, action)| Rc::new(ActionCommand::new(name, action)) as Rc<dyn Command>),
+        );
+        Self {
+            builtins,
+            registered: Vec::new(),
+            module_commands: Vec::new(),
+        }
+    }
+}
+
+impl CommandEngine {
+    /// Registra un comando adicional; sustituye a otro registrado con el mismo nombre.
+    pub fn register(&mut self, command: impl Command + 'static) {
+        let command: Rc<dyn Command> = Rc::new(command);
+        self.registered
+            .retain(|existing| existing.name() != command.name());
+        self.registered.push(command);
+    }
+
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.registered.len();
+        self.registered.retain(|command| command.name() != name);
+        self.registered.len() != before
+    }
+
+    /// Sustituye los comandos aportados por los módulos de estado.
+    pub(crate) fn set_module_commands(&mut self, commands: Vec<Rc<dyn Command>>) {
+        self.module_commands = commands;
+    }
+
+    pub fn find(&self, name: &str, custom: &[CustomCommand]) -> Option<Rc<dyn Command>> {
+        if name == CONDITIONAL_COMMAND {
+            return self.find_in(&self.builtins, name);
+        }
+        if let Some(custom) = custom.iter().find(|command| command.trigger == name) {
+            return Some(Rc::new(UserCommand::new(custom.clone())));
+        }
+        self.find_in(&self.registered, name)
+            .or_else(|| self.find_in(&self.module_commands, name))
+            .or_else(|| self.find_in(&self.builtins, name))
+    }
+
+    pub fn builtin_names(&self) -> Vec<&str> {
+        self.builtins.iter().map(|command| command.name()).collect()
+    }
+
+    /// Comandos integrados que no se limitan a envolver una acción predefinida.
+    pub fn special_builtins(&self) -> impl Iterator<Item = &Rc<dyn Command>> {
+        self.builtins
+            .iter()
+            .filter(|command| command.action().is_none())
+    }
+
+    /// Comandos de plugins y módulos, en el orden en que se resuelven.
+    pub fn extension_commands(&self) -> impl Iterator<Item = &Rc<dyn Command>> {
+        self.registered.iter().chain(self.module_commands.iter())
+    }
+
+    fn find_in(&self, commands: &[Rc<dyn Command>], name: &str) -> Option<Rc<dyn Command>> {
+        commands
+            .iter()
+            .find(|command| command.name() == name)
+            .cloned()
+    }
+}
+
+impl AppState {
+    /// Ejecuta una línea de chat, que puede encadenar comandos con `|`: la salida de cada
+    /// etapa llega a la siguiente como `input` y solo se muestra la de la última.
+    pub fn run_command_line(&mut self, line: &str) -> CommandOutcome {
+        let stages = split_pipeline(line);
+        self.record_audit(
+            AuditEventKind::CommandExecuted,
+            line.trim(),
+            BTreeMap::from([("etapas".to_string(), stages.len().to_string())]),
+        );
+        let mut piped: Option<String> = None;
+        let mut outcome = CommandOutcome {
+            messages: Vec::new(),
+        };
+        for (index, stage) in stages.iter().enumerate() {
+            let mut invocation = CommandInvocation::parse(stage);
+            if invocation.name.is_empty() {
+                return CommandOutcome::single(format!(
+                    "La etapa {} del pipeline está vacía.",
+                    index + 1
+                ));
+            }
+            invocation.input = piped.take();
+            outcome = self.resolve_command(invocation, 0);
+            if index + 1 < stages.len() {
+                if outcome.messages.is_empty() {
+                    return CommandOutcome::single(format!(
+                        "'{}' no produjo salida para la siguiente etapa.",
+                        stage
+                    ));
+                }
+                piped = Some(outcome.messages.join("\n"));
+            }
+        }
+        outcome
+    }
+
+    pub(crate) fn resolve_command(
+        &mut self,
+        invocation: CommandInvocation,
+        depth: usize,
+    ) -> CommandOutcome {
+        if depth > MAX_COMMAND_DEPTH {
+            return CommandOutcome::single(
+                "Recursión de comandos demasiado profunda. Revisa tus condicionales.".to_string(),
+            );
+        }
+
+        let Some(command) = self
+            .command_engine
+            .find(&invocation.name, &self.chat.custom_commands)
+        else {
+            return CommandOutcome::single(format!("Unknown command: {}", invocation.raw));
+        };
+
+        let mut ctx = CommandContext { state: self, depth };
+        command.execute(&mut ctx, &invocation)
+    }
+
+    /// Ejecuta un comando personalizado sin guardarlo, para probarlo desde el editor.
+    pub fn run_custom_command_preview(
+        &mut self,
+        command: CustomCommand,
+        arguments: &str,
+    ) -> CommandOutcome {
+        let invocation = CommandInvocation::parse(&format!("{} {}", command.trigger, arguments));
+        let mut ctx = CommandContext {
+            state: self,
+            depth: 0,
+        };
+        UserCommand::new(command).execute(&mut ctx, &invocation)
+    }
+}
+
+/// Sustituye en `template` los marcadores `{args}` (todos los posicionales), `{0}`, `{1}`…,
+/// `{input}` (la entrada de un pipeline) y `{clave}` (argumentos `clave=valor`). Devuelve
+/// los marcadores que no se pudieron rellenar.
+pub fn fill_template(
+    template: &str,
+    invocation: &CommandInvocation,
+) -> Result<String, Vec<String>> {
+    let mut missing = Vec::new();
+    let mut filled = template.to_string();
+    for name in placeholder_names(template) {
+        let value = match name.as_str() {
+            "args" => Some(invocation.positional.join(" ")),
+            "input" => invocation.input.clone(),
+            _ => match name.parse::<usize>() {
+                Ok(index) => invocation.positional.get(index).cloned(),
+                Err(_) => invocation.args.get(&name).cloned(),
+            },
+        };
+        match value {
+            Some(value) => filled = filled.replace(&format!("{{{}}}", name), &value),
+            None if !missing.contains(&name) => missing.push(name),
+            None => {}
+        }
+    }
+    if missing.is_empty() {
+        Ok(filled)
+    } else {
+        Err(missing)
+    }
+}
+
+/// Comando personalizado por el usuario desde el panel de Comandos.
+pub struct UserCommand {
+    command: CustomCommand,
+}
+
+impl UserCommand {
+    pub fn new(command: CustomCommand) -> Self {
+        Self { command }
+    }
+
+    fn fill(&self, template: &str, invocation: &CommandInvocation) -> Result<String, String> {
+        fill_template(template, invocation).map_err(|missing| {
+            format!(
+                "Faltan argumentos para {}: {}.",
+                self.command.trigger,
+                missing.join(", ")
+            )
+        })
+    }
+}
+
+impl Command for UserCommand {
+    fn name(&self) -> &str {
+        &self.command.trigger
+    }
+
+    fn summary(&self) -> String {
+        match &self.command.behavior {
+            CustomCommandBehavior::Action => self.command.action.description().to_string(),
+            _ => self.command.target_label(),
+        }
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        match self.command.behavior {
+            CustomCommandBehavior::Action => Some(self.command.action.documentation()),
+            _ => None,
+        }
+    }
+
+    fn action(&self) -> Option<CustomCommandAction> {
+        match self.command.behavior {
+            CustomCommandBehavior::Action => Some(self.command.action),
+            _ => None,
+        }
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        match &self.command.behavior {
+            CustomCommandBehavior::Action => CommandOutcome {
+                messages: run_action(ctx.state, self.command.action, invocation),
+            },
+            CustomCommandBehavior::Prompt { provider, template } => {
+                match self.fill(template, invocation) {
+                    Ok(prompt) => send_prompt(ctx.state, provider.as_deref(), prompt),
+                    Err(err) => CommandOutcome::single(err),
+                }
+            }
+            CustomCommandBehavior::Workflow { workflow_id } => {
+                launch_workflow(ctx.state, *workflow_id, invocation)
+            }
+            CustomCommandBehavior::Script { commands } => {
+                let mut messages = Vec::new();
+                for line in commands
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with("//"))
+                {
+                    let line = match self.fill(line, invocation) {
+                        Ok(line) => line,
+                        Err(err) => {
+                            messages.push(err);
+                            break;
+                        }
+                    };
+                    let mut nested = CommandInvocation::parse(&line);
+                    nested.input = invocation.input.clone();
+                    messages.extend(ctx.run(nested).messages);
+                }
+                CommandOutcome { messages }
+            }
+        }
+    }
+}
+
+/// Envía un prompt a un proveedor remoto o, con `provider` vacío, lo resuelve con Jarvis.
+/// Los avisos de credenciales, cuota o red los publica ya el propio envío.
+fn send_prompt(state: &mut AppState, provider: Option<&str>, prompt: String) -> CommandOutcome {
+    let Some(code) = provider.filter(|code| !code.trim().is_empty()) else {
+        return match state.generate_local_jarvis_reply(&prompt) {
+            Ok(reply) => CommandOutcome::single(reply.trim().to_string()),
+            Err(err) => CommandOutcome::single(format!("Jarvis no pudo responder: {}", err)),
+        };
+    };
+    let Some(kind) = RemoteProviderKind::from_short_code(code) else {
+        return CommandOutcome::single(format!("Proveedor desconocido: {}", code));
+    };
+    match state.invoke_provider_kind(kind, prompt) {
+        ProviderCallDispatch::Pending(_) => {
+            CommandOutcome::single(format!("Prompt enviado a {}.", kind.display_name()))
+        }
+        _ => CommandOutcome {
+            messages: Vec::new(),
+        },
+    }
+}
+
+/// Comando que ejecuta una acción predefinida; lo usan los integrados y los personalizados.
+pub struct ActionCommand {
+    name: String,
+    action: CustomCommandAction,
+}
+
+impl ActionCommand {
+    pub fn new(name: &str, action: CustomCommandAction) -> Self {
+        Self {
+            name: name.to_string(),
+            action,
+        }
+    }
+}
+
+impl Command for ActionCommand {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn summary(&self) -> String {
+        self.action.description().to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(self.action.documentation())
+    }
+
+    fn action(&self) -> Option<CustomCommandAction> {
+        Some(self.action)
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        CommandOutcome {
+            messages: run_action(ctx.state, self.action, invocation),
+        }
+    }
+}
+
+/// `/system`: estado resumido o, con `debug`, el diagnóstico completo.
+struct SystemCommand;
+
+impl Command for SystemCommand {
+    fn name(&self) -> &str {
+        "/system"
+    }
+
+    fn summary(&self) -> String {
+        "Estado del sistema; con 'debug' muestra el diagnóstico completo.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "/system [debug] [section=<sección>]",
+            summary: "Estado del sistema; con 'debug' muestra el diagnóstico completo.",
+            parameters: &["section → all | general | remote | local | commands | logs"],
+            examples: &["/system", "/system debug section=remote"],
+        })
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        let positional_debug = invocation
+            .positional
+            .first()
+            .map(|token| token.eq_ignore_ascii_case("debug"))
+            .unwrap_or(false);
+        let arg_debug = invocation
+            .arg("mode")
+            .map(|value| value.eq_ignore_ascii_case("debug"))
+            .unwrap_or(false)
+            || invocation.flag("debug");
+
+        let action = if positional_debug || arg_debug {
+            CustomCommandAction::ShowSystemDiagnostics
+        } else {
+            CustomCommandAction::ShowSystemStatus
+        };
+
+        CommandOutcome {
+            messages: run_action(ctx.state, action, invocation),
+        }
+    }
+}
+
+struct ReloadCommand;
+
+impl Command for ReloadCommand {
+    fn name(&self) -> &str {
+        "/reload"
+    }
+
+    fn summary(&self) -> String {
+        "Recarga las configuraciones de proveedores y caché.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "/reload [--force]",
+            summary: "Sincroniza la configuración y admite la bandera --force para reiniciar credenciales.",
+            parameters: &[],
+            examples: &["/reload", "/reload --force"],
+        })
+    }
+
+    fn execute(
+        &self,
+        _ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        let mut message =
+            "Recargando configuraciones... Los proveedores y la caché se sincronizarán en segundo plano.".to_string();
+        if invocation.flag("force") {
+            message.push_str(" Forzando refresco inmediato de todas las credenciales.");
+        }
+        CommandOutcome::single(message)
+    }
+}
+
+/// `/if <condición> then <comando> [else <comando>]`.
+struct ConditionalCommand;
+
+impl Command for ConditionalCommand {
+    fn name(&self) -> &str {
+        CONDITIONAL_COMMAND
+    }
+
+    fn summary(&self) -> String {
+        "Ejecuta un comando u otro según una condición.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "/if <condición> then <cmd> [else <cmd>]",
+            summary: "Ejecuta comandos condicionalmente. La condición admite &&, ||, !, paréntesis, comparaciones de números y textos y los métodos contains, starts_with y ends_with.",
+            parameters: conditions::STATE_VARIABLES,
+            examples: &[
+                "/if memory.enabled == true then /status",
+                "/if projects.count > 2 then /models else /help",
+                "/if providers.openai.configured && !(time.weekend) then /models",
+                "/if usage.spend_today_usd >= 5 || jarvis.model.contains('llama') then /system",
+            ],
+        })
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        let condition_text = invocation
+            .raw
+            .trim_start_matches(CONDITIONAL_COMMAND)
+            .trim_start()
+            .to_string();
+
+        if condition_text.is_empty() {
+            return CommandOutcome::single(
+                "Uso: /if <condición> then <comando> [else <comando>]".to_string(),
+            );
+        }
+
+        let (condition_part, outcome_part) = match condition_text.split_once(" then ") {
+            Some(parts) => parts,
+            None => {
+                return CommandOutcome::single(
+                    "La instrucción condicional necesita la palabra clave 'then'.".to_string(),
+                );
+            }
+        };
+
+        let (then_command, else_command) =
+            if let Some((then, otherwise)) = outcome_part.split_once(" else ") {
+                (then.trim(), Some(otherwise.trim()))
+            } else {
+                (outcome_part.trim(), None)
+            };
+
+        let state: &AppState = ctx.state;
+        let evaluation = match conditions::evaluate(condition_part.trim(), &[state]) {
+            Ok(value) => value,
+            Err(err) => return CommandOutcome::single(err),
+        };
+
+        let mut messages = Vec::new();
+        messages.push(format!(
+            "Condición '{}' evaluada como {}.",
+            condition_part.trim(),
+            if evaluation { "verdadera" } else { "falsa" }
+        ));
+
+        let branch = if evaluation {
+            then_command
+        } else if let Some(else_cmd) = else_command {
+            else_cmd
+        } else {
+            messages.push("No se especificó comando 'else'.".to_string());
+            return CommandOutcome { messages };
+        };
+
+        if branch.is_empty() {
+            messages.push("No hay comando que ejecutar tras la condición.".to_string());
+            return CommandOutcome { messages };
+        }
+
+        let mut nested_invocation = CommandInvocation::parse(branch);
+        nested_invocation.input = invocation.input.clone();
+        if nested_invocation.name.is_empty() {
+            messages.push("No se pudo interpretar el comando de la rama seleccionada.".to_string());
+            return CommandOutcome { messages };
+        }
+
+        messages.push(format!("Ejecutando '{}'.", branch));
+        let nested = ctx.run(nested_invocation);
+        messages.extend(nested.messages);
+        CommandOutcome { messages }
+    }
+}
+
+/// Comando de chat de un workflow de automatización (`chat_command`).
+pub struct WorkflowCommand {
+    trigger: String,
+    workflow_id: u32,
+    workflow_name: String,
+}
+
+impl WorkflowCommand {
+    pub fn new(trigger: &str, workflow_id: u32, workflow_name: &str) -> Self {
+        Self {
+            trigger: trigger.trim().to_string(),
+            workflow_id,
+            workflow_name: workflow_name.to_string(),
+        }
+    }
+}
+
+impl Command for WorkflowCommand {
+    fn name(&self) -> &str {
+        &self.trigger
+    }
+
+    fn summary(&self) -> String {
+        format!("Lanza el workflow '{}'.", self.workflow_name)
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        if !ctx
+            .state
+            .automation
+            .workflows
+            .workflows
+            .iter()
+            .any(|workflow| workflow.id == self.workflow_id)
+        {
+            return CommandOutcome::single(format!(
+                "El workflow '{}' ya no existe.",
+                self.workflow_name
+            ));
+        }
+        launch_workflow(ctx.state, self.workflow_id, invocation)
+    }
+}
+
+/// `/workflow <id|nombre> [clave=valor…]`: lanza un workflow rellenando los `{clave}` de
+/// sus pasos. La entrada de un pipeline llega como `{input}`.
+struct WorkflowRunCommand;
+
+impl Command for WorkflowRunCommand {
+    fn name(&self) -> &str {
+        "/workflow"
+    }
+
+    fn summary(&self) -> String {
+        "Lanza un workflow de automatización con parámetros para sus pasos.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "/workflow <id|nombre> [clave=valor…]",
+            summary: "Lanza un workflow de automatización. Los argumentos clave=valor sustituyen los {clave} de los pasos y la salida de un pipeline llega como {input}.",
+            parameters: &["Sin argumentos lista los workflows y sus parámetros."],
+            examples: &[
+                "/workflow",
+                "/workflow 2 canal=#qa",
+                "/models provider=openai | /workflow \"Briefing diario\"",
+            ],
+        })
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        let workflows = &ctx.state.automation.workflows.workflows;
+        if invocation.positional.is_empty() {
+            if workflows.is_empty() {
+                return CommandOutcome::single("No hay workflows configurados.".to_string());
+            }
+            let mut messages = vec!["Workflows disponibles:".to_string()];
+            messages.extend(workflows.iter().map(|workflow| {
+                let parameters = workflow.parameters();
+                if parameters.is_empty() {
+                    format!("• #{} {}", workflow.id, workflow.name)
+                } else {
+                    format!(
+                        "• #{} {} · parámetros: {}",
+                        workflow.id,
+                        workflow.name,
+                        parameters.join(", ")
+                    )
+                }
+            }));
+            return CommandOutcome { messages };
+        }
+
+        let reference = invocation.positional.join(" ");
+        let reference = reference.trim_matches(|c| c == '"' || c == '\'');
+        let found = workflows.iter().find(|workflow| {
+            reference.trim_start_matches('#').parse::<u32>().ok() == Some(workflow.id)
+                || workflow.name.eq_ignore_ascii_case(reference)
+        });
+        match found.map(|workflow| workflow.id) {
+            Some(workflow_id) => launch_workflow(ctx.state, workflow_id, invocation),
+            None => CommandOutcome::single(format!(
+                "No hay ningún workflow '{}'. Usa /workflow para ver la lista.",
+                reference
+            )),
+        }
+    }
+}
+
+fn launch_workflow(
+    state: &mut AppState,
+    workflow_id: u32,
+    invocation: &CommandInvocation,
+) -> CommandOutcome {
+    let mut parameters = invocation.args.clone();
+    if let Some(input) = &invocation.input {
+        parameters
+            .entry("input".to_string())
+            .or_insert_with(|| input.clone());
+    }
+    match state.run_workflow(workflow_id, &parameters) {
+        Ok(messages) => CommandOutcome { messages },
+        Err(err) => CommandOutcome::single(err),
+    }
+}
+
+/// `/summarize`: resume la entrada de un pipeline con Jarvis o, si no hay modelo local,
+/// se queda con las primeras líneas.
+struct SummarizeCommand;
+
+impl Command for SummarizeCommand {
+    fn name(&self) -> &str {
+        "/summarize"
+    }
+
+    fn summary(&self) -> String {
+        "Resume la salida del comando anterior de un pipeline.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "<comando> | /summarize [texto]",
+            summary: "Resume con Jarvis la salida del comando anterior o el texto indicado.",
+            parameters: &[],
+            examples: &[
+                "/models provider=openai | /summarize",
+                "/stats | /summarize",
+            ],
+        })
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        let text = match &invocation.input {
+            Some(input) => input.clone(),
+            None => invocation.positional.join(" "),
+        };
+        if text.trim().is_empty() {
+            return CommandOutcome::single(
+                "Nada que resumir. Usa '<comando> | /summarize' o '/summarize <texto>'."
+                    .to_string(),
+            );
+        }
+
+        let prompt = format!(
+            "Resume en pocas líneas y en español el siguiente texto:\n\n{}",
+            text
+        );
+        match ctx.state.generate_local_jarvis_reply(&prompt) {
+            Ok(summary) => CommandOutcome::single(summary.trim().to_string()),
+            Err(_) => {
+                let lines: Vec<&str> = text
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                let mut messages = vec![format!(
+                    "Jarvis no está disponible; resumen local de {} líneas y {} palabras:",
+                    lines.len(),
+                    text.split_whitespace().count()
+                )];
+                messages.extend(
+                    lines
+                        .iter()
+                        .take(SUMMARY_FALLBACK_LINES)
+                        .map(|line| line.to_string()),
+                );
+                if lines.len() > SUMMARY_FALLBACK_LINES {
+                    messages.push(format!(
+                        "… y {} líneas más.",
+                        lines.len() - SUMMARY_FALLBACK_LINES
+                    ));
+                }
+                CommandOutcome { messages }
+            }
+        }
+    }
+}
+
+/// `/transcribe <ruta>`: transcribe un audio con el backend de entrada de voz.
+struct TranscribeCommand;
+
+impl Command for TranscribeCommand {
+    fn name(&self) -> &str {
+        "/transcribe"
+    }
+
+    fn summary(&self) -> String {
+        "Transcribe una nota de voz o un audio y añade el texto al compositor.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "/transcribe <ruta del audio>",
+            summary: "Usa Whisper local u OpenAI según la configuración de entrada de voz.",
+            parameters: &["ruta: archivo flac, m4a, mp3, mp4, mpeg, mpga, ogg, wav o webm"],
+            examples: &["/transcribe ~/Descargas/nota.ogg"],
+        })
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        // Se usa la línea completa para admitir rutas con espacios o con '='.
+        let raw_path = invocation.raw[invocation.name.len()..].trim();
+        if raw_path.is_empty() {
+            return CommandOutcome::single("Uso: /transcribe <ruta del audio>".to_string());
+        }
+        let path = match (raw_path.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest),
+            _ => raw_path.into(),
+        };
+
+        match ctx.state.start_transcription(path) {
+            Ok(()) => CommandOutcome::single(format!(
+                "Transcribiendo con {}. El texto aparecerá en el compositor al terminar.",
+                ctx.state.config.voice_input.backend.label()
+            )),
+            Err(err) => CommandOutcome::single(err),
+        }
+    }
+}
+
+/// `/describe [ruta]`: describe una imagen con un modelo con visión.
+struct DescribeCommand;
+
+impl Command for DescribeCommand {
+    fn name(&self) -> &str {
+        "/describe"
+    }
+
+    fn summary(&self) -> String {
+        "Describe una imagen adjunta con un modelo con visión.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "/describe [ruta de la imagen]",
+            summary: "Sin ruta usa la última imagen adjunta. El proveedor sale de las reglas de enrutado y de las fichas multimodales del catálogo.",
+            parameters: &["ruta: archivo png, jpg, gif o webp de hasta 5 MB"],
+            examples: &["/describe", "/describe ~/Imágenes/captura.png"],
+        })
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        let raw_path = invocation.raw[invocation.name.len()..].trim();
+        let path = if raw_path.is_empty() {
+            match ctx.state.attachments.last_image() {
+                Some(image) => image.path.clone(),
+                None => {
+                    return CommandOutcome::single(
+                        "No hay imágenes adjuntas. Suelta una imagen sobre la ventana o usa '/describe <ruta>'."
+                            .to_string(),
+                    )
+                }
+            }
+        } else {
+            match (raw_path.strip_prefix("~/"), dirs::home_dir()) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => raw_path.into(),
+            }
+        };
+
+        match ctx.state.describe_image(&path) {
+            Ok(provider) => CommandOutcome::single(format!(
+                "Imagen enviada a {}. La descripción aparecerá en la conversación.",
+                provider
+            )),
+            Err(err) => CommandOutcome::single(err),
+        }
+    }
+}
+
+/// `/benchmark [targets=…] [iterations=N]`: mide la latencia y el rendimiento de los
+/// proveedores y de Jarvis con los prompts estandarizados.
+struct BenchmarkCommand;
+
+impl Command for BenchmarkCommand {
+    fn name(&self) -> &str {
+        "/benchmark"
+    }
+
+    fn summary(&self) -> String {
+        "Mide TTFB, latencia y tokens/s de los proveedores y de Jarvis.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "/benchmark [targets=anthropic,openai,groq,jarvis] [iterations=N]",
+            summary: "Envía prompts corto, medio y largo N veces a cada destino, guarda un informe comparativo y actualiza la latencia de las fichas del catálogo.",
+            parameters: &[
+                "targets: destinos separados por comas; por defecto, los marcados en la vista de evaluación",
+                "iterations: repeticiones de cada prompt, entre 1 y 20",
+                "cancel: detiene el benchmark en curso",
+            ],
+            examples: &[
+                "/benchmark",
+                "/benchmark targets=openai,jarvis iterations=5",
+                "/benchmark cancel",
+            ],
+        })
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        if invocation.positional.first().map(String::as_str) == Some("cancel") {
+            if !ctx.state.benchmark.is_running() {
+                return CommandOutcome::single("No hay ningún benchmark en curso.".to_string());
+            }
+            ctx.state.cancel_benchmark();
+            return CommandOutcome::single("Benchmark cancelado.".to_string());
+        }
+
+        let targets = match invocation.arg("targets") {
+            Some(codes) => {
+                let mut targets = Vec::new();
+                for code in codes.split(',').filter(|code| !code.trim().is_empty()) {
+                    match BatchTarget::from_code(code) {
+                        Some(target) if !targets.contains(&target) => targets.push(target),
+                        Some(_) => {}
+                        None => {
+                            return CommandOutcome::single(format!(
+                                "Destino desconocido '{}'. Usa anthropic, openai, groq o jarvis.",
+                                code.trim()
+                            ))
+                        }
+                    }
+                }
+                targets
+            }
+            None => ctx.state.benchmark.targets.clone(),
+        };
+        let iterations = match invocation.arg("iterations") {
+            Some(value) => match value.parse::<usize>() {
+                Ok(iterations) if iterations > 0 => iterations,
+                _ => {
+                    return CommandOutcome::single(format!(
+                        "'{}' no es un número de iteraciones válido.",
+                        value
+                    ))
+                }
+            },
+            None => ctx.state.benchmark.iterations,
+        };
+
+        match ctx.state.start_benchmark(&targets, iterations) {
+            Ok(calls) => CommandOutcome::single(format!(
+                "Benchmark iniciado: {} llamadas ({} iteraciones de {} prompts). El informe aparecerá en la vista de evaluación.",
+                calls,
+                iterations.min(benchmark::MAX_ITERATIONS),
+                benchmark::BenchmarkPromptSize::ALL.len()
+            )),
+            Err(err) => CommandOutcome::single(err),
+        }
+    }
+}
+
+/// `/search <consulta>`: busca en la web con el backend de las preferencias.
+struct SearchCommand;
+
+impl Command for SearchCommand {
+    fn name(&self) -> &str {
+        "/search"
+    }
+
+    fn summary(&self) -> String {
+        "Busca en la web y muestra los resultados ordenados en el chat.".to_string()
+    }
+
+    fn documentation(&self) -> Option<CommandDocumentation> {
+        Some(CommandDocumentation {
+            signature: "/search <consulta>",
+            summary: "Consulta SearXNG, Brave o Bing según Preferencias › Sistema › Búsqueda web. Desde el panel de resultados se puede ampliar cada uno y adjuntarlo como contexto de la conversación.",
+            parameters: &["consulta: texto libre; en un pipeline se usa la salida anterior si se omite"],
+            examples: &[
+                "/search última versión estable de Rust",
+                "/search precio del cobre hoy",
+            ],
+        })
+    }
+
+    fn execute(
+        &self,
+        ctx: &mut CommandContext<'_>,
+        invocation: &CommandInvocation,
+    ) -> CommandOutcome {
+        // Se usa la línea completa para admitir consultas con '='.
+        let raw_query = invocation.raw[invocation.name.len()..].trim();
+        let query = match (raw_query.is_empty(), invocation.input.as_deref()) {
+            (true, Some(input)) => input.trim(),
+            _ => raw_query,
+        };
+        if query.is_empty() {
+            return CommandOutcome::single("Uso: /search <consulta>".to_string());
+        }
+
+        match ctx.state.start_web_search(query) {
+            Ok(()) => CommandOutcome::single(format!(
+                "Buscando «{}» con {}. Los resultados aparecerán en el panel de búsqueda web del chat.",
+                query,
+                ctx.state.config.web_search.backend.label()
+            )),
+            Err(err) => CommandOutcome::single(err),
+        }
+    }
+}
+
+/// Ejecuta una acción predefinida. Todas son de solo lectura sobre el estado.
+fn run_action(
+    state: &AppState,
+    action: CustomCommandAction,
+    invocation: &CommandInvocation,
+) -> Vec<String> {
+    let handler: fn(&AppState, &CommandInvocation) -> Vec<String> = match action {
+        CustomCommandAction::ShowCurrentTime => show_current_time,
+        CustomCommandAction::ShowSystemStatus => show_system_status,
+        CustomCommandAction::ShowSystemDiagnostics => show_system_diagnostics,
+        CustomCommandAction::ShowUsageStatistics => show_usage_statistics,
+        CustomCommandAction::ListActiveProjects => list_active_projects,
+        CustomCommandAction::ListConfiguredProfiles => list_configured_profiles,
+        CustomCommandAction::ShowCacheConfiguration => show_cache_configuration,
+        CustomCommandAction::ListAvailableModels => list_available_models,
+        CustomCommandAction::ShowGithubSummary => show_github_summary,
+        CustomCommandAction::ShowMemorySettings => show_memory_settings,
+        CustomCommandAction::ShowActiveProviders => show_active_providers,
+        CustomCommandAction::ShowJarvisStatus => show_jarvis_status,
+        CustomCommandAction::ShowCommandHelp => show_command_help,
+    };
+    handler(state, invocation)
+}
+
+fn show_current_time(_state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let format = invocation.arg("format").unwrap_or("human");
+    let now = Local::now();
+    let rendered = match format {
+        "24" => now.format("%H:%M:%S").to_string(),
+        "iso" => now.to_rfc3339(),
+        _ => now.format("%I:%M %p").to_string(),
+    };
+    vec![format!("Hora actual: {}", rendered.trim())]
+}
+
+fn show_system_status(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let detail = invocation.arg("detail").unwrap_or("summary");
+    let verbose = invocation.flag("verbose");
+    let monitor = &state.resources.system_monitor;
+    let mut lines = vec![match monitor.latest() {
+        Some(sample) => format!(
+            "CPU {:.0} % · Memoria {} de {} (la aplicación usa {}).",
+            sample.cpu_percent,
+            format_bytes(sample.memory_used_bytes),
+            format_bytes(sample.memory_total_bytes),
+            format_bytes(sample.process_memory_bytes)
+        ),
+        None => "El monitor de recursos aún no ha tomado ninguna muestra.".to_string(),
+    }];
+    lines.push(format!(
+        "Límites configurados → Memoria: {:.1} GB · Disco: {:.1} GB.",
+        state.resource_memory_limit_gb, state.resource_disk_limit_gb
+    ));
+    if monitor.generation_paused {
+        lines.push(
+            "La generación local está en pausa por superar el límite de memoria.".to_string(),
+        );
+    }
+
+    match detail {
+        "memory" => lines.push(format!(
+            "Memoria disponible para caché: {:.1} GB. Auto limpieza: {}.",
+            state.resource_memory_limit_gb,
+            if state.enable_auto_cleanup {
+                "activada"
+            } else {
+                "desactivada"
+            }
+        )),
+        "disk" => lines.push(format!(
+            "Espacio de disco reservado para caché: {:.1} GB en {}.",
+            state.resource_disk_limit_gb, state.cache_directory
+        )),
+        "cache" => lines.push(format!(
+            "Limpieza automática cada {} horas. Última ejecución: {}.",
+            state.cache_cleanup_interval_hours,
+            state
+                .last_cache_cleanup
+                .clone()
+                .unwrap_or_else(|| "nunca".to_string())
+        )),
+        _ => {}
+    }
+
+    if verbose {
+        lines.push("Modo detallado activado: recuerda revisar la configuración de recursos en Preferencias.".to_string());
+    }
+
+    lines
+}
+
+fn show_system_diagnostics(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let mut focus_tokens = invocation.positional.clone();
+    if focus_tokens
+        .first()
+        .map(|token| token.eq_ignore_ascii_case("debug"))
+        .unwrap_or(false)
+    {
+        focus_tokens.remove(0);
+    }
+
+    let requested_section = invocation
+        .arg("section")
+        .or_else(|| invocation.arg("focus"))
+        .map(|value| value.to_ascii_lowercase())
+        .or_else(|| focus_tokens.first().map(|token| token.to_ascii_lowercase()))
+        .unwrap_or_else(|| "all".to_string());
+
+    let normalized = requested_section.as_str();
+    let wants_all = matches!(
+        normalized,
+        "all" | "todo" | "todos" | "todas" | "full" | "completo" | "completa"
+    );
+    let wants_general =
+        wants_all || matches!(normalized, "general" | "recursos" | "status" | "resumen");
+    let wants_remote = wants_all
+        || matches!(
+            normalized,
+            "remote" | "remoto" | "providers" | "proveedores" | "nube"
+        );
+    let wants_local = wants_all
+        || matches!(
+            normalized,
+            "local" | "jarvis" | "modelos" | "models" | "runtime"
+        );
+    let wants_commands =
+        wants_all || matches!(normalized, "commands" | "comandos" | "command" | "custom");
+    let wants_logs = wants_all
+        || matches!(
+            normalized,
+            "logs" | "errores" | "errors" | "diagnostico" | "diagnóstico"
+        );
+
+    let classify = |text: &str| {
+        let normalized = text.to_ascii_lowercase();
+        if normalized.contains("error")
+            || normalized.contains("fall")
+            || normalized.contains("no se pudo")
+            || normalized.contains("failed")
+        {
+            "ERROR"
+        } else if normalized.contains("sin ejecutar")
+            || normalized.contains("esperando")
+            || normalized.contains("pendiente")
+            || normalized.contains("sin actualizaciones")
+            || normalized.contains("no configurado")
+        {
+            "PEND"
+        } else {
+            "OK"
+        }
+    };
+
+    let mut lines = vec!["=== Diagnóstico avanzado del sistema ===".to_string()];
+    if !wants_all {
+        lines.push(format!(
+            "Filtro aplicado a la sección: {}.",
+            requested_section
+        ));
+    }
+
+    if wants_general {
+        lines.push("--- Recursos y configuración ---".to_string());
+        lines.push(format!(
+            "Memoria límite: {:.1} GB · Disco límite: {:.1} GB · Auto limpieza: {} (cada {} h).",
+            state.resource_memory_limit_gb,
+            state.resource_disk_limit_gb,
+            if state.enable_auto_cleanup {
+                "activa"
+            } else {
+                "inactiva"
+            },
+            state.cache_cleanup_interval_hours
+        ));
+        lines.push(format!(
+            "Directorio de caché: {} · Última limpieza: {}.",
+            state.cache_directory,
+            state
+                .last_cache_cleanup
+                .clone()
+                .unwrap_or_else(|| "nunca".to_string())
+        ));
+        lines.push(format!(
+            "Perfiles: {} · Proyectos: {} · Memoria contextual: {} ({} días).",
+            state.profiles.len(),
+            state.projects.len(),
+            if state.enable_memory_tracking {
+                "activa"
+            } else {
+                "inactiva"
+            },
+            state.memory_retention_days
+        ));
+    }
+
+    if wants_remote {
+        lines.push("--- Proveedores remotos ---".to_string());
+        let openai_status = state
+            .resources
+            .openai_test_status
+            .clone()
+            .unwrap_or_else(|| "sin ejecutar".to_string());
+        lines.push(format!(
+            "OpenAI [{}] modelo por defecto '{}' · {}.",
+            classify(&openai_status),
+            state.resources.openai_default_model,
+            openai_status
+        ));
+
+        let anthropic_status = state
+            .resources
+            .anthropic_test_status
+            .clone()
+            .unwrap_or_else(|| "sin ejecutar".to_string());
+        lines.push(format!(
+            "Claude [{}] modelo por defecto '{}' · {}.",
+            classify(&anthropic_status),
+            state.resources.claude_default_model,
+            anthropic_status
+        ));
+
+        let claude_catalog = state
+            .resources
+            .claude_models_status
+            .clone()
+            .unwrap_or_else(|| {
+                if state.resources.claude_available_models.is_empty() {
+                    "catálogo sin cargar".to_string()
+                } else {
+                    format!(
+                        "{} modelos disponibles en caché",
+                        state.resources.claude_available_models.len()
+                    )
+                }
+            });
+        lines.push(format!(
+            "Claude catálogo [{}] {}.",
+            classify(&claude_catalog),
+            claude_catalog
+        ));
+
+        let groq_status = state
+            .resources
+            .groq_test_status
+            .clone()
+            .unwrap_or_else(|| "sin ejecutar".to_string());
+        lines.push(format!(
+            "Groq [{}] modelo por defecto '{}' · {}.",
+            classify(&groq_status),
+            state.resources.groq_default_model,
+            groq_status
+        ));
+    }
+
+    if wants_local {
+        lines.push("--- Runtime local y Jarvis ---".to_string());
+        let jarvis_status = state
+            .resources
+            .jarvis_status
+            .clone()
+            .unwrap_or_else(|| "sin actualizaciones registradas".to_string());
+        let runtime_status = if let Some(runtime) = &state.resources.jarvis_runtime {
+            match runtime.active_adapters() {
+                [] => format!("Inicializado ({})", runtime.model_label()),
+                adapters => format!(
+                    "Inicializado ({} con LoRA {})",
+                    runtime.model_label(),
+                    adapters.join(", ")
+                ),
+            }
+        } else {
+            "No inicializado".to_string()
+        };
+        lines.push(format!(
+            "Jarvis [{}] {}.",
+            classify(&jarvis_status),
+            jarvis_status
+        ));
+        lines.push(format!(
+            "Runtime local: {} · Modelo configurado: {} · Instalación: {} · Autoarranque: {}.",
+            runtime_status,
+            state.resources.jarvis_model_path,
+            state.resources.jarvis_install_dir,
+            if state.resources.jarvis_auto_start {
+                "sí"
+            } else {
+                "no"
+            }
+        ));
+
+        if state.resources.installed_local_models.is_empty() {
+            lines.push("Modelos instalados: ninguno.".to_string());
+        } else {
+            let inventory = state
+                .resources
+                .installed_local_models
+                .iter()
+                .map(|model| {
+                    let label = model.identifier.display_label();
+                    let size = format_bytes(model.size_bytes);
+                    let installed = model
+                        .installed_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string();
+                    format!("{} · {} · instalado {}", label, size, installed)
+                })
+                .collect::<Vec<_>>()
+                .join(" | ");
+            lines.push(format!(
+                "Modelos instalados ({}): {}.",
+                state.resources.installed_local_models.len(),
+                inventory
+            ));
+        }
+
+        if state.chat.pending_local_installs.is_empty() {
+            lines.push("Instalaciones locales pendientes: ninguna.".to_string());
+        } else {
+            let installs = state
+                .chat
+                .pending_local_installs
+                .iter()
+                .map(|pending| {
+                    format!("{} › {}", pending.provider.display_name(), pending.model_id)
+                })
+                .collect::<Vec<_>>()
+                .join(" · ");
+            lines.push(format!(
+                "Instalaciones locales pendientes ({}): {}.",
+                state.chat.pending_local_installs.len(),
+                installs
+            ));
+        }
+
+        if state.chat.pending_provider_calls.is_empty() {
+            lines.push("Llamadas remotas en vuelo: ninguna.".to_string());
+        } else {
+            let preview = state
+                .chat
+                .pending_provider_calls
+                .iter()
+                .take(3)
+                .map(|call| {
+                    format!(
+                        "#{} {} · {} ({})",
+                        call.ticket.id,
+                        call.ticket.provider_name,
+                        call.ticket.alias,
+                        call.ticket.model
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" · ");
+            lines.push(format!(
+                "Llamadas remotas en vuelo ({}): {}{}.",
+                state.chat.pending_provider_calls.len(),
+                preview,
+                if state.chat.pending_provider_calls.len() > 3 {
+                    " · ..."
+                } else {
+                    ""
+                }
+            ));
+        }
+
+        lines.push("--- Proveedores locales ---".to_string());
+        for provider in LocalModelProvider::ALL {
+            let provider_state = state.provider_state(provider);
+            let token_state = if provider_state
+                .access_token
+                .as_ref()
+                .map(|token| !token.trim().is_empty())
+                .unwrap_or(false)
+            {
+                "token configurado"
+            } else {
+                "sin token"
+            };
+            let selection = provider_state
+                .selected_model
+                .and_then(|index| provider_state.models.get(index))
+                .map(|model| model.id.clone())
+                .unwrap_or_else(|| "ninguno".to_string());
+            let install_state = provider_state
+                .install_status
+                .clone()
+                .unwrap_or_else(|| "sin operaciones registradas".to_string());
+
+            lines.push(format!(
+                "{} → {} · modelos listados: {} · selección: {} · última instalación [{}] {}.",
+                provider.display_name(),
+                token_state,
+                provider_state.models.len(),
+                selection,
+                classify(&install_state),
+                install_state
+            ));
+        }
+    }
+
+    if wants_commands {
+        lines.push("--- Comandos personalizados ---".to_string());
+        if state.chat.custom_commands.is_empty() {
+            lines.push("No hay comandos personalizados registrados.".to_string());
+        } else {
+            for command in &state.chat.custom_commands {
+                lines.push(format!("{} → {}", command.trigger, command.action.label()));
+            }
+            lines.push(format!(
+                "Total de comandos personalizados: {}.",
+                state.chat.custom_commands.len()
+            ));
+        }
+    }
+
+    if wants_logs {
+        lines.push("--- Registros y alertas ---".to_string());
+        let ok_count = state
+            .automation
+            .activity_logs
+            .iter()
+            .filter(|entry| entry.status == LogStatus::Ok)
+            .count();
+        let warn_count = state
+            .automation
+            .activity_logs
+            .iter()
+            .filter(|entry| entry.status == LogStatus::Warning)
+            .count();
+        let err_count = state
+            .automation
+            .activity_logs
+            .iter()
+            .filter(|entry| entry.status == LogStatus::Error)
+            .count();
+        let running_count = state
+            .automation
+            .activity_logs
+            .iter()
+            .filter(|entry| entry.status == LogStatus::Running)
+            .count();
+
+        if let Some(last_error) = state
+            .automation
+            .activity_logs
+            .iter()
+            .rev()
+            .find(|entry| entry.status == LogStatus::Error)
+        {
+            lines.push(format!(
+                "Último error registrado a las {} desde {} → {}.",
+                last_error.timestamp, last_error.source, last_error.message
+            ));
+        } else {
+            lines.push("No hay errores registrados en los logs recientes.".to_string());
+        }
+
+        lines.push(format!(
+            "Resumen de logs → OK: {} · Warning: {} · Error: {} · Running: {}.",
+            ok_count, warn_count, err_count, running_count
+        ));
+    }
+
+    lines
+}
+
+fn show_usage_statistics(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let window = invocation.arg("window").unwrap_or("session");
+    let include = invocation
+        .arg("include")
+        .map(|value| {
+            value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let mut lines = vec![format!(
+        "Estadísticas (ventana: {}): {} mensajes registrados en esta sesión y {} comandos personalizados disponibles.",
+        window,
+        state.chat.messages.len(),
+        state.chat.custom_commands.len()
+    )];
+
+    if include.iter().any(|s| s == "commands") {
+        lines.push(format!(
+            "Triggers personalizados: {}",
+            state
+                .chat
+                .custom_commands
+                .iter()
+                .map(|cmd| cmd.trigger.clone())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+
+    if include.iter().any(|s| s == "messages") {
+        lines.push(format!(
+            "Último mensaje de usuario: {}",
+            state
+                .chat
+                .messages
+                .iter()
+                .rev()
+                .find(|msg| msg.sender == "User")
+                .map(|msg| msg.text.clone())
+                .unwrap_or_else(|| "sin actividad".to_string())
+        ));
+    }
+
+    lines
+}
+
+fn list_active_projects(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let limit = invocation
+        .arg("limit")
+        .and_then(|value| value.parse::<usize>().ok());
+    let mut projects = state.projects.clone();
+    if let Some(max) = limit {
+        projects.truncate(max);
+    }
+
+    if projects.is_empty() {
+        vec!["No hay proyectos configurados actualmente.".to_string()]
+    } else {
+        vec![format!("Proyectos activos: {}.", projects.join(", "))]
+    }
+}
+
+fn list_configured_profiles(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let mut profiles = state.profiles.clone();
+    match invocation.arg("sort").unwrap_or("asc") {
+        "desc" => profiles.sort_by(|a, b| b.cmp(a)),
+        _ => profiles.sort(),
+    }
+
+    if profiles.is_empty() {
+        vec!["No hay perfiles configurados.".to_string()]
+    } else {
+        vec![format!("Perfiles disponibles: {}.", profiles.join(", "))]
+    }
+}
+
+fn show_cache_configuration(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let include = invocation
+        .arg("include")
+        .map(|value| {
+            value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_else(|| vec!["limits".to_string()]);
+    let mut lines = Vec::new();
+
+    if include.iter().any(|s| s == "path") {
+        lines.push(format!("Directorio de caché: {}", state.cache_directory));
+    }
+    if include.iter().any(|s| s == "limits") {
+        lines.push(format!(
+            "Límite configurado: {:.1} GB, limpieza automática: {}.",
+            state.cache_size_limit_gb,
+            if state.enable_auto_cleanup {
+                "sí"
+            } else {
+                "no"
+            }
+        ));
+    }
+    if include.iter().any(|s| s == "schedule") {
+        lines.push(format!(
+            "La limpieza se programa cada {} horas. Última ejecución: {}.",
+            state.cache_cleanup_interval_hours,
+            state
+                .last_cache_cleanup
+                .clone()
+                .unwrap_or_else(|| "nunca".to_string())
+        ));
+    }
+
+    if lines.is_empty() {
+        lines.push("No se reconocieron parámetros para mostrar.".to_string());
+    }
+    lines
+}
+
+fn list_available_models(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let provider = invocation.arg("provider").unwrap_or("all");
+    let mut lines = Vec::new();
+
+    match provider {
+        "openai" => lines.push(format!(
+            "Modelo OpenAI activo: {}",
+            state.resources.openai_default_model
+        )),
+        "anthropic" => lines.push(format!(
+            "Modelo Claude activo: {}",
+            state.resources.claude_default_model
+        )),
+        "groq" => lines.push(format!(
+            "Modelo Groq activo: {}",
+            state.resources.groq_default_model
+        )),
+        "jarvis" => lines.push(format!(
+            "Jarvis está configurado con: {}",
+            state.resources.jarvis_model_path
+        )),
+        "huggingface" => {
+            let provider_state = state.provider_state(LocalModelProvider::HuggingFace);
+            if provider_state.models.is_empty() {
+                lines.push("No hay modelos de HuggingFace registrados.".to_string());
+            } else {
+                let joined = provider_state
+                    .models
+                    .iter()
+                    .map(|model| model.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                lines.push(format!("Modelos de HuggingFace: {}", joined));
+            }
+        }
+        "all" => {
+            lines.push(format!(
+                "OpenAI: {} · Claude: {} · Groq: {} · Jarvis: {}",
+                state.resources.openai_default_model,
+                state.resources.claude_default_model,
+                state.resources.groq_default_model,
+                state.resources.jarvis_model_path
+            ));
+            let provider_state = state.provider_state(LocalModelProvider::HuggingFace);
+            if provider_state.models.is_empty() {
+                lines.push("HuggingFace: sin resultados cargados.".to_string());
+            } else {
+                let preview: Vec<&str> = provider_state
+                    .models
+                    .iter()
+                    .take(5)
+                    .map(|model| model.id.as_str())
+                    .collect();
+                lines.push(format!(
+                    "HuggingFace ({} modelos): {}",
+                    provider_state.models.len(),
+                    preview.join(", ")
+                ));
+            }
+        }
+        other => lines.push(format!("Proveedor desconocido: {}", other)),
+    }
+
+    lines
+}
+
+fn show_github_summary(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let include_repos = invocation
+        .arg("include")
+        .map(|value| value.split(',').any(|v| v.trim() == "repos"))
+        .unwrap_or(false);
+
+    let mut lines = vec![
+        match (&state.github_username, state.github_repositories.is_empty()) {
+            (Some(username), false) => format!(
+                "GitHub autenticado como {} con {} repositorios sincronizables.",
+                username,
+                state.github_repositories.len()
+            ),
+            (Some(username), true) => format!(
+                "GitHub autenticado como {}, pero no se encontraron repositorios visibles.",
+                username
+            ),
+            _ => "GitHub no está conectado todavía.".to_string(),
+        },
+    ];
+
+    if include_repos && !state.github_repositories.is_empty() {
+        lines.push(format!(
+            "Repositorios: {}",
+            state.github_repositories.join(", ")
+        ));
+    }
+
+    lines
+}
+
+fn show_memory_settings(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let detail = invocation.arg("detail").unwrap_or("summary");
+    let mut lines = vec![format!(
+        "Memoria contextual {} con retención de {} días.",
+        if state.enable_memory_tracking {
+            "activada"
+        } else {
+            "desactivada"
+        },
+        state.memory_retention_days
+    )];
+
+    if detail == "retention" {
+        lines.push(
+            "Los recuerdos más antiguos se archivan para mantener el contexto relevante."
+                .to_string(),
+        );
+    }
+
+    lines
+}
+
+fn show_active_providers(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let include = invocation
+        .arg("include")
+        .map(|value| {
+            value
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    let mut lines = vec![format!(
+        "Proveedores activos → OpenAI ({}) · Claude ({}) · Groq ({})",
+        state.resources.openai_default_model,
+        state.resources.claude_default_model,
+        state.resources.groq_default_model
+    )];
+
+    if include.iter().any(|s| s == "models") {
+        lines.push(format!(
+            "Jarvis usa {} y hay {} modelos de HuggingFace listos.",
+            state.resources.jarvis_model_path,
+            state
+                .provider_state(LocalModelProvider::HuggingFace)
+                .models
+                .len()
+        ));
+    }
+    if include.iter().any(|s| s == "status") {
+        lines.push(format!(
+            "Estado de pruebas → OpenAI: {} · Claude: {} · Groq: {}",
+            state
+                .resources
+                .openai_test_status
+                .clone()
+                .unwrap_or_else(|| "sin ejecutar".to_string()),
+            state
+                .resources
+                .anthropic_test_status
+                .clone()
+                .unwrap_or_else(|| "sin ejecutar".to_string()),
+            state
+                .resources
+                .groq_test_status
+                .clone()
+                .unwrap_or_else(|| "sin ejecutar".to_string())
+        ));
+    }
+
+    lines
+}
+
+fn show_jarvis_status(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let detail = invocation.arg("detail").unwrap_or("summary");
+    let mut lines = vec![format!(
+        "Jarvis en '{}' ({}) → {}",
+        state.resources.jarvis_model_path,
+        if state.resources.jarvis_auto_start {
+            "autoarranque habilitado"
+        } else {
+            "autoarranque deshabilitado"
+        },
+        state
+            .resources
+            .jarvis_status
+            .clone()
+            .unwrap_or_else(|| "Jarvis esperando tareas.".to_string())
+    )];
+
+    match detail {
+        "path" => lines.push(format!(
+            "El modelo local se puede actualizar reemplazando el archivo en {}.",
+            state.resources.jarvis_model_path
+        )),
+        "logs" => lines.push("Los registros en tiempo real no están disponibles en modo demo, pero se guardan en /var/log/jarvis.".to_string()),
+        _ => {}
+    }
+
+    lines
+}
+
+fn show_command_help(state: &AppState, invocation: &CommandInvocation) -> Vec<String> {
+    let mode = invocation.arg("mode").unwrap_or("all");
+    let mut builtins: Vec<String> = state
+        .command_engine
+        .builtin_names()
+        .into_iter()
+        .map(|name| match name {
+            "/system" => "/system debug".to_string(),
+            other => other.to_string(),
+        })
+        .collect();
+    builtins.extend(
+        state
+            .command_engine
+            .extension_commands()
+            .map(|command| command.name().to_string()),
+    );
+    builtins.dedup();
+    let custom: Vec<String> = state
+        .chat
+        .custom_commands
+        .iter()
+        .map(|cmd| cmd.trigger.clone())
+        .collect();
+
+    let mut lines = Vec::new();
+    match mode {
+        "builtins" => lines.push(format!("Comandos base: {}", builtins.join(", "))),
+        "custom" => {
+            if custom.is_empty() {
+                lines.push("No hay comandos personalizados.".to_string());
+            } else {
+                lines.push(format!("Comandos personalizados: {}", custom.join(", ")));
+            }
+        }
+        _ => {
+            lines.push(format!("Comandos base: {}", builtins.join(", ")));
+            if custom.is_empty() {
+                lines.push("Comandos personalizados: ninguno configurado.".to_string());
+            } else {
+                lines.push(format!("Comandos personalizados: {}", custom.join(", ")));
+            }
+        }
+    }
+
+    if mode == "all" || mode == "builtins" {
+        lines.push(
+            "Utiliza '/if <condición> then <cmd>' para ejecutar lógica condicional.".to_string(),
+        );
+        lines.push(
+            "Encadena comandos con '|': la salida de uno llega al siguiente (p. ej. /models | /summarize)."
+                .to_string(),
+        );
+    }
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AutomationPermissionGrant;
+    use crate::state::{audit, permissions, CustomCommandDraft};
+
+    /// Estado cuyo registro de auditoría va a un directorio temporal y no al del usuario.
+    fn state_with_temporary_audit(name: &str) -> AppState {
+        let dir = std::env::temp_dir()
+            .join(format!("jmk-commands-{}-{}", name, std::process::id()))
+            .join(audit::AUDIT_DIR);
+        let _ = std::fs::remove_dir_all(dir.parent().unwrap());
+        AppState {
+            audit: audit::AuditState::in_directory(dir),
+            ..AppState::default()
+        }
+    }
+
+    fn run(state: &mut AppState, input: &str) -> Vec<String> {
+        state
+            .resolve_command(CommandInvocation::parse(input), 0)
+            .messages
+    }
+
+    struct EchoCommand;
+
+    impl Command for EchoCommand {
+        fn name(&self) -> &str {
+            "/echo"
+        }
+
+        fn summary(&self) -> String {
+            "Repite los argumentos.".to_string()
+        }
+
+        fn execute(
+            &self,
+            _ctx: &mut CommandContext<'_>,
+            invocation: &CommandInvocation,
+        ) -> CommandOutcome {
+            CommandOutcome::single(invocation.positional.join(" "))
+        }
+    }
+
+    #[test]
+    fn parse_splits_args_flags_and_positional() {
+        let invocation = CommandInvocation::parse("/status --detail=memory --verbose extra key=v");
+        assert_eq!(invocation.name, "/status");
+        assert_eq!(invocation.arg("detail"), Some("memory"));
+        assert_eq!(invocation.arg("key"), Some("v"));
+        assert!(invocation.flag("verbose"));
+        assert_eq!(invocation.positional, vec!["extra".to_string()]);
+    }
+
+    #[test]
+    fn time_honours_format() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/time --format=24");
+        assert_eq!(lines.len(), 1);
+        let rendered = lines[0].trim_start_matches("Hora actual: ");
+        assert_eq!(rendered.len(), "00:00:00".len());
+    }
+
+    #[test]
+    fn status_adds_requested_detail() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/status --detail=disk --verbose");
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].starts_with("Límites configurados"));
+        assert!(lines[2].contains(&state.cache_directory));
+    }
+
+    #[test]
+    fn system_debug_filters_sections() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/system debug section=commands");
+        assert!(lines[0].contains("Diagnóstico"));
+        assert!(lines
+            .iter()
+            .any(|line| line.contains("Comandos personalizados")));
+        assert!(!lines
+            .iter()
+            .any(|line| line.contains("Proveedores remotos")));
+
+        let plain = run(&mut state, "/system");
+        assert!(plain[0].starts_with("El monitor de recursos aún no"));
+    }
+
+    #[test]
+    fn models_filters_by_provider() {
+        let mut state = AppState::default();
+        state.resources.openai_default_model = "gpt-test".to_string();
+        let lines = run(&mut state, "/models provider=openai");
+        assert_eq!(lines, vec!["Modelo OpenAI activo: gpt-test".to_string()]);
+        let unknown = run(&mut state, "/models provider=nada");
+        assert_eq!(unknown, vec!["Proveedor desconocido: nada".to_string()]);
+    }
+
+    #[test]
+    fn stats_includes_requested_sections() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/stats include=commands,messages");
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("Triggers personalizados"));
+    }
+
+    #[test]
+    fn reload_mentions_force() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/reload --force");
+        assert!(lines[0].contains("Forzando refresco"));
+    }
+
+    #[test]
+    fn projects_respects_limit() {
+        let mut state = AppState {
+            projects: vec!["uno".to_string(), "dos".to_string()],
+            ..AppState::default()
+        };
+        let lines = run(&mut state, "/projects --limit=1");
+        assert_eq!(lines, vec!["Proyectos activos: uno.".to_string()]);
+    }
+
+    #[test]
+    fn profiles_sort_descending() {
+        let mut state = AppState {
+            profiles: vec!["a".to_string(), "c".to_string(), "b".to_string()],
+            ..AppState::default()
+        };
+        let lines = run(&mut state, "/profiles --sort=desc");
+        assert_eq!(lines, vec!["Perfiles disponibles: c, b, a.".to_string()]);
+    }
+
+    #[test]
+    fn cache_reports_unknown_sections() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/cache include=nada");
+        assert_eq!(
+            lines,
+            vec!["No se reconocieron parámetros para mostrar.".to_string()]
+        );
+        let path = run(&mut state, "/cache include=path");
+        assert!(path[0].starts_with("Directorio de caché"));
+    }
+
+    #[test]
+    fn github_reports_connection() {
+        let mut state = AppState {
+            github_username: None,
+            ..AppState::default()
+        };
+        let lines = run(&mut state, "/github");
+        assert_eq!(lines, vec!["GitHub no está conectado todavía.".to_string()]);
+    }
+
+    #[test]
+    fn memory_retention_detail() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/memory detail=retention");
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn providers_include_status() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/providers include=status");
+        assert!(lines[1].starts_with("Estado de pruebas"));
+    }
+
+    #[test]
+    fn jarvis_path_detail() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/jarvis detail=path");
+        assert_eq!(lines.len(), 2);
+        assert!(lines[1].contains(&state.resources.jarvis_model_path));
+    }
+
+    #[test]
+    fn help_lists_builtins_and_extensions() {
+        let mut state = AppState::default();
+        state.command_engine.register(EchoCommand);
+        let lines = run(&mut state, "/help mode=builtins");
+        assert!(lines[0].contains("/system debug"));
+        assert!(lines[0].contains("/echo"));
+    }
+
+    #[test]
+    fn conditional_runs_selected_branch() {
+        let mut state = AppState {
+            projects: vec!["uno".to_string()],
+            ..AppState::default()
+        };
+        let lines = run(&mut state, "/if projects.count > 0 then /reload else /time");
+        assert!(lines[0].contains("verdadera"));
+        assert!(lines.last().unwrap().starts_with("Recargando"));
+
+        let missing_else = run(&mut state, "/if projects.count > 5 then /reload");
+        assert_eq!(
+            missing_else.last().unwrap(),
+            "No se especificó comando 'else'."
+        );
+    }
+
+    #[test]
+    fn conditional_recursion_is_limited() {
+        let mut state = AppState::default();
+        let nested = format!(
+            "{}/reload",
+            "/if commands.count >= 0 then ".repeat(MAX_COMMAND_DEPTH + 2)
+        );
+        let lines = run(&mut state, &nested);
+        assert!(lines
+            .last()
+            .unwrap()
+            .starts_with("Recursión de comandos demasiado profunda"));
+    }
+
+    #[test]
+    fn custom_commands_override_builtins() {
+        let mut state = AppState::default();
+        state.chat.custom_commands = vec![CustomCommand::new(
+            "/models",
+            CustomCommandAction::ListActiveProjects,
+        )];
+        state.projects.clear();
+        let lines = run(&mut state, "/models");
+        assert_eq!(
+            lines,
+            vec!["No hay proyectos configurados actualmente.".to_string()]
+        );
+    }
+
+    #[test]
+    fn registered_commands_resolve_and_unregister() {
+        let mut state = AppState::default();
+        state.command_engine.register(EchoCommand);
+        assert_eq!(run(&mut state, "/echo hola mundo"), vec!["hola mundo"]);
+        assert!(state.command_engine.unregister("/echo"));
+        assert_eq!(
+            run(&mut state, "/echo hola"),
+            vec!["Unknown command: /echo hola"]
+        );
+    }
+
+    #[test]
+    fn workflow_commands_trigger_workflows() {
+        let mut state = state_with_temporary_audit("workflows");
+        let Some(workflow) = state.automation.workflows.workflows.first().cloned() else {
+            return;
+        };
+        state
+            .command_engine
+            .set_module_commands(vec![Rc::new(WorkflowCommand::new(
+                "/flujo",
+                workflow.id,
+                &workflow.name,
+            ))]);
+        state.config.automation_permissions.clear();
+        let lines = run(&mut state, "/flujo");
+        assert!(lines[0].contains("espera permiso"));
+        assert_eq!(state.permissions.queue.len(), 1);
+
+        state.resolve_automation_permission(true);
+        let lines = run(&mut state, "/flujo");
+        assert!(lines[0].contains("espera permiso"));
+        state.permissions.queue.clear();
+        for effect in permissions::workflow_effects(&workflow) {
+            state
+                .config
+                .automation_permissions
+                .push(AutomationPermissionGrant {
+                    effect,
+                    automation: None,
+                    granted_at: String::new(),
+                });
+        }
+        let lines = run(&mut state, "/flujo");
+        assert_eq!(lines[0], format!("Workflow '{}' lanzado.", workflow.name));
+        assert_eq!(lines.len(), 1 + workflow.steps.len());
+    }
+
+    struct UpperCommand;
+
+    impl Command for UpperCommand {
+        fn name(&self) -> &str {
+            "/upper"
+        }
+
+        fn summary(&self) -> String {
+            "Pasa la entrada a mayúsculas.".to_string()
+        }
+
+        fn execute(
+            &self,
+            _ctx: &mut CommandContext<'_>,
+            invocation: &CommandInvocation,
+        ) -> CommandOutcome {
+            CommandOutcome::single(invocation.input.clone().unwrap_or_default().to_uppercase())
+        }
+    }
+
+    #[test]
+    fn split_pipeline_ignores_logical_or_and_plain_bars() {
+        assert_eq!(
+            split_pipeline("/models provider=openai | /summarize"),
+            vec!["/models provider=openai", "/summarize"]
+        );
+        assert_eq!(
+            split_pipeline("/if a || b then /time"),
+            vec!["/if a || b then /time"]
+        );
+        assert_eq!(split_pipeline("/echo a|b"), vec!["/echo a|b"]);
+    }
+
+    #[test]
+    fn pipelines_feed_each_stage_with_the_previous_output() {
+        let mut state = state_with_temporary_audit("pipelines");
+        state.command_engine.register(EchoCommand);
+        state.command_engine.register(UpperCommand);
+
+        let outcome = state.run_command_line("/echo hola mundo | /upper");
+        assert_eq!(outcome.messages, vec!["HOLA MUNDO".to_string()]);
+    }
+
+    #[test]
+    fn summarize_requires_input() {
+        let mut state = AppState::default();
+        let lines = run(&mut state, "/summarize");
+        assert!(lines[0].starts_with("Nada que resumir"));
+    }
+
+    #[test]
+    fn workflow_command_maps_arguments_to_step_parameters() {
+        let mut state = state_with_temporary_audit("workflow");
+        let Some(mut workflow) = state.automation.workflows.workflows.first().cloned() else {
+            return;
+        };
+        workflow.id = 999;
+        workflow.name = "Publicar informe".to_string();
+        workflow.steps.truncate(1);
+        workflow.steps[0].detail = "Publica {input} en {canal}".to_string();
+        state.automation.workflows.workflows.push(workflow);
+
+        let missing = run(&mut state, "/workflow 999");
+        assert!(missing[0].contains("canal"));
+
+        state.command_engine.register(EchoCommand);
+        let outcome = state.run_command_line("/echo resumen | /workflow #999 canal=#qa");
+        assert!(outcome.messages[1].ends_with("Publica resumen en #qa"));
+
+        let unknown = run(&mut state, "/workflow \"No existe\"");
+        assert!(unknown[0].starts_with("No hay ningún workflow"));
+    }
+
+    #[test]
+    fn fill_template_replaces_positional_named_and_piped_values() {
+        let mut invocation = CommandInvocation::parse("/traducir hola mundo idioma=inglés");
+        invocation.input = Some("entrada".to_string());
+        assert_eq!(
+            fill_template("{args} | {0} | {1} | {idioma} | {input}", &invocation),
+            Ok("hola mundo | hola | mundo | inglés | entrada".to_string())
+        );
+        assert_eq!(
+            fill_template("{2} {tono} {tono}", &invocation),
+            Err(vec!["2".to_string(), "tono".to_string()])
+        );
+    }
+
+    #[test]
+    fn script_commands_run_each_line_with_arguments() {
+        let mut state = AppState::default();
+        state.command_engine.register(EchoCommand);
+        state.chat.custom_commands = vec![CustomCommand {
+            trigger: "/doble".to_string(),
+            action: CustomCommandAction::ShowCurrentTime,
+            behavior: CustomCommandBehavior::Script {
+                commands: "/echo uno {0}\n// comentario\n/echo dos {modo}".to_string(),
+            },
+        }];
+
+        let lines = run(&mut state, "/doble x modo=rápido");
+        assert_eq!(lines, vec!["uno x".to_string(), "dos rápido".to_string()]);
+
+        let missing = run(&mut state, "/doble x");
+        assert_eq!(missing[0], "uno x");
+        assert!(missing[1].contains("modo"));
+    }
+
+    #[test]
+    fn prompt_commands_require_their_placeholders() {
+        let mut state = AppState::default();
+        let command = CustomCommand {
+            trigger: "/resume".to_string(),
+            action: CustomCommandAction::ShowCurrentTime,
+            behavior: CustomCommandBehavior::Prompt {
+                provider: Some("openai".to_string()),
+                template: "Resume {tema} en tres puntos".to_string(),
+            },
+        };
+        let outcome = state.run_custom_command_preview(command, "");
+        assert_eq!(
+            outcome.messages,
+            vec!["Faltan argumentos para /resume: tema.".to_string()]
+        );
+    }
+
+    #[test]
+    fn drafts_normalize_triggers_and_reject_empty_bodies() {
+        let mut draft = CustomCommandDraft::from_command(
+            0,
+            &CustomCommand::new("/hora", CustomCommandAction::ShowCurrentTime),
+        );
+        draft.trigger = "hora".to_string();
+        assert_eq!(draft.to_command().unwrap().trigger, "/hora");
+
+        draft.trigger = "/dos palabras".to_string();
+        assert!(draft.to_command().is_err());
+
+        draft.trigger = "/hora".to_string();
+        draft.behavior = CustomCommandBehavior::Prompt {
+            provider: None,
+            template: " ".to_string(),
+        };
+        assert!(draft.to_command().is_err());
+
+        draft.behavior = CustomCommandBehavior::Script {
+            commands: "/time".to_string(),
+        };
+        assert!(draft.to_command().is_ok());
+    }
+}
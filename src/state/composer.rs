@@ -0,0 +1,153 @@
+use std::fs;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+
+use super::vault;
+
+/// Retraso máximo entre una edición y el guardado del borrador en disco.
+pub const DRAFT_AUTOSAVE_DELAY: Duration = Duration::from_secs(1);
+/// Mensajes enviados que se conservan para recuperarlos desde el editor.
+pub const INPUT_HISTORY_LIMIT: usize = 200;
+
+pub(super) const COMPOSER_FILE: &str = "composer.json";
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct StoredComposer {
+    draft: String,
+    history: Vec<String>,
+}
+
+/// Borrador del editor del chat e historial de mensajes enviados.
+#[derive(Default)]
+pub struct ComposerState {
+    /// Mensajes enviados, del más antiguo al más reciente.
+    pub history: Vec<String>,
+    pub show_history: bool,
+    pub history_query: String,
+    /// Entrada del historial mostrada en el editor al navegar con las flechas.
+    cursor: Option<usize>,
+    /// Lo que había escrito antes de empezar a recorrer el historial.
+    stashed_draft: String,
+    saved_draft: String,
+    history_changed: bool,
+    dirty_since: Option<Instant>,
+}
+
+impl ComposerState {
+    /// Recupera el historial guardado y devuelve también el último borrador.
+    pub fn load() -> (Self, String) {
+        let stored: StoredComposer = AppConfig::data_file(COMPOSER_FILE)
+            .ok()
+            .and_then(|path| vault::read_to_string(&path).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+
+        let state = Self {
+            saved_draft: stored.draft.clone(),
+            history: stored.history,
+            ..Self::default()
+        };
+        (state, stored.draft)
+    }
+
+    /// Añade un mensaje enviado al historial, sin repetir el último.
+    pub fn record_sent(&mut self, text: &str) {
+        self.cursor = None;
+        self.stashed_draft.clear();
+        if text.trim().is_empty() || self.history.last().is_some_and(|last| last == text) {
+            return;
+        }
+        self.history.push(text.to_string());
+        if self.history.len() > INPUT_HISTORY_LIMIT {
+            let excess = self.history.len() - INPUT_HISTORY_LIMIT;
+            self.history.drain(..excess);
+        }
+        self.history_changed = true;
+    }
+
+    /// Indica si `input` sigue siendo la entrada recuperada del historial, de modo que
+    /// las flechas continúen recorriéndolo en lugar de mover el cursor.
+    pub fn is_browsing(&self, input: &str) -> bool {
+        self.cursor
+            .and_then(|index| self.history.get(index))
+            .is_some_and(|entry| entry == input)
+    }
+
+    /// Sustituye `input` por el mensaje anterior del historial.
+    pub fn recall_previous(&mut self, input: &mut String) -> bool {
+        let index = match self.cursor {
+            Some(0) => return false,
+            Some(index) => index - 1,
+            None if self.history.is_empty() => return false,
+            None => {
+                self.stashed_draft = input.clone();
+                self.history.len() - 1
+            }
+        };
+        self.cursor = Some(index);
+        *input = self.history[index].clone();
+        true
+    }
+
+    /// Avanza hacia los mensajes más recientes; al pasar del último restaura el borrador.
+    pub fn recall_next(&mut self, input: &mut String) -> bool {
+        let Some(index) = self.cursor else {
+            return false;
+        };
+        if index + 1 < self.history.len() {
+            self.cursor = Some(index + 1);
+            *input = self.history[index + 1].clone();
+        } else {
+            self.cursor = None;
+            *input = std::mem::take(&mut self.stashed_draft);
+        }
+        true
+    }
+
+    /// Mensajes del historial que contienen `query`, del más reciente al más antiguo.
+    pub fn search(&self, query: &str) -> Vec<&str> {
+        let needle = query.trim().to_lowercase();
+        self.history
+            .iter()
+            .rev()
+            .filter(|entry| needle.is_empty() || entry.to_lowercase().contains(&needle))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Guarda el borrador y el historial como mucho `DRAFT_AUTOSAVE_DELAY` después de
+    /// que cambien. Devuelve `Ok(true)` si se escribió el fichero.
+    pub fn autosave(&mut self, draft: &str) -> Result<bool> {
+        if draft == self.saved_draft && !self.history_changed {
+            self.dirty_since = None;
+            return Ok(false);
+        }
+        let since = *self.dirty_since.get_or_insert_with(Instant::now);
+        if since.elapsed() < DRAFT_AUTOSAVE_DELAY {
+            return Ok(false);
+        }
+
+        // Se da por guardado aunque falle la escritura para no reintentarlo cada frame.
+        self.dirty_since = None;
+        self.saved_draft = draft.to_string();
+        self.history_changed = false;
+
+        let path = AppConfig::data_file(COMPOSER_FILE)?;
+        let json = serde_json::to_string(&StoredComposer {
+            draft: draft.to_string(),
+            history: self.history.clone(),
+        })?;
+        // Escribe en un temporal y lo renombra para no dejar el fichero a medias si la
+        // aplicación se cierra durante el guardado.
+        let temporary = path.with_extension("json.tmp");
+        vault::write(&temporary, &json)?;
+        fs::rename(&temporary, &path)
+            .with_context(|| format!("No se pudo guardar {}", path.display()))?;
+        Ok(true)
+    }
+}
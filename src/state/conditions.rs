@@ -0,0 +1,737 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use chrono::{Datelike, Local, Timelike};
+
+use super::{AppState, RemoteProviderKind};
+
+/// Variables que el estado de la aplicación expone a `/if` y a los listeners, con una
+/// descripción breve para la ayuda. `<p>` es el código del proveedor (anthropic, openai, groq).
+pub const STATE_VARIABLES: &[&str] = &[
+    "memory.enabled · cache.auto_cleanup · github.connected",
+    "profiles.count · projects.count · commands.count",
+    "jarvis.auto_start · jarvis.running · jarvis.model",
+    "network.online · network.offline_mode",
+    "providers.total · providers.configured",
+    "providers.<p>.configured · providers.<p>.status · providers.<p>.model",
+    "providers.<p>.calls_today · providers.<p>.tokens_today",
+    "providers.<p>.spend_today_usd · providers.<p>.spend_month_usd",
+    "usage.calls_today · usage.tokens_today · usage.spend_today_usd · usage.spend_month_usd",
+    "time.hour · time.minute · time.weekday (1 = lunes) · time.weekend · time.date · time.time",
+];
+
+/// Valor que puede tomar una variable o un literal dentro de una condición.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConditionValue {
+    Boolean(bool),
+    Number(f64),
+    Text(String),
+}
+
+impl ConditionValue {
+    fn type_name(&self) -> &'static str {
+        match self {
+            ConditionValue::Boolean(_) => "booleano",
+            ConditionValue::Number(_) => "número",
+            ConditionValue::Text(_) => "texto",
+        }
+    }
+}
+
+impl fmt::Display for ConditionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionValue::Boolean(value) => write!(f, "{}", value),
+            ConditionValue::Number(value) => write!(f, "{}", value),
+            ConditionValue::Text(value) => write!(f, "'{}'", value),
+        }
+    }
+}
+
+impl From<bool> for ConditionValue {
+    fn from(value: bool) -> Self {
+        ConditionValue::Boolean(value)
+    }
+}
+
+impl From<f64> for ConditionValue {
+    fn from(value: f64) -> Self {
+        ConditionValue::Number(value)
+    }
+}
+
+impl From<usize> for ConditionValue {
+    fn from(value: usize) -> Self {
+        ConditionValue::Number(value as f64)
+    }
+}
+
+impl From<String> for ConditionValue {
+    fn from(value: String) -> Self {
+        ConditionValue::Text(value)
+    }
+}
+
+impl From<&str> for ConditionValue {
+    fn from(value: &str) -> Self {
+        ConditionValue::Text(value.to_string())
+    }
+}
+
+/// Fuente de variables para las condiciones. Se consultan en orden y gana la primera
+/// que conozca el nombre, de modo que el payload de un evento puede tapar al estado.
+pub trait VariableResolver {
+    fn resolve(&self, name: &str) -> Option<ConditionValue>;
+}
+
+/// Campos del evento que dispara un listener (`message`, `command.name`, `task.name`…).
+#[derive(Clone, Debug, Default)]
+pub struct EventPayload {
+    fields: BTreeMap<String, ConditionValue>,
+}
+
+impl EventPayload {
+    pub fn with(mut self, name: &str, value: impl Into<ConditionValue>) -> Self {
+        self.fields.insert(name.to_string(), value.into());
+        self
+    }
+}
+
+impl VariableResolver for EventPayload {
+    fn resolve(&self, name: &str) -> Option<ConditionValue> {
+        self.fields.get(name).cloned()
+    }
+}
+
+impl VariableResolver for AppState {
+    fn resolve(&self, name: &str) -> Option<ConditionValue> {
+        if let Some(rest) = name.strip_prefix("providers.") {
+            if let Some((code, field)) = rest.split_once('.') {
+                let provider = PROVIDERS
+                    .into_iter()
+                    .find(|provider| provider.short_code() == code)?;
+                return self.provider_variable(provider, field);
+            }
+        }
+        if let Some(field) = name.strip_prefix("usage.") {
+            return PROVIDERS
+                .into_iter()
+                .map(|provider| self.provider_variable(provider, field))
+                .try_fold(0.0, |total, value| match value {
+                    Some(ConditionValue::Number(value)) => Some(total + value),
+                    _ => None,
+                })
+                .map(ConditionValue::Number);
+        }
+        if let Some(field) = name.strip_prefix("time.") {
+            return time_variable(field);
+        }
+
+        let value = match name {
+            "memory.enabled" => self.enable_memory_tracking.into(),
+            "profiles.count" => self.profiles.len().into(),
+            "projects.count" => self.projects.len().into(),
+            "cache.auto_cleanup" => self.enable_auto_cleanup.into(),
+            "github.connected" => self.github_username.is_some().into(),
+            "commands.count" => self.chat.custom_commands.len().into(),
+            "jarvis.auto_start" => self.resources.jarvis_auto_start.into(),
+            "jarvis.running" => self.resources.jarvis_runtime.is_some().into(),
+            "jarvis.model" => self
+                .resources
+                .jarvis_active_model
+                .as_ref()
+                .map(|model| model.model_id.clone())
+                .unwrap_or_default()
+                .into(),
+            "network.online" => (!self.is_offline()).into(),
+            "network.offline_mode" => self.config.network.offline_mode.into(),
+            "providers.total" => PROVIDERS.len().into(),
+            "providers.configured" => PROVIDERS
+                .into_iter()
+                .filter(|provider| self.provider_configured(*provider))
+                .count()
+                .into(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+const PROVIDERS: [RemoteProviderKind; 3] = [
+    RemoteProviderKind::Anthropic,
+    RemoteProviderKind::OpenAi,
+    RemoteProviderKind::Groq,
+];
+
+impl AppState {
+    fn provider_configured(&self, provider: RemoteProviderKind) -> bool {
+        self.remote_provider_config(provider)
+            .api_key
+            .as_deref()
+            .is_some_and(|key| !key.trim().is_empty())
+    }
+
+    fn provider_variable(
+        &self,
+        provider: RemoteProviderKind,
+        field: &str,
+    ) -> Option<ConditionValue> {
+        let config = self.remote_provider_config(provider);
+        // Los contadores no se ponen a cero hasta la siguiente llamada; aquí se descartan
+        // si pertenecen a un periodo ya cerrado.
+        let now = Local::now();
+        let usage = self
+            .resources
+            .provider_usage
+            .get(&provider)
+            .filter(|usage| usage.last_reset == now.date_naive());
+        let daily_spend = if config.spend.day == now.format("%Y-%m-%d").to_string() {
+            config.spend.daily_usd
+        } else {
+            0.0
+        };
+        let monthly_spend = if config.spend.month == now.format("%Y-%m").to_string() {
+            config.spend.monthly_usd
+        } else {
+            0.0
+        };
+
+        let value = match field {
+            "configured" => self.provider_configured(provider).into(),
+            "status" => match provider {
+                RemoteProviderKind::Anthropic => &self.resources.anthropic_test_status,
+                RemoteProviderKind::OpenAi => &self.resources.openai_test_status,
+                RemoteProviderKind::Groq => &self.resources.groq_test_status,
+            }
+            .clone()
+            .unwrap_or_default()
+            .into(),
+            "model" => config.default_model.as_str().into(),
+            "calls_today" => usage
+                .map_or(0.0, |usage| f64::from(usage.calls_today))
+                .into(),
+            "tokens_today" => usage
+                .map_or(0.0, |usage| {
+                    (usage.prompt_tokens_today + usage.completion_tokens_today) as f64
+                })
+                .into(),
+            "spend_today_usd" => f64::from(daily_spend).into(),
+            "spend_month_usd" => f64::from(monthly_spend).into(),
+            _ => return None,
+        };
+        Some(value)
+    }
+}
+
+fn time_variable(field: &str) -> Option<ConditionValue> {
+    let now = Local::now();
+    let value = match field {
+        "hour" => f64::from(now.hour()).into(),
+        "minute" => f64::from(now.minute()).into(),
+        "weekday" => f64::from(now.weekday().number_from_monday()).into(),
+        "weekend" => (now.weekday().number_from_monday() >= 6).into(),
+        "day" => f64::from(now.day()).into(),
+        "date" => now.format("%Y-%m-%d").to_string().into(),
+        "time" => now.format("%H:%M").to_string().into(),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Evalúa una condición como `providers.openai.configured && time.hour < 18`.
+///
+/// Admite `&&`, `||`, `!`, paréntesis, los comparadores `== != > >= < <=`, cadenas entre
+/// comillas simples o dobles y los métodos de texto `contains`, `starts_with` y
+/// `ends_with`. Las variables se buscan en `resolvers` por orden.
+pub fn evaluate(expression: &str, resolvers: &[&dyn VariableResolver]) -> Result<bool, String> {
+    let tokens = tokenize(expression)?;
+    if tokens.is_empty() {
+        return Err("La condición está vacía.".to_string());
+    }
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+    };
+    let expr = parser.parse_or()?;
+    if let Some(token) = parser.peek() {
+        return Err(format!("Sobra '{}' al final de la condición.", token));
+    }
+    expr.eval(resolvers)?.as_bool()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    Literal(ConditionValue),
+    Operator(Comparison),
+    And,
+    Or,
+    Not,
+    OpenParen,
+    CloseParen,
+    Comma,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Identifier(name) => write!(f, "{}", name),
+            Token::Literal(value) => write!(f, "{}", value),
+            Token::Operator(op) => write!(f, "{}", op.symbol()),
+            Token::And => write!(f, "&&"),
+            Token::Or => write!(f, "||"),
+            Token::Not => write!(f, "!"),
+            Token::OpenParen => write!(f, "("),
+            Token::CloseParen => write!(f, ")"),
+            Token::Comma => write!(f, ","),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Comparison {
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterOrEqual,
+    Less,
+    LessOrEqual,
+}
+
+impl Comparison {
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Equal => "==",
+            Comparison::NotEqual => "!=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+        }
+    }
+
+    fn accepts(self, ordering: std::cmp::Ordering) -> bool {
+        use std::cmp::Ordering::*;
+        match self {
+            Comparison::Equal => ordering == Equal,
+            Comparison::NotEqual => ordering != Equal,
+            Comparison::Greater => ordering == Greater,
+            Comparison::GreaterOrEqual => ordering != Less,
+            Comparison::Less => ordering == Less,
+            Comparison::LessOrEqual => ordering != Greater,
+        }
+    }
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let current = chars[index];
+        let next = chars.get(index + 1).copied();
+        match current {
+            c if c.is_whitespace() => index += 1,
+            '(' => {
+                tokens.push(Token::OpenParen);
+                index += 1;
+            }
+            ')' => {
+                tokens.push(Token::CloseParen);
+                index += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                index += 1;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                index += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                index += 2;
+            }
+            '=' if next == Some('=') => {
+                tokens.push(Token::Operator(Comparison::Equal));
+                index += 2;
+            }
+            '!' if next == Some('=') => {
+                tokens.push(Token::Operator(Comparison::NotEqual));
+                index += 2;
+            }
+            '!' => {
+                tokens.push(Token::Not);
+                index += 1;
+            }
+            '>' | '<' => {
+                let or_equal = next == Some('=');
+                let op = match (current, or_equal) {
+                    ('>', false) => Comparison::Greater,
+                    ('>', true) => Comparison::GreaterOrEqual,
+                    ('<', false) => Comparison::Less,
+                    _ => Comparison::LessOrEqual,
+                };
+                tokens.push(Token::Operator(op));
+                index += if or_equal { 2 } else { 1 };
+            }
+            '\'' | '"' => {
+                let end = chars[index + 1..]
+                    .iter()
+                    .position(|c| *c == current)
+                    .ok_or_else(|| "Falta cerrar una cadena en la condición.".to_string())?;
+                let text: String = chars[index + 1..index + 1 + end].iter().collect();
+                tokens.push(Token::Literal(ConditionValue::Text(text)));
+                index += end + 2;
+            }
+            c if c.is_ascii_digit()
+                || (c == '-' && next.is_some_and(|next| next.is_ascii_digit())) =>
+            {
+                let start = index;
+                index += 1;
+                while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.')
+                {
+                    index += 1;
+                }
+                let text: String = chars[start..index].iter().collect();
+                let number = text
+                    .parse::<f64>()
+                    .map_err(|_| format!("'{}' no es un número válido.", text))?;
+                tokens.push(Token::Literal(ConditionValue::Number(number)));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = index;
+                while index < chars.len()
+                    && (chars[index].is_alphanumeric() || matches!(chars[index], '_' | '.'))
+                {
+                    index += 1;
+                }
+                let word: String = chars[start..index].iter().collect();
+                tokens.push(match word.as_str() {
+                    "true" => Token::Literal(ConditionValue::Boolean(true)),
+                    "false" => Token::Literal(ConditionValue::Boolean(false)),
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Identifier(word),
+                });
+            }
+            other => return Err(format!("Carácter inesperado en la condición: '{}'.", other)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+#[derive(Debug)]
+enum Expr {
+    Literal(ConditionValue),
+    Variable(String),
+    Method {
+        target: Box<Expr>,
+        name: String,
+        argument: Box<Expr>,
+    },
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(Box<Expr>, Comparison, Box<Expr>),
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.advance() {
+            Some(token) if token == expected => Ok(()),
+            Some(token) => Err(format!("Se esperaba '{}' y llegó '{}'.", expected, token)),
+            None => Err(format!("Falta '{}' al final de la condición.", expected)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.advance();
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        while self.peek() == Some(&Token::And) {
+            self.advance();
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        if self.peek() == Some(&Token::Not) {
+            self.advance();
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        let lhs = self.parse_operand()?;
+        if let Some(Token::Operator(op)) = self.peek().cloned() {
+            self.advance();
+            let rhs = self.parse_operand()?;
+            return Ok(Expr::Compare(Box::new(lhs), op, Box::new(rhs)));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr, String> {
+        match self.advance() {
+            Some(Token::OpenParen) => {
+                let expr = self.parse_or()?;
+                self.expect(Token::CloseParen)?;
+                Ok(expr)
+            }
+            Some(Token::Literal(value)) => Ok(Expr::Literal(value)),
+            Some(Token::Identifier(name)) => {
+                if self.peek() != Some(&Token::OpenParen) {
+                    return Ok(Expr::Variable(name));
+                }
+                // `message.contains('x')`: el último segmento es el método.
+                let (target, method) = name
+                    .rsplit_once('.')
+                    .ok_or_else(|| format!("'{}' no es un método de texto.", name))?;
+                self.advance();
+                let argument = self.parse_or()?;
+                self.expect(Token::CloseParen)?;
+                Ok(Expr::Method {
+                    target: Box::new(Expr::Variable(target.to_string())),
+                    name: method.to_string(),
+                    argument: Box::new(argument),
+                })
+            }
+            Some(token) => Err(format!("'{}' no puede iniciar un operando.", token)),
+            None => Err("La condición termina de forma inesperada.".to_string()),
+        }
+    }
+}
+
+impl Expr {
+    fn eval(&self, resolvers: &[&dyn VariableResolver]) -> Result<ConditionValue, String> {
+        match self {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Variable(name) => resolvers
+                .iter()
+                .find_map(|resolver| resolver.resolve(name))
+                .ok_or_else(|| format!("Campo desconocido en la condición: {}", name)),
+            Expr::Method {
+                target,
+                name,
+                argument,
+            } => {
+                let target = target.eval(resolvers)?.as_text()?;
+                let argument = argument.eval(resolvers)?.as_text()?;
+                let result = match name.as_str() {
+                    "contains" => target.contains(&argument),
+                    "starts_with" => target.starts_with(&argument),
+                    "ends_with" => target.ends_with(&argument),
+                    other => return Err(format!("Método de texto desconocido: {}", other)),
+                };
+                Ok(ConditionValue::Boolean(result))
+            }
+            Expr::Not(inner) => Ok(ConditionValue::Boolean(!inner.eval(resolvers)?.as_bool()?)),
+            Expr::And(lhs, rhs) => Ok(ConditionValue::Boolean(
+                lhs.eval(resolvers)?.as_bool()? && rhs.eval(resolvers)?.as_bool()?,
+            )),
+            Expr::Or(lhs, rhs) => Ok(ConditionValue::Boolean(
+                lhs.eval(resolvers)?.as_bool()? || rhs.eval(resolvers)?.as_bool()?,
+            )),
+            Expr::Compare(lhs, op, rhs) => {
+                let lhs = lhs.eval(resolvers)?;
+                let rhs = rhs.eval(resolvers)?;
+                lhs.compare(*op, &rhs).map(ConditionValue::Boolean)
+            }
+        }
+    }
+}
+
+impl ConditionValue {
+    fn as_bool(&self) -> Result<bool, String> {
+        match self {
+            ConditionValue::Boolean(value) => Ok(*value),
+            other => Err(format!(
+                "Se esperaba un booleano y el valor {} es de tipo {}.",
+                other,
+                other.type_name()
+            )),
+        }
+    }
+
+    fn as_text(&self) -> Result<String, String> {
+        match self {
+            ConditionValue::Text(value) => Ok(value.clone()),
+            other => Err(format!(
+                "Se esperaba un texto y el valor {} es de tipo {}.",
+                other,
+                other.type_name()
+            )),
+        }
+    }
+
+    fn compare(&self, op: Comparison, rhs: &ConditionValue) -> Result<bool, String> {
+        use ConditionValue::*;
+        let ordering = match (self, rhs) {
+            (Number(lhs), Number(rhs)) => {
+                if (lhs - rhs).abs() < f64::EPSILON {
+                    std::cmp::Ordering::Equal
+                } else {
+                    lhs.partial_cmp(rhs).unwrap_or(std::cmp::Ordering::Equal)
+                }
+            }
+            (Text(lhs), Text(rhs)) => lhs.cmp(rhs),
+            // Se conserva la forma antigua `memory.enabled == 1`.
+            (Boolean(lhs), Number(rhs)) | (Number(rhs), Boolean(lhs))
+                if *rhs == 0.0 || *rhs == 1.0 =>
+            {
+                return self.compare_booleans(op, *lhs, *rhs == 1.0);
+            }
+            (Boolean(lhs), Boolean(rhs)) => return self.compare_booleans(op, *lhs, *rhs),
+            (lhs, rhs) => {
+                return Err(format!(
+                    "No se puede comparar {} ({}) con {} ({}).",
+                    lhs,
+                    lhs.type_name(),
+                    rhs,
+                    rhs.type_name()
+                ))
+            }
+        };
+        Ok(op.accepts(ordering))
+    }
+
+    fn compare_booleans(&self, op: Comparison, lhs: bool, rhs: bool) -> Result<bool, String> {
+        match op {
+            Comparison::Equal => Ok(lhs == rhs),
+            Comparison::NotEqual => Ok(lhs != rhs),
+            other => Err(format!(
+                "Operador '{}' no soportado para booleanos.",
+                other.symbol()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ListenerEventKind;
+
+    fn eval(expression: &str, payload: &EventPayload) -> Result<bool, String> {
+        evaluate(expression, &[payload])
+    }
+
+    fn payload() -> EventPayload {
+        EventPayload::default()
+            .with("message", "TODO: revisar el despliegue")
+            .with("count", 3usize)
+            .with("enabled", true)
+            .with("workflow_status", "failure")
+    }
+
+    #[test]
+    fn supports_boolean_logic_and_parentheses() {
+        let payload = payload();
+        assert_eq!(eval("enabled && count > 2", &payload), Ok(true));
+        assert_eq!(eval("!enabled || count == 3", &payload), Ok(true));
+        assert_eq!(eval("!(enabled && count < 2)", &payload), Ok(true));
+        assert_eq!(
+            eval("(count >= 4 || !enabled) && true", &payload),
+            Ok(false)
+        );
+        assert_eq!(eval("enabled and not (count != 3)", &payload), Ok(true));
+    }
+
+    #[test]
+    fn compares_strings_and_calls_text_methods() {
+        let payload = payload();
+        assert_eq!(eval("workflow_status == 'failure'", &payload), Ok(true));
+        assert_eq!(eval("workflow_status != \"failure\"", &payload), Ok(false));
+        assert_eq!(eval("message.contains('TODO:')", &payload), Ok(true));
+        assert_eq!(eval("message.starts_with('FIXME')", &payload), Ok(false));
+        assert_eq!(eval("'abc' < 'abd'", &payload), Ok(true));
+    }
+
+    #[test]
+    fn keeps_numeric_form_for_booleans() {
+        let payload = payload();
+        assert_eq!(eval("enabled == 1", &payload), Ok(true));
+        assert_eq!(eval("enabled != 0", &payload), Ok(true));
+        assert_eq!(eval("count <= -1", &payload), Ok(false));
+    }
+
+    #[test]
+    fn reports_unknown_fields_and_type_errors() {
+        let payload = payload();
+        assert!(eval("missing == 1", &payload)
+            .unwrap_err()
+            .contains("Campo desconocido"));
+        assert!(eval("count == 'tres'", &payload).is_err());
+        assert!(eval("count", &payload).is_err());
+        assert!(eval("enabled > true", &payload).is_err());
+        assert!(eval("(enabled", &payload).is_err());
+        assert!(eval("message == 'sin cerrar", &payload).is_err());
+        assert!(eval("", &payload).is_err());
+    }
+
+    #[test]
+    fn earlier_resolvers_shadow_later_ones() {
+        let event = EventPayload::default().with("projects.count", 42usize);
+        let state = AppState::default();
+        assert_eq!(
+            evaluate("projects.count == 42", &[&event, &state]),
+            Ok(true)
+        );
+        assert_eq!(
+            evaluate("providers.total == 3 && time.hour >= 0", &[&state]),
+            Ok(true)
+        );
+        assert!(evaluate("providers.unknown.configured", &[&state]).is_err());
+    }
+
+    #[test]
+    fn listeners_fire_only_when_their_condition_holds() {
+        let mut state = AppState::default();
+        let last_triggered = |state: &AppState| {
+            state
+                .automation
+                .event_automation
+                .listeners
+                .iter()
+                .find(|listener| listener.id == 1)
+                .and_then(|listener| listener.last_triggered.clone())
+        };
+        let before = last_triggered(&state);
+
+        let greeting = EventPayload::default().with("message", "hola");
+        state.dispatch_listener_event(ListenerEventKind::ChatMessage, &greeting);
+        assert_eq!(last_triggered(&state), before);
+
+        let todo = EventPayload::default().with("message", "TODO: probar los listeners");
+        state.dispatch_listener_event(ListenerEventKind::ChatMessage, &todo);
+        assert_ne!(last_triggered(&state), before);
+    }
+}
@@ -0,0 +1,411 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use super::file_watcher::{FileChangeKind, FileWatcher};
+use super::runtime;
+use crate::config::{AppConfig, WatchedPath};
+
+/// Retraso máximo entre un cambio de configuración y su escritura en disco.
+pub const CONFIG_SAVE_DELAY: Duration = Duration::from_millis(500);
+/// Tiempo que se espera a un guardado en curso al cerrar la aplicación.
+const SHUTDOWN_WAIT: Duration = Duration::from_secs(2);
+/// Agrupación de los eventos de `config.json`; los editores suelen escribir varias veces.
+const EXTERNAL_EDIT_DEBOUNCE: Duration = Duration::from_millis(300);
+
+type Sections = Map<String, Value>;
+/// API keys de los proveedores por sección, tal como están en el llavero.
+type ProviderKeys = BTreeMap<String, String>;
+
+/// Resultado de un guardado en segundo plano.
+#[derive(Debug)]
+pub struct ConfigSaveReport {
+    /// Secciones de primer nivel de la configuración que cambiaron.
+    pub sections: Vec<String>,
+    pub error: Option<String>,
+}
+
+/// Secciones editadas a la vez en `config.json` y en la aplicación, a la espera de que se
+/// elija qué versión conservar. Mientras tanto no se guarda nada.
+#[derive(Clone, Debug)]
+pub struct ConfigConflict {
+    pub sections: Vec<String>,
+    disk: Sections,
+}
+
+/// Resultado de incorporar una edición externa de `config.json`.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ExternalConfigChange {
+    /// Secciones que solo cambiaron en el fichero y ya se aplicaron.
+    pub applied: Vec<String>,
+    /// Secciones que también cambiaron en la aplicación; quedan en `ConfigSaver::conflict`.
+    pub conflicts: Vec<String>,
+}
+
+struct SaveOutcome {
+    report: ConfigSaveReport,
+    /// Secciones tal como quedaron en disco, referencia para el siguiente guardado.
+    written: Sections,
+    stored_keys: ProviderKeys,
+}
+
+/// Guarda la configuración fuera del hilo de la interfaz. Los cambios se agrupan durante
+/// `CONFIG_SAVE_DELAY` y solo se escribe el fichero si alguna sección cambió respecto a
+/// lo último guardado. Las API keys de los proveedores van al llavero del sistema en el
+/// mismo guardado.
+#[derive(Default)]
+pub struct ConfigSaver {
+    dirty_since: Option<Instant>,
+    /// Secciones guardadas por última vez; viaja al hilo de guardado mientras escribe.
+    written: Option<Sections>,
+    /// API keys guardadas por última vez en el llavero; viajan con `written`.
+    stored_keys: Option<ProviderKeys>,
+    save_rx: Option<Receiver<SaveOutcome>>,
+    /// Vigilante de `config.json` y ruta del fichero, para detectar ediciones externas.
+    watcher: Option<(FileWatcher, PathBuf)>,
+    /// El fichero cambió y falta compararlo, porque había un guardado en curso.
+    reload_pending: bool,
+    pub conflict: Option<ConfigConflict>,
+}
+
+impl ConfigSaver {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            written: sections_of(config).ok(),
+            stored_keys: Some(config.provider_keys()),
+            ..Self::default()
+        }
+    }
+
+    /// Fuerza a reescribir `config.json` y las API keys del llavero en el próximo guardado.
+    /// Se usa para retirar del fichero las API keys que quedaban en claro.
+    pub fn rewrite(&mut self) {
+        self.written = None;
+        self.stored_keys = None;
+        self.mark_dirty();
+    }
+
+    /// Anota que la configuración cambió; se guardará en el siguiente `poll` que toque.
+    pub fn mark_dirty(&mut self) {
+        self.dirty_since.get_or_insert_with(Instant::now);
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty_since.is_some()
+    }
+
+    /// Recoge el guardado terminado y lanza el siguiente si ya venció el retraso. Solo
+    /// hay un guardado en curso a la vez, de modo que las escrituras no se pisan.
+    pub fn poll(&mut self, config: &AppConfig) -> Option<ConfigSaveReport> {
+        if let Some(rx) = self.save_rx.as_ref() {
+            match rx.try_recv() {
+                Ok(outcome) => {
+                    self.save_rx = None;
+                    self.written = Some(outcome.written);
+                    self.stored_keys = Some(outcome.stored_keys);
+                    return Some(outcome.report);
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => self.save_rx = None,
+            }
+        }
+
+        if self.conflict.is_none()
+            && self
+                .dirty_since
+                .is_some_and(|since| since.elapsed() >= CONFIG_SAVE_DELAY)
+        {
+            self.spawn_save(config.clone());
+        }
+        None
+    }
+
+    /// Escribe los cambios pendientes en este hilo. Se usa al cerrar la aplicación, cuando
+    /// ya no hay frames que recojan un guardado en segundo plano. Un conflicto sin resolver
+    /// se cierra conservando la versión del fichero.
+    pub fn flush(&mut self, config: &AppConfig) -> Option<ConfigSaveReport> {
+        if let Some(rx) = self.save_rx.take() {
+            if let Ok(outcome) = rx.recv_timeout(SHUTDOWN_WAIT) {
+                self.written = Some(outcome.written);
+                self.stored_keys = Some(outcome.stored_keys);
+            }
+        }
+        let mut config = config.clone();
+        if self.conflict.is_some() {
+            let _ = self.resolve_conflict(&mut config, false);
+        }
+        self.dirty_since.take()?;
+        let outcome = save_changed(
+            &config,
+            self.written.take().unwrap_or_default(),
+            self.stored_keys.take().unwrap_or_default(),
+        );
+        self.written = Some(outcome.written);
+        self.stored_keys = Some(outcome.stored_keys);
+        Some(outcome.report)
+    }
+
+    /// Empieza a vigilar `config.json` para incorporar lo que se edite a mano.
+    pub fn watch(&mut self) -> anyhow::Result<()> {
+        let path = AppConfig::config_path()?;
+        let directory = path
+            .parent()
+            .context("La configuración no tiene directorio")?
+            .display()
+            .to_string();
+        let watcher = FileWatcher::start(
+            vec![WatchedPath {
+                path: directory,
+                pattern: "config.json".to_string(),
+            }],
+            EXTERNAL_EDIT_DEBOUNCE,
+        )?;
+        self.watcher = Some((watcher, path));
+        Ok(())
+    }
+
+    /// Incorpora las ediciones externas de `config.json` detectadas por el vigilante. Los
+    /// guardados propios también lo disparan, pero no aportan diferencias.
+    pub fn poll_external(
+        &mut self,
+        config: &mut AppConfig,
+    ) -> Option<Result<ExternalConfigChange, String>> {
+        let (watcher, path) = self.watcher.as_ref()?;
+        while let Some(event) = watcher.try_recv() {
+            if event.path == *path && event.kind != FileChangeKind::Removed {
+                self.reload_pending = true;
+            }
+        }
+        if !self.reload_pending || self.save_rx.is_some() {
+            return None;
+        }
+        self.reload_pending = false;
+        let disk = std::fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|data| {
+                serde_json::from_slice::<Sections>(&data)
+                    .map_err(|err| format!("config.json no es válido: {}", err))
+            });
+        Some(disk.and_then(|disk| self.merge_external(config, disk)))
+    }
+
+    /// Aplica las secciones que solo cambiaron en disco y deja pendientes las que también
+    /// cambiaron en la aplicación con otro valor.
+    fn merge_external(
+        &mut self,
+        config: &mut AppConfig,
+        disk: Sections,
+    ) -> Result<ExternalConfigChange, String> {
+        let Some(base) = self.written.as_mut() else {
+            self.reload_pending = true;
+            return Ok(ExternalConfigChange::default());
+        };
+        let current = sections_of(config)?;
+        let mut merged = current.clone();
+        let mut change = ExternalConfigChange::default();
+        for name in changed_sections(base, &disk) {
+            let value = &disk[&name];
+            match current.get(&name) {
+                Some(local) if local == value => {}
+                local if local == base.get(&name) => {
+                    merged.insert(name.clone(), value.clone());
+                    change.applied.push(name.clone());
+                }
+                _ => {
+                    change.conflicts.push(name);
+                    continue;
+                }
+            }
+            base.insert(name.clone(), value.clone());
+        }
+        if !change.applied.is_empty() {
+            let keys = config.provider_keys();
+            *config = serde_json::from_value(Value::Object(merged))
+                .map_err(|err| format!("config.json no es válido: {}", err))?;
+            config.keep_provider_keys(&keys);
+        }
+        if !change.conflicts.is_empty() {
+            self.conflict = Some(ConfigConflict {
+                sections: change.conflicts.clone(),
+                disk,
+            });
+        }
+        Ok(change)
+    }
+
+    /// Cierra el conflicto pendiente. Con `keep_local` se guardan los valores de la
+    /// aplicación; si no, se cargan los del fichero. Devuelve las secciones afectadas.
+    pub fn resolve_conflict(
+        &mut self,
+        config: &mut AppConfig,
+        keep_local: bool,
+    ) -> Result<Vec<String>, String> {
+        let Some(conflict) = self.conflict.take() else {
+            return Ok(Vec::new());
+        };
+        let base = self.written.get_or_insert_with(Sections::new);
+        if keep_local {
+            for name in &conflict.sections {
+                base.insert(name.clone(), conflict.disk[name].clone());
+            }
+            self.mark_dirty();
+            return Ok(conflict.sections);
+        }
+        let mut merged = sections_of(config)?;
+        for name in &conflict.sections {
+            merged.insert(name.clone(), conflict.disk[name].clone());
+        }
+        let keys = config.provider_keys();
+        *config = serde_json::from_value(Value::Object(merged))
+            .map_err(|err| format!("config.json no es válido: {}", err))?;
+        config.keep_provider_keys(&keys);
+        for name in &conflict.sections {
+            base.insert(name.clone(), conflict.disk[name].clone());
+        }
+        Ok(conflict.sections)
+    }
+
+    fn spawn_save(&mut self, config: AppConfig) {
+        let (tx, rx) = mpsc::channel();
+        self.save_rx = Some(rx);
+        self.dirty_since = None;
+        let written = self.written.take().unwrap_or_default();
+        let stored_keys = self.stored_keys.take().unwrap_or_default();
+        runtime::spawn_blocking(move || {
+            let _ = tx.send(save_changed(&config, written, stored_keys));
+        });
+    }
+}
+
+fn sections_of(config: &AppConfig) -> Result<Sections, String> {
+    match serde_json::to_value(config) {
+        Ok(Value::Object(sections)) => Ok(sections),
+        Ok(_) => Err("La configuración no se serializa como un objeto.".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+/// Secciones de `current` que no coinciden con `previous`, en orden alfabético.
+pub fn changed_sections(previous: &Sections, current: &Sections) -> Vec<String> {
+    current
+        .iter()
+        .filter(|(name, value)| previous.get(name.as_str()) != Some(value))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+fn save_changed(config: &AppConfig, written: Sections, stored_keys: ProviderKeys) -> SaveOutcome {
+    // Las API keys se guardan antes que el fichero, que solo apunta a ellas. Si el llavero
+    // falla se reintentan en el siguiente guardado; mientras, siguen en memoria.
+    let keys = config.provider_keys();
+    let (stored_keys, key_error) = if keys == stored_keys {
+        (stored_keys, None)
+    } else {
+        match config.store_provider_keys(&stored_keys) {
+            Ok(()) => (keys, None),
+            Err(err) => (stored_keys, Some(format!("{:#}", err))),
+        }
+    };
+
+    let current = match sections_of(config) {
+        Ok(current) => current,
+        Err(err) => {
+            return SaveOutcome {
+                report: ConfigSaveReport {
+                    sections: Vec::new(),
+                    error: Some(err),
+                },
+                written,
+                stored_keys,
+            }
+        }
+    };
+
+    let sections = changed_sections(&written, &current);
+    if sections.is_empty() {
+        return SaveOutcome {
+            report: ConfigSaveReport {
+                sections,
+                error: key_error,
+            },
+            written,
+            stored_keys,
+        };
+    }
+
+    match config.save() {
+        Ok(()) => SaveOutcome {
+            report: ConfigSaveReport {
+                sections,
+                error: key_error,
+            },
+            written: current,
+            stored_keys,
+        },
+        // Se conserva la referencia anterior para que el siguiente guardado lo reintente.
+        Err(err) => SaveOutcome {
+            report: ConfigSaveReport {
+                sections,
+                error: Some(format!("{:#}", err)),
+            },
+            written,
+            stored_keys,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_modified_sections_are_reported() {
+        let mut config = AppConfig::default();
+        let before = sections_of(&config).unwrap();
+        assert!(changed_sections(&before, &before).is_empty());
+
+        config.accessibility.ui_scale = 1.5;
+        config.selected_profile = Some(3);
+        let after = sections_of(&config).unwrap();
+        assert_eq!(
+            changed_sections(&before, &after),
+            vec!["accessibility".to_string(), "selected_profile".to_string()]
+        );
+    }
+
+    #[test]
+    fn external_edits_apply_unless_both_sides_changed() {
+        let mut config = AppConfig::default();
+        let mut saver = ConfigSaver::new(&config);
+        let mut disk = sections_of(&config).unwrap();
+        disk.insert("cache_size_limit_gb".to_string(), Value::from(2.0));
+        disk.insert("memory_retention_days".to_string(), Value::from(7));
+        config.memory_retention_days = 90;
+
+        let change = saver.merge_external(&mut config, disk).unwrap();
+        assert_eq!(change.applied, vec!["cache_size_limit_gb".to_string()]);
+        assert_eq!(change.conflicts, vec!["memory_retention_days".to_string()]);
+        assert_eq!(config.cache_size_limit_gb, 2.0);
+        assert_eq!(config.memory_retention_days, 90);
+
+        let resolved = saver.resolve_conflict(&mut config, false).unwrap();
+        assert_eq!(resolved, vec!["memory_retention_days".to_string()]);
+        assert_eq!(config.memory_retention_days, 7);
+    }
+
+    #[test]
+    fn changes_wait_for_the_save_delay() {
+        let config = AppConfig::default();
+        let mut saver = ConfigSaver::new(&config);
+        assert!(saver.poll(&config).is_none());
+
+        saver.mark_dirty();
+        assert!(saver.poll(&config).is_none());
+        assert!(saver.is_dirty());
+        assert!(saver.save_rx.is_none());
+    }
+}
@@ -0,0 +1,135 @@
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use super::{runtime, RemoteProviderKind};
+
+const PROBE_INTERVAL_ONLINE: Duration = Duration::from_secs(60);
+const PROBE_INTERVAL_OFFLINE: Duration = Duration::from_secs(10);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+const PROBE_HOSTS: [&str; 2] = ["api.openai.com:443", "api.anthropic.com:443"];
+
+/// Solicitud a un proveedor remoto retenida mientras no hay conexión.
+#[derive(Clone, Debug)]
+pub struct QueuedProviderCall {
+    pub provider_kind: RemoteProviderKind,
+    pub prompt: String,
+    /// Mensaje de aviso que se sustituirá por la respuesta al reenviarla.
+    pub message_index: usize,
+    pub queued_at: String,
+}
+
+/// Estado de la conectividad detectada con los proveedores remotos.
+pub struct ConnectivityState {
+    pub online: bool,
+    pub last_change: Option<String>,
+    last_probe: Option<Instant>,
+    probe_rx: Option<Receiver<bool>>,
+}
+
+impl Default for ConnectivityState {
+    fn default() -> Self {
+        Self {
+            online: true,
+            last_change: None,
+            last_probe: None,
+            probe_rx: None,
+        }
+    }
+}
+
+impl ConnectivityState {
+    /// Lanza una comprobación periódica en segundo plano y recoge su resultado.
+    /// Devuelve el nuevo estado cuando cambia respecto al anterior.
+    pub fn poll(&mut self, proxy_url: &str) -> Option<bool> {
+        if let Some(rx) = self.probe_rx.as_ref() {
+            match rx.try_recv() {
+                Ok(online) => {
+                    self.probe_rx = None;
+                    return self.set_online(online);
+                }
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => self.probe_rx = None,
+            }
+        }
+
+        let interval = if self.online {
+            PROBE_INTERVAL_ONLINE
+        } else {
+            PROBE_INTERVAL_OFFLINE
+        };
+        let due = self
+            .last_probe
+            .map(|last| last.elapsed() >= interval)
+            .unwrap_or(true);
+        if due {
+            self.spawn_probe(proxy_url);
+        }
+        None
+    }
+
+    /// Registra un fallo de red observado en una llamada real y adelanta la siguiente
+    /// comprobación.
+    pub fn report_network_failure(&mut self) -> Option<bool> {
+        self.last_probe = None;
+        self.set_online(false)
+    }
+
+    fn set_online(&mut self, online: bool) -> Option<bool> {
+        if self.online == online {
+            return None;
+        }
+        self.online = online;
+        self.last_change = Some(Local::now().format("%H:%M:%S").to_string());
+        Some(online)
+    }
+
+    fn spawn_probe(&mut self, proxy_url: &str) {
+        let targets = probe_targets(proxy_url);
+        let (tx, rx) = mpsc::channel();
+        self.probe_rx = Some(rx);
+        self.last_probe = Some(Instant::now());
+        runtime::spawn_blocking(move || {
+            let online = targets.iter().any(|target| probe(target));
+            let _ = tx.send(online);
+        });
+    }
+}
+
+/// Con un proxy configurado basta con alcanzarlo; sin él se prueban las APIs.
+fn probe_targets(proxy_url: &str) -> Vec<String> {
+    let proxy = proxy_url.trim();
+    if !proxy.is_empty() {
+        let without_scheme = proxy
+            .split_once("://")
+            .map(|(_, rest)| rest)
+            .unwrap_or(proxy);
+        let authority = without_scheme
+            .rsplit_once('@')
+            .map(|(_, host)| host)
+            .unwrap_or(without_scheme)
+            .trim_end_matches('/');
+        let target = if authority.contains(':') {
+            authority.to_string()
+        } else if proxy.starts_with("https") {
+            format!("{}:443", authority)
+        } else {
+            format!("{}:80", authority)
+        };
+        return vec![target];
+    }
+
+    PROBE_HOSTS.iter().map(|host| host.to_string()).collect()
+}
+
+fn probe(target: &str) -> bool {
+    let addresses: Vec<SocketAddr> = match target.to_socket_addrs() {
+        Ok(addresses) => addresses.collect(),
+        Err(_) => return false,
+    };
+    addresses
+        .iter()
+        .any(|address| TcpStream::connect_timeout(address, PROBE_TIMEOUT).is_ok())
+}
@@ -0,0 +1,479 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crate::config::{ContextConfig, ContextStrategy};
+use crate::tokenizer::TokenCounter;
+
+use super::search_index::fingerprint;
+use super::{ChatMessage, RemoteProviderKind};
+
+/// Mensajes antiguos que se resumen como mucho; los anteriores se omiten.
+const SUMMARY_MAX_ENTRIES: usize = 12;
+/// Longitud máxima de la respuesta del modelo al resumir un mensaje.
+pub const SUMMARY_MAX_TOKENS: u32 = 120;
+
+/// Historial seleccionado para acompañar a un prompt según la estrategia configurada.
+#[derive(Clone, Debug, Default)]
+pub struct ContextWindow {
+    /// Mensajes antiguos resumidos por el modelo, con la estrategia `SummarizeThenInclude`.
+    /// Los que aún no tienen resumen van completos.
+    pub summary: Option<String>,
+    /// Mensajes antiguos recuperados por similitud con el prompt.
+    pub recalled: Vec<String>,
+    /// Fragmentos de las fuentes de conocimiento del proyecto seleccionado y de los
+    /// documentos adjuntos, con su procedencia.
+    pub knowledge: Vec<String>,
+    pub head: Vec<String>,
+    pub tail: Vec<String>,
+    pub omitted: usize,
+}
+
+impl ContextWindow {
+    pub fn is_empty(&self) -> bool {
+        self.summary.is_none()
+            && self.recalled.is_empty()
+            && self.knowledge.is_empty()
+            && self.head.is_empty()
+            && self.tail.is_empty()
+    }
+
+    pub fn included_messages(&self) -> usize {
+        self.head.len() + self.tail.len()
+    }
+
+    pub fn history_tokens(&self, counter: &dyn TokenCounter) -> usize {
+        self.summary
+            .as_deref()
+            .map(|summary| counter.count(summary))
+            .unwrap_or(0)
+            + self
+                .recalled
+                .iter()
+                .chain(self.knowledge.iter())
+                .chain(self.head.iter())
+                .chain(self.tail.iter())
+                .map(|line| counter.count(line))
+                .sum::<usize>()
+    }
+
+    /// Construye el prompt final anteponiendo el historial seleccionado.
+    pub fn render(&self, prompt: &str) -> String {
+        if self.is_empty() {
+            return prompt.to_string();
+        }
+
+        let mut sections = Vec::new();
+        if let Some(summary) = self.summary.as_ref() {
+            sections.push(format!("Resumen de mensajes anteriores:\n{}", summary));
+        }
+        if !self.recalled.is_empty() {
+            sections.push(format!(
+                "Recuerdos relevantes:\n{}",
+                self.recalled.join("\n")
+            ));
+        }
+        if !self.knowledge.is_empty() {
+            sections.push(format!(
+                "Documentación de referencia (cita el documento y la página de los fragmentos que uses):\n{}",
+                self.knowledge.join("\n\n")
+            ));
+        }
+
+        let mut lines = self.head.clone();
+        if self.omitted > 0 && !self.head.is_empty() {
+            lines.push(format!("[… {} mensajes omitidos …]", self.omitted));
+        }
+        lines.extend(self.tail.iter().cloned());
+        if !lines.is_empty() {
+            sections.push(format!("Historial reciente:\n{}", lines.join("\n")));
+        }
+
+        format!(
+            "Contexto de la conversación:\n{}\n\nMensaje actual:\n{}",
+            sections.join("\n\n"),
+            prompt
+        )
+    }
+}
+
+/// Tokens de respuesta que se suponen al estimar el coste de un envío, limitados por
+/// la salida máxima del modelo.
+pub const ESTIMATED_REPLY_TOKENS: u32 = 500;
+
+/// Estimación de tokens para un proveedor y modelo concretos.
+#[derive(Clone, Debug)]
+pub struct ContextEstimate {
+    pub provider: RemoteProviderKind,
+    pub model: String,
+    pub tokens: usize,
+    pub context_limit: Option<u32>,
+    /// Tokens de respuesta incluidos en `estimated_cost`.
+    pub reply_tokens: u32,
+    /// Coste del prompt más una respuesta de `reply_tokens`, según el catálogo.
+    pub estimated_cost: Option<f32>,
+}
+
+impl ContextEstimate {
+    pub fn exceeds_context(&self) -> bool {
+        self.context_limit
+            .map(|limit| self.tokens > limit as usize)
+            .unwrap_or(false)
+    }
+}
+
+fn sender_label(message: &ChatMessage) -> &str {
+    if message.sender == "User" {
+        "Usuario"
+    } else {
+        message.sender.as_str()
+    }
+}
+
+pub(crate) fn history_line(message: &ChatMessage) -> String {
+    format!(
+        "{}: {}",
+        sender_label(message),
+        message.combined_text().trim()
+    )
+}
+
+/// Prompt con el que se pide al modelo el resumen de un mensaje del historial.
+pub fn summary_prompt(message: &ChatMessage) -> String {
+    format!(
+        "Resume en una o dos frases el siguiente mensaje de una conversación. Conserva nombres, cifras y decisiones, y responde solo con el resumen.\n\n{}",
+        history_line(message)
+    )
+}
+
+/// Resúmenes de los mensajes antiguos hechos por el modelo, por huella del mensaje, y
+/// peticiones en curso. Un mensaje que no se pudo o no se debe resumir no se vuelve a
+/// pedir y va completo.
+#[derive(Default)]
+pub struct ContextSummaries {
+    summaries: HashMap<u64, String>,
+    pending: HashMap<u64, Receiver<Result<String, String>>>,
+    skipped: HashSet<u64>,
+}
+
+impl ContextSummaries {
+    pub fn key(message: &ChatMessage) -> u64 {
+        fingerprint(history_line(message))
+    }
+
+    pub fn get(&self, message: &ChatMessage) -> Option<&str> {
+        self.summaries.get(&Self::key(message)).map(String::as_str)
+    }
+
+    pub fn needs_summary(&self, message: &ChatMessage) -> bool {
+        let key = Self::key(message);
+        !self.summaries.contains_key(&key)
+            && !self.pending.contains_key(&key)
+            && !self.skipped.contains(&key)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn start(&mut self, message: &ChatMessage, rx: Receiver<Result<String, String>>) {
+        self.pending.insert(Self::key(message), rx);
+    }
+
+    pub fn skip(&mut self, message: &ChatMessage) {
+        self.skipped.insert(Self::key(message));
+    }
+
+    pub fn insert(&mut self, message: &ChatMessage, summary: &str) {
+        self.summaries
+            .insert(Self::key(message), summary.trim().to_string());
+    }
+
+    /// Guarda los resúmenes que han llegado. Devuelve si alguno cambió y los errores.
+    pub fn poll(&mut self) -> (bool, Vec<String>) {
+        let mut finished = Vec::new();
+        self.pending.retain(|key, rx| match rx.try_recv() {
+            Ok(outcome) => {
+                finished.push((*key, outcome));
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => {
+                finished.push((*key, Err("la petición terminó sin respuesta".to_string())));
+                false
+            }
+        });
+        let mut errors = Vec::new();
+        for (key, outcome) in &finished {
+            match outcome {
+                Ok(summary) if !summary.trim().is_empty() => {
+                    self.summaries.insert(*key, summary.trim().to_string());
+                }
+                Ok(_) => {
+                    self.skipped.insert(*key);
+                }
+                Err(err) => {
+                    self.skipped.insert(*key);
+                    errors.push(err.clone());
+                }
+            }
+        }
+        (!finished.is_empty(), errors)
+    }
+}
+
+/// Mensajes visibles que cuentan como historial; si `exclude_trailing_user` es verdadero
+/// se omite el último mensaje del usuario, que es el propio prompt.
+fn eligible_messages(messages: &[ChatMessage], exclude_trailing_user: bool) -> Vec<&ChatMessage> {
+    let mut eligible: Vec<&ChatMessage> = messages
+        .iter()
+        .filter(|message| message.sender != "System" && !message.is_pending())
+        .collect();
+    if exclude_trailing_user
+        && eligible
+            .last()
+            .map(|message| message.sender == "User")
+            .unwrap_or(false)
+    {
+        eligible.pop();
+    }
+    eligible
+}
+
+/// Posición en `eligible` donde empiezan los mensajes recientes que van completos, y la
+/// del primer mensaje antiguo que se resume.
+fn summary_split(eligible: usize, config: &ContextConfig) -> (usize, usize) {
+    let split = eligible - config.summary_recent_messages.min(eligible);
+    (split, split.saturating_sub(SUMMARY_MAX_ENTRIES))
+}
+
+/// Mensajes antiguos de `messages` que la estrategia `SummarizeThenInclude` resumiría y
+/// aún no tienen resumen ni petición en curso.
+pub fn messages_to_summarize<'a>(
+    messages: &'a [ChatMessage],
+    config: &ContextConfig,
+    summaries: &ContextSummaries,
+) -> Vec<&'a ChatMessage> {
+    if config.strategy != ContextStrategy::SummarizeThenInclude {
+        return Vec::new();
+    }
+    let eligible = eligible_messages(messages, false);
+    let (split, start) = summary_split(eligible.len(), config);
+    eligible[start..split]
+        .iter()
+        .copied()
+        .filter(|message| summaries.needs_summary(message))
+        .collect()
+}
+
+/// Selecciona el historial relevante de `messages`. Los avisos del sistema y las
+/// respuestas pendientes se ignoran; si `exclude_trailing_user` es verdadero se omite
+/// el último mensaje del usuario porque corresponde al prompt que se está enviando.
+/// `summaries` aporta los resúmenes ya hechos para `SummarizeThenInclude`.
+pub fn build_context_window(
+    messages: &[ChatMessage],
+    config: &ContextConfig,
+    counter: &dyn TokenCounter,
+    exclude_trailing_user: bool,
+    summaries: &ContextSummaries,
+) -> ContextWindow {
+    let eligible = eligible_messages(messages, exclude_trailing_user);
+    if eligible.is_empty() {
+        return ContextWindow::default();
+    }
+
+    match config.strategy {
+        ContextStrategy::Disabled => ContextWindow::default(),
+        ContextStrategy::LastMessages => {
+            let keep = config.max_messages.min(eligible.len());
+            let start = eligible.len() - keep;
+            ContextWindow {
+                tail: eligible[start..].iter().map(|m| history_line(m)).collect(),
+                omitted: start,
+                ..ContextWindow::default()
+            }
+        }
+        ContextStrategy::TokenBudget => {
+            let lines: Vec<String> = eligible.iter().map(|m| history_line(m)).collect();
+            let costs: Vec<usize> = lines.iter().map(|line| counter.count(line)).collect();
+            let budget = config.token_budget;
+
+            if costs.iter().sum::<usize>() <= budget {
+                return ContextWindow {
+                    tail: lines,
+                    ..ContextWindow::default()
+                };
+            }
+
+            // Se prioriza el final de la conversación y se completa con el inicio,
+            // descartando la parte central.
+            let tail_budget = budget * 2 / 3;
+            let mut used = 0usize;
+            let mut tail_start = lines.len();
+            while tail_start > 0 && used + costs[tail_start - 1] <= tail_budget {
+                tail_start -= 1;
+                used += costs[tail_start];
+            }
+
+            let mut head_end = 0usize;
+            while head_end < tail_start && used + costs[head_end] <= budget {
+                used += costs[head_end];
+                head_end += 1;
+            }
+
+            ContextWindow {
+                head: lines[..head_end].to_vec(),
+                tail: lines[tail_start..].to_vec(),
+                omitted: tail_start - head_end,
+                ..ContextWindow::default()
+            }
+        }
+        ContextStrategy::SummarizeThenInclude => {
+            let (split, start) = summary_split(eligible.len(), config);
+            let summary = (start < split).then(|| {
+                eligible[start..split]
+                    .iter()
+                    .map(|message| match summaries.get(message) {
+                        Some(summary) => format!("- {}: {}", sender_label(message), summary),
+                        None => format!("- {}", history_line(message)),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+
+            ContextWindow {
+                summary,
+                tail: eligible[split..].iter().map(|m| history_line(m)).collect(),
+                omitted: start,
+                ..ContextWindow::default()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Un token por palabra, para que los presupuestos de las pruebas sean exactos.
+    struct WordCounter;
+
+    impl TokenCounter for WordCounter {
+        fn name(&self) -> &str {
+            "palabras"
+        }
+
+        fn count(&self, text: &str) -> usize {
+            text.split_whitespace().count()
+        }
+    }
+
+    fn conversation(count: usize) -> Vec<ChatMessage> {
+        (1..=count)
+            .map(|index| ChatMessage::user(format!("mensaje {}", index)))
+            .collect()
+    }
+
+    fn config(strategy: ContextStrategy) -> ContextConfig {
+        ContextConfig {
+            strategy,
+            ..ContextConfig::default()
+        }
+    }
+
+    #[test]
+    fn last_messages_keeps_the_most_recent_within_bounds() {
+        let messages = conversation(5);
+        let mut settings = config(ContextStrategy::LastMessages);
+        let summaries = ContextSummaries::default();
+
+        settings.max_messages = 2;
+        let window = build_context_window(&messages, &settings, &WordCounter, false, &summaries);
+        assert_eq!(
+            window.tail,
+            vec!["Usuario: mensaje 4", "Usuario: mensaje 5"]
+        );
+        assert_eq!(window.omitted, 3);
+
+        settings.max_messages = 5;
+        let window = build_context_window(&messages, &settings, &WordCounter, false, &summaries);
+        assert_eq!(window.included_messages(), 5);
+        assert_eq!(window.omitted, 0);
+
+        settings.max_messages = 50;
+        let window = build_context_window(&messages, &settings, &WordCounter, true, &summaries);
+        assert_eq!(window.included_messages(), 4);
+        assert_eq!(window.tail.last().unwrap(), "Usuario: mensaje 4");
+
+        settings.max_messages = 0;
+        let window = build_context_window(&messages, &settings, &WordCounter, false, &summaries);
+        assert!(window.is_empty());
+        assert_eq!(window.omitted, 5);
+    }
+
+    #[test]
+    fn token_budget_keeps_head_and_tail_and_drops_the_middle() {
+        // Cada línea ("Usuario: mensaje N") cuesta tres tokens.
+        let messages = conversation(10);
+        let mut settings = config(ContextStrategy::TokenBudget);
+        let summaries = ContextSummaries::default();
+
+        settings.token_budget = 30;
+        let window = build_context_window(&messages, &settings, &WordCounter, false, &summaries);
+        assert!(window.head.is_empty());
+        assert_eq!(window.tail.len(), 10);
+
+        // Dos tercios (6 tokens) para el final y el resto para el inicio.
+        settings.token_budget = 9;
+        let window = build_context_window(&messages, &settings, &WordCounter, false, &summaries);
+        assert_eq!(window.head, vec!["Usuario: mensaje 1"]);
+        assert_eq!(
+            window.tail,
+            vec!["Usuario: mensaje 9", "Usuario: mensaje 10"]
+        );
+        assert_eq!(window.omitted, 7);
+        assert!(window.history_tokens(&WordCounter) <= settings.token_budget);
+        assert!(window.render("hola").contains("[… 7 mensajes omitidos …]"));
+
+        settings.token_budget = 2;
+        let window = build_context_window(&messages, &settings, &WordCounter, false, &summaries);
+        assert_eq!(window.included_messages(), 0);
+        assert_eq!(window.omitted, 10);
+    }
+
+    #[test]
+    fn summarize_then_include_uses_cached_summaries() {
+        let mut messages = vec![
+            ChatMessage::user("Primera idea larga. Con detalles que el resumen condensa."),
+            ChatMessage::user("Otra idea antigua."),
+        ];
+        messages.extend(conversation(2));
+        let mut settings = config(ContextStrategy::SummarizeThenInclude);
+        settings.summary_recent_messages = 2;
+        let mut summaries = ContextSummaries::default();
+
+        let pending = messages_to_summarize(&messages, &settings, &summaries);
+        assert_eq!(pending.len(), 2);
+        assert!(summary_prompt(pending[0])
+            .ends_with("\n\nUsuario: Primera idea larga. Con detalles que el resumen condensa."));
+        summaries.insert(&messages[0], "Idea condensada.");
+        let pending = messages_to_summarize(&messages, &settings, &summaries);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].text, "Otra idea antigua.");
+
+        let window = build_context_window(&messages, &settings, &WordCounter, false, &summaries);
+        assert_eq!(
+            window.summary.as_deref(),
+            Some("- Usuario: Idea condensada.\n- Usuario: Otra idea antigua.")
+        );
+        assert_eq!(
+            window.tail,
+            vec!["Usuario: mensaje 1", "Usuario: mensaje 2"]
+        );
+        assert!(window
+            .render("hola")
+            .starts_with("Contexto de la conversación:\nResumen de mensajes anteriores:"));
+
+        settings.strategy = ContextStrategy::LastMessages;
+        assert!(messages_to_summarize(&messages, &settings, &summaries).is_empty());
+    }
+}
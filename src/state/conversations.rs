@@ -0,0 +1,301 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::api::GenerationOptions;
+use crate::config::AppConfig;
+
+use super::{language, vault, RemoteProviderKind};
+
+pub(super) const CONVERSATIONS_FILE: &str = "conversations.json";
+
+/// Temperatura máxima que aceptan los proveedores compatibles con OpenAI.
+pub const MAX_TEMPERATURE: f32 = 2.0;
+/// Límite razonable de tokens por respuesta para los ajustes de una conversación.
+pub const MAX_RESPONSE_TOKENS: u32 = 32_000;
+
+/// Enrutado por defecto de una conversación: a quién van los mensajes sin mención y con
+/// qué instrucciones y parámetros se generan las respuestas.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ConversationDefaults {
+    /// Código corto del proveedor remoto; `None` deja los mensajes a Jarvis.
+    pub provider: Option<String>,
+    /// Modelo del proveedor; `None` usa el modelo por defecto de sus preferencias.
+    pub model: Option<String>,
+    /// Instrucciones de sistema con las que responde el proveedor.
+    pub persona: String,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    /// Código del idioma de las respuestas; `None` responde en el idioma del mensaje.
+    pub reply_language: Option<String>,
+    /// Activa o desactiva los filtros de seguridad de Jarvis en esta conversación; `None`
+    /// sigue la preferencia global.
+    pub safety_filters: Option<bool>,
+    /// Muestra u oculta las sugerencias de seguimiento; `None` sigue la preferencia global.
+    pub follow_ups: Option<bool>,
+    pub updated_at: String,
+}
+
+impl ConversationDefaults {
+    pub fn provider_kind(&self) -> Option<RemoteProviderKind> {
+        self.provider
+            .as_deref()
+            .and_then(RemoteProviderKind::from_short_code)
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.provider.is_none()
+            && self.model.is_none()
+            && self.persona.trim().is_empty()
+            && self.temperature.is_none()
+            && self.max_tokens.is_none()
+            && self.reply_language.is_none()
+            && self.safety_filters.is_none()
+            && self.follow_ups.is_none()
+    }
+
+    /// Ajustes que se envían al proveedor junto a cada prompt de la conversación.
+    pub fn generation_options(&self) -> GenerationOptions {
+        let persona = self.persona.trim();
+        GenerationOptions {
+            system: (!persona.is_empty()).then(|| persona.to_string()),
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+        }
+    }
+
+    /// Idioma en que debe responderse a `prompt`: el fijado o, en automático, el detectado.
+    pub fn reply_language_for(&self, prompt: &str) -> Option<&str> {
+        match self.reply_language.as_deref() {
+            Some(code) => Some(code),
+            None => language::detect_language(prompt),
+        }
+    }
+
+    /// Como `generation_options`, con la instrucción del idioma de respuesta añadida a las
+    /// instrucciones de sistema.
+    pub fn generation_options_for(&self, prompt: &str) -> GenerationOptions {
+        let mut options = self.generation_options();
+        if let Some(instruction) = self
+            .reply_language_for(prompt)
+            .and_then(language::language_instruction)
+        {
+            options.system = Some(match options.system {
+                Some(persona) => format!("{}\n\n{}", persona, instruction),
+                None => instruction.to_string(),
+            });
+        }
+        options
+    }
+
+    /// Copia con los campos recortados y comprobados, lista para guardarse.
+    pub fn normalized(&self) -> Result<Self, String> {
+        let provider = match self.provider.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(code) => match RemoteProviderKind::from_short_code(code) {
+                Some(kind) => Some(kind.short_code().to_string()),
+                None => return Err(format!("Proveedor desconocido: '{}'.", code)),
+            },
+        };
+        let model = self
+            .model
+            .as_deref()
+            .map(str::trim)
+            .filter(|model| !model.is_empty() && provider.is_some())
+            .map(str::to_string);
+        if let Some(temperature) = self.temperature {
+            if !(0.0..=MAX_TEMPERATURE).contains(&temperature) {
+                return Err(format!(
+                    "La temperatura debe estar entre 0 y {}.",
+                    MAX_TEMPERATURE
+                ));
+            }
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            if max_tokens == 0 || max_tokens > MAX_RESPONSE_TOKENS {
+                return Err(format!(
+                    "El máximo de tokens debe estar entre 1 y {}.",
+                    MAX_RESPONSE_TOKENS
+                ));
+            }
+        }
+        let reply_language = match self.reply_language.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(code) if language::language_name(code).is_some() => Some(code.to_string()),
+            Some(code) => return Err(format!("Idioma de respuesta desconocido: '{}'.", code)),
+        };
+        Ok(Self {
+            provider,
+            model,
+            persona: self.persona.trim().to_string(),
+            temperature: self.temperature,
+            max_tokens: self.max_tokens,
+            reply_language,
+            safety_filters: self.safety_filters,
+            follow_ups: self.follow_ups,
+            updated_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        })
+    }
+}
+
+/// Ajustes por conversación guardados entre sesiones, indexados por la clave del hilo.
+#[derive(Default)]
+pub struct ConversationStore {
+    pub entries: BTreeMap<String, ConversationDefaults>,
+    /// Copia en edición desde el menú de ajustes de la cabecera del chat.
+    pub draft: ConversationDefaults,
+    pub status: Option<String>,
+}
+
+impl ConversationStore {
+    pub fn load() -> Self {
+        let entries = AppConfig::data_file(CONVERSATIONS_FILE)
+            .ok()
+            .and_then(|path| vault::read_to_string(&path).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            entries,
+            ..Self::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = AppConfig::data_file(CONVERSATIONS_FILE)?;
+        let json = serde_json::to_string_pretty(&self.entries)?;
+        vault::write(&path, &json)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&ConversationDefaults> {
+        self.entries.get(key)
+    }
+
+    /// Guarda los ajustes de `key`; los que coinciden con los valores por defecto se
+    /// eliminan para no acumular entradas vacías.
+    pub fn set(&mut self, key: &str, defaults: ConversationDefaults) {
+        if defaults.is_default() {
+            self.entries.remove(key);
+        } else {
+            self.entries.insert(key.to_string(), defaults);
+        }
+    }
+
+    /// Ajustes modificados más recientemente, con los que arranca una conversación nueva.
+    pub fn latest(&self) -> Option<&ConversationDefaults> {
+        self.entries
+            .values()
+            .max_by(|a, b| a.updated_at.cmp(&b.updated_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalized_validates_provider_and_parameters() {
+        let defaults = ConversationDefaults {
+            provider: Some(" groq ".to_string()),
+            model: Some(" llama3-70b ".to_string()),
+            persona: "  Revisor de código  ".to_string(),
+            temperature: Some(0.7),
+            max_tokens: Some(800),
+            ..ConversationDefaults::default()
+        };
+        let normalized = defaults.normalized().expect("ajustes válidos");
+        assert_eq!(normalized.provider.as_deref(), Some("groq"));
+        assert_eq!(normalized.model.as_deref(), Some("llama3-70b"));
+        assert_eq!(
+            normalized.generation_options().system.as_deref(),
+            Some("Revisor de código")
+        );
+
+        let unknown = ConversationDefaults {
+            provider: Some("mistral".to_string()),
+            ..ConversationDefaults::default()
+        };
+        assert!(unknown.normalized().is_err());
+
+        let hot = ConversationDefaults {
+            temperature: Some(3.5),
+            ..ConversationDefaults::default()
+        };
+        assert!(hot.normalized().is_err());
+    }
+
+    #[test]
+    fn model_without_provider_is_dropped() {
+        let defaults = ConversationDefaults {
+            model: Some("gpt-4o".to_string()),
+            ..ConversationDefaults::default()
+        };
+        let normalized = defaults.normalized().unwrap();
+        assert!(normalized.model.is_none());
+        assert!(normalized.is_default());
+    }
+
+    #[test]
+    fn latest_returns_most_recently_updated_entry() {
+        let mut store = ConversationStore::default();
+        let older = ConversationDefaults {
+            provider: Some("openai".to_string()),
+            updated_at: "2024-05-01 10:00:00".to_string(),
+            ..ConversationDefaults::default()
+        };
+        let newer = ConversationDefaults {
+            provider: Some("anthropic".to_string()),
+            updated_at: "2024-05-02 10:00:00".to_string(),
+            ..ConversationDefaults::default()
+        };
+        store.set("b", older);
+        store.set("a", newer);
+        assert_eq!(
+            store
+                .latest()
+                .and_then(|defaults| defaults.provider.as_deref()),
+            Some("anthropic")
+        );
+
+        store.set("a", ConversationDefaults::default());
+        assert!(store.get("a").is_none());
+    }
+
+    #[test]
+    fn reply_language_instruction_follows_setting_or_prompt() {
+        let auto = ConversationDefaults {
+            persona: "Revisor".to_string(),
+            ..ConversationDefaults::default()
+        };
+        assert_eq!(
+            auto.generation_options_for("What does this function do?")
+                .system
+                .as_deref(),
+            Some("Revisor\n\nAlways reply in English.")
+        );
+        assert_eq!(
+            auto.generation_options_for("ok").system.as_deref(),
+            Some("Revisor")
+        );
+
+        let fixed = ConversationDefaults {
+            reply_language: Some("es".to_string()),
+            ..ConversationDefaults::default()
+        };
+        assert_eq!(
+            fixed
+                .generation_options_for("What does this function do?")
+                .system
+                .as_deref(),
+            Some("Responde siempre en español.")
+        );
+        assert!(ConversationDefaults {
+            reply_language: Some("klingon".to_string()),
+            ..ConversationDefaults::default()
+        }
+        .normalized()
+        .is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, TimeZone, Timelike};
+
+/// Minutos que se exploran como máximo al buscar la próxima ejecución (un año).
+const MAX_LOOKAHEAD_MINUTES: i64 = 366 * 24 * 60;
+
+/// Expresión cron clásica de cinco campos: minuto, hora, día del mes, mes y día de la
+/// semana. Cada campo admite `*`, listas (`1,15`), rangos (`1-5`) y pasos (`*/4`, `0-30/10`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days_of_month: Vec<u32>,
+    months: Vec<u32>,
+    days_of_week: Vec<u32>,
+    /// Como en cron, si ambos días están restringidos basta con que encaje uno de ellos.
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "La expresión cron '{}' debe tener 5 campos y tiene {}.",
+                expression.trim(),
+                fields.len()
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], 0, 7, "día de la semana")?;
+        // 7 también representa el domingo.
+        for day in days_of_week.iter_mut() {
+            if *day == 7 {
+                *day = 0;
+            }
+        }
+        days_of_week.sort_unstable();
+        days_of_week.dedup();
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, "minuto")?,
+            hours: parse_field(fields[1], 0, 23, "hora")?,
+            days_of_month: parse_field(fields[2], 1, 31, "día del mes")?,
+            months: parse_field(fields[3], 1, 12, "mes")?,
+            days_of_week,
+            day_of_month_restricted: fields[2] != "*",
+            day_of_week_restricted: fields[4] != "*",
+        })
+    }
+
+    /// Indica si la expresión se cumple en el minuto de `time`.
+    pub fn matches<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> bool {
+        self.minutes.contains(&time.minute())
+            && self.hours.contains(&time.hour())
+            && self.occurs_on(time.date_naive())
+    }
+
+    /// Indica si la expresión se cumple en algún minuto de `date`.
+    pub fn occurs_on(&self, date: NaiveDate) -> bool {
+        if !self.months.contains(&date.month()) {
+            return false;
+        }
+        let day_of_month = self.days_of_month.contains(&date.day());
+        let day_of_week = self
+            .days_of_week
+            .contains(&date.weekday().num_days_from_sunday());
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            _ => day_of_month && day_of_week,
+        }
+    }
+
+    /// Ejecuciones en `[from, to)`, como mucho `limit`. Recorre días y no minutos para
+    /// que calcular un mes entero sea barato.
+    pub fn occurrences_between(
+        &self,
+        from: DateTime<Local>,
+        to: DateTime<Local>,
+        limit: usize,
+    ) -> Vec<DateTime<Local>> {
+        let mut found = Vec::new();
+        for date in from.date_naive().iter_days() {
+            if date > to.date_naive() || found.len() >= limit {
+                break;
+            }
+            if !self.occurs_on(date) {
+                continue;
+            }
+            for hour in &self.hours {
+                for minute in &self.minutes {
+                    let Some(time) = date
+                        .and_hms_opt(*hour, *minute, 0)
+                        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+                    else {
+                        continue;
+                    };
+                    if time >= from && time < to && found.len() < limit {
+                        found.push(time);
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Primer minuto estrictamente posterior a `after` en el que se cumple la expresión.
+    pub fn next_after(&self, after: DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        (0..MAX_LOOKAHEAD_MINUTES)
+            .map(|offset| start + Duration::minutes(offset))
+            .find(|candidate| self.matches(candidate))
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32, name: &str) -> Result<Vec<u32>, String> {
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("Paso inválido '{}' en el campo {}.", step, name))?;
+                if step == 0 {
+                    return Err(format!("El paso del campo {} no puede ser 0.", name));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (parse_value(start, name)?, parse_value(end, name)?)
+        } else {
+            let value = parse_value(range, name)?;
+            // `5/15` equivale a `5-max/15`.
+            if step > 1 {
+                (value, max)
+            } else {
+                (value, value)
+            }
+        };
+
+        if start < min || end > max || start > end {
+            return Err(format!(
+                "El valor '{}' del campo {} está fuera de {}-{}.",
+                range, name, min, max
+            ));
+        }
+        values.extend((start..=end).step_by(step as usize));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+fn parse_value(value: &str, name: &str) -> Result<u32, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Valor inválido '{}' en el campo {}.", value, name))
+}
@@ -0,0 +1,216 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, TimeZone};
+
+use super::{cron::CronSchedule, ReminderStatus, ScheduledReminder, ScheduledTask};
+
+/// Ejecuciones que se dibujan como mucho por tarea, para que un `* * * * *` no inunde
+/// la vista.
+const MAX_RUNS_PER_TASK: usize = 400;
+/// Ventana en la que se agrupan las ejecuciones para detectar choques.
+pub const CONFLICT_WINDOW_MINUTES: i64 = 30;
+/// Ejecuciones en la misma ventana a partir de las que se marca un choque.
+pub const CONFLICT_THRESHOLD: usize = 3;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CalendarSpan {
+    #[default]
+    Week,
+    Month,
+}
+
+impl CalendarSpan {
+    pub fn label(self) -> &'static str {
+        match self {
+            CalendarSpan::Week => "Semana",
+            CalendarSpan::Month => "Mes",
+        }
+    }
+
+    /// Primer y último día (incluido) del periodo que contiene `today` desplazado
+    /// `offset` semanas o meses. Las semanas empiezan en lunes.
+    pub fn range(self, today: NaiveDate, offset: i32) -> (NaiveDate, NaiveDate) {
+        match self {
+            CalendarSpan::Week => {
+                let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64)
+                    + Duration::weeks(offset as i64);
+                (monday, monday + Duration::days(6))
+            }
+            CalendarSpan::Month => {
+                let first = today.with_day(1).unwrap_or(today);
+                let months = Months::new(offset.unsigned_abs());
+                let first = if offset >= 0 {
+                    first.checked_add_months(months)
+                } else {
+                    first.checked_sub_months(months)
+                }
+                .unwrap_or(first);
+                let last = first
+                    .checked_add_months(Months::new(1))
+                    .map(|next| next - Duration::days(1))
+                    .unwrap_or(first);
+                (first, last)
+            }
+        }
+    }
+}
+
+/// Qué se ejecuta en una entrada del calendario.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CalendarSource {
+    Task(u32),
+    Reminder(u32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalendarEntry {
+    pub start: DateTime<Local>,
+    pub title: String,
+    pub source: CalendarSource,
+    /// `true` si coincide con otras muchas ejecuciones en la misma ventana.
+    pub conflict: bool,
+}
+
+/// Ejecuciones previstas entre `first` y `last` (incluidos) de las tareas activas y de
+/// los recordatorios pendientes, ordenadas por hora. Los recordatorios no tienen
+/// expresión cron y aparecen solo en su próximo aviso.
+pub fn build_calendar(
+    tasks: &[ScheduledTask],
+    reminders: &[ScheduledReminder],
+    first: NaiveDate,
+    last: NaiveDate,
+) -> Vec<CalendarEntry> {
+    let Some(from) = local_midnight(first) else {
+        return Vec::new();
+    };
+    let Some(to) = local_midnight(last + Duration::days(1)) else {
+        return Vec::new();
+    };
+
+    let mut entries = Vec::new();
+    for task in tasks.iter().filter(|task| task.enabled) {
+        let Ok(schedule) = CronSchedule::parse(&task.cron_expression) else {
+            continue;
+        };
+        entries.extend(
+            schedule
+                .occurrences_between(from, to, MAX_RUNS_PER_TASK)
+                .into_iter()
+                .map(|start| CalendarEntry {
+                    start,
+                    title: task.name.clone(),
+                    source: CalendarSource::Task(task.id),
+                    conflict: false,
+                }),
+        );
+    }
+    for reminder in reminders
+        .iter()
+        .filter(|reminder| reminder.status != ReminderStatus::Sent)
+    {
+        let Some(start) = NaiveDateTime::parse_from_str(&reminder.next_trigger, "%Y-%m-%d %H:%M")
+            .ok()
+            .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        else {
+            continue;
+        };
+        if start >= from && start < to {
+            entries.push(CalendarEntry {
+                start,
+                title: reminder.title.clone(),
+                source: CalendarSource::Reminder(reminder.id),
+                conflict: false,
+            });
+        }
+    }
+    entries.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.title.cmp(&b.title)));
+    mark_conflicts(&mut entries);
+    entries
+}
+
+/// Marca las entradas que caen en una ventana con `CONFLICT_THRESHOLD` o más ejecuciones.
+fn mark_conflicts(entries: &mut [CalendarEntry]) {
+    let mut windows: BTreeMap<i64, usize> = BTreeMap::new();
+    let window_of =
+        |entry: &CalendarEntry| entry.start.timestamp() / (CONFLICT_WINDOW_MINUTES * 60);
+    for entry in entries.iter() {
+        *windows.entry(window_of(entry)).or_default() += 1;
+    }
+    for entry in entries.iter_mut() {
+        entry.conflict = windows[&window_of(entry)] >= CONFLICT_THRESHOLD;
+    }
+}
+
+fn local_midnight(date: NaiveDate) -> Option<DateTime<Local>> {
+    date.and_hms_opt(0, 0, 0)
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{ScheduledTaskKind, ScheduledTaskStatus};
+
+    fn task(id: u32, cron_expression: &str) -> ScheduledTask {
+        ScheduledTask {
+            id,
+            name: format!("Tarea {}", id),
+            description: String::new(),
+            cron_expression: cron_expression.to_string(),
+            cadence_label: cron_expression.to_string(),
+            last_run: None,
+            next_run: None,
+            status: ScheduledTaskStatus::Scheduled,
+            owner: String::new(),
+            provider: None,
+            tags: Vec::new(),
+            enabled: true,
+            kind: ScheduledTaskKind::Generic,
+        }
+    }
+
+    #[test]
+    fn plots_runs_in_the_week_and_flags_collisions() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let (first, last) = CalendarSpan::Week.range(today, 0);
+        assert_eq!(first, NaiveDate::from_ymd_opt(2024, 5, 13).unwrap());
+        assert_eq!(last, NaiveDate::from_ymd_opt(2024, 5, 19).unwrap());
+        assert_eq!(
+            CalendarSpan::Month.range(today, -5),
+            (
+                NaiveDate::from_ymd_opt(2023, 12, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2023, 12, 31).unwrap()
+            )
+        );
+
+        let mut paused = task(4, "0 9 * * *");
+        paused.enabled = false;
+        let tasks = vec![
+            task(1, "0 9 * * 1-5"),
+            task(2, "10 9 * * 1"),
+            task(3, "20 9 * * 1"),
+            paused,
+        ];
+        let reminders = vec![ScheduledReminder {
+            id: 7,
+            title: "Standup".to_string(),
+            cadence: "Diario".to_string(),
+            next_trigger: "2024-05-16 09:00".to_string(),
+            audience: String::new(),
+            delivery_channel: String::new(),
+            status: ReminderStatus::Scheduled,
+        }];
+
+        let entries = build_calendar(&tasks, &reminders, first, last);
+        assert_eq!(entries.len(), 5 + 2 + 1);
+        let monday: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.start.date_naive() == first)
+            .collect();
+        assert_eq!(monday.len(), 3);
+        assert!(monday.iter().all(|entry| entry.conflict));
+        assert!(entries
+            .iter()
+            .any(|entry| entry.source == CalendarSource::Reminder(7) && !entry.conflict));
+    }
+}
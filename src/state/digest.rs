@@ -0,0 +1,314 @@
+use std::sync::mpsc::Receiver;
+
+use anyhow::{Context, Result};
+use chrono::{Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+use crate::api::github::GitHubNotification;
+
+/// Clave del hilo donde se publican los resúmenes.
+pub const DIGEST_THREAD_KEY: &str = "resumen-diario";
+pub const DIGEST_THREAD_TITLE: &str = "Resumen diario";
+/// Entradas de actividad más recientes que se incluyen en el resumen.
+const MAX_ACTIVITY_ENTRIES: usize = 40;
+
+/// Evento del calendario en la fecha del resumen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CalendarEvent {
+    /// Hora local de inicio; `None` en los eventos de día completo.
+    pub start: Option<NaiveTime>,
+    pub summary: String,
+    pub location: Option<String>,
+}
+
+/// Material de la jornada con el que el proveedor redacta el resumen.
+#[derive(Clone, Debug)]
+pub struct DigestSources {
+    pub date: NaiveDate,
+    pub activity: Vec<String>,
+    pub workflows: Vec<String>,
+    pub github: Vec<GitHubNotification>,
+    pub calendar: Vec<CalendarEvent>,
+    /// Fuentes que no se pudieron leer, para que el resumen lo mencione.
+    pub errors: Vec<String>,
+}
+
+impl DigestSources {
+    pub fn new(date: NaiveDate) -> Self {
+        Self {
+            date,
+            activity: Vec::new(),
+            workflows: Vec::new(),
+            github: Vec::new(),
+            calendar: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Conserva solo las entradas de actividad más recientes.
+    pub fn set_activity(&mut self, entries: Vec<String>) {
+        let skip = entries.len().saturating_sub(MAX_ACTIVITY_ENTRIES);
+        self.activity = entries.into_iter().skip(skip).collect();
+    }
+}
+
+/// Fase en curso de la generación del resumen.
+pub enum DigestStage {
+    /// Descargando las notificaciones de GitHub en segundo plano.
+    Gathering {
+        sources: DigestSources,
+        rx: Receiver<Result<Vec<GitHubNotification>, String>>,
+    },
+    /// Esperando a que el proveedor remoto redacte el resumen.
+    Composing {
+        provider_name: String,
+        model: String,
+        rx: Receiver<Result<String, String>>,
+    },
+}
+
+#[derive(Default)]
+pub struct DigestState {
+    pub stage: Option<DigestStage>,
+    pub status: Option<String>,
+    /// Último resumen generado en la sesión.
+    pub last_digest: Option<String>,
+}
+
+impl DigestState {
+    pub fn is_running(&self) -> bool {
+        self.stage.is_some()
+    }
+}
+
+/// Expresión cron diaria para una hora `HH:MM`.
+pub fn cron_expression(time: &str) -> Result<String, String> {
+    let parsed = NaiveTime::parse_from_str(time.trim(), "%H:%M")
+        .map_err(|_| format!("'{}' no es una hora válida (HH:MM).", time.trim()))?;
+    Ok(format!(
+        "{} {} * * *",
+        parsed.format("%-M"),
+        parsed.format("%-H")
+    ))
+}
+
+pub fn read_calendar(path: &str, date: NaiveDate) -> Result<Vec<CalendarEvent>> {
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("No se pudo leer {}", path))?;
+    Ok(parse_ics_events(&content, date))
+}
+
+/// Día de inicio de un evento y su hora, salvo en los que duran todo el día.
+type IcsStart = (NaiveDate, Option<NaiveTime>);
+
+/// Eventos `VEVENT` de un calendario iCalendar que empiezan en `date`, ordenados por hora.
+pub fn parse_ics_events(content: &str, date: NaiveDate) -> Vec<CalendarEvent> {
+    // Las líneas largas se pliegan con un espacio o tabulador al inicio de la siguiente.
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        match (line.strip_prefix(&[' ', '\t'][..]), lines.last_mut()) {
+            (Some(continuation), Some(previous)) => previous.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<(Option<IcsStart>, String, Option<String>)> = None;
+    for line in &lines {
+        if line == "BEGIN:VEVENT" {
+            current = Some((None, String::new(), None));
+            continue;
+        }
+        if line == "END:VEVENT" {
+            if let Some((Some((day, start)), summary, location)) = current.take() {
+                if day == date {
+                    events.push(CalendarEvent {
+                        start,
+                        summary,
+                        location,
+                    });
+                }
+            }
+            continue;
+        }
+        let Some(event) = current.as_mut() else {
+            continue;
+        };
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        let name = property.split(';').next().unwrap_or_default();
+        match name {
+            "DTSTART" => event.0 = parse_ics_start(value),
+            "SUMMARY" => event.1 = unescape_ics(value),
+            "LOCATION" if !value.trim().is_empty() => event.2 = Some(unescape_ics(value)),
+            _ => {}
+        }
+    }
+    events.sort_by_key(|event| event.start);
+    events
+}
+
+fn parse_ics_start(value: &str) -> Option<IcsStart> {
+    let value = value.trim();
+    if value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .ok()
+            .map(|day| (day, None));
+    }
+    let (value, utc) = match value.strip_suffix('Z') {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let parsed = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    let local = if utc {
+        Utc.from_utc_datetime(&parsed)
+            .with_timezone(&Local)
+            .naive_local()
+    } else {
+        parsed
+    };
+    Some((local.date(), Some(local.time())))
+}
+
+fn unescape_ics(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+/// Prompt con el material del día y las instrucciones del formato del resumen.
+pub fn build_prompt(sources: &DigestSources) -> String {
+    let mut sections = vec![format!(
+        "Redacta el resumen diario del {} para el equipo. Empieza por lo más importante, agrupa por temas y termina con una lista breve de pendientes. No inventes nada que no aparezca abajo.",
+        sources.date.format("%d/%m/%Y")
+    )];
+
+    let mut push_section = |title: &str, lines: Vec<String>| {
+        if !lines.is_empty() {
+            sections.push(format!("## {}\n{}", title, lines.join("\n")));
+        }
+    };
+    push_section(
+        "Agenda",
+        sources
+            .calendar
+            .iter()
+            .map(|event| {
+                let start = event
+                    .start
+                    .map(|start| start.format("%H:%M").to_string())
+                    .unwrap_or_else(|| "Todo el día".to_string());
+                match &event.location {
+                    Some(location) => format!("- {} · {} ({})", start, event.summary, location),
+                    None => format!("- {} · {}", start, event.summary),
+                }
+            })
+            .collect(),
+    );
+    push_section(
+        "Notificaciones de GitHub",
+        sources
+            .github
+            .iter()
+            .map(|notification| {
+                format!(
+                    "- [{}] {}: {} ({})",
+                    notification.repository,
+                    notification.kind,
+                    notification.title,
+                    notification.reason
+                )
+            })
+            .collect(),
+    );
+    push_section(
+        "Workflows ejecutados",
+        sources
+            .workflows
+            .iter()
+            .map(|line| format!("- {}", line))
+            .collect(),
+    );
+    push_section(
+        "Actividad",
+        sources
+            .activity
+            .iter()
+            .map(|line| format!("- {}", line))
+            .collect(),
+    );
+    push_section(
+        "Fuentes no disponibles",
+        sources
+            .errors
+            .iter()
+            .map(|line| format!("- {}", line))
+            .collect(),
+    );
+
+    if sections.len() == 1 {
+        sections.push("No hay actividad registrada hoy.".to_string());
+    }
+    sections.join("\n\n")
+}
+
+/// Muestra el resumen como notificación del sistema.
+pub fn notify_desktop(summary: &str, body: &str) -> Result<()> {
+    let body: String = body.chars().take(240).collect();
+    notify_rust::Notification::new()
+        .appname("JungleMonkAI")
+        .summary(summary)
+        .body(&body)
+        .show()
+        .context("No se pudo mostrar la notificación de escritorio")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_todays_events_from_ics() {
+        let ics = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART:20240515T100000\r\nSUMMARY:Revisión\r\n  de sprint\r\nLOCATION:Sala 2\\, planta 1\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20240515\r\nSUMMARY:Día de despliegue\r\nEND:VEVENT\r\nBEGIN:VEVENT\r\nDTSTART:20240516T090000\r\nSUMMARY:Mañana\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+        let date = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let events = parse_ics_events(ics, date);
+        assert_eq!(
+            events,
+            vec![
+                CalendarEvent {
+                    start: None,
+                    summary: "Día de despliegue".to_string(),
+                    location: None,
+                },
+                CalendarEvent {
+                    start: NaiveTime::from_hms_opt(10, 0, 0),
+                    summary: "Revisión de sprint".to_string(),
+                    location: Some("Sala 2, planta 1".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_cron_and_prompt_sections() {
+        assert_eq!(cron_expression("08:05").unwrap(), "5 8 * * *");
+        assert!(cron_expression("25:00").is_err());
+
+        let mut sources = DigestSources::new(NaiveDate::from_ymd_opt(2024, 5, 15).unwrap());
+        let prompt = build_prompt(&sources);
+        assert!(prompt.ends_with("No hay actividad registrada hoy."));
+
+        sources.set_activity((0..50).map(|index| format!("evento {}", index)).collect());
+        sources.errors.push("GitHub: sin token".to_string());
+        let prompt = build_prompt(&sources);
+        assert!(prompt.contains("15/05/2024"));
+        assert!(prompt.contains("## Actividad\n- evento 10\n"));
+        assert!(!prompt.contains("evento 9\n"));
+        assert!(prompt.contains("## Fuentes no disponibles\n- GitHub: sin token"));
+    }
+}
@@ -0,0 +1,1340 @@
+//! Extracción de texto de documentos PDF y DOCX, troceado por páginas y selección de los
+//! fragmentos que acompañan a un prompt.
+//!
+//! Los lectores son deliberadamente sencillos: del PDF se leen los flujos de contenido de
+//! cada página (sin compresión o con `FlateDecode`) y las tablas `ToUnicode` de sus
+//! fuentes; del DOCX, el texto de `word/document.xml` y sus saltos de página. Los PDF
+//! escaneados no tienen texto que extraer.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use flate2::read::{DeflateDecoder, ZlibDecoder};
+use once_cell::sync::Lazy;
+use regex::bytes::Regex as BytesRegex;
+use regex::Regex;
+
+/// Extensiones de los documentos con texto extraíble.
+pub const DOCUMENT_EXTENSIONS: &[&str] = &["pdf", "docx"];
+/// Documentos mayores que este tamaño no se procesan.
+pub const MAX_DOCUMENT_BYTES: u64 = 20 * 1024 * 1024;
+/// Fragmentos de los documentos adjuntos que acompañan a cada prompt.
+pub const ATTACHMENT_CONTEXT_CHUNKS: usize = 4;
+/// Desplazamiento de un `TJ`, en milésimas de unidad de texto, a partir del cual se
+/// interpreta como un espacio entre palabras.
+const TJ_SPACE_THRESHOLD: f64 = 200.0;
+/// Profundidad máxima del árbol de páginas, para no seguir ciclos.
+const MAX_PAGE_TREE_DEPTH: usize = 32;
+
+static OBJECT_HEADER: Lazy<BytesRegex> =
+    Lazy::new(|| BytesRegex::new(r"(?-u)(\d+)\s+\d+\s+obj").expect("patrón válido"));
+static PAGE_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(?:p[áa]g(?:ina)?s?\.?|pp?\.)\s*(\d+)").expect("patrón válido")
+});
+
+pub fn is_document(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            DOCUMENT_EXTENSIONS
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(extension))
+        })
+}
+
+/// Texto de una página, numerada desde 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentPage {
+    pub number: usize,
+    pub text: String,
+}
+
+/// Páginas que abarca un fragmento.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageRange {
+    pub first: usize,
+    pub last: usize,
+}
+
+impl PageRange {
+    pub fn contains(self, page: usize) -> bool {
+        (self.first..=self.last).contains(&page)
+    }
+
+    /// `p. 3` o `pp. 3–4`, para citar la procedencia de un fragmento.
+    pub fn citation(self) -> String {
+        if self.first == self.last {
+            format!("p. {}", self.first)
+        } else {
+            format!("pp. {}–{}", self.first, self.last)
+        }
+    }
+}
+
+/// Fragmento de un documento. Los de texto plano no tienen páginas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DocumentChunk {
+    pub text: String,
+    pub pages: Option<PageRange>,
+}
+
+impl DocumentChunk {
+    pub fn plain(text: String) -> Self {
+        Self { text, pages: None }
+    }
+
+    pub fn citation(&self) -> Option<String> {
+        self.pages.map(PageRange::citation)
+    }
+}
+
+/// Lee un PDF o DOCX y devuelve el texto de cada página que tenga alguno.
+pub fn extract_pages(path: &Path) -> Result<Vec<DocumentPage>> {
+    let size = fs::metadata(path)
+        .with_context(|| format!("No se pudo leer {}", path.display()))?
+        .len();
+    if size > MAX_DOCUMENT_BYTES {
+        bail!(
+            "{} supera el límite de {} MB",
+            path.display(),
+            MAX_DOCUMENT_BYTES / (1024 * 1024)
+        );
+    }
+    let bytes = fs::read(path).with_context(|| format!("No se pudo leer {}", path.display()))?;
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let pages = match extension.as_str() {
+        "pdf" => extract_pdf(&bytes),
+        "docx" => extract_docx(&bytes),
+        _ => Err(anyhow!("Formato no soportado")),
+    }
+    .with_context(|| format!("No se pudo extraer el texto de {}", path.display()))?;
+    if pages.is_empty() {
+        bail!(
+            "{} no contiene texto extraíble; si es un escaneo, pásalo antes por OCR",
+            path.display()
+        );
+    }
+    Ok(pages)
+}
+
+/// Extrae y trocea un documento en un solo paso.
+pub fn extract_chunks(path: &Path, max_chars: usize) -> Result<(usize, Vec<DocumentChunk>)> {
+    let pages = extract_pages(path)?;
+    let page_count = pages.last().map_or(0, |page| page.number);
+    Ok((page_count, chunk_pages(&pages, max_chars)))
+}
+
+/// Trocea las páginas en fragmentos de hasta `max_chars` caracteres sin partir párrafos
+/// (salvo los que por sí solos superan el límite, que se parten por líneas). Cada
+/// fragmento recuerda las páginas de las que procede.
+pub fn chunk_pages(pages: &[DocumentPage], max_chars: usize) -> Vec<DocumentChunk> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut range: Option<PageRange> = None;
+    for page in pages {
+        for piece in paragraph_pieces(&page.text, max_chars) {
+            if !current.is_empty() && current.chars().count() + piece.chars().count() > max_chars {
+                chunks.push(DocumentChunk {
+                    text: std::mem::take(&mut current),
+                    pages: range.take(),
+                });
+            }
+            if !current.is_empty() {
+                current.push_str("\n\n");
+            }
+            current.push_str(&piece);
+            range = Some(PageRange {
+                first: range.map_or(page.number, |range| range.first),
+                last: page.number,
+            });
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(DocumentChunk {
+            text: current,
+            pages: range,
+        });
+    }
+    chunks
+}
+
+fn paragraph_pieces(text: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if paragraph.chars().count() <= max_chars {
+            pieces.push(paragraph.to_string());
+            continue;
+        }
+        let mut current = String::new();
+        for line in paragraph.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if !current.is_empty() && current.chars().count() + line.chars().count() >= max_chars {
+                pieces.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push('\n');
+            }
+            current.push_str(line);
+        }
+        if !current.is_empty() {
+            pieces.push(current);
+        }
+    }
+    pieces
+}
+
+/// Palabras de cuatro o más letras, en minúsculas.
+pub(crate) fn terms(text: &str) -> HashSet<String> {
+    text.split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 4)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Los `limit` fragmentos que más palabras comparten con `query`. Si la consulta cita
+/// una página, los fragmentos de esa página van primero. Si ninguno coincide se
+/// devuelven los primeros, para que preguntas genéricas («resume el documento») también
+/// reciban contexto.
+pub fn rank_chunks<'a, T>(
+    query: &str,
+    candidates: impl IntoIterator<Item = (T, &'a DocumentChunk)>,
+    limit: usize,
+) -> Vec<(T, &'a DocumentChunk)> {
+    let query_terms = terms(query);
+    let pages: Vec<usize> = PAGE_REFERENCE
+        .captures_iter(query)
+        .filter_map(|captures| captures[1].parse().ok())
+        .collect();
+    let mut scored: Vec<(usize, (T, &'a DocumentChunk))> = candidates
+        .into_iter()
+        .map(|(key, chunk)| {
+            let mut score = terms(&chunk.text).intersection(&query_terms).count();
+            if chunk
+                .pages
+                .is_some_and(|range| pages.iter().any(|page| range.contains(*page)))
+            {
+                score += 100;
+            }
+            (score, (key, chunk))
+        })
+        .collect();
+    if scored.iter().any(|(score, _)| *score > 0) {
+        scored.retain(|(score, _)| *score > 0);
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    }
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------------------
+// PDF
+// ---------------------------------------------------------------------------------------
+
+type PdfDict = HashMap<String, PdfValue>;
+
+static NULL: PdfValue = PdfValue::Null;
+
+#[derive(Clone, Debug, PartialEq)]
+enum PdfValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Name(String),
+    Str(Vec<u8>),
+    Array(Vec<PdfValue>),
+    Dict(PdfDict),
+    Ref(u32),
+    /// Operador de un flujo de contenido o delimitador suelto.
+    Keyword(String),
+}
+
+impl PdfValue {
+    fn as_dict(&self) -> Option<&PdfDict> {
+        match self {
+            PdfValue::Dict(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    fn as_name(&self) -> Option<&str> {
+        match self {
+            PdfValue::Name(name) => Some(name),
+            _ => None,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            PdfValue::Number(number) => Some(*number),
+            _ => None,
+        }
+    }
+}
+
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\0' | b'\t' | b'\n' | b'\x0c' | b'\r' | b' ')
+}
+
+fn is_delimiter(byte: u8) -> bool {
+    matches!(
+        byte,
+        b'(' | b')' | b'<' | b'>' | b'[' | b']' | b'{' | b'}' | b'/' | b'%'
+    )
+}
+
+/// Lector de la sintaxis de objetos de PDF, que comparten los diccionarios, los flujos de
+/// contenido y las tablas CMap.
+struct PdfParser<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PdfParser<'a> {
+    fn new(data: &'a [u8], pos: usize) -> Self {
+        Self { data, pos }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(byte) = self.peek() {
+            if is_whitespace(byte) {
+                self.pos += 1;
+            } else if byte == b'%' {
+                while self
+                    .peek()
+                    .is_some_and(|byte| byte != b'\n' && byte != b'\r')
+                {
+                    self.pos += 1;
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn read_regular(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|byte| !is_whitespace(byte) && !is_delimiter(byte))
+        {
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn next_value(&mut self) -> Option<PdfValue> {
+        self.skip_whitespace();
+        let byte = self.peek()?;
+        match byte {
+            b'/' => {
+                self.pos += 1;
+                Some(PdfValue::Name(decode_name(self.read_regular())))
+            }
+            b'(' => Some(PdfValue::Str(self.read_literal_string())),
+            b'<' if self.data.get(self.pos + 1) == Some(&b'<') => {
+                self.pos += 2;
+                Some(PdfValue::Dict(self.read_dict()))
+            }
+            b'<' => Some(PdfValue::Str(self.read_hex_string())),
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip_whitespace();
+                    match self.peek() {
+                        None => break,
+                        Some(b']') => {
+                            self.pos += 1;
+                            break;
+                        }
+                        Some(_) => items.extend(self.next_value()),
+                    }
+                }
+                Some(PdfValue::Array(items))
+            }
+            b')' | b'>' | b']' | b'{' | b'}' => {
+                self.pos += 1;
+                Some(PdfValue::Keyword((byte as char).to_string()))
+            }
+            b'0'..=b'9' | b'+' | b'-' | b'.' => Some(self.read_number_or_ref()),
+            _ => {
+                let word = self.read_regular();
+                if word.is_empty() {
+                    self.pos += 1;
+                    return Some(PdfValue::Null);
+                }
+                Some(match word {
+                    b"true" => PdfValue::Bool(true),
+                    b"false" => PdfValue::Bool(false),
+                    b"null" => PdfValue::Null,
+                    _ => PdfValue::Keyword(String::from_utf8_lossy(word).into_owned()),
+                })
+            }
+        }
+    }
+
+    fn read_dict(&mut self) -> PdfDict {
+        let mut dict = PdfDict::new();
+        loop {
+            self.skip_whitespace();
+            if self.data[self.pos..].starts_with(b">>") {
+                self.pos += 2;
+                break;
+            }
+            match self.next_value() {
+                None => break,
+                Some(PdfValue::Name(key)) => {
+                    if let Some(value) = self.next_value() {
+                        dict.insert(key, value);
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+        dict
+    }
+
+    fn read_number_or_ref(&mut self) -> PdfValue {
+        let token = self.read_regular();
+        let number = std::str::from_utf8(token)
+            .ok()
+            .and_then(|text| text.parse::<f64>().ok())
+            .unwrap_or(0.0);
+        let is_object_number = token.iter().all(u8::is_ascii_digit);
+        if is_object_number {
+            // `12 0 R` es una referencia al objeto 12.
+            let saved = self.pos;
+            self.skip_whitespace();
+            let generation = self.read_regular();
+            self.skip_whitespace();
+            let is_generation = !generation.is_empty() && generation.iter().all(u8::is_ascii_digit);
+            if is_generation
+                && self.peek() == Some(b'R')
+                && self
+                    .data
+                    .get(self.pos + 1)
+                    .is_none_or(|byte| is_whitespace(*byte) || is_delimiter(*byte))
+            {
+                self.pos += 1;
+                return PdfValue::Ref(number as u32);
+            }
+            self.pos = saved;
+        }
+        PdfValue::Number(number)
+    }
+
+    fn read_literal_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        let mut depth = 1;
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            match byte {
+                b'(' => {
+                    depth += 1;
+                    bytes.push(byte);
+                }
+                b')' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                    bytes.push(byte);
+                }
+                b'\\' => {
+                    let Some(escaped) = self.peek() else {
+                        break;
+                    };
+                    self.pos += 1;
+                    match escaped {
+                        b'n' => bytes.push(b'\n'),
+                        b'r' => bytes.push(b'\r'),
+                        b't' => bytes.push(b'\t'),
+                        b'b' => bytes.push(0x08),
+                        b'f' => bytes.push(0x0c),
+                        b'0'..=b'7' => {
+                            let mut value = u32::from(escaped - b'0');
+                            for _ in 0..2 {
+                                match self.peek() {
+                                    Some(digit @ b'0'..=b'7') => {
+                                        value = value * 8 + u32::from(digit - b'0');
+                                        self.pos += 1;
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            bytes.push(value as u8);
+                        }
+                        // Una barra al final de la línea continúa la cadena.
+                        b'\r' => {
+                            if self.peek() == Some(b'\n') {
+                                self.pos += 1;
+                            }
+                        }
+                        b'\n' => {}
+                        other => bytes.push(other),
+                    }
+                }
+                _ => bytes.push(byte),
+            }
+        }
+        bytes
+    }
+
+    fn read_hex_string(&mut self) -> Vec<u8> {
+        self.pos += 1;
+        let mut digits = Vec::new();
+        while let Some(byte) = self.peek() {
+            self.pos += 1;
+            if byte == b'>' {
+                break;
+            }
+            if let Some(digit) = (byte as char).to_digit(16) {
+                digits.push(digit as u8);
+            }
+        }
+        if digits.len() % 2 == 1 {
+            digits.push(0);
+        }
+        digits
+            .chunks(2)
+            .map(|pair| pair[0] * 16 + pair[1])
+            .collect()
+    }
+
+    /// Salta los datos binarios de una imagen en línea (`BI … ID <datos> EI`).
+    fn skip_inline_image(&mut self) {
+        while self.pos + 2 < self.data.len() {
+            if self.data[self.pos..].starts_with(b"EI")
+                && is_whitespace(self.data[self.pos - 1])
+                && self
+                    .data
+                    .get(self.pos + 2)
+                    .is_none_or(|byte| is_whitespace(*byte))
+            {
+                self.pos += 2;
+                return;
+            }
+            self.pos += 1;
+        }
+        self.pos = self.data.len();
+    }
+}
+
+fn decode_name(raw: &[u8]) -> String {
+    let mut bytes = Vec::with_capacity(raw.len());
+    let mut index = 0;
+    while index < raw.len() {
+        let escaped = raw
+            .get(index + 1..index + 3)
+            .filter(|_| raw[index] == b'#')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        if let Some(value) = escaped {
+            bytes.push(value);
+            index += 3;
+            continue;
+        }
+        bytes.push(raw[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+struct PdfObject {
+    value: PdfValue,
+    /// Datos del flujo sin decodificar, si el objeto es un flujo.
+    stream: Option<Vec<u8>>,
+}
+
+/// Tabla `ToUnicode` de una fuente: de códigos de carácter a texto.
+struct ToUnicode {
+    map: HashMap<u32, String>,
+    code_bytes: usize,
+}
+
+impl ToUnicode {
+    fn parse(data: &[u8]) -> Self {
+        let mut cmap = ToUnicode {
+            map: HashMap::new(),
+            code_bytes: 1,
+        };
+        let mut parser = PdfParser::new(data, 0);
+        let mut section = "";
+        let mut operands: Vec<PdfValue> = Vec::new();
+        while let Some(value) = parser.next_value() {
+            match value {
+                PdfValue::Keyword(keyword) => {
+                    match keyword.as_str() {
+                        "beginbfchar" => section = "bfchar",
+                        "beginbfrange" => section = "bfrange",
+                        "endbfchar" | "endbfrange" => section = "",
+                        _ => {}
+                    }
+                    operands.clear();
+                }
+                value => {
+                    operands.push(value);
+                    match (section, operands.as_slice()) {
+                        ("bfchar", [PdfValue::Str(code), PdfValue::Str(target)]) => {
+                            cmap.insert(code, utf16_text(target));
+                            operands.clear();
+                        }
+                        (
+                            "bfrange",
+                            [PdfValue::Str(low), PdfValue::Str(high), PdfValue::Str(target)],
+                        ) => {
+                            let (low_code, high_code) = (code_value(low), code_value(high));
+                            let mut units = utf16_units(target);
+                            for code in low_code..=high_code.min(low_code + 0xffff) {
+                                cmap.insert_code(code, low.len(), String::from_utf16_lossy(&units));
+                                if let Some(last) = units.last_mut() {
+                                    *last = last.wrapping_add(1);
+                                }
+                            }
+                            operands.clear();
+                        }
+                        (
+                            "bfrange",
+                            [PdfValue::Str(low), PdfValue::Str(_), PdfValue::Array(targets)],
+                        ) => {
+                            let low_code = code_value(low);
+                            for (offset, target) in targets.iter().enumerate() {
+                                if let PdfValue::Str(target) = target {
+                                    cmap.insert_code(
+                                        low_code + offset as u32,
+                                        low.len(),
+                                        utf16_text(target),
+                                    );
+                                }
+                            }
+                            operands.clear();
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        cmap
+    }
+
+    fn insert(&mut self, code: &[u8], text: String) {
+        self.insert_code(code_value(code), code.len(), text);
+    }
+
+    fn insert_code(&mut self, code: u32, code_bytes: usize, text: String) {
+        self.code_bytes = self.code_bytes.max(code_bytes.clamp(1, 4));
+        self.map.insert(code, text);
+    }
+
+    fn decode(&self, bytes: &[u8]) -> String {
+        bytes
+            .chunks(self.code_bytes)
+            .filter_map(|code| {
+                let code = code_value(code);
+                match self.map.get(&code) {
+                    Some(text) => Some(text.clone()),
+                    None if self.code_bytes == 1 => Some(latin1_char(code as u8).to_string()),
+                    None => None,
+                }
+            })
+            .collect()
+    }
+}
+
+fn code_value(bytes: &[u8]) -> u32 {
+    bytes
+        .iter()
+        .fold(0u32, |code, byte| (code << 8) | u32::from(*byte))
+}
+
+fn utf16_units(bytes: &[u8]) -> Vec<u16> {
+    bytes
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+        .collect()
+}
+
+fn utf16_text(bytes: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(bytes))
+}
+
+fn latin1_char(byte: u8) -> char {
+    // Las fuentes simples usan WinAnsi; comillas y guiones son los casos más frecuentes
+    // fuera de Latin-1.
+    match byte {
+        0x91 | 0x92 => '\'',
+        0x93 | 0x94 => '"',
+        0x95 => '•',
+        0x96 | 0x97 => '–',
+        other => other as char,
+    }
+}
+
+/// Texto de una cadena sin tabla `ToUnicode`: UTF-16 si lleva BOM y Latin-1 si no.
+fn plain_text(bytes: &[u8]) -> String {
+    match bytes {
+        [0xfe, 0xff, rest @ ..] => utf16_text(rest),
+        _ => bytes.iter().map(|byte| latin1_char(*byte)).collect(),
+    }
+}
+
+struct PdfDocument {
+    objects: HashMap<u32, PdfObject>,
+}
+
+impl PdfDocument {
+    fn parse(data: &[u8]) -> Self {
+        let mut objects = HashMap::new();
+        let mut pos = 0;
+        while let Some(captures) = OBJECT_HEADER.captures_at(data, pos) {
+            let header = captures.get(0).expect("coincidencia completa");
+            let number = std::str::from_utf8(&captures[1])
+                .ok()
+                .and_then(|number| number.parse::<u32>().ok());
+            let mut parser = PdfParser::new(data, header.end());
+            let value = parser.next_value().unwrap_or(PdfValue::Null);
+            parser.skip_whitespace();
+            let mut stream = None;
+            if data[parser.pos..].starts_with(b"stream") {
+                let mut start = parser.pos + b"stream".len();
+                if data.get(start) == Some(&b'\r') {
+                    start += 1;
+                }
+                if data.get(start) == Some(&b'\n') {
+                    start += 1;
+                }
+                let declared = value
+                    .as_dict()
+                    .and_then(|dict| dict.get("Length"))
+                    .and_then(PdfValue::as_number)
+                    .map(|length| start + length as usize)
+                    .filter(|end| {
+                        data.get(*end..)
+                            .is_some_and(|rest| trim_start(rest).starts_with(b"endstream"))
+                    });
+                let end = declared
+                    .or_else(|| find(data, b"endstream", start))
+                    .unwrap_or(data.len());
+                stream = Some(data[start..end].to_vec());
+                parser.pos = end;
+            }
+            if let Some(number) = number {
+                objects.insert(number, PdfObject { value, stream });
+            }
+            pos = parser.pos.max(header.end());
+        }
+
+        let mut document = Self { objects };
+        document.expand_object_streams();
+        document
+    }
+
+    /// Incorpora los objetos guardados dentro de flujos de objetos (PDF 1.5 o posterior).
+    fn expand_object_streams(&mut self) {
+        let mut found = Vec::new();
+        for object in self.objects.values() {
+            let Some(dict) = object.value.as_dict() else {
+                continue;
+            };
+            if dict.get("Type").and_then(PdfValue::as_name) != Some("ObjStm") {
+                continue;
+            }
+            let Some(data) = object
+                .stream
+                .as_deref()
+                .and_then(|raw| decode_stream(dict, raw))
+            else {
+                continue;
+            };
+            let count = dict.get("N").and_then(PdfValue::as_number).unwrap_or(0.0) as usize;
+            let first = dict
+                .get("First")
+                .and_then(PdfValue::as_number)
+                .unwrap_or(0.0) as usize;
+            let mut header = PdfParser::new(&data, 0);
+            for _ in 0..count {
+                let (Some(PdfValue::Number(number)), Some(PdfValue::Number(offset))) =
+                    (header.next_value(), header.next_value())
+                else {
+                    break;
+                };
+                let mut parser = PdfParser::new(&data, first + offset as usize);
+                if let Some(value) = parser.next_value() {
+                    found.push((number as u32, value));
+                }
+            }
+        }
+        for (number, value) in found {
+            self.objects.entry(number).or_insert(PdfObject {
+                value,
+                stream: None,
+            });
+        }
+    }
+
+    fn resolve<'b>(&'b self, value: &'b PdfValue) -> &'b PdfValue {
+        let mut current = value;
+        for _ in 0..8 {
+            match current {
+                PdfValue::Ref(number) => match self.objects.get(number) {
+                    Some(object) => current = &object.value,
+                    None => return &NULL,
+                },
+                _ => break,
+            }
+        }
+        current
+    }
+
+    fn dict<'b>(&'b self, value: &'b PdfValue) -> Option<&'b PdfDict> {
+        self.resolve(value).as_dict()
+    }
+
+    /// Datos decodificados del flujo al que apunta `value`.
+    fn stream(&self, value: &PdfValue) -> Option<Vec<u8>> {
+        let PdfValue::Ref(number) = value else {
+            return None;
+        };
+        let object = self.objects.get(number)?;
+        decode_stream(object.value.as_dict()?, object.stream.as_deref()?)
+    }
+
+    /// Páginas en orden de lectura, con sus recursos (heredados si la página no los
+    /// declara).
+    fn pages(&self) -> Vec<(&PdfDict, Option<&PdfValue>)> {
+        let mut pages = Vec::new();
+        let catalog = self.objects.values().find_map(|object| {
+            object
+                .value
+                .as_dict()
+                .filter(|dict| dict.get("Type").and_then(PdfValue::as_name) == Some("Catalog"))
+        });
+        if let Some(root) = catalog.and_then(|catalog| catalog.get("Pages")) {
+            self.walk_pages(root, None, &mut pages, 0);
+        }
+        if pages.is_empty() {
+            let mut numbers: Vec<&u32> = self.objects.keys().collect();
+            numbers.sort();
+            for number in numbers {
+                let Some(dict) = self.objects[number].value.as_dict() else {
+                    continue;
+                };
+                if dict.get("Type").and_then(PdfValue::as_name) == Some("Page") {
+                    pages.push((dict, dict.get("Resources")));
+                }
+            }
+        }
+        pages
+    }
+
+    fn walk_pages<'b>(
+        &'b self,
+        node: &'b PdfValue,
+        inherited: Option<&'b PdfValue>,
+        pages: &mut Vec<(&'b PdfDict, Option<&'b PdfValue>)>,
+        depth: usize,
+    ) {
+        if depth > MAX_PAGE_TREE_DEPTH {
+            return;
+        }
+        let Some(dict) = self.dict(node) else {
+            return;
+        };
+        let resources = dict.get("Resources").or(inherited);
+        match dict.get("Kids").map(|kids| self.resolve(kids)) {
+            Some(PdfValue::Array(kids)) => {
+                for kid in kids {
+                    self.walk_pages(kid, resources, pages, depth + 1);
+                }
+            }
+            _ => pages.push((dict, resources)),
+        }
+    }
+
+    fn fonts(&self, resources: Option<&PdfValue>) -> HashMap<String, ToUnicode> {
+        let mut fonts = HashMap::new();
+        let Some(font_dict) = resources
+            .and_then(|resources| self.dict(resources))
+            .and_then(|resources| resources.get("Font"))
+            .and_then(|fonts| self.dict(fonts))
+        else {
+            return fonts;
+        };
+        for (name, font) in font_dict {
+            let cmap = self
+                .dict(font)
+                .and_then(|font| font.get("ToUnicode"))
+                .and_then(|cmap| self.stream(cmap))
+                .map(|data| ToUnicode::parse(&data));
+            if let Some(cmap) = cmap {
+                fonts.insert(name.clone(), cmap);
+            }
+        }
+        fonts
+    }
+
+    fn page_text(&self, page: &PdfDict, resources: Option<&PdfValue>) -> String {
+        let mut content = Vec::new();
+        match page
+            .get("Contents")
+            .map(|contents| (contents, self.resolve(contents)))
+        {
+            Some((_, PdfValue::Array(parts))) => {
+                for part in parts {
+                    content.extend(self.stream(part).unwrap_or_default());
+                    content.push(b'\n');
+                }
+            }
+            Some((reference, _)) => content.extend(self.stream(reference).unwrap_or_default()),
+            None => {}
+        }
+        content_text(&content, &self.fonts(resources))
+    }
+}
+
+fn trim_start(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| !is_whitespace(*byte))
+        .unwrap_or(bytes.len());
+    &bytes[start..]
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|position| from + position)
+}
+
+/// Decodifica un flujo sin filtros o con `FlateDecode`; el resto de filtros (imágenes,
+/// sobre todo) no contienen texto.
+fn decode_stream(dict: &PdfDict, raw: &[u8]) -> Option<Vec<u8>> {
+    let filters: Vec<&str> = match dict.get("Filter") {
+        None => Vec::new(),
+        Some(PdfValue::Name(name)) => vec![name.as_str()],
+        Some(PdfValue::Array(names)) => names.iter().filter_map(PdfValue::as_name).collect(),
+        Some(_) => return None,
+    };
+    let mut data = raw.to_vec();
+    for filter in filters {
+        if filter != "FlateDecode" {
+            return None;
+        }
+        let mut decoded = Vec::new();
+        // Algunos generadores dejan basura al final del flujo comprimido: se aprovecha lo
+        // que se haya podido descomprimir.
+        let result = ZlibDecoder::new(data.as_slice()).read_to_end(&mut decoded);
+        if result.is_err() && decoded.is_empty() {
+            return None;
+        }
+        data = decoded;
+    }
+    Some(data)
+}
+
+/// Texto que muestran los operadores de texto de un flujo de contenido.
+fn content_text(content: &[u8], fonts: &HashMap<String, ToUnicode>) -> String {
+    let mut parser = PdfParser::new(content, 0);
+    let mut operands: Vec<PdfValue> = Vec::new();
+    let mut text = String::new();
+    let mut font: Option<&ToUnicode> = None;
+    let mut line_y: Option<f64> = None;
+
+    let newline = |text: &mut String| {
+        if !text.is_empty() && !text.ends_with('\n') {
+            text.push('\n');
+        }
+    };
+    let show = |text: &mut String, font: Option<&ToUnicode>, bytes: &[u8]| match font {
+        Some(font) => text.push_str(&font.decode(bytes)),
+        None => text.push_str(&plain_text(bytes)),
+    };
+
+    while let Some(value) = parser.next_value() {
+        let PdfValue::Keyword(operator) = value else {
+            operands.push(value);
+            continue;
+        };
+        match operator.as_str() {
+            "Tf" => {
+                font = operands
+                    .first()
+                    .and_then(PdfValue::as_name)
+                    .and_then(|name| fonts.get(name));
+            }
+            "Tj" | "'" | "\"" => {
+                if operator != "Tj" {
+                    newline(&mut text);
+                }
+                if let Some(PdfValue::Str(bytes)) = operands.last() {
+                    show(&mut text, font, bytes);
+                }
+            }
+            "TJ" => {
+                if let Some(PdfValue::Array(items)) = operands.last() {
+                    for item in items {
+                        match item {
+                            PdfValue::Str(bytes) => show(&mut text, font, bytes),
+                            PdfValue::Number(offset)
+                                if *offset < -TJ_SPACE_THRESHOLD && !text.ends_with(' ') =>
+                            {
+                                text.push(' ');
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            "Td" | "TD" => {
+                let dy = operands.get(1).and_then(PdfValue::as_number).unwrap_or(0.0);
+                if dy.abs() > f64::EPSILON {
+                    newline(&mut text);
+                } else if !text.ends_with([' ', '\n']) && !text.is_empty() {
+                    text.push(' ');
+                }
+            }
+            "Tm" => {
+                let y = operands.get(5).and_then(PdfValue::as_number);
+                if y != line_y {
+                    newline(&mut text);
+                } else if !text.ends_with([' ', '\n']) && !text.is_empty() {
+                    text.push(' ');
+                }
+                line_y = y;
+            }
+            "T*" | "ET" => newline(&mut text),
+            "ID" => parser.skip_inline_image(),
+            _ => {}
+        }
+        operands.clear();
+    }
+    clean_text(&text)
+}
+
+/// Recorta las líneas y deja como mucho una línea en blanco seguida.
+fn clean_text(text: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() && lines.last().is_none_or(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    lines.join("\n")
+}
+
+pub fn extract_pdf(bytes: &[u8]) -> Result<Vec<DocumentPage>> {
+    if !bytes.starts_with(b"%PDF") {
+        bail!("No es un PDF");
+    }
+    if find(bytes, b"/Encrypt", 0).is_some() {
+        bail!("El PDF está cifrado");
+    }
+    let document = PdfDocument::parse(bytes);
+    let pages = document.pages();
+    if pages.is_empty() {
+        bail!("No se encontraron páginas");
+    }
+    Ok(pages
+        .into_iter()
+        .enumerate()
+        .map(|(index, (page, resources))| DocumentPage {
+            number: index + 1,
+            text: document.page_text(page, resources),
+        })
+        .filter(|page| !page.text.trim().is_empty())
+        .collect())
+}
+
+// ---------------------------------------------------------------------------------------
+// DOCX
+// ---------------------------------------------------------------------------------------
+
+fn read_u16(bytes: &[u8], at: usize) -> Option<usize> {
+    Some(u16::from_le_bytes(bytes.get(at..at + 2)?.try_into().ok()?) as usize)
+}
+
+fn read_u32(bytes: &[u8], at: usize) -> Option<usize> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?) as usize)
+}
+
+/// Contenido de la entrada `name` de un ZIP (solo entradas guardadas o con deflate).
+fn zip_entry(bytes: &[u8], name: &str) -> Result<Vec<u8>> {
+    const END_OF_DIRECTORY: &[u8] = b"PK\x05\x06";
+    const DIRECTORY_ENTRY: &[u8] = b"PK\x01\x02";
+    const LOCAL_HEADER: &[u8] = b"PK\x03\x04";
+    let truncated = || anyhow!("El archivo ZIP está truncado");
+
+    let end = bytes
+        .windows(4)
+        .rposition(|window| window == END_OF_DIRECTORY)
+        .ok_or_else(|| anyhow!("No es un archivo ZIP"))?;
+    let entries = read_u16(bytes, end + 10).ok_or_else(truncated)?;
+    let mut offset = read_u32(bytes, end + 16).ok_or_else(truncated)?;
+    for _ in 0..entries {
+        if bytes.get(offset..offset + 4) != Some(DIRECTORY_ENTRY) {
+            bail!("Directorio ZIP dañado");
+        }
+        let method = read_u16(bytes, offset + 10).ok_or_else(truncated)?;
+        let compressed = read_u32(bytes, offset + 20).ok_or_else(truncated)?;
+        let name_len = read_u16(bytes, offset + 28).ok_or_else(truncated)?;
+        let extra_len = read_u16(bytes, offset + 30).ok_or_else(truncated)?;
+        let comment_len = read_u16(bytes, offset + 32).ok_or_else(truncated)?;
+        let local = read_u32(bytes, offset + 42).ok_or_else(truncated)?;
+        let entry_name = bytes
+            .get(offset + 46..offset + 46 + name_len)
+            .ok_or_else(truncated)?;
+        offset += 46 + name_len + extra_len + comment_len;
+        if entry_name != name.as_bytes() {
+            continue;
+        }
+
+        if bytes.get(local..local + 4) != Some(LOCAL_HEADER) {
+            bail!("Cabecera ZIP dañada");
+        }
+        let start = local
+            + 30
+            + read_u16(bytes, local + 26).ok_or_else(truncated)?
+            + read_u16(bytes, local + 28).ok_or_else(truncated)?;
+        let data = bytes.get(start..start + compressed).ok_or_else(truncated)?;
+        return match method {
+            0 => Ok(data.to_vec()),
+            8 => {
+                let mut inflated = Vec::new();
+                DeflateDecoder::new(data)
+                    .read_to_end(&mut inflated)
+                    .context("No se pudo descomprimir el documento")?;
+                Ok(inflated)
+            }
+            other => bail!("Compresión ZIP {} no soportada", other),
+        };
+    }
+    bail!("Falta {} en el archivo", name)
+}
+
+fn xml_unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let tail = &rest[start..];
+        let Some(end) = tail.find(';') else {
+            result.push_str(tail);
+            return result;
+        };
+        let entity = &tail[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(str::parse))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(ch) => {
+                result.push(ch);
+                rest = &tail[end + 1..];
+            }
+            None => {
+                result.push('&');
+                rest = &tail[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+pub fn extract_docx(bytes: &[u8]) -> Result<Vec<DocumentPage>> {
+    let xml = zip_entry(bytes, "word/document.xml")?;
+    let xml = String::from_utf8_lossy(&xml);
+
+    let mut pages: Vec<DocumentPage> = Vec::new();
+    let mut current = String::new();
+    let mut in_text = false;
+    let mut rest: &str = &xml;
+    let mut flush = |current: &mut String| {
+        let text = clean_text(current);
+        current.clear();
+        if !text.is_empty() {
+            let number = pages.last().map_or(1, |page| page.number + 1);
+            pages.push(DocumentPage { number, text });
+        }
+    };
+
+    while let Some(open) = rest.find('<') {
+        if in_text {
+            current.push_str(&xml_unescape(&rest[..open]));
+        }
+        let Some(close) = rest[open..].find('>') else {
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        rest = &rest[open + close + 1..];
+
+        let name = tag
+            .trim_end_matches('/')
+            .split_whitespace()
+            .next()
+            .unwrap_or_default();
+        match name {
+            "w:t" => in_text = !tag.ends_with('/'),
+            "/w:t" => in_text = false,
+            "w:tab" => current.push('\t'),
+            "/w:p" => current.push_str("\n\n"),
+            "w:br" | "w:cr" if tag.contains("w:type=\"page\"") => flush(&mut current),
+            "w:br" | "w:cr" => current.push('\n'),
+            // Salto que Word calculó al guardar; coincide con la paginación que vio el
+            // autor. Tras un salto explícito no añade página porque `flush` no guarda
+            // páginas vacías.
+            "w:lastRenderedPageBreak" => flush(&mut current),
+            _ => {}
+        }
+    }
+    flush(&mut current);
+    Ok(pages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    fn compressed(data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn stored_zip(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut zip = Vec::new();
+        zip.extend_from_slice(b"PK\x03\x04");
+        zip.extend_from_slice(&[0; 14]);
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0; 2]);
+        zip.extend_from_slice(name.as_bytes());
+        zip.extend_from_slice(data);
+        let directory = zip.len();
+        zip.extend_from_slice(b"PK\x01\x02");
+        zip.extend_from_slice(&[0; 16]);
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        zip.extend_from_slice(&[0; 12]);
+        zip.extend_from_slice(&0u32.to_le_bytes());
+        zip.extend_from_slice(name.as_bytes());
+        let directory_len = zip.len() - directory;
+        zip.extend_from_slice(b"PK\x05\x06");
+        zip.extend_from_slice(&[0; 4]);
+        zip.extend_from_slice(&1u16.to_le_bytes());
+        zip.extend_from_slice(&1u16.to_le_bytes());
+        zip.extend_from_slice(&(directory_len as u32).to_le_bytes());
+        zip.extend_from_slice(&(directory as u32).to_le_bytes());
+        zip.extend_from_slice(&[0; 2]);
+        zip
+    }
+
+    #[test]
+    fn extracts_pages_and_cites_them() {
+        let cmap = b"begincmap 1 begincodespacerange <00> <FF> endcodespacerange\n\
+            2 beginbfchar <01> <0050> <02> <00E1> endbfchar\n\
+            1 beginbfrange <03> <05> <0061> endbfrange endcmap";
+        let second_page = compressed(b"BT /F2 12 Tf 72 700 Td <0102030405> Tj ET");
+        let mut pdf = b"%PDF-1.4\n".to_vec();
+        pdf.extend_from_slice(
+            b"1 0 obj << /Type /Catalog /Pages 2 0 R >> endobj\n\
+              2 0 obj << /Type /Pages /Kids [3 0 R 4 0 R] /Count 2 \
+              /Resources << /Font << /F1 7 0 R /F2 8 0 R >> >> >> endobj\n\
+              3 0 obj << /Type /Page /Parent 2 0 R /Contents 5 0 R >> endobj\n\
+              4 0 obj << /Type /Page /Parent 2 0 R /Contents 6 0 R >> endobj\n\
+              5 0 obj << /Length 76 >>\nstream\n\
+              BT /F1 12 Tf 72 720 Td (Cl\\341usula de pago) Tj 0 -14 Td [(plazo)-300(30)] TJ ET\n\
+              endstream endobj\n",
+        );
+        pdf.extend_from_slice(
+            format!(
+                "6 0 obj << /Length {} /Filter /FlateDecode >>\nstream\n",
+                second_page.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(&second_page);
+        pdf.extend_from_slice(b"\nendstream endobj\n7 0 obj << /Type /Font >> endobj\n");
+        pdf.extend_from_slice(
+            format!(
+                "8 0 obj << /Type /Font /ToUnicode 9 0 R >> endobj\n9 0 obj << /Length {} >>\nstream\n",
+                cmap.len()
+            )
+            .as_bytes(),
+        );
+        pdf.extend_from_slice(cmap);
+        pdf.extend_from_slice(b"\nendstream endobj\ntrailer << /Root 1 0 R >>\n%%EOF");
+
+        let pages = extract_pdf(&pdf).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].text, "Cláusula de pago\nplazo 30");
+        assert_eq!(pages[1].text, "Páabc");
+
+        let xml = "<w:document><w:body><w:p><w:r><w:t>Informe &amp; anexos</w:t></w:r></w:p>\
+            <w:p><w:r><w:br w:type=\"page\"/></w:r></w:p>\
+            <w:p><w:r><w:lastRenderedPageBreak/><w:t xml:space=\"preserve\">Penalización por retraso</w:t></w:r></w:p>\
+            </w:body></w:document>";
+        let pages = extract_docx(&stored_zip("word/document.xml", xml.as_bytes())).unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                DocumentPage {
+                    number: 1,
+                    text: "Informe & anexos".to_string()
+                },
+                DocumentPage {
+                    number: 2,
+                    text: "Penalización por retraso".to_string()
+                },
+            ]
+        );
+
+        let chunks = chunk_pages(&pages, 30);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1].citation().as_deref(), Some("p. 2"));
+        assert_eq!(
+            chunk_pages(&pages, 800)[0].pages,
+            Some(PageRange { first: 1, last: 2 })
+        );
+        let ranked = rank_chunks(
+            "¿Qué penalización hay por retraso?",
+            chunks.iter().map(|chunk| ((), chunk)),
+            1,
+        );
+        assert_eq!(ranked[0].1.pages, Some(PageRange { first: 2, last: 2 }));
+        let ranked = rank_chunks("¿Qué dice la página 1?", chunks.iter().map(|c| ((), c)), 1);
+        assert_eq!(ranked[0].1.citation().as_deref(), Some("p. 1"));
+    }
+}
@@ -0,0 +1,440 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use chrono::Local;
+
+use super::job_queue::{JobPriority, JobQueueState, EMAIL_LANE};
+use crate::config::{SmtpConfig, SmtpSecurity};
+
+/// Adjuntos mayores que este tamaño no se envían; la mayoría de servidores los rechazan.
+pub const MAX_ATTACHMENT_BYTES: u64 = 20 * 1024 * 1024;
+/// Longitud de las líneas de base64 en el mensaje, la que fija MIME.
+const BASE64_LINE: usize = 76;
+
+/// Sustituye `{titulo}`, `{fecha}` y `{contenido}` en una plantilla de correo.
+pub fn render_template(template: &str, title: &str, content: &str) -> String {
+    template
+        .replace("{titulo}", title)
+        .replace(
+            "{fecha}",
+            &Local::now().format("%d/%m/%Y %H:%M").to_string(),
+        )
+        .replace("{contenido}", content)
+}
+
+/// Direcciones separadas por comas, puntos y coma o espacios.
+pub fn parse_recipients(text: &str) -> Result<Vec<String>, String> {
+    let recipients: Vec<String> = text
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .map(str::trim)
+        .filter(|address| !address.is_empty())
+        .map(str::to_string)
+        .collect();
+    if recipients.is_empty() {
+        return Err("Indica al menos un destinatario.".to_string());
+    }
+    if let Some(invalid) = recipients
+        .iter()
+        .find(|address| !is_address(address.as_str()))
+    {
+        return Err(format!(
+            "'{}' no es una dirección de correo válida.",
+            invalid
+        ));
+    }
+    Ok(recipients)
+}
+
+fn is_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((user, domain)) => {
+            !user.is_empty()
+                && domain.contains('.')
+                && !address.contains(|c: char| c.is_control() || "<>\"".contains(c))
+        }
+        None => false,
+    }
+}
+
+/// Correo preparado para revisar antes de enviarlo.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EmailDraft {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<PathBuf>,
+}
+
+/// Cabecera codificada como indica RFC 2047 si no es ASCII. Los saltos de línea y demás
+/// caracteres de control pasan a ser espacios, para que el valor no pueda añadir cabeceras.
+fn encode_header(value: &str) -> String {
+    let value: String = value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+    if value.is_ascii() {
+        value
+    } else {
+        format!(
+            "=?UTF-8?B?{}?=",
+            base64::engine::general_purpose::STANDARD.encode(value)
+        )
+    }
+}
+
+fn base64_lines(bytes: &[u8]) -> String {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+    encoded
+        .as_bytes()
+        .chunks(BASE64_LINE)
+        .map(|line| String::from_utf8_lossy(line).into_owned())
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+fn attachment_type(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "md" | "markdown" => "text/markdown; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "json" => "application/json",
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Compone el mensaje MIME: texto en UTF-8 y, si hay adjuntos, `multipart/mixed`.
+pub fn build_message(
+    from: &str,
+    recipients: &[String],
+    draft: &EmailDraft,
+    boundary: &str,
+) -> Result<String> {
+    if let Some(invalid) = std::iter::once(from)
+        .chain(recipients.iter().map(String::as_str))
+        .find(|address| !is_address(address))
+    {
+        bail!(
+            "'{}' no es una dirección de correo válida",
+            invalid.escape_debug()
+        );
+    }
+    let mut message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\n",
+        from,
+        recipients.join(", "),
+        encode_header(draft.subject.trim()),
+        Local::now().to_rfc2822()
+    );
+    let body = format!(
+        "Content-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+        base64_lines(draft.body.replace("\r\n", "\n").replace('\n', "\r\n").as_bytes())
+    );
+    if draft.attachments.is_empty() {
+        message.push_str(&body);
+        return Ok(message);
+    }
+
+    message.push_str(&format!(
+        "Content-Type: multipart/mixed; boundary=\"{}\"\r\n\r\n--{}\r\n{}",
+        boundary, boundary, body
+    ));
+    for path in &draft.attachments {
+        let size = fs::metadata(path)
+            .with_context(|| format!("No se pudo leer {}", path.display()))?
+            .len();
+        if size > MAX_ATTACHMENT_BYTES {
+            bail!(
+                "{} supera el límite de {} MB para adjuntos",
+                path.display(),
+                MAX_ATTACHMENT_BYTES / (1024 * 1024)
+            );
+        }
+        let bytes =
+            fs::read(path).with_context(|| format!("No se pudo leer {}", path.display()))?;
+        let name = encode_header(
+            &path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "adjunto".to_string()),
+        );
+        message.push_str(&format!(
+            "--{}\r\nContent-Type: {}; name={}\r\nContent-Disposition: attachment; filename={}\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+            boundary,
+            attachment_type(path),
+            quoted(&name),
+            quoted(&name),
+            base64_lines(&bytes)
+        ));
+    }
+    message.push_str(&format!("--{}--\r\n", boundary));
+    Ok(message)
+}
+
+fn smtp_url(config: &SmtpConfig) -> String {
+    let scheme = match config.security {
+        SmtpSecurity::Tls => "smtps",
+        SmtpSecurity::StartTls | SmtpSecurity::None => "smtp",
+    };
+    format!("{}://{}:{}", scheme, config.host.trim(), config.port)
+}
+
+/// Valor entre comillas con `\` y `"` escapados, como lo esperan los parámetros MIME y el
+/// archivo de configuración de curl.
+fn quoted(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Comprueba que hay servidor y remitente antes de intentar un envío.
+pub fn validate(config: &SmtpConfig) -> Result<(), String> {
+    if config.host.trim().is_empty() {
+        return Err("Configura el servidor SMTP en Preferencias › Sistema › Correo.".to_string());
+    }
+    if !is_address(config.from_address.trim()) {
+        return Err("Indica una dirección de remitente válida.".to_string());
+    }
+    Ok(())
+}
+
+/// Mensaje listo para que curl lo suba: solo lo puede leer el usuario y se borra al soltarlo,
+/// también si el envío falla a mitad.
+struct MessageFile(PathBuf);
+
+impl MessageFile {
+    fn create(boundary: &str, message: &str) -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("{}.eml", boundary));
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(&path)
+            .with_context(|| format!("No se pudo escribir {}", path.display()))?;
+        let created = Self(path);
+        file.write_all(message.as_bytes())
+            .with_context(|| format!("No se pudo escribir {}", created.0.display()))?;
+        Ok(created)
+    }
+}
+
+impl Drop for MessageFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Envía el correo en este hilo con curl, que habla SMTP y TLS. Las credenciales se
+/// pasan por la entrada estándar para que no aparezcan en la lista de procesos.
+pub fn send(config: &SmtpConfig, draft: &EmailDraft) -> Result<()> {
+    validate(config).map_err(anyhow::Error::msg)?;
+    let recipients = parse_recipients(&draft.to).map_err(anyhow::Error::msg)?;
+    let from = config.from_address.trim();
+    let boundary = format!(
+        "jmk-{}",
+        Local::now().timestamp_nanos_opt().unwrap_or_default()
+    );
+    let message = build_message(from, &recipients, draft, &boundary)?;
+
+    let file = MessageFile::create(&boundary, &message)?;
+
+    let mut command = Command::new(config.curl_command.trim());
+    command
+        .arg("--silent")
+        .arg("--show-error")
+        .args(["--connect-timeout", "20"])
+        .arg("--url")
+        .arg(smtp_url(config))
+        .arg("--mail-from")
+        .arg(from)
+        .arg("--upload-file")
+        .arg(&file.0)
+        .args(["--config", "-"]);
+    if config.security == SmtpSecurity::StartTls {
+        command.arg("--ssl-reqd");
+    }
+    for recipient in &recipients {
+        command.arg("--mail-rcpt").arg(recipient);
+    }
+    let child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(err) => {
+            bail!(
+                "No se pudo ejecutar '{}': {}. Instala curl o indica su ruta en las preferencias",
+                config.curl_command.trim(),
+                err
+            );
+        }
+    };
+    if let Some(mut stdin) = child.stdin.take() {
+        if !config.username.trim().is_empty() {
+            let user = format!("{}:{}", config.username.trim(), config.password);
+            let _ = writeln!(stdin, "user = {}", quoted(&user));
+        }
+    }
+    let output = child.wait_with_output();
+    drop(file);
+    let output = output.context("curl no terminó correctamente")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "El servidor rechazó el envío: {}",
+            stderr.lines().last().unwrap_or("sin detalles")
+        );
+    }
+    Ok(())
+}
+
+/// Envío terminado en segundo plano.
+pub struct EmailOutcome {
+    pub subject: String,
+    pub recipients: String,
+    pub result: Result<(), String>,
+}
+
+/// Correo en preparación y envío en curso.
+#[derive(Default)]
+pub struct EmailState {
+    /// Borrador abierto en el diálogo de envío.
+    pub draft: Option<EmailDraft>,
+    pub status: Option<String>,
+    pending: Option<Receiver<EmailOutcome>>,
+}
+
+impl EmailState {
+    pub fn is_sending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Encola el envío de `draft` en la cola de llamadas salientes. Solo hay un envío en
+    /// curso a la vez.
+    pub fn start(
+        &mut self,
+        jobs: &mut JobQueueState,
+        config: SmtpConfig,
+        draft: EmailDraft,
+    ) -> Result<(), String> {
+        if self.is_sending() {
+            return Err("Ya hay un correo enviándose.".to_string());
+        }
+        validate(&config)?;
+        parse_recipients(&draft.to)?;
+        let (tx, rx) = mpsc::channel();
+        self.pending = Some(rx);
+        let label = format!("Correo «{}»", draft.subject);
+        jobs.submit(EMAIL_LANE, JobPriority::Background, label, move || {
+            let result = send(&config, &draft).map_err(|err| format!("{:#}", err));
+            let _ = tx.send(EmailOutcome {
+                subject: draft.subject,
+                recipients: draft.to,
+                result,
+            });
+        });
+        Ok(())
+    }
+
+    pub fn poll(&mut self) -> Option<EmailOutcome> {
+        let rx = self.pending.as_ref()?;
+        match rx.try_recv() {
+            Ok(outcome) => {
+                self.pending = None;
+                Some(outcome)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = None;
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_multipart_messages_and_checks_recipients() {
+        assert_eq!(
+            parse_recipients("ana@example.com; luis@example.org ,"),
+            Ok(vec![
+                "ana@example.com".to_string(),
+                "luis@example.org".to_string()
+            ])
+        );
+        assert!(parse_recipients("").is_err());
+        assert!(parse_recipients("ana@localhost").is_err());
+        assert_eq!(
+            render_template("Informe {titulo}", "semanal", ""),
+            "Informe semanal"
+        );
+
+        let attachment = std::env::temp_dir().join("jmk-email-test.md");
+        fs::write(&attachment, "# Informe").unwrap();
+        let draft = EmailDraft {
+            to: "ana@example.com".to_string(),
+            subject: "Resumen diario".to_string(),
+            body: "Hola".to_string(),
+            attachments: vec![attachment.clone()],
+        };
+        let message = build_message(
+            "yo@example.com",
+            &["ana@example.com".to_string()],
+            &draft,
+            "limite",
+        )
+        .unwrap();
+        let _ = fs::remove_file(&attachment);
+        assert!(message.contains("Subject: Resumen diario\r\n"));
+        assert!(message.contains("multipart/mixed; boundary=\"limite\""));
+        assert!(message.contains("filename=\"jmk-email-test.md\""));
+        assert!(message.contains(&base64::engine::general_purpose::STANDARD.encode("# Informe")));
+        assert!(message.ends_with("--limite--\r\n"));
+        assert_eq!(encode_header("Año"), "=?UTF-8?B?QcOxbw==?=");
+    }
+
+    #[test]
+    fn headers_cannot_be_injected() {
+        let attachment = std::env::temp_dir().join("jmk \"email\" test.md");
+        fs::write(&attachment, "# Informe").unwrap();
+        let draft = EmailDraft {
+            to: "ana@example.com".to_string(),
+            subject: "Resumen\r\nBcc: todos@example.com".to_string(),
+            body: "Hola".to_string(),
+            attachments: vec![attachment.clone()],
+        };
+        let message = build_message(
+            "yo@example.com",
+            &["ana@example.com".to_string()],
+            &draft,
+            "limite",
+        )
+        .unwrap();
+        assert!(message.contains("Subject: Resumen  Bcc: todos@example.com\r\n"));
+        assert!(!message.contains("\r\nBcc:"));
+        assert!(message.contains("filename=\"jmk \\\"email\\\" test.md\""));
+
+        let injected = build_message(
+            "yo@example.com",
+            &["ana@example.com\r\nBcc: todos@example.com".to_string()],
+            &draft,
+            "limite",
+        );
+        let _ = fs::remove_file(&attachment);
+        assert!(injected.is_err());
+    }
+}
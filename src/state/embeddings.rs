@@ -0,0 +1,379 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use crate::api::local::JarvisEmbedder;
+
+use super::job_queue::{JobPriority, JobQueueState, EMBEDDINGS_LANE};
+use super::{PreferencePanel, RemoteProviderKind, ResourceSection};
+
+/// Pausa tras la última pulsación antes de lanzar la búsqueda semántica.
+pub const SEMANTIC_SEARCH_DEBOUNCE: Duration = Duration::from_millis(500);
+/// Similitud mínima para mostrar un mensaje en la búsqueda semántica.
+pub const SEMANTIC_SEARCH_MIN_SCORE: f32 = 0.3;
+/// Similitud mínima para recordar un mensaje antiguo junto al prompt.
+pub const MEMORY_RECALL_MIN_SCORE: f32 = 0.35;
+/// Tamaño aproximado, en caracteres, de cada fragmento de README indexado.
+pub const README_CHUNK_CHARS: usize = 800;
+/// Vectores de consultas que se conservan para no volver a calcularlos.
+const MAX_QUERY_VECTORS: usize = 64;
+/// Texto con el que se prueba el backend desde las preferencias.
+pub(crate) const TEST_TEXT: &str = "Prueba de embeddings de JungleMonkAI";
+
+/// Origen de un texto indexado, con lo necesario para volver a él desde un resultado.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SemanticSource {
+    Message(usize),
+    ProjectReadme {
+        section: ResourceSection,
+        project: String,
+        chunk: usize,
+    },
+    /// Fragmento de un documento incorporado por el reindexado programado.
+    ProjectDocument {
+        section: ResourceSection,
+        project: String,
+        path: String,
+        chunk: usize,
+    },
+    KnowledgeCard {
+        panel: PreferencePanel,
+        title: String,
+    },
+}
+
+impl SemanticSource {
+    pub fn message_index(&self) -> Option<usize> {
+        match self {
+            SemanticSource::Message(index) => Some(*index),
+            _ => None,
+        }
+    }
+}
+
+struct IndexedText {
+    source: SemanticSource,
+    fingerprint: u64,
+    vector: Vec<f32>,
+    norm: f32,
+}
+
+/// Texto recuperado por similitud.
+#[derive(Clone, Debug)]
+pub struct SemanticHit {
+    pub source: SemanticSource,
+    pub score: f32,
+}
+
+/// Índice vectorial de los mensajes del chat, los README de proyectos y las tarjetas de
+/// conocimiento. Sirve de base de recuperación para la búsqueda semántica y la memoria;
+/// se vacía al cambiar de modelo de embeddings.
+#[derive(Default)]
+pub struct EmbeddingIndex {
+    signature: String,
+    entries: Vec<IndexedText>,
+}
+
+impl EmbeddingIndex {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Descarta las entradas obsoletas y devuelve los textos que faltan por indexar.
+    pub(crate) fn missing(
+        &mut self,
+        signature: &str,
+        texts: impl Iterator<Item = (SemanticSource, String)>,
+    ) -> Vec<(SemanticSource, u64, String)> {
+        if self.signature != signature {
+            self.signature = signature.to_string();
+            self.entries.clear();
+        }
+
+        let mut missing = Vec::new();
+        let mut current = Vec::new();
+        for (source, text) in texts {
+            let fingerprint = fingerprint(&text);
+            let indexed = self
+                .entries
+                .iter()
+                .any(|entry| entry.source == source && entry.fingerprint == fingerprint);
+            if !indexed {
+                missing.push((source.clone(), fingerprint, text));
+            }
+            current.push((source, fingerprint));
+        }
+        self.entries.retain(|entry| {
+            current.iter().any(|(source, fingerprint)| {
+                *source == entry.source && *fingerprint == entry.fingerprint
+            })
+        });
+        missing
+    }
+
+    pub(crate) fn insert(&mut self, source: SemanticSource, fingerprint: u64, vector: Vec<f32>) {
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.source == source && entry.fingerprint == fingerprint)
+        {
+            return;
+        }
+        let norm = vector_norm(&vector);
+        self.entries.push(IndexedText {
+            source,
+            fingerprint,
+            vector,
+            norm,
+        });
+    }
+
+    /// Entradas aceptadas por `filter` ordenadas por similitud con `query`.
+    pub fn search(
+        &self,
+        query: &[f32],
+        limit: usize,
+        min_score: f32,
+        filter: impl Fn(&SemanticSource) -> bool,
+    ) -> Vec<SemanticHit> {
+        let query_norm = vector_norm(query);
+        let mut hits: Vec<SemanticHit> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.vector.len() == query.len() && filter(&entry.source))
+            .map(|entry| SemanticHit {
+                source: entry.source.clone(),
+                score: entry
+                    .vector
+                    .iter()
+                    .zip(query)
+                    .map(|(a, b)| a * b)
+                    .sum::<f32>()
+                    / (entry.norm * query_norm),
+            })
+            .filter(|hit| hit.score >= min_score)
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(limit);
+        hits
+    }
+}
+
+/// Estado compartido por los subsistemas que usan embeddings.
+#[derive(Default)]
+pub struct EmbeddingState {
+    pub index: EmbeddingIndex,
+    /// Consulta a la que corresponden `search_hits`.
+    pub search_query: String,
+    pub search_hits: Vec<SemanticHit>,
+    /// Modo semántico de la barra de búsqueda: agrupa por origen solo los resultados
+    /// por similitud en lugar de mezclarlos con la búsqueda por palabras.
+    pub semantic_mode: bool,
+    /// Motivo por el que falló la última búsqueda semántica.
+    pub search_error: Option<String>,
+    pub status: Option<String>,
+    pub(crate) pending_query: Option<(String, Instant)>,
+    /// Peticiones que esperan al siguiente cálculo en segundo plano.
+    requests: Vec<(EmbeddingPurpose, Option<String>)>,
+    job: Option<EmbeddingJob>,
+    /// Vectores de consultas ya calculados, con la firma del backend que los dio.
+    query_signature: String,
+    query_vectors: HashMap<String, Vec<f32>>,
+}
+
+/// Para qué se pidió un cálculo de embeddings; se atiende cuando llega el resultado.
+pub(crate) enum EmbeddingPurpose {
+    /// Búsqueda semántica de la barra global.
+    Search,
+    /// Llamada a un proveedor que espera el vector de su prompt para recordar contexto.
+    Recall(u64),
+    /// Reindexado de la base de conocimiento de un proyecto.
+    Reindex {
+        project: String,
+        task_id: u32,
+        started: Instant,
+        failures: usize,
+    },
+    /// Prueba del backend desde las preferencias.
+    Test(Instant),
+}
+
+/// Backend de embeddings listo para usarse fuera del hilo de la interfaz.
+#[derive(Clone)]
+pub(crate) enum EmbeddingEngine {
+    Local(JarvisEmbedder),
+    OpenAi { api_key: String, model: String },
+}
+
+impl EmbeddingEngine {
+    pub(crate) fn embed(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>, String> {
+        match self {
+            EmbeddingEngine::Local(embedder) => embedder.embed(texts),
+            EmbeddingEngine::OpenAi { api_key, model } => {
+                crate::api::openai::embed(api_key, model, texts)
+            }
+        }
+        .map_err(|err| format!("{:#}", err))
+    }
+
+    fn lane(&self) -> &'static str {
+        match self {
+            EmbeddingEngine::Local(_) => EMBEDDINGS_LANE,
+            EmbeddingEngine::OpenAi { .. } => RemoteProviderKind::OpenAi.short_code(),
+        }
+    }
+}
+
+/// Vectores calculados en segundo plano: textos del índice que faltaban y consultas.
+struct EmbeddingBatch {
+    signature: String,
+    indexed: Vec<(SemanticSource, u64, Vec<f32>)>,
+    queries: Vec<(String, Vec<f32>)>,
+}
+
+struct EmbeddingJob {
+    purposes: Vec<EmbeddingPurpose>,
+    rx: Receiver<Result<EmbeddingBatch, String>>,
+}
+
+impl EmbeddingState {
+    /// Encola un cálculo para `purpose`; `query` es el texto cuyo vector necesita, si lo hay.
+    pub(crate) fn request(&mut self, purpose: EmbeddingPurpose, query: Option<String>) {
+        self.requests.push((purpose, query));
+    }
+
+    /// Vector ya calculado para `text` con el backend de `signature`.
+    pub(crate) fn query_vector(&self, signature: &str, text: &str) -> Option<&Vec<f32>> {
+        (self.query_signature == signature)
+            .then(|| self.query_vectors.get(text))
+            .flatten()
+    }
+
+    /// Saca las peticiones pendientes si no hay otro cálculo en marcha.
+    pub(crate) fn take_requests(&mut self) -> Option<Vec<(EmbeddingPurpose, Option<String>)>> {
+        (self.job.is_none() && !self.requests.is_empty())
+            .then(|| std::mem::take(&mut self.requests))
+    }
+
+    /// Calcula en segundo plano los textos del índice que faltan y las consultas.
+    pub(crate) fn start(
+        &mut self,
+        jobs: &mut JobQueueState,
+        engine: EmbeddingEngine,
+        signature: String,
+        missing: Vec<(SemanticSource, u64, String)>,
+        queries: Vec<String>,
+        purposes: Vec<EmbeddingPurpose>,
+    ) {
+        let priority = if purposes
+            .iter()
+            .all(|purpose| matches!(purpose, EmbeddingPurpose::Reindex { .. }))
+        {
+            JobPriority::Background
+        } else {
+            JobPriority::Interactive
+        };
+        let label = format!("Embeddings · {} textos", missing.len() + queries.len());
+        let lane = engine.lane();
+        let (tx, rx) = mpsc::channel();
+        jobs.submit(lane, priority, label, move || {
+            let texts: Vec<&str> = missing
+                .iter()
+                .map(|(_, _, text)| text.as_str())
+                .chain(queries.iter().map(String::as_str))
+                .collect();
+            let outcome = engine.embed(&texts).map(|mut vectors| {
+                let query_vectors = vectors.split_off(missing.len().min(vectors.len()));
+                EmbeddingBatch {
+                    signature,
+                    indexed: missing
+                        .into_iter()
+                        .zip(vectors)
+                        .map(|((source, fingerprint, _), vector)| (source, fingerprint, vector))
+                        .collect(),
+                    queries: queries.into_iter().zip(query_vectors).collect(),
+                }
+            });
+            let _ = tx.send(outcome);
+        });
+        self.job = Some(EmbeddingJob { purposes, rx });
+    }
+
+    /// Cálculo terminado: guarda sus vectores y devuelve las peticiones que atendía.
+    pub(crate) fn poll(&mut self) -> Option<(Vec<EmbeddingPurpose>, Result<usize, String>)> {
+        let outcome = match self.job.as_ref()?.rx.try_recv() {
+            Ok(outcome) => outcome,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                Err("El cálculo de embeddings terminó sin resultado.".to_string())
+            }
+        };
+        let purposes = self.job.take()?.purposes;
+        Some((purposes, outcome.map(|batch| self.apply(batch))))
+    }
+
+    /// Guarda los vectores de `batch` si siguen siendo del backend actual y devuelve
+    /// cuántos textos entraron en el índice.
+    fn apply(&mut self, batch: EmbeddingBatch) -> usize {
+        if self.query_signature != batch.signature {
+            self.query_signature = batch.signature.clone();
+            self.query_vectors.clear();
+        }
+        if self.query_vectors.len() + batch.queries.len() > MAX_QUERY_VECTORS {
+            self.query_vectors.clear();
+        }
+        self.query_vectors.extend(batch.queries);
+        if self.index.signature != batch.signature {
+            return 0;
+        }
+        let indexed = batch.indexed.len();
+        for (source, fingerprint, vector) in batch.indexed {
+            self.index.insert(source, fingerprint, vector);
+        }
+        indexed
+    }
+}
+
+/// Divide un README en fragmentos de unos `max_chars` caracteres respetando los párrafos.
+pub fn chunk_text(text: &str, max_chars: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if !current.is_empty() && current.chars().count() + paragraph.chars().count() > max_chars {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push_str("\n\n");
+        }
+        current.push_str(paragraph);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn fingerprint(text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn vector_norm(values: &[f32]) -> f32 {
+    values
+        .iter()
+        .map(|value| value * value)
+        .sum::<f32>()
+        .sqrt()
+        .max(1e-6)
+}
@@ -0,0 +1,473 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use chrono::Local;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+
+use crate::config::EventStreamConfig;
+
+use super::runtime;
+
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_HANDSHAKE_BYTES: usize = 8 * 1024;
+/// Tramas pendientes por observador; el que se queda atrás se desconecta.
+const CLIENT_QUEUE_LEN: usize = 256;
+/// Orígenes de navegador aceptados: solo páginas servidas desde el propio equipo.
+const LOCAL_ORIGIN_HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "[::1]"];
+
+/// Cola de tramas de un observador, vaciada por su propio hilo de conexión.
+type ClientQueue = SyncSender<Arc<Vec<u8>>>;
+
+/// Evento publicado a los observadores conectados al flujo WebSocket.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    ChatMessage {
+        index: usize,
+        sender: String,
+        text: String,
+        timestamp: String,
+        provider: Option<String>,
+    },
+    WorkflowStatus {
+        workflow_id: u32,
+        name: String,
+        status: String,
+    },
+    Log {
+        status: String,
+        source: String,
+        message: String,
+        timestamp: String,
+    },
+    ModelInstall {
+        provider: String,
+        model_id: String,
+        stage: String,
+        message: String,
+    },
+    ModelDownloadProgress {
+        provider: String,
+        model_id: String,
+        downloaded: u64,
+        total: u64,
+        bytes_per_second: u64,
+    },
+    FileChange {
+        path: String,
+        change: String,
+        timestamp: String,
+    },
+    MqttMessage {
+        topic: String,
+        payload: String,
+        timestamp: String,
+    },
+}
+
+/// Servidor WebSocket mínimo que solo envía: acepta conexiones y difunde eventos JSON.
+///
+/// Cada observador tiene su propio hilo y una cola acotada, así que uno lento no frena a
+/// los demás ni retiene la lista de conexiones: si su cola se llena, se desconecta.
+pub struct EventStreamServer {
+    address: SocketAddr,
+    clients: Arc<Mutex<Vec<ClientQueue>>>,
+    stop: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+impl EventStreamServer {
+    /// Escucha en `bind_address`. Con `token` no vacío solo se aceptan los observadores
+    /// que lo presentan, en `?token=` o en la cabecera `Authorization: Bearer`.
+    pub fn start(bind_address: &str, token: &str) -> Result<Self> {
+        let listener = TcpListener::bind(bind_address.trim())
+            .with_context(|| format!("No se pudo escuchar en {}", bind_address.trim()))?;
+        listener
+            .set_nonblocking(true)
+            .context("No se pudo configurar el socket del flujo de eventos")?;
+        let address = listener.local_addr()?;
+
+        let clients: Arc<Mutex<Vec<ClientQueue>>> = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        // Su handle se espera al detener el servidor para liberar el puerto antes de
+        // volver a escuchar.
+        let accept_thread = {
+            let clients = Arc::clone(&clients);
+            let stop = Arc::clone(&stop);
+            let token = token.trim().to_string();
+            runtime::spawn_service("event-stream", move || {
+                accept_loop(listener, clients, stop, token)
+            })
+            .context("No se pudo arrancar el flujo de eventos")?
+        };
+
+        Ok(Self {
+            address,
+            clients,
+            stop,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients
+            .lock()
+            .map(|clients| clients.len())
+            .unwrap_or(0)
+    }
+
+    pub fn publish(&self, event: &StreamEvent) {
+        let Ok(payload) = serde_json::to_string(event) else {
+            return;
+        };
+        let frame = Arc::new(text_frame(&payload));
+        if let Ok(mut clients) = self.clients.lock() {
+            clients.retain(|client| client.try_send(Arc::clone(&frame)).is_ok());
+        }
+    }
+}
+
+impl Drop for EventStreamServer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.accept_thread.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Estado del flujo de eventos y de lo ya publicado para no repetir eventos.
+#[derive(Default)]
+pub struct EventStreamState {
+    pub server: Option<EventStreamServer>,
+    pub status: Option<String>,
+    published_messages: usize,
+    workflow_statuses: HashMap<u32, String>,
+}
+
+impl EventStreamState {
+    /// Arranca o detiene el servidor según la configuración indicada. El servidor anterior
+    /// se detiene del todo, con su puerto liberado, antes de arrancar el nuevo.
+    pub fn configure(&mut self, settings: &EventStreamConfig) -> Result<()> {
+        self.server = None;
+        if !settings.enabled {
+            self.status = Some("Flujo de eventos detenido.".to_string());
+            return Ok(());
+        }
+
+        match EventStreamServer::start(&settings.bind_address, &settings.token) {
+            Ok(server) => {
+                self.status = Some(format!(
+                    "Escuchando en ws://{} desde las {}.",
+                    server.address(),
+                    Local::now().format("%H:%M:%S")
+                ));
+                self.server = Some(server);
+                Ok(())
+            }
+            Err(err) => {
+                self.status = Some(err.to_string());
+                Err(err)
+            }
+        }
+    }
+
+    /// Da por publicado el estado actual para que los observadores solo reciban cambios.
+    pub(crate) fn mark_published<'a>(
+        &mut self,
+        total_messages: usize,
+        workflows: impl Iterator<Item = (u32, &'a str)>,
+    ) {
+        self.published_messages = total_messages;
+        self.workflow_statuses = workflows
+            .map(|(id, status)| (id, status.to_string()))
+            .collect();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.server.is_some()
+    }
+
+    pub fn publish(&self, event: StreamEvent) {
+        if let Some(server) = &self.server {
+            server.publish(&event);
+        }
+    }
+
+    /// Devuelve el rango de mensajes del chat aún no publicados. Un mensaje pendiente
+    /// detiene el avance hasta que llegue su respuesta definitiva.
+    pub(crate) fn take_unpublished_messages(
+        &mut self,
+        total: usize,
+        first_pending: Option<usize>,
+    ) -> std::ops::Range<usize> {
+        if self.published_messages > total {
+            self.published_messages = total;
+        }
+        let end = first_pending.unwrap_or(total).max(self.published_messages);
+        let range = self.published_messages..end;
+        self.published_messages = end;
+        range
+    }
+
+    /// Registra el estado de un workflow y devuelve `true` si ha cambiado.
+    pub(crate) fn workflow_status_changed(&mut self, workflow_id: u32, status: &str) -> bool {
+        match self.workflow_statuses.get(&workflow_id) {
+            Some(previous) if previous == status => false,
+            _ => {
+                self.workflow_statuses
+                    .insert(workflow_id, status.to_string());
+                true
+            }
+        }
+    }
+}
+
+fn accept_loop(
+    listener: TcpListener,
+    clients: Arc<Mutex<Vec<ClientQueue>>>,
+    stop: Arc<AtomicBool>,
+    token: String,
+) {
+    while !stop.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let clients = Arc::clone(&clients);
+                let stop = Arc::clone(&stop);
+                let token = token.clone();
+                // El hilo de cada conexión hace el handshake y después le escribe sus tramas.
+                let _ = runtime::spawn_service("event-stream-client", move || {
+                    let Ok(stream) = accept_websocket(stream, &token) else {
+                        return;
+                    };
+                    let (queue, frames) = mpsc::sync_channel(CLIENT_QUEUE_LEN);
+                    match clients.lock() {
+                        Ok(mut clients) if !stop.load(Ordering::Relaxed) => clients.push(queue),
+                        _ => return,
+                    }
+                    serve_client(stream, frames);
+                });
+            }
+            // `WouldBlock` mientras no hay conexiones pendientes.
+            Err(_) => thread::sleep(ACCEPT_POLL_INTERVAL),
+        }
+    }
+
+    // Al soltar las colas, cada conexión envía la trama de cierre y termina.
+    if let Ok(mut clients) = clients.lock() {
+        clients.clear();
+    }
+}
+
+/// Escribe las tramas de un observador hasta que falla la conexión o se cierra su cola.
+fn serve_client(mut stream: TcpStream, frames: mpsc::Receiver<Arc<Vec<u8>>>) {
+    for frame in frames {
+        if stream.write_all(&frame).is_err() {
+            return;
+        }
+    }
+    let _ = stream.write_all(&[0x88, 0x00]);
+}
+
+fn accept_websocket(mut stream: TcpStream, token: &str) -> Result<TcpStream> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 || request.len() + read > MAX_HANDSHAKE_BYTES {
+            return Err(anyhow!("Solicitud de conexión incompleta"));
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    if let Err(err) = authorize(&request, token) {
+        let _ = stream.write_all(b"HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\n\r\n");
+        return Err(err);
+    }
+
+    let Some(key) = header(&request, "sec-websocket-key") else {
+        let _ = stream.write_all(
+            b"HTTP/1.1 426 Upgrade Required\r\nUpgrade: websocket\r\nContent-Length: 0\r\n\r\n",
+        );
+        return Err(anyhow!("La petición no solicita una conexión WebSocket"));
+    };
+
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    );
+    stream.write_all(response.as_bytes())?;
+    stream.set_read_timeout(None)?;
+    Ok(stream)
+}
+
+fn header<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request.lines().skip(1).find_map(|line| {
+        let (header, value) = line.split_once(':')?;
+        header
+            .trim()
+            .eq_ignore_ascii_case(name)
+            .then(|| value.trim())
+    })
+}
+
+/// Rechaza las páginas web de otros orígenes y, si hay token configurado, a quien no
+/// lo presenta. Los clientes que no son navegadores no envían `Origin`.
+fn authorize(request: &str, token: &str) -> Result<()> {
+    if let Some(origin) = header(request, "origin") {
+        if !is_local_origin(origin) {
+            bail!("Origen no permitido: {}", origin);
+        }
+    }
+    if token.is_empty() {
+        return Ok(());
+    }
+    let bearer = header(request, "authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim);
+    let query = request
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|target| target.split_once('?'))
+        .and_then(|(_, query)| {
+            query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("token="))
+        });
+    if bearer == Some(token) || query == Some(token) {
+        Ok(())
+    } else {
+        bail!("Token del flujo de eventos ausente o incorrecto")
+    }
+}
+
+fn is_local_origin(origin: &str) -> bool {
+    let Some((_, authority)) = origin.split_once("://") else {
+        return false;
+    };
+    let authority = authority.split('/').next().unwrap_or_default();
+    let host = match authority.find(']') {
+        Some(end) => &authority[..=end],
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    LOCAL_ORIGIN_HOSTS
+        .iter()
+        .any(|local| host.eq_ignore_ascii_case(local))
+}
+
+fn accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.as_bytes());
+    hasher.update(WEBSOCKET_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(hasher.finalize())
+}
+
+/// Trama de texto sin máscara, como exige RFC 6455 para los mensajes del servidor.
+fn text_frame(payload: &str) -> Vec<u8> {
+    let bytes = payload.as_bytes();
+    let mut frame = Vec::with_capacity(bytes.len() + 10);
+    frame.push(0x81);
+    match bytes.len() {
+        len if len < 126 => frame.push(len as u8),
+        len if len <= u16::MAX as usize => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(bytes);
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_the_rfc_sample() {
+        assert_eq!(
+            accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn text_frame_encodes_each_length_form() {
+        let short = text_frame(&"a".repeat(125));
+        assert_eq!(&short[..2], &[0x81, 125]);
+        assert_eq!(short.len(), 2 + 125);
+
+        let medium = text_frame(&"a".repeat(126));
+        assert_eq!(&medium[..4], &[0x81, 126, 0x00, 126]);
+        assert_eq!(medium.len(), 4 + 126);
+
+        let long = text_frame(&"a".repeat(65536));
+        assert_eq!(&long[..2], &[0x81, 127]);
+        assert_eq!(&long[2..10], &65536u64.to_be_bytes());
+        assert_eq!(long.len(), 10 + 65536);
+    }
+
+    #[test]
+    fn handshake_checks_origin_and_token() {
+        let request = |target: &str, extra: &str| {
+            format!(
+                "GET {} HTTP/1.1\r\nHost: 127.0.0.1:8765\r\nSec-WebSocket-Key: x\r\n{}\r\n",
+                target, extra
+            )
+        };
+        assert!(authorize(&request("/", ""), "").is_ok());
+        assert!(authorize(&request("/", "Origin: http://localhost:3000\r\n"), "").is_ok());
+        assert!(authorize(&request("/", "Origin: http://[::1]:3000\r\n"), "").is_ok());
+        assert!(authorize(&request("/", "Origin: https://evil.example\r\n"), "").is_err());
+        assert!(authorize(
+            &request("/", "Origin: http://localhost.evil.example\r\n"),
+            ""
+        )
+        .is_err());
+        assert!(authorize(&request("/", "Origin: null\r\n"), "").is_err());
+
+        assert!(authorize(&request("/", ""), "secreto").is_err());
+        assert!(authorize(&request("/?token=otro", ""), "secreto").is_err());
+        assert!(authorize(&request("/?v=1&token=secreto", ""), "secreto").is_ok());
+        assert!(authorize(
+            &request("/", "Authorization: Bearer secreto\r\n"),
+            "secreto"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn restarting_rebinds_the_same_port() {
+        let first = EventStreamServer::start("127.0.0.1:0", "").unwrap();
+        let address = first.address().to_string();
+        drop(first);
+        let second = EventStreamServer::start(&address, "").unwrap();
+        assert_eq!(second.address().to_string(), address);
+    }
+}
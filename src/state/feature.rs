@@ -1,21 +1,33 @@
 use std::collections::HashMap;
+use std::rc::Rc;
 
-use super::{CustomCommandAction, MainView, NavigationRegistry};
+use super::{Command, CustomCommandAction, MainView, NavigationRegistry};
 use crate::ui::workbench::WorkbenchView;
 
 /// Registra comandos personalizados aportados por los módulos de estado.
 #[derive(Default)]
 pub struct CommandRegistry {
     actions: Vec<CustomCommandAction>,
+    /// Comandos de chat propios del módulo; pasan al `CommandEngine` al reconstruirse.
+    commands: Vec<Rc<dyn Command>>,
 }
 
 impl CommandRegistry {
     pub fn new() -> Self {
         Self {
             actions: Vec::new(),
+            commands: Vec::new(),
         }
     }
 
+    pub fn register_command(&mut self, command: impl Command + 'static) {
+        self.commands.push(Rc::new(command));
+    }
+
+    pub(crate) fn take_commands(&mut self) -> Vec<Rc<dyn Command>> {
+        std::mem::take(&mut self.commands)
+    }
+
     pub fn extend(&mut self, actions: impl IntoIterator<Item = CustomCommandAction>) {
         for action in actions {
             if !self.actions.contains(&action) {
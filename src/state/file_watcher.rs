@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+use super::runtime::{self, UiSender};
+use crate::config::WatchedPath;
+
+/// Cambios recientes que se conservan para mostrarlos en el panel.
+pub const RECENT_FILE_CHANGES: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+impl FileChangeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            FileChangeKind::Created => "Creado",
+            FileChangeKind::Modified => "Modificado",
+            FileChangeKind::Removed => "Eliminado",
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            FileChangeKind::Created => "created",
+            FileChangeKind::Modified => "modified",
+            FileChangeKind::Removed => "removed",
+        }
+    }
+
+    fn from_notify(kind: &EventKind) -> Option<Self> {
+        match kind {
+            EventKind::Create(_) => Some(FileChangeKind::Created),
+            EventKind::Modify(_) => Some(FileChangeKind::Modified),
+            EventKind::Remove(_) => Some(FileChangeKind::Removed),
+            _ => None,
+        }
+    }
+
+    /// Une dos cambios del mismo fichero dentro de la ventana de agrupación.
+    fn merge(self, next: Self) -> Self {
+        match (self, next) {
+            (FileChangeKind::Created, FileChangeKind::Modified) => FileChangeKind::Created,
+            (_, next) => next,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FileChangeEvent {
+    pub path: PathBuf,
+    pub kind: FileChangeKind,
+    pub timestamp: String,
+}
+
+/// Vigilante basado en `notify`: filtra los eventos del sistema con el patrón de cada
+/// ruta y los agrupa por fichero para que un guardado produzca un único cambio.
+pub struct FileWatcher {
+    events_rx: Receiver<FileChangeEvent>,
+    // Al soltarse cierra el canal de eventos crudos y el hilo de agrupación termina.
+    _watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    pub fn start(watches: Vec<WatchedPath>, debounce: Duration) -> Result<Self> {
+        if watches.is_empty() {
+            bail!("No hay rutas vigiladas");
+        }
+
+        let roots: Vec<(PathBuf, String)> = watches
+            .iter()
+            .map(|watch| (PathBuf::from(watch.path.trim()), watch.pattern.clone()))
+            .collect();
+        let (raw_tx, raw_rx) = mpsc::channel::<(PathBuf, FileChangeKind)>();
+        let filter_roots = roots.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                let Ok(event) = result else {
+                    return;
+                };
+                let Some(kind) = FileChangeKind::from_notify(&event.kind) else {
+                    return;
+                };
+                for path in event.paths {
+                    if matches_watch(&filter_roots, &path) {
+                        let _ = raw_tx.send((path, kind));
+                    }
+                }
+            })
+            .context("No se pudo crear el vigilante de ficheros")?;
+
+        for (root, _) in &roots {
+            if !root.exists() {
+                bail!("La ruta {} no existe", root.display());
+            }
+            watcher
+                .watch(root, RecursiveMode::Recursive)
+                .with_context(|| format!("No se pudo vigilar {}", root.display()))?;
+        }
+
+        let (events_tx, events_rx) = runtime::ui_channel();
+        runtime::spawn_service("file-watcher", move || {
+            debounce_loop(raw_rx, events_tx, debounce)
+        })
+        .context("No se pudo arrancar el vigilante de ficheros")?;
+        Ok(Self {
+            events_rx,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn try_recv(&self) -> Option<FileChangeEvent> {
+        self.events_rx.try_recv().ok()
+    }
+}
+
+fn matches_watch(roots: &[(PathBuf, String)], path: &Path) -> bool {
+    roots.iter().any(|(root, pattern)| {
+        path.strip_prefix(root)
+            .map(|relative| glob_match(pattern, &relative.to_string_lossy()))
+            .unwrap_or(false)
+    })
+}
+
+/// Retiene cada fichero hasta que pasa `debounce` sin nuevos eventos y entonces lo emite.
+fn debounce_loop(
+    raw_rx: Receiver<(PathBuf, FileChangeKind)>,
+    events_tx: UiSender<FileChangeEvent>,
+    debounce: Duration,
+) {
+    let mut pending: HashMap<PathBuf, (FileChangeKind, Instant)> = HashMap::new();
+    loop {
+        match raw_rx.recv_timeout(debounce) {
+            Ok((path, kind)) => {
+                let entry = pending.entry(path).or_insert((kind, Instant::now()));
+                *entry = (entry.0.merge(kind), Instant::now());
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let ready: Vec<PathBuf> = pending
+            .iter()
+            .filter(|(_, (_, seen))| seen.elapsed() >= debounce)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in ready {
+            let Some((kind, _)) = pending.remove(&path) else {
+                continue;
+            };
+            let event = FileChangeEvent {
+                path,
+                kind,
+                timestamp: chrono::Local::now().format("%H:%M:%S").to_string(),
+            };
+            if events_tx.send(event).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Comprueba `path` contra un patrón glob con `*` (sin cruzar `/`), `**` (cualquier
+/// número de directorios) y `?`. Un patrón vacío acepta cualquier fichero y uno sin `/`
+/// se compara solo con el nombre del fichero.
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return true;
+    }
+    let path = path.replace('\\', "/");
+    let target = if pattern.contains('/') {
+        path.as_str()
+    } else {
+        path.rsplit('/').next().unwrap_or(&path)
+    };
+    matches(pattern.as_bytes(), target.as_bytes())
+}
+
+/// Como `glob_match`, pero un patrón relativo con directorios puede encajar con cualquier
+/// tramo final de una ruta absoluta, de modo que `Downloads/*` sirva en cualquier equipo.
+pub fn glob_match_tail(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern.contains('/') && !pattern.starts_with('/') && !pattern.starts_with("**") {
+        glob_match(&format!("**/{}", pattern), path)
+    } else {
+        glob_match(pattern, path)
+    }
+}
+
+fn matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', b'/', rest @ ..] => {
+            matches(rest, text)
+                || text
+                    .iter()
+                    .position(|&byte| byte == b'/')
+                    .is_some_and(|slash| matches(pattern, &text[slash + 1..]))
+        }
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|skip| matches(rest, &text[skip..])),
+        [b'*', rest @ ..] => (0..=text.len())
+            .take_while(|&skip| skip == 0 || text[skip - 1] != b'/')
+            .any(|skip| matches(rest, &text[skip..])),
+        [b'?', rest @ ..] => text
+            .split_first()
+            .is_some_and(|(&byte, tail)| byte != b'/' && matches(rest, tail)),
+        [expected, rest @ ..] => text
+            .split_first()
+            .is_some_and(|(byte, tail)| byte == expected && matches(rest, tail)),
+    }
+}
+
+/// Estado del vigilante de ficheros y formulario para añadir rutas.
+#[derive(Default)]
+pub struct FileWatcherState {
+    pub watcher: Option<FileWatcher>,
+    pub draft: WatchedPath,
+    /// Últimos cambios detectados, del más reciente al más antiguo.
+    pub recent: Vec<FileChangeEvent>,
+    pub status: Option<String>,
+}
+
+impl FileWatcherState {
+    pub fn is_running(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    pub(crate) fn record(&mut self, event: FileChangeEvent) {
+        self.recent.insert(0, event);
+        self.recent.truncate(RECENT_FILE_CHANGES);
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::Receiver;
+
+/// Caracteres de la pregunta y de la respuesta que acompañan a la petición.
+const CONTEXT_CHARS: usize = 1500;
+/// Las sugerencias más largas no caben en un chip y se descartan.
+const MAX_SUGGESTION_CHARS: usize = 140;
+/// Tokens de respuesta que se piden al proveedor: bastan para tres preguntas cortas.
+pub const MAX_TOKENS: u32 = 200;
+pub const MAX_SUGGESTIONS: usize = 3;
+
+/// Identifica una respuesta por su posición y su texto, para pedir sugerencias una sola vez.
+pub fn reply_key(index: usize, text: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    index.hash(&mut hasher);
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn tail(text: &str) -> String {
+    let count = text.chars().count();
+    text.chars()
+        .skip(count.saturating_sub(CONTEXT_CHARS))
+        .collect()
+}
+
+pub fn build_prompt(question: Option<&str>, reply: &str, count: usize) -> String {
+    let mut prompt = String::new();
+    if let Some(question) = question.filter(|question| !question.trim().is_empty()) {
+        prompt.push_str(&format!(
+            "Pregunta del usuario:\n{}\n\n",
+            tail(question.trim())
+        ));
+    }
+    prompt.push_str(&format!(
+        "Respuesta del asistente:\n{}\n\nPropón {} preguntas breves que el usuario podría hacer a continuación, en su mismo idioma. Escribe una por línea, sin numerarlas ni añadir nada más.",
+        tail(reply.trim()),
+        count
+    ));
+    prompt
+}
+
+/// Sugerencias de la respuesta del modelo, sin viñetas ni numeración ni repetidas.
+pub fn parse_suggestions(text: &str, count: usize) -> Vec<String> {
+    let mut suggestions: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let suggestion = line
+            .trim()
+            .trim_start_matches(|ch: char| {
+                ch.is_ascii_digit() || matches!(ch, '-' | '*' | '•' | '.' | ')' | ' ')
+            })
+            .trim_matches(|ch: char| matches!(ch, '"' | '“' | '”' | '«' | '»' | '`'))
+            .trim();
+        if suggestion.is_empty()
+            || suggestion.ends_with(':')
+            || suggestion.chars().count() > MAX_SUGGESTION_CHARS
+            || suggestions
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(suggestion))
+        {
+            continue;
+        }
+        suggestions.push(suggestion.to_string());
+        if suggestions.len() == count {
+            break;
+        }
+    }
+    suggestions
+}
+
+/// Sugerencias de la última respuesta del chat.
+#[derive(Default)]
+pub struct FollowUpState {
+    /// Respuesta para la que se pidieron, según `reply_key`.
+    pub key: Option<u64>,
+    pub message_index: Option<usize>,
+    pub suggestions: Vec<String>,
+    pub rx: Option<Receiver<Result<String, String>>>,
+}
+
+impl FollowUpState {
+    pub fn is_loading(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_one_suggestion_per_line_without_markers() {
+        let reply = "Aquí tienes:\n1. ¿Cómo lo despliego?\n- \"¿Y si falla la caché?\"\n\n2) ¿cómo lo despliego?\n• ¿Hay alternativas?\n¿Algo más?";
+        assert_eq!(
+            parse_suggestions(reply, 3),
+            vec![
+                "¿Cómo lo despliego?",
+                "¿Y si falla la caché?",
+                "¿Hay alternativas?"
+            ]
+        );
+        assert!(parse_suggestions(&"x".repeat(200), 3).is_empty());
+
+        let prompt = build_prompt(Some("¿Qué es Rust?"), "Un lenguaje.", 2);
+        assert!(prompt.starts_with("Pregunta del usuario:\n¿Qué es Rust?"));
+        assert!(prompt.contains("Propón 2 preguntas"));
+        assert_ne!(reply_key(3, "Un lenguaje."), reply_key(4, "Un lenguaje."));
+    }
+}
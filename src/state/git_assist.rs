@@ -0,0 +1,900 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use base64::Engine;
+use chrono::Local;
+
+/// Caracteres del diff que se envían como mucho; el resto se resume con una nota.
+const MAX_DIFF_CHARS: usize = 60_000;
+/// Commits que se listan en el historial de un archivo.
+pub const FILE_HISTORY_LIMIT: usize = 30;
+/// Commits entrantes que se listan para la rama actual tras un fetch.
+const INCOMING_LIMIT: usize = 10;
+/// Bytes iniciales en los que se buscan bytes nulos para decidir si un archivo es binario,
+/// el mismo criterio que usa git.
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Texto que se pide al modelo a partir de un diff.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffDocument {
+    CommitMessage,
+    PullRequest,
+}
+
+impl DiffDocument {
+    pub fn label(self) -> &'static str {
+        match self {
+            DiffDocument::CommitMessage => "Mensaje de commit",
+            DiffDocument::PullRequest => "Descripción de PR",
+        }
+    }
+
+    fn instructions(self) -> &'static str {
+        match self {
+            DiffDocument::CommitMessage => {
+                "Escribe un mensaje de commit para estos cambios: una línea de resumen en imperativo de 72 caracteres como máximo, una línea en blanco y, si hace falta, un cuerpo breve que explique el porqué. Responde solo con el mensaje."
+            }
+            DiffDocument::PullRequest => {
+                "Escribe la descripción de una pull request para estos cambios en Markdown: un título, un resumen de qué cambia y por qué, una lista de los cambios principales y cómo probarlos. No inventes nada que no aparezca en el diff."
+            }
+        }
+    }
+}
+
+/// Parte del árbol de trabajo cuyo diff se envía.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffScope {
+    File { path: PathBuf, relative: String },
+    WorkingTree,
+}
+
+impl DiffScope {
+    pub fn label(&self) -> String {
+        match self {
+            DiffScope::File { relative, .. } => relative.clone(),
+            DiffScope::WorkingTree => "todo el árbol de trabajo".to_string(),
+        }
+    }
+}
+
+/// Cambios respecto a `HEAD`, preparados o no, del repositorio que contiene `root`. Los
+/// archivos sin seguimiento no aparecen hasta que se añaden con `git add`.
+pub fn collect_diff(root: &Path, scope: &DiffScope) -> Result<String> {
+    let mut command = Command::new("git");
+    command.arg("-C").arg(root).args(["diff", "HEAD", "--"]);
+    if let DiffScope::File { path, .. } = scope {
+        command.arg(path);
+    }
+    let output = command.output().context("No se pudo ejecutar git")?;
+    if !output.status.success() {
+        bail!(
+            "git diff falló: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let diff = String::from_utf8_lossy(&output.stdout).to_string();
+    if diff.trim().is_empty() {
+        bail!("No hay cambios en {}.", scope.label());
+    }
+    Ok(diff)
+}
+
+pub fn build_prompt(document: DiffDocument, scope: &DiffScope, diff: &str) -> String {
+    let mut body: String = diff.chars().take(MAX_DIFF_CHARS).collect();
+    if body.len() < diff.len() {
+        body.push_str(&format!(
+            "\n[… diff recortado: faltan {} caracteres]",
+            diff.chars().count() - MAX_DIFF_CHARS
+        ));
+    }
+    format!(
+        "{}\n\nCambios en {}:\n```diff\n{}\n```",
+        document.instructions(),
+        scope.label(),
+        body.trim_end()
+    )
+}
+
+/// Prompt para explicar el cambio que hizo el commit `hash` en `relative`.
+pub fn build_explain_prompt(relative: &str, hash: &str, diff: &str) -> String {
+    let body: String = diff.chars().take(MAX_DIFF_CHARS).collect();
+    format!(
+        "Explica qué cambió el commit {} en {} y por qué parece haberse hecho. Señala si introduce algún riesgo.\n\n```diff\n{}\n```",
+        hash,
+        relative,
+        body.trim_end()
+    )
+}
+
+/// Autoría de una línea según `git blame`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlameLine {
+    pub line: usize,
+    pub commit: String,
+    pub author: String,
+    /// Fecha del commit en formato `YYYY-MM-DD`.
+    pub date: String,
+    pub summary: String,
+}
+
+/// Commit que modificó un archivo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileCommit {
+    pub hash: String,
+    pub author: String,
+    pub date: String,
+    pub subject: String,
+}
+
+impl FileCommit {
+    pub fn short_hash(&self) -> &str {
+        &self.hash[..self.hash.len().min(8)]
+    }
+}
+
+fn run_git(root: &Path, args: &[&str], path: &Path) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .arg(path)
+        .output()
+        .context("No se pudo ejecutar git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} falló: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Autoría de cada línea de `path`, en orden.
+pub fn git_blame(root: &Path, path: &Path) -> Result<Vec<BlameLine>> {
+    let output = run_git(root, &["blame", "--line-porcelain", "--"], path)?;
+    Ok(parse_blame(&output))
+}
+
+/// Últimos `limit` commits que tocaron `path`, del más reciente al más antiguo.
+pub fn git_file_history(root: &Path, path: &Path, limit: usize) -> Result<Vec<FileCommit>> {
+    let limit = format!("-n{}", limit);
+    let output = run_git(
+        root,
+        &[
+            "log",
+            &limit,
+            "--follow",
+            "--date=short",
+            "--format=%H%x1f%an%x1f%ad%x1f%s",
+            "--",
+        ],
+        path,
+    )?;
+    Ok(parse_commits(&output))
+}
+
+/// Commits de una salida de `git log` con `--format=%H%x1f%an%x1f%ad%x1f%s`.
+fn parse_commits(output: &str) -> Vec<FileCommit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\u{1f}');
+            Some(FileCommit {
+                hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                subject: fields.next().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Diff con el que un commit cambió `path`.
+pub fn commit_diff(root: &Path, hash: &str, path: &Path) -> Result<String> {
+    run_git(
+        root,
+        &[
+            "show",
+            "--format=%an, %ad%n%n%B",
+            "--date=short",
+            hash,
+            "--",
+        ],
+        path,
+    )
+}
+
+/// Interpreta la salida de `git blame --line-porcelain`: una cabecera por línea con el
+/// commit y el número de línea, los metadatos del commit y la línea precedida de tabulador.
+pub fn parse_blame(output: &str) -> Vec<BlameLine> {
+    let mut lines = Vec::new();
+    let mut current: Option<BlameLine> = None;
+    for row in output.lines() {
+        if row.starts_with('\t') {
+            if let Some(line) = current.take() {
+                lines.push(line);
+            }
+            continue;
+        }
+        let Some(entry) = current.as_mut() else {
+            let mut fields = row.split_whitespace();
+            let commit = fields.next().unwrap_or_default();
+            let line = fields.nth(1).and_then(|value| value.parse().ok());
+            if let (true, Some(line)) = (commit.len() == 40, line) {
+                current = Some(BlameLine {
+                    line,
+                    commit: commit.to_string(),
+                    author: String::new(),
+                    date: String::new(),
+                    summary: String::new(),
+                });
+            }
+            continue;
+        };
+        if let Some(author) = row.strip_prefix("author ") {
+            entry.author = author.to_string();
+        } else if let Some(time) = row.strip_prefix("author-time ") {
+            entry.date = time
+                .parse::<i64>()
+                .ok()
+                .and_then(|seconds| chrono::DateTime::from_timestamp(seconds, 0))
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+        } else if let Some(summary) = row.strip_prefix("summary ") {
+            entry.summary = summary.to_string();
+        }
+    }
+    lines
+}
+
+/// Agrupa líneas consecutivas del mismo commit como `10-14 · autor · fecha · resumen`.
+pub fn summarize_blame(lines: &[BlameLine]) -> Vec<String> {
+    let mut groups: Vec<(usize, usize, &BlameLine)> = Vec::new();
+    for line in lines {
+        match groups.last_mut() {
+            Some((_, end, first)) if first.commit == line.commit && *end + 1 == line.line => {
+                *end = line.line;
+            }
+            _ => groups.push((line.line, line.line, line)),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(start, end, line)| {
+            let range = if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            };
+            format!(
+                "{} · {} · {} · {} ({})",
+                range,
+                line.author,
+                line.date,
+                line.summary,
+                &line.commit[..8]
+            )
+        })
+        .collect()
+}
+
+/// Codificación con la que se decodificó un archivo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileEncoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// Bytes que no son UTF-8 válido, leídos como Latin-1 para no perder el contenido.
+    Latin1,
+}
+
+impl FileEncoding {
+    pub fn label(self) -> &'static str {
+        match self {
+            FileEncoding::Utf8 => "UTF-8",
+            FileEncoding::Utf16Le => "UTF-16 LE",
+            FileEncoding::Utf16Be => "UTF-16 BE",
+            FileEncoding::Latin1 => "Latin-1",
+        }
+    }
+}
+
+/// Contenido de un archivo del repositorio leído con límite de tamaño.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileContent {
+    pub path: PathBuf,
+    /// Tamaño completo del archivo en disco.
+    pub total_bytes: u64,
+    /// `true` si solo se leyeron los primeros `max_bytes`.
+    pub truncated: bool,
+    /// `None` en los archivos binarios, cuyo contenido no se devuelve.
+    pub encoding: Option<FileEncoding>,
+    pub text: String,
+}
+
+impl FileContent {
+    pub fn is_binary(&self) -> bool {
+        self.encoding.is_none()
+    }
+}
+
+/// Lee `relative` dentro de `root` sin pasar de `max_bytes`. Rechaza rutas que salen del
+/// repositorio, detecta binarios y decodifica UTF-8, UTF-16 con BOM o, en último caso,
+/// Latin-1.
+pub fn git_read_file(root: &Path, relative: &Path, max_bytes: usize) -> Result<FileContent> {
+    let root = root
+        .canonicalize()
+        .with_context(|| format!("No se pudo abrir {}", root.display()))?;
+    let path = root
+        .join(relative)
+        .canonicalize()
+        .with_context(|| format!("No se pudo abrir {}", relative.display()))?;
+    if !path.starts_with(&root) {
+        bail!("{} está fuera del repositorio", relative.display());
+    }
+    let file = File::open(&path).with_context(|| format!("No se pudo abrir {}", path.display()))?;
+    let total_bytes = file.metadata()?.len();
+    let mut bytes = Vec::new();
+    file.take(max_bytes as u64)
+        .read_to_end(&mut bytes)
+        .with_context(|| format!("No se pudo leer {}", path.display()))?;
+    let truncated = (bytes.len() as u64) < total_bytes;
+    let (encoding, text) = match decode(&bytes, truncated) {
+        Some((encoding, text)) => (Some(encoding), text),
+        None => (None, String::new()),
+    };
+    Ok(FileContent {
+        path,
+        total_bytes,
+        truncated,
+        encoding,
+        text,
+    })
+}
+
+/// Decodifica `bytes`; `None` si parecen binarios. Con `truncated`, un carácter multibyte
+/// cortado al final se descarta en lugar de tratarse como error.
+pub fn decode(bytes: &[u8], truncated: bool) -> Option<(FileEncoding, String)> {
+    let utf16 = |bytes: &[u8], encoding: FileEncoding| {
+        let units: Vec<u16> = bytes
+            .chunks_exact(2)
+            .map(|pair| match encoding {
+                FileEncoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+                _ => u16::from_le_bytes([pair[0], pair[1]]),
+            })
+            .collect();
+        Some((encoding, String::from_utf16_lossy(&units)))
+    };
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return utf16(rest, FileEncoding::Utf16Le);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return utf16(rest, FileEncoding::Utf16Be);
+    }
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return None;
+    }
+    let bytes = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some((FileEncoding::Utf8, text.to_string())),
+        Err(err) if truncated && err.error_len().is_none() => Some((
+            FileEncoding::Utf8,
+            String::from_utf8_lossy(&bytes[..err.valid_up_to()]).to_string(),
+        )),
+        Err(_) => Some((
+            FileEncoding::Latin1,
+            bytes.iter().map(|byte| char::from(*byte)).collect(),
+        )),
+    }
+}
+
+/// Bloque en conflicto delimitado por `<<<<<<<`, `=======` y `>>>>>>>`, con la base
+/// entre `|||||||` y `=======` si el repositorio usa el estilo `diff3`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConflictHunk {
+    /// Línea del marcador `<<<<<<<`, empezando en 1.
+    pub start_line: usize,
+    pub ours_label: String,
+    pub ours: String,
+    pub base: Option<String>,
+    pub theirs_label: String,
+    pub theirs: String,
+}
+
+/// Archivo con conflictos de fusión sin resolver.
+#[derive(Clone, Debug)]
+pub struct ConflictFile {
+    pub path: PathBuf,
+    pub relative: String,
+    pub hunks: Vec<ConflictHunk>,
+}
+
+/// Versión elegida para un bloque en conflicto.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HunkResolution {
+    Ours,
+    Theirs,
+    /// Las dos versiones, primero la nuestra.
+    Both,
+    /// Texto fusionado, propuesto por el modelo o escrito a mano.
+    Merged(String),
+}
+
+impl HunkResolution {
+    fn apply(&self, hunk: &ConflictHunk) -> String {
+        match self {
+            HunkResolution::Ours => hunk.ours.clone(),
+            HunkResolution::Theirs => hunk.theirs.clone(),
+            HunkResolution::Both => format!("{}{}", hunk.ours, hunk.theirs),
+            HunkResolution::Merged(text) if text.is_empty() || text.ends_with('\n') => text.clone(),
+            HunkResolution::Merged(text) => format!("{}\n", text),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ConflictSection {
+    Ours,
+    Base,
+    Theirs,
+}
+
+/// Recorre `text` y llama a `on_hunk` con cada bloque en conflicto y sus líneas originales;
+/// lo que devuelve sustituye al bloque. Las líneas fuera de conflicto se copian tal cual.
+fn rewrite_conflicts(
+    text: &str,
+    mut on_hunk: impl FnMut(usize, ConflictHunk, &str) -> String,
+) -> (String, usize) {
+    let mut output = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut raw = String::new();
+    let mut current: Option<(ConflictHunk, ConflictSection)> = None;
+    for (index, line) in text.split_inclusive('\n').enumerate() {
+        let marker = line.trim_end_matches(&['\r', '\n'][..]);
+        let Some((hunk, section)) = current.as_mut() else {
+            if let Some(label) = marker.strip_prefix("<<<<<<<") {
+                raw.push_str(line);
+                current = Some((
+                    ConflictHunk {
+                        start_line: index + 1,
+                        ours_label: label.trim().to_string(),
+                        ours: String::new(),
+                        base: None,
+                        theirs_label: String::new(),
+                        theirs: String::new(),
+                    },
+                    ConflictSection::Ours,
+                ));
+            } else {
+                output.push_str(line);
+            }
+            continue;
+        };
+        raw.push_str(line);
+        if marker.starts_with("|||||||") && *section == ConflictSection::Ours {
+            hunk.base = Some(String::new());
+            *section = ConflictSection::Base;
+        } else if marker == "=======" && *section != ConflictSection::Theirs {
+            *section = ConflictSection::Theirs;
+        } else if let Some(label) = marker
+            .strip_prefix(">>>>>>>")
+            .filter(|_| *section == ConflictSection::Theirs)
+        {
+            let (mut hunk, _) = current.take().expect("bloque en curso");
+            hunk.theirs_label = label.trim().to_string();
+            output.push_str(&on_hunk(count, hunk, &raw));
+            raw.clear();
+            count += 1;
+        } else {
+            match section {
+                ConflictSection::Ours => hunk.ours.push_str(line),
+                ConflictSection::Base => hunk.base.get_or_insert_with(String::new).push_str(line),
+                ConflictSection::Theirs => hunk.theirs.push_str(line),
+            }
+        }
+    }
+    // Un bloque sin cerrar no es un conflicto válido: se deja como estaba.
+    output.push_str(&raw);
+    (output, count)
+}
+
+pub fn parse_conflicts(text: &str) -> Vec<ConflictHunk> {
+    let mut hunks = Vec::new();
+    rewrite_conflicts(text, |_, hunk, raw| {
+        hunks.push(hunk);
+        raw.to_string()
+    });
+    hunks
+}
+
+/// Sustituye los bloques con resolución por el texto elegido; los demás se conservan con
+/// sus marcadores.
+pub fn resolve_conflicts(text: &str, resolutions: &BTreeMap<usize, HunkResolution>) -> String {
+    rewrite_conflicts(text, |index, hunk, raw| match resolutions.get(&index) {
+        Some(resolution) => resolution.apply(&hunk),
+        None => raw.to_string(),
+    })
+    .0
+}
+
+/// Archivos con conflictos sin resolver bajo `root`, con sus bloques.
+pub fn list_conflicts(root: &Path) -> Result<Vec<ConflictFile>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(["diff", "--name-only", "--diff-filter=U", "--relative"])
+        .output()
+        .context("No se pudo ejecutar git")?;
+    if !output.status.success() {
+        bail!(
+            "git diff falló: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let mut files = Vec::new();
+    for relative in String::from_utf8_lossy(&output.stdout).lines() {
+        let path = root.join(relative);
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("No se pudo leer {}", path.display()))?;
+        files.push(ConflictFile {
+            path,
+            relative: relative.to_string(),
+            hunks: parse_conflicts(&text),
+        });
+    }
+    Ok(files)
+}
+
+/// Prompt con las dos versiones de un bloque (y la base, si existe) para que el modelo
+/// proponga una fusión.
+pub fn build_merge_prompt(relative: &str, hunk: &ConflictHunk) -> String {
+    let mut prompt = format!(
+        "Hay un conflicto de fusión en {} (línea {}). Propón una versión fusionada que conserve la intención de ambos lados. Responde con el resultado en un único bloque de código y después explica brevemente qué has combinado.\n\nNuestra versión ({}):\n```\n{}```\n",
+        relative, hunk.start_line, hunk.ours_label, hunk.ours
+    );
+    if let Some(base) = &hunk.base {
+        prompt.push_str(&format!("\nVersión común de partida:\n```\n{}```\n", base));
+    }
+    prompt.push_str(&format!(
+        "\nSu versión ({}):\n```\n{}```",
+        hunk.theirs_label, hunk.theirs
+    ));
+    prompt
+}
+
+/// Contenido del primer bloque de código de una respuesta.
+pub fn extract_code_block(text: &str) -> Option<String> {
+    let mut lines = text.lines();
+    while let Some(line) = lines.next() {
+        if line.trim_start().starts_with("```") {
+            let body: Vec<&str> = lines
+                .by_ref()
+                .take_while(|line| !line.trim_start().starts_with("```"))
+                .collect();
+            return Some(body.join("\n") + "\n");
+        }
+    }
+    None
+}
+
+/// Conflictos del proyecto abierto en el explorador y resoluciones elegidas para el
+/// archivo seleccionado.
+#[derive(Default)]
+pub struct ConflictState {
+    pub files: Vec<ConflictFile>,
+    pub selected: Option<usize>,
+    /// Resolución elegida por índice de bloque del archivo seleccionado.
+    pub resolutions: BTreeMap<usize, HunkResolution>,
+    /// Fusiones propuestas por el modelo, pendientes de aprobación.
+    pub proposals: BTreeMap<usize, String>,
+    /// Bloque cuya propuesta se espera y mensaje del chat donde llegará.
+    pub awaiting: Option<(usize, usize)>,
+    pub status: Option<String>,
+}
+
+impl ConflictState {
+    pub fn select(&mut self, index: usize) {
+        self.selected = Some(index);
+        self.resolutions.clear();
+        self.proposals.clear();
+        self.awaiting = None;
+    }
+
+    pub fn selected_file(&self) -> Option<&ConflictFile> {
+        self.files.get(self.selected?)
+    }
+}
+
+/// Estado de una rama local frente a la rama remota que sigue.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BranchStatus {
+    pub name: String,
+    /// Rama remota que sigue, p. ej. `origin/main`.
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    /// La rama remota que seguía ya no existe.
+    pub gone: bool,
+    pub current: bool,
+}
+
+impl BranchStatus {
+    pub fn is_diverged(&self) -> bool {
+        self.ahead > 0 && self.behind > 0
+    }
+
+    pub fn summary(&self) -> String {
+        let Some(upstream) = &self.upstream else {
+            return "sin rama remota".to_string();
+        };
+        if self.gone {
+            return format!("{} ya no existe", upstream);
+        }
+        match (self.ahead, self.behind) {
+            (0, 0) => format!("al día con {}", upstream),
+            (ahead, 0) => format!("{} commits por delante de {}", ahead, upstream),
+            (0, behind) => format!("{} commits por detrás de {}", behind, upstream),
+            (ahead, behind) => format!(
+                "divergida de {}: {} por delante, {} por detrás",
+                upstream, ahead, behind
+            ),
+        }
+    }
+}
+
+/// Resultado de traer los remotos sin tocar el árbol de trabajo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FetchStatus {
+    pub branches: Vec<BranchStatus>,
+    /// Commits del remoto que aún no tiene la rama actual, del más reciente al más antiguo.
+    pub incoming: Vec<FileCommit>,
+    /// Ancestro común de la rama actual y su remota cuando han divergido.
+    pub merge_base: Option<String>,
+    pub fetched_at: String,
+}
+
+impl FetchStatus {
+    pub fn current(&self) -> Option<&BranchStatus> {
+        self.branches.iter().find(|branch| branch.current)
+    }
+}
+
+/// Ejecuta git en `root` con variables de entorno adicionales y devuelve su salida.
+fn git_output(root: &Path, args: &[&str], env: &[(String, String)]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(root)
+        .args(args)
+        .envs(env.iter().cloned())
+        .output()
+        .context("No se pudo ejecutar git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} falló: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Entorno para el fetch: nunca pide credenciales por terminal y, con token de GitHub,
+/// lo envía solo a github.com sin dejarlo en la línea de comandos.
+fn fetch_env(github_token: Option<&str>) -> Vec<(String, String)> {
+    let mut env = vec![("GIT_TERMINAL_PROMPT".to_string(), "0".to_string())];
+    if let Some(token) = github_token
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+    {
+        let credentials =
+            base64::engine::general_purpose::STANDARD.encode(format!("x-access-token:{}", token));
+        env.extend([
+            ("GIT_CONFIG_COUNT".to_string(), "1".to_string()),
+            (
+                "GIT_CONFIG_KEY_0".to_string(),
+                "http.https://github.com/.extraheader".to_string(),
+            ),
+            (
+                "GIT_CONFIG_VALUE_0".to_string(),
+                format!("Authorization: Basic {}", credentials),
+            ),
+        ]);
+    }
+    env
+}
+
+/// Ramas de una salida de `git for-each-ref` con
+/// `%(HEAD)%00%(refname:short)%00%(upstream:short)%00%(upstream:track,nobracket)`.
+fn parse_branches(output: &str) -> Vec<BranchStatus> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\0');
+            let head = fields.next()?;
+            let name = fields.next()?.to_string();
+            let upstream = fields.next().filter(|upstream| !upstream.is_empty());
+            let track = fields.next().unwrap_or_default();
+            let mut branch = BranchStatus {
+                name,
+                upstream: upstream.map(str::to_string),
+                ahead: 0,
+                behind: 0,
+                gone: track == "gone",
+                current: head == "*",
+            };
+            for part in track.split(", ") {
+                match part.split_once(' ') {
+                    Some(("ahead", count)) => branch.ahead = count.parse().unwrap_or(0),
+                    Some(("behind", count)) => branch.behind = count.parse().unwrap_or(0),
+                    _ => {}
+                }
+            }
+            Some(branch)
+        })
+        .collect()
+}
+
+/// Trae todos los remotos de `root` y calcula cuánto adelanta o atrasa cada rama local.
+/// No fusiona ni modifica el árbol de trabajo.
+pub fn git_fetch_status(root: &Path, github_token: Option<&str>) -> Result<FetchStatus> {
+    git_output(
+        root,
+        &["fetch", "--all", "--prune", "--quiet"],
+        &fetch_env(github_token),
+    )?;
+    let branches = parse_branches(&git_output(
+        root,
+        &[
+            "for-each-ref",
+            "--format=%(HEAD)%00%(refname:short)%00%(upstream:short)%00%(upstream:track,nobracket)",
+            "refs/heads",
+        ],
+        &[],
+    )?);
+
+    let mut status = FetchStatus {
+        branches,
+        incoming: Vec::new(),
+        merge_base: None,
+        fetched_at: Local::now().format("%H:%M").to_string(),
+    };
+    let Some(current) = status.current().filter(|branch| branch.behind > 0).cloned() else {
+        return Ok(status);
+    };
+    let limit = format!("-n{}", INCOMING_LIMIT);
+    status.incoming = parse_commits(&git_output(
+        root,
+        &[
+            "log",
+            &limit,
+            "--date=short",
+            "--format=%H%x1f%an%x1f%ad%x1f%s",
+            "HEAD..@{upstream}",
+        ],
+        &[],
+    )?);
+    if current.is_diverged() {
+        status.merge_base = git_output(root, &["merge-base", "HEAD", "@{upstream}"], &[])
+            .ok()
+            .map(|hash| hash.trim().to_string());
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_includes_scope_and_trims_long_diffs() {
+        let scope = DiffScope::File {
+            path: PathBuf::from("/proyecto/src/main.rs"),
+            relative: "src/main.rs".to_string(),
+        };
+        let prompt = build_prompt(DiffDocument::CommitMessage, &scope, "-uno\n+dos\n");
+        assert!(prompt.starts_with("Escribe un mensaje de commit"));
+        assert!(prompt.ends_with("Cambios en src/main.rs:\n```diff\n-uno\n+dos\n```"));
+
+        let long = "+x\n".repeat(MAX_DIFF_CHARS);
+        let prompt = build_prompt(DiffDocument::PullRequest, &DiffScope::WorkingTree, &long);
+        assert!(prompt.contains(&format!(
+            "faltan {} caracteres",
+            long.len() - MAX_DIFF_CHARS
+        )));
+    }
+
+    #[test]
+    fn parses_line_porcelain_and_groups_consecutive_lines() {
+        let commit_a = "a".repeat(40);
+        let commit_b = "b".repeat(40);
+        let output = format!(
+            "{a} 1 1 2\nauthor Ana\nauthor-time 1715767200\nsummary Primer commit\nfilename src/main.rs\n\tfn main() {{\n\
+             {a} 2 2\nauthor Ana\nauthor-time 1715767200\nsummary Primer commit\nfilename src/main.rs\n\t    uno();\n\
+             {b} 3 3 1\nauthor Luis\nauthor-time 1715853600\nsummary Arregla el cierre\nfilename src/main.rs\n\t}}\n",
+            a = commit_a,
+            b = commit_b
+        );
+        let lines = parse_blame(&output);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[2].author, "Luis");
+        assert_eq!(lines[0].date, "2024-05-15");
+        assert_eq!(
+            summarize_blame(&lines),
+            vec![
+                "1-2 · Ana · 2024-05-15 · Primer commit (aaaaaaaa)".to_string(),
+                "3 · Luis · 2024-05-16 · Arregla el cierre (bbbbbbbb)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_text_and_detects_binaries() {
+        assert_eq!(
+            decode("añil".as_bytes(), false),
+            Some((FileEncoding::Utf8, "añil".to_string()))
+        );
+        // "ñ" cortado por el límite de tamaño: se descarta el byte suelto.
+        assert_eq!(
+            decode(&"añ".as_bytes()[..2], true),
+            Some((FileEncoding::Utf8, "a".to_string()))
+        );
+        assert_eq!(
+            decode(&[0x61, 0xF1, 0x6F], false),
+            Some((FileEncoding::Latin1, "año".to_string()))
+        );
+        assert_eq!(
+            decode(&[0xFF, 0xFE, 0x68, 0x00, 0x69, 0x00], false),
+            Some((FileEncoding::Utf16Le, "hi".to_string()))
+        );
+        assert_eq!(decode(&[0x89, 0x50, 0x4E, 0x47, 0x00, 0x01], false), None);
+    }
+
+    #[test]
+    fn parses_and_resolves_conflict_hunks() {
+        let text = "inicio\n<<<<<<< HEAD\nuno\n||||||| base\ncero\n=======\ndos\n>>>>>>> rama\nmedio\n<<<<<<< HEAD\na\n=======\nb\n>>>>>>> rama\nfin\n";
+        let hunks = parse_conflicts(text);
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(hunks[0].start_line, 2);
+        assert_eq!(hunks[0].ours_label, "HEAD");
+        assert_eq!(hunks[0].base.as_deref(), Some("cero\n"));
+        assert_eq!(hunks[0].theirs, "dos\n");
+        assert_eq!(hunks[1].theirs_label, "rama");
+
+        let resolutions = BTreeMap::from([(1, HunkResolution::Merged("a y b".to_string()))]);
+        let partial = resolve_conflicts(text, &resolutions);
+        assert!(partial.ends_with("medio\na y b\nfin\n"));
+        assert_eq!(parse_conflicts(&partial).len(), 1);
+
+        let resolutions = BTreeMap::from([(0, HunkResolution::Both), (1, HunkResolution::Theirs)]);
+        assert_eq!(
+            resolve_conflicts(text, &resolutions),
+            "inicio\nuno\ndos\nmedio\nb\nfin\n"
+        );
+        assert_eq!(
+            extract_code_block("Propuesta:\n```rust\nfn a() {}\n```\nListo").as_deref(),
+            Some("fn a() {}\n")
+        );
+    }
+
+    #[test]
+    fn parses_branch_tracking_and_summarizes_it() {
+        let output = "*\0main\0origin/main\0behind 2\n \0feature\0origin/feature\0ahead 1, behind 3\n \0old\0origin/old\0gone\n \0local\0\0\n";
+        let branches = parse_branches(output);
+        assert_eq!(branches.len(), 4);
+        assert!(branches[0].current);
+        assert_eq!(branches[0].summary(), "2 commits por detrás de origin/main");
+        assert!(branches[1].is_diverged());
+        assert_eq!((branches[1].ahead, branches[1].behind), (1, 3));
+        assert!(branches[2].gone);
+        assert_eq!(branches[3].upstream, None);
+        assert_eq!(fetch_env(None).len(), 1);
+        assert!(fetch_env(Some("ghp_x"))[3]
+            .1
+            .starts_with("Authorization: Basic "));
+    }
+}
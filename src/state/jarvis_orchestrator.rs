@@ -5,7 +5,7 @@ use regex::Regex;
 
 use super::{
     AppState, ChatMessage, LogStatus, ProviderCallDispatch, ProviderCallResult, ProviderCallTicket,
-    RemoteProviderKind, ScheduledTask, ScheduledTaskStatus,
+    RemoteProviderKind, ScheduledTask, ScheduledTaskKind, ScheduledTaskStatus,
 };
 
 pub struct JarvisOrchestrator<'a> {
@@ -149,6 +149,7 @@ impl<'a> JarvisOrchestrator<'a> {
                         provider: provider_hint,
                         tags: vec!["jarvis".to_string(), "automation".to_string()],
                         enabled: true,
+                        kind: ScheduledTaskKind::Generic,
                     };
                     self.state.automation.cron_board.tasks.push(task.clone());
                     self.scheduled_tasks.push(task);
@@ -228,6 +229,56 @@ impl<'a> JarvisOrchestrator<'a> {
                     alias,
                     provider_kind.display_name()
                 )),
+                ProviderCallDispatch::Queued {
+                    provider_kind,
+                    provider_name,
+                    alias,
+                    position,
+                } => blocked.push(format!(
+                    "{} (@{}) en cola (#{}) [{}] hasta recuperar la conexión.",
+                    provider_name,
+                    alias,
+                    position,
+                    provider_kind.short_code()
+                )),
+                ProviderCallDispatch::Offline {
+                    provider_kind,
+                    provider_name,
+                    alias,
+                } => blocked.push(format!(
+                    "{} (@{}) no disponible sin conexión [{}].",
+                    provider_name,
+                    alias,
+                    provider_kind.short_code()
+                )),
+                ProviderCallDispatch::BudgetExhausted {
+                    provider_kind,
+                    provider_name,
+                    alias,
+                    period,
+                    spent,
+                    limit,
+                } => blocked.push(format!(
+                    "{} (@{}) bloqueado [{}]: presupuesto {} agotado (${:.2} de ${:.2}).",
+                    provider_name,
+                    alias,
+                    provider_kind.short_code(),
+                    period.label(),
+                    spent,
+                    limit
+                )),
+                ProviderCallDispatch::LocalOnly {
+                    provider_kind,
+                    provider_name,
+                    alias,
+                    projects,
+                } => blocked.push(format!(
+                    "{} (@{}) bloqueado [{}]: el contexto incluye {} (solo local).",
+                    provider_name,
+                    alias,
+                    provider_kind.short_code(),
+                    projects.join(", ")
+                )),
             }
         }
 
@@ -0,0 +1,397 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use super::runtime::{self, TaskHandle};
+use crate::config::JobQueueConfig;
+
+/// Ventana del límite de llamadas por minuto.
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+/// Trabajos terminados o cancelados que se conservan en el inspector.
+const MAX_FINISHED: usize = 30;
+/// Carril de las publicaciones en Slack y Discord.
+pub const WEBHOOK_LANE: &str = "webhooks";
+/// Carril de las búsquedas web y la descarga de páginas.
+pub const WEB_SEARCH_LANE: &str = "web";
+/// Carril de la transcripción con Whisper local; la de OpenAI va en el de su proveedor.
+pub const TRANSCRIPTION_LANE: &str = "whisper";
+/// Carril de los correos salientes.
+pub const EMAIL_LANE: &str = "email";
+/// Carril de las respuestas del puente con Telegram.
+pub const TELEGRAM_LANE: &str = "telegram";
+/// Carril de las publicaciones MQTT.
+pub const MQTT_LANE: &str = "mqtt";
+/// Carril de los embeddings del modelo local; los de OpenAI van en el de su proveedor.
+pub const EMBEDDINGS_LANE: &str = "embeddings";
+
+/// Las llamadas interactivas del chat adelantan a las de segundo plano.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum JobPriority {
+    Interactive,
+    Background,
+}
+
+impl JobPriority {
+    pub fn label(self) -> &'static str {
+        match self {
+            JobPriority::Interactive => "Interactiva",
+            JobPriority::Background => "Segundo plano",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Finished,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            JobStatus::Pending => "En cola",
+            JobStatus::Running => "En curso",
+            JobStatus::Finished => "Terminado",
+            JobStatus::Cancelled => "Cancelado",
+        }
+    }
+}
+
+/// Lo que el inspector muestra de cada trabajo.
+#[derive(Clone, Debug)]
+pub struct JobInfo {
+    pub id: u64,
+    pub lane: String,
+    pub label: String,
+    pub priority: JobPriority,
+    pub status: JobStatus,
+    pub queued_at: String,
+    /// Llamada del chat que espera este trabajo; al cancelarlo se descarta también.
+    pub provider_call: Option<u64>,
+    enqueued: Instant,
+    started: Option<Instant>,
+}
+
+impl JobInfo {
+    /// Tiempo en cola antes de empezar, o desde que se encoló si aún espera.
+    pub fn waited(&self) -> Duration {
+        self.started
+            .unwrap_or_else(Instant::now)
+            .duration_since(self.enqueued)
+    }
+
+    pub fn started(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Una llamada en curso no se puede interrumpir salvo que su respuesta se pueda
+    /// descartar, como las del chat.
+    pub fn can_cancel(&self) -> bool {
+        match self.status {
+            JobStatus::Pending => true,
+            JobStatus::Running => self.provider_call.is_some(),
+            JobStatus::Finished | JobStatus::Cancelled => false,
+        }
+    }
+}
+
+type Work = Box<dyn FnOnce() + Send + 'static>;
+
+struct Job {
+    info: JobInfo,
+    /// Trabajo por hacer mientras espera turno.
+    work: Option<Work>,
+    /// Tarea del runtime una vez arrancado.
+    task: Option<TaskHandle>,
+}
+
+/// Cola central de las llamadas salientes a proveedores e integraciones. Cada carril
+/// tiene su límite de concurrencia y de llamadas por minuto; dentro de él empiezan
+/// antes las interactivas y, a igual prioridad, las más antiguas.
+pub struct JobQueueState {
+    limits: JobQueueConfig,
+    /// Trabajos en cola o en curso, por orden de llegada.
+    jobs: Vec<Job>,
+    pub finished: VecDeque<JobInfo>,
+    /// Carril que se escribe en las preferencias para darle límites propios.
+    pub lane_draft: String,
+    next_id: u64,
+    /// Inicios recientes de cada carril, para el límite por minuto.
+    starts: HashMap<String, VecDeque<Instant>>,
+}
+
+impl Default for JobQueueState {
+    fn default() -> Self {
+        Self {
+            limits: JobQueueConfig::default(),
+            jobs: Vec::new(),
+            finished: VecDeque::new(),
+            lane_draft: String::new(),
+            next_id: 1,
+            starts: HashMap::new(),
+        }
+    }
+}
+
+impl JobQueueState {
+    /// Aplica los límites configurados; los trabajos en curso no se ven afectados.
+    pub fn configure(&mut self, limits: JobQueueConfig) {
+        self.limits = limits;
+        self.pump();
+    }
+
+    /// Encola `work`, que se ejecutará en el runtime compartido cuando su carril lo permita.
+    pub fn submit(
+        &mut self,
+        lane: impl Into<String>,
+        priority: JobPriority,
+        label: impl Into<String>,
+        work: impl FnOnce() + Send + 'static,
+    ) -> u64 {
+        self.push(lane.into(), priority, label.into(), None, Box::new(work))
+    }
+
+    /// Como `submit`, para una llamada del chat cuyo ticket es `call_id`.
+    pub fn submit_provider_call(
+        &mut self,
+        lane: impl Into<String>,
+        label: impl Into<String>,
+        call_id: u64,
+        work: impl FnOnce() + Send + 'static,
+    ) -> u64 {
+        self.push(
+            lane.into(),
+            JobPriority::Interactive,
+            label.into(),
+            Some(call_id),
+            Box::new(work),
+        )
+    }
+
+    fn push(
+        &mut self,
+        lane: String,
+        priority: JobPriority,
+        label: String,
+        provider_call: Option<u64>,
+        work: Work,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(Job {
+            info: JobInfo {
+                id,
+                lane,
+                label,
+                priority,
+                status: JobStatus::Pending,
+                queued_at: Local::now().format("%H:%M:%S").to_string(),
+                provider_call,
+                enqueued: Instant::now(),
+                started: None,
+            },
+            work: Some(work),
+            task: None,
+        });
+        self.pump();
+        id
+    }
+
+    /// Trabajos en cola y en curso.
+    pub fn active(&self) -> impl Iterator<Item = &JobInfo> {
+        self.jobs.iter().map(|job| &job.info)
+    }
+
+    pub fn count(&self, status: JobStatus) -> usize {
+        self.active().filter(|job| job.status == status).count()
+    }
+
+    /// Límites del carril: concurrencia (al menos 1) y llamadas por minuto (0 sin límite).
+    pub fn lane_limits(&self, lane: &str) -> (usize, usize) {
+        match self.limits.lanes.iter().find(|limit| limit.lane == lane) {
+            Some(limit) => (
+                limit.max_concurrent.max(1) as usize,
+                limit.per_minute as usize,
+            ),
+            None => (self.limits.default_max_concurrent.max(1) as usize, 0),
+        }
+    }
+
+    /// Recoge los trabajos terminados y arranca los que caben en su carril. Si alguno
+    /// espera al límite por minuto, pide un repintado para cuando se libere. Devuelve si
+    /// algo cambió.
+    pub fn pump(&mut self) -> bool {
+        let mut changed = false;
+        let mut index = 0;
+        while index < self.jobs.len() {
+            let done = self.jobs[index]
+                .task
+                .as_ref()
+                .is_some_and(TaskHandle::is_finished);
+            if !done {
+                index += 1;
+                continue;
+            }
+            let mut info = self.jobs.remove(index).info;
+            if info.status == JobStatus::Running {
+                info.status = JobStatus::Finished;
+            }
+            self.record_finished(info);
+            changed = true;
+        }
+
+        let now = Instant::now();
+        for starts in self.starts.values_mut() {
+            while starts
+                .front()
+                .is_some_and(|start| now.duration_since(*start) >= RATE_WINDOW)
+            {
+                starts.pop_front();
+            }
+        }
+
+        let mut order: Vec<usize> = (0..self.jobs.len())
+            .filter(|index| self.jobs[*index].info.status == JobStatus::Pending)
+            .collect();
+        order.sort_by_key(|index| (self.jobs[*index].info.priority, self.jobs[*index].info.id));
+        let mut wake_after: Option<Duration> = None;
+        for index in order {
+            let lane = self.jobs[index].info.lane.clone();
+            let (max_concurrent, per_minute) = self.lane_limits(&lane);
+            let running = self
+                .jobs
+                .iter()
+                .filter(|job| job.info.lane == lane && job.task.is_some())
+                .count();
+            if running >= max_concurrent {
+                continue;
+            }
+            let starts = self.starts.entry(lane).or_default();
+            if per_minute > 0 && starts.len() >= per_minute {
+                if let Some(oldest) = starts.front() {
+                    let wait = RATE_WINDOW.saturating_sub(now.duration_since(*oldest));
+                    wake_after = Some(wake_after.map_or(wait, |current| current.min(wait)));
+                }
+                continue;
+            }
+            starts.push_back(now);
+            let job = &mut self.jobs[index];
+            let Some(work) = job.work.take() else {
+                continue;
+            };
+            job.info.status = JobStatus::Running;
+            job.info.started = Some(now);
+            job.task = Some(runtime::spawn_blocking(work));
+            changed = true;
+        }
+        if let Some(wait) = wake_after {
+            runtime::request_repaint_after(wait);
+        }
+        changed
+    }
+
+    /// Cancela un trabajo. Si aún no empezó se descarta sin llegar a llamar; si está en
+    /// curso solo se marca, y quien lo lanzó debe ignorar su resultado.
+    pub fn cancel(&mut self, id: u64) -> Option<JobInfo> {
+        let position = self.jobs.iter().position(|job| job.info.id == id)?;
+        if !self.jobs[position].info.can_cancel() {
+            return None;
+        }
+        if self.jobs[position].info.status == JobStatus::Pending {
+            let mut info = self.jobs.remove(position).info;
+            info.status = JobStatus::Cancelled;
+            self.record_finished(info.clone());
+            self.pump();
+            return Some(info);
+        }
+        let info = &mut self.jobs[position].info;
+        info.status = JobStatus::Cancelled;
+        Some(info.clone())
+    }
+
+    /// Trabajo que atiende la llamada del chat `call_id`, si sigue activo.
+    pub fn job_for_provider_call(&self, call_id: u64) -> Option<u64> {
+        self.active()
+            .find(|job| job.provider_call == Some(call_id) && job.can_cancel())
+            .map(|job| job.id)
+    }
+
+    fn record_finished(&mut self, info: JobInfo) {
+        self.finished.push_front(info);
+        self.finished.truncate(MAX_FINISHED);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::JobLaneLimit;
+    use std::sync::mpsc;
+    use std::thread;
+
+    fn wait_until(queue: &mut JobQueueState, condition: impl Fn(&JobQueueState) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition(queue) {
+            assert!(Instant::now() < deadline, "la cola no avanzó");
+            queue.pump();
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn status(queue: &JobQueueState, id: u64) -> Option<JobStatus> {
+        queue
+            .active()
+            .chain(queue.finished.iter())
+            .find(|job| job.id == id)
+            .map(|job| job.status)
+    }
+
+    #[test]
+    fn respects_lane_limits_and_runs_interactive_jobs_first() {
+        let mut queue = JobQueueState::default();
+        queue.configure(JobQueueConfig {
+            lanes: vec![
+                JobLaneLimit {
+                    lane: "a".to_string(),
+                    max_concurrent: 1,
+                    per_minute: 0,
+                },
+                JobLaneLimit {
+                    lane: "b".to_string(),
+                    max_concurrent: 5,
+                    per_minute: 1,
+                },
+            ],
+            default_max_concurrent: 4,
+        });
+
+        let (release_tx, release_rx) = mpsc::channel::<()>();
+        let first = queue.submit("a", JobPriority::Background, "primero", move || {
+            let _ = release_rx.recv();
+        });
+        let background = queue.submit("a", JobPriority::Background, "informe", || {});
+        let interactive = queue.submit_provider_call("a", "chat", 7, || {});
+        assert_eq!(status(&queue, first), Some(JobStatus::Running));
+        assert_eq!(queue.count(JobStatus::Pending), 2);
+        assert_eq!(queue.job_for_provider_call(7), Some(interactive));
+
+        drop(release_tx);
+        wait_until(&mut queue, |queue| {
+            status(queue, interactive) != Some(JobStatus::Pending)
+        });
+        assert_eq!(status(&queue, background), Some(JobStatus::Pending));
+        let cancelled = queue.cancel(background).unwrap();
+        assert_eq!(cancelled.status, JobStatus::Cancelled);
+        assert!(queue.cancel(background).is_none());
+
+        let quick = queue.submit("b", JobPriority::Background, "rápido", || {});
+        let limited = queue.submit("b", JobPriority::Background, "limitado", || {});
+        wait_until(&mut queue, |queue| {
+            status(queue, quick) == Some(JobStatus::Finished)
+        });
+        assert_eq!(status(&queue, limited), Some(JobStatus::Pending));
+    }
+}
@@ -0,0 +1,329 @@
+use std::fmt;
+
+use eframe::egui::{Key, Modifiers};
+
+use crate::config::KeybindingsConfig;
+
+/// Acciones de la aplicación que se pueden asociar a un atajo de teclado.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ShortcutAction {
+    SendMessage,
+    OpenPalette,
+    PreviousMessage,
+    NextMessage,
+    ShowChatTab,
+    ShowCronTab,
+    ShowActivityTab,
+    ShowDebugTab,
+    CancelPendingCall,
+    PreviousNavigation,
+    NextNavigation,
+    ToggleZen,
+    ShowCheatSheet,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 13] = [
+        ShortcutAction::SendMessage,
+        ShortcutAction::CancelPendingCall,
+        ShortcutAction::PreviousMessage,
+        ShortcutAction::NextMessage,
+        ShortcutAction::OpenPalette,
+        ShortcutAction::ShowChatTab,
+        ShortcutAction::ShowCronTab,
+        ShortcutAction::ShowActivityTab,
+        ShortcutAction::ShowDebugTab,
+        ShortcutAction::PreviousNavigation,
+        ShortcutAction::NextNavigation,
+        ShortcutAction::ToggleZen,
+        ShortcutAction::ShowCheatSheet,
+    ];
+
+    /// Identificador con el que se guarda la combinación en la configuración.
+    pub fn id(self) -> &'static str {
+        match self {
+            ShortcutAction::SendMessage => "chat.send",
+            ShortcutAction::OpenPalette => "palette.open",
+            ShortcutAction::PreviousMessage => "chat.previous_message",
+            ShortcutAction::NextMessage => "chat.next_message",
+            ShortcutAction::ShowChatTab => "view.chat",
+            ShortcutAction::ShowCronTab => "view.cron",
+            ShortcutAction::ShowActivityTab => "view.activity",
+            ShortcutAction::ShowDebugTab => "view.debug",
+            ShortcutAction::CancelPendingCall => "chat.cancel_call",
+            ShortcutAction::PreviousNavigation => "navigation.previous",
+            ShortcutAction::NextNavigation => "navigation.next",
+            ShortcutAction::ToggleZen => "view.zen",
+            ShortcutAction::ShowCheatSheet => "help.shortcuts",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ShortcutAction::SendMessage => "Enviar el mensaje",
+            ShortcutAction::OpenPalette => "Abrir la paleta de búsqueda",
+            ShortcutAction::PreviousMessage => "Saltar al mensaje anterior",
+            ShortcutAction::NextMessage => "Saltar al mensaje siguiente",
+            ShortcutAction::ShowChatTab => "Pestaña Chat",
+            ShortcutAction::ShowCronTab => "Pestaña Cron",
+            ShortcutAction::ShowActivityTab => "Pestaña Actividad",
+            ShortcutAction::ShowDebugTab => "Pestaña Depuración",
+            ShortcutAction::CancelPendingCall => "Cancelar la llamada en curso",
+            ShortcutAction::PreviousNavigation => "Elemento anterior de la barra lateral",
+            ShortcutAction::NextNavigation => "Elemento siguiente de la barra lateral",
+            ShortcutAction::ToggleZen => "Modo zen",
+            ShortcutAction::ShowCheatSheet => "Mostrar los atajos de teclado",
+        }
+    }
+
+    pub fn category(self) -> &'static str {
+        match self {
+            ShortcutAction::SendMessage
+            | ShortcutAction::CancelPendingCall
+            | ShortcutAction::PreviousMessage
+            | ShortcutAction::NextMessage => "Chat",
+            ShortcutAction::OpenPalette
+            | ShortcutAction::ShowChatTab
+            | ShortcutAction::ShowCronTab
+            | ShortcutAction::ShowActivityTab
+            | ShortcutAction::ShowDebugTab
+            | ShortcutAction::PreviousNavigation
+            | ShortcutAction::NextNavigation => "Navegación",
+            ShortcutAction::ToggleZen | ShortcutAction::ShowCheatSheet => "Vista",
+        }
+    }
+
+    pub fn default_chord(self) -> KeyChord {
+        match self {
+            ShortcutAction::SendMessage => KeyChord::command(Key::Enter),
+            ShortcutAction::OpenPalette => KeyChord::command(Key::K),
+            ShortcutAction::PreviousMessage => KeyChord::alt(Key::ArrowUp),
+            ShortcutAction::NextMessage => KeyChord::alt(Key::ArrowDown),
+            ShortcutAction::ShowChatTab => KeyChord::command(Key::Num1),
+            ShortcutAction::ShowCronTab => KeyChord::command(Key::Num2),
+            ShortcutAction::ShowActivityTab => KeyChord::command(Key::Num3),
+            ShortcutAction::ShowDebugTab => KeyChord::command(Key::Num4),
+            ShortcutAction::CancelPendingCall => KeyChord::plain(Key::Escape),
+            ShortcutAction::PreviousNavigation => KeyChord {
+                shift: true,
+                ..KeyChord::alt(Key::ArrowUp)
+            },
+            ShortcutAction::NextNavigation => KeyChord {
+                shift: true,
+                ..KeyChord::alt(Key::ArrowDown)
+            },
+            ShortcutAction::ToggleZen => KeyChord::plain(Key::F11),
+            ShortcutAction::ShowCheatSheet => KeyChord::plain(Key::F1),
+        }
+    }
+}
+
+/// Combinación de teclas. `command` es Ctrl, o Cmd en macOS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub command: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub key: Key,
+}
+
+impl KeyChord {
+    pub fn plain(key: Key) -> Self {
+        Self {
+            command: false,
+            alt: false,
+            shift: false,
+            key,
+        }
+    }
+
+    pub fn command(key: Key) -> Self {
+        Self {
+            command: true,
+            ..Self::plain(key)
+        }
+    }
+
+    pub fn alt(key: Key) -> Self {
+        Self {
+            alt: true,
+            ..Self::plain(key)
+        }
+    }
+
+    pub fn from_input(key: Key, modifiers: Modifiers) -> Self {
+        Self {
+            command: modifiers.command || modifiers.ctrl,
+            alt: modifiers.alt,
+            shift: modifiers.shift,
+            key,
+        }
+    }
+
+    /// Interpreta textos como `Ctrl+Shift+K`; acepta `Cmd` como sinónimo de `Ctrl`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = text.split('+').map(str::trim).collect();
+        let Some((key_name, modifiers)) = parts.split_last() else {
+            return Err("La combinación está vacía.".to_string());
+        };
+        let key = Key::from_name(key_name)
+            .ok_or_else(|| format!("Tecla desconocida: '{}'.", key_name))?;
+        let mut chord = Self::plain(key);
+        for modifier in modifiers {
+            match modifier.to_lowercase().as_str() {
+                "ctrl" | "cmd" | "control" => chord.command = true,
+                "alt" | "option" => chord.alt = true,
+                "shift" | "mayús" => chord.shift = true,
+                other => return Err(format!("Modificador desconocido: '{}'.", other)),
+            }
+        }
+        Ok(chord)
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.command {
+            f.write_str("Ctrl+")?;
+        }
+        if self.alt {
+            f.write_str("Alt+")?;
+        }
+        if self.shift {
+            f.write_str("Shift+")?;
+        }
+        f.write_str(self.key.name())
+    }
+}
+
+/// Atajos fijos de la interfaz que no se pueden reasignar.
+pub const RESERVED_SHORTCUTS: &[(&str, &str)] = &[
+    ("Up", "Mensaje anterior del historial resaltado"),
+    ("Down", "Mensaje siguiente del historial resaltado"),
+    ("Home", "Primer mensaje del historial"),
+    ("End", "Último mensaje del historial"),
+    ("Enter", "Enviar o responder al mensaje resaltado"),
+    ("Shift+Enter", "Salto de línea en el editor"),
+    ("Tab", "Siguiente control de la vista"),
+    ("Shift+Tab", "Control anterior de la vista"),
+    ("Ctrl+Plus", "Aumentar la escala"),
+    ("Ctrl+Minus", "Reducir la escala"),
+    ("Ctrl+C", "Copiar"),
+    ("Ctrl+V", "Pegar"),
+    ("Ctrl+X", "Cortar"),
+    ("Ctrl+Z", "Deshacer"),
+    ("Ctrl+A", "Seleccionar todo"),
+];
+
+/// Dos o más acciones, o una acción y un atajo fijo, que comparten combinación.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeybindingConflict {
+    pub chord: KeyChord,
+    pub actions: Vec<ShortcutAction>,
+    /// Descripción del atajo fijo con el que choca, si lo hay.
+    pub reserved: Option<&'static str>,
+}
+
+/// Combinación efectiva de `action`: la personalizada si es válida, si no la predeterminada.
+pub fn binding(config: &KeybindingsConfig, action: ShortcutAction) -> KeyChord {
+    config
+        .overrides
+        .get(action.id())
+        .and_then(|text| KeyChord::parse(text).ok())
+        .unwrap_or_else(|| action.default_chord())
+}
+
+/// Acción asociada a `chord`. Con combinaciones repetidas gana la primera de `ALL`.
+pub fn action_for(config: &KeybindingsConfig, chord: KeyChord) -> Option<ShortcutAction> {
+    ShortcutAction::ALL
+        .into_iter()
+        .find(|action| binding(config, *action) == chord)
+}
+
+pub fn reserved_description(chord: KeyChord) -> Option<&'static str> {
+    RESERVED_SHORTCUTS
+        .iter()
+        .find(|(keys, _)| KeyChord::parse(keys).ok() == Some(chord))
+        .map(|(_, description)| *description)
+}
+
+pub fn conflicts(config: &KeybindingsConfig) -> Vec<KeybindingConflict> {
+    let mut conflicts: Vec<KeybindingConflict> = Vec::new();
+    for action in ShortcutAction::ALL {
+        let chord = binding(config, action);
+        match conflicts
+            .iter_mut()
+            .find(|conflict| conflict.chord == chord)
+        {
+            Some(conflict) => conflict.actions.push(action),
+            None => conflicts.push(KeybindingConflict {
+                chord,
+                actions: vec![action],
+                reserved: reserved_description(chord),
+            }),
+        }
+    }
+    conflicts.retain(|conflict| conflict.actions.len() > 1 || conflict.reserved.is_some());
+    conflicts
+}
+
+/// Estado de la interfaz de atajos: chuleta superpuesta y captura de combinaciones.
+#[derive(Default)]
+pub struct KeybindingState {
+    pub show_cheatsheet: bool,
+    /// Acción que espera la siguiente combinación pulsada para reasignarse.
+    pub capturing: Option<ShortcutAction>,
+    pub status: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chords_round_trip_through_text() {
+        for action in ShortcutAction::ALL {
+            let chord = action.default_chord();
+            assert_eq!(KeyChord::parse(&chord.to_string()), Ok(chord));
+        }
+        assert_eq!(
+            KeyChord::parse("cmd + shift + K"),
+            Ok(KeyChord {
+                shift: true,
+                ..KeyChord::command(Key::K)
+            })
+        );
+        assert!(KeyChord::parse("Hyper+K").is_err());
+        assert!(KeyChord::parse("Ctrl+Nada").is_err());
+    }
+
+    #[test]
+    fn default_bindings_have_no_conflicts() {
+        assert!(conflicts(&KeybindingsConfig::default()).is_empty());
+    }
+
+    #[test]
+    fn conflicts_report_duplicates_and_reserved_chords() {
+        let mut config = KeybindingsConfig::default();
+        config.overrides.insert(
+            ShortcutAction::ShowCheatSheet.id().to_string(),
+            "Ctrl+K".to_string(),
+        );
+        config.overrides.insert(
+            ShortcutAction::ToggleZen.id().to_string(),
+            "Ctrl+C".to_string(),
+        );
+
+        let found = conflicts(&config);
+        assert_eq!(found.len(), 2);
+        assert!(found.iter().any(|conflict| conflict.actions
+            == vec![ShortcutAction::OpenPalette, ShortcutAction::ShowCheatSheet]));
+        assert!(found
+            .iter()
+            .any(|conflict| conflict.reserved == Some("Copiar")));
+        assert_eq!(
+            action_for(&config, KeyChord::command(Key::K)),
+            Some(ShortcutAction::OpenPalette)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::documents::{self, DocumentChunk};
+use super::{attachments, embeddings, ocr};
+use crate::config::OcrConfig;
+
+/// Extensiones de los documentos de texto que se incorporan a la base de conocimiento.
+/// Los PDF y DOCX de `documents::DOCUMENT_EXTENSIONS` también se incorporan.
+pub const KNOWLEDGE_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "rst", "adoc"];
+/// Directorios que nunca se recorren al reindexar ni en el explorador de código.
+pub const SKIPPED_DIRECTORIES: &[&str] =
+    &[".git", "target", "node_modules", ".venv", "dist", "build"];
+/// Programación propuesta para un reindexado nuevo: cada noche a las 03:00.
+pub const DEFAULT_REINDEX_CRON: &str = "0 3 * * *";
+/// Documentos de texto mayores que este tamaño se ignoran y se anotan como diagnóstico.
+const MAX_DOCUMENT_BYTES: u64 = 512 * 1024;
+
+/// Documento de un proyecto ya troceado para el índice de embeddings.
+#[derive(Clone, Debug)]
+pub struct IndexedDocument {
+    pub modified: SystemTime,
+    /// Fragmentos con las páginas de las que proceden, en los PDF y DOCX.
+    pub chunks: Vec<DocumentChunk>,
+}
+
+/// Resultado de una pasada incremental sobre un proyecto.
+#[derive(Clone, Debug, Default)]
+pub struct ReindexReport {
+    pub scanned: usize,
+    pub changed: usize,
+    pub removed: usize,
+    /// Ficheros que no se pudieron leer, con el motivo.
+    pub failures: Vec<(PathBuf, String)>,
+}
+
+/// Documentos troceados de cada proyecto, indexados por nombre de proyecto. Solo se
+/// vuelven a leer los ficheros cuya fecha de modificación ha cambiado.
+#[derive(Default)]
+pub struct KnowledgeIndexState {
+    pub projects: HashMap<String, HashMap<PathBuf, IndexedDocument>>,
+    /// Programación en edición desde el planificador.
+    pub draft_project: String,
+    pub draft_cron: String,
+    pub status: Option<String>,
+}
+
+impl KnowledgeIndexState {
+    pub fn document_count(&self, project: &str) -> usize {
+        self.projects.get(project).map_or(0, HashMap::len)
+    }
+
+    /// Recorre `root` y actualiza los documentos del proyecto que han cambiado. Con `ocr`,
+    /// también las imágenes, cuyo texto se reconoce con Tesseract.
+    pub fn reindex_project(
+        &mut self,
+        project: &str,
+        root: &Path,
+        ocr: Option<&OcrConfig>,
+    ) -> ReindexReport {
+        let mut report = ReindexReport::default();
+        let documents = self.projects.entry(project.to_string()).or_default();
+
+        let mut seen = HashSet::new();
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(dir) = pending.pop() {
+            let entries = match fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    report.failures.push((dir, err.to_string()));
+                    continue;
+                }
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                if file_type.is_dir() {
+                    let skipped = path
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| SKIPPED_DIRECTORIES.contains(&name));
+                    if !skipped {
+                        pending.push(path);
+                    }
+                    continue;
+                }
+                let image = ocr.is_some() && attachments::image_media_type(&path).is_some();
+                if !image && !is_knowledge_document(&path) {
+                    continue;
+                }
+
+                report.scanned += 1;
+                seen.insert(path.clone());
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(err) => {
+                        report.failures.push((path, err.to_string()));
+                        continue;
+                    }
+                };
+                let binary = image || documents::is_document(&path);
+                let limit = if binary {
+                    documents::MAX_DOCUMENT_BYTES
+                } else {
+                    MAX_DOCUMENT_BYTES
+                };
+                if metadata.len() > limit {
+                    documents.remove(&path);
+                    report
+                        .failures
+                        .push((path, format!("supera el límite de {} KB", limit / 1024)));
+                    continue;
+                }
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                if documents
+                    .get(&path)
+                    .is_some_and(|document| document.modified == modified)
+                {
+                    continue;
+                }
+                let chunks = if let Some(config) = ocr.filter(|_| image) {
+                    ocr::recognize(config, &path)
+                        .map(|text| ocr::chunk_text(&text))
+                        .map_err(|err| format!("{:#}", err))
+                } else if binary {
+                    documents::extract_chunks(&path, embeddings::README_CHUNK_CHARS)
+                        .map(|(_, chunks)| chunks)
+                        .map_err(|err| format!("{:#}", err))
+                } else {
+                    fs::read_to_string(&path)
+                        .map(|text| {
+                            embeddings::chunk_text(&text, embeddings::README_CHUNK_CHARS)
+                                .into_iter()
+                                .map(DocumentChunk::plain)
+                                .collect()
+                        })
+                        .map_err(|err| err.to_string())
+                };
+                match chunks {
+                    Ok(chunks) => {
+                        documents.insert(path, IndexedDocument { modified, chunks });
+                        report.changed += 1;
+                    }
+                    Err(err) => report.failures.push((path, err)),
+                }
+            }
+        }
+
+        let before = documents.len();
+        documents.retain(|path, _| seen.contains(path));
+        report.removed = before - documents.len();
+        report
+    }
+}
+
+fn is_knowledge_document(path: &Path) -> bool {
+    documents::is_document(path)
+        || path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| {
+                KNOWLEDGE_EXTENSIONS
+                    .iter()
+                    .any(|known| known.eq_ignore_ascii_case(extension))
+            })
+}
@@ -0,0 +1,130 @@
+/// Idiomas de respuesta disponibles: código, nombre en su idioma e instrucción que se
+/// añade a las instrucciones de sistema del proveedor.
+pub const REPLY_LANGUAGES: &[(&str, &str, &str)] = &[
+    ("es", "Español", "Responde siempre en español."),
+    ("en", "English", "Always reply in English."),
+    ("fr", "Français", "Réponds toujours en français."),
+    ("de", "Deutsch", "Antworte immer auf Deutsch."),
+    ("pt", "Português", "Responde sempre em português."),
+    ("it", "Italiano", "Rispondi sempre in italiano."),
+];
+
+/// Palabras muy frecuentes de cada idioma con las que se puntúa un texto.
+const STOPWORDS: &[(&str, &[&str])] = &[
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "que", "de", "y", "en", "un", "una", "es", "por", "para",
+            "con", "no", "qué", "cómo", "puedes", "este", "esta", "pero", "del", "al",
+        ],
+    ),
+    (
+        "en",
+        &[
+            "the", "and", "is", "are", "of", "to", "in", "what", "how", "can", "you", "this",
+            "that", "with", "for", "it", "please", "does", "not",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "les", "des", "est", "et", "une", "que", "pour", "dans", "pas", "vous", "avec",
+            "ce", "cette", "comment", "je",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ich", "mit", "wie", "was", "ein", "eine",
+            "zu", "bitte", "für",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "os", "as", "que", "de", "e", "em", "um", "uma", "não", "para", "com", "você",
+            "como", "isso", "do", "da",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "gli", "che", "di", "e", "è", "un", "una", "per", "non", "con", "come", "sono",
+            "questo", "della",
+        ],
+    ),
+];
+
+/// Palabras que hacen falta como mínimo para fiarse de la detección.
+const MIN_DETECTION_HITS: usize = 2;
+
+pub fn language_name(code: &str) -> Option<&'static str> {
+    REPLY_LANGUAGES
+        .iter()
+        .find(|(candidate, _, _)| *candidate == code)
+        .map(|(_, name, _)| *name)
+}
+
+pub fn language_instruction(code: &str) -> Option<&'static str> {
+    REPLY_LANGUAGES
+        .iter()
+        .find(|(candidate, _, _)| *candidate == code)
+        .map(|(_, _, instruction)| *instruction)
+}
+
+/// Idioma más probable de `text` por recuento de palabras frecuentes. `None` si el texto
+/// es demasiado corto o ambiguo para decidir.
+pub fn detect_language(text: &str) -> Option<&'static str> {
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|ch: char| !ch.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect();
+    let mut scores: Vec<(&'static str, usize)> = STOPWORDS
+        .iter()
+        .map(|(code, stopwords)| {
+            let hits = words
+                .iter()
+                .filter(|word| stopwords.contains(*word))
+                .count();
+            (*code, hits)
+        })
+        .collect();
+    // Los signos de apertura y la eñe delatan el español aunque haya pocas palabras.
+    if lowered.contains('¿') || lowered.contains('¡') || lowered.contains('ñ') {
+        if let Some(spanish) = scores.iter_mut().find(|(code, _)| *code == "es") {
+            spanish.1 += MIN_DETECTION_HITS;
+        }
+    }
+    scores.sort_by_key(|(_, hits)| std::cmp::Reverse(*hits));
+    match scores.as_slice() {
+        [(code, best), (_, second), ..] if *best >= MIN_DETECTION_HITS && best > second => {
+            Some(*code)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_common_languages_and_gives_up_on_short_text() {
+        assert_eq!(
+            detect_language("¿Puedes revisar el informe de la semana?"),
+            Some("es")
+        );
+        assert_eq!(
+            detect_language("Can you summarize what this function does?"),
+            Some("en")
+        );
+        assert_eq!(
+            detect_language("Wie funktioniert das und was ist der Fehler?"),
+            Some("de")
+        );
+        assert_eq!(detect_language("cargo build"), None);
+        assert_eq!(language_name("fr"), Some("Français"));
+        assert!(language_instruction("xx").is_none());
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashSet;
+
+/// Líneas más cortas que esto no identifican un proyecto: coincidirían con texto corriente.
+const MIN_FINGERPRINT_CHARS: usize = 40;
+
+/// Líneas características del contenido de un proyecto marcado como solo local.
+#[derive(Clone, Debug, Default)]
+pub struct ProjectFingerprint {
+    pub project: String,
+    lines: HashSet<String>,
+}
+
+impl ProjectFingerprint {
+    pub fn new<'a>(project: &str, texts: impl IntoIterator<Item = &'a str>) -> Self {
+        let lines = texts
+            .into_iter()
+            .flat_map(str::lines)
+            .map(normalize_line)
+            .filter(|line| line.chars().count() >= MIN_FINGERPRINT_CHARS)
+            .collect();
+        Self {
+            project: project.to_string(),
+            lines,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// `true` si `text` contiene alguna línea del proyecto, aunque sea pegada dentro de
+    /// otra frase o con otro espaciado.
+    pub fn matches(&self, normalized_text: &str) -> bool {
+        self.lines
+            .iter()
+            .any(|line| normalized_text.contains(line.as_str()))
+    }
+}
+
+fn normalize_line(line: &str) -> String {
+    line.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Normaliza un texto línea a línea igual que las huellas de los proyectos.
+pub fn normalize(text: &str) -> String {
+    text.lines()
+        .map(normalize_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Proyectos de `fingerprints` de los que procede parte de `text`.
+pub fn derived_projects(fingerprints: &[ProjectFingerprint], text: &str) -> Vec<String> {
+    let normalized = normalize(text);
+    fingerprints
+        .iter()
+        .filter(|fingerprint| fingerprint.matches(&normalized))
+        .map(|fingerprint| fingerprint.project.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_pasted_project_content_regardless_of_spacing() {
+        let readme =
+            "# Automation\nScripts para pipelines QA, builds nocturnos y despliegues sandbox.";
+        let fingerprints = vec![
+            ProjectFingerprint::new("Automation", [readme]),
+            ProjectFingerprint::new("Docs", ["Corto"]),
+        ];
+        assert!(fingerprints[1].is_empty());
+
+        let prompt =
+            "Resume esto: scripts para   pipelines QA, builds nocturnos y despliegues SANDBOX.";
+        assert_eq!(
+            derived_projects(&fingerprints, prompt),
+            vec!["Automation".to_string()]
+        );
+        assert!(derived_projects(&fingerprints, "# Automation").is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::{Days, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+
+const METRICS_FILE: &str = "metricas_uso.json";
+/// Latencias que se guardan por proveedor y día para calcular los percentiles.
+const MAX_LATENCY_SAMPLES: usize = 500;
+/// Intervalo mínimo entre escrituras del fichero mientras se usa la aplicación.
+const SAVE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Llamadas, tokens y latencias de un proveedor en un día.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderUsage {
+    pub calls: u64,
+    pub errors: u64,
+    pub prompt_tokens: u64,
+    pub response_tokens: u64,
+    pub latencies_ms: Vec<u32>,
+}
+
+/// Uso registrado en un día, por comando y por proveedor.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DailyUsage {
+    pub commands: BTreeMap<String, u64>,
+    pub providers: BTreeMap<String, ProviderUsage>,
+}
+
+/// Totales de un proveedor en el periodo consultado.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProviderSummary {
+    pub name: String,
+    pub calls: u64,
+    pub errors: u64,
+    pub prompt_tokens: u64,
+    pub response_tokens: u64,
+    pub p50_ms: Option<u32>,
+    pub p90_ms: Option<u32>,
+    pub p99_ms: Option<u32>,
+}
+
+/// Resumen del periodo que muestra el panel de uso.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UsageSummary {
+    /// Un punto por día del periodo, incluidos los días sin actividad.
+    pub days: Vec<(NaiveDate, u64, u64)>,
+    /// Comandos ordenados de más a menos usado.
+    pub commands: Vec<(String, u64)>,
+    pub providers: Vec<ProviderSummary>,
+}
+
+impl UsageSummary {
+    pub fn total_calls(&self) -> u64 {
+        self.providers.iter().map(|provider| provider.calls).sum()
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.providers
+            .iter()
+            .map(|provider| provider.prompt_tokens + provider.response_tokens)
+            .sum()
+    }
+
+    pub fn total_commands(&self) -> u64 {
+        self.commands.iter().map(|(_, count)| count).sum()
+    }
+}
+
+/// Contadores de uso por día, guardados solo en el directorio de datos local. El módulo
+/// no depende de `api` ni del flujo de eventos: no hay ningún camino por el que estas
+/// métricas puedan salir del equipo.
+#[derive(Default)]
+pub struct UsageMetrics {
+    days: BTreeMap<NaiveDate, DailyUsage>,
+    dirty: bool,
+    last_saved: Option<Instant>,
+}
+
+impl UsageMetrics {
+    pub fn load() -> Self {
+        let days = AppConfig::data_file(METRICS_FILE)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            days,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.days.is_empty()
+    }
+
+    pub fn record_command(&mut self, day: NaiveDate, name: &str) {
+        *self
+            .days
+            .entry(day)
+            .or_default()
+            .commands
+            .entry(name.to_string())
+            .or_default() += 1;
+        self.dirty = true;
+    }
+
+    /// Anota una llamada a `provider`. Las respuestas desde caché no tienen latencia real
+    /// y se registran con `latency_ms = None`.
+    pub fn record_provider_call(
+        &mut self,
+        day: NaiveDate,
+        provider: &str,
+        prompt_tokens: usize,
+        response_tokens: usize,
+        latency_ms: Option<u32>,
+        failed: bool,
+    ) {
+        let usage = self
+            .days
+            .entry(day)
+            .or_default()
+            .providers
+            .entry(provider.to_string())
+            .or_default();
+        usage.calls += 1;
+        usage.errors += u64::from(failed);
+        usage.prompt_tokens += prompt_tokens as u64;
+        usage.response_tokens += response_tokens as u64;
+        if let Some(latency) = latency_ms {
+            if usage.latencies_ms.len() >= MAX_LATENCY_SAMPLES {
+                usage.latencies_ms.remove(0);
+            }
+            usage.latencies_ms.push(latency);
+        }
+        self.dirty = true;
+    }
+
+    /// Descarta los días anteriores a la retención configurada.
+    pub fn prune(&mut self, today: NaiveDate, retention_days: u32) {
+        let Some(oldest) = today.checked_sub_days(Days::new(u64::from(retention_days))) else {
+            return;
+        };
+        let before = self.days.len();
+        self.days.retain(|day, _| *day > oldest);
+        self.dirty |= self.days.len() != before;
+    }
+
+    /// Agrega los últimos `days` días hasta `today` incluido.
+    pub fn summary(&self, today: NaiveDate, days: u32) -> UsageSummary {
+        let first = today
+            .checked_sub_days(Days::new(u64::from(days.max(1) - 1)))
+            .unwrap_or(today);
+        let mut summary = UsageSummary::default();
+        let mut commands: BTreeMap<&str, u64> = BTreeMap::new();
+        let mut providers: BTreeMap<&str, ProviderUsage> = BTreeMap::new();
+
+        for day in first.iter_days().take_while(|day| *day <= today) {
+            let Some(usage) = self.days.get(&day) else {
+                summary.days.push((day, 0, 0));
+                continue;
+            };
+            let mut calls = 0;
+            let mut tokens = 0;
+            for (name, count) in &usage.commands {
+                *commands.entry(name).or_default() += count;
+            }
+            for (name, provider) in &usage.providers {
+                calls += provider.calls;
+                tokens += provider.prompt_tokens + provider.response_tokens;
+                let total = providers.entry(name).or_default();
+                total.calls += provider.calls;
+                total.errors += provider.errors;
+                total.prompt_tokens += provider.prompt_tokens;
+                total.response_tokens += provider.response_tokens;
+                total.latencies_ms.extend(&provider.latencies_ms);
+            }
+            summary.days.push((day, calls, tokens));
+        }
+
+        summary.commands = commands
+            .into_iter()
+            .map(|(name, count)| (name.to_string(), count))
+            .collect();
+        summary
+            .commands
+            .sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        summary.providers = providers
+            .into_iter()
+            .map(|(name, mut usage)| {
+                usage.latencies_ms.sort_unstable();
+                ProviderSummary {
+                    name: name.to_string(),
+                    calls: usage.calls,
+                    errors: usage.errors,
+                    prompt_tokens: usage.prompt_tokens,
+                    response_tokens: usage.response_tokens,
+                    p50_ms: percentile(&usage.latencies_ms, 50.0),
+                    p90_ms: percentile(&usage.latencies_ms, 90.0),
+                    p99_ms: percentile(&usage.latencies_ms, 99.0),
+                }
+            })
+            .collect();
+        summary
+            .providers
+            .sort_by_key(|provider| std::cmp::Reverse(provider.calls));
+        summary
+    }
+
+    /// Guarda los cambios si ha pasado el intervalo mínimo desde la última escritura.
+    pub fn save_if_due(&mut self) -> Result<()> {
+        if self
+            .last_saved
+            .is_some_and(|saved| saved.elapsed() < SAVE_INTERVAL)
+        {
+            return Ok(());
+        }
+        self.save()
+    }
+
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let path = AppConfig::data_file(METRICS_FILE)?;
+        let json = serde_json::to_string(&self.days)?;
+        fs::write(&path, json).with_context(|| format!("No se pudo guardar {}", path.display()))?;
+        self.dirty = false;
+        self.last_saved = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Borra todas las métricas, también del disco.
+    pub fn clear(&mut self) -> Result<()> {
+        self.days.clear();
+        self.dirty = false;
+        let path = AppConfig::data_file(METRICS_FILE)?;
+        if path.exists() {
+            fs::remove_file(&path)
+                .with_context(|| format!("No se pudo borrar {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+/// Percentil `p` por el método del rango más cercano sobre valores ya ordenados.
+pub fn percentile(sorted: &[u32], p: f64) -> Option<u32> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_days_and_latency_percentiles() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 15).unwrap();
+        let yesterday = NaiveDate::from_ymd_opt(2024, 5, 14).unwrap();
+        let old = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut metrics = UsageMetrics::default();
+        metrics.record_command(today, "resumen");
+        metrics.record_command(yesterday, "resumen");
+        metrics.record_command(today, "buscar");
+        for latency in 1..=100 {
+            metrics.record_provider_call(today, "OpenAI", 10, 5, Some(latency), false);
+        }
+        metrics.record_provider_call(yesterday, "Groq", 4, 0, None, true);
+        metrics.record_provider_call(old, "Groq", 1, 1, Some(7), false);
+
+        metrics.prune(today, 90);
+        let summary = metrics.summary(today, 7);
+        assert_eq!(summary.days.len(), 7);
+        assert_eq!(summary.days[6], (today, 100, 1500));
+        assert_eq!(summary.days[5], (yesterday, 1, 4));
+        assert_eq!(
+            summary.commands,
+            vec![("resumen".to_string(), 2), ("buscar".to_string(), 1)]
+        );
+
+        let openai = &summary.providers[0];
+        assert_eq!(openai.name, "OpenAI");
+        assert_eq!(
+            (openai.p50_ms, openai.p90_ms, openai.p99_ms),
+            (Some(50), Some(90), Some(99))
+        );
+        let groq = &summary.providers[1];
+        assert_eq!((groq.calls, groq.errors, groq.p50_ms), (1, 1, None));
+        assert_eq!(summary.total_calls(), 101);
+    }
+}
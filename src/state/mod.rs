@@ -1,20 +1,148 @@
+pub mod attachments;
+pub mod audit;
 pub mod automation;
+pub mod batch_eval;
+pub mod benchmark;
+pub mod bookmarks;
+pub mod briefing;
+pub mod budget;
 pub mod chat;
+pub mod clipboard;
+pub mod code_viewer;
+pub mod commands;
+pub mod composer;
+pub mod conditions;
+pub mod config_saver;
+pub mod connectivity;
+pub mod context;
+pub mod conversations;
+pub mod cron;
+pub mod cron_calendar;
+pub mod digest;
+pub mod documents;
+pub mod email;
+pub mod embeddings;
+pub mod event_stream;
 pub mod feature;
+pub mod file_watcher;
+pub mod follow_ups;
+pub mod git_assist;
 pub mod jarvis_orchestrator;
+pub mod job_queue;
+pub mod keybindings;
+pub mod knowledge_index;
+pub mod language;
+pub mod local_only;
+pub mod metrics;
+pub mod model_pool;
+pub mod model_trash;
+pub mod model_updates;
+pub mod mqtt;
+pub mod notifications;
+pub mod ocr;
+pub mod performance;
+pub mod permissions;
+pub mod playbooks;
+pub mod project_context;
+pub mod quantize;
+pub mod quick_tests;
+pub mod redaction;
+pub mod reports;
 pub mod resources;
-
+pub mod response_cache;
+pub mod runtime;
+pub mod safety;
+pub mod search_index;
+pub mod session;
+pub mod share;
+pub mod size_index;
+pub mod state_journal;
+pub mod structured;
+pub mod system_monitor;
+pub mod task_chain;
+pub mod telegram;
+pub mod threads;
+pub mod toasts;
+pub mod transcription;
+pub mod vault;
+pub mod web_search;
+pub mod webhooks;
+pub mod workflow_bundle;
+
+pub use attachments::{AttachmentKind, AttachmentState, ComposerAttachment};
+pub use audit::{AuditEntry, AuditEventKind, AuditState, AuditVerification};
 pub use automation::AutomationState;
-pub use chat::ChatState;
+pub use batch_eval::{BatchEvalState, BatchTarget};
+pub use bookmarks::{Bookmark, BookmarkState};
+pub use budget::BudgetPeriod;
+pub use chat::{ChatState, CostConfirmation, CustomCommandDraft, LocalOnlyConfirmation};
+pub use code_viewer::{CodeAnchor, CodeViewerState};
+use code_viewer::{GitAnswer, GitQuery};
+pub use commands::{
+    Command, CommandContext, CommandDocumentation, CommandEngine, CommandInvocation,
+    CommandOutcome, MAX_COMMAND_DEPTH,
+};
+pub use composer::ComposerState;
+pub use conditions::{ConditionValue, EventPayload, VariableResolver};
+pub use config_saver::ConfigSaver;
+pub use connectivity::ConnectivityState;
+pub use context::ContextEstimate;
+pub use conversations::{ConversationDefaults, ConversationStore};
+pub use digest::DigestState;
+pub use embeddings::{EmbeddingState, SemanticHit, SemanticSource};
+pub use event_stream::{EventStreamState, StreamEvent};
 pub use feature::{CommandRegistry, FeatureModule, WorkbenchRegistry};
+pub use file_watcher::{FileChangeEvent, FileWatcherState};
+pub use keybindings::{KeyChord, KeybindingState, ShortcutAction};
+pub use knowledge_index::{KnowledgeIndexState, ReindexReport};
+pub use metrics::UsageMetrics;
+pub use model_trash::{ModelDiskUsage, OrphanModelDir, UninstallMode};
+pub use model_updates::{ModelUpdateState, ModelUpdateStatus};
+pub use notifications::NotificationSource;
+pub use performance::{JarvisCallSample, JarvisPerformanceState};
+pub use playbooks::PlaybookState;
+pub use redaction::{PreviewSegment, RedactionState};
+pub use reports::{GeneratedReport, ReportState};
 pub use resources::ResourceState;
-
+pub use response_cache::{ResponseCache, ResponseCacheState};
+pub use safety::SafetyState;
+pub use session::{SessionSnapshot, SessionState};
+pub use share::ConversationShareState;
+pub use size_index::{DirectorySize, SizeIndex};
+pub use structured::{StructuredOutputState, StructuredRequest};
+pub use system_monitor::{LoadLevel, ResourceSample, SystemMonitor};
+pub use threads::{ConversationCheckpoint, ConversationThread, ThreadState};
+pub use toasts::{ToastAction, ToastState};
+pub use transcription::TranscriptionState;
+
+use batch_eval::{BatchPrompt, BatchResult, BatchRun, RemoteBatchOutcome};
+use connectivity::QueuedProviderCall;
+use embeddings::{EmbeddingEngine, EmbeddingPurpose};
 use jarvis_orchestrator::JarvisOrchestrator;
+use job_queue::JobPriority;
+use mqtt::MqttEvent;
+use search_index::{SearchEntry, SearchSource};
+use telegram::{TelegramEvent, TelegramRelay};
 
 use crate::{
-    api::{claude::AnthropicModel, local::JarvisRuntime},
-    config::{AppConfig, InstalledModelConfig},
+    api::{
+        claude::AnthropicModel,
+        error::ProviderError,
+        github,
+        local::{self, JarvisRuntime, LoraAdapter},
+        GenerationOptions,
+    },
+    config::{
+        AppConfig, AutomationPermissionGrant, ConfigRecovery, ContextStrategy, DailyDigestConfig,
+        EmbeddingBackend, EventStreamConfig, InstalledModelConfig, JarvisModelAlias,
+        KnowledgeReindexSchedule, LayoutPreset, LayoutSnapshot, NetworkConfig, NotificationChannel,
+        Playbook, PopoutWindow, ProjectContextProfile, QuickTestCase, RedactionConfig,
+        ReportTemplate, SafetyAction, StorageEncryptionConfig, StorageKeySource,
+        TrashedModelConfig, WatchedPath, WebhookConnectorConfig,
+    },
+    i18n::{self, t, tf, Language},
     local_providers::{LocalModelCard, LocalModelIdentifier, LocalModelProvider},
+    tokenizer::TokenizerService,
     ui::{
         theme::{self, FontSource, ThemePreset, ThemeTokens},
         workbench::WorkbenchView,
@@ -29,7 +157,10 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::mpsc::RecvTimeoutError;
 use std::time::{Duration, Instant};
-use vscode_shell::{layout::LayoutConfig, AppShell};
+use vscode_shell::{
+    layout::{LayoutConfig, NAVIGATION_WIDTH_RANGE, RESOURCE_WIDTH_RANGE},
+    AppShell,
+};
 
 pub use navigation::{
     NavigationNode, NavigationRegistry, NavigationTarget, SECTION_PRIMARY,
@@ -50,8 +181,24 @@ pub enum PreferencePanel {
     SystemGithub,
     SystemCache,
     SystemResources,
+    SystemNetwork,
+    SystemJobQueue,
+    SystemWebSearch,
+    SystemEmail,
+    SystemTelegram,
+    SystemMqtt,
+    SystemRedaction,
+    SystemUsage,
+    SystemNotifications,
+    SystemPermissions,
+    SystemStorage,
     CustomizationCommands,
     CustomizationAppearance,
+    CustomizationAccessibility,
+    CustomizationKeybindings,
+    CustomizationVoice,
+    CustomizationDigest,
+    CustomizationTextActions,
     CustomizationMemory,
     CustomizationProfiles,
     CustomizationProjects,
@@ -59,6 +206,9 @@ pub enum PreferencePanel {
     ProvidersOpenAi,
     ProvidersGroq,
     LocalJarvis,
+    LocalJarvisKnowledge,
+    LocalJarvisSafety,
+    LocalOcr,
 }
 
 impl PreferencePanel {
@@ -82,6 +232,72 @@ impl PreferencePanel {
                     "Delimita el uso permitido de memoria y almacenamiento para la ejecución local.",
                 breadcrumb: &["Preferencias", "Sistema", "Recursos"],
             },
+            PreferencePanel::SystemNetwork => PanelMetadata {
+                title: "Preferencias › Sistema › Red",
+                description:
+                    "Define el proxy, los certificados raíz adicionales y el timeout de las peticiones salientes.",
+                breadcrumb: &["Preferencias", "Sistema", "Red"],
+            },
+            PreferencePanel::SystemJobQueue => PanelMetadata {
+                title: "Preferencias › Sistema › Cola de llamadas",
+                description:
+                    "Limita las llamadas simultáneas y por minuto a cada proveedor e integración, y revisa o cancela las que esperan turno. Las del chat se atienden antes que las de segundo plano.",
+                breadcrumb: &["Preferencias", "Sistema", "Cola de llamadas"],
+            },
+            PreferencePanel::SystemWebSearch => PanelMetadata {
+                title: "Preferencias › Sistema › Búsqueda web",
+                description:
+                    "Elige el buscador (SearXNG, Brave o Bing) que usan el comando /search y los proveedores remotos cuando necesitan información actual.",
+                breadcrumb: &["Preferencias", "Sistema", "Búsqueda web"],
+            },
+            PreferencePanel::SystemEmail => PanelMetadata {
+                title: "Preferencias › Sistema › Correo",
+                description:
+                    "Configura el servidor SMTP y las plantillas con las que se envían por correo los informes, los resúmenes diarios y las conversaciones exportadas.",
+                breadcrumb: &["Preferencias", "Sistema", "Correo"],
+            },
+            PreferencePanel::SystemTelegram => PanelMetadata {
+                title: "Preferencias › Sistema › Telegram",
+                description:
+                    "Conecta un bot de Telegram para conversar con tu agente desde el móvil. Los mensajes de los chats autorizados llegan a un hilo propio y reciben la respuesta de la ruta habitual.",
+                breadcrumb: &["Preferencias", "Sistema", "Telegram"],
+            },
+            PreferencePanel::SystemMqtt => PanelMetadata {
+                title: "Preferencias › Sistema › MQTT",
+                description:
+                    "Conecta con un broker MQTT, como el de Home Assistant, para publicar los resultados de los workflows y usar los temas suscritos como disparadores de eventos.",
+                breadcrumb: &["Preferencias", "Sistema", "MQTT"],
+            },
+            PreferencePanel::SystemRedaction => PanelMetadata {
+                title: "Preferencias › Sistema › Redacción",
+                description:
+                    "Oculta claves, correos y hosts internos de los prompts antes de enviarlos a proveedores remotos.",
+                breadcrumb: &["Preferencias", "Sistema", "Redacción"],
+            },
+            PreferencePanel::SystemUsage => PanelMetadata {
+                title: "Preferencias › Sistema › Uso",
+                description:
+                    "Consulta tus propias métricas de uso: comandos, proveedores, tokens y latencias. Se guardan solo en este equipo.",
+                breadcrumb: &["Preferencias", "Sistema", "Uso"],
+            },
+            PreferencePanel::SystemNotifications => PanelMetadata {
+                title: "Preferencias › Sistema › Notificaciones",
+                description:
+                    "Decide qué avisos del planificador, los proveedores y las integraciones llegan a la pantalla o al sistema, y cuándo guardar silencio.",
+                breadcrumb: &["Preferencias", "Sistema", "Notificaciones"],
+            },
+            PreferencePanel::SystemPermissions => PanelMetadata {
+                title: "Preferencias › Sistema › Permisos",
+                description:
+                    "Revisa qué efectos pueden provocar los workflows y listeners sin preguntar, como borrar ficheros, hacer git push, llamar a servicios externos o ejecutar comandos, y retira los permisos que ya no quieras.",
+                breadcrumb: &["Preferencias", "Sistema", "Permisos"],
+            },
+            PreferencePanel::SystemStorage => PanelMetadata {
+                title: "Preferencias › Sistema › Cifrado",
+                description:
+                    "Cifra en disco las conversaciones, los borradores, los marcadores y la sesión guardada con una clave del llavero del sistema o una frase de paso, y migra el historial que ya estaba en claro.",
+                breadcrumb: &["Preferencias", "Sistema", "Cifrado"],
+            },
             PreferencePanel::CustomizationCommands => PanelMetadata {
                 title: "Preferencias › Personalización › Comandos",
                 description:
@@ -94,6 +310,36 @@ impl PreferencePanel {
                     "Selecciona el tema claro u oscuro inspirado en la estética de VSCode.",
                 breadcrumb: &["Preferencias", "Personalización", "Apariencia"],
             },
+            PreferencePanel::CustomizationAccessibility => PanelMetadata {
+                title: "Preferencias › Personalización › Accesibilidad",
+                description:
+                    "Ajusta la escala de la interfaz, reduce las animaciones y configura la navegación por teclado.",
+                breadcrumb: &["Preferencias", "Personalización", "Accesibilidad"],
+            },
+            PreferencePanel::CustomizationKeybindings => PanelMetadata {
+                title: "Preferencias › Personalización › Atajos de teclado",
+                description:
+                    "Reasigna los atajos del chat y la navegación y detecta combinaciones en conflicto.",
+                breadcrumb: &["Preferencias", "Personalización", "Atajos de teclado"],
+            },
+            PreferencePanel::CustomizationVoice => PanelMetadata {
+                title: "Preferencias › Personalización › Entrada de voz",
+                description:
+                    "Elige cómo se transcriben las notas de voz y los audios adjuntos: Whisper local u OpenAI.",
+                breadcrumb: &["Preferencias", "Personalización", "Entrada de voz"],
+            },
+            PreferencePanel::CustomizationDigest => PanelMetadata {
+                title: "Preferencias › Personalización › Resumen diario",
+                description:
+                    "Programa un resumen de la actividad, los workflows, GitHub y la agenda redactado por el proveedor elegido.",
+                breadcrumb: &["Preferencias", "Personalización", "Resumen diario"],
+            },
+            PreferencePanel::CustomizationTextActions => PanelMetadata {
+                title: "Preferencias › Personalización › Acciones de texto",
+                description:
+                    "Edita las transformaciones del menú contextual de los mensajes (explicar, traducir, reescribir o extraer tareas), las sugerencias de seguimiento y el vigilante del portapapeles.",
+                breadcrumb: &["Preferencias", "Personalización", "Acciones de texto"],
+            },
             PreferencePanel::CustomizationMemory => PanelMetadata {
                 title: "Preferencias › Personalización › Memoria",
                 description:
@@ -136,6 +382,24 @@ impl PreferencePanel {
                     "Controla la ruta, instalación y comportamiento de arranque del runtime Jarvis.",
                 breadcrumb: &["Preferencias", "Modelos locales", "Jarvis"],
             },
+            PreferencePanel::LocalJarvisKnowledge => PanelMetadata {
+                title: "Preferencias › Modelos locales › Base de conocimientos",
+                description:
+                    "Edita las entradas que enrutan los prompts de Jarvis, prueba el enrutado y recárgalas en caliente.",
+                breadcrumb: &["Preferencias", "Modelos locales", "Conocimiento"],
+            },
+            PreferencePanel::LocalJarvisSafety => PanelMetadata {
+                title: "Preferencias › Modelos locales › Filtros de seguridad",
+                description:
+                    "Bloquea, avisa o redacta lo que Jarvis recibe y responde con listas de palabras, expresiones regulares y un clasificador opcional.",
+                breadcrumb: &["Preferencias", "Modelos locales", "Filtros"],
+            },
+            PreferencePanel::LocalOcr => PanelMetadata {
+                title: "Preferencias › Modelos locales › OCR",
+                description:
+                    "Reconoce con Tesseract el texto de las imágenes adjuntas y de los proyectos para usarlo como contexto y en la búsqueda, y elige los paquetes de idioma.",
+                breadcrumb: &["Preferencias", "Modelos locales", "OCR"],
+            },
         }
     }
 }
@@ -154,6 +418,9 @@ pub enum ResourceSection {
     InstalledLocal,
     ConnectedProjects,
     GithubRepositories,
+    Bookmarks,
+    Reports,
+    Playbooks,
 }
 
 impl ResourceSection {
@@ -235,11 +502,29 @@ impl ResourceSection {
                     "Consulta repositorios enlazados con previews de README y sincronización bidireccional.",
                 breadcrumb: &["Recursos", "Productividad", "GitHub"],
             },
+            ResourceSection::Bookmarks => PanelMetadata {
+                title: "Recursos › Marcadores",
+                description:
+                    "Reúne los mensajes fijados en todas las conversaciones para volver a ellos o exportarlos.",
+                breadcrumb: &["Recursos", "Productividad", "Marcadores"],
+            },
+            ResourceSection::Reports => PanelMetadata {
+                title: "Recursos › Informes",
+                description:
+                    "Define plantillas Markdown con secciones redactadas por un modelo y consulta los informes generados.",
+                breadcrumb: &["Recursos", "Productividad", "Informes"],
+            },
+            ResourceSection::Playbooks => PanelMetadata {
+                title: "Recursos › Playbooks",
+                description:
+                    "Define flujos de varios pasos con campos y lánzalos contra el proveedor de la conversación para obtener un documento con las respuestas.",
+                breadcrumb: &["Recursos", "Productividad", "Playbooks"],
+            },
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MainView {
     ChatMultimodal,
     CronScheduler,
@@ -247,6 +532,9 @@ pub enum MainView {
     DebugConsole,
     Preferences,
     ResourceBrowser,
+    BatchEvaluation,
+    Audit,
+    CodeViewer,
 }
 
 impl Default for MainView {
@@ -287,7 +575,11 @@ impl MainTab {
             MainView::CronScheduler => Some(MainTab::Cron),
             MainView::ActivityFeed => Some(MainTab::Activity),
             MainView::DebugConsole => Some(MainTab::DebugConsole),
-            MainView::Preferences | MainView::ResourceBrowser => None,
+            MainView::Preferences
+            | MainView::ResourceBrowser
+            | MainView::BatchEvaluation
+            | MainView::Audit
+            | MainView::CodeViewer => None,
         }
     }
 }
@@ -337,13 +629,34 @@ mod navigation {
                     MainView::DebugConsole => "main:debug".into(),
                     MainView::Preferences => "main:preferences".into(),
                     MainView::ResourceBrowser => "main:resources".into(),
+                    MainView::BatchEvaluation => "main:batch_eval".into(),
+                    MainView::Audit => "main:audit".into(),
+                    MainView::CodeViewer => "main:code".into(),
                 },
                 NavigationTarget::Preference(panel) => match panel {
                     PreferencePanel::SystemGithub => "pref:system_github".into(),
                     PreferencePanel::SystemCache => "pref:system_cache".into(),
                     PreferencePanel::SystemResources => "pref:system_resources".into(),
+                    PreferencePanel::SystemNetwork => "pref:system_network".into(),
+                    PreferencePanel::SystemJobQueue => "pref:system_job_queue".into(),
+                    PreferencePanel::SystemWebSearch => "pref:system_web_search".into(),
+                    PreferencePanel::SystemEmail => "pref:system_email".into(),
+                    PreferencePanel::SystemTelegram => "pref:system_telegram".into(),
+                    PreferencePanel::SystemMqtt => "pref:system_mqtt".into(),
+                    PreferencePanel::SystemRedaction => "pref:system_redaction".into(),
+                    PreferencePanel::SystemUsage => "pref:system_usage".into(),
+                    PreferencePanel::SystemNotifications => "pref:system_notifications".into(),
+                    PreferencePanel::SystemPermissions => "pref:system_permissions".into(),
+                    PreferencePanel::SystemStorage => "pref:system_storage".into(),
                     PreferencePanel::CustomizationCommands => "pref:custom_commands".into(),
                     PreferencePanel::CustomizationAppearance => "pref:custom_appearance".into(),
+                    PreferencePanel::CustomizationAccessibility => {
+                        "pref:custom_accessibility".into()
+                    }
+                    PreferencePanel::CustomizationKeybindings => "pref:custom_keybindings".into(),
+                    PreferencePanel::CustomizationVoice => "pref:custom_voice".into(),
+                    PreferencePanel::CustomizationDigest => "pref:custom_digest".into(),
+                    PreferencePanel::CustomizationTextActions => "pref:custom_text_actions".into(),
                     PreferencePanel::CustomizationMemory => "pref:custom_memory".into(),
                     PreferencePanel::CustomizationProfiles => "pref:custom_profiles".into(),
                     PreferencePanel::CustomizationProjects => "pref:custom_projects".into(),
@@ -351,6 +664,9 @@ mod navigation {
                     PreferencePanel::ProvidersOpenAi => "pref:providers_openai".into(),
                     PreferencePanel::ProvidersGroq => "pref:providers_groq".into(),
                     PreferencePanel::LocalJarvis => "pref:local_jarvis".into(),
+                    PreferencePanel::LocalJarvisKnowledge => "pref:local_jarvis_knowledge".into(),
+                    PreferencePanel::LocalJarvisSafety => "pref:local_jarvis_safety".into(),
+                    PreferencePanel::LocalOcr => "pref:local_ocr".into(),
                 },
                 NavigationTarget::Resource(section) => match section {
                     ResourceSection::LocalCatalog(provider) => {
@@ -362,6 +678,9 @@ mod navigation {
                     ResourceSection::InstalledLocal => "resource:installed".into(),
                     ResourceSection::ConnectedProjects => "resource:projects".into(),
                     ResourceSection::GithubRepositories => "resource:github".into(),
+                    ResourceSection::Bookmarks => "resource:bookmarks".into(),
+                    ResourceSection::Reports => "resource:reports".into(),
+                    ResourceSection::Playbooks => "resource:playbooks".into(),
                 },
             }
         }
@@ -534,6 +853,76 @@ impl RemoteProviderKind {
             RemoteProviderKind::Groq => "groq",
         }
     }
+
+    /// Parámetros que cada cliente envía junto al prompt, con los ajustes de la
+    /// conversación aplicados; forman parte de la clave de la caché de respuestas.
+    pub fn request_parameters(self, options: &GenerationOptions) -> String {
+        let mut parameters = match self {
+            RemoteProviderKind::Anthropic => {
+                let mut parameters = format!(
+                    "max_tokens={}",
+                    options.max_tokens.unwrap_or(crate::api::claude::MAX_TOKENS)
+                );
+                if let Some(temperature) = options.temperature {
+                    parameters.push_str(&format!(";temperature={}", temperature.clamp(0.0, 1.0)));
+                }
+                parameters
+            }
+            RemoteProviderKind::OpenAi => format!(
+                "max_tokens={};temperature={}",
+                options.max_tokens.unwrap_or(crate::api::openai::MAX_TOKENS),
+                options
+                    .temperature
+                    .unwrap_or(crate::api::openai::TEMPERATURE)
+            ),
+            RemoteProviderKind::Groq => format!(
+                "max_tokens={};temperature={}",
+                options.max_tokens.unwrap_or(crate::api::groq::MAX_TOKENS),
+                options.temperature.unwrap_or(crate::api::groq::TEMPERATURE)
+            ),
+        };
+        if let Some(system) = &options.system {
+            parameters.push_str(&format!(";system={}", system));
+        }
+        parameters
+    }
+
+    /// Cliente que acepta imágenes, en los proveedores con modelos de visión.
+    fn vision_caller(self) -> Option<VisionCaller> {
+        match self {
+            RemoteProviderKind::Anthropic => Some(crate::api::claude::describe_image),
+            RemoteProviderKind::OpenAi => Some(crate::api::openai::describe_image),
+            RemoteProviderKind::Groq => None,
+        }
+    }
+
+    /// Cliente con modo JSON nativo, en los proveedores que lo ofrecen.
+    fn json_caller(self) -> Option<ProviderCaller> {
+        match self {
+            RemoteProviderKind::Anthropic => None,
+            RemoteProviderKind::OpenAi => Some(crate::api::openai::send_message_json),
+            RemoteProviderKind::Groq => Some(crate::api::groq::send_message_json),
+        }
+    }
+
+    pub fn from_short_code(code: &str) -> Option<Self> {
+        [
+            RemoteProviderKind::Anthropic,
+            RemoteProviderKind::OpenAi,
+            RemoteProviderKind::Groq,
+        ]
+        .into_iter()
+        .find(|provider| provider.short_code().eq_ignore_ascii_case(code.trim()))
+    }
+
+    /// Panel de preferencias donde se configuran las credenciales del proveedor.
+    pub fn preference_panel(self) -> PreferencePanel {
+        match self {
+            RemoteProviderKind::Anthropic => PreferencePanel::ProvidersAnthropic,
+            RemoteProviderKind::OpenAi => PreferencePanel::ProvidersOpenAi,
+            RemoteProviderKind::Groq => PreferencePanel::ProvidersGroq,
+        }
+    }
 }
 
 fn build_navigation_registry(config: &AppConfig) -> NavigationRegistry {
@@ -622,6 +1011,17 @@ fn build_navigation_registry(config: &AppConfig) -> NavigationRegistry {
                 PreferencePanel::SystemGithub,
                 PreferencePanel::SystemCache,
                 PreferencePanel::SystemResources,
+                PreferencePanel::SystemNetwork,
+                PreferencePanel::SystemJobQueue,
+                PreferencePanel::SystemWebSearch,
+                PreferencePanel::SystemEmail,
+                PreferencePanel::SystemTelegram,
+                PreferencePanel::SystemMqtt,
+                PreferencePanel::SystemRedaction,
+                PreferencePanel::SystemUsage,
+                PreferencePanel::SystemNotifications,
+                PreferencePanel::SystemPermissions,
+                PreferencePanel::SystemStorage,
             ],
         ),
         (
@@ -629,6 +1029,11 @@ fn build_navigation_registry(config: &AppConfig) -> NavigationRegistry {
             &[
                 PreferencePanel::CustomizationCommands,
                 PreferencePanel::CustomizationAppearance,
+                PreferencePanel::CustomizationAccessibility,
+                PreferencePanel::CustomizationKeybindings,
+                PreferencePanel::CustomizationVoice,
+                PreferencePanel::CustomizationDigest,
+                PreferencePanel::CustomizationTextActions,
                 PreferencePanel::CustomizationMemory,
                 PreferencePanel::CustomizationProfiles,
                 PreferencePanel::CustomizationProjects,
@@ -642,7 +1047,15 @@ fn build_navigation_registry(config: &AppConfig) -> NavigationRegistry {
                 PreferencePanel::ProvidersGroq,
             ],
         ),
-        (SECTION_PREFERENCES_LOCAL, &[PreferencePanel::LocalJarvis]),
+        (
+            SECTION_PREFERENCES_LOCAL,
+            &[
+                PreferencePanel::LocalJarvis,
+                PreferencePanel::LocalJarvisKnowledge,
+                PreferencePanel::LocalJarvisSafety,
+                PreferencePanel::LocalOcr,
+            ],
+        ),
     ];
 
     for (section_id, panels) in preference_groups {
@@ -747,6 +1160,37 @@ impl RemoteModelCard {
     }
 }
 
+impl RemoteModelCard {
+    /// Ficha de un modelo descubierto en la API, sin precios ni latencia conocidos.
+    pub fn from_anthropic(model: &AnthropicModel) -> Self {
+        let mut capabilities = vec!["texto"];
+        let mut tags = vec!["api"];
+        if model.supports_vision {
+            capabilities.push("visión");
+            tags.push("multimodal");
+        }
+        Self::sample(
+            RemoteProviderKind::Anthropic,
+            &model.id,
+            model.label(),
+            model
+                .description
+                .as_deref()
+                .unwrap_or("Modelo publicado por la API de Anthropic para esta cuenta."),
+            model.context_window.unwrap_or(0),
+            model.output_token_limit.unwrap_or(0),
+            0.0,
+            0.0,
+            0,
+            tags,
+            capabilities,
+            "Descubierto al actualizar el catálogo de Anthropic.",
+            Vec::new(),
+            model.supports_vision,
+        )
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct RemoteCatalogFilters {
     pub search: String,
@@ -967,6 +1411,32 @@ impl RemoteCatalogState {
         self.provider_cards.entry(provider).or_default()
     }
 
+    /// Incorpora los modelos de Claude publicados para la cuenta: actualiza las fichas
+    /// existentes con sus capacidades y añade las que faltan, conservando precios y notas.
+    pub fn merge_anthropic_models(&mut self, models: &[AnthropicModel]) -> usize {
+        let cards = self.cards_for_mut(RemoteProviderKind::Anthropic);
+        let mut added = 0;
+        for model in models {
+            match cards.iter_mut().find(|card| card.key.id == model.id) {
+                Some(card) => {
+                    card.title = model.label().to_string();
+                    if let Some(context) = model.context_window {
+                        card.context_tokens = context;
+                    }
+                    if let Some(limit) = model.output_token_limit {
+                        card.max_output_tokens = limit;
+                    }
+                    card.multimodal = model.supports_vision;
+                }
+                None => {
+                    cards.push(RemoteModelCard::from_anthropic(model));
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
     pub fn is_favorite(&self, key: &RemoteModelKey) -> bool {
         self.favorites.contains(key)
     }
@@ -1249,6 +1719,8 @@ pub enum ScheduledTaskStatus {
     Success,
     Failed,
     Paused,
+    /// No se lanzó porque una tarea previa de su cadena falló.
+    Skipped,
 }
 
 impl ScheduledTaskStatus {
@@ -1259,10 +1731,25 @@ impl ScheduledTaskStatus {
             ScheduledTaskStatus::Success => "Completado",
             ScheduledTaskStatus::Failed => "Error",
             ScheduledTaskStatus::Paused => "Pausado",
+            ScheduledTaskStatus::Skipped => "Omitido",
         }
     }
 }
 
+/// Acción que ejecuta el planificador cuando vence una tarea.
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum ScheduledTaskKind {
+    /// Tarea informativa sin acción integrada.
+    #[default]
+    Generic,
+    /// Reindexado incremental de los documentos de un proyecto local.
+    ReindexKnowledge { project: String },
+    /// Resumen diario de la actividad, publicado en su propio hilo.
+    DailyDigest,
+    /// Informe generado a partir de la plantilla con ese nombre.
+    GenerateReport { template: String },
+}
+
 #[derive(Clone, Debug)]
 pub struct ScheduledTask {
     pub id: u32,
@@ -1277,6 +1764,7 @@ pub struct ScheduledTask {
     pub provider: Option<RemoteProviderKind>,
     pub tags: Vec<String>,
     pub enabled: bool,
+    pub kind: ScheduledTaskKind,
 }
 
 impl ScheduledTask {
@@ -1284,6 +1772,96 @@ impl ScheduledTask {
         self.provider
             .map(|provider| format!("@{}", provider.short_code()))
     }
+
+    /// Tarea integrada "Reindexar base de conocimiento" a partir de su programación.
+    pub fn knowledge_reindex(id: u32, schedule: &KnowledgeReindexSchedule) -> Self {
+        let next_run = cron::CronSchedule::parse(&schedule.cron_expression)
+            .ok()
+            .and_then(|cron| cron.next_after(Local::now()))
+            .map(|next| next.format("%Y-%m-%d %H:%M").to_string());
+        Self {
+            id,
+            name: format!("Reindexar base de conocimiento · {}", schedule.project),
+            description: format!(
+                "Actualiza en el índice semántico los documentos de '{}' que han cambiado desde la última pasada.",
+                schedule.project
+            ),
+            cron_expression: schedule.cron_expression.clone(),
+            cadence_label: schedule.cron_expression.clone(),
+            last_run: schedule.last_run.clone(),
+            next_run,
+            status: if schedule.enabled {
+                ScheduledTaskStatus::Scheduled
+            } else {
+                ScheduledTaskStatus::Paused
+            },
+            owner: "Knowledge".to_string(),
+            provider: None,
+            tags: vec!["rag".to_string(), "conocimiento".to_string()],
+            enabled: schedule.enabled,
+            kind: ScheduledTaskKind::ReindexKnowledge {
+                project: schedule.project.clone(),
+            },
+        }
+    }
+
+    /// Tarea integrada "Resumen diario". `None` si la hora configurada no es válida.
+    pub fn daily_digest(id: u32, config: &DailyDigestConfig) -> Option<Self> {
+        let cron_expression = digest::cron_expression(&config.time).ok()?;
+        let next_run = cron::CronSchedule::parse(&cron_expression)
+            .ok()
+            .and_then(|cron| cron.next_after(Local::now()))
+            .map(|next| next.format("%Y-%m-%d %H:%M").to_string());
+        Some(Self {
+            id,
+            name: "Resumen diario".to_string(),
+            description: "Reúne la actividad, los workflows, las notificaciones de GitHub y la agenda del día y publica un resumen en su propia conversación.".to_string(),
+            cron_expression,
+            cadence_label: format!("Cada día a las {}", config.time.trim()),
+            last_run: config.last_run.clone(),
+            next_run,
+            status: ScheduledTaskStatus::Scheduled,
+            owner: "Resumen diario".to_string(),
+            provider: config
+                .provider
+                .as_deref()
+                .and_then(RemoteProviderKind::from_short_code),
+            tags: vec!["resumen".to_string()],
+            enabled: config.enabled,
+            kind: ScheduledTaskKind::DailyDigest,
+        })
+    }
+
+    /// Tarea integrada que genera el informe de una plantilla con programación.
+    pub fn report(id: u32, template: &ReportTemplate) -> Self {
+        let next_run = cron::CronSchedule::parse(&template.cron_expression)
+            .ok()
+            .and_then(|cron| cron.next_after(Local::now()))
+            .map(|next| next.format("%Y-%m-%d %H:%M").to_string());
+        Self {
+            id,
+            name: format!("Informe · {}", template.name),
+            description: format!(
+                "Genera el informe '{}' y lo escribe en su directorio de salida.",
+                template.name
+            ),
+            cron_expression: template.cron_expression.clone(),
+            cadence_label: template.cron_expression.clone(),
+            last_run: template.last_run.clone(),
+            next_run,
+            status: ScheduledTaskStatus::Scheduled,
+            owner: "Informes".to_string(),
+            provider: template
+                .provider
+                .as_deref()
+                .and_then(RemoteProviderKind::from_short_code),
+            tags: vec!["informes".to_string()],
+            enabled: true,
+            kind: ScheduledTaskKind::GenerateReport {
+                template: template.name.clone(),
+            },
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1293,6 +1871,16 @@ pub struct CronBoardState {
     pub provider_filter: Option<RemoteProviderKind>,
     pub tag_filter: Option<String>,
     pub selected_task: Option<u32>,
+    /// Último minuto ("%Y-%m-%d %H:%M") en el que el planificador revisó las tareas.
+    pub(crate) last_tick: Option<String>,
+    /// Motivo por el que se omitió cada tarea, por nombre.
+    pub skip_reasons: BTreeMap<String, String>,
+    /// Tarea previa elegida en el detalle para añadirla a la cadena.
+    pub dependency_draft: Option<String>,
+    pub chain_status: Option<String>,
+    pub calendar_span: cron_calendar::CalendarSpan,
+    /// Semanas o meses de desplazamiento del calendario respecto al actual.
+    pub calendar_offset: i32,
 }
 
 impl Default for CronBoardState {
@@ -1303,6 +1891,12 @@ impl Default for CronBoardState {
             provider_filter: None,
             tag_filter: None,
             selected_task: None,
+            last_tick: None,
+            skip_reasons: BTreeMap::new(),
+            dependency_draft: None,
+            chain_status: None,
+            calendar_span: cron_calendar::CalendarSpan::default(),
+            calendar_offset: 0,
         }
     }
 }
@@ -1370,6 +1964,14 @@ impl CronBoardState {
         self.selected_task
             .and_then(|id| self.tasks.iter().find(|task| task.id == id))
     }
+
+    pub fn task_by_name(&self, name: &str) -> Option<&ScheduledTask> {
+        self.tasks.iter().find(|task| task.name == name)
+    }
+
+    pub fn next_task_id(&self) -> u32 {
+        self.tasks.iter().map(|task| task.id).max().unwrap_or(0) + 1
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -1449,7 +2051,72 @@ pub struct AutomationWorkflow {
     pub steps: Vec<WorkflowStep>,
 }
 
-impl AutomationWorkflow {}
+impl AutomationWorkflow {
+    /// Nombres de los marcadores `{clave}` que aparecen en los pasos, sin repetir.
+    pub fn parameters(&self) -> Vec<String> {
+        let mut parameters: Vec<String> = Vec::new();
+        for step in &self.steps {
+            for text in [&step.label, &step.detail] {
+                for name in placeholder_names(text) {
+                    if !parameters.contains(&name) {
+                        parameters.push(name);
+                    }
+                }
+            }
+        }
+        parameters
+    }
+
+    /// Pasos con los marcadores sustituidos, o los parámetros que faltan.
+    pub fn resolve_steps(
+        &self,
+        values: &BTreeMap<String, String>,
+    ) -> Result<Vec<WorkflowStep>, Vec<String>> {
+        let missing: Vec<String> = self
+            .parameters()
+            .into_iter()
+            .filter(|name| !values.contains_key(name))
+            .collect();
+        if !missing.is_empty() {
+            return Err(missing);
+        }
+
+        let fill = |text: &str| {
+            values.iter().fold(text.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+        };
+        Ok(self
+            .steps
+            .iter()
+            .map(|step| WorkflowStep {
+                label: fill(&step.label),
+                detail: fill(&step.detail),
+                ..step.clone()
+            })
+            .collect())
+    }
+}
+
+fn placeholder_names(text: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start + 1..].find('}') else {
+            break;
+        };
+        let name = &rest[start + 1..start + 1 + length];
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            names.push(name.to_string());
+        }
+        rest = &rest[start + 1 + length + 1..];
+    }
+    names
+}
 
 #[derive(Clone, Debug)]
 pub struct AutomationWorkflowBoard {
@@ -1522,6 +2189,8 @@ pub enum ListenerEventKind {
     GithubChange,
     CommandExecution,
     Scheduler,
+    FileChange,
+    MqttMessage,
 }
 
 impl ListenerEventKind {
@@ -1531,6 +2200,8 @@ impl ListenerEventKind {
             ListenerEventKind::GithubChange => "Webhook GitHub",
             ListenerEventKind::CommandExecution => "Ejecución de comando",
             ListenerEventKind::Scheduler => "Finalización de tarea",
+            ListenerEventKind::FileChange => "Cambio de fichero",
+            ListenerEventKind::MqttMessage => "Mensaje MQTT",
         }
     }
 }
@@ -1705,6 +2376,8 @@ pub struct GlobalSearchResult {
     pub title: String,
     pub subtitle: String,
     pub action_hint: String,
+    /// Origen al que salta el resultado en lugar de repetir la búsqueda.
+    pub source: Option<SemanticSource>,
 }
 
 #[derive(Clone, Debug)]
@@ -1837,18 +2510,48 @@ pub(crate) enum LocalInstallMessage {
         provider: LocalModelProvider,
         model: LocalModelCard,
         install_path: PathBuf,
+        sizes: DirectorySize,
     },
     Error {
         provider: LocalModelProvider,
         model_id: String,
         error: String,
     },
+    UpdateCheck {
+        identifier: LocalModelIdentifier,
+        installed: Option<String>,
+        result: Result<crate::api::huggingface::RemoteRevision, String>,
+    },
+    Updated {
+        identifier: LocalModelIdentifier,
+        result: Result<crate::api::huggingface::ModelUpdate, String>,
+    },
 }
 
+/// Cada cuánto se recalcula la velocidad y se publica el progreso de una descarga.
+const INSTALL_PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+/// Cada cuánto se revisan los embeddings mientras se espera a una llamada fuera de la
+/// interfaz, como en la línea de comandos.
+const EMBEDDINGS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 #[derive(Clone, Debug)]
 pub(crate) struct PendingLocalInstall {
     provider: LocalModelProvider,
     model_id: String,
+    progress: std::sync::Arc<crate::api::huggingface::DownloadProgress>,
+    /// Última muestra de bytes descargados, para calcular la velocidad.
+    sample: (Instant, u64),
+    bytes_per_second: u64,
+}
+
+/// Progreso de una instalación en curso, para la interfaz.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstallProgress {
+    pub model_id: String,
+    pub downloaded: u64,
+    pub total: u64,
+    pub bytes_per_second: u64,
+    pub cancelled: bool,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -1867,6 +2570,9 @@ pub struct InstalledLocalModel {
     pub install_path: String,
     pub size_bytes: u64,
     pub installed_at: DateTime<Utc>,
+    pub revision: Option<String>,
+    /// Modelo original si este es una variante cuantizada generada en el equipo.
+    pub quantized_from: Option<LocalModelIdentifier>,
 }
 
 impl InstalledLocalModel {
@@ -1876,6 +2582,11 @@ impl InstalledLocalModel {
             install_path: config.install_path.clone(),
             size_bytes: config.size_bytes,
             installed_at: config.installed_at,
+            revision: config.revision.clone(),
+            quantized_from: config
+                .quantized_from
+                .as_deref()
+                .map(LocalModelIdentifier::parse),
         }
     }
 
@@ -1885,6 +2596,11 @@ impl InstalledLocalModel {
             install_path: self.install_path.clone(),
             size_bytes: self.size_bytes,
             installed_at: self.installed_at,
+            revision: self.revision.clone(),
+            quantized_from: self
+                .quantized_from
+                .as_ref()
+                .map(LocalModelIdentifier::serialize),
         }
     }
 }
@@ -1966,7 +2682,7 @@ impl CustomCommandAction {
     }
 
     pub fn description(self) -> &'static str {
-        match self {
+        t(match self {
             CustomCommandAction::ShowCurrentTime => "Display the current local time.",
             CustomCommandAction::ShowSystemStatus => "Summarize the system health of the agent.",
             CustomCommandAction::ShowSystemDiagnostics => {
@@ -1996,6 +2712,38 @@ impl CustomCommandAction {
             CustomCommandAction::ShowCommandHelp => {
                 "List every available slash command in the chat."
             }
+        })
+    }
+}
+
+/// Qué ocurre al invocar un comando personalizado.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum CustomCommandBehavior {
+    /// Ejecuta `action`, una de las funciones predefinidas.
+    #[default]
+    Action,
+    /// Rellena la plantilla con los argumentos y la envía al proveedor indicado por su
+    /// código corto o, sin proveedor, a Jarvis.
+    Prompt {
+        provider: Option<String>,
+        template: String,
+    },
+    Workflow {
+        workflow_id: u32,
+    },
+    /// Comandos del chat, uno por línea, con los mismos marcadores que las plantillas.
+    Script {
+        commands: String,
+    },
+}
+
+impl CustomCommandBehavior {
+    pub fn kind_label(&self) -> &'static str {
+        match self {
+            CustomCommandBehavior::Action => "Función predefinida",
+            CustomCommandBehavior::Prompt { .. } => "Plantilla de prompt",
+            CustomCommandBehavior::Workflow { .. } => "Workflow",
+            CustomCommandBehavior::Script { .. } => "Guion de comandos",
         }
     }
 }
@@ -2004,22 +2752,48 @@ impl CustomCommandAction {
 pub struct CustomCommand {
     pub trigger: String,
     pub action: CustomCommandAction,
+    #[serde(default)]
+    pub behavior: CustomCommandBehavior,
+}
+
+impl CustomCommand {
+    pub fn new(trigger: &str, action: CustomCommandAction) -> Self {
+        Self {
+            trigger: trigger.to_string(),
+            action,
+            behavior: CustomCommandBehavior::Action,
+        }
+    }
+
+    /// Destino del comando en una línea: la función, el proveedor, el workflow o el guion.
+    pub fn target_label(&self) -> String {
+        match &self.behavior {
+            CustomCommandBehavior::Action => self.action.label().to_string(),
+            CustomCommandBehavior::Prompt { provider, .. } => format!(
+                "prompt → {}",
+                provider
+                    .as_deref()
+                    .and_then(RemoteProviderKind::from_short_code)
+                    .map(|provider| provider.display_name())
+                    .unwrap_or("Jarvis")
+            ),
+            CustomCommandBehavior::Workflow { workflow_id } => format!("workflow #{}", workflow_id),
+            CustomCommandBehavior::Script { commands } => format!(
+                "guion ({} comandos)",
+                commands
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .count()
+            ),
+        }
+    }
 }
 
 pub fn default_custom_commands() -> Vec<CustomCommand> {
     vec![
-        CustomCommand {
-            trigger: "/time".to_string(),
-            action: CustomCommandAction::ShowCurrentTime,
-        },
-        CustomCommand {
-            trigger: "/projects".to_string(),
-            action: CustomCommandAction::ListActiveProjects,
-        },
-        CustomCommand {
-            trigger: "/providers".to_string(),
-            action: CustomCommandAction::ShowActiveProviders,
-        },
+        CustomCommand::new("/time", CustomCommandAction::ShowCurrentTime),
+        CustomCommand::new("/projects", CustomCommandAction::ListActiveProjects),
+        CustomCommand::new("/providers", CustomCommandAction::ShowActiveProviders),
     ]
 }
 
@@ -2063,6 +2837,7 @@ fn default_scheduled_tasks() -> Vec<ScheduledTask> {
             provider: Some(RemoteProviderKind::Anthropic),
             tags: vec!["sync".to_string(), "github".to_string()],
             enabled: true,
+            kind: ScheduledTaskKind::Generic,
         },
         ScheduledTask {
             id: 2,
@@ -2077,6 +2852,7 @@ fn default_scheduled_tasks() -> Vec<ScheduledTask> {
             provider: Some(RemoteProviderKind::OpenAi),
             tags: vec!["report".to_string(), "analytics".to_string()],
             enabled: true,
+            kind: ScheduledTaskKind::Generic,
         },
         ScheduledTask {
             id: 3,
@@ -2093,6 +2869,7 @@ fn default_scheduled_tasks() -> Vec<ScheduledTask> {
             provider: None,
             tags: vec!["mantenimiento".to_string(), "sistema".to_string()],
             enabled: true,
+            kind: ScheduledTaskKind::Generic,
         },
         ScheduledTask {
             id: 4,
@@ -2108,6 +2885,7 @@ fn default_scheduled_tasks() -> Vec<ScheduledTask> {
             provider: Some(RemoteProviderKind::Groq),
             tags: vec!["ml".to_string(), "embedding".to_string()],
             enabled: false,
+            kind: ScheduledTaskKind::Generic,
         },
         ScheduledTask {
             id: 5,
@@ -2123,6 +2901,7 @@ fn default_scheduled_tasks() -> Vec<ScheduledTask> {
             provider: Some(RemoteProviderKind::Anthropic),
             tags: vec!["comunicación".to_string(), "equipo".to_string()],
             enabled: false,
+            kind: ScheduledTaskKind::Generic,
         },
     ]
 }
@@ -2288,6 +3067,39 @@ fn default_event_listeners() -> Vec<EventListener> {
             enabled: true,
             last_triggered: Some("2024-05-12 11:02".to_string()),
         },
+        EventListener {
+            id: 5,
+            name: "Reindexar proyecto al guardar".to_string(),
+            description:
+                "Cuando cambia un documento de una ruta vigilada se relanza la sincronización RAG.".to_string(),
+            event: ListenerEventKind::FileChange,
+            condition: "**/*.md".to_string(),
+            action: "workflow:3".to_string(),
+            enabled: true,
+            last_triggered: None,
+        },
+        EventListener {
+            id: 6,
+            name: "Resumir descargas nuevas".to_string(),
+            description:
+                "Los ficheros nuevos de la carpeta de descargas se envían al briefing diario.".to_string(),
+            event: ListenerEventKind::FileChange,
+            condition: "Downloads/*".to_string(),
+            action: "workflow:2".to_string(),
+            enabled: false,
+            last_triggered: None,
+        },
+        EventListener {
+            id: 7,
+            name: "Resumir anomalías de sensores".to_string(),
+            description:
+                "Las alertas que Home Assistant publica por MQTT se incorporan al resumen diario.".to_string(),
+            event: ListenerEventKind::MqttMessage,
+            condition: "homeassistant/+/anomalia/#".to_string(),
+            action: "workflow:2".to_string(),
+            enabled: false,
+            last_triggered: None,
+        },
     ]
 }
 
@@ -2425,6 +3237,36 @@ fn default_external_integrations() -> Vec<ExternalIntegrationCard> {
     ]
 }
 
+/// Plantilla de prompt de las acciones rápidas que traen las tarjetas del catálogo.
+fn builtin_quick_action_template(label: &str) -> String {
+    let template = match label {
+        "Generar informe" => {
+            "Genera un informe estructurado (resumen, hallazgos clave y próximos pasos) sobre:\n"
+        }
+        "Analizar conversación" => {
+            "Analiza la conversación anterior: temas principales, decisiones tomadas y preguntas abiertas."
+        }
+        "Redactar resumen" => "Redacta un resumen breve y claro del siguiente texto:\n",
+        "Generar unit tests" => "Escribe tests unitarios que cubran casos normales y límite para:\n",
+        "Responder FAQ" => "Responde a esta pregunta frecuente de forma breve y amable:\n",
+        "Validar intención" => "Identifica la intención del usuario en este mensaje y su nivel de confianza:\n",
+        "Resumir hilo" => "Resume este hilo destacando acuerdos, dudas y tareas pendientes:\n",
+        "Generar story" => "Escribe una historia de usuario con criterios de aceptación para:\n",
+        "Auditar código" => "Audita este código buscando errores, riesgos de seguridad y mejoras:\n",
+        "Planificar roadmap" => "Propón un roadmap por fases, con hitos y riesgos, para:\n",
+        "Crear plan de experimentos" => "Diseña un plan de experimentos con hipótesis, métricas y criterios de éxito para:\n",
+        "Refinar prompts" => "Mejora este prompt para que sea más claro y preciso, y explica los cambios:\n",
+        "Explicar código" => "Explica paso a paso qué hace el siguiente código y señala posibles problemas:\n",
+        "Responder tests" => "Analiza estos resultados de tests y propone cómo corregir los fallos:\n",
+        "Resumir logs" => "Resume estos logs e identifica los errores y su causa probable:\n",
+        "Describir métricas" => "Describe las tendencias y anomalías de estas métricas:\n",
+        "Responder ticket" => "Redacta una respuesta para este ticket de soporte:\n",
+        "Clasificar bug" => "Clasifica este bug por componente, severidad y prioridad, justificando la elección:\n",
+        other => return format!("{}:\n", other),
+    };
+    template.to_string()
+}
+
 fn default_project_resources() -> Vec<ProjectResourceCard> {
     vec![
         ProjectResourceCard {
@@ -2575,8 +3417,14 @@ pub struct AppState {
     pub resource_memory_limit_gb: f32,
     /// Límite de disco en GB para la caché.
     pub resource_disk_limit_gb: f32,
+    /// Borrador editable de los ajustes de red, aplicado al validarse.
+    pub network_draft: NetworkConfig,
+    /// Resultado de la última aplicación de los ajustes de red.
+    pub network_status: Option<String>,
     /// Registro centralizado de comandos declarados por los módulos.
     pub command_registry: CommandRegistry,
+    /// Comandos de chat: integrados, de plugins y de los módulos registrados.
+    pub command_engine: CommandEngine,
     /// Registro de vistas disponibles en el panel principal.
     pub workbench_views: HashMap<MainView, Box<dyn WorkbenchView>>,
     /// Inicializadores dinámicos para registrar vistas externas en el workbench.
@@ -2597,19 +3445,126 @@ pub struct AppState {
     pub chat_routing: ChatRoutingState,
     /// Registro centralizado de secciones y nodos de navegación.
     pub navigation: NavigationRegistry,
+    /// Perfil con el que se construyó `navigation`, para reconstruirla solo si cambia.
+    navigation_profile: Option<usize>,
+    /// Huella de los comandos de workflows con la que se construyó `command_registry`.
+    command_registry_key: Option<u64>,
+    /// Guardado de la configuración en segundo plano.
+    pub config_saver: ConfigSaver,
+    /// Cambios recientes de la configuración, con su origen, para revisarlos y revertirlos.
+    pub state_journal: state_journal::StateJournal,
+    /// Tamaños en disco de las carpetas de modelos, medidos en segundo plano.
+    pub size_index: SizeIndex,
+    /// Métricas de uso locales; solo se alimentan si el usuario las activa.
+    pub usage_metrics: UsageMetrics,
     /// Configuración de layout para los paneles del shell.
     pub layout: LayoutConfig,
+    /// Altura actual del compositor del chat.
+    pub composer_height: f32,
+    /// Fuerza al compositor a adoptar `composer_height` en el siguiente frame.
+    pub composer_height_sync: bool,
+    /// Distribución y vista previas al modo zen, restauradas al salir.
+    pub zen_restore: Option<(LayoutConfig, MainView)>,
+    /// Nombre propuesto para guardar la distribución actual como preset.
+    pub layout_preset_name: String,
+    /// Escala aplicada al contexto de egui; `None` hasta el primer frame.
+    pub applied_ui_scale: Option<f32>,
     /// Estado de automatizaciones y cron jobs.
     pub automation: AutomationState,
     /// Consola de depuración del sistema.
     pub debug_console: DebugConsoleState,
     /// Consultas recientes en el buscador global.
     pub global_search_recent: Vec<String>,
+    /// Contadores de tokens por proveedor y para el modelo local.
+    pub tokenizers: TokenizerService,
+    /// Conectividad detectada con los proveedores remotos.
+    pub connectivity: ConnectivityState,
+    /// Servidor WebSocket para observadores externos.
+    pub event_stream: EventStreamState,
+    pub file_watcher: FileWatcherState,
+    /// Documentos de proyectos incorporados por las tareas de reindexado.
+    pub knowledge_index: KnowledgeIndexState,
+    /// Evaluación de prompts por lotes y su último informe.
+    pub batch_eval: BatchEvalState,
+    /// Benchmark de latencia y rendimiento de los proveedores y de Jarvis.
+    pub benchmark: benchmark::BenchmarkState,
+    /// Pruebas con aserciones contra los modelos de la comparativa y su historial.
+    pub quick_tests: quick_tests::QuickTestState,
+    /// Registro encadenado de efectos externos por conversación.
+    pub audit: AuditState,
+    /// Reglas de redacción compiladas de los prompts salientes.
+    pub redaction: RedactionState,
+    /// Filtros de seguridad de Jarvis y coincidencias de la sesión.
+    pub safety: SafetyState,
+    /// Sugerencias de seguimiento de la última respuesta.
+    pub follow_ups: follow_ups::FollowUpState,
+    /// Resúmenes de los mensajes antiguos para la estrategia de resumir e incluir.
+    pub context_summaries: context::ContextSummaries,
+    /// Generación en curso y último resumen diario.
+    pub digest: DigestState,
+    /// Puesta al día de la actividad reciente en curso.
+    pub briefing: briefing::BriefingState,
+    /// Plantillas de informes, generación en curso e informes escritos.
+    pub reports: ReportState,
+    /// Playbook en edición y ejecución en curso de sus pasos.
+    pub playbooks: PlaybookState,
+    /// Rutas y paquete pendiente del intercambio de workflows entre instalaciones.
+    pub workflow_bundles: workflow_bundle::WorkflowBundleState,
+    /// Automatizaciones que esperan permiso para provocar efectos externos.
+    pub permissions: permissions::PermissionState,
+    /// Formulario para activar, desbloquear o desactivar el cifrado del historial.
+    pub storage_encryption: vault::StorageEncryptionState,
+    /// Visor de código de los proyectos locales para preguntar por selecciones.
+    pub code_viewer: CodeViewerState,
+    /// Diálogo para exportar e importar conversaciones cifradas.
+    pub share: ConversationShareState,
+    pub embeddings: EmbeddingState,
+    /// Transcripción de notas de voz y audios adjuntos.
+    pub transcription: TranscriptionState,
+    /// Archivos adjuntos al compositor del chat.
+    pub attachments: AttachmentState,
+    /// Búsqueda web del chat, resultados adjuntos y búsquedas pedidas por los proveedores.
+    pub web_search: web_search::WebSearchState,
+    /// Vigilante del portapapeles y último contenido reconocido.
+    pub clipboard: clipboard::ClipboardState,
+    /// Reconocimiento de texto de las imágenes adjuntas.
+    pub ocr: ocr::OcrState,
+    /// Correo en preparación y envíos por SMTP.
+    pub email: email::EmailState,
+    /// Conectores de Slack y Discord: alta, pruebas y publicaciones en curso.
+    pub webhooks: webhooks::WebhookState,
+    /// Puente con el bot de Telegram y mensajes remotos pendientes de respuesta.
+    pub telegram: telegram::TelegramState,
+    /// Cliente MQTT: suscripción al broker, mensajes recibidos y publicaciones.
+    pub mqtt: mqtt::MqttState,
+    /// Cola de las llamadas salientes a proveedores e integraciones.
+    pub jobs: job_queue::JobQueueState,
+    /// Índice de la búsqueda global y últimos grupos filtrados.
+    pub search_index: search_index::SearchIndex,
+    pub response_cache: ResponseCacheState,
+    /// Borrador editable de los ajustes del flujo de eventos.
+    pub event_stream_draft: EventStreamConfig,
+    pub composer: ComposerState,
+    pub bookmarks: BookmarkState,
+    /// Hilos bifurcados de la conversación y sus puntos de control.
+    pub threads: ThreadState,
+    pub conversations: ConversationStore,
+    /// Perfil de contexto del proyecto seleccionado que se anunció en el chat.
+    pub project_context: project_context::ProjectContextState,
+    pub keybindings: KeybindingState,
+    /// Instantáneas periódicas y recuperación tras un cierre inesperado.
+    pub session: SessionState,
+    pub toasts: ToastState,
 }
 
 impl Default for AppState {
     fn default() -> Self {
-        let config = AppConfig::load_or_default();
+        let (mut config, config_recovery) = AppConfig::load_with_recovery();
+        let (plaintext_keys, provider_key_errors) = config.load_provider_keys();
+        crate::api::network::apply_settings(&config.network);
+        i18n::set_language(config.language);
+        // Antes de leer el historial, que puede estar cifrado.
+        let vault_error = vault::initialize(&config.storage_encryption).err();
 
         let mut profiles = if config.profiles.is_empty() {
             vec![
@@ -2644,7 +3599,10 @@ impl Default for AppState {
             .filter(|idx| projects.get(*idx).is_some())
             .or(Some(0));
 
-        let chat = ChatState::from_config(&config);
+        let mut chat = ChatState::from_config(&config);
+        // Recupera lo que quedó escrito en el editor en la sesión anterior.
+        let (composer, draft) = ComposerState::load();
+        chat.input = draft;
         let automation = AutomationState::from_config(&config);
         let mut resources = ResourceState::from_config(&config, &profiles, &projects);
         resources.ensure_library_selection();
@@ -2652,6 +3610,9 @@ impl Default for AppState {
         let global_search_recent = default_global_search_recent();
 
         let theme_preset = config.theme;
+        let mut layout = LayoutConfig::default();
+        apply_layout_snapshot(&mut layout, &config.layout.current);
+        layout.request_width_sync();
 
         let mut state = Self {
             show_settings_modal: false,
@@ -2660,7 +3621,7 @@ impl Default for AppState {
             config: config.clone(),
             theme: ThemeTokens::from_preset(theme_preset),
             font_sources: theme::default_font_sources(),
-            active_main_view: MainView::default(),
+            active_main_view: config.layout.active_view,
             active_main_tab: MainTab::default(),
             selected_preference: PreferencePanel::default(),
             preference_tabs: HashMap::new(),
@@ -2677,7 +3638,10 @@ impl Default for AppState {
             last_cache_cleanup: None,
             resource_memory_limit_gb: config.resource_memory_limit_gb,
             resource_disk_limit_gb: config.resource_disk_limit_gb,
+            network_draft: config.network.clone(),
+            network_status: None,
             command_registry: CommandRegistry::default(),
+            command_engine: CommandEngine::default(),
             workbench_views: HashMap::new(),
             workbench_initializers: Vec::new(),
             enable_memory_tracking: config.enable_memory_tracking,
@@ -2688,16 +3652,116 @@ impl Default for AppState {
             selected_project,
             chat_routing,
             navigation: build_navigation_registry(&config),
-            layout: LayoutConfig::default(),
+            navigation_profile: config.selected_profile,
+            command_registry_key: None,
+            config_saver: ConfigSaver::new(&config),
+            state_journal: state_journal::StateJournal::new(&config),
+            size_index: SizeIndex::load(),
+            usage_metrics: UsageMetrics::load(),
+            layout,
+            composer_height: config.layout.current.composer_height,
+            composer_height_sync: config.layout.current.composer_height > 0.0,
+            zen_restore: None,
+            layout_preset_name: String::new(),
+            applied_ui_scale: None,
             automation,
             debug_console: DebugConsoleState::with_entries(default_debug_console_entries()),
             global_search_recent,
+            tokenizers: TokenizerService::default(),
+            connectivity: ConnectivityState::default(),
+            event_stream: EventStreamState::default(),
+            file_watcher: FileWatcherState::default(),
+            knowledge_index: KnowledgeIndexState::default(),
+            batch_eval: BatchEvalState::default(),
+            benchmark: benchmark::BenchmarkState::load(),
+            quick_tests: quick_tests::QuickTestState::load(),
+            audit: AuditState::default(),
+            redaction: RedactionState::default(),
+            safety: SafetyState::default(),
+            follow_ups: Default::default(),
+            context_summaries: Default::default(),
+            digest: DigestState::default(),
+            briefing: Default::default(),
+            reports: ReportState::default(),
+            playbooks: PlaybookState::default(),
+            workflow_bundles: Default::default(),
+            permissions: Default::default(),
+            storage_encryption: vault::StorageEncryptionState {
+                key_source: config.storage_encryption.key_source,
+                ..Default::default()
+            },
+            code_viewer: CodeViewerState::default(),
+            share: ConversationShareState::default(),
+            embeddings: EmbeddingState::default(),
+            transcription: TranscriptionState::default(),
+            attachments: AttachmentState::default(),
+            web_search: Default::default(),
+            clipboard: Default::default(),
+            ocr: Default::default(),
+            email: Default::default(),
+            webhooks: Default::default(),
+            telegram: Default::default(),
+            mqtt: Default::default(),
+            jobs: Default::default(),
+            search_index: Default::default(),
+            response_cache: ResponseCacheState::default(),
+            event_stream_draft: config.event_stream.clone(),
+            composer,
+            bookmarks: BookmarkState::load(),
+            threads: ThreadState::new(String::new()),
+            conversations: ConversationStore::load(),
+            project_context: Default::default(),
+            keybindings: KeybindingState::default(),
+            session: SessionState::detect_previous(),
+            toasts: ToastState::default(),
         };
+        state.threads = ThreadState::new(state.bookmarks.conversation.clone());
+        // La conversación nueva arranca con el último enrutado que se configuró.
+        if let Some(latest) = state.conversations.latest().cloned() {
+            let key = state.bookmarks.conversation.clone();
+            state.conversations.entries.entry(key).or_insert(latest);
+        }
 
         state.register_workbench_initializer(|registry| {
             crate::ui::chat::register_preferences_workbench_view(registry);
         });
 
+        if let Some(recovery) = config_recovery {
+            state.report_config_recovery(recovery);
+        }
+        if !plaintext_keys.is_empty() {
+            state.config_saver.rewrite();
+            state.push_activity_log(
+                LogStatus::Ok,
+                "Proveedores",
+                format!(
+                    "Las API keys de {} pasan de config.json al llavero del sistema.",
+                    plaintext_keys.join(", ")
+                ),
+            );
+        }
+        for err in provider_key_errors {
+            state.push_activity_log(LogStatus::Warning, "Proveedores", err);
+        }
+        if let Some(err) = vault_error {
+            state.storage_encryption.status = Some(format!("{:#}", err));
+            state.push_activity_log(
+                LogStatus::Warning,
+                "Almacenamiento",
+                format!("El historial cifrado sigue bloqueado: {:#}", err),
+            );
+        }
+        if let Err(err) = state.config_saver.watch() {
+            state.push_debug_event(
+                DebugLogLevel::Warning,
+                "config",
+                format!("No se vigilarán las ediciones de config.json: {:#}", err),
+            );
+        }
+        state.apply_benchmark_latencies();
+        state.purge_expired_model_trash();
+        state.reconcile_model_directories();
+        state.refresh_lora_adapters();
         if state.resources.jarvis_auto_start {
             match state.ensure_jarvis_runtime() {
                 Ok(runtime) => {
@@ -2715,30 +3779,51 @@ impl Default for AppState {
             }
         }
 
+        state.jobs.configure(state.config.job_queue.clone());
+
         state.refresh_personalization_resources();
         state.rebuild_navigation();
-        let routing_label = state.chat.current_route_display();
-        state
-            .navigation_registry_mut()
-            .register_node(navigation::NavigationNode {
-                id: "main:routing-status".into(),
-                label: format!("Rutas por alias · {}", routing_label),
-                description: Some(
-                    "Divide tus mensajes entre proveedores remotos usando menciones @alias.".into(),
-                ),
-                icon: Some("🚦".into()),
-                badge: None,
-                target: navigation::NavigationTarget::main(MainView::ChatMultimodal),
-                order: 4,
-                section_id: navigation::SECTION_PRIMARY.to_string(),
-            });
         state.rebuild_command_registry();
+        let (dependencies, dropped) = task_chain::without_cycles(&state.config.cron_dependencies);
+        state.config.cron_dependencies = dependencies;
+        for reason in dropped {
+            state.push_debug_event(
+                DebugLogLevel::Warning,
+                "cron::scheduler",
+                format!("Dependencia descartada: {}", reason),
+            );
+        }
+        let retention_days = state.config.usage_metrics.retention_days;
+        state
+            .usage_metrics
+            .prune(Local::now().date_naive(), retention_days);
         state.rebuild_workbench_views();
 
         state
     }
 }
 
+/// Vistas que se pueden abrir en una ventana aparte.
+pub const POPOUT_VIEWS: [MainView; 2] = [MainView::ChatMultimodal, MainView::DebugConsole];
+const POPOUT_DEFAULT_SIZE: [f32; 2] = [720.0, 560.0];
+
+pub const UI_SCALE_MIN: f32 = 0.75;
+pub const UI_SCALE_MAX: f32 = 2.0;
+
+fn apply_layout_snapshot(layout: &mut LayoutConfig, snapshot: &LayoutSnapshot) {
+    layout.show_navigation = snapshot.show_navigation;
+    layout.show_resource_panel = snapshot.show_resource_panel;
+    layout.set_navigation_collapsed(snapshot.navigation_collapsed);
+    layout.set_resource_collapsed(snapshot.resource_collapsed);
+    layout.navigation_width = snapshot.navigation_width.clamp(
+        *NAVIGATION_WIDTH_RANGE.start(),
+        *NAVIGATION_WIDTH_RANGE.end(),
+    );
+    layout.resource_width = snapshot
+        .resource_width
+        .clamp(*RESOURCE_WIDTH_RANGE.start(), *RESOURCE_WIDTH_RANGE.end());
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum ChatMessageStatus {
     Normal,
@@ -2759,6 +3844,13 @@ pub struct ChatMessage {
     pub status: ChatMessageStatus,
     pub origin: Option<RemoteProviderKind>,
     pub mention: Option<String>,
+    pub source_prompt: Option<String>,
+    pub reply_to: Option<MessageQuote>,
+    pub error: Option<ProviderError>,
+    /// JSON validado contra el esquema de una petición estructurada.
+    pub structured: Option<serde_json::Value>,
+    /// Rango de código por el que se preguntó desde el visor.
+    pub code_anchor: Option<CodeAnchor>,
 }
 
 impl ChatMessage {
@@ -2770,6 +3862,11 @@ impl ChatMessage {
             status: ChatMessageStatus::Normal,
             origin: None,
             mention: None,
+            source_prompt: None,
+            reply_to: None,
+            error: None,
+            structured: None,
+            code_anchor: None,
         }
     }
 
@@ -2793,6 +3890,11 @@ impl ChatMessage {
             status: ChatMessageStatus::Pending,
             origin,
             mention: None,
+            source_prompt: None,
+            reply_to: None,
+            error: None,
+            structured: None,
+            code_anchor: None,
         }
     }
 
@@ -2805,6 +3907,30 @@ impl ChatMessage {
         self
     }
 
+    pub fn with_source_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.source_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Aviso del sistema que sustituye a una respuesta fallida de un proveedor.
+    /// Conserva el origen y el prompt para poder reintentarla.
+    pub fn provider_failure(
+        text: impl Into<String>,
+        origin: RemoteProviderKind,
+        source_prompt: Option<String>,
+        error: ProviderError,
+    ) -> Self {
+        let mut message = Self::system(text);
+        message.origin = Some(origin);
+        message.source_prompt = source_prompt;
+        message.error = Some(error);
+        message
+    }
+
+    pub fn is_regenerable(&self) -> bool {
+        self.source_prompt.is_some() && self.origin.is_some() && !self.is_pending()
+    }
+
     pub fn sender_display_label(&self) -> Cow<'_, str> {
         if self.sender == "User" {
             return Cow::Borrowed("Tú");
@@ -2850,7 +3976,55 @@ impl Default for ChatMessage {
     }
 }
 
-pub const MAX_COMMAND_DEPTH: usize = 5;
+/// Referencia a un mensaje anterior al que responde el usuario.
+#[derive(Clone, Debug)]
+pub struct MessageQuote {
+    pub message_index: usize,
+    pub sender: String,
+    pub text: String,
+    pub timestamp: String,
+}
+
+impl MessageQuote {
+    pub fn from_message(message_index: usize, message: &ChatMessage) -> Self {
+        Self {
+            message_index,
+            sender: message.sender_display_label().into_owned(),
+            text: message.combined_text(),
+            timestamp: message.timestamp.clone(),
+        }
+    }
+
+    pub fn excerpt(&self, max_chars: usize) -> String {
+        let first_line = self
+            .text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+        if first_line.chars().count() > max_chars {
+            let mut truncated: String = first_line.chars().take(max_chars).collect();
+            truncated.push('…');
+            truncated
+        } else {
+            first_line.to_string()
+        }
+    }
+
+    /// Antepone el mensaje citado al prompt para que el proveedor reciba el contexto.
+    pub fn wrap_prompt(&self, prompt: &str) -> String {
+        let quoted = self
+            .text
+            .lines()
+            .map(|line| format!("> {}", line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!(
+            "En respuesta a {} ({}):\n{}\n\n{}",
+            self.sender, self.timestamp, quoted, prompt
+        )
+    }
+}
 
 #[derive(Clone, Debug)]
 pub struct ProviderCallTicket {
@@ -2865,7 +4039,7 @@ pub struct ProviderCallTicket {
 #[derive(Clone, Debug)]
 pub struct ProviderCallResult {
     pub ticket: ProviderCallTicket,
-    pub outcome: std::result::Result<String, String>,
+    pub outcome: std::result::Result<String, ProviderError>,
 }
 
 #[derive(Clone, Debug)]
@@ -2885,86 +4059,84 @@ pub enum ProviderCallDispatch {
         provider_name: String,
         alias: String,
     },
+    /// Sin conexión: la solicitud quedó en cola en la posición indicada.
+    Queued {
+        provider_kind: RemoteProviderKind,
+        provider_name: String,
+        alias: String,
+        position: usize,
+    },
+    /// Sin conexión y con la cola desactivada: la solicitud se rechazó.
+    Offline {
+        provider_kind: RemoteProviderKind,
+        provider_name: String,
+        alias: String,
+    },
+    /// El presupuesto de gasto del proveedor está agotado y tiene el bloqueo activo.
+    BudgetExhausted {
+        provider_kind: RemoteProviderKind,
+        provider_name: String,
+        alias: String,
+        period: BudgetPeriod,
+        spent: f32,
+        limit: f32,
+    },
+    /// El contexto incluye contenido de proyectos marcados como solo local.
+    LocalOnly {
+        provider_kind: RemoteProviderKind,
+        provider_name: String,
+        alias: String,
+        projects: Vec<String>,
+    },
 }
 
-#[derive(Clone, Debug)]
-pub(crate) struct PendingProviderCall {
-    ticket: ProviderCallTicket,
-}
-
-#[derive(Debug)]
-pub(crate) struct ProviderResponse {
-    id: u64,
-    outcome: std::result::Result<String, String>,
-}
-
-#[derive(Clone, Debug, Default)]
-pub struct CommandInvocation {
-    pub raw: String,
-    pub name: String,
-    pub args: BTreeMap<String, String>,
-    pub flags: BTreeSet<String>,
-    pub positional: Vec<String>,
-}
-
-impl CommandInvocation {
-    pub fn parse(input: &str) -> Self {
-        let mut invocation = CommandInvocation {
-            raw: input.trim().to_string(),
-            ..Default::default()
-        };
-
-        let mut tokens = input.split_whitespace();
-        if let Some(first) = tokens.next() {
-            invocation.name = first.to_string();
-        } else {
-            return invocation;
-        }
-
-        for token in tokens {
-            if token.starts_with("--") {
-                let stripped = &token[2..];
-                if let Some((key, value)) = stripped.split_once('=') {
-                    invocation.args.insert(key.to_string(), value.to_string());
-                } else {
-                    invocation.flags.insert(stripped.to_string());
-                }
-            } else if let Some((key, value)) = token.split_once('=') {
-                invocation.args.insert(key.to_string(), value.to_string());
-            } else {
-                invocation.positional.push(token.to_string());
-            }
-        }
-
-        invocation
-    }
-
-    pub fn arg(&self, key: &str) -> Option<&str> {
-        self.args.get(key).map(|s| s.as_str())
-    }
-
-    pub fn flag(&self, key: &str) -> bool {
-        self.flags.contains(key)
-    }
-}
-
-pub struct CommandOutcome {
-    pub messages: Vec<String>,
+#[derive(Clone, Debug)]
+pub(crate) struct PendingProviderCall {
+    ticket: ProviderCallTicket,
+    structured: Option<StructuredRequest>,
+    /// Tokens del prompt enviado, para calcular el gasto al recibir la respuesta.
+    prompt_tokens: usize,
+    /// Conversación en la que se hizo la llamada, para la auditoría.
+    conversation: String,
+    /// Momento del envío, para medir la latencia.
+    started_at: Instant,
+    /// El prompt ofrecía la búsqueda web como herramienta.
+    web_search_tool: bool,
+}
+
+type ProviderCaller = fn(&str, &str, &str, &GenerationOptions) -> anyhow::Result<String>;
+/// Clave, modelo, imagen, tipo MIME e instrucción.
+type VisionCaller = fn(&str, &str, &[u8], &str, &str) -> anyhow::Result<String>;
+
+/// Llamada a un proveedor ya aceptada, con lo necesario para componer su prompt cuando
+/// esté el vector con el que recordar contexto.
+pub(crate) struct ProviderRequest {
+    ticket: ProviderCallTicket,
+    key: String,
+    caller: ProviderCaller,
+    prompt: String,
+    /// Fin del historial que acompaña al prompt.
+    history_end: usize,
+    /// Adjuntos, páginas web y resultados de búsqueda que van tras los recuerdos.
+    knowledge: Vec<String>,
+    options: GenerationOptions,
+    cache: Option<ResponseCache>,
 }
 
-impl CommandOutcome {
-    pub fn single(message: String) -> Self {
-        CommandOutcome {
-            messages: vec![message],
-        }
-    }
+struct ProviderCallProfile {
+    alias: String,
+    provider_name: &'static str,
+    api_key: Option<String>,
+    model: String,
+    caller: ProviderCaller,
 }
 
-pub struct CommandDocumentation {
-    pub signature: &'static str,
-    pub summary: &'static str,
-    pub parameters: &'static [&'static str],
-    pub examples: &'static [&'static str],
+#[derive(Debug)]
+pub(crate) struct ProviderResponse {
+    id: u64,
+    outcome: std::result::Result<String, ProviderError>,
+    /// La respuesta salió de la caché sin llegar a consultar al proveedor.
+    cached: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -2985,7 +4157,7 @@ pub struct LogEntry {
 
 impl CustomCommandAction {
     pub fn documentation(self) -> CommandDocumentation {
-        match self {
+        let mut documentation = match self {
             CustomCommandAction::ShowCurrentTime => CommandDocumentation {
                 signature: "showCurrentTime(format=human)",
                 summary: "Muestra la hora actual con distintos formatos de salida.",
@@ -3073,11 +4245,36 @@ impl CustomCommandAction {
                 parameters: &["mode → all | builtins | custom"],
                 examples: &["/help", "/help mode=custom"],
             },
-        }
+        };
+        documentation.summary = t(documentation.summary);
+        documentation
     }
 }
 
 impl AppState {
+    /// Arranca los servicios en segundo plano: la descarga de los tokenizers y los activados
+    /// en la configuración, es decir, el flujo de eventos, los vigilantes de ficheros y
+    /// portapapeles, el puente de Telegram y MQTT.
+    /// Solo lo llama la interfaz; la CLI y las pruebas construyen el estado sin ellos.
+    pub fn start_services(&mut self) {
+        self.tokenizers.load_pretrained();
+        if self.config.event_stream.enabled {
+            let _ = self.restart_event_stream();
+        }
+        if self.config.file_watcher.enabled {
+            let _ = self.restart_file_watcher();
+        }
+        if self.config.clipboard_watcher.enabled {
+            self.restart_clipboard_watcher();
+        }
+        if self.config.telegram.enabled {
+            self.restart_telegram_bridge();
+        }
+        if self.config.mqtt.enabled {
+            self.restart_mqtt_client();
+        }
+    }
+
     pub fn navigation_registry(&self) -> &NavigationRegistry {
         &self.navigation
     }
@@ -3132,6 +4329,123 @@ impl AppState {
         }
     }
 
+    /// Cambia el idioma de la interfaz sin reiniciar; los textos se traducen al pintar.
+    pub fn set_language(&mut self, language: Language) {
+        if self.config.language == language {
+            return;
+        }
+        self.config.language = language;
+        i18n::set_language(language);
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Idioma",
+            tf("Idioma de la interfaz: {0}", &[language.native_name()]),
+        );
+    }
+
+    pub fn set_ui_scale(&mut self, scale: f32) {
+        let scale = scale.clamp(UI_SCALE_MIN, UI_SCALE_MAX);
+        if (self.config.accessibility.ui_scale - scale).abs() > f32::EPSILON {
+            self.config.accessibility.ui_scale = scale;
+            self.persist_config();
+        }
+    }
+
+    pub fn set_reduced_motion(&mut self, enabled: bool) {
+        if self.config.accessibility.reduced_motion != enabled {
+            self.config.accessibility.reduced_motion = enabled;
+            self.persist_config();
+        }
+    }
+
+    pub fn set_keyboard_navigation(&mut self, enabled: bool) {
+        if self.config.accessibility.keyboard_navigation != enabled {
+            self.config.accessibility.keyboard_navigation = enabled;
+            if !enabled {
+                self.chat.focused_message = None;
+            }
+            self.persist_config();
+        }
+    }
+
+    /// Asigna `chord` a `action`. Volver a la combinación predeterminada borra la
+    /// personalización. Los conflictos se permiten y se señalan en el panel.
+    pub fn set_keybinding(&mut self, action: ShortcutAction, chord: KeyChord) {
+        let overrides = &mut self.config.keybindings.overrides;
+        if chord == action.default_chord() {
+            overrides.remove(action.id());
+        } else {
+            overrides.insert(action.id().to_string(), chord.to_string());
+        }
+        self.keybindings.status = Some(format!("{}: {}.", action.label(), chord));
+        self.persist_config();
+    }
+
+    pub fn reset_keybindings(&mut self) {
+        self.config.keybindings.overrides.clear();
+        self.keybindings.capturing = None;
+        self.keybindings.status = Some("Atajos restablecidos.".to_string());
+        self.persist_config();
+    }
+
+    pub fn set_enter_sends(&mut self, enabled: bool) {
+        if self.config.keybindings.enter_sends != enabled {
+            self.config.keybindings.enter_sends = enabled;
+            self.persist_config();
+        }
+    }
+
+    /// Activa el nodo de la barra lateral anterior (`step < 0`) o siguiente al activo,
+    /// en el mismo orden en que se muestran.
+    pub fn step_navigation(&mut self, step: isize) {
+        let targets: Vec<NavigationTarget> = self
+            .navigation
+            .sidebar_sections()
+            .into_iter()
+            .flat_map(|(_, nodes)| nodes.into_iter().map(|node| node.target))
+            .collect();
+        if targets.is_empty() {
+            return;
+        }
+
+        let len = targets.len() as isize;
+        let next = match targets
+            .iter()
+            .position(|target| self.is_navigation_target_active(*target))
+        {
+            Some(current) => (current as isize + step).rem_euclid(len),
+            None if step < 0 => len - 1,
+            None => 0,
+        };
+        self.activate_navigation_target(targets[next as usize]);
+    }
+
+    /// Mueve el foco del historial del chat; `None` como destino lo retira.
+    pub fn focus_chat_message(&mut self, index: Option<usize>) {
+        let index = index.filter(|index| *index < self.chat.messages.len());
+        if self.chat.focused_message != index {
+            self.chat.focused_message = index;
+            self.chat.scroll_to_focused = index.is_some();
+        }
+    }
+
+    /// Resalta el mensaje anterior (`step < 0`) o siguiente al resaltado. Sin resaltado,
+    /// empieza por el último mensaje o por el primero.
+    pub fn step_focused_message(&mut self, step: isize) {
+        let count = self.chat.messages.len();
+        if count == 0 {
+            return;
+        }
+        let target = match self.chat.focused_message.filter(|index| *index < count) {
+            Some(index) if step < 0 => index.saturating_sub(1),
+            Some(index) => (index + 1).min(count - 1),
+            None if step < 0 => count - 1,
+            None => 0,
+        };
+        self.focus_chat_message(Some(target));
+    }
+
     pub fn set_theme_preset(&mut self, preset: ThemePreset) {
         if self.config.theme != preset {
             self.config.theme = preset;
@@ -3150,8 +4464,274 @@ impl AppState {
         }
     }
 
+    /// Cambia el texto del buscador; los grupos se recalculan en la siguiente
+    /// actualización, así que se pide un repintado para que llegue.
+    pub fn set_global_search_query(&mut self, value: String) {
+        if self.search_buffer != value {
+            self.search_buffer = value;
+            runtime::request_repaint();
+        }
+    }
+
+    /// Grupos de la búsqueda global calculados en la última actualización.
     pub fn global_search_groups(&self) -> Vec<GlobalSearchGroup> {
+        self.search_index.groups().to_vec()
+    }
+
+    /// Reindexa las fuentes de la búsqueda global que cambiaron y vuelve a filtrar cuando
+    /// la consulta deja de cambiar. Devuelve si cambiaron los grupos.
+    fn refresh_global_search(&mut self) -> bool {
+        for source in [
+            SearchSource::Models,
+            SearchSource::Conversations,
+            SearchSource::Preferences,
+            SearchSource::Documents,
+            SearchSource::Workflows,
+        ] {
+            let fingerprint = self.search_source_fingerprint(source);
+            if self.search_index.needs_rebuild(source, fingerprint) {
+                let entries = self.search_source_entries(source);
+                self.search_index.replace(source, fingerprint, entries);
+            }
+        }
         let query = self.search_buffer.trim().to_lowercase();
+        let context = self.search_context_fingerprint();
+        let Some(query) = self.search_index.settle(&query, context) else {
+            return false;
+        };
+        let groups = self.build_global_search_groups(&query);
+        self.search_index.store(query, context, groups);
+        true
+    }
+
+    /// Huella de lo que muestra cada fuente; los mensajes solo cuentan los últimos.
+    fn search_source_fingerprint(&self, source: SearchSource) -> u64 {
+        match source {
+            SearchSource::Models => search_index::fingerprint(
+                self.resources
+                    .remote_catalog
+                    .provider_cards
+                    .values()
+                    .flatten()
+                    .map(|card| (&card.key.id, card.context_tokens))
+                    .collect::<Vec<_>>(),
+            ),
+            SearchSource::Conversations => search_index::fingerprint(
+                self.chat
+                    .messages
+                    .iter()
+                    .rev()
+                    .take(12)
+                    .map(|message| (&message.sender, &message.timestamp, &message.text))
+                    .collect::<Vec<_>>(),
+            ),
+            SearchSource::Preferences => 0,
+            SearchSource::Documents => search_index::fingerprint(
+                self.resources
+                    .project_resources
+                    .iter()
+                    .map(|card| {
+                        (
+                            &card.name,
+                            &card.readme_preview,
+                            &card.tags,
+                            card.kind.label(),
+                            card.status.label(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            SearchSource::Workflows => search_index::fingerprint(
+                self.automation
+                    .workflows
+                    .workflows
+                    .iter()
+                    .map(|workflow| {
+                        (
+                            &workflow.name,
+                            &workflow.description,
+                            &workflow.chat_command,
+                            &workflow.last_run,
+                            workflow.trigger.label(),
+                        )
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+        }
+    }
+
+    /// Lo que, además de la consulta, cambia los grupos sin pasar por el índice.
+    fn search_context_fingerprint(&self) -> u64 {
+        search_index::fingerprint((
+            self.embeddings.semantic_mode,
+            &self.embeddings.search_query,
+            &self.embeddings.search_error,
+            self.embeddings
+                .search_hits
+                .iter()
+                .map(|hit| (hit.score.to_bits(), hit.source.message_index()))
+                .collect::<Vec<_>>(),
+            &self.global_search_recent,
+        ))
+    }
+
+    fn search_source_entries(&self, source: SearchSource) -> Vec<SearchEntry> {
+        match source {
+            SearchSource::Models => self
+                .resources
+                .remote_catalog
+                .provider_cards
+                .iter()
+                .flat_map(|(provider, cards)| {
+                    cards.iter().map(move |card| {
+                        SearchEntry::new(
+                            &format!(
+                                "{} {} {} {}",
+                                card.title,
+                                card.description,
+                                card.tags.join(" "),
+                                card.capabilities.join(" ")
+                            ),
+                            GlobalSearchResult {
+                                title: card.title.clone(),
+                                subtitle: format!(
+                                    "{} · Contexto {} tokens",
+                                    provider.display_name(),
+                                    card.context_tokens
+                                ),
+                                action_hint: format!("Abrir catálogo {}", provider.display_name()),
+                                source: None,
+                            },
+                        )
+                    })
+                })
+                .collect(),
+            SearchSource::Conversations => self
+                .chat
+                .messages
+                .iter()
+                .rev()
+                .take(12)
+                .map(|message| {
+                    let mut preview = message.text.clone();
+                    if preview.len() > 96 {
+                        preview.truncate(93);
+                        preview.push_str("...");
+                    }
+                    SearchEntry::new(
+                        &format!("{} {}", message.sender, message.text),
+                        GlobalSearchResult {
+                            title: preview,
+                            subtitle: format!("{} · {}", message.sender, message.timestamp),
+                            action_hint: "Ir al historial de chat".to_string(),
+                            source: None,
+                        },
+                    )
+                })
+                .collect(),
+            SearchSource::Preferences => [
+                PreferencePanel::SystemGithub,
+                PreferencePanel::SystemCache,
+                PreferencePanel::SystemResources,
+                PreferencePanel::SystemNetwork,
+                PreferencePanel::SystemJobQueue,
+                PreferencePanel::SystemWebSearch,
+                PreferencePanel::SystemEmail,
+                PreferencePanel::SystemTelegram,
+                PreferencePanel::SystemMqtt,
+                PreferencePanel::SystemRedaction,
+                PreferencePanel::SystemUsage,
+                PreferencePanel::SystemNotifications,
+                PreferencePanel::SystemPermissions,
+                PreferencePanel::SystemStorage,
+                PreferencePanel::CustomizationCommands,
+                PreferencePanel::CustomizationAppearance,
+                PreferencePanel::CustomizationAccessibility,
+                PreferencePanel::CustomizationKeybindings,
+                PreferencePanel::CustomizationVoice,
+                PreferencePanel::CustomizationDigest,
+                PreferencePanel::CustomizationTextActions,
+                PreferencePanel::CustomizationMemory,
+                PreferencePanel::CustomizationProfiles,
+                PreferencePanel::CustomizationProjects,
+                PreferencePanel::ProvidersAnthropic,
+                PreferencePanel::ProvidersOpenAi,
+                PreferencePanel::ProvidersGroq,
+                PreferencePanel::LocalJarvis,
+                PreferencePanel::LocalJarvisKnowledge,
+                PreferencePanel::LocalJarvisSafety,
+                PreferencePanel::LocalOcr,
+            ]
+            .into_iter()
+            .map(|panel| {
+                let metadata = panel.metadata();
+                SearchEntry::new(
+                    &format!("{} {}", metadata.title, metadata.description),
+                    GlobalSearchResult {
+                        title: metadata.title.to_string(),
+                        subtitle: metadata.description.to_string(),
+                        action_hint: "Abrir preferencias".to_string(),
+                        source: None,
+                    },
+                )
+            })
+            .collect(),
+            SearchSource::Documents => self
+                .resources
+                .project_resources
+                .iter()
+                .map(|card| {
+                    SearchEntry::new(
+                        &format!(
+                            "{} {} {}",
+                            card.name,
+                            card.readme_preview,
+                            card.tags.join(" ")
+                        ),
+                        GlobalSearchResult {
+                            title: card.name.clone(),
+                            subtitle: format!("{} · {}", card.kind.label(), card.status.label()),
+                            action_hint: "Abrir recurso".to_string(),
+                            source: None,
+                        },
+                    )
+                })
+                .collect(),
+            SearchSource::Workflows => self
+                .automation
+                .workflows
+                .workflows
+                .iter()
+                .map(|workflow| {
+                    let command_hint = workflow
+                        .chat_command
+                        .as_ref()
+                        .map(|cmd| format!("Ejecutar {}", cmd))
+                        .unwrap_or_else(|| "Iniciar workflow".to_string());
+                    let last_run = workflow.last_run.as_deref().unwrap_or("sin registros");
+                    SearchEntry::new(
+                        &format!("{} {}", workflow.name, workflow.description),
+                        GlobalSearchResult {
+                            title: workflow.name.clone(),
+                            subtitle: format!(
+                                "{} · Última ejecución {}",
+                                workflow.trigger.label(),
+                                last_run
+                            ),
+                            action_hint: command_hint,
+                            source: None,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Filtra el índice con `query`, ya en minúsculas y sin espacios en los extremos.
+    fn build_global_search_groups(&self, query: &str) -> Vec<GlobalSearchGroup> {
+        if self.embeddings.semantic_mode && !query.is_empty() {
+            return self.semantic_search_groups(query);
+        }
         let mut groups = Vec::new();
 
         if query.is_empty() && !self.global_search_recent.is_empty() {
@@ -3162,6 +4742,7 @@ impl AppState {
                     title: entry.clone(),
                     subtitle: "Búsqueda reciente".to_string(),
                     action_hint: "Pulsa Enter para repetir".to_string(),
+                    source: None,
                 })
                 .collect();
             groups.push(GlobalSearchGroup {
@@ -3170,159 +4751,214 @@ impl AppState {
             });
         }
 
-        let mut model_results = Vec::new();
-        for (provider, cards) in &self.resources.remote_catalog.provider_cards {
-            for card in cards {
-                let haystack = format!(
-                    "{} {} {} {}",
-                    card.title,
-                    card.description,
-                    card.tags.join(" "),
-                    card.capabilities.join(" ")
-                )
-                .to_lowercase();
-                if query.is_empty() || haystack.contains(&query) {
-                    model_results.push(GlobalSearchResult {
-                        title: format!("{}", card.title),
-                        subtitle: format!(
-                            "{} · Contexto {} tokens",
-                            provider.display_name(),
-                            card.context_tokens
-                        ),
-                        action_hint: format!("Abrir catálogo {}", provider.display_name()),
-                    });
-                }
+        let push = |groups: &mut Vec<GlobalSearchGroup>, title: &str, source, limit| {
+            let results = self.search_index.matches(source, query, limit);
+            if !results.is_empty() {
+                groups.push(GlobalSearchGroup {
+                    title: title.to_string(),
+                    results,
+                });
             }
-        }
-        if !model_results.is_empty() {
-            model_results.truncate(5);
-            groups.push(GlobalSearchGroup {
-                title: "Modelos".to_string(),
-                results: model_results,
-            });
-        }
+        };
+        push(&mut groups, "Modelos", SearchSource::Models, 5);
 
-        let mut conversation_results = Vec::new();
-        for message in self.chat.messages.iter().rev().take(12) {
-            let haystack = format!("{} {}", message.sender, message.text).to_lowercase();
-            if query.is_empty() || haystack.contains(&query) {
-                let mut preview = message.text.clone();
-                if preview.len() > 96 {
-                    preview.truncate(93);
-                    preview.push_str("...");
-                }
-                conversation_results.push(GlobalSearchResult {
-                    title: preview,
-                    subtitle: format!("{} · {}", message.sender, message.timestamp),
-                    action_hint: "Ir al historial de chat".to_string(),
+        if !query.is_empty() && self.embeddings.search_query.to_lowercase() == query {
+            let results: Vec<GlobalSearchResult> = self
+                .embeddings
+                .search_hits
+                .iter()
+                .filter(|hit| hit.source.message_index().is_some())
+                .filter_map(|hit| self.semantic_search_result(hit))
+                .collect();
+            if !results.is_empty() {
+                groups.push(GlobalSearchGroup {
+                    title: "Coincidencias semánticas".to_string(),
+                    results,
                 });
             }
         }
-        if !conversation_results.is_empty() {
-            conversation_results.truncate(6);
-            groups.push(GlobalSearchGroup {
-                title: "Conversaciones".to_string(),
-                results: conversation_results,
-            });
-        }
 
-        let preference_panels = [
-            PreferencePanel::SystemGithub,
-            PreferencePanel::SystemCache,
-            PreferencePanel::SystemResources,
-            PreferencePanel::CustomizationCommands,
-            PreferencePanel::CustomizationAppearance,
-            PreferencePanel::CustomizationMemory,
-            PreferencePanel::CustomizationProfiles,
-            PreferencePanel::CustomizationProjects,
-            PreferencePanel::ProvidersAnthropic,
-            PreferencePanel::ProvidersOpenAi,
-            PreferencePanel::ProvidersGroq,
-            PreferencePanel::LocalJarvis,
-        ];
+        push(
+            &mut groups,
+            "Conversaciones",
+            SearchSource::Conversations,
+            6,
+        );
+        push(&mut groups, "Preferencias", SearchSource::Preferences, 6);
+        push(
+            &mut groups,
+            "Documentos y recursos",
+            SearchSource::Documents,
+            6,
+        );
+        push(&mut groups, "Workflows", SearchSource::Workflows, 6);
+        groups
+    }
 
-        let mut preference_results = Vec::new();
-        for panel in preference_panels {
-            let metadata = panel.metadata();
-            let haystack = format!("{} {}", metadata.title, metadata.description).to_lowercase();
-            if query.is_empty() || haystack.contains(&query) {
-                preference_results.push(GlobalSearchResult {
-                    title: metadata.title.to_string(),
-                    subtitle: metadata.description.to_string(),
-                    action_hint: "Abrir preferencias".to_string(),
+    /// Resultados del modo semántico agrupados por origen.
+    fn semantic_search_groups(&self, query: &str) -> Vec<GlobalSearchGroup> {
+        if self.embeddings.search_query.to_lowercase() != query {
+            let hint = if query.chars().count() < 3 {
+                "Escribe al menos tres caracteres"
+            } else {
+                "Buscando por significado…"
+            };
+            return vec![GlobalSearchGroup {
+                title: "Búsqueda semántica".to_string(),
+                results: vec![GlobalSearchResult {
+                    title: hint.to_string(),
+                    subtitle: "Los resultados se ordenan por similitud con la consulta".to_string(),
+                    action_hint: "Esperando".to_string(),
+                    source: None,
+                }],
+            }];
+        }
+
+        let titles = [
+            ("Mensajes", 0),
+            ("Documentos de proyectos", 1),
+            ("Tarjetas de conocimiento", 2),
+        ];
+        let mut groups = Vec::new();
+        for (title, kind) in titles {
+            let results: Vec<GlobalSearchResult> = self
+                .embeddings
+                .search_hits
+                .iter()
+                .filter(|hit| {
+                    kind == match hit.source {
+                        SemanticSource::Message(_) => 0,
+                        SemanticSource::ProjectReadme { .. }
+                        | SemanticSource::ProjectDocument { .. } => 1,
+                        SemanticSource::KnowledgeCard { .. } => 2,
+                    }
+                })
+                .filter_map(|hit| self.semantic_search_result(hit))
+                .collect();
+            if !results.is_empty() {
+                groups.push(GlobalSearchGroup {
+                    title: title.to_string(),
+                    results,
                 });
             }
         }
-        if !preference_results.is_empty() {
-            preference_results.truncate(6);
+        if groups.is_empty() {
             groups.push(GlobalSearchGroup {
-                title: "Preferencias".to_string(),
-                results: preference_results,
+                title: "Búsqueda semántica".to_string(),
+                results: vec![GlobalSearchResult {
+                    title: "Sin coincidencias por significado".to_string(),
+                    subtitle: self
+                        .embeddings
+                        .search_error
+                        .clone()
+                        .unwrap_or_else(|| "Prueba con otra formulación".to_string()),
+                    action_hint: "Desactiva el modo semántico para buscar por palabras".to_string(),
+                    source: None,
+                }],
             });
         }
+        groups
+    }
 
-        let mut document_results = Vec::new();
-        for card in &self.resources.project_resources {
-            let haystack = format!(
-                "{} {} {}",
-                card.name,
-                card.readme_preview,
-                card.tags.join(" ")
-            )
-            .to_lowercase();
-            if query.is_empty() || haystack.contains(&query) {
-                document_results.push(GlobalSearchResult {
-                    title: card.name.clone(),
-                    subtitle: format!("{} · {}", card.kind.label(), card.status.label()),
-                    action_hint: "Abrir recurso".to_string(),
-                });
+    fn semantic_search_result(&self, hit: &SemanticHit) -> Option<GlobalSearchResult> {
+        let similarity = format!("similitud {:.0}%", hit.score * 100.0);
+        let (text, subtitle, action_hint) = match &hit.source {
+            SemanticSource::Message(index) => {
+                let message = self.chat.messages.get(*index)?;
+                (
+                    message.text.clone(),
+                    format!("{} · {}", message.sender, similarity),
+                    "Ir a la conversación",
+                )
             }
-        }
-        if !document_results.is_empty() {
-            document_results.truncate(6);
-            groups.push(GlobalSearchGroup {
-                title: "Documentos y recursos".to_string(),
-                results: document_results,
-            });
-        }
+            SemanticSource::ProjectReadme { project, chunk, .. } => (
+                project.clone(),
+                format!("README · fragmento {} · {}", chunk + 1, similarity),
+                "Abrir recurso",
+            ),
+            SemanticSource::ProjectDocument {
+                project,
+                path,
+                chunk,
+                ..
+            } => (
+                format!("{} · {}", project, path),
+                format!("Documento · fragmento {} · {}", chunk + 1, similarity),
+                "Abrir recurso",
+            ),
+            SemanticSource::KnowledgeCard { panel, title } => (
+                title.clone(),
+                format!("{} · {}", panel.metadata().title, similarity),
+                "Abrir preferencias",
+            ),
+        };
+        let mut title: String = text.chars().take(93).collect();
+        if title.len() < text.len() {
+            title.push_str("...");
+        }
+        Some(GlobalSearchResult {
+            title,
+            subtitle,
+            action_hint: action_hint.to_string(),
+            source: Some(hit.source.clone()),
+        })
+    }
 
-        let mut workflow_results = Vec::new();
-        for workflow in &self.automation.workflows.workflows {
-            let haystack = format!("{} {}", workflow.name, workflow.description).to_lowercase();
-            if query.is_empty() || haystack.contains(&query) {
-                let command_hint = workflow
-                    .chat_command
-                    .as_ref()
-                    .map(|cmd| format!("Ejecutar {}", cmd))
-                    .unwrap_or_else(|| "Iniciar workflow".to_string());
-                let last_run = workflow
-                    .last_run
-                    .as_ref()
-                    .map(|value| value.as_str())
-                    .unwrap_or("sin registros");
-                workflow_results.push(GlobalSearchResult {
-                    title: workflow.name.clone(),
-                    subtitle: format!(
-                        "{} · Última ejecución {}",
-                        workflow.trigger.label(),
-                        last_run
-                    ),
-                    action_hint: command_hint,
-                });
+    /// Lleva la interfaz al origen de un resultado semántico.
+    pub fn open_semantic_source(&mut self, source: &SemanticSource) {
+        match source {
+            SemanticSource::Message(index) => {
+                self.activate_navigation_target(NavigationTarget::main(MainView::ChatMultimodal));
+                self.focus_chat_message(Some(*index));
+            }
+            SemanticSource::ProjectReadme { section, .. }
+            | SemanticSource::ProjectDocument { section, .. } => {
+                self.activate_navigation_target(NavigationTarget::Resource(*section));
+            }
+            SemanticSource::KnowledgeCard { panel, .. } => {
+                self.activate_navigation_target(NavigationTarget::Preference(*panel));
             }
         }
-        if !workflow_results.is_empty() {
-            workflow_results.truncate(6);
-            groups.push(GlobalSearchGroup {
-                title: "Workflows".to_string(),
-                results: workflow_results,
-            });
-        }
+    }
 
-        groups
+    pub fn toggle_semantic_search_mode(&mut self) {
+        self.embeddings.semantic_mode = !self.embeddings.semantic_mode;
+        // Fuerza a repetir la consulta actual con el alcance del nuevo modo.
+        self.embeddings.search_query.clear();
+        self.embeddings.search_hits.clear();
+        self.embeddings.pending_query = None;
     }
 
+    /// Lanza el workflow si sus efectos están autorizados; si no, lo deja esperando el
+    /// permiso del usuario.
     pub fn trigger_workflow(&mut self, workflow_id: u32) -> Option<String> {
+        let workflow = self
+            .automation
+            .workflows
+            .workflows
+            .iter()
+            .find(|workflow| workflow.id == workflow_id)?;
+        let key = permissions::workflow_key(&workflow.name);
+        let effects = permissions::missing(
+            &self.config.automation_permissions,
+            &key,
+            &permissions::workflow_effects(workflow),
+        );
+        if effects.is_empty() {
+            return self.start_workflow(workflow_id);
+        }
+        let name = workflow.name.clone();
+        Some(
+            self.request_automation_permission(permissions::PermissionRequest {
+                target: permissions::PermissionTarget::Workflow(workflow_id),
+                key,
+                name,
+                effects,
+            }),
+        )
+    }
+
+    fn start_workflow(&mut self, workflow_id: u32) -> Option<String> {
         if let Some(workflow) = self
             .automation
             .workflows
@@ -3334,6 +4970,12 @@ impl AppState {
             let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
             workflow.last_run = Some(timestamp.clone());
             let message = format!("Workflow '{}' lanzado.", workflow.name);
+            let details = BTreeMap::from([
+                ("workflow".to_string(), workflow_id.to_string()),
+                ("inicio".to_string(), timestamp.clone()),
+            ]);
+            let summary = workflow.name.clone();
+            self.record_audit(AuditEventKind::WorkflowTriggered, summary, details);
             self.push_activity_log(LogStatus::Running, "Automation", &message);
             self.push_debug_event(
                 DebugLogLevel::Info,
@@ -3346,11 +4988,99 @@ impl AppState {
         }
     }
 
-    pub fn toggle_listener_enabled(&mut self, listener_id: u32) -> Option<bool> {
-        let mut result = None;
-        let mut message = None;
-
-        if let Some(listener) = self
+    /// Lanza un workflow con los parámetros de sus pasos y devuelve el mensaje de inicio
+    /// seguido de los pasos ya resueltos.
+    pub fn run_workflow(
+        &mut self,
+        workflow_id: u32,
+        parameters: &BTreeMap<String, String>,
+    ) -> Result<Vec<String>, String> {
+        let workflow = self
+            .automation
+            .workflows
+            .workflows
+            .iter()
+            .find(|workflow| workflow.id == workflow_id)
+            .ok_or_else(|| format!("No existe el workflow #{}.", workflow_id))?;
+        let steps = workflow.resolve_steps(parameters).map_err(|missing| {
+            format!(
+                "Faltan parámetros para '{}': {}. Pásalos como clave=valor.",
+                workflow.name,
+                missing.join(", ")
+            )
+        })?;
+        let workflow_name = workflow.name.clone();
+        let unused: Vec<&str> = {
+            let expected = workflow.parameters();
+            parameters
+                .keys()
+                .filter(|name| name.as_str() != "input" && !expected.contains(name))
+                .map(String::as_str)
+                .collect()
+        };
+
+        let mut lines = Vec::new();
+        lines.extend(self.trigger_workflow(workflow_id));
+        let target = permissions::PermissionTarget::Workflow(workflow_id);
+        if self
+            .permissions
+            .queue
+            .iter()
+            .any(|request| request.target == target)
+        {
+            lines.push("Se ejecutará cuando concedas el permiso.".to_string());
+            return Ok(lines);
+        }
+        for (index, step) in steps.iter().enumerate() {
+            let mut line = format!("{}. {} · {}", index + 1, step.label, step.detail);
+            // Los pasos de sincronización que nombran un conector publican en su canal.
+            if step.kind == WorkflowStepKind::SyncAction {
+                let target = format!("{} {}", step.label, step.detail);
+                if let Some(connector) =
+                    webhooks::connector_for(&self.config.webhook_connectors, &target).cloned()
+                {
+                    line.push_str(&format!(
+                        " → {} '{}'",
+                        connector.kind.label(),
+                        connector.name
+                    ));
+                    self.webhooks.start(
+                        &mut self.jobs,
+                        connector,
+                        format!("Workflow '{}'", workflow_name),
+                        format!("{} · {}\n{}", workflow_name, step.label, step.detail),
+                    );
+                }
+            }
+            self.push_debug_event(DebugLogLevel::Info, "automation::workflow", line.clone());
+            lines.push(line);
+        }
+        if !unused.is_empty() {
+            lines.push(format!("Parámetros sin usar: {}", unused.join(", ")));
+        }
+        let results_topic = self.config.mqtt.results_topic.trim().to_string();
+        if self.config.mqtt.enabled && !results_topic.is_empty() {
+            let payload = serde_json::json!({
+                "workflow_id": workflow_id,
+                "name": workflow_name,
+                "steps": lines,
+                "timestamp": Local::now().to_rfc3339(),
+            });
+            self.mqtt.publish(
+                &mut self.jobs,
+                self.config.mqtt.clone(),
+                results_topic,
+                payload.to_string(),
+            );
+        }
+        Ok(lines)
+    }
+
+    pub fn toggle_listener_enabled(&mut self, listener_id: u32) -> Option<bool> {
+        let mut result = None;
+        let mut message = None;
+
+        if let Some(listener) = self
             .automation
             .event_automation
             .listeners
@@ -3387,6 +5117,16 @@ impl AppState {
             timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
         };
 
+        session::record_log(format!(
+            "{} [{:?}] {}: {}",
+            entry.timestamp, entry.status, entry.source, entry.message
+        ));
+        self.event_stream.publish(StreamEvent::Log {
+            status: format!("{:?}", entry.status).to_lowercase(),
+            source: entry.source.clone(),
+            message: entry.message.clone(),
+            timestamp: entry.timestamp.clone(),
+        });
         self.automation.activity_logs.push(entry);
         const MAX_ACTIVITY_LOGS: usize = 200;
         if self.automation.activity_logs.len() > MAX_ACTIVITY_LOGS {
@@ -3395,19 +5135,516 @@ impl AppState {
         }
     }
 
+    /// Registra el aviso en la actividad y, según la política de notificaciones, lo
+    /// muestra además como aviso en pantalla o notificación del sistema.
+    pub(crate) fn notify(
+        &mut self,
+        source: NotificationSource,
+        status: LogStatus,
+        origin: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        let origin = origin.into();
+        let message = message.into();
+        self.push_activity_log(status, origin.clone(), message.clone());
+        let channel = notifications::route(
+            &self.config.notifications,
+            source,
+            status,
+            Local::now().time(),
+        );
+        if channel >= NotificationChannel::Toast {
+            self.toasts.push(status, format!("{}: {}", origin, message));
+            for connector in self
+                .config
+                .webhook_connectors
+                .iter()
+                .filter(|connector| connector.enabled && connector.forward_notifications)
+            {
+                self.webhooks.start(
+                    &mut self.jobs,
+                    connector.clone(),
+                    "Aviso".to_string(),
+                    format!("{}: {}", origin, message),
+                );
+            }
+        }
+        if channel == NotificationChannel::Desktop {
+            if let Err(err) = digest::notify_desktop(&origin, &message) {
+                self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    "notifications::desktop",
+                    format!("{:#}", err),
+                );
+            }
+        }
+    }
+
+    pub fn in_quiet_hours(&self) -> bool {
+        notifications::in_quiet_hours(&self.config.notifications, Local::now().time())
+    }
+
     pub fn push_debug_event(
         &mut self,
         level: DebugLogLevel,
         component: impl Into<String>,
         message: impl Into<String>,
     ) {
+        let component = component.into();
+        let message = message.into();
+        session::record_log(format!(
+            "{} [{:?}] {}: {}",
+            Local::now().format("%Y-%m-%d %H:%M:%S"),
+            level,
+            component,
+            message
+        ));
         self.debug_console.push_entry(level, component, message);
     }
 
+    /// Registra un efecto externo en la auditoría de la conversación activa.
+    pub(crate) fn record_audit(
+        &mut self,
+        kind: AuditEventKind,
+        summary: impl Into<String>,
+        details: BTreeMap<String, String>,
+    ) {
+        let conversation = self.bookmarks.conversation.clone();
+        self.record_audit_in(&conversation, kind, summary, details);
+    }
+
+    /// Como `record_audit`, para efectos que terminan cuando ya se cambió de conversación.
+    fn record_audit_in(
+        &mut self,
+        conversation: &str,
+        kind: AuditEventKind,
+        summary: impl Into<String>,
+        details: BTreeMap<String, String>,
+    ) {
+        if let Err(err) = self.audit.record(conversation, kind, summary, details) {
+            self.push_debug_event(
+                DebugLogLevel::Error,
+                "audit",
+                format!("No se pudo registrar el evento: {:#}", err),
+            );
+        }
+    }
+
+    /// Aplica los filtros de redacción a un prompt que va a salir hacia `provider_name` y
+    /// deja constancia en la auditoría de cuántos fragmentos sustituyó cada regla.
+    fn redact_outgoing(&mut self, text: &str, provider_name: &str) -> String {
+        let config = self.effective_redaction_config();
+        let (redacted, hits) = self.redaction.apply(&config, text);
+        if hits.is_empty() {
+            return redacted;
+        }
+        let mut details = redaction::count_by_rule(&hits);
+        details.insert("proveedor".to_string(), provider_name.to_string());
+        self.record_audit(
+            AuditEventKind::PromptRedacted,
+            format!("{} fragmentos redactados", hits.len()),
+            details,
+        );
+        self.push_debug_event(
+            DebugLogLevel::Info,
+            "redaction",
+            format!(
+                "{} fragmentos redactados antes de enviar a {}",
+                hits.len(),
+                provider_name
+            ),
+        );
+        redacted
+    }
+
+    /// Indica si los filtros de seguridad se aplican en la conversación abierta.
+    pub fn safety_filters_active(&self) -> bool {
+        self.active_conversation_defaults()
+            .safety_filters
+            .unwrap_or(self.config.safety_filters.enabled)
+    }
+
+    /// Indica si se proponen sugerencias de seguimiento en la conversación abierta.
+    pub fn follow_ups_active(&self) -> bool {
+        self.active_conversation_defaults()
+            .follow_ups
+            .unwrap_or(self.config.follow_ups.enabled)
+    }
+
+    /// Pide sugerencias cuando cambia la última respuesta del chat y recoge las que llegan
+    /// del proveedor en segundo plano.
+    fn poll_follow_ups(&mut self) -> bool {
+        let latest = self
+            .chat
+            .messages
+            .iter()
+            .enumerate()
+            .next_back()
+            .filter(|(_, message)| {
+                !matches!(message.sender.as_str(), "User" | "System")
+                    && !message.is_pending()
+                    && message.error.is_none()
+                    && !message.text.trim().is_empty()
+            })
+            .map(|(index, message)| (index, follow_ups::reply_key(index, &message.text)));
+        let Some((index, key)) = latest.filter(|_| self.follow_ups_active()) else {
+            let shown = self.follow_ups.key.is_some();
+            self.follow_ups.clear();
+            return shown;
+        };
+        if self.follow_ups.key != Some(key) {
+            self.request_follow_ups(index, key);
+            return true;
+        }
+
+        let Some(rx) = &self.follow_ups.rx else {
+            return false;
+        };
+        let outcome = match rx.try_recv() {
+            Ok(outcome) => outcome,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("la petición terminó sin respuesta".to_string())
+            }
+        };
+        self.follow_ups.rx = None;
+        match outcome {
+            Ok(text) => {
+                self.follow_ups.suggestions =
+                    follow_ups::parse_suggestions(&text, self.config.follow_ups.count);
+            }
+            Err(err) => self.push_debug_event(
+                DebugLogLevel::Warning,
+                "follow_ups",
+                format!("No se pudieron generar sugerencias: {}", err),
+            ),
+        }
+        true
+    }
+
+    /// Genera las sugerencias de la respuesta `index` con el proveedor configurado o, sin él,
+    /// con Jarvis si ya tiene un modelo cargado. Sin conexión o con proyectos de solo local
+    /// en el contexto no se envía nada fuera.
+    fn request_follow_ups(&mut self, index: usize, key: u64) {
+        self.follow_ups.clear();
+        self.follow_ups.key = Some(key);
+        self.follow_ups.message_index = Some(index);
+        let question = self.chat.messages[..index]
+            .iter()
+            .rev()
+            .find(|message| message.sender == "User")
+            .map(|message| message.text.clone());
+        let settings = self.config.follow_ups.clone();
+        let prompt = follow_ups::build_prompt(
+            question.as_deref(),
+            &self.chat.messages[index].text,
+            settings.count,
+        );
+
+        let provider = settings
+            .provider
+            .as_deref()
+            .and_then(RemoteProviderKind::from_short_code);
+        let Some(provider) = provider else {
+            // No se carga un modelo solo para proponer sugerencias.
+            if self.resources.jarvis_runtime.is_none() {
+                return;
+            }
+            match self.generate_local_jarvis_reply(&prompt) {
+                Ok(text) => {
+                    self.follow_ups.suggestions =
+                        follow_ups::parse_suggestions(&text, settings.count);
+                }
+                Err(err) => self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    "follow_ups",
+                    format!("Jarvis no pudo proponer sugerencias: {}", err),
+                ),
+            }
+            return;
+        };
+        if self.is_offline() || !self.local_only_conflict(&prompt).is_empty() {
+            return;
+        }
+        let profile = self.provider_call_profile(provider);
+        let Some(api_key) = profile.api_key else {
+            return;
+        };
+        let model = match settings.model.trim() {
+            "" => profile.model,
+            model => model.to_string(),
+        };
+        let prompt = self.redact_outgoing(&prompt, profile.provider_name);
+        let caller = profile.caller;
+        let options = GenerationOptions {
+            max_tokens: Some(follow_ups::MAX_TOKENS),
+            ..GenerationOptions::default()
+        };
+        let (tx, rx) = std::sync::mpsc::channel();
+        let thread_model = model.clone();
+        self.jobs.submit(
+            provider.short_code(),
+            JobPriority::Background,
+            "Sugerencias de seguimiento",
+            move || {
+                let outcome = caller(&api_key, &thread_model, &prompt, &options)
+                    .map_err(|err| ProviderError::from_anyhow(err).message().to_string());
+                let _ = tx.send(outcome);
+            },
+        );
+        self.record_audit(
+            AuditEventKind::ProviderCall,
+            format!("{} · {}", profile.provider_name, model),
+            BTreeMap::from([
+                ("proveedor".to_string(), profile.provider_name.to_string()),
+                ("modelo".to_string(), model),
+                ("uso".to_string(), "sugerencias".to_string()),
+            ]),
+        );
+        self.follow_ups.rx = Some(rx);
+    }
+
+    /// Recoge los resúmenes del historial que han llegado y, con la estrategia de resumir e
+    /// incluir, pide los de los mensajes antiguos que aún no lo tienen. Los resume el
+    /// proveedor de las preferencias o, sin él, el de la conversación; sin ninguno, sin
+    /// conexión o con contenido de proyectos solo locales el mensaje va completo.
+    fn poll_context_summaries(&mut self) -> bool {
+        let (updated, errors) = self.context_summaries.poll();
+        for err in errors {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "context",
+                format!("No se pudo resumir un mensaje del historial: {}", err),
+            );
+        }
+        if self.config.context.strategy != ContextStrategy::SummarizeThenInclude
+            || self.is_offline()
+        {
+            return updated;
+        }
+        let settings = self.config.context.clone();
+        let provider = settings
+            .summary_provider
+            .as_deref()
+            .and_then(RemoteProviderKind::from_short_code)
+            .or_else(|| self.effective_conversation_defaults().provider_kind());
+        let Some(provider) = provider else {
+            return updated;
+        };
+        let candidates: Vec<ChatMessage> =
+            context::messages_to_summarize(&self.chat.messages, &settings, &self.context_summaries)
+                .into_iter()
+                .cloned()
+                .collect();
+        if candidates.is_empty() {
+            return updated;
+        }
+        let profile = self.provider_call_profile(provider);
+        let Some(api_key) = profile.api_key else {
+            return updated;
+        };
+        let model = match settings.summary_model.trim() {
+            "" => profile.model,
+            model => model.to_string(),
+        };
+        let options = GenerationOptions {
+            max_tokens: Some(context::SUMMARY_MAX_TOKENS),
+            ..GenerationOptions::default()
+        };
+        let fingerprints = self.local_only_fingerprints();
+        for message in candidates {
+            let line = context::history_line(&message);
+            if !local_only::derived_projects(&fingerprints, &line).is_empty() {
+                self.context_summaries.skip(&message);
+                continue;
+            }
+            let prompt = context::summary_prompt(&message);
+            let prompt = self.redact_outgoing(&prompt, profile.provider_name);
+            let caller = profile.caller;
+            let (api_key, model, options) = (api_key.clone(), model.clone(), options.clone());
+            let (tx, rx) = std::sync::mpsc::channel();
+            self.jobs.submit(
+                provider.short_code(),
+                JobPriority::Background,
+                "Resumen del historial",
+                move || {
+                    let outcome = caller(&api_key, &model, &prompt, &options)
+                        .map_err(|err| ProviderError::from_anyhow(err).message().to_string());
+                    let _ = tx.send(outcome);
+                },
+            );
+            self.context_summaries.start(&message, rx);
+        }
+        self.record_audit(
+            AuditEventKind::ProviderCall,
+            format!("{} · {}", profile.provider_name, model),
+            BTreeMap::from([
+                ("proveedor".to_string(), profile.provider_name.to_string()),
+                ("modelo".to_string(), model),
+                ("uso".to_string(), "resumen del historial".to_string()),
+            ]),
+        );
+        true
+    }
+
+    /// Tema vetado al que se parece `text`, según el clasificador de los filtros. Un
+    /// fallo al calcular los embeddings se registra y deja pasar el texto.
+    fn classify_safety(&mut self, text: &str) -> Option<safety::SafetyHit> {
+        let classifier = self.config.safety_filters.classifier.clone();
+        if !classifier.enabled || classifier.topics.is_empty() {
+            return None;
+        }
+        if self.safety.topic_vectors(&classifier.topics).is_none() {
+            let topics: Vec<&str> = classifier.topics.iter().map(String::as_str).collect();
+            match self.embed_texts(&topics) {
+                Ok(vectors) => self.safety.set_topic_vectors(&classifier.topics, vectors),
+                Err(err) => {
+                    self.push_debug_event(
+                        DebugLogLevel::Warning,
+                        "safety",
+                        format!(
+                            "No se pudieron calcular los temas del clasificador: {}",
+                            err
+                        ),
+                    );
+                    return None;
+                }
+            }
+        }
+        let vector = match self.embed_texts(&[text]) {
+            Ok(mut vectors) if !vectors.is_empty() => vectors.remove(0),
+            Ok(_) => return None,
+            Err(err) => {
+                self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    "safety",
+                    format!("El clasificador no pudo evaluar el texto: {}", err),
+                );
+                return None;
+            }
+        };
+        let topic_vectors = self.safety.topic_vectors(&classifier.topics)?;
+        let (topic, _) = safety::classify(
+            &vector,
+            &classifier.topics,
+            topic_vectors,
+            classifier.threshold,
+        )?;
+        Some(safety::SafetyHit {
+            rule: format!("Clasificador: {}", topic),
+            action: match classifier.action {
+                SafetyAction::Redact => SafetyAction::Block,
+                action => action,
+            },
+            range: None,
+        })
+    }
+
+    /// Pasa un prompt o una respuesta de Jarvis por los filtros de seguridad y registra
+    /// cada coincidencia. Un prompt bloqueado devuelve `Err`; una respuesta bloqueada se
+    /// sustituye por un aviso.
+    fn apply_safety_filters(
+        &mut self,
+        stage: safety::FilterStage,
+        text: String,
+    ) -> Result<String, String> {
+        if !self.safety_filters_active() {
+            return Ok(text);
+        }
+        let classified = self.classify_safety(&text);
+        let outcome = self
+            .safety
+            .filter(&self.config.safety_filters, &text, classified);
+        let Some(action) = outcome.action() else {
+            return Ok(text);
+        };
+        let rule = outcome.deciding_rule().unwrap_or_default().to_string();
+
+        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let conversation = self.bookmarks.conversation.clone();
+        let mut details: BTreeMap<String, String> = BTreeMap::new();
+        for hit in &outcome.hits {
+            self.safety.record(safety::SafetyLogEntry {
+                timestamp: timestamp.clone(),
+                conversation: conversation.clone(),
+                stage,
+                rule: hit.rule.clone(),
+                action: hit.action,
+                excerpt: safety::excerpt(&text, hit),
+            });
+            details.insert(
+                hit.rule.clone(),
+                safety::action_label(hit.action).to_string(),
+            );
+        }
+        details.insert("texto".to_string(), stage.label().to_string());
+        self.record_audit(
+            AuditEventKind::SafetyFilter,
+            format!(
+                "{}: {} coincidencias, acción {}",
+                stage.label(),
+                outcome.hits.len(),
+                safety::action_label(action)
+            ),
+            details,
+        );
+
+        match (action, stage) {
+            (SafetyAction::Block, safety::FilterStage::Prompt) => {
+                let message = format!("El filtro '{}' ha bloqueado el prompt.", rule);
+                self.push_activity_log(LogStatus::Warning, "Jarvis", message.clone());
+                Err(message)
+            }
+            (SafetyAction::Block, safety::FilterStage::Response) => {
+                self.push_activity_log(
+                    LogStatus::Warning,
+                    "Jarvis",
+                    format!("El filtro '{}' ha bloqueado una respuesta.", rule),
+                );
+                Ok(format!(
+                    "[Respuesta retenida por el filtro de seguridad '{}'.]",
+                    rule
+                ))
+            }
+            (SafetyAction::Redact, _) => {
+                self.push_debug_event(
+                    DebugLogLevel::Info,
+                    "safety",
+                    format!(
+                        "{}: {} fragmentos filtrados",
+                        stage.label(),
+                        outcome.hits.len()
+                    ),
+                );
+                Ok(outcome.text)
+            }
+            (SafetyAction::Warn, _) => {
+                self.toasts.push(
+                    LogStatus::Warning,
+                    format!(
+                        "{} de Jarvis: coincide con el filtro '{}'.",
+                        stage.label(),
+                        rule
+                    ),
+                );
+                Ok(outcome.text)
+            }
+        }
+    }
+
+    fn record_file_written(&mut self, path: &Path, origin: &str) {
+        self.record_audit(
+            AuditEventKind::FileWritten,
+            path.display().to_string(),
+            BTreeMap::from([("origen".to_string(), origin.to_string())]),
+        );
+    }
+
     pub fn activate_jarvis_model(&mut self, identifier: &LocalModelIdentifier) -> String {
         self.resources.jarvis_selected_provider = identifier.provider;
         self.resources.jarvis_active_model = Some(identifier.clone());
         self.resources.jarvis_runtime = None;
+        self.tokenizers.clear_jarvis();
 
         let install_path = self
             .installed_model(identifier)
@@ -3477,6 +5714,7 @@ impl AppState {
     pub fn deactivate_jarvis_model(&mut self) -> String {
         self.resources.jarvis_active_model = None;
         self.resources.jarvis_runtime = None;
+        self.tokenizers.clear_jarvis();
         self.resources.jarvis_model_path.clear();
 
         let status = "Jarvis quedó sin modelo activo.".to_string();
@@ -3486,85 +5724,739 @@ impl AppState {
         status
     }
 
-    pub fn uninstall_local_model(&mut self, identifier: &LocalModelIdentifier) -> Option<String> {
-        if let Some(position) = self
+    /// Quita el modelo de la biblioteca y se ocupa de sus archivos según `mode`. Con un
+    /// plazo de papelera de 0 días, `Trash` equivale a `Delete`.
+    pub fn uninstall_local_model(
+        &mut self,
+        identifier: &LocalModelIdentifier,
+        mode: UninstallMode,
+    ) -> Option<String> {
+        let position = self
             .resources
             .installed_local_models
             .iter()
             .position(|model| {
                 model.identifier.provider == identifier.provider
                     && model.identifier.model_id == identifier.model_id
+            })?;
+        let removed = self.resources.installed_local_models.remove(position);
+        if self
+            .resources
+            .jarvis_active_model
+            .as_ref()
+            .map(|active| {
+                active.provider == identifier.provider && active.model_id == identifier.model_id
             })
+            .unwrap_or(false)
         {
-            let removed = self.resources.installed_local_models.remove(position);
-            if self
-                .resources
-                .jarvis_active_model
-                .as_ref()
-                .map(|active| {
-                    active.provider == identifier.provider && active.model_id == identifier.model_id
-                })
-                .unwrap_or(false)
-            {
-                self.resources.jarvis_active_model = None;
-                self.resources.jarvis_runtime = None;
+            self.resources.jarvis_active_model = None;
+            self.resources.jarvis_runtime = None;
+            self.tokenizers.clear_jarvis();
+        }
+        self.resources.jarvis_pool.remove(identifier);
+        self.resources.model_updates.statuses.remove(identifier);
+
+        let label = removed.identifier.display_label();
+        let model_dir = PathBuf::from(removed.install_path.trim());
+        let retention_days = self.config.jarvis.trash_retention_days;
+        let mut level = LogStatus::Warning;
+        let files_status = if removed.install_path.trim().is_empty() || !model_dir.exists() {
+            "No había archivos en disco.".to_string()
+        } else if mode == UninstallMode::Trash && retention_days > 0 {
+            let size_bytes = self
+                .size_index
+                .size_of(&model_dir)
+                .unwrap_or(removed.size_bytes);
+            match model_trash::move_to_trash(&model_dir) {
+                Ok(trash_path) => {
+                    self.size_index.rename(&model_dir, &trash_path);
+                    self.config.jarvis.trashed_models.push(TrashedModelConfig {
+                        identifier: removed.identifier.serialize(),
+                        original_path: removed.install_path.clone(),
+                        trash_path: trash_path.display().to_string(),
+                        size_bytes,
+                        revision: removed.revision.clone(),
+                        trashed_at: Utc::now(),
+                    });
+                    format!(
+                        "{} movidos a la papelera; se borrarán en {} días si no se restauran.",
+                        format_bytes(size_bytes),
+                        retention_days
+                    )
+                }
+                Err(err) => {
+                    level = LogStatus::Error;
+                    format!("Los archivos siguen en disco: {}", err)
+                }
             }
-            self.persist_config();
-            let label = removed.identifier.display_label();
-            let status = format!("Modelo '{}' eliminado de la biblioteca local.", label);
-            self.push_activity_log(LogStatus::Warning, "Jarvis", status.clone());
-            Some(status)
         } else {
-            None
-        }
-    }
+            match model_trash::delete_dir(&model_dir) {
+                Ok(()) => {
+                    self.size_index.remove(&model_dir);
+                    format!("Se liberaron {}.", format_bytes(removed.size_bytes))
+                }
+                Err(err) => {
+                    level = LogStatus::Error;
+                    format!("Los archivos siguen en disco: {}", err)
+                }
+            }
+        };
 
-    pub fn mark_local_model_updated(
-        &mut self,
-        identifier: &LocalModelIdentifier,
-    ) -> Option<String> {
-        if let Some(entry) = self
-            .resources
-            .installed_local_models
-            .iter_mut()
-            .find(|model| {
-                model.identifier.provider == identifier.provider
-                    && model.identifier.model_id == identifier.model_id
-            })
-        {
-            entry.installed_at = Utc::now();
-            self.persist_config();
-            let message = format!(
-                "Modelo '{}' actualizado correctamente.",
-                identifier.display_label()
-            );
-            self.push_activity_log(LogStatus::Ok, "Jarvis", message.clone());
-            Some(message)
-        } else {
-            None
-        }
+        self.save_size_index();
+        self.persist_config();
+        let status = format!(
+            "Modelo '{}' eliminado de la biblioteca local. {}",
+            label, files_status
+        );
+        self.push_activity_log(level, "Jarvis", status.clone());
+        Some(status)
     }
 
-    pub fn queue_huggingface_install(
-        &mut self,
-        model: LocalModelCard,
-        token: Option<String>,
-    ) -> bool {
-        let provider = model.provider;
-        if self
-            .chat
-            .pending_local_installs
-            .iter()
-            .any(|pending| pending.provider == provider && pending.model_id == model.id)
-        {
-            return false;
+    /// Devuelve un modelo de la papelera a su carpeta y a la biblioteca.
+    pub fn restore_trashed_model(&mut self, index: usize) -> String {
+        let Some(entry) = self.config.jarvis.trashed_models.get(index).cloned() else {
+            return "El modelo ya no está en la papelera.".to_string();
+        };
+        let identifier = LocalModelIdentifier::parse(&entry.identifier);
+        if let Err(err) = model_trash::restore(&entry) {
+            let status = format!(
+                "No se pudo restaurar '{}': {}",
+                identifier.display_label(),
+                err
+            );
+            self.push_activity_log(LogStatus::Error, "Jarvis", status.clone());
+            return status;
         }
 
-        let sanitized_status = format!("Descargando '{}' desde Hugging Face…", model.id);
-        self.provider_state_mut(provider).install_status = Some(sanitized_status.clone());
-        self.push_activity_log(LogStatus::Running, "Jarvis", sanitized_status);
+        self.config.jarvis.trashed_models.remove(index);
+        self.size_index.rename(
+            Path::new(&entry.trash_path),
+            Path::new(&entry.original_path),
+        );
+        self.save_size_index();
+        self.upsert_installed_model(InstalledLocalModel {
+            identifier: identifier.clone(),
+            install_path: entry.original_path.clone(),
+            size_bytes: entry.size_bytes,
+            installed_at: Utc::now(),
+            revision: entry.revision.clone(),
+            quantized_from: None,
+        });
+        self.persist_config();
+        let status = format!(
+            "Modelo '{}' restaurado en {}.",
+            identifier.display_label(),
+            entry.original_path
+        );
+        self.push_activity_log(LogStatus::Ok, "Jarvis", status.clone());
+        status
+    }
 
-        let trimmed_token = token.and_then(|value| {
+    /// Borra definitivamente un modelo de la papelera.
+    pub fn delete_trashed_model(&mut self, index: usize) -> String {
+        if index >= self.config.jarvis.trashed_models.len() {
+            return "El modelo ya no está en la papelera.".to_string();
+        }
+        let entry = self.config.jarvis.trashed_models.remove(index);
+        let label = LocalModelIdentifier::parse(&entry.identifier).display_label();
+        let status = match model_trash::delete_dir(Path::new(&entry.trash_path)) {
+            Ok(()) => {
+                self.size_index.remove(Path::new(&entry.trash_path));
+                self.save_size_index();
+                format!(
+                    "'{}' borrado de la papelera. Se liberaron {}.",
+                    label,
+                    format_bytes(entry.size_bytes)
+                )
+            }
+            Err(err) => {
+                // Se conserva la entrada para poder reintentarlo.
+                self.config.jarvis.trashed_models.insert(index, entry);
+                format!("No se pudo borrar '{}': {}", label, err)
+            }
+        };
+        self.persist_config();
+        self.push_activity_log(LogStatus::Warning, "Jarvis", status.clone());
+        status
+    }
+
+    /// Avisa en el chat de que `config.json` estaba dañado y de qué se cargó en su lugar.
+    fn report_config_recovery(&mut self, recovery: ConfigRecovery) {
+        let message = match recovery {
+            ConfigRecovery::Restored { backup, damaged } => format!(
+                "La configuración estaba dañada y se restauró la copia {}. El archivo dañado se guardó en {}.",
+                backup.display(),
+                damaged.display()
+            ),
+            ConfigRecovery::Defaults { damaged } => format!(
+                "La configuración estaba dañada y no había copias válidas: se usan los valores por defecto. El archivo dañado se guardó en {}.",
+                damaged.display()
+            ),
+        };
+        self.chat
+            .messages
+            .push(ChatMessage::system(message.clone()));
+        self.push_activity_log(LogStatus::Warning, "Configuración", message);
+    }
+
+    pub fn empty_model_trash(&mut self) -> String {
+        let freed = self.purge_model_trash(|_| true);
+        format!("Papelera vaciada. Se liberaron {}.", format_bytes(freed))
+    }
+
+    /// Borra los modelos cuyo plazo en la papelera ya venció. Se ejecuta al arrancar.
+    pub fn purge_expired_model_trash(&mut self) {
+        let retention_days = self.config.jarvis.trash_retention_days;
+        let before = self.config.jarvis.trashed_models.len();
+        let freed = self.purge_model_trash(|entry| model_trash::is_expired(entry, retention_days));
+        let purged = before - self.config.jarvis.trashed_models.len();
+        if purged > 0 {
+            self.push_activity_log(
+                LogStatus::Ok,
+                "Jarvis",
+                format!(
+                    "Se borraron {} modelos caducados de la papelera ({}).",
+                    purged,
+                    format_bytes(freed)
+                ),
+            );
+        }
+    }
+
+    fn purge_model_trash(
+        &mut self,
+        mut should_purge: impl FnMut(&TrashedModelConfig) -> bool,
+    ) -> u64 {
+        let before = self.config.jarvis.trashed_models.len();
+        let mut freed = 0;
+        let mut failures = Vec::new();
+        let size_index = &mut self.size_index;
+        self.config.jarvis.trashed_models.retain(|entry| {
+            if !should_purge(entry) {
+                return true;
+            }
+            match model_trash::delete_dir(Path::new(&entry.trash_path)) {
+                Ok(()) => {
+                    size_index.remove(Path::new(&entry.trash_path));
+                    freed += entry.size_bytes;
+                    false
+                }
+                Err(err) => {
+                    failures.push(err.to_string());
+                    true
+                }
+            }
+        });
+        for failure in failures {
+            self.push_activity_log(LogStatus::Error, "Jarvis", failure);
+        }
+        if self.config.jarvis.trashed_models.len() != before {
+            self.save_size_index();
+            self.persist_config();
+        }
+        freed
+    }
+
+    /// Busca carpetas de modelos en el directorio de instalación que no están registradas.
+    pub fn reconcile_model_directories(&mut self) {
+        let known: Vec<PathBuf> = self
+            .resources
+            .installed_local_models
+            .iter()
+            .filter(|model| !model.install_path.trim().is_empty())
+            .map(|model| PathBuf::from(&model.install_path))
+            .collect();
+        let orphans = model_trash::find_orphans(
+            Path::new(&self.resources.jarvis_install_dir),
+            &known,
+            &self.size_index,
+        );
+        if !orphans.is_empty() {
+            self.push_activity_log(
+                LogStatus::Warning,
+                "Jarvis",
+                format!(
+                    "Hay {} carpetas de modelos en disco que no figuran en la biblioteca.",
+                    orphans.len()
+                ),
+            );
+        }
+        self.resources.orphan_model_dirs = orphans;
+    }
+
+    /// Mide el espacio de los modelos instalados, la papelera, las carpetas sin registrar
+    /// y las descargas a medias. Los tamaños salen de la biblioteca y del índice, salvo
+    /// las carpetas temporales, que se miden ahora.
+    pub fn refresh_model_disk_usage(&mut self) -> ModelDiskUsage {
+        let install_dir = PathBuf::from(&self.resources.jarvis_install_dir);
+        let usage = ModelDiskUsage {
+            installed: self
+                .resources
+                .installed_local_models
+                .iter()
+                .map(|model| model.size_bytes)
+                .sum(),
+            trash: self
+                .config
+                .jarvis
+                .trashed_models
+                .iter()
+                .map(|entry| entry.size_bytes)
+                .sum(),
+            orphans: self
+                .resources
+                .orphan_model_dirs
+                .iter()
+                .map(|orphan| orphan.size_bytes)
+                .sum(),
+            work_dirs: model_trash::find_work_dirs(&install_dir)
+                .iter()
+                .map(|dir| dir.size_bytes)
+                .sum(),
+        };
+        self.resources.model_disk_usage = Some(usage.clone());
+        usage
+    }
+
+    /// Borra las descargas a medias y los modelos caducados de la papelera. Con `dry_run`
+    /// solo describe lo que se borraría. Las carpetas temporales se respetan mientras haya
+    /// descargas o actualizaciones en curso, porque pueden ser suyas.
+    pub fn collect_model_garbage(&mut self, dry_run: bool) -> String {
+        let busy = self.has_pending_local_installs()
+            || self
+                .resources
+                .model_updates
+                .statuses
+                .values()
+                .any(ModelUpdateStatus::is_busy);
+        let work_dirs = if busy {
+            Vec::new()
+        } else {
+            model_trash::find_work_dirs(Path::new(&self.resources.jarvis_install_dir))
+        };
+        let retention_days = self.config.jarvis.trash_retention_days;
+        let expired: Vec<(String, u64)> = self
+            .config
+            .jarvis
+            .trashed_models
+            .iter()
+            .filter(|entry| model_trash::is_expired(entry, retention_days))
+            .map(|entry| {
+                (
+                    LocalModelIdentifier::parse(&entry.identifier).display_label(),
+                    entry.size_bytes,
+                )
+            })
+            .collect();
+
+        let mut items: Vec<String> = work_dirs
+            .iter()
+            .map(|dir| {
+                format!(
+                    "descarga a medias {} ({})",
+                    dir.path.display(),
+                    format_bytes(dir.size_bytes)
+                )
+            })
+            .chain(expired.iter().map(|(label, size)| {
+                format!(
+                    "{} caducado en la papelera ({})",
+                    label,
+                    format_bytes(*size)
+                )
+            }))
+            .collect();
+        if busy {
+            items.push(
+                "las descargas a medias se revisarán cuando terminen las que están en curso"
+                    .to_string(),
+            );
+        }
+        let planned: u64 = work_dirs.iter().map(|dir| dir.size_bytes).sum::<u64>()
+            + expired.iter().map(|(_, size)| size).sum::<u64>();
+        if work_dirs.is_empty() && expired.is_empty() {
+            return "No hay nada que limpiar.".to_string();
+        }
+        if dry_run {
+            return format!(
+                "Se liberarían {}: {}.",
+                format_bytes(planned),
+                items.join("; ")
+            );
+        }
+
+        let mut freed = 0;
+        for dir in &work_dirs {
+            match model_trash::delete_dir(&dir.path) {
+                Ok(()) => freed += dir.size_bytes,
+                Err(err) => self.push_activity_log(LogStatus::Error, "Jarvis", err.to_string()),
+            }
+        }
+        freed += self.purge_model_trash(|entry| model_trash::is_expired(entry, retention_days));
+        self.refresh_model_disk_usage();
+        let status = format!("Limpieza terminada. Se liberaron {}.", format_bytes(freed));
+        self.push_activity_log(LogStatus::Ok, "Jarvis", status.clone());
+        status
+    }
+
+    /// Registra en la biblioteca una carpeta encontrada al reconciliar.
+    pub fn adopt_orphan_model(&mut self, index: usize) -> String {
+        if index >= self.resources.orphan_model_dirs.len() {
+            return "La carpeta ya no está pendiente.".to_string();
+        }
+        let orphan = self.resources.orphan_model_dirs.remove(index);
+        let model_id = orphan.model_id.clone().unwrap_or_else(|| {
+            orphan
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        let identifier = LocalModelIdentifier::new(LocalModelProvider::HuggingFace, model_id);
+        self.upsert_installed_model(InstalledLocalModel {
+            identifier: identifier.clone(),
+            install_path: orphan.path.display().to_string(),
+            size_bytes: orphan.size_bytes,
+            installed_at: Utc::now(),
+            revision: crate::api::huggingface::installed_revision(&orphan.path),
+            quantized_from: None,
+        });
+        self.persist_config();
+        let status = format!(
+            "Modelo '{}' añadido a la biblioteca desde {}.",
+            identifier.display_label(),
+            orphan.path.display()
+        );
+        self.push_activity_log(LogStatus::Ok, "Jarvis", status.clone());
+        status
+    }
+
+    pub fn delete_orphan_model(&mut self, index: usize) -> String {
+        if index >= self.resources.orphan_model_dirs.len() {
+            return "La carpeta ya no está pendiente.".to_string();
+        }
+        let orphan = self.resources.orphan_model_dirs.remove(index);
+        let status = match model_trash::delete_dir(&orphan.path) {
+            Ok(()) => {
+                self.size_index.remove(&orphan.path);
+                self.save_size_index();
+                format!(
+                    "Carpeta {} borrada. Se liberaron {}.",
+                    orphan.path.display(),
+                    format_bytes(orphan.size_bytes)
+                )
+            }
+            Err(err) => {
+                let status = format!("No se pudo borrar {}: {}", orphan.path.display(), err);
+                self.resources.orphan_model_dirs.insert(index, orphan);
+                status
+            }
+        };
+        self.push_activity_log(LogStatus::Warning, "Jarvis", status.clone());
+        status
+    }
+
+    pub fn mark_local_model_updated(
+        &mut self,
+        identifier: &LocalModelIdentifier,
+    ) -> Option<String> {
+        if let Some(entry) = self
+            .resources
+            .installed_local_models
+            .iter_mut()
+            .find(|model| {
+                model.identifier.provider == identifier.provider
+                    && model.identifier.model_id == identifier.model_id
+            })
+        {
+            entry.installed_at = Utc::now();
+            self.persist_config();
+            let message = format!(
+                "Modelo '{}' actualizado correctamente.",
+                identifier.display_label()
+            );
+            self.push_activity_log(LogStatus::Ok, "Jarvis", message.clone());
+            Some(message)
+        } else {
+            None
+        }
+    }
+
+    /// Compara en segundo plano la revisión instalada de cada modelo de Hugging Face
+    /// con la última publicada. Los resultados llegan por el canal de instalaciones.
+    pub fn check_local_model_updates(&mut self) -> usize {
+        let targets: Vec<(LocalModelIdentifier, PathBuf, Option<String>)> = self
+            .resources
+            .installed_local_models
+            .iter()
+            .filter(|model| model.identifier.provider == LocalModelProvider::HuggingFace)
+            // Las variantes cuantizadas no tienen repositorio propio del que actualizarse.
+            .filter(|model| model.quantized_from.is_none())
+            .filter(|model| {
+                !self
+                    .resources
+                    .model_updates
+                    .status(&model.identifier)
+                    .is_some_and(ModelUpdateStatus::is_busy)
+            })
+            .map(|model| {
+                (
+                    model.identifier.clone(),
+                    PathBuf::from(&model.install_path),
+                    model.revision.clone(),
+                )
+            })
+            .collect();
+        if targets.is_empty() {
+            return 0;
+        }
+
+        for (identifier, _, _) in &targets {
+            self.resources
+                .model_updates
+                .statuses
+                .insert(identifier.clone(), ModelUpdateStatus::Checking);
+        }
+        self.resources.model_updates.last_checked = Some(performance::now());
+
+        let count = targets.len();
+        let token = self
+            .provider_state(LocalModelProvider::HuggingFace)
+            .access_token
+            .clone();
+        let tx = self.chat.local_install_tx.clone();
+        runtime::spawn_blocking(move || {
+            for (identifier, install_path, revision) in targets {
+                let installed =
+                    revision.or_else(|| crate::api::huggingface::installed_revision(&install_path));
+                let result = crate::api::huggingface::latest_revision(
+                    &identifier.model_id,
+                    token.as_deref(),
+                )
+                .map_err(|err| err.to_string());
+                let message = LocalInstallMessage::UpdateCheck {
+                    identifier,
+                    installed,
+                    result,
+                };
+                if tx.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.push_activity_log(
+            LogStatus::Running,
+            "Jarvis",
+            format!("Buscando actualizaciones de {} modelos locales.", count),
+        );
+        count
+    }
+
+    /// Descarga los archivos que cambiaron desde la revisión instalada del modelo.
+    pub fn queue_local_model_update(&mut self, identifier: &LocalModelIdentifier) -> String {
+        if identifier.provider != LocalModelProvider::HuggingFace {
+            return format!(
+                "Las actualizaciones incrementales solo están disponibles para modelos de Hugging Face ('{}').",
+                identifier.display_label()
+            );
+        }
+        let Some(record) = self.installed_model(identifier) else {
+            return format!("'{}' ya no está instalado.", identifier.display_label());
+        };
+        if self
+            .resources
+            .model_updates
+            .status(identifier)
+            .is_some_and(ModelUpdateStatus::is_busy)
+        {
+            return format!(
+                "'{}' ya se está comprobando o actualizando.",
+                identifier.display_label()
+            );
+        }
+
+        let install_path = PathBuf::from(&record.install_path);
+        let thread_identifier = identifier.clone();
+        let token = self
+            .provider_state(LocalModelProvider::HuggingFace)
+            .access_token
+            .clone();
+        let parallelism = self.config.jarvis.download_parallelism;
+        let tx = self.chat.local_install_tx.clone();
+        runtime::spawn_blocking(move || {
+            let result = crate::api::huggingface::update_model(
+                &thread_identifier.model_id,
+                &install_path,
+                token.as_deref(),
+                parallelism,
+            )
+            .map_err(|err| err.to_string());
+            let _ = tx.send(LocalInstallMessage::Updated {
+                identifier: thread_identifier,
+                result,
+            });
+        });
+
+        self.resources
+            .model_updates
+            .statuses
+            .insert(identifier.clone(), ModelUpdateStatus::Updating);
+        let status = format!(
+            "Actualizando '{}' desde Hugging Face…",
+            identifier.display_label()
+        );
+        self.push_activity_log(LogStatus::Running, "Jarvis", status.clone());
+        status
+    }
+
+    fn apply_model_update_check(
+        &mut self,
+        identifier: LocalModelIdentifier,
+        installed: Option<String>,
+        result: Result<crate::api::huggingface::RemoteRevision, String>,
+    ) {
+        let status = match result {
+            Ok(remote) if installed.as_deref() == Some(remote.sha.as_str()) => {
+                ModelUpdateStatus::UpToDate
+            }
+            Ok(remote) => {
+                self.push_activity_log(
+                    LogStatus::Warning,
+                    "Jarvis",
+                    format!(
+                        "Hay una revisión nueva de '{}' ({}).",
+                        identifier.display_label(),
+                        short_revision(&remote.sha)
+                    ),
+                );
+                ModelUpdateStatus::Available {
+                    revision: remote.sha,
+                    last_modified: remote.last_modified,
+                    changelog_url: crate::api::huggingface::changelog_url(&identifier.model_id),
+                }
+            }
+            Err(err) => {
+                self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    "Jarvis",
+                    format!(
+                        "No se pudo comprobar la revisión de '{}': {}",
+                        identifier.display_label(),
+                        err
+                    ),
+                );
+                ModelUpdateStatus::Failed(err)
+            }
+        };
+
+        // Las instalaciones antiguas no guardaban la revisión: se completa con la leída
+        // de sus metadatos para no volver a leerlos en cada comprobación.
+        if let Some(record) = self
+            .resources
+            .installed_local_models
+            .iter_mut()
+            .find(|model| model.identifier == identifier)
+        {
+            if record.revision.is_none() && installed.is_some() {
+                record.revision = installed;
+                self.persist_config();
+            }
+        }
+        self.resources
+            .model_updates
+            .statuses
+            .insert(identifier, status);
+    }
+
+    fn apply_model_update(
+        &mut self,
+        identifier: LocalModelIdentifier,
+        result: Result<crate::api::huggingface::ModelUpdate, String>,
+    ) {
+        let feedback = match result {
+            Ok(update) => {
+                if let Some(record) = self
+                    .resources
+                    .installed_local_models
+                    .iter_mut()
+                    .find(|model| model.identifier == identifier)
+                {
+                    record.revision = Some(update.revision.clone());
+                    let install_path = PathBuf::from(&record.install_path);
+                    match self
+                        .size_index
+                        .update_files(&install_path, &update.changed_files)
+                    {
+                        Some(size_bytes) => record.size_bytes = size_bytes,
+                        // Sin entrada en el índice se mide la carpeta en segundo plano.
+                        None => self.size_index.request_refresh(vec![install_path]),
+                    }
+                }
+                self.save_size_index();
+                self.resources
+                    .model_updates
+                    .statuses
+                    .insert(identifier.clone(), ModelUpdateStatus::UpToDate);
+
+                let is_active = self.resources.jarvis_active_model.as_ref() == Some(&identifier);
+                if is_active && !update.changed_files.is_empty() {
+                    // El runtime tiene cargados los pesos anteriores.
+                    self.resources.jarvis_runtime = None;
+                    self.tokenizers.clear_jarvis();
+                }
+                if !update.changed_files.is_empty() {
+                    self.resources.jarvis_pool.remove(&identifier);
+                }
+
+                let mut message = self
+                    .mark_local_model_updated(&identifier)
+                    .unwrap_or_default();
+                message.push_str(&format!(
+                    " Revisión {}: {} archivos descargados.",
+                    short_revision(&update.revision),
+                    update.changed_files.len()
+                ));
+                message
+            }
+            Err(err) => {
+                let message = format!(
+                    "No se pudo actualizar '{}': {}",
+                    identifier.display_label(),
+                    err
+                );
+                self.push_activity_log(LogStatus::Error, "Jarvis", message.clone());
+                self.resources
+                    .model_updates
+                    .statuses
+                    .insert(identifier, ModelUpdateStatus::Failed(err));
+                message
+            }
+        };
+        self.resources.local_library.operation_feedback = Some(feedback);
+    }
+
+    pub fn queue_huggingface_install(
+        &mut self,
+        model: LocalModelCard,
+        token: Option<String>,
+    ) -> bool {
+        let provider = model.provider;
+        if self
+            .chat
+            .pending_local_installs
+            .iter()
+            .any(|pending| pending.provider == provider && pending.model_id == model.id)
+        {
+            return false;
+        }
+
+        let sanitized_status = format!("Descargando '{}' desde Hugging Face…", model.id);
+        self.publish_model_install(provider, &model.id, "started", &sanitized_status);
+        self.provider_state_mut(provider).install_status = Some(sanitized_status.clone());
+        self.push_activity_log(LogStatus::Running, "Jarvis", sanitized_status);
+
+        let trimmed_token = token.and_then(|value| {
             let trimmed = value.trim();
             if trimmed.is_empty() {
                 None
@@ -3574,23 +6466,36 @@ impl AppState {
         });
 
         let install_dir = PathBuf::from(&self.resources.jarvis_install_dir);
+        let parallelism = self.config.jarvis.download_parallelism;
         let tx = self.chat.local_install_tx.clone();
         let thread_model = model.clone();
+        let progress = std::sync::Arc::new(crate::api::huggingface::DownloadProgress::default());
+        let thread_progress = std::sync::Arc::clone(&progress);
         let pending = PendingLocalInstall {
             provider,
             model_id: model.id.clone(),
+            progress,
+            sample: (Instant::now(), 0),
+            bytes_per_second: 0,
         };
         self.chat.pending_local_installs.push(pending);
 
-        std::thread::spawn(move || {
+        runtime::spawn_blocking(move || {
             let token_ref = trimmed_token.as_deref();
-            let outcome =
-                crate::api::huggingface::download_model(&thread_model, &install_dir, token_ref);
+            let outcome = crate::api::huggingface::download_model(
+                &thread_model,
+                &install_dir,
+                token_ref,
+                parallelism,
+                &thread_progress,
+            );
 
             let message = match outcome {
                 Ok(path) => LocalInstallMessage::Success {
                     provider,
                     model: thread_model,
+                    // Se mide aquí, fuera del hilo de la interfaz, archivo por archivo.
+                    sizes: DirectorySize::scan(&path),
                     install_path: path,
                 },
                 Err(err) => LocalInstallMessage::Error {
@@ -3606,21 +6511,201 @@ impl AppState {
         true
     }
 
-    pub fn provider_state(&self, provider: LocalModelProvider) -> &LocalProviderState {
-        self.resources
-            .local_provider_states
-            .get(&provider)
-            .expect("estado del proveedor no inicializado")
+    pub fn has_pending_local_installs(&self) -> bool {
+        !self.chat.pending_local_installs.is_empty()
     }
 
-    pub fn provider_state_mut(&mut self, provider: LocalModelProvider) -> &mut LocalProviderState {
-        if !self.resources.local_provider_states.contains_key(&provider) {
-            self.resources.local_provider_states.insert(
-                provider,
-                LocalProviderState::from_config(provider, &self.config),
-            );
-        }
-        self.resources
+    /// Descargas en curso del proveedor con sus bytes y su velocidad.
+    pub fn install_progress(&self, provider: LocalModelProvider) -> Vec<InstallProgress> {
+        self.chat
+            .pending_local_installs
+            .iter()
+            .filter(|pending| pending.provider == provider)
+            .map(|pending| InstallProgress {
+                model_id: pending.model_id.clone(),
+                downloaded: pending.progress.downloaded(),
+                total: pending.progress.total(),
+                bytes_per_second: pending.bytes_per_second,
+                cancelled: pending.progress.is_cancelled(),
+            })
+            .collect()
+    }
+
+    /// Pide parar la descarga; lo ya descargado queda para reanudarla al reinstalar.
+    pub fn cancel_local_install(&mut self, provider: LocalModelProvider, model_id: &str) -> bool {
+        let Some(pending) = self
+            .chat
+            .pending_local_installs
+            .iter()
+            .find(|pending| pending.provider == provider && pending.model_id == model_id)
+        else {
+            return false;
+        };
+        pending.progress.cancel();
+        self.provider_state_mut(provider).install_status =
+            Some(format!("Cancelando la descarga de '{}'…", model_id));
+        true
+    }
+
+    /// Convierte en segundo plano los pesos de `identifier` a `level` bits. La variante
+    /// se registra en la biblioteca como un modelo más cuando termina.
+    pub fn start_model_quantization(
+        &mut self,
+        identifier: &LocalModelIdentifier,
+        level: quantize::QuantizationLevel,
+    ) -> Result<String, String> {
+        if let Some(job) = &self.resources.model_quantize.job {
+            return Err(format!(
+                "Ya se está cuantizando '{}'.",
+                job.source.display_label()
+            ));
+        }
+        let Some(record) = self.installed_model(identifier) else {
+            return Err(format!(
+                "'{}' no está instalado.",
+                identifier.display_label()
+            ));
+        };
+        if record.quantized_from.is_some() {
+            return Err("El modelo ya es una variante cuantizada.".to_string());
+        }
+        let variant = quantize::quantized_identifier(identifier, level);
+        if self.installed_model(&variant).is_some() {
+            return Err(format!("'{}' ya existe.", variant.display_label()));
+        }
+
+        let source_dir = self.jarvis_model_directory_for(identifier);
+        let output_dir =
+            Path::new(&self.resources.jarvis_install_dir).join(variant.sanitized_dir_name());
+        if output_dir.exists() {
+            return Err(format!("Ya existe {}.", output_dir.display()));
+        }
+        self.resources.model_quantize.job = Some(quantize::spawn(
+            identifier.clone(),
+            source_dir,
+            output_dir,
+            level,
+        ));
+        let status = format!(
+            "Cuantizando '{}' a {}…",
+            identifier.display_label(),
+            level.label()
+        );
+        self.resources.local_library.operation_feedback = Some(status.clone());
+        self.push_activity_log(LogStatus::Running, "Jarvis", status.clone());
+        Ok(status)
+    }
+
+    fn poll_model_quantization(&mut self) -> bool {
+        let Some(job) = self.resources.model_quantize.job.as_mut() else {
+            return false;
+        };
+        let mut updated = false;
+        let mut finished = None;
+        while let Ok(message) = job.rx.try_recv() {
+            updated = true;
+            match message {
+                quantize::QuantizeMessage::Progress { done, total } => {
+                    job.done = done;
+                    job.total = total;
+                }
+                quantize::QuantizeMessage::Finished(result) => finished = Some(result),
+            }
+        }
+        let Some(result) = finished else {
+            return updated;
+        };
+        let job = self
+            .resources
+            .model_quantize
+            .job
+            .take()
+            .expect("trabajo de cuantización en curso");
+
+        match result {
+            Ok(report) => {
+                let variant = quantize::quantized_identifier(&job.source, job.level);
+                self.upsert_installed_model(InstalledLocalModel {
+                    identifier: variant.clone(),
+                    install_path: report.output_dir.display().to_string(),
+                    size_bytes: report.quantized_bytes,
+                    installed_at: Utc::now(),
+                    revision: None,
+                    quantized_from: Some(job.source.clone()),
+                });
+                self.persist_config();
+                let status = format!(
+                    "'{}' registrado: {} frente a {} ({} de {} tensores cuantizados).",
+                    variant.display_label(),
+                    format_bytes(report.quantized_bytes),
+                    format_bytes(report.original_bytes),
+                    report.quantized_tensors,
+                    report.total_tensors
+                );
+                self.push_activity_log(LogStatus::Ok, "Jarvis", status.clone());
+                self.toasts.push(
+                    LogStatus::Ok,
+                    format!("Variante {} lista.", variant.display_label()),
+                );
+                self.resources.local_library.operation_feedback = Some(status);
+            }
+            Err(err) => {
+                let status = format!(
+                    "No se pudo cuantizar '{}': {}",
+                    job.source.display_label(),
+                    err
+                );
+                self.push_activity_log(LogStatus::Error, "Jarvis", status.clone());
+                self.resources.local_library.operation_feedback = Some(status);
+            }
+        }
+        true
+    }
+
+    /// Recalcula la velocidad de las descargas en curso y publica su progreso.
+    fn poll_install_progress(&mut self) -> bool {
+        let now = Instant::now();
+        let mut events = Vec::new();
+        for pending in &mut self.chat.pending_local_installs {
+            let (sampled_at, sampled_bytes) = pending.sample;
+            let elapsed = now.duration_since(sampled_at);
+            if elapsed < INSTALL_PROGRESS_INTERVAL {
+                continue;
+            }
+            let downloaded = pending.progress.downloaded();
+            pending.bytes_per_second =
+                (downloaded.saturating_sub(sampled_bytes) as f64 / elapsed.as_secs_f64()) as u64;
+            pending.sample = (now, downloaded);
+            events.push(StreamEvent::ModelDownloadProgress {
+                provider: pending.provider.display_name().to_string(),
+                model_id: pending.model_id.clone(),
+                downloaded,
+                total: pending.progress.total(),
+                bytes_per_second: pending.bytes_per_second,
+            });
+        }
+        let updated = !events.is_empty();
+        for event in events {
+            self.event_stream.publish(event);
+        }
+        updated
+    }
+
+    pub fn provider_state(&self, provider: LocalModelProvider) -> &LocalProviderState {
+        self.resources
+            .local_provider_states
+            .get(&provider)
+            .expect("estado del proveedor no inicializado")
+    }
+
+    pub fn provider_state_mut(&mut self, provider: LocalModelProvider) -> &mut LocalProviderState {
+        if !self.resources.local_provider_states.contains_key(&provider) {
+            self.resources.local_provider_states.insert(
+                provider,
+                LocalProviderState::from_config(provider, &self.config),
+            );
+        }
+        self.resources
             .local_provider_states
             .get_mut(&provider)
             .expect("estado del proveedor no inicializado")
@@ -3653,6 +6738,97 @@ impl AppState {
             .find(|model| &model.identifier == identifier)
     }
 
+    /// Petición estructurada que acompaña a la próxima llamada: una reparación preparada
+    /// o, con el modo activo, el esquema del compositor.
+    fn take_structured_request(&mut self, prompt: &str) -> Option<StructuredRequest> {
+        if let Some(repair) = self.chat.structured.pending_repair.take() {
+            return Some(repair);
+        }
+        self.chat
+            .structured
+            .active_schema()
+            .map(|schema| StructuredRequest {
+                schema,
+                original_prompt: prompt.to_string(),
+                attempt: 0,
+            })
+    }
+
+    /// Valida la respuesta de una petición estructurada. Si no cumple el esquema y
+    /// quedan intentos, relanza la llamada con los errores para que el modelo la repare.
+    fn check_structured_response(
+        &mut self,
+        ticket: &ProviderCallTicket,
+        request: StructuredRequest,
+        text: &str,
+    ) {
+        let source = format!("providers::{}", ticket.provider_kind.short_code());
+        match structured::parse_and_validate(&request.schema, text) {
+            Ok(value) => {
+                if let Some(message) = self.chat.messages.get_mut(ticket.message_index) {
+                    message.structured = Some(value);
+                }
+                self.push_debug_event(
+                    DebugLogLevel::Info,
+                    source,
+                    format!(
+                        "Respuesta de '{}' válida según el esquema (reparaciones: {}).",
+                        ticket.model, request.attempt
+                    ),
+                );
+            }
+            Err(errors) if request.attempt < structured::MAX_REPAIR_ATTEMPTS => {
+                self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    source,
+                    format!(
+                        "Respuesta de '{}' no válida ({}); solicitando reparación {}/{}.",
+                        ticket.model,
+                        errors.join("; "),
+                        request.attempt + 1,
+                        structured::MAX_REPAIR_ATTEMPTS
+                    ),
+                );
+                let repair_prompt = request.repair_prompt(text, &errors);
+                let original_prompt = request.original_prompt.clone();
+                self.chat.structured.pending_repair = Some(StructuredRequest {
+                    attempt: request.attempt + 1,
+                    ..request
+                });
+                let dispatch = self.handle_provider_call(
+                    ticket.provider_kind,
+                    repair_prompt,
+                    Some(ticket.message_index),
+                );
+                // La reparación conserva el prompt original para poder regenerar.
+                self.chat.structured.pending_repair = None;
+                if let ProviderCallDispatch::Pending(repair) = dispatch {
+                    if let Some(message) = self.chat.messages.get_mut(repair.message_index) {
+                        message.source_prompt = Some(original_prompt);
+                    }
+                }
+            }
+            Err(errors) => {
+                self.push_activity_log(
+                    LogStatus::Warning,
+                    ticket.provider_name.clone(),
+                    format!(
+                        "La respuesta de '{}' no cumple el esquema tras {} reparaciones.",
+                        ticket.model, request.attempt
+                    ),
+                );
+                self.chat.messages.push(ChatMessage::system(format!(
+                    "La respuesta no cumple el esquema JSON:\n{}",
+                    errors
+                        .iter()
+                        .map(|error| format!("- {}", error))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                )));
+            }
+        }
+    }
+
     fn apply_provider_response(
         &mut self,
         response: ProviderResponse,
@@ -3666,6 +6842,7 @@ impl AppState {
             let pending = self.chat.pending_provider_calls.remove(position);
             let ticket = pending.ticket.clone();
             let outcome = response.outcome;
+            let mut completion_tokens = None;
 
             match &outcome {
                 Ok(text) => {
@@ -3679,34 +6856,129 @@ impl AppState {
 
                     let char_count = text.chars().count();
                     let snippet: String = text.chars().take(120).collect();
-                    *self.provider_status_slot(ticket.provider_kind) = Some(format!(
-                        "{} respondió correctamente ({} caracteres).",
-                        ticket.model, char_count
-                    ));
-                    self.push_activity_log(
-                        LogStatus::Ok,
-                        ticket.provider_name.clone(),
-                        format!("Respuesta recibida de '{}': {}", ticket.model, snippet),
-                    );
+                    self.response_cache.usage = None;
+                    if response.cached {
+                        self.resources.release_provider_quota(ticket.provider_kind);
+                        *self.provider_status_slot(ticket.provider_kind) = Some(format!(
+                            "{} respondió desde la caché ({} caracteres).",
+                            ticket.model, char_count
+                        ));
+                        self.push_activity_log(
+                            LogStatus::Ok,
+                            ticket.provider_name.clone(),
+                            format!("Respuesta en caché de '{}': {}", ticket.model, snippet),
+                        );
+                    } else {
+                        let tokens = self
+                            .tokenizers
+                            .for_provider(ticket.provider_kind)
+                            .count(text);
+                        completion_tokens = Some(tokens);
+                        self.resources
+                            .usage_state_mut(ticket.provider_kind)
+                            .record_completion_tokens(tokens);
+                        self.record_provider_spend(&ticket, pending.prompt_tokens, tokens);
+                        *self.provider_status_slot(ticket.provider_kind) = Some(format!(
+                            "{} respondió correctamente ({} caracteres).",
+                            ticket.model, char_count
+                        ));
+                        self.push_activity_log(
+                            LogStatus::Ok,
+                            ticket.provider_name.clone(),
+                            format!("Respuesta recibida de '{}': {}", ticket.model, snippet),
+                        );
+                    }
+
+                    let tool_query =
+                        web_search::tool_query(text).filter(|_| pending.web_search_tool);
+                    if let Some(query) = tool_query {
+                        self.start_web_search_tool(&ticket, query, &pending.conversation);
+                    } else if let Some(request) = pending.structured {
+                        self.check_structured_response(&ticket, request, text);
+                    }
                 }
                 Err(err) => {
+                    if matches!(err, ProviderError::Network(_))
+                        && self.connectivity.report_network_failure().is_some()
+                    {
+                        self.notify(
+                            NotificationSource::Providers,
+                            LogStatus::Warning,
+                            "Red",
+                            "Se perdió la conexión con los proveedores remotos.",
+                        );
+                    }
                     *self.provider_status_slot(ticket.provider_kind) =
-                        Some(format!("Último error: {}", err));
-                    self.push_activity_log(
-                        LogStatus::Error,
+                        Some(format!("Último error ({}): {}", err.kind_label(), err));
+                    let status = if err.is_retryable() {
+                        LogStatus::Warning
+                    } else {
+                        LogStatus::Error
+                    };
+                    self.notify(
+                        NotificationSource::Providers,
+                        status,
                         ticket.provider_name.clone(),
-                        format!("Fallo al invocar '{}': {}", ticket.model, err),
+                        format!(
+                            "Fallo al invocar '{}' [{}]: {}",
+                            ticket.model,
+                            err.kind_label(),
+                            err
+                        ),
                     );
 
                     if let Some(message) = self.chat.messages.get_mut(ticket.message_index) {
-                        *message = ChatMessage::system(format!(
-                            "{}: error al solicitar respuesta: {}",
-                            ticket.alias, err
-                        ));
+                        let source_prompt = message.source_prompt.take();
+                        let code_anchor = message.code_anchor.take();
+                        *message = ChatMessage::provider_failure(
+                            format!("{}: error al solicitar respuesta: {}", ticket.alias, err),
+                            ticket.provider_kind,
+                            source_prompt,
+                            err.clone(),
+                        );
+                        message.code_anchor = code_anchor;
                     }
                 }
             }
 
+            let mut details = BTreeMap::from([
+                ("proveedor".to_string(), ticket.provider_name.clone()),
+                ("modelo".to_string(), ticket.model.clone()),
+                (
+                    "tokens_prompt".to_string(),
+                    pending.prompt_tokens.to_string(),
+                ),
+            ]);
+            if let Some(tokens) = completion_tokens {
+                details.insert("tokens_respuesta".to_string(), tokens.to_string());
+            }
+            if response.cached {
+                details.insert("cache".to_string(), "sí".to_string());
+            }
+            if let Err(err) = &outcome {
+                details.insert("error".to_string(), err.to_string());
+            }
+            self.record_audit_in(
+                &pending.conversation,
+                AuditEventKind::ProviderCall,
+                format!("{} · {}", ticket.provider_name, ticket.model),
+                details,
+            );
+            let latency_ms = (!response.cached).then(|| {
+                pending
+                    .started_at
+                    .elapsed()
+                    .as_millis()
+                    .min(u32::MAX as u128) as u32
+            });
+            self.record_provider_usage(
+                &ticket.provider_name,
+                pending.prompt_tokens,
+                completion_tokens.unwrap_or_default(),
+                latency_ms,
+                outcome.is_err(),
+            );
+
             Some(ProviderCallResult { ticket, outcome })
         } else {
             None
@@ -3714,7 +6986,47 @@ impl AppState {
     }
 
     pub fn update_async_tasks(&mut self) -> bool {
-        let mut updated = false;
+        let mut updated = self.jobs.pump();
+        updated |= self.poll_connectivity();
+        updated |= self.poll_file_watcher();
+        updated |= self.poll_scheduled_tasks();
+        self.publish_stream_events();
+        updated |= self.poll_batch_evaluation();
+        updated |= self.poll_quick_tests();
+        updated |= self.poll_benchmark();
+        updated |= self.poll_daily_digest();
+        updated |= self.poll_activity_briefing();
+        updated |= self.poll_report_run();
+        updated |= self.poll_playbook_run();
+        updated |= self.poll_follow_ups();
+        updated |= self.poll_context_summaries();
+        updated |= self.poll_conflict_proposal();
+        updated |= self.poll_fetch_status();
+        updated |= self.poll_git_query();
+        updated |= self.poll_install_progress();
+        updated |= self.poll_model_quantization();
+        updated |= self.refresh_semantic_search();
+        updated |= self.poll_embeddings();
+        updated |= self.refresh_registries();
+        updated |= self.refresh_global_search();
+        // Con el almacenamiento bloqueado no se guarda nada para no pisar el historial cifrado.
+        if !vault::is_locked() {
+            self.autosave_composer_draft();
+            self.autosave_session();
+        }
+        self.poll_config_saver();
+        updated |= self.poll_size_index();
+        self.save_usage_metrics(false);
+        updated |= self.poll_system_monitor();
+        updated |= self.poll_claude_models();
+        updated |= self.poll_transcription();
+        updated |= self.poll_ocr();
+        updated |= self.poll_email();
+        updated |= self.poll_webhooks();
+        updated |= self.poll_telegram();
+        updated |= self.poll_mqtt();
+        updated |= self.poll_web_search();
+        updated |= self.poll_clipboard();
 
         while let Ok(response) = self.chat.provider_response_rx.try_recv() {
             if self.apply_provider_response(response).is_some() {
@@ -3728,18 +7040,24 @@ impl AppState {
                     provider,
                     model,
                     install_path,
+                    sizes,
                 } => {
                     let model_id = model.id.clone();
                     let identifier = LocalModelIdentifier::new(provider, &model_id);
-                    let size_bytes = compute_directory_size(&install_path);
+                    let size_bytes = sizes.total();
+                    self.size_index.insert(&install_path, sizes);
+                    self.save_size_index();
                     let install_path_string = install_path.display().to_string();
                     let record = InstalledLocalModel {
                         identifier: identifier.clone(),
                         install_path: install_path_string.clone(),
                         size_bytes,
                         installed_at: Utc::now(),
+                        revision: crate::api::huggingface::installed_revision(&install_path),
+                        quantized_from: None,
                     };
                     self.upsert_installed_model(record);
+                    self.resources.model_updates.statuses.remove(&identifier);
 
                     let activation_status = self.activate_jarvis_model(&identifier);
                     let size_label = format_bytes(size_bytes);
@@ -3763,6 +7081,7 @@ impl AppState {
                     );
 
                     self.resources.jarvis_status = Some(status_message.clone());
+                    self.publish_model_install(provider, &model_id, "completed", &status_message);
 
                     {
                         let provider_state = self.provider_state_mut(provider);
@@ -3790,6 +7109,7 @@ impl AppState {
 
                     let status = format!("Fallo al instalar '{}': {}", model_id, error);
                     self.resources.jarvis_status = Some(status.clone());
+                    self.publish_model_install(provider, &model_id, "failed", &status);
                     self.push_activity_log(
                         LogStatus::Error,
                         "Jarvis",
@@ -3797,6 +7117,14 @@ impl AppState {
                     );
                     self.provider_state_mut(provider).install_status = Some(status);
                 }
+                LocalInstallMessage::UpdateCheck {
+                    identifier,
+                    installed,
+                    result,
+                } => self.apply_model_update_check(identifier, installed, result),
+                LocalInstallMessage::Updated { identifier, result } => {
+                    self.apply_model_update(identifier, result)
+                }
             }
 
             updated = true;
@@ -3819,12 +7147,22 @@ impl AppState {
         let deadline = Instant::now() + timeout;
 
         while !remaining.is_empty() {
+            // Las llamadas que esperan sus recuerdos salen al llegar los embeddings.
+            let waiting_recall = !self.chat.recall_waiting.is_empty();
+            if waiting_recall {
+                self.poll_embeddings();
+            }
             let now = Instant::now();
             if let Some(wait) = deadline.checked_duration_since(now) {
                 if wait.is_zero() {
                     break;
                 }
 
+                let wait = if waiting_recall {
+                    wait.min(EMBEDDINGS_POLL_INTERVAL)
+                } else {
+                    wait
+                };
                 match self.chat.provider_response_rx.recv_timeout(wait) {
                     Ok(response) => {
                         if let Some(result) = self.apply_provider_response(response) {
@@ -3833,7 +7171,7 @@ impl AppState {
                             }
                         }
                     }
-                    Err(RecvTimeoutError::Timeout) => break,
+                    Err(RecvTimeoutError::Timeout) => continue,
                     Err(RecvTimeoutError::Disconnected) => break,
                 }
             } else {
@@ -3856,6 +7194,10 @@ impl AppState {
     }
 
     fn sync_config_from_state(&mut self) {
+        if !self.is_zen_mode() {
+            self.config.layout.current = self.layout_snapshot();
+            self.config.layout.active_view = self.active_main_view;
+        }
         self.config.github_token = if self.github_token.trim().is_empty() {
             None
         } else {
@@ -3874,6 +7216,32 @@ impl AppState {
         self.config.selected_profile = self.selected_profile;
         self.config.projects = self.projects.clone();
         self.config.selected_project = self.selected_project;
+        self.config.knowledge_reindex = self
+            .automation
+            .cron_board
+            .tasks
+            .iter()
+            .filter_map(|task| {
+                let ScheduledTaskKind::ReindexKnowledge { project } = &task.kind else {
+                    return None;
+                };
+                Some(KnowledgeReindexSchedule {
+                    project: project.clone(),
+                    cron_expression: task.cron_expression.clone(),
+                    enabled: task.enabled,
+                    last_run: task.last_run.clone(),
+                })
+            })
+            .collect();
+        if let Some(task) = self
+            .automation
+            .cron_board
+            .tasks
+            .iter()
+            .find(|task| task.kind == ScheduledTaskKind::DailyDigest)
+        {
+            self.config.daily_digest.last_run = task.last_run.clone();
+        }
         let hf_state = self.provider_state(LocalModelProvider::HuggingFace).clone();
         self.config.huggingface.last_search_query = hf_state.search_query;
         self.config.huggingface.access_token = hf_state.access_token;
@@ -3935,573 +7303,7813 @@ impl AppState {
         Self::normalize_string_option(&mut self.config.ollama.access_token);
         Self::normalize_string_option(&mut self.config.openrouter.access_token);
         Self::normalize_string_option(&mut self.config.modelscope.access_token);
+        self.config.link_provider_keys();
     }
 
+    /// Vuelca el estado en la configuración y programa su guardado en segundo plano.
     pub fn persist_config(&mut self) {
         self.sync_config_from_state();
-        self.rebuild_navigation();
-        if let Err(err) = self.config.save() {
-            self.chat.messages.push(ChatMessage::system(format!(
-                "No se pudo guardar la configuración: {}",
-                err
-            )));
+        let origin = self.state_change_origin();
+        self.record_state_changes(&origin);
+        self.config_saver.mark_dirty();
+    }
+
+    /// Recoge el resultado del último guardado de la configuración y lanza el siguiente.
+    fn poll_config_saver(&mut self) {
+        if let Some(report) = self.config_saver.poll(&self.config) {
+            self.apply_config_save_report(report);
+        }
+        match self.config_saver.poll_external(&mut self.config) {
+            Some(Ok(change)) => self.apply_external_config_change(change),
+            Some(Err(err)) => self.push_debug_event(
+                DebugLogLevel::Warning,
+                "config",
+                format!("Se ignoró la edición externa de config.json: {}", err),
+            ),
+            None => {}
         }
     }
 
-    pub fn refresh_personalization_resources(&mut self) {
-        self.resources.personalization_resources = PersonalizationResourcesState::from_sources(
-            &self.profiles,
-            &self.projects,
-            &self.github_repositories,
-        );
+    fn apply_external_config_change(&mut self, change: config_saver::ExternalConfigChange) {
+        if !change.applied.is_empty() {
+            self.refresh_from_config(&change.applied);
+            self.record_state_changes("config.json");
+            let message = format!(
+                "Se aplicaron cambios hechos a mano en config.json ({}).",
+                change.applied.join(", ")
+            );
+            self.push_activity_log(LogStatus::Ok, "Configuración", message.clone());
+            self.toasts.push(LogStatus::Ok, message);
+        }
+        if !change.conflicts.is_empty() {
+            self.push_activity_log(
+                LogStatus::Warning,
+                "Configuración",
+                format!(
+                    "config.json y la aplicación cambiaron a la vez: {}.",
+                    change.conflicts.join(", ")
+                ),
+            );
+        }
     }
 
-    pub fn rebuild_command_registry(&mut self) {
-        let mut registry = CommandRegistry::new();
-        self.chat.register_commands(&mut registry);
-        self.automation.register_commands(&mut registry);
-        self.resources.register_commands(&mut registry);
-        self.command_registry = registry;
+    /// Cierra el conflicto de `config.json` con la versión elegida.
+    pub fn resolve_config_conflict(&mut self, keep_local: bool) {
+        match self
+            .config_saver
+            .resolve_conflict(&mut self.config, keep_local)
+        {
+            Ok(sections) if !keep_local => {
+                self.refresh_from_config(&sections);
+                self.record_state_changes("config.json");
+            }
+            Ok(_) => {}
+            Err(err) => self.chat.messages.push(ChatMessage::system(format!(
+                "No se pudo aplicar config.json: {}",
+                err
+            ))),
+        }
     }
 
-    #[cfg_attr(not(test), allow(dead_code))]
-    pub fn register_workbench_view<V>(&mut self, view: MainView, view_impl: V)
-    where
-        V: WorkbenchView + 'static,
-    {
-        self.workbench_views.insert(view, Box::new(view_impl));
+    /// Propaga a la sesión las secciones de la configuración que se recargaron del disco o
+    /// se revirtieron. El resto de ajustes se lee de `config` cada vez que se usa.
+    fn refresh_from_config(&mut self, sections: &[String]) {
+        for section in sections {
+            match section.as_str() {
+                "network" => {
+                    crate::api::network::apply_settings(&self.config.network);
+                    self.network_draft = self.config.network.clone();
+                }
+                "language" => i18n::set_language(self.config.language),
+                "theme" => self.theme = ThemeTokens::from_preset(self.config.theme),
+                "github_token" => {
+                    self.github_token = self.config.github_token.clone().unwrap_or_default()
+                }
+                "event_stream" => {
+                    self.event_stream_draft = self.config.event_stream.clone();
+                    let _ = self.restart_event_stream();
+                }
+                "anthropic" => {
+                    self.resources.claude_default_model =
+                        self.config.anthropic.default_model.clone();
+                    self.resources.claude_alias = self.config.anthropic.alias.clone();
+                }
+                "openai" => {
+                    self.resources.openai_default_model = self.config.openai.default_model.clone();
+                    self.resources.openai_alias = self.config.openai.alias.clone();
+                }
+                "groq" => {
+                    self.resources.groq_default_model = self.config.groq.default_model.clone();
+                    self.resources.groq_alias = self.config.groq.alias.clone();
+                }
+                "jarvis" => {
+                    let jarvis = &self.config.jarvis;
+                    self.resources.jarvis_model_path = jarvis.model_path.clone();
+                    self.resources.jarvis_install_dir = jarvis.install_dir.clone();
+                    self.resources.jarvis_auto_start = jarvis.auto_start;
+                    self.resources.jarvis_alias = jarvis.chat_alias.clone();
+                    self.resources.jarvis_active_model = jarvis
+                        .active_model
+                        .as_ref()
+                        .map(|value| LocalModelIdentifier::parse(value));
+                }
+                "huggingface" | "github_models" | "replicate" | "ollama" | "openrouter"
+                | "modelscope" => {
+                    let provider = match section.as_str() {
+                        "huggingface" => LocalModelProvider::HuggingFace,
+                        "github_models" => LocalModelProvider::GithubModels,
+                        "replicate" => LocalModelProvider::Replicate,
+                        "ollama" => LocalModelProvider::Ollama,
+                        "openrouter" => LocalModelProvider::OpenRouter,
+                        _ => LocalModelProvider::Modelscope,
+                    };
+                    let loaded = LocalProviderState::from_config(provider, &self.config);
+                    let provider_state = self.provider_state_mut(provider);
+                    provider_state.access_token = loaded.access_token;
+                    provider_state.token_input = loaded.token_input;
+                    provider_state.search_query = loaded.search_query;
+                }
+                "custom_commands" => {
+                    self.chat.custom_commands = self.config.custom_commands.clone()
+                }
+                "cache_directory" => self.cache_directory = self.config.cache_directory.clone(),
+                "cache_size_limit_gb" => self.cache_size_limit_gb = self.config.cache_size_limit_gb,
+                "enable_auto_cleanup" => self.enable_auto_cleanup = self.config.enable_auto_cleanup,
+                "cache_cleanup_interval_hours" => {
+                    self.cache_cleanup_interval_hours = self.config.cache_cleanup_interval_hours
+                }
+                "resource_memory_limit_gb" => {
+                    self.resource_memory_limit_gb = self.config.resource_memory_limit_gb
+                }
+                "resource_disk_limit_gb" => {
+                    self.resource_disk_limit_gb = self.config.resource_disk_limit_gb
+                }
+                "enable_memory_tracking" => {
+                    self.enable_memory_tracking = self.config.enable_memory_tracking
+                }
+                "memory_retention_days" => {
+                    self.memory_retention_days = self.config.memory_retention_days
+                }
+                "profiles" => self.profiles = self.config.profiles.clone(),
+                "selected_profile" => self.selected_profile = self.config.selected_profile,
+                "projects" => self.projects = self.config.projects.clone(),
+                "selected_project" => self.selected_project = self.config.selected_project,
+                _ => {}
+            }
+        }
     }
 
-    pub fn register_workbench_initializer<F>(&mut self, initializer: F)
-    where
-        F: Fn(&mut WorkbenchRegistry) + 'static,
-    {
-        self.workbench_initializers.push(Box::new(initializer));
-        self.rebuild_workbench_views();
+    /// Origen con el que se anotan los cambios de estado: el workflow en curso, si lo hay.
+    fn state_change_origin(&self) -> String {
+        match self
+            .automation
+            .workflows
+            .workflows
+            .iter()
+            .find(|workflow| workflow.status == WorkflowStatus::Running)
+        {
+            Some(workflow) => format!("Workflow '{}'", workflow.name),
+            None => "Interfaz".to_string(),
+        }
     }
 
-    pub fn workbench_view(&self, view: MainView) -> Option<&dyn WorkbenchView> {
-        self.workbench_views.get(&view).map(|view| view.as_ref())
+    /// Anota en el diario y en la consola de depuración lo que cambió en la configuración.
+    fn record_state_changes(&mut self, origin: &str) {
+        for change in self.state_journal.record(&self.config, origin) {
+            self.push_debug_event(DebugLogLevel::Info, "state", change.describe());
+        }
     }
 
-    pub fn with_workbench_view_mut<R>(
-        &mut self,
-        view: MainView,
-        f: impl FnOnce(&mut dyn WorkbenchView, &mut AppState) -> R,
-    ) -> Option<R> {
-        let mut view_impl = self.workbench_views.remove(&view)?;
-        let result = f(view_impl.as_mut(), self);
-        self.workbench_views.insert(view, view_impl);
-        Some(result)
+    /// Devuelve un campo del diario de cambios a su valor anterior y lo propaga a la sesión.
+    pub fn revert_state_change(&mut self, id: u64, field: usize) -> Result<String, String> {
+        let (config, change) = self.state_journal.revert(&self.config, id, field)?;
+        let keys = self.config.provider_keys();
+        self.config = config;
+        self.config.keep_provider_keys(&keys);
+        self.refresh_from_config(&change.path[..1]);
+        self.record_state_changes(&format!("Reversión de #{}", id));
+        self.persist_config();
+        // Los campos que se copian desde la sesión y no se recargan vuelven a imponerse.
+        if state_journal::value_at(&self.config, &change.path) != change.before {
+            return Err(format!(
+                "{} no se puede revertir desde aquí; cámbialo en su panel.",
+                change.path_label()
+            ));
+        }
+        self.state_journal.mark_reverted(id, field);
+        let message = format!("{} revertido ({}).", change.path_label(), change.summary());
+        self.push_activity_log(LogStatus::Ok, "Depuración", message.clone());
+        Ok(message)
     }
 
-    pub fn rebuild_workbench_views(&mut self) {
-        self.workbench_views.clear();
-        let mut registry = WorkbenchRegistry::new(&mut self.workbench_views);
-        self.chat.register_workbench_views(&mut registry);
-        self.automation.register_workbench_views(&mut registry);
-        self.resources.register_workbench_views(&mut registry);
-        for initializer in &self.workbench_initializers {
-            initializer(&mut registry);
+    /// Escribe en el acto la configuración pendiente; se invoca al cerrar la ventana.
+    pub fn flush_config(&mut self) {
+        if let Some(report) = self.config_saver.flush(&self.config) {
+            self.apply_config_save_report(report);
         }
     }
 
-    pub fn rebuild_navigation(&mut self) {
-        let mut registry = build_navigation_registry(&self.config);
-        self.chat.register_navigation(&mut registry);
-        self.automation.register_navigation(&mut registry);
-        self.resources.register_navigation(&mut registry);
-        self.navigation = registry;
+    fn apply_config_save_report(&mut self, report: config_saver::ConfigSaveReport) {
+        match report.error {
+            Some(err) => self.chat.messages.push(ChatMessage::system(format!(
+                "No se pudo guardar la configuración: {}",
+                err
+            ))),
+            None if !report.sections.is_empty() => self.push_debug_event(
+                DebugLogLevel::Info,
+                "config",
+                format!("Configuración guardada ({}).", report.sections.join(", ")),
+            ),
+            None => {}
+        }
     }
 
-    fn jarvis_model_directory(&self) -> Option<PathBuf> {
-        let direct_path = self.resources.jarvis_model_path.trim();
+    pub fn layout_snapshot(&self) -> LayoutSnapshot {
+        LayoutSnapshot {
+            show_navigation: self.layout.show_navigation,
+            show_resource_panel: self.layout.show_resource_panel,
+            navigation_collapsed: self.layout.navigation_collapsed(),
+            resource_collapsed: self.layout.resource_collapsed(),
+            navigation_width: self.layout.navigation_width.round(),
+            resource_width: self.layout.resource_width.round(),
+            composer_height: self.composer_height.round(),
+        }
+    }
+
+    fn restore_layout_snapshot(&mut self, snapshot: &LayoutSnapshot) {
+        apply_layout_snapshot(&mut self.layout, snapshot);
+        self.layout.request_width_sync();
+        self.composer_height = snapshot.composer_height;
+        self.composer_height_sync = snapshot.composer_height > 0.0;
+    }
+
+    /// Guarda la distribución y la vista activa cuando cambian. Se invoca al final de
+    /// cada frame, salvo mientras se arrastra un panel, para no escribir en disco
+    /// continuamente.
+    pub fn sync_layout_persistence(&mut self) {
+        if self.is_zen_mode() {
+            return;
+        }
+
+        let snapshot = self.layout_snapshot();
+        if snapshot == self.config.layout.current
+            && self.active_main_view == self.config.layout.active_view
+        {
+            return;
+        }
+
+        self.config.layout.current = snapshot;
+        self.config.layout.active_view = self.active_main_view;
+        self.config_saver.mark_dirty();
+    }
+
+    pub fn save_layout_preset(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Indica un nombre para la distribución.".to_string());
+        }
+
+        let layout = self.layout_snapshot();
+        match self
+            .config
+            .layout
+            .presets
+            .iter_mut()
+            .find(|preset| preset.name.eq_ignore_ascii_case(name))
+        {
+            Some(preset) => preset.layout = layout,
+            None => self.config.layout.presets.push(LayoutPreset {
+                name: name.to_string(),
+                layout,
+            }),
+        }
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Layout",
+            tf("Distribución '{0}' guardada.", &[name]),
+        );
+        Ok(())
+    }
+
+    pub fn apply_layout_preset(&mut self, index: usize) {
+        let Some(preset) = self.config.layout.presets.get(index).cloned() else {
+            return;
+        };
+        if self.is_zen_mode() {
+            self.toggle_zen_mode();
+        }
+        self.restore_layout_snapshot(&preset.layout);
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Layout",
+            tf("Distribución '{0}' aplicada.", &[&preset.name]),
+        );
+    }
+
+    pub fn delete_layout_preset(&mut self, index: usize) {
+        if index < self.config.layout.presets.len() {
+            let preset = self.config.layout.presets.remove(index);
+            self.persist_config();
+            self.push_activity_log(
+                LogStatus::Ok,
+                "Layout",
+                tf("Distribución '{0}' eliminada.", &[&preset.name]),
+            );
+        }
+    }
+
+    pub fn is_popped_out(&self, view: MainView) -> bool {
+        self.config
+            .layout
+            .popouts
+            .iter()
+            .any(|popout| popout.view == view)
+    }
+
+    /// Abre `view` en una ventana propia. Solo el chat y la consola de depuración se
+    /// pueden sacar de la ventana principal.
+    pub fn pop_out_view(&mut self, view: MainView) {
+        if !POPOUT_VIEWS.contains(&view) || self.is_popped_out(view) {
+            return;
+        }
+        self.config.layout.popouts.push(PopoutWindow {
+            view,
+            position: None,
+            size: POPOUT_DEFAULT_SIZE,
+        });
+        self.config_saver.mark_dirty();
+    }
+
+    /// Cierra la ventana propia de `view`; la vista vuelve a la ventana principal.
+    pub fn dock_view(&mut self, view: MainView) {
+        let before = self.config.layout.popouts.len();
+        self.config
+            .layout
+            .popouts
+            .retain(|popout| popout.view != view);
+        if self.config.layout.popouts.len() != before {
+            self.config_saver.mark_dirty();
+        }
+    }
+
+    /// Recuerda dónde quedó la ventana de `view` para restaurarla igual al arrancar.
+    pub fn update_popout_geometry(&mut self, view: MainView, position: [f32; 2], size: [f32; 2]) {
+        let Some(popout) = self
+            .config
+            .layout
+            .popouts
+            .iter_mut()
+            .find(|popout| popout.view == view)
+        else {
+            return;
+        };
+        if popout.position == Some(position) && popout.size == size {
+            return;
+        }
+        popout.position = Some(position);
+        popout.size = size;
+        self.config_saver.mark_dirty();
+    }
+
+    pub fn is_zen_mode(&self) -> bool {
+        self.zen_restore.is_some()
+    }
+
+    /// Alterna el modo zen: oculta cabecera y paneles laterales y deja solo el chat.
+    pub fn toggle_zen_mode(&mut self) {
+        match self.zen_restore.take() {
+            Some((layout, view)) => {
+                self.layout = layout;
+                self.layout.request_width_sync();
+                self.active_main_view = view;
+            }
+            None => {
+                self.zen_restore = Some((self.layout.clone(), self.active_main_view));
+                self.layout.show_header = false;
+                self.layout.show_navigation = false;
+                self.layout.show_resource_panel = false;
+                self.active_main_view = MainView::ChatMultimodal;
+            }
+        }
+    }
+
+    /// Valida el borrador de red y, si es correcto, lo aplica a los nuevos clientes HTTP.
+    pub fn apply_network_settings(&mut self) -> Result<(), String> {
+        let draft = self.network_draft.clone();
+        if let Err(err) = crate::api::network::validate(&draft) {
+            let message = format!("{:#}", err);
+            self.network_status = Some(format!("Configuración de red rechazada: {}", message));
+            self.push_debug_event(DebugLogLevel::Error, "network", message.clone());
+            return Err(message);
+        }
+
+        crate::api::network::apply_settings(&draft);
+        self.config.network = draft;
+        self.persist_config();
+
+        let summary = if self.config.network.proxy_url.trim().is_empty() {
+            "conexión directa".to_string()
+        } else {
+            format!("proxy {}", self.config.network.proxy_url.trim())
+        };
+        self.network_status = Some(format!("Ajustes de red aplicados ({}).", summary));
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Red",
+            format!("Ajustes de red actualizados: {}", summary),
+        );
+        Ok(())
+    }
+
+    /// Cifra la conversación actual en `share.export_path` con la frase de paso indicada.
+    pub fn export_shared_conversation(&mut self) -> Result<usize, String> {
+        if self.share.passphrase != self.share.passphrase_confirm {
+            return Err("Las frases de paso no coinciden.".to_string());
+        }
+        let mut path = PathBuf::from(self.share.export_path.trim());
+        if path.as_os_str().is_empty() {
+            return Err("Indica dónde guardar la conversación.".to_string());
+        }
+        if path.extension().is_none() {
+            path.set_extension(share::SHARE_EXTENSION);
+        }
+
+        let conversation = share::SharedConversation::from_messages(&self.chat.messages);
+        let count = conversation.messages.len();
+        if count == 0 {
+            return Err("No hay mensajes que exportar.".to_string());
+        }
+        share::export_conversation(&path, &conversation, &self.share.passphrase)
+            .map_err(|err| format!("{:#}", err))?;
+        self.record_file_written(&path, "Conversación cifrada");
+
+        self.share.passphrase.clear();
+        self.share.passphrase_confirm.clear();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Chat",
+            format!(
+                "Conversación exportada y cifrada en {} ({} mensajes).",
+                path.display(),
+                count
+            ),
+        );
+        Ok(count)
+    }
+
+    pub fn is_message_pinned(&self, index: usize) -> bool {
+        self.chat.messages.get(index).is_some_and(|message| {
+            self.bookmarks
+                .entries
+                .iter()
+                .any(|bookmark| bookmark.matches(&self.bookmarks.conversation, message))
+        })
+    }
+
+    /// Posiciones de los mensajes fijados en la conversación actual.
+    pub fn pinned_messages(&self) -> Vec<usize> {
+        (0..self.chat.messages.len())
+            .filter(|index| self.is_message_pinned(*index))
+            .collect()
+    }
+
+    /// Fija o suelta un mensaje; los fijados se guardan como marcadores.
+    pub fn toggle_message_pin(&mut self, index: usize) {
+        let Some(message) = self.chat.messages.get(index) else {
+            return;
+        };
+        let conversation = self.bookmarks.conversation.clone();
+        let before = self.bookmarks.entries.len();
+        self.bookmarks
+            .entries
+            .retain(|bookmark| !bookmark.matches(&conversation, message));
+        if self.bookmarks.entries.len() == before {
+            self.bookmarks
+                .entries
+                .push(Bookmark::from_message(&conversation, message));
+        }
+        self.save_bookmarks();
+    }
+
+    pub fn remove_bookmark(&mut self, index: usize) {
+        if index < self.bookmarks.entries.len() {
+            self.bookmarks.entries.remove(index);
+            self.save_bookmarks();
+        }
+    }
+
+    fn save_bookmarks(&mut self) {
+        if let Err(err) = self.bookmarks.save() {
+            self.bookmarks.status =
+                Some(format!("No se pudieron guardar los marcadores: {:#}", err));
+        }
+    }
+
+    /// Vuelve al mensaje de un marcador si su conversación sigue abierta.
+    pub fn open_bookmark(&mut self, index: usize) -> bool {
+        let Some(bookmark) = self.bookmarks.entries.get(index) else {
+            return false;
+        };
+        let position = self
+            .chat
+            .messages
+            .iter()
+            .position(|message| bookmark.matches(&self.bookmarks.conversation, message));
+        match position {
+            Some(position) => {
+                self.activate_navigation_target(NavigationTarget::main(MainView::ChatMultimodal));
+                self.focus_chat_message(Some(position));
+                true
+            }
+            None => {
+                self.bookmarks.status = Some(
+                    "El mensaje pertenece a una conversación anterior; se conserva su texto en el marcador."
+                        .to_string(),
+                );
+                false
+            }
+        }
+    }
+
+    pub fn export_bookmarks(&mut self) -> Result<usize, String> {
+        let path = PathBuf::from(self.bookmarks.export_path.trim());
+        if path.as_os_str().is_empty() {
+            return Err("Indica dónde guardar los marcadores.".to_string());
+        }
+        let count = self
+            .bookmarks
+            .export_markdown(&path)
+            .map_err(|err| format!("{:#}", err))?;
+        self.record_file_written(&path, "Marcadores");
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Chat",
+            format!("{} marcadores exportados a {}.", count, path.display()),
+        );
+        Ok(count)
+    }
+
+    /// Empaqueta los workflows indicados en `workflow_bundles.export_path` o, si no se
+    /// indica, en `workflows/` dentro de los datos de la app.
+    pub fn export_workflow_bundle(&mut self, ids: &[u32]) -> Result<PathBuf, String> {
+        let selected: Vec<&AutomationWorkflow> = self
+            .automation
+            .workflows
+            .workflows
+            .iter()
+            .filter(|workflow| ids.contains(&workflow.id))
+            .collect();
+        let name = match selected.as_slice() {
+            [] => return Err("No hay workflows que exportar.".to_string()),
+            [workflow] => workflow.name.clone(),
+            _ => format!("{} workflows", selected.len()),
+        };
+        let now = Local::now();
+        let bundle = workflow_bundle::export(
+            &name,
+            &now.format("%Y-%m-%d %H:%M:%S").to_string(),
+            &selected,
+            &self.automation,
+            &mut self.redaction,
+            &self.config.redaction,
+        );
+
+        let mut path = PathBuf::from(self.workflow_bundles.export_path.trim());
+        if path.as_os_str().is_empty() {
+            let directory =
+                AppConfig::data_file("workflows").map_err(|err| format!("{:#}", err))?;
+            fs::create_dir_all(&directory)
+                .map_err(|err| format!("No se pudo crear {}: {}", directory.display(), err))?;
+            path = directory.join(reports::report_file_name(&name, now));
+        }
+        path.set_extension("json");
+        let content = serde_json::to_string_pretty(&bundle).map_err(|err| err.to_string())?;
+        fs::write(&path, content)
+            .map_err(|err| format!("No se pudo escribir {}: {}", path.display(), err))?;
+        self.record_file_written(&path, "Workflows");
+
+        let mut message = format!(
+            "{} workflows, {} programaciones y {} listeners exportados a {}.",
+            bundle.workflows.len(),
+            bundle.schedules.len(),
+            bundle.listeners.len(),
+            path.display()
+        );
+        if !bundle.secrets.is_empty() {
+            message.push_str(&format!(
+                " {} secretos sustituidos por marcadores.",
+                bundle.secrets.len()
+            ));
+        }
+        self.push_activity_log(LogStatus::Ok, "Automation", message);
+        Ok(path)
+    }
+
+    /// Lee y valida el paquete de `workflow_bundles.import_path` y lo deja pendiente de
+    /// confirmar junto con sus conflictos y los secretos que pide.
+    pub fn load_workflow_bundle(&mut self) -> Result<(), String> {
+        let path = PathBuf::from(self.workflow_bundles.import_path.trim());
+        if path.as_os_str().is_empty() {
+            return Err("Indica el paquete que quieres importar.".to_string());
+        }
+        let text = fs::read_to_string(&path)
+            .map_err(|err| format!("No se pudo leer {}: {}", path.display(), err))?;
+        let bundle = workflow_bundle::parse(&text)?;
+        self.workflow_bundles.conflicts = workflow_bundle::conflicts(&bundle, &self.automation);
+        self.workflow_bundles.secrets = bundle
+            .secrets
+            .iter()
+            .map(|secret| (secret.name.clone(), String::new()))
+            .collect();
+        self.workflow_bundles.pending = Some(bundle);
+        Ok(())
+    }
+
+    pub fn discard_workflow_bundle(&mut self) {
+        self.workflow_bundles.pending = None;
+        self.workflow_bundles.conflicts.clear();
+        self.workflow_bundles.secrets.clear();
+    }
+
+    /// Incorpora el paquete pendiente y lo guarda en la configuración para que siga
+    /// disponible en los siguientes arranques.
+    pub fn import_workflow_bundle(&mut self) -> Result<workflow_bundle::ImportSummary, String> {
+        let Some(bundle) = self.workflow_bundles.pending.take() else {
+            return Err("No hay ningún paquete pendiente de importar.".to_string());
+        };
+        let (summary, installed) = workflow_bundle::apply(
+            &bundle,
+            self.workflow_bundles.resolution,
+            &self.workflow_bundles.secrets,
+            &mut self.automation,
+        );
+        self.discard_workflow_bundle();
+        if !installed.workflows.is_empty()
+            || !installed.schedules.is_empty()
+            || !installed.listeners.is_empty()
+        {
+            self.config.imported_workflows.push(installed);
+            self.persist_config();
+        }
+
+        let message = format!(
+            "Paquete '{}' importado: {} añadidas, {} reemplazadas y {} omitidas.",
+            bundle.name, summary.added, summary.replaced, summary.skipped
+        );
+        if summary.missing_secrets.is_empty() {
+            self.push_activity_log(LogStatus::Ok, "Automation", message);
+        } else {
+            self.push_activity_log(
+                LogStatus::Warning,
+                "Automation",
+                format!(
+                    "{} Quedan secretos sin valor: {}.",
+                    message,
+                    summary.missing_secrets.join(", ")
+                ),
+            );
+        }
+        Ok(summary)
+    }
+
+    /// Descifra una conversación compartida y la añade al final del historial.
+    pub fn import_shared_conversation(&mut self) -> Result<usize, String> {
+        let path = PathBuf::from(self.share.import_path.trim());
+        if path.as_os_str().is_empty() {
+            return Err("Indica el archivo que quieres importar.".to_string());
+        }
+        let conversation = share::import_conversation(&path, &self.share.passphrase)
+            .map_err(|err| format!("{:#}", err))?;
+
+        let exported_at = conversation.exported_at.clone();
+        self.chat.messages.push(ChatMessage::system(format!(
+            "Conversación importada desde {} (exportada el {}).",
+            path.display(),
+            exported_at
+        )));
+        let offset = self.chat.messages.len();
+        let messages = conversation.into_messages(offset);
+        let count = messages.len();
+        self.chat.messages.extend(messages);
+
+        self.share.passphrase.clear();
+        self.share.passphrase_confirm.clear();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Chat",
+            format!("Importados {} mensajes desde {}.", count, path.display()),
+        );
+        Ok(count)
+    }
+
+    /// Lanza la evaluación configurada en `batch_eval`. Cada proveedor remoto recorre los
+    /// prompts en su propio hilo; Jarvis los atiende de uno en uno desde
+    /// `update_async_tasks`, ya que el runtime local vive en el hilo de la interfaz.
+    pub fn start_batch_evaluation(&mut self) -> Result<usize, String> {
+        if self.batch_eval.is_running() {
+            return Err("Ya hay una evaluación en curso.".to_string());
+        }
+        let path = self.batch_eval.prompts_path.trim().to_string();
+        if path.is_empty() {
+            return Err("Indica el fichero de prompts.".to_string());
+        }
+        let targets = self.batch_eval.targets.clone();
+        if targets.is_empty() {
+            return Err("Selecciona al menos un destino.".to_string());
+        }
+        let prompts =
+            batch_eval::load_prompts(Path::new(&path)).map_err(|err| format!("{:#}", err))?;
+
+        let mut workers = Vec::new();
+        for target in &targets {
+            if let BatchTarget::Remote(provider) = *target {
+                let profile = self.provider_call_profile(provider);
+                let key = profile.api_key.ok_or_else(|| {
+                    format!(
+                        "{} no tiene una API key configurada.",
+                        profile.provider_name
+                    )
+                })?;
+                if self.is_offline() {
+                    return Err(format!(
+                        "Sin conexión: {} no está disponible.",
+                        profile.provider_name
+                    ));
+                }
+                workers.push((provider, key, profile.model, profile.caller));
+            }
+        }
+        // Los prompts remotos salen redactados; Jarvis sigue recibiendo los originales.
+        let remote_prompts: Vec<BatchPrompt> = if workers.is_empty() {
+            Vec::new()
+        } else {
+            prompts
+                .iter()
+                .map(|prompt| BatchPrompt {
+                    id: prompt.id.clone(),
+                    prompt: self.redact_outgoing(&prompt.prompt, "Evaluación por lotes"),
+                })
+                .collect()
+        };
+
+        let cache = if self.batch_eval.bypass_cache {
+            None
+        } else {
+            self.response_cache.handle(&self.config)
+        };
+        let (tx, rx) = runtime::ui_channel();
+        for (provider, key, model, caller) in workers {
+            let tx = tx.clone();
+            let prompts = remote_prompts.clone();
+            let cache = cache.clone();
+            let parameters = provider.request_parameters(&GenerationOptions::default());
+            self.jobs.submit(
+                provider.short_code(),
+                JobPriority::Background,
+                "Evaluación por lotes",
+                move || {
+                    for prompt in prompts {
+                        let started = Instant::now();
+                        let cache_key =
+                            ResponseCache::key(provider, &model, &prompt.prompt, &parameters);
+                        let outcome = match cache.as_ref().and_then(|cache| cache.get(&cache_key)) {
+                            Some(text) => Ok(text),
+                            None => {
+                                let outcome = caller(
+                                    &key,
+                                    &model,
+                                    &prompt.prompt,
+                                    &GenerationOptions::default(),
+                                )
+                                .map_err(|err| {
+                                    ProviderError::from_anyhow(err).message().to_string()
+                                });
+                                if let (Some(cache), Ok(text)) = (&cache, &outcome) {
+                                    let _ = cache.put(&cache_key, provider, &model, text);
+                                }
+                                outcome
+                            }
+                        };
+                        let message = RemoteBatchOutcome {
+                            prompt,
+                            provider,
+                            outcome,
+                            latency: started.elapsed(),
+                        };
+                        if tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                },
+            );
+        }
+
+        let jarvis_queue = if targets.contains(&BatchTarget::Jarvis) {
+            prompts.iter().cloned().collect()
+        } else {
+            Default::default()
+        };
+        let expected = prompts.len() * targets.len();
+        self.batch_eval.results.clear();
+        self.batch_eval.run = Some(BatchRun {
+            remote_rx: rx,
+            jarvis_queue,
+            expected,
+            started_at: Instant::now(),
+        });
+
+        let message = format!(
+            "Evaluando {} prompts contra {} destinos.",
+            prompts.len(),
+            targets.len()
+        );
+        self.batch_eval.status = Some(message.clone());
+        self.push_activity_log(LogStatus::Running, "Evaluación", message);
+        Ok(expected)
+    }
+
+    pub fn cancel_batch_evaluation(&mut self) {
+        if self.batch_eval.run.take().is_some() {
+            let message = format!(
+                "Evaluación cancelada con {} resultados.",
+                self.batch_eval.results.len()
+            );
+            self.batch_eval.status = Some(message.clone());
+            self.push_activity_log(LogStatus::Warning, "Evaluación", message);
+        }
+    }
+
+    fn poll_batch_evaluation(&mut self) -> bool {
+        let Some(run) = self.batch_eval.run.as_mut() else {
+            return false;
+        };
+
+        let mut outcomes = Vec::new();
+        let mut workers_done = false;
+        loop {
+            match run.remote_rx.try_recv() {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    workers_done = true;
+                    break;
+                }
+            }
+        }
+        let jarvis_prompt = run.jarvis_queue.pop_front();
+        let jarvis_done = run.jarvis_queue.is_empty();
+        let expected = run.expected;
+        let updated = !outcomes.is_empty() || jarvis_prompt.is_some();
+
+        for outcome in outcomes {
+            let result = BatchResult::from_outcome(
+                BatchTarget::Remote(outcome.provider),
+                outcome.prompt,
+                outcome.outcome,
+                outcome.latency,
+                self.tokenizers.for_provider(outcome.provider),
+            );
+            self.batch_eval.results.push(result);
+        }
+
+        if let Some(prompt) = jarvis_prompt {
+            let started = Instant::now();
+            let outcome = self.generate_local_jarvis_reply(&prompt.prompt);
+            let result = BatchResult::from_outcome(
+                BatchTarget::Jarvis,
+                prompt,
+                outcome,
+                started.elapsed(),
+                self.tokenizers.jarvis(),
+            );
+            self.batch_eval.results.push(result);
+        }
+
+        // Un hilo que termine antes de tiempo no debe dejar la evaluación colgada.
+        if self.batch_eval.results.len() >= expected || (workers_done && jarvis_done) {
+            self.finish_batch_evaluation();
+        }
+        updated
+    }
+
+    fn finish_batch_evaluation(&mut self) {
+        let Some(run) = self.batch_eval.run.take() else {
+            return;
+        };
+        let elapsed = run.started_at.elapsed().as_secs_f32();
+        let failures = self
+            .batch_eval
+            .results
+            .iter()
+            .filter(|result| result.error.is_some())
+            .count();
+        let mut message = format!(
+            "Evaluación completada: {} resultados, {} errores en {:.1} s.",
+            self.batch_eval.results.len(),
+            failures,
+            elapsed
+        );
+
+        let output = self.batch_eval.output_path.trim().to_string();
+        let mut status = if failures > 0 {
+            LogStatus::Warning
+        } else {
+            LogStatus::Ok
+        };
+        if !output.is_empty() {
+            match batch_eval::write_results(Path::new(&output), &self.batch_eval.results) {
+                Ok(()) => {
+                    message.push_str(&format!(" Resultados en {}.", output));
+                    self.record_file_written(Path::new(&output), "Evaluación por lotes");
+                }
+                Err(err) => {
+                    message.push_str(&format!(" No se pudo guardar {}: {:#}", output, err));
+                    status = LogStatus::Error;
+                }
+            }
+        }
+        self.batch_eval.status = Some(message.clone());
+        self.push_activity_log(status, "Evaluación", message);
+    }
+
+    /// Guarda la prueba del formulario, nueva o sustituyendo a la que se editaba.
+    pub fn save_quick_test_case(&mut self) -> Result<(), String> {
+        let mut case = self.quick_tests.draft.clone();
+        case.name = case.name.trim().to_string();
+        quick_tests::validate_case(&case)?;
+        let duplicate = self
+            .config
+            .quick_tests
+            .iter()
+            .enumerate()
+            .any(|(index, existing)| {
+                existing.name == case.name && Some(index) != self.quick_tests.editing
+            });
+        if duplicate {
+            return Err(format!("Ya hay una prueba llamada '{}'.", case.name));
+        }
+        match self
+            .quick_tests
+            .editing
+            .filter(|index| *index < self.config.quick_tests.len())
+        {
+            Some(index) => self.config.quick_tests[index] = case,
+            None => self.config.quick_tests.push(case),
+        }
+        self.quick_tests.draft = QuickTestCase::default();
+        self.quick_tests.editing = None;
+        self.persist_config();
+        Ok(())
+    }
+
+    pub fn remove_quick_test_case(&mut self, index: usize) {
+        if index >= self.config.quick_tests.len() {
+            return;
+        }
+        self.config.quick_tests.remove(index);
+        if self.quick_tests.editing == Some(index) {
+            self.quick_tests.editing = None;
+            self.quick_tests.draft = QuickTestCase::default();
+        }
+        self.persist_config();
+    }
+
+    /// Modelos contra los que se ejecutan las pruebas: los de la comparativa del catálogo
+    /// y, si se pide, el modelo activo de Jarvis.
+    pub fn quick_test_targets(&self) -> Vec<quick_tests::QuickTestTarget> {
+        let mut targets: Vec<quick_tests::QuickTestTarget> = self
+            .resources
+            .remote_catalog
+            .comparison
+            .iter()
+            .map(quick_tests::QuickTestTarget::remote)
+            .collect();
+        if self.quick_tests.include_jarvis {
+            let model = self
+                .resources
+                .jarvis_active_model
+                .as_ref()
+                .map(|model| model.model_id.clone())
+                .unwrap_or_else(|| "local".to_string());
+            targets.push(quick_tests::QuickTestTarget::jarvis(model));
+        }
+        targets
+    }
+
+    /// Lanza todas las pruebas guardadas contra los modelos de `quick_test_targets`. Igual
+    /// que la evaluación por lotes, cada modelo remoto recorre las pruebas en su propio hilo
+    /// y Jarvis las atiende de una en una desde `update_async_tasks`.
+    pub fn start_quick_tests(&mut self) -> Result<usize, String> {
+        if self.quick_tests.is_running() {
+            return Err("Ya hay pruebas en curso.".to_string());
+        }
+        let cases = self.config.quick_tests.clone();
+        if cases.is_empty() {
+            return Err("Crea al menos una prueba.".to_string());
+        }
+        let targets = self.quick_test_targets();
+        if targets.is_empty() {
+            return Err(
+                "Añade modelos a la comparativa o incluye Jarvis para ejecutar las pruebas."
+                    .to_string(),
+            );
+        }
+
+        let mut workers = Vec::new();
+        for (index, target) in targets.iter().enumerate() {
+            let Some(key) = target.remote_key() else {
+                continue;
+            };
+            let profile = self.provider_call_profile(key.provider);
+            let api_key = profile.api_key.ok_or_else(|| {
+                format!(
+                    "{} no tiene una API key configurada.",
+                    profile.provider_name
+                )
+            })?;
+            if self.is_offline() {
+                return Err(format!(
+                    "Sin conexión: {} no está disponible.",
+                    profile.provider_name
+                ));
+            }
+            workers.push((index, key.provider, api_key, key.id, profile.caller));
+        }
+        // Los prompts remotos salen redactados; Jarvis sigue recibiendo los originales.
+        let remote_prompts: Vec<String> = if workers.is_empty() {
+            Vec::new()
+        } else {
+            cases
+                .iter()
+                .map(|case| self.redact_outgoing(&case.prompt, "Pruebas rápidas"))
+                .collect()
+        };
+
+        let (tx, rx) = runtime::ui_channel();
+        for (target, provider, api_key, model, caller) in workers {
+            let tx = tx.clone();
+            let prompts = remote_prompts.clone();
+            self.jobs.submit(
+                provider.short_code(),
+                JobPriority::Background,
+                "Pruebas rápidas",
+                move || {
+                    for (case, prompt) in prompts.iter().enumerate() {
+                        let started = Instant::now();
+                        let outcome =
+                            caller(&api_key, &model, prompt, &GenerationOptions::default())
+                                .map_err(|err| {
+                                    ProviderError::from_anyhow(err).message().to_string()
+                                });
+                        let message = quick_tests::RemoteTestOutcome {
+                            case,
+                            target,
+                            outcome,
+                            latency: started.elapsed(),
+                        };
+                        if tx.send(message).is_err() {
+                            break;
+                        }
+                    }
+                },
+            );
+        }
+
+        let jarvis_target = targets.iter().position(|target| target.provider.is_none());
+        let jarvis_queue = if jarvis_target.is_some() {
+            (0..cases.len()).collect()
+        } else {
+            Default::default()
+        };
+        let expected = cases.len() * targets.len();
+        let message = format!(
+            "Ejecutando {} pruebas contra {} modelos.",
+            cases.len(),
+            targets.len()
+        );
+        self.quick_tests.run = Some(quick_tests::QuickTestRun {
+            remote_rx: rx,
+            jarvis_queue,
+            jarvis_target,
+            record: quick_tests::QuickTestRunRecord {
+                started_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                cases: cases.iter().map(|case| case.name.clone()).collect(),
+                targets,
+                outcomes: Vec::new(),
+            },
+            cases,
+            expected,
+            started_at: Instant::now(),
+        });
+        self.quick_tests.status = Some(message.clone());
+        self.push_activity_log(LogStatus::Running, "Pruebas rápidas", message);
+        Ok(expected)
+    }
+
+    pub fn cancel_quick_tests(&mut self) {
+        if self.quick_tests.run.take().is_some() {
+            let message = "Pruebas canceladas.".to_string();
+            self.quick_tests.status = Some(message.clone());
+            self.push_activity_log(LogStatus::Warning, "Pruebas rápidas", message);
+        }
+    }
+
+    fn poll_quick_tests(&mut self) -> bool {
+        let Some(run) = self.quick_tests.run.as_mut() else {
+            return false;
+        };
+
+        let mut outcomes = Vec::new();
+        let mut workers_done = false;
+        loop {
+            match run.remote_rx.try_recv() {
+                Ok(outcome) => outcomes.push(outcome),
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    workers_done = true;
+                    break;
+                }
+            }
+        }
+        for outcome in outcomes.iter() {
+            let case = &run.cases[outcome.case];
+            run.record
+                .outcomes
+                .push(quick_tests::QuickTestOutcome::evaluate(
+                    case,
+                    outcome.target,
+                    outcome.outcome.clone(),
+                    outcome.latency,
+                ));
+        }
+        let jarvis_case = run
+            .jarvis_target
+            .and_then(|target| Some((target, run.jarvis_queue.pop_front()?)))
+            .map(|(target, case)| (target, run.cases[case].clone()));
+        let updated = !outcomes.is_empty() || jarvis_case.is_some();
+
+        if let Some((target, case)) = jarvis_case {
+            let started = Instant::now();
+            let outcome = self.generate_local_jarvis_reply(&case.prompt);
+            if let Some(run) = self.quick_tests.run.as_mut() {
+                run.record
+                    .outcomes
+                    .push(quick_tests::QuickTestOutcome::evaluate(
+                        &case,
+                        target,
+                        outcome,
+                        started.elapsed(),
+                    ));
+            }
+        }
+
+        // Un hilo que termine antes de tiempo no debe dejar las pruebas colgadas.
+        let finished = self.quick_tests.run.as_ref().is_some_and(|run| {
+            run.record.outcomes.len() >= run.expected
+                || (workers_done && run.jarvis_queue.is_empty())
+        });
+        if finished {
+            self.finish_quick_tests();
+        }
+        updated
+    }
+
+    fn finish_quick_tests(&mut self) {
+        let Some(run) = self.quick_tests.run.take() else {
+            return;
+        };
+        let record = run.record;
+        let message = format!(
+            "Pruebas completadas: {}/{} superadas en {:.1} s.",
+            record.passed(),
+            record.outcomes.len(),
+            run.started_at.elapsed().as_secs_f32()
+        );
+        let status = if record.passed() == record.outcomes.len() {
+            LogStatus::Ok
+        } else {
+            LogStatus::Warning
+        };
+        self.quick_tests.history.insert(0, record);
+        self.quick_tests.history.truncate(quick_tests::MAX_HISTORY);
+        self.quick_tests.selected_run = 0;
+        if let Err(err) = quick_tests::save_history(&self.quick_tests.history) {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "quick_tests",
+                format!("No se pudo guardar el historial de pruebas: {:#}", err),
+            );
+        }
+        self.quick_tests.status = Some(message.clone());
+        self.push_activity_log(status, "Pruebas rápidas", message);
+    }
+
+    /// Hace predeterminado del proveedor un modelo que superó las pruebas.
+    pub fn use_remote_model_as_default(&mut self, key: &RemoteModelKey) {
+        let default_model = match key.provider {
+            RemoteProviderKind::Anthropic => &mut self.resources.claude_default_model,
+            RemoteProviderKind::OpenAi => &mut self.resources.openai_default_model,
+            RemoteProviderKind::Groq => &mut self.resources.groq_default_model,
+        };
+        *default_model = key.id.clone();
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Pruebas rápidas",
+            format!("{} es ahora el modelo predeterminado.", key.as_display()),
+        );
+    }
+
+    /// Lanza el benchmark de latencia: cada prompt estandarizado se envía `iterations`
+    /// veces a cada destino. Como en la evaluación por lotes, cada proveedor remoto trabaja
+    /// en su propio hilo y Jarvis atiende una llamada por fotograma.
+    pub fn start_benchmark(
+        &mut self,
+        targets: &[BatchTarget],
+        iterations: usize,
+    ) -> Result<usize, String> {
+        if self.benchmark.is_running() {
+            return Err("Ya hay un benchmark en curso.".to_string());
+        }
+        if targets.is_empty() {
+            return Err("Selecciona al menos un destino.".to_string());
+        }
+        let iterations = iterations.clamp(1, benchmark::MAX_ITERATIONS);
+
+        let mut workers = Vec::new();
+        let mut models = Vec::new();
+        for target in targets {
+            let model = match *target {
+                BatchTarget::Remote(provider) => {
+                    let profile = self.provider_call_profile(provider);
+                    let key = profile.api_key.ok_or_else(|| {
+                        format!(
+                            "{} no tiene una API key configurada.",
+                            profile.provider_name
+                        )
+                    })?;
+                    if self.is_offline() {
+                        return Err(format!(
+                            "Sin conexión: {} no está disponible.",
+                            profile.provider_name
+                        ));
+                    }
+                    workers.push((*target, key, profile.model.clone(), profile.caller));
+                    profile.model
+                }
+                BatchTarget::Jarvis => self
+                    .resources
+                    .jarvis_active_model
+                    .as_ref()
+                    .map(|model| model.model_id.clone())
+                    .unwrap_or_else(|| "local".to_string()),
+            };
+            models.push(benchmark::BenchmarkModel {
+                target: target.code().to_string(),
+                model,
+            });
+        }
+
+        let (tx, rx) = runtime::ui_channel();
+        for (target, key, model, caller) in workers {
+            let tx = tx.clone();
+            self.jobs.submit(
+                target.code(),
+                JobPriority::Background,
+                "Benchmark",
+                move || {
+                    for size in benchmark::BenchmarkPromptSize::ALL {
+                        let prompt = size.prompt();
+                        for iteration in 0..iterations {
+                            crate::api::network::take_first_byte();
+                            let started = Instant::now();
+                            let outcome =
+                                caller(&key, &model, &prompt, &GenerationOptions::default())
+                                    .map_err(|err| {
+                                        ProviderError::from_anyhow(err).message().to_string()
+                                    });
+                            let total = started.elapsed();
+                            let ttfb = crate::api::network::take_first_byte()
+                                .map(|first_byte| first_byte.saturating_duration_since(started));
+                            let call = benchmark::BenchmarkCall {
+                                target,
+                                size,
+                                iteration,
+                                outcome,
+                                ttfb,
+                                total,
+                            };
+                            if tx.send(call).is_err() {
+                                return;
+                            }
+                        }
+                    }
+                },
+            );
+        }
+
+        let jarvis_queue = if targets.contains(&BatchTarget::Jarvis) {
+            benchmark::BenchmarkPromptSize::ALL
+                .into_iter()
+                .flat_map(|size| (0..iterations).map(move |iteration| (size, iteration)))
+                .collect()
+        } else {
+            Default::default()
+        };
+        let expected = targets.len() * benchmark::BenchmarkPromptSize::ALL.len() * iterations;
+        self.benchmark.run = Some(benchmark::BenchmarkRun {
+            remote_rx: rx,
+            jarvis_queue,
+            record: benchmark::BenchmarkRecord {
+                started_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                iterations,
+                models,
+                samples: Vec::new(),
+            },
+            expected,
+            started_at: Instant::now(),
+        });
+
+        let message = format!(
+            "Benchmark en curso: {} llamadas a {} destinos.",
+            expected,
+            targets.len()
+        );
+        self.benchmark.status = Some(message.clone());
+        self.push_activity_log(LogStatus::Running, "Benchmark", message);
+        Ok(expected)
+    }
+
+    pub fn cancel_benchmark(&mut self) {
+        if self.benchmark.run.take().is_some() {
+            let message = "Benchmark cancelado.".to_string();
+            self.benchmark.status = Some(message.clone());
+            self.push_activity_log(LogStatus::Warning, "Benchmark", message);
+        }
+    }
+
+    fn poll_benchmark(&mut self) -> bool {
+        let Some(run) = self.benchmark.run.as_mut() else {
+            return false;
+        };
+
+        let mut calls = Vec::new();
+        let mut workers_done = false;
+        loop {
+            match run.remote_rx.try_recv() {
+                Ok(call) => calls.push(call),
+                Err(std::sync::mpsc::TryRecvError::Empty) => break,
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    workers_done = true;
+                    break;
+                }
+            }
+        }
+        let jarvis_call = run.jarvis_queue.pop_front();
+        let updated = !calls.is_empty() || jarvis_call.is_some();
+
+        let mut samples = Vec::new();
+        for call in calls {
+            let BatchTarget::Remote(provider) = call.target else {
+                continue;
+            };
+            samples.push(benchmark::BenchmarkSample::measure(
+                call,
+                self.tokenizers.for_provider(provider),
+            ));
+        }
+        if let Some((size, iteration)) = jarvis_call {
+            let started = Instant::now();
+            let outcome = self.generate_local_jarvis_reply(&size.prompt());
+            let call = benchmark::BenchmarkCall {
+                target: BatchTarget::Jarvis,
+                size,
+                iteration,
+                outcome,
+                ttfb: None,
+                total: started.elapsed(),
+            };
+            samples.push(benchmark::BenchmarkSample::measure(
+                call,
+                self.tokenizers.jarvis(),
+            ));
+        }
+
+        // Un hilo que termine antes de tiempo no debe dejar el benchmark colgado.
+        let finished = self.benchmark.run.as_mut().is_some_and(|run| {
+            run.record.samples.extend(samples);
+            run.record.samples.len() >= run.expected
+                || (workers_done && run.jarvis_queue.is_empty())
+        });
+        if finished {
+            self.finish_benchmark();
+        }
+        updated
+    }
+
+    fn finish_benchmark(&mut self) {
+        let Some(run) = self.benchmark.run.take() else {
+            return;
+        };
+        let record = run.record;
+        let failures = record
+            .samples
+            .iter()
+            .filter(|sample| sample.error.is_some())
+            .count();
+        let mut message = format!(
+            "Benchmark completado: {} llamadas, {} errores en {:.1} s.",
+            record.samples.len(),
+            failures,
+            run.started_at.elapsed().as_secs_f32()
+        );
+        let mut status = if failures > 0 {
+            LogStatus::Warning
+        } else {
+            LogStatus::Ok
+        };
+
+        let report = AppConfig::data_file(benchmark::REPORTS_DIR).and_then(|directory| {
+            fs::create_dir_all(&directory)?;
+            let path = directory.join(reports::report_file_name("Benchmark", Local::now()));
+            fs::write(&path, record.render_report())?;
+            Ok(path)
+        });
+        match report {
+            Ok(path) => {
+                message.push_str(&format!(" Informe en {}.", path.display()));
+                self.record_file_written(&path, "Benchmark");
+            }
+            Err(err) => {
+                message.push_str(&format!(" No se pudo guardar el informe: {:#}", err));
+                status = LogStatus::Error;
+            }
+        }
+        if let Err(err) = benchmark::save_last_run(&record) {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "benchmark",
+                format!("No se pudo guardar el último benchmark: {:#}", err),
+            );
+        }
+
+        self.benchmark.last_run = Some(record);
+        let updated_cards = self.apply_benchmark_latencies();
+        if updated_cards > 0 {
+            message.push_str(&format!(
+                " Latencia actualizada en {} fichas del catálogo.",
+                updated_cards
+            ));
+        }
+        self.benchmark.status = Some(message.clone());
+        self.push_activity_log(status, "Benchmark", message);
+    }
+
+    /// Sustituye la latencia de las fichas del catálogo por la medida en el último
+    /// benchmark. Devuelve cuántas fichas cambiaron.
+    fn apply_benchmark_latencies(&mut self) -> usize {
+        let Some(record) = self.benchmark.last_run.as_ref() else {
+            return 0;
+        };
+        let mut updated = 0;
+        for (provider, model, latency_ms) in record.card_latencies() {
+            let cards = self.resources.remote_catalog.cards_for_mut(provider);
+            if let Some(card) = cards.iter_mut().find(|card| card.key.id == model) {
+                card.latency_ms = latency_ms;
+                updated += 1;
+            }
+        }
+        updated
+    }
+
+    /// Aplica el borrador del flujo de eventos y reinicia el servidor.
+    pub fn apply_event_stream_settings(&mut self) -> Result<(), String> {
+        self.config.event_stream = self.event_stream_draft.clone();
+        self.persist_config();
+        self.restart_event_stream()
+    }
+
+    fn restart_event_stream(&mut self) -> Result<(), String> {
+        let settings = self.config.event_stream.clone();
+        match self.event_stream.configure(&settings) {
+            Ok(()) => {
+                let workflows = self.automation.workflows.workflows.iter();
+                let statuses: Vec<(u32, String)> = workflows
+                    .map(|workflow| (workflow.id, format!("{:?}", workflow.status).to_lowercase()))
+                    .collect();
+                self.event_stream.mark_published(
+                    self.chat.messages.len(),
+                    statuses.iter().map(|(id, status)| (*id, status.as_str())),
+                );
+                if let Some(server) = &self.event_stream.server {
+                    let message = format!("Flujo de eventos en ws://{}", server.address());
+                    self.push_activity_log(LogStatus::Ok, "Eventos", message);
+                }
+                Ok(())
+            }
+            Err(err) => {
+                let message = format!("{:#}", err);
+                self.notify(
+                    NotificationSource::Integrations,
+                    LogStatus::Error,
+                    "Eventos",
+                    format!("No se pudo iniciar el flujo de eventos: {}", message),
+                );
+                Err(message)
+            }
+        }
+    }
+
+    /// Arranca el vigilante con las rutas configuradas, o lo detiene si está desactivado.
+    pub fn restart_file_watcher(&mut self) -> Result<(), String> {
+        self.file_watcher.watcher = None;
+        let settings = self.config.file_watcher.clone();
+        if !settings.enabled {
+            self.file_watcher.status = Some("Vigilante de ficheros detenido.".to_string());
+            return Ok(());
+        }
+
+        let debounce = Duration::from_millis(settings.debounce_millis.max(50));
+        match file_watcher::FileWatcher::start(settings.paths.clone(), debounce) {
+            Ok(watcher) => {
+                self.file_watcher.watcher = Some(watcher);
+                let message = format!("Vigilando {} rutas.", settings.paths.len());
+                self.file_watcher.status = Some(message.clone());
+                self.push_activity_log(LogStatus::Ok, "Ficheros", message);
+                Ok(())
+            }
+            Err(err) => {
+                let message = format!("{:#}", err);
+                self.file_watcher.status = Some(message.clone());
+                self.notify(
+                    NotificationSource::Integrations,
+                    LogStatus::Error,
+                    "Ficheros",
+                    format!("No se pudo iniciar el vigilante de ficheros: {}", message),
+                );
+                Err(message)
+            }
+        }
+    }
+
+    pub fn set_file_watcher_enabled(&mut self, enabled: bool) {
+        self.config.file_watcher.enabled = enabled;
+        self.persist_config();
+        let _ = self.restart_file_watcher();
+    }
+
+    pub fn add_watched_path(&mut self) -> Result<(), String> {
+        let draft = self.file_watcher.draft.clone();
+        let path = draft.path.trim();
+        if path.is_empty() {
+            return Err("Indica la ruta que quieres vigilar.".to_string());
+        }
+        if !Path::new(path).exists() {
+            return Err(format!("La ruta {} no existe.", path));
+        }
+        self.config.file_watcher.paths.push(WatchedPath {
+            path: path.to_string(),
+            pattern: draft.pattern.trim().to_string(),
+        });
+        self.file_watcher.draft = Default::default();
+        self.persist_config();
+        if self.config.file_watcher.enabled {
+            self.restart_file_watcher()?;
+        }
+        Ok(())
+    }
+
+    pub fn remove_watched_path(&mut self, index: usize) {
+        if index >= self.config.file_watcher.paths.len() {
+            return;
+        }
+        self.config.file_watcher.paths.remove(index);
+        self.persist_config();
+        if self.config.file_watcher.enabled {
+            let _ = self.restart_file_watcher();
+        }
+    }
+
+    /// Reparte los cambios detectados: los publica en el flujo de eventos y dispara los
+    /// listeners de ficheros cuyo patrón encaja, como mucho una vez por pasada.
+    fn poll_file_watcher(&mut self) -> bool {
+        let Some(watcher) = &self.file_watcher.watcher else {
+            return false;
+        };
+        let mut events = Vec::new();
+        while let Some(event) = watcher.try_recv() {
+            events.push(event);
+        }
+        if events.is_empty() {
+            return false;
+        }
+
+        let mut fired: Vec<u32> = Vec::new();
+        for event in events {
+            let path = event.path.display().to_string();
+            self.event_stream.publish(StreamEvent::FileChange {
+                path: path.clone(),
+                change: event.kind.code().to_string(),
+                timestamp: event.timestamp.clone(),
+            });
+            self.push_debug_event(
+                DebugLogLevel::Info,
+                "automation::files",
+                format!("{}: {}", event.kind.label(), path),
+            );
+            for listener in &self.automation.event_automation.listeners {
+                if listener.enabled
+                    && listener.event == ListenerEventKind::FileChange
+                    && file_watcher::glob_match_tail(&listener.condition, &path)
+                    && !fired.contains(&listener.id)
+                {
+                    fired.push(listener.id);
+                }
+            }
+            self.file_watcher.record(event);
+        }
+
+        for listener_id in fired {
+            self.fire_listener(listener_id);
+        }
+        true
+    }
+
+    /// Evalúa la condición de los listeners habilitados para `event` con los campos del
+    /// evento por delante de las variables del estado y dispara los que se cumplen.
+    pub fn dispatch_listener_event(&mut self, event: ListenerEventKind, payload: &EventPayload) {
+        let mut fired = Vec::new();
+        let mut invalid = Vec::new();
+        for listener in &self.automation.event_automation.listeners {
+            if !listener.enabled || listener.event != event {
+                continue;
+            }
+            match conditions::evaluate(&listener.condition, &[payload, &*self]) {
+                Ok(true) => fired.push(listener.id),
+                Ok(false) => {}
+                Err(err) => invalid.push(format!(
+                    "Condición de '{}' no válida: {}",
+                    listener.name, err
+                )),
+            }
+        }
+        for message in invalid {
+            self.push_debug_event(DebugLogLevel::Warning, "automation::listeners", message);
+        }
+        for listener_id in fired {
+            self.fire_listener(listener_id);
+        }
+    }
+
+    fn fire_listener(&mut self, listener_id: u32) {
+        let Some(listener) = self
+            .automation
+            .event_automation
+            .listeners
+            .iter_mut()
+            .find(|listener| listener.id == listener_id)
+        else {
+            return;
+        };
+        listener.last_triggered = Some(Local::now().format("%Y-%m-%d %H:%M").to_string());
+        let name = listener.name.clone();
+        let action = listener.action.clone();
+
+        let workflow_id = action
+            .trim()
+            .strip_prefix("workflow:")
+            .and_then(|id| id.trim().parse::<u32>().ok());
+        match workflow_id {
+            Some(workflow_id) if self.trigger_workflow(workflow_id).is_some() => {}
+            Some(workflow_id) => self.push_activity_log(
+                LogStatus::Warning,
+                "Automation",
+                format!(
+                    "El listener '{}' apunta a un workflow inexistente (#{}).",
+                    name, workflow_id
+                ),
+            ),
+            None => {
+                let key = permissions::listener_key(&name);
+                let effects = permissions::missing(
+                    &self.config.automation_permissions,
+                    &key,
+                    &permissions::action_effects(&action),
+                );
+                if effects.is_empty() {
+                    self.push_activity_log(
+                        LogStatus::Ok,
+                        "Automation",
+                        format!("Listener '{}' disparado: {}", name, action),
+                    );
+                } else {
+                    self.request_automation_permission(permissions::PermissionRequest {
+                        target: permissions::PermissionTarget::Listener {
+                            name: name.clone(),
+                            action,
+                        },
+                        key,
+                        name,
+                        effects,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Deja la automatización esperando al diálogo de permisos y devuelve el aviso.
+    fn request_automation_permission(&mut self, request: permissions::PermissionRequest) -> String {
+        let effects: Vec<&str> = request
+            .effects
+            .iter()
+            .map(|effect| permissions::effect_label(*effect))
+            .collect();
+        let message = format!(
+            "'{}' espera permiso para: {}.",
+            request.name,
+            effects.join(", ")
+        );
+        if self.permissions.request(request) {
+            self.notify(
+                NotificationSource::Scheduler,
+                LogStatus::Warning,
+                "Automation",
+                message.clone(),
+            );
+        }
+        message
+    }
+
+    /// Responde a la petición de permiso más antigua. Al concederla se recuerda con el
+    /// alcance elegido en el diálogo y la automatización continúa.
+    pub fn resolve_automation_permission(&mut self, allow: bool) {
+        let Some(request) = self.permissions.queue.pop_front() else {
+            return;
+        };
+        let scope = std::mem::take(&mut self.permissions.scope);
+        let effects: Vec<&str> = request
+            .effects
+            .iter()
+            .map(|effect| permissions::effect_label(*effect))
+            .collect();
+        let details = BTreeMap::from([
+            ("automatización".to_string(), request.key.clone()),
+            ("efectos".to_string(), effects.join(", ")),
+            (
+                "decisión".to_string(),
+                if allow { scope.label() } else { "Denegado" }.to_string(),
+            ),
+        ]);
+        if !allow {
+            self.record_audit(
+                AuditEventKind::AutomationPermission,
+                format!("Permiso denegado a '{}'", request.name),
+                details,
+            );
+            self.push_activity_log(
+                LogStatus::Warning,
+                "Automation",
+                format!("'{}' no se ejecutó: permiso denegado.", request.name),
+            );
+            return;
+        }
+
+        self.record_audit(
+            AuditEventKind::AutomationPermission,
+            format!("Permiso concedido a '{}'", request.name),
+            details,
+        );
+        let automation = match scope {
+            permissions::PermissionScope::Once => None,
+            permissions::PermissionScope::Automation => Some(Some(request.key.clone())),
+            permissions::PermissionScope::Always => Some(None),
+        };
+        if let Some(automation) = automation {
+            let granted_at = Local::now().format("%Y-%m-%d %H:%M").to_string();
+            for effect in &request.effects {
+                self.config
+                    .automation_permissions
+                    .push(AutomationPermissionGrant {
+                        effect: *effect,
+                        automation: automation.clone(),
+                        granted_at: granted_at.clone(),
+                    });
+            }
+            self.persist_config();
+        }
+
+        match request.target {
+            permissions::PermissionTarget::Workflow(workflow_id) => {
+                self.start_workflow(workflow_id);
+            }
+            permissions::PermissionTarget::Listener { name, action } => self.push_activity_log(
+                LogStatus::Ok,
+                "Automation",
+                format!("Listener '{}' disparado: {}", name, action),
+            ),
+        }
+    }
+
+    /// Retira un permiso recordado: la automatización volverá a preguntar.
+    pub fn revoke_automation_permission(&mut self, index: usize) {
+        if index >= self.config.automation_permissions.len() {
+            return;
+        }
+        let grant = self.config.automation_permissions.remove(index);
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Automation",
+            format!(
+                "Permiso retirado: {} · {}",
+                permissions::effect_label(grant.effect),
+                grant
+                    .automation
+                    .as_deref()
+                    .unwrap_or("cualquier automatización")
+            ),
+        );
+    }
+
+    /// Programa el reindexado de un proyecto local con el formulario del planificador.
+    pub fn add_knowledge_reindex_schedule(&mut self) -> Result<u32, String> {
+        let project = self.knowledge_index.draft_project.trim().to_string();
+        let cron_expression = match self.knowledge_index.draft_cron.trim() {
+            "" => knowledge_index::DEFAULT_REINDEX_CRON.to_string(),
+            expression => expression.to_string(),
+        };
+        if project.is_empty() {
+            return Err("Elige el proyecto que quieres reindexar.".to_string());
+        }
+        cron::CronSchedule::parse(&cron_expression)?;
+        let already_scheduled = self.automation.cron_board.tasks.iter().any(|task| {
+            task.kind
+                == ScheduledTaskKind::ReindexKnowledge {
+                    project: project.clone(),
+                }
+        });
+        if already_scheduled {
+            return Err(format!("'{}' ya tiene un reindexado programado.", project));
+        }
+
+        let id = self.automation.cron_board.next_task_id();
+        let task = ScheduledTask::knowledge_reindex(
+            id,
+            &KnowledgeReindexSchedule {
+                project: project.clone(),
+                cron_expression,
+                enabled: true,
+                last_run: None,
+            },
+        );
+        self.automation.cron_board.tasks.push(task);
+        self.automation.cron_board.select_task(Some(id));
+        self.knowledge_index.draft_project.clear();
+        self.knowledge_index.status = Some(format!("Reindexado de '{}' programado.", project));
+        self.persist_config();
+        Ok(id)
+    }
+
+    /// Lanza las tareas con acción integrada cuya expresión cron se cumple en el minuto
+    /// actual. Cada minuto se revisa una sola vez.
+    fn poll_scheduled_tasks(&mut self) -> bool {
+        let now = Local::now();
+        let tick = now.format("%Y-%m-%d %H:%M").to_string();
+        if self.automation.cron_board.last_tick.as_deref() == Some(tick.as_str()) {
+            return false;
+        }
+        self.automation.cron_board.last_tick = Some(tick);
+
+        let due: Vec<u32> = self
+            .automation
+            .cron_board
+            .tasks
+            .iter()
+            .filter(|task| task.enabled && task.kind != ScheduledTaskKind::Generic)
+            // Las tareas con previas se lanzan al terminar su cadena, no por su cron.
+            .filter(|task| {
+                task_chain::prerequisites(&self.config.cron_dependencies, &task.name).is_empty()
+            })
+            .filter(|task| {
+                cron::CronSchedule::parse(&task.cron_expression)
+                    .map(|cron| cron.matches(&now))
+                    .unwrap_or(false)
+            })
+            .map(|task| task.id)
+            .collect();
+        let updated = !due.is_empty();
+        for task_id in due {
+            self.run_scheduled_task(task_id);
+        }
+        updated
+    }
+
+    /// Ejecuta una tarea del planificador, ya sea por su programación o a mano.
+    pub fn run_scheduled_task(&mut self, task_id: u32) {
+        let Some(task) = self
+            .automation
+            .cron_board
+            .tasks
+            .iter_mut()
+            .find(|task| task.id == task_id)
+        else {
+            return;
+        };
+        task.status = ScheduledTaskStatus::Running;
+        task.last_run = Some(Local::now().format("%Y-%m-%d %H:%M").to_string());
+        let name = task.name.clone();
+        let kind = task.kind.clone();
+        self.automation.cron_board.skip_reasons.remove(&name);
+
+        match kind {
+            ScheduledTaskKind::Generic => {
+                self.push_activity_log(
+                    LogStatus::Running,
+                    "Cron",
+                    format!("Tarea '{}' ejecutada manualmente", name),
+                );
+                self.push_debug_event(
+                    DebugLogLevel::Info,
+                    "cron::manual",
+                    format!("Lanzando '{}'", name),
+                );
+            }
+            ScheduledTaskKind::ReindexKnowledge { project } => {
+                // Los embeddings se calculan en segundo plano y cierran la tarea al llegar.
+                if !self.reindex_project_knowledge(&project, task_id) {
+                    self.finish_reindex_task(task_id, ScheduledTaskStatus::Failed);
+                }
+            }
+            ScheduledTaskKind::DailyDigest => {
+                if let Err(err) = self.start_daily_digest() {
+                    self.set_digest_task_status(ScheduledTaskStatus::Failed);
+                    self.notify(
+                        NotificationSource::Scheduler,
+                        LogStatus::Error,
+                        "Resumen diario",
+                        err,
+                    );
+                }
+            }
+            ScheduledTaskKind::GenerateReport { template } => {
+                if let Err(err) = self.generate_report(&template) {
+                    self.set_report_task_status(&template, ScheduledTaskStatus::Failed);
+                    self.notify(
+                        NotificationSource::Scheduler,
+                        LogStatus::Error,
+                        "Informes",
+                        err,
+                    );
+                }
+            }
+        }
+
+        let status = self
+            .automation
+            .cron_board
+            .tasks
+            .iter()
+            .find(|task| task.id == task_id)
+            .map(|task| format!("{:?}", task.status).to_lowercase())
+            .unwrap_or_default();
+        let payload = EventPayload::default()
+            .with("task.name", name)
+            .with("task.status", status);
+        self.dispatch_listener_event(ListenerEventKind::Scheduler, &payload);
+    }
+
+    /// Propaga el final de `task` por su cadena. Si terminó bien, lanza las dependientes
+    /// cuyas previas han terminado todas bien en su última ejecución; si falló, omite en
+    /// cascada todas las que dependen de ella.
+    fn advance_task_chain(&mut self, task: &str, status: ScheduledTaskStatus) {
+        let dependencies = &self.config.cron_dependencies;
+        let board = &self.automation.cron_board;
+        match status {
+            ScheduledTaskStatus::Success => {
+                let ready: Vec<(u32, String)> = task_chain::dependents(dependencies, task)
+                    .into_iter()
+                    .filter_map(|name| {
+                        let dependent = board.task_by_name(&name)?;
+                        let prerequisites_done = task_chain::prerequisites(dependencies, &name)
+                            .iter()
+                            .all(|previous| {
+                                board.task_by_name(previous).is_some_and(|previous| {
+                                    previous.status == ScheduledTaskStatus::Success
+                                })
+                            });
+                        (dependent.enabled && prerequisites_done).then_some((dependent.id, name))
+                    })
+                    .collect();
+                for (task_id, name) in ready {
+                    self.push_activity_log(
+                        LogStatus::Running,
+                        "Cron",
+                        format!("'{}' lanzada tras completar '{}'.", name, task),
+                    );
+                    self.run_scheduled_task(task_id);
+                }
+            }
+            ScheduledTaskStatus::Failed | ScheduledTaskStatus::Skipped => {
+                let skipped = task_chain::downstream(dependencies, task);
+                if skipped.is_empty() {
+                    return;
+                }
+                let reason = format!("'{}' no terminó bien.", task);
+                for name in &skipped {
+                    if let Some(dependent) = self
+                        .automation
+                        .cron_board
+                        .tasks
+                        .iter_mut()
+                        .find(|dependent| dependent.name == *name)
+                    {
+                        dependent.status = ScheduledTaskStatus::Skipped;
+                    }
+                    self.automation
+                        .cron_board
+                        .skip_reasons
+                        .insert(name.clone(), reason.clone());
+                }
+                self.notify(
+                    NotificationSource::Scheduler,
+                    LogStatus::Warning,
+                    "Cron",
+                    format!(
+                        "Omitidas por el fallo de '{}': {}.",
+                        task,
+                        skipped.join(", ")
+                    ),
+                );
+            }
+            _ => {}
+        }
+    }
+
+    /// Hace que `task` espere a que `prerequisite` termine bien antes de lanzarse.
+    pub fn add_task_dependency(&mut self, task: &str, prerequisite: &str) -> Result<(), String> {
+        task_chain::check_dependency(&self.config.cron_dependencies, task, prerequisite)?;
+        self.config
+            .cron_dependencies
+            .entry(task.to_string())
+            .or_default()
+            .push(prerequisite.to_string());
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Cron",
+            format!("'{}' se lanzará tras '{}'.", task, prerequisite),
+        );
+        Ok(())
+    }
+
+    pub fn remove_task_dependency(&mut self, task: &str, prerequisite: &str) {
+        let Some(previous) = self.config.cron_dependencies.get_mut(task) else {
+            return;
+        };
+        previous.retain(|name| name != prerequisite);
+        if previous.is_empty() {
+            self.config.cron_dependencies.remove(task);
+        }
+        self.persist_config();
+    }
+
+    /// Rehace la tarea del resumen diario en el planificador tras cambiar su configuración.
+    pub fn sync_daily_digest_task(&mut self) -> Result<(), String> {
+        let config = self.config.daily_digest.clone();
+        let cron_check = digest::cron_expression(&config.time).map(|_| ());
+        self.automation
+            .cron_board
+            .tasks
+            .retain(|task| task.kind != ScheduledTaskKind::DailyDigest);
+        if config.enabled {
+            let id = self.automation.cron_board.next_task_id();
+            if let Some(task) = ScheduledTask::daily_digest(id, &config) {
+                self.automation.cron_board.tasks.push(task);
+            }
+        }
+        self.persist_config();
+        cron_check
+    }
+
+    fn set_digest_task_status(&mut self, status: ScheduledTaskStatus) {
+        if let Some(task) = self
+            .automation
+            .cron_board
+            .tasks
+            .iter_mut()
+            .find(|task| task.kind == ScheduledTaskKind::DailyDigest)
+        {
+            task.status = status;
+            task.next_run = cron::CronSchedule::parse(&task.cron_expression)
+                .ok()
+                .and_then(|cron| cron.next_after(Local::now()))
+                .map(|next| next.format("%Y-%m-%d %H:%M").to_string());
+            let name = task.name.clone();
+            self.advance_task_chain(&name, status);
+        }
+    }
+
+    /// Reúne el material del día y lanza la redacción del resumen. Las notificaciones de
+    /// GitHub se descargan en segundo plano antes de componer el prompt.
+    pub fn start_daily_digest(&mut self) -> Result<(), String> {
+        if self.digest.is_running() {
+            return Err("Ya se está generando un resumen.".to_string());
+        }
+        let config = self.config.daily_digest.clone();
+        let today = Local::now().date_naive();
+        let mut sources = digest::DigestSources::new(today);
+
+        if config.include_activity {
+            sources.set_activity(
+                self.automation
+                    .activity_logs
+                    .iter()
+                    .filter(|entry| entry.source != "Resumen diario")
+                    .map(|entry| {
+                        format!("{} [{}] {}", entry.timestamp, entry.source, entry.message)
+                    })
+                    .collect(),
+            );
+        }
+        if config.include_workflows {
+            let prefix = today.format("%Y-%m-%d").to_string();
+            sources.workflows = self
+                .automation
+                .workflows
+                .workflows
+                .iter()
+                .filter_map(|workflow| {
+                    let last_run = workflow.last_run.as_deref()?;
+                    last_run.starts_with(&prefix).then(|| {
+                        format!(
+                            "{} · {} · {}",
+                            workflow.name,
+                            workflow.status.label(),
+                            last_run
+                        )
+                    })
+                })
+                .collect();
+        }
+        if config.include_calendar && !config.calendar_file.trim().is_empty() {
+            match digest::read_calendar(config.calendar_file.trim(), today) {
+                Ok(events) => sources.calendar = events,
+                Err(err) => sources.errors.push(format!("Calendario: {:#}", err)),
+            }
+        }
+
+        let token = self
+            .config
+            .github_token
+            .clone()
+            .filter(|token| !token.trim().is_empty());
+        match token {
+            Some(token) if config.include_github && !self.is_offline() => {
+                let (tx, rx) = std::sync::mpsc::channel();
+                self.jobs.submit(
+                    "github",
+                    JobPriority::Background,
+                    "Notificaciones de GitHub",
+                    move || {
+                        let outcome =
+                            github::fetch_notifications(&token).map_err(|err| format!("{:#}", err));
+                        let _ = tx.send(outcome);
+                    },
+                );
+                self.digest.stage = Some(digest::DigestStage::Gathering { sources, rx });
+                self.digest.status = Some("Descargando notificaciones de GitHub…".to_string());
+            }
+            token => {
+                if config.include_github {
+                    sources.errors.push(if token.is_none() {
+                        "GitHub: no hay un token configurado.".to_string()
+                    } else {
+                        "GitHub: sin conexión.".to_string()
+                    });
+                }
+                self.compose_daily_digest(sources);
+            }
+        }
+        self.set_digest_task_status(ScheduledTaskStatus::Running);
+        self.push_activity_log(
+            LogStatus::Running,
+            "Resumen diario",
+            "Preparando el resumen del día.",
+        );
+        Ok(())
+    }
+
+    /// Envía el prompt del resumen al proveedor configurado o, si no hay ninguno
+    /// disponible, lo redacta Jarvis en el hilo de la interfaz.
+    fn compose_daily_digest(&mut self, sources: digest::DigestSources) {
+        let prompt = digest::build_prompt(&sources);
+        let provider = self
+            .config
+            .daily_digest
+            .provider
+            .as_deref()
+            .and_then(RemoteProviderKind::from_short_code);
+        if let Some(provider) = provider {
+            let profile = self.provider_call_profile(provider);
+            match profile.api_key {
+                Some(key) if !self.is_offline() => {
+                    let prompt = self.redact_outgoing(&prompt, "Resumen diario");
+                    let model = profile.model.clone();
+                    let caller = profile.caller;
+                    let (tx, rx) = std::sync::mpsc::channel();
+                    self.jobs.submit(
+                        provider.short_code(),
+                        JobPriority::Background,
+                        "Resumen diario",
+                        move || {
+                            let outcome =
+                                caller(&key, &model, &prompt, &GenerationOptions::default())
+                                    .map_err(|err| {
+                                        ProviderError::from_anyhow(err).message().to_string()
+                                    });
+                            let _ = tx.send(outcome);
+                        },
+                    );
+                    self.digest.status = Some(format!(
+                        "{} está redactando el resumen…",
+                        profile.provider_name
+                    ));
+                    self.digest.stage = Some(digest::DigestStage::Composing {
+                        provider_name: profile.provider_name.to_string(),
+                        model: profile.model,
+                        rx,
+                    });
+                    return;
+                }
+                Some(_) => self.push_activity_log(
+                    LogStatus::Warning,
+                    "Resumen diario",
+                    format!(
+                        "Sin conexión: Jarvis redacta el resumen en lugar de {}.",
+                        profile.provider_name
+                    ),
+                ),
+                None => self.push_activity_log(
+                    LogStatus::Warning,
+                    "Resumen diario",
+                    format!(
+                        "{} no tiene API key: Jarvis redacta el resumen.",
+                        profile.provider_name
+                    ),
+                ),
+            }
+        }
+        self.digest.stage = None;
+        let outcome = self.generate_local_jarvis_reply(&prompt);
+        self.finish_daily_digest(outcome, "Jarvis");
+    }
+
+    fn poll_daily_digest(&mut self) -> bool {
+        match self.digest.stage.take() {
+            Some(digest::DigestStage::Gathering { mut sources, rx }) => match rx.try_recv() {
+                Ok(outcome) => {
+                    match outcome {
+                        Ok(notifications) => sources.github = notifications,
+                        Err(err) => sources.errors.push(format!("GitHub: {}", err)),
+                    }
+                    self.compose_daily_digest(sources);
+                    true
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    self.digest.stage = Some(digest::DigestStage::Gathering { sources, rx });
+                    false
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    sources
+                        .errors
+                        .push("GitHub: la descarga terminó sin respuesta.".to_string());
+                    self.compose_daily_digest(sources);
+                    true
+                }
+            },
+            Some(digest::DigestStage::Composing {
+                provider_name,
+                model,
+                rx,
+            }) => match rx.try_recv() {
+                Ok(outcome) => {
+                    let mut details = BTreeMap::from([
+                        ("proveedor".to_string(), provider_name.clone()),
+                        ("modelo".to_string(), model.clone()),
+                    ]);
+                    if let Err(err) = &outcome {
+                        details.insert("error".to_string(), err.clone());
+                    }
+                    self.record_audit_in(
+                        digest::DIGEST_THREAD_KEY,
+                        AuditEventKind::ProviderCall,
+                        format!("{} · {}", provider_name, model),
+                        details,
+                    );
+                    self.finish_daily_digest(outcome, &provider_name);
+                    true
+                }
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    self.digest.stage = Some(digest::DigestStage::Composing {
+                        provider_name,
+                        model,
+                        rx,
+                    });
+                    false
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    self.finish_daily_digest(
+                        Err("la llamada terminó sin respuesta".to_string()),
+                        &provider_name,
+                    );
+                    true
+                }
+            },
+            None => false,
+        }
+    }
+
+    /// Publica el resumen en su hilo y avisa con un toast y, si está activada, con una
+    /// notificación de escritorio.
+    fn finish_daily_digest(&mut self, outcome: Result<String, String>, author: &str) {
+        let text = match outcome {
+            Ok(text) => text,
+            Err(err) => {
+                let message = format!("No se pudo generar el resumen con {}: {}", author, err);
+                self.digest.status = Some(message.clone());
+                self.set_digest_task_status(ScheduledTaskStatus::Failed);
+                self.notify(
+                    NotificationSource::Scheduler,
+                    LogStatus::Error,
+                    "Resumen diario",
+                    message,
+                );
+                return;
+            }
+        };
+
+        let title = format!(
+            "{} · {}",
+            digest::DIGEST_THREAD_TITLE,
+            Local::now().format("%d/%m/%Y")
+        );
+        let message = ChatMessage::new(author, format!("**{}**\n\n{}", title, text));
+        let thread_id = self
+            .threads
+            .ensure_thread(digest::DIGEST_THREAD_KEY, digest::DIGEST_THREAD_TITLE);
+        if thread_id == self.threads.active {
+            self.chat.messages.push(message);
+        } else if let Some(thread) = self.threads.thread_mut(thread_id) {
+            thread.messages.push(message);
+        }
+
+        self.config.daily_digest.last_run = Some(Local::now().format("%Y-%m-%d %H:%M").to_string());
+        self.set_digest_task_status(ScheduledTaskStatus::Success);
+        self.persist_config();
+        self.digest.last_digest = Some(text.clone());
+        self.digest.status = Some(format!("Resumen publicado por {}.", author));
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Resumen diario",
+            format!("Resumen publicado en '{}'.", digest::DIGEST_THREAD_TITLE),
+        );
+        for connector in self
+            .config
+            .webhook_connectors
+            .iter()
+            .filter(|connector| connector.enabled && connector.post_digest)
+        {
+            self.webhooks.start(
+                &mut self.jobs,
+                connector.clone(),
+                "Resumen diario".to_string(),
+                format!("{}\n\n{}", title, text),
+            );
+        }
+        // El resumen tiene su propio aviso, pero también respeta las horas de silencio.
+        if self.in_quiet_hours() {
+            return;
+        }
+        self.toasts
+            .push(LogStatus::Ok, "El resumen diario está listo.".to_string());
+        if self.config.daily_digest.desktop_notification {
+            if let Err(err) = digest::notify_desktop(&title, &text) {
+                self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    "digest::notify",
+                    format!("{:#}", err),
+                );
+            }
+        }
+    }
+
+    /// Resume con el proveedor enrutado de la conversación la actividad registrada desde
+    /// la última puesta al día. Sin él, o sin conexión, la redacta Jarvis al momento.
+    pub fn start_activity_briefing(&mut self) -> Result<(), String> {
+        if self.briefing.is_running() {
+            return Err("Ya se está preparando una puesta al día.".to_string());
+        }
+        let entries = briefing::recent_entries(
+            &self.automation.activity_logs,
+            self.briefing.since.as_deref(),
+        );
+        let Some(last) = entries.last().map(|entry| entry.timestamp.clone()) else {
+            return Err("No hay actividad nueva desde la última puesta al día.".to_string());
+        };
+        let prompt = briefing::build_prompt(&entries);
+        let count = entries.len();
+        self.briefing.since = Some(last);
+
+        let defaults = self.effective_conversation_defaults();
+        if let Some(provider) = defaults.provider_kind() {
+            let profile = self.provider_call_profile(provider);
+            match profile.api_key {
+                Some(key) if !self.is_offline() => {
+                    let prompt = self.redact_outgoing(&prompt, profile.provider_name);
+                    let model = profile.model.clone();
+                    let caller = profile.caller;
+                    let options = defaults.generation_options();
+                    let (tx, rx) = std::sync::mpsc::channel();
+                    self.jobs.submit(
+                        provider.short_code(),
+                        JobPriority::Background,
+                        "Puesta al día",
+                        move || {
+                            let outcome = caller(&key, &model, &prompt, &options).map_err(|err| {
+                                ProviderError::from_anyhow(err).message().to_string()
+                            });
+                            let _ = tx.send(outcome);
+                        },
+                    );
+                    self.record_audit(
+                        AuditEventKind::ProviderCall,
+                        format!("{} · {}", profile.provider_name, profile.model),
+                        BTreeMap::from([
+                            ("proveedor".to_string(), profile.provider_name.to_string()),
+                            ("modelo".to_string(), profile.model.clone()),
+                            ("entradas".to_string(), count.to_string()),
+                        ]),
+                    );
+                    self.briefing.status = Some(format!(
+                        "{} está resumiendo {} entradas…",
+                        profile.provider_name, count
+                    ));
+                    self.briefing.run = Some((profile.provider_name.to_string(), rx));
+                    return Ok(());
+                }
+                Some(_) => self.push_activity_log(
+                    LogStatus::Warning,
+                    briefing::BRIEFING_SOURCE,
+                    format!(
+                        "Sin conexión: Jarvis resume la actividad en lugar de {}.",
+                        profile.provider_name
+                    ),
+                ),
+                None => self.push_activity_log(
+                    LogStatus::Warning,
+                    briefing::BRIEFING_SOURCE,
+                    format!(
+                        "{} no tiene API key: Jarvis resume la actividad.",
+                        profile.provider_name
+                    ),
+                ),
+            }
+        }
+        let outcome = self.generate_local_jarvis_reply(&prompt);
+        self.finish_activity_briefing(outcome, "Jarvis");
+        Ok(())
+    }
+
+    fn poll_activity_briefing(&mut self) -> bool {
+        let Some((author, rx)) = self.briefing.run.take() else {
+            return false;
+        };
+        let outcome = match rx.try_recv() {
+            Ok(outcome) => outcome,
+            Err(std::sync::mpsc::TryRecvError::Empty) => {
+                self.briefing.run = Some((author, rx));
+                return false;
+            }
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("la llamada terminó sin respuesta".to_string())
+            }
+        };
+        self.finish_activity_briefing(outcome, &author);
+        true
+    }
+
+    /// Publica la puesta al día en el chat y, si se pidió, la lee en voz alta.
+    fn finish_activity_briefing(&mut self, outcome: Result<String, String>, author: &str) {
+        let text = match outcome {
+            Ok(text) => text.trim().to_string(),
+            Err(err) => {
+                let message = format!("No se pudo resumir la actividad con {}: {}", author, err);
+                self.briefing.status = Some(message.clone());
+                // La próxima vez se vuelven a incluir las entradas que no se resumieron.
+                self.briefing.since = None;
+                self.push_activity_log(LogStatus::Error, briefing::BRIEFING_SOURCE, message);
+                return;
+            }
+        };
+        self.chat.messages.push(ChatMessage::new(
+            author,
+            format!("**{}**\n\n{}", briefing::BRIEFING_SOURCE, text),
+        ));
+        self.push_activity_log(
+            LogStatus::Ok,
+            briefing::BRIEFING_SOURCE,
+            format!("Puesta al día de {} publicada en el chat.", author),
+        );
+        self.toasts.push(
+            LogStatus::Ok,
+            "Puesta al día publicada en el chat.".to_string(),
+        );
+        self.briefing.status = Some(format!("Resumida por {}.", author));
+        if self.config.speak_briefings {
+            if let Err(err) = briefing::speak(&text) {
+                self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    "briefing::speak",
+                    format!("{:#}", err),
+                );
+                self.briefing.status = Some(format!("{:#}", err));
+            }
+        }
+    }
+
+    /// Abre el hilo de los resúmenes diarios.
+    pub fn open_digest_thread(&mut self) -> Result<(), String> {
+        let thread_id = self
+            .threads
+            .ensure_thread(digest::DIGEST_THREAD_KEY, digest::DIGEST_THREAD_TITLE);
+        self.switch_thread(thread_id)?;
+        self.activate_navigation_target(NavigationTarget::main(MainView::ChatMultimodal));
+        Ok(())
+    }
+
+    /// Guarda la plantilla en edición, nueva o existente, y rehace su tarea programada.
+    pub fn save_report_template_draft(&mut self) -> Result<String, String> {
+        let mut draft = self.reports.draft.clone();
+        draft.name = draft.name.trim().to_string();
+        draft.cron_expression = draft.cron_expression.trim().to_string();
+        if draft.name.is_empty() {
+            return Err("La plantilla necesita un nombre.".to_string());
+        }
+        if draft.body.trim().is_empty() {
+            return Err("La plantilla está vacía.".to_string());
+        }
+        let duplicated =
+            self.config
+                .report_templates
+                .iter()
+                .enumerate()
+                .any(|(index, template)| {
+                    template.name == draft.name && Some(index) != self.reports.editing
+                });
+        if duplicated {
+            return Err(format!("Ya existe una plantilla llamada '{}'.", draft.name));
+        }
+        if !draft.cron_expression.is_empty() {
+            cron::CronSchedule::parse(&draft.cron_expression)
+                .map_err(|err| format!("Programación no válida: {}", err))?;
+        }
+
+        match self
+            .reports
+            .editing
+            .and_then(|index| self.config.report_templates.get_mut(index))
+        {
+            Some(template) => *template = draft.clone(),
+            None => {
+                self.config.report_templates.push(draft.clone());
+                self.reports.editing = Some(self.config.report_templates.len() - 1);
+            }
+        }
+        self.reports.draft = draft.clone();
+        self.sync_report_tasks();
+        self.persist_config();
+        self.reports.refresh(&self.config.report_templates);
+        Ok(format!("Plantilla '{}' guardada.", draft.name))
+    }
+
+    pub fn delete_report_template(&mut self, index: usize) {
+        if index >= self.config.report_templates.len() {
+            return;
+        }
+        let removed = self.config.report_templates.remove(index);
+        self.reports.editing = None;
+        self.reports.draft = ReportTemplate::default();
+        self.sync_report_tasks();
+        self.persist_config();
+        self.reports.status = Some(format!("Plantilla '{}' eliminada.", removed.name));
+    }
+
+    /// Sustituye en el planificador las tareas de informes por las de las plantillas
+    /// con programación.
+    fn sync_report_tasks(&mut self) {
+        self.automation
+            .cron_board
+            .tasks
+            .retain(|task| !matches!(task.kind, ScheduledTaskKind::GenerateReport { .. }));
+        for template in &self.config.report_templates {
+            if !template.cron_expression.is_empty() {
+                let id = self.automation.cron_board.next_task_id();
+                self.automation
+                    .cron_board
+                    .tasks
+                    .push(ScheduledTask::report(id, template));
+            }
+        }
+    }
+
+    fn set_report_task_status(&mut self, template: &str, status: ScheduledTaskStatus) {
+        if let Some(task) = self.automation.cron_board.tasks.iter_mut().find(|task| {
+            matches!(&task.kind, ScheduledTaskKind::GenerateReport { template: name } if name == template)
+        }) {
+            task.status = status;
+            task.next_run = cron::CronSchedule::parse(&task.cron_expression)
+                .ok()
+                .and_then(|cron| cron.next_after(Local::now()))
+                .map(|next| next.format("%Y-%m-%d %H:%M").to_string());
+            let name = task.name.clone();
+            self.advance_task_chain(&name, status);
+        }
+    }
+
+    /// Rellena las secciones de la plantilla `name`. Con un proveedor remoto las secciones
+    /// se piden una a una en segundo plano; sin él, las redacta Jarvis al momento.
+    pub fn generate_report(&mut self, name: &str) -> Result<(), String> {
+        if self.reports.is_running() {
+            return Err("Ya se está generando un informe.".to_string());
+        }
+        let template = self
+            .config
+            .report_templates
+            .iter()
+            .find(|template| template.name == name)
+            .cloned()
+            .ok_or_else(|| format!("No existe la plantilla '{}'.", name))?;
+        let started_at = Local::now();
+        let parts = reports::parse_template(&template.body, &template.name, started_at);
+        let prompts = reports::section_prompts(&template.name, &parts);
+
+        self.set_report_task_status(&template.name, ScheduledTaskStatus::Running);
+        self.push_activity_log(
+            LogStatus::Running,
+            "Informes",
+            format!(
+                "Generando '{}' ({} secciones).",
+                template.name,
+                prompts.len()
+            ),
+        );
+
+        let provider = template
+            .provider
+            .as_deref()
+            .and_then(RemoteProviderKind::from_short_code);
+        if let Some(provider) = provider.filter(|_| !prompts.is_empty()) {
+            let profile = self.provider_call_profile(provider);
+            match profile.api_key {
+                Some(key) if !self.is_offline() => {
+                    let prompts: Vec<String> = prompts
+                        .iter()
+                        .map(|prompt| self.redact_outgoing(prompt, "Informes"))
+                        .collect();
+                    let model = profile.model.clone();
+                    let caller = profile.caller;
+                    let (tx, rx) = std::sync::mpsc::channel();
+                    self.jobs.submit(
+                        provider.short_code(),
+                        JobPriority::Background,
+                        format!("Informe '{}'", template.name),
+                        move || {
+                            let outcome = prompts
+                                .iter()
+                                .map(|prompt| {
+                                    caller(&key, &model, prompt, &GenerationOptions::default())
+                                        .map_err(|err| {
+                                            ProviderError::from_anyhow(err).message().to_string()
+                                        })
+                                })
+                                .collect::<Result<Vec<_>, _>>();
+                            let _ = tx.send(outcome);
+                        },
+                    );
+                    self.record_audit(
+                        AuditEventKind::ProviderCall,
+                        format!("{} · {}", profile.provider_name, profile.model),
+                        BTreeMap::from([
+                            ("proveedor".to_string(), profile.provider_name.to_string()),
+                            ("modelo".to_string(), profile.model.clone()),
+                            ("informe".to_string(), template.name.clone()),
+                        ]),
+                    );
+                    self.reports.status = Some(format!(
+                        "{} está redactando '{}'…",
+                        profile.provider_name, template.name
+                    ));
+                    self.reports.run = Some(reports::ReportRun {
+                        template,
+                        parts,
+                        started_at,
+                        author: profile.provider_name.to_string(),
+                        rx,
+                    });
+                    return Ok(());
+                }
+                Some(_) => self.push_activity_log(
+                    LogStatus::Warning,
+                    "Informes",
+                    format!(
+                        "Sin conexión: Jarvis redacta '{}' en lugar de {}.",
+                        template.name, profile.provider_name
+                    ),
+                ),
+                None => self.push_activity_log(
+                    LogStatus::Warning,
+                    "Informes",
+                    format!(
+                        "{} no tiene API key: Jarvis redacta '{}'.",
+                        profile.provider_name, template.name
+                    ),
+                ),
+            }
+        }
+
+        let outcome = prompts
+            .iter()
+            .map(|prompt| self.generate_local_jarvis_reply(prompt))
+            .collect::<Result<Vec<_>, _>>();
+        self.finish_report(template, &parts, started_at, outcome, "Jarvis");
+        Ok(())
+    }
+
+    fn poll_report_run(&mut self) -> bool {
+        let Some(run) = self.reports.run.take() else {
+            return false;
+        };
+        let outcome = match run.rx.try_recv() {
+            Ok(outcome) => outcome,
+            Err(std::sync::mpsc::TryRecvError::Empty) => {
+                self.reports.run = Some(run);
+                return false;
+            }
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("la generación terminó sin respuesta".to_string())
+            }
+        };
+        self.finish_report(
+            run.template,
+            &run.parts,
+            run.started_at,
+            outcome,
+            &run.author,
+        );
+        true
+    }
+
+    /// Escribe el informe renderizado en el directorio de la plantilla y, si se pidió, lo
+    /// confirma en el repositorio Git que lo contiene.
+    fn finish_report(
+        &mut self,
+        template: ReportTemplate,
+        parts: &[reports::TemplatePart],
+        started_at: DateTime<Local>,
+        outcome: Result<Vec<String>, String>,
+        author: &str,
+    ) {
+        let written = outcome.and_then(|sections| {
+            let content = reports::render(parts, &sections);
+            let directory =
+                reports::output_directory(&template).map_err(|err| format!("{:#}", err))?;
+            std::fs::create_dir_all(&directory)
+                .map_err(|err| format!("No se pudo crear {}: {}", directory.display(), err))?;
+            let path = directory.join(reports::report_file_name(&template.name, started_at));
+            std::fs::write(&path, content)
+                .map_err(|err| format!("No se pudo escribir {}: {}", path.display(), err))?;
+            Ok(path)
+        });
+        let path = match written {
+            Ok(path) => path,
+            Err(err) => {
+                let message = format!("No se pudo generar '{}': {}", template.name, err);
+                self.reports.status = Some(message.clone());
+                self.set_report_task_status(&template.name, ScheduledTaskStatus::Failed);
+                self.notify(
+                    NotificationSource::Scheduler,
+                    LogStatus::Error,
+                    "Informes",
+                    message,
+                );
+                return;
+            }
+        };
+        self.record_file_written(&path, "informes");
+
+        let mut status = format!("'{}' escrito en {}.", template.name, path.display());
+        if template.commit_to_git {
+            let message = format!(
+                "Informe '{}' del {}",
+                template.name,
+                started_at.format("%Y-%m-%d %H:%M")
+            );
+            match reports::commit_report(&path, &message) {
+                Ok(()) => status.push_str(" Confirmado en Git."),
+                Err(err) => {
+                    status.push_str(&format!(" No se pudo confirmar en Git: {:#}", err));
+                    self.push_activity_log(LogStatus::Warning, "Informes", format!("{:#}", err));
+                }
+            }
+        }
+
+        if let Some(stored) = self
+            .config
+            .report_templates
+            .iter_mut()
+            .find(|stored| stored.name == template.name)
+        {
+            stored.last_run = Some(started_at.format("%Y-%m-%d %H:%M").to_string());
+        }
+        self.persist_config();
+        self.set_report_task_status(&template.name, ScheduledTaskStatus::Success);
+        self.reports.refresh(&self.config.report_templates);
+        self.reports.select(&path);
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Informes",
+            format!("'{}' redactado por {}.", template.name, author),
+        );
+        self.toasts.push(LogStatus::Ok, status.clone());
+        self.reports.status = Some(status);
+    }
+
+    pub fn save_playbook_draft(&mut self) -> Result<String, String> {
+        let mut draft = self.playbooks.draft.clone();
+        draft.name = draft.name.trim().to_string();
+        draft.description = draft.description.trim().to_string();
+        for step in &mut draft.steps {
+            step.title = step.title.trim().to_string();
+        }
+        playbooks::validate(&draft)?;
+        let duplicated = self
+            .config
+            .playbooks
+            .iter()
+            .enumerate()
+            .any(|(index, playbook)| {
+                playbook.name == draft.name && Some(index) != self.playbooks.editing
+            });
+        if duplicated {
+            return Err(format!("Ya existe un playbook llamado '{}'.", draft.name));
+        }
+
+        match self
+            .playbooks
+            .editing
+            .and_then(|index| self.config.playbooks.get_mut(index))
+        {
+            Some(playbook) => *playbook = draft.clone(),
+            None => {
+                self.config.playbooks.push(draft.clone());
+                self.playbooks.editing = Some(self.config.playbooks.len() - 1);
+            }
+        }
+        self.playbooks.draft = draft.clone();
+        self.persist_config();
+        Ok(format!("Playbook '{}' guardado.", draft.name))
+    }
+
+    pub fn delete_playbook(&mut self, index: usize) {
+        if index >= self.config.playbooks.len() {
+            return;
+        }
+        let removed = self.config.playbooks.remove(index);
+        self.playbooks.editing = None;
+        self.playbooks.draft = Playbook::default();
+        self.persist_config();
+        self.playbooks.status = Some(format!("Playbook '{}' eliminado.", removed.name));
+    }
+
+    /// Recorre los pasos del playbook `index` con los campos de `self.playbooks.values`.
+    /// Los pasos van en orden al proveedor enrutado de la conversación abierta, con su
+    /// persona y parámetros; sin proveedor, o sin conexión, los responde Jarvis al momento.
+    pub fn launch_playbook(&mut self, index: usize) -> Result<(), String> {
+        if self.playbooks.is_running() {
+            return Err("Ya se está ejecutando un playbook.".to_string());
+        }
+        let playbook = self
+            .config
+            .playbooks
+            .get(index)
+            .cloned()
+            .ok_or_else(|| "El playbook ya no existe.".to_string())?;
+        playbooks::validate(&playbook)?;
+        let mut values = BTreeMap::new();
+        for field in playbooks::fields(&playbook) {
+            let value = self
+                .playbooks
+                .values
+                .get(&field)
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default();
+            if value.is_empty() {
+                return Err(format!("Falta el campo '{}'.", field));
+            }
+            values.insert(field, value);
+        }
+        let started_at = Local::now();
+        let prompts: Vec<String> = playbook
+            .steps
+            .iter()
+            .map(|step| playbooks::fill_fields(&step.prompt, &values))
+            .collect();
+        self.playbooks.document = None;
+        self.playbooks.preview.clear();
+        self.push_activity_log(
+            LogStatus::Running,
+            "Playbooks",
+            format!(
+                "Lanzando '{}' ({} pasos).",
+                playbook.name,
+                playbook.steps.len()
+            ),
+        );
+
+        let defaults = self.effective_conversation_defaults();
+        if let Some(provider) = defaults.provider_kind() {
+            let profile = self.provider_call_profile(provider);
+            match profile.api_key {
+                Some(key) if !self.is_offline() => {
+                    let prompts: Vec<String> = prompts
+                        .iter()
+                        .map(|prompt| self.redact_outgoing(prompt, profile.provider_name))
+                        .collect();
+                    let titles: Vec<String> = playbook
+                        .steps
+                        .iter()
+                        .map(|step| step.title.clone())
+                        .collect();
+                    let model = profile.model.clone();
+                    let caller = profile.caller;
+                    let options = defaults.generation_options();
+                    let (tx, rx) = std::sync::mpsc::channel();
+                    self.jobs.submit(
+                        provider.short_code(),
+                        JobPriority::Background,
+                        format!("Playbook '{}'", playbook.name),
+                        move || {
+                            let mut outputs: Vec<String> = Vec::new();
+                            for (title, prompt) in titles.into_iter().zip(prompts) {
+                                let prompt = playbooks::fill_outputs(&prompt, &outputs);
+                                let output = match caller(&key, &model, &prompt, &options) {
+                                    Ok(output) => output,
+                                    Err(err) => {
+                                        let message =
+                                            ProviderError::from_anyhow(err).message().to_string();
+                                        let _ = tx.send(playbooks::PlaybookMessage::Finished(Err(
+                                            message,
+                                        )));
+                                        return;
+                                    }
+                                };
+                                outputs.push(output.clone());
+                                let step = playbooks::StepResult {
+                                    title,
+                                    prompt,
+                                    output,
+                                };
+                                // Sin receptor, el playbook se canceló.
+                                if tx.send(playbooks::PlaybookMessage::Step(step)).is_err() {
+                                    return;
+                                }
+                            }
+                            let _ = tx.send(playbooks::PlaybookMessage::Finished(Ok(())));
+                        },
+                    );
+                    self.record_audit(
+                        AuditEventKind::ProviderCall,
+                        format!("{} · {}", profile.provider_name, profile.model),
+                        BTreeMap::from([
+                            ("proveedor".to_string(), profile.provider_name.to_string()),
+                            ("modelo".to_string(), profile.model.clone()),
+                            ("playbook".to_string(), playbook.name.clone()),
+                        ]),
+                    );
+                    self.playbooks.status = Some(format!(
+                        "{} está respondiendo a '{}'…",
+                        profile.provider_name, playbook.name
+                    ));
+                    self.playbooks.run = Some(playbooks::PlaybookRun {
+                        playbook,
+                        values,
+                        started_at,
+                        author: profile.provider_name.to_string(),
+                        results: Vec::new(),
+                        rx,
+                    });
+                    return Ok(());
+                }
+                Some(_) => self.push_activity_log(
+                    LogStatus::Warning,
+                    "Playbooks",
+                    format!(
+                        "Sin conexión: Jarvis responde a '{}' en lugar de {}.",
+                        playbook.name, profile.provider_name
+                    ),
+                ),
+                None => self.push_activity_log(
+                    LogStatus::Warning,
+                    "Playbooks",
+                    format!(
+                        "{} no tiene API key: Jarvis responde a '{}'.",
+                        profile.provider_name, playbook.name
+                    ),
+                ),
+            }
+        }
+
+        let mut results: Vec<playbooks::StepResult> = Vec::new();
+        let mut outcome = Ok(());
+        for (step, prompt) in playbook.steps.iter().zip(&prompts) {
+            let outputs: Vec<String> = results.iter().map(|result| result.output.clone()).collect();
+            let prompt = playbooks::fill_outputs(prompt, &outputs);
+            match self.generate_local_jarvis_reply(&prompt) {
+                Ok(output) => results.push(playbooks::StepResult {
+                    title: step.title.clone(),
+                    prompt,
+                    output,
+                }),
+                Err(err) => {
+                    outcome = Err(err);
+                    break;
+                }
+            }
+        }
+        self.finish_playbook(&playbook, &values, started_at, &results, outcome, "Jarvis");
+        Ok(())
+    }
+
+    /// Detiene el playbook en curso; los pasos ya recibidos se descartan.
+    pub fn cancel_playbook(&mut self) {
+        let Some(run) = self.playbooks.run.take() else {
+            return;
+        };
+        let message = format!(
+            "'{}' cancelado tras {} de {} pasos.",
+            run.playbook.name,
+            run.results.len(),
+            run.playbook.steps.len()
+        );
+        self.push_activity_log(LogStatus::Warning, "Playbooks", message.clone());
+        self.playbooks.status = Some(message);
+    }
+
+    fn poll_playbook_run(&mut self) -> bool {
+        let Some(mut run) = self.playbooks.run.take() else {
+            return false;
+        };
+        let mut updated = false;
+        let outcome = loop {
+            match run.rx.try_recv() {
+                Ok(playbooks::PlaybookMessage::Step(result)) => {
+                    run.results.push(result);
+                    self.playbooks.status = Some(format!(
+                        "{} · paso {} de {} completado.",
+                        run.playbook.name,
+                        run.results.len(),
+                        run.playbook.steps.len()
+                    ));
+                    updated = true;
+                }
+                Ok(playbooks::PlaybookMessage::Finished(outcome)) => break outcome,
+                Err(std::sync::mpsc::TryRecvError::Empty) => {
+                    self.playbooks.run = Some(run);
+                    return updated;
+                }
+                Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                    break Err("la ejecución terminó sin respuesta".to_string())
+                }
+            }
+        };
+        self.finish_playbook(
+            &run.playbook,
+            &run.values,
+            run.started_at,
+            &run.results,
+            outcome,
+            &run.author,
+        );
+        true
+    }
+
+    /// Escribe el documento con las respuestas recibidas en `playbooks/` y lo anuncia en el
+    /// chat. Si un paso falla se conserva lo que se obtuvo hasta entonces.
+    fn finish_playbook(
+        &mut self,
+        playbook: &Playbook,
+        values: &BTreeMap<String, String>,
+        started_at: DateTime<Local>,
+        results: &[playbooks::StepResult],
+        outcome: Result<(), String>,
+        author: &str,
+    ) {
+        if let (Err(err), true) = (&outcome, results.is_empty()) {
+            let message = format!("No se pudo ejecutar '{}': {}", playbook.name, err);
+            self.playbooks.status = Some(message.clone());
+            self.notify(
+                NotificationSource::Providers,
+                LogStatus::Error,
+                "Playbooks",
+                message,
+            );
+            return;
+        }
+
+        let content = playbooks::render_document(playbook, values, results, started_at, author);
+        let written = AppConfig::data_file("playbooks")
+            .map_err(|err| format!("{:#}", err))
+            .and_then(|directory| {
+                std::fs::create_dir_all(&directory)
+                    .map_err(|err| format!("No se pudo crear {}: {}", directory.display(), err))?;
+                let path = directory.join(reports::report_file_name(&playbook.name, started_at));
+                std::fs::write(&path, &content)
+                    .map_err(|err| format!("No se pudo escribir {}: {}", path.display(), err))?;
+                Ok(path)
+            });
+        let path = match written {
+            Ok(path) => path,
+            Err(err) => {
+                let message = format!("No se pudo guardar '{}': {}", playbook.name, err);
+                self.playbooks.status = Some(message.clone());
+                self.push_activity_log(LogStatus::Error, "Playbooks", message);
+                return;
+            }
+        };
+        self.record_file_written(&path, "playbooks");
+        self.playbooks.preview = content;
+        self.playbooks.document = Some(path.clone());
+
+        let (status, message) = match outcome {
+            Ok(()) => (
+                LogStatus::Ok,
+                format!(
+                    "Playbook '{}' completado por {}: {}",
+                    playbook.name,
+                    author,
+                    path.display()
+                ),
+            ),
+            Err(err) => (
+                LogStatus::Warning,
+                format!(
+                    "Playbook '{}' interrumpido en el paso {} ({}). Resultado parcial: {}",
+                    playbook.name,
+                    results.len() + 1,
+                    err,
+                    path.display()
+                ),
+            ),
+        };
+        self.chat
+            .messages
+            .push(ChatMessage::system(message.clone()));
+        self.push_activity_log(status, "Playbooks", message.clone());
+        self.toasts.push(status, message.clone());
+        self.playbooks.status = Some(message);
+    }
+
+    /// Deja la tarea de reindexado `task_id` en `status` y lanza las que dependen de ella.
+    fn finish_reindex_task(&mut self, task_id: u32, status: ScheduledTaskStatus) {
+        let Some(task) = self
+            .automation
+            .cron_board
+            .tasks
+            .iter_mut()
+            .find(|task| task.id == task_id)
+        else {
+            return;
+        };
+        task.status = status;
+        task.next_run = cron::CronSchedule::parse(&task.cron_expression)
+            .ok()
+            .and_then(|cron| cron.next_after(Local::now()))
+            .map(|next| next.format("%Y-%m-%d %H:%M").to_string());
+        let name = task.name.clone();
+        self.persist_config();
+        self.advance_task_chain(&name, status);
+    }
+
+    /// Relee los documentos modificados de un proyecto local y pide en segundo plano los
+    /// embeddings de los fragmentos nuevos, que cierran la tarea `task_id` al llegar.
+    /// Devuelve `false` si el reindexado falló antes de llegar a ellos. El progreso va a
+    /// la actividad y los fallos a la consola.
+    fn reindex_project_knowledge(&mut self, project: &str, task_id: u32) -> bool {
+        let started = Instant::now();
+        let Some(root) = self
+            .resources
+            .project_resources
+            .iter()
+            .find(|card| card.name == project && card.kind == ProjectResourceKind::LocalProject)
+            .map(|card| PathBuf::from(&card.location))
+        else {
+            let message = format!(
+                "No hay un proyecto local llamado '{}' entre los recursos conectados.",
+                project
+            );
+            self.push_debug_event(DebugLogLevel::Error, "knowledge::reindex", message.clone());
+            self.push_activity_log(
+                LogStatus::Error,
+                "Knowledge",
+                format!("Reindexado de '{}' fallido: {}", project, message),
+            );
+            return false;
+        };
+
+        self.push_activity_log(
+            LogStatus::Running,
+            "Knowledge",
+            format!("Reindexando '{}' desde {}…", project, root.display()),
+        );
+        let ocr = Some(&self.config.ocr).filter(|ocr| ocr.enabled && ocr.index_project_images);
+        let report = self.knowledge_index.reindex_project(project, &root, ocr);
+        for (path, reason) in report.failures.iter().take(20) {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "knowledge::reindex",
+                format!("{}: {}", path.display(), reason),
+            );
+        }
+        if report.failures.len() > 20 {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "knowledge::reindex",
+                format!(
+                    "… y {} ficheros más con errores",
+                    report.failures.len() - 20
+                ),
+            );
+        }
+        self.push_activity_log(
+            LogStatus::Running,
+            "Knowledge",
+            format!(
+                "'{}': {} documentos revisados · {} cambiados · {} eliminados",
+                project, report.scanned, report.changed, report.removed
+            ),
+        );
+
+        self.embeddings.request(
+            EmbeddingPurpose::Reindex {
+                project: project.to_string(),
+                task_id,
+                started,
+                failures: report.failures.len(),
+            },
+            None,
+        );
+        true
+    }
+
+    /// Cierra el reindexado de `project` cuando llegan los embeddings de sus fragmentos.
+    fn finish_knowledge_reindex(
+        &mut self,
+        project: &str,
+        task_id: u32,
+        started: Instant,
+        failures: usize,
+        outcome: Result<usize, String>,
+    ) {
+        let status = match outcome {
+            Ok(indexed) => {
+                let message = format!(
+                    "Base de conocimiento de '{}' reindexada: {} fragmentos nuevos en {} ms{}",
+                    project,
+                    indexed,
+                    started.elapsed().as_millis(),
+                    if failures == 0 {
+                        String::new()
+                    } else {
+                        format!(" ({} ficheros con errores)", failures)
+                    }
+                );
+                self.knowledge_index.status = Some(message.clone());
+                let status = if failures == 0 {
+                    LogStatus::Ok
+                } else {
+                    LogStatus::Warning
+                };
+                self.push_activity_log(status, "Knowledge", message);
+                ScheduledTaskStatus::Success
+            }
+            Err(err) => {
+                self.push_debug_event(
+                    DebugLogLevel::Error,
+                    "knowledge::reindex",
+                    format!(
+                        "No se pudieron calcular los embeddings de '{}': {}",
+                        project, err
+                    ),
+                );
+                let message = format!("Reindexado de '{}' fallido: {}", project, err);
+                self.knowledge_index.status = Some(message.clone());
+                self.push_activity_log(LogStatus::Error, "Knowledge", message);
+                ScheduledTaskStatus::Failed
+            }
+        };
+        self.finish_reindex_task(task_id, status);
+    }
+
+    /// Publica los mensajes definitivos y los cambios de estado de los workflows
+    /// ocurridos desde la última llamada.
+    fn publish_stream_events(&mut self) {
+        if !self.event_stream.is_running() {
+            return;
+        }
+
+        let first_pending = self
+            .chat
+            .messages
+            .iter()
+            .position(|message| message.is_pending());
+        let range = self
+            .event_stream
+            .take_unpublished_messages(self.chat.messages.len(), first_pending);
+        for index in range {
+            let message = &self.chat.messages[index];
+            self.event_stream.publish(StreamEvent::ChatMessage {
+                index,
+                sender: message.sender.clone(),
+                text: message.text.clone(),
+                timestamp: message.timestamp.clone(),
+                provider: message
+                    .origin
+                    .map(|provider| provider.display_name().to_string()),
+            });
+        }
+
+        for workflow in &self.automation.workflows.workflows {
+            let status = format!("{:?}", workflow.status).to_lowercase();
+            if self
+                .event_stream
+                .workflow_status_changed(workflow.id, &status)
+            {
+                self.event_stream.publish(StreamEvent::WorkflowStatus {
+                    workflow_id: workflow.id,
+                    name: workflow.name.clone(),
+                    status,
+                });
+            }
+        }
+    }
+
+    fn publish_model_install(
+        &self,
+        provider: LocalModelProvider,
+        model_id: &str,
+        stage: &str,
+        message: &str,
+    ) {
+        self.event_stream.publish(StreamEvent::ModelInstall {
+            provider: provider.display_name().to_string(),
+            model_id: model_id.to_string(),
+            stage: stage.to_string(),
+            message: message.to_string(),
+        });
+    }
+
+    pub fn refresh_personalization_resources(&mut self) {
+        self.resources.personalization_resources = PersonalizationResourcesState::from_sources(
+            &self.profiles,
+            &self.projects,
+            &self.github_repositories,
+        );
+    }
+
+    pub fn rebuild_command_registry(&mut self) {
+        let mut registry = CommandRegistry::new();
+        self.chat.register_commands(&mut registry);
+        self.automation.register_commands(&mut registry);
+        self.resources.register_commands(&mut registry);
+        self.command_engine
+            .set_module_commands(registry.take_commands());
+        self.command_registry = registry;
+        self.command_registry_key = Some(self.workflow_commands_fingerprint());
+    }
+
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn register_workbench_view<V>(&mut self, view: MainView, view_impl: V)
+    where
+        V: WorkbenchView + 'static,
+    {
+        self.workbench_views.insert(view, Box::new(view_impl));
+    }
+
+    pub fn register_workbench_initializer<F>(&mut self, initializer: F)
+    where
+        F: Fn(&mut WorkbenchRegistry) + 'static,
+    {
+        self.workbench_initializers.push(Box::new(initializer));
+        self.rebuild_workbench_views();
+    }
+
+    pub fn workbench_view(&self, view: MainView) -> Option<&dyn WorkbenchView> {
+        self.workbench_views.get(&view).map(|view| view.as_ref())
+    }
+
+    pub fn with_workbench_view_mut<R>(
+        &mut self,
+        view: MainView,
+        f: impl FnOnce(&mut dyn WorkbenchView, &mut AppState) -> R,
+    ) -> Option<R> {
+        let mut view_impl = self.workbench_views.remove(&view)?;
+        let result = f(view_impl.as_mut(), self);
+        self.workbench_views.insert(view, view_impl);
+        Some(result)
+    }
+
+    pub fn rebuild_workbench_views(&mut self) {
+        self.workbench_views.clear();
+        let mut registry = WorkbenchRegistry::new(&mut self.workbench_views);
+        self.chat.register_workbench_views(&mut registry);
+        self.automation.register_workbench_views(&mut registry);
+        self.resources.register_workbench_views(&mut registry);
+        self.batch_eval.register_workbench_views(&mut registry);
+        self.audit.register_workbench_views(&mut registry);
+        self.code_viewer.register_workbench_views(&mut registry);
+        for initializer in &self.workbench_initializers {
+            initializer(&mut registry);
+        }
+    }
+
+    pub fn rebuild_navigation(&mut self) {
+        let mut registry = build_navigation_registry(&self.config);
+        self.chat.register_navigation(&mut registry);
+        self.automation.register_navigation(&mut registry);
+        self.resources.register_navigation(&mut registry);
+        self.batch_eval.register_navigation(&mut registry);
+        self.audit.register_navigation(&mut registry);
+        self.code_viewer.register_navigation(&mut registry);
+        registry.register_node(navigation::NavigationNode {
+            id: "main:routing-status".into(),
+            label: format!("Rutas por alias · {}", self.chat.current_route_display()),
+            description: Some(
+                "Divide tus mensajes entre proveedores remotos usando menciones @alias.".into(),
+            ),
+            icon: Some("🚦".into()),
+            badge: None,
+            target: navigation::NavigationTarget::main(MainView::ChatMultimodal),
+            order: 4,
+            section_id: navigation::SECTION_PRIMARY.to_string(),
+        });
+        self.navigation = registry;
+        self.navigation_profile = self.config.selected_profile;
+    }
+
+    /// Rehace la navegación y el registro de comandos solo si cambió aquello de lo que
+    /// dependen: el perfil seleccionado y los comandos de chat de los workflows.
+    fn refresh_registries(&mut self) -> bool {
+        let mut changed = false;
+        if self.navigation_profile != self.config.selected_profile {
+            self.rebuild_navigation();
+            changed = true;
+        }
+        if self.command_registry_key != Some(self.workflow_commands_fingerprint()) {
+            self.rebuild_command_registry();
+            changed = true;
+        }
+        changed
+    }
+
+    fn workflow_commands_fingerprint(&self) -> u64 {
+        search_index::fingerprint(
+            self.automation
+                .workflows
+                .workflows
+                .iter()
+                .map(|workflow| (workflow.id, &workflow.name, &workflow.chat_command))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn jarvis_model_directory(&self) -> Option<PathBuf> {
+        let direct_path = self.resources.jarvis_model_path.trim();
         if !direct_path.is_empty() {
             let dir = Path::new(direct_path);
             if dir.is_dir() {
                 return Some(dir.to_path_buf());
             }
-            if let Some(parent) = dir.parent() {
-                if parent.is_dir() {
-                    return Some(parent.to_path_buf());
+            if let Some(parent) = dir.parent() {
+                if parent.is_dir() {
+                    return Some(parent.to_path_buf());
+                }
+            }
+        }
+
+        self.resources
+            .jarvis_active_model
+            .as_ref()
+            .map(|model| self.jarvis_model_directory_for(model))
+    }
+
+    fn jarvis_model_directory_for(&self, model: &LocalModelIdentifier) -> PathBuf {
+        if let Some(record) = self.installed_model(model) {
+            let path = Path::new(&record.install_path);
+            if path.is_dir() {
+                return path.to_path_buf();
+            }
+        }
+
+        Path::new(&self.resources.jarvis_install_dir).join(model.sanitized_dir_name())
+    }
+
+    pub fn ensure_jarvis_runtime(&mut self) -> anyhow::Result<&mut JarvisRuntime> {
+        let target_dir = self
+            .jarvis_model_directory()
+            .ok_or_else(|| anyhow::anyhow!("No hay un modelo local configurado para Jarvis."))?;
+
+        let adapters: Vec<LoraAdapter> = self
+            .chat
+            .lora_adapters
+            .iter()
+            .filter_map(|path| LoraAdapter::from_dir(path))
+            .collect();
+
+        let needs_reload = match &self.resources.jarvis_runtime {
+            Some(runtime) => !runtime.matches(&target_dir) || !runtime.matches_adapters(&adapters),
+            None => true,
+        };
+
+        if needs_reload {
+            self.push_activity_log(
+                LogStatus::Running,
+                "Jarvis",
+                format!("Cargando modelo local desde {}", target_dir.display()),
+            );
+            let runtime = JarvisRuntime::load(
+                target_dir.clone(),
+                self.resources
+                    .jarvis_active_model
+                    .as_ref()
+                    .map(|model| model.model_id.clone()),
+                &self.config.jarvis.knowledge_base,
+                &adapters,
+            )?;
+            let applied = runtime.active_adapters().len();
+            if applied < adapters.len() {
+                self.push_activity_log(
+                    LogStatus::Warning,
+                    "Jarvis",
+                    format!(
+                        "Solo se aplicaron {} de {} adaptadores LoRA; revisa el registro de depuración.",
+                        applied,
+                        adapters.len()
+                    ),
+                );
+            }
+            self.resources.jarvis_performance.record_load(
+                runtime.model_label(),
+                runtime.precision().map(str::to_string),
+                runtime.load_duration().as_secs_f64() * 1000.0,
+                crate::api::local::resident_memory_bytes(),
+            );
+            self.resources.jarvis_runtime = Some(runtime);
+            self.resources.jarvis_model_path = target_dir.display().to_string();
+            let loaded_label = self
+                .resources
+                .jarvis_runtime
+                .as_ref()
+                .map(|runtime| runtime.model_label());
+            if let Some(label) = loaded_label {
+                if let Err(err) = self.tokenizers.load_jarvis(&target_dir, &label) {
+                    self.push_debug_event(
+                        DebugLogLevel::Warning,
+                        "jarvis::tokenizer",
+                        format!("Se usará el contador BPE genérico: {}", err),
+                    );
+                }
+                self.push_activity_log(
+                    LogStatus::Ok,
+                    "Jarvis",
+                    format!("Modelo {} listo para responder.", label),
+                );
+                let mut status = format!(
+                    "Jarvis cargó {} desde {}.",
+                    label, self.resources.jarvis_model_path
+                );
+                if let Some(runtime) = &self.resources.jarvis_runtime {
+                    if !runtime.active_adapters().is_empty() {
+                        status.push_str(&format!(
+                            " Adaptadores LoRA: {}.",
+                            runtime.active_adapters().join(", ")
+                        ));
+                    }
+                }
+                self.resources.jarvis_status = Some(status);
+            }
+        }
+
+        Ok(self
+            .resources
+            .jarvis_runtime
+            .as_mut()
+            .expect("runtime recién cargado"))
+    }
+
+    /// Carga `model` para su alias si no lo estaba, descargando antes los modelos por alias
+    /// usados hace más tiempo para no pasar del límite de memoria junto al de Jarvis. Estos
+    /// modelos no aplican adaptadores LoRA.
+    fn ensure_pooled_runtime(
+        &mut self,
+        model: &LocalModelIdentifier,
+    ) -> anyhow::Result<&mut JarvisRuntime> {
+        const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+        if self.resources.jarvis_pool.contains(model) {
+            return Ok(self
+                .resources
+                .jarvis_pool
+                .touch(model)
+                .expect("modelo ya cargado"));
+        }
+
+        let bytes = self
+            .installed_model(model)
+            .map(|record| record.size_bytes)
+            .ok_or_else(|| anyhow::anyhow!("'{}' no está instalado.", model.display_label()))?;
+        let primary_bytes = match (
+            &self.resources.jarvis_runtime,
+            &self.resources.jarvis_active_model,
+        ) {
+            (Some(_), Some(active)) => self
+                .installed_model(active)
+                .map_or(0, |record| record.size_bytes),
+            _ => 0,
+        };
+        let budget =
+            ((f64::from(self.resource_memory_limit_gb) * GIB) as u64).saturating_sub(primary_bytes);
+        if bytes > budget {
+            anyhow::bail!(
+                "'{}' ocupa {} y no cabe junto al modelo de Jarvis en el límite de {:.1} GB.",
+                model.display_label(),
+                format_bytes(bytes),
+                self.resource_memory_limit_gb
+            );
+        }
+        for evicted in self.resources.jarvis_pool.make_room(bytes, budget) {
+            self.push_activity_log(
+                LogStatus::Ok,
+                "Jarvis",
+                format!(
+                    "Se descargó '{}' para dejar sitio en memoria.",
+                    evicted.display_label()
+                ),
+            );
+        }
+
+        let target_dir = self.jarvis_model_directory_for(model);
+        self.push_activity_log(
+            LogStatus::Running,
+            "Jarvis",
+            format!(
+                "Cargando '{}' desde {}",
+                model.display_label(),
+                target_dir.display()
+            ),
+        );
+        let runtime = JarvisRuntime::load(
+            target_dir,
+            Some(model.model_id.clone()),
+            &self.config.jarvis.knowledge_base,
+            &[],
+        )?;
+        self.resources.jarvis_performance.record_load(
+            runtime.model_label(),
+            runtime.precision().map(str::to_string),
+            runtime.load_duration().as_secs_f64() * 1000.0,
+            crate::api::local::resident_memory_bytes(),
+        );
+        Ok(self
+            .resources
+            .jarvis_pool
+            .insert(model.clone(), runtime, bytes))
+    }
+
+    /// Descarga todos los modelos por alias; se vuelven a cargar con su próxima mención.
+    pub fn unload_alias_models(&mut self) {
+        let count = self.resources.jarvis_pool.clear();
+        if count > 0 {
+            self.push_activity_log(
+                LogStatus::Ok,
+                "Jarvis",
+                format!("{} modelos por alias descargados de memoria.", count),
+            );
+        }
+    }
+
+    /// Vuelve a buscar adaptadores LoRA junto al modelo activo y en el directorio de
+    /// instalación.
+    pub fn refresh_lora_adapters(&mut self) {
+        let model_dir = self.jarvis_model_directory();
+        self.resources.jarvis_adapters = local::discover_lora_adapters(
+            model_dir.as_deref(),
+            Path::new(&self.resources.jarvis_install_dir),
+        );
+        let available: Vec<&Path> = self
+            .resources
+            .jarvis_adapters
+            .iter()
+            .map(|adapter| adapter.path.as_path())
+            .collect();
+        self.chat
+            .lora_adapters
+            .retain(|path| available.contains(&path.as_path()));
+    }
+
+    /// Activa o desactiva un adaptador para la conversación actual. La fusión se hace al
+    /// cargar los pesos, así que el runtime se recarga con el siguiente mensaje a Jarvis.
+    pub fn toggle_lora_adapter(&mut self, path: &Path) {
+        if let Some(position) = self.chat.lora_adapters.iter().position(|p| p == path) {
+            self.chat.lora_adapters.remove(position);
+        } else {
+            self.chat.lora_adapters.push(path.to_path_buf());
+        }
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let active = self.chat.lora_adapters.iter().any(|p| p == path);
+        self.resources.jarvis_status = Some(format!(
+            "Adaptador '{}' {} para esta conversación; se aplicará en la próxima respuesta de Jarvis.",
+            name,
+            if active { "activado" } else { "desactivado" }
+        ));
+    }
+
+    fn embedding_signature(&self) -> String {
+        match self.config.embeddings.backend {
+            EmbeddingBackend::Local => format!(
+                "local:{}",
+                self.jarvis_model_directory()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default()
+            ),
+            EmbeddingBackend::OpenAi => format!("openai:{}", self.config.embeddings.openai_model),
+        }
+    }
+
+    /// Calcula embeddings con el backend configurado en `config.embeddings` en este
+    /// hilo. Lo usa el filtro de seguridad de Jarvis, que ya responde en este hilo; el
+    /// resto pide los vectores en segundo plano con `request_embeddings`.
+    pub fn embed_texts(&mut self, texts: &[&str]) -> Result<Vec<Vec<f32>>, String> {
+        self.embedding_engine()?.embed(texts)
+    }
+
+    /// Backend de `config.embeddings` preparado para calcular fuera de este hilo.
+    fn embedding_engine(&mut self) -> Result<EmbeddingEngine, String> {
+        match self.config.embeddings.backend {
+            EmbeddingBackend::Local => self
+                .ensure_jarvis_runtime()
+                .map(|runtime| EmbeddingEngine::Local(runtime.embedder()))
+                .map_err(|err| format!("{:#}", err)),
+            EmbeddingBackend::OpenAi => {
+                if self.is_offline() {
+                    return Err(
+                        "Los embeddings de OpenAI no están disponibles sin conexión.".into(),
+                    );
+                }
+                let key = self
+                    .config
+                    .openai
+                    .api_key
+                    .clone()
+                    .filter(|key| !key.trim().is_empty())
+                    .ok_or_else(|| {
+                        "Configura la API key de OpenAI para usar sus embeddings.".to_string()
+                    })?;
+                Ok(EmbeddingEngine::OpenAi {
+                    api_key: key,
+                    model: self.config.embeddings.openai_model.clone(),
+                })
+            }
+        }
+    }
+
+    fn autosave_session(&mut self) {
+        let Self {
+            session,
+            chat,
+            bookmarks,
+            active_main_view,
+            ..
+        } = self;
+        let result = session.autosave(|| SessionSnapshot {
+            saved_at: String::new(),
+            conversation_key: bookmarks.conversation.clone(),
+            active_view: *active_main_view,
+            input: chat.input.clone(),
+            chat: share::SharedConversation::from_messages(&chat.messages),
+        });
+        if let Err(err) = result {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "session",
+                format!("No se pudo guardar la instantánea de la sesión: {:#}", err),
+            );
+        }
+    }
+
+    /// Sustituye la conversación por la de la sesión interrumpida y vuelve a la vista en
+    /// la que estaba. Devuelve el número de mensajes recuperados.
+    pub fn restore_previous_session(&mut self) -> Option<usize> {
+        let snapshot = self.session.take_recovery()?;
+        self.session.crash_report = None;
+        let key = snapshot.conversation_key.clone();
+        if !key.is_empty() {
+            self.bookmarks.conversation = key.clone();
+            if let Some(main) = self.threads.thread_mut(1) {
+                main.key = key;
+            }
+        }
+        if self.chat.input.trim().is_empty() {
+            self.chat.input = snapshot.input;
+        }
+        self.active_main_view = snapshot.active_view;
+        self.chat.messages = snapshot.chat.into_messages(0);
+        self.chat.focused_message = None;
+        self.chat.pending_reply = None;
+        self.chat.editing_message = None;
+
+        let count = self.chat.messages.len();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Sesión",
+            format!(
+                "Sesión anterior restaurada ({} mensajes, copia del {}).",
+                count, snapshot.saved_at
+            ),
+        );
+        Some(count)
+    }
+
+    pub fn discard_previous_session(&mut self) {
+        self.session.discard_recovery();
+        self.push_activity_log(LogStatus::Warning, "Sesión", "Sesión anterior descartada.");
+    }
+
+    /// Desbloquea el historial cifrado, con el llavero o con la frase de paso del
+    /// formulario, y carga lo que no se pudo leer al arrancar.
+    pub fn unlock_storage(&mut self) -> Result<(), String> {
+        let passphrase = std::mem::take(&mut self.storage_encryption.passphrase);
+        let result = match self.config.storage_encryption.key_source {
+            StorageKeySource::Keychain => vault::initialize(&self.config.storage_encryption),
+            StorageKeySource::Passphrase => vault::unlock(&passphrase),
+        };
+        result.map_err(|err| format!("{:#}", err))?;
+
+        self.bookmarks.entries = BookmarkState::load().entries;
+        self.conversations.entries = ConversationStore::load().entries;
+        if let Some(latest) = self.conversations.latest().cloned() {
+            let key = self.bookmarks.conversation.clone();
+            self.conversations.entries.entry(key).or_insert(latest);
+        }
+        let (composer, draft) = ComposerState::load();
+        self.composer = composer;
+        if self.chat.input.is_empty() {
+            self.chat.input = draft;
+        }
+        self.session = SessionState::detect_previous();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Almacenamiento",
+            "Historial cifrado desbloqueado.",
+        );
+        Ok(())
+    }
+
+    /// Cifra el historial con una clave nueva y migra los archivos guardados en claro.
+    /// También sirve para cambiar de frase de paso o pasar al llavero del sistema.
+    pub fn enable_storage_encryption(&mut self) -> Result<usize, String> {
+        let key_source = self.storage_encryption.key_source;
+        let secret = match key_source {
+            StorageKeySource::Keychain => vault::generate_secret()
+                .and_then(|secret| vault::store_keychain_secret(&secret).map(|_| secret))
+                .map_err(|err| format!("{:#}", err))?,
+            StorageKeySource::Passphrase => {
+                let passphrase = self.storage_encryption.passphrase.clone();
+                if passphrase.chars().count() < share::MIN_PASSPHRASE_LEN {
+                    return Err(tf(
+                        "La frase de paso necesita al menos {0} caracteres.",
+                        &[&share::MIN_PASSPHRASE_LEN.to_string()],
+                    ));
+                }
+                if passphrase != self.storage_encryption.passphrase_confirm {
+                    return Err(t("Las frases de paso no coinciden.").to_string());
+                }
+                passphrase
+            }
+        };
+        let migrated = vault::enable(&secret).map_err(|err| format!("{:#}", err))?;
+        // Las respuestas guardadas hasta ahora están en claro; se descartan en vez de migrarlas.
+        self.response_cache.cache(&self.config).clear();
+        self.storage_encryption.passphrase.clear();
+        self.storage_encryption.passphrase_confirm.clear();
+        self.config.storage_encryption = StorageEncryptionConfig {
+            enabled: true,
+            key_source,
+        };
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Almacenamiento",
+            format!("Historial cifrado; {} archivos migrados.", migrated),
+        );
+        Ok(migrated)
+    }
+
+    /// Descifra el historial y vuelve a guardarlo en claro.
+    pub fn disable_storage_encryption(&mut self) -> Result<usize, String> {
+        let restored = vault::disable().map_err(|err| format!("{:#}", err))?;
+        self.config.storage_encryption.enabled = false;
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Warning,
+            "Almacenamiento",
+            format!(
+                "Cifrado desactivado; {} archivos guardados en claro.",
+                restored
+            ),
+        );
+        Ok(restored)
+    }
+
+    /// Se invoca al pedir el cierre de la ventana para que el próximo arranque no ofrezca
+    /// recuperar la sesión.
+    pub fn shutdown_session(&mut self) {
+        self.save_usage_metrics(true);
+        // Bloqueado, la sesión anterior no llegó a ofrecerse: se conserva para el próximo arranque.
+        if vault::is_locked() {
+            return;
+        }
+        if let Err(err) = self.session.shutdown() {
+            eprintln!("No se pudo cerrar la sesión limpiamente: {:#}", err);
+        }
+    }
+
+    /// Recoge el refresco de tamaños en segundo plano y lanza el siguiente cuando toca.
+    /// Devuelve `true` si cambió algún tamaño mostrado.
+    fn poll_size_index(&mut self) -> bool {
+        let mut updated = false;
+        if let Some(changed) = self.size_index.poll_refresh() {
+            let mut records_changed = false;
+            for record in &mut self.resources.installed_local_models {
+                if let Some(size) = changed.get(record.install_path.trim()) {
+                    record.size_bytes = *size;
+                    records_changed = true;
+                }
+            }
+            for entry in &mut self.config.jarvis.trashed_models {
+                if let Some(size) = changed.get(entry.trash_path.as_str()) {
+                    entry.size_bytes = *size;
+                    records_changed = true;
+                }
+            }
+            for orphan in &mut self.resources.orphan_model_dirs {
+                if let Some(size) = changed.get(&orphan.path.display().to_string()) {
+                    orphan.size_bytes = *size;
+                    updated = true;
+                }
+            }
+            if records_changed {
+                self.persist_config();
+            }
+            self.save_size_index();
+            updated |= records_changed;
+        }
+
+        if self.size_index.refresh_due() {
+            let dirs = self.indexed_model_dirs();
+            self.size_index.request_refresh(dirs);
+        }
+        updated
+    }
+
+    /// Arranca el monitor la primera vez, recoge sus muestras y aplica el límite de memoria
+    /// configurado a la generación local.
+    fn poll_system_monitor(&mut self) -> bool {
+        const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+        let monitor = &mut self.resources.system_monitor;
+        monitor.start();
+        if !monitor.poll() {
+            return false;
+        }
+
+        let limit_bytes = (f64::from(self.resource_memory_limit_gb) * GIB) as u64;
+        match monitor.enforce_memory_limit(limit_bytes) {
+            Some(true) => {
+                let used = monitor
+                    .latest()
+                    .map(|sample| sample.process_memory_bytes)
+                    .unwrap_or(0);
+                let message = format!(
+                    "Generación local en pausa: la aplicación usa {} y el límite es {:.1} GB.",
+                    format_bytes(used),
+                    self.resource_memory_limit_gb
+                );
+                self.push_activity_log(LogStatus::Warning, "Recursos", message.clone());
+                self.toasts.push(LogStatus::Warning, message);
+                self.unload_alias_models();
+            }
+            Some(false) => {
+                self.push_activity_log(
+                    LogStatus::Ok,
+                    "Recursos",
+                    "La memoria volvió por debajo del límite; se reanuda la generación local.",
+                );
+            }
+            None => {}
+        }
+        true
+    }
+
+    /// Pide en segundo plano los modelos de Anthropic disponibles para la cuenta.
+    pub fn refresh_claude_models(&mut self) {
+        if self.resources.claude_models_rx.is_some() {
+            return;
+        }
+        let api_key = self
+            .config
+            .anthropic
+            .api_key
+            .as_deref()
+            .map(str::trim)
+            .unwrap_or_default()
+            .to_string();
+        if api_key.is_empty() {
+            self.resources.claude_models_status =
+                Some("Ingresa una API key válida antes de solicitar el catálogo.".to_string());
+            return;
+        }
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        self.resources.claude_models_rx = Some(rx);
+        self.resources.claude_models_status =
+            Some("Consultando el catálogo de Anthropic…".to_string());
+        self.jobs.submit(
+            RemoteProviderKind::Anthropic.short_code(),
+            JobPriority::Interactive,
+            "Catálogo de modelos",
+            move || {
+                let result =
+                    crate::api::claude::list_models(&api_key).map_err(|err| format!("{:#}", err));
+                let _ = tx.send(result);
+            },
+        );
+    }
+
+    fn poll_claude_models(&mut self) -> bool {
+        let Some(rx) = self.resources.claude_models_rx.as_ref() else {
+            return false;
+        };
+        let result = match rx.try_recv() {
+            Ok(result) => result,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("La consulta del catálogo terminó sin respuesta.".to_string())
+            }
+        };
+        self.resources.claude_models_rx = None;
+        self.apply_claude_models(result);
+        true
+    }
+
+    fn apply_claude_models(&mut self, result: Result<Vec<AnthropicModel>, String>) {
+        match result {
+            Ok(models) => {
+                let added = self
+                    .resources
+                    .remote_catalog
+                    .merge_anthropic_models(&models);
+                // Las fichas existentes cambian de capacidades sin cambiar su huella.
+                self.search_index.mark_dirty(SearchSource::Models);
+                let count = models.len();
+                self.resources.claude_available_models = models;
+                self.resources.claude_models_status = Some(if count == 0 {
+                    "No se encontraron modelos disponibles para esta cuenta.".to_string()
+                } else {
+                    format!(
+                        "Se encontraron {} modelos disponibles ({} nuevos en la galería).",
+                        count, added
+                    )
+                });
+                self.push_activity_log(
+                    LogStatus::Ok,
+                    "Anthropic",
+                    format!("Catálogo actualizado con {} modelos.", count),
+                );
+            }
+            Err(err) => {
+                self.resources.claude_models_status =
+                    Some(format!("No se pudo obtener el listado de modelos: {}", err));
+                self.notify(
+                    NotificationSource::Providers,
+                    LogStatus::Error,
+                    "Anthropic",
+                    format!("No se pudo actualizar el catálogo: {}", err),
+                );
+            }
+        }
+    }
+
+    /// Carpetas cuyo tamaño se muestra: modelos instalados, papelera y huérfanas.
+    fn indexed_model_dirs(&self) -> Vec<PathBuf> {
+        self.resources
+            .installed_local_models
+            .iter()
+            .map(|record| record.install_path.trim())
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .chain(
+                self.config
+                    .jarvis
+                    .trashed_models
+                    .iter()
+                    .map(|entry| PathBuf::from(&entry.trash_path)),
+            )
+            .chain(
+                self.resources
+                    .orphan_model_dirs
+                    .iter()
+                    .map(|orphan| orphan.path.clone()),
+            )
+            .collect()
+    }
+
+    fn save_size_index(&mut self) {
+        if let Err(err) = self.size_index.save() {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "jarvis::sizes",
+                format!("No se pudo guardar el índice de tamaños: {:#}", err),
+            );
+        }
+    }
+
+    fn record_provider_usage(
+        &mut self,
+        provider: &str,
+        prompt_tokens: usize,
+        response_tokens: usize,
+        latency_ms: Option<u32>,
+        failed: bool,
+    ) {
+        if self.config.usage_metrics.enabled {
+            self.usage_metrics.record_provider_call(
+                Local::now().date_naive(),
+                provider,
+                prompt_tokens,
+                response_tokens,
+                latency_ms,
+                failed,
+            );
+        }
+    }
+
+    fn record_command_usage(&mut self, name: &str) {
+        if self.config.usage_metrics.enabled {
+            self.usage_metrics
+                .record_command(Local::now().date_naive(), name);
+        }
+    }
+
+    /// Escribe las métricas pendientes; sin `force`, como mucho una vez por minuto.
+    fn save_usage_metrics(&mut self, force: bool) {
+        let result = if force {
+            self.usage_metrics.save()
+        } else {
+            self.usage_metrics.save_if_due()
+        };
+        if let Err(err) = result {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "metrics",
+                format!("No se pudieron guardar las métricas de uso: {:#}", err),
+            );
+        }
+    }
+
+    pub fn set_usage_metrics_enabled(&mut self, enabled: bool) {
+        if self.config.usage_metrics.enabled == enabled {
+            return;
+        }
+        self.config.usage_metrics.enabled = enabled;
+        self.persist_config();
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Métricas",
+            if enabled {
+                "Registro local de métricas de uso activado."
+            } else {
+                "Registro local de métricas de uso desactivado."
+            },
+        );
+    }
+
+    pub fn clear_usage_metrics(&mut self) {
+        match self.usage_metrics.clear() {
+            Ok(()) => {
+                self.push_activity_log(LogStatus::Ok, "Métricas", "Métricas de uso borradas.")
+            }
+            Err(err) => self.push_activity_log(
+                LogStatus::Error,
+                "Métricas",
+                format!("No se pudieron borrar las métricas: {:#}", err),
+            ),
+        }
+    }
+
+    fn autosave_composer_draft(&mut self) {
+        if let Err(err) = self.composer.autosave(&self.chat.input) {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "chat::composer",
+                format!("No se pudo guardar el borrador: {:#}", err),
+            );
+        }
+    }
+
+    /// Textos que alimentan el índice: mensajes definitivos, fragmentos de los README de
+    /// los proyectos y tarjetas de conocimiento.
+    fn semantic_corpus(&self) -> Vec<(SemanticSource, String)> {
+        let mut corpus: Vec<(SemanticSource, String)> = self
+            .chat
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, message)| message.sender != "System" && !message.is_pending())
+            .map(|(index, message)| (SemanticSource::Message(index), message.text.clone()))
+            .collect();
+
+        for card in &self.resources.project_resources {
+            let section = match card.kind {
+                ProjectResourceKind::LocalProject => ResourceSection::ConnectedProjects,
+                ProjectResourceKind::GithubRepository => ResourceSection::GithubRepositories,
+            };
+            // Los proyectos locales se leen del disco; el resto usa la vista previa.
+            let readme = match card.kind {
+                ProjectResourceKind::LocalProject => {
+                    std::fs::read_to_string(Path::new(&card.location).join("README.md")).ok()
+                }
+                ProjectResourceKind::GithubRepository => None,
+            }
+            .unwrap_or_else(|| card.readme_preview.clone());
+            for (chunk, text) in embeddings::chunk_text(&readme, embeddings::README_CHUNK_CHARS)
+                .into_iter()
+                .enumerate()
+            {
+                corpus.push((
+                    SemanticSource::ProjectReadme {
+                        section,
+                        project: card.name.clone(),
+                        chunk,
+                    },
+                    format!("{}\n{}", card.name, text),
+                ));
+            }
+        }
+
+        // Documentos incorporados por las tareas de reindexado programado.
+        for (project, documents) in &self.knowledge_index.projects {
+            let Some(card) = self
+                .resources
+                .project_resources
+                .iter()
+                .find(|card| &card.name == project)
+            else {
+                continue;
+            };
+            let section = match card.kind {
+                ProjectResourceKind::LocalProject => ResourceSection::ConnectedProjects,
+                ProjectResourceKind::GithubRepository => ResourceSection::GithubRepositories,
+            };
+            for (path, document) in documents {
+                let relative = path
+                    .strip_prefix(&card.location)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string();
+                // El README ya forma parte del corpus como vista del proyecto.
+                if relative.eq_ignore_ascii_case("README.md") {
+                    continue;
+                }
+                for (index, chunk) in document.chunks.iter().enumerate() {
+                    let origin = match chunk.citation() {
+                        Some(citation) => format!("{} · {} · {}", project, relative, citation),
+                        None => format!("{} · {}", project, relative),
+                    };
+                    corpus.push((
+                        SemanticSource::ProjectDocument {
+                            section,
+                            project: project.clone(),
+                            path: relative.clone(),
+                            chunk: index,
+                        },
+                        format!("{}\n{}", origin, chunk.text),
+                    ));
+                }
+            }
+        }
+
+        let knowledge = &self.resources.personalization_resources;
+        for (panel, cards) in [
+            (PreferencePanel::CustomizationMemory, &knowledge.memories),
+            (PreferencePanel::CustomizationProfiles, &knowledge.profiles),
+            (PreferencePanel::CustomizationProjects, &knowledge.contexts),
+        ] {
+            for card in cards {
+                corpus.push((
+                    SemanticSource::KnowledgeCard {
+                        panel,
+                        title: card.title.clone(),
+                    },
+                    format!(
+                        "{}\n{}\n{}",
+                        card.title,
+                        card.subtitle,
+                        card.tags.join(", ")
+                    ),
+                ));
+            }
+        }
+        corpus
+    }
+
+    /// Atiende el cálculo de embeddings que haya terminado y lanza en segundo plano el
+    /// siguiente con las peticiones acumuladas: los textos del corpus que aún no tienen
+    /// vector y las consultas que faltan.
+    fn poll_embeddings(&mut self) -> bool {
+        let mut updated = false;
+        if let Some((purposes, outcome)) = self.embeddings.poll() {
+            updated = true;
+            for purpose in purposes {
+                self.finish_embedding_purpose(purpose, outcome.clone());
+            }
+        }
+        let Some(requests) = self.embeddings.take_requests() else {
+            return updated;
+        };
+        let engine = match self.embedding_engine() {
+            Ok(engine) => engine,
+            Err(err) => {
+                for (purpose, _) in requests {
+                    self.finish_embedding_purpose(purpose, Err(err.clone()));
+                }
+                return true;
+            }
+        };
+
+        let signature = self.embedding_signature();
+        let missing = if requests
+            .iter()
+            .any(|(purpose, _)| !matches!(purpose, EmbeddingPurpose::Test(_)))
+        {
+            let corpus = self.semantic_corpus();
+            self.embeddings
+                .index
+                .missing(&signature, corpus.into_iter())
+        } else {
+            Vec::new()
+        };
+        let mut queries: Vec<String> = Vec::new();
+        for (purpose, query) in &requests {
+            let Some(query) = query else {
+                continue;
+            };
+            // La prueba del backend siempre llega hasta él.
+            let cached = !matches!(purpose, EmbeddingPurpose::Test(_))
+                && self.embeddings.query_vector(&signature, query).is_some();
+            if !cached && !queries.contains(query) {
+                queries.push(query.clone());
+            }
+        }
+        let purposes: Vec<EmbeddingPurpose> =
+            requests.into_iter().map(|(purpose, _)| purpose).collect();
+        if missing.is_empty() && queries.is_empty() {
+            for purpose in purposes {
+                self.finish_embedding_purpose(purpose, Ok(0));
+            }
+        } else {
+            self.embeddings.start(
+                &mut self.jobs,
+                engine,
+                signature,
+                missing,
+                queries,
+                purposes,
+            );
+        }
+        true
+    }
+
+    /// Completa lo que esperaba un cálculo de embeddings; `outcome` es el número de
+    /// textos nuevos en el índice o el error del backend.
+    fn finish_embedding_purpose(
+        &mut self,
+        purpose: EmbeddingPurpose,
+        outcome: Result<usize, String>,
+    ) {
+        match purpose {
+            EmbeddingPurpose::Search => self.finish_semantic_search(outcome),
+            EmbeddingPurpose::Recall(call_id) => self.resume_recall_call(call_id, outcome),
+            EmbeddingPurpose::Reindex {
+                project,
+                task_id,
+                started,
+                failures,
+            } => self.finish_knowledge_reindex(&project, task_id, started, failures, outcome),
+            EmbeddingPurpose::Test(started) => {
+                let signature = self.embedding_signature();
+                let status = match outcome {
+                    Ok(_) => format!(
+                        "{} respondió con vectores de {} dimensiones en {} ms.",
+                        self.config.embeddings.backend.label(),
+                        self.embeddings
+                            .query_vector(&signature, embeddings::TEST_TEXT)
+                            .map_or(0, Vec::len),
+                        started.elapsed().as_millis()
+                    ),
+                    Err(err) => err,
+                };
+                self.embeddings.status = Some(status);
+            }
+        }
+    }
+
+    /// Busca por significado cuando la consulta global deja de cambiar durante un
+    /// instante. Fuera del modo semántico solo se consultan los mensajes del chat.
+    fn refresh_semantic_search(&mut self) -> bool {
+        let query = self.search_buffer.trim().to_string();
+        let enabled = self.config.embeddings.semantic_search || self.embeddings.semantic_mode;
+        if !enabled || query.chars().count() < 3 {
+            self.embeddings.pending_query = None;
+            if !self.embeddings.search_hits.is_empty() {
+                self.embeddings.search_hits.clear();
+                return true;
+            }
+            return false;
+        }
+        if query == self.embeddings.search_query {
+            return false;
+        }
+        match &self.embeddings.pending_query {
+            Some((pending, since)) if *pending == query => {
+                if since.elapsed() < embeddings::SEMANTIC_SEARCH_DEBOUNCE {
+                    return false;
+                }
+            }
+            _ => {
+                self.embeddings.pending_query = Some((query, Instant::now()));
+                return false;
+            }
+        }
+
+        self.embeddings.pending_query = None;
+        self.embeddings.search_query = query.clone();
+        self.embeddings
+            .request(EmbeddingPurpose::Search, Some(query));
+        true
+    }
+
+    /// Muestra los resultados de la búsqueda semántica cuando llega el vector de la consulta.
+    fn finish_semantic_search(&mut self, outcome: Result<usize, String>) {
+        if let Err(err) = outcome {
+            self.embeddings.search_hits.clear();
+            self.embeddings.search_error = Some(err.clone());
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "embeddings::search",
+                format!("Búsqueda semántica no disponible: {}", err),
+            );
+            return;
+        }
+        let signature = self.embedding_signature();
+        // Si la consulta cambió mientras se calculaba, la nueva trae su propio cálculo.
+        let Some(vector) = self
+            .embeddings
+            .query_vector(&signature, &self.embeddings.search_query)
+        else {
+            return;
+        };
+        let index = &self.embeddings.index;
+        let search = |filter: fn(&SemanticSource) -> bool| {
+            index.search(vector, 5, embeddings::SEMANTIC_SEARCH_MIN_SCORE, filter)
+        };
+        let mut hits = search(|source| matches!(source, SemanticSource::Message(_)));
+        if self.embeddings.semantic_mode {
+            hits.extend(search(|source| {
+                matches!(
+                    source,
+                    SemanticSource::ProjectReadme { .. } | SemanticSource::ProjectDocument { .. }
+                )
+            }));
+            hits.extend(search(|source| {
+                matches!(source, SemanticSource::KnowledgeCard { .. })
+            }));
+        }
+        self.embeddings.search_hits = hits;
+        self.embeddings.search_error = None;
+    }
+
+    /// Indica si una llamada con `prompt` recordaría mensajes antiguos o documentación
+    /// del perfil activo, para lo que necesita el vector del prompt.
+    fn wants_recall(&self, prompt: &str) -> bool {
+        !prompt.trim().is_empty()
+            && ((self.config.embeddings.recall_messages > 0 && self.enable_memory_tracking)
+                || self
+                    .active_project_profile()
+                    .is_some_and(|(_, profile)| !profile.knowledge_sources.is_empty()))
+    }
+
+    /// Recupera los mensajes anteriores a `end` más parecidos al prompt, cuyo vector es
+    /// `query`, para que el proveedor los reciba como memoria.
+    fn recall_relevant_messages(&self, query: &[f32], end: usize) -> Vec<String> {
+        let limit = self.config.embeddings.recall_messages;
+        if limit == 0 || !self.enable_memory_tracking {
+            return Vec::new();
+        }
+        self.embeddings
+            .index
+            .search(
+                query,
+                limit,
+                embeddings::MEMORY_RECALL_MIN_SCORE,
+                |source| source.message_index().is_some_and(|index| index < end),
+            )
+            .into_iter()
+            .filter_map(|hit| self.chat.messages.get(hit.source.message_index()?))
+            .map(context::history_line)
+            .collect()
+    }
+
+    /// Comprueba el backend de embeddings con un texto de ejemplo, en segundo plano.
+    pub fn test_embeddings(&mut self) {
+        self.embeddings.status = Some(format!(
+            "Probando {}…",
+            self.config.embeddings.backend.label()
+        ));
+        self.embeddings.request(
+            EmbeddingPurpose::Test(Instant::now()),
+            Some(embeddings::TEST_TEXT.to_string()),
+        );
+    }
+
+    /// Transcribe `path` en segundo plano; el texto se añade al compositor al terminar.
+    pub fn start_transcription(&mut self, path: PathBuf) -> Result<(), String> {
+        let voice = self.config.voice_input.clone();
+        let backend = voice.backend;
+        let openai_key = self.config.openai.api_key.clone();
+        if let Err(err) = self
+            .transcription
+            .start(&mut self.jobs, voice, openai_key, path.clone())
+        {
+            self.transcription.status = Some(err.clone());
+            return Err(err);
+        }
+        self.transcription.status = Some(format!(
+            "Transcribiendo {} con {}…",
+            path.display(),
+            backend.label()
+        ));
+        Ok(())
+    }
+
+    fn poll_transcription(&mut self) -> bool {
+        let Some(result) = self.transcription.poll() else {
+            return false;
+        };
+        let file = result
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| result.path.display().to_string());
+        match result.text {
+            Ok(text) if !text.is_empty() => {
+                if !self.chat.input.trim().is_empty() {
+                    self.chat.input.push(' ');
+                }
+                self.chat.input.push_str(&text);
+                self.transcription.status = Some(format!(
+                    "{} transcrito con {} ({} caracteres).",
+                    file,
+                    result.backend.label(),
+                    text.chars().count()
+                ));
+                self.push_activity_log(
+                    LogStatus::Ok,
+                    "Voz",
+                    format!("Transcripción de {} añadida al compositor.", file),
+                );
+                self.toasts
+                    .push(LogStatus::Ok, format!("Transcripción de {} lista.", file));
+            }
+            Ok(_) => {
+                self.transcription.status = Some(format!("No se reconoció voz en {}.", file));
+            }
+            Err(err) => {
+                self.transcription.status =
+                    Some(format!("No se pudo transcribir {}: {}", file, err));
+                self.push_activity_log(
+                    LogStatus::Error,
+                    "Voz",
+                    format!("Falló la transcripción de {}: {}", file, err),
+                );
+                self.toasts.push(
+                    LogStatus::Error,
+                    format!("No se pudo transcribir {}.", file),
+                );
+            }
+        }
+        true
+    }
+
+    /// Lanza una búsqueda web con el backend de las preferencias; los resultados aparecen
+    /// en el panel del chat.
+    pub fn start_web_search(&mut self, query: &str) -> Result<(), String> {
+        if self.is_offline() {
+            return Err("Sin conexión: la búsqueda web no está disponible.".to_string());
+        }
+        let result = self
+            .web_search
+            .start(&mut self.jobs, self.config.web_search.clone(), query);
+        self.web_search.status = Some(match &result {
+            Ok(()) => format!(
+                "Buscando «{}» en {}…",
+                query.trim(),
+                self.config.web_search.backend.label()
+            ),
+            Err(err) => err.clone(),
+        });
+        result
+    }
+
+    /// La búsqueda web se ofrece como herramienta a los proveedores remotos.
+    fn web_search_tool_available(&self) -> bool {
+        self.config.web_search.expose_to_providers
+            && crate::api::web_search::validate(&self.config.web_search).is_ok()
+    }
+
+    /// Atiende la búsqueda que pidió un proveedor: deja su mensaje en espera y lanza la
+    /// búsqueda, tras la que se le vuelve a llamar con los resultados.
+    fn start_web_search_tool(
+        &mut self,
+        ticket: &ProviderCallTicket,
+        query: String,
+        conversation: &str,
+    ) {
+        let Some(message) = self.chat.messages.get_mut(ticket.message_index) else {
+            return;
+        };
+        let Some(prompt) = message.source_prompt.clone() else {
+            return;
+        };
+        message.text = format!("Buscando en la web «{}»…", query);
+        message.status = ChatMessageStatus::Pending;
+        self.push_activity_log(
+            LogStatus::Running,
+            ticket.provider_name.clone(),
+            format!("'{}' pidió una búsqueda web: {}", ticket.model, query),
+        );
+        self.web_search.start_tool(
+            &mut self.jobs,
+            self.config.web_search.clone(),
+            ticket,
+            prompt,
+            query,
+            conversation.to_string(),
+        );
+    }
+
+    fn poll_web_search(&mut self) -> bool {
+        let mut updated = false;
+        if let Some(outcome) = self.web_search.poll() {
+            updated = true;
+            let query = self.web_search.query.clone();
+            match outcome {
+                Ok(results) => {
+                    self.web_search.status =
+                        Some(format!("{} resultados para «{}».", results.len(), query));
+                    self.push_activity_log(
+                        LogStatus::Ok,
+                        "Búsqueda web",
+                        format!("«{}»: {} resultados.", query, results.len()),
+                    );
+                    self.web_search.results = results;
+                }
+                Err(err) => {
+                    self.web_search.status = Some(format!("La búsqueda falló: {}", err));
+                    self.push_activity_log(
+                        LogStatus::Error,
+                        "Búsqueda web",
+                        format!("Falló la búsqueda «{}»: {}", query, err),
+                    );
+                }
+            }
+        }
+
+        for (url, outcome) in self.web_search.poll_pages() {
+            updated = true;
+            if let Err(err) = outcome {
+                self.toasts.push(
+                    LogStatus::Error,
+                    format!("No se pudo abrir {}: {}", url, err),
+                );
+            }
+        }
+
+        for (call, outcome) in self.web_search.poll_tools() {
+            updated = true;
+            let source = format!("providers::{}", call.provider.short_code());
+            match &outcome {
+                Ok(results) => self.push_debug_event(
+                    DebugLogLevel::Info,
+                    source,
+                    format!(
+                        "Búsqueda web «{}»: {} resultados para el proveedor.",
+                        call.query,
+                        results.len()
+                    ),
+                ),
+                Err(err) => self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    source,
+                    format!("Falló la búsqueda web «{}»: {}", call.query, err),
+                ),
+            }
+            if call.conversation != self.bookmarks.conversation {
+                continue;
+            }
+            // Los resultados quedan en el panel para revisar lo que leyó el proveedor.
+            if let (Ok(results), false) = (&outcome, self.web_search.is_searching()) {
+                self.web_search.query = call.query.clone();
+                self.web_search.results = results.clone();
+                self.web_search.expanded.clear();
+            }
+            self.web_search.pending_tool_context =
+                Some(web_search::tool_context(&call.query, &outcome));
+            self.handle_provider_call(call.provider, call.prompt, Some(call.message_index));
+            self.web_search.pending_tool_context = None;
+        }
+        updated
+    }
+
+    /// Arranca o detiene el vigilante del portapapeles según la configuración.
+    pub fn restart_clipboard_watcher(&mut self) {
+        self.clipboard.stop();
+        self.toasts.dismiss_clipboard();
+        let settings = self.config.clipboard_watcher.clone();
+        if settings.enabled {
+            self.clipboard.start(settings.poll_interval_secs);
+        }
+    }
+
+    /// Ofrece acciones en un aviso cuando se copia una traza, código o una URL. Lo que
+    /// se copia desde la propia aplicación (mensajes o el compositor) no se ofrece.
+    fn poll_clipboard(&mut self) -> bool {
+        let texts = match self.clipboard.poll() {
+            Ok(texts) => texts,
+            Err(err) => {
+                self.push_activity_log(
+                    LogStatus::Warning,
+                    "Portapapeles",
+                    format!("Vigilante detenido: {}", err),
+                );
+                return true;
+            }
+        };
+        let Some((kind, text)) = texts.into_iter().rev().find_map(|text| {
+            clipboard::classify_enabled(&self.config.clipboard_watcher, &text)
+                .map(|kind| (kind, text))
+        }) else {
+            return false;
+        };
+        let trimmed = text.trim();
+        let own = self.chat.input.contains(trimmed)
+            || self
+                .chat
+                .messages
+                .iter()
+                .any(|message| message.text.contains(trimmed));
+        if own {
+            return false;
+        }
+
+        self.toasts.dismiss_clipboard();
+        let actions = kind
+            .actions()
+            .iter()
+            .enumerate()
+            .map(|(index, action)| (action.label.to_string(), ToastAction::Clipboard(index)))
+            .collect();
+        self.toasts.push_with_actions(
+            LogStatus::Running,
+            format!("Has copiado {}.", kind.label()),
+            actions,
+        );
+        self.clipboard.capture = Some((kind, text));
+        true
+    }
+
+    /// Ejecuta la acción de un botón de un aviso.
+    pub fn run_toast_action(&mut self, action: ToastAction) {
+        match action {
+            ToastAction::Clipboard(index) => self.run_clipboard_action(index),
+        }
+    }
+
+    /// Prepara un mensaje nuevo en el chat con la plantilla de la acción y lo copiado.
+    fn run_clipboard_action(&mut self, index: usize) {
+        let Some((kind, text)) = self.clipboard.capture.take() else {
+            return;
+        };
+        let Some(action) = kind.actions().get(index) else {
+            return;
+        };
+        if action.attach_page {
+            self.web_search.attach_url(&mut self.jobs, text.trim());
+        }
+        self.chat.input = action.render(&text);
+        self.activate_navigation_target(NavigationTarget::main(MainView::ChatMultimodal));
+    }
+
+    /// Adjunta un archivo soltado sobre la ventana al compositor del chat.
+    pub fn attach_file(&mut self, path: PathBuf) {
+        if !path.is_file() {
+            return;
+        }
+        let attachment = ComposerAttachment::new(path.clone());
+        if self.attachments.items.iter().any(|item| item.path == path) {
+            return;
+        }
+        if attachment.kind == AttachmentKind::Document {
+            match documents::extract_chunks(&path, embeddings::README_CHUNK_CHARS) {
+                Ok((pages, chunks)) => {
+                    self.attachments
+                        .documents
+                        .insert(path.clone(), (pages, chunks));
+                    self.attachments.add(path);
+                    self.toasts.push(
+                        LogStatus::Ok,
+                        format!(
+                            "{} adjuntado: {} páginas con texto. Sus fragmentos acompañarán a tus preguntas.",
+                            attachment.file_name(),
+                            pages
+                        ),
+                    );
+                }
+                Err(err) => {
+                    self.toasts.push(LogStatus::Error, format!("{:#}", err));
+                }
+            }
+            return;
+        }
+        if !self.attachments.add(path.clone()) {
+            return;
+        }
+        if attachment.kind == AttachmentKind::Image && self.config.ocr.enabled {
+            self.recognize_image_text(path);
+            self.toasts.push(
+                LogStatus::Ok,
+                format!(
+                    "{} adjuntado. Reconociendo su texto…",
+                    attachment.file_name()
+                ),
+            );
+        } else {
+            self.toasts.push(
+                LogStatus::Ok,
+                format!("{} adjuntado.", attachment.file_name()),
+            );
+        }
+    }
+
+    /// Abre el diálogo de envío con el asunto y el cuerpo de las plantillas de correo.
+    pub fn compose_email(&mut self, title: &str, content: &str, attachments: Vec<PathBuf>) {
+        let smtp = &self.config.smtp;
+        self.email.status = None;
+        self.email.draft = Some(email::EmailDraft {
+            to: smtp.default_recipients.clone(),
+            subject: email::render_template(&smtp.subject_template, title, content),
+            body: email::render_template(&smtp.body_template, title, content),
+            attachments,
+        });
+    }
+
+    /// Prepara el envío de un informe generado, con el Markdown en el cuerpo y adjunto.
+    pub fn compose_report_email(&mut self, path: &Path) -> Result<(), String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("No se pudo leer {}: {}", path.display(), err))?;
+        let title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "Informe".to_string());
+        self.compose_email(&title, &content, vec![path.to_path_buf()]);
+        Ok(())
+    }
+
+    pub fn compose_digest_email(&mut self) -> Result<(), String> {
+        let Some(text) = self.digest.last_digest.clone() else {
+            return Err("Aún no se ha generado ningún resumen en esta sesión.".to_string());
+        };
+        let title = format!(
+            "{} · {}",
+            digest::DIGEST_THREAD_TITLE,
+            Local::now().format("%d/%m/%Y")
+        );
+        self.compose_email(&title, &text, Vec::new());
+        Ok(())
+    }
+
+    /// Prepara el envío de la última conversación exportada. La frase de paso no viaja en
+    /// el correo.
+    pub fn compose_shared_conversation_email(&mut self) -> Result<(), String> {
+        let mut path = PathBuf::from(self.share.export_path.trim());
+        if path.extension().is_none() {
+            path.set_extension(share::SHARE_EXTENSION);
+        }
+        if !path.is_file() {
+            return Err("Exporta antes la conversación cifrada.".to_string());
+        }
+        let file = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let content = format!(
+            "Adjunto la conversación cifrada {}. Ábrela desde JungleMonkAI › Compartir conversación › Importar con la frase de paso que te enviaré por otro canal.",
+            file
+        );
+        self.compose_email("Conversación compartida", &content, vec![path]);
+        Ok(())
+    }
+
+    /// Envía el borrador abierto en segundo plano.
+    pub fn send_email_draft(&mut self) -> Result<(), String> {
+        let Some(draft) = self.email.draft.clone() else {
+            return Err("No hay ningún correo preparado.".to_string());
+        };
+        self.email
+            .start(&mut self.jobs, self.config.smtp.clone(), draft.clone())?;
+        self.email.draft = None;
+        self.email.status = Some(format!("Enviando «{}»…", draft.subject.trim()));
+        self.push_activity_log(
+            LogStatus::Running,
+            "Correo",
+            format!("Enviando «{}» a {}.", draft.subject.trim(), draft.to.trim()),
+        );
+        Ok(())
+    }
+
+    fn poll_email(&mut self) -> bool {
+        let Some(outcome) = self.email.poll() else {
+            return false;
+        };
+        let subject = outcome.subject.trim();
+        match outcome.result {
+            Ok(()) => {
+                let message = format!("«{}» enviado a {}.", subject, outcome.recipients.trim());
+                self.email.status = Some(message.clone());
+                self.push_activity_log(LogStatus::Ok, "Correo", message.clone());
+                self.toasts.push(LogStatus::Ok, message);
+            }
+            Err(err) => {
+                self.email.status = Some(format!("No se pudo enviar «{}»: {}", subject, err));
+                self.push_activity_log(
+                    LogStatus::Error,
+                    "Correo",
+                    format!("Falló el envío de «{}»: {}", subject, err),
+                );
+                self.toasts.push(
+                    LogStatus::Error,
+                    format!("No se pudo enviar «{}».", subject),
+                );
+            }
+        }
+        true
+    }
+
+    /// Da de alta el conector del formulario de integraciones y guarda su URL en el
+    /// llavero del sistema.
+    pub fn add_webhook_connector(&mut self) -> Result<(), String> {
+        let name = self.webhooks.name_draft.trim().to_string();
+        let kind = self.webhooks.kind_draft;
+        if name.is_empty() {
+            return Err("Pon un nombre al conector.".to_string());
+        }
+        if self
+            .config
+            .webhook_connectors
+            .iter()
+            .any(|connector| connector.name.eq_ignore_ascii_case(&name))
+        {
+            return Err(format!("Ya hay un conector llamado '{}'.", name));
+        }
+        webhooks::validate_url(kind, &self.webhooks.url_draft)?;
+        vault::store_keychain_entry(
+            &webhooks::keychain_account(&name),
+            self.webhooks.url_draft.trim(),
+        )
+        .map_err(|err| format!("{:#}", err))?;
+        self.config.webhook_connectors.push(WebhookConnectorConfig {
+            name: name.clone(),
+            kind,
+            ..WebhookConnectorConfig::default()
+        });
+        self.persist_config();
+        self.webhooks.name_draft.clear();
+        self.webhooks.url_draft.clear();
+        let message = format!(
+            "Conector de {} '{}' añadido; su URL queda en el llavero del sistema.",
+            kind.label(),
+            name
+        );
+        self.webhooks.status = Some(message.clone());
+        self.push_activity_log(LogStatus::Ok, "Webhooks", message);
+        Ok(())
+    }
+
+    pub fn remove_webhook_connector(&mut self, name: &str) {
+        self.config
+            .webhook_connectors
+            .retain(|connector| connector.name != name);
+        self.persist_config();
+        if let Err(err) = vault::delete_keychain_entry(&webhooks::keychain_account(name)) {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "webhooks::keychain",
+                format!("{:#}", err),
+            );
+        }
+        self.webhooks.status = Some(format!("Conector '{}' eliminado.", name));
+    }
+
+    /// Envía un mensaje de prueba al canal del conector.
+    pub fn test_webhook_connector(&mut self, name: &str) {
+        let Some(connector) = self
+            .config
+            .webhook_connectors
+            .iter()
+            .find(|connector| connector.name == name)
+            .cloned()
+        else {
+            return;
+        };
+        self.webhooks.status = Some(format!("Enviando un mensaje de prueba a '{}'…", name));
+        self.webhooks.start(
+            &mut self.jobs,
+            connector,
+            "Prueba".to_string(),
+            format!(
+                "Mensaje de prueba de JungleMonkAI · {}",
+                Local::now().format("%d/%m/%Y %H:%M")
+            ),
+        );
+    }
+
+    fn poll_webhooks(&mut self) -> bool {
+        let finished = self.webhooks.poll();
+        if finished.is_empty() {
+            return false;
+        }
+        // Los fallos no pasan por `notify`: un aviso reenviado al mismo conector volvería
+        // a fallar.
+        for outcome in finished {
+            match outcome.result {
+                Ok(()) => {
+                    let message =
+                        format!("Publicado en '{}' ({}).", outcome.connector, outcome.origin);
+                    self.webhooks.status = Some(message.clone());
+                    self.push_activity_log(LogStatus::Ok, "Webhooks", message);
+                }
+                Err(err) => {
+                    let message = format!(
+                        "No se pudo publicar en '{}' ({}): {}",
+                        outcome.connector, outcome.origin, err
+                    );
+                    self.webhooks.status = Some(message.clone());
+                    self.push_activity_log(LogStatus::Error, "Webhooks", message);
+                    self.toasts.push(
+                        LogStatus::Error,
+                        format!("Falló la publicación en '{}'.", outcome.connector),
+                    );
+                }
+            }
+        }
+        true
+    }
+
+    /// Arranca el puente de Telegram si está activado y tiene token; si no, lo detiene.
+    pub fn restart_telegram_bridge(&mut self) {
+        self.telegram.stop();
+        let settings = &self.config.telegram;
+        if settings.enabled && !settings.bot_token.trim().is_empty() {
+            self.telegram.start(settings.bot_token.trim().to_string());
+        }
+    }
+
+    fn poll_telegram(&mut self) -> bool {
+        let events = self.telegram.poll();
+        let mut updated = !events.is_empty();
+        for event in events {
+            match event {
+                TelegramEvent::Connected(name) => {
+                    self.telegram.status = Some(format!("Conectado como {}.", name));
+                    self.push_activity_log(
+                        LogStatus::Ok,
+                        "Telegram",
+                        format!("Puente conectado como {}.", name),
+                    );
+                    self.telegram.bot_name = Some(name);
+                }
+                TelegramEvent::Message(message) => {
+                    if !self
+                        .config
+                        .telegram
+                        .allowed_chat_ids
+                        .contains(&message.chat_id)
+                    {
+                        // El identificador queda en la actividad para poder autorizarlo.
+                        self.push_activity_log(
+                            LogStatus::Warning,
+                            "Telegram",
+                            format!(
+                                "Mensaje de {} ignorado: el chat {} no está autorizado.",
+                                message.sender, message.chat_id
+                            ),
+                        );
+                        continue;
+                    }
+                    self.telegram.inbox.push_back(message);
+                }
+                TelegramEvent::Error(err) => {
+                    self.telegram.status = Some(err.clone());
+                    self.push_debug_event(DebugLogLevel::Warning, "telegram", err);
+                }
+                TelegramEvent::Stopped(err) => {
+                    self.telegram.stop();
+                    self.telegram.status = Some(err.clone());
+                    self.push_activity_log(
+                        LogStatus::Error,
+                        "Telegram",
+                        format!("Puente detenido: {}", err),
+                    );
+                }
+            }
+        }
+        updated |= self.finish_telegram_relay();
+        updated |= self.start_telegram_relay();
+        updated
+    }
+
+    /// Pasa el siguiente mensaje recibido al hilo de Telegram, que sigue la ruta habitual
+    /// de proveedores y Jarvis. Se atiende uno cada vez y solo con la conversación libre;
+    /// el puente se usa lejos del escritorio, así que el hilo se abre si hace falta.
+    fn start_telegram_relay(&mut self) -> bool {
+        if self.telegram.relay.is_some()
+            || self.telegram.inbox.is_empty()
+            || self.ensure_conversation_idle().is_err()
+        {
+            return false;
+        }
+        let Some(message) = self.telegram.inbox.pop_front() else {
+            return false;
+        };
+        let token = self.config.telegram.bot_token.trim().to_string();
+        if message.text.starts_with('/') {
+            let reply = if message.text.starts_with("/start") {
+                "Hola. Escríbeme y te responderá tu agente de JungleMonkAI."
+            } else {
+                "Los comandos de JungleMonkAI no están disponibles desde Telegram."
+            };
+            self.telegram
+                .send(&mut self.jobs, token, message.chat_id, reply.to_string());
+            return true;
+        }
+
+        let title = match self.config.telegram.thread_title.trim() {
+            "" => "Telegram".to_string(),
+            title => title.to_string(),
+        };
+        let thread_id = self
+            .threads
+            .ensure_thread(telegram::TELEGRAM_THREAD_KEY, &title);
+        if let Err(err) = self.switch_thread(thread_id) {
+            self.telegram.inbox.push_front(message);
+            self.telegram.status = Some(err);
+            return false;
+        }
+        self.push_activity_log(
+            LogStatus::Running,
+            "Telegram",
+            format!("Mensaje de {} en '{}'.", message.sender, title),
+        );
+        self.telegram.relay = Some(TelegramRelay {
+            chat_id: message.chat_id,
+            thread_id,
+            message_index: self.chat.messages.len(),
+        });
+        self.dispatch_user_message(message.text, None);
+        true
+    }
+
+    /// Devuelve al chat de Telegram las respuestas del mensaje en curso cuando terminan.
+    fn finish_telegram_relay(&mut self) -> bool {
+        let Some(relay) = self.telegram.relay.clone() else {
+            return false;
+        };
+        if self.ensure_conversation_idle().is_err() {
+            return false;
+        }
+        let messages: &[ChatMessage] = if relay.thread_id == self.threads.active {
+            &self.chat.messages
+        } else {
+            self.threads
+                .thread(relay.thread_id)
+                .map(|thread| thread.messages.as_slice())
+                .unwrap_or_default()
+        };
+        let replies: Vec<&ChatMessage> = messages
+            .get(relay.message_index + 1..)
+            .unwrap_or_default()
+            .iter()
+            .filter(|message| message.sender != "User")
+            .collect();
+        if replies.iter().any(|message| message.is_pending()) {
+            return false;
+        }
+        let text = match replies.as_slice() {
+            [] => "Sin respuesta.".to_string(),
+            [reply] => reply.text.trim().to_string(),
+            replies => replies
+                .iter()
+                .map(|reply| format!("{}:\n{}", reply.sender_display_label(), reply.text.trim()))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+        };
+        self.telegram.relay = None;
+        let token = self.config.telegram.bot_token.trim().to_string();
+        self.telegram
+            .send(&mut self.jobs, token, relay.chat_id, text);
+        true
+    }
+
+    /// Reconecta el cliente MQTT con la configuración actual; si está desactivado o no
+    /// tiene broker, lo detiene.
+    pub fn restart_mqtt_client(&mut self) {
+        self.mqtt.stop();
+        let settings = &self.config.mqtt;
+        if settings.enabled && !settings.host.trim().is_empty() {
+            self.mqtt.start(settings.clone());
+        }
+    }
+
+    /// Publica un mensaje de prueba en el tema de resultados.
+    pub fn publish_mqtt_test(&mut self) {
+        let topic = self.config.mqtt.results_topic.trim().to_string();
+        if topic.is_empty() {
+            self.mqtt.status = Some("Indica un tema para los resultados.".to_string());
+            return;
+        }
+        let payload = serde_json::json!({
+            "test": true,
+            "timestamp": Local::now().to_rfc3339(),
+        });
+        self.mqtt.publish(
+            &mut self.jobs,
+            self.config.mqtt.clone(),
+            topic,
+            payload.to_string(),
+        );
+    }
+
+    /// Reparte los mensajes recibidos: los publica en el flujo de eventos y dispara los
+    /// listeners MQTT cuyo filtro de tema encaja, como mucho una vez por pasada.
+    fn poll_mqtt(&mut self) -> bool {
+        let events = self.mqtt.poll();
+        if events.is_empty() {
+            return false;
+        }
+        let mut fired: Vec<u32> = Vec::new();
+        for event in events {
+            match event {
+                MqttEvent::Connected => {
+                    self.mqtt.connected = true;
+                    let broker =
+                        format!("{}:{}", self.config.mqtt.host.trim(), self.config.mqtt.port);
+                    self.mqtt.status = Some(format!("Conectado a {}.", broker));
+                    self.push_activity_log(
+                        LogStatus::Ok,
+                        "MQTT",
+                        format!("Conectado al broker {}.", broker),
+                    );
+                }
+                MqttEvent::Message(message) => {
+                    self.event_stream.publish(StreamEvent::MqttMessage {
+                        topic: message.topic.clone(),
+                        payload: message.payload.clone(),
+                        timestamp: message.timestamp.clone(),
+                    });
+                    self.push_debug_event(
+                        DebugLogLevel::Info,
+                        "automation::mqtt",
+                        format!("{}: {}", message.topic, message.payload),
+                    );
+                    for listener in &self.automation.event_automation.listeners {
+                        if listener.enabled
+                            && listener.event == ListenerEventKind::MqttMessage
+                            && mqtt::topic_matches(&listener.condition, &message.topic)
+                            && !fired.contains(&listener.id)
+                        {
+                            fired.push(listener.id);
+                        }
+                    }
+                    self.mqtt.record(message);
+                }
+                MqttEvent::Published(Ok(topic)) => {
+                    self.mqtt.status = Some(format!("Publicado en {}.", topic));
+                }
+                MqttEvent::Published(Err(err)) => {
+                    self.mqtt.status = Some(err.clone());
+                    self.push_activity_log(LogStatus::Warning, "MQTT", err);
+                }
+                MqttEvent::Error(err) => {
+                    self.mqtt.connected = false;
+                    self.mqtt.status = Some(err.clone());
+                    self.push_debug_event(DebugLogLevel::Warning, "mqtt", err);
+                }
+            }
+        }
+        for listener_id in fired {
+            self.fire_listener(listener_id);
+        }
+        true
+    }
+
+    /// Lanza el OCR de una imagen adjunta; su texto acompañará a los prompts como el de
+    /// los documentos, también para los modelos sin visión.
+    pub fn recognize_image_text(&mut self, path: PathBuf) {
+        self.ocr.start(self.config.ocr.clone(), path);
+    }
+
+    fn poll_ocr(&mut self) -> bool {
+        let finished = self.ocr.poll();
+        if finished.is_empty() {
+            return false;
+        }
+        for result in finished {
+            let file = result
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| result.path.display().to_string());
+            // La imagen pudo quitarse del compositor mientras se reconocía.
+            if !self
+                .attachments
+                .items
+                .iter()
+                .any(|item| item.path == result.path)
+            {
+                continue;
+            }
+            match result.text {
+                Ok(text) if !text.is_empty() => {
+                    let chunks = ocr::chunk_text(&text);
+                    self.push_activity_log(
+                        LogStatus::Ok,
+                        "OCR",
+                        format!(
+                            "{}: {} caracteres reconocidos en {} fragmentos.",
+                            file,
+                            text.chars().count(),
+                            chunks.len()
+                        ),
+                    );
+                    self.attachments.documents.insert(result.path, (1, chunks));
+                }
+                Ok(_) => {
+                    self.ocr.status = Some(format!("No se reconoció texto en {}.", file));
+                }
+                Err(err) => {
+                    self.ocr.status = Some(format!("No se pudo reconocer {}: {}", file, err));
+                    self.push_activity_log(
+                        LogStatus::Error,
+                        "OCR",
+                        format!("Falló el OCR de {}: {}", file, err),
+                    );
+                    self.toasts.push(
+                        LogStatus::Error,
+                        format!("No se pudo reconocer el texto de {}.", file),
+                    );
+                }
+            }
+        }
+        true
+    }
+
+    /// Proveedor y modelo con visión para describir imágenes. Se prueban los proveedores
+    /// en el orden de las reglas de enrutado y se usa el modelo por defecto si su ficha
+    /// del catálogo es multimodal, o la primera ficha multimodal del proveedor si no.
+    pub fn vision_provider(&self) -> Option<(RemoteProviderKind, String)> {
+        let mut providers: Vec<RemoteProviderKind> = Vec::new();
+        for provider in self
+            .chat_routing
+            .suggestions
+            .iter()
+            .map(|suggestion| suggestion.provider)
+            .chain([RemoteProviderKind::Anthropic, RemoteProviderKind::OpenAi])
+        {
+            if !providers.contains(&provider) {
+                providers.push(provider);
+            }
+        }
+
+        providers.into_iter().find_map(|provider| {
+            provider.vision_caller()?;
+            let profile = self.provider_call_profile(provider);
+            profile.api_key.as_ref()?;
+            let cards = self.resources.remote_catalog.cards_for(provider);
+            let default_is_multimodal = cards
+                .iter()
+                .find(|card| card.key.id == profile.model)
+                .is_some_and(|card| card.multimodal);
+            if default_is_multimodal {
+                return Some((provider, profile.model));
+            }
+            cards
+                .iter()
+                .find(|card| card.multimodal)
+                .map(|card| (provider, card.key.id.clone()))
+        })
+    }
+
+    /// Envía la imagen a un modelo con visión y publica la descripción en la conversación
+    /// como respuesta del proveedor. Devuelve el proveedor y modelo elegidos.
+    pub fn describe_image(&mut self, path: &Path) -> Result<String, String> {
+        let Some(media_type) = attachments::image_media_type(path) else {
+            return Err(format!(
+                "{} no es una imagen PNG, JPEG, GIF o WebP.",
+                path.display()
+            ));
+        };
+        let metadata = std::fs::metadata(path)
+            .map_err(|err| format!("No se pudo leer {}: {}", path.display(), err))?;
+        if metadata.len() > attachments::MAX_IMAGE_BYTES {
+            return Err(format!(
+                "La imagen ocupa {} y el máximo admitido es {}.",
+                format_bytes(metadata.len()),
+                format_bytes(attachments::MAX_IMAGE_BYTES)
+            ));
+        }
+        let Some((provider_kind, model)) = self.vision_provider() else {
+            return Err("Ningún proveedor configurado tiene un modelo con visión. Añade la API key de Anthropic u OpenAI y marca un modelo multimodal en la galería.".to_string());
+        };
+        if self.is_offline() {
+            return Err("Sin conexión: no se puede describir la imagen ahora.".to_string());
+        }
+        let image = std::fs::read(path)
+            .map_err(|err| format!("No se pudo leer {}: {}", path.display(), err))?;
+
+        let ProviderCallProfile {
+            alias,
+            provider_name,
+            api_key,
+            ..
+        } = self.provider_call_profile(provider_kind);
+        let (Some(key), Some(caller)) = (api_key, provider_kind.vision_caller()) else {
+            return Err(format!("Falta la API key para {}.", provider_name));
+        };
+        let prompt = attachments::DESCRIBE_PROMPT;
+        if let Err(exceeded) =
+            self.resources
+                .try_acquire_provider_quota(provider_kind, &alias, prompt, &model)
+        {
+            return Err(format!(
+                "Se alcanzó el límite diario ({}/{}) para {}.",
+                exceeded.used, exceeded.limit, provider_name
+            ));
+        }
+
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.display().to_string());
+        self.chat.messages.push(ChatMessage::pending(
+            alias.clone(),
+            format!("Describiendo {} con {}…", file_name, model),
+            Some(provider_kind),
+        ));
+        let call_id = self.chat.next_provider_call_id;
+        self.chat.next_provider_call_id += 1;
+        let ticket = ProviderCallTicket {
+            id: call_id,
+            provider_kind,
+            provider_name: provider_name.to_string(),
+            alias,
+            model: model.clone(),
+            message_index: self.chat.messages.len() - 1,
+        };
+        self.chat.pending_provider_calls.push(PendingProviderCall {
+            ticket,
+            structured: None,
+            prompt_tokens: self.tokenizers.for_provider(provider_kind).count(prompt),
+            conversation: self.bookmarks.conversation.clone(),
+            started_at: Instant::now(),
+            web_search_tool: false,
+        });
+        self.push_activity_log(
+            LogStatus::Running,
+            provider_name,
+            format!("Describiendo la imagen {} con '{}'.", file_name, model),
+        );
+
+        let tx = self.chat.provider_response_tx.clone();
+        let summary = format!("{} ({})", provider_name, model);
+        self.jobs.submit_provider_call(
+            provider_kind.short_code(),
+            format!("Imagen · {}", file_name),
+            call_id,
+            move || {
+                let outcome = caller(&key, &model, &image, media_type, prompt)
+                    .map_err(ProviderError::from_anyhow);
+                let _ = tx.send(ProviderResponse {
+                    id: call_id,
+                    outcome,
+                    cached: false,
+                });
+            },
+        );
+        Ok(summary)
+    }
+
+    pub fn set_embedding_backend(&mut self, backend: EmbeddingBackend) {
+        if self.config.embeddings.backend == backend {
+            return;
+        }
+        self.config.embeddings.backend = backend;
+        self.embeddings.index.clear();
+        self.embeddings.search_query.clear();
+        self.embeddings.search_hits.clear();
+        self.embeddings.status = None;
+        self.persist_config();
+    }
+
+    pub fn jarvis_knowledge_dirty(&self) -> bool {
+        self.resources.jarvis_knowledge != self.config.jarvis.knowledge_base
+    }
+
+    /// Guarda la base de conocimientos editada y la recarga en el runtime activo sin
+    /// necesidad de reiniciar Jarvis.
+    pub fn apply_jarvis_knowledge(&mut self) -> Result<usize, String> {
+        let mut entries = self.resources.jarvis_knowledge.clone();
+        for entry in &mut entries {
+            entry.label = entry.label.trim().to_string();
+            entry.triggers = entry
+                .normalized_triggers()
+                .into_iter()
+                .map(str::to_string)
+                .collect();
+            entry.sources = entry
+                .sources
+                .iter()
+                .map(|source| source.trim().to_string())
+                .filter(|source| !source.is_empty())
+                .collect();
+            entry.response = entry.response.trim().to_string();
+        }
+        if entries.iter().any(|entry| entry.label.is_empty()) {
+            return Err("Todas las entradas necesitan un nombre.".to_string());
+        }
+        if let Some(entry) = entries.iter().find(|entry| entry.triggers.is_empty()) {
+            return Err(format!(
+                "La entrada '{}' necesita al menos una frase de activación.",
+                entry.label
+            ));
+        }
+
+        self.resources.jarvis_knowledge = entries.clone();
+        self.config.jarvis.knowledge_base = entries;
+        self.persist_config();
+
+        let total = self.config.jarvis.knowledge_base.len();
+        let message = match self.resources.jarvis_runtime.as_mut() {
+            Some(runtime) => {
+                let indexed = runtime.reload_knowledge(&self.config.jarvis.knowledge_base);
+                format!(
+                    "Base de conocimientos recargada en {} ({} de {} entradas indexadas).",
+                    runtime.model_label(),
+                    indexed,
+                    total
+                )
+            }
+            None => format!(
+                "Base de conocimientos guardada ({} entradas); se cargará con el próximo modelo.",
+                total
+            ),
+        };
+        self.push_activity_log(LogStatus::Ok, "Jarvis", message.clone());
+        self.resources.jarvis_knowledge_status = Some(message);
+        Ok(total)
+    }
+
+    pub fn discard_jarvis_knowledge_changes(&mut self) {
+        self.resources.jarvis_knowledge = self.config.jarvis.knowledge_base.clone();
+        self.resources.jarvis_knowledge_selected = None;
+        self.resources.jarvis_knowledge_status = None;
+    }
+
+    /// Calcula a qué entrada de la base editada se enrutaría el prompt de prueba.
+    pub fn probe_jarvis_knowledge(&mut self) {
+        let prompt = self.resources.jarvis_knowledge_probe.trim().to_string();
+        if prompt.is_empty() {
+            self.resources.jarvis_knowledge_matches.clear();
+            return;
+        }
+
+        let runtime = self.resources.jarvis_runtime.as_ref();
+        let semantic = runtime.is_some();
+        match JarvisRuntime::preview_routing(runtime, &self.resources.jarvis_knowledge, &prompt) {
+            Ok(matches) => {
+                let winner = matches
+                    .first()
+                    .filter(|best| best.is_routed())
+                    .map(|best| best.label.clone());
+                let mut status = match winner {
+                    Some(label) => format!("El prompt se enrutaría a '{}'.", label),
+                    None => {
+                        "Ninguna entrada supera el umbral; Jarvis respondería de forma genérica."
+                            .to_string()
+                    }
+                };
+                if !semantic {
+                    status.push_str(
+                        " Sin modelo cargado se usa la coincidencia por palabras clave del modo degradado.",
+                    );
+                }
+                self.resources.jarvis_knowledge_matches = matches;
+                self.resources.jarvis_knowledge_status = Some(status);
+            }
+            Err(err) => {
+                self.resources.jarvis_knowledge_matches.clear();
+                self.resources.jarvis_knowledge_status =
+                    Some(format!("No se pudo evaluar el prompt: {}", err));
+            }
+        }
+    }
+
+    pub fn generate_local_jarvis_reply(&mut self, prompt: &str) -> Result<String, String> {
+        self.generate_local_reply(None, prompt)
+    }
+
+    /// Genera la respuesta con `model` o, si es `None` o el modelo activo, con el runtime
+    /// principal de Jarvis.
+    fn generate_local_reply(
+        &mut self,
+        model: Option<&LocalModelIdentifier>,
+        prompt: &str,
+    ) -> Result<String, String> {
+        if self.resources.system_monitor.generation_paused {
+            return Err(format!(
+                "La generación local está en pausa porque se superó el límite de memoria de {:.1} GB.",
+                self.resource_memory_limit_gb
+            ));
+        }
+        let prompt = self.apply_safety_filters(safety::FilterStage::Prompt, prompt.to_string())?;
+        let prompt = prompt.as_str();
+        self.push_activity_log(
+            LogStatus::Running,
+            "Jarvis",
+            format!(
+                "Procesando entrada local de {} caracteres.",
+                prompt.chars().count()
+            ),
+        );
+
+        let runtime = match model {
+            Some(model) if self.resources.jarvis_active_model.as_ref() != Some(model) => {
+                self.ensure_pooled_runtime(model)
+            }
+            _ => self.ensure_jarvis_runtime(),
+        };
+        match runtime {
+            Ok(runtime) => {
+                let label = runtime.model_label();
+                let precision = runtime.precision().map(str::to_string);
+                let (result, metrics) = runtime.generate_reply_measured(prompt);
+                let counter = self.tokenizers.jarvis();
+                let prompt_tokens = counter.count(prompt);
+                let response_tokens = result.as_ref().map_or(0, |reply| counter.count(reply));
+                if let Ok(reply) = &result {
+                    self.resources
+                        .jarvis_performance
+                        .record_call(JarvisCallSample {
+                            timestamp: performance::now(),
+                            model: label.clone(),
+                            precision,
+                            duration_ms: metrics.duration.as_secs_f64() * 1000.0,
+                            prompt_chars: prompt.chars().count(),
+                            prompt_tokens,
+                            response_chars: reply.chars().count(),
+                            response_tokens,
+                            memory_bytes: metrics.memory_bytes,
+                        });
+                }
+                self.record_provider_usage(
+                    "Jarvis",
+                    prompt_tokens,
+                    response_tokens,
+                    Some(metrics.duration.as_millis().min(u32::MAX as u128) as u32),
+                    result.is_err(),
+                );
+                match result {
+                    Ok(reply) => {
+                        self.resources.jarvis_status =
+                            Some(format!("Jarvis responde con el modelo {}.", label));
+                        self.push_activity_log(
+                            LogStatus::Ok,
+                            "Jarvis",
+                            format!("Respuesta generada por {}", label),
+                        );
+                        self.apply_safety_filters(safety::FilterStage::Response, reply)
+                    }
+                    Err(err) => {
+                        self.resources.jarvis_status = Some(format!(
+                            "Jarvis falló al generar respuesta ({label}): {}",
+                            err
+                        ));
+                        self.push_activity_log(
+                            LogStatus::Error,
+                            "Jarvis",
+                            format!("Error al generar respuesta con {}: {}", label, err),
+                        );
+                        Err(err.to_string())
+                    }
+                }
+            }
+            Err(err) => {
+                self.resources.jarvis_status = Some(format!("Jarvis no está listo: {}", err));
+                self.push_activity_log(
+                    LogStatus::Error,
+                    "Jarvis",
+                    format!("Runtime inalcanzable: {}", err),
+                );
+                Err(err.to_string())
+            }
+        }
+    }
+
+    pub fn respond_with_jarvis(&mut self, prompt: String) {
+        let mut orchestrator = JarvisOrchestrator::new(self);
+        orchestrator.execute(prompt);
+    }
+
+    fn provider_alias_display(alias: &str, fallback: &str) -> String {
+        let trimmed = alias.trim();
+        if trimmed.is_empty() {
+            fallback.to_string()
+        } else {
+            trimmed.to_string()
+        }
+    }
+
+    fn parse_provider_mentions(&self, input: &str) -> (Vec<(RemoteProviderKind, String)>, String) {
+        #[derive(Clone, Copy)]
+        struct RawMention {
+            kind: RemoteProviderKind,
+            start: usize,
+            alias_end: usize,
+        }
+
+        let alias_entries: Vec<(RemoteProviderKind, String)> = vec![
+            (
+                RemoteProviderKind::Anthropic,
+                Self::provider_alias_display(&self.resources.claude_alias, "claude"),
+            ),
+            (
+                RemoteProviderKind::OpenAi,
+                Self::provider_alias_display(&self.resources.openai_alias, "openai"),
+            ),
+            (
+                RemoteProviderKind::Groq,
+                Self::provider_alias_display(&self.resources.groq_alias, "groq"),
+            ),
+        ]
+        .into_iter()
+        .map(|(kind, alias)| {
+            let sanitized = alias.trim().trim_start_matches('@').to_lowercase();
+            (kind, sanitized)
+        })
+        .filter(|(_, alias)| !alias.is_empty())
+        .collect();
+
+        if alias_entries.is_empty() {
+            return (Vec::new(), input.trim().to_string());
+        }
+
+        let mut raw_mentions = Vec::new();
+        let mut index = 0usize;
+        let bytes = input.as_bytes();
+        while index < bytes.len() {
+            let rest = &input[index..];
+            let mut chars = rest.chars();
+            let Some(ch) = chars.next() else {
+                break;
+            };
+
+            if ch != '@' {
+                index += ch.len_utf8();
+                continue;
+            }
+
+            let alias_slice = &rest[ch.len_utf8()..];
+            let mut alias = String::new();
+            let mut consumed = 0usize;
+            for (offset, next) in alias_slice.char_indices() {
+                if next.is_alphanumeric() || matches!(next, '_' | '-') {
+                    alias.push(next);
+                    consumed = offset + next.len_utf8();
+                } else {
+                    break;
+                }
+            }
+
+            if alias.is_empty() {
+                index += ch.len_utf8();
+                continue;
+            }
+
+            let alias_lower = alias.to_lowercase();
+            if let Some((kind, _)) = alias_entries
+                .iter()
+                .find(|(_, candidate)| *candidate == alias_lower)
+            {
+                let alias_end = index + ch.len_utf8() + consumed;
+                raw_mentions.push(RawMention {
+                    kind: *kind,
+                    start: index,
+                    alias_end,
+                });
+                index = alias_end;
+            } else {
+                index += ch.len_utf8();
+            }
+        }
+
+        if raw_mentions.is_empty() {
+            return (Vec::new(), input.trim().to_string());
+        }
+
+        let mut mentions = Vec::new();
+        let mut consumed_ranges: Vec<(usize, usize)> = Vec::new();
+
+        for (idx, raw) in raw_mentions.iter().enumerate() {
+            let mut prompt_start = raw.alias_end;
+            if prompt_start >= input.len() {
+                continue;
+            }
+
+            while let Some(ch) = input[prompt_start..].chars().next() {
+                if ch.is_whitespace() || matches!(ch, ':' | ',' | '-') {
+                    prompt_start += ch.len_utf8();
+                } else {
+                    break;
+                }
+
+                if prompt_start >= input.len() {
+                    break;
+                }
+            }
+
+            if prompt_start >= input.len() {
+                continue;
+            }
+
+            let next_start = raw_mentions
+                .get(idx + 1)
+                .map(|next| next.start)
+                .unwrap_or_else(|| input.len());
+
+            if prompt_start >= next_start {
+                continue;
+            }
+
+            let prompt_slice = input[prompt_start..next_start].trim();
+            if prompt_slice.is_empty() {
+                continue;
+            }
+
+            mentions.push((raw.kind, prompt_slice.to_string()));
+            consumed_ranges.push((raw.start, next_start));
+        }
+
+        if consumed_ranges.is_empty() {
+            return (Vec::new(), input.trim().to_string());
+        }
+
+        consumed_ranges.sort_by_key(|(start, _)| *start);
+        let mut residual = String::new();
+        let mut cursor = 0usize;
+        for (start, end) in consumed_ranges {
+            if start > cursor {
+                residual.push_str(&input[cursor..start]);
+            }
+            cursor = end;
+        }
+
+        if cursor < input.len() {
+            residual.push_str(&input[cursor..]);
+        }
+
+        (mentions, residual.trim().to_string())
+    }
+
+    fn format_provider_list(names: &[String]) -> String {
+        match names.len() {
+            0 => String::new(),
+            1 => names[0].clone(),
+            2 => format!("{} y {}", names[0], names[1]),
+            _ => {
+                let mut result = names[..names.len() - 1].join(", ");
+                result.push_str(&format!(" y {}", names.last().unwrap()));
+                result
+            }
+        }
+    }
+
+    fn extract_alias_prompt(alias: &str, input: &str) -> Option<String> {
+        let alias_trimmed = alias.trim();
+        if alias_trimmed.is_empty() {
+            return None;
+        }
+
+        let mut patterns = Vec::new();
+        patterns.push(alias_trimmed.to_string());
+        if !alias_trimmed.starts_with('@') {
+            patterns.push(format!("@{}", alias_trimmed));
+        } else {
+            patterns.push(alias_trimmed.trim_start_matches('@').to_string());
+        }
+
+        for pattern in patterns
+            .into_iter()
+            .filter(|candidate| !candidate.trim().is_empty())
+        {
+            if let Some(remainder) = Self::match_alias_pattern(&pattern, input) {
+                return Some(remainder);
+            }
+        }
+
+        None
+    }
+
+    fn match_alias_pattern(pattern: &str, input: &str) -> Option<String> {
+        let input_trimmed = input.trim_start();
+        let pattern_chars: Vec<char> = pattern.chars().collect();
+        let mut input_iter = input_trimmed.chars();
+
+        for alias_ch in &pattern_chars {
+            match input_iter.next() {
+                Some(user_ch) if user_ch.eq_ignore_ascii_case(alias_ch) => {}
+                _ => return None,
+            }
+        }
+
+        let pattern_bytes: usize = pattern_chars.iter().map(|c| c.len_utf8()).sum();
+        if input_trimmed.len() < pattern_bytes {
+            return None;
+        }
+
+        let mut remainder = input_trimmed[pattern_bytes..].trim_start();
+        remainder = remainder.trim_start_matches(|c: char| matches!(c, ':' | ','));
+        remainder = remainder.trim_start();
+
+        if remainder.is_empty() {
+            None
+        } else {
+            Some(remainder.to_string())
+        }
+    }
+
+    fn provider_call_profile(&self, provider_kind: RemoteProviderKind) -> ProviderCallProfile {
+        let (alias, provider_name, api_key, model, caller): (
+            String,
+            &'static str,
+            Option<String>,
+            String,
+            ProviderCaller,
+        ) = match provider_kind {
+            RemoteProviderKind::Anthropic => (
+                Self::provider_alias_display(&self.resources.claude_alias, "claude"),
+                "Anthropic",
+                self.config.anthropic.api_key.clone(),
+                self.resources.claude_default_model.clone(),
+                crate::api::claude::send_message_with,
+            ),
+            RemoteProviderKind::OpenAi => (
+                Self::provider_alias_display(&self.resources.openai_alias, "openai"),
+                "OpenAI",
+                self.config.openai.api_key.clone(),
+                self.resources.openai_default_model.clone(),
+                crate::api::openai::send_message_with,
+            ),
+            RemoteProviderKind::Groq => (
+                Self::provider_alias_display(&self.resources.groq_alias, "groq"),
+                "Groq",
+                self.config.groq.api_key.clone(),
+                self.resources.groq_default_model.clone(),
+                crate::api::groq::send_message_with,
+            ),
+        };
+
+        // Una acción rápida del catálogo fija el modelo del siguiente mensaje enrutado.
+        // Si no, manda el modelo fijado en los ajustes de la conversación.
+        let model = match &self.chat.model_override {
+            Some(key) if key.provider == provider_kind => key.id.clone(),
+            _ => self
+                .conversations
+                .get(&self.bookmarks.conversation)
+                .filter(|defaults| defaults.provider_kind() == Some(provider_kind))
+                .and_then(|defaults| defaults.model.clone())
+                .unwrap_or(model),
+        };
+
+        let api_key = api_key.and_then(|k| {
+            let trimmed = k.trim();
+            if trimmed.is_empty() {
+                None
+            } else {
+                Some(trimmed.to_string())
+            }
+        });
+
+        ProviderCallProfile {
+            alias,
+            provider_name,
+            api_key,
+            model,
+            caller,
+        }
+    }
+
+    /// Lanza la llamada al proveedor. Si `target_index` apunta a un mensaje existente,
+    /// la respuesta pendiente lo reemplaza en lugar de añadirse al final del historial.
+    fn handle_provider_call(
+        &mut self,
+        provider_kind: RemoteProviderKind,
+        prompt: String,
+        target_index: Option<usize>,
+    ) -> ProviderCallDispatch {
+        let ProviderCallProfile {
+            alias,
+            provider_name,
+            api_key,
+            model,
+            caller,
+        } = self.provider_call_profile(provider_kind);
+
+        let history_end = target_index
+            .filter(|index| *index < self.chat.messages.len())
+            .unwrap_or(self.chat.messages.len());
+        let local_projects = self.local_only_projects_in(provider_kind, &prompt, history_end, true);
+        if !local_projects.is_empty() {
+            let list = local_projects.join(", ");
+            if !self.chat.local_only_override {
+                self.chat.messages.push(ChatMessage::system(format!(
+                    "No se envió la solicitud a '{}': el contexto incluye contenido de {} y está marcado como solo local. Pregunta a Jarvis o confirma el envío desde el compositor.",
+                    alias, list
+                )));
+                self.push_activity_log(
+                    LogStatus::Warning,
+                    provider_name,
+                    format!("Envío bloqueado por contexto solo local ({}).", list),
+                );
+                return ProviderCallDispatch::LocalOnly {
+                    provider_kind,
+                    provider_name: provider_name.to_string(),
+                    alias,
+                    projects: local_projects,
+                };
+            }
+            self.push_activity_log(
+                LogStatus::Warning,
+                provider_name,
+                format!(
+                    "Envío confirmado con contexto de proyectos solo locales ({}).",
+                    list
+                ),
+            );
+        }
+
+        if api_key.is_some() && self.is_offline() {
+            return self.hold_offline_provider_call(
+                provider_kind,
+                provider_name,
+                alias,
+                prompt,
+                target_index,
+            );
+        }
+
+        if let Some(key) = api_key {
+            let config = self.remote_provider_config_mut(provider_kind);
+            if let Some(exhausted) = budget::exhausted(&config.budget, &mut config.spend) {
+                let reason = format!(
+                    "Presupuesto {} de {} agotado (${:.2} de ${:.2}).",
+                    exhausted.period.label(),
+                    provider_name,
+                    exhausted.spent,
+                    exhausted.limit
+                );
+                self.chat.messages.push(ChatMessage::system(format!(
+                    "{} No se envió la solicitud a '{}'. Amplía el presupuesto o desactiva el bloqueo en Preferencias › {} › Uso.",
+                    reason, alias, provider_name
+                )));
+                self.push_activity_log(LogStatus::Warning, provider_name, reason);
+                return ProviderCallDispatch::BudgetExhausted {
+                    provider_kind,
+                    provider_name: provider_name.to_string(),
+                    alias,
+                    period: exhausted.period,
+                    spent: exhausted.spent,
+                    limit: exhausted.limit,
+                };
+            }
+
+            match self
+                .resources
+                .try_acquire_provider_quota(provider_kind, &alias, &prompt, &model)
+            {
+                Ok(usage) => {
+                    let mut status = format!("Consultando '{}' con el alias '{}'.", model, alias);
+                    if let Some(limit) = usage.limit {
+                        status.push_str(&format!(" Uso registrado: {}/{}.", usage.used, limit));
+                    }
+                    self.push_activity_log(LogStatus::Running, provider_name, status);
+
+                    let structured = self.take_structured_request(&prompt);
+                    // La segunda llamada, con los resultados de la búsqueda que pidió el
+                    // proveedor, ya no ofrece la herramienta. Tampoco se ofrece a las
+                    // peticiones estructuradas, que deben responder con JSON.
+                    let tool_context = self.web_search.pending_tool_context.take();
+                    let web_search_tool = tool_context.is_none()
+                        && structured.is_none()
+                        && self.web_search_tool_available();
+                    let mut knowledge = self
+                        .attachments
+                        .document_context(&prompt, documents::ATTACHMENT_CONTEXT_CHUNKS);
+                    knowledge.extend(self.web_search.attached_context());
+                    knowledge.extend(tool_context.unwrap_or_default());
+                    let options = self
+                        .effective_conversation_defaults()
+                        .generation_options_for(&prompt);
+
+                    let pending = ChatMessage::pending(
+                        alias.clone(),
+                        format!("Esperando respuesta de {}…", provider_name),
+                        Some(provider_kind),
+                    )
+                    .with_source_prompt(prompt.clone());
+                    let message_index = match target_index {
+                        Some(index) if index < self.chat.messages.len() => {
+                            self.chat.messages[index] = pending;
+                            index
+                        }
+                        _ => {
+                            self.chat.messages.push(pending);
+                            self.chat.messages.len() - 1
+                        }
+                    };
+
+                    let call_id = self.chat.next_provider_call_id;
+                    self.chat.next_provider_call_id += 1;
+
+                    let ticket = ProviderCallTicket {
+                        id: call_id,
+                        provider_kind,
+                        provider_name: provider_name.to_string(),
+                        alias: alias.clone(),
+                        model: model.clone(),
+                        message_index,
+                    };
+
+                    self.chat.pending_provider_calls.push(PendingProviderCall {
+                        ticket: ticket.clone(),
+                        structured,
+                        prompt_tokens: 0,
+                        conversation: self.bookmarks.conversation.clone(),
+                        started_at: Instant::now(),
+                        web_search_tool,
+                    });
+
+                    let cache = if self.chat.bypass_response_cache {
+                        None
+                    } else {
+                        self.response_cache.handle(&self.config)
+                    };
+                    let wants_recall = self.wants_recall(&prompt);
+                    let request = ProviderRequest {
+                        ticket: ticket.clone(),
+                        key,
+                        caller,
+                        prompt,
+                        history_end,
+                        knowledge,
+                        options,
+                        cache,
+                    };
+                    // Los recuerdos necesitan el vector del prompt: la llamada espera a que
+                    // se calcule en segundo plano y sale desde `poll_embeddings`.
+                    if wants_recall {
+                        self.embeddings.request(
+                            EmbeddingPurpose::Recall(call_id),
+                            Some(request.prompt.clone()),
+                        );
+                        self.chat.recall_waiting.push(request);
+                    } else {
+                        self.send_provider_request(request, None);
+                    }
+
+                    ProviderCallDispatch::Pending(ticket)
+                }
+                Err(ProviderQuotaExceeded {
+                    limit,
+                    used,
+                    alias: exceeded_alias,
+                    model: exceeded_model,
+                    created_at,
+                    ..
+                }) => {
+                    self.chat.messages.push(ChatMessage::system(format!(
+                        "Se alcanzó el límite diario ({}/{}) para {}. La solicitud '{}' ({}) se diferirá.",
+                        used,
+                        limit,
+                        provider_name,
+                        exceeded_alias,
+                        exceeded_model
+                    )));
+                    self.push_activity_log(
+                        LogStatus::Warning,
+                        provider_name,
+                        format!(
+                            "Límite diario ({}/{}) alcanzado; solicitud diferida para '{}'.",
+                            used, limit, model
+                        ),
+                    );
+
+                    ProviderCallDispatch::Deferred {
+                        provider_kind,
+                        provider_name: provider_name.to_string(),
+                        alias,
+                        model,
+                        limit,
+                        used,
+                        created_at,
+                    }
                 }
             }
+        } else {
+            self.chat.messages.push(ChatMessage::system(format!(
+                "Configura la API key de {} antes de usar el alias '{}'.",
+                provider_name, alias
+            )));
+            *self.provider_status_slot(provider_kind) =
+                Some(format!("Falta la API key para {}.", provider_name));
+            ProviderCallDispatch::MissingCredentials {
+                provider_kind,
+                provider_name: provider_name.to_string(),
+                alias,
+            }
         }
+    }
 
-        self.resources
-            .jarvis_active_model
-            .as_ref()
-            .map(|model| self.jarvis_model_directory_for(model))
+    /// Retoma la llamada `call_id`, que esperaba el vector de su prompt. Si no se pudo
+    /// calcular, sale sin recuerdos.
+    fn resume_recall_call(&mut self, call_id: u64, outcome: Result<usize, String>) {
+        let Some(position) = self
+            .chat
+            .recall_waiting
+            .iter()
+            .position(|request| request.ticket.id == call_id)
+        else {
+            return;
+        };
+        let request = self.chat.recall_waiting.remove(position);
+        let query = match outcome {
+            Ok(_) => {
+                let signature = self.embedding_signature();
+                self.embeddings
+                    .query_vector(&signature, &request.prompt)
+                    .cloned()
+            }
+            Err(err) => {
+                self.push_debug_event(
+                    DebugLogLevel::Warning,
+                    "embeddings::memory",
+                    format!("No se pudieron recuperar recuerdos: {}", err),
+                );
+                None
+            }
+        };
+        self.send_provider_request(request, query);
     }
 
-    fn jarvis_model_directory_for(&self, model: &LocalModelIdentifier) -> PathBuf {
-        if let Some(record) = self.installed_model(model) {
-            let path = Path::new(&record.install_path);
-            if path.is_dir() {
-                return path.to_path_buf();
+    /// Compone el prompt de `request` con el historial, los recuerdos parecidos a `query`
+    /// y el contexto adjunto, y lo pone en la cola. Una llamada cancelada mientras
+    /// esperaba sus recuerdos ya no sale.
+    fn send_provider_request(&mut self, request: ProviderRequest, query: Option<Vec<f32>>) {
+        let ProviderRequest {
+            ticket,
+            key,
+            caller,
+            prompt,
+            history_end,
+            knowledge,
+            options,
+            cache,
+        } = request;
+        let Some(pending) = self
+            .chat
+            .pending_provider_calls
+            .iter()
+            .find(|pending| pending.ticket.id == ticket.id)
+        else {
+            return;
+        };
+        let instructions = pending
+            .structured
+            .as_ref()
+            .filter(|request| request.attempt == 0)
+            .map(StructuredRequest::instructions);
+        let wants_native_json = pending
+            .structured
+            .as_ref()
+            .is_some_and(StructuredRequest::wants_native_json);
+        let web_search_tool = pending.web_search_tool;
+        let provider_kind = ticket.provider_kind;
+        let provider_name = ticket.provider_name.clone();
+        let model = ticket.model.clone();
+        let call_id = ticket.id;
+        let history_end = history_end.min(self.chat.messages.len());
+
+        let counter = self.tokenizers.for_provider(provider_kind);
+        let mut window = context::build_context_window(
+            &self.chat.messages[..history_end],
+            &self.config.context,
+            counter,
+            true,
+            &self.context_summaries,
+        );
+        if let Some(query) = &query {
+            window.recalled = self
+                .recall_relevant_messages(query, history_end)
+                .into_iter()
+                .filter(|line| !window.head.contains(line) && !window.tail.contains(line))
+                .collect();
+            window.knowledge = self.recall_project_knowledge(query);
+        }
+        window.knowledge.extend(knowledge);
+        let mut request_prompt = window.render(&prompt);
+        if let Some(instructions) = instructions {
+            request_prompt.push_str("\n\n");
+            request_prompt.push_str(&instructions);
+        }
+        if web_search_tool {
+            request_prompt.push_str("\n\n");
+            request_prompt.push_str(&web_search::tool_instructions());
+        }
+        let request_prompt = self.redact_outgoing(&request_prompt, &provider_name);
+        let counter = self.tokenizers.for_provider(provider_kind);
+        let native_json = wants_native_json && provider_kind.json_caller().is_some();
+        let caller = match provider_kind.json_caller() {
+            Some(json_caller) if native_json => json_caller,
+            _ => caller,
+        };
+        let request_tokens = counter.count(&request_prompt);
+        let history_tokens = window.history_tokens(counter);
+        self.resources
+            .usage_state_mut(provider_kind)
+            .record_prompt_tokens(request_tokens);
+        if let Some(limit) = self.model_context_limit(provider_kind, &model) {
+            if request_tokens > limit as usize {
+                self.push_activity_log(
+                    LogStatus::Warning,
+                    provider_name.clone(),
+                    format!(
+                        "El prompt (~{} tokens) supera el contexto de '{}' ({} tokens).",
+                        request_tokens, model, limit
+                    ),
+                );
             }
         }
+        if !window.is_empty() {
+            self.push_debug_event(
+                DebugLogLevel::Info,
+                format!("providers::{}", provider_kind.short_code()),
+                format!(
+                    "Contexto adjunto: {} mensajes, {} omitidos (~{} tokens)",
+                    window.included_messages(),
+                    window.omitted,
+                    history_tokens
+                ),
+            );
+        }
+        if let Some(pending) = self
+            .chat
+            .pending_provider_calls
+            .iter_mut()
+            .find(|pending| pending.ticket.id == call_id)
+        {
+            pending.prompt_tokens = request_tokens;
+            pending.started_at = Instant::now();
+        }
+
+        let mut parameters = provider_kind.request_parameters(&options);
+        if native_json {
+            parameters.push_str(";response_format=json_object");
+        }
+        let cache_key = ResponseCache::key(provider_kind, &model, &request_prompt, &parameters);
+        let tx = self.chat.provider_response_tx.clone();
+        self.jobs.submit_provider_call(
+            provider_kind.short_code(),
+            format!("Chat · {}", ticket.alias),
+            call_id,
+            move || {
+                if let Some(text) = cache.as_ref().and_then(|cache| cache.get(&cache_key)) {
+                    let _ = tx.send(ProviderResponse {
+                        id: call_id,
+                        outcome: Ok(text),
+                        cached: true,
+                    });
+                    return;
+                }
+                let outcome = caller(&key, &model, &request_prompt, &options)
+                    .map_err(ProviderError::from_anyhow);
+                if let (Some(cache), Ok(text)) = (&cache, &outcome) {
+                    let _ = cache.put(&cache_key, provider_kind, &model, text);
+                }
+                let _ = tx.send(ProviderResponse {
+                    id: call_id,
+                    outcome,
+                    cached: false,
+                });
+            },
+        );
+    }
 
-        Path::new(&self.resources.jarvis_install_dir).join(model.sanitized_dir_name())
+    pub fn remote_provider_config(
+        &self,
+        provider: RemoteProviderKind,
+    ) -> &crate::config::ProviderConfig {
+        match provider {
+            RemoteProviderKind::Anthropic => &self.config.anthropic,
+            RemoteProviderKind::OpenAi => &self.config.openai,
+            RemoteProviderKind::Groq => &self.config.groq,
+        }
     }
 
-    pub fn ensure_jarvis_runtime(&mut self) -> anyhow::Result<&mut JarvisRuntime> {
-        let target_dir = self
-            .jarvis_model_directory()
-            .ok_or_else(|| anyhow::anyhow!("No hay un modelo local configurado para Jarvis."))?;
+    pub fn remote_provider_config_mut(
+        &mut self,
+        provider: RemoteProviderKind,
+    ) -> &mut crate::config::ProviderConfig {
+        match provider {
+            RemoteProviderKind::Anthropic => &mut self.config.anthropic,
+            RemoteProviderKind::OpenAi => &mut self.config.openai,
+            RemoteProviderKind::Groq => &mut self.config.groq,
+        }
+    }
 
-        let needs_reload = match &self.resources.jarvis_runtime {
-            Some(runtime) => !runtime.matches(&target_dir),
-            None => true,
+    /// Suma al presupuesto del proveedor el coste de una respuesta según los precios
+    /// del catálogo y avisa de los umbrales que se crucen.
+    fn record_provider_spend(
+        &mut self,
+        ticket: &ProviderCallTicket,
+        prompt_tokens: usize,
+        completion_tokens: usize,
+    ) {
+        let Some(card) = self
+            .resources
+            .remote_catalog
+            .cards_for(ticket.provider_kind)
+            .iter()
+            .find(|card| card.key.id == ticket.model)
+        else {
+            return;
         };
+        let cost = (prompt_tokens as f32 * card.input_cost_per_million
+            + completion_tokens as f32 * card.output_cost_per_million)
+            / 1_000_000.0;
+        if cost <= 0.0 {
+            return;
+        }
 
-        if needs_reload {
-            self.push_activity_log(
-                LogStatus::Running,
-                "Jarvis",
-                format!("Cargando modelo local desde {}", target_dir.display()),
+        let config = self.remote_provider_config_mut(ticket.provider_kind);
+        let alerts = budget::record_spend(&config.budget, &mut config.spend, cost);
+        for alert in alerts {
+            let message = format!(
+                "{} ha consumido el {}% del presupuesto {} (${:.2} de ${:.2}).",
+                ticket.provider_name,
+                alert.percent,
+                alert.period.label(),
+                alert.spent,
+                alert.limit
             );
-            let runtime = JarvisRuntime::load(
-                target_dir.clone(),
-                self.resources
-                    .jarvis_active_model
-                    .as_ref()
-                    .map(|model| model.model_id.clone()),
-            )?;
-            self.resources.jarvis_runtime = Some(runtime);
-            self.resources.jarvis_model_path = target_dir.display().to_string();
-            let loaded_label = self
-                .resources
-                .jarvis_runtime
-                .as_ref()
-                .map(|runtime| runtime.model_label());
-            if let Some(label) = loaded_label {
-                self.push_activity_log(
-                    LogStatus::Ok,
-                    "Jarvis",
-                    format!("Modelo {} listo para responder.", label),
-                );
-                self.resources.jarvis_status = Some(format!(
-                    "Jarvis cargó {} desde {}.",
-                    label, self.resources.jarvis_model_path
-                ));
-            }
+            let status = if alert.percent >= 100 {
+                LogStatus::Error
+            } else {
+                LogStatus::Warning
+            };
+            self.toasts.push(status, message.clone());
+            self.push_activity_log(status, ticket.provider_name.clone(), message);
         }
+        self.persist_config();
+    }
 
-        Ok(self
-            .resources
-            .jarvis_runtime
-            .as_mut()
-            .expect("runtime recién cargado"))
+    /// Indica si las llamadas remotas deben retenerse, ya sea por el modo sin conexión
+    /// manual o porque la última comprobación de red falló.
+    pub fn is_offline(&self) -> bool {
+        self.config.network.offline_mode || !self.connectivity.online
     }
 
-    pub fn generate_local_jarvis_reply(&mut self, prompt: &str) -> Result<String, String> {
+    pub fn set_offline_mode(&mut self, enabled: bool) {
+        if self.config.network.offline_mode == enabled {
+            return;
+        }
+        self.config.network.offline_mode = enabled;
+        self.network_draft.offline_mode = enabled;
+        self.persist_config();
         self.push_activity_log(
-            LogStatus::Running,
-            "Jarvis",
+            LogStatus::Warning,
+            "Red",
+            if enabled {
+                "Modo sin conexión activado: Jarvis atenderá las peticiones localmente."
+            } else {
+                "Modo sin conexión desactivado."
+            },
+        );
+        if !self.is_offline() {
+            self.replay_offline_queue();
+        }
+    }
+
+    /// Ocupación de la caché de respuestas, medida solo cuando ha podido cambiar.
+    pub fn response_cache_usage(&mut self) -> response_cache::ResponseCacheUsage {
+        if let Some(usage) = self.response_cache.usage {
+            return usage;
+        }
+        let usage = self.response_cache.cache(&self.config).usage();
+        self.response_cache.usage = Some(usage);
+        usage
+    }
+
+    pub fn clear_response_cache(&mut self) {
+        let removed = self.response_cache.cache(&self.config).clear();
+        self.response_cache.stats.reset();
+        self.response_cache.usage = None;
+        let message = format!("Caché de respuestas vaciada ({} entradas).", removed);
+        self.response_cache.status = Some(message.clone());
+        self.push_activity_log(LogStatus::Ok, "Caché", message);
+    }
+
+    pub fn set_offline_queueing(&mut self, enabled: bool) {
+        self.config.network.queue_offline_requests = enabled;
+        self.network_draft.queue_offline_requests = enabled;
+        self.persist_config();
+    }
+
+    fn hold_offline_provider_call(
+        &mut self,
+        provider_kind: RemoteProviderKind,
+        provider_name: &'static str,
+        alias: String,
+        prompt: String,
+        target_index: Option<usize>,
+    ) -> ProviderCallDispatch {
+        if !self.config.network.queue_offline_requests {
+            self.chat.messages.push(ChatMessage::system(format!(
+                "Sin conexión: no se envió la solicitud a {} (@{}).",
+                provider_name, alias
+            )));
+            *self.provider_status_slot(provider_kind) =
+                Some("Sin conexión: solicitud rechazada.".to_string());
+            return ProviderCallDispatch::Offline {
+                provider_kind,
+                provider_name: provider_name.to_string(),
+                alias,
+            };
+        }
+
+        let position = self.chat.offline_queue.len() + 1;
+        let mut notice = ChatMessage::system(format!(
+            "Sin conexión: la solicitud a {} (@{}) quedó en cola (#{}) y se enviará al recuperar la red.",
+            provider_name, alias, position
+        ))
+        .with_source_prompt(prompt.clone());
+        notice.origin = Some(provider_kind);
+        let message_index = match target_index {
+            Some(index) if index < self.chat.messages.len() => {
+                self.chat.messages[index] = notice;
+                index
+            }
+            _ => {
+                self.chat.messages.push(notice);
+                self.chat.messages.len() - 1
+            }
+        };
+
+        self.chat
+            .offline_queue
+            .retain(|queued| queued.message_index != message_index);
+        self.chat.offline_queue.push(QueuedProviderCall {
+            provider_kind,
+            prompt,
+            message_index,
+            queued_at: Local::now().format("%H:%M:%S").to_string(),
+        });
+        self.push_activity_log(
+            LogStatus::Warning,
+            provider_name,
             format!(
-                "Procesando entrada local de {} caracteres.",
-                prompt.chars().count()
+                "Sin conexión: solicitud para '{}' en cola (#{}).",
+                alias, position
             ),
         );
 
-        match self.ensure_jarvis_runtime() {
-            Ok(runtime) => {
-                let label = runtime.model_label();
-                match runtime.generate_reply(prompt) {
-                    Ok(reply) => {
-                        self.resources.jarvis_status =
-                            Some(format!("Jarvis responde con el modelo {}.", label));
-                        self.push_activity_log(
-                            LogStatus::Ok,
-                            "Jarvis",
-                            format!("Respuesta generada por {}", label),
-                        );
-                        Ok(reply)
-                    }
-                    Err(err) => {
-                        self.resources.jarvis_status = Some(format!(
-                            "Jarvis falló al generar respuesta ({label}): {}",
-                            err
-                        ));
-                        self.push_activity_log(
-                            LogStatus::Error,
-                            "Jarvis",
-                            format!("Error al generar respuesta con {}: {}", label, err),
-                        );
-                        Err(err.to_string())
-                    }
+        ProviderCallDispatch::Queued {
+            provider_kind,
+            provider_name: provider_name.to_string(),
+            alias,
+            position,
+        }
+    }
+
+    /// Reenvía en orden las solicitudes retenidas mientras no había conexión.
+    pub fn replay_offline_queue(&mut self) -> usize {
+        if self.is_offline() || self.chat.offline_queue.is_empty() {
+            return 0;
+        }
+
+        let queued = std::mem::take(&mut self.chat.offline_queue);
+        let total = queued.len();
+        for call in queued {
+            self.push_debug_event(
+                DebugLogLevel::Info,
+                format!("providers::{}", call.provider_kind.short_code()),
+                format!(
+                    "Reenviando solicitud en cola desde las {} (mensaje #{})",
+                    call.queued_at, call.message_index
+                ),
+            );
+            self.handle_provider_call(call.provider_kind, call.prompt, Some(call.message_index));
+        }
+        self.push_activity_log(
+            LogStatus::Ok,
+            "Red",
+            format!("Conexión recuperada: {} solicitudes reenviadas.", total),
+        );
+        total
+    }
+
+    pub fn discard_offline_queue(&mut self) -> usize {
+        let discarded = self.chat.offline_queue.len();
+        self.chat.offline_queue.clear();
+        discarded
+    }
+
+    fn poll_connectivity(&mut self) -> bool {
+        let proxy = self.config.network.proxy_url.clone();
+        match self.connectivity.poll(&proxy) {
+            Some(true) => {
+                self.push_activity_log(LogStatus::Ok, "Red", "Conectividad restablecida.");
+                self.replay_offline_queue();
+                true
+            }
+            Some(false) => {
+                self.push_activity_log(
+                    LogStatus::Warning,
+                    "Red",
+                    "Sin conexión con los proveedores remotos: Jarvis atenderá las peticiones.",
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn provider_status_slot(&mut self, provider: RemoteProviderKind) -> &mut Option<String> {
+        match provider {
+            RemoteProviderKind::Anthropic => &mut self.resources.anthropic_test_status,
+            RemoteProviderKind::OpenAi => &mut self.resources.openai_test_status,
+            RemoteProviderKind::Groq => &mut self.resources.groq_test_status,
+        }
+    }
+
+    /// Ventana de contexto declarada en el catálogo para el modelo indicado.
+    pub fn model_context_limit(&self, provider: RemoteProviderKind, model: &str) -> Option<u32> {
+        self.resources
+            .remote_catalog
+            .cards_for(provider)
+            .iter()
+            .find(|card| card.key.id == model)
+            .map(|card| card.context_tokens)
+    }
+
+    /// Estima los tokens y el coste que tendría enviar `draft` a cada proveedor con la
+    /// estrategia de contexto activa.
+    pub fn context_estimates(&self, draft: &str) -> Vec<ContextEstimate> {
+        [
+            RemoteProviderKind::Anthropic,
+            RemoteProviderKind::OpenAi,
+            RemoteProviderKind::Groq,
+        ]
+        .into_iter()
+        .map(|provider| self.estimate_prompt(provider, draft.trim()))
+        .collect()
+    }
+
+    /// Estimación del envío de `draft` tal y como está escrito: solo los proveedores
+    /// mencionados, con la cita y el esquema JSON que se adjuntarían a cada prompt.
+    pub fn send_estimates(&self, draft: &str) -> Vec<ContextEstimate> {
+        let (mentions, _) = self.parse_provider_mentions(draft);
+        let instructions = self.chat.structured.pending_instructions();
+        mentions
+            .into_iter()
+            .filter(|(_, prompt)| !prompt.is_empty())
+            .map(|(provider, prompt)| {
+                let mut prompt = match self.chat.pending_reply.as_ref() {
+                    Some(quote) => quote.wrap_prompt(&prompt),
+                    None => prompt,
+                };
+                if let Some(instructions) = instructions.as_deref() {
+                    prompt.push_str("\n\n");
+                    prompt.push_str(instructions);
                 }
+                self.estimate_prompt(provider, &prompt)
+            })
+            .collect()
+    }
+
+    /// Huellas del contenido de los proyectos marcados como solo local: su README y los
+    /// documentos incorporados a la base de conocimiento.
+    fn local_only_fingerprints(&self) -> Vec<local_only::ProjectFingerprint> {
+        self.config
+            .local_only_projects
+            .iter()
+            .map(|project| {
+                let readme = self
+                    .resources
+                    .project_resources
+                    .iter()
+                    .filter(|card| &card.name == project)
+                    .map(|card| card.readme_preview.as_str());
+                let documents = self
+                    .knowledge_index
+                    .projects
+                    .get(project)
+                    .into_iter()
+                    .flat_map(|documents| documents.values())
+                    .flat_map(|document| document.chunks.iter().map(|chunk| chunk.text.as_str()));
+                local_only::ProjectFingerprint::new(project, readme.chain(documents))
+            })
+            .filter(|fingerprint| !fingerprint.is_empty())
+            .collect()
+    }
+
+    /// Proyectos solo locales de los que procede el prompt o el historial que se enviaría
+    /// a `provider` junto a él. `prompt_in_history` indica si el prompt ya es el último
+    /// mensaje del usuario, que entonces no cuenta como historial.
+    fn local_only_projects_in(
+        &self,
+        provider: RemoteProviderKind,
+        prompt: &str,
+        history_end: usize,
+        prompt_in_history: bool,
+    ) -> Vec<String> {
+        if self.config.local_only_projects.is_empty() {
+            return Vec::new();
+        }
+        let window = context::build_context_window(
+            &self.chat.messages[..history_end],
+            &self.config.context,
+            self.tokenizers.for_provider(provider),
+            prompt_in_history,
+            &self.context_summaries,
+        );
+        local_only::derived_projects(&self.local_only_fingerprints(), &window.render(prompt))
+    }
+
+    /// Proyectos solo locales que saldrían hacia proveedores remotos al enviar `draft`, ya
+    /// sea por menciones o por el proveedor por defecto de la conversación.
+    pub fn local_only_conflict(&self, draft: &str) -> Vec<String> {
+        if self.config.local_only_projects.is_empty() || draft.starts_with('/') {
+            return Vec::new();
+        }
+        let (mentions, _) = self.parse_provider_mentions(draft);
+        let mut targets: Vec<(RemoteProviderKind, String)> = mentions
+            .into_iter()
+            .filter(|(_, prompt)| !prompt.is_empty())
+            .collect();
+        if targets.is_empty()
+            && Self::extract_alias_prompt(&self.resources.jarvis_alias, draft).is_none()
+            && self.match_model_alias(draft).is_none()
+        {
+            if let Some(provider) = self.effective_conversation_defaults().provider_kind() {
+                targets.push((provider, draft.to_string()));
             }
-            Err(err) => {
-                self.resources.jarvis_status = Some(format!("Jarvis no está listo: {}", err));
-                self.push_activity_log(
-                    LogStatus::Error,
-                    "Jarvis",
-                    format!("Runtime inalcanzable: {}", err),
-                );
-                Err(err.to_string())
+        }
+
+        let mut projects: Vec<String> = Vec::new();
+        for (provider, prompt) in targets {
+            // El borrador aún no está en el historial: el último mensaje sí se enviaría.
+            for project in
+                self.local_only_projects_in(provider, &prompt, self.chat.messages.len(), false)
+            {
+                if !projects.contains(&project) {
+                    projects.push(project);
+                }
             }
         }
+        projects
     }
 
-    pub fn respond_with_jarvis(&mut self, prompt: String) {
-        let mut orchestrator = JarvisOrchestrator::new(self);
-        orchestrator.execute(prompt);
+    pub fn is_project_local_only(&self, project: &str) -> bool {
+        self.config
+            .local_only_projects
+            .iter()
+            .any(|name| name == project)
     }
 
-    fn provider_alias_display(alias: &str, fallback: &str) -> String {
-        let trimmed = alias.trim();
-        if trimmed.is_empty() {
-            fallback.to_string()
+    pub fn set_project_local_only(&mut self, project: &str, local_only: bool) {
+        if self.is_project_local_only(project) == local_only {
+            return;
+        }
+        if local_only {
+            self.config.local_only_projects.push(project.to_string());
         } else {
-            trimmed.to_string()
+            self.config
+                .local_only_projects
+                .retain(|name| name != project);
         }
+        self.persist_config();
+        let message = if local_only {
+            format!(
+                "'{}' marcado como solo local: su contenido solo se enviará a Jarvis.",
+                project
+            )
+        } else {
+            format!(
+                "'{}' puede volver a usarse con proveedores remotos.",
+                project
+            )
+        };
+        self.push_activity_log(LogStatus::Ok, "Recursos", message);
     }
 
-    fn parse_provider_mentions(&self, input: &str) -> (Vec<(RemoteProviderKind, String)>, String) {
-        #[derive(Clone, Copy)]
-        struct RawMention {
-            kind: RemoteProviderKind,
-            start: usize,
-            alias_end: usize,
+    /// Abre un proyecto local conectado en el explorador de código.
+    pub fn open_code_viewer(&mut self, project: &str) -> Result<(), String> {
+        let root = self
+            .resources
+            .project_resources
+            .iter()
+            .find(|card| card.name == project && card.kind == ProjectResourceKind::LocalProject)
+            .map(|card| PathBuf::from(&card.location))
+            .ok_or_else(|| {
+                format!(
+                    "No hay un proyecto local llamado '{}' entre los recursos conectados.",
+                    project
+                )
+            })?;
+        let count = self
+            .code_viewer
+            .open_project(project, &root)
+            .map_err(|err| err.to_string())?;
+        self.code_viewer.status = Some(format!("{} archivos en {}.", count, project));
+        self.activate_navigation_target(NavigationTarget::main(MainView::CodeViewer));
+        Ok(())
+    }
+
+    /// Vuelve al archivo y al rango de líneas al que se refiere un mensaje del chat.
+    pub fn open_code_anchor(&mut self, anchor: &CodeAnchor) -> Result<(), String> {
+        if self.code_viewer.project.as_deref() != Some(anchor.project.as_str()) {
+            self.open_code_viewer(&anchor.project)?;
+        }
+        self.code_viewer
+            .open_file(&anchor.path)
+            .map_err(|err| err.to_string())?;
+        self.code_viewer.click_line(anchor.start, false);
+        self.code_viewer.click_line(anchor.end, true);
+        self.activate_navigation_target(NavigationTarget::main(MainView::CodeViewer));
+        Ok(())
+    }
+
+    /// Pregunta por la selección del explorador al proveedor por defecto de la conversación
+    /// o, si no hay ninguno, a Jarvis. La pregunta y la respuesta quedan ancladas al rango.
+    pub fn ask_about_selection(&mut self) -> Result<(), String> {
+        let (anchor, prompt) = self
+            .code_viewer
+            .selection_prompt()
+            .ok_or_else(|| "Selecciona al menos una línea del archivo.".to_string())?;
+        let question = self.code_viewer.question.trim();
+        let text = if question.is_empty() {
+            format!("¿Qué hace {}?", anchor.label())
+        } else {
+            question.to_string()
+        };
+        let mut message = ChatMessage::user(text);
+        message.code_anchor = Some(anchor.clone());
+        self.chat.messages.push(message);
+
+        if let Some(index) = self.send_with_conversation_route(prompt) {
+            if let Some(reply) = self.chat.messages.get_mut(index) {
+                reply.code_anchor = Some(anchor.clone());
+            }
         }
 
-        let alias_entries: Vec<(RemoteProviderKind, String)> = vec![
-            (
-                RemoteProviderKind::Anthropic,
-                Self::provider_alias_display(&self.resources.claude_alias, "claude"),
-            ),
-            (
-                RemoteProviderKind::OpenAi,
-                Self::provider_alias_display(&self.resources.openai_alias, "openai"),
-            ),
-            (
-                RemoteProviderKind::Groq,
-                Self::provider_alias_display(&self.resources.groq_alias, "groq"),
-            ),
-        ]
-        .into_iter()
-        .map(|(kind, alias)| {
-            let sanitized = alias.trim().trim_start_matches('@').to_lowercase();
-            (kind, sanitized)
-        })
-        .filter(|(_, alias)| !alias.is_empty())
-        .collect();
+        self.code_viewer.question.clear();
+        self.code_viewer.status = Some(format!("Pregunta enviada sobre {}.", anchor.label()));
+        Ok(())
+    }
+
+    /// Envía `prompt` al proveedor por defecto de la conversación o, sin él o sin conexión,
+    /// a Jarvis. Devuelve el índice del mensaje donde llegará la respuesta; `None` si el
+    /// envío no salió, en cuyo caso el propio envío ya dejó el motivo en el chat.
+    fn send_with_conversation_route(&mut self, prompt: String) -> Option<usize> {
+        let first_reply = self.chat.messages.len();
+        let dispatch = self
+            .effective_conversation_defaults()
+            .provider_kind()
+            .map(|provider| self.invoke_provider_kind(provider, prompt.clone()));
+        match dispatch {
+            Some(ProviderCallDispatch::Pending(ticket)) => Some(ticket.message_index),
+            None | Some(ProviderCallDispatch::Offline { .. }) => {
+                self.respond_with_jarvis(prompt);
+                (self.chat.messages.len() > first_reply).then(|| self.chat.messages.len() - 1)
+            }
+            Some(_) => None,
+        }
+    }
 
-        if alias_entries.is_empty() {
-            return (Vec::new(), input.trim().to_string());
+    /// Pide al proveedor de la conversación un mensaje de commit o una descripción de PR
+    /// a partir del diff del archivo abierto en el explorador o de todo el árbol de trabajo.
+    /// El diff se obtiene en segundo plano.
+    pub fn generate_from_diff(&mut self, document: git_assist::DiffDocument) -> Result<(), String> {
+        if self.code_viewer.project.is_none() {
+            return Err("Abre primero un proyecto local en el explorador.".to_string());
         }
+        let root = self.code_viewer.root.clone();
+        let scope = if self.code_viewer.diff_whole_tree {
+            git_assist::DiffScope::WorkingTree
+        } else {
+            let file =
+                self.code_viewer.open.as_ref().ok_or_else(|| {
+                    "Elige un archivo o usa todo el árbol de trabajo.".to_string()
+                })?;
+            git_assist::DiffScope::File {
+                path: file.path.clone(),
+                relative: file.relative.clone(),
+            }
+        };
+        let label = scope.label();
+        let query = GitQuery::WorkingDiff {
+            document,
+            scope: scope.clone(),
+        };
+        self.start_git_query(query, move || {
+            git_assist::collect_diff(&root, &scope).map(GitAnswer::Diff)
+        })?;
+        self.code_viewer.status = Some(format!("Calculando el diff de {}…", label));
+        Ok(())
+    }
 
-        let mut raw_mentions = Vec::new();
-        let mut index = 0usize;
-        let bytes = input.as_bytes();
-        while index < bytes.len() {
-            let rest = &input[index..];
-            let mut chars = rest.chars();
-            let Some(ch) = chars.next() else {
-                break;
-            };
+    fn finish_generate_from_diff(
+        &mut self,
+        document: git_assist::DiffDocument,
+        scope: &git_assist::DiffScope,
+        diff: &str,
+    ) {
+        let prompt = git_assist::build_prompt(document, scope, diff);
+
+        self.chat.messages.push(ChatMessage::user(format!(
+            "{} · {}",
+            document.label(),
+            scope.label()
+        )));
+        self.send_with_conversation_route(prompt);
+        self.code_viewer.status = Some(format!(
+            "{} solicitado en el chat ({}).",
+            document.label(),
+            scope.label()
+        ));
+    }
 
-            if ch != '@' {
-                index += ch.len_utf8();
-                continue;
-            }
+    /// Lanza `work` en segundo plano como la consulta de git `query` del explorador. Solo
+    /// hay una a la vez; el resultado se recoge en `poll_git_query`.
+    fn start_git_query(
+        &mut self,
+        query: GitQuery,
+        work: impl FnOnce() -> anyhow::Result<GitAnswer> + Send + 'static,
+    ) -> Result<(), String> {
+        if self.code_viewer.git_job.is_some() {
+            return Err("Ya hay una consulta de git en curso.".to_string());
+        }
+        let (tx, rx) = std::sync::mpsc::channel();
+        runtime::spawn_blocking(move || {
+            let _ = tx.send(work().map_err(|err| format!("{:#}", err)));
+        });
+        self.code_viewer.git_job = Some((query, rx));
+        Ok(())
+    }
 
-            let alias_slice = &rest[ch.len_utf8()..];
-            let mut alias = String::new();
-            let mut consumed = 0usize;
-            for (offset, next) in alias_slice.char_indices() {
-                if next.is_alphanumeric() || matches!(next, '_' | '-') {
-                    alias.push(next);
-                    consumed = offset + next.len_utf8();
-                } else {
-                    break;
-                }
+    fn poll_git_query(&mut self) -> bool {
+        let Some((_, rx)) = self.code_viewer.git_job.as_ref() else {
+            return false;
+        };
+        let outcome = match rx.try_recv() {
+            Ok(outcome) => outcome,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("git terminó sin respuesta".to_string())
             }
-
-            if alias.is_empty() {
-                index += ch.len_utf8();
-                continue;
+        };
+        let Some((query, _)) = self.code_viewer.git_job.take() else {
+            return false;
+        };
+        let scanning_conflicts = matches!(query, GitQuery::Conflicts);
+        let finished = outcome.and_then(|answer| match (query, answer) {
+            (GitQuery::History, GitAnswer::History(history)) => {
+                self.code_viewer.history = Some(history);
+                Ok(())
             }
-
-            let alias_lower = alias.to_lowercase();
-            if let Some((kind, _)) = alias_entries
-                .iter()
-                .find(|(_, candidate)| *candidate == alias_lower)
-            {
-                let alias_end = index + ch.len_utf8() + consumed;
-                raw_mentions.push(RawMention {
-                    kind: *kind,
-                    start: index,
-                    alias_end,
-                });
-                index = alias_end;
-            } else {
-                index += ch.len_utf8();
+            (
+                GitQuery::Blame {
+                    anchor,
+                    selection_prompt,
+                },
+                GitAnswer::Blame(blame),
+            ) => self.finish_who_wrote_selection(anchor, selection_prompt, blame),
+            (GitQuery::CommitDiff { hash, relative }, GitAnswer::Diff(diff)) => {
+                self.finish_explain_file_commit(&hash, &relative, &diff);
+                Ok(())
             }
+            (GitQuery::WorkingDiff { document, scope }, GitAnswer::Diff(diff)) => {
+                self.finish_generate_from_diff(document, &scope, &diff);
+                Ok(())
+            }
+            (GitQuery::Conflicts, GitAnswer::Conflicts(files)) => {
+                self.finish_merge_conflict_scan(files);
+                Ok(())
+            }
+            _ => Err("git devolvió una respuesta inesperada".to_string()),
+        });
+        match finished {
+            Err(err) if scanning_conflicts => self.code_viewer.conflicts.status = Some(err),
+            Err(err) => self.code_viewer.status = Some(err),
+            Ok(()) => {}
         }
+        true
+    }
 
-        if raw_mentions.is_empty() {
-            return (Vec::new(), input.trim().to_string());
-        }
+    /// Carga en segundo plano los últimos commits del archivo abierto en el explorador.
+    pub fn load_file_history(&mut self) -> Result<(), String> {
+        let file = self
+            .code_viewer
+            .open
+            .as_ref()
+            .ok_or_else(|| "Elige un archivo de la lista.".to_string())?;
+        let root = self.code_viewer.root.clone();
+        let path = file.path.clone();
+        self.start_git_query(GitQuery::History, move || {
+            git_assist::git_file_history(&root, &path, git_assist::FILE_HISTORY_LIMIT)
+                .map(GitAnswer::History)
+        })
+    }
 
-        let mut mentions = Vec::new();
-        let mut consumed_ranges: Vec<(usize, usize)> = Vec::new();
+    /// Pregunta quién escribió las líneas seleccionadas: el prompt lleva el `git blame`
+    /// agrupado por commit y el código, y la respuesta queda anclada a la selección. El
+    /// blame se calcula en segundo plano.
+    pub fn ask_who_wrote_selection(&mut self) -> Result<(), String> {
+        let (anchor, selection_prompt) = self
+            .code_viewer
+            .selection_prompt()
+            .ok_or_else(|| "Selecciona al menos una línea del archivo.".to_string())?;
+        let root = self.code_viewer.root.clone();
+        let path = anchor.path.clone();
+        let label = anchor.label();
+        self.start_git_query(
+            GitQuery::Blame {
+                anchor,
+                selection_prompt,
+            },
+            move || git_assist::git_blame(&root, &path).map(GitAnswer::Blame),
+        )?;
+        self.code_viewer.status = Some(format!("Calculando la autoría de {}…", label));
+        Ok(())
+    }
 
-        for (idx, raw) in raw_mentions.iter().enumerate() {
-            let mut prompt_start = raw.alias_end;
-            if prompt_start >= input.len() {
-                continue;
+    fn finish_who_wrote_selection(
+        &mut self,
+        anchor: CodeAnchor,
+        selection_prompt: String,
+        blame: Vec<git_assist::BlameLine>,
+    ) -> Result<(), String> {
+        let selected: Vec<_> = blame
+            .into_iter()
+            .filter(|line| (anchor.start..=anchor.end).contains(&line.line))
+            .collect();
+        if selected.is_empty() {
+            return Err("Las líneas seleccionadas aún no están en ningún commit.".to_string());
+        }
+        let authorship = git_assist::summarize_blame(&selected).join("\n");
+        let prompt = format!(
+            "Según git blame, estas líneas proceden de:\n{}\n\nExplica quién escribió cada parte, en qué commit y qué pretendía cada cambio.\n\n{}",
+            authorship, selection_prompt
+        );
+
+        let mut message = ChatMessage::user(format!("¿Quién escribió {}?", anchor.label()));
+        message.code_anchor = Some(anchor.clone());
+        self.chat.messages.push(message);
+        if let Some(index) = self.send_with_conversation_route(prompt) {
+            if let Some(reply) = self.chat.messages.get_mut(index) {
+                reply.code_anchor = Some(anchor.clone());
             }
+        }
+        self.code_viewer.status = Some(format!("Autoría de {} enviada al chat.", anchor.label()));
+        Ok(())
+    }
 
-            while let Some(ch) = input[prompt_start..].chars().next() {
-                if ch.is_whitespace() || matches!(ch, ':' | ',' | '-') {
-                    prompt_start += ch.len_utf8();
-                } else {
-                    break;
-                }
+    /// Pide al proveedor de la conversación que explique el cambio de un commit sobre el
+    /// archivo abierto. El diff del commit se obtiene en segundo plano.
+    pub fn explain_file_commit(&mut self, hash: &str) -> Result<(), String> {
+        let file = self
+            .code_viewer
+            .open
+            .as_ref()
+            .ok_or_else(|| "Elige un archivo de la lista.".to_string())?;
+        let relative = file.relative.clone();
+        let root = self.code_viewer.root.clone();
+        let path = file.path.clone();
+        let commit = hash.to_string();
+        self.start_git_query(
+            GitQuery::CommitDiff {
+                hash: hash.to_string(),
+                relative,
+            },
+            move || git_assist::commit_diff(&root, &commit, &path).map(GitAnswer::Diff),
+        )
+    }
 
-                if prompt_start >= input.len() {
-                    break;
+    fn finish_explain_file_commit(&mut self, hash: &str, relative: &str, diff: &str) {
+        let short = &hash[..hash.len().min(8)];
+        let prompt = git_assist::build_explain_prompt(relative, short, diff);
+
+        self.chat.messages.push(ChatMessage::user(format!(
+            "Explica el cambio {} en {}",
+            short, relative
+        )));
+        self.send_with_conversation_route(prompt);
+        self.code_viewer.status = Some(format!("Commit {} enviado al chat.", short));
+    }
+
+    /// Busca en segundo plano archivos con conflictos de fusión en el proyecto abierto en
+    /// el explorador.
+    pub fn scan_merge_conflicts(&mut self) -> Result<(), String> {
+        if self.code_viewer.project.is_none() {
+            return Err("Abre primero un proyecto local en el explorador.".to_string());
+        }
+        let root = self.code_viewer.root.clone();
+        self.start_git_query(GitQuery::Conflicts, move || {
+            git_assist::list_conflicts(&root).map(GitAnswer::Conflicts)
+        })?;
+        self.code_viewer.conflicts.status = Some("Buscando conflictos de fusión…".to_string());
+        Ok(())
+    }
+
+    fn finish_merge_conflict_scan(&mut self, files: Vec<git_assist::ConflictFile>) {
+        let count = files.len();
+        let conflicts = &mut self.code_viewer.conflicts;
+        conflicts.files = files;
+        conflicts.selected = None;
+        conflicts.resolutions.clear();
+        conflicts.proposals.clear();
+        conflicts.awaiting = None;
+        if count > 0 {
+            conflicts.select(0);
+        }
+        conflicts.status = Some(match count {
+            0 => "No hay conflictos de fusión.".to_string(),
+            count => format!("{} archivos con conflictos.", count),
+        });
+    }
+
+    /// Trae los remotos del proyecto abierto en segundo plano, con el token de GitHub
+    /// guardado, para mostrar cuánto adelanta o atrasa cada rama.
+    pub fn fetch_remote_status(&mut self) -> Result<(), String> {
+        if self.code_viewer.project.is_none() {
+            return Err("Abre primero un proyecto local en el explorador.".to_string());
+        }
+        if self.code_viewer.fetch_rx.is_some() {
+            return Err("Ya se están trayendo los remotos.".to_string());
+        }
+        let root = self.code_viewer.root.clone();
+        let token = self.config.github_token.clone();
+        let (tx, rx) = std::sync::mpsc::channel();
+        runtime::spawn_blocking(move || {
+            let result = git_assist::git_fetch_status(&root, token.as_deref())
+                .map_err(|err| format!("{:#}", err));
+            let _ = tx.send(result);
+        });
+        self.code_viewer.fetch_rx = Some(rx);
+        Ok(())
+    }
+
+    fn poll_fetch_status(&mut self) -> bool {
+        let Some(rx) = self.code_viewer.fetch_rx.as_ref() else {
+            return false;
+        };
+        let outcome = match rx.try_recv() {
+            Ok(outcome) => outcome,
+            Err(std::sync::mpsc::TryRecvError::Empty) => return false,
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                Err("el fetch terminó sin respuesta".to_string())
+            }
+        };
+        self.code_viewer.fetch_rx = None;
+        let project = self.code_viewer.project.clone().unwrap_or_default();
+        match outcome {
+            Ok(status) => {
+                if let Some(current) = status.current() {
+                    self.push_activity_log(
+                        LogStatus::Ok,
+                        "Git",
+                        format!("{} · {}: {}", project, current.name, current.summary()),
+                    );
                 }
+                self.code_viewer.fetch = Some(status);
             }
-
-            if prompt_start >= input.len() {
-                continue;
+            Err(err) => {
+                self.code_viewer.status = Some(format!("No se pudo traer los remotos: {}", err));
+                self.push_activity_log(LogStatus::Error, "Git", err);
             }
+        }
+        true
+    }
 
-            let next_start = raw_mentions
-                .get(idx + 1)
-                .map(|next| next.start)
-                .unwrap_or_else(|| input.len());
-
-            if prompt_start >= next_start {
-                continue;
+    /// Envía las dos versiones del bloque `hunk` al proveedor de la conversación y espera
+    /// su propuesta de fusión para aprobarla en el explorador.
+    pub fn propose_conflict_merge(&mut self, hunk: usize) -> Result<(), String> {
+        let conflicts = &self.code_viewer.conflicts;
+        if conflicts.awaiting.is_some() {
+            return Err("Ya hay una propuesta de fusión en curso.".to_string());
+        }
+        let file = conflicts
+            .selected_file()
+            .ok_or_else(|| "Elige un archivo en conflicto.".to_string())?;
+        let block = file
+            .hunks
+            .get(hunk)
+            .ok_or_else(|| "El bloque ya no existe.".to_string())?;
+        let prompt = git_assist::build_merge_prompt(&file.relative, block);
+        let label = format!("{}:{}", file.relative, block.start_line);
+
+        self.chat.messages.push(ChatMessage::user(format!(
+            "Propón una fusión para el conflicto de {}",
+            label
+        )));
+        match self.send_with_conversation_route(prompt) {
+            Some(index) => {
+                self.code_viewer.conflicts.awaiting = Some((hunk, index));
+                self.code_viewer.conflicts.status =
+                    Some(format!("Esperando la propuesta para {}…", label));
+                Ok(())
             }
+            None => Err("No se pudo enviar el conflicto; el motivo está en el chat.".to_string()),
+        }
+    }
 
-            let prompt_slice = input[prompt_start..next_start].trim();
-            if prompt_slice.is_empty() {
-                continue;
+    fn poll_conflict_proposal(&mut self) -> bool {
+        let Some((hunk, index)) = self.code_viewer.conflicts.awaiting else {
+            return false;
+        };
+        let Some(message) = self.chat.messages.get(index) else {
+            self.code_viewer.conflicts.awaiting = None;
+            return true;
+        };
+        if message.is_pending() {
+            return false;
+        }
+        let conflicts = &mut self.code_viewer.conflicts;
+        conflicts.awaiting = None;
+        conflicts.status = Some(if message.error.is_some() {
+            "El proveedor no pudo proponer una fusión.".to_string()
+        } else {
+            match git_assist::extract_code_block(&message.text) {
+                Some(merged) => {
+                    conflicts.proposals.insert(hunk, merged);
+                    "Propuesta de fusión lista para revisar.".to_string()
+                }
+                None => "La respuesta no incluye un bloque de código con la fusión.".to_string(),
             }
+        });
+        true
+    }
 
-            mentions.push((raw.kind, prompt_slice.to_string()));
-            consumed_ranges.push((raw.start, next_start));
+    /// Escribe en el archivo seleccionado los bloques con resolución elegida y vuelve a
+    /// leer los que quedan.
+    pub fn write_conflict_resolution(&mut self) -> Result<usize, String> {
+        let conflicts = &self.code_viewer.conflicts;
+        let file = conflicts
+            .selected_file()
+            .ok_or_else(|| "Elige un archivo en conflicto.".to_string())?;
+        if conflicts.resolutions.is_empty() {
+            return Err("Elige cómo resolver al menos un bloque.".to_string());
+        }
+        let path = file.path.clone();
+        let text = std::fs::read_to_string(&path)
+            .map_err(|err| format!("No se pudo leer {}: {}", path.display(), err))?;
+        let resolved = git_assist::resolve_conflicts(&text, &conflicts.resolutions);
+        std::fs::write(&path, &resolved)
+            .map_err(|err| format!("No se pudo escribir {}: {}", path.display(), err))?;
+        self.record_file_written(&path, "Resolución de conflictos");
+
+        let remaining = git_assist::parse_conflicts(&resolved);
+        let count = remaining.len();
+        let conflicts = &mut self.code_viewer.conflicts;
+        if let Some(selected) = conflicts.selected {
+            if let Some(file) = conflicts.files.get_mut(selected) {
+                file.hunks = remaining;
+            }
+            conflicts.select(selected);
         }
+        conflicts.status = Some(match count {
+            0 => format!(
+                "{} ya no tiene conflictos. Revísalo y márcalo como resuelto con git add.",
+                path.display()
+            ),
+            count => format!("Resolución escrita; quedan {} bloques.", count),
+        });
+        Ok(count)
+    }
 
-        if consumed_ranges.is_empty() {
-            return (Vec::new(), input.trim().to_string());
+    /// Aplica la acción de texto `action` al mensaje `index` con el proveedor de la
+    /// conversación, o con Jarvis si no hay ninguno. La respuesta cita el mensaje de origen.
+    pub fn apply_text_action(&mut self, index: usize, action: usize) -> Result<String, String> {
+        let template = self
+            .config
+            .text_actions
+            .actions
+            .get(action)
+            .cloned()
+            .ok_or_else(|| "La acción ya no existe.".to_string())?;
+        let message = self
+            .chat
+            .messages
+            .get(index)
+            .filter(|message| !message.is_pending())
+            .ok_or_else(|| "El mensaje no está disponible.".to_string())?;
+        let quote = MessageQuote::from_message(index, message);
+        let prompt = template.render(&quote.text, &self.config.text_actions.target_language);
+
+        if let Some(reply) = self.send_with_conversation_route(prompt) {
+            if let Some(reply) = self.chat.messages.get_mut(reply) {
+                reply.reply_to = Some(quote);
+            }
         }
+        Ok(format!("{} · mensaje #{}", template.label, index + 1))
+    }
 
-        consumed_ranges.sort_by_key(|(start, _)| *start);
-        let mut residual = String::new();
-        let mut cursor = 0usize;
-        for (start, end) in consumed_ranges {
-            if start > cursor {
-                residual.push_str(&input[cursor..start]);
-            }
-            cursor = end;
+    /// Guarda la sugerencia de parche del explorador junto al archivo al que se refiere.
+    pub fn save_patch_suggestion(&mut self) -> Result<PathBuf, String> {
+        let patch = self
+            .code_viewer
+            .patch
+            .as_ref()
+            .ok_or_else(|| "No hay ninguna sugerencia de parche.".to_string())?;
+        let file_name = patch
+            .anchor
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "sugerencia".to_string());
+        let path = patch
+            .anchor
+            .path
+            .with_file_name(format!("{}.{}.patch", file_name, patch.anchor.start));
+        std::fs::write(&path, &patch.diff)
+            .map_err(|err| format!("No se pudo guardar {}: {}", path.display(), err))?;
+        self.record_file_written(&path, "Explorador de código");
+        self.code_viewer.status = Some(format!("Parche guardado en {}.", path.display()));
+        Ok(path)
+    }
+
+    /// Coste total estimado de enviar `draft` si supera el umbral de confirmación.
+    pub fn send_cost_requiring_confirmation(&self, draft: &str) -> Option<f32> {
+        let threshold = self.config.context.confirm_cost_above;
+        if threshold <= 0.0 {
+            return None;
+        }
+        let total: f32 = self
+            .send_estimates(draft)
+            .iter()
+            .filter_map(|estimate| estimate.estimated_cost)
+            .sum();
+        (total > threshold).then_some(total)
+    }
+
+    fn estimate_prompt(&self, provider: RemoteProviderKind, prompt: &str) -> ContextEstimate {
+        let counter = self.tokenizers.for_provider(provider);
+        let window = context::build_context_window(
+            &self.chat.messages,
+            &self.config.context,
+            counter,
+            false,
+            &self.context_summaries,
+        );
+        let tokens = counter.count(&window.render(prompt));
+        let model = self.provider_call_profile(provider).model;
+        let card = self
+            .resources
+            .remote_catalog
+            .cards_for(provider)
+            .iter()
+            .find(|card| card.key.id == model);
+        let reply_tokens = card.map_or(0, |card| {
+            card.max_output_tokens.min(context::ESTIMATED_REPLY_TOKENS)
+        });
+        ContextEstimate {
+            provider,
+            tokens,
+            context_limit: card.map(|card| card.context_tokens),
+            reply_tokens,
+            estimated_cost: card.map(|card| {
+                (tokens as f32 * card.input_cost_per_million
+                    + reply_tokens as f32 * card.output_cost_per_million)
+                    / 1_000_000.0
+            }),
+            model,
         }
+    }
+
+    pub fn invoke_provider_kind(
+        &mut self,
+        provider: RemoteProviderKind,
+        prompt: String,
+    ) -> ProviderCallDispatch {
+        self.handle_provider_call(provider, prompt, None)
+    }
 
-        if cursor < input.len() {
-            residual.push_str(&input[cursor..]);
-        }
+    /// Vuelve a enviar el prompt que originó la respuesta en `index`, reemplazándola.
+    /// Si `provider` es `None` se reutiliza el proveedor que generó la respuesta original.
+    pub fn regenerate_message(
+        &mut self,
+        index: usize,
+        provider: Option<RemoteProviderKind>,
+    ) -> Result<ProviderCallDispatch, String> {
+        let message = self
+            .chat
+            .messages
+            .get(index)
+            .ok_or_else(|| "El mensaje seleccionado ya no existe.".to_string())?;
+
+        if message.is_pending() {
+            return Err("La respuesta todavía está en curso.".to_string());
+        }
+
+        let prompt = message
+            .source_prompt
+            .clone()
+            .ok_or_else(|| "Este mensaje no conserva el prompt que lo originó.".to_string())?;
+        let provider = provider
+            .or(message.origin)
+            .ok_or_else(|| "No se pudo determinar el proveedor de la respuesta.".to_string())?;
+
+        self.cancel_provider_calls_from(index, Some(index));
+        self.push_debug_event(
+            DebugLogLevel::Info,
+            format!("providers::{}", provider.short_code()),
+            format!(
+                "Regenerando la respuesta #{} con {}",
+                index,
+                provider.display_name()
+            ),
+        );
 
-        (mentions, residual.trim().to_string())
+        Ok(self.handle_provider_call(provider, prompt, Some(index)))
     }
 
-    fn format_provider_list(names: &[String]) -> String {
-        match names.len() {
-            0 => String::new(),
-            1 => names[0].clone(),
-            2 => format!("{} y {}", names[0], names[1]),
-            _ => {
-                let mut result = names[..names.len() - 1].join(", ");
-                result.push_str(&format!(" y {}", names.last().unwrap()));
-                result
+    /// Carga el texto de un mensaje del usuario en el compositor para editarlo y reenviarlo.
+    pub fn begin_message_edit(&mut self, index: usize) -> bool {
+        match self.chat.messages.get(index) {
+            Some(message) if message.sender == "User" => {
+                self.chat.input = message.text.clone();
+                self.chat.pending_reply = message.reply_to.clone();
+                self.chat.editing_message = Some(index);
+                true
             }
+            _ => false,
         }
     }
 
-    fn extract_alias_prompt(alias: &str, input: &str) -> Option<String> {
-        let alias_trimmed = alias.trim();
-        if alias_trimmed.is_empty() {
-            return None;
+    pub fn cancel_message_edit(&mut self) {
+        if self.chat.editing_message.take().is_some() {
+            self.chat.input.clear();
+            self.chat.pending_reply = None;
         }
+    }
 
-        let mut patterns = Vec::new();
-        patterns.push(alias_trimmed.to_string());
-        if !alias_trimmed.starts_with('@') {
-            patterns.push(format!("@{}", alias_trimmed));
-        } else {
-            patterns.push(alias_trimmed.trim_start_matches('@').to_string());
+    /// Los hilos solo pueden cambiarse cuando ninguna respuesta va a escribirse en el
+    /// historial abierto.
+    fn ensure_conversation_idle(&self) -> Result<(), String> {
+        if !self.chat.pending_provider_calls.is_empty() || !self.chat.offline_queue.is_empty() {
+            return Err(
+                "Espera a que terminen las respuestas pendientes antes de cambiar de hilo."
+                    .to_string(),
+            );
         }
+        Ok(())
+    }
 
-        for pattern in patterns
-            .into_iter()
-            .filter(|candidate| !candidate.trim().is_empty())
-        {
-            if let Some(remainder) = Self::match_alias_pattern(&pattern, input) {
-                return Some(remainder);
-            }
+    /// Crea un hilo nuevo con la conversación hasta `index` incluido y lo abre.
+    pub fn fork_conversation(&mut self, index: usize) -> Result<u32, String> {
+        if index >= self.chat.messages.len() {
+            return Err("El mensaje ya no existe.".to_string());
         }
+        self.ensure_conversation_idle()?;
 
-        None
+        let origin = threads::ThreadOrigin {
+            thread_id: self.threads.active,
+            message_index: index,
+        };
+        let parent = self
+            .threads
+            .active_thread()
+            .map(|thread| thread.title.clone())
+            .unwrap_or_default();
+        let messages = threads::copy_until(&self.chat.messages, index);
+        let title = format!("{} › mensaje {}", parent, index + 1);
+        let id = self
+            .threads
+            .add_thread(title.clone(), Some(origin), messages);
+        // El hilo bifurcado hereda el enrutado de la conversación de la que sale.
+        if let (Some(defaults), Some(thread)) = (
+            self.conversations
+                .get(&self.bookmarks.conversation)
+                .cloned(),
+            self.threads.thread(id),
+        ) {
+            self.conversations
+                .entries
+                .insert(thread.key.clone(), defaults);
+        }
+        self.switch_thread(id)?;
+        self.threads.status = Some(format!("Hilo '{}' creado.", title));
+        self.push_debug_event(
+            DebugLogLevel::Info,
+            "chat::threads",
+            format!("Conversación bifurcada en el mensaje {}", index + 1),
+        );
+        Ok(id)
     }
 
-    fn match_alias_pattern(pattern: &str, input: &str) -> Option<String> {
-        let input_trimmed = input.trim_start();
-        let pattern_chars: Vec<char> = pattern.chars().collect();
-        let mut input_iter = input_trimmed.chars();
-
-        for alias_ch in &pattern_chars {
-            match input_iter.next() {
-                Some(user_ch) if user_ch.eq_ignore_ascii_case(alias_ch) => {}
-                _ => return None,
-            }
+    /// Guarda el historial abierto en su hilo y carga el del hilo `id`.
+    pub fn switch_thread(&mut self, id: u32) -> Result<(), String> {
+        if id == self.threads.active {
+            return Ok(());
         }
-
-        let pattern_bytes: usize = pattern_chars.iter().map(|c| c.len_utf8()).sum();
-        if input_trimmed.len() < pattern_bytes {
-            return None;
+        if self.threads.thread(id).is_none() {
+            return Err("El hilo ya no existe.".to_string());
         }
+        self.ensure_conversation_idle()?;
 
-        let mut remainder = input_trimmed[pattern_bytes..].trim_start();
-        remainder = remainder.trim_start_matches(|c: char| matches!(c, ':' | ','));
-        remainder = remainder.trim_start();
-
-        if remainder.is_empty() {
-            None
-        } else {
-            Some(remainder.to_string())
+        let current = std::mem::take(&mut self.chat.messages);
+        if let Some(thread) = self.threads.thread_mut(self.threads.active) {
+            thread.messages = current;
         }
+        let Some(thread) = self.threads.thread_mut(id) else {
+            return Err("El hilo ya no existe.".to_string());
+        };
+        self.chat.messages = std::mem::take(&mut thread.messages);
+        self.bookmarks.conversation = thread.key.clone();
+        self.threads.active = id;
+
+        self.chat.editing_message = None;
+        self.chat.pending_reply = None;
+        self.chat.focused_message = None;
+        self.chat.scroll_to_focused = false;
+        Ok(())
+    }
+
+    /// Elimina un hilo inactivo junto con sus puntos de control.
+    pub fn delete_thread(&mut self, id: u32) -> Result<(), String> {
+        if id == self.threads.active {
+            return Err("No se puede eliminar el hilo abierto.".to_string());
+        }
+        self.threads.threads.retain(|thread| thread.id != id);
+        self.threads
+            .checkpoints
+            .retain(|checkpoint| checkpoint.thread_id != id);
+        Ok(())
+    }
+
+    /// Guarda una copia con nombre del hilo abierto.
+    pub fn create_checkpoint(&mut self) -> Result<u32, String> {
+        let name = match self.threads.checkpoint_name.trim() {
+            "" => format!("Punto {}", Local::now().format("%H:%M:%S")),
+            name => name.to_string(),
+        };
+        let last =
+            self.chat.messages.len().checked_sub(1).ok_or_else(|| {
+                "La conversación está vacía; no hay nada que guardar.".to_string()
+            })?;
+        let messages = threads::copy_until(&self.chat.messages, last);
+        let id = self.threads.add_checkpoint(name.clone(), messages);
+        self.threads.checkpoint_name.clear();
+        self.threads.status = Some(format!("Punto de control '{}' guardado.", name));
+        Ok(id)
+    }
+
+    /// Devuelve el hilo del punto de control al estado guardado, abriéndolo si hace falta.
+    pub fn restore_checkpoint(&mut self, id: u32) -> Result<(), String> {
+        let Some(checkpoint) = self
+            .threads
+            .checkpoints
+            .iter()
+            .find(|checkpoint| checkpoint.id == id)
+            .cloned()
+        else {
+            return Err("El punto de control ya no existe.".to_string());
+        };
+        self.ensure_conversation_idle()?;
+        self.switch_thread(checkpoint.thread_id)?;
+        self.chat.messages = checkpoint.messages;
+        self.threads.status = Some(format!(
+            "Restaurado el punto de control '{}'.",
+            checkpoint.name
+        ));
+        self.push_debug_event(
+            DebugLogLevel::Info,
+            "chat::threads",
+            format!("Punto de control '{}' restaurado", checkpoint.name),
+        );
+        Ok(())
     }
 
-    fn handle_provider_call(
-        &mut self,
-        provider_kind: RemoteProviderKind,
-        alias: String,
-        provider_name: &str,
-        prompt: String,
-        api_key: Option<String>,
-        model: String,
-        caller: fn(&str, &str, &str) -> anyhow::Result<String>,
-    ) -> ProviderCallDispatch {
-        if let Some(key) = api_key {
-            match self
-                .resources
-                .try_acquire_provider_quota(provider_kind, &alias, &prompt, &model)
-            {
-                Ok(usage) => {
-                    let mut status = format!("Consultando '{}' con el alias '{}'.", model, alias);
-                    if let Some(limit) = usage.limit {
-                        status.push_str(&format!(" Uso registrado: {}/{}.", usage.used, limit));
-                    }
-                    self.push_activity_log(LogStatus::Running, provider_name, status);
-                    let message_index = self.chat.messages.len();
-                    let pending = ChatMessage::pending(
-                        alias.clone(),
-                        format!("Esperando respuesta de {}…", provider_name),
-                        Some(provider_kind),
-                    );
-                    self.chat.messages.push(pending);
+    pub fn delete_checkpoint(&mut self, id: u32) {
+        self.threads
+            .checkpoints
+            .retain(|checkpoint| checkpoint.id != id);
+    }
 
-                    let call_id = self.chat.next_provider_call_id;
-                    self.chat.next_provider_call_id += 1;
+    /// Prepara una respuesta citando el mensaje en `index`.
+    pub fn begin_quote_reply(&mut self, index: usize) -> bool {
+        match self.chat.messages.get(index) {
+            Some(message) if !message.is_pending() => {
+                self.chat.pending_reply = Some(MessageQuote::from_message(index, message));
+                true
+            }
+            _ => false,
+        }
+    }
 
-                    let ticket = ProviderCallTicket {
-                        id: call_id,
-                        provider_kind,
-                        provider_name: provider_name.to_string(),
-                        alias: alias.clone(),
-                        model: model.clone(),
-                        message_index,
-                    };
+    pub fn cancel_quote_reply(&mut self) {
+        self.chat.pending_reply = None;
+    }
 
-                    self.chat.pending_provider_calls.push(PendingProviderCall {
-                        ticket: ticket.clone(),
-                    });
+    /// Elimina el mensaje en `index` y todos los posteriores, descartando las llamadas
+    /// a proveedores cuya respuesta iba a escribirse en la parte recortada.
+    pub fn truncate_conversation(&mut self, index: usize) -> usize {
+        if index >= self.chat.messages.len() {
+            return 0;
+        }
 
-                    let tx = self.chat.provider_response_tx.clone();
-                    std::thread::spawn(move || {
-                        let outcome = caller(&key, &model, &prompt).map_err(|err| err.to_string());
-                        let _ = tx.send(ProviderResponse {
-                            id: call_id,
-                            outcome,
-                        });
-                    });
+        self.cancel_provider_calls_from(index, None);
+        self.chat
+            .offline_queue
+            .retain(|queued| queued.message_index < index);
+        let removed = self.chat.messages.len() - index;
+        self.chat.messages.truncate(index);
+        self.push_debug_event(
+            DebugLogLevel::Info,
+            "chat::history",
+            format!("Conversación recortada: {} mensajes descartados", removed),
+        );
+        removed
+    }
+
+    /// Descarta las llamadas pendientes asociadas a `index` (o a cualquier mensaje
+    /// posterior si `only` es `None`). Las respuestas tardías se ignorarán al no
+    /// encontrar su ticket.
+    fn cancel_provider_calls_from(&mut self, index: usize, only: Option<usize>) {
+        let mut cancelled = Vec::new();
+        self.chat.pending_provider_calls.retain(|pending| {
+            let affected = match only {
+                Some(target) => pending.ticket.message_index == target,
+                None => pending.ticket.message_index >= index,
+            };
+            if affected {
+                cancelled.push(pending.ticket.clone());
+            }
+            !affected
+        });
 
-                    ProviderCallDispatch::Pending(ticket)
-                }
-                Err(ProviderQuotaExceeded {
-                    limit,
-                    used,
-                    alias: exceeded_alias,
-                    model: exceeded_model,
-                    created_at,
-                    ..
-                }) => {
-                    self.chat.messages.push(ChatMessage::system(format!(
-                        "Se alcanzó el límite diario ({}/{}) para {}. La solicitud '{}' ({}) se diferirá.",
-                        used,
-                        limit,
-                        provider_name,
-                        exceeded_alias,
-                        exceeded_model
-                    )));
-                    self.push_activity_log(
-                        LogStatus::Warning,
-                        provider_name,
-                        format!(
-                            "Límite diario ({}/{}) alcanzado; solicitud diferida para '{}'.",
-                            used, limit, model
-                        ),
-                    );
+        for ticket in cancelled {
+            self.cancel_provider_job(&ticket);
+            self.push_activity_log(
+                LogStatus::Warning,
+                ticket.provider_name.clone(),
+                format!(
+                    "Solicitud a '{}' descartada desde el historial.",
+                    ticket.model
+                ),
+            );
+        }
+    }
 
-                    ProviderCallDispatch::Deferred {
-                        provider_kind,
-                        provider_name: provider_name.to_string(),
-                        alias,
-                        model,
-                        limit,
-                        used,
-                        created_at,
-                    }
-                }
+    /// Saca de la cola el trabajo de una llamada descartada; si aún no había empezado,
+    /// devuelve la llamada reservada del límite diario.
+    fn cancel_provider_job(&mut self, ticket: &ProviderCallTicket) {
+        let waiting = self.chat.recall_waiting.len();
+        self.chat
+            .recall_waiting
+            .retain(|request| request.ticket.id != ticket.id);
+        if self.chat.recall_waiting.len() < waiting {
+            self.resources.release_provider_quota(ticket.provider_kind);
+            return;
+        }
+        let Some(job_id) = self.jobs.job_for_provider_call(ticket.id) else {
+            return;
+        };
+        if let Some(job) = self.jobs.cancel(job_id) {
+            if !job.started() {
+                self.resources.release_provider_quota(ticket.provider_kind);
             }
-        } else {
-            self.chat.messages.push(ChatMessage::system(format!(
-                "Configura la API key de {} antes de usar el alias '{}'.",
-                provider_name, alias
-            )));
-            *self.provider_status_slot(provider_kind) =
-                Some(format!("Falta la API key para {}.", provider_name));
-            ProviderCallDispatch::MissingCredentials {
-                provider_kind,
-                provider_name: provider_name.to_string(),
-                alias,
+        }
+    }
+
+    /// Descarta la última llamada a un proveedor que sigue esperando respuesta. El
+    /// mensaje pendiente queda como aviso y conserva el prompt para regenerarlo.
+    pub fn cancel_latest_provider_call(&mut self) -> bool {
+        match self.chat.pending_provider_calls.len().checked_sub(1) {
+            Some(position) => {
+                self.cancel_provider_call_at(position, "desde el teclado");
+                true
             }
+            None => false,
         }
     }
 
-    fn provider_status_slot(&mut self, provider: RemoteProviderKind) -> &mut Option<String> {
-        match provider {
-            RemoteProviderKind::Anthropic => &mut self.resources.anthropic_test_status,
-            RemoteProviderKind::OpenAi => &mut self.resources.openai_test_status,
-            RemoteProviderKind::Groq => &mut self.resources.groq_test_status,
+    pub fn apply_job_queue_limits(&mut self) {
+        self.jobs.configure(self.config.job_queue.clone());
+        self.persist_config();
+    }
+
+    /// Cancela un trabajo desde el inspector de la cola. Las llamadas del chat se
+    /// descartan como al cancelarlas desde el teclado.
+    pub fn cancel_job(&mut self, job_id: u64) {
+        let position = self
+            .jobs
+            .active()
+            .find(|job| job.id == job_id)
+            .and_then(|job| job.provider_call)
+            .and_then(|call_id| {
+                self.chat
+                    .pending_provider_calls
+                    .iter()
+                    .position(|pending| pending.ticket.id == call_id)
+            });
+        if let Some(position) = position {
+            self.cancel_provider_call_at(position, "desde la cola de llamadas");
+        } else if let Some(job) = self.jobs.cancel(job_id) {
+            self.push_activity_log(
+                LogStatus::Warning,
+                "Cola de llamadas",
+                format!("'{}' cancelado antes de empezar.", job.label),
+            );
         }
     }
 
-    pub fn invoke_provider_kind(
-        &mut self,
-        provider: RemoteProviderKind,
-        prompt: String,
-    ) -> ProviderCallDispatch {
-        match provider {
-            RemoteProviderKind::Anthropic => self.invoke_anthropic(prompt),
-            RemoteProviderKind::OpenAi => self.invoke_openai(prompt),
-            RemoteProviderKind::Groq => self.invoke_groq(prompt),
+    fn cancel_provider_call_at(&mut self, position: usize, origin: &str) {
+        let ticket = self.chat.pending_provider_calls.remove(position).ticket;
+        self.cancel_provider_job(&ticket);
+        if let Some(message) = self.chat.messages.get_mut(ticket.message_index) {
+            let mut cancelled =
+                ChatMessage::system(format!("{}: solicitud cancelada.", ticket.alias));
+            cancelled.origin = Some(ticket.provider_kind);
+            cancelled.source_prompt = message.source_prompt.take();
+            *message = cancelled;
         }
+        self.push_activity_log(
+            LogStatus::Warning,
+            ticket.provider_name.clone(),
+            format!("Solicitud a '{}' cancelada {}.", ticket.model, origin),
+        );
     }
 
     pub fn execute_remote_quick_test(&mut self, key: RemoteModelKey) -> Option<String> {
@@ -4539,1160 +15147,633 @@ impl AppState {
                 "Configura la API key de {} antes de ejecutar la prueba rápida.",
                 key.provider.display_name()
             )),
+            ProviderCallDispatch::Queued { position, .. } => Some(format!(
+                "Sin conexión: la prueba rápida para {} quedó en cola (#{}).",
+                label, position
+            )),
+            ProviderCallDispatch::Offline { .. } => Some(format!(
+                "Sin conexión: no se pudo enviar la prueba rápida a {}.",
+                label
+            )),
+            ProviderCallDispatch::BudgetExhausted { period, .. } => Some(format!(
+                "No se pudo ejecutar la prueba rápida: presupuesto {} agotado para {}.",
+                period.label(),
+                key.provider.display_name()
+            )),
+            ProviderCallDispatch::LocalOnly { projects, .. } => Some(format!(
+                "No se envió la prueba rápida: el contexto incluye {} (solo local).",
+                projects.join(", ")
+            )),
         }
     }
 
-    pub fn invoke_anthropic(&mut self, prompt: String) -> ProviderCallDispatch {
-        let alias = Self::provider_alias_display(&self.resources.claude_alias, "claude");
-        let key = self.config.anthropic.api_key.clone().and_then(|k| {
-            let trimmed = k.trim();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some(trimmed.to_string())
-            }
-        });
-        self.handle_provider_call(
-            RemoteProviderKind::Anthropic,
-            alias,
-            "Anthropic",
-            prompt,
-            key,
-            self.resources.claude_default_model.clone(),
-            crate::api::claude::send_message,
-        )
-    }
-
-    pub fn invoke_openai(&mut self, prompt: String) -> ProviderCallDispatch {
-        let alias = Self::provider_alias_display(&self.resources.openai_alias, "openai");
-        let key = self.config.openai.api_key.clone().and_then(|k| {
-            let trimmed = k.trim();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some(trimmed.to_string())
-            }
-        });
-        self.handle_provider_call(
-            RemoteProviderKind::OpenAi,
-            alias,
-            "OpenAI",
-            prompt,
-            key,
-            self.resources.openai_default_model.clone(),
-            crate::api::openai::send_message,
-        )
-    }
-
-    pub fn invoke_groq(&mut self, prompt: String) -> ProviderCallDispatch {
-        let alias = Self::provider_alias_display(&self.resources.groq_alias, "groq");
-        let key = self.config.groq.api_key.clone().and_then(|k| {
-            let trimmed = k.trim();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some(trimmed.to_string())
-            }
-        });
-        self.handle_provider_call(
-            RemoteProviderKind::Groq,
-            alias,
-            "Groq",
-            prompt,
-            key,
-            self.resources.groq_default_model.clone(),
-            crate::api::groq::send_message,
-        )
+    /// Acciones rápidas de un modelo del catálogo: las predefinidas de su tarjeta seguidas
+    /// de las que el usuario ha definido en preferencias, como pares `(etiqueta, plantilla)`.
+    pub fn model_quick_actions(&self, key: &RemoteModelKey) -> Vec<(String, String)> {
+        let builtin = self
+            .resources
+            .remote_catalog
+            .provider_cards
+            .get(&key.provider)
+            .and_then(|cards| cards.iter().find(|card| card.key == *key))
+            .map(|card| card.quick_actions.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|label| {
+                let template = builtin_quick_action_template(&label);
+                (label, template)
+            });
+        let custom = self
+            .config
+            .model_quick_actions
+            .iter()
+            .filter(|action| {
+                RemoteProviderKind::from_short_code(&action.provider) == Some(key.provider)
+                    && action.model_id == key.id
+            })
+            .map(|action| (action.label.clone(), action.template.clone()));
+        builtin.chain(custom).collect()
+    }
+
+    /// Abre el chat con la plantilla de la acción dirigida al modelo indicado.
+    pub fn run_model_quick_action(&mut self, key: RemoteModelKey, label: &str, template: &str) {
+        let alias = self.provider_call_profile(key.provider).alias;
+        let mention = if alias.starts_with('@') {
+            alias
+        } else {
+            format!("@{}", alias)
+        };
+        self.chat.input = format!("{} {}", mention, template.trim_start());
+        self.resources.remote_catalog.update_status(Some(format!(
+            "'{}' preparado en el chat para {}.",
+            label,
+            key.as_display()
+        )));
+        self.chat.model_override = Some(key);
+        self.activate_navigation_target(NavigationTarget::main(MainView::ChatMultimodal));
+    }
+
+    pub fn add_model_quick_action(&mut self, provider: RemoteProviderKind) -> Result<(), String> {
+        let mut action = self.resources.quick_action_draft.clone();
+        action.provider = provider.short_code().to_string();
+        action.label = action.label.trim().to_string();
+        if action.model_id.trim().is_empty() {
+            return Err("Elige el modelo al que se dirige la acción.".to_string());
+        }
+        if action.label.is_empty() || action.template.trim().is_empty() {
+            return Err("La acción necesita un nombre y una plantilla.".to_string());
+        }
+        self.config.model_quick_actions.push(action);
+        self.resources.quick_action_draft = Default::default();
+        self.persist_config();
+        Ok(())
     }
 
-    pub fn try_route_provider_message(&mut self, input: &str) -> String {
+    pub fn remove_model_quick_action(&mut self, index: usize) {
+        if index < self.config.model_quick_actions.len() {
+            self.config.model_quick_actions.remove(index);
+            self.persist_config();
+        }
+    }
+
+    pub fn try_route_provider_message(
+        &mut self,
+        input: &str,
+        quote: Option<&MessageQuote>,
+    ) -> String {
         let (mentions, residual) = self.parse_provider_mentions(input);
         if mentions.is_empty() {
+            self.chat.model_override = None;
+            self.chat.bypass_response_cache = false;
             return residual;
         }
 
         let mut invoked = Vec::new();
+        let mut queued = Vec::new();
+        let mut local_fallback = Vec::new();
         for (provider, prompt) in mentions {
             if prompt.is_empty() {
                 continue;
             }
 
-            if let ProviderCallDispatch::Pending(_) = self.invoke_provider_kind(provider, prompt) {
-                invoked.push(provider.display_name().to_string());
+            let wrapped = match quote {
+                Some(quote) => quote.wrap_prompt(&prompt),
+                None => prompt.clone(),
+            };
+
+            match self.invoke_provider_kind(provider, wrapped) {
+                ProviderCallDispatch::Pending(_) => {
+                    invoked.push(provider.display_name().to_string());
+                }
+                ProviderCallDispatch::Queued { .. } => {
+                    queued.push(provider.display_name().to_string());
+                }
+                ProviderCallDispatch::Offline { .. } => local_fallback.push(prompt),
+                _ => {}
             }
         }
+        self.chat.model_override = None;
+        self.chat.bypass_response_cache = false;
 
         if !invoked.is_empty() {
             let summary = Self::format_provider_list(&invoked);
             self.chat_routing
                 .update_status(Some(format!("Mensaje enviado a {}.", summary)));
+        } else if !queued.is_empty() {
+            let summary = Self::format_provider_list(&queued);
+            self.chat_routing.update_status(Some(format!(
+                "Sin conexión: mensaje en cola para {}.",
+                summary
+            )));
         }
 
-        residual
-    }
-
-    pub fn try_invoke_jarvis_alias(&mut self, input: &str) -> bool {
-        if let Some(prompt) = Self::extract_alias_prompt(&self.resources.jarvis_alias, input) {
-            self.respond_with_jarvis(prompt);
-            true
-        } else {
-            false
+        if local_fallback.is_empty() {
+            return residual;
         }
-    }
 
-    pub fn jarvis_mention_tag(&self) -> Option<String> {
-        let alias = self.resources.jarvis_alias.trim();
-        if alias.is_empty() {
-            None
-        } else if alias.starts_with('@') {
-            Some(alias.to_string())
-        } else {
-            Some(format!("@{}", alias))
+        // Sin conexión ni cola, Jarvis atiende localmente lo que iba dirigido a los
+        // proveedores remotos.
+        self.chat_routing.update_status(Some(
+            "Sin conexión: Jarvis responderá localmente.".to_string(),
+        ));
+        let mut combined = local_fallback.join("\n");
+        if !residual.trim().is_empty() {
+            combined.push('\n');
+            combined.push_str(residual.trim());
         }
+        combined
     }
 
-    pub fn handle_command(&mut self, command_input: String) {
-        let trimmed = command_input.trim();
-        if trimmed.is_empty() {
-            return;
-        }
-
-        let invocation = CommandInvocation::parse(trimmed);
-        if invocation.name.is_empty() {
-            return;
-        }
-
-        let outcome = self.resolve_command(invocation, 0);
-        if outcome.messages.is_empty() {
-            return;
-        }
+    /// Ajustes de enrutado de la conversación abierta.
+    pub fn active_conversation_defaults(&self) -> ConversationDefaults {
+        self.conversations
+            .get(&self.bookmarks.conversation)
+            .cloned()
+            .unwrap_or_default()
+    }
 
-        for message in outcome.messages {
-            self.chat.messages.push(ChatMessage::system(message));
-        }
+    /// Nombre y perfil de contexto del proyecto seleccionado, si tiene uno.
+    pub fn active_project_profile(&self) -> Option<(&str, &ProjectContextProfile)> {
+        let project = self.projects.get(self.selected_project?)?;
+        self.config
+            .project_profiles
+            .get(project)
+            .filter(|profile| !project_context::is_empty(profile))
+            .map(|profile| (project.as_str(), profile))
     }
 
-    fn resolve_command(&mut self, invocation: CommandInvocation, depth: usize) -> CommandOutcome {
-        if depth > MAX_COMMAND_DEPTH {
-            return CommandOutcome::single(
-                "Recursión de comandos demasiado profunda. Revisa tus condicionales.".to_string(),
-            );
+    /// Ajustes de la conversación abierta completados con el perfil del proyecto
+    /// seleccionado. Son los que deciden el enrutado y la persona de cada envío.
+    pub fn effective_conversation_defaults(&self) -> ConversationDefaults {
+        let defaults = self.active_conversation_defaults();
+        match self.active_project_profile() {
+            Some((_, profile)) => project_context::apply_to_defaults(defaults, profile),
+            None => defaults,
         }
+    }
 
-        if invocation.name == "/if" {
-            return self.execute_conditional(invocation, depth);
-        }
+    /// Reglas de redacción con las que salen los prompts mientras el proyecto está activo.
+    pub fn effective_redaction_config(&self) -> RedactionConfig {
+        project_context::redaction_config(
+            &self.config.redaction,
+            self.active_project_profile().map(|(_, profile)| profile),
+        )
+    }
 
-        if let Some(custom) = self
-            .chat
-            .custom_commands
-            .iter()
-            .find(|cmd| cmd.trigger == invocation.name)
-            .cloned()
-        {
-            return CommandOutcome {
-                messages: self.execute_custom_action(custom.action, &invocation),
-            };
+    /// Cambia el proyecto seleccionado y anuncia en el chat el perfil de contexto que
+    /// pasa a aplicarse.
+    pub fn select_project(&mut self, index: Option<usize>) {
+        if index == self.selected_project {
+            return;
         }
+        self.selected_project = index;
+        self.persist_config();
 
-        match invocation.name.as_str() {
-            "/status" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ShowSystemStatus, &invocation),
-            },
-            "/system" => {
-                let positional_debug = invocation
-                    .positional
-                    .first()
-                    .map(|token| token.eq_ignore_ascii_case("debug"))
-                    .unwrap_or(false);
-                let arg_debug = invocation
-                    .arg("mode")
-                    .map(|value| value.eq_ignore_ascii_case("debug"))
-                    .unwrap_or(false)
-                    || invocation.flag("debug");
-
-                let action = if positional_debug || arg_debug {
-                    CustomCommandAction::ShowSystemDiagnostics
-                } else {
-                    CustomCommandAction::ShowSystemStatus
-                };
-
-                CommandOutcome {
-                    messages: self.execute_custom_action(action, &invocation),
-                }
+        let active = self
+            .active_project_profile()
+            .map(|(project, profile)| (project.to_string(), project_context::summary(profile)));
+        self.project_context.banner_dismissed = false;
+        match active {
+            Some((project, summary)) => {
+                let route = self.conversation_route_label(&self.effective_conversation_defaults());
+                self.chat_routing.update_status(Some(format!(
+                    "Contexto de '{}' activo ({}). Sin mención: {}.",
+                    project,
+                    summary.join(", "),
+                    route
+                )));
+                self.push_activity_log(
+                    LogStatus::Ok,
+                    "Proyectos",
+                    format!("Perfil de contexto de '{}' aplicado.", project),
+                );
+                self.project_context.announced = Some(project);
             }
-            "/models" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ListAvailableModels, &invocation),
-            },
-            "/stats" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ShowUsageStatistics, &invocation),
-            },
-            "/reload" => {
-                let mut message =
-                    "Recargando configuraciones... Los proveedores y la caché se sincronizarán en segundo plano.".to_string();
-                if invocation.flag("force") {
-                    message.push_str(" Forzando refresco inmediato de todas las credenciales.");
+            None => {
+                if let Some(previous) = self.project_context.announced.take() {
+                    self.chat_routing.update_status(Some(format!(
+                        "Sin perfil de contexto: '{}' ya no está activo.",
+                        previous
+                    )));
                 }
-                CommandOutcome::single(message)
             }
-            "/help" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ShowCommandHelp, &invocation),
-            },
-            "/time" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ShowCurrentTime, &invocation),
-            },
-            "/projects" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ListActiveProjects, &invocation),
-            },
-            "/profiles" => CommandOutcome {
-                messages: self.execute_custom_action(
-                    CustomCommandAction::ListConfiguredProfiles,
-                    &invocation,
-                ),
-            },
-            "/cache" => CommandOutcome {
-                messages: self.execute_custom_action(
-                    CustomCommandAction::ShowCacheConfiguration,
-                    &invocation,
-                ),
-            },
-            "/github" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ShowGithubSummary, &invocation),
-            },
-            "/memory" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ShowMemorySettings, &invocation),
-            },
-            "/providers" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ShowActiveProviders, &invocation),
-            },
-            "/jarvis" => CommandOutcome {
-                messages: self
-                    .execute_custom_action(CustomCommandAction::ShowJarvisStatus, &invocation),
-            },
-            _ => CommandOutcome::single(format!("Unknown command: {}", invocation.raw)),
         }
     }
 
-    fn execute_conditional(
-        &mut self,
-        invocation: CommandInvocation,
-        depth: usize,
-    ) -> CommandOutcome {
-        let condition_text = invocation
-            .raw
-            .trim_start_matches("/if")
-            .trim_start()
-            .to_string();
-
-        if condition_text.is_empty() {
-            return CommandOutcome::single(
-                "Uso: /if <condición> then <comando> [else <comando>]".to_string(),
-            );
-        }
-
-        let (condition_part, outcome_part) = match condition_text.split_once(" then ") {
-            Some(parts) => parts,
-            None => {
-                return CommandOutcome::single(
-                    "La instrucción condicional necesita la palabra clave 'then'.".to_string(),
-                );
-            }
-        };
-
-        let (then_command, else_command) =
-            if let Some((then, otherwise)) = outcome_part.split_once(" else ") {
-                (then.trim(), Some(otherwise.trim()))
-            } else {
-                (outcome_part.trim(), None)
-            };
-
-        let evaluation = match self.evaluate_condition(condition_part.trim()) {
-            Ok(value) => value,
-            Err(err) => return CommandOutcome::single(err),
-        };
-
-        let mut messages = Vec::new();
-        messages.push(format!(
-            "Condición '{}' evaluada como {}.",
-            condition_part.trim(),
-            if evaluation { "verdadera" } else { "falsa" }
-        ));
-
-        let branch = if evaluation {
-            then_command
-        } else if let Some(else_cmd) = else_command {
-            else_cmd
-        } else {
-            messages.push("No se especificó comando 'else'.".to_string());
-            return CommandOutcome { messages };
+    /// Fragmentos de las fuentes de conocimiento del perfil activo más parecidos al
+    /// prompt, cuyo vector es `query`, con el proyecto y el documento del que proceden.
+    fn recall_project_knowledge(&self, query: &[f32]) -> Vec<String> {
+        let Some(sources) = self
+            .active_project_profile()
+            .map(|(_, profile)| profile.knowledge_sources.clone())
+            .filter(|sources| !sources.is_empty())
+        else {
+            return Vec::new();
         };
-
-        if branch.is_empty() {
-            messages.push("No hay comando que ejecutar tras la condición.".to_string());
-            return CommandOutcome { messages };
-        }
-
-        let nested_invocation = CommandInvocation::parse(branch);
-        if nested_invocation.name.is_empty() {
-            messages.push("No se pudo interpretar el comando de la rama seleccionada.".to_string());
-            return CommandOutcome { messages };
+        let hits = self.embeddings.index.search(
+            query,
+            project_context::KNOWLEDGE_RECALL_CHUNKS,
+            embeddings::MEMORY_RECALL_MIN_SCORE,
+            |source| match source {
+                SemanticSource::ProjectReadme { project, .. }
+                | SemanticSource::ProjectDocument { project, .. } => sources.contains(project),
+                _ => false,
+            },
+        );
+        if hits.is_empty() {
+            return Vec::new();
         }
-
-        messages.push(format!("Ejecutando '{}'.", branch));
-        let nested = self.resolve_command(nested_invocation, depth + 1);
-        messages.extend(nested.messages);
-        CommandOutcome { messages }
+        let corpus = self.semantic_corpus();
+        hits.into_iter()
+            .filter_map(|hit| {
+                corpus
+                    .iter()
+                    .find(|(source, _)| *source == hit.source)
+                    .map(|(_, text)| text.clone())
+            })
+            .collect()
     }
 
-    fn evaluate_condition(&self, expression: &str) -> Result<bool, String> {
-        let parts: Vec<&str> = expression.split_whitespace().collect();
-        if parts.len() < 3 {
-            return Err(
-                "La condición debe tener el formato <campo> <operador> <valor>.".to_string(),
-            );
+    /// Destino de los mensajes sin mención según `defaults`, para la cabecera del chat.
+    pub fn conversation_route_label(&self, defaults: &ConversationDefaults) -> String {
+        match defaults.provider_kind() {
+            Some(provider) => {
+                let model = defaults
+                    .model
+                    .clone()
+                    .unwrap_or_else(|| self.provider_call_profile(provider).model);
+                format!("{} · {}", provider.display_name(), model)
+            }
+            None => "Jarvis".to_string(),
         }
-
-        let field = parts[0];
-        let operator = parts[1];
-        let value = parts[2..].join(" ");
-
-        let lhs = self.resolve_condition_value(field)?;
-        lhs.compare(operator, value.trim())
     }
 
-    fn resolve_condition_value(&self, field: &str) -> Result<ConditionValue, String> {
-        match field {
-            "memory.enabled" => Ok(ConditionValue::Boolean(self.enable_memory_tracking)),
-            "profiles.count" => Ok(ConditionValue::Number(self.profiles.len() as f64)),
-            "projects.count" => Ok(ConditionValue::Number(self.projects.len() as f64)),
-            "cache.auto_cleanup" => Ok(ConditionValue::Boolean(self.enable_auto_cleanup)),
-            "providers.total" => Ok(ConditionValue::Number(3.0)),
-            "github.connected" => Ok(ConditionValue::Boolean(self.github_username.is_some())),
-            "jarvis.auto_start" => Ok(ConditionValue::Boolean(self.resources.jarvis_auto_start)),
-            "commands.count" => Ok(ConditionValue::Number(
-                self.chat.custom_commands.len() as f64
-            )),
-            _ => Err(format!("Campo desconocido en la condición: {}", field)),
-        }
+    /// Copia los ajustes de la conversación abierta al borrador del menú de ajustes.
+    pub fn edit_conversation_defaults(&mut self) {
+        self.conversations.draft = self.active_conversation_defaults();
+        self.conversations.status = None;
     }
 
-    fn execute_custom_action(
-        &mut self,
-        action: CustomCommandAction,
-        invocation: &CommandInvocation,
-    ) -> Vec<String> {
-        match action {
-            CustomCommandAction::ShowCurrentTime => {
-                let format = invocation.arg("format").unwrap_or("human");
-                let now = Local::now();
-                let rendered = match format {
-                    "24" => now.format("%H:%M:%S").to_string(),
-                    "iso" => now.to_rfc3339(),
-                    _ => now.format("%I:%M %p").to_string(),
-                };
-                vec![format!("Hora actual: {}", rendered.trim())]
-            }
-            CustomCommandAction::ShowSystemStatus => {
-                let detail = invocation.arg("detail").unwrap_or("summary");
-                let verbose = invocation.flag("verbose");
-                let mut lines = vec![format!(
-                    "Sistema operativo estable. Límites configurados → Memoria: {:.1} GB · Disco: {:.1} GB.",
-                    self.resource_memory_limit_gb, self.resource_disk_limit_gb
-                )];
-
-                match detail {
-                    "memory" => lines.push(format!(
-                        "Memoria disponible para caché: {:.1} GB. Auto limpieza: {}.",
-                        self.resource_memory_limit_gb,
-                        if self.enable_auto_cleanup {
-                            "activada"
-                        } else {
-                            "desactivada"
-                        }
-                    )),
-                    "disk" => lines.push(format!(
-                        "Espacio de disco reservado para caché: {:.1} GB en {}.",
-                        self.resource_disk_limit_gb, self.cache_directory
-                    )),
-                    "cache" => lines.push(format!(
-                        "Limpieza automática cada {} horas. Última ejecución: {}.",
-                        self.cache_cleanup_interval_hours,
-                        self.last_cache_cleanup
-                            .clone()
-                            .unwrap_or_else(|| "nunca".to_string())
-                    )),
-                    _ => {}
-                }
-
-                if verbose {
-                    lines.push("Modo detallado activado: recuerda revisar la configuración de recursos en Preferencias.".to_string());
-                }
-
-                lines
-            }
-            CustomCommandAction::ShowSystemDiagnostics => {
-                let mut focus_tokens = invocation.positional.clone();
-                if focus_tokens
-                    .first()
-                    .map(|token| token.eq_ignore_ascii_case("debug"))
-                    .unwrap_or(false)
-                {
-                    focus_tokens.remove(0);
-                }
-
-                let requested_section = invocation
-                    .arg("section")
-                    .or_else(|| invocation.arg("focus"))
-                    .map(|value| value.to_ascii_lowercase())
-                    .or_else(|| focus_tokens.first().map(|token| token.to_ascii_lowercase()))
-                    .unwrap_or_else(|| "all".to_string());
-
-                let normalized = requested_section.as_str();
-                let wants_all = matches!(
-                    normalized,
-                    "all" | "todo" | "todos" | "todas" | "full" | "completo" | "completa"
-                );
-                let wants_general = wants_all
-                    || matches!(normalized, "general" | "recursos" | "status" | "resumen");
-                let wants_remote = wants_all
-                    || matches!(
-                        normalized,
-                        "remote" | "remoto" | "providers" | "proveedores" | "nube"
-                    );
-                let wants_local = wants_all
-                    || matches!(
-                        normalized,
-                        "local" | "jarvis" | "modelos" | "models" | "runtime"
-                    );
-                let wants_commands = wants_all
-                    || matches!(normalized, "commands" | "comandos" | "command" | "custom");
-                let wants_logs = wants_all
-                    || matches!(
-                        normalized,
-                        "logs" | "errores" | "errors" | "diagnostico" | "diagnóstico"
-                    );
-
-                let classify = |text: &str| {
-                    let normalized = text.to_ascii_lowercase();
-                    if normalized.contains("error")
-                        || normalized.contains("fall")
-                        || normalized.contains("no se pudo")
-                        || normalized.contains("failed")
-                    {
-                        "ERROR"
-                    } else if normalized.contains("sin ejecutar")
-                        || normalized.contains("esperando")
-                        || normalized.contains("pendiente")
-                        || normalized.contains("sin actualizaciones")
-                        || normalized.contains("no configurado")
-                    {
-                        "PEND"
-                    } else {
-                        "OK"
-                    }
-                };
-
-                let mut lines = vec!["=== Diagnóstico avanzado del sistema ===".to_string()];
-                if !wants_all {
-                    lines.push(format!(
-                        "Filtro aplicado a la sección: {}.",
-                        requested_section
-                    ));
-                }
-
-                if wants_general {
-                    lines.push("--- Recursos y configuración ---".to_string());
-                    lines.push(format!(
-                        "Memoria límite: {:.1} GB · Disco límite: {:.1} GB · Auto limpieza: {} (cada {} h).",
-                        self.resource_memory_limit_gb,
-                        self.resource_disk_limit_gb,
-                        if self.enable_auto_cleanup {
-                            "activa"
-                        } else {
-                            "inactiva"
-                        },
-                        self.cache_cleanup_interval_hours
-                    ));
-                    lines.push(format!(
-                        "Directorio de caché: {} · Última limpieza: {}.",
-                        self.cache_directory,
-                        self.last_cache_cleanup
-                            .clone()
-                            .unwrap_or_else(|| "nunca".to_string())
-                    ));
-                    lines.push(format!(
-                        "Perfiles: {} · Proyectos: {} · Memoria contextual: {} ({} días).",
-                        self.profiles.len(),
-                        self.projects.len(),
-                        if self.enable_memory_tracking {
-                            "activa"
-                        } else {
-                            "inactiva"
-                        },
-                        self.memory_retention_days
-                    ));
-                }
-
-                if wants_remote {
-                    lines.push("--- Proveedores remotos ---".to_string());
-                    let openai_status = self
-                        .resources
-                        .openai_test_status
-                        .clone()
-                        .unwrap_or_else(|| "sin ejecutar".to_string());
-                    lines.push(format!(
-                        "OpenAI [{}] modelo por defecto '{}' · {}.",
-                        classify(&openai_status),
-                        self.resources.openai_default_model,
-                        openai_status
-                    ));
-
-                    let anthropic_status = self
-                        .resources
-                        .anthropic_test_status
-                        .clone()
-                        .unwrap_or_else(|| "sin ejecutar".to_string());
-                    lines.push(format!(
-                        "Claude [{}] modelo por defecto '{}' · {}.",
-                        classify(&anthropic_status),
-                        self.resources.claude_default_model,
-                        anthropic_status
-                    ));
-
-                    let claude_catalog = self
-                        .resources
-                        .claude_models_status
-                        .clone()
-                        .unwrap_or_else(|| {
-                            if self.resources.claude_available_models.is_empty() {
-                                "catálogo sin cargar".to_string()
-                            } else {
-                                format!(
-                                    "{} modelos disponibles en caché",
-                                    self.resources.claude_available_models.len()
-                                )
-                            }
-                        });
-                    lines.push(format!(
-                        "Claude catálogo [{}] {}.",
-                        classify(&claude_catalog),
-                        claude_catalog
-                    ));
-
-                    let groq_status = self
-                        .resources
-                        .groq_test_status
-                        .clone()
-                        .unwrap_or_else(|| "sin ejecutar".to_string());
-                    lines.push(format!(
-                        "Groq [{}] modelo por defecto '{}' · {}.",
-                        classify(&groq_status),
-                        self.resources.groq_default_model,
-                        groq_status
-                    ));
-                }
-
-                if wants_local {
-                    lines.push("--- Runtime local y Jarvis ---".to_string());
-                    let jarvis_status = self
-                        .resources
-                        .jarvis_status
-                        .clone()
-                        .unwrap_or_else(|| "sin actualizaciones registradas".to_string());
-                    let runtime_status = if let Some(runtime) = &self.resources.jarvis_runtime {
-                        format!("Inicializado ({})", runtime.model_label())
-                    } else {
-                        "No inicializado".to_string()
-                    };
-                    lines.push(format!(
-                        "Jarvis [{}] {}.",
-                        classify(&jarvis_status),
-                        jarvis_status
-                    ));
-                    lines.push(format!(
-                        "Runtime local: {} · Modelo configurado: {} · Instalación: {} · Autoarranque: {}.",
-                        runtime_status,
-                        self.resources.jarvis_model_path,
-                        self.resources.jarvis_install_dir,
-                        if self.resources.jarvis_auto_start {
-                            "sí"
-                        } else {
-                            "no"
-                        }
-                    ));
-
-                    if self.resources.installed_local_models.is_empty() {
-                        lines.push("Modelos instalados: ninguno.".to_string());
-                    } else {
-                        let inventory = self
-                            .resources
-                            .installed_local_models
-                            .iter()
-                            .map(|model| {
-                                let label = model.identifier.display_label();
-                                let size = format_bytes(model.size_bytes);
-                                let installed = model
-                                    .installed_at
-                                    .with_timezone(&Local)
-                                    .format("%Y-%m-%d %H:%M")
-                                    .to_string();
-                                format!("{} · {} · instalado {}", label, size, installed)
-                            })
-                            .collect::<Vec<_>>()
-                            .join(" | ");
-                        lines.push(format!(
-                            "Modelos instalados ({}): {}.",
-                            self.resources.installed_local_models.len(),
-                            inventory
-                        ));
-                    }
-
-                    if self.chat.pending_local_installs.is_empty() {
-                        lines.push("Instalaciones locales pendientes: ninguna.".to_string());
-                    } else {
-                        let installs = self
-                            .chat
-                            .pending_local_installs
-                            .iter()
-                            .map(|pending| {
-                                format!(
-                                    "{} › {}",
-                                    pending.provider.display_name(),
-                                    pending.model_id
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                            .join(" · ");
-                        lines.push(format!(
-                            "Instalaciones locales pendientes ({}): {}.",
-                            self.chat.pending_local_installs.len(),
-                            installs
-                        ));
-                    }
-
-                    if self.chat.pending_provider_calls.is_empty() {
-                        lines.push("Llamadas remotas en vuelo: ninguna.".to_string());
-                    } else {
-                        let preview = self
-                            .chat
-                            .pending_provider_calls
-                            .iter()
-                            .take(3)
-                            .map(|call| {
-                                format!(
-                                    "#{} {} · {} ({})",
-                                    call.ticket.id,
-                                    call.ticket.provider_name,
-                                    call.ticket.alias,
-                                    call.ticket.model
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                            .join(" · ");
-                        lines.push(format!(
-                            "Llamadas remotas en vuelo ({}): {}{}.",
-                            self.chat.pending_provider_calls.len(),
-                            preview,
-                            if self.chat.pending_provider_calls.len() > 3 {
-                                " · ..."
-                            } else {
-                                ""
-                            }
-                        ));
-                    }
-
-                    lines.push("--- Proveedores locales ---".to_string());
-                    for provider in LocalModelProvider::ALL {
-                        let provider_state = self.provider_state(provider);
-                        let token_state = if provider_state
-                            .access_token
-                            .as_ref()
-                            .map(|token| !token.trim().is_empty())
-                            .unwrap_or(false)
-                        {
-                            "token configurado"
-                        } else {
-                            "sin token"
-                        };
-                        let selection = provider_state
-                            .selected_model
-                            .and_then(|index| provider_state.models.get(index))
-                            .map(|model| model.id.clone())
-                            .unwrap_or_else(|| "ninguno".to_string());
-                        let install_state = provider_state
-                            .install_status
-                            .clone()
-                            .unwrap_or_else(|| "sin operaciones registradas".to_string());
-
-                        lines.push(format!(
-                            "{} → {} · modelos listados: {} · selección: {} · última instalación [{}] {}.",
-                            provider.display_name(),
-                            token_state,
-                            provider_state.models.len(),
-                            selection,
-                            classify(&install_state),
-                            install_state
-                        ));
-                    }
-                }
-
-                if wants_commands {
-                    lines.push("--- Comandos personalizados ---".to_string());
-                    if self.chat.custom_commands.is_empty() {
-                        lines.push("No hay comandos personalizados registrados.".to_string());
-                    } else {
-                        for command in &self.chat.custom_commands {
-                            lines.push(format!("{} → {}", command.trigger, command.action.label()));
-                        }
-                        lines.push(format!(
-                            "Total de comandos personalizados: {}.",
-                            self.chat.custom_commands.len()
-                        ));
-                    }
-                }
-
-                if wants_logs {
-                    lines.push("--- Registros y alertas ---".to_string());
-                    let ok_count = self
-                        .automation
-                        .activity_logs
-                        .iter()
-                        .filter(|entry| entry.status == LogStatus::Ok)
-                        .count();
-                    let warn_count = self
-                        .automation
-                        .activity_logs
-                        .iter()
-                        .filter(|entry| entry.status == LogStatus::Warning)
-                        .count();
-                    let err_count = self
-                        .automation
-                        .activity_logs
-                        .iter()
-                        .filter(|entry| entry.status == LogStatus::Error)
-                        .count();
-                    let running_count = self
-                        .automation
-                        .activity_logs
-                        .iter()
-                        .filter(|entry| entry.status == LogStatus::Running)
-                        .count();
-
-                    if let Some(last_error) = self
-                        .automation
-                        .activity_logs
-                        .iter()
-                        .rev()
-                        .find(|entry| entry.status == LogStatus::Error)
-                    {
-                        lines.push(format!(
-                            "Último error registrado a las {} desde {} → {}.",
-                            last_error.timestamp, last_error.source, last_error.message
-                        ));
-                    } else {
-                        lines.push("No hay errores registrados en los logs recientes.".to_string());
-                    }
-
-                    lines.push(format!(
-                        "Resumen de logs → OK: {} · Warning: {} · Error: {} · Running: {}.",
-                        ok_count, warn_count, err_count, running_count
-                    ));
-                }
-
-                lines
-            }
-            CustomCommandAction::ShowUsageStatistics => {
-                let window = invocation.arg("window").unwrap_or("session");
-                let include = invocation
-                    .arg("include")
-                    .map(|value| {
-                        value
-                            .split(',')
-                            .map(|s| s.trim().to_string())
-                            .collect::<Vec<_>>()
-                    })
-                    .unwrap_or_default();
-
-                let mut lines = vec![format!(
-                    "Estadísticas (ventana: {}): {} mensajes registrados en esta sesión y {} comandos personalizados disponibles.",
-                    window,
-                    self.chat.messages.len(),
-                    self.chat.custom_commands.len()
-                )];
-
-                if include.iter().any(|s| s == "commands") {
-                    lines.push(format!(
-                        "Triggers personalizados: {}",
-                        self.chat
-                            .custom_commands
-                            .iter()
-                            .map(|cmd| cmd.trigger.clone())
-                            .collect::<Vec<_>>()
-                            .join(", ")
-                    ));
-                }
+    /// Valida el borrador y lo guarda como enrutado de la conversación abierta.
+    pub fn save_conversation_defaults(&mut self) -> Result<(), String> {
+        let defaults = self.conversations.draft.normalized()?;
+        let key = self.bookmarks.conversation.clone();
+        let route = self.conversation_route_label(&defaults);
+        self.conversations.set(&key, defaults.clone());
+        self.conversations.draft = defaults;
+        if let Err(err) = self.conversations.save() {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "chat::conversations",
+                format!(
+                    "No se pudieron guardar los ajustes de la conversación: {}",
+                    err
+                ),
+            );
+        }
+        self.conversations.status = Some(format!("Ajustes guardados. Sin mención: {}.", route));
+        self.chat_routing.update_status(Some(format!(
+            "Los mensajes sin mención de esta conversación van a {}.",
+            route
+        )));
+        Ok(())
+    }
+
+    /// Cambia el idioma de respuesta de la conversación abierta sin pasar por el borrador
+    /// del menú de ajustes. `None` vuelve a la detección automática.
+    pub fn set_reply_language(&mut self, code: Option<String>) -> Result<(), String> {
+        let mut defaults = self.active_conversation_defaults();
+        defaults.reply_language = code;
+        let defaults = defaults.normalized()?;
+        let label = match defaults.reply_language.as_deref() {
+            Some(code) => language::language_name(code).unwrap_or(code).to_string(),
+            None => "automático".to_string(),
+        };
+        let key = self.bookmarks.conversation.clone();
+        self.conversations.set(&key, defaults.clone());
+        self.conversations.draft = defaults;
+        if let Err(err) = self.conversations.save() {
+            self.push_debug_event(
+                DebugLogLevel::Warning,
+                "chat::conversations",
+                format!(
+                    "No se pudieron guardar los ajustes de la conversación: {}",
+                    err
+                ),
+            );
+        }
+        self.chat_routing.update_status(Some(format!(
+            "Idioma de respuesta de esta conversación: {}.",
+            label
+        )));
+        Ok(())
+    }
 
-                if include.iter().any(|s| s == "messages") {
-                    lines.push(format!(
-                        "Último mensaje de usuario: {}",
-                        self.chat
-                            .messages
-                            .iter()
-                            .rev()
-                            .find(|msg| msg.sender == "User")
-                            .map(|msg| msg.text.clone())
-                            .unwrap_or_else(|| "sin actividad".to_string())
-                    ));
-                }
+    /// Idioma detectado en el último mensaje del usuario, para la cabecera del chat.
+    pub fn detected_user_language(&self) -> Option<&'static str> {
+        self.chat
+            .messages
+            .iter()
+            .rev()
+            .find(|message| message.sender == "User")
+            .and_then(|message| language::detect_language(&message.text))
+    }
+
+    /// Publica `input` como mensaje del usuario en la conversación abierta y lo reparte
+    /// entre comandos, menciones de proveedores, el alias de Jarvis y la ruta por defecto.
+    pub fn dispatch_user_message(&mut self, input: String, quote: Option<MessageQuote>) {
+        let mut user_message = ChatMessage::user(input.clone());
+        user_message.reply_to = quote.clone();
+        self.chat.messages.push(user_message);
+        if input.starts_with('/') {
+            self.handle_command(input);
+            return;
+        }
 
-                lines
-            }
-            CustomCommandAction::ListActiveProjects => {
-                let limit = invocation
-                    .arg("limit")
-                    .and_then(|value| value.parse::<usize>().ok());
-                let mut projects = self.projects.clone();
-                if let Some(max) = limit {
-                    projects.truncate(max);
-                }
+        let payload = EventPayload::default()
+            .with("message", input.as_str())
+            .with("message.sender", "user")
+            .with("message.length", input.chars().count());
+        self.dispatch_listener_event(ListenerEventKind::ChatMessage, &payload);
+        let residual = self.try_route_provider_message(&input, quote.as_ref());
 
-                if projects.is_empty() {
-                    vec!["No hay proyectos configurados actualmente.".to_string()]
-                } else {
-                    vec![format!("Proyectos activos: {}.", projects.join(", "))]
-                }
-            }
-            CustomCommandAction::ListConfiguredProfiles => {
-                let mut profiles = self.profiles.clone();
-                match invocation.arg("sort").unwrap_or("asc") {
-                    "desc" => profiles.sort_by(|a, b| b.cmp(a)),
-                    _ => profiles.sort(),
-                }
+        if self.try_invoke_jarvis_alias(residual.as_str(), quote.as_ref()) {
+            return;
+        }
 
-                if profiles.is_empty() {
-                    vec!["No hay perfiles configurados.".to_string()]
-                } else {
-                    vec![format!("Perfiles disponibles: {}.", profiles.join(", "))]
-                }
-            }
-            CustomCommandAction::ShowCacheConfiguration => {
-                let include = invocation
-                    .arg("include")
-                    .map(|value| {
-                        value
-                            .split(',')
-                            .map(|s| s.trim().to_string())
-                            .collect::<Vec<_>>()
-                    })
-                    .unwrap_or_else(|| vec!["limits".to_string()]);
-                let mut lines = Vec::new();
-
-                if include.iter().any(|s| s == "path") {
-                    lines.push(format!("Directorio de caché: {}", self.cache_directory));
-                }
-                if include.iter().any(|s| s == "limits") {
-                    lines.push(format!(
-                        "Límite configurado: {:.1} GB, limpieza automática: {}.",
-                        self.cache_size_limit_gb,
-                        if self.enable_auto_cleanup {
-                            "sí"
-                        } else {
-                            "no"
-                        }
-                    ));
-                }
-                if include.iter().any(|s| s == "schedule") {
-                    lines.push(format!(
-                        "La limpieza se programa cada {} horas. Última ejecución: {}.",
-                        self.cache_cleanup_interval_hours,
-                        self.last_cache_cleanup
-                            .clone()
-                            .unwrap_or_else(|| "nunca".to_string())
-                    ));
-                }
+        let trimmed_residual = residual.trim();
+        if trimmed_residual.is_empty() {
+            return;
+        }
 
-                if lines.is_empty() {
-                    lines.push("No se reconocieron parámetros para mostrar.".to_string());
-                }
-                lines
-            }
-            CustomCommandAction::ListAvailableModels => {
-                let provider = invocation.arg("provider").unwrap_or("all");
-                let mut lines = Vec::new();
-
-                match provider {
-                    "openai" => lines.push(format!(
-                        "Modelo OpenAI activo: {}",
-                        self.resources.openai_default_model
-                    )),
-                    "anthropic" => lines.push(format!(
-                        "Modelo Claude activo: {}",
-                        self.resources.claude_default_model
-                    )),
-                    "groq" => lines.push(format!(
-                        "Modelo Groq activo: {}",
-                        self.resources.groq_default_model
-                    )),
-                    "jarvis" => lines.push(format!(
-                        "Jarvis está configurado con: {}",
-                        self.resources.jarvis_model_path
-                    )),
-                    "huggingface" => {
-                        let provider_state = self.provider_state(LocalModelProvider::HuggingFace);
-                        if provider_state.models.is_empty() {
-                            lines.push("No hay modelos de HuggingFace registrados.".to_string());
-                        } else {
-                            let joined = provider_state
-                                .models
-                                .iter()
-                                .map(|model| model.id.as_str())
-                                .collect::<Vec<_>>()
-                                .join(", ");
-                            lines.push(format!("Modelos de HuggingFace: {}", joined));
-                        }
-                    }
-                    "all" => {
-                        lines.push(format!(
-                            "OpenAI: {} · Claude: {} · Groq: {} · Jarvis: {}",
-                            self.resources.openai_default_model,
-                            self.resources.claude_default_model,
-                            self.resources.groq_default_model,
-                            self.resources.jarvis_model_path
-                        ));
-                        let provider_state = self.provider_state(LocalModelProvider::HuggingFace);
-                        if provider_state.models.is_empty() {
-                            lines.push("HuggingFace: sin resultados cargados.".to_string());
-                        } else {
-                            let preview: Vec<&str> = provider_state
-                                .models
-                                .iter()
-                                .take(5)
-                                .map(|model| model.id.as_str())
-                                .collect();
-                            lines.push(format!(
-                                "HuggingFace ({} modelos): {}",
-                                provider_state.models.len(),
-                                preview.join(", ")
-                            ));
-                        }
-                    }
-                    other => lines.push(format!("Proveedor desconocido: {}", other)),
-                }
+        let prompt = match quote.as_ref() {
+            Some(quote) => quote.wrap_prompt(trimmed_residual),
+            None => trimmed_residual.to_string(),
+        };
+        if self.try_route_conversation_default(&prompt) {
+            return;
+        }
+        self.respond_with_jarvis(prompt);
+    }
 
-                lines
-            }
-            CustomCommandAction::ShowGithubSummary => {
-                let include_repos = invocation
-                    .arg("include")
-                    .map(|value| value.split(',').any(|v| v.trim() == "repos"))
-                    .unwrap_or(false);
-
-                let mut lines =
-                    vec![
-                        match (&self.github_username, self.github_repositories.is_empty()) {
-                            (Some(username), false) => format!(
-                                "GitHub autenticado como {} con {} repositorios sincronizables.",
-                                username,
-                                self.github_repositories.len()
-                            ),
-                            (Some(username), true) => format!(
-                        "GitHub autenticado como {}, pero no se encontraron repositorios visibles.",
-                        username
-                    ),
-                            _ => "GitHub no está conectado todavía.".to_string(),
-                        },
-                    ];
+    /// Envía un mensaje sin mención al proveedor por defecto de la conversación. Devuelve
+    /// `false` si no hay ninguno o si, sin conexión, debe responder Jarvis.
+    pub fn try_route_conversation_default(&mut self, prompt: &str) -> bool {
+        let Some(provider) = self.effective_conversation_defaults().provider_kind() else {
+            return false;
+        };
+        !matches!(
+            self.invoke_provider_kind(provider, prompt.to_string()),
+            ProviderCallDispatch::Offline { .. }
+        )
+    }
 
-                if include_repos && !self.github_repositories.is_empty() {
-                    lines.push(format!(
-                        "Repositorios: {}",
-                        self.github_repositories.join(", ")
-                    ));
-                }
+    pub fn try_invoke_jarvis_alias(&mut self, input: &str, quote: Option<&MessageQuote>) -> bool {
+        if let Some(prompt) = Self::extract_alias_prompt(&self.resources.jarvis_alias, input) {
+            let prompt = match quote {
+                Some(quote) => quote.wrap_prompt(&prompt),
+                None => prompt,
+            };
+            self.respond_with_jarvis(prompt);
+            true
+        } else if let Some((alias, model, prompt)) = self.match_model_alias(input) {
+            let prompt = match quote {
+                Some(quote) => quote.wrap_prompt(&prompt),
+                None => prompt,
+            };
+            self.respond_with_model_alias(&alias, &model, &prompt);
+            true
+        } else {
+            false
+        }
+    }
 
-                lines
-            }
-            CustomCommandAction::ShowMemorySettings => {
-                let detail = invocation.arg("detail").unwrap_or("summary");
-                let mut lines = vec![format!(
-                    "Memoria contextual {} con retención de {} días.",
-                    if self.enable_memory_tracking {
-                        "activada"
-                    } else {
-                        "desactivada"
-                    },
-                    self.memory_retention_days
-                )];
+    /// Alias por modelo con el que empieza `input`, su modelo y el prompt que le sigue.
+    fn match_model_alias(&self, input: &str) -> Option<(String, LocalModelIdentifier, String)> {
+        self.config.jarvis.model_aliases.iter().find_map(|entry| {
+            let prompt = Self::extract_alias_prompt(&entry.alias, input)?;
+            Some((
+                entry.alias.clone(),
+                LocalModelIdentifier::parse(&entry.model),
+                prompt,
+            ))
+        })
+    }
 
-                if detail == "retention" {
-                    lines.push("Los recuerdos más antiguos se archivan para mantener el contexto relevante.".to_string());
-                }
+    /// Responde con el modelo de `alias` sin cambiar el modelo activo de Jarvis.
+    fn respond_with_model_alias(
+        &mut self,
+        alias: &str,
+        model: &LocalModelIdentifier,
+        prompt: &str,
+    ) {
+        let tag = format!("@{}", alias.trim().trim_start_matches('@'));
+        let message = match self.generate_local_reply(Some(model), prompt) {
+            Ok(reply) => ChatMessage::new(tag.clone(), reply).with_mention(tag),
+            Err(err) => ChatMessage::system(format!("{} no pudo responder: {}", tag, err)),
+        };
+        self.chat.messages.push(message);
+    }
 
-                lines
-            }
-            CustomCommandAction::ShowActiveProviders => {
-                let include = invocation
-                    .arg("include")
-                    .map(|value| {
-                        value
-                            .split(',')
-                            .map(|s| s.trim().to_string())
-                            .collect::<Vec<_>>()
-                    })
-                    .unwrap_or_default();
-                let mut lines = vec![format!(
-                    "Proveedores activos → OpenAI ({}) · Claude ({}) · Groq ({})",
-                    self.resources.openai_default_model,
-                    self.resources.claude_default_model,
-                    self.resources.groq_default_model
-                )];
-
-                if include.iter().any(|s| s == "models") {
-                    lines.push(format!(
-                        "Jarvis usa {} y hay {} modelos de HuggingFace listos.",
-                        self.resources.jarvis_model_path,
-                        self.provider_state(LocalModelProvider::HuggingFace)
-                            .models
-                            .len()
-                    ));
-                }
-                if include.iter().any(|s| s == "status") {
-                    lines.push(format!(
-                        "Estado de pruebas → OpenAI: {} · Claude: {} · Groq: {}",
-                        self.resources
-                            .openai_test_status
-                            .clone()
-                            .unwrap_or_else(|| "sin ejecutar".to_string()),
-                        self.resources
-                            .anthropic_test_status
-                            .clone()
-                            .unwrap_or_else(|| "sin ejecutar".to_string()),
-                        self.resources
-                            .groq_test_status
-                            .clone()
-                            .unwrap_or_else(|| "sin ejecutar".to_string())
-                    ));
-                }
+    /// Añade un alias por modelo. El alias no puede repetir el de Jarvis ni el de otro
+    /// modelo.
+    pub fn add_model_alias(
+        &mut self,
+        alias: &str,
+        model: &LocalModelIdentifier,
+    ) -> Result<String, String> {
+        let alias = alias.trim().trim_start_matches('@').to_lowercase();
+        if alias.is_empty() || alias.contains(char::is_whitespace) {
+            return Err("El alias debe ser una sola palabra.".to_string());
+        }
+        let taken = std::iter::once(self.resources.jarvis_alias.as_str())
+            .chain(
+                self.config
+                    .jarvis
+                    .model_aliases
+                    .iter()
+                    .map(|entry| entry.alias.as_str()),
+            )
+            .any(|existing| {
+                existing
+                    .trim()
+                    .trim_start_matches('@')
+                    .eq_ignore_ascii_case(&alias)
+            });
+        if taken {
+            return Err(format!("El alias @{} ya está en uso.", alias));
+        }
+        if self.installed_model(model).is_none() {
+            return Err(format!("'{}' no está instalado.", model.display_label()));
+        }
+        self.config.jarvis.model_aliases.push(JarvisModelAlias {
+            alias: alias.clone(),
+            model: model.serialize(),
+        });
+        self.persist_config();
+        Ok(format!(
+            "@{} responderá con {}.",
+            alias,
+            model.display_label()
+        ))
+    }
 
-                lines
-            }
-            CustomCommandAction::ShowJarvisStatus => {
-                let detail = invocation.arg("detail").unwrap_or("summary");
-                let mut lines = vec![format!(
-                    "Jarvis en '{}' ({}) → {}",
-                    self.resources.jarvis_model_path,
-                    if self.resources.jarvis_auto_start {
-                        "autoarranque habilitado"
-                    } else {
-                        "autoarranque deshabilitado"
-                    },
-                    self.resources
-                        .jarvis_status
-                        .clone()
-                        .unwrap_or_else(|| "Jarvis esperando tareas.".to_string())
-                )];
+    pub fn remove_model_alias(&mut self, index: usize) {
+        if index >= self.config.jarvis.model_aliases.len() {
+            return;
+        }
+        let removed = self.config.jarvis.model_aliases.remove(index);
+        let model = LocalModelIdentifier::parse(&removed.model);
+        let still_used = self
+            .config
+            .jarvis
+            .model_aliases
+            .iter()
+            .any(|entry| entry.model == removed.model);
+        if !still_used {
+            self.resources.jarvis_pool.remove(&model);
+        }
+        self.persist_config();
+    }
 
-                match detail {
-                    "path" => lines.push(format!(
-                        "El modelo local se puede actualizar reemplazando el archivo en {}.",
-                        self.resources.jarvis_model_path
-                    )),
-                    "logs" => lines.push("Los registros en tiempo real no están disponibles en modo demo, pero se guardan en /var/log/jarvis.".to_string()),
-                    _ => {}
-                }
+    pub fn jarvis_mention_tag(&self) -> Option<String> {
+        let alias = self.resources.jarvis_alias.trim();
+        if alias.is_empty() {
+            None
+        } else if alias.starts_with('@') {
+            Some(alias.to_string())
+        } else {
+            Some(format!("@{}", alias))
+        }
+    }
 
-                lines
+    /// Guarda el comando del editor, ya sea nuevo o el que se estaba modificando.
+    pub fn save_custom_command_draft(&mut self) -> Result<String, String> {
+        let command = self.chat.command_draft.to_command()?;
+        let editing = self.chat.command_draft.editing;
+        if command.trigger == "/if" {
+            return Err("/if está reservado para las condiciones.".to_string());
+        }
+        let duplicated = self
+            .chat
+            .custom_commands
+            .iter()
+            .enumerate()
+            .any(|(index, existing)| existing.trigger == command.trigger && Some(index) != editing);
+        if duplicated {
+            return Err(format!("El comando '{}' ya existe.", command.trigger));
+        }
+
+        let message = match editing.filter(|index| *index < self.chat.custom_commands.len()) {
+            Some(index) => {
+                self.chat.custom_commands[index] = command.clone();
+                format!(
+                    "'{}' actualizado ({}).",
+                    command.trigger,
+                    command.target_label()
+                )
             }
-            CustomCommandAction::ShowCommandHelp => {
-                let mode = invocation.arg("mode").unwrap_or("all");
-                let mut builtins = vec![
-                    "/status",
-                    "/system debug",
-                    "/models",
-                    "/stats",
-                    "/reload",
-                    "/help",
-                    "/if",
-                ];
-                builtins.extend([
-                    "/time",
-                    "/projects",
-                    "/profiles",
-                    "/cache",
-                    "/github",
-                    "/memory",
-                    "/providers",
-                    "/jarvis",
-                ]);
-                let custom: Vec<String> = self
-                    .chat
-                    .custom_commands
-                    .iter()
-                    .map(|cmd| cmd.trigger.clone())
-                    .collect();
-
-                let mut lines = Vec::new();
-                match mode {
-                    "builtins" => lines.push(format!("Comandos base: {}", builtins.join(", "))),
-                    "custom" => {
-                        if custom.is_empty() {
-                            lines.push("No hay comandos personalizados.".to_string());
-                        } else {
-                            lines.push(format!("Comandos personalizados: {}", custom.join(", ")));
-                        }
-                    }
-                    _ => {
-                        lines.push(format!("Comandos base: {}", builtins.join(", ")));
-                        if custom.is_empty() {
-                            lines.push("Comandos personalizados: ninguno configurado.".to_string());
-                        } else {
-                            lines.push(format!("Comandos personalizados: {}", custom.join(", ")));
-                        }
-                    }
-                }
-
-                if mode == "all" || mode == "builtins" {
-                    lines.push(
-                        "Utiliza '/if <condición> then <cmd>' para ejecutar lógica condicional."
-                            .to_string(),
-                    );
-                }
+            None => {
+                self.chat.custom_commands.push(command.clone());
+                format!(
+                    "'{}' añadido ({}).",
+                    command.trigger,
+                    command.target_label()
+                )
+            }
+        };
+        self.chat.command_draft = CustomCommandDraft::default();
+        self.chat.command_feedback = Some(message.clone());
+        self.persist_config();
+        Ok(message)
+    }
 
-                lines
+    pub fn remove_custom_command(&mut self, index: usize) {
+        if index >= self.chat.custom_commands.len() {
+            return;
+        }
+        let command = self.chat.custom_commands.remove(index);
+        match self.chat.command_draft.editing {
+            Some(editing) if editing == index => {
+                self.chat.command_draft = CustomCommandDraft::default();
             }
+            Some(editing) if editing > index => {
+                self.chat.command_draft.editing = Some(editing - 1);
+            }
+            _ => {}
         }
+        self.chat.command_feedback = Some(format!(
+            "'{}' eliminado ({}).",
+            command.trigger,
+            command.target_label()
+        ));
+        self.persist_config();
     }
-}
 
-pub fn compute_directory_size(path: &Path) -> u64 {
-    fn visit(path: &Path, total: &mut u64) {
-        match fs::metadata(path) {
-            Ok(metadata) if metadata.is_file() => {
-                *total += metadata.len();
-            }
-            Ok(metadata) if metadata.is_dir() => {
-                if let Ok(entries) = fs::read_dir(path) {
-                    for entry in entries.flatten() {
-                        visit(&entry.path(), total);
-                    }
+    /// Ejecuta el comando del editor con los argumentos de prueba y guarda su salida.
+    pub fn test_custom_command_draft(&mut self) {
+        let output = match self.chat.command_draft.to_command() {
+            Ok(command) => {
+                let arguments = self.chat.command_draft.test_arguments.clone();
+                let outcome = self.run_custom_command_preview(command, &arguments);
+                if outcome.messages.is_empty() {
+                    vec!["El comando no devolvió salida.".to_string()]
+                } else {
+                    outcome.messages
                 }
             }
-            _ => {}
+            Err(err) => vec![err],
+        };
+        self.chat.command_draft.test_output = output;
+    }
+
+    pub fn handle_command(&mut self, command_input: String) {
+        let trimmed = command_input.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+
+        let invocation = CommandInvocation::parse(trimmed);
+        if invocation.name.is_empty() {
+            return;
+        }
+
+        let payload = EventPayload::default()
+            .with("command.name", invocation.name.as_str())
+            .with("command.input", trimmed)
+            .with("command.args", invocation.positional.join(" "));
+        self.record_command_usage(&invocation.name);
+        let outcome = self.run_command_line(trimmed);
+        for message in outcome.messages {
+            self.chat.messages.push(ChatMessage::system(message));
         }
+        self.dispatch_listener_event(ListenerEventKind::CommandExecution, &payload);
     }
+}
 
-    let mut total = 0u64;
-    visit(path, &mut total);
-    total
+/// Abreviatura de una revisión de git para mostrarla en la interfaz.
+pub fn short_revision(revision: &str) -> &str {
+    revision.get(..7).unwrap_or(revision)
 }
 
 pub fn format_bytes(bytes: u64) -> String {
@@ -5716,52 +15797,9 @@ pub fn format_bytes(bytes: u64) -> String {
     }
 }
 
-enum ConditionValue {
-    Boolean(bool),
-    Number(f64),
-}
-
-impl ConditionValue {
-    fn compare(&self, operator: &str, rhs: &str) -> Result<bool, String> {
-        match self {
-            ConditionValue::Boolean(lhs) => {
-                let rhs_bool = match rhs {
-                    "true" | "1" => Ok(true),
-                    "false" | "0" => Ok(false),
-                    _ => Err("Se esperaba un valor booleano (true/false).".to_string()),
-                }?;
-                match operator {
-                    "==" => Ok(*lhs == rhs_bool),
-                    "!=" => Ok(*lhs != rhs_bool),
-                    _ => Err(format!(
-                        "Operador '{}' no soportado para booleanos.",
-                        operator
-                    )),
-                }
-            }
-            ConditionValue::Number(lhs) => {
-                let rhs_num = rhs
-                    .parse::<f64>()
-                    .map_err(|_| "Se esperaba un número en la comparación.".to_string())?;
-                match operator {
-                    "==" => Ok((*lhs - rhs_num).abs() < f64::EPSILON),
-                    "!=" => Ok((*lhs - rhs_num).abs() >= f64::EPSILON),
-                    ">" => Ok(*lhs > rhs_num),
-                    ">=" => Ok(*lhs >= rhs_num),
-                    "<" => Ok(*lhs < rhs_num),
-                    "<=" => Ok(*lhs <= rhs_num),
-                    _ => Err(format!(
-                        "Operador '{}' no soportado para números.",
-                        operator
-                    )),
-                }
-            }
-        }
-    }
-}
-
 impl AppShell for AppState {
     fn init(&mut self, cc: &eframe::CreationContext<'_>) {
+        runtime::install_repaint(&cc.egui_ctx);
         theme::install_fonts(&cc.egui_ctx, self.font_sources.clone());
         theme::apply(&cc.egui_ctx, &self.theme);
     }
@@ -5840,4 +15878,227 @@ mod tests {
         assert!(state.activate_navigation_node("main:custom-hook"));
         assert_eq!(state.active_main_view, MainView::DebugConsole);
     }
+
+    #[test]
+    fn task_chains_skip_dependents_on_failure_and_run_them_on_success() {
+        let mut state = AppState::default();
+        state.config.cron_dependencies.clear();
+        state
+            .add_task_dependency("Informe de métricas", "Sincronización de repositorios")
+            .expect("dependencia");
+        state
+            .add_task_dependency("Limpieza de caché", "Informe de métricas")
+            .expect("dependencia");
+        assert!(state
+            .add_task_dependency("Sincronización de repositorios", "Limpieza de caché")
+            .is_err());
+
+        state.advance_task_chain(
+            "Sincronización de repositorios",
+            ScheduledTaskStatus::Failed,
+        );
+        let board = &state.automation.cron_board;
+        for name in ["Informe de métricas", "Limpieza de caché"] {
+            assert_eq!(
+                board.task_by_name(name).map(|task| task.status),
+                Some(ScheduledTaskStatus::Skipped)
+            );
+            assert!(board.skip_reasons.contains_key(name));
+        }
+
+        state.advance_task_chain(
+            "Sincronización de repositorios",
+            ScheduledTaskStatus::Success,
+        );
+        let board = &state.automation.cron_board;
+        assert_eq!(
+            board
+                .task_by_name("Informe de métricas")
+                .map(|task| task.status),
+            Some(ScheduledTaskStatus::Running)
+        );
+        assert!(!board.skip_reasons.contains_key("Informe de métricas"));
+        assert_eq!(
+            board
+                .task_by_name("Limpieza de caché")
+                .map(|task| task.status),
+            Some(ScheduledTaskStatus::Skipped)
+        );
+    }
+
+    #[test]
+    fn popped_out_views_keep_their_geometry_until_docked() {
+        let mut state = AppState::default();
+        state.config.layout.popouts.clear();
+
+        state.pop_out_view(MainView::Preferences);
+        assert!(!state.is_popped_out(MainView::Preferences));
+
+        state.pop_out_view(MainView::DebugConsole);
+        state.pop_out_view(MainView::DebugConsole);
+        assert_eq!(state.config.layout.popouts.len(), 1);
+
+        state.update_popout_geometry(MainView::DebugConsole, [40.0, 60.0], [800.0, 600.0]);
+        let popout = &state.config.layout.popouts[0];
+        assert_eq!(popout.position, Some([40.0, 60.0]));
+        assert_eq!(popout.size, [800.0, 600.0]);
+
+        state.dock_view(MainView::DebugConsole);
+        assert!(!state.is_popped_out(MainView::DebugConsole));
+    }
+
+    #[test]
+    fn conversation_defaults_pick_model_and_carry_over_to_forks() {
+        let mut state = AppState::default();
+        state.conversations.entries.clear();
+        state.chat.messages.push(ChatMessage::user("Hola"));
+        let key = state.bookmarks.conversation.clone();
+        state.conversations.set(
+            &key,
+            ConversationDefaults {
+                provider: Some("groq".to_string()),
+                model: Some("llama3-70b".to_string()),
+                persona: "Revisor".to_string(),
+                ..ConversationDefaults::default()
+            },
+        );
+
+        assert_eq!(
+            state.provider_call_profile(RemoteProviderKind::Groq).model,
+            "llama3-70b"
+        );
+        assert_ne!(
+            state
+                .provider_call_profile(RemoteProviderKind::OpenAi)
+                .model,
+            "llama3-70b"
+        );
+        assert_eq!(
+            state.conversation_route_label(&state.active_conversation_defaults()),
+            "Groq · llama3-70b"
+        );
+
+        state.fork_conversation(0).expect("bifurcación");
+        assert_ne!(state.bookmarks.conversation, key);
+        let forked = state.active_conversation_defaults();
+        assert_eq!(forked.provider.as_deref(), Some("groq"));
+        assert_eq!(
+            forked.generation_options().system.as_deref(),
+            Some("Revisor")
+        );
+    }
+
+    #[test]
+    fn restoring_previous_session_replaces_conversation_and_view() {
+        let mut state = AppState::default();
+        state.session.recovery = Some(SessionSnapshot {
+            saved_at: "2024-05-01 10:00:00".to_string(),
+            conversation_key: "2024-05-01 09:00:00".to_string(),
+            active_view: MainView::CronScheduler,
+            input: "a medio escribir".to_string(),
+            chat: share::SharedConversation::from_messages(&[
+                ChatMessage::user("Hola"),
+                ChatMessage::system("Respuesta"),
+            ]),
+        });
+        state.chat.input.clear();
+
+        assert_eq!(state.restore_previous_session(), Some(2));
+        assert!(state.session.recovery.is_none());
+        assert_eq!(state.chat.messages[0].text, "Hola");
+        assert_eq!(state.chat.input, "a medio escribir");
+        assert_eq!(state.active_main_view, MainView::CronScheduler);
+        assert_eq!(state.bookmarks.conversation, "2024-05-01 09:00:00");
+        assert_eq!(
+            state
+                .threads
+                .active_thread()
+                .map(|thread| thread.key.as_str()),
+            Some("2024-05-01 09:00:00")
+        );
+        assert_eq!(state.restore_previous_session(), None);
+    }
+
+    #[test]
+    fn anthropic_models_enrich_existing_cards_and_add_new_ones() {
+        let mut models: Vec<AnthropicModel> = serde_json::from_value(serde_json::json!([
+            { "id": "claude-3-opus-20240229", "display_name": "Claude 3 Opus" },
+            { "id": "claude-sonnet-4-20250514", "display_name": "Claude Sonnet 4" }
+        ]))
+        .unwrap();
+        models
+            .iter_mut()
+            .for_each(AnthropicModel::enrich_capabilities);
+
+        let mut catalog = RemoteCatalogState::default();
+        let before = catalog.cards_for(RemoteProviderKind::Anthropic).len();
+        assert_eq!(catalog.merge_anthropic_models(&models), 1);
+
+        let cards = catalog.cards_for(RemoteProviderKind::Anthropic);
+        assert_eq!(cards.len(), before + 1);
+        let sonnet = cards
+            .iter()
+            .find(|card| card.key.id == "claude-sonnet-4-20250514")
+            .unwrap();
+        assert_eq!(sonnet.context_tokens, 200_000);
+        assert!(sonnet.multimodal);
+        assert_eq!(catalog.merge_anthropic_models(&models), 0);
+    }
+
+    #[test]
+    fn vision_provider_follows_routing_and_multimodal_cards() {
+        let mut state = AppState::default();
+        state.config.anthropic.api_key = None;
+        state.config.openai.api_key = None;
+        state.config.groq.api_key = Some("gsk-test".to_string());
+        state.chat.model_override = None;
+        assert_eq!(state.vision_provider(), None);
+
+        state.config.anthropic.api_key = Some("sk-ant-test".to_string());
+        state.resources.claude_default_model = "claude-3-haiku-20240307".to_string();
+        state.conversations = ConversationStore::default();
+        assert_eq!(
+            state.vision_provider(),
+            Some((
+                RemoteProviderKind::Anthropic,
+                "claude-3-haiku-20240307".to_string()
+            ))
+        );
+
+        state
+            .chat_routing
+            .suggestions
+            .retain(|suggestion| suggestion.provider != RemoteProviderKind::Anthropic);
+        state.config.openai.api_key = Some("sk-test".to_string());
+        let (provider, _) = state.vision_provider().unwrap();
+        assert_eq!(provider, RemoteProviderKind::OpenAi);
+    }
+
+    #[test]
+    fn local_only_projects_block_remote_calls_until_confirmed() {
+        let mut state = AppState::default();
+        state.config.local_only_projects = vec!["Workspace · Automation".to_string()];
+        state.chat.messages.push(ChatMessage::user(
+            "Scripts para pipelines QA, builds nocturnos y despliegues sandbox.",
+        ));
+
+        let draft = format!("@{} resume lo anterior", state.config.anthropic.alias);
+        assert_eq!(
+            state.local_only_conflict(&draft),
+            vec!["Workspace · Automation".to_string()]
+        );
+
+        state.chat.messages.push(ChatMessage::user(draft.clone()));
+        let dispatch =
+            state.invoke_provider_kind(RemoteProviderKind::Anthropic, "resume".to_string());
+        assert!(matches!(
+            dispatch,
+            ProviderCallDispatch::LocalOnly { ref projects, .. }
+                if projects == &["Workspace · Automation".to_string()]
+        ));
+        assert!(state.chat.pending_provider_calls.is_empty());
+
+        state.config.local_only_projects.clear();
+        assert!(state.local_only_conflict(&draft).is_empty());
+    }
 }
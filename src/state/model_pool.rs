@@ -0,0 +1,119 @@
+use crate::local_providers::LocalModelIdentifier;
+
+/// Modelos por alias cargados a la vez como máximo, aunque quepan en memoria.
+pub const MAX_POOLED_MODELS: usize = 3;
+
+pub struct PooledModel<T> {
+    pub model: LocalModelIdentifier,
+    pub runtime: T,
+    /// Memoria estimada por el tamaño de los pesos en disco.
+    pub bytes: u64,
+}
+
+/// Modelos locales cargados además del de Jarvis para responder a sus alias, del usado
+/// hace más tiempo al más reciente.
+pub struct ModelPool<T> {
+    entries: Vec<PooledModel<T>>,
+}
+
+impl<T> Default for ModelPool<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<T> ModelPool<T> {
+    pub fn contains(&self, model: &LocalModelIdentifier) -> bool {
+        self.entries.iter().any(|entry| &entry.model == model)
+    }
+
+    pub fn loaded(&self) -> &[PooledModel<T>] {
+        &self.entries
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.bytes).sum()
+    }
+
+    /// Devuelve el runtime de `model` y lo marca como el usado más recientemente.
+    pub fn touch(&mut self, model: &LocalModelIdentifier) -> Option<&mut T> {
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| &entry.model == model)?;
+        let entry = self.entries.remove(position);
+        self.entries.push(entry);
+        self.entries.last_mut().map(|entry| &mut entry.runtime)
+    }
+
+    /// Descarga los modelos menos usados hasta que quepan `bytes` más sin pasar de
+    /// `budget` ni de `MAX_POOLED_MODELS`. Devuelve los que se descargaron.
+    pub fn make_room(&mut self, bytes: u64, budget: u64) -> Vec<LocalModelIdentifier> {
+        let mut evicted = Vec::new();
+        while !self.entries.is_empty()
+            && (self.entries.len() >= MAX_POOLED_MODELS
+                || self.used_bytes().saturating_add(bytes) > budget)
+        {
+            evicted.push(self.entries.remove(0).model);
+        }
+        evicted
+    }
+
+    pub fn insert(&mut self, model: LocalModelIdentifier, runtime: T, bytes: u64) -> &mut T {
+        self.remove(&model);
+        self.entries.push(PooledModel {
+            model,
+            runtime,
+            bytes,
+        });
+        &mut self
+            .entries
+            .last_mut()
+            .expect("modelo recién añadido")
+            .runtime
+    }
+
+    pub fn remove(&mut self, model: &LocalModelIdentifier) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| &entry.model != model);
+        self.entries.len() != before
+    }
+
+    /// Descarga todos los modelos y devuelve cuántos había.
+    pub fn clear(&mut self) -> usize {
+        let count = self.entries.len();
+        self.entries.clear();
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_providers::LocalModelProvider;
+
+    fn model(id: &str) -> LocalModelIdentifier {
+        LocalModelIdentifier::new(LocalModelProvider::HuggingFace, id)
+    }
+
+    #[test]
+    fn evicts_the_least_recently_used_model_to_fit_the_budget() {
+        let mut pool = ModelPool::default();
+        pool.insert(model("coder"), "coder", 4);
+        pool.insert(model("writer"), "writer", 3);
+        assert_eq!(pool.touch(&model("coder")), Some(&mut "coder"));
+
+        assert_eq!(pool.make_room(2, 10), Vec::new());
+        assert_eq!(pool.make_room(5, 10), vec![model("writer")]);
+        pool.insert(model("vision"), "vision", 5);
+        assert_eq!(pool.used_bytes(), 9);
+
+        pool.insert(model("tiny"), "tiny", 0);
+        assert_eq!(pool.make_room(0, 100), vec![model("coder")]);
+        assert!(!pool.contains(&model("coder")));
+        assert!(pool.remove(&model("tiny")));
+        assert_eq!(pool.clear(), 1);
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{Duration, Utc};
+use serde_json::Value;
+
+use super::{DirectorySize, SizeIndex};
+use crate::config::TrashedModelConfig;
+
+/// Carpeta, junto a los modelos, donde esperan los desinstalados.
+pub const TRASH_DIR: &str = ".trash";
+
+/// Sufijos de las carpetas temporales de descarga y actualización.
+const WORK_DIR_SUFFIXES: [&str; 2] = ["__downloading", "__update"];
+
+/// Qué hacer con los archivos al desinstalar un modelo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UninstallMode {
+    /// Mover a la papelera durante el plazo configurado.
+    Trash,
+    /// Borrar del disco de inmediato.
+    Delete,
+}
+
+/// Carpeta de modelo presente en disco que no figura en la configuración.
+#[derive(Clone, Debug)]
+pub struct OrphanModelDir {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Identificador leído de `metadata.json`, si la descarga llegó a guardarlo.
+    pub model_id: Option<String>,
+}
+
+/// Carpeta temporal de una descarga o actualización que quedó a medias.
+#[derive(Clone, Debug)]
+pub struct WorkDir {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// Espacio que ocupan los modelos en disco, por categoría.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModelDiskUsage {
+    pub installed: u64,
+    pub trash: u64,
+    pub orphans: u64,
+    pub work_dirs: u64,
+}
+
+impl ModelDiskUsage {
+    pub fn total(&self) -> u64 {
+        self.installed + self.trash + self.orphans + self.work_dirs
+    }
+}
+
+/// Mueve la carpeta del modelo a la papelera de su directorio padre y devuelve el destino.
+pub fn move_to_trash(model_dir: &Path) -> Result<PathBuf> {
+    let parent = model_dir
+        .parent()
+        .ok_or_else(|| anyhow!("{:?} no tiene directorio padre", model_dir))?;
+    let trash = parent.join(TRASH_DIR);
+    fs::create_dir_all(&trash).with_context(|| format!("No se pudo crear {:?}", trash))?;
+
+    let name = model_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "modelo".to_string());
+    let destination = trash.join(format!("{}__{}", name, Utc::now().format("%Y%m%d%H%M%S")));
+    fs::rename(model_dir, &destination)
+        .with_context(|| format!("No se pudo mover {:?} a {:?}", model_dir, destination))?;
+    Ok(destination)
+}
+
+/// Devuelve la carpeta a su ubicación original si no se ha ocupado entretanto.
+pub fn restore(entry: &TrashedModelConfig) -> Result<()> {
+    let original = Path::new(&entry.original_path);
+    if original.exists() {
+        return Err(anyhow!(
+            "Ya existe {:?}; muévelo o elimínalo antes de restaurar.",
+            original
+        ));
+    }
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("No se pudo crear {:?}", parent))?;
+    }
+    fs::rename(&entry.trash_path, original).with_context(|| {
+        format!(
+            "No se pudo mover {:?} a {:?}",
+            entry.trash_path, entry.original_path
+        )
+    })
+}
+
+/// Borra la carpeta. Una carpeta que ya no existe se da por borrada.
+pub fn delete_dir(path: &Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    fs::remove_dir_all(path).with_context(|| format!("No se pudo borrar {:?}", path))
+}
+
+pub fn is_expired(entry: &TrashedModelConfig, retention_days: u32) -> bool {
+    Utc::now() - entry.trashed_at >= Duration::days(i64::from(retention_days))
+}
+
+/// Carpetas de `install_dir` con pinta de modelo (tienen `config.json`) que no aparecen
+/// entre las rutas conocidas. Se ignoran la papelera y las descargas en curso. El tamaño
+/// sale del índice; las carpetas sin medir quedan a 0 hasta el siguiente refresco.
+pub fn find_orphans(
+    install_dir: &Path,
+    known: &[PathBuf],
+    sizes: &SizeIndex,
+) -> Vec<OrphanModelDir> {
+    let Ok(entries) = fs::read_dir(install_dir) else {
+        return Vec::new();
+    };
+    let known: Vec<PathBuf> = known.iter().map(|path| normalize(path)).collect();
+
+    let mut orphans: Vec<OrphanModelDir> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.join("config.json").exists())
+        .filter(|path| {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            name != TRASH_DIR
+                && !WORK_DIR_SUFFIXES
+                    .iter()
+                    .any(|suffix| name.ends_with(suffix))
+        })
+        .filter(|path| !known.contains(&normalize(path)))
+        .map(|path| OrphanModelDir {
+            size_bytes: sizes.size_of(&path).unwrap_or(0),
+            model_id: metadata_model_id(&path),
+            path,
+        })
+        .collect();
+    orphans.sort_by(|a, b| a.path.cmp(&b.path));
+    orphans
+}
+
+/// Carpetas temporales de descarga y actualización de `install_dir`. Guardan pocos
+/// archivos, así que se miden en el momento.
+pub fn find_work_dirs(install_dir: &Path) -> Vec<WorkDir> {
+    let Ok(entries) = fs::read_dir(install_dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<WorkDir> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && is_work_dir(path))
+        .map(|path| WorkDir {
+            size_bytes: DirectorySize::scan(&path).total(),
+            path,
+        })
+        .collect();
+    dirs.sort_by(|a, b| a.path.cmp(&b.path));
+    dirs
+}
+
+fn is_work_dir(path: &Path) -> bool {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .is_some_and(|name| {
+            WORK_DIR_SUFFIXES
+                .iter()
+                .any(|suffix| name.ends_with(suffix))
+        })
+}
+
+fn metadata_model_id(model_dir: &Path) -> Option<String> {
+    let data = fs::read_to_string(model_dir.join("metadata.json")).ok()?;
+    let metadata: Value = serde_json::from_str(&data).ok()?;
+    metadata
+        .get("id")
+        .or_else(|| metadata.get("modelId"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
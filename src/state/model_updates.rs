@@ -0,0 +1,52 @@
+use std::collections::BTreeMap;
+
+use crate::local_providers::LocalModelIdentifier;
+
+/// Situación de un modelo instalado frente a su repositorio de origen.
+#[derive(Clone, Debug)]
+pub enum ModelUpdateStatus {
+    Checking,
+    UpToDate,
+    Available {
+        revision: String,
+        last_modified: Option<String>,
+        changelog_url: String,
+    },
+    Updating,
+    Failed(String),
+}
+
+impl ModelUpdateStatus {
+    pub fn is_busy(&self) -> bool {
+        matches!(
+            self,
+            ModelUpdateStatus::Checking | ModelUpdateStatus::Updating
+        )
+    }
+}
+
+/// Resultado de las comprobaciones de actualización de la biblioteca local.
+#[derive(Clone, Debug, Default)]
+pub struct ModelUpdateState {
+    pub statuses: BTreeMap<LocalModelIdentifier, ModelUpdateStatus>,
+    pub last_checked: Option<String>,
+}
+
+impl ModelUpdateState {
+    pub fn status(&self, identifier: &LocalModelIdentifier) -> Option<&ModelUpdateStatus> {
+        self.statuses.get(identifier)
+    }
+
+    pub fn outdated_count(&self) -> usize {
+        self.statuses
+            .values()
+            .filter(|status| matches!(status, ModelUpdateStatus::Available { .. }))
+            .count()
+    }
+
+    pub fn is_checking(&self) -> bool {
+        self.statuses
+            .values()
+            .any(|status| matches!(status, ModelUpdateStatus::Checking))
+    }
+}
@@ -0,0 +1,472 @@
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Local;
+
+use super::job_queue::{JobPriority, JobQueueState, MQTT_LANE};
+use super::runtime::{self, UiSender};
+use crate::config::MqttConfig;
+
+// Tipos de paquete de MQTT 3.1.1, en los cuatro bits altos de la cabecera.
+const CONNECT: u8 = 0x10;
+const CONNACK: u8 = 0x20;
+const PUBLISH: u8 = 0x30;
+/// SUBSCRIBE lleva fijos los bits `0010` que exige la especificación.
+const SUBSCRIBE: u8 = 0x82;
+const SUBACK: u8 = 0x90;
+const PINGREQ: u8 = 0xC0;
+const DISCONNECT: u8 = 0xE0;
+const PROTOCOL_LEVEL: u8 = 4;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Cada cuánto se despierta el hilo suscriptor para enviar pings y comprobar si debe parar.
+const READ_POLL: Duration = Duration::from_secs(1);
+const RETRY_DELAY: Duration = Duration::from_secs(15);
+/// Mensajes recibidos que se conservan para las preferencias.
+pub const MAX_RECENT: usize = 50;
+/// Caracteres de cada mensaje recibido que se conservan.
+const MAX_PAYLOAD_CHARS: usize = 4000;
+
+/// Mensaje recibido en un tema suscrito.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: String,
+    pub timestamp: String,
+}
+
+pub enum MqttEvent {
+    Connected,
+    Message(MqttMessage),
+    /// Publicación terminada: el tema, o el motivo del fallo.
+    Published(Result<String, String>),
+    /// Fallo de la suscripción; el cliente reconecta solo.
+    Error(String),
+}
+
+/// Comprueba si `topic` encaja en un filtro con los comodines `+` (un nivel) y `#`
+/// (el resto de niveles, incluido ninguno).
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut filter_levels = filter.trim().split('/');
+    let mut topic_levels = topic.split('/');
+    loop {
+        match (filter_levels.next(), topic_levels.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(_)) => {}
+            (Some(expected), Some(level)) if expected == level => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+fn keep_alive(config: &MqttConfig) -> u16 {
+    config.keep_alive_secs.max(5)
+}
+
+fn push_remaining_length(out: &mut Vec<u8>, mut length: usize) {
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        out.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+}
+
+fn push_string(out: &mut Vec<u8>, value: &str) {
+    out.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    out.extend_from_slice(value.as_bytes());
+}
+
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![header];
+    push_remaining_length(&mut out, body.len());
+    out.extend_from_slice(body);
+    out
+}
+
+/// CONNECT con sesión limpia y, si hay usuario, sus credenciales.
+fn connect_packet(config: &MqttConfig, client_id: &str) -> Vec<u8> {
+    let username = config.username.trim();
+    let mut flags = 0x02;
+    if !username.is_empty() {
+        flags |= 0x80;
+        if !config.password.is_empty() {
+            flags |= 0x40;
+        }
+    }
+    let mut body = Vec::new();
+    push_string(&mut body, "MQTT");
+    body.push(PROTOCOL_LEVEL);
+    body.push(flags);
+    body.extend_from_slice(&keep_alive(config).to_be_bytes());
+    push_string(&mut body, client_id);
+    if !username.is_empty() {
+        push_string(&mut body, username);
+        if !config.password.is_empty() {
+            push_string(&mut body, &config.password);
+        }
+    }
+    packet(CONNECT, &body)
+}
+
+/// SUBSCRIBE con calidad de servicio 0 para todos los filtros.
+fn subscribe_packet(packet_id: u16, filters: &[String]) -> Vec<u8> {
+    let mut body = packet_id.to_be_bytes().to_vec();
+    for filter in filters {
+        push_string(&mut body, filter);
+        body.push(0);
+    }
+    packet(SUBSCRIBE, &body)
+}
+
+fn publish_packet(topic: &str, payload: &[u8]) -> Vec<u8> {
+    let mut body = Vec::new();
+    push_string(&mut body, topic);
+    body.extend_from_slice(payload);
+    packet(PUBLISH, &body)
+}
+
+/// Tema y contenido de un PUBLISH. Con QoS 1 o 2 el tema va seguido del identificador.
+fn parse_publish(header: u8, body: &[u8]) -> Result<(String, Vec<u8>)> {
+    let invalid = || anyhow!("Paquete PUBLISH mal formado");
+    let length = body.get(..2).ok_or_else(invalid)?;
+    let topic_end = 2 + usize::from(u16::from_be_bytes([length[0], length[1]]));
+    let topic = body.get(2..topic_end).ok_or_else(invalid)?;
+    let payload_start = if (header >> 1) & 0x03 > 0 {
+        topic_end + 2
+    } else {
+        topic_end
+    };
+    let payload = body.get(payload_start..).ok_or_else(invalid)?;
+    Ok((
+        String::from_utf8_lossy(topic).into_owned(),
+        payload.to_vec(),
+    ))
+}
+
+/// Lee un paquete completo. Devuelve `None` si vence el tiempo de espera antes de que
+/// llegue el primer byte.
+fn read_packet(stream: &mut impl Read) -> Result<Option<(u8, Vec<u8>)>> {
+    let mut header = [0u8; 1];
+    match stream.read(&mut header) {
+        Ok(0) => bail!("El broker cerró la conexión"),
+        Ok(_) => {}
+        Err(err) if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+            return Ok(None)
+        }
+        Err(err) => return Err(err).context("No se pudo leer del broker"),
+    }
+    let mut length = 0usize;
+    let mut shift = 0;
+    loop {
+        let mut byte = [0u8; 1];
+        stream
+            .read_exact(&mut byte)
+            .context("Paquete MQTT incompleto")?;
+        length |= usize::from(byte[0] & 0x7F) << shift;
+        if byte[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+        if shift > 21 {
+            bail!("Longitud de paquete MQTT no válida");
+        }
+    }
+    let mut body = vec![0; length];
+    stream
+        .read_exact(&mut body)
+        .context("Paquete MQTT incompleto")?;
+    Ok(Some((header[0], body)))
+}
+
+fn connack_error(code: u8) -> &'static str {
+    match code {
+        1 => "el broker no admite MQTT 3.1.1",
+        2 => "el identificador de cliente fue rechazado",
+        3 => "el servicio no está disponible",
+        4 => "usuario o contraseña incorrectos",
+        5 => "el cliente no está autorizado",
+        _ => "código de respuesta desconocido",
+    }
+}
+
+/// Abre la conexión y espera la confirmación del broker.
+fn connect(config: &MqttConfig, client_id: &str) -> Result<TcpStream> {
+    let host = config.host.trim();
+    if host.is_empty() {
+        bail!("Configura el broker MQTT en Preferencias › Sistema › MQTT");
+    }
+    let address = (host, config.port)
+        .to_socket_addrs()
+        .with_context(|| format!("No se pudo resolver {}", host))?
+        .next()
+        .ok_or_else(|| anyhow!("{} no tiene ninguna dirección", host))?;
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)
+        .with_context(|| format!("No se pudo conectar con {}:{}", host, config.port))?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CONNECT_TIMEOUT))?;
+    stream.write_all(&connect_packet(config, client_id))?;
+    match read_packet(&mut stream)? {
+        Some((header, body)) if header & 0xF0 == CONNACK && body.len() == 2 => match body[1] {
+            0 => Ok(stream),
+            code => bail!("El broker rechazó la conexión: {}", connack_error(code)),
+        },
+        _ => bail!("El broker no confirmó la conexión"),
+    }
+}
+
+/// Publica `payload` en este hilo con una conexión propia, para no desplazar al
+/// suscriptor, que usa el identificador de cliente configurado.
+pub fn publish(config: &MqttConfig, topic: &str, payload: &str) -> Result<()> {
+    let client_id = format!(
+        "{}-pub-{}",
+        config.client_id.trim(),
+        Local::now().timestamp_millis()
+    );
+    let mut stream = connect(config, &client_id)?;
+    stream.write_all(&publish_packet(topic.trim(), payload.as_bytes()))?;
+    stream.write_all(&[DISCONNECT, 0])?;
+    Ok(())
+}
+
+fn payload_text(payload: &[u8]) -> String {
+    let text = String::from_utf8_lossy(payload);
+    let text = text.trim();
+    if text.chars().count() > MAX_PAYLOAD_CHARS {
+        let cut: String = text.chars().take(MAX_PAYLOAD_CHARS).collect();
+        format!("{}…", cut)
+    } else {
+        text.to_string()
+    }
+}
+
+/// Una sesión del suscriptor: conecta, se suscribe y reenvía los mensajes hasta que se
+/// pide parar (`Ok`) o falla la conexión (`Err`).
+fn listen(config: &MqttConfig, tx: &UiSender<MqttEvent>, stop: &AtomicBool) -> Result<()> {
+    let mut stream = connect(config, config.client_id.trim())?;
+    let filters: Vec<String> = config
+        .subscriptions
+        .iter()
+        .map(|filter| filter.trim().to_string())
+        .filter(|filter| !filter.is_empty())
+        .collect();
+    if !filters.is_empty() {
+        stream.write_all(&subscribe_packet(1, &filters))?;
+    }
+    stream.set_read_timeout(Some(READ_POLL))?;
+    let _ = tx.send(MqttEvent::Connected);
+
+    let keep_alive = Duration::from_secs(u64::from(keep_alive(config)));
+    let mut last_ping = Instant::now();
+    let mut last_received = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        if last_ping.elapsed() >= keep_alive / 2 {
+            stream.write_all(&[PINGREQ, 0])?;
+            last_ping = Instant::now();
+        }
+        if last_received.elapsed() > keep_alive * 2 {
+            bail!("El broker dejó de responder");
+        }
+        let Some((header, body)) = read_packet(&mut stream)? else {
+            continue;
+        };
+        last_received = Instant::now();
+        match header & 0xF0 {
+            PUBLISH => {
+                let (topic, payload) = parse_publish(header, &body)?;
+                let message = MqttMessage {
+                    topic,
+                    payload: payload_text(&payload),
+                    timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                };
+                if tx.send(MqttEvent::Message(message)).is_err() {
+                    return Ok(());
+                }
+            }
+            SUBACK if body.iter().skip(2).any(|code| *code == 0x80) => {
+                let _ = tx.send(MqttEvent::Error(
+                    "El broker rechazó alguna de las suscripciones".to_string(),
+                ));
+            }
+            // PINGRESP y los SUBACK sin rechazos solo cuentan como señal de vida.
+            _ => {}
+        }
+    }
+    let _ = stream.write_all(&[DISCONNECT, 0]);
+    Ok(())
+}
+
+/// Mantiene la suscripción y reconecta tras cada fallo hasta que se pide parar.
+fn run(config: MqttConfig, tx: UiSender<MqttEvent>, stop: Arc<AtomicBool>) {
+    while !stop.load(Ordering::Relaxed) {
+        match listen(&config, &tx, &stop) {
+            Ok(()) => return,
+            Err(err) => {
+                if tx.send(MqttEvent::Error(format!("{:#}", err))).is_err() {
+                    return;
+                }
+            }
+        }
+        let retry_at = Instant::now() + RETRY_DELAY;
+        while Instant::now() < retry_at {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            thread::sleep(READ_POLL);
+        }
+    }
+}
+
+/// Conexión con el broker, últimos mensajes recibidos y publicaciones en curso.
+#[derive(Default)]
+pub struct MqttState {
+    pub status: Option<String>,
+    pub connected: bool,
+    pub recent: VecDeque<MqttMessage>,
+    /// Filtro de tema que se escribe en las preferencias para añadirlo.
+    pub subscription_draft: String,
+    rx: Option<Receiver<MqttEvent>>,
+    stop: Option<Arc<AtomicBool>>,
+    publishes: Vec<Receiver<MqttEvent>>,
+}
+
+impl MqttState {
+    pub fn is_running(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn start(&mut self, config: MqttConfig) {
+        self.stop();
+        let (tx, rx) = runtime::ui_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let stop = Arc::clone(&stop);
+            let _ = runtime::spawn_service("mqtt", move || run(config, tx, stop));
+        }
+        self.rx = Some(rx);
+        self.stop = Some(stop);
+        self.status = Some("Conectando con el broker…".to_string());
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        self.rx = None;
+        self.connected = false;
+        self.status = None;
+    }
+
+    /// Encola la publicación en la cola de llamadas salientes; el resultado llega con los
+    /// demás eventos.
+    pub fn publish(
+        &mut self,
+        jobs: &mut JobQueueState,
+        config: MqttConfig,
+        topic: String,
+        payload: String,
+    ) {
+        let (tx, rx) = mpsc::channel();
+        self.publishes.push(rx);
+        let label = format!("Publicación en {}", topic);
+        jobs.submit(MQTT_LANE, JobPriority::Background, label, move || {
+            let result = publish(&config, &topic, &payload)
+                .map(|()| topic.clone())
+                .map_err(|err| format!("No se pudo publicar en {}: {:#}", topic, err));
+            let _ = tx.send(MqttEvent::Published(result));
+        });
+    }
+
+    pub fn record(&mut self, message: MqttMessage) {
+        self.recent.push_front(message);
+        self.recent.truncate(MAX_RECENT);
+    }
+
+    pub fn poll(&mut self) -> Vec<MqttEvent> {
+        let mut events = Vec::new();
+        self.publishes.retain(|rx| match rx.try_recv() {
+            Ok(event) => {
+                events.push(event);
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+        if let Some(rx) = &self.rx {
+            loop {
+                match rx.try_recv() {
+                    Ok(event) => events.push(event),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.rx = None;
+                        self.connected = false;
+                        break;
+                    }
+                }
+            }
+        }
+        events
+    }
+}
+
+impl Drop for MqttState {
+    fn drop(&mut self) {
+        if let Some(stop) = &self.stop {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn encodes_packets_and_matches_topic_filters() {
+        let mut length = Vec::new();
+        push_remaining_length(&mut length, 321);
+        assert_eq!(length, vec![0xC1, 0x02]);
+
+        let config = MqttConfig {
+            keep_alive_secs: 60,
+            ..MqttConfig::default()
+        };
+        assert_eq!(
+            connect_packet(&config, "jm"),
+            vec![0x10, 14, 0, 4, b'M', b'Q', b'T', b'T', 4, 0x02, 0, 60, 0, 2, b'j', b'm']
+        );
+        assert_eq!(
+            subscribe_packet(1, &["casa/#".to_string()]),
+            vec![0x82, 11, 0, 1, 0, 6, b'c', b'a', b's', b'a', b'/', b'#', 0]
+        );
+
+        let mut stream = Cursor::new(publish_packet("casa/salon", b" 21.5 "));
+        let (header, body) = read_packet(&mut stream).unwrap().unwrap();
+        let (topic, payload) = parse_publish(header, &body).unwrap();
+        assert_eq!(topic, "casa/salon");
+        assert_eq!(payload_text(&payload), "21.5");
+        assert!(read_packet(&mut stream).is_err());
+
+        assert!(topic_matches(
+            "casa/+/temperatura",
+            "casa/salon/temperatura"
+        ));
+        assert!(topic_matches("casa/#", "casa"));
+        assert!(topic_matches("casa/#", "casa/cocina/humo"));
+        assert!(!topic_matches("casa/+", "casa/cocina/humo"));
+        assert!(!topic_matches("casa/salon", "casa/cocina"));
+    }
+}
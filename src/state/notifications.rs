@@ -0,0 +1,168 @@
+use chrono::NaiveTime;
+
+use crate::config::{
+    NotificationChannel, NotificationPolicyConfig, NotificationRoute, NotificationSeverity,
+};
+
+use super::LogStatus;
+
+/// Origen de un aviso; cada uno tiene su propia ruta en la política.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotificationSource {
+    Scheduler,
+    Providers,
+    Integrations,
+}
+
+impl NotificationSource {
+    pub const ALL: [NotificationSource; 3] = [
+        NotificationSource::Scheduler,
+        NotificationSource::Providers,
+        NotificationSource::Integrations,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            NotificationSource::Scheduler => "Planificador",
+            NotificationSource::Providers => "Proveedores",
+            NotificationSource::Integrations => "Integraciones",
+        }
+    }
+
+    pub fn route(self, policy: &NotificationPolicyConfig) -> NotificationRoute {
+        match self {
+            NotificationSource::Scheduler => policy.scheduler,
+            NotificationSource::Providers => policy.providers,
+            NotificationSource::Integrations => policy.integrations,
+        }
+    }
+
+    pub fn route_mut(self, policy: &mut NotificationPolicyConfig) -> &mut NotificationRoute {
+        match self {
+            NotificationSource::Scheduler => &mut policy.scheduler,
+            NotificationSource::Providers => &mut policy.providers,
+            NotificationSource::Integrations => &mut policy.integrations,
+        }
+    }
+}
+
+pub fn channel_label(channel: NotificationChannel) -> &'static str {
+    match channel {
+        NotificationChannel::LogOnly => "Solo registro",
+        NotificationChannel::Toast => "Aviso en pantalla",
+        NotificationChannel::Desktop => "Notificación del sistema",
+    }
+}
+
+pub fn severity_label(severity: NotificationSeverity) -> &'static str {
+    match severity {
+        NotificationSeverity::Info => "Información",
+        NotificationSeverity::Warning => "Advertencia",
+        NotificationSeverity::Error => "Error",
+    }
+}
+
+pub fn severity_of(status: LogStatus) -> NotificationSeverity {
+    match status {
+        LogStatus::Ok | LogStatus::Running => NotificationSeverity::Info,
+        LogStatus::Warning => NotificationSeverity::Warning,
+        LogStatus::Error => NotificationSeverity::Error,
+    }
+}
+
+/// Indica si `now` cae en las horas de silencio. Un intervalo cuyo fin es anterior al
+/// inicio cruza la medianoche; horas mal escritas desactivan el silencio.
+pub fn in_quiet_hours(policy: &NotificationPolicyConfig, now: NaiveTime) -> bool {
+    if !policy.quiet_hours_enabled {
+        return false;
+    }
+    let (Ok(start), Ok(end)) = (
+        NaiveTime::parse_from_str(policy.quiet_start.trim(), "%H:%M"),
+        NaiveTime::parse_from_str(policy.quiet_end.trim(), "%H:%M"),
+    ) else {
+        return false;
+    };
+    if start <= end {
+        now >= start && now < end
+    } else {
+        now >= start || now < end
+    }
+}
+
+/// Canal por el que se entrega un aviso de `source` con estado `status` a la hora `now`.
+/// En horas de silencio solo los errores pasan, y como mucho como aviso en pantalla.
+pub fn route(
+    policy: &NotificationPolicyConfig,
+    source: NotificationSource,
+    status: LogStatus,
+    now: NaiveTime,
+) -> NotificationChannel {
+    let route = source.route(policy);
+    let severity = severity_of(status);
+    if severity < route.min_severity {
+        return NotificationChannel::LogOnly;
+    }
+    if !in_quiet_hours(policy, now) {
+        return route.channel;
+    }
+    if severity == NotificationSeverity::Error && policy.errors_break_quiet_hours {
+        route.channel.min(NotificationChannel::Toast)
+    } else {
+        NotificationChannel::LogOnly
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(time, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn routes_by_severity_and_silences_quiet_hours() {
+        let mut policy = NotificationPolicyConfig::default();
+        policy.scheduler.channel = NotificationChannel::Desktop;
+        let scheduler = NotificationSource::Scheduler;
+
+        assert_eq!(
+            route(&policy, scheduler, LogStatus::Ok, at("12:00")),
+            NotificationChannel::LogOnly
+        );
+        assert_eq!(
+            route(&policy, scheduler, LogStatus::Warning, at("23:30")),
+            NotificationChannel::Desktop
+        );
+        assert_eq!(
+            route(
+                &policy,
+                NotificationSource::Providers,
+                LogStatus::Warning,
+                at("12:00")
+            ),
+            NotificationChannel::LogOnly
+        );
+
+        policy.quiet_hours_enabled = true;
+        assert!(in_quiet_hours(&policy, at("23:30")));
+        assert!(in_quiet_hours(&policy, at("07:59")));
+        assert!(!in_quiet_hours(&policy, at("08:00")));
+        assert_eq!(
+            route(&policy, scheduler, LogStatus::Warning, at("23:30")),
+            NotificationChannel::LogOnly
+        );
+        assert_eq!(
+            route(&policy, scheduler, LogStatus::Error, at("23:30")),
+            NotificationChannel::Toast
+        );
+        policy.errors_break_quiet_hours = false;
+        assert_eq!(
+            route(&policy, scheduler, LogStatus::Error, at("23:30")),
+            NotificationChannel::LogOnly
+        );
+
+        policy.quiet_end = "8h".to_string();
+        assert!(!in_quiet_hours(&policy, at("23:30")));
+    }
+}
@@ -0,0 +1,209 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use anyhow::{bail, Context, Result};
+
+use super::attachments;
+use super::documents::DocumentChunk;
+use super::embeddings;
+use super::runtime;
+use crate::config::OcrConfig;
+
+/// Paquete de Tesseract para detectar la orientación, que no es un idioma.
+const ORIENTATION_PACK: &str = "osd";
+
+/// Argumentos de Tesseract para volcar el texto de `path` por la salida estándar.
+fn tesseract_args(config: &OcrConfig, path: &Path) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec![path.into(), "stdout".into()];
+    let languages: Vec<&str> = config
+        .languages
+        .iter()
+        .map(|language| language.trim())
+        .filter(|language| !language.is_empty())
+        .collect();
+    if !languages.is_empty() {
+        args.push("-l".into());
+        args.push(languages.join("+").into());
+    }
+    args
+}
+
+/// Quita el salto de página final de Tesseract, los espacios sobrantes y las líneas en
+/// blanco repetidas.
+fn clean_text(raw: &str) -> String {
+    let mut text = String::new();
+    let mut blank = false;
+    for line in raw.replace('\u{c}', "\n").lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            blank = !text.is_empty();
+            continue;
+        }
+        if blank {
+            text.push('\n');
+            blank = false;
+        }
+        if !text.is_empty() {
+            text.push('\n');
+        }
+        text.push_str(line);
+    }
+    text
+}
+
+/// Reconoce el texto de una imagen en este hilo.
+pub fn recognize(config: &OcrConfig, path: &Path) -> Result<String> {
+    let command = config.tesseract_command.trim();
+    let output = Command::new(command)
+        .args(tesseract_args(config, path))
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| {
+            format!(
+                "No se pudo ejecutar '{}'. Instala Tesseract o indica su ruta en las preferencias",
+                command
+            )
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "Tesseract terminó con {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or_default()
+        );
+    }
+    Ok(clean_text(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Paquetes de idioma instalados según `tesseract --list-langs`. Las versiones antiguas
+/// escriben la lista en la salida de error.
+pub fn installed_languages(command: &str) -> Result<Vec<String>> {
+    let output = Command::new(command.trim())
+        .arg("--list-langs")
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("No se pudo ejecutar '{}'", command.trim()))?;
+    let listing = if output.stdout.is_empty() {
+        String::from_utf8_lossy(&output.stderr).into_owned()
+    } else {
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    Ok(parse_language_list(&listing))
+}
+
+fn parse_language_list(listing: &str) -> Vec<String> {
+    let mut languages: Vec<String> = listing
+        .lines()
+        .skip_while(|line| !line.starts_with("List of available languages"))
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && *line != ORIENTATION_PACK)
+        .map(str::to_string)
+        .collect();
+    languages.sort();
+    languages
+}
+
+/// Trozos del texto reconocido, en el formato de los documentos adjuntos e indexados.
+pub fn chunk_text(text: &str) -> Vec<DocumentChunk> {
+    embeddings::chunk_text(text, embeddings::README_CHUNK_CHARS)
+        .into_iter()
+        .map(DocumentChunk::plain)
+        .collect()
+}
+
+/// Reconocimiento terminado en segundo plano.
+pub struct OcrResult {
+    pub path: PathBuf,
+    pub text: Result<String, String>,
+}
+
+/// Reconocimiento de texto de las imágenes adjuntas y paquetes de idioma disponibles.
+#[derive(Default)]
+pub struct OcrState {
+    pub status: Option<String>,
+    /// Paquetes instalados, consultados desde las preferencias.
+    pub installed_languages: Vec<String>,
+    /// Código de idioma que se escribe en las preferencias para añadirlo.
+    pub language_draft: String,
+    pending: Vec<(PathBuf, Receiver<OcrResult>)>,
+}
+
+impl OcrState {
+    pub fn is_running(&self, path: &Path) -> bool {
+        self.pending.iter().any(|(pending, _)| pending == path)
+    }
+
+    /// Lanza el reconocimiento de `path` en un hilo aparte.
+    pub fn start(&mut self, config: OcrConfig, path: PathBuf) {
+        if self.is_running(&path) || attachments::image_media_type(&path).is_none() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        self.pending.push((path.clone(), rx));
+        runtime::spawn_blocking(move || {
+            let text = recognize(&config, &path).map_err(|err| format!("{:#}", err));
+            let _ = tx.send(OcrResult { path, text });
+        });
+    }
+
+    pub fn poll(&mut self) -> Vec<OcrResult> {
+        let mut finished = Vec::new();
+        self.pending.retain(|(_, rx)| match rx.try_recv() {
+            Ok(result) => {
+                finished.push(result);
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+        finished
+    }
+
+    pub fn refresh_languages(&mut self, command: &str) {
+        match installed_languages(command) {
+            Ok(languages) => {
+                self.status = Some(format!(
+                    "{} paquetes de idioma instalados.",
+                    languages.len()
+                ));
+                self.installed_languages = languages;
+            }
+            Err(err) => {
+                self.status = Some(format!("{:#}", err));
+                self.installed_languages.clear();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_arguments_and_parses_tesseract_output() {
+        let config = OcrConfig {
+            languages: vec!["spa".to_string(), " ".to_string(), "eng".to_string()],
+            ..OcrConfig::default()
+        };
+        let args = tesseract_args(&config, Path::new("captura.png"));
+        assert_eq!(
+            args,
+            ["captura.png", "stdout", "-l", "spa+eng"].map(OsString::from)
+        );
+        let config = OcrConfig {
+            languages: Vec::new(),
+            ..OcrConfig::default()
+        };
+        assert_eq!(tesseract_args(&config, Path::new("a.png")).len(), 2);
+
+        assert_eq!(
+            clean_text("Factura 42  \n\n\n  Total: 10 €\n\u{c}"),
+            "Factura 42\n\n  Total: 10 €"
+        );
+        let listing = "List of available languages in \"/usr/share/tesseract-ocr/5/tessdata/\" (3):\neng\nosd\nspa\n";
+        assert_eq!(parse_language_list(listing), vec!["eng", "spa"]);
+    }
+}
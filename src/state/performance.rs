@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+use chrono::Local;
+
+/// Llamadas que se conservan en el historial de rendimiento.
+const MAX_SAMPLES: usize = 200;
+
+/// Medición de una respuesta generada por Jarvis.
+#[derive(Clone, Debug)]
+pub struct JarvisCallSample {
+    pub timestamp: String,
+    pub model: String,
+    pub precision: Option<String>,
+    pub duration_ms: f64,
+    pub prompt_chars: usize,
+    pub prompt_tokens: usize,
+    pub response_chars: usize,
+    pub response_tokens: usize,
+    pub memory_bytes: Option<u64>,
+}
+
+impl JarvisCallSample {
+    pub fn tokens_per_second(&self) -> f64 {
+        if self.duration_ms <= 0.0 {
+            return 0.0;
+        }
+        self.response_tokens as f64 / (self.duration_ms / 1000.0)
+    }
+}
+
+/// Carga de un modelo en el runtime.
+#[derive(Clone, Debug)]
+pub struct JarvisLoadSample {
+    pub timestamp: String,
+    pub model: String,
+    pub precision: Option<String>,
+    pub duration_ms: f64,
+    pub memory_bytes: Option<u64>,
+}
+
+/// Medias por combinación de modelo y precisión, para comparar alternativas.
+#[derive(Clone, Debug)]
+pub struct JarvisModelSummary {
+    pub model: String,
+    pub precision: Option<String>,
+    pub calls: usize,
+    pub avg_duration_ms: f64,
+    pub avg_tokens_per_second: f64,
+    pub avg_prompt_tokens: f64,
+    pub avg_response_tokens: f64,
+    pub last_load_ms: Option<f64>,
+    pub peak_memory_bytes: Option<u64>,
+}
+
+/// Métricas de rendimiento del runtime local de Jarvis durante la sesión.
+#[derive(Default)]
+pub struct JarvisPerformanceState {
+    pub calls: VecDeque<JarvisCallSample>,
+    pub loads: Vec<JarvisLoadSample>,
+    /// Modelo seleccionado en el panel; `None` muestra todos.
+    pub filter_model: Option<String>,
+}
+
+impl JarvisPerformanceState {
+    pub fn record_call(&mut self, sample: JarvisCallSample) {
+        self.calls.push_back(sample);
+        while self.calls.len() > MAX_SAMPLES {
+            self.calls.pop_front();
+        }
+    }
+
+    pub fn record_load(
+        &mut self,
+        model: String,
+        precision: Option<String>,
+        duration_ms: f64,
+        memory_bytes: Option<u64>,
+    ) {
+        self.loads.push(JarvisLoadSample {
+            timestamp: now(),
+            model,
+            precision,
+            duration_ms,
+            memory_bytes,
+        });
+        if self.loads.len() > MAX_SAMPLES {
+            self.loads.remove(0);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.calls.clear();
+        self.loads.clear();
+    }
+
+    /// Llamadas del modelo filtrado, en orden cronológico.
+    pub fn visible_calls(&self) -> impl Iterator<Item = &JarvisCallSample> {
+        self.calls.iter().filter(|sample| {
+            self.filter_model
+                .as_ref()
+                .is_none_or(|model| &sample.model == model)
+        })
+    }
+
+    pub fn summaries(&self) -> Vec<JarvisModelSummary> {
+        let mut summaries: Vec<JarvisModelSummary> = Vec::new();
+        for sample in &self.calls {
+            let index = match summaries.iter().position(|summary| {
+                summary.model == sample.model && summary.precision == sample.precision
+            }) {
+                Some(index) => index,
+                None => {
+                    summaries.push(JarvisModelSummary {
+                        model: sample.model.clone(),
+                        precision: sample.precision.clone(),
+                        calls: 0,
+                        avg_duration_ms: 0.0,
+                        avg_tokens_per_second: 0.0,
+                        avg_prompt_tokens: 0.0,
+                        avg_response_tokens: 0.0,
+                        last_load_ms: None,
+                        peak_memory_bytes: None,
+                    });
+                    summaries.len() - 1
+                }
+            };
+            let summary = &mut summaries[index];
+            summary.calls += 1;
+            // Media incremental para no guardar sumas aparte.
+            let weight = 1.0 / summary.calls as f64;
+            summary.avg_duration_ms += (sample.duration_ms - summary.avg_duration_ms) * weight;
+            summary.avg_tokens_per_second +=
+                (sample.tokens_per_second() - summary.avg_tokens_per_second) * weight;
+            summary.avg_prompt_tokens +=
+                (sample.prompt_tokens as f64 - summary.avg_prompt_tokens) * weight;
+            summary.avg_response_tokens +=
+                (sample.response_tokens as f64 - summary.avg_response_tokens) * weight;
+            summary.peak_memory_bytes = summary.peak_memory_bytes.max(sample.memory_bytes);
+        }
+
+        for summary in &mut summaries {
+            summary.last_load_ms = self
+                .loads
+                .iter()
+                .rev()
+                .find(|load| load.model == summary.model && load.precision == summary.precision)
+                .map(|load| load.duration_ms);
+        }
+        summaries
+    }
+}
+
+pub(crate) fn now() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
@@ -0,0 +1,240 @@
+use std::collections::VecDeque;
+
+use crate::config::{AutomationPermissionGrant, AutomationSideEffect};
+
+use super::{AutomationWorkflow, WorkflowStepKind};
+
+/// Fragmentos que delatan cada efecto en los pasos de un workflow o en la acción de un
+/// listener, comparados en minúsculas.
+const EFFECT_HINTS: [(AutomationSideEffect, &[&str]); 4] = [
+    (
+        AutomationSideEffect::FileDeletion,
+        &[
+            "rm ", "rm -", "rmdir", "delete", "borra", "elimina", "unlink", "purge",
+        ],
+    ),
+    (
+        AutomationSideEffect::GitPush,
+        &["git push", "push origin", "force-push"],
+    ),
+    (
+        AutomationSideEffect::HttpRequest,
+        &[
+            "http://", "https://", "webhook", "post ", "curl ", "slack", "s3", "github.",
+            "linear.", "ci.", "sube ", "subir ",
+        ],
+    ),
+    (
+        AutomationSideEffect::ShellExec,
+        &[".sh", ".py", "bash ", "exec", "shell"],
+    ),
+];
+
+pub fn effect_label(effect: AutomationSideEffect) -> &'static str {
+    match effect {
+        AutomationSideEffect::FileDeletion => "Borrar ficheros",
+        AutomationSideEffect::GitPush => "Publicar con git push",
+        AutomationSideEffect::HttpRequest => "Enviar peticiones a servicios externos",
+        AutomationSideEffect::ShellExec => "Ejecutar comandos locales",
+    }
+}
+
+fn add_hinted(effects: &mut Vec<AutomationSideEffect>, text: &str) {
+    let text = text.to_lowercase();
+    for (effect, hints) in EFFECT_HINTS {
+        if hints.iter().any(|hint| text.contains(hint)) {
+            effects.push(effect);
+        }
+    }
+}
+
+fn normalized(mut effects: Vec<AutomationSideEffect>) -> Vec<AutomationSideEffect> {
+    effects.sort();
+    effects.dedup();
+    effects
+}
+
+/// Efectos de los pasos de un workflow: los scripts locales ejecutan comandos, las
+/// sincronizaciones hablan con servicios externos y el texto de cada paso añade el resto.
+pub fn workflow_effects(workflow: &AutomationWorkflow) -> Vec<AutomationSideEffect> {
+    let mut effects = Vec::new();
+    for step in &workflow.steps {
+        match step.kind {
+            WorkflowStepKind::LocalScript => effects.push(AutomationSideEffect::ShellExec),
+            WorkflowStepKind::SyncAction => effects.push(AutomationSideEffect::HttpRequest),
+            WorkflowStepKind::RemoteModel => {}
+        }
+        add_hinted(&mut effects, &step.label);
+        add_hinted(&mut effects, &step.detail);
+    }
+    normalized(effects)
+}
+
+/// Efectos de la acción de un listener que no lanza un workflow.
+pub fn action_effects(action: &str) -> Vec<AutomationSideEffect> {
+    let mut effects = Vec::new();
+    add_hinted(&mut effects, action);
+    normalized(effects)
+}
+
+pub fn workflow_key(name: &str) -> String {
+    format!("workflow:{}", name)
+}
+
+pub fn listener_key(name: &str) -> String {
+    format!("listener:{}", name)
+}
+
+/// Efectos de `effects` que ningún permiso guardado cubre para la automatización `key`.
+pub fn missing(
+    grants: &[AutomationPermissionGrant],
+    key: &str,
+    effects: &[AutomationSideEffect],
+) -> Vec<AutomationSideEffect> {
+    effects
+        .iter()
+        .copied()
+        .filter(|effect| {
+            !grants.iter().any(|grant| {
+                grant.effect == *effect
+                    && grant
+                        .automation
+                        .as_deref()
+                        .is_none_or(|automation| automation == key)
+            })
+        })
+        .collect()
+}
+
+/// Hasta dónde se recuerda una autorización.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PermissionScope {
+    #[default]
+    Once,
+    Automation,
+    Always,
+}
+
+impl PermissionScope {
+    pub const ALL: [PermissionScope; 3] = [
+        PermissionScope::Once,
+        PermissionScope::Automation,
+        PermissionScope::Always,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PermissionScope::Once => "Solo esta vez",
+            PermissionScope::Automation => "Siempre para esta automatización",
+            PermissionScope::Always => "Siempre para cualquier automatización",
+        }
+    }
+}
+
+/// Qué se ejecuta cuando se concede el permiso.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PermissionTarget {
+    Workflow(u32),
+    Listener { name: String, action: String },
+}
+
+/// Automatización detenida a la espera de que el usuario autorice sus efectos.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermissionRequest {
+    pub target: PermissionTarget,
+    /// Clave con la que se recuerda el permiso, según `workflow_key` o `listener_key`.
+    pub key: String,
+    pub name: String,
+    pub effects: Vec<AutomationSideEffect>,
+}
+
+/// Peticiones de permiso pendientes, de la más antigua a la más reciente.
+#[derive(Default)]
+pub struct PermissionState {
+    pub queue: VecDeque<PermissionRequest>,
+    /// Alcance elegido en el diálogo abierto.
+    pub scope: PermissionScope,
+}
+
+impl PermissionState {
+    /// Encola la petición salvo que la misma automatización ya esté esperando.
+    pub fn request(&mut self, request: PermissionRequest) -> bool {
+        if self.queue.iter().any(|pending| pending.key == request.key) {
+            return false;
+        }
+        self.queue.push_back(request);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{WorkflowStatus, WorkflowStep, WorkflowTriggerKind};
+
+    #[test]
+    fn detects_effects_and_honours_remembered_grants() {
+        let step = |kind, label: &str| WorkflowStep {
+            kind,
+            label: label.to_string(),
+            detail: String::new(),
+            provider: None,
+        };
+        let workflow = AutomationWorkflow {
+            id: 7,
+            name: "Release".to_string(),
+            description: String::new(),
+            trigger: WorkflowTriggerKind::Manual,
+            chat_command: None,
+            linked_schedule: None,
+            status: WorkflowStatus::Ready,
+            last_run: None,
+            pinned: false,
+            steps: vec![
+                step(WorkflowStepKind::RemoteModel, "Redacta las notas"),
+                step(WorkflowStepKind::LocalScript, "git push origin main"),
+            ],
+        };
+        assert_eq!(
+            workflow_effects(&workflow),
+            vec![
+                AutomationSideEffect::GitPush,
+                AutomationSideEffect::ShellExec
+            ]
+        );
+        assert_eq!(
+            action_effects("github.create_issue(label='automation')"),
+            vec![AutomationSideEffect::HttpRequest]
+        );
+        assert!(action_effects("reminders.mark_sent").is_empty());
+
+        let key = workflow_key(&workflow.name);
+        let grants = vec![
+            AutomationPermissionGrant {
+                effect: AutomationSideEffect::ShellExec,
+                automation: None,
+                granted_at: String::new(),
+            },
+            AutomationPermissionGrant {
+                effect: AutomationSideEffect::GitPush,
+                automation: Some(workflow_key("Otro")),
+                granted_at: String::new(),
+            },
+        ];
+        assert_eq!(
+            missing(&grants, &key, &workflow_effects(&workflow)),
+            vec![AutomationSideEffect::GitPush]
+        );
+
+        let mut state = PermissionState::default();
+        let request = PermissionRequest {
+            target: PermissionTarget::Workflow(workflow.id),
+            key,
+            name: workflow.name.clone(),
+            effects: vec![AutomationSideEffect::GitPush],
+        };
+        assert!(state.request(request.clone()));
+        assert!(!state.request(request));
+        assert_eq!(state.queue.len(), 1);
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::mpsc::Receiver;
+
+use chrono::{DateTime, Local};
+
+use crate::config::Playbook;
+
+/// Marcador con la respuesta del paso anterior.
+const PREVIOUS_FIELD: &str = "anterior";
+/// Marcador con la respuesta de un paso concreto: `{{paso: N}}`, contando desde 1.
+const STEP_PREFIX: &str = "paso:";
+
+/// Número de paso de un marcador `paso: N`.
+fn step_reference(inner: &str) -> Option<Option<usize>> {
+    inner
+        .strip_prefix(STEP_PREFIX)
+        .map(|number| number.trim().parse().ok())
+}
+
+/// Sustituye cada `{{marcador}}` de `text` por lo que devuelva `replace`; si devuelve
+/// `None`, o el marcador no se cierra, se conserva tal cual.
+fn substitute(text: &str, mut replace: impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start..].find("}}") else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        let marker = &rest[start..start + length + 2];
+        match replace(marker[2..marker.len() - 2].trim()) {
+            Some(value) => output.push_str(&value),
+            None => output.push_str(marker),
+        }
+        rest = &rest[start + length + 2..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Campos que hay que pedir al lanzar el playbook, en el orden en que aparecen.
+pub fn fields(playbook: &Playbook) -> Vec<String> {
+    let mut fields: Vec<String> = Vec::new();
+    for step in &playbook.steps {
+        substitute(&step.prompt, |inner| {
+            if !inner.is_empty()
+                && inner != PREVIOUS_FIELD
+                && step_reference(inner).is_none()
+                && !fields.iter().any(|field| field == inner)
+            {
+                fields.push(inner.to_string());
+            }
+            None
+        });
+    }
+    fields
+}
+
+/// Sustituye los campos del usuario; los que falten quedan vacíos.
+pub fn fill_fields(prompt: &str, values: &BTreeMap<String, String>) -> String {
+    substitute(prompt, |inner| {
+        if inner == PREVIOUS_FIELD || step_reference(inner).is_some() {
+            return None;
+        }
+        Some(
+            values
+                .get(inner)
+                .map(|value| value.trim())
+                .unwrap_or_default()
+                .to_string(),
+        )
+    })
+}
+
+/// Sustituye `{{anterior}}` y `{{paso: N}}` con las respuestas ya recibidas.
+pub fn fill_outputs(prompt: &str, outputs: &[String]) -> String {
+    substitute(prompt, |inner| {
+        let output = if inner == PREVIOUS_FIELD {
+            outputs.last()
+        } else {
+            outputs.get(step_reference(inner)??.checked_sub(1)?)
+        };
+        output.map(|output| output.trim().to_string())
+    })
+}
+
+/// Comprueba que el playbook tiene nombre y pasos, y que cada paso solo usa respuestas de
+/// pasos anteriores.
+pub fn validate(playbook: &Playbook) -> Result<(), String> {
+    if playbook.name.trim().is_empty() {
+        return Err("El playbook necesita un nombre.".to_string());
+    }
+    if playbook.steps.is_empty() {
+        return Err("El playbook necesita al menos un paso.".to_string());
+    }
+    for (index, step) in playbook.steps.iter().enumerate() {
+        if step.prompt.trim().is_empty() {
+            return Err(format!("El paso {} no tiene prompt.", index + 1));
+        }
+        let mut error = None;
+        substitute(&step.prompt, |inner| {
+            if inner == PREVIOUS_FIELD && index == 0 {
+                error = Some("El primer paso no puede usar {{anterior}}.".to_string());
+            }
+            match step_reference(inner) {
+                Some(Some(number)) if number >= 1 && number <= index => {}
+                Some(_) => {
+                    error = Some(format!(
+                        "El paso {} usa '{{{{{}}}}}', que no es un paso anterior.",
+                        index + 1,
+                        inner
+                    ));
+                }
+                None => {}
+            }
+            None
+        });
+        if let Some(error) = error {
+            return Err(error);
+        }
+    }
+    Ok(())
+}
+
+/// Prompt enviado en un paso y la respuesta recibida.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StepResult {
+    pub title: String,
+    pub prompt: String,
+    pub output: String,
+}
+
+/// Documento Markdown con los datos de entrada y la respuesta de cada paso.
+pub fn render_document(
+    playbook: &Playbook,
+    values: &BTreeMap<String, String>,
+    results: &[StepResult],
+    started_at: DateTime<Local>,
+    author: &str,
+) -> String {
+    let mut document = format!(
+        "# {}\n\n_{} · {}_\n\n",
+        playbook.name.trim(),
+        started_at.format("%Y-%m-%d %H:%M"),
+        author
+    );
+    if !playbook.description.trim().is_empty() {
+        document.push_str(&format!("{}\n\n", playbook.description.trim()));
+    }
+    let inputs: Vec<(&String, &String)> = values
+        .iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .collect();
+    if !inputs.is_empty() {
+        document.push_str("## Datos de entrada\n\n");
+        for (field, value) in inputs {
+            document.push_str(&format!("- **{}**: {}\n", field, value.trim()));
+        }
+        document.push('\n');
+    }
+    for (index, result) in results.iter().enumerate() {
+        document.push_str(&format!(
+            "## {}. {}\n\n{}\n\n<details><summary>Prompt</summary>\n\n{}\n\n</details>\n\n",
+            index + 1,
+            result.title.trim(),
+            result.output.trim(),
+            result.prompt.trim()
+        ));
+    }
+    document
+}
+
+pub enum PlaybookMessage {
+    Step(StepResult),
+    Finished(Result<(), String>),
+}
+
+/// Ejecución en curso de un playbook. Al soltar el receptor el hilo deja de enviar pasos.
+pub struct PlaybookRun {
+    pub playbook: Playbook,
+    pub values: BTreeMap<String, String>,
+    pub started_at: DateTime<Local>,
+    /// Nombre del proveedor que responde a los pasos, para la actividad y el documento.
+    pub author: String,
+    pub results: Vec<StepResult>,
+    pub rx: Receiver<PlaybookMessage>,
+}
+
+/// Playbook en edición, datos de lanzamiento y último documento generado.
+#[derive(Default)]
+pub struct PlaybookState {
+    pub draft: Playbook,
+    /// Índice del playbook que se está editando; `None` al crear uno nuevo.
+    pub editing: Option<usize>,
+    /// Valores de los campos del playbook que se va a lanzar.
+    pub values: BTreeMap<String, String>,
+    pub run: Option<PlaybookRun>,
+    pub document: Option<PathBuf>,
+    pub preview: String,
+    pub status: Option<String>,
+}
+
+impl PlaybookState {
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_fields_and_previous_outputs_in_order() {
+        let playbook = &Playbook::examples()[0];
+        assert!(validate(playbook).is_ok());
+        assert_eq!(fields(playbook), vec!["informe", "componente"]);
+
+        let values = BTreeMap::from([
+            (
+                "informe".to_string(),
+                " La app se cierra al guardar ".to_string(),
+            ),
+            ("componente".to_string(), "config".to_string()),
+        ]);
+        let second = fill_fields(&playbook.steps[1].prompt, &values);
+        assert!(second.contains("componente config"));
+        assert!(second.ends_with("{{anterior}}"));
+        let outputs = vec!["Resumen".to_string(), "Causas".to_string()];
+        assert!(fill_outputs(&second, &outputs[..1]).ends_with("\n\nResumen"));
+        let third = fill_outputs(&playbook.steps[2].prompt, &outputs);
+        assert!(third.contains("Resumen:\nResumen") && third.ends_with("Causas:\nCausas"));
+        assert_eq!(
+            fill_outputs("{{paso: 9}} {{sin cerrar", &outputs),
+            "{{paso: 9}} {{sin cerrar"
+        );
+
+        let mut broken = playbook.clone();
+        broken.steps[1].prompt = "Revisa {{paso: 2}}".to_string();
+        assert!(validate(&broken).is_err());
+        broken.steps.truncate(0);
+        assert!(validate(&broken).is_err());
+    }
+}
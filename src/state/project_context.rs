@@ -0,0 +1,119 @@
+use crate::config::{ProjectContextProfile, RedactionConfig};
+
+use super::conversations::ConversationDefaults;
+use super::RemoteProviderKind;
+
+/// Fragmentos de las fuentes de conocimiento del perfil que acompañan a cada prompt.
+pub const KNOWLEDGE_RECALL_CHUNKS: usize = 3;
+
+/// Perfil aplicado en el chat y si el usuario ya cerró su aviso.
+#[derive(Default)]
+pub struct ProjectContextState {
+    /// Proyecto cuyo perfil anunció el último cambio de proyecto.
+    pub announced: Option<String>,
+    pub banner_dismissed: bool,
+}
+
+pub fn is_empty(profile: &ProjectContextProfile) -> bool {
+    profile.persona.trim().is_empty()
+        && profile.provider.is_none()
+        && profile.knowledge_sources.is_empty()
+        && profile.redaction_rules.is_none()
+}
+
+/// Ajustes de la conversación completados con el perfil: la persona y el proveedor del
+/// perfil solo se usan si la conversación no fija los suyos.
+pub fn apply_to_defaults(
+    mut defaults: ConversationDefaults,
+    profile: &ProjectContextProfile,
+) -> ConversationDefaults {
+    if defaults.provider.is_none() {
+        defaults.provider = profile
+            .provider
+            .clone()
+            .filter(|code| RemoteProviderKind::from_short_code(code).is_some());
+    }
+    if defaults.persona.trim().is_empty() {
+        defaults.persona = profile.persona.trim().to_string();
+    }
+    defaults
+}
+
+/// Reglas de redacción activas con el perfil: las de preferencias que el perfil incluye.
+pub fn redaction_config(
+    config: &RedactionConfig,
+    profile: Option<&ProjectContextProfile>,
+) -> RedactionConfig {
+    let Some(included) = profile.and_then(|profile| profile.redaction_rules.as_ref()) else {
+        return config.clone();
+    };
+    let mut filtered = config.clone();
+    for rule in &mut filtered.rules {
+        rule.enabled &= included.contains(&rule.name);
+    }
+    filtered
+}
+
+/// Partes del resumen del perfil para el aviso del chat.
+pub fn summary(profile: &ProjectContextProfile) -> Vec<String> {
+    let mut parts = Vec::new();
+    if !profile.persona.trim().is_empty() {
+        parts.push("persona".to_string());
+    }
+    if let Some(provider) = profile
+        .provider
+        .as_deref()
+        .and_then(RemoteProviderKind::from_short_code)
+    {
+        parts.push(provider.display_name().to_string());
+    }
+    if !profile.knowledge_sources.is_empty() {
+        parts.push(format!("{} fuentes", profile.knowledge_sources.len()));
+    }
+    if let Some(rules) = &profile.redaction_rules {
+        parts.push(format!("{} reglas de redacción", rules.len()));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_fills_gaps_without_overriding_the_conversation() {
+        let profile = ProjectContextProfile {
+            persona: "Analista de datos".to_string(),
+            provider: Some("groq".to_string()),
+            knowledge_sources: vec!["Workspace · RAG".to_string()],
+            redaction_rules: Some(vec!["API keys".to_string()]),
+        };
+        let merged = apply_to_defaults(ConversationDefaults::default(), &profile);
+        assert_eq!(merged.provider.as_deref(), Some("groq"));
+        assert_eq!(merged.persona, "Analista de datos");
+
+        let own = ConversationDefaults {
+            provider: Some("openai".to_string()),
+            persona: "Revisor".to_string(),
+            ..ConversationDefaults::default()
+        };
+        let merged = apply_to_defaults(own, &profile);
+        assert_eq!(merged.provider.as_deref(), Some("openai"));
+        assert_eq!(merged.persona, "Revisor");
+
+        let redaction = redaction_config(&RedactionConfig::default(), Some(&profile));
+        let enabled: Vec<&str> = redaction
+            .rules
+            .iter()
+            .filter(|rule| rule.enabled)
+            .map(|rule| rule.name.as_str())
+            .collect();
+        assert_eq!(enabled, vec!["API keys"]);
+        assert_eq!(
+            redaction_config(&RedactionConfig::default(), None),
+            RedactionConfig::default()
+        );
+        assert_eq!(summary(&profile).len(), 4);
+        assert!(is_empty(&ProjectContextProfile::default()));
+    }
+}
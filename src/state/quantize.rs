@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Receiver;
+
+use anyhow::{bail, Context, Result};
+use candle_core::quantized::{gguf_file, GgmlDType, QTensor};
+use candle_core::{DType, Device, Tensor};
+use serde_json::Value;
+
+use super::runtime::{self, UiSender};
+use crate::local_providers::LocalModelIdentifier;
+
+/// Formatos a los que se pueden convertir los pesos `.safetensors` de un modelo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuantizationLevel {
+    Q8,
+    Q4,
+}
+
+impl QuantizationLevel {
+    pub const ALL: [QuantizationLevel; 2] = [QuantizationLevel::Q8, QuantizationLevel::Q4];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QuantizationLevel::Q8 => "8 bits",
+            QuantizationLevel::Q4 => "4 bits",
+        }
+    }
+
+    /// Sufijo del identificador y del fichero GGUF de la variante.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            QuantizationLevel::Q8 => "q8_0",
+            QuantizationLevel::Q4 => "q4_0",
+        }
+    }
+
+    fn ggml_dtype(self) -> GgmlDType {
+        match self {
+            QuantizationLevel::Q8 => GgmlDType::Q8_0,
+            QuantizationLevel::Q4 => GgmlDType::Q4_0,
+        }
+    }
+}
+
+/// Identificador con el que se registra la variante cuantizada de `model`.
+pub fn quantized_identifier(
+    model: &LocalModelIdentifier,
+    level: QuantizationLevel,
+) -> LocalModelIdentifier {
+    LocalModelIdentifier::new(
+        model.provider,
+        format!("{}-{}", model.model_id, level.suffix()),
+    )
+}
+
+/// Solo se cuantizan las matrices cuyas filas encajan en bloques completos; sesgos,
+/// normalizaciones y tensores irregulares se guardan en `f32`.
+fn quantizable(shape: &[usize], level: QuantizationLevel) -> bool {
+    shape.len() >= 2
+        && shape
+            .last()
+            .is_some_and(|columns| columns % level.ggml_dtype().block_size() == 0)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuantizeReport {
+    pub output_dir: PathBuf,
+    pub original_bytes: u64,
+    pub quantized_bytes: u64,
+    pub quantized_tensors: usize,
+    pub total_tensors: usize,
+}
+
+pub enum QuantizeMessage {
+    Progress { done: usize, total: usize },
+    Finished(Result<QuantizeReport, String>),
+}
+
+/// Conversión en curso en segundo plano.
+pub struct QuantizeJob {
+    pub source: LocalModelIdentifier,
+    pub level: QuantizationLevel,
+    pub done: usize,
+    pub total: usize,
+    pub rx: Receiver<QuantizeMessage>,
+}
+
+impl QuantizeJob {
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.done as f32 / self.total as f32
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct QuantizeState {
+    pub job: Option<QuantizeJob>,
+}
+
+/// Lanza la conversión de `source_dir` en el runtime compartido y devuelve el trabajo a sondear.
+pub fn spawn(
+    source: LocalModelIdentifier,
+    source_dir: PathBuf,
+    output_dir: PathBuf,
+    level: QuantizationLevel,
+) -> QuantizeJob {
+    let (tx, rx) = runtime::ui_channel();
+    runtime::spawn_blocking(move || {
+        let result = if output_dir.exists() {
+            Err(format!("Ya existe {}.", output_dir.display()))
+        } else {
+            quantize_model(&source_dir, &output_dir, level, &tx).map_err(|err| {
+                // No se deja una variante a medias que luego parezca instalable.
+                let _ = fs::remove_dir_all(&output_dir);
+                format!("{:#}", err)
+            })
+        };
+        let _ = tx.send(QuantizeMessage::Finished(result));
+    });
+    QuantizeJob {
+        source,
+        level,
+        done: 0,
+        total: 0,
+        rx,
+    }
+}
+
+/// Convierte los pesos de `source_dir` a un único GGUF en `output_dir`, que no debe
+/// existir, y copia junto a él la configuración y el tokenizer del modelo original.
+pub fn quantize_model(
+    source_dir: &Path,
+    output_dir: &Path,
+    level: QuantizationLevel,
+    progress: &UiSender<QuantizeMessage>,
+) -> Result<QuantizeReport> {
+    let mut weight_files: Vec<PathBuf> = fs::read_dir(source_dir)
+        .with_context(|| format!("No se pudo listar {}", source_dir.display()))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("safetensors"))
+        })
+        .collect();
+    weight_files.sort();
+    if weight_files.is_empty() {
+        bail!(
+            "No hay pesos '.safetensors' que convertir en {}.",
+            source_dir.display()
+        );
+    }
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("No se pudo crear {}", output_dir.display()))?;
+
+    let device = Device::Cpu;
+    let mut tensors: BTreeMap<String, Tensor> = BTreeMap::new();
+    let mut original_bytes = 0;
+    for file in &weight_files {
+        original_bytes += fs::metadata(file).map(|meta| meta.len()).unwrap_or(0);
+        tensors.extend(
+            candle_core::safetensors::load(file, &device)
+                .with_context(|| format!("No se pudo leer {}", file.display()))?,
+        );
+    }
+
+    let total = tensors.len();
+    let mut quantized = Vec::with_capacity(total);
+    let mut quantized_tensors = 0;
+    for (index, (name, tensor)) in tensors.into_iter().enumerate() {
+        let tensor = tensor.to_dtype(DType::F32)?;
+        let dtype = if quantizable(tensor.dims(), level) {
+            quantized_tensors += 1;
+            level.ggml_dtype()
+        } else {
+            GgmlDType::F32
+        };
+        let qtensor = QTensor::quantize(&tensor, dtype)
+            .with_context(|| format!("No se pudo cuantizar '{}'", name))?;
+        quantized.push((name, qtensor));
+        let _ = progress.send_progress(QuantizeMessage::Progress {
+            done: index + 1,
+            total,
+        });
+    }
+
+    let weights_path = output_dir.join(format!("model-{}.gguf", level.suffix()));
+    let mut writer = BufWriter::new(
+        fs::File::create(&weights_path)
+            .with_context(|| format!("No se pudo crear {}", weights_path.display()))?,
+    );
+    let quantization = gguf_file::Value::String(level.suffix().to_string());
+    let metadata = [("general.quantization", &quantization)];
+    let tensor_refs: Vec<(&str, &QTensor)> = quantized
+        .iter()
+        .map(|(name, tensor)| (name.as_str(), tensor))
+        .collect();
+    gguf_file::write(&mut writer, &metadata, &tensor_refs)
+        .with_context(|| format!("No se pudo escribir {}", weights_path.display()))?;
+    drop(writer);
+
+    copy_model_files(source_dir, output_dir, level)?;
+    let quantized_bytes = fs::read_dir(output_dir)?
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .map(|meta| meta.len())
+        .sum();
+
+    Ok(QuantizeReport {
+        output_dir: output_dir.to_path_buf(),
+        original_bytes,
+        quantized_bytes,
+        quantized_tensors,
+        total_tensors: total,
+    })
+}
+
+/// Copia todo salvo los pesos originales. `config.json` pierde `torch_dtype` para que la
+/// precisión se lea del nombre del GGUF.
+fn copy_model_files(source_dir: &Path, output_dir: &Path, level: QuantizationLevel) -> Result<()> {
+    for entry in fs::read_dir(source_dir)?.flatten() {
+        let path = entry.path();
+        let is_weights = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("safetensors"));
+        if !path.is_file() || is_weights {
+            continue;
+        }
+        let target = output_dir.join(entry.file_name());
+        if entry.file_name() == "config.json" {
+            let raw = fs::read_to_string(&path)?;
+            let mut config: Value = serde_json::from_str(&raw)
+                .with_context(|| format!("No se pudo interpretar {}", path.display()))?;
+            if let Some(object) = config.as_object_mut() {
+                object.remove("torch_dtype");
+                object.insert(
+                    "quantization".to_string(),
+                    Value::String(level.suffix().to_string()),
+                );
+            }
+            fs::write(&target, serde_json::to_string_pretty(&config)?)?;
+        } else {
+            fs::copy(&path, &target)
+                .with_context(|| format!("No se pudo copiar {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local_providers::LocalModelProvider;
+
+    #[test]
+    fn names_variants_and_only_quantizes_full_blocks() {
+        let model = LocalModelIdentifier::new(LocalModelProvider::HuggingFace, "org/bert-mini");
+        assert_eq!(
+            quantized_identifier(&model, QuantizationLevel::Q4).model_id,
+            "org/bert-mini-q4_0"
+        );
+        assert!(quantizable(&[256, 128], QuantizationLevel::Q8));
+        assert!(!quantizable(&[128], QuantizationLevel::Q8));
+        assert!(!quantizable(&[4, 30], QuantizationLevel::Q4));
+    }
+}
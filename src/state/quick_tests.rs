@@ -0,0 +1,340 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::Receiver;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::config::{AppConfig, QuickTestAssertion, QuickTestAssertionKind, QuickTestCase};
+
+use super::{structured, vault, RemoteModelKey, RemoteProviderKind};
+
+pub(crate) const HISTORY_FILE: &str = "pruebas_rapidas.json";
+/// Ejecuciones que se conservan en el historial.
+pub const MAX_HISTORY: usize = 20;
+/// Caracteres de cada respuesta que se guardan para revisarla después.
+const RESPONSE_PREVIEW_CHARS: usize = 600;
+
+pub fn kind_label(kind: QuickTestAssertionKind) -> &'static str {
+    match kind {
+        QuickTestAssertionKind::Contains => "Contiene",
+        QuickTestAssertionKind::Regex => "Expresión regular",
+        QuickTestAssertionKind::JsonSchema => "Esquema JSON",
+    }
+}
+
+/// Comprueba una aserción sobre la respuesta y devuelve el motivo si no se cumple.
+pub fn check(assertion: &QuickTestAssertion, response: &str) -> Result<(), String> {
+    match assertion.kind {
+        QuickTestAssertionKind::Contains => {
+            if response
+                .to_lowercase()
+                .contains(&assertion.value.to_lowercase())
+            {
+                Ok(())
+            } else {
+                Err(format!("No contiene «{}».", assertion.value))
+            }
+        }
+        QuickTestAssertionKind::Regex => {
+            let regex = Regex::new(&assertion.value)
+                .map_err(|err| format!("Expresión regular no válida: {}", err))?;
+            if regex.is_match(response) {
+                Ok(())
+            } else {
+                Err(format!("No coincide con /{}/.", assertion.value))
+            }
+        }
+        QuickTestAssertionKind::JsonSchema => {
+            let schema: Value = serde_json::from_str(&assertion.value)
+                .map_err(|err| format!("Esquema JSON no válido: {}", err))?;
+            structured::parse_and_validate(&schema, response)
+                .map(|_| ())
+                .map_err(|errors| errors.join("; "))
+        }
+    }
+}
+
+/// Rechaza las pruebas sin nombre, prompt o aserciones, y las que no se podrían evaluar.
+pub fn validate_case(case: &QuickTestCase) -> Result<(), String> {
+    if case.name.trim().is_empty() {
+        return Err("Ponle un nombre a la prueba.".to_string());
+    }
+    if case.prompt.trim().is_empty() {
+        return Err("Escribe el prompt de la prueba.".to_string());
+    }
+    if case.assertions.is_empty() {
+        return Err("Añade al menos una aserción.".to_string());
+    }
+    for assertion in &case.assertions {
+        if assertion.value.trim().is_empty() {
+            return Err(format!(
+                "La aserción «{}» está vacía.",
+                kind_label(assertion.kind)
+            ));
+        }
+        match assertion.kind {
+            QuickTestAssertionKind::Contains => {}
+            QuickTestAssertionKind::Regex => {
+                Regex::new(&assertion.value)
+                    .map_err(|err| format!("Expresión regular no válida: {}", err))?;
+            }
+            QuickTestAssertionKind::JsonSchema => {
+                serde_json::from_str::<Value>(&assertion.value)
+                    .map_err(|err| format!("Esquema JSON no válido: {}", err))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Modelo contra el que se ejecutan las pruebas. Sin proveedor es Jarvis.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickTestTarget {
+    /// Código corto del proveedor remoto.
+    pub provider: Option<String>,
+    pub model: String,
+}
+
+impl QuickTestTarget {
+    pub fn remote(key: &RemoteModelKey) -> Self {
+        Self {
+            provider: Some(key.provider.short_code().to_string()),
+            model: key.id.clone(),
+        }
+    }
+
+    pub fn jarvis(model: impl Into<String>) -> Self {
+        Self {
+            provider: None,
+            model: model.into(),
+        }
+    }
+
+    pub fn remote_key(&self) -> Option<RemoteModelKey> {
+        let provider = RemoteProviderKind::from_short_code(self.provider.as_deref()?)?;
+        Some(RemoteModelKey::new(provider, self.model.clone()))
+    }
+
+    pub fn label(&self) -> String {
+        match self.remote_key() {
+            Some(key) => key.as_display(),
+            None => format!("Jarvis · {}", self.model),
+        }
+    }
+}
+
+/// Resultado de una prueba contra un modelo.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct QuickTestOutcome {
+    pub case: String,
+    pub target: usize,
+    pub passed: bool,
+    /// Aserciones incumplidas o error de la llamada.
+    pub failures: Vec<String>,
+    pub response: Option<String>,
+    pub latency_ms: u64,
+}
+
+impl QuickTestOutcome {
+    pub fn evaluate(
+        case: &QuickTestCase,
+        target: usize,
+        outcome: std::result::Result<String, String>,
+        latency: Duration,
+    ) -> Self {
+        let (failures, response) = match outcome {
+            Ok(response) => {
+                let failures = case
+                    .assertions
+                    .iter()
+                    .filter_map(|assertion| check(assertion, &response).err())
+                    .collect();
+                (
+                    failures,
+                    Some(response.chars().take(RESPONSE_PREVIEW_CHARS).collect()),
+                )
+            }
+            Err(err) => (vec![err], None),
+        };
+        Self {
+            case: case.name.clone(),
+            target,
+            passed: failures.is_empty(),
+            failures,
+            response,
+            latency_ms: latency.as_millis() as u64,
+        }
+    }
+}
+
+/// Ejecución completa: cada prueba contra cada modelo.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct QuickTestRunRecord {
+    pub started_at: String,
+    pub cases: Vec<String>,
+    pub targets: Vec<QuickTestTarget>,
+    pub outcomes: Vec<QuickTestOutcome>,
+}
+
+impl QuickTestRunRecord {
+    pub fn outcome(&self, case: &str, target: usize) -> Option<&QuickTestOutcome> {
+        self.outcomes
+            .iter()
+            .find(|outcome| outcome.case == case && outcome.target == target)
+    }
+
+    /// Pruebas superadas y ejecutadas por un modelo.
+    pub fn score(&self, target: usize) -> (usize, usize) {
+        let outcomes = self
+            .outcomes
+            .iter()
+            .filter(|outcome| outcome.target == target);
+        let total = outcomes.clone().count();
+        (outcomes.filter(|outcome| outcome.passed).count(), total)
+    }
+
+    pub fn passed(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| outcome.passed)
+            .count()
+    }
+}
+
+pub fn load_history() -> Vec<QuickTestRunRecord> {
+    AppConfig::data_file(HISTORY_FILE)
+        .ok()
+        .and_then(|path| vault::read_to_string(&path).ok())
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_history(history: &[QuickTestRunRecord]) -> Result<()> {
+    let path = AppConfig::data_file(HISTORY_FILE)?;
+    let json = serde_json::to_string_pretty(history)?;
+    vault::write(&path, &json).with_context(|| format!("No se pudo guardar {}", path.display()))
+}
+
+/// Respuesta de un modelo remoto recibida desde su hilo de trabajo.
+pub(crate) struct RemoteTestOutcome {
+    pub case: usize,
+    pub target: usize,
+    pub outcome: std::result::Result<String, String>,
+    pub latency: Duration,
+}
+
+/// Ejecución en curso.
+pub(crate) struct QuickTestRun {
+    pub remote_rx: Receiver<RemoteTestOutcome>,
+    /// Pruebas que faltan por pasar a Jarvis, que se atienden de una en una.
+    pub jarvis_queue: VecDeque<usize>,
+    pub jarvis_target: Option<usize>,
+    pub cases: Vec<QuickTestCase>,
+    pub record: QuickTestRunRecord,
+    pub expected: usize,
+    pub started_at: Instant,
+}
+
+/// Formulario de pruebas, ejecución en curso e historial de resultados.
+#[derive(Default)]
+pub struct QuickTestState {
+    pub draft: QuickTestCase,
+    /// Prueba guardada que se está editando en el formulario.
+    pub editing: Option<usize>,
+    pub include_jarvis: bool,
+    /// Ejecuciones anteriores, de la más reciente a la más antigua.
+    pub history: Vec<QuickTestRunRecord>,
+    /// Ejecución del historial que muestra la tabla.
+    pub selected_run: usize,
+    pub status: Option<String>,
+    pub(crate) run: Option<QuickTestRun>,
+}
+
+impl QuickTestState {
+    pub fn load() -> Self {
+        Self {
+            history: load_history(),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    pub fn progress(&self) -> Option<(usize, usize)> {
+        self.run
+            .as_ref()
+            .map(|run| (run.record.outcomes.len(), run.expected))
+    }
+
+    /// Ejecución que se muestra: la que está en curso o la elegida del historial.
+    pub fn shown_run(&self) -> Option<&QuickTestRunRecord> {
+        match &self.run {
+            Some(run) => Some(&run.record),
+            None => self.history.get(self.selected_run),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_each_assertion_kind() {
+        let examples = QuickTestCase::examples();
+        let capital = &examples[0];
+        let contact = &examples[1];
+        let date = &examples[2];
+        assert!(examples.iter().all(|case| validate_case(case).is_ok()));
+
+        let outcome = QuickTestOutcome::evaluate(
+            capital,
+            0,
+            Ok("paRÍS".to_string()),
+            Duration::from_millis(120),
+        );
+        assert!(outcome.passed);
+        let outcome = QuickTestOutcome::evaluate(
+            contact,
+            1,
+            Ok("```json\n{\"nombre\": \"Ada\", \"edad\": \"36\"}\n```".to_string()),
+            Duration::ZERO,
+        );
+        assert!(!outcome.passed);
+        assert_eq!(outcome.failures.len(), 1);
+        assert!(
+            QuickTestOutcome::evaluate(date, 0, Ok("1969-07-20".into()), Duration::ZERO).passed
+        );
+        let failed =
+            QuickTestOutcome::evaluate(date, 1, Err("Tiempo agotado".into()), Duration::ZERO);
+        assert_eq!(failed.failures, vec!["Tiempo agotado".to_string()]);
+
+        let record = QuickTestRunRecord {
+            started_at: String::new(),
+            cases: vec![contact.name.clone()],
+            targets: vec![
+                QuickTestTarget::remote(&RemoteModelKey::new(RemoteProviderKind::OpenAi, "gpt-4o")),
+                QuickTestTarget::jarvis("phi-3"),
+            ],
+            outcomes: vec![outcome, failed],
+        };
+        assert_eq!(record.score(1), (0, 2));
+        assert_eq!(record.targets[0].label(), "OpenAI · GPT · gpt-4o");
+        assert_eq!(
+            record.targets[0].remote_key(),
+            Some(RemoteModelKey::new(RemoteProviderKind::OpenAi, "gpt-4o"))
+        );
+
+        let mut broken = capital.clone();
+        broken.assertions[0] = QuickTestAssertion {
+            kind: QuickTestAssertionKind::Regex,
+            value: "(".to_string(),
+        };
+        assert!(validate_case(&broken).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::BTreeMap;
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::config::{RedactionConfig, RedactionRule};
+
+/// Fragmento del prompt que se sustituye al enviarlo.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RedactionHit {
+    pub rule: String,
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// Trozo de la vista previa: texto que se envía tal cual o que se sustituye.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PreviewSegment {
+    Kept(String),
+    Redacted {
+        original: String,
+        replacement: String,
+        rule: String,
+    },
+}
+
+struct CompiledRule {
+    name: String,
+    regex: Regex,
+    replacement: String,
+}
+
+/// Reglas compiladas a partir de la configuración. Se recompilan solo cuando cambian,
+/// porque la vista previa del compositor las aplica en cada fotograma.
+#[derive(Default)]
+pub struct RedactionState {
+    source: Option<RedactionConfig>,
+    compiled: Vec<CompiledRule>,
+    /// Error de compilación de cada regla inválida, por índice.
+    pub errors: BTreeMap<usize, String>,
+    /// Texto de prueba del panel de preferencias.
+    pub sample: String,
+}
+
+impl RedactionState {
+    fn sync(&mut self, config: &RedactionConfig) {
+        if self.source.as_ref() == Some(config) {
+            return;
+        }
+        self.compiled.clear();
+        self.errors.clear();
+        if config.enabled {
+            for (index, rule) in config.rules.iter().enumerate() {
+                if !rule.enabled || rule.pattern.trim().is_empty() {
+                    continue;
+                }
+                match compile(rule) {
+                    Ok(compiled) => self.compiled.push(compiled),
+                    Err(err) => {
+                        self.errors.insert(index, err.to_string());
+                    }
+                }
+            }
+        }
+        self.source = Some(config.clone());
+    }
+
+    /// Fragmentos de `text` que se redactarían. Si dos reglas se solapan gana la que
+    /// empieza antes y, a igualdad, la que aparece primero en la lista.
+    pub fn hits(&mut self, config: &RedactionConfig, text: &str) -> Vec<RedactionHit> {
+        self.sync(config);
+        let mut hits: Vec<RedactionHit> = Vec::new();
+        for rule in &self.compiled {
+            for found in rule.regex.find_iter(text) {
+                if found.range().is_empty() {
+                    continue;
+                }
+                hits.push(RedactionHit {
+                    rule: rule.name.clone(),
+                    range: found.range(),
+                    replacement: rule.replacement.clone(),
+                });
+            }
+        }
+        hits.sort_by_key(|hit| hit.range.start);
+        let mut accepted: Vec<RedactionHit> = Vec::with_capacity(hits.len());
+        for hit in hits {
+            if accepted
+                .last()
+                .is_none_or(|last| hit.range.start >= last.range.end)
+            {
+                accepted.push(hit);
+            }
+        }
+        accepted
+    }
+
+    /// Devuelve el texto redactado y los fragmentos sustituidos.
+    pub fn apply(&mut self, config: &RedactionConfig, text: &str) -> (String, Vec<RedactionHit>) {
+        let hits = self.hits(config, text);
+        let mut redacted = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for hit in &hits {
+            redacted.push_str(&text[cursor..hit.range.start]);
+            redacted.push_str(&hit.replacement);
+            cursor = hit.range.end;
+        }
+        redacted.push_str(&text[cursor..]);
+        (redacted, hits)
+    }
+
+    pub fn preview(&mut self, config: &RedactionConfig, text: &str) -> Vec<PreviewSegment> {
+        let hits = self.hits(config, text);
+        let mut segments = Vec::with_capacity(hits.len() * 2 + 1);
+        let mut cursor = 0;
+        for hit in hits {
+            if hit.range.start > cursor {
+                segments.push(PreviewSegment::Kept(
+                    text[cursor..hit.range.start].to_string(),
+                ));
+            }
+            segments.push(PreviewSegment::Redacted {
+                original: text[hit.range.clone()].to_string(),
+                replacement: hit.replacement,
+                rule: hit.rule,
+            });
+            cursor = hit.range.end;
+        }
+        if cursor < text.len() {
+            segments.push(PreviewSegment::Kept(text[cursor..].to_string()));
+        }
+        segments
+    }
+}
+
+fn compile(rule: &RedactionRule) -> Result<CompiledRule, regex::Error> {
+    Ok(CompiledRule {
+        name: rule.name.clone(),
+        regex: Regex::new(&rule.pattern)?,
+        replacement: rule.replacement.clone(),
+    })
+}
+
+/// Cuántos fragmentos redactó cada regla, para el registro de auditoría.
+pub fn count_by_rule(hits: &[RedactionHit]) -> BTreeMap<String, String> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for hit in hits {
+        *counts.entry(hit.rule.clone()).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(rule, count)| (rule, count.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_rules_redact_keys_emails_and_internal_hosts() {
+        let config = RedactionConfig::default();
+        let mut state = RedactionState::default();
+        let prompt = "Usa sk-ant-REDACTED en build.corp y avisa a ana@example.com";
+        let (redacted, hits) = state.apply(&config, prompt);
+        assert_eq!(redacted, "Usa [API_KEY] en [HOST] y avisa a [EMAIL]");
+        assert_eq!(hits.len(), 3);
+        assert_eq!(count_by_rule(&hits).get("API keys"), Some(&"1".to_string()));
+
+        let preview = state.preview(&config, "correo: ana@example.com");
+        assert_eq!(
+            preview,
+            vec![
+                PreviewSegment::Kept("correo: ".to_string()),
+                PreviewSegment::Redacted {
+                    original: "ana@example.com".to_string(),
+                    replacement: "[EMAIL]".to_string(),
+                    rule: "Correos electrónicos".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled_and_invalid_rules_are_skipped() {
+        let mut config = RedactionConfig::default();
+        config.rules[1].enabled = false;
+        config.rules.push(RedactionRule {
+            name: "Rota".to_string(),
+            pattern: "(".to_string(),
+            replacement: "x".to_string(),
+            enabled: true,
+        });
+        let mut state = RedactionState::default();
+        let (redacted, _) = state.apply(&config, "ana@example.com");
+        assert_eq!(redacted, "ana@example.com");
+        assert!(state.errors.contains_key(&3));
+
+        config.enabled = false;
+        assert!(state
+            .hits(&config, "sk-abcdefghijklmnopqrstuvwx")
+            .is_empty());
+    }
+}
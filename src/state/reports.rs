@@ -0,0 +1,303 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::Receiver;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+
+use crate::config::{AppConfig, ReportTemplate};
+
+/// Marcador de una sección que redacta el modelo: `{{modelo: instrucción}}`.
+const SECTION_PREFIX: &str = "modelo:";
+/// Caracteres del documento que acompañan a cada sección como contexto.
+const SECTION_CONTEXT_CHARS: usize = 2000;
+
+pub const EXAMPLE_TEMPLATE: &str = "# Informe semanal · {{fecha}}\n\n## Resumen\n{{modelo: Resume en un párrafo el estado general del proyecto.}}\n\n## Riesgos\n{{modelo: Enumera los tres riesgos principales con una acción para cada uno.}}\n";
+
+/// Trozo de una plantilla: texto literal o sección que rellena el modelo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TemplatePart {
+    Text(String),
+    Section(String),
+}
+
+/// Divide la plantilla en texto y secciones, sustituyendo antes `{{fecha}}`, `{{hora}}`
+/// y `{{plantilla}}`. Un `{{` sin cerrar se conserva como texto.
+pub fn parse_template(body: &str, name: &str, now: DateTime<Local>) -> Vec<TemplatePart> {
+    let body = body
+        .replace("{{fecha}}", &now.format("%Y-%m-%d").to_string())
+        .replace("{{hora}}", &now.format("%H:%M").to_string())
+        .replace("{{plantilla}}", name);
+
+    let mut parts = Vec::new();
+    let mut text = String::new();
+    let mut rest = body.as_str();
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start..].find("}}") else {
+            break;
+        };
+        let inner = rest[start + 2..start + length].trim();
+        match inner.strip_prefix(SECTION_PREFIX) {
+            Some(instruction) => {
+                text.push_str(&rest[..start]);
+                if !text.is_empty() {
+                    parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                }
+                parts.push(TemplatePart::Section(instruction.trim().to_string()));
+            }
+            None => text.push_str(&rest[..start + length + 2]),
+        }
+        rest = &rest[start + length + 2..];
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        parts.push(TemplatePart::Text(text));
+    }
+    parts
+}
+
+/// Prompt de cada sección, con el documento alrededor y las demás secciones numeradas.
+pub fn section_prompts(name: &str, parts: &[TemplatePart]) -> Vec<String> {
+    let mut outline = String::new();
+    let mut number = 0;
+    for part in parts {
+        match part {
+            TemplatePart::Text(text) => outline.push_str(text),
+            TemplatePart::Section(_) => {
+                number += 1;
+                outline.push_str(&format!("[Sección {}]", number));
+            }
+        }
+    }
+    let outline: String = outline.chars().take(SECTION_CONTEXT_CHARS).collect();
+
+    parts
+        .iter()
+        .filter_map(|part| match part {
+            TemplatePart::Section(instruction) => Some(instruction),
+            TemplatePart::Text(_) => None,
+        })
+        .enumerate()
+        .map(|(index, instruction)| {
+            format!(
+                "Estás redactando el informe \"{}\" en Markdown. Este es el documento, con las secciones pendientes marcadas:\n\n{}\n\nEscribe únicamente el contenido de la [Sección {}], sin repetir su título: {}",
+                name,
+                outline,
+                index + 1,
+                instruction
+            )
+        })
+        .collect()
+}
+
+/// Une el texto de la plantilla con las secciones generadas, en orden.
+pub fn render(parts: &[TemplatePart], sections: &[String]) -> String {
+    let mut sections = sections.iter();
+    let mut output = String::new();
+    for part in parts {
+        match part {
+            TemplatePart::Text(text) => output.push_str(text),
+            TemplatePart::Section(_) => {
+                if let Some(section) = sections.next() {
+                    output.push_str(section.trim());
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Directorio de salida de una plantilla; sin configurar, `informes/` en los datos de la app.
+pub fn output_directory(template: &ReportTemplate) -> Result<PathBuf> {
+    match template.output_directory.trim() {
+        "" => AppConfig::data_file("informes"),
+        directory => Ok(PathBuf::from(directory)),
+    }
+}
+
+pub fn report_file_name(name: &str, now: DateTime<Local>) -> String {
+    let slug = name
+        .to_lowercase()
+        .chars()
+        .map(|ch| if ch.is_alphanumeric() { ch } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|piece| !piece.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug = if slug.is_empty() {
+        "informe".to_string()
+    } else {
+        slug
+    };
+    format!("{}-{}.md", slug, now.format("%Y%m%d-%H%M"))
+}
+
+/// Añade el informe al repositorio Git que contiene `path` y crea un commit solo con él.
+pub fn commit_report(path: &Path, message: &str) -> Result<()> {
+    let directory = path.parent().context("El informe no tiene directorio")?;
+    let file = path.file_name().context("El informe no tiene nombre")?;
+    for args in [
+        vec!["add".into(), file.to_os_string()],
+        vec![
+            "commit".into(),
+            "-m".into(),
+            message.into(),
+            "--".into(),
+            file.to_os_string(),
+        ],
+    ] {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(directory)
+            .args(&args)
+            .output()
+            .context("No se pudo ejecutar git")?;
+        if !output.status.success() {
+            bail!(
+                "git {} falló: {}",
+                args[0].to_string_lossy(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Informe ya escrito en disco.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedReport {
+    pub path: PathBuf,
+    pub template: String,
+    pub modified: String,
+    pub size: u64,
+}
+
+/// Informes Markdown de los directorios dados, del más reciente al más antiguo.
+pub fn list_reports<'a>(
+    directories: impl IntoIterator<Item = (&'a str, PathBuf)>,
+) -> Vec<GeneratedReport> {
+    let mut reports: Vec<(std::time::SystemTime, GeneratedReport)> = Vec::new();
+    for (template, directory) in directories {
+        let Ok(entries) = fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+                continue;
+            }
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let modified_at = metadata.modified().unwrap_or(std::time::UNIX_EPOCH);
+            if reports.iter().any(|(_, report)| report.path == path) {
+                continue;
+            }
+            reports.push((
+                modified_at,
+                GeneratedReport {
+                    path,
+                    template: template.to_string(),
+                    modified: DateTime::<Local>::from(modified_at)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string(),
+                    size: metadata.len(),
+                },
+            ));
+        }
+    }
+    reports.sort_by_key(|(modified_at, _)| std::cmp::Reverse(*modified_at));
+    reports.into_iter().map(|(_, report)| report).collect()
+}
+
+/// Generación en curso de un informe.
+pub struct ReportRun {
+    pub template: ReportTemplate,
+    pub parts: Vec<TemplatePart>,
+    pub started_at: DateTime<Local>,
+    /// Nombre de quien redacta las secciones, para la actividad.
+    pub author: String,
+    pub rx: Receiver<Result<Vec<String>, String>>,
+}
+
+/// Plantillas en edición, informes generados y vista previa de la sección de recursos.
+#[derive(Default)]
+pub struct ReportState {
+    pub draft: ReportTemplate,
+    /// Índice de la plantilla que se está editando; `None` al crear una nueva.
+    pub editing: Option<usize>,
+    pub reports: Vec<GeneratedReport>,
+    pub selected: Option<PathBuf>,
+    pub preview: String,
+    pub run: Option<ReportRun>,
+    pub status: Option<String>,
+    /// `true` tras la primera lectura de los directorios de salida.
+    pub loaded: bool,
+}
+
+impl ReportState {
+    pub fn is_running(&self) -> bool {
+        self.run.is_some()
+    }
+
+    pub fn refresh(&mut self, templates: &[ReportTemplate]) {
+        let directories = templates.iter().filter_map(|template| {
+            output_directory(template)
+                .ok()
+                .map(|directory| (template.name.as_str(), directory))
+        });
+        self.reports = list_reports(directories);
+        self.loaded = true;
+    }
+
+    pub fn select(&mut self, path: &Path) {
+        self.preview = fs::read_to_string(path)
+            .unwrap_or_else(|err| format!("No se pudo leer {}: {}", path.display(), err));
+        self.selected = Some(path.to_path_buf());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parses_sections_and_renders_them_in_place() {
+        let now = Local.with_ymd_and_hms(2024, 5, 15, 9, 30, 0).unwrap();
+        let parts = parse_template(
+            "# {{plantilla}} {{fecha}}\n{{modelo: Resume}}\nFin {{otro}} {{modelo:Riesgos}}",
+            "Semanal",
+            now,
+        );
+        assert_eq!(
+            parts,
+            vec![
+                TemplatePart::Text("# Semanal 2024-05-15\n".to_string()),
+                TemplatePart::Section("Resume".to_string()),
+                TemplatePart::Text("\nFin {{otro}} ".to_string()),
+                TemplatePart::Section("Riesgos".to_string()),
+            ]
+        );
+
+        let prompts = section_prompts("Semanal", &parts);
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[1].contains("[Sección 1]\nFin {{otro}} [Sección 2]"));
+        assert!(prompts[1].ends_with("[Sección 2], sin repetir su título: Riesgos"));
+
+        let rendered = render(
+            &parts,
+            &["Todo bien.\n".to_string(), "Ninguno.".to_string()],
+        );
+        assert_eq!(
+            rendered,
+            "# Semanal 2024-05-15\nTodo bien.\nFin {{otro}} Ninguno."
+        );
+        assert_eq!(
+            report_file_name("Informe Semanal / QA", now),
+            "informe-semanal-qa-20240515-0930.md"
+        );
+    }
+}
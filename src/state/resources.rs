@@ -1,15 +1,23 @@
 use std::collections::BTreeMap;
+use std::sync::mpsc::Receiver;
 
 use chrono::{Local, NaiveDate};
 
 use super::{
     feature::{CommandRegistry, FeatureModule, WorkbenchRegistry},
+    model_pool::ModelPool,
+    model_trash::{ModelDiskUsage, OrphanModelDir},
+    model_updates::ModelUpdateState,
     navigation::{NavigationNode, NavigationTarget},
+    performance::JarvisPerformanceState,
+    quantize::QuantizeState,
+    system_monitor::SystemMonitor,
     AnthropicModel, LocalLibraryState, LocalModelCard, LocalModelIdentifier, LocalModelProvider,
     LocalProviderState, NavigationRegistry, PersonalizationResourcesState, ProjectResourceCard,
     ProjectResourceKind, RemoteCatalogState, RemoteProviderKind,
 };
-use crate::config::AppConfig;
+use crate::api::local::{JarvisKnowledgeEntry, KnowledgeMatch, LoraAdapter};
+use crate::config::{AppConfig, ModelQuickAction};
 use crate::state::{InstalledLocalModel, JarvisRuntime};
 
 pub struct ResourceState {
@@ -23,12 +31,28 @@ pub struct ResourceState {
     pub jarvis_selected_provider: LocalModelProvider,
     pub jarvis_active_model: Option<LocalModelIdentifier>,
     pub jarvis_runtime: Option<JarvisRuntime>,
+    /// Modelos cargados para los alias por modelo, aparte del runtime principal.
+    pub jarvis_pool: ModelPool<JarvisRuntime>,
+    /// Tiempos de carga y de respuesta del runtime local.
+    pub jarvis_performance: JarvisPerformanceState,
     pub jarvis_alias: String,
+    /// Copia editable de la base de conocimientos; se aplica al runtime al confirmarla.
+    pub jarvis_knowledge: Vec<JarvisKnowledgeEntry>,
+    pub jarvis_knowledge_selected: Option<usize>,
+    pub jarvis_knowledge_probe: String,
+    pub jarvis_knowledge_matches: Vec<KnowledgeMatch>,
+    pub jarvis_knowledge_status: Option<String>,
+    /// Acción rápida personalizada que se está redactando en preferencias.
+    pub quick_action_draft: ModelQuickAction,
+    /// Adaptadores LoRA encontrados junto a los modelos instalados.
+    pub jarvis_adapters: Vec<LoraAdapter>,
     pub claude_default_model: String,
     pub claude_alias: String,
     pub anthropic_test_status: Option<String>,
     pub claude_available_models: Vec<AnthropicModel>,
     pub claude_models_status: Option<String>,
+    /// Consulta del catálogo de Anthropic en curso.
+    pub claude_models_rx: Option<Receiver<Result<Vec<AnthropicModel>, String>>>,
     pub openai_default_model: String,
     pub openai_alias: String,
     pub openai_test_status: Option<String>,
@@ -37,6 +61,16 @@ pub struct ResourceState {
     pub groq_test_status: Option<String>,
     pub remote_catalog: RemoteCatalogState,
     pub local_library: LocalLibraryState,
+    /// Revisiones publicadas frente a las instaladas en la biblioteca local.
+    pub model_updates: ModelUpdateState,
+    /// Conversión a pesos cuantizados en curso y su último resultado.
+    pub model_quantize: QuantizeState,
+    /// Carpetas de modelos encontradas en disco que no figuran en la configuración.
+    pub orphan_model_dirs: Vec<OrphanModelDir>,
+    /// Último informe de espacio ocupado por los modelos, medido bajo demanda.
+    pub model_disk_usage: Option<ModelDiskUsage>,
+    /// Uso de CPU, memoria, GPU y disco muestreado en segundo plano.
+    pub system_monitor: SystemMonitor,
     pub personalization_resources: PersonalizationResourcesState,
     pub personalization_feedback: Option<String>,
     pub project_resources: Vec<ProjectResourceCard>,
@@ -123,11 +157,20 @@ impl ResourceState {
             jarvis_selected_provider,
             jarvis_active_model,
             jarvis_runtime: None,
+            jarvis_pool: ModelPool::default(),
+            jarvis_performance: JarvisPerformanceState::default(),
             jarvis_alias: if config.jarvis.chat_alias.trim().is_empty() {
                 "jarvis".to_string()
             } else {
                 config.jarvis.chat_alias.clone()
             },
+            jarvis_knowledge: config.jarvis.knowledge_base.clone(),
+            jarvis_knowledge_selected: None,
+            jarvis_knowledge_probe: String::new(),
+            jarvis_knowledge_matches: Vec::new(),
+            jarvis_knowledge_status: None,
+            quick_action_draft: ModelQuickAction::default(),
+            jarvis_adapters: Vec::new(),
             claude_default_model: if config.anthropic.default_model.is_empty() {
                 "claude-3-opus-20240229".to_string()
             } else {
@@ -141,6 +184,7 @@ impl ResourceState {
             anthropic_test_status: None,
             claude_available_models: Vec::new(),
             claude_models_status: None,
+            claude_models_rx: None,
             openai_default_model: if config.openai.default_model.is_empty() {
                 "gpt-4.1-mini".to_string()
             } else {
@@ -165,6 +209,11 @@ impl ResourceState {
             groq_test_status: None,
             remote_catalog: RemoteCatalogState::default(),
             local_library: LocalLibraryState::default(),
+            model_updates: ModelUpdateState::default(),
+            model_quantize: QuantizeState::default(),
+            orphan_model_dirs: Vec::new(),
+            model_disk_usage: None,
+            system_monitor: SystemMonitor::default(),
             personalization_resources,
             personalization_feedback: None,
             project_resources: super::default_project_resources(),
@@ -225,12 +274,21 @@ impl ResourceState {
             limit: usage.daily_limit,
         })
     }
+
+    /// Devuelve la llamada reservada por `try_acquire_provider_quota` cuando la respuesta
+    /// no llegó a pedirse al proveedor.
+    pub fn release_provider_quota(&mut self, provider: RemoteProviderKind) {
+        let usage = self.usage_state_mut(provider);
+        usage.calls_today = usage.calls_today.saturating_sub(1);
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct ProviderUsageState {
     pub daily_limit: Option<u32>,
     pub calls_today: u32,
+    pub prompt_tokens_today: u64,
+    pub completion_tokens_today: u64,
     pub last_reset: NaiveDate,
 }
 
@@ -239,6 +297,8 @@ impl ProviderUsageState {
         Self {
             daily_limit: limit,
             calls_today: 0,
+            prompt_tokens_today: 0,
+            completion_tokens_today: 0,
             last_reset: Local::now().date_naive(),
         }
     }
@@ -247,9 +307,21 @@ impl ProviderUsageState {
         let today = Local::now().date_naive();
         if today != self.last_reset {
             self.calls_today = 0;
+            self.prompt_tokens_today = 0;
+            self.completion_tokens_today = 0;
             self.last_reset = today;
         }
     }
+
+    pub fn record_prompt_tokens(&mut self, tokens: usize) {
+        self.refresh_if_needed();
+        self.prompt_tokens_today += tokens as u64;
+    }
+
+    pub fn record_completion_tokens(&mut self, tokens: usize) {
+        self.refresh_if_needed();
+        self.completion_tokens_today += tokens as u64;
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -360,6 +432,24 @@ impl FeatureModule for ResourceState {
                 "Repositorios disponibles desde GitHub",
                 2u32,
             ),
+            (
+                super::ResourceSection::Bookmarks,
+                "📌",
+                "Mensajes fijados en todas las conversaciones",
+                3u32,
+            ),
+            (
+                super::ResourceSection::Reports,
+                "📝",
+                "Plantillas de informes y documentos generados",
+                4u32,
+            ),
+            (
+                super::ResourceSection::Playbooks,
+                "📋",
+                "Flujos de conversación de varios pasos",
+                5u32,
+            ),
         ];
 
         for (section, icon, description, order) in installed_nodes {
@@ -0,0 +1,269 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+
+use crate::config::AppConfig;
+
+use super::{vault, RemoteProviderKind};
+
+const RESPONSE_CACHE_DIR: &str = "responses";
+
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    provider: String,
+    model: String,
+    stored_at: i64,
+    response: String,
+}
+
+/// Contadores de uso compartidos entre el hilo principal y los hilos de llamada.
+#[derive(Debug, Default)]
+pub struct ResponseCacheStats {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResponseCacheStats {
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Ocupación en disco de la caché de respuestas.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ResponseCacheUsage {
+    pub entries: usize,
+    pub bytes: u64,
+}
+
+/// Caché en disco de respuestas de proveedores remotos, indexada por proveedor, modelo,
+/// prompt normalizado y parámetros de la petición. Es barata de clonar para llevarla a
+/// los hilos que realizan las llamadas.
+#[derive(Clone, Debug)]
+pub struct ResponseCache {
+    directory: PathBuf,
+    ttl_secs: i64,
+    max_entries: usize,
+    max_bytes: u64,
+    stats: Arc<ResponseCacheStats>,
+}
+
+impl ResponseCache {
+    pub fn key(
+        provider: RemoteProviderKind,
+        model: &str,
+        prompt: &str,
+        parameters: &str,
+    ) -> String {
+        let normalized = prompt.split_whitespace().collect::<Vec<_>>().join(" ");
+        let mut hasher = Sha1::new();
+        for part in [provider.short_code(), model.trim(), &normalized, parameters] {
+            hasher.update(part.as_bytes());
+            hasher.update([0u8]);
+        }
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// Respuesta guardada para `key`, si existe y no ha caducado.
+    pub fn get(&self, key: &str) -> Option<String> {
+        let path = self.entry_path(key);
+        // Si no se puede leer (no existe, el almacenamiento está bloqueado o la clave no
+        // sirve) la entrada se conserva; solo se borran las dañadas y las caducadas.
+        let Ok(data) = vault::read_to_string(&path) else {
+            self.stats.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        };
+        let cached = serde_json::from_str::<CachedResponse>(&data)
+            .ok()
+            .filter(|cached| Utc::now().timestamp() - cached.stored_at <= self.ttl_secs);
+
+        match cached {
+            Some(cached) => {
+                self.stats.hits.fetch_add(1, Ordering::Relaxed);
+                Some(cached.response)
+            }
+            None => {
+                let _ = fs::remove_file(&path);
+                self.stats.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub fn put(
+        &self,
+        key: &str,
+        provider: RemoteProviderKind,
+        model: &str,
+        response: &str,
+    ) -> Result<()> {
+        fs::create_dir_all(&self.directory)
+            .with_context(|| format!("No se pudo crear {}", self.directory.display()))?;
+        let json = serde_json::to_string(&CachedResponse {
+            provider: provider.short_code().to_string(),
+            model: model.to_string(),
+            stored_at: Utc::now().timestamp(),
+            response: response.to_string(),
+        })?;
+        let path = self.entry_path(key);
+        vault::write(&path, &json)
+            .with_context(|| format!("No se pudo guardar {}", path.display()))?;
+        self.enforce_limits();
+        Ok(())
+    }
+
+    pub fn usage(&self) -> ResponseCacheUsage {
+        let entries = self.entries();
+        ResponseCacheUsage {
+            entries: entries.len(),
+            bytes: entries.iter().map(|(_, size, _)| size).sum(),
+        }
+    }
+
+    /// Borra todas las respuestas guardadas y devuelve cuántas había.
+    pub fn clear(&self) -> usize {
+        let entries = self.entries();
+        for (path, _, _) in &entries {
+            let _ = fs::remove_file(path);
+        }
+        entries.len()
+    }
+
+    /// Elimina las entradas caducadas y, después, las más antiguas hasta respetar los
+    /// límites de número de entradas y tamaño.
+    fn enforce_limits(&self) {
+        let now = Utc::now().timestamp();
+        let mut entries = self.entries();
+        entries.retain(|(path, _, stored_at)| {
+            let expired = now - stored_at > self.ttl_secs;
+            if expired {
+                let _ = fs::remove_file(path);
+            }
+            !expired
+        });
+        entries.sort_by_key(|(_, _, stored_at)| *stored_at);
+
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        let mut remaining = entries.len();
+        for (path, size, _) in &entries {
+            if remaining <= self.max_entries && total <= self.max_bytes {
+                break;
+            }
+            let _ = fs::remove_file(path);
+            remaining -= 1;
+            total = total.saturating_sub(*size);
+        }
+    }
+
+    /// Entradas en disco con su tamaño y momento de guardado.
+    fn entries(&self) -> Vec<(PathBuf, u64, i64)> {
+        let Ok(read_dir) = fs::read_dir(&self.directory) else {
+            return Vec::new();
+        };
+        read_dir
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let size = fs::metadata(&path).ok()?.len();
+                let stored_at = read_stored_at(&path).unwrap_or_default();
+                Some((path, size, stored_at))
+            })
+            .collect()
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.directory.join(format!("{}.json", key))
+    }
+}
+
+fn read_stored_at(path: &Path) -> Option<i64> {
+    let data = vault::read_to_string(path).ok()?;
+    serde_json::from_str::<CachedResponse>(&data)
+        .ok()
+        .map(|cached| cached.stored_at)
+}
+
+/// Estado de la caché de respuestas en la aplicación.
+#[derive(Default)]
+pub struct ResponseCacheState {
+    pub stats: Arc<ResponseCacheStats>,
+    /// Última medición de la ocupación en disco; se recalcula bajo demanda.
+    pub usage: Option<ResponseCacheUsage>,
+    pub status: Option<String>,
+}
+
+impl ResponseCacheState {
+    /// Caché configurada según `config`, esté o no activada.
+    pub fn cache(&self, config: &AppConfig) -> ResponseCache {
+        let settings = &config.response_cache;
+        ResponseCache {
+            directory: PathBuf::from(&config.cache_directory).join(RESPONSE_CACHE_DIR),
+            ttl_secs: i64::from(settings.ttl_hours.max(1)) * 3600,
+            max_entries: settings.max_entries.max(1),
+            max_bytes: u64::from(settings.max_size_mb.max(1)) * 1024 * 1024,
+            stats: Arc::clone(&self.stats),
+        }
+    }
+
+    /// Caché que deben usar las llamadas, o `None` si está desactivada o el almacenamiento
+    /// cifrado sigue bloqueado.
+    pub fn handle(&self, config: &AppConfig) -> Option<ResponseCache> {
+        (config.response_cache.enabled && !vault::is_locked()).then(|| self.cache(config))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unreadable_entries_and_drops_damaged_ones() {
+        let directory =
+            std::env::temp_dir().join(format!("jmk-response-cache-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let cache = ResponseCache {
+            directory: directory.clone(),
+            ttl_secs: 3600,
+            max_entries: 10,
+            max_bytes: 1 << 20,
+            stats: Arc::default(),
+        };
+        cache
+            .put("vigente", RemoteProviderKind::OpenAi, "gpt-4o", "hola")
+            .unwrap();
+        assert_eq!(cache.get("vigente").as_deref(), Some("hola"));
+
+        // Cifrada con una clave que no hay: puede ser el almacenamiento bloqueado.
+        let mut sealed = b"JMKV\x01".to_vec();
+        sealed.extend_from_slice(&[0u8; 40]);
+        fs::write(cache.entry_path("cifrada"), &sealed).unwrap();
+        assert_eq!(cache.get("cifrada"), None);
+        assert!(cache.entry_path("cifrada").exists());
+
+        fs::write(cache.entry_path("dañada"), "{no es json").unwrap();
+        assert_eq!(cache.get("dañada"), None);
+        assert!(!cache.entry_path("dañada").exists());
+        assert_eq!((cache.stats.hits(), cache.stats.misses()), (1, 2));
+        let _ = fs::remove_dir_all(&directory);
+    }
+}
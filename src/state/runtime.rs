@@ -0,0 +1,183 @@
+use std::io;
+use std::sync::mpsc::{self, Receiver, SendError, SyncSender, TrySendError};
+use std::sync::OnceLock;
+use std::thread::{self, JoinHandle as ThreadHandle, ThreadId};
+use std::time::Duration;
+
+use eframe::egui;
+use once_cell::sync::Lazy;
+use tokio::runtime::{Builder, Runtime};
+use tokio::task::JoinHandle;
+
+/// Hilos del planificador de tokio. El trabajo de la aplicación es bloqueante (clientes
+/// HTTP síncronos, git, curl) y se ejecuta en el grupo aparte de `spawn_blocking`.
+const WORKER_THREADS: usize = 2;
+/// Tope del grupo bloqueante; por encima las tareas esperan turno.
+const MAX_BLOCKING_THREADS: usize = 32;
+/// Tiempo que un hilo ocioso se conserva para reutilizarlo en la siguiente tarea.
+const THREAD_KEEP_ALIVE: Duration = Duration::from_secs(30);
+/// Mensajes que caben en los canales de las tareas hacia la interfaz.
+pub const CHANNEL_CAPACITY: usize = 64;
+
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+    Builder::new_multi_thread()
+        .worker_threads(WORKER_THREADS)
+        .max_blocking_threads(MAX_BLOCKING_THREADS)
+        .thread_keep_alive(THREAD_KEEP_ALIVE)
+        .thread_name("junglemonkai-worker")
+        .build()
+        .expect("no se pudo crear el runtime de tareas")
+});
+
+static REPAINT: OnceLock<egui::Context> = OnceLock::new();
+static UI_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+/// Registra el contexto de egui para que las tareas despierten a la interfaz al
+/// terminar, en lugar de esperar a que el usuario mueva el ratón. Se llama desde el
+/// hilo de la interfaz, que queda anotado para que nunca espere a un canal lleno.
+pub fn install_repaint(ctx: &egui::Context) {
+    let _ = REPAINT.set(ctx.clone());
+    let _ = UI_THREAD.set(thread::current().id());
+}
+
+fn is_ui_thread() -> bool {
+    UI_THREAD.get() == Some(&thread::current().id())
+}
+
+pub fn request_repaint() {
+    if let Some(ctx) = REPAINT.get() {
+        ctx.request_repaint();
+    }
+}
+
+pub fn request_repaint_after(delay: Duration) {
+    if let Some(ctx) = REPAINT.get() {
+        ctx.request_repaint_after(delay);
+    }
+}
+
+/// Pide un repintado al soltarse, también si la tarea entra en pánico.
+struct RepaintGuard;
+
+impl Drop for RepaintGuard {
+    fn drop(&mut self) {
+        request_repaint();
+    }
+}
+
+/// Tarea lanzada en el runtime compartido.
+pub struct TaskHandle(JoinHandle<()>);
+
+impl TaskHandle {
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+}
+
+/// Ejecuta `work` en el grupo bloqueante del runtime, que reutiliza hilos entre tareas,
+/// y despierta a la interfaz cuando termina para que recoja el resultado.
+pub fn spawn_blocking(work: impl FnOnce() + Send + 'static) -> TaskHandle {
+    TaskHandle(RUNTIME.spawn_blocking(move || {
+        let _repaint = RepaintGuard;
+        work();
+    }))
+}
+
+/// Hilo dedicado a un servicio que vive mientras está activo: vigilantes, puentes y
+/// conexiones abiertas. No va al grupo bloqueante porque ocuparía una de sus plazas
+/// durante toda la sesión y dejaría sin hueco a las tareas cortas; al terminar, también
+/// por pánico, pide un repintado para que la interfaz vea el canal cerrado.
+pub fn spawn_service(
+    name: &str,
+    work: impl FnOnce() + Send + 'static,
+) -> io::Result<ThreadHandle<()>> {
+    thread::Builder::new()
+        .name(format!("junglemonkai-{}", name))
+        .spawn(move || {
+            let _repaint = RepaintGuard;
+            work();
+        })
+}
+
+/// Extremo emisor de un canal acotado hacia la interfaz: cada mensaje pide un
+/// repintado. `send` hace esperar a las tareas si el canal está lleno, nunca al hilo de
+/// la interfaz, y `send_progress` descarta el aviso en lugar de esperar.
+pub struct UiSender<T>(SyncSender<T>);
+
+impl<T> Clone for UiSender<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> UiSender<T> {
+    /// Envía un resultado que la interfaz no debe perder. Con el canal lleno, la tarea
+    /// espera a que la interfaz lo vacíe; si quien envía es la propia interfaz, esperar
+    /// la bloquearía para siempre, así que el mensaje se devuelve como error.
+    pub fn send(&self, value: T) -> Result<(), SendError<T>> {
+        let result = match self.0.try_send(value) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(value)) if is_ui_thread() => Err(SendError(value)),
+            Err(TrySendError::Full(value)) => {
+                request_repaint();
+                self.0.send(value)
+            }
+            Err(TrySendError::Disconnected(value)) => Err(SendError(value)),
+        };
+        request_repaint();
+        result
+    }
+
+    /// Envía un aviso de progreso o una muestra periódica sin esperar nunca: con el canal
+    /// lleno se descarta, porque el siguiente lo sustituye. Solo falla si la interfaz ya
+    /// no escucha.
+    pub fn send_progress(&self, value: T) -> Result<(), SendError<T>> {
+        let result = match self.0.try_send(value) {
+            Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+            Err(TrySendError::Disconnected(value)) => Err(SendError(value)),
+        };
+        request_repaint();
+        result
+    }
+}
+
+/// Canal acotado a `CHANNEL_CAPACITY` mensajes cuyo receptor sondea la interfaz.
+pub fn ui_channel<T>() -> (UiSender<T>, Receiver<T>) {
+    let (tx, rx) = mpsc::sync_channel(CHANNEL_CAPACITY);
+    (UiSender(tx), rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_blocking_tasks_and_bounds_ui_channels() {
+        let (tx, rx) = ui_channel();
+        let task = spawn_blocking(move || {
+            for value in 0..CHANNEL_CAPACITY + 10 {
+                tx.send(value).unwrap();
+            }
+        });
+        let received: Vec<usize> = rx.iter().collect();
+        assert_eq!(received, (0..CHANNEL_CAPACITY + 10).collect::<Vec<_>>());
+        while !task.is_finished() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // Los avisos de progreso no esperan: los que no caben se descartan.
+        let (tx, rx) = ui_channel();
+        for value in 0..CHANNEL_CAPACITY + 10 {
+            tx.send_progress(value).unwrap();
+        }
+        assert_eq!(rx.try_iter().count(), CHANNEL_CAPACITY);
+
+        let (tx, rx) = ui_channel::<u8>();
+        drop(rx);
+        assert!(tx.send(1).is_err());
+        assert!(tx.send_progress(1).is_err());
+
+        let service = spawn_service("prueba", || {}).unwrap();
+        service.join().unwrap();
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+
+use regex::Regex;
+
+use crate::config::{SafetyAction, SafetyFilterConfig, SafetyFilterRule};
+
+/// Coincidencias que se conservan en el registro del panel de filtros.
+pub const MAX_LOGGED_HITS: usize = 200;
+/// Caracteres del texto filtrado que se guardan con cada coincidencia.
+const EXCERPT_CHARS: usize = 80;
+
+/// Texto al que se aplican los filtros.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterStage {
+    Prompt,
+    Response,
+}
+
+impl FilterStage {
+    pub fn label(self) -> &'static str {
+        match self {
+            FilterStage::Prompt => "Prompt",
+            FilterStage::Response => "Respuesta",
+        }
+    }
+}
+
+pub fn action_label(action: SafetyAction) -> &'static str {
+    match action {
+        SafetyAction::Warn => "Avisar",
+        SafetyAction::Redact => "Redactar",
+        SafetyAction::Block => "Bloquear",
+    }
+}
+
+/// Coincidencia de una regla o del clasificador; las del clasificador no tienen `range`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SafetyHit {
+    pub rule: String,
+    pub action: SafetyAction,
+    pub range: Option<Range<usize>>,
+}
+
+/// Resultado de filtrar un texto: el texto con los fragmentos redactados y todas las
+/// coincidencias.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilterOutcome {
+    pub text: String,
+    pub hits: Vec<SafetyHit>,
+}
+
+impl FilterOutcome {
+    pub fn action(&self) -> Option<SafetyAction> {
+        self.hits.iter().map(|hit| hit.action).max()
+    }
+
+    /// Regla que decidió la acción final, para los mensajes al usuario.
+    pub fn deciding_rule(&self) -> Option<&str> {
+        let action = self.action()?;
+        self.hits
+            .iter()
+            .find(|hit| hit.action == action)
+            .map(|hit| hit.rule.as_str())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SafetyLogEntry {
+    pub timestamp: String,
+    pub conversation: String,
+    pub stage: FilterStage,
+    pub rule: String,
+    pub action: SafetyAction,
+    pub excerpt: String,
+}
+
+struct CompiledRule {
+    name: String,
+    regex: Regex,
+    action: SafetyAction,
+}
+
+/// Reglas compiladas, embeddings de los temas del clasificador y registro de
+/// coincidencias de la sesión.
+#[derive(Default)]
+pub struct SafetyState {
+    source: Option<Vec<SafetyFilterRule>>,
+    compiled: Vec<CompiledRule>,
+    /// Error de compilación de cada regla inválida, por índice.
+    pub errors: BTreeMap<usize, String>,
+    /// Temas con los que se calcularon `topic_vectors`.
+    topics: Vec<String>,
+    topic_vectors: Vec<Vec<f32>>,
+    /// Texto de prueba del panel de preferencias.
+    pub sample: String,
+    pub log: VecDeque<SafetyLogEntry>,
+}
+
+impl SafetyState {
+    fn sync(&mut self, rules: &[SafetyFilterRule]) {
+        if self.source.as_deref() == Some(rules) {
+            return;
+        }
+        self.compiled.clear();
+        self.errors.clear();
+        for (index, rule) in rules.iter().enumerate() {
+            if !rule.enabled {
+                continue;
+            }
+            let pattern = if rule.regex {
+                Some(rule.pattern.clone()).filter(|pattern| !pattern.trim().is_empty())
+            } else {
+                keyword_pattern(&rule.pattern)
+            };
+            let Some(pattern) = pattern else {
+                continue;
+            };
+            match Regex::new(&pattern) {
+                Ok(regex) => self.compiled.push(CompiledRule {
+                    name: rule.name.clone(),
+                    regex,
+                    action: rule.action,
+                }),
+                Err(err) => {
+                    self.errors.insert(index, err.to_string());
+                }
+            }
+        }
+        self.source = Some(rules.to_vec());
+    }
+
+    /// Compila las reglas si han cambiado, para mostrar los errores en el panel.
+    pub fn validate(&mut self, config: &SafetyFilterConfig) {
+        self.sync(&config.rules);
+    }
+
+    /// Aplica las reglas a `text` junto con la coincidencia del clasificador, si la hay.
+    /// Si dos fragmentos se solapan se queda el que empieza antes.
+    pub fn filter(
+        &mut self,
+        config: &SafetyFilterConfig,
+        text: &str,
+        classified: Option<SafetyHit>,
+    ) -> FilterOutcome {
+        self.sync(&config.rules);
+        let mut found: Vec<SafetyHit> = Vec::new();
+        for rule in &self.compiled {
+            for matched in rule.regex.find_iter(text) {
+                if matched.range().is_empty() {
+                    continue;
+                }
+                found.push(SafetyHit {
+                    rule: rule.name.clone(),
+                    action: rule.action,
+                    range: Some(matched.range()),
+                });
+            }
+        }
+        found.sort_by_key(|hit| hit.range.as_ref().map_or(0, |range| range.start));
+
+        let mut hits: Vec<SafetyHit> = Vec::with_capacity(found.len() + 1);
+        let mut cursor = 0;
+        let mut filtered = String::with_capacity(text.len());
+        for hit in found {
+            let Some(range) = hit.range.clone() else {
+                continue;
+            };
+            if range.start < cursor {
+                continue;
+            }
+            if hit.action == SafetyAction::Redact {
+                filtered.push_str(&text[cursor..range.start]);
+                filtered.push_str(&config.replacement);
+                cursor = range.end;
+            }
+            hits.push(hit);
+        }
+        filtered.push_str(&text[cursor..]);
+        hits.extend(classified);
+        FilterOutcome {
+            text: filtered,
+            hits,
+        }
+    }
+
+    /// Embeddings de los temas si se calcularon con la lista actual.
+    pub fn topic_vectors(&self, topics: &[String]) -> Option<&[Vec<f32>]> {
+        (self.topics == topics && self.topic_vectors.len() == topics.len())
+            .then_some(self.topic_vectors.as_slice())
+    }
+
+    pub fn set_topic_vectors(&mut self, topics: &[String], vectors: Vec<Vec<f32>>) {
+        self.topics = topics.to_vec();
+        self.topic_vectors = vectors;
+    }
+
+    /// Añade una coincidencia al registro, descartando las más antiguas.
+    pub fn record(&mut self, entry: SafetyLogEntry) {
+        self.log.push_front(entry);
+        self.log.truncate(MAX_LOGGED_HITS);
+    }
+}
+
+/// Patrón que busca cualquiera de las palabras de `keywords` completa y sin distinguir
+/// mayúsculas. `None` si la lista está vacía.
+fn keyword_pattern(keywords: &str) -> Option<String> {
+    let words: Vec<String> = keywords
+        .split(',')
+        .map(str::trim)
+        .filter(|word| !word.is_empty())
+        .map(regex::escape)
+        .collect();
+    (!words.is_empty()).then(|| format!(r"(?i)\b(?:{})\b", words.join("|")))
+}
+
+/// Tema de `topics` más parecido a `vector` si su similitud coseno llega a `threshold`.
+pub fn classify<'a>(
+    vector: &[f32],
+    topics: &'a [String],
+    topic_vectors: &[Vec<f32>],
+    threshold: f32,
+) -> Option<(&'a str, f32)> {
+    topics
+        .iter()
+        .zip(topic_vectors)
+        .map(|(topic, topic_vector)| (topic.as_str(), cosine(vector, topic_vector)))
+        .filter(|(_, score)| *score >= threshold)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm =
+        a.iter().map(|x| x * x).sum::<f32>().sqrt() * b.iter().map(|y| y * y).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        0.0
+    } else {
+        dot / norm
+    }
+}
+
+/// Principio del texto filtrado o del fragmento que coincidió, para el registro.
+pub fn excerpt(text: &str, hit: &SafetyHit) -> String {
+    let fragment = hit
+        .range
+        .clone()
+        .and_then(|range| text.get(range))
+        .unwrap_or(text);
+    let mut excerpt: String = fragment.chars().take(EXCERPT_CHARS).collect();
+    if fragment.chars().count() > EXCERPT_CHARS {
+        excerpt.push('…');
+    }
+    excerpt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_keywords_and_escalates_to_the_strictest_action() {
+        let mut config = SafetyFilterConfig::default();
+        let mut state = SafetyState::default();
+        let outcome = state.filter(&config, "Pues joder, la password: hunter2 no vale", None);
+        assert_eq!(outcome.text, "Pues [FILTRADO], la [FILTRADO] no vale");
+        assert_eq!(outcome.action(), Some(SafetyAction::Redact));
+        assert!(state
+            .filter(&config, "Jodido, pero sin tacos", None)
+            .hits
+            .is_empty());
+
+        config.rules.push(SafetyFilterRule {
+            name: "Competencia".to_string(),
+            pattern: "AcmeCorp".to_string(),
+            regex: false,
+            action: SafetyAction::Block,
+            enabled: true,
+        });
+        config.rules.push(SafetyFilterRule {
+            name: "Rota".to_string(),
+            pattern: "(".to_string(),
+            regex: true,
+            action: SafetyAction::Warn,
+            enabled: true,
+        });
+        let outcome = state.filter(&config, "Mejor que acmecorp, joder", None);
+        assert_eq!(outcome.action(), Some(SafetyAction::Block));
+        assert_eq!(outcome.deciding_rule(), Some("Competencia"));
+        assert!(state.errors.contains_key(&3));
+
+        let topics = vec!["violencia".to_string(), "cocina".to_string()];
+        let vectors = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        assert_eq!(
+            classify(&[0.9, 0.1], &topics, &vectors, 0.8).map(|(topic, _)| topic),
+            Some("violencia")
+        );
+        assert_eq!(classify(&[0.6, 0.6], &topics, &vectors, 0.8), None);
+    }
+}
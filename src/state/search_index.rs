@@ -0,0 +1,201 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use super::runtime;
+use super::{GlobalSearchGroup, GlobalSearchResult};
+
+/// Pausa en la escritura tras la que se vuelve a filtrar la búsqueda global.
+pub const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Fuentes del índice de la búsqueda global; cada una se reconstruye por separado.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SearchSource {
+    Models,
+    Conversations,
+    Preferences,
+    Documents,
+    Workflows,
+}
+
+/// Resultado ya preparado junto al texto en minúsculas con el que se compara.
+pub struct SearchEntry {
+    haystack: String,
+    result: GlobalSearchResult,
+}
+
+impl SearchEntry {
+    pub fn new(haystack: &str, result: GlobalSearchResult) -> Self {
+        Self {
+            haystack: haystack.to_lowercase(),
+            result,
+        }
+    }
+}
+
+struct IndexedSource {
+    fingerprint: u64,
+    entries: Vec<SearchEntry>,
+}
+
+/// Índice de la búsqueda global. Las entradas de cada fuente solo se rehacen cuando
+/// cambia su huella o se marcan como sucias, y los grupos filtrados se guardan hasta
+/// que cambia la consulta, ya sin pulsaciones pendientes, o alguna fuente.
+#[derive(Default)]
+pub struct SearchIndex {
+    sources: HashMap<SearchSource, IndexedSource>,
+    dirty: HashSet<SearchSource>,
+    /// Consulta y huella del contexto con las que se calcularon `groups`.
+    applied: Option<(String, u64)>,
+    /// Consulta escrita que espera a que pase `SEARCH_DEBOUNCE`.
+    pending: Option<(String, Instant)>,
+    stale: bool,
+    groups: Vec<GlobalSearchGroup>,
+}
+
+impl SearchIndex {
+    /// Obliga a rehacer la fuente en la siguiente actualización aunque su huella no cambie.
+    pub fn mark_dirty(&mut self, source: SearchSource) {
+        self.dirty.insert(source);
+    }
+
+    pub fn needs_rebuild(&self, source: SearchSource, fingerprint: u64) -> bool {
+        self.dirty.contains(&source)
+            || self
+                .sources
+                .get(&source)
+                .is_none_or(|indexed| indexed.fingerprint != fingerprint)
+    }
+
+    pub fn replace(&mut self, source: SearchSource, fingerprint: u64, entries: Vec<SearchEntry>) {
+        self.dirty.remove(&source);
+        self.sources.insert(
+            source,
+            IndexedSource {
+                fingerprint,
+                entries,
+            },
+        );
+        self.stale = true;
+    }
+
+    /// Hasta `limit` resultados de la fuente que contienen `query`, ya en minúsculas.
+    pub fn matches(
+        &self,
+        source: SearchSource,
+        query: &str,
+        limit: usize,
+    ) -> Vec<GlobalSearchResult> {
+        let Some(indexed) = self.sources.get(&source) else {
+            return Vec::new();
+        };
+        indexed
+            .entries
+            .iter()
+            .filter(|entry| query.is_empty() || entry.haystack.contains(query))
+            .take(limit)
+            .map(|entry| entry.result.clone())
+            .collect()
+    }
+
+    /// Devuelve la consulta que toca aplicar, o `None` si los grupos guardados siguen
+    /// valiendo o la escritura aún no se ha detenido; en ese caso pide un repintado para
+    /// cuando venza la espera. Vaciar el buscador no espera.
+    pub fn settle(&mut self, query: &str, context: u64) -> Option<String> {
+        let current = self
+            .applied
+            .as_ref()
+            .is_some_and(|(applied, fingerprint)| applied == query && *fingerprint == context);
+        if current && !self.stale {
+            self.pending = None;
+            return None;
+        }
+        let typing = self
+            .applied
+            .as_ref()
+            .is_some_and(|(applied, _)| applied != query);
+        if typing && !query.is_empty() {
+            match &self.pending {
+                Some((pending, since)) if pending == query => {
+                    let elapsed = since.elapsed();
+                    if elapsed < SEARCH_DEBOUNCE {
+                        runtime::request_repaint_after(SEARCH_DEBOUNCE - elapsed);
+                        return None;
+                    }
+                }
+                _ => {
+                    self.pending = Some((query.to_string(), Instant::now()));
+                    runtime::request_repaint_after(SEARCH_DEBOUNCE);
+                    return None;
+                }
+            }
+        }
+        self.pending = None;
+        Some(query.to_string())
+    }
+
+    pub fn store(&mut self, query: String, context: u64, groups: Vec<GlobalSearchGroup>) {
+        self.applied = Some((query, context));
+        self.stale = false;
+        self.groups = groups;
+    }
+
+    pub fn groups(&self) -> &[GlobalSearchGroup] {
+        &self.groups
+    }
+}
+
+/// Huella barata de lo que alimenta una fuente, para detectar cambios sin reindexar.
+pub fn fingerprint(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(title: &str) -> GlobalSearchResult {
+        GlobalSearchResult {
+            title: title.to_string(),
+            subtitle: String::new(),
+            action_hint: String::new(),
+            source: None,
+        }
+    }
+
+    #[test]
+    fn rebuilds_changed_sources_and_debounces_typing() {
+        let mut index = SearchIndex::default();
+        assert!(index.needs_rebuild(SearchSource::Workflows, 1));
+        index.replace(
+            SearchSource::Workflows,
+            1,
+            vec![
+                SearchEntry::new("Informe Diario", result("Informe diario")),
+                SearchEntry::new("Revisión de PR", result("Revisión")),
+            ],
+        );
+        assert!(!index.needs_rebuild(SearchSource::Workflows, 1));
+        assert!(index.needs_rebuild(SearchSource::Workflows, 2));
+        index.mark_dirty(SearchSource::Workflows);
+        assert!(index.needs_rebuild(SearchSource::Workflows, 1));
+        let hits = index.matches(SearchSource::Workflows, "informe", 6);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "Informe diario");
+        assert_eq!(index.matches(SearchSource::Workflows, "", 1).len(), 1);
+
+        assert_eq!(index.settle("", 0).as_deref(), Some(""));
+        index.store(String::new(), 0, Vec::new());
+        assert_eq!(index.settle("", 0), None);
+        assert_eq!(index.settle("inf", 0), None);
+        assert_eq!(index.settle("inf", 0), None);
+        std::thread::sleep(SEARCH_DEBOUNCE);
+        assert_eq!(index.settle("inf", 0).as_deref(), Some("inf"));
+        index.store("inf".to_string(), 0, Vec::new());
+        assert_eq!(index.settle("inf", 7).as_deref(), Some("inf"));
+        assert_eq!(index.settle("", 7).as_deref(), Some(""));
+    }
+}
@@ -0,0 +1,260 @@
+use std::backtrace::Backtrace;
+use std::collections::VecDeque;
+use std::fs;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::config::AppConfig;
+
+use super::share::SharedConversation;
+use super::{vault, MainView};
+
+/// Intervalo mínimo entre dos instantáneas de la sesión.
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(30);
+/// Líneas de registro recientes que se adjuntan al informe de fallo.
+pub const CRASH_LOG_LINES: usize = 200;
+
+pub(super) const SNAPSHOT_FILE: &str = "session.json";
+/// Existe mientras la aplicación está abierta; si sigue ahí al arrancar, la sesión
+/// anterior no terminó limpiamente.
+const RUNNING_MARKER: &str = "session.lock";
+const CRASH_DIR: &str = "crashes";
+
+/// Últimas líneas del registro de actividad y de depuración, accesibles desde el gancho
+/// de pánico, que no tiene acceso al estado de la aplicación.
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Copia en disco de lo necesario para retomar la sesión tras un cierre inesperado.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    pub saved_at: String,
+    /// Clave de la conversación, para que marcadores y ajustes sigan asociados a ella.
+    pub conversation_key: String,
+    pub active_view: MainView,
+    pub input: String,
+    pub chat: SharedConversation,
+}
+
+impl SessionSnapshot {
+    pub fn message_count(&self) -> usize {
+        self.chat.messages.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.chat.messages.is_empty() && self.input.trim().is_empty()
+    }
+}
+
+/// Instantáneas periódicas de la sesión y recuperación de la anterior.
+#[derive(Default)]
+pub struct SessionState {
+    /// Sesión anterior que no se cerró limpiamente, a la espera de restaurarla o descartarla.
+    pub recovery: Option<SessionSnapshot>,
+    /// Informe generado por el último pánico, si la sesión anterior terminó así.
+    pub crash_report: Option<PathBuf>,
+    last_attempt: Option<Instant>,
+    /// Contenido de la última instantánea escrita, sin la hora, para no repetirla.
+    last_written: String,
+    marker_written: bool,
+}
+
+impl SessionState {
+    /// Comprueba si la sesión anterior dejó su marcador en disco y, en ese caso, carga su
+    /// última instantánea para ofrecer restaurarla.
+    pub fn detect_previous() -> Self {
+        let unclean = AppConfig::data_file(RUNNING_MARKER)
+            .map(|path| path.exists())
+            .unwrap_or(false);
+        if !unclean {
+            return Self::default();
+        }
+
+        let recovery = AppConfig::data_file(SNAPSHOT_FILE)
+            .ok()
+            .and_then(|path| vault::read_to_string(&path).ok())
+            .and_then(|data| serde_json::from_str::<SessionSnapshot>(&data).ok())
+            .filter(|snapshot| !snapshot.is_empty());
+        let crash_report = AppConfig::data_file(CRASH_DIR)
+            .ok()
+            .and_then(|dir| latest_crash_report(&dir));
+        Self {
+            recovery,
+            crash_report,
+            ..Self::default()
+        }
+    }
+
+    /// Escribe la instantánea que devuelve `capture` como mucho cada `SNAPSHOT_INTERVAL`
+    /// y solo si cambió. Mientras haya una sesión pendiente de recuperar no se guarda
+    /// nada, para no sobrescribirla. Devuelve `Ok(true)` si se escribió el fichero.
+    pub fn autosave(&mut self, capture: impl FnOnce() -> SessionSnapshot) -> Result<bool> {
+        if self.recovery.is_some()
+            || self
+                .last_attempt
+                .is_some_and(|at| at.elapsed() < SNAPSHOT_INTERVAL)
+        {
+            return Ok(false);
+        }
+        // Se da por intentado aunque falle la escritura para no reintentarlo cada frame.
+        self.last_attempt = Some(Instant::now());
+
+        if !self.marker_written {
+            let marker = AppConfig::data_file(RUNNING_MARKER)?;
+            fs::write(&marker, std::process::id().to_string())
+                .with_context(|| format!("No se pudo crear {}", marker.display()))?;
+            self.marker_written = true;
+        }
+
+        let mut snapshot = capture();
+        snapshot.saved_at.clear();
+        let content = serde_json::to_string(&snapshot)?;
+        if content == self.last_written {
+            return Ok(false);
+        }
+        snapshot.saved_at = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        let path = AppConfig::data_file(SNAPSHOT_FILE)?;
+        let json = serde_json::to_string_pretty(&snapshot)?;
+        let temporary = path.with_extension("json.tmp");
+        vault::write(&temporary, &json)?;
+        fs::rename(&temporary, &path)
+            .with_context(|| format!("No se pudo guardar {}", path.display()))?;
+        self.last_written = content;
+        Ok(true)
+    }
+
+    /// Entrega la sesión pendiente para restaurarla y reanuda las instantáneas.
+    pub fn take_recovery(&mut self) -> Option<SessionSnapshot> {
+        self.last_attempt = None;
+        self.recovery.take()
+    }
+
+    pub fn discard_recovery(&mut self) {
+        self.take_recovery();
+        self.crash_report = None;
+    }
+
+    /// Cierre ordenado: borra el marcador para que el próximo arranque no ofrezca
+    /// recuperar nada. Si la recuperación sigue pendiente se conserva para la próxima vez.
+    pub fn shutdown(&mut self) -> Result<()> {
+        if self.recovery.is_some() || !self.marker_written {
+            return Ok(());
+        }
+        self.marker_written = false;
+        let marker = AppConfig::data_file(RUNNING_MARKER)?;
+        match fs::remove_file(&marker) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => {
+                Err(err).with_context(|| format!("No se pudo borrar {}", marker.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Añade una línea al registro reciente que acompaña a los informes de fallo.
+pub fn record_log(line: String) {
+    let Ok(mut logs) = RECENT_LOGS.lock() else {
+        return;
+    };
+    logs.push_back(line);
+    if logs.len() > CRASH_LOG_LINES {
+        logs.pop_front();
+    }
+}
+
+/// Instala un gancho de pánico que vuelca el registro y escribe un informe de fallo
+/// antes de delegar en el gancho anterior.
+pub fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        log::logger().flush();
+        match write_crash_report(&info.to_string()) {
+            Ok(path) => eprintln!("Informe de fallo guardado en {}", path.display()),
+            Err(err) => eprintln!("No se pudo guardar el informe de fallo: {:#}", err),
+        }
+        previous(info);
+    }));
+}
+
+fn write_crash_report(panic_message: &str) -> Result<PathBuf> {
+    let dir = AppConfig::data_file(CRASH_DIR)?;
+    fs::create_dir_all(&dir).with_context(|| format!("No se pudo crear {}", dir.display()))?;
+    let path = dir.join(format!(
+        "crash-{}.log",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::write(&path, crash_report(panic_message))
+        .with_context(|| format!("No se pudo guardar {}", path.display()))?;
+    Ok(path)
+}
+
+fn crash_report(panic_message: &str) -> String {
+    // Si el pánico ocurrió con el registro bloqueado, el informe sale sin él.
+    let logs = RECENT_LOGS
+        .try_lock()
+        .map(|logs| logs.iter().cloned().collect::<Vec<_>>().join("\n"))
+        .unwrap_or_default();
+    format!(
+        "JungleMonkAI {}\nFecha: {}\n\n{}\n\nTraza:\n{}\n\nRegistro reciente:\n{}\n",
+        env!("CARGO_PKG_VERSION"),
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        panic_message,
+        Backtrace::force_capture(),
+        logs
+    )
+}
+
+fn latest_crash_report(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("crash-"))
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::ChatMessage;
+
+    #[test]
+    fn snapshot_round_trips_messages_and_view() {
+        let messages = vec![
+            ChatMessage::user("¿Qué tal?"),
+            ChatMessage::system("Respuesta"),
+        ];
+        let snapshot = SessionSnapshot {
+            saved_at: "2024-05-01 10:00:00".to_string(),
+            conversation_key: "2024-05-01 09:00:00".to_string(),
+            active_view: MainView::Preferences,
+            input: "borrador".to_string(),
+            chat: SharedConversation::from_messages(&messages),
+        };
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let restored: SessionSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.active_view, MainView::Preferences);
+        assert_eq!(restored.message_count(), 2);
+        assert!(!restored.is_empty());
+        let messages = restored.chat.into_messages(0);
+        assert_eq!(messages[0].text, "¿Qué tal?");
+    }
+
+    #[test]
+    fn crash_report_includes_panic_and_recent_logs() {
+        record_log("[Info] chat: mensaje enviado".to_string());
+        let report = crash_report("panicked at src/main.rs:1:1:\nfallo");
+        assert!(report.contains("fallo"));
+        assert!(report.contains("mensaje enviado"));
+    }
+}
@@ -0,0 +1,224 @@
+use std::fs;
+use std::io::{Read, Write};
+use std::num::NonZeroU32;
+use std::path::Path;
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::Local;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use ring::aead::{self, Aad, LessSafeKey, Nonce, UnboundKey, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use super::{ChatMessage, MessageQuote, RemoteProviderKind};
+
+/// Extensión sugerida para los archivos de conversación compartidos.
+pub const SHARE_EXTENSION: &str = "jmkshare";
+/// Longitud mínima exigida a la frase de paso al exportar.
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+const MAGIC: &[u8; 4] = b"JMKA";
+const FORMAT_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: u32 = 210_000;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+/// Conversación empaquetada para intercambiarla entre instancias de JungleMonkAI.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedConversation {
+    pub exported_at: String,
+    pub messages: Vec<SharedMessage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedMessage {
+    pub sender: String,
+    pub text: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(default)]
+    pub mention: Option<String>,
+    #[serde(default)]
+    pub reply_to: Option<SharedQuote>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedQuote {
+    pub message_index: usize,
+    pub sender: String,
+    pub text: String,
+    pub timestamp: String,
+}
+
+impl SharedConversation {
+    /// Empaqueta los mensajes definitivos; las respuestas pendientes no se exportan.
+    pub fn from_messages(messages: &[ChatMessage]) -> Self {
+        // Posición de cada mensaje exportado dentro del archivo, para reubicar las citas.
+        let mut positions = vec![None; messages.len()];
+        let mut next = 0;
+        for (index, message) in messages.iter().enumerate() {
+            if !message.is_pending() {
+                positions[index] = Some(next);
+                next += 1;
+            }
+        }
+
+        Self {
+            exported_at: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            messages: messages
+                .iter()
+                .filter(|message| !message.is_pending())
+                .map(|message| SharedMessage {
+                    sender: message.sender.clone(),
+                    text: message.text.clone(),
+                    timestamp: message.timestamp.clone(),
+                    provider: message
+                        .origin
+                        .map(|provider| provider.short_code().to_string()),
+                    mention: message.mention.clone(),
+                    reply_to: message.reply_to.as_ref().and_then(|quote| {
+                        Some(SharedQuote {
+                            message_index: positions.get(quote.message_index).copied()??,
+                            sender: quote.sender.clone(),
+                            text: quote.text.clone(),
+                            timestamp: quote.timestamp.clone(),
+                        })
+                    }),
+                })
+                .collect(),
+        }
+    }
+
+    /// Reconstruye los mensajes desplazando las citas `offset` posiciones, para que
+    /// sigan apuntando a su original al añadirse tras el historial existente.
+    pub fn into_messages(self, offset: usize) -> Vec<ChatMessage> {
+        self.messages
+            .into_iter()
+            .map(|shared| {
+                let mut message = ChatMessage::new(shared.sender, shared.text);
+                message.timestamp = shared.timestamp;
+                message.origin = shared
+                    .provider
+                    .as_deref()
+                    .and_then(RemoteProviderKind::from_short_code);
+                message.mention = shared.mention;
+                message.reply_to = shared.reply_to.map(|quote| MessageQuote {
+                    message_index: quote.message_index + offset,
+                    sender: quote.sender,
+                    text: quote.text,
+                    timestamp: quote.timestamp,
+                });
+                message
+            })
+            .collect()
+    }
+}
+
+/// Cifra la conversación con AES-256-GCM usando una clave derivada de la frase de paso
+/// con PBKDF2-HMAC-SHA256 y la escribe en `path`.
+pub fn export_conversation(
+    path: &Path,
+    conversation: &SharedConversation,
+    passphrase: &str,
+) -> Result<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        bail!(
+            "La frase de paso debe tener al menos {} caracteres",
+            MIN_PASSPHRASE_LEN
+        );
+    }
+
+    let json = serde_json::to_vec(conversation)?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&json)?;
+    let mut payload = encoder.finish()?;
+
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|_| rng.fill(&mut nonce))
+        .map_err(|_| anyhow!("No se pudo generar material aleatorio"))?;
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(FORMAT_VERSION);
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    derive_key(passphrase, &salt)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(&header),
+            &mut payload,
+        )
+        .map_err(|_| anyhow!("No se pudo cifrar la conversación"))?;
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    header.extend_from_slice(&payload);
+    fs::write(path, header).with_context(|| format!("No se pudo escribir {}", path.display()))
+}
+
+pub fn import_conversation(path: &Path, passphrase: &str) -> Result<SharedConversation> {
+    let mut data = fs::read(path).with_context(|| format!("No se pudo leer {}", path.display()))?;
+    if data.len() <= HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+        bail!(
+            "{} no es una conversación exportada por JungleMonkAI",
+            path.display()
+        );
+    }
+    if data[MAGIC.len()] != FORMAT_VERSION {
+        bail!("Versión de archivo no soportada: {}", data[MAGIC.len()]);
+    }
+
+    let salt_start = MAGIC.len() + 1;
+    let nonce_start = salt_start + SALT_LEN;
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&data[nonce_start..HEADER_LEN]);
+    let key = derive_key(passphrase, &data[salt_start..nonce_start])?;
+
+    let (header, payload) = data.split_at_mut(HEADER_LEN);
+    let compressed = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(&*header),
+            payload,
+        )
+        .map_err(|_| anyhow!("Frase de paso incorrecta o archivo dañado"))?;
+
+    let mut json = Vec::new();
+    GzDecoder::new(&compressed[..]).read_to_end(&mut json)?;
+    serde_json::from_slice(&json).context("El contenido de la conversación no es válido")
+}
+
+pub(super) fn derive_key(passphrase: &str, salt: &[u8]) -> Result<LessSafeKey> {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        NonZeroU32::new(PBKDF2_ITERATIONS).expect("iteraciones distintas de cero"),
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    let key = UnboundKey::new(&aead::AES_256_GCM, &key)
+        .map_err(|_| anyhow!("No se pudo preparar la clave de cifrado"))?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// Estado del diálogo para compartir conversaciones.
+#[derive(Default)]
+pub struct ConversationShareState {
+    pub show_modal: bool,
+    pub export_path: String,
+    pub import_path: String,
+    pub passphrase: String,
+    pub passphrase_confirm: String,
+    pub status: Option<String>,
+}
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::runtime;
+use crate::config::AppConfig;
+
+const SIZE_INDEX_FILE: &str = "size_index.json";
+/// Cada cuánto se vuelven a medir en segundo plano las carpetas de modelos.
+pub const SIZE_REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Tamaños de los archivos de una carpeta, medidos al descargarla o al refrescar el índice.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DirectorySize {
+    /// Bytes de cada archivo, indexados por su ruta relativa a la carpeta.
+    pub files: BTreeMap<String, u64>,
+    pub scanned_at: Option<DateTime<Utc>>,
+}
+
+impl DirectorySize {
+    /// Recorre la carpeta entera. Con modelos de decenas de GB puede tardar, así que solo
+    /// se invoca desde hilos en segundo plano.
+    pub fn scan(dir: &Path) -> Self {
+        fn visit(root: &Path, path: &Path, files: &mut BTreeMap<String, u64>) {
+            match fs::metadata(path) {
+                Ok(metadata) if metadata.is_file() => {
+                    let relative = path.strip_prefix(root).unwrap_or(path);
+                    files.insert(relative_key(relative), metadata.len());
+                }
+                Ok(metadata) if metadata.is_dir() => {
+                    if let Ok(entries) = fs::read_dir(path) {
+                        for entry in entries.flatten() {
+                            visit(root, &entry.path(), files);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut files = BTreeMap::new();
+        visit(dir, dir, &mut files);
+        Self {
+            files,
+            scanned_at: Some(Utc::now()),
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.files.values().sum()
+    }
+
+    /// Vuelve a medir solo los archivos indicados; los que ya no existen se retiran.
+    pub fn refresh_files(&mut self, dir: &Path, names: &[String]) {
+        for name in names {
+            match fs::metadata(dir.join(name)) {
+                Ok(metadata) if metadata.is_file() => {
+                    self.files
+                        .insert(relative_key(Path::new(name)), metadata.len());
+                }
+                _ => {
+                    self.files.remove(&relative_key(Path::new(name)));
+                }
+            }
+        }
+    }
+}
+
+/// Las rutas relativas se guardan siempre con `/` para que el índice sea portable.
+fn relative_key(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn dir_key(dir: &Path) -> String {
+    dir.display().to_string()
+}
+
+/// Índice persistente con el tamaño de las carpetas de modelos, para que la interfaz no
+/// tenga que recorrerlas. Se actualiza al descargar, al actualizar archivos sueltos y con
+/// un refresco periódico en segundo plano.
+#[derive(Default)]
+pub struct SizeIndex {
+    entries: BTreeMap<String, DirectorySize>,
+    refresh_rx: Option<Receiver<Vec<(String, DirectorySize)>>>,
+    last_refresh: Option<Instant>,
+}
+
+impl SizeIndex {
+    pub fn load() -> Self {
+        let entries = AppConfig::data_file(SIZE_INDEX_FILE)
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        Self {
+            entries,
+            ..Self::default()
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = AppConfig::data_file(SIZE_INDEX_FILE)?;
+        let json = serde_json::to_string(&self.entries)?;
+        fs::write(&path, json).with_context(|| format!("No se pudo guardar {}", path.display()))
+    }
+
+    pub fn size_of(&self, dir: &Path) -> Option<u64> {
+        self.entries.get(&dir_key(dir)).map(DirectorySize::total)
+    }
+
+    pub fn insert(&mut self, dir: &Path, size: DirectorySize) {
+        self.entries.insert(dir_key(dir), size);
+    }
+
+    pub fn remove(&mut self, dir: &Path) {
+        self.entries.remove(&dir_key(dir));
+    }
+
+    /// Traslada la entrada cuando la carpeta se mueve, p. ej. a la papelera.
+    pub fn rename(&mut self, from: &Path, to: &Path) {
+        if let Some(size) = self.entries.remove(&dir_key(from)) {
+            self.entries.insert(dir_key(to), size);
+        }
+    }
+
+    /// Actualiza los archivos indicados de una carpeta indexada y devuelve su nuevo total.
+    /// Devuelve `None` si la carpeta no estaba en el índice.
+    pub fn update_files(&mut self, dir: &Path, names: &[String]) -> Option<u64> {
+        let entry = self.entries.get_mut(&dir_key(dir))?;
+        entry.refresh_files(dir, names);
+        Some(entry.total())
+    }
+
+    pub fn is_refreshing(&self) -> bool {
+        self.refresh_rx.is_some()
+    }
+
+    pub fn refresh_due(&self) -> bool {
+        !self.is_refreshing()
+            && self
+                .last_refresh
+                .map(|last| last.elapsed() >= SIZE_REFRESH_INTERVAL)
+                .unwrap_or(true)
+    }
+
+    /// Mide `dirs` en un hilo aparte. Las carpetas que ya no existen salen del índice al
+    /// recoger el resultado.
+    pub fn request_refresh(&mut self, dirs: Vec<PathBuf>) {
+        if self.is_refreshing() {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        self.refresh_rx = Some(rx);
+        self.last_refresh = Some(Instant::now());
+        runtime::spawn_blocking(move || {
+            let sizes = dirs
+                .iter()
+                .map(|dir| (dir_key(dir), DirectorySize::scan(dir)))
+                .collect();
+            let _ = tx.send(sizes);
+        });
+    }
+
+    /// Incorpora el refresco terminado y devuelve los totales que cambiaron.
+    pub fn poll_refresh(&mut self) -> Option<BTreeMap<String, u64>> {
+        let rx = self.refresh_rx.as_ref()?;
+        let sizes = match rx.try_recv() {
+            Ok(sizes) => sizes,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                self.refresh_rx = None;
+                return None;
+            }
+        };
+        self.refresh_rx = None;
+
+        let mut changed = BTreeMap::new();
+        for (key, size) in sizes {
+            if size.files.is_empty() && !Path::new(&key).exists() {
+                self.entries.remove(&key);
+                continue;
+            }
+            let total = size.total();
+            if self.entries.get(&key).map(DirectorySize::total) != Some(total) {
+                changed.insert(key.clone(), total);
+            }
+            self.entries.insert(key, size);
+        }
+        Some(changed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_and_incremental_updates_track_file_sizes() {
+        let dir = std::env::temp_dir().join(format!("jmk-size-index-{}", std::process::id()));
+        fs::create_dir_all(dir.join("1_Pooling")).unwrap();
+        fs::write(dir.join("config.json"), vec![0u8; 10]).unwrap();
+        fs::write(dir.join("1_Pooling/config.json"), vec![0u8; 5]).unwrap();
+
+        let mut index = SizeIndex::default();
+        index.insert(&dir, DirectorySize::scan(&dir));
+        assert_eq!(index.size_of(&dir), Some(15));
+
+        fs::write(dir.join("model.safetensors"), vec![0u8; 100]).unwrap();
+        fs::remove_file(dir.join("1_Pooling/config.json")).unwrap();
+        let names = vec![
+            "model.safetensors".to_string(),
+            "1_Pooling/config.json".to_string(),
+        ];
+        assert_eq!(index.update_files(&dir, &names), Some(110));
+
+        let trash = dir.with_extension("trash");
+        index.rename(&dir, &trash);
+        assert_eq!(index.size_of(&dir), None);
+        assert_eq!(index.size_of(&trash), Some(110));
+        assert_eq!(index.update_files(&dir, &names), None);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::VecDeque;
+
+use chrono::Local;
+use serde_json::{Map, Value};
+
+use crate::config::AppConfig;
+
+/// Cambios que se conservan en el diario; los más antiguos se descartan.
+pub const MAX_ENTRIES: usize = 100;
+/// Caracteres de cada valor que se muestran en el diario.
+const PREVIEW_CHARS: usize = 80;
+/// Secciones que cambian al mover paneles y solo añadirían ruido.
+const IGNORED_SECTIONS: [&str; 1] = ["layout"];
+/// Fragmentos de las claves cuyos valores no se muestran.
+const SECRET_KEYS: [&str; 5] = [
+    "api_key",
+    "access_token",
+    "github_token",
+    "bot_token",
+    "password",
+];
+
+type Sections = Map<String, Value>;
+
+/// Qué parte del estado tocó un cambio, según la ruta del campo.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateChangeKind {
+    Routing,
+    Model,
+    Config,
+}
+
+impl StateChangeKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            StateChangeKind::Routing => "Enrutado",
+            StateChangeKind::Model => "Modelos",
+            StateChangeKind::Config => "Configuración",
+        }
+    }
+
+    fn of(path: &[String]) -> Self {
+        let field = path.get(1).map(String::as_str).unwrap_or_default();
+        match (path[0].as_str(), field) {
+            (_, "alias" | "chat_alias" | "default_model") => StateChangeKind::Routing,
+            ("jarvis", "active_model" | "installed_models" | "model_path") => {
+                StateChangeKind::Model
+            }
+            ("model_quick_actions", _) => StateChangeKind::Model,
+            _ => StateChangeKind::Config,
+        }
+    }
+}
+
+/// Valor de un campo antes y después de un cambio. `None` significa que no existía.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    /// Claves desde la raíz de la configuración hasta el campo.
+    pub path: Vec<String>,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub reverted: bool,
+}
+
+impl FieldChange {
+    pub fn path_label(&self) -> String {
+        self.path.join(".")
+    }
+
+    /// `antes → después`, con los secretos ocultos y los valores largos recortados.
+    pub fn summary(&self) -> String {
+        let secret = self.path.iter().any(|key| {
+            let key = key.to_lowercase();
+            SECRET_KEYS.iter().any(|fragment| key.contains(fragment))
+        });
+        let preview = |value: &Option<Value>| match value {
+            None => "—".to_string(),
+            Some(_) if secret => "••••".to_string(),
+            Some(value) => {
+                let text = value.to_string();
+                if text.chars().count() > PREVIEW_CHARS {
+                    let cut: String = text.chars().take(PREVIEW_CHARS).collect();
+                    format!("{}…", cut)
+                } else {
+                    text
+                }
+            }
+        };
+        format!("{} → {}", preview(&self.before), preview(&self.after))
+    }
+}
+
+/// Cambios guardados a la vez, con su origen.
+#[derive(Clone, Debug)]
+pub struct StateChange {
+    pub id: u64,
+    pub timestamp: String,
+    /// Quién lo provocó: la interfaz, un workflow en curso, `config.json` o una reversión.
+    pub origin: String,
+    pub kind: StateChangeKind,
+    pub fields: Vec<FieldChange>,
+}
+
+impl StateChange {
+    /// Una línea para la consola de depuración.
+    pub fn describe(&self) -> String {
+        let fields: Vec<String> = self
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.path_label(), field.summary()))
+            .collect();
+        format!(
+            "#{} {} · {}: {}",
+            self.id,
+            self.kind.label(),
+            self.origin,
+            fields.join("; ")
+        )
+    }
+}
+
+fn sections_of(config: &AppConfig) -> Option<Sections> {
+    match serde_json::to_value(config) {
+        Ok(Value::Object(mut sections)) => {
+            for name in IGNORED_SECTIONS {
+                sections.remove(name);
+            }
+            Some(sections)
+        }
+        _ => None,
+    }
+}
+
+/// Campos hoja que difieren entre `before` y `after`. Las listas se comparan enteras.
+fn diff(path: &mut Vec<String>, before: Option<&Value>, after: Option<&Value>) -> Vec<FieldChange> {
+    if before == after {
+        return Vec::new();
+    }
+    if let (Some(Value::Object(before)), Some(Value::Object(after))) = (before, after) {
+        let mut keys: Vec<&String> = before.keys().chain(after.keys()).collect();
+        keys.sort();
+        keys.dedup();
+        let mut changes = Vec::new();
+        for key in keys {
+            path.push(key.clone());
+            changes.extend(diff(path, before.get(key), after.get(key)));
+            path.pop();
+        }
+        return changes;
+    }
+    vec![FieldChange {
+        path: path.clone(),
+        before: before.cloned(),
+        after: after.cloned(),
+        reverted: false,
+    }]
+}
+
+fn lookup<'a>(root: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(root, |value, key| value.get(key))
+}
+
+/// Valor actual del campo `path` de la configuración.
+pub fn value_at(config: &AppConfig, path: &[String]) -> Option<Value> {
+    let root = serde_json::to_value(config).ok()?;
+    lookup(&root, path).cloned()
+}
+
+/// Pone `value` en `path`, creando los objetos intermedios, o quita el campo si es `None`.
+fn assign(root: &mut Value, path: &[String], value: Option<Value>) -> Result<(), String> {
+    let Some((last, parents)) = path.split_last() else {
+        return Err("Ruta vacía".to_string());
+    };
+    let mut current = root;
+    for key in parents {
+        let Value::Object(map) = current else {
+            return Err(format!("{} no es una sección", key));
+        };
+        current = map
+            .entry(key.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    let Value::Object(map) = current else {
+        return Err(format!("No se puede asignar {}", path.join(".")));
+    };
+    match value {
+        Some(value) => map.insert(last.clone(), value),
+        None => map.remove(last),
+    };
+    Ok(())
+}
+
+/// Diario de los cambios de la configuración que hace la aplicación: enrutado, modelos
+/// activos y preferencias, comparando cada guardado con el anterior.
+pub struct StateJournal {
+    baseline: Option<Sections>,
+    pub entries: VecDeque<StateChange>,
+    next_id: u64,
+    pub status: Option<String>,
+}
+
+impl StateJournal {
+    pub fn new(config: &AppConfig) -> Self {
+        Self {
+            baseline: sections_of(config),
+            entries: VecDeque::new(),
+            next_id: 1,
+            status: None,
+        }
+    }
+
+    /// Anota lo que cambió desde la última llamada, agrupado por tipo de cambio.
+    /// Devuelve los cambios nuevos.
+    pub fn record(&mut self, config: &AppConfig, origin: &str) -> Vec<StateChange> {
+        let Some(current) = sections_of(config) else {
+            return Vec::new();
+        };
+        let previous = self.baseline.replace(current.clone());
+        let Some(previous) = previous else {
+            return Vec::new();
+        };
+        let fields = diff(
+            &mut Vec::new(),
+            Some(&Value::Object(previous)),
+            Some(&Value::Object(current)),
+        );
+
+        let mut recorded: Vec<StateChange> = Vec::new();
+        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        for field in fields {
+            let kind = StateChangeKind::of(&field.path);
+            match recorded.iter_mut().find(|change| change.kind == kind) {
+                Some(change) => change.fields.push(field),
+                None => {
+                    recorded.push(StateChange {
+                        id: self.next_id,
+                        timestamp: timestamp.clone(),
+                        origin: origin.to_string(),
+                        kind,
+                        fields: vec![field],
+                    });
+                    self.next_id += 1;
+                }
+            }
+        }
+        self.entries.extend(recorded.iter().cloned());
+        while self.entries.len() > MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        recorded
+    }
+
+    /// Configuración con el campo `field` del cambio `id` devuelto a su valor anterior,
+    /// junto al cambio revertido. Se niega si el campo volvió a cambiar después, para no
+    /// deshacer cambios posteriores.
+    pub fn revert(
+        &self,
+        config: &AppConfig,
+        id: u64,
+        field: usize,
+    ) -> Result<(AppConfig, FieldChange), String> {
+        let change = self
+            .entries
+            .iter()
+            .find(|change| change.id == id)
+            .and_then(|change| change.fields.get(field))
+            .ok_or_else(|| "Ese cambio ya no está en el diario.".to_string())?;
+        if change.reverted {
+            return Err("Ese cambio ya se revirtió.".to_string());
+        }
+        let mut root = serde_json::to_value(config).map_err(|err| err.to_string())?;
+        if lookup(&root, &change.path) != change.after.as_ref() {
+            return Err(format!(
+                "{} cambió después; revierte antes los cambios posteriores.",
+                change.path_label()
+            ));
+        }
+        assign(&mut root, &change.path, change.before.clone())?;
+        let config =
+            serde_json::from_value(root).map_err(|err| format!("No se pudo revertir: {}", err))?;
+        Ok((config, change.clone()))
+    }
+
+    pub fn mark_reverted(&mut self, id: u64, field: usize) {
+        if let Some(change) = self
+            .entries
+            .iter_mut()
+            .find(|change| change.id == id)
+            .and_then(|change| change.fields.get_mut(field))
+        {
+            change.reverted = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_field_diffs_and_reverts_single_changes() {
+        let mut config = AppConfig::default();
+        let mut journal = StateJournal::new(&config);
+        config.openai.alias = "rapido".to_string();
+        config.jarvis.active_model = Some("huggingface::org/modelo".to_string());
+        config.speak_briefings = !config.speak_briefings;
+        config.layout.current.navigation_width += 40.0;
+
+        let recorded = journal.record(&config, "Workflow 'Release'");
+        let kinds: Vec<StateChangeKind> = recorded.iter().map(|change| change.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                StateChangeKind::Model,
+                StateChangeKind::Routing,
+                StateChangeKind::Config
+            ]
+        );
+        let routing = &recorded[1];
+        assert_eq!(routing.fields[0].path_label(), "openai.alias");
+        assert!(routing.describe().contains("Workflow 'Release'"));
+        assert!(journal.record(&config, "Interfaz").is_empty());
+
+        let (reverted, field) = journal.revert(&config, routing.id, 0).unwrap();
+        assert_eq!(field.path_label(), "openai.alias");
+        assert_eq!(reverted.openai.alias, AppConfig::default().openai.alias);
+        assert_eq!(reverted.jarvis.active_model, config.jarvis.active_model);
+
+        config.openai.alias = "otro".to_string();
+        assert!(journal.revert(&config, routing.id, 0).is_err());
+    }
+}
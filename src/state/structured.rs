@@ -0,0 +1,305 @@
+use serde_json::Value;
+
+/// Llamadas de reparación que se intentan como máximo cuando la respuesta no valida.
+pub const MAX_REPAIR_ATTEMPTS: u8 = 2;
+/// Errores de validación que se incluyen en la petición de reparación.
+const MAX_REPORTED_ERRORS: usize = 8;
+
+/// Esquema propuesto al activar el modo estructurado por primera vez.
+pub const EXAMPLE_SCHEMA: &str = r#"{
+  "type": "object",
+  "properties": {
+    "resumen": { "type": "string" },
+    "puntos": { "type": "array", "items": { "type": "string" } }
+  },
+  "required": ["resumen", "puntos"]
+}"#;
+
+/// Modo de salida estructurada del compositor: mientras está activo, cada mensaje
+/// enrutado a un proveedor lleva adjunto el esquema JSON.
+#[derive(Default)]
+pub struct StructuredOutputState {
+    pub enabled: bool,
+    pub schema_text: String,
+    pub show_editor: bool,
+    /// Motivo por el que el esquema escrito no se puede usar.
+    pub schema_error: Option<String>,
+    /// Reparación preparada para la próxima llamada al proveedor.
+    pub(crate) pending_repair: Option<StructuredRequest>,
+}
+
+impl StructuredOutputState {
+    /// Esquema vigente si el modo está activo y el texto es JSON válido.
+    pub fn active_schema(&self) -> Option<Value> {
+        if !self.enabled {
+            return None;
+        }
+        serde_json::from_str(&self.schema_text).ok()
+    }
+
+    /// Comprueba el esquema escrito y guarda el error para mostrarlo en el editor.
+    pub fn check_schema(&mut self) -> bool {
+        self.schema_error = match serde_json::from_str::<Value>(&self.schema_text) {
+            Ok(Value::Object(_)) => None,
+            Ok(_) => Some("El esquema debe ser un objeto JSON.".to_string()),
+            Err(err) => Some(format!("JSON no válido: {}", err)),
+        };
+        self.schema_error.is_none()
+    }
+
+    /// Instrucciones que se añadirían al próximo mensaje enrutado.
+    pub fn pending_instructions(&self) -> Option<String> {
+        let request = StructuredRequest {
+            schema: self.active_schema()?,
+            original_prompt: String::new(),
+            attempt: 0,
+        };
+        Some(request.instructions())
+    }
+}
+
+/// Esquema y prompt original de una petición estructurada en curso.
+#[derive(Clone, Debug)]
+pub struct StructuredRequest {
+    pub schema: Value,
+    pub original_prompt: String,
+    /// Llamadas de reparación ya realizadas.
+    pub attempt: u8,
+}
+
+impl StructuredRequest {
+    /// Solo se usa el modo JSON nativo cuando el esquema describe un objeto, que es lo
+    /// único que garantiza `response_format: json_object`.
+    pub fn wants_native_json(&self) -> bool {
+        matches!(
+            self.schema.get("type").and_then(Value::as_str),
+            None | Some("object")
+        )
+    }
+
+    /// Instrucciones que acompañan al prompt original.
+    pub fn instructions(&self) -> String {
+        format!(
+            "Responde exclusivamente con JSON que cumpla este JSON Schema, sin texto adicional ni bloques de código:\n{}",
+            pretty(&self.schema)
+        )
+    }
+
+    /// Prompt de la llamada de reparación con la respuesta anterior y sus errores.
+    pub fn repair_prompt(&self, response: &str, errors: &[String]) -> String {
+        let listed: Vec<String> = errors
+            .iter()
+            .take(MAX_REPORTED_ERRORS)
+            .map(|error| format!("- {}", error))
+            .collect();
+        format!(
+            "Tu respuesta anterior a la petición \"{}\" no cumple el esquema.\n\nRespuesta anterior:\n{}\n\nErrores:\n{}\n\n{}",
+            self.original_prompt.trim(),
+            response.trim(),
+            listed.join("\n"),
+            self.instructions()
+        )
+    }
+}
+
+/// Extrae el JSON de la respuesta (admite bloques ```json y texto alrededor) y lo valida.
+pub fn parse_and_validate(schema: &Value, response: &str) -> Result<Value, Vec<String>> {
+    let value = extract_json(response).map_err(|err| vec![err])?;
+    let errors = validate(schema, &value);
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(errors)
+    }
+}
+
+fn extract_json(response: &str) -> Result<Value, String> {
+    let trimmed = response.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Ok(value);
+    }
+
+    let fenced = trimmed
+        .split("```")
+        .nth(1)
+        .map(|block| block.trim_start_matches("json").trim());
+    if let Some(value) = fenced.and_then(|block| serde_json::from_str(block).ok()) {
+        return Ok(value);
+    }
+
+    let start = trimmed.find(['{', '[']);
+    let end = trimmed.rfind(['}', ']']);
+    if let (Some(start), Some(end)) = (start, end) {
+        if start < end {
+            if let Ok(value) = serde_json::from_str(&trimmed[start..=end]) {
+                return Ok(value);
+            }
+        }
+    }
+    Err("La respuesta no contiene JSON válido.".to_string())
+}
+
+/// Validación de un subconjunto de JSON Schema: `type`, `enum`, `const`, `properties`,
+/// `required`, `additionalProperties`, `items`, límites numéricos, de longitud y de
+/// elementos, y `anyOf`/`oneOf`. Devuelve los errores con la ruta JSON de cada uno.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(schema, value, "$", &mut errors);
+    errors
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(single) => vec![single.as_str()],
+            Value::Array(many) => many.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|kind| has_type(value, kind)) {
+            errors.push(format!(
+                "{}: se esperaba {} y llegó {}",
+                path,
+                types.join(" | "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            errors.push(format!("{}: valor fuera de los permitidos", path));
+        }
+    }
+    if let Some(constant) = schema.get("const") {
+        if constant != value {
+            errors.push(format!("{}: debe valer {}", path, constant));
+        }
+    }
+
+    for keyword in ["anyOf", "oneOf"] {
+        if let Some(Value::Array(options)) = schema.get(keyword) {
+            let matching = options
+                .iter()
+                .filter(|option| validate(option, value).is_empty())
+                .count();
+            let valid = if keyword == "oneOf" {
+                matching == 1
+            } else {
+                matching > 0
+            };
+            if !valid {
+                errors.push(format!("{}: no cumple {}", path, keyword));
+            }
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        errors.push(format!(
+                            "{}: falta la propiedad obligatoria '{}'",
+                            path, key
+                        ));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, child) in object {
+                let child_path = format!("{}.{}", path, key);
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(child_schema) => validate_at(child_schema, child, &child_path, errors),
+                    None => match schema.get("additionalProperties") {
+                        Some(Value::Bool(false)) => {
+                            errors.push(format!("{}: propiedad no permitida", child_path))
+                        }
+                        Some(extra @ Value::Object(_)) => {
+                            validate_at(extra, child, &child_path, errors)
+                        }
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_bound(schema, "minItems", items.len(), path, errors, |a, b| a >= b);
+            check_bound(schema, "maxItems", items.len(), path, errors, |a, b| a <= b);
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        Value::String(text) => {
+            let length = text.chars().count();
+            check_bound(schema, "minLength", length, path, errors, |a, b| a >= b);
+            check_bound(schema, "maxLength", length, path, errors, |a, b| a <= b);
+        }
+        Value::Number(number) => {
+            let number = number.as_f64().unwrap_or_default();
+            if let Some(minimum) = schema.get("minimum").and_then(Value::as_f64) {
+                if number < minimum {
+                    errors.push(format!("{}: debe ser como mínimo {}", path, minimum));
+                }
+            }
+            if let Some(maximum) = schema.get("maximum").and_then(Value::as_f64) {
+                if number > maximum {
+                    errors.push(format!("{}: debe ser como máximo {}", path, maximum));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_bound(
+    schema: &serde_json::Map<String, Value>,
+    keyword: &str,
+    actual: usize,
+    path: &str,
+    errors: &mut Vec<String>,
+    holds: fn(u64, u64) -> bool,
+) {
+    if let Some(limit) = schema.get(keyword).and_then(Value::as_u64) {
+        if !holds(actual as u64, limit) {
+            errors.push(format!(
+                "{}: {} = {} (tiene {})",
+                path, keyword, limit, actual
+            ));
+        }
+    }
+}
+
+fn has_type(value: &Value, kind: &str) -> bool {
+    match kind {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+fn pretty(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
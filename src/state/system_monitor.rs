@@ -0,0 +1,340 @@
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::Duration;
+
+use chrono::Local;
+use sysinfo::{Disks, System};
+
+use super::runtime;
+
+/// Intervalo entre dos muestras del monitor.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(2);
+/// Muestras que se conservan: cinco minutos con el intervalo por defecto.
+const HISTORY_LEN: usize = 150;
+/// Fracción del límite de memoria por debajo de la cual se reanuda la generación local,
+/// para no alternar entre pausa y reanudación en cada muestra.
+const RESUME_RATIO: f64 = 0.9;
+
+/// Uso de la GPU según `nvidia-smi`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuSample {
+    pub utilization_percent: f32,
+    pub memory_used_bytes: u64,
+    pub memory_total_bytes: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DiskSample {
+    pub mount_point: PathBuf,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+impl DiskSample {
+    pub fn used_percent(&self) -> f32 {
+        percent(
+            self.total_bytes.saturating_sub(self.available_bytes),
+            self.total_bytes,
+        )
+    }
+}
+
+/// Medición del sistema tomada por el hilo del monitor.
+#[derive(Clone, Debug, Default)]
+pub struct ResourceSample {
+    pub timestamp: String,
+    pub cpu_percent: f32,
+    pub memory_used_bytes: u64,
+    pub memory_total_bytes: u64,
+    /// Memoria residente del propio proceso; es la que se compara con el límite configurado.
+    pub process_memory_bytes: u64,
+    /// `None` si no hay una GPU que se pueda consultar.
+    pub gpu: Option<GpuSample>,
+    pub disks: Vec<DiskSample>,
+}
+
+impl ResourceSample {
+    pub fn memory_percent(&self) -> f32 {
+        percent(self.memory_used_bytes, self.memory_total_bytes)
+    }
+
+    /// Disco que contiene `path`: el de punto de montaje más largo que lo prefija.
+    pub fn disk_for(&self, path: &Path) -> Option<&DiskSample> {
+        self.disks
+            .iter()
+            .filter(|disk| path.starts_with(&disk.mount_point))
+            .max_by_key(|disk| disk.mount_point.as_os_str().len())
+    }
+}
+
+/// Nivel de carga con el que se colorean los indicadores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadLevel {
+    Normal,
+    High,
+    Critical,
+}
+
+impl LoadLevel {
+    pub fn from_percent(percent: f32) -> Self {
+        if percent >= 90.0 {
+            LoadLevel::Critical
+        } else if percent >= 75.0 {
+            LoadLevel::High
+        } else {
+            LoadLevel::Normal
+        }
+    }
+}
+
+fn percent(used: u64, total: u64) -> f32 {
+    if total == 0 {
+        return 0.0;
+    }
+    (used as f64 / total as f64 * 100.0) as f32
+}
+
+/// Muestreo periódico de CPU, memoria, GPU y discos en un hilo aparte.
+#[derive(Default)]
+pub struct SystemMonitor {
+    pub history: VecDeque<ResourceSample>,
+    /// La generación local está en pausa porque el proceso supera el límite de memoria.
+    pub generation_paused: bool,
+    sample_rx: Option<Receiver<ResourceSample>>,
+}
+
+impl SystemMonitor {
+    pub fn is_running(&self) -> bool {
+        self.sample_rx.is_some()
+    }
+
+    /// Arranca el hilo de muestreo. El hilo termina solo cuando se descarta el monitor.
+    pub fn start(&mut self) {
+        if self.is_running() {
+            return;
+        }
+        let (tx, rx) = runtime::ui_channel();
+        self.sample_rx = Some(rx);
+        let _ = runtime::spawn_service("system-monitor", move || {
+            let mut system = System::new();
+            let mut disks = Disks::new_with_refreshed_list();
+            let pid = sysinfo::get_current_pid().ok();
+            // Si `nvidia-smi` no responde la primera vez no se vuelve a intentar.
+            let mut gpu_available = true;
+            loop {
+                system.refresh_cpu();
+                system.refresh_memory();
+                if let Some(pid) = pid {
+                    system.refresh_process(pid);
+                }
+                disks.refresh();
+
+                let gpu = if gpu_available { query_gpu() } else { None };
+                gpu_available = gpu.is_some();
+
+                let sample = ResourceSample {
+                    timestamp: Local::now().format("%H:%M:%S").to_string(),
+                    cpu_percent: system.global_cpu_info().cpu_usage(),
+                    memory_used_bytes: system.used_memory(),
+                    memory_total_bytes: system.total_memory(),
+                    process_memory_bytes: pid
+                        .and_then(|pid| system.process(pid))
+                        .map(|process| process.memory())
+                        .unwrap_or(0),
+                    gpu,
+                    disks: disks
+                        .iter()
+                        .map(|disk| DiskSample {
+                            mount_point: disk.mount_point().to_path_buf(),
+                            total_bytes: disk.total_space(),
+                            available_bytes: disk.available_space(),
+                        })
+                        .collect(),
+                };
+                if tx.send_progress(sample).is_err() {
+                    break;
+                }
+                std::thread::sleep(SAMPLE_INTERVAL);
+            }
+        });
+    }
+
+    /// Recoge las muestras nuevas. Devuelve `true` si llegó alguna.
+    pub fn poll(&mut self) -> bool {
+        let Some(rx) = self.sample_rx.take() else {
+            return false;
+        };
+        let mut received = false;
+        let connected = loop {
+            match rx.try_recv() {
+                Ok(sample) => {
+                    self.record(sample);
+                    received = true;
+                }
+                Err(TryRecvError::Empty) => break true,
+                Err(TryRecvError::Disconnected) => break false,
+            }
+        };
+        if connected {
+            self.sample_rx = Some(rx);
+        }
+        received
+    }
+
+    pub fn record(&mut self, sample: ResourceSample) {
+        self.history.push_back(sample);
+        while self.history.len() > HISTORY_LEN {
+            self.history.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> Option<&ResourceSample> {
+        self.history.back()
+    }
+
+    /// Pausa la generación local si la memoria del proceso supera `limit_bytes` y la
+    /// reanuda cuando baja del 90 % del límite. Devuelve el nuevo estado si cambió.
+    pub fn enforce_memory_limit(&mut self, limit_bytes: u64) -> Option<bool> {
+        let used = self.latest()?.process_memory_bytes;
+        let paused = if self.generation_paused {
+            used as f64 >= limit_bytes as f64 * RESUME_RATIO
+        } else {
+            limit_bytes > 0 && used > limit_bytes
+        };
+        if paused == self.generation_paused {
+            return None;
+        }
+        self.generation_paused = paused;
+        Some(paused)
+    }
+}
+
+fn query_gpu() -> Option<GpuSample> {
+    let output = Command::new("nvidia-smi")
+        .args([
+            "--query-gpu=utilization.gpu,memory.used,memory.total",
+            "--format=csv,noheader,nounits",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_nvidia_smi(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Interpreta la salida CSV de `nvidia-smi` (memoria en MiB). Con varias GPU se suman las
+/// memorias y se toma la utilización más alta.
+fn parse_nvidia_smi(output: &str) -> Option<GpuSample> {
+    const MIB: u64 = 1024 * 1024;
+    let mut sample: Option<GpuSample> = None;
+    for line in output.lines().filter(|line| !line.trim().is_empty()) {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [utilization, used, total] = fields.as_slice() else {
+            continue;
+        };
+        let (Ok(utilization), Ok(used), Ok(total)) = (
+            utilization.parse::<f32>(),
+            used.parse::<u64>(),
+            total.parse::<u64>(),
+        ) else {
+            continue;
+        };
+        let current = sample.get_or_insert(GpuSample {
+            utilization_percent: 0.0,
+            memory_used_bytes: 0,
+            memory_total_bytes: 0,
+        });
+        current.utilization_percent = current.utilization_percent.max(utilization);
+        current.memory_used_bytes += used * MIB;
+        current.memory_total_bytes += total * MIB;
+    }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nvidia_smi_output_for_several_gpus() {
+        let sample = parse_nvidia_smi("37, 1024, 8192\n80, 512, 8192\n").unwrap();
+        assert_eq!(sample.utilization_percent, 80.0);
+        assert_eq!(sample.memory_used_bytes, 1536 * 1024 * 1024);
+        assert_eq!(sample.memory_total_bytes, 16384 * 1024 * 1024);
+        assert!(parse_nvidia_smi("No devices were found").is_none());
+    }
+
+    #[test]
+    fn memory_limit_pauses_and_resumes_with_hysteresis() {
+        let mut monitor = SystemMonitor::default();
+        let sample = |process_memory_bytes| ResourceSample {
+            process_memory_bytes,
+            ..ResourceSample::default()
+        };
+
+        monitor.record(sample(900));
+        assert_eq!(monitor.enforce_memory_limit(1000), None);
+        monitor.record(sample(1100));
+        assert_eq!(monitor.enforce_memory_limit(1000), Some(true));
+        monitor.record(sample(950));
+        assert_eq!(monitor.enforce_memory_limit(1000), None);
+        assert!(monitor.generation_paused);
+        monitor.record(sample(850));
+        assert_eq!(monitor.enforce_memory_limit(1000), Some(false));
+    }
+
+    #[test]
+    fn history_keeps_the_latest_samples_and_classifies_load() {
+        let mut monitor = SystemMonitor::default();
+        let (tx, rx) = std::sync::mpsc::channel();
+        monitor.sample_rx = Some(rx);
+        for index in 0..HISTORY_LEN + 5 {
+            tx.send(ResourceSample {
+                process_memory_bytes: index as u64,
+                ..ResourceSample::default()
+            })
+            .unwrap();
+        }
+        assert!(monitor.poll());
+        assert!(monitor.is_running());
+        assert_eq!(monitor.history.len(), HISTORY_LEN);
+        assert_eq!(monitor.history.front().unwrap().process_memory_bytes, 5);
+        assert_eq!(
+            monitor.latest().unwrap().process_memory_bytes,
+            HISTORY_LEN as u64 + 4
+        );
+        drop(tx);
+        assert!(!monitor.poll());
+        assert!(!monitor.is_running());
+
+        assert_eq!(LoadLevel::from_percent(74.9), LoadLevel::Normal);
+        assert_eq!(LoadLevel::from_percent(75.0), LoadLevel::High);
+        assert_eq!(LoadLevel::from_percent(90.0), LoadLevel::Critical);
+        let sample = ResourceSample {
+            memory_used_bytes: 3,
+            memory_total_bytes: 4,
+            ..ResourceSample::default()
+        };
+        assert_eq!(sample.memory_percent(), 75.0);
+        assert_eq!(ResourceSample::default().memory_percent(), 0.0);
+    }
+
+    #[test]
+    fn disk_for_picks_the_deepest_mount_point() {
+        let disk = |mount: &str| DiskSample {
+            mount_point: PathBuf::from(mount),
+            total_bytes: 100,
+            available_bytes: 25,
+        };
+        let sample = ResourceSample {
+            disks: vec![disk("/"), disk("/home")],
+            ..ResourceSample::default()
+        };
+        let found = sample.disk_for(Path::new("/home/user/models")).unwrap();
+        assert_eq!(found.mount_point, PathBuf::from("/home"));
+        assert_eq!(found.used_percent(), 75.0);
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+/// Dependencias entre tareas del planificador por nombre: tarea → tareas que deben
+/// terminar bien antes de que se lance.
+pub type TaskDependencies = BTreeMap<String, Vec<String>>;
+
+pub fn prerequisites<'a>(dependencies: &'a TaskDependencies, task: &str) -> &'a [String] {
+    dependencies
+        .get(task)
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Tareas que dependen directamente de `task`.
+pub fn dependents(dependencies: &TaskDependencies, task: &str) -> Vec<String> {
+    dependencies
+        .iter()
+        .filter(|(_, previous)| previous.iter().any(|name| name == task))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Todas las tareas que dependen de `task` directa o indirectamente, de la más cercana
+/// a la más lejana.
+pub fn downstream(dependencies: &TaskDependencies, task: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut seen = BTreeSet::from([task.to_string()]);
+    let mut queue = VecDeque::from([task.to_string()]);
+    while let Some(current) = queue.pop_front() {
+        for dependent in dependents(dependencies, &current) {
+            if seen.insert(dependent.clone()) {
+                found.push(dependent.clone());
+                queue.push_back(dependent);
+            }
+        }
+    }
+    found
+}
+
+fn upstream(dependencies: &TaskDependencies, task: &str) -> BTreeSet<String> {
+    let mut found = BTreeSet::new();
+    let mut queue = VecDeque::from([task.to_string()]);
+    while let Some(current) = queue.pop_front() {
+        for previous in prerequisites(dependencies, &current) {
+            if found.insert(previous.clone()) {
+                queue.push_back(previous.clone());
+            }
+        }
+    }
+    found
+}
+
+/// Comprueba que `task` pueda depender de `prerequisite` sin crear un ciclo.
+pub fn check_dependency(
+    dependencies: &TaskDependencies,
+    task: &str,
+    prerequisite: &str,
+) -> Result<(), String> {
+    if task == prerequisite {
+        return Err("Una tarea no puede depender de sí misma.".to_string());
+    }
+    if prerequisites(dependencies, task)
+        .iter()
+        .any(|name| name == prerequisite)
+    {
+        return Err(format!("'{}' ya depende de '{}'.", task, prerequisite));
+    }
+    if upstream(dependencies, prerequisite).contains(task) {
+        return Err(format!(
+            "'{}' ya depende de '{}'; la cadena formaría un ciclo.",
+            prerequisite, task
+        ));
+    }
+    Ok(())
+}
+
+/// Copia de `dependencies` sin las aristas que cierran un ciclo, p. ej. tras editar
+/// la configuración a mano.
+pub fn without_cycles(dependencies: &TaskDependencies) -> (TaskDependencies, Vec<String>) {
+    let mut clean = TaskDependencies::new();
+    let mut dropped = Vec::new();
+    for (task, previous) in dependencies {
+        for prerequisite in previous {
+            match check_dependency(&clean, task, prerequisite) {
+                Ok(()) => clean
+                    .entry(task.clone())
+                    .or_default()
+                    .push(prerequisite.clone()),
+                Err(reason) => dropped.push(reason),
+            }
+        }
+    }
+    (clean, dropped)
+}
+
+/// Cadena en la que participa `task`: sus previas, ella misma y las que dependen de
+/// ella, agrupadas por nivel desde las tareas raíz.
+pub fn chain_levels(dependencies: &TaskDependencies, task: &str) -> Vec<Vec<String>> {
+    let mut members = upstream(dependencies, task);
+    members.insert(task.to_string());
+    members.extend(downstream(dependencies, task));
+
+    let mut levels: BTreeMap<String, usize> = BTreeMap::new();
+    let mut grouped: Vec<Vec<String>> = Vec::new();
+    for member in &members {
+        let level = level_of(dependencies, &members, &mut levels, member);
+        if grouped.len() <= level {
+            grouped.resize_with(level + 1, Vec::new);
+        }
+        grouped[level].push(member.clone());
+    }
+    grouped
+}
+
+/// Nivel de `task` dentro de la cadena: la longitud del camino más largo desde una raíz.
+fn level_of(
+    dependencies: &TaskDependencies,
+    members: &BTreeSet<String>,
+    levels: &mut BTreeMap<String, usize>,
+    task: &str,
+) -> usize {
+    if let Some(level) = levels.get(task) {
+        return *level;
+    }
+    let level = prerequisites(dependencies, task)
+        .iter()
+        .filter(|previous| members.contains(*previous))
+        .map(|previous| level_of(dependencies, members, levels, previous) + 1)
+        .max()
+        .unwrap_or(0);
+    levels.insert(task.to_string(), level);
+    level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> TaskDependencies {
+        TaskDependencies::from([
+            ("Informe".to_string(), vec!["Sincronizar".to_string()]),
+            (
+                "Publicar".to_string(),
+                vec!["Informe".to_string(), "Reindexar".to_string()],
+            ),
+        ])
+    }
+
+    #[test]
+    fn rejects_cycles_and_groups_the_chain_by_level() {
+        let dependencies = chain();
+        assert!(check_dependency(&dependencies, "Sincronizar", "Publicar").is_err());
+        assert!(check_dependency(&dependencies, "Informe", "Informe").is_err());
+        assert!(check_dependency(&dependencies, "Reindexar", "Sincronizar").is_ok());
+
+        assert_eq!(
+            downstream(&dependencies, "Sincronizar"),
+            vec!["Informe".to_string(), "Publicar".to_string()]
+        );
+        assert_eq!(
+            chain_levels(&dependencies, "Informe"),
+            vec![
+                vec!["Sincronizar".to_string()],
+                vec!["Informe".to_string()],
+                vec!["Publicar".to_string()],
+            ]
+        );
+
+        let mut looped = chain();
+        looped.insert("Sincronizar".to_string(), vec!["Publicar".to_string()]);
+        let (clean, dropped) = without_cycles(&looped);
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(clean.values().flatten().count(), 3);
+    }
+}
@@ -0,0 +1,438 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use reqwest::blocking::Client;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use super::job_queue::{JobPriority, JobQueueState, TELEGRAM_LANE};
+use super::runtime::{self, UiSender};
+use crate::api::network;
+
+const API_BASE: &str = "https://api.telegram.org";
+/// Segundos que Telegram retiene cada consulta si no llegan mensajes.
+const LONG_POLL_SECS: u64 = 25;
+/// Espera antes de volver a consultar tras un fallo.
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+/// Caracteres que admite un mensaje de Telegram.
+const MAX_MESSAGE_CHARS: usize = 4096;
+/// Clave fija del hilo donde se vuelca la conversación con el bot.
+pub const TELEGRAM_THREAD_KEY: &str = "telegram";
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    ok: bool,
+    #[serde(default)]
+    description: Option<String>,
+    result: Option<T>,
+}
+
+#[derive(Deserialize)]
+struct Update {
+    update_id: i64,
+    #[serde(default)]
+    message: Option<Message>,
+}
+
+#[derive(Deserialize)]
+struct Message {
+    chat: Chat,
+    #[serde(default)]
+    from: Option<User>,
+    #[serde(default)]
+    text: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+#[derive(Deserialize)]
+struct User {
+    #[serde(default)]
+    first_name: String,
+    #[serde(default)]
+    username: Option<String>,
+}
+
+impl User {
+    fn display_name(&self) -> String {
+        match &self.username {
+            Some(username) => format!("@{}", username),
+            None => self.first_name.clone(),
+        }
+    }
+}
+
+/// Mensaje de texto recibido por el bot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TelegramMessage {
+    pub chat_id: i64,
+    pub sender: String,
+    pub text: String,
+}
+
+pub enum TelegramEvent {
+    /// El token es válido; lleva el nombre del bot.
+    Connected(String),
+    Message(TelegramMessage),
+    /// Fallo pasajero; el puente sigue consultando.
+    Error(String),
+    /// El puente no puede seguir, normalmente por un token no válido.
+    Stopped(String),
+}
+
+/// Identificadores de chat separados por comas o espacios. Los de grupos son negativos.
+pub fn parse_chat_ids(text: &str) -> Result<Vec<i64>, String> {
+    let mut ids = Vec::new();
+    for part in text
+        .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+    {
+        let id = part
+            .parse::<i64>()
+            .map_err(|_| format!("'{}' no es un identificador de chat.", part))?;
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+    }
+    Ok(ids)
+}
+
+/// Trozos que caben en un mensaje de Telegram, cortados por líneas cuando es posible.
+pub fn split_message(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in text.trim().lines() {
+        let mut line = line.to_string();
+        loop {
+            let room = MAX_MESSAGE_CHARS - current.chars().count();
+            let needed = line.chars().count() + usize::from(!current.is_empty());
+            if needed <= room {
+                if !current.is_empty() {
+                    current.push('\n');
+                }
+                current.push_str(&line);
+                break;
+            }
+            if !current.is_empty() {
+                chunks.push(std::mem::take(&mut current));
+                continue;
+            }
+            // La línea sola no cabe: se corta por caracteres.
+            let head: String = line.chars().take(MAX_MESSAGE_CHARS).collect();
+            line = line.chars().skip(MAX_MESSAGE_CHARS).collect();
+            chunks.push(head);
+            if line.is_empty() {
+                break;
+            }
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Mensajes de texto de una respuesta de `getUpdates` y el `offset` de la siguiente
+/// consulta, que confirma los ya recibidos.
+fn collect_messages(updates: Vec<Update>) -> (Option<i64>, Vec<TelegramMessage>) {
+    let next_offset = updates.iter().map(|update| update.update_id + 1).max();
+    let messages = updates
+        .into_iter()
+        .filter_map(|update| update.message)
+        .filter_map(|message| {
+            let text = message.text?.trim().to_string();
+            (!text.is_empty()).then(|| TelegramMessage {
+                chat_id: message.chat.id,
+                sender: message
+                    .from
+                    .map(|user| user.display_name())
+                    .unwrap_or_default(),
+                text,
+            })
+        })
+        .collect();
+    (next_offset, messages)
+}
+
+fn call<T: DeserializeOwned>(
+    client: &Client,
+    token: &str,
+    method: &str,
+    body: &Value,
+    timeout: Duration,
+) -> Result<T> {
+    call_at(API_BASE, client, token, method, body, timeout)
+}
+
+/// La URL lleva el token, así que se quita de los errores de reqwest antes de que
+/// acaben en el registro de actividad o en el flujo de eventos.
+fn call_at<T: DeserializeOwned>(
+    base: &str,
+    client: &Client,
+    token: &str,
+    method: &str,
+    body: &Value,
+    timeout: Duration,
+) -> Result<T> {
+    let response: ApiResponse<T> = client
+        .post(format!("{}/bot{}/{}", base, token.trim(), method))
+        .timeout(timeout)
+        .json(body)
+        .send()
+        .map_err(reqwest::Error::without_url)
+        .context("No se pudo conectar con Telegram")?
+        .json()
+        .map_err(reqwest::Error::without_url)
+        .context("Telegram devolvió una respuesta no válida")?;
+    match response.result {
+        Some(result) if response.ok => Ok(result),
+        _ => bail!(
+            "Telegram rechazó {}: {}",
+            method,
+            response.description.unwrap_or_default()
+        ),
+    }
+}
+
+fn client() -> Result<Client> {
+    network::client_builder(Duration::from_secs(20))?
+        .user_agent("JungleMonkAI/0.1")
+        .build()
+        .context("No se pudo crear el cliente HTTP para Telegram")
+}
+
+/// Envía `text` al chat en este hilo, en varios mensajes si es largo.
+pub fn send_message(token: &str, chat_id: i64, text: &str) -> Result<()> {
+    let client = client()?;
+    for chunk in split_message(text) {
+        call::<Value>(
+            &client,
+            token,
+            "sendMessage",
+            &json!({ "chat_id": chat_id, "text": chunk }),
+            Duration::from_secs(20),
+        )?;
+    }
+    Ok(())
+}
+
+/// Consulta el bot con `getUpdates` hasta que se pide parar. Los fallos de red se
+/// reintentan; si el token no sirve, el puente se detiene.
+fn run(token: String, tx: UiSender<TelegramEvent>, stop: Arc<AtomicBool>) {
+    let setup = client().and_then(|client| {
+        let me: User = call(
+            &client,
+            &token,
+            "getMe",
+            &json!({}),
+            Duration::from_secs(20),
+        )?;
+        Ok((client, me))
+    });
+    let client = match setup {
+        Ok((client, me)) => {
+            let _ = tx.send(TelegramEvent::Connected(me.display_name()));
+            client
+        }
+        Err(err) => {
+            let _ = tx.send(TelegramEvent::Stopped(format!("{:#}", err)));
+            return;
+        }
+    };
+
+    let mut offset = 0;
+    while !stop.load(Ordering::Relaxed) {
+        let body = json!({
+            "offset": offset,
+            "timeout": LONG_POLL_SECS,
+            "allowed_updates": ["message"],
+        });
+        let timeout = Duration::from_secs(LONG_POLL_SECS + 10);
+        match call::<Vec<Update>>(&client, &token, "getUpdates", &body, timeout) {
+            Ok(updates) => {
+                let (next_offset, messages) = collect_messages(updates);
+                if let Some(next_offset) = next_offset {
+                    offset = next_offset;
+                }
+                for message in messages {
+                    if tx.send(TelegramEvent::Message(message)).is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(err) => {
+                if tx.send(TelegramEvent::Error(format!("{:#}", err))).is_err() {
+                    return;
+                }
+                thread::sleep(RETRY_DELAY);
+            }
+        }
+    }
+}
+
+/// Mensaje de Telegram ya enviado al chat cuya respuesta se está esperando.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TelegramRelay {
+    pub chat_id: i64,
+    pub thread_id: u32,
+    /// Posición del mensaje en el hilo; las respuestas son los mensajes posteriores.
+    pub message_index: usize,
+}
+
+/// Puente con el bot, mensajes a la espera y respuesta en preparación.
+#[derive(Default)]
+pub struct TelegramState {
+    pub status: Option<String>,
+    /// Nombre del bot una vez validado el token.
+    pub bot_name: Option<String>,
+    /// Mensajes recibidos que esperan a que la conversación quede libre.
+    pub inbox: VecDeque<TelegramMessage>,
+    pub relay: Option<TelegramRelay>,
+    /// Texto del campo de chats permitidos en las preferencias.
+    pub chat_ids_draft: String,
+    rx: Option<Receiver<TelegramEvent>>,
+    tx: Option<UiSender<TelegramEvent>>,
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl TelegramState {
+    pub fn is_running(&self) -> bool {
+        self.rx.is_some()
+    }
+
+    pub fn start(&mut self, token: String) {
+        self.stop();
+        let (tx, rx) = runtime::ui_channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        {
+            let tx = tx.clone();
+            let stop = Arc::clone(&stop);
+            let _ = runtime::spawn_service("telegram", move || run(token, tx, stop));
+        }
+        self.rx = Some(rx);
+        self.tx = Some(tx);
+        self.stop = Some(stop);
+        self.status = Some("Conectando con Telegram…".to_string());
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        self.rx = None;
+        self.tx = None;
+        self.bot_name = None;
+        self.status = None;
+        self.inbox.clear();
+        self.relay = None;
+    }
+
+    /// Encola la respuesta en la cola de llamadas salientes; si falla, el error llega con
+    /// los demás eventos.
+    pub fn send(&self, jobs: &mut JobQueueState, token: String, chat_id: i64, text: String) {
+        let Some(tx) = self.tx.clone() else {
+            return;
+        };
+        jobs.submit(
+            TELEGRAM_LANE,
+            JobPriority::Background,
+            "Respuesta en Telegram",
+            move || {
+                if let Err(err) = send_message(&token, chat_id, &text) {
+                    let _ = tx.send(TelegramEvent::Error(format!(
+                        "No se pudo responder en Telegram: {:#}",
+                        err
+                    )));
+                }
+            },
+        );
+    }
+
+    pub fn poll(&mut self) -> Vec<TelegramEvent> {
+        let Some(rx) = self.rx.as_ref() else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        loop {
+            match rx.try_recv() {
+                Ok(event) => events.push(event),
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => return events,
+            }
+        }
+    }
+}
+
+impl Drop for TelegramState {
+    fn drop(&mut self) {
+        if let Some(stop) = &self.stop {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_updates_chat_ids_and_splits_long_replies() {
+        let updates: ApiResponse<Vec<Update>> = serde_json::from_str(
+            r#"{"ok":true,"result":[
+                {"update_id":7,"message":{"message_id":1,"chat":{"id":42,"type":"private"},
+                 "from":{"id":42,"first_name":"Ana","username":"ana"},"text":" hola "}},
+                {"update_id":8,"message":{"message_id":2,"chat":{"id":42},"sticker":{}}}
+            ]}"#,
+        )
+        .unwrap();
+        let (offset, messages) = collect_messages(updates.result.unwrap());
+        assert_eq!(offset, Some(9));
+        assert_eq!(
+            messages,
+            vec![TelegramMessage {
+                chat_id: 42,
+                sender: "@ana".to_string(),
+                text: "hola".to_string(),
+            }]
+        );
+
+        assert_eq!(parse_chat_ids("42, -1001234 42"), Ok(vec![42, -1001234]));
+        assert!(parse_chat_ids("42, ana").is_err());
+
+        let line = "x".repeat(3000);
+        let chunks = split_message(&format!("{}\n{}", line, line));
+        assert_eq!(chunks, vec![line.clone(), line]);
+        let long = "y".repeat(MAX_MESSAGE_CHARS + 5);
+        let chunks = split_message(&long);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[1], "yyyyy");
+    }
+
+    #[test]
+    fn failed_calls_do_not_leak_the_token() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let token = "123456:secreto-del-bot";
+        let err = call_at::<Value>(
+            &base,
+            &client().unwrap(),
+            token,
+            "getMe",
+            &json!({}),
+            Duration::from_secs(2),
+        )
+        .unwrap_err();
+        let text = format!("{:#}", err);
+        assert!(text.starts_with("No se pudo conectar con Telegram"));
+        assert!(!text.contains("secreto-del-bot"), "{}", text);
+    }
+}
@@ -0,0 +1,159 @@
+use chrono::Local;
+
+use super::ChatMessage;
+
+/// Punto de la conversación del que salió una bifurcación.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThreadOrigin {
+    pub thread_id: u32,
+    pub message_index: usize,
+}
+
+/// Hilo de conversación. El hilo activo vive en `ChatState::messages`; el resto guarda
+/// aquí su historial hasta que se vuelve a abrir.
+#[derive(Clone, Debug)]
+pub struct ConversationThread {
+    pub id: u32,
+    pub title: String,
+    /// Identificador con el que se asocian los marcadores del hilo.
+    pub key: String,
+    pub forked_from: Option<ThreadOrigin>,
+    pub created_at: String,
+    pub(crate) messages: Vec<ChatMessage>,
+}
+
+/// Copia con nombre de un hilo que se puede restaurar más tarde.
+#[derive(Clone, Debug)]
+pub struct ConversationCheckpoint {
+    pub id: u32,
+    pub name: String,
+    pub thread_id: u32,
+    pub created_at: String,
+    pub messages: Vec<ChatMessage>,
+}
+
+/// Hilos abiertos en la sesión y puntos de control creados sobre ellos.
+pub struct ThreadState {
+    pub threads: Vec<ConversationThread>,
+    pub active: u32,
+    pub checkpoints: Vec<ConversationCheckpoint>,
+    /// Nombre en edición para el próximo punto de control.
+    pub checkpoint_name: String,
+    pub status: Option<String>,
+    next_id: u32,
+}
+
+impl ThreadState {
+    /// Crea el estado con el hilo principal, que comparte la clave de marcadores de la sesión.
+    pub fn new(main_key: String) -> Self {
+        Self {
+            threads: vec![ConversationThread {
+                id: 1,
+                title: "Principal".to_string(),
+                key: main_key,
+                forked_from: None,
+                created_at: now(),
+                messages: Vec::new(),
+            }],
+            active: 1,
+            checkpoints: Vec::new(),
+            checkpoint_name: String::new(),
+            status: None,
+            next_id: 2,
+        }
+    }
+
+    pub fn active_thread(&self) -> Option<&ConversationThread> {
+        self.thread(self.active)
+    }
+
+    pub fn thread(&self, id: u32) -> Option<&ConversationThread> {
+        self.threads.iter().find(|thread| thread.id == id)
+    }
+
+    pub(crate) fn thread_mut(&mut self, id: u32) -> Option<&mut ConversationThread> {
+        self.threads.iter_mut().find(|thread| thread.id == id)
+    }
+
+    /// Añade un hilo con los mensajes dados y devuelve su identificador.
+    pub(crate) fn add_thread(
+        &mut self,
+        title: String,
+        forked_from: Option<ThreadOrigin>,
+        messages: Vec<ChatMessage>,
+    ) -> u32 {
+        let id = self.take_id();
+        self.threads.push(ConversationThread {
+            id,
+            title,
+            key: format!("{}#{}", now(), id),
+            forked_from,
+            created_at: now(),
+            messages,
+        });
+        id
+    }
+
+    /// Devuelve el hilo con clave fija `key`, creándolo vacío si aún no existe.
+    pub(crate) fn ensure_thread(&mut self, key: &str, title: &str) -> u32 {
+        if let Some(thread) = self.threads.iter().find(|thread| thread.key == key) {
+            return thread.id;
+        }
+        let id = self.take_id();
+        self.threads.push(ConversationThread {
+            id,
+            title: title.to_string(),
+            key: key.to_string(),
+            forked_from: None,
+            created_at: now(),
+            messages: Vec::new(),
+        });
+        id
+    }
+
+    pub(crate) fn add_checkpoint(&mut self, name: String, messages: Vec<ChatMessage>) -> u32 {
+        let id = self.take_id();
+        self.checkpoints.push(ConversationCheckpoint {
+            id,
+            name,
+            thread_id: self.active,
+            created_at: now(),
+            messages,
+        });
+        id
+    }
+
+    pub fn checkpoints_for(&self, thread_id: u32) -> impl Iterator<Item = &ConversationCheckpoint> {
+        self.checkpoints
+            .iter()
+            .filter(move |checkpoint| checkpoint.thread_id == thread_id)
+    }
+
+    fn take_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+}
+
+/// Copia los mensajes hasta `end` incluido, sin las respuestas que aún no han llegado.
+pub(crate) fn copy_until(messages: &[ChatMessage], end: usize) -> Vec<ChatMessage> {
+    messages
+        .iter()
+        .take(end + 1)
+        .map(|message| {
+            if message.is_pending() {
+                ChatMessage::system(format!(
+                    "Respuesta de {} pendiente en el hilo original; no se copió.",
+                    message.sender
+                ))
+            } else {
+                message.clone()
+            }
+        })
+        .collect()
+}
+
+fn now() -> String {
+    Local::now().format("%Y-%m-%d %H:%M:%S").to_string()
+}
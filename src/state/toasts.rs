@@ -0,0 +1,85 @@
+use std::time::{Duration, Instant};
+
+use super::LogStatus;
+
+/// Tiempo que un aviso permanece visible.
+const TOAST_LIFETIME: Duration = Duration::from_secs(8);
+/// Los avisos con acciones duran más para dar tiempo a elegir una.
+const ACTION_TOAST_LIFETIME: Duration = Duration::from_secs(20);
+/// Avisos simultáneos como máximo; los más antiguos se descartan.
+const MAX_TOASTS: usize = 4;
+
+/// Acción de un botón del aviso, que resuelve `AppState::run_toast_action`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ToastAction {
+    /// Índice de la acción del contenido capturado del portapapeles.
+    Clipboard(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct Toast {
+    pub status: LogStatus,
+    pub message: String,
+    pub created: Instant,
+    /// Botones del aviso: etiqueta y acción.
+    pub actions: Vec<(String, ToastAction)>,
+}
+
+/// Avisos emergentes que se muestran sobre la interfaz durante unos segundos.
+#[derive(Default)]
+pub struct ToastState {
+    pub items: Vec<Toast>,
+}
+
+impl ToastState {
+    pub fn push(&mut self, status: LogStatus, message: impl Into<String>) {
+        self.push_with_actions(status, message, Vec::new());
+    }
+
+    pub fn push_with_actions(
+        &mut self,
+        status: LogStatus,
+        message: impl Into<String>,
+        actions: Vec<(String, ToastAction)>,
+    ) {
+        self.items.push(Toast {
+            status,
+            message: message.into(),
+            created: Instant::now(),
+            actions,
+        });
+        if self.items.len() > MAX_TOASTS {
+            let overflow = self.items.len() - MAX_TOASTS;
+            self.items.drain(0..overflow);
+        }
+    }
+
+    /// Elimina los avisos caducados y devuelve si queda alguno visible.
+    pub fn prune(&mut self) -> bool {
+        self.items.retain(|toast| {
+            let lifetime = if toast.actions.is_empty() {
+                TOAST_LIFETIME
+            } else {
+                ACTION_TOAST_LIFETIME
+            };
+            toast.created.elapsed() < lifetime
+        });
+        !self.items.is_empty()
+    }
+
+    pub fn dismiss(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.items.remove(index);
+        }
+    }
+
+    /// Quita los avisos que ofrecen acciones del portapapeles, ya superados por otro.
+    pub fn dismiss_clipboard(&mut self) {
+        self.items.retain(|toast| {
+            !toast
+                .actions
+                .iter()
+                .any(|(_, action)| matches!(action, ToastAction::Clipboard(_)))
+        });
+    }
+}
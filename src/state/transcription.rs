@@ -0,0 +1,230 @@
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use anyhow::{bail, Context, Result};
+
+use super::job_queue::{JobPriority, JobQueueState, TRANSCRIPTION_LANE};
+use super::RemoteProviderKind;
+use crate::config::{TranscriptionBackend, VoiceInputConfig};
+
+/// Formatos que aceptan tanto Whisper como el endpoint de OpenAI.
+pub const AUDIO_EXTENSIONS: &[&str] = &[
+    "flac", "m4a", "mp3", "mp4", "mpeg", "mpga", "ogg", "wav", "webm",
+];
+
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.as_str()))
+}
+
+/// Transcripción terminada en segundo plano.
+pub struct TranscriptionResult {
+    pub path: PathBuf,
+    pub backend: TranscriptionBackend,
+    pub text: Result<String, String>,
+}
+
+/// Transcripción de notas de voz y audios adjuntos con el backend configurado.
+#[derive(Default)]
+pub struct TranscriptionState {
+    /// Ruta escrita en el panel de entrada de voz.
+    pub audio_path: String,
+    pub status: Option<String>,
+    pending: Option<Receiver<TranscriptionResult>>,
+}
+
+impl TranscriptionState {
+    pub fn is_running(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Encola la transcripción de `path` en la cola de llamadas salientes. Solo hay una en
+    /// curso a la vez.
+    pub fn start(
+        &mut self,
+        jobs: &mut JobQueueState,
+        config: VoiceInputConfig,
+        openai_key: Option<String>,
+        path: PathBuf,
+    ) -> Result<(), String> {
+        if self.is_running() {
+            return Err("Ya hay una transcripción en curso.".to_string());
+        }
+        if !path.is_file() {
+            return Err(format!("No se encontró el archivo {}.", path.display()));
+        }
+        if !is_audio_file(&path) {
+            return Err(format!(
+                "Formato de audio no admitido. Usa uno de: {}.",
+                AUDIO_EXTENSIONS.join(", ")
+            ));
+        }
+        if config.backend == TranscriptionBackend::OpenAi
+            && openai_key
+                .as_deref()
+                .is_none_or(|key| key.trim().is_empty())
+        {
+            return Err(
+                "Configura una API key de OpenAI para transcribir con su servicio.".to_string(),
+            );
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.pending = Some(rx);
+        let lane = match config.backend {
+            TranscriptionBackend::OpenAi => RemoteProviderKind::OpenAi.short_code(),
+            TranscriptionBackend::LocalWhisper => TRANSCRIPTION_LANE,
+        };
+        let label = match path.file_name() {
+            Some(name) => format!("Transcripción de {}", name.to_string_lossy()),
+            None => "Transcripción".to_string(),
+        };
+        jobs.submit(lane, JobPriority::Interactive, label, move || {
+            let text = transcribe(&config, openai_key.as_deref(), &path)
+                .map_err(|err| format!("{:#}", err));
+            let _ = tx.send(TranscriptionResult {
+                path,
+                backend: config.backend,
+                text,
+            });
+        });
+        Ok(())
+    }
+
+    pub fn poll(&mut self) -> Option<TranscriptionResult> {
+        let rx = self.pending.as_ref()?;
+        match rx.try_recv() {
+            Ok(result) => {
+                self.pending = None;
+                Some(result)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = None;
+                None
+            }
+        }
+    }
+}
+
+/// Transcribe `path` en este hilo con el backend configurado.
+pub fn transcribe(
+    config: &VoiceInputConfig,
+    openai_key: Option<&str>,
+    path: &Path,
+) -> Result<String> {
+    let language = Some(config.language.trim()).filter(|code| !code.is_empty());
+    match config.backend {
+        TranscriptionBackend::OpenAi => {
+            let Some(key) = openai_key.map(str::trim).filter(|key| !key.is_empty()) else {
+                bail!("Falta la API key de OpenAI");
+            };
+            crate::api::openai::transcribe_audio(key, &config.openai_model, path, language)
+        }
+        TranscriptionBackend::LocalWhisper => transcribe_locally(config, path, language),
+    }
+}
+
+/// Ejecuta la CLI de Whisper y lee el `.txt` que deja en un directorio temporal.
+fn transcribe_locally(
+    config: &VoiceInputConfig,
+    path: &Path,
+    language: Option<&str>,
+) -> Result<String> {
+    let output_dir = std::env::temp_dir().join(format!("jmk-whisper-{}", std::process::id()));
+    fs::create_dir_all(&output_dir)
+        .with_context(|| format!("No se pudo crear {}", output_dir.display()))?;
+
+    let output = Command::new(config.whisper_command.trim())
+        .args(whisper_args(config, path, &output_dir, language))
+        .output()
+        .with_context(|| {
+            format!(
+                "No se pudo ejecutar '{}'. Instala Whisper o elige OpenAI como backend",
+                config.whisper_command.trim()
+            )
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "Whisper terminó con {}: {}",
+            output.status,
+            stderr.lines().last().unwrap_or_default()
+        );
+    }
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let transcript = output_dir.join(format!("{}.txt", stem));
+    let text = fs::read_to_string(&transcript)
+        .with_context(|| format!("Whisper no generó {}", transcript.display()))?;
+    let _ = fs::remove_file(&transcript);
+    Ok(text.trim().to_string())
+}
+
+fn whisper_args(
+    config: &VoiceInputConfig,
+    path: &Path,
+    output_dir: &Path,
+    language: Option<&str>,
+) -> Vec<OsString> {
+    let mut args: Vec<OsString> = vec![
+        path.into(),
+        "--model".into(),
+        config.whisper_model.trim().into(),
+        "--output_format".into(),
+        "txt".into(),
+        "--output_dir".into(),
+        output_dir.into(),
+    ];
+    if let Some(language) = language {
+        args.push("--language".into());
+        args.push(language.into());
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whisper_arguments_include_language_only_when_set() {
+        let config = VoiceInputConfig::default();
+        let args = whisper_args(&config, Path::new("nota.ogg"), Path::new("/tmp/out"), None);
+        assert_eq!(args[0], OsString::from("nota.ogg"));
+        assert!(!args.contains(&OsString::from("--language")));
+
+        let args = whisper_args(
+            &config,
+            Path::new("nota.ogg"),
+            Path::new("/tmp/out"),
+            Some("es"),
+        );
+        assert_eq!(
+            args[args.len() - 2..],
+            ["--language", "es"].map(OsString::from)
+        );
+    }
+
+    #[test]
+    fn rejects_missing_files_and_unsupported_formats() {
+        assert!(is_audio_file(Path::new("voz.M4A")));
+        assert!(!is_audio_file(Path::new("notas.txt")));
+
+        let mut state = TranscriptionState::default();
+        let error = state
+            .start(
+                &mut JobQueueState::default(),
+                VoiceInputConfig::default(),
+                None,
+                PathBuf::from("/no/existe.wav"),
+            )
+            .unwrap_err();
+        assert!(error.contains("No se encontró"));
+        assert!(!state.is_running());
+    }
+}
@@ -0,0 +1,760 @@
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{AppConfig, StorageEncryptionConfig, StorageKeySource};
+
+use super::{audit, benchmark, bookmarks, composer, conversations, quick_tests, session, share};
+
+/// Ficheros con el historial, los borradores, las instantáneas de conversación, los
+/// resultados de pruebas y benchmarks y los datos de la auditoría que se cifran.
+pub const ENCRYPTED_FILES: [&str; 8] = [
+    session::SNAPSHOT_FILE,
+    composer::COMPOSER_FILE,
+    bookmarks::BOOKMARKS_FILE,
+    conversations::CONVERSATIONS_FILE,
+    quick_tests::HISTORY_FILE,
+    benchmark::LAST_RUN_FILE,
+    audit::AUDIT_HEADS_FILE,
+    audit::AUDIT_KEY_FILE,
+];
+/// Directorios de ficheros de solo anexado que se cifran línea a línea.
+pub const ENCRYPTED_LINE_DIRS: [&str; 1] = [audit::AUDIT_DIR];
+/// Prefijo de las líneas cifradas, seguidas del contenido sellado en base64.
+const LINE_PREFIX: &str = "jmkv:";
+/// Sal y verificador de la clave; nunca guarda la clave ni el secreto.
+const VAULT_FILE: &str = "vault.json";
+/// Cabecera de la clave nueva mientras dura un cambio de clave sin confirmar.
+const STAGED_VAULT_FILE: &str = "vault.json.new";
+/// Sufijo de las copias recifradas que esperan a que se confirme la clave nueva.
+const REKEY_SUFFIX: &str = ".rekey";
+const MAGIC: &[u8; 4] = b"JMKV";
+const FORMAT_VERSION: u8 = 1;
+const HEADER_LEN: usize = MAGIC.len() + 1 + NONCE_LEN;
+const SALT_LEN: usize = 16;
+/// Texto que se cifra en `vault.json` para comprobar la clave al desbloquear.
+const CHECK_TEXT: &[u8] = b"JungleMonkAI";
+const KEYCHAIN_SERVICE: &str = "JungleMonkAI";
+const KEYCHAIN_ACCOUNT: &str = "almacenamiento";
+
+enum VaultStatus {
+    Disabled,
+    /// El cifrado está activo pero aún no hay clave: no se lee ni se escribe nada cifrado.
+    Locked,
+    Unlocked(Box<LessSafeKey>),
+}
+
+/// Clave de la sesión, compartida por las funciones de carga y guardado de cada módulo.
+static VAULT: Mutex<VaultStatus> = Mutex::new(VaultStatus::Disabled);
+
+#[derive(Serialize, Deserialize)]
+struct VaultHeader {
+    salt: String,
+    check: String,
+}
+
+fn set_status(status: VaultStatus) {
+    *VAULT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = status;
+}
+
+pub fn is_locked() -> bool {
+    matches!(
+        *VAULT
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        VaultStatus::Locked
+    )
+}
+
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.len() > HEADER_LEN && data.starts_with(MAGIC)
+}
+
+fn seal(key: &LessSafeKey, plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce)
+        .map_err(|_| anyhow!("No se pudo generar material aleatorio"))?;
+    let mut sealed = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    sealed.extend_from_slice(MAGIC);
+    sealed.push(FORMAT_VERSION);
+    sealed.extend_from_slice(&nonce);
+    let mut payload = plaintext.to_vec();
+    key.seal_in_place_append_tag(
+        Nonce::assume_unique_for_key(nonce),
+        Aad::from(&sealed[..HEADER_LEN]),
+        &mut payload,
+    )
+    .map_err(|_| anyhow!("No se pudo cifrar el contenido"))?;
+    sealed.extend_from_slice(&payload);
+    Ok(sealed)
+}
+
+fn open(key: &LessSafeKey, data: &[u8]) -> Result<Vec<u8>> {
+    if !is_sealed(data) {
+        bail!("El contenido no está cifrado por JungleMonkAI");
+    }
+    if data[MAGIC.len()] != FORMAT_VERSION {
+        bail!("Versión de cifrado no soportada: {}", data[MAGIC.len()]);
+    }
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&data[MAGIC.len() + 1..HEADER_LEN]);
+    let mut payload = data[HEADER_LEN..].to_vec();
+    let plaintext = key
+        .open_in_place(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(&data[..HEADER_LEN]),
+            &mut payload,
+        )
+        .map_err(|_| anyhow!("Clave incorrecta o archivo dañado"))?;
+    Ok(plaintext.to_vec())
+}
+
+/// Lee un fichero de datos, descifrándolo si hace falta. Los que siguen en claro se leen
+/// tal cual, así que los archivos anteriores al cifrado se migran al volver a guardarse.
+pub fn read_to_string(path: &Path) -> Result<String> {
+    let status = VAULT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let key = match &*status {
+        VaultStatus::Unlocked(key) => Some(key.as_ref()),
+        VaultStatus::Disabled | VaultStatus::Locked => None,
+    };
+    read_with(path, key)
+}
+
+fn read_with(path: &Path, key: Option<&LessSafeKey>) -> Result<String> {
+    let data = fs::read(path).with_context(|| format!("No se pudo leer {}", path.display()))?;
+    if !is_sealed(&data) {
+        return String::from_utf8(data)
+            .with_context(|| format!("{} no es texto válido", path.display()));
+    }
+    let Some(key) = key else {
+        bail!(
+            "{} está cifrado y el almacenamiento sigue bloqueado",
+            path.display()
+        );
+    };
+    let plaintext =
+        open(key, &data).with_context(|| format!("No se pudo descifrar {}", path.display()))?;
+    String::from_utf8(plaintext).with_context(|| format!("{} no es texto válido", path.display()))
+}
+
+/// Escribe un fichero de datos, cifrado si el almacenamiento cifrado está activo. Mientras
+/// está bloqueado no se escribe nada, para no pisar los archivos cifrados.
+pub fn write(path: &Path, contents: &str) -> Result<()> {
+    let data = match &*VAULT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+    {
+        VaultStatus::Disabled => contents.as_bytes().to_vec(),
+        VaultStatus::Locked => bail!("El almacenamiento cifrado está bloqueado"),
+        VaultStatus::Unlocked(key) => seal(key, contents.as_bytes())?,
+    };
+    write_atomic(path, &data)
+}
+
+/// Cifra una línea para anexarla a un fichero de solo anexado; en claro si el cifrado
+/// está desactivado. Mientras está bloqueado no se puede anexar nada.
+pub fn seal_line(line: &str) -> Result<String> {
+    match &*VAULT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+    {
+        VaultStatus::Disabled => Ok(line.to_string()),
+        VaultStatus::Locked => bail!("El almacenamiento cifrado está bloqueado"),
+        VaultStatus::Unlocked(key) => seal_line_with(line, Some(key.as_ref())),
+    }
+}
+
+/// Descifra una línea escrita con `seal_line`; las que siguen en claro se devuelven tal cual.
+pub fn open_line(line: &str) -> Result<String> {
+    let status = VAULT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let key = match &*status {
+        VaultStatus::Unlocked(key) => Some(key.as_ref()),
+        VaultStatus::Disabled | VaultStatus::Locked => None,
+    };
+    open_line_with(line, key)
+}
+
+fn seal_line_with(line: &str, key: Option<&LessSafeKey>) -> Result<String> {
+    match key {
+        Some(key) => Ok(format!(
+            "{}{}",
+            LINE_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(seal(key, line.as_bytes())?)
+        )),
+        None => Ok(line.to_string()),
+    }
+}
+
+fn open_line_with(line: &str, key: Option<&LessSafeKey>) -> Result<String> {
+    let Some(encoded) = line.strip_prefix(LINE_PREFIX) else {
+        return Ok(line.to_string());
+    };
+    let Some(key) = key else {
+        bail!("La línea está cifrada y el almacenamiento sigue bloqueado");
+    };
+    let sealed = base64::engine::general_purpose::STANDARD
+        .decode(encoded.trim())
+        .context("Línea cifrada ilegible")?;
+    String::from_utf8(open(key, &sealed)?).context("La línea descifrada no es texto válido")
+}
+
+/// Escribe en un temporal sincronizado con el disco y lo renombra, para que un fallo a
+/// mitad no deje el fichero truncado ni mezclado entre dos claves. En Unix el fichero
+/// solo lo puede leer el usuario, también cuando se recifra.
+fn write_atomic(path: &Path, data: &[u8]) -> Result<()> {
+    let temporary = with_suffix(path, ".vault-tmp");
+    // Un temporal que quedó de un corte conservaría sus permisos anteriores.
+    remove_if_exists(&temporary)?;
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(&temporary)
+        .with_context(|| format!("No se pudo guardar {}", temporary.display()))?;
+    file.write_all(data)
+        .and_then(|()| file.sync_all())
+        .with_context(|| format!("No se pudo guardar {}", temporary.display()))?;
+    fs::rename(&temporary, path).with_context(|| format!("No se pudo guardar {}", path.display()))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn remove_if_exists(path: &Path) -> Result<()> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != ErrorKind::NotFound => {
+            Err(err).with_context(|| format!("No se pudo borrar {}", path.display()))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn read_header(path: &Path) -> Result<VaultHeader> {
+    let data =
+        fs::read_to_string(path).with_context(|| format!("No se pudo leer {}", path.display()))?;
+    serde_json::from_str(&data).with_context(|| format!("{} no es válido", path.display()))
+}
+
+/// Deriva la clave con la sal de `vault.json` y la comprueba con su verificador.
+pub fn unlock(secret: &str) -> Result<()> {
+    let key = unlock_key(&AppConfig::data_file(VAULT_FILE)?, secret)?;
+    set_status(VaultStatus::Unlocked(Box::new(key)));
+    Ok(())
+}
+
+fn unlock_key(vault_path: &Path, secret: &str) -> Result<LessSafeKey> {
+    let header = read_header(vault_path)?;
+    let engine = base64::engine::general_purpose::STANDARD;
+    let salt = engine.decode(&header.salt).context("Sal ilegible")?;
+    let check = engine
+        .decode(&header.check)
+        .context("Verificador ilegible")?;
+    let key = share::derive_key(secret, &salt)?;
+    if !open(&key, &check).is_ok_and(|text| text == CHECK_TEXT) {
+        bail!("Frase de paso o secreto incorrecto");
+    }
+    Ok(key)
+}
+
+/// Ficheros que migran al activar o desactivar el cifrado.
+struct VaultFiles {
+    header: PathBuf,
+    /// Cabecera de la clave nueva durante un cambio de clave.
+    staged_header: PathBuf,
+    /// Ficheros que se cifran enteros.
+    whole: Vec<PathBuf>,
+    /// Ficheros de solo anexado que se cifran línea a línea.
+    lines: Vec<PathBuf>,
+}
+
+fn vault_paths() -> Result<VaultFiles> {
+    let whole = ENCRYPTED_FILES
+        .iter()
+        .map(|name| AppConfig::data_file(name))
+        .collect::<Result<Vec<_>>>()?;
+    let mut lines = Vec::new();
+    for name in ENCRYPTED_LINE_DIRS {
+        if let Ok(entries) = fs::read_dir(AppConfig::data_file(name)?) {
+            lines.extend(entries.flatten().map(|entry| entry.path()).filter(|path| {
+                let name = path.to_string_lossy();
+                path.is_file() && !name.ends_with(REKEY_SUFFIX) && !name.ends_with(".vault-tmp")
+            }));
+        }
+    }
+    Ok(VaultFiles {
+        header: AppConfig::data_file(VAULT_FILE)?,
+        staged_header: AppConfig::data_file(STAGED_VAULT_FILE)?,
+        whole,
+        lines,
+    })
+}
+
+/// Crea una clave nueva a partir de `secret`, cifra los ficheros que estaban en claro y
+/// devuelve cuántos se migraron.
+pub fn enable(secret: &str) -> Result<usize> {
+    let files = vault_paths()?;
+    let mut status = VAULT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    // Se leen con la clave anterior por si ya estaban cifrados y se reescriben con la nueva.
+    let previous = match &*status {
+        VaultStatus::Disabled => None,
+        VaultStatus::Locked => bail!("Desbloquea el almacenamiento antes de cambiar la clave"),
+        VaultStatus::Unlocked(key) => Some(key.as_ref()),
+    };
+    let (key, migrated) = enable_files(&files, secret, previous)?;
+    *status = VaultStatus::Unlocked(Box::new(key));
+    Ok(migrated)
+}
+
+fn enable_files(
+    files: &VaultFiles,
+    secret: &str,
+    previous: Option<&LessSafeKey>,
+) -> Result<(LessSafeKey, usize)> {
+    recover_rekey(files)?;
+    let contents = read_all(&files.whole, previous)?;
+    let lines = read_all_lines(&files.lines, previous)?;
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .map_err(|_| anyhow!("No se pudo generar material aleatorio"))?;
+    let key = share::derive_key(secret, &salt)?;
+    let engine = base64::engine::general_purpose::STANDARD;
+    let header = VaultHeader {
+        salt: engine.encode(salt),
+        check: engine.encode(seal(&key, CHECK_TEXT)?),
+    };
+    // La cabecera nueva queda aparte y los ficheros recifrados se escriben junto a los
+    // originales; renombrar la cabecera confirma la clave y solo entonces las copias
+    // sustituyen a los originales. Un corte en cualquier punto lo resuelve
+    // `recover_rekey` sin mezclar ficheros de dos claves.
+    write_atomic(
+        &files.staged_header,
+        serde_json::to_string_pretty(&header)?.as_bytes(),
+    )?;
+    write_all(&staged(&contents), Some(&key))?;
+    write_all_lines(&staged(&lines), Some(&key))?;
+    fs::rename(&files.staged_header, &files.header)
+        .with_context(|| format!("No se pudo guardar {}", files.header.display()))?;
+    recover_rekey(files)?;
+    Ok((key, contents.len() + lines.len()))
+}
+
+fn staged<T: Clone>(contents: &[(PathBuf, T)]) -> Vec<(PathBuf, T)> {
+    contents
+        .iter()
+        .map(|(path, content)| (with_suffix(path, REKEY_SUFFIX), content.clone()))
+        .collect()
+}
+
+/// Termina o deshace un cambio de clave interrumpido. Si la cabecera nueva sigue aparte
+/// la clave no llegó a confirmarse y las copias recifradas se descartan; si ya no está,
+/// la clave vigente es la nueva y las copias que queden sustituyen a los originales.
+fn recover_rekey(files: &VaultFiles) -> Result<()> {
+    let committed = !files.staged_header.exists();
+    for path in files.whole.iter().chain(files.lines.iter()) {
+        let copy = with_suffix(path, REKEY_SUFFIX);
+        if !copy.exists() {
+            continue;
+        }
+        if committed {
+            fs::rename(&copy, path)
+                .with_context(|| format!("No se pudo guardar {}", path.display()))?;
+        } else {
+            remove_if_exists(&copy)?;
+        }
+    }
+    remove_if_exists(&files.staged_header)
+}
+
+/// Descifra los ficheros, los deja en claro y olvida la clave. Necesita el almacenamiento
+/// desbloqueado.
+pub fn disable() -> Result<usize> {
+    let files = vault_paths()?;
+    let mut status = VAULT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let key = match &*status {
+        VaultStatus::Disabled => None,
+        VaultStatus::Locked => {
+            bail!("Desbloquea el almacenamiento antes de desactivar el cifrado")
+        }
+        VaultStatus::Unlocked(key) => Some(key.as_ref()),
+    };
+    let migrated = disable_files(&files, key)?;
+    *status = VaultStatus::Disabled;
+    Ok(migrated)
+}
+
+fn disable_files(files: &VaultFiles, key: Option<&LessSafeKey>) -> Result<usize> {
+    recover_rekey(files)?;
+    let contents = read_all(&files.whole, key)?;
+    let lines = read_all_lines(&files.lines, key)?;
+    write_all(&contents, None)?;
+    write_all_lines(&lines, None)?;
+    // `vault.json` se borra al final: hasta entonces los que sigan cifrados se pueden abrir.
+    remove_if_exists(&files.header)?;
+    Ok(contents.len() + lines.len())
+}
+
+fn read_all(files: &[PathBuf], key: Option<&LessSafeKey>) -> Result<Vec<(PathBuf, String)>> {
+    let mut contents = Vec::new();
+    for path in files {
+        if path.exists() {
+            contents.push((path.clone(), read_with(path, key)?));
+        }
+    }
+    Ok(contents)
+}
+
+fn read_all_lines(
+    files: &[PathBuf],
+    key: Option<&LessSafeKey>,
+) -> Result<Vec<(PathBuf, Vec<String>)>> {
+    let mut contents = Vec::new();
+    for path in files {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("No se pudo leer {}", path.display()))?;
+        let lines = text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                open_line_with(line, key)
+                    .with_context(|| format!("No se pudo descifrar {}", path.display()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        contents.push((path.clone(), lines));
+    }
+    Ok(contents)
+}
+
+fn write_all_lines(contents: &[(PathBuf, Vec<String>)], key: Option<&LessSafeKey>) -> Result<()> {
+    for (path, lines) in contents {
+        let mut text = String::new();
+        for line in lines {
+            text.push_str(&seal_line_with(line, key)?);
+            text.push('\n');
+        }
+        write_atomic(path, text.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn write_all(contents: &[(PathBuf, String)], key: Option<&LessSafeKey>) -> Result<()> {
+    for (path, text) in contents {
+        let data = match key {
+            Some(key) => seal(key, text.as_bytes())?,
+            None => text.as_bytes().to_vec(),
+        };
+        write_atomic(path, &data)?;
+    }
+    Ok(())
+}
+
+/// Aplica la preferencia al arrancar: con el llavero se desbloquea en el acto; con frase
+/// de paso queda bloqueado hasta que el usuario la escriba.
+pub fn initialize(config: &StorageEncryptionConfig) -> Result<()> {
+    if !config.enabled {
+        set_status(VaultStatus::Disabled);
+        return Ok(());
+    }
+    set_status(VaultStatus::Locked);
+    recover_rekey(&vault_paths()?)?;
+    match config.key_source {
+        StorageKeySource::Passphrase => Ok(()),
+        StorageKeySource::Keychain => match keychain_secret()? {
+            Some(secret) => unlock(&secret),
+            None => bail!("El llavero del sistema no tiene el secreto del almacenamiento"),
+        },
+    }
+}
+
+/// Secreto aleatorio para guardar en el llavero del sistema.
+pub fn generate_secret() -> Result<String> {
+    let mut secret = [0u8; 32];
+    SystemRandom::new()
+        .fill(&mut secret)
+        .map_err(|_| anyhow!("No se pudo generar material aleatorio"))?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(secret))
+}
+
+/// Lee el secreto del llavero con `security` en macOS o `secret-tool` en Linux.
+pub fn keychain_secret() -> Result<Option<String>> {
+    keychain_entry(KEYCHAIN_ACCOUNT)
+}
+
+pub fn store_keychain_secret(secret: &str) -> Result<()> {
+    store_keychain_entry(KEYCHAIN_ACCOUNT, secret)
+}
+
+/// Lee la entrada `account` del servicio de JungleMonkAI en el llavero del sistema.
+pub fn keychain_entry(account: &str) -> Result<Option<String>> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args([
+            "find-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ]);
+        command
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("secret-tool");
+        command.args(["lookup", "service", KEYCHAIN_SERVICE, "account", account]);
+        command
+    } else {
+        bail!("No hay un llavero compatible en este sistema; usa una frase de paso");
+    };
+    let output = command
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .with_context(|| {
+            format!(
+                "No se pudo consultar el llavero con {}",
+                command.get_program().to_string_lossy()
+            )
+        })?;
+    let secret = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Ok(Some(secret).filter(|secret| output.status.success() && !secret.is_empty()))
+}
+
+pub fn store_keychain_entry(account: &str, secret: &str) -> Result<()> {
+    // El secreto se pasa por la entrada estándar, nunca en la línea de órdenes, donde lo
+    // vería cualquier proceso del sistema.
+    let (mut command, input) = if cfg!(target_os = "macos") {
+        // Con `-w` al final y sin valor, security pide la contraseña y su confirmación.
+        let mut command = Command::new("security");
+        command.args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ]);
+        (command, format!("{0}\n{0}\n", secret))
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("secret-tool");
+        command.args([
+            "store",
+            &format!("--label=JungleMonkAI · {}", account),
+            "service",
+            KEYCHAIN_SERVICE,
+            "account",
+            account,
+        ]);
+        (command, secret.to_string())
+    } else {
+        bail!("No hay un llavero compatible en este sistema; usa una frase de paso");
+    };
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "No se pudo ejecutar {}",
+                command.get_program().to_string_lossy()
+            )
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+    if !child.wait()?.success() {
+        bail!("El llavero del sistema rechazó el secreto");
+    }
+    Ok(())
+}
+
+/// Borra la entrada `account` del llavero. Que no exista no es un error.
+pub fn delete_keychain_entry(account: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("security");
+        command.args([
+            "delete-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+        ]);
+        command
+    } else if cfg!(target_os = "linux") {
+        let mut command = Command::new("secret-tool");
+        command.args(["clear", "service", KEYCHAIN_SERVICE, "account", account]);
+        command
+    } else {
+        bail!("No hay un llavero compatible en este sistema");
+    };
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .with_context(|| {
+            format!(
+                "No se pudo ejecutar {}",
+                command.get_program().to_string_lossy()
+            )
+        })?;
+    Ok(())
+}
+
+/// Formulario del cifrado del almacenamiento y resultado de la última operación.
+#[derive(Default)]
+pub struct StorageEncryptionState {
+    /// Origen de clave elegido para la próxima activación.
+    pub key_source: StorageKeySource,
+    pub passphrase: String,
+    pub passphrase_confirm: String,
+    pub status: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_contents_round_trip_and_reject_other_keys() {
+        let key = share::derive_key("frase de paso larga", b"sal-de-prueba-16").unwrap();
+        let sealed = seal(&key, b"{\"mensajes\":[]}").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!is_sealed(b"{\"mensajes\":[]}"));
+        assert_eq!(open(&key, &sealed).unwrap(), b"{\"mensajes\":[]}");
+
+        let other = share::derive_key("otra frase de paso", b"sal-de-prueba-16").unwrap();
+        assert!(open(&other, &sealed).is_err());
+        let mut tampered = sealed.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(open(&key, &tampered).is_err());
+    }
+
+    #[test]
+    fn enable_and_disable_migrate_files_atomically() {
+        let dir = std::env::temp_dir().join(format!("jmk-vault-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let vault_path = dir.join(VAULT_FILE);
+        let log = dir.join("audit.jsonl");
+        let files = VaultFiles {
+            header: vault_path.clone(),
+            staged_header: dir.join(STAGED_VAULT_FILE),
+            whole: vec![dir.join("session.json"), dir.join("no-existe.json")],
+            lines: vec![log.clone()],
+        };
+        fs::write(&files.whole[0], "{\"mensajes\":[]}").unwrap();
+        fs::write(&log, "{\"sequence\":1}\n").unwrap();
+
+        let (key, migrated) = enable_files(&files, "frase de paso", None).unwrap();
+        assert_eq!(migrated, 2);
+        assert!(vault_path.exists());
+        let sealed_log = fs::read_to_string(&log).unwrap();
+        assert!(sealed_log.starts_with(LINE_PREFIX));
+        assert_eq!(
+            open_line_with(sealed_log.trim(), Some(&key)).unwrap(),
+            "{\"sequence\":1}"
+        );
+        let files_whole = &files.whole;
+        assert!(is_sealed(&fs::read(&files_whole[0]).unwrap()));
+        assert!(read_with(&files_whole[0], None).is_err());
+        assert_eq!(
+            read_with(&files_whole[0], Some(&key)).unwrap(),
+            "{\"mensajes\":[]}"
+        );
+
+        // La clave se vuelve a derivar de la sal guardada en `vault.json`.
+        let unlocked = unlock_key(&vault_path, "frase de paso").unwrap();
+        assert_eq!(
+            read_with(&files_whole[0], Some(&unlocked)).unwrap(),
+            "{\"mensajes\":[]}"
+        );
+        assert!(unlock_key(&vault_path, "otra frase").is_err());
+
+        // Cambio de clave: la nueva sustituye a la anterior sin dejar copias.
+        let (rekeyed, migrated) = enable_files(&files, "otra frase", Some(&unlocked)).unwrap();
+        assert_eq!(migrated, 2);
+        assert!(unlock_key(&vault_path, "frase de paso").is_err());
+        let unlocked = unlock_key(&vault_path, "otra frase").unwrap();
+        assert_eq!(
+            read_with(&files_whole[0], Some(&unlocked)).unwrap(),
+            "{\"mensajes\":[]}"
+        );
+        assert!(read_with(&files_whole[0], Some(&key)).is_err());
+        assert_eq!(
+            open_line_with(fs::read_to_string(&log).unwrap().trim(), Some(&rekeyed)).unwrap(),
+            "{\"sequence\":1}"
+        );
+        assert!(!files.staged_header.exists());
+        assert!(!with_suffix(&files_whole[0], REKEY_SUFFIX).exists());
+
+        assert_eq!(disable_files(&files, Some(&unlocked)).unwrap(), 2);
+        assert!(!vault_path.exists());
+        assert_eq!(
+            fs::read_to_string(&files_whole[0]).unwrap(),
+            "{\"mensajes\":[]}"
+        );
+        assert_eq!(fs::read_to_string(&log).unwrap(), "{\"sequence\":1}\n");
+        assert!(fs::read_dir(&dir)
+            .unwrap()
+            .flatten()
+            .all(|entry| !entry.file_name().to_string_lossy().ends_with(".vault-tmp")));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn interrupted_rekey_rolls_back_or_forward() {
+        let dir = std::env::temp_dir().join(format!("jmk-vault-rekey-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let files = VaultFiles {
+            header: dir.join(VAULT_FILE),
+            staged_header: dir.join(STAGED_VAULT_FILE),
+            whole: vec![dir.join("session.json")],
+            lines: Vec::new(),
+        };
+        let copy = with_suffix(&files.whole[0], REKEY_SUFFIX);
+
+        // Sin confirmar: la cabecera nueva sigue aparte y la copia se descarta.
+        fs::write(&files.whole[0], "clave anterior").unwrap();
+        fs::write(&copy, "clave nueva").unwrap();
+        fs::write(&files.staged_header, "{}").unwrap();
+        recover_rekey(&files).unwrap();
+        assert_eq!(
+            fs::read_to_string(&files.whole[0]).unwrap(),
+            "clave anterior"
+        );
+        assert!(!copy.exists());
+        assert!(!files.staged_header.exists());
+
+        // Confirmada: la cabecera ya se renombró y la copia sustituye al original.
+        fs::write(&copy, "clave nueva").unwrap();
+        recover_rekey(&files).unwrap();
+        assert_eq!(fs::read_to_string(&files.whole[0]).unwrap(), "clave nueva");
+        assert!(!copy.exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,437 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+
+use crate::api::web_search::{self as api, WebSearchHit};
+use crate::config::WebSearchConfig;
+
+use super::job_queue::{JobPriority, JobQueueState, WEB_SEARCH_LANE};
+use super::{documents, ProviderCallTicket, RemoteProviderKind};
+
+/// Resultados que se piden como máximo, tanto desde el chat como desde la herramienta.
+pub const MAX_RESULTS: usize = 20;
+/// Caracteres de una página ampliada que se conservan.
+const MAX_PAGE_CHARS: usize = 4000;
+/// Caracteres de cada resultado que se envían como contexto.
+const CONTEXT_CHARS: usize = 1500;
+/// Prefijo con el que un proveedor pide una búsqueda en lugar de responder.
+pub const TOOL_PREFIX: &str = "BUSCAR_WEB:";
+
+/// Instrucciones que acompañan a los prompts cuando la herramienta está disponible.
+pub fn tool_instructions() -> String {
+    format!(
+        "Tienes una herramienta de búsqueda web. Si necesitas información actual o que no \
+         conoces para responder, contesta solo con una línea `{} <consulta>` y recibirás los \
+         resultados; si no, responde directamente.",
+        TOOL_PREFIX
+    )
+}
+
+/// Consulta que pide un proveedor, si su respuesta es una llamada a la herramienta.
+pub fn tool_query(response: &str) -> Option<String> {
+    let first = response.trim().trim_matches('`').lines().next()?.trim();
+    let query = first.strip_prefix(TOOL_PREFIX)?.trim().trim_matches('"');
+    (!query.is_empty()).then(|| query.to_string())
+}
+
+/// Resultado de una búsqueda, ya ordenado.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WebSearchResult {
+    /// Posición tras ordenar, empezando en 1.
+    pub rank: usize,
+    pub title: String,
+    pub url: String,
+    pub snippet: String,
+    /// Texto de la página, una vez ampliado el resultado.
+    pub content: Option<String>,
+}
+
+impl WebSearchResult {
+    /// Bloque de contexto para el prompt: título, URL y el texto más completo disponible.
+    pub fn context(&self) -> String {
+        let body = self.content.as_deref().unwrap_or(&self.snippet);
+        let mut text: String = body.chars().take(CONTEXT_CHARS).collect();
+        if text.len() < body.len() {
+            text.push('…');
+        }
+        format!("[{}] {} — {}\n{}", self.rank, self.title, self.url, text)
+    }
+}
+
+fn normalized_url(url: &str) -> String {
+    url.trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.")
+        .trim_end_matches('/')
+        .to_lowercase()
+}
+
+/// Ordena los resultados del buscador: primero los que más palabras de la consulta
+/// comparten en el título y el fragmento, y a igualdad, en el orden del buscador. Quita
+/// los duplicados y los que no tienen URL.
+pub fn rank_hits(query: &str, hits: Vec<WebSearchHit>, limit: usize) -> Vec<WebSearchResult> {
+    let query_terms = documents::terms(query);
+    let mut seen = HashSet::new();
+    let mut scored: Vec<(usize, usize, WebSearchHit)> = hits
+        .into_iter()
+        .filter(|hit| !hit.url.trim().is_empty() && seen.insert(normalized_url(&hit.url)))
+        .enumerate()
+        .map(|(position, hit)| {
+            let title = documents::terms(&hit.title)
+                .intersection(&query_terms)
+                .count();
+            let snippet = documents::terms(&hit.snippet)
+                .intersection(&query_terms)
+                .count();
+            (title * 2 + snippet, position, hit)
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    scored
+        .into_iter()
+        .take(limit)
+        .enumerate()
+        .map(|(index, (_, _, hit))| WebSearchResult {
+            rank: index + 1,
+            title: if hit.title.trim().is_empty() {
+                hit.url.clone()
+            } else {
+                hit.title.trim().to_string()
+            },
+            url: hit.url.trim().to_string(),
+            snippet: hit.snippet.trim().to_string(),
+            content: None,
+        })
+        .collect()
+}
+
+/// Busca en este hilo con el backend configurado.
+pub fn search(config: &WebSearchConfig, query: &str) -> Result<Vec<WebSearchResult>, String> {
+    let limit = config.max_results.clamp(1, MAX_RESULTS);
+    api::search(config, query, limit)
+        .map(|hits| rank_hits(query, hits, limit))
+        .map_err(|err| format!("{:#}", err))
+}
+
+/// Búsqueda que pidió un proveedor; al terminar se le vuelve a llamar con los resultados.
+pub(crate) struct WebSearchToolCall {
+    pub provider: RemoteProviderKind,
+    pub message_index: usize,
+    /// Prompt original del usuario, que se repite en la segunda llamada.
+    pub prompt: String,
+    pub query: String,
+    /// Conversación de la llamada, para no responder en otra si el usuario cambió.
+    pub conversation: String,
+    rx: Receiver<Result<Vec<WebSearchResult>, String>>,
+}
+
+/// Búsqueda lanzada desde el chat, resultados mostrados y resultados adjuntos como
+/// contexto de los próximos mensajes.
+#[derive(Default)]
+pub struct WebSearchState {
+    /// Consulta escrita en las preferencias.
+    pub draft: String,
+    /// Consulta de los resultados mostrados.
+    pub query: String,
+    pub results: Vec<WebSearchResult>,
+    pub status: Option<String>,
+    /// Resultados adjuntos a la conversación.
+    pub attached: Vec<WebSearchResult>,
+    /// URL de los resultados desplegados en el panel.
+    pub expanded: HashSet<String>,
+    pending: Option<Receiver<Result<Vec<WebSearchResult>, String>>>,
+    /// Páginas que se están descargando, por URL.
+    fetching: HashMap<String, Receiver<Result<String, String>>>,
+    tool_calls: Vec<WebSearchToolCall>,
+    /// Resultados para la llamada al proveedor que se está preparando.
+    pub(crate) pending_tool_context: Option<Vec<String>>,
+}
+
+impl WebSearchState {
+    pub fn is_searching(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn is_fetching(&self, url: &str) -> bool {
+        self.fetching.contains_key(url)
+    }
+
+    pub fn is_attached(&self, url: &str) -> bool {
+        self.attached.iter().any(|result| result.url == url)
+    }
+
+    /// Encola la búsqueda en la cola de llamadas salientes. Solo hay una en curso a la vez.
+    pub fn start(
+        &mut self,
+        jobs: &mut JobQueueState,
+        config: WebSearchConfig,
+        query: &str,
+    ) -> Result<(), String> {
+        let query = query.trim().to_string();
+        if query.is_empty() {
+            return Err("Escribe qué quieres buscar.".to_string());
+        }
+        if self.is_searching() {
+            return Err("Ya hay una búsqueda web en curso.".to_string());
+        }
+        api::validate(&config).map_err(|err| format!("{:#}", err))?;
+
+        let (tx, rx) = mpsc::channel();
+        self.pending = Some(rx);
+        self.query = query.clone();
+        self.results.clear();
+        self.expanded.clear();
+        let label = format!("Búsqueda web «{}»", query);
+        jobs.submit(
+            WEB_SEARCH_LANE,
+            JobPriority::Interactive,
+            label,
+            move || {
+                let _ = tx.send(search(&config, &query));
+            },
+        );
+        Ok(())
+    }
+
+    pub fn poll(&mut self) -> Option<Result<Vec<WebSearchResult>, String>> {
+        let rx = self.pending.as_ref()?;
+        match rx.try_recv() {
+            Ok(result) => {
+                self.pending = None;
+                Some(result)
+            }
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = None;
+                None
+            }
+        }
+    }
+
+    /// Despliega un resultado y, la primera vez, descarga el texto de su página.
+    pub fn expand(&mut self, jobs: &mut JobQueueState, url: &str) {
+        self.expanded.insert(url.to_string());
+        let loaded = self
+            .results
+            .iter()
+            .any(|result| result.url == url && result.content.is_some());
+        if !loaded {
+            self.fetch(jobs, url);
+        }
+    }
+
+    /// Adjunta una URL suelta como contexto y descarga el texto de su página.
+    pub fn attach_url(&mut self, jobs: &mut JobQueueState, url: &str) {
+        if url.is_empty() || self.is_attached(url) {
+            return;
+        }
+        self.attached.push(WebSearchResult {
+            rank: self.attached.len() + 1,
+            title: url.to_string(),
+            url: url.to_string(),
+            snippet: String::new(),
+            content: None,
+        });
+        self.fetch(jobs, url);
+    }
+
+    fn fetch(&mut self, jobs: &mut JobQueueState, url: &str) {
+        if self.is_fetching(url) {
+            return;
+        }
+        let (tx, rx) = mpsc::channel();
+        self.fetching.insert(url.to_string(), rx);
+        let url = url.to_string();
+        let label = format!("Página {}", url);
+        jobs.submit(
+            WEB_SEARCH_LANE,
+            JobPriority::Interactive,
+            label,
+            move || {
+                let text = api::fetch_page_text(&url)
+                    .map(|text| text.chars().take(MAX_PAGE_CHARS).collect())
+                    .map_err(|err| format!("{:#}", err));
+                let _ = tx.send(text);
+            },
+        );
+    }
+
+    /// Guarda el texto de las páginas descargadas. Devuelve los errores por URL.
+    pub fn poll_pages(&mut self) -> Vec<(String, Result<(), String>)> {
+        let mut finished = Vec::new();
+        self.fetching.retain(|url, rx| match rx.try_recv() {
+            Ok(result) => {
+                finished.push((url.clone(), result));
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+        finished
+            .into_iter()
+            .map(|(url, result)| {
+                let outcome = result.map(|text| {
+                    for result in self
+                        .results
+                        .iter_mut()
+                        .chain(self.attached.iter_mut())
+                        .filter(|result| result.url == url)
+                    {
+                        result.content = Some(text.clone());
+                    }
+                });
+                (url, outcome)
+            })
+            .collect()
+    }
+
+    /// Encola la búsqueda que pidió el proveedor de `ticket`.
+    pub(crate) fn start_tool(
+        &mut self,
+        jobs: &mut JobQueueState,
+        config: WebSearchConfig,
+        ticket: &ProviderCallTicket,
+        prompt: String,
+        query: String,
+        conversation: String,
+    ) {
+        let (tx, rx) = mpsc::channel();
+        let thread_query = query.clone();
+        let label = format!("Búsqueda web de {} «{}»", ticket.provider_name, query);
+        jobs.submit(
+            WEB_SEARCH_LANE,
+            JobPriority::Interactive,
+            label,
+            move || {
+                let _ = tx.send(search(&config, &thread_query));
+            },
+        );
+        self.tool_calls.push(WebSearchToolCall {
+            provider: ticket.provider_kind,
+            message_index: ticket.message_index,
+            prompt,
+            query,
+            conversation,
+            rx,
+        });
+    }
+
+    /// Búsquedas de los proveedores que han terminado, con sus resultados.
+    pub(crate) fn poll_tools(
+        &mut self,
+    ) -> Vec<(WebSearchToolCall, Result<Vec<WebSearchResult>, String>)> {
+        let mut finished = Vec::new();
+        let mut index = 0;
+        while index < self.tool_calls.len() {
+            let outcome = match self.tool_calls[index].rx.try_recv() {
+                Ok(outcome) => outcome,
+                Err(TryRecvError::Empty) => {
+                    index += 1;
+                    continue;
+                }
+                Err(TryRecvError::Disconnected) => Err("La búsqueda se interrumpió.".to_string()),
+            };
+            finished.push((self.tool_calls.remove(index), outcome));
+        }
+        finished
+    }
+
+    /// Adjunta o quita un resultado de la última búsqueda.
+    pub fn toggle_attached(&mut self, url: &str) {
+        if self.is_attached(url) {
+            self.attached.retain(|result| result.url != url);
+        } else if let Some(result) = self.results.iter().find(|result| result.url == url) {
+            self.attached.push(result.clone());
+        }
+    }
+
+    /// Contexto de los resultados adjuntos para el próximo prompt.
+    pub fn attached_context(&self) -> Vec<String> {
+        self.attached.iter().map(WebSearchResult::context).collect()
+    }
+}
+
+/// Contexto con los resultados de una búsqueda pedida por un proveedor.
+pub fn tool_context(query: &str, outcome: &Result<Vec<WebSearchResult>, String>) -> Vec<String> {
+    match outcome {
+        Ok(results) if !results.is_empty() => {
+            let mut lines = vec![format!(
+                "Resultados de la búsqueda web «{}» (cita la URL de los que uses):",
+                query
+            )];
+            lines.extend(results.iter().map(WebSearchResult::context));
+            lines
+        }
+        Ok(_) => vec![format!(
+            "La búsqueda web «{}» no devolvió resultados; responde con lo que sepas.",
+            query
+        )],
+        Err(err) => vec![format!(
+            "La búsqueda web «{}» falló ({}); responde con lo que sepas.",
+            query, err
+        )],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(title: &str, url: &str, snippet: &str) -> WebSearchHit {
+        WebSearchHit {
+            title: title.to_string(),
+            url: url.to_string(),
+            snippet: snippet.to_string(),
+        }
+    }
+
+    #[test]
+    fn ranks_hits_and_parses_tool_calls() {
+        let hits = vec![
+            hit("Noticias del día", "https://example.com/hoy", "Portada"),
+            hit(
+                "Versión estable de Rust",
+                "https://blog.rust-lang.org/",
+                "Rust publica versión estable",
+            ),
+            hit("Duplicado", "http://www.blog.rust-lang.org", "otro"),
+            hit("Sin URL", " ", "nada"),
+            hit("", "https://example.com/rust", "Rust versión anterior"),
+        ];
+        let results = rank_hits("última versión estable de Rust", hits, 5);
+        let urls: Vec<&str> = results.iter().map(|result| result.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://blog.rust-lang.org/",
+                "https://example.com/rust",
+                "https://example.com/hoy"
+            ]
+        );
+        assert_eq!(results[1].title, "https://example.com/rust");
+        assert_eq!(results[2].rank, 3);
+        assert!(results[0]
+            .context()
+            .starts_with("[1] Versión estable de Rust — "));
+
+        assert_eq!(
+            tool_query("BUSCAR_WEB: precio del cobre hoy\n"),
+            Some("precio del cobre hoy".to_string())
+        );
+        assert_eq!(
+            tool_query("`BUSCAR_WEB: \"rust 1.80\"`"),
+            Some("rust 1.80".to_string())
+        );
+        assert_eq!(tool_query("La respuesta es 42. BUSCAR_WEB: nada"), None);
+        assert_eq!(tool_query("BUSCAR_WEB:   "), None);
+
+        let failed = tool_context("cobre", &Err("timeout".to_string()));
+        assert_eq!(failed.len(), 1);
+        assert!(failed[0].contains("timeout"));
+        assert_eq!(tool_context("rust", &Ok(results)).len(), 4);
+        assert_eq!(
+            api::html_to_text("<p>Hola&nbsp;<b>mundo</b></p><script>x()</script><p>a &amp; b</p>"),
+            "Hola mundo\na & b"
+        );
+    }
+}
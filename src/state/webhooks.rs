@@ -0,0 +1,197 @@
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde_json::json;
+
+use super::job_queue::{JobPriority, JobQueueState, WEBHOOK_LANE};
+use super::vault;
+use crate::api::network;
+use crate::config::{WebhookConnectorConfig, WebhookKind};
+
+/// Caracteres por mensaje que admite Discord; Slack acepta más, pero los recorta.
+const DISCORD_MAX_CHARS: usize = 2000;
+const SLACK_MAX_CHARS: usize = 40_000;
+
+/// Entrada del llavero con la URL del conector `name`.
+pub fn keychain_account(name: &str) -> String {
+    format!("webhook:{}", name.trim())
+}
+
+/// Comprueba que la URL es la de un webhook entrante del servicio indicado.
+pub fn validate_url(kind: WebhookKind, url: &str) -> Result<(), String> {
+    let url = url.trim();
+    let valid = match kind {
+        WebhookKind::Slack => url.starts_with("https://hooks.slack.com/"),
+        WebhookKind::Discord => {
+            url.starts_with("https://discord.com/api/webhooks/")
+                || url.starts_with("https://discordapp.com/api/webhooks/")
+        }
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "La URL no parece un webhook entrante de {}.",
+            kind.label()
+        ))
+    }
+}
+
+/// Cuerpo JSON del mensaje, recortado al límite del servicio.
+pub fn payload(kind: WebhookKind, text: &str) -> serde_json::Value {
+    let limit = match kind {
+        WebhookKind::Slack => SLACK_MAX_CHARS,
+        WebhookKind::Discord => DISCORD_MAX_CHARS,
+    };
+    let text = text.trim();
+    let text: String = if text.chars().count() > limit {
+        text.chars().take(limit - 1).chain(['…']).collect()
+    } else {
+        text.to_string()
+    };
+    match kind {
+        WebhookKind::Slack => json!({ "text": text }),
+        WebhookKind::Discord => json!({ "content": text, "allowed_mentions": { "parse": [] } }),
+    }
+}
+
+/// Conector cuyo nombre aparece en `text`, o el primero de su servicio si solo se
+/// menciona «Slack» o «Discord». Los conectores desactivados no cuentan.
+pub fn connector_for<'a>(
+    connectors: &'a [WebhookConnectorConfig],
+    text: &str,
+) -> Option<&'a WebhookConnectorConfig> {
+    let text = text.to_lowercase();
+    let enabled = || connectors.iter().filter(|connector| connector.enabled);
+    enabled()
+        .find(|connector| {
+            let name = connector.name.trim().to_lowercase();
+            !name.is_empty() && text.contains(&name)
+        })
+        .or_else(|| {
+            enabled().find(|connector| text.contains(&connector.kind.label().to_lowercase()))
+        })
+}
+
+/// Publica `text` en este hilo con la URL guardada en el llavero.
+pub fn post(connector: &WebhookConnectorConfig, text: &str) -> Result<()> {
+    let url = vault::keychain_entry(&keychain_account(&connector.name))?
+        .with_context(|| format!("El llavero no tiene la URL de '{}'", connector.name))?;
+    let label = connector.kind.label();
+    let client = network::client_builder(Duration::from_secs(15))?
+        .user_agent("JungleMonkAI/0.1")
+        .build()
+        .with_context(|| format!("No se pudo crear el cliente HTTP para {}", label))?;
+    // La URL es el secreto del webhook: el error solo nombra el conector.
+    let response = client
+        .post(url.trim())
+        .json(&payload(connector.kind, text))
+        .send()
+        .map_err(reqwest::Error::without_url)
+        .with_context(|| format!("No se pudo conectar con {} '{}'", label, connector.name))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().unwrap_or_default();
+        bail!("{} respondió {}: {}", label, status, body.trim());
+    }
+    Ok(())
+}
+
+/// Publicación terminada en segundo plano.
+pub struct WebhookOutcome {
+    pub connector: String,
+    /// Quién pidió la publicación: un workflow, el resumen o una prueba.
+    pub origin: String,
+    pub result: Result<(), String>,
+}
+
+/// Formulario de alta de conectores y publicaciones en curso.
+#[derive(Default)]
+pub struct WebhookState {
+    pub name_draft: String,
+    pub kind_draft: WebhookKind,
+    /// URL que se guarda en el llavero al añadir el conector; no se conserva después.
+    pub url_draft: String,
+    pub status: Option<String>,
+    pending: Vec<Receiver<WebhookOutcome>>,
+}
+
+impl WebhookState {
+    pub fn is_posting(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// Encola la publicación de `text` en la cola de llamadas salientes.
+    pub fn start(
+        &mut self,
+        jobs: &mut JobQueueState,
+        connector: WebhookConnectorConfig,
+        origin: String,
+        text: String,
+    ) {
+        let (tx, rx) = mpsc::channel();
+        self.pending.push(rx);
+        let label = format!("{} · {}", connector.kind.label(), connector.name);
+        jobs.submit(WEBHOOK_LANE, JobPriority::Background, label, move || {
+            let result = post(&connector, &text).map_err(|err| format!("{:#}", err));
+            let _ = tx.send(WebhookOutcome {
+                connector: connector.name,
+                origin,
+                result,
+            });
+        });
+    }
+
+    pub fn poll(&mut self) -> Vec<WebhookOutcome> {
+        let mut finished = Vec::new();
+        self.pending.retain(|rx| match rx.try_recv() {
+            Ok(outcome) => {
+                finished.push(outcome);
+                false
+            }
+            Err(TryRecvError::Empty) => true,
+            Err(TryRecvError::Disconnected) => false,
+        });
+        finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_payloads_and_matches_connectors_by_name() {
+        assert_eq!(
+            payload(WebhookKind::Slack, " Hola "),
+            json!({ "text": "Hola" })
+        );
+        let long = "a".repeat(DISCORD_MAX_CHARS + 10);
+        let discord = payload(WebhookKind::Discord, &long);
+        let content = discord["content"].as_str().unwrap();
+        assert_eq!(content.chars().count(), DISCORD_MAX_CHARS);
+        assert!(content.ends_with('…'));
+
+        assert!(validate_url(WebhookKind::Slack, "https://hooks.slack.com/services/T/B/X").is_ok());
+        assert!(validate_url(WebhookKind::Discord, "https://hooks.slack.com/services/T").is_err());
+
+        let connectors = vec![
+            WebhookConnectorConfig {
+                name: "qa".to_string(),
+                kind: WebhookKind::Slack,
+                ..WebhookConnectorConfig::default()
+            },
+            WebhookConnectorConfig {
+                name: "equipo".to_string(),
+                kind: WebhookKind::Discord,
+                ..WebhookConnectorConfig::default()
+            },
+        ];
+        let named = connector_for(&connectors, "Publica en el canal de Equipo");
+        assert_eq!(named.map(|c| c.name.as_str()), Some("equipo"));
+        let by_kind = connector_for(&connectors, "Publicar resumen en Slack");
+        assert_eq!(by_kind.map(|c| c.name.as_str()), Some("qa"));
+        assert!(connector_for(&connectors, "Guardar en disco").is_none());
+    }
+}
@@ -0,0 +1,929 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::RedactionConfig;
+
+use super::automation::AutomationState;
+use super::redaction::RedactionState;
+use super::{
+    cron, AutomationWorkflow, EventListener, ListenerEventKind, RemoteProviderKind, ScheduledTask,
+    ScheduledTaskKind, ScheduledTaskStatus, WorkflowStatus, WorkflowStep, WorkflowStepKind,
+    WorkflowTriggerKind,
+};
+
+/// Versión del formato que escribe esta build; se aceptan paquetes de esta o anteriores.
+pub const FORMAT_VERSION: u32 = 1;
+/// Prefijo de las acciones de listener que lanzan un workflow.
+const WORKFLOW_ACTION: &str = "workflow:";
+/// Marcador de un secreto: `{{secreto:NOMBRE}}`.
+const SECRET_OPEN: &str = "{{secreto:";
+const SECRET_CLOSE: &str = "}}";
+
+/// Automatizaciones empaquetadas para compartirlas entre instalaciones. Los secretos
+/// viajan como marcadores y se piden al importar.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkflowBundle {
+    pub format_version: u32,
+    pub name: String,
+    pub exported_at: String,
+    pub workflows: Vec<BundleWorkflow>,
+    pub schedules: Vec<BundleSchedule>,
+    pub listeners: Vec<BundleListener>,
+    pub secrets: Vec<BundleSecret>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BundleWorkflow {
+    pub name: String,
+    pub description: String,
+    /// `manual`, `chat`, `schedule` o `listener`.
+    pub trigger: String,
+    pub chat_command: Option<String>,
+    /// Nombre de la programación del paquete que lo lanza.
+    pub schedule: Option<String>,
+    pub pinned: bool,
+    pub steps: Vec<BundleStep>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BundleStep {
+    /// `remote_model`, `local_script` o `sync`.
+    pub kind: String,
+    pub label: String,
+    pub detail: String,
+    /// Código corto del proveedor, como en las menciones del chat.
+    pub provider: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BundleSchedule {
+    pub name: String,
+    pub description: String,
+    pub cron_expression: String,
+    pub cadence_label: String,
+    pub owner: String,
+    pub provider: Option<String>,
+    pub tags: Vec<String>,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BundleListener {
+    pub name: String,
+    pub description: String,
+    /// `chat_message`, `github`, `command`, `scheduler` o `file_change`.
+    pub event: String,
+    pub condition: String,
+    /// Las acciones `workflow:` llevan el nombre del workflow en lugar de su id.
+    pub action: String,
+    pub enabled: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BundleSecret {
+    pub name: String,
+    pub description: String,
+}
+
+fn trigger_code(trigger: WorkflowTriggerKind) -> &'static str {
+    match trigger {
+        WorkflowTriggerKind::Manual => "manual",
+        WorkflowTriggerKind::ChatCommand => "chat",
+        WorkflowTriggerKind::Scheduled => "schedule",
+        WorkflowTriggerKind::EventListener => "listener",
+    }
+}
+
+fn trigger_from_code(code: &str) -> Option<WorkflowTriggerKind> {
+    match code {
+        "manual" => Some(WorkflowTriggerKind::Manual),
+        "chat" => Some(WorkflowTriggerKind::ChatCommand),
+        "schedule" => Some(WorkflowTriggerKind::Scheduled),
+        "listener" => Some(WorkflowTriggerKind::EventListener),
+        _ => None,
+    }
+}
+
+fn step_code(kind: WorkflowStepKind) -> &'static str {
+    match kind {
+        WorkflowStepKind::RemoteModel => "remote_model",
+        WorkflowStepKind::LocalScript => "local_script",
+        WorkflowStepKind::SyncAction => "sync",
+    }
+}
+
+fn step_from_code(code: &str) -> Option<WorkflowStepKind> {
+    match code {
+        "remote_model" => Some(WorkflowStepKind::RemoteModel),
+        "local_script" => Some(WorkflowStepKind::LocalScript),
+        "sync" => Some(WorkflowStepKind::SyncAction),
+        _ => None,
+    }
+}
+
+fn event_code(event: ListenerEventKind) -> &'static str {
+    match event {
+        ListenerEventKind::ChatMessage => "chat_message",
+        ListenerEventKind::GithubChange => "github",
+        ListenerEventKind::CommandExecution => "command",
+        ListenerEventKind::Scheduler => "scheduler",
+        ListenerEventKind::FileChange => "file_change",
+        ListenerEventKind::MqttMessage => "mqtt",
+    }
+}
+
+fn event_from_code(code: &str) -> Option<ListenerEventKind> {
+    match code {
+        "chat_message" => Some(ListenerEventKind::ChatMessage),
+        "github" => Some(ListenerEventKind::GithubChange),
+        "command" => Some(ListenerEventKind::CommandExecution),
+        "scheduler" => Some(ListenerEventKind::Scheduler),
+        "file_change" => Some(ListenerEventKind::FileChange),
+        "mqtt" => Some(ListenerEventKind::MqttMessage),
+        _ => None,
+    }
+}
+
+/// Sustituye los fragmentos que detectan las reglas de redacción por marcadores de
+/// secreto. El mismo valor recibe siempre el mismo marcador.
+struct SecretExtractor<'a> {
+    redaction: &'a mut RedactionState,
+    config: RedactionConfig,
+    found: Vec<(String, BundleSecret)>,
+}
+
+impl SecretExtractor<'_> {
+    fn protect(&mut self, text: &str) -> String {
+        let hits = self.redaction.hits(&self.config, text);
+        let mut output = String::with_capacity(text.len());
+        let mut cursor = 0;
+        for hit in hits {
+            let value = &text[hit.range.clone()];
+            let name = match self.found.iter().find(|(known, _)| known == value) {
+                Some((_, secret)) => secret.name.clone(),
+                None => {
+                    let base: String = hit
+                        .rule
+                        .to_uppercase()
+                        .chars()
+                        .map(|ch| if ch.is_alphanumeric() { ch } else { '_' })
+                        .collect();
+                    let name = format!("{}_{}", base.trim_matches('_'), self.found.len() + 1);
+                    self.found.push((
+                        value.to_string(),
+                        BundleSecret {
+                            name: name.clone(),
+                            description: format!("Detectado por la regla '{}'", hit.rule),
+                        },
+                    ));
+                    name
+                }
+            };
+            output.push_str(&text[cursor..hit.range.start]);
+            output.push_str(&format!("{}{}{}", SECRET_OPEN, name, SECRET_CLOSE));
+            cursor = hit.range.end;
+        }
+        output.push_str(&text[cursor..]);
+        output
+    }
+}
+
+/// Empaqueta los workflows con sus programaciones y los listeners que los lanzan. Los
+/// secretos se buscan con las reglas de redacción aunque estén desactivadas para el chat.
+pub fn export(
+    name: &str,
+    exported_at: &str,
+    workflows: &[&AutomationWorkflow],
+    automation: &AutomationState,
+    redaction: &mut RedactionState,
+    redaction_config: &RedactionConfig,
+) -> WorkflowBundle {
+    let mut secrets = SecretExtractor {
+        redaction,
+        config: RedactionConfig {
+            enabled: true,
+            ..redaction_config.clone()
+        },
+        found: Vec::new(),
+    };
+    let mut bundle = WorkflowBundle {
+        format_version: FORMAT_VERSION,
+        name: name.to_string(),
+        exported_at: exported_at.to_string(),
+        ..WorkflowBundle::default()
+    };
+
+    for workflow in workflows {
+        // Las tareas integradas dependen de la configuración de cada instalación.
+        let schedule = workflow
+            .linked_schedule
+            .and_then(|id| {
+                automation
+                    .cron_board
+                    .tasks
+                    .iter()
+                    .find(|task| task.id == id)
+            })
+            .filter(|task| task.kind == ScheduledTaskKind::Generic);
+        if let Some(task) = schedule {
+            if !bundle.schedules.iter().any(|known| known.name == task.name) {
+                bundle.schedules.push(BundleSchedule {
+                    name: task.name.clone(),
+                    description: task.description.clone(),
+                    cron_expression: task.cron_expression.clone(),
+                    cadence_label: task.cadence_label.clone(),
+                    owner: task.owner.clone(),
+                    provider: task
+                        .provider
+                        .map(|provider| provider.short_code().to_string()),
+                    tags: task.tags.clone(),
+                    enabled: task.enabled,
+                });
+            }
+        }
+        bundle.workflows.push(BundleWorkflow {
+            name: workflow.name.clone(),
+            description: workflow.description.clone(),
+            trigger: trigger_code(workflow.trigger).to_string(),
+            chat_command: workflow.chat_command.clone(),
+            schedule: schedule.map(|task| task.name.clone()),
+            pinned: workflow.pinned,
+            steps: workflow
+                .steps
+                .iter()
+                .map(|step| BundleStep {
+                    kind: step_code(step.kind).to_string(),
+                    label: secrets.protect(&step.label),
+                    detail: secrets.protect(&step.detail),
+                    provider: step
+                        .provider
+                        .map(|provider| provider.short_code().to_string()),
+                })
+                .collect(),
+        });
+    }
+
+    for listener in &automation.event_automation.listeners {
+        let Some(target) = workflow_action_id(&listener.action) else {
+            continue;
+        };
+        let Some(workflow) = workflows.iter().find(|workflow| workflow.id == target) else {
+            continue;
+        };
+        bundle.listeners.push(BundleListener {
+            name: listener.name.clone(),
+            description: listener.description.clone(),
+            event: event_code(listener.event).to_string(),
+            condition: secrets.protect(&listener.condition),
+            action: format!("{}{}", WORKFLOW_ACTION, workflow.name),
+            enabled: listener.enabled,
+        });
+    }
+
+    bundle.secrets = secrets
+        .found
+        .into_iter()
+        .map(|(_, secret)| secret)
+        .collect();
+    bundle
+}
+
+fn workflow_action_id(action: &str) -> Option<u32> {
+    action
+        .trim()
+        .strip_prefix(WORKFLOW_ACTION)
+        .and_then(|id| id.trim().parse().ok())
+}
+
+/// Nombres de los secretos que aparecen en `text`, en orden y sin repetir.
+pub fn secret_names(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(SECRET_OPEN) {
+        let after = &rest[start + SECRET_OPEN.len()..];
+        let Some(length) = after.find(SECRET_CLOSE) else {
+            break;
+        };
+        let name = after[..length].trim();
+        if !name.is_empty() && !names.iter().any(|known| known == name) {
+            names.push(name.to_string());
+        }
+        rest = &after[length + SECRET_CLOSE.len()..];
+    }
+    names
+}
+
+/// Sustituye los marcadores de secreto con valor; los que falten se conservan.
+pub fn fill_secrets(text: &str, values: &BTreeMap<String, String>) -> String {
+    values
+        .iter()
+        .filter(|(_, value)| !value.trim().is_empty())
+        .fold(text.to_string(), |text, (name, value)| {
+            text.replace(
+                &format!("{}{}{}", SECRET_OPEN, name, SECRET_CLOSE),
+                value.trim(),
+            )
+        })
+}
+
+fn bundle_texts(bundle: &WorkflowBundle) -> impl Iterator<Item = &String> {
+    let steps = bundle
+        .workflows
+        .iter()
+        .flat_map(|workflow| &workflow.steps)
+        .flat_map(|step| [&step.label, &step.detail]);
+    let listeners = bundle
+        .listeners
+        .iter()
+        .flat_map(|listener| [&listener.condition, &listener.action]);
+    steps.chain(listeners)
+}
+
+fn check_unique<'a>(kind: &str, names: impl Iterator<Item = &'a String>) -> Result<(), String> {
+    let mut seen: Vec<&str> = Vec::new();
+    for name in names {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("Hay un {} sin nombre.", kind));
+        }
+        if seen.contains(&name) {
+            return Err(format!("El {} '{}' aparece dos veces.", kind, name));
+        }
+        seen.push(name);
+    }
+    Ok(())
+}
+
+/// Comprueba que el paquete es de una versión conocida, que sus nombres no se repiten y
+/// que cada referencia a una programación, un workflow o un secreto está incluida.
+pub fn validate(bundle: &WorkflowBundle) -> Result<(), String> {
+    if bundle.format_version == 0 || bundle.format_version > FORMAT_VERSION {
+        return Err(format!(
+            "Versión de formato {} no soportada (como máximo {}).",
+            bundle.format_version, FORMAT_VERSION
+        ));
+    }
+    if bundle.workflows.is_empty() && bundle.listeners.is_empty() && bundle.schedules.is_empty() {
+        return Err("El paquete no contiene automatizaciones.".to_string());
+    }
+    check_unique("workflow", bundle.workflows.iter().map(|item| &item.name))?;
+    check_unique(
+        "programación",
+        bundle.schedules.iter().map(|item| &item.name),
+    )?;
+    check_unique("listener", bundle.listeners.iter().map(|item| &item.name))?;
+    check_unique("secreto", bundle.secrets.iter().map(|item| &item.name))?;
+
+    for schedule in &bundle.schedules {
+        cron::CronSchedule::parse(&schedule.cron_expression).map_err(|err| {
+            format!(
+                "La programación '{}' tiene un cron inválido: {}",
+                schedule.name, err
+            )
+        })?;
+    }
+    for workflow in &bundle.workflows {
+        if trigger_from_code(&workflow.trigger).is_none() {
+            return Err(format!(
+                "El workflow '{}' tiene un disparador desconocido: '{}'.",
+                workflow.name, workflow.trigger
+            ));
+        }
+        if workflow.steps.is_empty() {
+            return Err(format!("El workflow '{}' no tiene pasos.", workflow.name));
+        }
+        if let Some(schedule) = &workflow.schedule {
+            if !bundle.schedules.iter().any(|known| &known.name == schedule) {
+                return Err(format!(
+                    "El workflow '{}' usa la programación '{}', que no está en el paquete.",
+                    workflow.name, schedule
+                ));
+            }
+        }
+        for step in &workflow.steps {
+            if step_from_code(&step.kind).is_none() {
+                return Err(format!(
+                    "El workflow '{}' tiene un paso de tipo desconocido: '{}'.",
+                    workflow.name, step.kind
+                ));
+            }
+            if let Some(code) = &step.provider {
+                if RemoteProviderKind::from_short_code(code).is_none() {
+                    return Err(format!(
+                        "El workflow '{}' usa un proveedor desconocido: '{}'.",
+                        workflow.name, code
+                    ));
+                }
+            }
+        }
+    }
+    for listener in &bundle.listeners {
+        if event_from_code(&listener.event).is_none() {
+            return Err(format!(
+                "El listener '{}' escucha un evento desconocido: '{}'.",
+                listener.name, listener.event
+            ));
+        }
+        if let Some(target) = listener.action.trim().strip_prefix(WORKFLOW_ACTION) {
+            if !bundle
+                .workflows
+                .iter()
+                .any(|workflow| workflow.name == target.trim())
+            {
+                return Err(format!(
+                    "El listener '{}' lanza '{}', que no está en el paquete.",
+                    listener.name,
+                    target.trim()
+                ));
+            }
+        }
+    }
+    for text in bundle_texts(bundle) {
+        for name in secret_names(text) {
+            if !bundle.secrets.iter().any(|secret| secret.name == name) {
+                return Err(format!(
+                    "El secreto '{}' se usa pero no está declarado.",
+                    name
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lee y valida un paquete en JSON.
+pub fn parse(text: &str) -> Result<WorkflowBundle, String> {
+    let bundle: WorkflowBundle = serde_json::from_str(text)
+        .map_err(|err| format!("El archivo no es un paquete de workflows válido: {}", err))?;
+    validate(&bundle)?;
+    Ok(bundle)
+}
+
+/// Qué hacer con las automatizaciones del paquete que ya existen con el mismo nombre.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictResolution {
+    #[default]
+    Skip,
+    Replace,
+    Rename,
+}
+
+impl ConflictResolution {
+    pub const ALL: [ConflictResolution; 3] = [
+        ConflictResolution::Skip,
+        ConflictResolution::Replace,
+        ConflictResolution::Rename,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConflictResolution::Skip => "Conservar las existentes",
+            ConflictResolution::Replace => "Reemplazar las existentes",
+            ConflictResolution::Rename => "Importar con otro nombre",
+        }
+    }
+}
+
+/// Automatizaciones del paquete cuyo nombre ya está en uso.
+pub fn conflicts(bundle: &WorkflowBundle, automation: &AutomationState) -> Vec<String> {
+    let mut conflicts = Vec::new();
+    for workflow in &bundle.workflows {
+        if automation
+            .workflows
+            .workflows
+            .iter()
+            .any(|known| known.name == workflow.name)
+        {
+            conflicts.push(format!("Workflow '{}'", workflow.name));
+        }
+    }
+    for schedule in &bundle.schedules {
+        if automation.cron_board.task_by_name(&schedule.name).is_some() {
+            conflicts.push(format!("Programación '{}'", schedule.name));
+        }
+    }
+    for listener in &bundle.listeners {
+        if automation
+            .event_automation
+            .listeners
+            .iter()
+            .any(|known| known.name == listener.name)
+        {
+            conflicts.push(format!("Listener '{}'", listener.name));
+        }
+    }
+    conflicts
+}
+
+fn renamed(name: &str, taken: impl Fn(&str) -> bool) -> String {
+    let mut candidate = format!("{} (importado)", name);
+    let mut counter = 2;
+    while taken(&candidate) {
+        candidate = format!("{} (importado {})", name, counter);
+        counter += 1;
+    }
+    candidate
+}
+
+/// Resultado de aplicar un paquete.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub replaced: usize,
+    pub skipped: usize,
+    /// Secretos que siguen como marcador porque no se les dio valor.
+    pub missing_secrets: Vec<String>,
+}
+
+/// Incorpora el paquete con los secretos indicados. Devuelve lo que cambió y el paquete tal
+/// como quedó instalado (nombres definitivos, secretos rellenos y sin lo omitido), que es
+/// lo que se guarda para volver a aplicarlo al arrancar.
+pub fn apply(
+    bundle: &WorkflowBundle,
+    resolution: ConflictResolution,
+    secrets: &BTreeMap<String, String>,
+    automation: &mut AutomationState,
+) -> (ImportSummary, WorkflowBundle) {
+    let mut summary = ImportSummary {
+        missing_secrets: bundle
+            .secrets
+            .iter()
+            .filter(|secret| {
+                secrets
+                    .get(&secret.name)
+                    .is_none_or(|value| value.trim().is_empty())
+            })
+            .map(|secret| secret.name.clone())
+            .collect(),
+        ..ImportSummary::default()
+    };
+    let mut installed = WorkflowBundle {
+        secrets: bundle
+            .secrets
+            .iter()
+            .filter(|secret| summary.missing_secrets.contains(&secret.name))
+            .cloned()
+            .collect(),
+        ..bundle.clone()
+    };
+    installed.workflows.clear();
+    installed.schedules.clear();
+    installed.listeners.clear();
+
+    // Id y nombre definitivos de cada programación y workflow, por su nombre en el paquete.
+    // Lo que se omite apunta a la automatización que ya existía con ese nombre.
+    let mut schedule_ids: BTreeMap<String, u32> = BTreeMap::new();
+    let mut workflow_ids: BTreeMap<String, u32> = BTreeMap::new();
+    let mut schedule_names: BTreeMap<String, String> = BTreeMap::new();
+    let mut workflow_names: BTreeMap<String, String> = BTreeMap::new();
+
+    for schedule in &bundle.schedules {
+        let tasks = &mut automation.cron_board.tasks;
+        let existing = tasks.iter().position(|task| task.name == schedule.name);
+        let (position, name) = match (existing, resolution) {
+            (Some(position), ConflictResolution::Skip) => {
+                schedule_ids.insert(schedule.name.clone(), tasks[position].id);
+                summary.skipped += 1;
+                continue;
+            }
+            (Some(position), ConflictResolution::Replace) => {
+                summary.replaced += 1;
+                (Some(position), schedule.name.clone())
+            }
+            (Some(_), ConflictResolution::Rename) => {
+                let name = renamed(&schedule.name, |candidate| {
+                    tasks.iter().any(|task| task.name == candidate)
+                });
+                summary.added += 1;
+                (None, name)
+            }
+            (None, _) => {
+                summary.added += 1;
+                (None, schedule.name.clone())
+            }
+        };
+        let id = match position {
+            Some(position) => tasks[position].id,
+            None => automation.cron_board.next_task_id(),
+        };
+        let next_run = cron::CronSchedule::parse(&schedule.cron_expression)
+            .ok()
+            .and_then(|cron| cron.next_after(chrono::Local::now()))
+            .map(|next| next.format("%Y-%m-%d %H:%M").to_string());
+        let task = ScheduledTask {
+            id,
+            name: name.clone(),
+            description: schedule.description.clone(),
+            cron_expression: schedule.cron_expression.clone(),
+            cadence_label: if schedule.cadence_label.trim().is_empty() {
+                schedule.cron_expression.clone()
+            } else {
+                schedule.cadence_label.clone()
+            },
+            last_run: None,
+            next_run,
+            status: if schedule.enabled {
+                ScheduledTaskStatus::Scheduled
+            } else {
+                ScheduledTaskStatus::Paused
+            },
+            owner: schedule.owner.clone(),
+            provider: schedule
+                .provider
+                .as_deref()
+                .and_then(RemoteProviderKind::from_short_code),
+            tags: schedule.tags.clone(),
+            enabled: schedule.enabled,
+            kind: ScheduledTaskKind::Generic,
+        };
+        let tasks = &mut automation.cron_board.tasks;
+        match position {
+            Some(position) => tasks[position] = task,
+            None => tasks.push(task),
+        }
+        schedule_ids.insert(schedule.name.clone(), id);
+        schedule_names.insert(schedule.name.clone(), name.clone());
+        installed.schedules.push(BundleSchedule {
+            name,
+            ..schedule.clone()
+        });
+    }
+
+    for workflow in &bundle.workflows {
+        let board = &mut automation.workflows.workflows;
+        let existing = board.iter().position(|known| known.name == workflow.name);
+        let (position, name) = match (existing, resolution) {
+            (Some(position), ConflictResolution::Skip) => {
+                workflow_ids.insert(workflow.name.clone(), board[position].id);
+                summary.skipped += 1;
+                continue;
+            }
+            (Some(position), ConflictResolution::Replace) => {
+                summary.replaced += 1;
+                (Some(position), workflow.name.clone())
+            }
+            (Some(_), ConflictResolution::Rename) => {
+                let name = renamed(&workflow.name, |candidate| {
+                    board.iter().any(|known| known.name == candidate)
+                });
+                summary.added += 1;
+                (None, name)
+            }
+            (None, _) => {
+                summary.added += 1;
+                (None, workflow.name.clone())
+            }
+        };
+        let id = match position {
+            Some(position) => board[position].id,
+            None => board.iter().map(|known| known.id).max().unwrap_or(0) + 1,
+        };
+        let steps: Vec<BundleStep> = workflow
+            .steps
+            .iter()
+            .map(|step| BundleStep {
+                label: fill_secrets(&step.label, secrets),
+                detail: fill_secrets(&step.detail, secrets),
+                ..step.clone()
+            })
+            .collect();
+        let imported = AutomationWorkflow {
+            id,
+            name: name.clone(),
+            description: workflow.description.clone(),
+            trigger: trigger_from_code(&workflow.trigger).unwrap_or(WorkflowTriggerKind::Manual),
+            chat_command: workflow
+                .chat_command
+                .clone()
+                .filter(|command| !command.trim().is_empty()),
+            linked_schedule: workflow.schedule.as_ref().and_then(|schedule| {
+                schedule_ids.get(schedule).copied().or_else(|| {
+                    automation
+                        .cron_board
+                        .task_by_name(schedule)
+                        .map(|task| task.id)
+                })
+            }),
+            status: WorkflowStatus::Ready,
+            last_run: None,
+            pinned: workflow.pinned,
+            steps: steps
+                .iter()
+                .map(|step| WorkflowStep {
+                    kind: step_from_code(&step.kind).unwrap_or(WorkflowStepKind::SyncAction),
+                    label: step.label.clone(),
+                    detail: step.detail.clone(),
+                    provider: step
+                        .provider
+                        .as_deref()
+                        .and_then(RemoteProviderKind::from_short_code),
+                })
+                .collect(),
+        };
+        let board = &mut automation.workflows.workflows;
+        match position {
+            Some(position) => board[position] = imported,
+            None => board.push(imported),
+        }
+        workflow_ids.insert(workflow.name.clone(), id);
+        workflow_names.insert(workflow.name.clone(), name.clone());
+        installed.workflows.push(BundleWorkflow {
+            name,
+            steps,
+            schedule: workflow.schedule.as_ref().map(|schedule| {
+                schedule_names
+                    .get(schedule)
+                    .cloned()
+                    .unwrap_or_else(|| schedule.clone())
+            }),
+            ..workflow.clone()
+        });
+    }
+
+    for listener in &bundle.listeners {
+        let listeners = &mut automation.event_automation.listeners;
+        let existing = listeners
+            .iter()
+            .position(|known| known.name == listener.name);
+        let (position, name) = match (existing, resolution) {
+            (Some(_), ConflictResolution::Skip) => {
+                summary.skipped += 1;
+                continue;
+            }
+            (Some(position), ConflictResolution::Replace) => {
+                summary.replaced += 1;
+                (Some(position), listener.name.clone())
+            }
+            (Some(_), ConflictResolution::Rename) => {
+                let name = renamed(&listener.name, |candidate| {
+                    listeners.iter().any(|known| known.name == candidate)
+                });
+                summary.added += 1;
+                (None, name)
+            }
+            (None, _) => {
+                summary.added += 1;
+                (None, listener.name.clone())
+            }
+        };
+        let target = listener
+            .action
+            .trim()
+            .strip_prefix(WORKFLOW_ACTION)
+            .map(str::trim);
+        let target_id = target.and_then(|target| {
+            workflow_ids.get(target).copied().or_else(|| {
+                automation
+                    .workflows
+                    .workflows
+                    .iter()
+                    .find(|known| known.name == target)
+                    .map(|known| known.id)
+            })
+        });
+        let listeners = &mut automation.event_automation.listeners;
+        let action = match target_id {
+            Some(id) => format!("{}{}", WORKFLOW_ACTION, id),
+            None => fill_secrets(&listener.action, secrets),
+        };
+        let imported = EventListener {
+            id: match position {
+                Some(position) => listeners[position].id,
+                None => listeners.iter().map(|known| known.id).max().unwrap_or(0) + 1,
+            },
+            name: name.clone(),
+            description: listener.description.clone(),
+            event: event_from_code(&listener.event).unwrap_or(ListenerEventKind::ChatMessage),
+            condition: fill_secrets(&listener.condition, secrets),
+            action: action.clone(),
+            enabled: listener.enabled,
+            last_triggered: None,
+        };
+        match position {
+            Some(position) => listeners[position] = imported,
+            None => listeners.push(imported),
+        }
+        // El paquete guardado sigue refiriéndose al workflow por su nombre definitivo.
+        let action = match target {
+            Some(target) => format!(
+                "{}{}",
+                WORKFLOW_ACTION,
+                workflow_names.get(target).map_or(target, String::as_str)
+            ),
+            None => action,
+        };
+        installed.listeners.push(BundleListener {
+            name,
+            condition: fill_secrets(&listener.condition, secrets),
+            action,
+            ..listener.clone()
+        });
+    }
+
+    (summary, installed)
+}
+
+/// Rutas de importación y exportación y paquete pendiente de confirmar.
+#[derive(Default)]
+pub struct WorkflowBundleState {
+    pub export_path: String,
+    pub import_path: String,
+    /// Paquete leído y validado que espera a que se confirme la importación.
+    pub pending: Option<WorkflowBundle>,
+    pub conflicts: Vec<String>,
+    pub resolution: ConflictResolution,
+    /// Valor de cada secreto del paquete pendiente.
+    pub secrets: BTreeMap<String, String>,
+    pub status: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::AppConfig;
+
+    #[test]
+    fn round_trips_workflows_with_listeners_and_secret_placeholders() {
+        let mut automation = AutomationState::from_config(&AppConfig::default());
+        let mut workflow = automation.workflows.workflows[0].clone();
+        workflow.steps[0].detail =
+            "curl -H 'Authorization: sk-abcdefghijklmnopqrstuvwxyz0123'".to_string();
+        let workflow_id = workflow.id;
+        automation.workflows.workflows[0] = workflow.clone();
+        automation.event_automation.listeners[0].action = format!("workflow:{}", workflow_id);
+
+        let mut redaction = RedactionState::default();
+        let bundle = export(
+            "Compartido",
+            "2024-05-15 10:00:00",
+            &[&workflow],
+            &automation,
+            &mut redaction,
+            &RedactionConfig::default(),
+        );
+        assert_eq!(bundle.secrets.len(), 1);
+        let secret = bundle.secrets[0].name.clone();
+        assert!(!bundle.workflows[0].steps[0].detail.contains("sk-abc"));
+        assert_eq!(
+            secret_names(&bundle.workflows[0].steps[0].detail),
+            vec![secret.clone()]
+        );
+        assert_eq!(
+            bundle.listeners[0].action,
+            format!("workflow:{}", workflow.name)
+        );
+
+        let text = serde_json::to_string_pretty(&bundle).unwrap();
+        let parsed = parse(&text).unwrap();
+        assert_eq!(parsed, bundle);
+        // El workflow, su programación y el listener que lo lanza ya existen.
+        assert_eq!(bundle.schedules.len(), 1);
+        assert_eq!(conflicts(&parsed, &automation).len(), 3);
+
+        let workflows_before = automation.workflows.workflows.len();
+        let (summary, _) = apply(
+            &parsed,
+            ConflictResolution::Skip,
+            &BTreeMap::new(),
+            &mut automation,
+        );
+        assert_eq!((summary.added, summary.skipped), (0, 3));
+        assert_eq!(automation.workflows.workflows.len(), workflows_before);
+
+        let values = BTreeMap::from([(secret.clone(), "sk-nuevo".to_string())]);
+        let (summary, installed) = apply(
+            &parsed,
+            ConflictResolution::Rename,
+            &values,
+            &mut automation,
+        );
+        assert_eq!(summary.added, 3);
+        assert!(summary.missing_secrets.is_empty());
+        let imported = automation.workflows.workflows.last().unwrap();
+        assert_eq!(imported.name, format!("{} (importado)", workflow.name));
+        assert!(imported.steps[0].detail.contains("sk-nuevo"));
+        let task = automation.cron_board.tasks.last().unwrap();
+        assert_eq!(imported.linked_schedule, Some(task.id));
+        let listener = automation.event_automation.listeners.last().unwrap();
+        assert_eq!(listener.action, format!("workflow:{}", imported.id));
+        assert_eq!(
+            installed.listeners[0].action,
+            format!("workflow:{}", imported.name)
+        );
+        assert!(validate(&installed).is_ok());
+
+        let mut broken = parsed.clone();
+        broken.secrets.clear();
+        assert!(validate(&broken).is_err());
+        broken = parsed;
+        broken.format_version = FORMAT_VERSION + 1;
+        assert!(validate(&broken).is_err());
+    }
+}
@@ -0,0 +1,315 @@
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tokenizers::Tokenizer;
+
+use crate::state::{runtime, RemoteProviderKind};
+
+/// Repositorios de Hugging Face con el `tokenizer.json` de cada familia de modelos.
+const O200K_REPOSITORY: &str = "Xenova/gpt-4o";
+const CL100K_REPOSITORY: &str = "Xenova/gpt-4";
+const CLAUDE_REPOSITORY: &str = "Xenova/claude-tokenizer";
+
+/// Cuenta tokens para un modelo o familia de modelos concreta.
+pub trait TokenCounter: Send + Sync {
+    fn name(&self) -> &str;
+    fn count(&self, text: &str) -> usize;
+}
+
+/// Patrón de pre-tokenización equivalente al de `cl100k_base` (sin la aserción
+/// de lookahead final, que el motor de `regex` no admite).
+static CL100K_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i:'s|'t|'re|'ve|'m|'ll|'d)|[^\r\n\p{L}\p{N}]?\p{L}+|\p{N}{1,3}| ?[^\s\p{L}\p{N}]+[\r\n]*|\s*[\r\n]+|\s+",
+    )
+    .expect("patrón cl100k válido")
+});
+
+/// Estimación estilo tiktoken mientras no está cargado el vocabulario real.
+///
+/// Reproduce la pre-tokenización BPE de `cl100k_base` y estima cuántas fusiones
+/// sobreviven en cada fragmento: las palabras ASCII cortas suelen ser un único
+/// token, las largas se parten aproximadamente cada cuatro bytes y los
+/// caracteres no latinos consumen más tokens por carácter.
+#[derive(Default)]
+pub struct BpeTokenCounter;
+
+impl BpeTokenCounter {
+    fn piece_tokens(piece: &str) -> usize {
+        if piece.is_ascii() {
+            let trimmed = piece.trim_start();
+            let len = trimmed.len();
+            if len == 0 {
+                return 1;
+            }
+            if trimmed.chars().all(|ch| ch.is_ascii_alphabetic()) && len <= 7 {
+                return 1;
+            }
+            return len.div_ceil(4).max(1);
+        }
+
+        piece
+            .chars()
+            .map(|ch| match ch.len_utf8() {
+                1 | 2 => 1,
+                _ => 2,
+            })
+            .sum::<usize>()
+            .div_ceil(2)
+            .max(1)
+    }
+}
+
+impl TokenCounter for BpeTokenCounter {
+    fn name(&self) -> &str {
+        "BPE cl100k (estimado)"
+    }
+
+    fn count(&self, text: &str) -> usize {
+        CL100K_PATTERN
+            .find_iter(text)
+            .map(|piece| Self::piece_tokens(piece.as_str()))
+            .sum()
+    }
+}
+
+/// Aproximación publicada por Anthropic, unos 3,5 caracteres por token, para cuando no
+/// está cargado su tokenizer.
+#[derive(Default)]
+pub struct AnthropicTokenCounter;
+
+impl TokenCounter for AnthropicTokenCounter {
+    fn name(&self) -> &str {
+        "Anthropic (≈3,5 caracteres/token)"
+    }
+
+    fn count(&self, text: &str) -> usize {
+        let chars = text.chars().count();
+        (chars * 2).div_ceil(7)
+    }
+}
+
+/// Contador basado en el `tokenizer.json` del modelo local de Jarvis.
+pub struct HuggingFaceTokenCounter {
+    label: String,
+    tokenizer: Tokenizer,
+}
+
+impl HuggingFaceTokenCounter {
+    pub fn from_file(path: &Path, label: impl Into<String>) -> anyhow::Result<Self> {
+        let tokenizer = Tokenizer::from_file(path)
+            .map_err(|err| anyhow::anyhow!("No se pudo cargar el tokenizer {:?}: {}", path, err))?;
+        Ok(Self {
+            label: label.into(),
+            tokenizer,
+        })
+    }
+}
+
+impl TokenCounter for HuggingFaceTokenCounter {
+    fn name(&self) -> &str {
+        &self.label
+    }
+
+    fn count(&self, text: &str) -> usize {
+        match self.tokenizer.encode(text, false) {
+            Ok(encoding) => encoding.len(),
+            Err(_) => BpeTokenCounter.count(text),
+        }
+    }
+}
+
+/// Contador con el vocabulario real de una familia de modelos, publicado en Hugging Face.
+///
+/// El `tokenizer.json` se descarga en segundo plano con `load` y queda en la caché de
+/// `hf-hub`; hasta que está listo, o si no se puede descargar, cuenta con `fallback`.
+pub struct PretrainedTokenCounter {
+    label: &'static str,
+    repository: &'static str,
+    tokenizer: Arc<OnceLock<Tokenizer>>,
+    fallback: Box<dyn TokenCounter>,
+}
+
+impl PretrainedTokenCounter {
+    pub fn new(
+        label: &'static str,
+        repository: &'static str,
+        fallback: impl TokenCounter + 'static,
+    ) -> Self {
+        Self {
+            label,
+            repository,
+            tokenizer: Arc::new(OnceLock::new()),
+            fallback: Box::new(fallback),
+        }
+    }
+
+    /// Descarga y carga el tokenizer en el runtime compartido si aún no lo está.
+    pub fn load(&self) {
+        if self.is_loaded() {
+            return;
+        }
+        let repository = self.repository;
+        let tokenizer = Arc::clone(&self.tokenizer);
+        runtime::spawn_blocking(move || {
+            if let Ok(loaded) = Tokenizer::from_pretrained(repository, None) {
+                let _ = tokenizer.set(loaded);
+            }
+        });
+    }
+
+    /// Carga el tokenizer ya descargado en `path`, sin pasar por la red.
+    pub fn load_file(&self, path: &Path) -> anyhow::Result<()> {
+        let loaded = Tokenizer::from_file(path)
+            .map_err(|err| anyhow::anyhow!("No se pudo cargar el tokenizer {:?}: {}", path, err))?;
+        let _ = self.tokenizer.set(loaded);
+        Ok(())
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.tokenizer.get().is_some()
+    }
+}
+
+impl TokenCounter for PretrainedTokenCounter {
+    fn name(&self) -> &str {
+        if self.is_loaded() {
+            self.label
+        } else {
+            self.fallback.name()
+        }
+    }
+
+    fn count(&self, text: &str) -> usize {
+        self.tokenizer
+            .get()
+            .and_then(|tokenizer| tokenizer.encode(text, false).ok())
+            .map(|encoding| encoding.len())
+            .unwrap_or_else(|| self.fallback.count(text))
+    }
+}
+
+/// Punto de acceso único a los contadores de tokens de cada proveedor.
+pub struct TokenizerService {
+    openai: Arc<PretrainedTokenCounter>,
+    groq: Arc<PretrainedTokenCounter>,
+    anthropic: Arc<PretrainedTokenCounter>,
+    jarvis: Option<Arc<dyn TokenCounter>>,
+}
+
+impl Default for TokenizerService {
+    fn default() -> Self {
+        Self {
+            openai: Arc::new(PretrainedTokenCounter::new(
+                "BPE o200k",
+                O200K_REPOSITORY,
+                BpeTokenCounter,
+            )),
+            groq: Arc::new(PretrainedTokenCounter::new(
+                "BPE cl100k",
+                CL100K_REPOSITORY,
+                BpeTokenCounter,
+            )),
+            anthropic: Arc::new(PretrainedTokenCounter::new(
+                "Claude",
+                CLAUDE_REPOSITORY,
+                AnthropicTokenCounter,
+            )),
+            jarvis: None,
+        }
+    }
+}
+
+impl TokenizerService {
+    /// Descarga en segundo plano los tokenizers reales de los proveedores remotos. No se
+    /// hace al construir el servicio para que la CLI y las pruebas no dependan de la red.
+    pub fn load_pretrained(&self) {
+        self.openai.load();
+        self.groq.load();
+        self.anthropic.load();
+    }
+
+    pub fn for_provider(&self, provider: RemoteProviderKind) -> &dyn TokenCounter {
+        match provider {
+            RemoteProviderKind::Anthropic => self.anthropic.as_ref(),
+            RemoteProviderKind::OpenAi => self.openai.as_ref(),
+            RemoteProviderKind::Groq => self.groq.as_ref(),
+        }
+    }
+
+    /// Contador del modelo local; si no hay tokenizer cargado se usa el BPE genérico.
+    pub fn jarvis(&self) -> &dyn TokenCounter {
+        self.jarvis.as_deref().unwrap_or_else(|| self.groq.as_ref())
+    }
+
+    pub fn has_jarvis_tokenizer(&self) -> bool {
+        self.jarvis.is_some()
+    }
+
+    /// Registra el tokenizer del modelo instalado en `model_dir`, si existe.
+    pub fn load_jarvis(&mut self, model_dir: &Path, label: &str) -> anyhow::Result<()> {
+        let path = model_dir.join("tokenizer.json");
+        let counter = HuggingFaceTokenCounter::from_file(&path, format!("Jarvis · {}", label))?;
+        self.jarvis = Some(Arc::new(counter));
+        Ok(())
+    }
+
+    pub fn clear_jarvis(&mut self) {
+        self.jarvis = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tokenizer de palabras con un vocabulario mínimo, para contar sin descargar nada.
+    const WORD_LEVEL_TOKENIZER: &str = r#"{
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": null,
+        "pre_tokenizer": { "type": "Whitespace" },
+        "post_processor": null,
+        "decoder": null,
+        "model": {
+            "type": "WordLevel",
+            "vocab": { "[UNK]": 0, "hola": 1, "mundo": 2, "!": 3 },
+            "unk_token": "[UNK]"
+        }
+    }"#;
+
+    #[test]
+    fn pretrained_counter_uses_the_fallback_until_loaded() {
+        let counter = PretrainedTokenCounter::new("Palabras", "sin/red", AnthropicTokenCounter);
+        assert_eq!(counter.name(), AnthropicTokenCounter.name());
+        assert_eq!(counter.count("hola mundo!"), 4);
+
+        let path = std::env::temp_dir().join(format!("jmk-tokenizer-{}.json", std::process::id()));
+        std::fs::write(&path, WORD_LEVEL_TOKENIZER).unwrap();
+        counter.load_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(counter.is_loaded());
+        assert_eq!(counter.name(), "Palabras");
+        assert_eq!(counter.count("hola mundo!"), 3);
+        assert_eq!(counter.count("hola hola hola desconocida"), 4);
+    }
+
+    #[test]
+    #[ignore = "descarga los tokenizers de Hugging Face"]
+    fn pretrained_tokenizers_match_tiktoken_counts() {
+        for (repository, text, expected) in [
+            (CL100K_REPOSITORY, "hello world", 2),
+            (CL100K_REPOSITORY, "tiktoken is great!", 6),
+            (O200K_REPOSITORY, "hello world", 2),
+        ] {
+            let tokenizer = Tokenizer::from_pretrained(repository, None).unwrap();
+            let encoding = tokenizer.encode(text, false).unwrap();
+            assert_eq!(encoding.len(), expected, "{} · {}", repository, text);
+        }
+    }
+}
@@ -1,20 +1,44 @@
-use crate::api::{claude::AnthropicModel, github};
+use crate::api::{
+    claude::AnthropicModel, error::ProviderError, github, local::JarvisKnowledgeEntry,
+};
+use crate::config::{
+    ContextStrategy, EmbeddingBackend, JobLaneLimit, LayoutSnapshot, NotificationChannel,
+    NotificationSeverity, Playbook, PlaybookStep, QuickTestAssertion, QuickTestAssertionKind,
+    RedactionRule, ReportTemplate, SafetyAction, SafetyFilterRule, SmtpSecurity, StorageKeySource,
+    TextActionTemplate, TextActionsConfig, TranscriptionBackend, WebSearchBackend, WebhookKind,
+};
+use crate::i18n::{self, t, t_path, tf};
 use crate::local_providers::{LocalModelCard, LocalModelIdentifier, LocalModelProvider};
 use crate::state::{
-    feature::WorkbenchRegistry, format_bytes, AppState, AutomationWorkflow, ChatMessage,
-    DebugLogLevel, InstalledLocalModel, IntegrationStatus, KnowledgeResourceCard, LogStatus,
-    MainTab, MainView, PreferencePanel, ProjectResourceCard, ProjectResourceKind, ReminderStatus,
+    benchmark, budget, code_viewer,
+    commands::fill_template,
+    conversations, cron_calendar, email,
+    feature::WorkbenchRegistry,
+    follow_ups, format_bytes, git_assist,
+    job_queue::{JobInfo, JobStatus},
+    keybindings, language, mqtt, notifications, permissions, playbooks, project_context, quantize,
+    quick_tests, reports, safety, short_revision, structured, task_chain, telegram, vault,
+    web_search,
+    workflow_bundle::ConflictResolution,
+    AppState, AttachmentKind, AuditEventKind, AuditVerification, AutomationWorkflow, BatchTarget,
+    ChatMessage, CodeAnchor, CommandInvocation, CostConfirmation, CustomCommandAction,
+    CustomCommandBehavior, CustomCommandDraft, DebugLogLevel, InstalledLocalModel,
+    IntegrationStatus, KnowledgeResourceCard, LocalOnlyConfirmation, LogStatus, MainTab, MainView,
+    MessageQuote, ModelUpdateStatus, NavigationTarget, NotificationSource, PreferencePanel,
+    PreviewSegment, ProjectResourceCard, ProjectResourceKind, ProviderCallDispatch, ReminderStatus,
     RemoteModelCard, RemoteModelKey, RemoteProviderKind, ResourceSection, ScheduledTaskStatus,
-    SyncHealth, WorkflowStatus, WorkflowStepKind,
+    ShortcutAction, SyncHealth, UninstallMode, WorkflowStatus, WorkflowStepKind,
 };
 use anyhow::Result;
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Datelike, Local, Utc};
 use eframe::egui::{self, Color32, RichText, Spinner};
 use egui_extras::{Column, TableBuilder};
-use std::path::Path;
-use vscode_shell::components::{self, MainContentModel, MainContentProps, MainContentTab};
+use std::path::{Path, PathBuf};
+use vscode_shell::components::{
+    self, MainContentAction, MainContentModel, MainContentProps, MainContentTab,
+};
 
-use super::{logs, tabs, theme};
+use super::{data_table, logs, tabs, theme};
 use crate::ui::{
     layout_bridge::shell_theme,
     theme::{ThemePreset, ThemeTokens},
@@ -27,7 +51,9 @@ const ICON_ASSISTANT: &str = "\u{f544}"; // robot
 const ICON_CLOCK: &str = "\u{f017}"; // clock
 const ICON_COPY: &str = "\u{f0c5}"; // copy
 const ICON_QUOTE: &str = "\u{f10e}"; // quote-right
+const ICON_REPLY: &str = "\u{f3e5}"; // reply
 const ICON_PIN: &str = "\u{f08d}"; // thumb-tack
+const ICON_REUSE: &str = "\u{f0ea}"; // paste
 const ICON_SEND: &str = "\u{f04b}"; // play
 const ICON_CODE: &str = "\u{f121}"; // code
 const ICON_PREMIUM: &str = "\u{f521}"; // crown
@@ -44,6 +70,8 @@ const ICON_FOLDER: &str = "\u{f07c}"; // folder-open
 const ICON_FILE_DOC: &str = "\u{f15b}"; // file
 const ICON_CALENDAR: &str = "\u{f073}"; // calendar-alt
 const ICON_REPEAT: &str = "\u{f021}"; // sync-alt
+const ICON_EDIT: &str = "\u{f044}"; // edit
+const ICON_SHUFFLE: &str = "\u{f074}"; // random
 const ICON_PLAY: &str = "\u{f04b}"; // play
 const ICON_STOP: &str = "\u{f04d}"; // stop
 const ICON_BUG: &str = "\u{f188}"; // bug
@@ -52,6 +80,13 @@ const ICON_BOOK: &str = "\u{f02d}"; // book
 const ICON_SLIDERS: &str = "\u{f1de}"; // sliders-h
 const ICON_DATABASE: &str = "\u{f1c0}"; // database
 const ICON_CHART: &str = "\u{f080}"; // line-chart
+const ICON_KEY: &str = "\u{f084}"; // key
+const ICON_WARNING: &str = "\u{f071}"; // exclamation-triangle
+const ICON_TRASH: &str = "\u{f1f8}"; // trash
+const ICON_FORK: &str = "\u{f126}"; // code-branch
+const ICON_FLAG: &str = "\u{f024}"; // flag
+const ICON_SHIELD: &str = "\u{f3ed}"; // shield-alt
+const ICON_GLOBE: &str = "\u{f0ac}"; // globe
 
 const QUICK_MENTIONS: [(&str, &str); 3] =
     [("@claude", "@claude"), ("@gpt", "@gpt"), ("@groq", "@groq")];
@@ -63,10 +98,32 @@ const QUICK_COMMANDS: [(&str, &str); 4] = [
     ("@jarvis test", "@jarvis test"),
 ];
 
+const COMPOSER_HEIGHT_RANGE: std::ops::RangeInclusive<f32> = 120.0..=480.0;
+
 enum PendingChatAction {
     Mention(String),
     Quote(String),
     Reuse(String),
+    Regenerate {
+        index: usize,
+        provider: Option<RemoteProviderKind>,
+    },
+    EditResend(usize),
+    Reply(usize),
+    TogglePin(usize),
+    Fork(usize),
+    Focus(usize),
+    OpenPreference(PreferencePanel),
+    OpenCodeAnchor(CodeAnchor),
+    ShowPatch {
+        anchor: CodeAnchor,
+        diff: String,
+    },
+    TextAction {
+        index: usize,
+        action: usize,
+    },
+    FollowUp(String),
 }
 
 fn desired_main_width(available_width: f32) -> f32 {
@@ -125,8 +182,8 @@ impl MainContentModel for AppMainContent<'_> {
 
         if let Some(view) = self.state.workbench_view(self.state.active_main_view) {
             let metadata = view.metadata(self.state);
-            props.title = metadata.title;
-            props.subtitle = metadata.subtitle;
+            props.title = metadata.title.map(|title| t_path(&title));
+            props.subtitle = metadata.subtitle.map(|subtitle| t(&subtitle).to_string());
             props.actions = view.actions(self.state);
             props.tabs = view.tabs(self.state);
             props.active_tab = view.active_tab(self.state);
@@ -145,6 +202,14 @@ impl MainContentModel for AppMainContent<'_> {
                 let next = !self.state.layout.resource_collapsed();
                 self.state.layout.emit_resource_signal(next);
             }
+            "toggle-popout" => {
+                let view = self.state.active_main_view;
+                if self.state.is_popped_out(view) {
+                    self.state.dock_view(view);
+                } else {
+                    self.state.pop_out_view(view);
+                }
+            }
             _ => {
                 let active_view = self.state.active_main_view;
                 self.state
@@ -173,6 +238,20 @@ impl MainContentModel for AppMainContent<'_> {
 
     fn show_content(&mut self, ui: &mut egui::Ui) {
         let active_view = self.state.active_main_view;
+        // La vista ya se dibuja en su propia ventana; dibujarla aquí duplicaría sus widgets.
+        if self.state.is_popped_out(active_view) {
+            ui.vertical_centered(|ui| {
+                ui.add_space(48.0);
+                ui.label(
+                    RichText::new(t("Esta vista está abierta en otra ventana."))
+                        .color(theme::color_text_weak()),
+                );
+                if ui.button(t("Traer a la ventana principal")).clicked() {
+                    self.state.dock_view(active_view);
+                }
+            });
+            return;
+        }
         if self
             .state
             .with_workbench_view_mut(active_view, |view, state| {
@@ -190,12 +269,88 @@ impl MainContentModel for AppMainContent<'_> {
     }
 }
 
+/// Acciones comunes más la que abre la vista en una ventana aparte o la devuelve.
+fn popout_actions(state: &AppState, view: MainView) -> Vec<MainContentAction> {
+    let mut actions = default_layout_actions(state);
+    actions.push(MainContentAction {
+        id: "toggle-popout".into(),
+        label: t(if state.is_popped_out(view) {
+            "Traer a la ventana principal"
+        } else {
+            "Abrir en ventana aparte"
+        })
+        .into(),
+        icon: Some("🗗".into()),
+        enabled: true,
+    });
+    actions
+}
+
+/// Dibuja cada vista abierta en ventana aparte como viewport propio. Comparten el mismo
+/// `AppState` que la ventana principal, así que los cambios se ven en ambas a la vez.
+pub fn draw_popout_windows(ctx: &egui::Context, state: &mut AppState) {
+    for popout in state.config.layout.popouts.clone() {
+        let title = state
+            .workbench_view(popout.view)
+            .and_then(|view| view.metadata(state).title)
+            .map(|title| t_path(&title))
+            .unwrap_or_default();
+        let mut builder = egui::ViewportBuilder::default()
+            .with_title(format!("{} · JungleMonkAI", title))
+            .with_inner_size(popout.size);
+        if let Some(position) = popout.position {
+            builder = builder.with_position(position);
+        }
+
+        let viewport = egui::ViewportId::from_hash_of(("popout", popout.view));
+        ctx.show_viewport_immediate(viewport, builder, |ctx, class| {
+            let render = |ui: &mut egui::Ui, state: &mut AppState| {
+                state.with_workbench_view_mut(popout.view, |view, state| view.render(ui, state));
+            };
+
+            // Sin soporte de varias ventanas en el backend se muestra flotando en la principal.
+            if class == egui::ViewportClass::Embedded {
+                let mut open = true;
+                egui::Window::new(&title)
+                    .id(egui::Id::new(("popout", popout.view)))
+                    .open(&mut open)
+                    .default_size(popout.size)
+                    .show(ctx, |ui| render(ui, state));
+                if !open {
+                    state.dock_view(popout.view);
+                }
+                return;
+            }
+
+            egui::CentralPanel::default().show(ctx, |ui| render(ui, state));
+            let (close_requested, outer, inner, dragging) = ctx.input(|input| {
+                let info = input.viewport();
+                (
+                    info.close_requested(),
+                    info.outer_rect,
+                    info.inner_rect,
+                    input.pointer.any_down(),
+                )
+            });
+            if close_requested {
+                state.dock_view(popout.view);
+            } else if let (Some(outer), Some(inner), false) = (outer, inner, dragging) {
+                state.update_popout_geometry(
+                    popout.view,
+                    [outer.min.x, outer.min.y],
+                    [inner.width(), inner.height()],
+                );
+            }
+        });
+    }
+}
+
 fn main_section_tabs() -> Vec<MainContentTab> {
     tabs::CHAT_SECTION_TABS
         .iter()
         .map(|definition| MainContentTab {
             id: tab_id(definition.id),
-            label: definition.label.to_string(),
+            label: t(definition.label).to_string(),
             icon: definition.icon.map(|icon| icon.to_string()),
         })
         .collect()
@@ -211,6 +366,10 @@ impl WorkbenchView for ChatWorkbenchView {
         )
     }
 
+    fn actions(&self, state: &AppState) -> Vec<MainContentAction> {
+        popout_actions(state, MainView::ChatMultimodal)
+    }
+
     fn tabs(&self, _state: &AppState) -> Vec<MainContentTab> {
         main_section_tabs()
     }
@@ -307,6 +466,10 @@ impl WorkbenchView for DebugWorkbenchView {
         )
     }
 
+    fn actions(&self, state: &AppState) -> Vec<MainContentAction> {
+        popout_actions(state, MainView::DebugConsole)
+    }
+
     fn tabs(&self, _state: &AppState) -> Vec<MainContentTab> {
         main_section_tabs()
     }
@@ -383,6 +546,67 @@ pub fn register_resource_workbench_view(registry: &mut WorkbenchRegistry) {
     registry.register_view(MainView::ResourceBrowser, ResourceWorkbenchView);
 }
 
+struct BatchEvalWorkbenchView;
+
+impl WorkbenchView for BatchEvalWorkbenchView {
+    fn metadata(&self, _state: &AppState) -> WorkbenchMetadata {
+        WorkbenchMetadata::new(
+            Some("Evaluación por lotes".into()),
+            Some("Compara respuestas, latencias y tokens entre proveedores".into()),
+        )
+    }
+
+    fn render(&self, ui: &mut egui::Ui, state: &mut AppState) {
+        draw_batch_eval_view(ui, state);
+    }
+}
+
+pub fn register_batch_eval_workbench_view(registry: &mut WorkbenchRegistry) {
+    registry.register_view(MainView::BatchEvaluation, BatchEvalWorkbenchView);
+}
+
+struct AuditWorkbenchView;
+
+impl WorkbenchView for AuditWorkbenchView {
+    fn metadata(&self, _state: &AppState) -> WorkbenchMetadata {
+        WorkbenchMetadata::new(
+            Some("Auditoría".into()),
+            Some("Efectos externos registrados en cada conversación".into()),
+        )
+    }
+
+    fn render(&self, ui: &mut egui::Ui, state: &mut AppState) {
+        draw_audit_view(ui, state);
+    }
+}
+
+pub fn register_audit_workbench_view(registry: &mut WorkbenchRegistry) {
+    registry.register_view(MainView::Audit, AuditWorkbenchView);
+}
+
+struct CodeViewerWorkbenchView;
+
+impl WorkbenchView for CodeViewerWorkbenchView {
+    fn metadata(&self, state: &AppState) -> WorkbenchMetadata {
+        WorkbenchMetadata::new(
+            Some("Explorador de código".into()),
+            state
+                .code_viewer
+                .project
+                .clone()
+                .or_else(|| Some("Pregunta al modelo por líneas de tus proyectos locales".into())),
+        )
+    }
+
+    fn render(&self, ui: &mut egui::Ui, state: &mut AppState) {
+        draw_code_viewer(ui, state);
+    }
+}
+
+pub fn register_code_viewer_workbench_view(registry: &mut WorkbenchRegistry) {
+    registry.register_view(MainView::CodeViewer, CodeViewerWorkbenchView);
+}
+
 fn tab_id(tab: MainTab) -> String {
     match tab {
         MainTab::Chat => "tab:chat",
@@ -425,14 +649,22 @@ fn draw_chat_view(ui: &mut egui::Ui, state: &mut AppState) {
                 content_ui.set_min_height(rect.height());
                 content_ui.set_clip_rect(rect);
 
-                egui::TopBottomPanel::bottom("chat_input_panel")
-                    .resizable(false)
+                let mut composer = egui::TopBottomPanel::bottom("chat_input_panel")
+                    .resizable(true)
+                    .height_range(COMPOSER_HEIGHT_RANGE)
                     .show_separator_line(false)
-                    .frame(egui::Frame::none())
-                    .show_inside(&mut content_ui, |ui| {
-                        ui.add_space(8.0);
-                        draw_chat_input(ui, state);
-                    });
+                    .frame(egui::Frame::none());
+                if state.composer_height > 0.0 {
+                    composer = composer.default_height(state.composer_height);
+                }
+                if std::mem::take(&mut state.composer_height_sync) {
+                    composer = composer.exact_height(state.composer_height);
+                }
+                let composer_response = composer.show_inside(&mut content_ui, |ui| {
+                    ui.add_space(8.0);
+                    draw_chat_input(ui, state);
+                });
+                state.composer_height = composer_response.response.rect.height();
 
                 egui::CentralPanel::default()
                     .frame(egui::Frame::none())
@@ -462,7 +694,7 @@ fn draw_preferences_view(ui: &mut egui::Ui, state: &mut AppState) {
                 let breadcrumb_text = if metadata.breadcrumb.is_empty() {
                     String::new()
                 } else {
-                    metadata.breadcrumb.join(" › ")
+                    t_path(&metadata.breadcrumb.join(" › "))
                 };
 
                 let heading = metadata
@@ -504,11 +736,11 @@ fn draw_preferences_view(ui: &mut egui::Ui, state: &mut AppState) {
                 }
 
                 ui.heading(
-                    RichText::new(heading)
+                    RichText::new(t(heading))
                         .color(theme::color_text_primary())
                         .strong(),
                 );
-                ui.label(RichText::new(metadata.description).color(theme::color_text_weak()));
+                ui.label(RichText::new(t(metadata.description)).color(theme::color_text_weak()));
                 ui.add_space(12.0);
 
                 let active_tab_index = *active_tab_entry;
@@ -545,6 +777,34 @@ fn preference_tab_definitions(panel: PreferencePanel) -> Vec<tabs::TabDefinition
                 tooltip: "Revisa la actividad reciente de los comandos",
             },
         ],
+        PreferencePanel::CustomizationMemory => vec![
+            tabs::TabDefinition {
+                id: 0,
+                label: "Memoria",
+                icon: Some(ICON_DATABASE),
+                tooltip: "Ajusta la memoria contextual y su retención",
+            },
+            tabs::TabDefinition {
+                id: 1,
+                label: "Contexto",
+                icon: Some(ICON_SLIDERS),
+                tooltip: "Define cuánto historial acompaña a cada prompt",
+            },
+        ],
+        PreferencePanel::LocalJarvis => vec![
+            tabs::TabDefinition {
+                id: 0,
+                label: "Configuration",
+                icon: Some(ICON_SLIDERS),
+                tooltip: "Configura el modelo local y el arranque de Jarvis",
+            },
+            tabs::TabDefinition {
+                id: 1,
+                label: "Rendimiento",
+                icon: Some(ICON_ACTIVITY),
+                tooltip: "Compara tiempos de carga, velocidad y memoria por modelo",
+            },
+        ],
         PreferencePanel::ProvidersAnthropic => vec![
             tabs::TabDefinition {
                 id: 0,
@@ -656,7 +916,7 @@ fn draw_resource_view(ui: &mut egui::Ui, state: &mut AppState) {
                     let breadcrumb_text = if metadata.breadcrumb.is_empty() {
                         String::new()
                     } else {
-                        metadata.breadcrumb.join(" › ")
+                        t_path(&metadata.breadcrumb.join(" › "))
                     };
 
                     let heading = metadata
@@ -693,12 +953,12 @@ fn draw_resource_view(ui: &mut egui::Ui, state: &mut AppState) {
                     }
 
                     ui.heading(
-                        RichText::new(heading)
+                        RichText::new(t(heading))
                             .color(theme::color_text_primary())
                             .strong(),
                     );
                     ui.label(
-                        RichText::new(metadata.description)
+                        RichText::new(t(metadata.description))
                             .color(theme::color_text_weak()),
                     );
                     ui.add_space(12.0);
@@ -713,9 +973,9 @@ fn draw_resource_view(ui: &mut egui::Ui, state: &mut AppState) {
                     ui.vertical_centered(|ui| {
                         ui.add_space(80.0);
                         ui.label(
-                            RichText::new(
+                            RichText::new(t(
                                 "Selecciona un recurso en el panel izquierdo para explorar su contenido.",
-                            )
+                            ))
                             .color(theme::color_text_weak()),
                         );
                     });
@@ -760,28 +1020,36 @@ fn draw_cron_view(ui: &mut egui::Ui, state: &mut AppState) {
                 ui.add_space(12.0);
                 draw_cron_summary(ui, state);
                 ui.add_space(10.0);
+                draw_cron_calendar(ui, state);
+                ui.add_space(10.0);
                 draw_workflow_panel(ui, state);
                 ui.add_space(10.0);
                 draw_reminder_panel(ui, state);
                 ui.add_space(10.0);
+                draw_knowledge_reindex_panel(ui, state);
+                ui.add_space(10.0);
                 draw_cron_filters(ui, state);
                 ui.add_space(10.0);
                 draw_cron_table(ui, state);
 
-                if let Some(task) = state.automation.cron_board.selected_task() {
+                if let Some(task) = state.automation.cron_board.selected_task().cloned() {
                     ui.add_space(14.0);
-                    draw_cron_task_detail(ui, state, task);
+                    draw_cron_task_detail(ui, state, &task);
+                    ui.add_space(10.0);
+                    draw_cron_task_chain(ui, state, &task);
                 }
 
                 ui.add_space(14.0);
                 draw_listener_panel(ui, state);
                 ui.add_space(14.0);
+                draw_file_watcher_panel(ui, state);
+                ui.add_space(14.0);
                 draw_integration_panel(ui, state);
             });
     });
 }
 
-fn draw_activity_view(ui: &mut egui::Ui, state: &AppState) {
+fn draw_activity_view(ui: &mut egui::Ui, state: &mut AppState) {
     with_centered_main_surface(ui, |ui| {
         logs::draw_logs_view(ui, state);
     });
@@ -824,5061 +1092,13787 @@ fn draw_debug_console_view(ui: &mut egui::Ui, state: &mut AppState) {
                 ui.add_space(10.0);
                 draw_debug_filters(ui, state);
                 ui.add_space(10.0);
+                draw_state_journal(ui, state);
+                ui.add_space(10.0);
                 draw_debug_entries(ui, state);
             });
     });
 }
 
-fn draw_cron_summary(ui: &mut egui::Ui, state: &AppState) {
-    let total_enabled = state
-        .automation
-        .cron_board
-        .tasks
-        .iter()
-        .filter(|task| task.enabled)
-        .count();
-    let running = state
-        .automation
-        .cron_board
-        .status_count(ScheduledTaskStatus::Running);
-    let failing = state
-        .automation
-        .cron_board
-        .status_count(ScheduledTaskStatus::Failed);
-
-    ui.horizontal(|ui| {
-        summary_chip(
-            ui,
-            ICON_REPEAT,
-            "Activas",
-            total_enabled,
-            theme::color_primary(),
-            &state.theme,
-        );
-        summary_chip(
-            ui,
-            ICON_PLAY,
-            "En ejecución",
-            running,
-            Color32::from_rgb(64, 172, 255),
-            &state.theme,
-        );
-        summary_chip(
-            ui,
-            ICON_STOP,
-            "Con errores",
-            failing,
-            theme::color_danger(),
-            &state.theme,
-        );
-    });
-}
-
-fn summary_chip(
-    ui: &mut egui::Ui,
-    icon: &str,
-    label: &str,
-    value: usize,
-    color: Color32,
-    tokens: &ThemeTokens,
-) {
-    egui::Frame::none()
-        .fill(Color32::from_rgb(34, 36, 42))
-        .stroke(theme::subtle_border(tokens))
-        .rounding(egui::Rounding::same(12.0))
-        .inner_margin(egui::Margin::symmetric(16.0, 12.0))
-        .show(ui, |ui| {
-            ui.horizontal(|ui| {
-                ui.label(
-                    RichText::new(icon)
-                        .font(theme::icon_font(16.0))
-                        .color(color),
-                );
-                ui.vertical(|ui| {
+fn draw_batch_eval_view(ui: &mut egui::Ui, state: &mut AppState) {
+    with_centered_main_surface(ui, |ui| {
+        egui::Frame::none()
+            .fill(Color32::from_rgb(26, 28, 32))
+            .stroke(theme::subtle_border(&state.theme))
+            .inner_margin(egui::Margin::same(20.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 10.0;
                     ui.label(
-                        RichText::new(label)
-                            .color(theme::color_text_weak())
-                            .size(11.0),
+                        RichText::new(ICON_COMPARE)
+                            .font(theme::icon_font(18.0))
+                            .color(theme::color_primary()),
                     );
-                    ui.label(
-                        RichText::new(value.to_string())
+                    ui.heading(
+                        RichText::new(t("Evaluación por lotes"))
                             .color(theme::color_text_primary())
-                            .size(16.0)
                             .strong(),
                     );
                 });
+                ui.label(
+                    RichText::new(t(
+                        "Envía un fichero JSONL o CSV de prompts a varios destinos y guarda las respuestas con sus métricas.",
+                    ))
+                    .color(theme::color_text_weak()),
+                );
+
+                ui.add_space(10.0);
+                draw_batch_eval_form(ui, state);
+                ui.add_space(12.0);
+                draw_batch_eval_summary(ui, state);
+                ui.add_space(12.0);
+                draw_batch_eval_results(ui, state);
+                ui.add_space(16.0);
+                draw_benchmark_section(ui, state);
             });
-        });
+    });
 }
 
-fn draw_workflow_panel(ui: &mut egui::Ui, state: &mut AppState) {
-    egui::Frame::none()
-        .fill(Color32::from_rgb(34, 36, 42))
-        .stroke(theme::subtle_border(&state.theme))
-        .rounding(egui::Rounding::same(14.0))
-        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
-        .show(ui, |ui| {
-            ui.horizontal(|ui| {
-                ui.spacing_mut().item_spacing.x = 10.0;
-                ui.label(
-                    RichText::new(ICON_LIGHTNING)
-                        .font(theme::icon_font(16.0))
-                        .color(theme::color_primary()),
-                );
-                ui.heading(
-                    RichText::new("Workflows automatizados")
-                        .color(theme::color_text_primary())
-                        .strong(),
+fn draw_benchmark_section(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.separator();
+    ui.heading(
+        RichText::new(t("Benchmark de latencia"))
+            .color(theme::color_text_primary())
+            .strong(),
+    );
+    ui.label(
+        RichText::new(t(
+            "Envía prompts corto, medio y largo a cada destino, mide TTFB, latencia y tokens/s y actualiza la latencia de las fichas del catálogo. También disponible con /benchmark.",
+        ))
+        .color(theme::color_text_weak()),
+    );
+    ui.add_space(8.0);
+
+    let running = state.benchmark.is_running();
+    ui.add_enabled_ui(!running, |ui| {
+        egui::Grid::new("benchmark_form")
+            .num_columns(2)
+            .spacing(egui::vec2(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.label(t("Destinos"));
+                ui.horizontal_wrapped(|ui| {
+                    for target in BatchTarget::ALL {
+                        let mut selected = state.benchmark.targets.contains(&target);
+                        if ui.checkbox(&mut selected, t(target.label())).changed() {
+                            state.benchmark.toggle_target(target);
+                        }
+                    }
+                });
+                ui.end_row();
+
+                ui.label(t("Iteraciones"));
+                ui.add(
+                    egui::DragValue::new(&mut state.benchmark.iterations)
+                        .clamp_range(1..=benchmark::MAX_ITERATIONS),
                 );
-                ui.add_space(ui.available_width());
-                ui.checkbox(
-                    &mut state.automation.workflows.show_only_pinned,
-                    "Solo favoritos",
-                )
-                .on_hover_text("Filtra workflows fijados para acceso rápido");
+                ui.end_row();
             });
-            ui.label(
-                RichText::new(
-                    "Encadena modelos remotos con scripts locales y orquesta pipelines desde el chat.",
-                )
-                .color(theme::color_text_weak())
-                .size(12.0),
-            );
+    });
 
-            ui.add_space(8.0);
-            let indices = state.automation.workflows.filtered_indices();
-            if indices.is_empty() {
-                ui.colored_label(
-                    theme::color_text_weak(),
-                    "No hay workflows guardados con los filtros actuales.",
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        if running {
+            if ui
+                .add(theme::secondary_button(
+                    RichText::new(format!("{} {}", ICON_STOP, t("Cancelar")))
+                        .font(theme::icon_font(13.0)),
+                    &state.theme,
+                ))
+                .clicked()
+            {
+                state.cancel_benchmark();
+            }
+            if let Some((done, total)) = state.benchmark.progress() {
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                        .desired_width(240.0)
+                        .text(format!("{}/{}", done, total)),
                 );
-                return;
             }
+        } else if ui
+            .add(theme::primary_button(
+                RichText::new(format!("{} {}", ICON_PLAY, t("Ejecutar benchmark")))
+                    .font(theme::icon_font(13.0)),
+                &state.theme,
+            ))
+            .clicked()
+        {
+            let targets = state.benchmark.targets.clone();
+            let iterations = state.benchmark.iterations;
+            if let Err(err) = state.start_benchmark(&targets, iterations) {
+                state.benchmark.status = Some(err);
+            }
+        }
+    });
 
-            for index in indices {
-                let workflow_snapshot = state.automation.workflows.workflows[index].clone();
-                draw_workflow_card(ui, state, index, &workflow_snapshot);
-                ui.add_space(8.0);
+    if let Some(status) = &state.benchmark.status {
+        ui.add_space(6.0);
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+
+    let Some(record) = state.benchmark.shown_run() else {
+        return;
+    };
+    let summaries = record.summaries();
+    if summaries.is_empty() {
+        return;
+    }
+    ui.add_space(8.0);
+    ui.label(
+        RichText::new(tf(
+            "Ejecución del {0} · {1} iteraciones por prompt",
+            &[&record.started_at, &record.iterations.to_string()],
+        ))
+        .color(theme::color_text_weak())
+        .size(11.0),
+    );
+    let millis = |value: Option<u64>| value.map_or("—".to_string(), |ms| format!("{} ms", ms));
+    egui::Grid::new("benchmark_summary")
+        .num_columns(9)
+        .striped(true)
+        .spacing(egui::vec2(18.0, 6.0))
+        .show(ui, |ui| {
+            for header in [
+                "Destino", "Modelo", "Prompt", "Llamadas", "Errores", "TTFB", "Latencia", "p90",
+                "Tokens/s",
+            ] {
+                ui.label(
+                    RichText::new(t(header))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            }
+            ui.end_row();
+
+            for summary in &summaries {
+                let label = BatchTarget::from_code(&summary.target)
+                    .map(|target| t(target.label()).to_string())
+                    .unwrap_or_else(|| summary.target.clone());
+                let overall = summary.size.is_none();
+                let strong = |text: String| {
+                    let text = RichText::new(text).color(theme::color_text_primary());
+                    if overall {
+                        text.strong()
+                    } else {
+                        text
+                    }
+                };
+                ui.label(strong(label));
+                ui.label(record.model_for(&summary.target).unwrap_or("—"));
+                ui.label(strong(
+                    t(summary.size.map_or("Todos", |size| size.label())).to_string(),
+                ));
+                ui.label(summary.runs.to_string());
+                let failures = RichText::new(summary.failures.to_string());
+                ui.label(if summary.failures > 0 {
+                    failures.color(theme::color_danger())
+                } else {
+                    failures
+                });
+                ui.label(millis(summary.ttfb_ms));
+                ui.label(strong(millis(summary.total_ms)));
+                ui.label(millis(summary.p90_total_ms));
+                ui.label(
+                    summary
+                        .tokens_per_second
+                        .map_or("—".to_string(), |rate| format!("{:.1}", rate)),
+                );
+                ui.end_row();
             }
         });
 }
 
-fn draw_workflow_card(
-    ui: &mut egui::Ui,
-    state: &mut AppState,
-    index: usize,
-    workflow: &AutomationWorkflow,
-) {
-    egui::Frame::none()
-        .fill(Color32::from_rgb(28, 30, 36))
-        .stroke(theme::subtle_border(&state.theme))
-        .rounding(egui::Rounding::same(12.0))
-        .inner_margin(egui::Margin::symmetric(14.0, 12.0))
-        .show(ui, |ui| {
-            ui.vertical(|ui| {
-                ui.horizontal(|ui| {
-                    ui.heading(
-                        RichText::new(&workflow.name)
-                            .color(theme::color_text_primary())
-                            .size(15.0)
-                            .strong(),
-                    );
-                    if workflow.pinned {
-                        ui.label(
-                            RichText::new(ICON_STAR)
-                                .font(theme::icon_font(14.0))
-                                .color(Color32::from_rgb(255, 196, 0)),
-                        );
+fn draw_batch_eval_form(ui: &mut egui::Ui, state: &mut AppState) {
+    let running = state.batch_eval.is_running();
+    ui.add_enabled_ui(!running, |ui| {
+        egui::Grid::new("batch_eval_form")
+            .num_columns(2)
+            .spacing(egui::vec2(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.label(t("Fichero de prompts"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.batch_eval.prompts_path)
+                        .hint_text("prompts.jsonl")
+                        .desired_width(320.0),
+                );
+                ui.end_row();
+
+                ui.label(t("Fichero de resultados"));
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.batch_eval.output_path)
+                        .hint_text("batch_results.jsonl")
+                        .desired_width(320.0),
+                );
+                ui.end_row();
+
+                ui.label(t("Destinos"));
+                ui.horizontal_wrapped(|ui| {
+                    for target in BatchTarget::ALL {
+                        let mut selected = state.batch_eval.targets.contains(&target);
+                        if ui.checkbox(&mut selected, t(target.label())).changed() {
+                            state.batch_eval.toggle_target(target);
+                        }
                     }
-                    ui.add_space(ui.available_width());
-                    ui.label(
-                        RichText::new(workflow.status.label())
-                            .color(workflow_status_color(workflow.status))
-                            .monospace()
-                            .size(11.0),
-                    );
                 });
+                ui.end_row();
+
+                ui.label(t("Caché"));
+                ui.checkbox(
+                    &mut state.batch_eval.bypass_cache,
+                    t("Ignorar respuestas en caché"),
+                );
+                ui.end_row();
+            });
+    });
+
+    ui.add_space(8.0);
+    ui.horizontal(|ui| {
+        if running {
+            if ui
+                .add(theme::secondary_button(
+                    RichText::new(format!("{} {}", ICON_STOP, t("Cancelar")))
+                        .font(theme::icon_font(13.0)),
+                    &state.theme,
+                ))
+                .clicked()
+            {
+                state.cancel_batch_evaluation();
+            }
+            if let Some((done, total)) = state.batch_eval.progress() {
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                        .desired_width(240.0)
+                        .text(format!("{}/{}", done, total)),
+                );
+            }
+        } else if ui
+            .add(theme::primary_button(
+                RichText::new(format!("{} {}", ICON_PLAY, t("Ejecutar evaluación")))
+                    .font(theme::icon_font(13.0)),
+                &state.theme,
+            ))
+            .clicked()
+        {
+            if let Err(err) = state.start_batch_evaluation() {
+                state.batch_eval.status = Some(err);
+            }
+        }
+    });
+
+    if let Some(status) = &state.batch_eval.status {
+        ui.add_space(6.0);
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+}
+
+fn draw_batch_eval_summary(ui: &mut egui::Ui, state: &AppState) {
+    let summaries = state.batch_eval.summary();
+    if summaries.is_empty() {
+        return;
+    }
 
+    egui::Grid::new("batch_eval_summary")
+        .num_columns(7)
+        .striped(true)
+        .spacing(egui::vec2(18.0, 6.0))
+        .show(ui, |ui| {
+            for header in [
+                "Destino",
+                "Prompts",
+                "Errores",
+                "Latencia media",
+                "p95",
+                "Tokens de entrada",
+                "Tokens de salida",
+            ] {
                 ui.label(
-                    RichText::new(&workflow.description)
+                    RichText::new(t(header))
                         .color(theme::color_text_weak())
-                        .size(12.0),
+                        .size(11.0),
                 );
-
-                ui.add_space(6.0);
-                ui.horizontal(|ui| {
-                    ui.label(
-                        RichText::new(format!("Disparador: {}", workflow.trigger.label()))
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                    );
-                    if let Some(command) = &workflow.chat_command {
-                        ui.add_space(16.0);
-                        ui.label(
-                            RichText::new(format!("Comando: {}", command))
-                                .color(theme::color_text_primary())
-                                .monospace()
-                                .size(11.0),
-                        );
-                    }
-                    if let Some(cron_id) = workflow.linked_schedule {
-                        ui.add_space(16.0);
-                        ui.label(
-                            RichText::new(format!("Vinculado a tarea #{cron_id}"))
-                                .color(theme::color_text_weak())
-                                .size(11.0),
-                        );
-                    }
+            }
+            ui.end_row();
+
+            for summary in &summaries {
+                let label = BatchTarget::from_code(&summary.target)
+                    .map(|target| t(target.label()).to_string())
+                    .unwrap_or_else(|| summary.target.clone());
+                ui.label(RichText::new(label).color(theme::color_text_primary()));
+                ui.label(summary.total.to_string());
+                let failures = RichText::new(summary.failures.to_string());
+                ui.label(if summary.failures > 0 {
+                    failures.color(theme::color_danger())
+                } else {
+                    failures
                 });
+                ui.label(format!("{} ms", summary.average_latency_ms));
+                ui.label(format!("{} ms", summary.p95_latency_ms));
+                ui.label(summary.prompt_tokens.to_string());
+                ui.label(summary.response_tokens.to_string());
+                ui.end_row();
+            }
+        });
+}
 
-                ui.add_space(8.0);
-                for step in &workflow.steps {
-                    ui.horizontal(|ui| {
-                        ui.spacing_mut().item_spacing.x = 8.0;
-                        ui.label(
-                            RichText::new(workflow_step_icon(step.kind))
-                                .font(theme::icon_font(14.0))
-                                .color(theme::color_primary()),
-                        );
+fn draw_batch_eval_results(ui: &mut egui::Ui, state: &AppState) {
+    if state.batch_eval.results.is_empty() {
+        return;
+    }
+
+    egui::ScrollArea::vertical()
+        .id_source("batch_eval_results")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for (index, result) in state.batch_eval.results.iter().enumerate() {
+                let icon = if result.error.is_some() { "⚠" } else { "✔" };
+                let title = format!(
+                    "{} #{} · {} · {} ms",
+                    icon, result.prompt_id, result.target, result.latency_ms
+                );
+                egui::CollapsingHeader::new(title)
+                    .id_source(("batch_eval_result", index))
+                    .show(ui, |ui| {
                         ui.label(
-                            RichText::new(format!("{} · {}", step.kind.label(), step.label))
-                                .color(theme::color_text_primary())
-                                .size(12.0),
+                            RichText::new(&result.prompt)
+                                .color(theme::color_text_weak())
+                                .italics(),
                         );
-                        if let Some(provider) = step.provider {
-                            ui.label(
-                                RichText::new(format!("@{}", provider.short_code()))
-                                    .color(theme::color_text_weak())
-                                    .size(11.0)
-                                    .monospace(),
-                            );
+                        ui.add_space(4.0);
+                        match (&result.response, &result.error) {
+                            (_, Some(error)) => {
+                                ui.colored_label(theme::color_danger(), error);
+                            }
+                            (Some(response), None) => {
+                                ui.label(response);
+                            }
+                            (None, None) => {}
                         }
-                    });
-                    ui.label(
-                        RichText::new(&step.detail)
+                        ui.label(
+                            RichText::new(tf(
+                                "{0} tokens de entrada · {1} tokens de salida",
+                                &[
+                                    &result.prompt_tokens.to_string(),
+                                    &result.response_tokens.to_string(),
+                                ],
+                            ))
                             .color(theme::color_text_weak())
                             .size(11.0),
-                    );
-                    ui.add_space(4.0);
-                }
+                        );
+                    });
+            }
+        });
+}
 
-                if let Some(last_run) = &workflow.last_run {
+fn draw_audit_view(ui: &mut egui::Ui, state: &mut AppState) {
+    if state.audit.viewer.conversation.is_empty() {
+        let active = state.bookmarks.conversation.clone();
+        state.audit.open(&active);
+    }
+
+    with_centered_main_surface(ui, |ui| {
+        egui::Frame::none()
+            .fill(Color32::from_rgb(26, 28, 32))
+            .stroke(theme::subtle_border(&state.theme))
+            .inner_margin(egui::Margin::same(20.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 10.0;
                     ui.label(
-                        RichText::new(format!("Última ejecución: {last_run}"))
-                            .color(theme::color_text_weak())
-                            .size(11.0),
+                        RichText::new(ICON_SHIELD)
+                            .font(theme::icon_font(18.0))
+                            .color(theme::color_primary()),
                     );
-                } else {
-                    ui.label(
-                        RichText::new("Nunca ejecutado")
-                            .color(theme::color_text_weak())
-                            .size(11.0),
+                    ui.heading(
+                        RichText::new(t("Auditoría"))
+                            .color(theme::color_text_primary())
+                            .strong(),
                     );
-                }
+                });
+                ui.label(
+                    RichText::new(t(
+                        "Cada llamada a un proveedor, comando, workflow y archivo escrito queda en un registro de solo anexado encadenado por hashes.",
+                    ))
+                    .color(theme::color_text_weak()),
+                );
 
+                ui.add_space(10.0);
+                draw_audit_filters(ui, state);
                 ui.add_space(8.0);
-                ui.horizontal(|ui| {
-                    let run_button = theme::primary_button(
-                        RichText::new("Lanzar pipeline")
-                            .color(Color32::WHITE)
-                            .strong(),
-                        &state.theme,
-                    )
-                    .min_size(egui::vec2(150.0, 30.0));
-                    if ui.add(run_button).clicked() {
-                        if let Some(message) = state.trigger_workflow(workflow.id) {
-                            ui.colored_label(theme::color_text_weak(), message);
-                        }
-                    }
+                draw_audit_verification(ui, state);
+                ui.add_space(12.0);
+                draw_audit_entries(ui, state);
+            });
+    });
+}
 
-                    ui.add_space(8.0);
-                    let select_button = theme::secondary_button(
-                        RichText::new("Registrar en chat")
-                            .color(theme::color_text_primary())
-                            .strong(),
-                        &state.theme,
-                    )
-                    .min_size(egui::vec2(150.0, 30.0));
-                    if ui.add(select_button).clicked() {
-                        if let Some(message) =
-                            state.automation.workflows.workflows.get(index).map(|wf| {
-                                format!("Workflow '{}' listo para orquestación.", wf.name)
-                            })
-                        {
-                            state.push_activity_log(LogStatus::Ok, "Automation", &message);
-                            state.push_debug_event(
-                                DebugLogLevel::Info,
-                                "automation::note",
-                                message,
-                            );
-                        }
+fn draw_audit_filters(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut open = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.label(t("Conversación"));
+        egui::ComboBox::from_id_source("audit_conversation")
+            .selected_text(state.audit.viewer.conversation.clone())
+            .width(240.0)
+            .show_ui(ui, |ui| {
+                for conversation in state.audit.conversations() {
+                    let selected = conversation == state.audit.viewer.conversation;
+                    if ui.selectable_label(selected, &conversation).clicked() {
+                        open = Some(conversation);
                     }
-                });
+                }
             });
-        });
-}
 
-fn workflow_step_icon(kind: WorkflowStepKind) -> &'static str {
-    match kind {
-        WorkflowStepKind::RemoteModel => ICON_LIGHTNING,
-        WorkflowStepKind::LocalScript => ICON_CODE,
-        WorkflowStepKind::SyncAction => ICON_REPEAT,
+        let kind_label = state
+            .audit
+            .viewer
+            .kind_filter
+            .map(|kind| t(kind.label()).to_string())
+            .unwrap_or_else(|| t("Todos los eventos").to_string());
+        egui::ComboBox::from_id_source("audit_kind")
+            .selected_text(kind_label)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(
+                    &mut state.audit.viewer.kind_filter,
+                    None,
+                    t("Todos los eventos"),
+                );
+                for kind in AuditEventKind::ALL {
+                    ui.selectable_value(
+                        &mut state.audit.viewer.kind_filter,
+                        Some(kind),
+                        t(kind.label()),
+                    );
+                }
+            });
+
+        ui.add(
+            egui::TextEdit::singleline(&mut state.audit.viewer.search)
+                .hint_text(t("Buscar en el registro"))
+                .desired_width(200.0),
+        );
+
+        if ui
+            .add(theme::secondary_button(
+                RichText::new(format!("{} {}", ICON_REPEAT, t("Verificar integridad")))
+                    .font(theme::icon_font(13.0)),
+                &state.theme,
+            ))
+            .clicked()
+        {
+            open = Some(state.audit.viewer.conversation.clone());
+        }
+    });
+
+    if let Some(conversation) = open {
+        state.audit.open(&conversation);
     }
 }
 
-fn workflow_status_color(status: WorkflowStatus) -> Color32 {
-    match status {
-        WorkflowStatus::Ready => theme::color_primary(),
-        WorkflowStatus::Running => Color32::from_rgb(64, 172, 255),
-        WorkflowStatus::Failed => theme::color_danger(),
-        WorkflowStatus::Draft => Color32::from_rgb(160, 160, 160),
+fn draw_audit_verification(ui: &mut egui::Ui, state: &AppState) {
+    if let Some(status) = &state.audit.viewer.status {
+        ui.colored_label(theme::color_danger(), status);
+        return;
+    }
+    match &state.audit.viewer.verification {
+        Some(AuditVerification::Intact(count)) => {
+            ui.colored_label(
+                theme::color_success(),
+                tf(
+                    "Cadena íntegra: {0} entradas verificadas.",
+                    &[&count.to_string()],
+                ),
+            );
+        }
+        Some(AuditVerification::Broken { sequence, reason }) => {
+            ui.colored_label(
+                theme::color_danger(),
+                tf(
+                    "Cadena rota en la entrada #{0}: {1}.",
+                    &[&sequence.to_string(), t(reason)],
+                ),
+            );
+        }
+        None => {}
     }
 }
 
-fn draw_reminder_panel(ui: &mut egui::Ui, state: &AppState) {
-    egui::Frame::none()
-        .fill(Color32::from_rgb(34, 36, 42))
-        .stroke(theme::subtle_border(&state.theme))
-        .rounding(egui::Rounding::same(14.0))
-        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
-        .show(ui, |ui| {
-            ui.horizontal(|ui| {
-                ui.spacing_mut().item_spacing.x = 10.0;
-                ui.label(
-                    RichText::new(ICON_CLOCK)
-                        .font(theme::icon_font(16.0))
-                        .color(theme::color_primary()),
-                );
-                ui.heading(
-                    RichText::new("Recordatorios programados")
-                        .color(theme::color_text_primary())
-                        .strong(),
-                );
-            });
-            ui.label(
-                RichText::new(
-                    "Visualiza próximos avisos y confirma su canal de entrega en tiempo real.",
-                )
-                .color(theme::color_text_weak())
-                .size(12.0),
-            );
+fn draw_audit_entries(ui: &mut egui::Ui, state: &AppState) {
+    let entries = state.audit.visible_entries();
+    if entries.is_empty() {
+        ui.label(
+            RichText::new(t("No hay eventos registrados en esta conversación."))
+                .color(theme::color_text_weak()),
+        );
+        return;
+    }
 
-            ui.add_space(8.0);
-            if state.automation.scheduled_reminders.is_empty() {
-                ui.colored_label(
-                    theme::color_text_weak(),
-                    "No existen recordatorios activos por ahora.",
+    egui::ScrollArea::vertical()
+        .id_source("audit_entries")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for entry in entries {
+                let timestamp = DateTime::parse_from_rfc3339(&entry.timestamp)
+                    .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|_| entry.timestamp.clone());
+                let title = format!(
+                    "#{} · {} · {} · {}",
+                    entry.sequence,
+                    timestamp,
+                    t(entry.kind.label()),
+                    entry.summary
                 );
-                return;
-            }
-
-            for reminder in &state.automation.scheduled_reminders {
-                egui::Frame::none()
-                    .fill(Color32::from_rgb(28, 30, 36))
-                    .stroke(theme::subtle_border(&state.theme))
-                    .rounding(egui::Rounding::same(10.0))
-                    .inner_margin(egui::Margin::symmetric(12.0, 10.0))
+                egui::CollapsingHeader::new(title)
+                    .id_source(("audit_entry", entry.sequence))
                     .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            let color = reminder_status_color(reminder.status);
-                            ui.label(RichText::new("●").color(color).size(14.0).monospace());
-                            ui.label(
-                                RichText::new(format!("#{} {}", reminder.id, reminder.title))
-                                    .color(theme::color_text_primary())
-                                    .strong()
-                                    .size(13.0),
-                            );
-                            ui.add_space(ui.available_width());
-                            ui.label(
-                                RichText::new(reminder.status.label())
-                                    .color(color)
-                                    .size(11.0)
-                                    .monospace(),
-                            );
-                        });
-                        ui.label(
-                            RichText::new(format!(
-                                "Cadencia: {} · Próximo envío {}",
-                                reminder.cadence, reminder.next_trigger
-                            ))
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                        );
-                        ui.label(
-                            RichText::new(format!(
-                                "Canal: {} · Audiencia: {}",
-                                reminder.delivery_channel, reminder.audience
-                            ))
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                        );
+                        egui::Grid::new(("audit_details", entry.sequence))
+                            .num_columns(2)
+                            .spacing(egui::vec2(12.0, 4.0))
+                            .show(ui, |ui| {
+                                for (key, value) in &entry.details {
+                                    ui.label(RichText::new(key).color(theme::color_text_weak()));
+                                    ui.label(value);
+                                    ui.end_row();
+                                }
+                                ui.label(RichText::new("hash").color(theme::color_text_weak()));
+                                ui.label(RichText::new(&entry.hash).monospace().size(11.0));
+                                ui.end_row();
+                            });
                     });
-                ui.add_space(6.0);
             }
         });
 }
 
-fn reminder_status_color(status: ReminderStatus) -> Color32 {
-    match status {
-        ReminderStatus::Scheduled => theme::color_primary(),
-        ReminderStatus::Sent => theme::color_success(),
-        ReminderStatus::Snoozed => Color32::from_rgb(255, 196, 0),
-    }
-}
-
-fn draw_listener_panel(ui: &mut egui::Ui, state: &mut AppState) {
-    egui::Frame::none()
-        .fill(Color32::from_rgb(34, 36, 42))
-        .stroke(theme::subtle_border(&state.theme))
-        .rounding(egui::Rounding::same(14.0))
-        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
-        .show(ui, |ui| {
-            ui.horizontal(|ui| {
-                ui.spacing_mut().item_spacing.x = 10.0;
+fn draw_code_viewer(ui: &mut egui::Ui, state: &mut AppState) {
+    with_centered_main_surface(ui, |ui| {
+        egui::Frame::none()
+            .fill(Color32::from_rgb(26, 28, 32))
+            .stroke(theme::subtle_border(&state.theme))
+            .inner_margin(egui::Margin::same(20.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 10.0;
+                    ui.label(
+                        RichText::new(ICON_CODE)
+                            .font(theme::icon_font(18.0))
+                            .color(theme::color_primary()),
+                    );
+                    ui.heading(
+                        RichText::new(t("Explorador de código"))
+                            .color(theme::color_text_primary())
+                            .strong(),
+                    );
+                });
                 ui.label(
-                    RichText::new(ICON_INFO)
-                        .font(theme::icon_font(16.0))
-                        .color(theme::color_primary()),
-                );
-                ui.heading(
-                    RichText::new("Listeners y disparadores")
-                        .color(theme::color_text_primary())
-                        .strong(),
+                    RichText::new(t(
+                        "Selecciona líneas de un archivo (Mayús+clic para ampliar) y pregunta por ellas: la respuesta queda anclada al rango.",
+                    ))
+                    .color(theme::color_text_weak()),
                 );
-                ui.add_space(ui.available_width());
-                ui.checkbox(
-                    &mut state.automation.event_automation.show_only_enabled,
-                    "Solo activos",
-                )
-                .on_hover_text("Oculta listeners deshabilitados");
+
+                ui.add_space(10.0);
+                draw_code_viewer_project_picker(ui, state);
+                if let Some(status) = &state.code_viewer.status {
+                    ui.label(RichText::new(status).color(theme::color_text_weak()));
+                }
+                ui.add_space(8.0);
+
+                if state.code_viewer.project.is_none() {
+                    return;
+                }
+                draw_code_viewer_fetch_status(ui, state);
+                ui.add_space(8.0);
+                ui.columns(2, |columns| {
+                    draw_code_viewer_files(&mut columns[0], state);
+                    draw_code_viewer_lines(&mut columns[1], state);
+                });
+                ui.add_space(10.0);
+                draw_code_viewer_question(ui, state);
+                ui.add_space(10.0);
+                draw_code_viewer_git_assist(ui, state);
+                if state.code_viewer.open.is_some() {
+                    ui.add_space(10.0);
+                    draw_code_viewer_history(ui, state);
+                }
+                ui.add_space(10.0);
+                draw_code_viewer_conflicts(ui, state);
+                if state.code_viewer.patch.is_some() {
+                    ui.add_space(12.0);
+                    draw_code_viewer_patch(ui, state);
+                }
             });
-            ui.label(
-                RichText::new(
-                    "Configura automatizaciones basadas en eventos de chat, repositorios o jobs.",
-                )
-                .color(theme::color_text_weak())
-                .size(12.0),
-            );
+    });
+}
 
-            ui.add_space(8.0);
-            let indices: Vec<usize> = state
-                .automation
-                .event_automation
-                .listeners
-                .iter()
-                .enumerate()
-                .filter(|(_, listener)| {
-                    if state.automation.event_automation.show_only_enabled && !listener.enabled {
-                        return false;
+fn draw_code_viewer_project_picker(ui: &mut egui::Ui, state: &mut AppState) {
+    let projects: Vec<String> = state
+        .resources
+        .project_resources
+        .iter()
+        .filter(|card| card.kind == ProjectResourceKind::LocalProject)
+        .map(|card| card.name.clone())
+        .collect();
+    if projects.is_empty() {
+        ui.label(
+            RichText::new(t("No hay proyectos locales conectados."))
+                .color(theme::color_text_weak()),
+        );
+        return;
+    }
+
+    let mut open = None;
+    ui.horizontal(|ui| {
+        ui.label(t("Proyecto"));
+        let current = state.code_viewer.project.clone().unwrap_or_default();
+        egui::ComboBox::from_id_source("code_viewer_project")
+            .selected_text(current.clone())
+            .width(240.0)
+            .show_ui(ui, |ui| {
+                for project in &projects {
+                    if ui.selectable_label(*project == current, project).clicked() {
+                        open = Some(project.clone());
                     }
-                    true
-                })
-                .map(|(idx, _)| idx)
-                .collect();
+                }
+            });
+    });
+    if let Some(project) = open {
+        if let Err(err) = state.open_code_viewer(&project) {
+            state.code_viewer.status = Some(err);
+        }
+    }
+}
 
-            if indices.is_empty() {
-                ui.colored_label(
-                    theme::color_text_weak(),
-                    "No hay listeners configurados para estos filtros.",
-                );
-                return;
+fn draw_code_viewer_fetch_status(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut fetch = false;
+    ui.horizontal_wrapped(|ui| {
+        let running = state.code_viewer.fetch_rx.is_some();
+        fetch = ui
+            .add_enabled(!running, egui::Button::new(t("Traer remotos")))
+            .on_hover_text(t(
+                "Ejecuta git fetch y compara cada rama con su remota sin tocar el árbol de trabajo.",
+            ))
+            .clicked();
+        if running {
+            ui.spinner();
+        }
+        let Some(status) = &state.code_viewer.fetch else {
+            return;
+        };
+        ui.label(
+            RichText::new(tf("Actualizado a las {0}", &[&status.fetched_at]))
+                .color(theme::color_text_weak())
+                .size(11.0),
+        );
+        for branch in status.branches.iter().filter(|branch| branch.upstream.is_some()) {
+            let color = if branch.behind > 0 || branch.gone {
+                theme::color_danger()
+            } else if branch.ahead > 0 {
+                theme::color_primary()
+            } else {
+                theme::color_success()
+            };
+            let label = RichText::new(format!("{} · {}", branch.name, branch.summary()))
+                .size(11.0)
+                .color(color);
+            ui.label(if branch.current { label.strong() } else { label });
+        }
+    });
+    if let Some(status) = &state.code_viewer.fetch {
+        if !status.incoming.is_empty() {
+            egui::CollapsingHeader::new(tf(
+                "Commits entrantes ({0})",
+                &[&status.incoming.len().to_string()],
+            ))
+            .id_source("code_viewer_incoming")
+            .show(ui, |ui| {
+                if let Some(base) = &status.merge_base {
+                    ui.label(
+                        RichText::new(tf(
+                            "Las ramas divergieron desde {0}",
+                            &[short_revision(base)],
+                        ))
+                        .color(theme::color_text_weak()),
+                    );
+                }
+                for commit in &status.incoming {
+                    ui.label(
+                        RichText::new(format!(
+                            "{} · {} · {} — {}",
+                            commit.short_hash(),
+                            commit.date,
+                            commit.author,
+                            commit.subject
+                        ))
+                        .monospace()
+                        .size(11.0),
+                    );
+                }
+            });
+        }
+    }
+    if fetch {
+        if let Err(err) = state.fetch_remote_status() {
+            state.code_viewer.status = Some(err);
+        }
+    }
+}
+
+fn draw_code_viewer_files(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.add(
+        egui::TextEdit::singleline(&mut state.code_viewer.filter)
+            .hint_text(t("Filtrar archivos"))
+            .desired_width(f32::INFINITY),
+    );
+    ui.add_space(6.0);
+
+    let root = state.code_viewer.root.clone();
+    let current = state
+        .code_viewer
+        .open
+        .as_ref()
+        .map(|file| file.path.clone());
+    let files: Vec<PathBuf> = state
+        .code_viewer
+        .visible_files()
+        .into_iter()
+        .cloned()
+        .collect();
+    let mut selected = None;
+    egui::ScrollArea::vertical()
+        .id_source("code_viewer_files")
+        .max_height(420.0)
+        .auto_shrink([false, false])
+        .show_rows(ui, 18.0, files.len(), |ui, rows| {
+            for path in &files[rows] {
+                let label = path
+                    .strip_prefix(&root)
+                    .unwrap_or(path)
+                    .display()
+                    .to_string();
+                let active = current.as_ref() == Some(path);
+                if ui
+                    .selectable_label(active, RichText::new(label).monospace().size(12.0))
+                    .clicked()
+                {
+                    selected = Some(path.clone());
+                }
             }
+        });
 
-            for index in indices {
-                let listener_snapshot = state.automation.event_automation.listeners[index].clone();
-                egui::Frame::none()
-                    .fill(Color32::from_rgb(28, 30, 36))
-                    .stroke(theme::subtle_border(&state.theme))
-                    .rounding(egui::Rounding::same(10.0))
-                    .inner_margin(egui::Margin::symmetric(12.0, 10.0))
-                    .show(ui, |ui| {
+    if let Some(path) = selected {
+        state.code_viewer.status = state
+            .code_viewer
+            .open_file(&path)
+            .err()
+            .map(|err| err.to_string());
+    }
+}
+
+fn draw_code_viewer_lines(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(file) = state.code_viewer.open.as_ref() else {
+        ui.label(RichText::new(t("Elige un archivo de la lista.")).color(theme::color_text_weak()));
+        return;
+    };
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(&file.relative)
+                .color(theme::color_text_primary())
+                .strong(),
+        );
+        ui.label(
+            RichText::new(file.encoding.label())
+                .color(theme::color_text_weak())
+                .size(11.0),
+        );
+        if let Some(total) = file.truncated_from {
+            ui.label(
+                RichText::new(tf("Solo el principio de {0}", &[&format_bytes(total)]))
+                    .color(theme::color_danger())
+                    .size(11.0),
+            );
+        }
+    });
+    ui.add_space(6.0);
+
+    let extend = ui.input(|input| input.modifiers.shift);
+    let mut clicked = None;
+    egui::ScrollArea::both()
+        .id_source(("code_viewer_lines", file.relative.as_str()))
+        .max_height(420.0)
+        .auto_shrink([false, false])
+        .show_rows(ui, 16.0, file.lines.len(), |ui, rows| {
+            for index in rows {
+                let number = index + 1;
+                let text = format!("{:>5}  {}", number, file.lines[index]);
+                let selected = state.code_viewer.is_selected(number);
+                if ui
+                    .selectable_label(selected, RichText::new(text).monospace().size(12.0))
+                    .clicked()
+                {
+                    clicked = Some(number);
+                }
+            }
+        });
+
+    if let Some(number) = clicked {
+        state.code_viewer.click_line(number, extend);
+    }
+}
+
+fn draw_code_viewer_question(ui: &mut egui::Ui, state: &mut AppState) {
+    let anchor = state.code_viewer.current_anchor();
+    ui.label(
+        RichText::new(match &anchor {
+            Some(anchor) => tf("Selección: {0}", &[&anchor.label()]),
+            None => t("Sin selección").to_string(),
+        })
+        .color(theme::color_text_weak()),
+    );
+    ui.add(
+        egui::TextEdit::multiline(&mut state.code_viewer.question)
+            .hint_text(t("¿Qué quieres saber de estas líneas?"))
+            .desired_rows(2)
+            .desired_width(f32::INFINITY),
+    );
+    let mut ask = false;
+    let mut who_wrote = false;
+    let git_busy = state.code_viewer.git_job.is_some();
+    ui.horizontal(|ui| {
+        ui.checkbox(
+            &mut state.code_viewer.request_patch,
+            t("Pedir la respuesta como parche"),
+        );
+        ui.add_space((ui.available_width() - 330.0).max(0.0));
+        let button = theme::primary_button(
+            RichText::new(format!("{} {}", ICON_SEND, t("Preguntar")))
+                .font(theme::icon_font(13.0))
+                .color(Color32::WHITE),
+            &state.theme,
+        )
+        .min_size(egui::vec2(150.0, 30.0));
+        ask = ui.add_enabled(anchor.is_some(), button).clicked();
+        who_wrote = ui
+            .add_enabled(
+                anchor.is_some() && !git_busy,
+                egui::Button::new(t("¿Quién escribió esto?")),
+            )
+            .clicked();
+    });
+    if ask {
+        if let Err(err) = state.ask_about_selection() {
+            state.code_viewer.status = Some(err);
+        }
+    }
+    if who_wrote {
+        if let Err(err) = state.ask_who_wrote_selection() {
+            state.code_viewer.status = Some(err);
+        }
+    }
+}
+
+fn draw_code_viewer_history(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut load = false;
+    let mut explain = None;
+    let git_busy = state.code_viewer.git_job.is_some();
+    egui::CollapsingHeader::new(RichText::new(t("Historial del archivo")).strong())
+        .id_source("code_viewer_history")
+        .show(ui, |ui| {
+            let Some(history) = state.code_viewer.history.as_ref() else {
+                ui.horizontal(|ui| {
+                    load = ui
+                        .add_enabled(!git_busy, egui::Button::new(t("Cargar historial")))
+                        .clicked();
+                    if git_busy {
+                        ui.spinner();
+                    }
+                });
+                return;
+            };
+            if history.is_empty() {
+                ui.colored_label(theme::color_text_weak(), t("El archivo no tiene commits."));
+            }
+            egui::ScrollArea::vertical()
+                .id_source("code_viewer_history_list")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for commit in history {
                         ui.horizontal(|ui| {
                             ui.label(
-                                RichText::new(&listener_snapshot.name)
-                                    .color(theme::color_text_primary())
-                                    .strong()
-                                    .size(13.0),
+                                RichText::new(commit.short_hash())
+                                    .monospace()
+                                    .color(theme::color_primary()),
                             );
-                            ui.add_space(ui.available_width());
                             ui.label(
-                                RichText::new(listener_snapshot.event.label())
+                                RichText::new(format!("{} · {}", commit.date, commit.author))
                                     .color(theme::color_text_weak())
-                                    .size(11.0),
+                                    .size(12.0),
                             );
+                            ui.label(&commit.subject);
+                            if ui
+                                .add_enabled(
+                                    !git_busy,
+                                    egui::Button::new(t("Explicar este cambio")).small(),
+                                )
+                                .clicked()
+                            {
+                                explain = Some(commit.hash.clone());
+                            }
                         });
+                    }
+                });
+        });
+    if load {
+        if let Err(err) = state.load_file_history() {
+            state.code_viewer.status = Some(err);
+        }
+    }
+    if let Some(hash) = explain {
+        if let Err(err) = state.explain_file_commit(&hash) {
+            state.code_viewer.status = Some(err);
+        }
+    }
+}
+
+fn draw_code_viewer_git_assist(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut document = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.label(RichText::new(t("Asistente de commits")).strong());
+        ui.radio_value(
+            &mut state.code_viewer.diff_whole_tree,
+            false,
+            t("Archivo abierto"),
+        );
+        ui.radio_value(
+            &mut state.code_viewer.diff_whole_tree,
+            true,
+            t("Todo el árbol de trabajo"),
+        );
+        let ready = (state.code_viewer.diff_whole_tree || state.code_viewer.open.is_some())
+            && state.code_viewer.git_job.is_none();
+        for candidate in [
+            git_assist::DiffDocument::CommitMessage,
+            git_assist::DiffDocument::PullRequest,
+        ] {
+            if ui
+                .add_enabled(ready, egui::Button::new(t(candidate.label())))
+                .clicked()
+            {
+                document = Some(candidate);
+            }
+        }
+    });
+    if let Some(document) = document {
+        if let Err(err) = state.generate_from_diff(document) {
+            state.code_viewer.status = Some(err);
+        }
+    }
+}
+
+fn draw_code_viewer_conflicts(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut scan = false;
+    let mut select = None;
+    let mut propose = None;
+    let mut write = false;
+    let git_busy = state.code_viewer.git_job.is_some();
+    egui::CollapsingHeader::new(RichText::new(t("Conflictos de fusión")).strong())
+        .id_source("code_viewer_conflicts")
+        .show(ui, |ui| {
+            let conflicts = &mut state.code_viewer.conflicts;
+            ui.horizontal(|ui| {
+                scan = ui
+                    .add_enabled(!git_busy, egui::Button::new(t("Buscar conflictos")))
+                    .clicked();
+                if conflicts.awaiting.is_some() || git_busy {
+                    ui.spinner();
+                }
+                if let Some(status) = &conflicts.status {
+                    ui.colored_label(theme::color_text_weak(), status.as_str());
+                }
+            });
+            if conflicts.files.is_empty() {
+                return;
+            }
+            ui.horizontal_wrapped(|ui| {
+                for (index, file) in conflicts.files.iter().enumerate() {
+                    let label = format!("{} ({})", file.relative, file.hunks.len());
+                    if ui
+                        .selectable_label(conflicts.selected == Some(index), label)
+                        .clicked()
+                    {
+                        select = Some(index);
+                    }
+                }
+            });
+            let Some(file) = conflicts
+                .selected
+                .and_then(|index| conflicts.files.get(index))
+            else {
+                return;
+            };
+            for (index, hunk) in file.hunks.iter().enumerate() {
+                ui.add_space(6.0);
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(20, 22, 26))
+                    .rounding(egui::Rounding::same(8.0))
+                    .inner_margin(egui::Margin::same(10.0))
+                    .show(ui, |ui| {
+                        ui.set_width(ui.available_width());
                         ui.label(
-                            RichText::new(&listener_snapshot.description)
-                                .color(theme::color_text_weak())
-                                .size(11.0),
-                        );
-                        ui.add_space(4.0);
-                        ui.label(
-                            RichText::new(format!("Condición: {}", listener_snapshot.condition))
-                                .color(theme::color_text_weak())
-                                .size(11.0)
-                                .monospace(),
-                        );
-                        ui.label(
-                            RichText::new(format!("Acción: {}", listener_snapshot.action))
-                                .color(theme::color_text_primary())
-                                .size(11.0)
-                                .monospace(),
+                            RichText::new(tf(
+                                "Bloque en la línea {0}",
+                                &[&hunk.start_line.to_string()],
+                            ))
+                            .strong(),
                         );
-                        if let Some(last) = &listener_snapshot.last_triggered {
+                        ui.columns(2, |columns| {
+                            for (column, title, text, color) in [
+                                (0, &hunk.ours_label, &hunk.ours, theme::color_success()),
+                                (1, &hunk.theirs_label, &hunk.theirs, theme::color_danger()),
+                            ] {
+                                columns[column].label(
+                                    RichText::new(title)
+                                        .color(theme::color_text_weak())
+                                        .size(11.0),
+                                );
+                                columns[column].label(
+                                    RichText::new(text.trim_end())
+                                        .monospace()
+                                        .size(12.0)
+                                        .color(color),
+                                );
+                            }
+                        });
+                        let resolution = conflicts.resolutions.get(&index).cloned();
+                        ui.horizontal(|ui| {
+                            for (choice, label) in [
+                                (git_assist::HunkResolution::Ours, t("Usar la nuestra")),
+                                (git_assist::HunkResolution::Theirs, t("Usar la suya")),
+                                (git_assist::HunkResolution::Both, t("Ambas")),
+                            ] {
+                                let selected = resolution.as_ref() == Some(&choice);
+                                if ui.selectable_label(selected, label).clicked() {
+                                    conflicts.resolutions.insert(index, choice);
+                                }
+                            }
+                            if ui
+                                .add_enabled(
+                                    conflicts.awaiting.is_none(),
+                                    egui::Button::new(t("Proponer fusión")),
+                                )
+                                .clicked()
+                            {
+                                propose = Some(index);
+                            }
+                        });
+                        if let Some(proposal) = conflicts.proposals.get(&index).cloned() {
+                            ui.add_space(4.0);
                             ui.label(
-                                RichText::new(format!("Último disparo: {last}"))
+                                RichText::new(t("Propuesta del modelo"))
                                     .color(theme::color_text_weak())
                                     .size(11.0),
                             );
-                        }
-
-                        ui.add_space(6.0);
-                        let mut enabled_label = "Deshabilitar";
-                        if !listener_snapshot.enabled {
-                            enabled_label = "Habilitar";
-                        }
-                        let toggle_button = theme::secondary_button(
-                            RichText::new(enabled_label)
-                                .color(theme::color_text_primary())
-                                .strong(),
-                            &state.theme,
-                        )
-                        .min_size(egui::vec2(130.0, 28.0));
-                        if ui.add(toggle_button).clicked() {
-                            state.toggle_listener_enabled(listener_snapshot.id);
+                            ui.label(
+                                RichText::new(proposal.trim_end())
+                                    .monospace()
+                                    .size(12.0)
+                                    .color(theme::color_primary()),
+                            );
+                            let accepted = resolution
+                                == Some(git_assist::HunkResolution::Merged(proposal.clone()));
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .selectable_label(accepted, t("Aceptar propuesta"))
+                                    .clicked()
+                                {
+                                    conflicts.resolutions.insert(
+                                        index,
+                                        git_assist::HunkResolution::Merged(proposal),
+                                    );
+                                }
+                                if ui.button(t("Descartar")).clicked() {
+                                    conflicts.proposals.remove(&index);
+                                    if accepted {
+                                        conflicts.resolutions.remove(&index);
+                                    }
+                                }
+                            });
                         }
                     });
-                ui.add_space(6.0);
             }
+            ui.add_space(6.0);
+            write = ui
+                .add_enabled(
+                    !conflicts.resolutions.is_empty(),
+                    egui::Button::new(tf(
+                        "Escribir resolución ({0} de {1} bloques)",
+                        &[
+                            &conflicts.resolutions.len().to_string(),
+                            &file.hunks.len().to_string(),
+                        ],
+                    )),
+                )
+                .clicked();
         });
+
+    if scan {
+        if let Err(err) = state.scan_merge_conflicts() {
+            state.code_viewer.conflicts.status = Some(err);
+        }
+    }
+    if let Some(index) = select {
+        state.code_viewer.conflicts.select(index);
+    }
+    if let Some(hunk) = propose {
+        if let Err(err) = state.propose_conflict_merge(hunk) {
+            state.code_viewer.conflicts.status = Some(err);
+        }
+    }
+    if write {
+        if let Err(err) = state.write_conflict_resolution() {
+            state.code_viewer.conflicts.status = Some(err);
+        }
+    }
 }
 
-fn draw_integration_panel(ui: &mut egui::Ui, state: &AppState) {
+fn draw_code_viewer_patch(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(patch) = state.code_viewer.patch.clone() else {
+        return;
+    };
+    let mut save = false;
+    let mut discard = false;
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(tf("Parche sugerido para {0}", &[&patch.anchor.label()]))
+                .color(theme::color_text_primary())
+                .strong(),
+        );
+        ui.add_space((ui.available_width() - 260.0).max(0.0));
+        if ui.button(t("Copiar")).clicked() {
+            ui.output_mut(|out| out.copied_text = patch.diff.clone());
+        }
+        save = ui.button(t("Guardar .patch")).clicked();
+        discard = ui.button(t("Descartar")).clicked();
+    });
     egui::Frame::none()
-        .fill(Color32::from_rgb(34, 36, 42))
-        .stroke(theme::subtle_border(&state.theme))
-        .rounding(egui::Rounding::same(14.0))
-        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
+        .fill(Color32::from_rgb(20, 22, 26))
+        .rounding(egui::Rounding::same(8.0))
+        .inner_margin(egui::Margin::same(10.0))
         .show(ui, |ui| {
-            ui.horizontal(|ui| {
-                ui.spacing_mut().item_spacing.x = 10.0;
-                ui.label(
-                    RichText::new(ICON_LINK)
-                        .font(theme::icon_font(16.0))
-                        .color(theme::color_primary()),
+            egui::ScrollArea::vertical()
+                .id_source("code_viewer_patch")
+                .max_height(240.0)
+                .show(ui, |ui| {
+                    for line in patch.diff.lines() {
+                        let color = if line.starts_with('+') && !line.starts_with("+++") {
+                            theme::color_success()
+                        } else if line.starts_with('-') && !line.starts_with("---") {
+                            theme::color_danger()
+                        } else {
+                            theme::color_text_weak()
+                        };
+                        ui.label(RichText::new(line).monospace().size(12.0).color(color));
+                    }
+                });
+        });
+
+    if save {
+        if let Err(err) = state.save_patch_suggestion() {
+            state.code_viewer.status = Some(err);
+        }
+    }
+    if discard {
+        state.code_viewer.patch = None;
+    }
+}
+
+fn draw_cron_summary(ui: &mut egui::Ui, state: &AppState) {
+    let total_enabled = state
+        .automation
+        .cron_board
+        .tasks
+        .iter()
+        .filter(|task| task.enabled)
+        .count();
+    let running = state
+        .automation
+        .cron_board
+        .status_count(ScheduledTaskStatus::Running);
+    let failing = state
+        .automation
+        .cron_board
+        .status_count(ScheduledTaskStatus::Failed);
+
+    ui.horizontal(|ui| {
+        summary_chip(
+            ui,
+            ICON_REPEAT,
+            "Activas",
+            total_enabled,
+            theme::color_primary(),
+            &state.theme,
+        );
+        summary_chip(
+            ui,
+            ICON_PLAY,
+            "En ejecución",
+            running,
+            Color32::from_rgb(64, 172, 255),
+            &state.theme,
+        );
+        summary_chip(
+            ui,
+            ICON_STOP,
+            "Con errores",
+            failing,
+            theme::color_danger(),
+            &state.theme,
+        );
+    });
+}
+
+fn summary_chip(
+    ui: &mut egui::Ui,
+    icon: &str,
+    label: &str,
+    value: usize,
+    color: Color32,
+    tokens: &ThemeTokens,
+) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(tokens))
+        .rounding(egui::Rounding::same(12.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 12.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(icon)
+                        .font(theme::icon_font(16.0))
+                        .color(color),
+                );
+                ui.vertical(|ui| {
+                    ui.label(
+                        RichText::new(label)
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                    ui.label(
+                        RichText::new(value.to_string())
+                            .color(theme::color_text_primary())
+                            .size(16.0)
+                            .strong(),
+                    );
+                });
+            });
+        });
+}
+
+fn draw_workflow_panel(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(14.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 10.0;
+                ui.label(
+                    RichText::new(ICON_LIGHTNING)
+                        .font(theme::icon_font(16.0))
+                        .color(theme::color_primary()),
                 );
                 ui.heading(
-                    RichText::new("Integraciones externas")
+                    RichText::new("Workflows automatizados")
                         .color(theme::color_text_primary())
                         .strong(),
                 );
+                ui.add_space(ui.available_width());
+                ui.checkbox(
+                    &mut state.automation.workflows.show_only_pinned,
+                    "Solo favoritos",
+                )
+                .on_hover_text("Filtra workflows fijados para acceso rápido");
             });
             ui.label(
                 RichText::new(
-                    "Gmail, Calendar, CI/CD e IFTTT se orquestan como triggers y acciones del agente.",
+                    "Encadena modelos remotos con scripts locales y orquesta pipelines desde el chat.",
                 )
                 .color(theme::color_text_weak())
                 .size(12.0),
             );
 
             ui.add_space(8.0);
-            if state.automation.external_integrations.connectors.is_empty() {
+            draw_workflow_bundle_exchange(ui, state);
+
+            ui.add_space(8.0);
+            let indices = state.automation.workflows.filtered_indices();
+            if indices.is_empty() {
                 ui.colored_label(
                     theme::color_text_weak(),
-                    "Sin conectores registrados todavía.",
+                    "No hay workflows guardados con los filtros actuales.",
                 );
                 return;
             }
 
-            for connector in &state.automation.external_integrations.connectors {
-                egui::Frame::none()
-                    .fill(Color32::from_rgb(28, 30, 36))
-                    .stroke(theme::subtle_border(&state.theme))
-                    .rounding(egui::Rounding::same(10.0))
-                    .inner_margin(egui::Margin::symmetric(12.0, 10.0))
-                    .show(ui, |ui| {
-                        ui.horizontal(|ui| {
-                            ui.label(
-                                RichText::new(connector.service.label())
-                                    .color(theme::color_text_primary())
-                                    .strong()
-                                    .size(12.0),
-                            );
-                            ui.add_space(8.0);
-                            ui.label(
-                                RichText::new(format!("#{} {}", connector.id, connector.name))
-                                    .color(theme::color_text_weak())
-                                    .size(11.0),
-                            );
-                            ui.add_space(ui.available_width());
-                            ui.label(
-                                RichText::new(connector.status.label())
-                                    .color(integration_status_color(connector.status))
-                                    .size(11.0)
-                                    .monospace(),
-                            );
-                        });
-                        ui.label(
-                            RichText::new(&connector.status_detail)
-                                .color(theme::color_text_weak())
-                                .size(11.0),
-                        );
-                        if let Some(last) = &connector.last_event {
-                            ui.label(
-                                RichText::new(format!("Último evento: {last}"))
-                                    .color(theme::color_text_weak())
-                                    .size(11.0),
-                            );
-                        }
-                        if let Some(next) = &connector.next_sync {
-                            ui.label(
-                                RichText::new(format!("Próxima sincronización: {next}"))
-                                    .color(theme::color_text_weak())
-                                    .size(11.0),
-                            );
-                        }
-                        if !connector.metadata.is_empty() {
-                            ui.horizontal_wrapped(|ui| {
-                                ui.spacing_mut().item_spacing.x = 6.0;
-                                for entry in &connector.metadata {
-                                    selectable_chip(ui, entry, false);
-                                }
-                            });
-                        }
-                        if !connector.quick_actions.is_empty() {
-                            ui.add_space(6.0);
-                            ui.horizontal(|ui| {
-                                for action in &connector.quick_actions {
-                                    let button = theme::secondary_button(
-                                        RichText::new(action)
-                                            .color(theme::color_text_primary())
-                                            .strong(),
-                                        &state.theme,
-                                    )
-                                    .min_size(egui::vec2(130.0, 26.0));
-                                    ui.add(button);
-                                    ui.add_space(6.0);
-                                }
-                            });
-                        }
-                    });
-                ui.add_space(6.0);
+            for index in indices {
+                let workflow_snapshot = state.automation.workflows.workflows[index].clone();
+                draw_workflow_card(ui, state, index, &workflow_snapshot);
+                ui.add_space(8.0);
             }
         });
 }
 
-fn integration_status_color(status: IntegrationStatus) -> Color32 {
-    match status {
-        IntegrationStatus::Connected => theme::color_success(),
-        IntegrationStatus::Warning => Color32::from_rgb(255, 196, 0),
-        IntegrationStatus::Error => theme::color_danger(),
-        IntegrationStatus::Syncing => Color32::from_rgb(64, 172, 255),
-    }
+fn draw_workflow_bundle_exchange(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::CollapsingHeader::new(t("Compartir workflows"))
+        .id_source("workflow_bundle_exchange")
+        .default_open(state.workflow_bundles.pending.is_some())
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new(t("Los paquetes JSON incluyen los pasos, las programaciones vinculadas y los listeners que lanzan cada workflow. Los secretos que detectan las reglas de redacción se exportan como marcadores y se piden al importar."))
+                    .color(theme::color_text_weak())
+                    .size(11.0),
+            );
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.workflow_bundles.export_path)
+                        .hint_text(t("Vacío: carpeta workflows de la app"))
+                        .desired_width(260.0),
+                );
+                if ui.button(t("Exportar los visibles")).clicked() {
+                    let ids: Vec<u32> = state
+                        .automation
+                        .workflows
+                        .filtered_indices()
+                        .into_iter()
+                        .map(|index| state.automation.workflows.workflows[index].id)
+                        .collect();
+                    state.workflow_bundles.status = Some(match state.export_workflow_bundle(&ids) {
+                        Ok(path) => tf("Paquete exportado a {0}.", &[&path.display().to_string()]),
+                        Err(err) => err,
+                    });
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.workflow_bundles.import_path)
+                        .hint_text("workflows.json")
+                        .desired_width(260.0),
+                );
+                if ui.button(t("Leer paquete")).clicked() {
+                    state.workflow_bundles.status = state.load_workflow_bundle().err();
+                }
+            });
+            if let Some(status) = &state.workflow_bundles.status {
+                ui.colored_label(theme::color_text_weak(), status);
+            }
+            draw_pending_workflow_bundle(ui, state);
+        });
 }
 
-fn draw_cron_filters(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.horizontal(|ui| {
-        let toggle = ui.checkbox(
-            &mut state.automation.cron_board.show_only_enabled,
-            "Solo habilitadas",
-        );
-        toggle.on_hover_text("Oculta tareas desactivadas o pausadas");
+fn draw_pending_workflow_bundle(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(bundle) = &state.workflow_bundles.pending else {
+        return;
+    };
+    ui.add_space(8.0);
+    ui.strong(&bundle.name);
+    ui.label(
+        RichText::new(tf(
+            "Exportado el {0} · {1} workflows · {2} programaciones · {3} listeners",
+            &[
+                &bundle.exported_at,
+                &bundle.workflows.len().to_string(),
+                &bundle.schedules.len().to_string(),
+                &bundle.listeners.len().to_string(),
+            ],
+        ))
+        .color(theme::color_text_weak())
+        .size(11.0),
+    );
+    let secrets: Vec<(String, String)> = bundle
+        .secrets
+        .iter()
+        .map(|secret| (secret.name.clone(), secret.description.clone()))
+        .collect();
 
-        let provider_text = state
-            .automation
-            .cron_board
-            .provider_filter
-            .map(|provider| provider.display_name().to_string())
-            .unwrap_or_else(|| "Todos los proveedores".to_string());
-        egui::ComboBox::from_id_source("cron_provider_filter")
-            .selected_text(provider_text)
+    let bundles = &mut state.workflow_bundles;
+    if !bundles.conflicts.is_empty() {
+        ui.add_space(6.0);
+        ui.colored_label(
+            theme::color_danger(),
+            tf(
+                "Ya existen con el mismo nombre: {0}",
+                &[&bundles.conflicts.join(", ")],
+            ),
+        );
+        egui::ComboBox::from_id_source("workflow_bundle_resolution")
+            .selected_text(t(bundles.resolution.label()))
             .show_ui(ui, |ui| {
-                if ui
-                    .selectable_label(
-                        state.automation.cron_board.provider_filter.is_none(),
-                        "Todos",
-                    )
-                    .clicked()
-                {
-                    state.automation.cron_board.provider_filter = None;
+                for resolution in ConflictResolution::ALL {
+                    ui.selectable_value(&mut bundles.resolution, resolution, t(resolution.label()));
                 }
-                for provider in [
-                    RemoteProviderKind::Anthropic,
-                    RemoteProviderKind::OpenAi,
-                    RemoteProviderKind::Groq,
-                ] {
-                    let selected = state.automation.cron_board.provider_filter == Some(provider);
-                    let label = format!("{} ({})", provider.display_name(), provider.short_code());
-                    if ui.selectable_label(selected, label).clicked() {
-                        state.automation.cron_board.provider_filter = Some(provider);
-                    }
+            });
+    }
+    if !secrets.is_empty() {
+        ui.add_space(6.0);
+        ui.label(t("Secretos que necesita el paquete"));
+        egui::Grid::new("workflow_bundle_secrets")
+            .num_columns(2)
+            .spacing(egui::vec2(10.0, 4.0))
+            .show(ui, |ui| {
+                for (name, description) in &secrets {
+                    ui.label(RichText::new(name).monospace())
+                        .on_hover_text(description);
+                    let value = bundles.secrets.entry(name.clone()).or_default();
+                    ui.add(
+                        egui::TextEdit::singleline(value)
+                            .password(true)
+                            .desired_width(220.0),
+                    );
+                    ui.end_row();
                 }
             });
+    }
 
-        if ui
-            .add(egui::Button::new("Limpiar filtros").min_size(egui::vec2(120.0, 28.0)))
-            .clicked()
-        {
-            state.automation.cron_board.show_only_enabled = false;
-            state.automation.cron_board.provider_filter = None;
-            state.automation.cron_board.tag_filter = None;
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        if ui.button(t("Importar")).clicked() {
+            state.workflow_bundles.status = Some(match state.import_workflow_bundle() {
+                Ok(summary) => tf(
+                    "Importado: {0} añadidas, {1} reemplazadas y {2} omitidas.",
+                    &[
+                        &summary.added.to_string(),
+                        &summary.replaced.to_string(),
+                        &summary.skipped.to_string(),
+                    ],
+                ),
+                Err(err) => err,
+            });
+        }
+        if ui.button(t("Descartar")).clicked() {
+            state.discard_workflow_bundle();
         }
     });
-
-    let tags = state.automation.cron_board.unique_tags();
-    if !tags.is_empty() {
-        ui.add_space(6.0);
-        ui.horizontal_wrapped(|ui| {
-            ui.spacing_mut().item_spacing.x = 6.0;
-            ui.label(
-                RichText::new(format!("{} Tags", ICON_FOLDER))
-                    .color(theme::color_text_weak())
-                    .size(11.0),
-            );
-            for tag in tags {
-                let selected = state
-                    .automation
-                    .cron_board
-                    .tag_filter
-                    .as_ref()
-                    .map(|current| current.eq_ignore_ascii_case(&tag))
-                    .unwrap_or(false);
-                if selectable_chip(ui, &tag, selected).clicked() {
-                    if selected {
-                        state.automation.cron_board.tag_filter = None;
-                    } else {
-                        state.automation.cron_board.tag_filter = Some(tag);
-                    }
-                }
-            }
-            if state.automation.cron_board.tag_filter.is_some() && ui.button("Quitar tag").clicked()
-            {
-                state.automation.cron_board.tag_filter = None;
-            }
-        });
-    }
 }
 
-fn draw_cron_table(ui: &mut egui::Ui, state: &mut AppState) {
-    let indices = state.automation.cron_board.filtered_indices();
-    if indices.is_empty() {
-        ui.colored_label(
-            theme::color_text_weak(),
-            "No hay tareas que coincidan con los filtros seleccionados.",
-        );
-        state.automation.cron_board.select_task(None);
-        return;
-    }
+fn draw_workflow_card(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    index: usize,
+    workflow: &AutomationWorkflow,
+) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(28, 30, 36))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(12.0))
+        .inner_margin(egui::Margin::symmetric(14.0, 12.0))
+        .show(ui, |ui| {
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(
+                        RichText::new(&workflow.name)
+                            .color(theme::color_text_primary())
+                            .size(15.0)
+                            .strong(),
+                    );
+                    if workflow.pinned {
+                        ui.label(
+                            RichText::new(ICON_STAR)
+                                .font(theme::icon_font(14.0))
+                                .color(Color32::from_rgb(255, 196, 0)),
+                        );
+                    }
+                    ui.add_space(ui.available_width());
+                    ui.label(
+                        RichText::new(workflow.status.label())
+                            .color(workflow_status_color(workflow.status))
+                            .monospace()
+                            .size(11.0),
+                    );
+                });
 
-    let min_height = ui.available_height().max(220.0);
-    TableBuilder::new(ui)
-        .striped(true)
-        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-        .column(Column::initial(36.0))
-        .column(Column::remainder().at_least(160.0))
-        .column(Column::initial(120.0))
-        .column(Column::initial(120.0))
-        .column(Column::initial(120.0))
-        .column(Column::initial(100.0))
-        .column(Column::initial(90.0))
-        .min_scrolled_height(min_height)
-        .header(26.0, |mut header| {
-            header.col(|ui| {
-                ui.label(
-                    RichText::new("Estado")
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                );
-            });
-            header.col(|ui| {
-                ui.label(
-                    RichText::new("Tarea")
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                );
-            });
-            header.col(|ui| {
-                ui.label(
-                    RichText::new("Cadencia")
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                );
-            });
-            header.col(|ui| {
-                ui.label(
-                    RichText::new("Próxima ejecución")
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                );
-            });
-            header.col(|ui| {
-                ui.label(
-                    RichText::new("Última ejecución")
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                );
-            });
-            header.col(|ui| {
-                ui.label(
-                    RichText::new("Proveedor")
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                );
-            });
-            header.col(|ui| {
                 ui.label(
-                    RichText::new("Acciones")
+                    RichText::new(&workflow.description)
                         .color(theme::color_text_weak())
-                        .size(11.0),
+                        .size(12.0),
                 );
-            });
-        })
-        .body(|mut body| {
-            for index in indices {
-                let task_snapshot = state.automation.cron_board.tasks[index].clone();
-                let mut selection_change = None;
-                let mut new_enabled: Option<bool> = None;
-                let mut trigger_run = false;
 
-                body.row(32.0, |mut row| {
-                    row.col(|ui| {
-                        let (rect, _) =
-                            ui.allocate_exact_size(egui::vec2(24.0, 18.0), egui::Sense::hover());
-                        let painter = ui.painter_at(rect);
-                        painter.circle_filled(
-                            rect.center(),
-                            6.0,
-                            cron_status_color(task_snapshot.status),
-                        );
-                    });
-                    row.col(|ui| {
-                        let selected =
-                            state.automation.cron_board.selected_task == Some(task_snapshot.id);
-                        let response = ui.add(egui::SelectableLabel::new(
-                            selected,
-                            RichText::new(&task_snapshot.name)
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(format!("Disparador: {}", workflow.trigger.label()))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                    if let Some(command) = &workflow.chat_command {
+                        ui.add_space(16.0);
+                        ui.label(
+                            RichText::new(format!("Comando: {}", command))
                                 .color(theme::color_text_primary())
-                                .size(13.0),
-                        ));
-                        if response.clicked() {
-                            selection_change = Some(task_snapshot.id);
-                        }
-                    });
-                    row.col(|ui| {
+                                .monospace()
+                                .size(11.0),
+                        );
+                    }
+                    if let Some(cron_id) = workflow.linked_schedule {
+                        ui.add_space(16.0);
                         ui.label(
-                            RichText::new(&task_snapshot.cadence_label)
+                            RichText::new(format!("Vinculado a tarea #{cron_id}"))
                                 .color(theme::color_text_weak())
                                 .size(11.0),
                         );
-                    });
-                    row.col(|ui| {
+                    }
+                });
+
+                ui.add_space(8.0);
+                for step in &workflow.steps {
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 8.0;
                         ui.label(
-                            RichText::new(
-                                task_snapshot
-                                    .next_run
-                                    .clone()
-                                    .unwrap_or_else(|| "—".to_string()),
-                            )
-                            .color(theme::color_text_primary())
-                            .size(11.0),
+                            RichText::new(workflow_step_icon(step.kind))
+                                .font(theme::icon_font(14.0))
+                                .color(theme::color_primary()),
                         );
-                    });
-                    row.col(|ui| {
                         ui.label(
-                            RichText::new(
-                                task_snapshot
-                                    .last_run
-                                    .clone()
-                                    .unwrap_or_else(|| "—".to_string()),
-                            )
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                        );
-                    });
-                    row.col(|ui| {
-                        let badge = task_snapshot
-                            .provider_badge()
-                            .unwrap_or_else(|| "local".to_string());
-                        ui.label(
-                            RichText::new(badge)
-                                .color(theme::color_text_weak())
-                                .monospace(),
+                            RichText::new(format!("{} · {}", step.kind.label(), step.label))
+                                .color(theme::color_text_primary())
+                                .size(12.0),
                         );
+                        if let Some(provider) = step.provider {
+                            ui.label(
+                                RichText::new(format!("@{}", provider.short_code()))
+                                    .color(theme::color_text_weak())
+                                    .size(11.0)
+                                    .monospace(),
+                            );
+                        }
                     });
-                    row.col(|ui| {
-                        ui.horizontal(|ui| {
-                            let mut enabled = task_snapshot.enabled;
-                            if ui.checkbox(&mut enabled, "").changed() {
-                                new_enabled = Some(enabled);
-                            }
-
-                            let run_label = RichText::new(format!("{} Ejecutar", ICON_PLAY))
-                                .color(Color32::from_rgb(240, 240, 240))
-                                .size(11.0);
-                            if ui
-                                .add(egui::Button::new(run_label).min_size(egui::vec2(96.0, 26.0)))
-                                .on_hover_text("Lanzar inmediatamente")
-                                .clicked()
-                            {
-                                trigger_run = true;
-                            }
-                        });
-                    });
-                });
+                    ui.label(
+                        RichText::new(&step.detail)
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                    ui.add_space(4.0);
+                }
 
-                if let Some(task_id) = selection_change {
-                    state.automation.cron_board.select_task(Some(task_id));
+                if let Some(last_run) = &workflow.last_run {
+                    ui.label(
+                        RichText::new(format!("Última ejecución: {last_run}"))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                } else {
+                    ui.label(
+                        RichText::new("Nunca ejecutado")
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
                 }
 
-                if let Some(enabled) = new_enabled {
-                    let mut message = None;
-                    {
-                        let task = &mut state.automation.cron_board.tasks[index];
-                        if task.enabled != enabled {
-                            task.enabled = enabled;
-                            let task_name = task.name.clone();
-                            let text = if enabled {
-                                format!("Tarea '{}' activada", task_name)
-                            } else {
-                                format!("Tarea '{}' pausada", task_name)
-                            };
-                            message = Some(text);
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
+                    let run_button = theme::primary_button(
+                        RichText::new("Lanzar pipeline")
+                            .color(Color32::WHITE)
+                            .strong(),
+                        &state.theme,
+                    )
+                    .min_size(egui::vec2(150.0, 30.0));
+                    if ui.add(run_button).clicked() {
+                        if let Some(message) = state.trigger_workflow(workflow.id) {
+                            ui.colored_label(theme::color_text_weak(), message);
                         }
                     }
-                    if let Some(text) = message {
-                        state.push_debug_event(
-                            DebugLogLevel::Info,
-                            "cron::scheduler",
-                            text.clone(),
-                        );
-                        state.push_activity_log(
-                            if enabled {
-                                LogStatus::Ok
-                            } else {
-                                LogStatus::Warning
-                            },
-                            "Cron",
-                            text,
-                        );
+
+                    ui.add_space(8.0);
+                    let select_button = theme::secondary_button(
+                        RichText::new("Registrar en chat")
+                            .color(theme::color_text_primary())
+                            .strong(),
+                        &state.theme,
+                    )
+                    .min_size(egui::vec2(150.0, 30.0));
+                    if ui.add(select_button).clicked() {
+                        if let Some(message) =
+                            state.automation.workflows.workflows.get(index).map(|wf| {
+                                format!("Workflow '{}' listo para orquestación.", wf.name)
+                            })
+                        {
+                            state.push_activity_log(LogStatus::Ok, "Automation", &message);
+                            state.push_debug_event(
+                                DebugLogLevel::Info,
+                                "automation::note",
+                                message,
+                            );
+                        }
                     }
-                }
 
-                if trigger_run {
-                    let name = {
-                        let task = &mut state.automation.cron_board.tasks[index];
-                        task.status = ScheduledTaskStatus::Running;
-                        task.last_run = Some(Local::now().format("%Y-%m-%d %H:%M").to_string());
-                        task.name.clone()
-                    };
-                    state.push_activity_log(
-                        LogStatus::Running,
-                        "Cron",
-                        format!("Tarea '{}' ejecutada manualmente", name),
-                    );
-                    state.push_debug_event(
-                        DebugLogLevel::Info,
-                        "cron::manual",
-                        format!("Lanzando '{}'", name),
-                    );
-                }
-            }
+                    ui.add_space(8.0);
+                    if ui
+                        .button(t("Exportar"))
+                        .on_hover_text(t("Guarda este workflow como paquete para otra instalación"))
+                        .clicked()
+                    {
+                        state.workflow_bundles.status =
+                            Some(match state.export_workflow_bundle(&[workflow.id]) {
+                                Ok(path) => {
+                                    tf("Paquete exportado a {0}.", &[&path.display().to_string()])
+                                }
+                                Err(err) => err,
+                            });
+                    }
+                });
+            });
         });
 }
 
-fn draw_cron_task_detail(ui: &mut egui::Ui, state: &AppState, task: &crate::state::ScheduledTask) {
+fn workflow_step_icon(kind: WorkflowStepKind) -> &'static str {
+    match kind {
+        WorkflowStepKind::RemoteModel => ICON_LIGHTNING,
+        WorkflowStepKind::LocalScript => ICON_CODE,
+        WorkflowStepKind::SyncAction => ICON_REPEAT,
+    }
+}
+
+fn workflow_status_color(status: WorkflowStatus) -> Color32 {
+    match status {
+        WorkflowStatus::Ready => theme::color_primary(),
+        WorkflowStatus::Running => Color32::from_rgb(64, 172, 255),
+        WorkflowStatus::Failed => theme::color_danger(),
+        WorkflowStatus::Draft => Color32::from_rgb(160, 160, 160),
+    }
+}
+
+fn draw_reminder_panel(ui: &mut egui::Ui, state: &AppState) {
     egui::Frame::none()
         .fill(Color32::from_rgb(34, 36, 42))
         .stroke(theme::subtle_border(&state.theme))
         .rounding(egui::Rounding::same(14.0))
-        .inner_margin(egui::Margin::symmetric(18.0, 14.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
         .show(ui, |ui| {
             ui.horizontal(|ui| {
                 ui.spacing_mut().item_spacing.x = 10.0;
                 ui.label(
-                    RichText::new(ICON_INFO)
+                    RichText::new(ICON_CLOCK)
                         .font(theme::icon_font(16.0))
                         .color(theme::color_primary()),
                 );
                 ui.heading(
-                    RichText::new(&task.name)
+                    RichText::new("Recordatorios programados")
                         .color(theme::color_text_primary())
-                        .size(16.0)
                         .strong(),
                 );
-                ui.add_space(ui.available_width());
-                ui.label(
-                    RichText::new(task.status.label())
-                        .color(cron_status_color(task.status))
-                        .monospace(),
-                );
             });
-            ui.add_space(4.0);
             ui.label(
-                RichText::new(&task.description)
-                    .color(theme::color_text_weak())
-                    .size(12.0),
+                RichText::new(
+                    "Visualiza próximos avisos y confirma su canal de entrega en tiempo real.",
+                )
+                .color(theme::color_text_weak())
+                .size(12.0),
             );
 
             ui.add_space(8.0);
-            ui.horizontal(|ui| {
-                ui.label(
-                    RichText::new(format!("Expresión cron: `{}`", task.cron_expression))
-                        .color(theme::color_text_weak())
-                        .monospace(),
+            if state.automation.scheduled_reminders.is_empty() {
+                ui.colored_label(
+                    theme::color_text_weak(),
+                    "No existen recordatorios activos por ahora.",
                 );
-            });
-
-            if !task.tags.is_empty() {
-                ui.add_space(8.0);
-                ui.horizontal_wrapped(|ui| {
-                    ui.spacing_mut().item_spacing.x = 6.0;
-                    for tag in &task.tags {
-                        selectable_chip(ui, tag, false);
-                    }
-                });
+                return;
             }
 
-            ui.add_space(10.0);
-            let badge = task.provider_badge().unwrap_or_else(|| "local".to_string());
-            ui.label(
-                RichText::new(format!(
-                    "Responsable: {} · Proveedor: {}",
-                    task.owner, badge
-                ))
-                .color(theme::color_text_weak())
-                .size(11.0),
-            );
+            for reminder in &state.automation.scheduled_reminders {
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(28, 30, 36))
+                    .stroke(theme::subtle_border(&state.theme))
+                    .rounding(egui::Rounding::same(10.0))
+                    .inner_margin(egui::Margin::symmetric(12.0, 10.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            let color = reminder_status_color(reminder.status);
+                            ui.label(RichText::new("●").color(color).size(14.0).monospace());
+                            ui.label(
+                                RichText::new(format!("#{} {}", reminder.id, reminder.title))
+                                    .color(theme::color_text_primary())
+                                    .strong()
+                                    .size(13.0),
+                            );
+                            ui.add_space(ui.available_width());
+                            ui.label(
+                                RichText::new(reminder.status.label())
+                                    .color(color)
+                                    .size(11.0)
+                                    .monospace(),
+                            );
+                        });
+                        ui.label(
+                            RichText::new(format!(
+                                "Cadencia: {} · Próximo envío {}",
+                                reminder.cadence, reminder.next_trigger
+                            ))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                        );
+                        ui.label(
+                            RichText::new(format!(
+                                "Canal: {} · Audiencia: {}",
+                                reminder.delivery_channel, reminder.audience
+                            ))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                        );
+                    });
+                ui.add_space(6.0);
+            }
+        });
+}
 
-            if let Some(status) = state
-                .automation
-                .activity_logs
-                .iter()
-                .rev()
-                .find(|entry| entry.source == "Cron")
-            {
-                ui.add_space(6.0);
-                ui.label(
-                    RichText::new(format!(
-                        "Última actividad registrada: {} ({})",
-                        status.message, status.timestamp
-                    ))
-                    .color(theme::color_text_weak())
-                    .size(11.0),
-                );
-            }
-        });
-}
-
-fn cron_status_color(status: ScheduledTaskStatus) -> Color32 {
+fn reminder_status_color(status: ReminderStatus) -> Color32 {
     match status {
-        ScheduledTaskStatus::Scheduled => theme::color_primary(),
-        ScheduledTaskStatus::Running => Color32::from_rgb(64, 172, 255),
-        ScheduledTaskStatus::Success => theme::color_success(),
-        ScheduledTaskStatus::Failed => theme::color_danger(),
-        ScheduledTaskStatus::Paused => Color32::from_rgb(160, 160, 160),
+        ReminderStatus::Scheduled => theme::color_primary(),
+        ReminderStatus::Sent => theme::color_success(),
+        ReminderStatus::Snoozed => Color32::from_rgb(255, 196, 0),
     }
 }
 
-fn draw_debug_summary(
-    ui: &mut egui::Ui,
-    info: usize,
-    warning: usize,
-    error: usize,
-    tokens: &ThemeTokens,
-) {
-    ui.horizontal(|ui| {
-        summary_chip(ui, ICON_INFO, "Info", info, theme::color_primary(), tokens);
-        summary_chip(
-            ui,
-            ICON_LIGHTNING,
-            "Warnings",
-            warning,
-            Color32::from_rgb(255, 196, 0),
-            tokens,
-        );
-        summary_chip(
-            ui,
-            ICON_STOP,
-            "Errores",
-            error,
-            theme::color_danger(),
-            tokens,
-        );
-    });
-}
-
-fn draw_debug_filters(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.horizontal(|ui| {
-        let search_width = (ui.available_width() - 160.0).max(200.0);
-        ui.add_sized(
-            [search_width, 28.0],
-            egui::TextEdit::singleline(&mut state.debug_console.search)
-                .hint_text("Buscar por mensaje o componente"),
-        );
-        if ui
-            .add_sized([120.0, 28.0], egui::Button::new("Limpiar búsqueda"))
-            .clicked()
-        {
-            state.debug_console.search.clear();
-        }
-    });
-
-    ui.add_space(6.0);
-    ui.horizontal(|ui| {
-        let selected_text = match state.debug_console.level_filter {
-            Some(DebugLogLevel::Info) => "Solo INFO",
-            Some(DebugLogLevel::Warning) => "Solo WARN",
-            Some(DebugLogLevel::Error) => "Solo ERR",
-            None => "Todos los niveles",
-        };
-        egui::ComboBox::from_id_source("debug_level_filter")
-            .selected_text(selected_text)
-            .show_ui(ui, |ui| {
-                if ui
-                    .selectable_label(state.debug_console.level_filter.is_none(), "Todos")
-                    .clicked()
-                {
-                    state.debug_console.level_filter = None;
-                }
-                for level in [
-                    DebugLogLevel::Info,
-                    DebugLogLevel::Warning,
-                    DebugLogLevel::Error,
-                ] {
-                    let selected = state.debug_console.level_filter == Some(level);
-                    if ui.selectable_label(selected, level.label()).clicked() {
-                        state.debug_console.level_filter = Some(level);
-                    }
-                }
+fn draw_listener_panel(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(14.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 10.0;
+                ui.label(
+                    RichText::new(ICON_INFO)
+                        .font(theme::icon_font(16.0))
+                        .color(theme::color_primary()),
+                );
+                ui.heading(
+                    RichText::new("Listeners y disparadores")
+                        .color(theme::color_text_primary())
+                        .strong(),
+                );
+                ui.add_space(ui.available_width());
+                ui.checkbox(
+                    &mut state.automation.event_automation.show_only_enabled,
+                    "Solo activos",
+                )
+                .on_hover_text("Oculta listeners deshabilitados");
             });
+            ui.label(
+                RichText::new(
+                    "Configura automatizaciones basadas en eventos de chat, repositorios o jobs.",
+                )
+                .color(theme::color_text_weak())
+                .size(12.0),
+            );
 
-        if ui
-            .checkbox(&mut state.debug_console.auto_scroll, "Auto-scroll")
-            .changed()
-        {
-            // nothing extra
-        }
-
-        if ui
-            .add_sized([120.0, 28.0], egui::Button::new("Limpiar consola"))
-            .clicked()
-        {
-            state.debug_console.entries.clear();
-        }
-    });
-}
+            ui.add_space(8.0);
+            let indices: Vec<usize> = state
+                .automation
+                .event_automation
+                .listeners
+                .iter()
+                .enumerate()
+                .filter(|(_, listener)| {
+                    if state.automation.event_automation.show_only_enabled && !listener.enabled {
+                        return false;
+                    }
+                    true
+                })
+                .map(|(idx, _)| idx)
+                .collect();
 
-fn draw_debug_entries(ui: &mut egui::Ui, state: &AppState) {
-    let entries = state.debug_console.filtered_entries();
-    if entries.is_empty() {
-        ui.colored_label(
-            theme::color_text_weak(),
-            "Sin eventos registrados bajo los filtros actuales.",
-        );
-        return;
-    }
+            if indices.is_empty() {
+                ui.colored_label(
+                    theme::color_text_weak(),
+                    "No hay listeners configurados para estos filtros.",
+                );
+                return;
+            }
 
-    egui::ScrollArea::vertical()
-        .id_source("debug_console_scroll")
-        .stick_to_bottom(state.debug_console.auto_scroll)
-        .auto_shrink([false, false])
-        .show(ui, |ui| {
-            for entry in entries {
+            for index in indices {
+                let listener_snapshot = state.automation.event_automation.listeners[index].clone();
                 egui::Frame::none()
-                    .fill(Color32::from_rgb(32, 34, 40))
+                    .fill(Color32::from_rgb(28, 30, 36))
                     .stroke(theme::subtle_border(&state.theme))
                     .rounding(egui::Rounding::same(10.0))
-                    .inner_margin(egui::Margin::symmetric(14.0, 10.0))
+                    .inner_margin(egui::Margin::symmetric(12.0, 10.0))
                     .show(ui, |ui| {
                         ui.horizontal(|ui| {
                             ui.label(
-                                RichText::new(entry.level.label())
-                                    .color(debug_level_color(entry.level))
-                                    .monospace(),
-                            );
-                            ui.label(
-                                RichText::new(&entry.timestamp)
-                                    .color(theme::color_text_weak())
-                                    .monospace()
-                                    .size(11.0),
+                                RichText::new(&listener_snapshot.name)
+                                    .color(theme::color_text_primary())
+                                    .strong()
+                                    .size(13.0),
                             );
                             ui.add_space(ui.available_width());
                             ui.label(
-                                RichText::new(&entry.component)
-                                    .color(theme::color_text_primary())
-                                    .monospace()
+                                RichText::new(listener_snapshot.event.label())
+                                    .color(theme::color_text_weak())
                                     .size(11.0),
                             );
                         });
+                        ui.label(
+                            RichText::new(&listener_snapshot.description)
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
                         ui.add_space(4.0);
                         ui.label(
-                            RichText::new(&entry.message)
+                            RichText::new(format!("Condición: {}", listener_snapshot.condition))
                                 .color(theme::color_text_weak())
-                                .size(12.0),
+                                .size(11.0)
+                                .monospace(),
+                        );
+                        ui.label(
+                            RichText::new(format!("Acción: {}", listener_snapshot.action))
+                                .color(theme::color_text_primary())
+                                .size(11.0)
+                                .monospace(),
                         );
+                        if let Some(last) = &listener_snapshot.last_triggered {
+                            ui.label(
+                                RichText::new(format!("Último disparo: {last}"))
+                                    .color(theme::color_text_weak())
+                                    .size(11.0),
+                            );
+                        }
+
+                        ui.add_space(6.0);
+                        let mut enabled_label = "Deshabilitar";
+                        if !listener_snapshot.enabled {
+                            enabled_label = "Habilitar";
+                        }
+                        let toggle_button = theme::secondary_button(
+                            RichText::new(enabled_label)
+                                .color(theme::color_text_primary())
+                                .strong(),
+                            &state.theme,
+                        )
+                        .min_size(egui::vec2(130.0, 28.0));
+                        if ui.add(toggle_button).clicked() {
+                            state.toggle_listener_enabled(listener_snapshot.id);
+                        }
                     });
                 ui.add_space(6.0);
             }
         });
 }
 
-fn debug_level_color(level: DebugLogLevel) -> Color32 {
-    match level {
-        DebugLogLevel::Info => theme::color_primary(),
-        DebugLogLevel::Warning => Color32::from_rgb(255, 196, 0),
-        DebugLogLevel::Error => theme::color_danger(),
-    }
-}
-
-fn draw_chat_history(ui: &mut egui::Ui, state: &mut AppState) {
-    let mut pending_actions = Vec::new();
-
-    let max_width = ui.available_width().min(580.0);
-    let target_height = ui.available_height();
-    ui.allocate_ui_with_layout(
-        egui::vec2(max_width, target_height),
-        egui::Layout::top_down(egui::Align::LEFT),
-        |ui| {
-            ui.set_width(max_width);
-            egui::Frame::none()
-                .fill(Color32::from_rgb(26, 28, 32))
-                .stroke(theme::subtle_border(&state.theme))
-                .rounding(egui::Rounding::same(16.0))
-                .inner_margin(egui::Margin {
-                    left: 20.0,
-                    right: 12.0,
-                    top: 20.0,
-                    bottom: 18.0,
-                })
-                .show(ui, |ui| {
-                    let available_height = ui.available_height();
-                    ui.set_min_height(available_height);
-                    ui.set_width(ui.available_width());
+fn draw_knowledge_reindex_panel(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(14.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 10.0;
+                ui.label(
+                    RichText::new(ICON_CLOCK)
+                        .font(theme::icon_font(16.0))
+                        .color(theme::color_primary()),
+                );
+                ui.heading(
+                    RichText::new(t("Reindexar base de conocimiento"))
+                        .color(theme::color_text_primary())
+                        .strong(),
+                );
+            });
+            ui.label(
+                RichText::new(t(
+                    "Programa por proyecto la actualización del índice semántico. Solo se releen los documentos que han cambiado desde la última pasada.",
+                ))
+                .color(theme::color_text_weak())
+                .size(12.0),
+            );
 
-                    egui::ScrollArea::vertical()
-                        .id_source("chat_history_scroll")
-                        .stick_to_bottom(true)
-                        .auto_shrink([false, false])
-                        .show(ui, |ui| {
-                            let feed_width = ui.available_width().min(540.0);
-                            ui.set_width(feed_width);
-                            for (index, message) in state.chat.messages.iter().enumerate() {
-                                draw_message_bubble(
-                                    ui,
-                                    state,
-                                    message,
-                                    index,
-                                    &mut pending_actions,
-                                );
-                            }
-                        });
-                });
-        },
-    );
+            ui.add_space(8.0);
+            let projects: Vec<String> = state
+                .resources
+                .project_resources
+                .iter()
+                .filter(|card| card.kind == ProjectResourceKind::LocalProject)
+                .map(|card| card.name.clone())
+                .collect();
+            ui.horizontal(|ui| {
+                let selected = if state.knowledge_index.draft_project.is_empty() {
+                    t("Elige un proyecto").to_string()
+                } else {
+                    state.knowledge_index.draft_project.clone()
+                };
+                egui::ComboBox::from_id_source("knowledge_reindex_project")
+                    .selected_text(selected)
+                    .width(220.0)
+                    .show_ui(ui, |ui| {
+                        for project in &projects {
+                            let documents = state.knowledge_index.document_count(project);
+                            let label = if documents > 0 {
+                                format!("{} · {} docs", project, documents)
+                            } else {
+                                project.clone()
+                            };
+                            ui.selectable_value(
+                                &mut state.knowledge_index.draft_project,
+                                project.clone(),
+                                label,
+                            );
+                        }
+                    });
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.knowledge_index.draft_cron)
+                        .hint_text(crate::state::knowledge_index::DEFAULT_REINDEX_CRON)
+                        .desired_width(120.0)
+                        .font(egui::TextStyle::Monospace),
+                );
+                if ui.button(t("Programar")).clicked() {
+                    if let Err(err) = state.add_knowledge_reindex_schedule() {
+                        state.knowledge_index.status = Some(err);
+                    }
+                }
+            });
 
-    apply_pending_actions(state, pending_actions);
+            if let Some(status) = &state.knowledge_index.status {
+                ui.colored_label(theme::color_text_weak(), status);
+            }
+        });
 }
 
-fn draw_model_routing_bar(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.vertical(|ui| {
-        ui.spacing_mut().item_spacing.y = 4.0;
-        ui.label(
-            RichText::new("Enrutamiento por alias")
-                .color(theme::color_text_primary())
-                .strong()
-                .size(13.0),
-        );
-
-        let status = state
-            .chat_routing
-            .status
-            .as_deref()
-            .unwrap_or("Menciona @alias de un proveedor para enviarle parte de tu mensaje.");
-
-        ui.horizontal(|ui| {
-            ui.spacing_mut().item_spacing.x = 6.0;
-            ui.label(
-                RichText::new(ICON_LIGHTNING)
-                    .font(theme::icon_font(13.0))
-                    .color(theme::color_primary()),
-            );
+fn draw_file_watcher_panel(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(14.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 10.0;
+                ui.label(
+                    RichText::new(ICON_INFO)
+                        .font(theme::icon_font(16.0))
+                        .color(theme::color_primary()),
+                );
+                ui.heading(
+                    RichText::new(t("Vigilante de ficheros"))
+                        .color(theme::color_text_primary())
+                        .strong(),
+                );
+                ui.add_space(ui.available_width());
+                let mut enabled = state.config.file_watcher.enabled;
+                if ui.checkbox(&mut enabled, t("Activo")).changed() {
+                    state.set_file_watcher_enabled(enabled);
+                }
+            });
             ui.label(
-                RichText::new(status)
-                    .color(theme::color_text_weak())
-                    .size(12.0),
+                RichText::new(t(
+                    "Los cambios en las rutas vigiladas llegan al flujo de eventos y disparan los listeners de tipo «Cambio de fichero». Usa la acción workflow:<id> para lanzar un workflow.",
+                ))
+                .color(theme::color_text_weak())
+                .size(12.0),
             );
-        });
-    });
 
-    if !state.chat_routing.suggestions.is_empty() {
-        ui.add_space(6.0);
-        let suggestions = state.chat_routing.suggestions.clone();
-        ui.horizontal_wrapped(|ui| {
-            ui.spacing_mut().item_spacing.x = 10.0;
-            for suggestion in &suggestions {
-                ui.vertical(|ui| {
-                    let response = ui
-                        .add(
-                            egui::Button::new(
-                                RichText::new(&suggestion.title)
-                                    .color(Color32::from_rgb(240, 240, 240))
-                                    .size(12.0),
-                            )
-                            .fill(Color32::from_rgb(44, 46, 54))
-                            .rounding(egui::Rounding::same(10.0)),
-                        )
-                        .on_hover_text(&suggestion.description);
+            ui.add_space(8.0);
+            let mut remove = None;
+            for (index, watch) in state.config.file_watcher.paths.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(&watch.path).monospace().size(12.0));
+                    let pattern = if watch.pattern.trim().is_empty() {
+                        "*"
+                    } else {
+                        watch.pattern.as_str()
+                    };
+                    ui.label(
+                        RichText::new(pattern)
+                            .color(theme::color_text_weak())
+                            .monospace()
+                            .size(12.0),
+                    );
+                    if ui.small_button(t("Quitar")).clicked() {
+                        remove = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = remove {
+                state.remove_watched_path(index);
+            }
 
-                    if response.clicked() {
-                        let provider = suggestion.provider;
-                        state.chat_routing.update_status(Some(format!(
-                            "Recuerda mencionar @{} para {}.",
-                            provider.short_code(),
-                            suggestion.title.as_str()
-                        )));
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.file_watcher.draft.path)
+                        .hint_text("~/Proyectos/notas")
+                        .desired_width(240.0),
+                );
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.file_watcher.draft.pattern)
+                        .hint_text("**/*.md")
+                        .desired_width(120.0),
+                );
+                if ui.button(t("Vigilar")).clicked() {
+                    if let Err(err) = state.add_watched_path() {
+                        state.file_watcher.status = Some(err);
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(t("Agrupar cambios durante"));
+                let response = ui.add(
+                    egui::DragValue::new(&mut state.config.file_watcher.debounce_millis)
+                        .clamp_range(50..=10_000)
+                        .speed(10)
+                        .suffix(" ms"),
+                );
+                if response.drag_stopped() || response.lost_focus() {
+                    state.persist_config();
+                    if state.config.file_watcher.enabled {
+                        let _ = state.restart_file_watcher();
                     }
+                }
+            });
 
-                    if !suggestion.tags.is_empty() {
-                        ui.add_space(4.0);
-                        ui.horizontal_wrapped(|ui| {
-                            ui.spacing_mut().item_spacing.x = 4.0;
-                            for tag in &suggestion.tags {
-                                let _ = selectable_chip(ui, tag, false);
-                            }
-                        });
+            if let Some(status) = &state.file_watcher.status {
+                ui.colored_label(theme::color_text_weak(), status);
+            }
+
+            if !state.file_watcher.recent.is_empty() {
+                ui.add_space(6.0);
+                egui::CollapsingHeader::new(tf(
+                    "Cambios recientes ({0})",
+                    &[&state.file_watcher.recent.len().to_string()],
+                ))
+                .id_source("file_watcher_recent")
+                .show(ui, |ui| {
+                    for event in &state.file_watcher.recent {
+                        ui.label(
+                            RichText::new(format!(
+                                "{} · {} · {}",
+                                event.timestamp,
+                                t(event.kind.label()),
+                                event.path.display()
+                            ))
+                            .size(11.0)
+                            .color(theme::color_text_weak()),
+                        );
                     }
                 });
             }
         });
-    }
-}
-
-fn insert_quick_token(state: &mut AppState, token: &str) {
-    if !state.chat.input.is_empty() && !state.chat.input.ends_with(' ') {
-        state.chat.input.push(' ');
-    }
-    state.chat.input.push_str(token);
-    if !token.ends_with(' ') {
-        state.chat.input.push(' ');
-    }
 }
 
-fn draw_message_bubble(
-    ui: &mut egui::Ui,
-    state: &AppState,
-    message: &ChatMessage,
-    index: usize,
-    pending_actions: &mut Vec<PendingChatAction>,
-) {
-    ui.add_space(if index == 0 { 0.0 } else { 10.0 });
+fn draw_integration_panel(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(14.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 10.0;
+                ui.label(
+                    RichText::new(ICON_LINK)
+                        .font(theme::icon_font(16.0))
+                        .color(theme::color_primary()),
+                );
+                ui.heading(
+                    RichText::new("Integraciones externas")
+                        .color(theme::color_text_primary())
+                        .strong(),
+                );
+            });
+            ui.label(
+                RichText::new(
+                    "Gmail, Calendar, CI/CD e IFTTT se orquestan como triggers y acciones del agente.",
+                )
+                .color(theme::color_text_weak())
+                .size(12.0),
+            );
 
-    let is_user = message.sender == "User";
-    let is_system = message.sender == "System";
-    let (background, border, icon, accent) = if is_user {
-        (
-            Color32::from_rgb(34, 48, 70),
-            Color32::from_rgb(62, 120, 192),
-            ICON_USER,
-            Color32::from_rgb(130, 180, 240),
-        )
-    } else if is_system {
-        (
-            Color32::from_rgb(36, 36, 36),
-            Color32::from_rgb(88, 88, 88),
-            ICON_SYSTEM,
-            Color32::from_rgb(200, 200, 200),
-        )
-    } else {
-        (
-            Color32::from_rgb(30, 36, 46),
-            Color32::from_rgb(70, 110, 180),
-            ICON_ASSISTANT,
-            Color32::from_rgb(150, 200, 255),
-        )
-    };
+            ui.add_space(8.0);
+            if state.automation.external_integrations.connectors.is_empty() {
+                ui.colored_label(
+                    theme::color_text_weak(),
+                    "Sin conectores registrados todavía.",
+                );
+            }
 
-    let layout = if is_user {
-        egui::Layout::right_to_left(egui::Align::TOP)
-    } else {
-        egui::Layout::left_to_right(egui::Align::TOP)
-    };
+            for connector in &state.automation.external_integrations.connectors {
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(28, 30, 36))
+                    .stroke(theme::subtle_border(&state.theme))
+                    .rounding(egui::Rounding::same(10.0))
+                    .inner_margin(egui::Margin::symmetric(12.0, 10.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(connector.service.label())
+                                    .color(theme::color_text_primary())
+                                    .strong()
+                                    .size(12.0),
+                            );
+                            ui.add_space(8.0);
+                            ui.label(
+                                RichText::new(format!("#{} {}", connector.id, connector.name))
+                                    .color(theme::color_text_weak())
+                                    .size(11.0),
+                            );
+                            ui.add_space(ui.available_width());
+                            ui.label(
+                                RichText::new(connector.status.label())
+                                    .color(integration_status_color(connector.status))
+                                    .size(11.0)
+                                    .monospace(),
+                            );
+                        });
+                        ui.label(
+                            RichText::new(&connector.status_detail)
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                        if let Some(last) = &connector.last_event {
+                            ui.label(
+                                RichText::new(format!("Último evento: {last}"))
+                                    .color(theme::color_text_weak())
+                                    .size(11.0),
+                            );
+                        }
+                        if let Some(next) = &connector.next_sync {
+                            ui.label(
+                                RichText::new(format!("Próxima sincronización: {next}"))
+                                    .color(theme::color_text_weak())
+                                    .size(11.0),
+                            );
+                        }
+                        if !connector.metadata.is_empty() {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.spacing_mut().item_spacing.x = 6.0;
+                                for entry in &connector.metadata {
+                                    selectable_chip(ui, entry, false);
+                                }
+                            });
+                        }
+                        if !connector.quick_actions.is_empty() {
+                            ui.add_space(6.0);
+                            ui.horizontal(|ui| {
+                                for action in &connector.quick_actions {
+                                    let button = theme::secondary_button(
+                                        RichText::new(action)
+                                            .color(theme::color_text_primary())
+                                            .strong(),
+                                        &state.theme,
+                                    )
+                                    .min_size(egui::vec2(130.0, 26.0));
+                                    ui.add(button);
+                                    ui.add_space(6.0);
+                                }
+                            });
+                        }
+                    });
+                ui.add_space(6.0);
+            }
 
-    ui.with_layout(layout, |ui| {
-        let available_width = ui.available_width();
-        let mut bubble_width = if available_width > 32.0 {
-            (available_width - 16.0).max(available_width * 0.6)
-        } else {
-            available_width
-        };
-        if available_width > 320.0 {
-            bubble_width = bubble_width.clamp(320.0, available_width);
-        }
-        bubble_width = bubble_width.min(available_width);
+            ui.add_space(8.0);
+            draw_webhook_connectors(ui, state);
+        });
+}
 
-        ui.add_space(8.0);
-        let frame = egui::Frame::none()
-            .fill(background)
-            .stroke(egui::Stroke::new(1.4, border))
-            .rounding(egui::Rounding::same(14.0))
-            .inner_margin(egui::Margin::same(16.0));
+/// Conectores salientes de Slack y Discord, con su prueba de envío y el alta de nuevos.
+fn draw_webhook_connectors(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.label(
+        RichText::new(t("Webhooks salientes"))
+            .color(theme::color_text_primary())
+            .strong(),
+    );
+    ui.label(
+        RichText::new(t(
+            "Los pasos de sincronización de los workflows que nombran un conector, o su servicio, publican en su canal. La URL de cada webhook se guarda en el llavero del sistema.",
+        ))
+        .color(theme::color_text_weak())
+        .size(11.0),
+    );
+    ui.add_space(6.0);
 
-        let response = frame.show(ui, |ui| {
-            ui.set_width(bubble_width);
-            ui.vertical(|ui| {
-                draw_message_header(ui, message, icon, accent, pending_actions);
+    let mut changed = false;
+    let mut test = None;
+    let mut remove = None;
+    for connector in state.config.webhook_connectors.iter_mut() {
+        egui::Frame::none()
+            .fill(Color32::from_rgb(28, 30, 36))
+            .stroke(theme::subtle_border(&state.theme))
+            .rounding(egui::Rounding::same(10.0))
+            .inner_margin(egui::Margin::symmetric(12.0, 10.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(connector.kind.label())
+                            .color(theme::color_text_primary())
+                            .strong()
+                            .size(12.0),
+                    );
+                    ui.add_space(8.0);
+                    ui.label(
+                        RichText::new(&connector.name)
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                });
+                ui.horizontal_wrapped(|ui| {
+                    changed |= ui.checkbox(&mut connector.enabled, t("Activo")).changed();
+                    changed |= ui
+                        .checkbox(&mut connector.forward_notifications, t("Reenviar avisos"))
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut connector.post_digest, t("Publicar el resumen diario"))
+                        .changed();
+                });
                 ui.add_space(6.0);
-                draw_message_body(ui, message, accent);
-                draw_developer_artifacts(ui, message, &state.theme);
+                ui.horizontal(|ui| {
+                    let button = theme::secondary_button(
+                        RichText::new(t("Enviar prueba"))
+                            .color(theme::color_text_primary())
+                            .strong(),
+                        &state.theme,
+                    )
+                    .min_size(egui::vec2(130.0, 26.0));
+                    if ui.add_enabled(connector.enabled, button).clicked() {
+                        test = Some(connector.name.clone());
+                    }
+                    ui.add_space(6.0);
+                    if ui.small_button(t("Quitar")).clicked() {
+                        remove = Some(connector.name.clone());
+                    }
+                });
             });
-        });
-
-        if response.response.double_clicked() && !is_user && !message.is_pending() {
-            pending_actions.push(PendingChatAction::Mention(format!(
-                "@{}",
-                message.sender.to_lowercase()
-            )));
-        }
-    });
-}
+        ui.add_space(6.0);
+    }
+    if changed {
+        state.persist_config();
+    }
+    if let Some(name) = test {
+        state.test_webhook_connector(&name);
+    }
+    if let Some(name) = remove {
+        state.remove_webhook_connector(&name);
+    }
 
-fn draw_message_header(
-    ui: &mut egui::Ui,
-    message: &ChatMessage,
-    icon: &str,
-    accent: Color32,
-    pending_actions: &mut Vec<PendingChatAction>,
-) {
     ui.horizontal(|ui| {
-        ui.spacing_mut().item_spacing.x = 8.0;
-        ui.label(
-            RichText::new(icon)
-                .font(theme::icon_font(16.0))
-                .color(accent),
+        ui.add(
+            egui::TextEdit::singleline(&mut state.webhooks.name_draft)
+                .hint_text(t("Nombre"))
+                .desired_width(120.0),
         );
-        let sender_label = message.sender_display_label();
-        ui.label(
-            RichText::new(sender_label.as_ref())
-                .strong()
-                .color(theme::color_text_primary()),
+        egui::ComboBox::from_id_source("webhook_kind_draft")
+            .selected_text(state.webhooks.kind_draft.label())
+            .show_ui(ui, |ui| {
+                for kind in WebhookKind::ALL {
+                    ui.selectable_value(&mut state.webhooks.kind_draft, kind, kind.label());
+                }
+            });
+        ui.add(
+            egui::TextEdit::singleline(&mut state.webhooks.url_draft)
+                .password(true)
+                .hint_text(t("URL del webhook"))
+                .desired_width(240.0),
         );
-        if let Some(origin) = message.origin {
-            ui.label(
-                RichText::new(origin.display_name())
-                    .color(accent)
-                    .size(12.0)
-                    .italics(),
-            );
+        if ui.button(t("Añadir conector")).clicked() {
+            if let Err(err) = state.add_webhook_connector() {
+                state.webhooks.status = Some(err);
+            }
+        }
+    });
+    ui.horizontal(|ui| {
+        if state.webhooks.is_posting() {
+            ui.spinner();
+        }
+        if let Some(status) = &state.webhooks.status {
+            ui.colored_label(theme::color_text_weak(), status);
         }
-        ui.label(
-            RichText::new(ICON_CLOCK)
-                .font(theme::icon_font(12.0))
-                .color(theme::color_text_weak()),
-        );
-        ui.label(
-            RichText::new(&message.timestamp)
-                .italics()
-                .size(12.0)
-                .color(theme::color_text_weak()),
-        );
-        ui.add_space(ui.available_width());
-        draw_message_actions(ui, message, pending_actions);
     });
 }
 
-fn draw_message_actions(
-    ui: &mut egui::Ui,
-    message: &ChatMessage,
-    pending_actions: &mut Vec<PendingChatAction>,
-) {
-    let enabled = !message.is_pending();
-
-    if message_action_button(ui, ICON_COPY, "Copiar mensaje al portapapeles", enabled).clicked() {
-        let text = message.combined_text();
-        ui.output_mut(|out| out.copied_text = text);
+fn integration_status_color(status: IntegrationStatus) -> Color32 {
+    match status {
+        IntegrationStatus::Connected => theme::color_success(),
+        IntegrationStatus::Warning => Color32::from_rgb(255, 196, 0),
+        IntegrationStatus::Error => theme::color_danger(),
+        IntegrationStatus::Syncing => Color32::from_rgb(64, 172, 255),
     }
+}
 
-    if message_action_button(ui, ICON_QUOTE, "Citar mensaje en el input", enabled).clicked() {
-        let mut quoted = message
-            .combined_text()
-            .lines()
-            .map(|line| format!("> {}", line))
-            .collect::<Vec<_>>()
-            .join("\n");
-        quoted.push_str("\n\n");
-        pending_actions.push(PendingChatAction::Quote(quoted));
-    }
-
-    if message_action_button(ui, ICON_PIN, "Reutilizar este mensaje", enabled).clicked() {
-        pending_actions.push(PendingChatAction::Reuse(message.combined_text()));
-    }
-}
+fn draw_cron_filters(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        let toggle = ui.checkbox(
+            &mut state.automation.cron_board.show_only_enabled,
+            "Solo habilitadas",
+        );
+        toggle.on_hover_text("Oculta tareas desactivadas o pausadas");
 
-fn message_action_button(
-    ui: &mut egui::Ui,
-    icon: &str,
-    tooltip: &str,
-    enabled: bool,
-) -> egui::Response {
-    let button = egui::Button::new(
-        RichText::new(icon)
-            .font(theme::icon_font(13.0))
-            .color(Color32::from_rgb(230, 230, 230)),
-    )
-    .min_size(egui::vec2(30.0, 26.0))
-    .fill(Color32::from_rgb(44, 46, 54))
-    .rounding(egui::Rounding::same(6.0));
+        let provider_text = state
+            .automation
+            .cron_board
+            .provider_filter
+            .map(|provider| provider.display_name().to_string())
+            .unwrap_or_else(|| "Todos los proveedores".to_string());
+        egui::ComboBox::from_id_source("cron_provider_filter")
+            .selected_text(provider_text)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(
+                        state.automation.cron_board.provider_filter.is_none(),
+                        "Todos",
+                    )
+                    .clicked()
+                {
+                    state.automation.cron_board.provider_filter = None;
+                }
+                for provider in [
+                    RemoteProviderKind::Anthropic,
+                    RemoteProviderKind::OpenAi,
+                    RemoteProviderKind::Groq,
+                ] {
+                    let selected = state.automation.cron_board.provider_filter == Some(provider);
+                    let label = format!("{} ({})", provider.display_name(), provider.short_code());
+                    if ui.selectable_label(selected, label).clicked() {
+                        state.automation.cron_board.provider_filter = Some(provider);
+                    }
+                }
+            });
 
-    let response = ui.add_enabled(enabled, button);
-    response.on_hover_text(tooltip)
-}
+        if ui
+            .add(egui::Button::new("Limpiar filtros").min_size(egui::vec2(120.0, 28.0)))
+            .clicked()
+        {
+            state.automation.cron_board.show_only_enabled = false;
+            state.automation.cron_board.provider_filter = None;
+            state.automation.cron_board.tag_filter = None;
+        }
+    });
 
-fn draw_message_body(ui: &mut egui::Ui, message: &ChatMessage, accent: Color32) {
-    if message.is_pending() {
-        let pending_text = message.combined_text();
-        ui.horizontal(|ui| {
-            ui.add(Spinner::new().size(18.0));
+    let tags = state.automation.cron_board.unique_tags();
+    if !tags.is_empty() {
+        ui.add_space(6.0);
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 6.0;
             ui.label(
-                RichText::new(pending_text)
+                RichText::new(format!("{} Tags", ICON_FOLDER))
                     .color(theme::color_text_weak())
-                    .italics()
-                    .size(14.0),
+                    .size(11.0),
             );
-        });
-        return;
-    }
-
-    let display_text = message.combined_text();
-    let blocks = parse_markdown_blocks(&display_text);
-    if blocks.is_empty() {
-        render_formatted_text(ui, &display_text, theme::color_text_primary(), 15.0);
-    } else {
-        render_markdown_blocks(ui, &blocks, accent);
-    }
-}
-
-fn render_markdown_blocks(ui: &mut egui::Ui, blocks: &[MarkdownBlock], accent: Color32) {
-    let mut first = true;
-    for block in blocks {
-        if !first {
-            ui.add_space(6.0);
-        }
-        first = false;
-
-        match block {
-            MarkdownBlock::Heading { level, text } => {
-                let size = match level {
-                    1 => 20.0,
-                    2 => 18.0,
-                    3 => 16.0,
-                    _ => 15.0,
-                };
-                ui.label(RichText::new(text).color(accent).strong().size(size));
-            }
-            MarkdownBlock::Paragraph(text) => {
-                render_formatted_text(ui, text, theme::color_text_primary(), 15.0);
-            }
-            MarkdownBlock::BulletList(items) => {
-                ui.vertical(|ui| {
-                    for item in items {
-                        ui.horizontal(|ui| {
-                            ui.spacing_mut().item_spacing.x = 8.0;
-                            ui.label(RichText::new("•").color(accent).strong().size(16.0));
-                            render_formatted_text(ui, item, theme::color_text_primary(), 15.0);
-                        });
+            for tag in tags {
+                let selected = state
+                    .automation
+                    .cron_board
+                    .tag_filter
+                    .as_ref()
+                    .map(|current| current.eq_ignore_ascii_case(&tag))
+                    .unwrap_or(false);
+                if selectable_chip(ui, &tag, selected).clicked() {
+                    if selected {
+                        state.automation.cron_board.tag_filter = None;
+                    } else {
+                        state.automation.cron_board.tag_filter = Some(tag);
                     }
-                });
-            }
-            MarkdownBlock::CodeBlock { language, code } => {
-                draw_code_block(ui, language, code);
+                }
             }
-            MarkdownBlock::Table { headers, rows } => {
-                draw_markdown_table(ui, headers, rows);
+            if state.automation.cron_board.tag_filter.is_some() && ui.button("Quitar tag").clicked()
+            {
+                state.automation.cron_board.tag_filter = None;
             }
-        }
+        });
     }
 }
 
-fn draw_code_block(ui: &mut egui::Ui, language: &str, code: &str) {
-    let code_string = code.trim_end_matches('\n').to_string();
-    let header_label = if language.trim().is_empty() {
-        "Bloque de código".to_string()
-    } else {
-        format!("{}", language)
-    };
+fn draw_cron_table(ui: &mut egui::Ui, state: &mut AppState) {
+    let indices = state.automation.cron_board.filtered_indices();
+    if indices.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "No hay tareas que coincidan con los filtros seleccionados.",
+        );
+        state.automation.cron_board.select_task(None);
+        return;
+    }
 
-    egui::CollapsingHeader::new(
-        RichText::new(format!("{} {}", ICON_CODE, header_label))
-            .color(theme::color_text_primary())
-            .strong()
-            .size(13.0),
-    )
-    .default_open(true)
-    .show(ui, |ui| {
-        egui::Frame::none()
-            .fill(Color32::from_rgb(32, 34, 40))
-            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(60, 72, 92)))
-            .rounding(egui::Rounding::same(10.0))
-            .inner_margin(egui::Margin::symmetric(14.0, 12.0))
-            .show(ui, |ui| {
-                ui.set_width(ui.available_width());
-                ui.horizontal(|ui| {
-                    ui.add_space(ui.available_width());
-                    if code_copy_button(ui).clicked() {
-                        ui.output_mut(|out| out.copied_text = code_string.clone());
-                    }
-                });
-                ui.add_space(6.0);
-                let mut code_buffer = code_string.clone();
-                let rows = code_buffer.lines().count().max(1);
-                ui.add(
-                    egui::TextEdit::multiline(&mut code_buffer)
-                        .font(egui::FontId::monospace(14.0))
-                        .desired_rows(rows)
-                        .frame(false)
-                        .interactive(false)
-                        .desired_width(f32::INFINITY),
+    let min_height = ui.available_height().max(220.0);
+    TableBuilder::new(ui)
+        .striped(true)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::initial(36.0))
+        .column(Column::remainder().at_least(160.0))
+        .column(Column::initial(120.0))
+        .column(Column::initial(120.0))
+        .column(Column::initial(120.0))
+        .column(Column::initial(100.0))
+        .column(Column::initial(90.0))
+        .min_scrolled_height(min_height)
+        .header(26.0, |mut header| {
+            header.col(|ui| {
+                ui.label(
+                    RichText::new("Estado")
+                        .color(theme::color_text_weak())
+                        .size(11.0),
                 );
             });
-    });
-}
-
-fn code_copy_button(ui: &mut egui::Ui) -> egui::Response {
-    let button = egui::Button::new(
-        RichText::new(ICON_COPY)
-            .font(theme::icon_font(14.0))
-            .color(Color32::from_rgb(230, 230, 230)),
-    )
-    .min_size(egui::vec2(32.0, 26.0))
-    .fill(Color32::from_rgb(45, 47, 56))
-    .rounding(egui::Rounding::same(6.0));
-
-    ui.add(button).on_hover_text("Copiar bloque de código")
-}
-
-fn draw_markdown_table(ui: &mut egui::Ui, headers: &[String], rows: &[Vec<String>]) {
-    egui::Frame::none()
-        .fill(Color32::from_rgb(32, 34, 40))
-        .stroke(egui::Stroke::new(1.0, Color32::from_rgb(60, 72, 92)))
-        .rounding(egui::Rounding::same(10.0))
-        .inner_margin(egui::Margin::symmetric(12.0, 10.0))
-        .show(ui, |ui| {
-            ui.horizontal(|ui| {
+            header.col(|ui| {
                 ui.label(
-                    RichText::new(format!("{} Tabla", ICON_TABLE))
-                        .color(theme::color_text_primary())
-                        .strong()
-                        .size(13.0),
+                    RichText::new("Tarea")
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            });
+            header.col(|ui| {
+                ui.label(
+                    RichText::new("Cadencia")
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            });
+            header.col(|ui| {
+                ui.label(
+                    RichText::new("Próxima ejecución")
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            });
+            header.col(|ui| {
+                ui.label(
+                    RichText::new("Última ejecución")
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            });
+            header.col(|ui| {
+                ui.label(
+                    RichText::new("Proveedor")
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            });
+            header.col(|ui| {
+                ui.label(
+                    RichText::new("Acciones")
+                        .color(theme::color_text_weak())
+                        .size(11.0),
                 );
-                ui.add_space(ui.available_width());
-                if code_copy_button(ui).clicked() {
-                    let mut buffer = String::new();
-                    buffer.push('|');
-                    buffer.push_str(&headers.join("|"));
-                    buffer.push('|');
-                    buffer.push('\n');
-                    buffer.push('|');
-                    buffer.push_str(&headers.iter().map(|_| "---").collect::<Vec<_>>().join("|"));
-                    buffer.push('|');
-                    buffer.push('\n');
-                    for row in rows {
-                        buffer.push('|');
-                        buffer.push_str(&row.join("|"));
-                        buffer.push('|');
-                        buffer.push('\n');
-                    }
-                    ui.output_mut(|out| out.copied_text = buffer);
-                }
             });
+        })
+        .body(|mut body| {
+            for index in indices {
+                let task_snapshot = state.automation.cron_board.tasks[index].clone();
+                let prerequisites =
+                    task_chain::prerequisites(&state.config.cron_dependencies, &task_snapshot.name)
+                        .to_vec();
+                let mut selection_change = None;
+                let mut new_enabled: Option<bool> = None;
+                let mut trigger_run = false;
 
-            ui.add_space(6.0);
-            ui.push_id(("markdown_table", headers.len(), rows.len()), |ui| {
-                egui::Grid::new("markdown_table_grid")
-                    .striped(true)
-                    .spacing(egui::vec2(12.0, 4.0))
-                    .show(ui, |ui| {
-                        for header in headers {
-                            ui.label(
-                                RichText::new(header)
-                                    .color(theme::color_text_primary())
-                                    .strong(),
-                            );
+                body.row(32.0, |mut row| {
+                    row.col(|ui| {
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(24.0, 18.0), egui::Sense::hover());
+                        let painter = ui.painter_at(rect);
+                        painter.circle_filled(
+                            rect.center(),
+                            6.0,
+                            cron_status_color(task_snapshot.status),
+                        );
+                    });
+                    row.col(|ui| {
+                        let selected =
+                            state.automation.cron_board.selected_task == Some(task_snapshot.id);
+                        let response = ui.add(egui::SelectableLabel::new(
+                            selected,
+                            RichText::new(&task_snapshot.name)
+                                .color(theme::color_text_primary())
+                                .size(13.0),
+                        ));
+                        if response.clicked() {
+                            selection_change = Some(task_snapshot.id);
                         }
-                        ui.end_row();
+                    });
+                    row.col(|ui| {
+                        let cadence = if prerequisites.is_empty() {
+                            task_snapshot.cadence_label.clone()
+                        } else {
+                            tf("Tras {0}", &[&prerequisites.join(", ")])
+                        };
+                        ui.label(
+                            RichText::new(cadence)
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                    });
+                    row.col(|ui| {
+                        ui.label(
+                            RichText::new(
+                                task_snapshot
+                                    .next_run
+                                    .clone()
+                                    .unwrap_or_else(|| "—".to_string()),
+                            )
+                            .color(theme::color_text_primary())
+                            .size(11.0),
+                        );
+                    });
+                    row.col(|ui| {
+                        ui.label(
+                            RichText::new(
+                                task_snapshot
+                                    .last_run
+                                    .clone()
+                                    .unwrap_or_else(|| "—".to_string()),
+                            )
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                        );
+                    });
+                    row.col(|ui| {
+                        let badge = task_snapshot
+                            .provider_badge()
+                            .unwrap_or_else(|| "local".to_string());
+                        ui.label(
+                            RichText::new(badge)
+                                .color(theme::color_text_weak())
+                                .monospace(),
+                        );
+                    });
+                    row.col(|ui| {
+                        ui.horizontal(|ui| {
+                            let mut enabled = task_snapshot.enabled;
+                            if ui.checkbox(&mut enabled, "").changed() {
+                                new_enabled = Some(enabled);
+                            }
 
-                        for row in rows {
-                            for cell in row {
-                                ui.label(
-                                    RichText::new(cell)
-                                        .color(theme::color_text_weak())
-                                        .size(12.0),
-                                );
+                            let run_label = RichText::new(format!("{} Ejecutar", ICON_PLAY))
+                                .color(Color32::from_rgb(240, 240, 240))
+                                .size(11.0);
+                            if ui
+                                .add(egui::Button::new(run_label).min_size(egui::vec2(96.0, 26.0)))
+                                .on_hover_text("Lanzar inmediatamente")
+                                .clicked()
+                            {
+                                trigger_run = true;
                             }
-                            ui.end_row();
-                        }
+                        });
                     });
-            });
-        });
-}
+                });
 
-fn draw_developer_artifacts(ui: &mut egui::Ui, message: &ChatMessage, tokens: &ThemeTokens) {
-    if message.sender == "User" || message.sender == "System" || message.is_pending() {
-        return;
-    }
+                if let Some(task_id) = selection_change {
+                    state.automation.cron_board.select_task(Some(task_id));
+                }
 
-    let blocks = parse_markdown_blocks(&message.text);
-    let diff_block = extract_diff_block(&blocks);
-    let preview_block = extract_preview_block(&blocks);
-    let summary = extract_summary(&message.text);
+                if let Some(enabled) = new_enabled {
+                    let mut message = None;
+                    {
+                        let task = &mut state.automation.cron_board.tasks[index];
+                        if task.enabled != enabled {
+                            task.enabled = enabled;
+                            let task_name = task.name.clone();
+                            let text = if enabled {
+                                format!("Tarea '{}' activada", task_name)
+                            } else {
+                                format!("Tarea '{}' pausada", task_name)
+                            };
+                            message = Some(text);
+                        }
+                    }
+                    if let Some(text) = message {
+                        state.push_debug_event(
+                            DebugLogLevel::Info,
+                            "cron::scheduler",
+                            text.clone(),
+                        );
+                        state.push_activity_log(
+                            if enabled {
+                                LogStatus::Ok
+                            } else {
+                                LogStatus::Warning
+                            },
+                            "Cron",
+                            text,
+                        );
+                    }
+                }
 
-    if diff_block.is_none() && preview_block.is_none() && summary.is_none() {
-        return;
-    }
+                if trigger_run {
+                    state.run_scheduled_task(task_snapshot.id);
+                }
+            }
+        });
+}
 
-    ui.add_space(10.0);
+fn draw_cron_task_detail(ui: &mut egui::Ui, state: &AppState, task: &crate::state::ScheduledTask) {
     egui::Frame::none()
-        .fill(Color32::from_rgb(34, 38, 44))
-        .stroke(theme::subtle_border(tokens))
-        .rounding(egui::Rounding::same(10.0))
-        .inner_margin(egui::Margin::symmetric(12.0, 10.0))
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(14.0))
+        .inner_margin(egui::Margin::symmetric(18.0, 14.0))
         .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 10.0;
+                ui.label(
+                    RichText::new(ICON_INFO)
+                        .font(theme::icon_font(16.0))
+                        .color(theme::color_primary()),
+                );
+                ui.heading(
+                    RichText::new(&task.name)
+                        .color(theme::color_text_primary())
+                        .size(16.0)
+                        .strong(),
+                );
+                ui.add_space(ui.available_width());
+                ui.label(
+                    RichText::new(task.status.label())
+                        .color(cron_status_color(task.status))
+                        .monospace(),
+                );
+            });
+            ui.add_space(4.0);
             ui.label(
-                RichText::new("Herramientas de desarrollo")
-                    .color(theme::color_text_primary())
-                    .strong()
-                    .size(13.0),
+                RichText::new(&task.description)
+                    .color(theme::color_text_weak())
+                    .size(12.0),
             );
-
-            if let Some(summary) = summary {
-                ui.add_space(6.0);
-                ui.horizontal(|ui| {
-                    ui.label(
-                        RichText::new(ICON_QUOTE)
-                            .font(theme::icon_font(12.0))
-                            .color(theme::color_primary()),
-                    );
-                    ui.label(
-                        RichText::new(summary)
-                            .color(theme::color_text_primary())
-                            .size(12.0),
-                    );
-                });
+            if let Some(reason) = state.automation.cron_board.skip_reasons.get(&task.name) {
+                ui.add_space(4.0);
+                ui.label(
+                    RichText::new(tf("Omitida: {0}", &[reason]))
+                        .color(cron_status_color(ScheduledTaskStatus::Skipped))
+                        .size(12.0),
+                );
             }
 
-            if let Some(diff) = diff_block {
-                ui.add_space(6.0);
-                egui::CollapsingHeader::new(
-                    RichText::new(format!("{} Diferencias detectadas", ICON_COMPARE))
-                        .color(theme::color_text_primary())
-                        .strong()
-                        .size(12.0),
-                )
-                .default_open(false)
-                .show(ui, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.add_space(ui.available_width());
-                        if code_copy_button(ui).clicked() {
-                            ui.output_mut(|out| out.copied_text = diff.clone());
-                        }
-                    });
-                    let preview: String = diff
-                        .lines()
-                        .take(20)
-                        .map(|line| line.to_string())
-                        .collect::<Vec<_>>()
-                        .join("\n");
-                    ui.add(
-                        egui::TextEdit::multiline(&mut preview.clone())
-                            .font(egui::FontId::monospace(13.0))
-                            .desired_rows(6)
-                            .frame(false)
-                            .interactive(false)
-                            .desired_width(f32::INFINITY),
-                    );
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                ui.label(
+                    RichText::new(format!("Expresión cron: `{}`", task.cron_expression))
+                        .color(theme::color_text_weak())
+                        .monospace(),
+                );
+            });
+
+            if !task.tags.is_empty() {
+                ui.add_space(8.0);
+                ui.horizontal_wrapped(|ui| {
+                    ui.spacing_mut().item_spacing.x = 6.0;
+                    for tag in &task.tags {
+                        selectable_chip(ui, tag, false);
+                    }
                 });
             }
 
-            if let Some((language, code)) = preview_block {
+            ui.add_space(10.0);
+            let badge = task.provider_badge().unwrap_or_else(|| "local".to_string());
+            ui.label(
+                RichText::new(format!(
+                    "Responsable: {} · Proveedor: {}",
+                    task.owner, badge
+                ))
+                .color(theme::color_text_weak())
+                .size(11.0),
+            );
+
+            if let Some(status) = state
+                .automation
+                .activity_logs
+                .iter()
+                .rev()
+                .find(|entry| entry.source == "Cron")
+            {
                 ui.add_space(6.0);
-                egui::CollapsingHeader::new(
-                    RichText::new(format!("{} Vista previa de {}", ICON_FILE_DOC, language))
+                ui.label(
+                    RichText::new(format!(
+                        "Última actividad registrada: {} ({})",
+                        status.message, status.timestamp
+                    ))
+                    .color(theme::color_text_weak())
+                    .size(11.0),
+                );
+            }
+        });
+}
+
+const WEEKDAY_LABELS: [&str; 7] = ["Lun", "Mar", "Mié", "Jue", "Vie", "Sáb", "Dom"];
+/// Entradas visibles por día en la vista mensual antes de resumir el resto.
+const MONTH_CELL_ENTRIES: usize = 3;
+
+/// Calendario semanal o mensual con las próximas ejecuciones de tareas y recordatorios.
+/// Al pulsar una tarea se selecciona para editarla en el detalle.
+fn draw_cron_calendar(ui: &mut egui::Ui, state: &mut AppState) {
+    let board = &state.automation.cron_board;
+    let span = board.calendar_span;
+    let today = Local::now().date_naive();
+    let (first, last) = span.range(today, board.calendar_offset);
+    let entries = cron_calendar::build_calendar(
+        &board.tasks,
+        &state.automation.scheduled_reminders,
+        first,
+        last,
+    );
+    let conflicts = entries.iter().filter(|entry| entry.conflict).count();
+    let selected = board.selected_task;
+    let mut clicked = None;
+    let mut offset = board.calendar_offset;
+    let mut new_span = span;
+
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(14.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 10.0;
+                ui.label(
+                    RichText::new(ICON_CALENDAR)
+                        .font(theme::icon_font(16.0))
+                        .color(theme::color_primary()),
+                );
+                ui.heading(
+                    RichText::new(t("Calendario de ejecuciones"))
                         .color(theme::color_text_primary())
-                        .strong()
-                        .size(12.0),
-                )
-                .default_open(false)
-                .show(ui, |ui| {
-                    ui.horizontal(|ui| {
-                        ui.add_space(ui.available_width());
-                        if code_copy_button(ui).clicked() {
-                            ui.output_mut(|out| out.copied_text = code.clone());
-                        }
-                    });
-                    let snippet: String = code
-                        .lines()
-                        .take(20)
-                        .map(|line| line.to_string())
-                        .collect::<Vec<_>>()
-                        .join("\n");
-                    ui.add(
-                        egui::TextEdit::multiline(&mut snippet.clone())
-                            .font(egui::FontId::monospace(13.0))
-                            .desired_rows(6)
-                            .frame(false)
-                            .interactive(false)
-                            .desired_width(f32::INFINITY),
+                        .strong(),
+                );
+                ui.add_space(12.0);
+                for candidate in [cron_calendar::CalendarSpan::Week, cron_calendar::CalendarSpan::Month] {
+                    if ui
+                        .selectable_label(new_span == candidate, t(candidate.label()))
+                        .clicked()
+                        && new_span != candidate
+                    {
+                        new_span = candidate;
+                        offset = 0;
+                    }
+                }
+                if ui.small_button("‹").clicked() {
+                    offset -= 1;
+                }
+                if ui.small_button(t("Hoy")).clicked() {
+                    offset = 0;
+                }
+                if ui.small_button("›").clicked() {
+                    offset += 1;
+                }
+                ui.label(
+                    RichText::new(format!(
+                        "{} – {}",
+                        first.format("%d/%m"),
+                        last.format("%d/%m/%Y")
+                    ))
+                    .color(theme::color_text_weak()),
+                );
+            });
+            if conflicts > 0 {
+                ui.label(
+                    RichText::new(tf(
+                        "⚠ {0} ejecuciones coinciden con otras en la misma media hora.",
+                        &[&conflicts.to_string()],
+                    ))
+                    .color(theme::color_danger())
+                    .size(11.0),
+                );
+            }
+            ui.add_space(8.0);
+
+            // Las semanas del calendario empiezan en lunes aunque el mes no lo haga.
+            let grid_start =
+                first - chrono::Duration::days(first.weekday().num_days_from_monday() as i64);
+            let weeks = ((last - grid_start).num_days() / 7 + 1) as usize;
+            ui.columns(7, |columns| {
+                for (column, label) in columns.iter_mut().zip(WEEKDAY_LABELS) {
+                    column.label(
+                        RichText::new(t(label))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
                     );
+                }
+            });
+            for week in 0..weeks {
+                ui.columns(7, |columns| {
+                    for (weekday, column) in columns.iter_mut().enumerate() {
+                        let day = grid_start + chrono::Duration::days((week * 7 + weekday) as i64);
+                        if day < first || day > last {
+                            continue;
+                        }
+                        let day_entries: Vec<_> = entries
+                            .iter()
+                            .filter(|entry| entry.start.date_naive() == day)
+                            .collect();
+                        let visible = match span {
+                            cron_calendar::CalendarSpan::Week => day_entries.len(),
+                            cron_calendar::CalendarSpan::Month => MONTH_CELL_ENTRIES,
+                        };
+                        egui::Frame::none()
+                            .fill(if day == today {
+                                Color32::from_rgb(40, 46, 58)
+                            } else {
+                                Color32::from_rgb(28, 30, 36)
+                            })
+                            .rounding(egui::Rounding::same(6.0))
+                            .inner_margin(egui::Margin::same(6.0))
+                            .show(column, |ui| {
+                                ui.set_min_width(ui.available_width());
+                                ui.label(
+                                    RichText::new(day.format("%d").to_string())
+                                        .color(theme::color_text_primary())
+                                        .strong()
+                                        .size(11.0),
+                                );
+                                for entry in day_entries.iter().take(visible) {
+                                    let color = if entry.conflict {
+                                        theme::color_danger()
+                                    } else if matches!(entry.source, cron_calendar::CalendarSource::Reminder(_)) {
+                                        theme::color_text_weak()
+                                    } else {
+                                        theme::color_text_primary()
+                                    };
+                                    let is_selected = matches!(
+                                        entry.source,
+                                        cron_calendar::CalendarSource::Task(id) if selected == Some(id)
+                                    );
+                                    let response = ui
+                                        .add(egui::SelectableLabel::new(
+                                            is_selected,
+                                            RichText::new(format!(
+                                                "{} {}",
+                                                entry.start.format("%H:%M"),
+                                                entry.title
+                                            ))
+                                            .color(color)
+                                            .size(11.0),
+                                        ))
+                                        .on_hover_text(match entry.source {
+                                            cron_calendar::CalendarSource::Task(_) => {
+                                                t("Abrir la tarea en el detalle").to_string()
+                                            }
+                                            cron_calendar::CalendarSource::Reminder(_) => {
+                                                t("Recordatorio").to_string()
+                                            }
+                                        });
+                                    if let (true, cron_calendar::CalendarSource::Task(id)) =
+                                        (response.clicked(), entry.source)
+                                    {
+                                        clicked = Some(id);
+                                    }
+                                }
+                                if day_entries.len() > visible {
+                                    ui.label(
+                                        RichText::new(tf(
+                                            "+{0} más",
+                                            &[&(day_entries.len() - visible).to_string()],
+                                        ))
+                                        .color(theme::color_text_weak())
+                                        .size(10.0),
+                                    );
+                                }
+                            });
+                    }
                 });
             }
+            if entries.is_empty() {
+                ui.colored_label(
+                    theme::color_text_weak(),
+                    t("No hay ejecuciones previstas en este periodo."),
+                );
+            }
         });
+
+    let board = &mut state.automation.cron_board;
+    board.calendar_span = new_span;
+    board.calendar_offset = offset;
+    if let Some(task_id) = clicked {
+        board.select_task(Some(task_id));
+    }
 }
 
-fn render_formatted_text(ui: &mut egui::Ui, text: &str, color: Color32, size: f32) {
-    let segments = parse_inline_segments(text);
-    ui.horizontal_wrapped(|ui| {
-        ui.spacing_mut().item_spacing.x = 0.0;
-        for segment in segments {
-            if segment.text.is_empty() {
-                continue;
-            }
+fn cron_status_color(status: ScheduledTaskStatus) -> Color32 {
+    match status {
+        ScheduledTaskStatus::Scheduled => theme::color_primary(),
+        ScheduledTaskStatus::Running => Color32::from_rgb(64, 172, 255),
+        ScheduledTaskStatus::Success => theme::color_success(),
+        ScheduledTaskStatus::Failed => theme::color_danger(),
+        ScheduledTaskStatus::Paused => Color32::from_rgb(160, 160, 160),
+        ScheduledTaskStatus::Skipped => Color32::from_rgb(224, 164, 64),
+    }
+}
 
-            let mut rich = RichText::new(segment.text).color(color).size(size);
-            if segment.bold {
-                rich = rich.strong();
+/// Cadena de dependencias de la tarea seleccionada, con el estado de cada eslabón, y
+/// edición de sus tareas previas.
+fn draw_cron_task_chain(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    task: &crate::state::ScheduledTask,
+) {
+    let mut remove = None;
+    let mut add = None;
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(14.0))
+        .inner_margin(egui::Margin::symmetric(18.0, 14.0))
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new(t("Cadena de dependencias"))
+                    .color(theme::color_text_primary())
+                    .strong(),
+            );
+            ui.add_space(6.0);
+
+            let board = &state.automation.cron_board;
+            let levels = task_chain::chain_levels(&state.config.cron_dependencies, &task.name);
+            if levels.len() > 1 {
+                ui.horizontal_wrapped(|ui| {
+                    for (index, level) in levels.iter().enumerate() {
+                        if index > 0 {
+                            ui.label(RichText::new("→").color(theme::color_text_weak()));
+                        }
+                        ui.vertical(|ui| {
+                            for name in level {
+                                let status = board.task_by_name(name).map(|task| task.status);
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        RichText::new("●").color(
+                                            status
+                                                .map(cron_status_color)
+                                                .unwrap_or_else(theme::color_text_weak),
+                                        ),
+                                    );
+                                    let label = RichText::new(name).size(12.0);
+                                    let label = if *name == task.name {
+                                        label.color(theme::color_primary()).strong()
+                                    } else {
+                                        label.color(theme::color_text_primary())
+                                    };
+                                    ui.label(label).on_hover_text(
+                                        status
+                                            .map(|status| status.label().to_string())
+                                            .unwrap_or_else(|| {
+                                                t("Tarea no disponible").to_string()
+                                            }),
+                                    );
+                                });
+                            }
+                        });
+                    }
+                });
+            } else {
+                ui.colored_label(
+                    theme::color_text_weak(),
+                    t("Esta tarea no forma parte de ninguna cadena."),
+                );
             }
-            if segment.italic {
-                rich = rich.italics();
+
+            let prerequisites =
+                task_chain::prerequisites(&state.config.cron_dependencies, &task.name);
+            if !prerequisites.is_empty() {
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new(t("Se lanza cuando terminan bien:"))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+                for previous in prerequisites {
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(previous).size(12.0));
+                        if ui
+                            .small_button("✕")
+                            .on_hover_text(t("Quitar dependencia"))
+                            .clicked()
+                        {
+                            remove = Some(previous.clone());
+                        }
+                    });
+                }
             }
-            if segment.code {
-                rich = rich
-                    .monospace()
-                    .background_color(Color32::from_rgb(40, 44, 54))
-                    .color(Color32::from_rgb(220, 220, 220));
+
+            ui.add_space(8.0);
+            let candidates: Vec<String> = board
+                .tasks
+                .iter()
+                .filter(|candidate| candidate.name != task.name)
+                .map(|candidate| candidate.name.clone())
+                .collect();
+            let mut draft = board.dependency_draft.clone();
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("cron_dependency_draft")
+                    .selected_text(
+                        draft
+                            .clone()
+                            .unwrap_or_else(|| t("Elige una tarea previa").to_string()),
+                    )
+                    .show_ui(ui, |ui| {
+                        for name in candidates {
+                            ui.selectable_value(&mut draft, Some(name.clone()), name);
+                        }
+                    });
+                if ui
+                    .add_enabled(draft.is_some(), egui::Button::new(t("Añadir dependencia")))
+                    .clicked()
+                {
+                    add = draft.clone();
+                }
+            });
+            state.automation.cron_board.dependency_draft = draft;
+            if let Some(error) = &state.automation.cron_board.chain_status {
+                ui.colored_label(theme::color_danger(), error);
             }
+        });
 
-            ui.label(rich);
+    if let Some(previous) = remove {
+        state.remove_task_dependency(&task.name, &previous);
+        state.automation.cron_board.chain_status = None;
+    }
+    if let Some(previous) = add {
+        let result = state.add_task_dependency(&task.name, &previous);
+        if result.is_ok() {
+            state.automation.cron_board.dependency_draft = None;
         }
-    });
+        state.automation.cron_board.chain_status = result.err();
+    }
 }
 
-fn parse_markdown_blocks(text: &str) -> Vec<MarkdownBlock> {
-    let mut blocks = Vec::new();
-    let mut paragraph: Vec<String> = Vec::new();
-    let mut list_items: Vec<String> = Vec::new();
-    let mut code_lines: Vec<String> = Vec::new();
-    let mut code_language = String::new();
-    let mut in_code_block = false;
-    let mut in_table = false;
-    let mut table_headers: Vec<String> = Vec::new();
-    let mut table_rows: Vec<Vec<String>> = Vec::new();
-
-    let flush_paragraph = |blocks: &mut Vec<MarkdownBlock>, paragraph: &mut Vec<String>| {
-        if paragraph.is_empty() {
-            return;
-        }
-        let mut combined = String::new();
-        for (index, line) in paragraph.iter().enumerate() {
-            if index > 0 {
-                combined.push(' ');
-            }
-            combined.push_str(line);
-        }
-        paragraph.clear();
-        blocks.push(MarkdownBlock::Paragraph(combined));
-    };
+fn draw_debug_summary(
+    ui: &mut egui::Ui,
+    info: usize,
+    warning: usize,
+    error: usize,
+    tokens: &ThemeTokens,
+) {
+    ui.horizontal(|ui| {
+        summary_chip(ui, ICON_INFO, "Info", info, theme::color_primary(), tokens);
+        summary_chip(
+            ui,
+            ICON_LIGHTNING,
+            "Warnings",
+            warning,
+            Color32::from_rgb(255, 196, 0),
+            tokens,
+        );
+        summary_chip(
+            ui,
+            ICON_STOP,
+            "Errores",
+            error,
+            theme::color_danger(),
+            tokens,
+        );
+    });
+}
 
-    let flush_list = |blocks: &mut Vec<MarkdownBlock>, list_items: &mut Vec<String>| {
-        if list_items.is_empty() {
-            return;
+fn draw_debug_filters(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        let search_width = (ui.available_width() - 160.0).max(200.0);
+        ui.add_sized(
+            [search_width, 28.0],
+            egui::TextEdit::singleline(&mut state.debug_console.search)
+                .hint_text("Buscar por mensaje o componente"),
+        );
+        if ui
+            .add_sized([120.0, 28.0], egui::Button::new("Limpiar búsqueda"))
+            .clicked()
+        {
+            state.debug_console.search.clear();
         }
-        blocks.push(MarkdownBlock::BulletList(list_items.clone()));
-        list_items.clear();
-    };
-
-    for line in text.lines() {
-        let trimmed_start = line.trim_start();
-        let trimmed = line.trim();
+    });
 
-        let is_table_candidate =
-            trimmed.contains('|') && trimmed.chars().filter(|ch| *ch == '|').count() >= 2;
-        let is_table_separator = trimmed
-            .chars()
-            .all(|ch| matches!(ch, '|' | '-' | ':' | ' '));
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        let selected_text = match state.debug_console.level_filter {
+            Some(DebugLogLevel::Info) => "Solo INFO",
+            Some(DebugLogLevel::Warning) => "Solo WARN",
+            Some(DebugLogLevel::Error) => "Solo ERR",
+            None => "Todos los niveles",
+        };
+        egui::ComboBox::from_id_source("debug_level_filter")
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_label(state.debug_console.level_filter.is_none(), "Todos")
+                    .clicked()
+                {
+                    state.debug_console.level_filter = None;
+                }
+                for level in [
+                    DebugLogLevel::Info,
+                    DebugLogLevel::Warning,
+                    DebugLogLevel::Error,
+                ] {
+                    let selected = state.debug_console.level_filter == Some(level);
+                    if ui.selectable_label(selected, level.label()).clicked() {
+                        state.debug_console.level_filter = Some(level);
+                    }
+                }
+            });
 
-        if in_code_block {
-            if trimmed_start.starts_with("```") {
-                let code = code_lines.join("\n");
-                blocks.push(MarkdownBlock::CodeBlock {
-                    language: code_language.clone(),
-                    code,
-                });
-                code_lines.clear();
-                code_language.clear();
-                in_code_block = false;
-            } else {
-                code_lines.push(line.to_string());
-            }
-            continue;
+        if ui
+            .checkbox(&mut state.debug_console.auto_scroll, "Auto-scroll")
+            .changed()
+        {
+            // nothing extra
         }
 
-        if trimmed_start.starts_with("```") {
-            flush_paragraph(&mut blocks, &mut paragraph);
-            flush_list(&mut blocks, &mut list_items);
-            flush_table_block(
-                &mut blocks,
-                &mut table_headers,
-                &mut table_rows,
-                &mut in_table,
-            );
-            code_language = trimmed_start[3..].trim().to_string();
-            in_code_block = true;
-            code_lines.clear();
-            continue;
+        if ui
+            .add_sized([120.0, 28.0], egui::Button::new("Limpiar consola"))
+            .clicked()
+        {
+            state.debug_console.entries.clear();
         }
+    });
+}
 
-        if in_table && (!is_table_candidate || trimmed.is_empty()) {
-            flush_table_block(
-                &mut blocks,
-                &mut table_headers,
-                &mut table_rows,
-                &mut in_table,
+fn draw_state_journal(ui: &mut egui::Ui, state: &mut AppState) {
+    let title = tf(
+        "Cambios de estado ({0})",
+        &[&state.state_journal.entries.len().to_string()],
+    );
+    egui::CollapsingHeader::new(title)
+        .id_source("state_journal")
+        .show(ui, |ui| {
+            ui.colored_label(
+                theme::color_text_weak(),
+                t("Enrutado, modelos y preferencias que cambiaron en esta sesión, con lo que los provocó. Revierte un campo para devolverlo a su valor anterior."),
             );
-        }
-
-        if is_table_candidate && !is_table_separator {
-            flush_paragraph(&mut blocks, &mut paragraph);
-            flush_list(&mut blocks, &mut list_items);
-            let cells = parse_table_cells(trimmed_start);
-            if !in_table {
-                table_headers = cells;
-                in_table = true;
-            } else {
-                table_rows.push(cells);
+            if state.state_journal.entries.is_empty() {
+                ui.colored_label(theme::color_text_weak(), t("Sin cambios registrados."));
+                return;
             }
-            continue;
-        }
-
-        if in_table && is_table_separator {
-            continue;
-        }
-
-        if trimmed_start.starts_with("```") {
-            flush_paragraph(&mut blocks, &mut paragraph);
-            flush_list(&mut blocks, &mut list_items);
-            code_language = trimmed_start[3..].trim().to_string();
-            in_code_block = true;
-            code_lines.clear();
-            continue;
-        }
-
-        if trimmed.is_empty() {
-            flush_paragraph(&mut blocks, &mut paragraph);
-            flush_list(&mut blocks, &mut list_items);
-            flush_table_block(
-                &mut blocks,
-                &mut table_headers,
-                &mut table_rows,
-                &mut in_table,
-            );
-            continue;
-        }
-
-        if trimmed_start.starts_with('#') {
-            let hash_count = trimmed_start
-                .chars()
-                .take_while(|ch| *ch == '#')
-                .count()
-                .max(1);
-            let content = trimmed_start[hash_count..].trim();
-            flush_paragraph(&mut blocks, &mut paragraph);
-            flush_list(&mut blocks, &mut list_items);
-            flush_table_block(
-                &mut blocks,
-                &mut table_headers,
-                &mut table_rows,
-                &mut in_table,
-            );
-            blocks.push(MarkdownBlock::Heading {
-                level: hash_count.min(6),
-                text: content.to_string(),
-            });
-            continue;
-        }
+            let mut revert = None;
+            egui::ScrollArea::vertical()
+                .id_source("state_journal_scroll")
+                .max_height(220.0)
+                .show(ui, |ui| {
+                    for change in state.state_journal.entries.iter().rev() {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(format!("#{}", change.id))
+                                    .color(theme::color_primary())
+                                    .monospace(),
+                            );
+                            ui.label(
+                                RichText::new(&change.timestamp)
+                                    .color(theme::color_text_weak())
+                                    .monospace()
+                                    .size(11.0),
+                            );
+                            ui.strong(t(change.kind.label()));
+                            ui.label(
+                                RichText::new(&change.origin)
+                                    .color(theme::color_text_weak())
+                                    .size(11.0),
+                            );
+                        });
+                        for (index, field) in change.fields.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                ui.add_space(16.0);
+                                ui.label(RichText::new(field.path_label()).monospace().size(11.0));
+                                ui.label(
+                                    RichText::new(field.summary())
+                                        .color(theme::color_text_weak())
+                                        .size(11.0),
+                                );
+                                if field.reverted {
+                                    ui.colored_label(theme::color_text_weak(), t("Revertido"));
+                                } else if ui.small_button(t("Revertir")).clicked() {
+                                    revert = Some((change.id, index));
+                                }
+                            });
+                        }
+                        ui.add_space(4.0);
+                    }
+                });
+            if let Some((id, field)) = revert {
+                state.state_journal.status = Some(match state.revert_state_change(id, field) {
+                    Ok(message) => message,
+                    Err(err) => err,
+                });
+            }
+            if let Some(status) = &state.state_journal.status {
+                ui.colored_label(theme::color_text_weak(), status);
+            }
+        });
+}
 
-        if let Some(stripped) = trimmed_start.strip_prefix("- ") {
-            flush_paragraph(&mut blocks, &mut paragraph);
-            list_items.push(stripped.trim().to_string());
-            continue;
-        }
+fn draw_debug_entries(ui: &mut egui::Ui, state: &AppState) {
+    let entries = state.debug_console.filtered_entries();
+    if entries.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "Sin eventos registrados bajo los filtros actuales.",
+        );
+        return;
+    }
 
-        if let Some(stripped) = trimmed_start.strip_prefix("* ") {
-            flush_paragraph(&mut blocks, &mut paragraph);
-            list_items.push(stripped.trim().to_string());
-            continue;
-        }
-
-        flush_table_block(
-            &mut blocks,
-            &mut table_headers,
-            &mut table_rows,
-            &mut in_table,
-        );
-        paragraph.push(trimmed.to_string());
-    }
-
-    if in_code_block {
-        let code = code_lines.join("\n");
-        blocks.push(MarkdownBlock::CodeBlock {
-            language: code_language,
-            code,
+    egui::ScrollArea::vertical()
+        .id_source("debug_console_scroll")
+        .stick_to_bottom(state.debug_console.auto_scroll)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for entry in entries {
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(32, 34, 40))
+                    .stroke(theme::subtle_border(&state.theme))
+                    .rounding(egui::Rounding::same(10.0))
+                    .inner_margin(egui::Margin::symmetric(14.0, 10.0))
+                    .show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                RichText::new(entry.level.label())
+                                    .color(debug_level_color(entry.level))
+                                    .monospace(),
+                            );
+                            ui.label(
+                                RichText::new(&entry.timestamp)
+                                    .color(theme::color_text_weak())
+                                    .monospace()
+                                    .size(11.0),
+                            );
+                            ui.add_space(ui.available_width());
+                            ui.label(
+                                RichText::new(&entry.component)
+                                    .color(theme::color_text_primary())
+                                    .monospace()
+                                    .size(11.0),
+                            );
+                        });
+                        ui.add_space(4.0);
+                        ui.label(
+                            RichText::new(&entry.message)
+                                .color(theme::color_text_weak())
+                                .size(12.0),
+                        );
+                    });
+                ui.add_space(6.0);
+            }
         });
-    }
-
-    flush_paragraph(&mut blocks, &mut paragraph);
-    flush_list(&mut blocks, &mut list_items);
-    flush_table_block(
-        &mut blocks,
-        &mut table_headers,
-        &mut table_rows,
-        &mut in_table,
-    );
-
-    blocks
 }
 
-fn flush_table_block(
-    blocks: &mut Vec<MarkdownBlock>,
-    headers: &mut Vec<String>,
-    rows: &mut Vec<Vec<String>>,
-    in_table: &mut bool,
-) {
-    if *in_table {
-        blocks.push(MarkdownBlock::Table {
-            headers: headers.clone(),
-            rows: rows.clone(),
-        });
-        headers.clear();
-        rows.clear();
-        *in_table = false;
+fn debug_level_color(level: DebugLogLevel) -> Color32 {
+    match level {
+        DebugLogLevel::Info => theme::color_primary(),
+        DebugLogLevel::Warning => Color32::from_rgb(255, 196, 0),
+        DebugLogLevel::Error => theme::color_danger(),
     }
 }
 
-fn parse_table_cells(line: &str) -> Vec<String> {
-    line.trim()
-        .trim_matches('|')
-        .split('|')
-        .map(|cell| cell.trim().to_string())
-        .collect()
-}
+/// Mensajes fijados de la conversación actual, con acceso directo a cada uno.
+fn draw_pinned_messages(
+    ui: &mut egui::Ui,
+    state: &AppState,
+    pending_actions: &mut Vec<PendingChatAction>,
+) {
+    let pinned = state.pinned_messages();
+    if pinned.is_empty() {
+        return;
+    }
 
-fn extract_diff_block(blocks: &[MarkdownBlock]) -> Option<String> {
-    for block in blocks {
-        if let MarkdownBlock::CodeBlock { language, code } = block {
-            if language.trim().eq_ignore_ascii_case("diff") {
-                return Some(code.clone());
+    egui::CollapsingHeader::new(
+        RichText::new(format!(
+            "{} {}",
+            ICON_PIN,
+            tf("Mensajes fijados ({0})", &[&pinned.len().to_string()])
+        ))
+        .color(theme::color_text_primary())
+        .size(13.0),
+    )
+    .id_source("chat_pinned_messages")
+    .default_open(false)
+    .show(ui, |ui| {
+        for index in pinned {
+            let message = &state.chat.messages[index];
+            let mut preview: String = message.text.chars().take(80).collect();
+            if preview.len() < message.text.len() {
+                preview.push('…');
             }
+            let label = format!("{} · {} — {}", message.sender, message.timestamp, preview);
+            ui.horizontal(|ui| {
+                if ui
+                    .selectable_label(state.chat.focused_message == Some(index), label)
+                    .on_hover_text(t("Ir al mensaje"))
+                    .clicked()
+                {
+                    pending_actions.push(PendingChatAction::Focus(index));
+                }
+                if ui.small_button(t("Soltar")).clicked() {
+                    pending_actions.push(PendingChatAction::TogglePin(index));
+                }
+            });
         }
-    }
-    None
+    });
+    ui.add_space(8.0);
 }
 
-fn extract_preview_block(blocks: &[MarkdownBlock]) -> Option<(String, String)> {
-    for block in blocks {
-        if let MarkdownBlock::CodeBlock { language, code } = block {
-            if language.trim().eq_ignore_ascii_case("diff") {
-                continue;
+/// Resultados de la última búsqueda web, que se pueden ampliar con el texto de la página
+/// y adjuntar como contexto de la conversación.
+fn draw_web_search_results(ui: &mut egui::Ui, state: &mut AppState) {
+    if state.web_search.query.is_empty() {
+        return;
+    }
+
+    let mut expand = None;
+    let mut collapse = None;
+    let mut toggle_attached = None;
+    let mut clear = false;
+    egui::CollapsingHeader::new(
+        RichText::new(format!(
+            "{} {}",
+            ICON_GLOBE,
+            tf(
+                "Búsqueda web: «{0}» ({1})",
+                &[
+                    &state.web_search.query,
+                    &state.web_search.results.len().to_string()
+                ]
+            )
+        ))
+        .color(theme::color_text_primary())
+        .size(13.0),
+    )
+    .id_source("chat_web_search_results")
+    .default_open(true)
+    .show(ui, |ui| {
+        ui.horizontal(|ui| {
+            if state.web_search.is_searching() {
+                ui.add(Spinner::new().size(14.0));
             }
-            if !code.trim().is_empty() {
-                return Some((language.clone(), code.clone()));
+            if let Some(status) = &state.web_search.status {
+                ui.colored_label(theme::color_text_weak(), status);
             }
-        }
+            if ui.small_button(t("Limpiar")).clicked() {
+                clear = true;
+            }
+        });
+
+        egui::ScrollArea::vertical()
+            .id_source("chat_web_search_scroll")
+            .max_height(260.0)
+            .show(ui, |ui| {
+                for result in &state.web_search.results {
+                    let expanded = state.web_search.expanded.contains(&result.url);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(
+                            RichText::new(format!("{}.", result.rank))
+                                .color(theme::color_text_weak()),
+                        );
+                        ui.hyperlink_to(RichText::new(&result.title).strong(), &result.url);
+                    });
+                    ui.label(
+                        RichText::new(truncate_middle(&result.url, 80))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                    if expanded {
+                        match &result.content {
+                            Some(content) => {
+                                ui.label(content);
+                            }
+                            None if state.web_search.is_fetching(&result.url) => {
+                                ui.horizontal(|ui| {
+                                    ui.add(Spinner::new().size(12.0));
+                                    ui.label(&result.snippet);
+                                });
+                            }
+                            None => {
+                                ui.label(&result.snippet);
+                            }
+                        }
+                    } else {
+                        let mut preview: String = result.snippet.chars().take(200).collect();
+                        if preview.len() < result.snippet.len() {
+                            preview.push('…');
+                        }
+                        ui.label(preview);
+                    }
+                    ui.horizontal(|ui| {
+                        let label = if expanded { "Contraer" } else { "Ampliar" };
+                        if ui
+                            .small_button(t(label))
+                            .on_hover_text(t("Descarga el texto de la página"))
+                            .clicked()
+                        {
+                            if expanded {
+                                collapse = Some(result.url.clone());
+                            } else {
+                                expand = Some(result.url.clone());
+                            }
+                        }
+                        let attached = state.web_search.is_attached(&result.url);
+                        let label = if attached {
+                            "Quitar del contexto"
+                        } else {
+                            "Adjuntar como contexto"
+                        };
+                        if ui.small_button(t(label)).clicked() {
+                            toggle_attached = Some(result.url.clone());
+                        }
+                    });
+                    ui.add_space(6.0);
+                }
+            });
+    });
+    ui.add_space(8.0);
+
+    if let Some(url) = expand {
+        state.web_search.expand(&mut state.jobs, &url);
+    }
+    if let Some(url) = collapse {
+        state.web_search.expanded.remove(&url);
+    }
+    if let Some(url) = toggle_attached {
+        state.web_search.toggle_attached(&url);
+    }
+    if clear {
+        state.web_search.query.clear();
+        state.web_search.results.clear();
+        state.web_search.expanded.clear();
+        state.web_search.status = None;
     }
-    None
 }
 
-fn extract_summary(text: &str) -> Option<String> {
-    let mut lines = text.lines().peekable();
-    while let Some(line) = lines.next() {
-        let trimmed = line.trim();
-        let lower = trimmed.to_lowercase();
-        if lower.starts_with("resumen")
-            || lower.contains("resumen semántico")
-            || lower.starts_with("summary")
-        {
-            let mut summary = String::new();
-            if let Some(index) = trimmed.find(':') {
-                let remainder = trimmed[index + 1..].trim();
-                if !remainder.is_empty() {
-                    summary.push_str(remainder);
-                }
-            }
+/// Destino de los mensajes sin mención y menú para editar los ajustes de la conversación.
+fn draw_conversation_settings(ui: &mut egui::Ui, state: &mut AppState) {
+    let defaults = state.active_conversation_defaults();
+    let route = state.conversation_route_label(&state.effective_conversation_defaults());
+    let mut save = false;
+    let mut reset = false;
+    let mut reply_language = None;
 
-            while let Some(peek) = lines.peek() {
-                if peek.trim().is_empty()
-                    || peek.trim_start().starts_with("```")
-                    || peek.trim_start().starts_with('#')
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(tf("Sin mención: {0}", &[&route]))
+                .color(theme::color_text_weak())
+                .size(12.0),
+        );
+        let language_label = match defaults.reply_language.as_deref() {
+            Some(code) => language::language_name(code).unwrap_or(code).to_string(),
+            None => match state.detected_user_language() {
+                Some(code) => tf("Auto ({0})", &[code]),
+                None => t("Auto").to_string(),
+            },
+        };
+        ui.menu_button(
+            RichText::new(format!("🌐 {}", language_label))
+                .color(theme::color_text_weak())
+                .size(12.0),
+            |ui| {
+                ui.label(
+                    RichText::new(t("Idioma de respuesta"))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+                if ui
+                    .selectable_label(
+                        defaults.reply_language.is_none(),
+                        t("Automático (idioma del mensaje)"),
+                    )
+                    .clicked()
                 {
-                    break;
-                }
-                let next_line = lines.next().unwrap();
-                if !summary.is_empty() {
-                    summary.push(' ');
+                    reply_language = Some(None);
+                    ui.close_menu();
                 }
-                summary.push_str(next_line.trim());
-                if summary.len() > 320 {
-                    break;
+                for (code, name, _) in language::REPLY_LANGUAGES {
+                    if ui
+                        .selectable_label(defaults.reply_language.as_deref() == Some(*code), *name)
+                        .clicked()
+                    {
+                        reply_language = Some(Some(code.to_string()));
+                        ui.close_menu();
+                    }
                 }
-            }
+            },
+        )
+        .response
+        .on_hover_text(t("Idioma en que responden los proveedores en esta conversación"));
+        let response = ui.menu_button(
+            RichText::new(ICON_SLIDERS)
+                .font(theme::icon_font(13.0))
+                .color(Color32::from_rgb(230, 230, 230)),
+            |ui| {
+                ui.set_min_width(300.0);
+                ui.label(
+                    RichText::new(t("Ajustes de la conversación"))
+                        .color(theme::color_text_primary())
+                        .strong(),
+                );
+                ui.label(
+                    RichText::new(t(
+                        "Se guardan con la conversación y se aplican a los mensajes sin @mención.",
+                    ))
+                    .color(theme::color_text_weak())
+                    .size(11.0),
+                );
+                ui.add_space(6.0);
 
-            if summary.is_empty() {
-                continue;
-            }
+                let draft = &mut state.conversations.draft;
+                let provider = draft.provider_kind();
+                egui::Grid::new("conversation_settings_grid")
+                    .num_columns(2)
+                    .spacing([8.0, 6.0])
+                    .show(ui, |ui| {
+                        ui.label(t("Proveedor"));
+                        egui::ComboBox::from_id_source("conversation_settings_provider")
+                            .selected_text(
+                                provider.map_or_else(|| t("Jarvis (local)").to_string(), |kind| {
+                                    kind.display_name().to_string()
+                                }),
+                            )
+                            .show_ui(ui, |ui| {
+                                if ui
+                                    .selectable_label(provider.is_none(), t("Jarvis (local)"))
+                                    .clicked()
+                                {
+                                    draft.provider = None;
+                                }
+                                for kind in [
+                                    RemoteProviderKind::Anthropic,
+                                    RemoteProviderKind::OpenAi,
+                                    RemoteProviderKind::Groq,
+                                ] {
+                                    if ui
+                                        .selectable_label(
+                                            provider == Some(kind),
+                                            kind.display_name(),
+                                        )
+                                        .clicked()
+                                        && provider != Some(kind)
+                                    {
+                                        draft.provider = Some(kind.short_code().to_string());
+                                        draft.model = None;
+                                    }
+                                }
+                            });
+                        ui.end_row();
 
-            return Some(summary);
-        }
-    }
+                        ui.label(t("Modelo"));
+                        let default_model = match provider {
+                            Some(RemoteProviderKind::Anthropic) => {
+                                state.resources.claude_default_model.as_str()
+                            }
+                            Some(RemoteProviderKind::OpenAi) => {
+                                state.resources.openai_default_model.as_str()
+                            }
+                            Some(RemoteProviderKind::Groq) => {
+                                state.resources.groq_default_model.as_str()
+                            }
+                            None => "",
+                        };
+                        let mut model = draft.model.clone().unwrap_or_default();
+                        if ui
+                            .add_enabled(
+                                provider.is_some(),
+                                egui::TextEdit::singleline(&mut model)
+                                    .hint_text(default_model)
+                                    .desired_width(180.0),
+                            )
+                            .changed()
+                        {
+                            draft.model = Some(model);
+                        }
+                        ui.end_row();
 
-    None
-}
+                        ui.label(t("Temperatura"));
+                        ui.horizontal(|ui| {
+                            let mut custom = draft.temperature.is_some();
+                            if ui.checkbox(&mut custom, "").changed() {
+                                draft.temperature = custom.then_some(0.7);
+                            }
+                            match &mut draft.temperature {
+                                Some(temperature) => {
+                                    ui.add(egui::Slider::new(
+                                        temperature,
+                                        0.0..=conversations::MAX_TEMPERATURE,
+                                    ));
+                                }
+                                None => {
+                                    ui.label(
+                                        RichText::new(t("Por defecto del proveedor"))
+                                            .color(theme::color_text_weak()),
+                                    );
+                                }
+                            }
+                        });
+                        ui.end_row();
 
-fn parse_inline_segments(text: &str) -> Vec<InlineSegment> {
-    let mut segments = Vec::new();
-    let mut current = String::new();
-    let mut bold = false;
-    let mut italic = false;
-    let mut code = false;
-    let mut index = 0;
-    let bytes = text.as_bytes();
+                        ui.label(t("Idioma de respuesta"));
+                        egui::ComboBox::from_id_source("conversation_settings_language")
+                            .selected_text(
+                                draft
+                                    .reply_language
+                                    .as_deref()
+                                    .and_then(language::language_name)
+                                    .unwrap_or(t("Automático")),
+                            )
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(
+                                    &mut draft.reply_language,
+                                    None,
+                                    t("Automático"),
+                                );
+                                for (code, name, _) in language::REPLY_LANGUAGES {
+                                    ui.selectable_value(
+                                        &mut draft.reply_language,
+                                        Some(code.to_string()),
+                                        *name,
+                                    );
+                                }
+                            });
+                        ui.end_row();
 
-    while index < bytes.len() {
-        if !code && text[index..].starts_with("**") {
-            if !current.is_empty() {
-                segments.push(InlineSegment {
-                    text: current.clone(),
-                    bold,
-                    italic,
-                    code,
-                });
-                current.clear();
-            }
-            bold = !bold;
-            index += 2;
-            continue;
-        }
+                        ui.label(t("Máx. tokens"));
+                        ui.horizontal(|ui| {
+                            let mut custom = draft.max_tokens.is_some();
+                            if ui.checkbox(&mut custom, "").changed() {
+                                draft.max_tokens = custom.then_some(1024);
+                            }
+                            match &mut draft.max_tokens {
+                                Some(max_tokens) => {
+                                    ui.add(
+                                        egui::DragValue::new(max_tokens)
+                                            .clamp_range(1..=conversations::MAX_RESPONSE_TOKENS),
+                                    );
+                                }
+                                None => {
+                                    ui.label(
+                                        RichText::new(t("Por defecto del proveedor"))
+                                            .color(theme::color_text_weak()),
+                                    );
+                                }
+                            }
+                        });
+                        ui.end_row();
 
-        if !code && text[index..].starts_with('*') {
-            if !current.is_empty() {
-                segments.push(InlineSegment {
-                    text: current.clone(),
-                    bold,
-                    italic,
-                    code,
-                });
-                current.clear();
-            }
-            italic = !italic;
-            index += 1;
-            continue;
-        }
+                        ui.label(t("Filtros de seguridad"));
+                        let safety_label = |value: Option<bool>| match value {
+                            None => t("Según preferencias"),
+                            Some(true) => t("Activados"),
+                            Some(false) => t("Desactivados"),
+                        };
+                        egui::ComboBox::from_id_source("conversation_settings_safety")
+                            .selected_text(safety_label(draft.safety_filters))
+                            .show_ui(ui, |ui| {
+                                for value in [None, Some(true), Some(false)] {
+                                    ui.selectable_value(
+                                        &mut draft.safety_filters,
+                                        value,
+                                        safety_label(value),
+                                    );
+                                }
+                            });
+                        ui.end_row();
 
-        if text[index..].starts_with('`') {
-            if !current.is_empty() {
-                segments.push(InlineSegment {
-                    text: current.clone(),
-                    bold,
-                    italic,
-                    code,
+                        ui.label(t("Sugerencias"));
+                        let follow_up_label = |value: Option<bool>| match value {
+                            None => t("Según preferencias"),
+                            Some(true) => t("Activadas"),
+                            Some(false) => t("Desactivadas"),
+                        };
+                        egui::ComboBox::from_id_source("conversation_settings_follow_ups")
+                            .selected_text(follow_up_label(draft.follow_ups))
+                            .show_ui(ui, |ui| {
+                                for value in [None, Some(true), Some(false)] {
+                                    ui.selectable_value(
+                                        &mut draft.follow_ups,
+                                        value,
+                                        follow_up_label(value),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    });
+
+                ui.add_space(4.0);
+                ui.label(t("Persona"));
+                ui.add(
+                    egui::TextEdit::multiline(&mut draft.persona)
+                        .hint_text(t("Instrucciones de sistema, p. ej. «Eres un revisor de código exigente».",))
+                        .desired_rows(3)
+                        .desired_width(f32::INFINITY),
+                );
+
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.button(t("Guardar")).clicked() {
+                        save = true;
+                    }
+                    if ui.button(t("Restablecer")).clicked() {
+                        reset = true;
+                    }
                 });
-                current.clear();
-            }
-            code = !code;
-            index += 1;
-            continue;
+                if let Some(status) = &state.conversations.status {
+                    ui.colored_label(theme::color_text_weak(), status);
+                }
+            },
+        );
+        if response.response.clicked() && response.inner.is_some() {
+            state.edit_conversation_defaults();
         }
+        theme::icon_button_label(response.response, t("Ajustes de la conversación"));
+    });
 
-        let ch = text[index..].chars().next().unwrap();
-        current.push(ch);
-        index += ch.len_utf8();
+    if let Some(code) = reply_language {
+        if let Err(err) = state.set_reply_language(code) {
+            state.chat_routing.update_status(Some(err));
+        }
     }
-
-    if !current.is_empty() {
-        segments.push(InlineSegment {
-            text: current,
-            bold,
-            italic,
-            code,
-        });
+    if reset {
+        state.conversations.draft = Default::default();
+        save = true;
     }
-
-    segments
+    if save {
+        if let Err(err) = state.save_conversation_defaults() {
+            state.conversations.status = Some(err);
+        }
+    }
+    ui.add_space(4.0);
 }
 
-#[derive(Clone)]
-struct InlineSegment {
-    text: String,
-    bold: bool,
-    italic: bool,
-    code: bool,
-}
+/// Selector de hilos y puntos de control, visible en cuanto existe más de un hilo o
+/// algún punto de control.
+fn draw_thread_bar(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut switch_to = None;
+    let mut delete_thread = None;
+    let mut restore = None;
+    let mut delete_checkpoint = None;
+    let mut create_checkpoint = false;
 
-#[derive(Debug)]
-enum MarkdownBlock {
-    Heading {
-        level: usize,
-        text: String,
-    },
-    Paragraph(String),
-    BulletList(Vec<String>),
-    CodeBlock {
-        language: String,
-        code: String,
-    },
-    Table {
-        headers: Vec<String>,
-        rows: Vec<Vec<String>>,
-    },
-}
+    let active_title = state
+        .threads
+        .active_thread()
+        .map(|thread| thread.title.clone())
+        .unwrap_or_default();
+    let header = if state.threads.threads.len() > 1 {
+        format!(
+            "{} {}",
+            ICON_FORK,
+            tf(
+                "Hilo: {0} ({1} hilos)",
+                &[&active_title, &state.threads.threads.len().to_string()]
+            )
+        )
+    } else {
+        format!("{} {}", ICON_FLAG, t("Hilos y puntos de control"))
+    };
 
-fn apply_pending_actions(state: &mut AppState, actions: Vec<PendingChatAction>) {
-    for action in actions {
-        match action {
-            PendingChatAction::Mention(tag) => insert_mention(state, &tag),
-            PendingChatAction::Quote(text) => {
-                if !state.chat.input.ends_with('\n') && !state.chat.input.is_empty() {
-                    state.chat.input.push('\n');
+    egui::CollapsingHeader::new(
+        RichText::new(header)
+            .color(theme::color_text_primary())
+            .size(13.0),
+    )
+    .id_source("chat_thread_bar")
+    .default_open(false)
+    .show(ui, |ui| {
+        for thread in &state.threads.threads {
+            let active = thread.id == state.threads.active;
+            ui.horizontal(|ui| {
+                let mut label = thread.title.clone();
+                if let Some(origin) = &thread.forked_from {
+                    if let Some(parent) = state.threads.thread(origin.thread_id) {
+                        label = format!("{} ← {}", label, parent.title);
+                    }
                 }
-                state.chat.input.push_str(&text);
+                if ui
+                    .selectable_label(active, label)
+                    .on_hover_text(thread.created_at.as_str())
+                    .clicked()
+                {
+                    switch_to = Some(thread.id);
+                }
+                if !active && ui.small_button(t("Eliminar")).clicked() {
+                    delete_thread = Some(thread.id);
+                }
+            });
+
+            for checkpoint in state.threads.checkpoints_for(thread.id) {
+                ui.horizontal(|ui| {
+                    ui.add_space(18.0);
+                    ui.label(
+                        RichText::new(format!(
+                            "{} {} · {} · {}",
+                            ICON_FLAG,
+                            checkpoint.name,
+                            checkpoint.created_at,
+                            tf("{0} mensajes", &[&checkpoint.messages.len().to_string()])
+                        ))
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                    );
+                    if ui.small_button(t("Restaurar")).clicked() {
+                        restore = Some(checkpoint.id);
+                    }
+                    if ui.small_button(t("Eliminar")).clicked() {
+                        delete_checkpoint = Some(checkpoint.id);
+                    }
+                });
             }
-            PendingChatAction::Reuse(text) => state.chat.input = text,
         }
+
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut state.threads.checkpoint_name)
+                    .hint_text(t("Nombre del punto de control"))
+                    .desired_width(200.0),
+            );
+            if ui.button(t("Guardar punto de control")).clicked() {
+                create_checkpoint = true;
+            }
+        });
+        if let Some(status) = &state.threads.status {
+            ui.colored_label(theme::color_text_weak(), status);
+        }
+    });
+    ui.add_space(8.0);
+
+    let result = if let Some(id) = switch_to {
+        state.switch_thread(id)
+    } else if let Some(id) = delete_thread {
+        state.delete_thread(id)
+    } else if let Some(id) = restore {
+        state.restore_checkpoint(id)
+    } else if create_checkpoint {
+        state.create_checkpoint().map(|_| ())
+    } else {
+        if let Some(id) = delete_checkpoint {
+            state.delete_checkpoint(id);
+        }
+        Ok(())
+    };
+    if let Err(err) = result {
+        state.threads.status = Some(err);
     }
 }
 
-fn draw_chat_input(ui: &mut egui::Ui, state: &mut AppState) {
+fn draw_chat_history(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut pending_actions = Vec::new();
+    handle_history_keyboard(ui.ctx(), state, &mut pending_actions);
+    let focused = state.chat.focused_message;
+    let scroll_to_focused = std::mem::take(&mut state.chat.scroll_to_focused);
+    let focus_stroke = egui::Stroke::new(2.0, theme::color_primary());
+
     let max_width = ui.available_width().min(580.0);
+    let target_height = ui.available_height();
     ui.allocate_ui_with_layout(
-        egui::vec2(max_width, 0.0),
+        egui::vec2(max_width, target_height),
         egui::Layout::top_down(egui::Align::LEFT),
         |ui| {
             ui.set_width(max_width);
             egui::Frame::none()
-                .fill(Color32::from_rgb(24, 26, 32))
+                .fill(Color32::from_rgb(26, 28, 32))
                 .stroke(theme::subtle_border(&state.theme))
                 .rounding(egui::Rounding::same(16.0))
-                .inner_margin(egui::Margin::symmetric(18.0, 14.0))
+                .inner_margin(egui::Margin {
+                    left: 20.0,
+                    right: 12.0,
+                    top: 20.0,
+                    bottom: 18.0,
+                })
                 .show(ui, |ui| {
-                    let full_width = ui.available_width().min(560.0);
-                    ui.set_width(full_width);
-                    ui.vertical(|ui| {
-                        draw_model_routing_bar(ui, state);
-                        ui.add_space(6.0);
-                        ui.horizontal(|ui| {
-                            ui.spacing_mut().item_spacing.x = 8.0;
-                            if let Some(tag) = state.jarvis_mention_tag() {
-                                if quick_chip(ui, &tag).clicked() {
-                                    insert_mention(state, &tag);
-                                }
-                            }
+                    let available_height = ui.available_height();
+                    ui.set_min_height(available_height);
+                    ui.set_width(ui.available_width());
 
-                            for (mention, label) in QUICK_MENTIONS {
-                                if quick_chip(ui, label).clicked() {
-                                    insert_mention(state, mention);
+                    draw_conversation_settings(ui, state);
+                    draw_project_context_banner(ui, state);
+                    draw_thread_bar(ui, state);
+                    draw_pinned_messages(ui, state, &mut pending_actions);
+                    draw_web_search_results(ui, state);
+
+                    egui::ScrollArea::vertical()
+                        .id_source("chat_history_scroll")
+                        .stick_to_bottom(true)
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            let feed_width = ui.available_width().min(540.0);
+                            ui.set_width(feed_width);
+                            for (index, message) in state.chat.messages.iter().enumerate() {
+                                let top = ui.cursor().top();
+                                draw_message_bubble(
+                                    ui,
+                                    state,
+                                    message,
+                                    index,
+                                    &mut pending_actions,
+                                );
+                                if state.follow_ups.message_index == Some(index) {
+                                    draw_follow_up_chips(ui, state, &mut pending_actions);
+                                }
+                                if focused == Some(index) {
+                                    let rect = egui::Rect::from_x_y_ranges(
+                                        ui.max_rect().x_range(),
+                                        top..=ui.min_rect().bottom(),
+                                    )
+                                    .expand(4.0);
+                                    ui.painter().rect_stroke(
+                                        rect,
+                                        egui::Rounding::same(14.0),
+                                        focus_stroke,
+                                    );
+                                    if scroll_to_focused {
+                                        ui.scroll_to_rect(rect, None);
+                                    }
                                 }
                             }
+                        });
+                });
+        },
+    );
 
-                            ui.add_space(ui.available_width());
+    apply_pending_actions(state, pending_actions);
+}
 
-                            if quick_chip_with_icon(ui, ICON_CODE, "Insertar bloque de código").clicked() {
-                                insert_code_template(state);
-                            }
-                        });
-
-                        ui.add_space(4.0);
-                        ui.horizontal_wrapped(|ui| {
-                            ui.spacing_mut().item_spacing.x = 8.0;
-                            for (command, label) in QUICK_COMMANDS {
-                                if quick_chip(ui, label).clicked() {
-                                    insert_quick_token(state, command);
-                                }
-                            }
-                        });
-
-                        ui.add_space(12.0);
-
-                        let mut should_send = false;
+/// Recorre el historial con el teclado cuando ningún widget tiene el foco:
+/// ↑/↓ mueven el resaltado, Inicio/Fin saltan a los extremos, Intro responde al
+/// mensaje resaltado y Esc lo descarta.
+fn handle_history_keyboard(
+    ctx: &egui::Context,
+    state: &mut AppState,
+    pending_actions: &mut Vec<PendingChatAction>,
+) {
+    if !state.config.accessibility.keyboard_navigation
+        || ctx.memory(|memory| memory.focused().is_some())
+    {
+        return;
+    }
 
-                        let text_height = 82.0;
-                        let enter_pressed = ui.input(|input| {
-                            input.key_pressed(egui::Key::Enter) && !input.modifiers.shift
-                        });
+    let count = state.chat.messages.len();
+    if count == 0 {
+        state.focus_chat_message(None);
+        return;
+    }
 
-                        let text_response = ui
-                            .allocate_ui_with_layout(
-                                egui::vec2(ui.available_width(), text_height),
-                                egui::Layout::top_down(egui::Align::LEFT),
-                                |ui| {
-                                    let text_edit = egui::TextEdit::multiline(
-                                        &mut state.chat.input,
-                                    )
-                                    .desired_rows(3)
-                                    .hint_text(
-                                        "Escribe tu mensaje o comando. Usa Shift+Enter para saltos de línea.",
-                                    )
-                                    .lock_focus(true)
-                                    .desired_width(f32::INFINITY)
-                                    .frame(false);
+    let current = state.chat.focused_message.filter(|index| *index < count);
+    let (up, down, home, end, enter, escape) = ctx.input(|input| {
+        (
+            input.key_pressed(egui::Key::ArrowUp) && input.modifiers.is_none(),
+            input.key_pressed(egui::Key::ArrowDown) && input.modifiers.is_none(),
+            input.key_pressed(egui::Key::Home),
+            input.key_pressed(egui::Key::End),
+            input.key_pressed(egui::Key::Enter),
+            input.key_pressed(egui::Key::Escape),
+        )
+    });
 
-                                    let text_frame = egui::Frame::none()
-                                        .fill(Color32::from_rgb(30, 32, 38))
-                                        .stroke(theme::subtle_border(&state.theme))
-                                        .rounding(egui::Rounding::same(12.0))
-                                        .inner_margin(egui::Margin::symmetric(14.0, 10.0));
+    let target = if up {
+        Some(current.map_or(count - 1, |index| index.saturating_sub(1)))
+    } else if down {
+        Some(current.map_or(0, |index| (index + 1).min(count - 1)))
+    } else if home {
+        Some(0)
+    } else if end {
+        Some(count - 1)
+    } else if escape {
+        None
+    } else {
+        if enter {
+            if let Some(index) = current {
+                pending_actions.push(PendingChatAction::Reply(index));
+            }
+        }
+        current
+    };
+    state.focus_chat_message(target);
+}
 
-                                    text_frame
-                                        .show(ui, |ui| {
-                                            ui.set_height(text_height);
-                                            ui.spacing_mut().item_spacing.x = 12.0;
+fn draw_model_routing_bar(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.vertical(|ui| {
+        ui.spacing_mut().item_spacing.y = 4.0;
+        ui.label(
+            RichText::new("Enrutamiento por alias")
+                .color(theme::color_text_primary())
+                .strong()
+                .size(13.0),
+        );
 
-                                            ui.horizontal(|ui| {
-                                                let button_width = 34.0;
-                                                let available = ui.available_width();
-                                                let text_size = [
-                                                    (available - button_width).max(120.0),
-                                                    text_height - 20.0,
-                                                ];
-                                                let text_response =
-                                                    ui.add_sized(text_size, text_edit);
+        let status = state
+            .chat_routing
+            .status
+            .as_deref()
+            .unwrap_or("Menciona @alias de un proveedor para enviarle parte de tu mensaje.");
 
-                                                let (button_rect, send_response) = ui
-                                                    .allocate_exact_size(
-                                                        egui::vec2(
-                                                            button_width,
-                                                            text_response
-                                                                .rect
-                                                                .height()
-                                                                .max(28.0),
-                                                        ),
-                                                        egui::Sense::click(),
-                                                    );
-                                                let send_response = send_response
-                                                    .on_hover_text("Enviar mensaje")
-                                                    .on_hover_cursor(egui::CursorIcon::PointingHand);
-                                                let painter = ui.painter_at(button_rect);
-                                                painter.text(
-                                                    button_rect.center(),
-                                                    egui::Align2::CENTER_CENTER,
-                                                    ICON_SEND,
-                                                    theme::icon_font(20.0),
-                                                    Color32::from_rgb(240, 240, 240),
-                                                );
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 6.0;
+            ui.label(
+                RichText::new(ICON_LIGHTNING)
+                    .font(theme::icon_font(13.0))
+                    .color(theme::color_primary()),
+            );
+            ui.label(
+                RichText::new(status)
+                    .color(theme::color_text_weak())
+                    .size(12.0),
+            );
+        });
+    });
 
-                                                (text_response, send_response)
-                                            })
-                                            .inner
-                                        })
-                                        .inner
-                                },
+    if !state.chat_routing.suggestions.is_empty() {
+        ui.add_space(6.0);
+        let suggestions = state.chat_routing.suggestions.clone();
+        ui.horizontal_wrapped(|ui| {
+            ui.spacing_mut().item_spacing.x = 10.0;
+            for suggestion in &suggestions {
+                ui.vertical(|ui| {
+                    let response = ui
+                        .add(
+                            egui::Button::new(
+                                RichText::new(&suggestion.title)
+                                    .color(Color32::from_rgb(240, 240, 240))
+                                    .size(12.0),
                             )
-                            .inner;
-
-                        let (text_response, send_response) = text_response;
-
-                        if text_response.has_focus() && enter_pressed {
-                            should_send = true;
-                            ui.ctx()
-                                .memory_mut(|mem| mem.request_focus(text_response.id));
-                        }
+                            .fill(Color32::from_rgb(44, 46, 54))
+                            .rounding(egui::Rounding::same(10.0)),
+                        )
+                        .on_hover_text(&suggestion.description);
 
-                        if send_response.clicked() {
-                            should_send = true;
-                        }
+                    if response.clicked() {
+                        let provider = suggestion.provider;
+                        state.chat_routing.update_status(Some(format!(
+                            "Recuerda mencionar @{} para {}.",
+                            provider.short_code(),
+                            suggestion.title.as_str()
+                        )));
+                    }
 
-                        if should_send {
-                            submit_chat_message(state);
-                        }
-                    });
+                    if !suggestion.tags.is_empty() {
+                        ui.add_space(4.0);
+                        ui.horizontal_wrapped(|ui| {
+                            ui.spacing_mut().item_spacing.x = 4.0;
+                            for tag in &suggestion.tags {
+                                let _ = selectable_chip(ui, tag, false);
+                            }
+                        });
+                    }
                 });
-        },
-    );
+            }
+        });
+    }
 }
 
-fn submit_chat_message(state: &mut AppState) {
-    let trimmed = state.chat.input.trim();
-    if trimmed.is_empty() {
-        state.chat.input.clear();
+/// Chips con las sugerencias de seguimiento bajo la última respuesta.
+fn draw_follow_up_chips(
+    ui: &mut egui::Ui,
+    state: &AppState,
+    pending_actions: &mut Vec<PendingChatAction>,
+) {
+    if state.follow_ups.is_loading() {
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(
+                RichText::new(t("Buscando sugerencias…"))
+                    .color(theme::color_text_weak())
+                    .size(11.0),
+            );
+        });
         return;
     }
-
-    let mut input = trimmed.to_string();
-    while input.ends_with('\n') {
-        input.pop();
+    if state.follow_ups.suggestions.is_empty() {
+        return;
     }
-    state.chat.input.clear();
-
-    if input.starts_with('/') {
-        state.chat.messages.push(ChatMessage::user(input.clone()));
-        state.handle_command(input);
+    let hover = if state.config.follow_ups.send_immediately {
+        t("Enviar esta pregunta")
     } else {
-        state.chat.messages.push(ChatMessage::user(input.clone()));
-        let residual = state.try_route_provider_message(&input);
-
-        if state.try_invoke_jarvis_alias(residual.as_str()) {
-            return;
-        }
-
-        let trimmed_residual = residual.trim();
-        if trimmed_residual.is_empty() {
-            return;
+        t("Llevar al compositor")
+    };
+    ui.add_space(6.0);
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        for suggestion in &state.follow_ups.suggestions {
+            if selectable_chip(ui, suggestion, false)
+                .on_hover_text(hover)
+                .clicked()
+            {
+                pending_actions.push(PendingChatAction::FollowUp(suggestion.clone()));
+            }
         }
-
-        state.respond_with_jarvis(trimmed_residual.to_string());
-    }
+    });
 }
 
-fn draw_selected_preference(ui: &mut egui::Ui, state: &mut AppState, tab_index: usize) {
-    match state.selected_preference {
-        PreferencePanel::SystemGithub => draw_system_github(ui, state),
-        PreferencePanel::SystemCache => draw_system_cache(ui, state),
-        PreferencePanel::SystemResources => draw_system_resources(ui, state),
-        PreferencePanel::CustomizationCommands => {
-            draw_custom_commands_section(ui, state, tab_index)
-        }
-        PreferencePanel::CustomizationAppearance => draw_customization_appearance(ui, state),
-        PreferencePanel::CustomizationMemory => draw_customization_memory(ui, state),
-        PreferencePanel::CustomizationProfiles => draw_customization_profiles(ui, state),
-        PreferencePanel::CustomizationProjects => draw_customization_projects(ui, state),
-        PreferencePanel::ProvidersAnthropic => draw_provider_anthropic(ui, state, tab_index),
-        PreferencePanel::ProvidersOpenAi => draw_provider_openai(ui, state, tab_index),
-        PreferencePanel::ProvidersGroq => draw_provider_groq(ui, state, tab_index),
-        PreferencePanel::LocalJarvis => draw_local_settings(ui, state),
+fn insert_quick_token(state: &mut AppState, token: &str) {
+    if !state.chat.input.is_empty() && !state.chat.input.ends_with(' ') {
+        state.chat.input.push(' ');
     }
-}
-
-fn draw_selected_resource(ui: &mut egui::Ui, state: &mut AppState, section: ResourceSection) {
-    match section {
-        ResourceSection::LocalCatalog(provider) => draw_local_provider(ui, state, provider),
-        ResourceSection::RemoteCatalog(kind) => draw_remote_provider_catalog(ui, state, kind),
-        ResourceSection::InstalledLocal => draw_local_library_overview(ui, state),
-        ResourceSection::ConnectedProjects => {
-            draw_project_resources(ui, state, ProjectResourceKind::LocalProject)
-        }
-        ResourceSection::GithubRepositories => {
-            draw_project_resources(ui, state, ProjectResourceKind::GithubRepository)
-        }
+    state.chat.input.push_str(token);
+    if !token.ends_with(' ') {
+        state.chat.input.push(' ');
     }
 }
 
-fn draw_project_resources(ui: &mut egui::Ui, state: &mut AppState, kind: ProjectResourceKind) {
-    let (title, subtitle) = match kind {
-        ProjectResourceKind::LocalProject => (
-            "Proyectos locales sincronizados",
-            "Explora carpetas conectadas al agente con estado de sincronización y README en vivo.",
-        ),
-        ProjectResourceKind::GithubRepository => (
-            "Repositorios GitHub enlazados",
-            "Consulta repositorios con sincronización bidireccional y acciones rápidas desde JungleMonkAI.",
-        ),
-    };
+fn draw_message_bubble(
+    ui: &mut egui::Ui,
+    state: &AppState,
+    message: &ChatMessage,
+    index: usize,
+    pending_actions: &mut Vec<PendingChatAction>,
+) {
+    ui.add_space(if index == 0 { 0.0 } else { 10.0 });
 
-    ui.heading(
-        RichText::new(title)
-            .color(theme::color_text_primary())
-            .strong()
-            .size(18.0),
-    );
-    ui.label(
-        RichText::new(subtitle)
-            .color(theme::color_text_weak())
-            .size(12.0),
-    );
+    let is_user = message.sender == "User";
+    let is_system = message.sender == "System";
+    let (background, border, icon, accent) = if is_user {
+        (
+            Color32::from_rgb(34, 48, 70),
+            Color32::from_rgb(62, 120, 192),
+            ICON_USER,
+            Color32::from_rgb(130, 180, 240),
+        )
+    } else if is_system {
+        (
+            Color32::from_rgb(36, 36, 36),
+            Color32::from_rgb(88, 88, 88),
+            ICON_SYSTEM,
+            Color32::from_rgb(200, 200, 200),
+        )
+    } else {
+        (
+            Color32::from_rgb(30, 36, 46),
+            Color32::from_rgb(70, 110, 180),
+            ICON_ASSISTANT,
+            Color32::from_rgb(150, 200, 255),
+        )
+    };
 
-    ui.add_space(10.0);
+    let layout = if is_user {
+        egui::Layout::right_to_left(egui::Align::TOP)
+    } else {
+        egui::Layout::left_to_right(egui::Align::TOP)
+    };
 
-    let cards = state.resources.project_resources_by_kind(kind);
-    if cards.is_empty() {
-        ui.colored_label(
-            theme::color_text_weak(),
-            "No hay recursos sincronizados en esta categoría todavía.",
-        );
-        return;
-    }
+    ui.with_layout(layout, |ui| {
+        let available_width = ui.available_width();
+        let mut bubble_width = if available_width > 32.0 {
+            (available_width - 16.0).max(available_width * 0.6)
+        } else {
+            available_width
+        };
+        if available_width > 320.0 {
+            bubble_width = bubble_width.clamp(320.0, available_width);
+        }
+        bubble_width = bubble_width.min(available_width);
 
-    for card in cards {
-        draw_project_resource_card(ui, state, &card);
-        ui.add_space(12.0);
-    }
-}
+        ui.add_space(8.0);
+        let frame = egui::Frame::none()
+            .fill(background)
+            .stroke(egui::Stroke::new(1.4, border))
+            .rounding(egui::Rounding::same(14.0))
+            .inner_margin(egui::Margin::same(16.0));
 
-fn draw_project_resource_card(ui: &mut egui::Ui, state: &mut AppState, card: &ProjectResourceCard) {
-    egui::Frame::none()
-        .fill(Color32::from_rgb(34, 36, 42))
-        .stroke(theme::subtle_border(&state.theme))
-        .rounding(egui::Rounding::same(16.0))
-        .inner_margin(egui::Margin {
-            left: 18.0,
-            right: 18.0,
-            top: 14.0,
-            bottom: 14.0,
-        })
-        .show(ui, |ui| {
-            ui.set_width(ui.available_width());
+        let response = frame.show(ui, |ui| {
+            ui.set_width(bubble_width);
             ui.vertical(|ui| {
-                ui.horizontal(|ui| {
-                    ui.heading(
-                        RichText::new(&card.name)
-                            .color(theme::color_text_primary())
-                            .size(16.0)
-                            .strong(),
-                    );
-                    ui.add_space(ui.available_width());
-                    let status_color = sync_health_color(card.status.health);
-                    ui.label(
-                        RichText::new(card.status.label())
-                            .color(status_color)
-                            .monospace()
-                            .size(12.0),
-                    );
-                });
-
-                ui.label(
-                    RichText::new(card.status.detail())
-                        .color(theme::color_text_weak())
-                        .size(12.0),
-                );
-
-                ui.add_space(8.0);
-                ui.horizontal(|ui| {
-                    ui.label(
-                        RichText::new(format!("Ubicación: {}", card.location))
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                    );
-                    ui.add_space(18.0);
-                    ui.label(
-                        RichText::new(format!("Última sincronización: {}", card.last_sync))
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                    );
-                    ui.add_space(18.0);
-                    ui.label(
-                        RichText::new(format!("Rama principal: {}", card.default_branch))
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                    );
-                });
-
-                if !card.tags.is_empty() {
+                let pinned = state.is_message_pinned(index);
+                draw_message_header(ui, message, index, pinned, icon, accent, pending_actions);
+                ui.add_space(6.0);
+                if let Some(quote) = message.reply_to.as_ref() {
+                    draw_reply_quote_header(ui, quote, index, accent);
                     ui.add_space(6.0);
-                    ui.horizontal_wrapped(|ui| {
-                        ui.spacing_mut().item_spacing.x = 6.0;
-                        ui.label(
-                            RichText::new("Tags")
-                                .color(theme::color_text_weak())
-                                .size(11.0),
-                        );
-                        for tag in &card.tags {
-                            selectable_chip(ui, tag, false);
-                        }
-                    });
                 }
-
-                ui.add_space(10.0);
-                ui.label(
-                    RichText::new("README destacado")
-                        .color(theme::color_text_primary())
-                        .size(12.0)
-                        .strong(),
-                );
-                ui.add_space(4.0);
-                egui::Frame::none()
-                    .fill(Color32::from_rgb(28, 30, 36))
-                    .stroke(theme::subtle_border(&state.theme))
-                    .rounding(egui::Rounding::same(12.0))
-                    .inner_margin(egui::Margin::same(12.0))
-                    .show(ui, |ui| {
-                        ui.label(
-                            RichText::new(&card.readme_preview)
-                                .color(theme::color_text_weak())
-                                .monospace()
-                                .size(12.0),
-                        );
-                    });
-
-                if !card.pending_actions.is_empty() {
-                    ui.add_space(8.0);
-                    ui.label(
-                        RichText::new("Acciones sugeridas")
-                            .color(theme::color_text_primary())
-                            .size(12.0)
-                            .strong(),
-                    );
-                    for action in &card.pending_actions {
-                        ui.label(
-                            RichText::new(format!("• {}", action))
-                                .color(theme::color_text_weak())
-                                .size(11.0),
-                        );
-                    }
+                if let Some(anchor) = message.code_anchor.as_ref() {
+                    draw_code_anchor_chip(ui, message, anchor, accent, pending_actions);
+                    ui.add_space(6.0);
                 }
-
-                ui.add_space(10.0);
-                ui.horizontal(|ui| {
-                    let open_button = theme::secondary_button(
-                        RichText::new("Abrir README")
-                            .color(theme::color_text_primary())
-                            .strong(),
-                        &state.theme,
-                    )
-                    .min_size(egui::vec2(140.0, 30.0));
-                    if ui.add(open_button).clicked() {
-                        state.push_activity_log(
-                            LogStatus::Ok,
-                            "Recursos",
-                            format!("Abrió README de {}", card.name),
-                        );
-                        ui.output_mut(|out| out.copied_text = card.readme_preview.clone());
-                    }
-
-                    ui.add_space(8.0);
-                    let sync_button = theme::primary_button(
-                        RichText::new("Sincronizar ahora")
-                            .color(Color32::WHITE)
-                            .strong(),
-                        &state.theme,
-                    )
-                    .min_size(egui::vec2(150.0, 30.0));
-                    if ui.add(sync_button).clicked() {
-                        state.push_activity_log(
-                            LogStatus::Running,
-                            "Recursos",
-                            format!("Sincronización solicitada para {}", card.name),
-                        );
-                        state.push_debug_event(
-                            DebugLogLevel::Info,
-                            "resources::sync",
-                            format!("Marcado '{}' para sincronización manual", card.name),
-                        );
+                let body = ui.scope(|ui| match &message.structured {
+                    Some(value) => {
+                        ui.push_id(("structured_output", index), |ui| {
+                            draw_structured_output(ui, value, &message.text, accent);
+                        });
                     }
+                    None => draw_message_body(ui, message, accent),
                 });
+                if !message.is_pending() {
+                    draw_text_actions_menu(ui, state, &body.response, index, pending_actions);
+                }
+                if let Some(error) = message.error.as_ref() {
+                    ui.add_space(6.0);
+                    draw_provider_error_actions(ui, message, error, index, pending_actions);
+                }
+                draw_developer_artifacts(ui, message, &state.theme);
             });
         });
-}
 
-fn sync_health_color(health: SyncHealth) -> Color32 {
-    match health {
-        SyncHealth::Healthy => theme::color_success(),
-        SyncHealth::Warning => Color32::from_rgb(255, 196, 0),
-        SyncHealth::Error => theme::color_danger(),
-    }
+        if response.response.double_clicked() && !is_user && !message.is_pending() {
+            pending_actions.push(PendingChatAction::Mention(format!(
+                "@{}",
+                message.sender.to_lowercase()
+            )));
+        }
+    });
 }
 
-fn draw_remote_provider_catalog(
+/// Menú de acciones de texto que se abre con clic derecho sobre el cuerpo del mensaje.
+/// Se dibuja a mano: un `interact` sobre toda la burbuja taparía los botones de los
+/// bloques de código y las tablas.
+fn draw_text_actions_menu(
     ui: &mut egui::Ui,
-    state: &mut AppState,
-    provider: RemoteProviderKind,
+    state: &AppState,
+    body: &egui::Response,
+    index: usize,
+    pending_actions: &mut Vec<PendingChatAction>,
 ) {
-    match provider {
-        RemoteProviderKind::Anthropic => {
-            let anthropic_key = state.config.anthropic.api_key.clone().unwrap_or_default();
-            let trimmed = anthropic_key.trim().to_string();
-            draw_claude_catalog(ui, state, trimmed.as_str());
-            ui.add_space(18.0);
-            draw_remote_catalog_explorer(ui, state, provider);
-        }
-        RemoteProviderKind::OpenAi | RemoteProviderKind::Groq => {
-            draw_remote_catalog_explorer(ui, state, provider);
+    let actions = &state.config.text_actions.actions;
+    if actions.is_empty() {
+        return;
+    }
+    let menu_id = egui::Id::new(("text_actions_menu", index));
+    let opened_here =
+        ui.input(|input| input.pointer.secondary_clicked()) && body.contains_pointer();
+    if opened_here {
+        if let Some(pos) = ui.input(|input| input.pointer.interact_pos()) {
+            ui.data_mut(|data| data.insert_temp(menu_id, pos));
         }
     }
+    let Some(pos) = ui.data(|data| data.get_temp::<egui::Pos2>(menu_id)) else {
+        return;
+    };
+
+    let mut close = false;
+    let area = egui::Area::new(menu_id.with("area"))
+        .order(egui::Order::Foreground)
+        .fixed_pos(pos)
+        .show(ui.ctx(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(
+                    RichText::new(t("Transformar mensaje"))
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                );
+                for (action, template) in actions.iter().enumerate() {
+                    if ui.button(t(&template.label)).clicked() {
+                        pending_actions.push(PendingChatAction::TextAction { index, action });
+                        close = true;
+                    }
+                }
+                ui.separator();
+                if ui.button(t("Editar acciones…")).clicked() {
+                    pending_actions.push(PendingChatAction::OpenPreference(
+                        PreferencePanel::CustomizationTextActions,
+                    ));
+                    close = true;
+                }
+            });
+        });
+    let (clicked, escape) = ui.input(|input| {
+        (
+            input.pointer.any_click(),
+            input.key_pressed(egui::Key::Escape),
+        )
+    });
+    let clicked_outside = clicked && !opened_here && !area.response.contains_pointer();
+    if close || clicked_outside || escape {
+        ui.data_mut(|data| data.remove::<egui::Pos2>(menu_id));
+    }
 }
 
-fn draw_remote_catalog_explorer(
+fn draw_provider_error_actions(
     ui: &mut egui::Ui,
-    state: &mut AppState,
-    provider: RemoteProviderKind,
+    message: &ChatMessage,
+    error: &ProviderError,
+    index: usize,
+    pending_actions: &mut Vec<PendingChatAction>,
 ) {
-    let provider_label = provider.display_name();
-    ui.heading(
-        RichText::new(format!("{} · Galería enriquecida", provider_label))
-            .color(theme::color_text_primary())
-            .strong()
-            .size(18.0),
-    );
-    ui.label(
-        RichText::new(
-            "Compara capacidades, costos y lanza pruebas rápidas directamente desde JungleMonkAI.",
-        )
-        .color(theme::color_text_weak())
-        .size(12.0),
-    );
-
-    ui.add_space(10.0);
-    let tags = state.resources.remote_catalog.all_tags(provider);
-    let mut reset_status = false;
-
-    {
-        let filters = state.resources.remote_catalog.filters_mut(provider);
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 8.0;
+        ui.label(
+            RichText::new(ICON_WARNING)
+                .font(theme::icon_font(12.0))
+                .color(theme::color_danger()),
+        );
+        ui.label(
+            RichText::new(error.kind_label())
+                .color(theme::color_danger())
+                .strong()
+                .size(12.0),
+        );
 
-        ui.horizontal(|ui| {
-            let search_width = (ui.available_width() - 140.0).max(200.0);
-            let search_response = ui.add_sized(
-                [search_width, 30.0],
-                egui::TextEdit::singleline(&mut filters.search)
-                    .hint_text("Buscar por nombre, tags o capacidades"),
-            );
-            if search_response.changed() {
-                reset_status = true;
+        if error.requires_reauth() {
+            if let Some(provider) = message.origin {
+                let button = egui::Button::new(
+                    RichText::new(format!("{} Revisar credenciales", ICON_KEY))
+                        .color(Color32::from_rgb(240, 240, 240))
+                        .size(12.0),
+                )
+                .fill(Color32::from_rgb(70, 48, 48));
+                if ui
+                    .add(button)
+                    .on_hover_text(format!(
+                        "Abre la configuración de {} para actualizar la API key.",
+                        provider.display_name()
+                    ))
+                    .clicked()
+                {
+                    pending_actions.push(PendingChatAction::OpenPreference(
+                        provider.preference_panel(),
+                    ));
+                }
             }
-
-            if ui
-                .add_sized([120.0, 30.0], egui::Button::new("Limpiar filtros"))
-                .clicked()
-            {
-                *filters = Default::default();
-                reset_status = true;
+        } else if error.is_retryable() {
+            if let Some(wait) = error.retry_after() {
+                ui.label(
+                    RichText::new(format!("Espera ≈{} s antes de reintentar.", wait.as_secs()))
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                );
             }
-        });
-
-        ui.add_space(6.0);
-        ui.horizontal(|ui| {
-            let mut cost_enabled = filters.max_cost.is_some();
+            let button = egui::Button::new(
+                RichText::new(format!("{} Reintentar", ICON_REPEAT))
+                    .color(Color32::from_rgb(240, 240, 240))
+                    .size(12.0),
+            )
+            .fill(Color32::from_rgb(44, 52, 68));
             if ui
-                .checkbox(&mut cost_enabled, "Coste ≤ USD / 1M tokens")
-                .changed()
+                .add_enabled(message.is_regenerable(), button)
+                .on_hover_text("Vuelve a enviar el mismo prompt al proveedor.")
+                .clicked()
             {
-                if cost_enabled {
-                    filters.max_cost = Some(filters.max_cost.unwrap_or(15.0));
-                } else {
-                    filters.max_cost = None;
-                }
+                pending_actions.push(PendingChatAction::Regenerate {
+                    index,
+                    provider: None,
+                });
             }
+        } else {
+            ui.label(
+                RichText::new("Revisa el prompt o el modelo seleccionado antes de reenviarlo.")
+                    .color(theme::color_text_weak())
+                    .size(12.0),
+            );
+        }
+    });
+}
 
-            if cost_enabled {
-                let mut value = filters.max_cost.unwrap_or(15.0);
-                if ui
-                    .add(
-                        egui::Slider::new(&mut value, 0.5..=120.0)
-                            .logarithmic(true)
-                            .text("USD / 1M"),
-                    )
-                    .changed()
-                {
-                    filters.max_cost = Some(value);
-                }
-            }
+fn draw_code_anchor_chip(
+    ui: &mut egui::Ui,
+    message: &ChatMessage,
+    anchor: &CodeAnchor,
+    accent: Color32,
+    pending_actions: &mut Vec<PendingChatAction>,
+) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 8.0;
+        let chip = egui::Button::new(
+            RichText::new(format!("{} {}", ICON_CODE, anchor.label()))
+                .font(theme::icon_font(12.0))
+                .color(accent),
+        )
+        .fill(Color32::from_rgb(24, 28, 34))
+        .stroke(egui::Stroke::new(1.0, accent.linear_multiply(0.4)));
+        if ui
+            .add(chip)
+            .on_hover_text(tf(
+                "Abrir {0} en el explorador de código",
+                &[&anchor.project],
+            ))
+            .clicked()
+        {
+            pending_actions.push(PendingChatAction::OpenCodeAnchor(anchor.clone()));
+        }
 
-            let mut context_enabled = filters.min_context.is_some();
+        if message.sender == "User" || message.is_pending() {
+            return;
+        }
+        if let Some(diff) = code_viewer::extract_patch(&message.text) {
             if ui
-                .checkbox(&mut context_enabled, "Contexto mínimo")
-                .changed()
+                .button(RichText::new(t("Ver como parche")).size(12.0))
+                .on_hover_text(t("Muestra el diff de la respuesta junto al archivo"))
+                .clicked()
             {
-                if context_enabled {
-                    filters.min_context = Some(filters.min_context.unwrap_or(8192));
-                } else {
-                    filters.min_context = None;
-                }
-            }
-
-            if context_enabled {
-                let mut value = filters.min_context.unwrap_or(8192) as f32;
-                if ui
-                    .add(
-                        egui::Slider::new(&mut value, 4096.0..=400_000.0)
-                            .logarithmic(true)
-                            .text("tokens"),
-                    )
-                    .changed()
-                {
-                    filters.min_context = Some(value.round() as u32);
-                }
+                pending_actions.push(PendingChatAction::ShowPatch {
+                    anchor: anchor.clone(),
+                    diff,
+                });
             }
-        });
-
-        ui.add_space(4.0);
-        ui.horizontal(|ui| {
-            ui.checkbox(&mut filters.favorites_only, "Solo favoritos");
-            ui.checkbox(&mut filters.multimodal_only, "Solo multimodal");
-        });
+        }
+    });
+}
 
-        if !tags.is_empty() {
-            ui.add_space(6.0);
-            ui.horizontal_wrapped(|ui| {
-                ui.spacing_mut().item_spacing.x = 6.0;
+fn draw_reply_quote_header(ui: &mut egui::Ui, quote: &MessageQuote, index: usize, accent: Color32) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(24, 28, 34))
+        .stroke(egui::Stroke::new(1.0, accent.linear_multiply(0.4)))
+        .rounding(egui::Rounding::same(8.0))
+        .inner_margin(egui::Margin::symmetric(10.0, 6.0))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            egui::CollapsingHeader::new(
+                RichText::new(format!(
+                    "En respuesta a {} (#{}) · {}",
+                    quote.sender,
+                    quote.message_index + 1,
+                    quote.excerpt(60)
+                ))
+                .color(theme::color_text_weak())
+                .size(12.0),
+            )
+            .id_source(("reply_quote", index))
+            .default_open(false)
+            .show(ui, |ui| {
                 ui.label(
-                    RichText::new(format!("{} Tags", ICON_FILTER))
+                    RichText::new(&quote.text)
                         .color(theme::color_text_weak())
-                        .size(11.0),
+                        .italics()
+                        .size(13.0),
                 );
-                for tag in &tags {
-                    let selected = filters.tag_filters.contains(tag);
-                    if selectable_chip(ui, tag, selected).clicked() {
-                        if selected {
-                            filters.tag_filters.remove(tag);
-                        } else {
-                            filters.tag_filters.insert(tag.clone());
-                        }
-                        reset_status = true;
-                    }
-                }
-                if !filters.tag_filters.is_empty()
-                    && ui
-                        .button(RichText::new("Limpiar tags").size(11.0))
-                        .clicked()
-                {
-                    filters.tag_filters.clear();
-                    reset_status = true;
-                }
             });
-        }
-    }
-
-    if reset_status {
-        state.resources.remote_catalog.update_status(None);
-    }
+        });
+}
 
-    ui.add_space(10.0);
+fn draw_message_header(
+    ui: &mut egui::Ui,
+    message: &ChatMessage,
+    index: usize,
+    pinned: bool,
+    icon: &str,
+    accent: Color32,
+    pending_actions: &mut Vec<PendingChatAction>,
+) {
     ui.horizontal(|ui| {
-        let prompt_width = (ui.available_width() - 140.0).max(200.0);
-        ui.add_sized(
-            [prompt_width, 30.0],
-            egui::TextEdit::singleline(&mut state.resources.remote_catalog.quick_test_prompt)
-                .hint_text("Prompt para 'Probar' (ej. Resume los últimos commits)"),
+        ui.spacing_mut().item_spacing.x = 8.0;
+        ui.label(
+            RichText::new(icon)
+                .font(theme::icon_font(16.0))
+                .color(accent),
         );
-        if ui
-            .add_sized([120.0, 30.0], egui::Button::new("Limpiar prompt"))
-            .clicked()
-        {
-            state.resources.remote_catalog.quick_test_prompt.clear();
+        let sender_label = message.sender_display_label();
+        ui.label(
+            RichText::new(sender_label.as_ref())
+                .strong()
+                .color(theme::color_text_primary()),
+        );
+        if let Some(origin) = message.origin {
+            ui.label(
+                RichText::new(origin.display_name())
+                    .color(accent)
+                    .size(12.0)
+                    .italics(),
+            );
         }
-    });
-
-    if let Some(status) = &state.resources.remote_catalog.last_status {
-        ui.add_space(6.0);
-        ui.colored_label(theme::color_text_weak(), status);
+        ui.label(
+            RichText::new(ICON_CLOCK)
+                .font(theme::icon_font(12.0))
+                .color(theme::color_text_weak()),
+        );
+        ui.label(
+            RichText::new(&message.timestamp)
+                .italics()
+                .size(12.0)
+                .color(theme::color_text_weak()),
+        );
+        if pinned {
+            ui.label(
+                RichText::new(ICON_PIN)
+                    .font(theme::icon_font(12.0))
+                    .color(theme::color_primary()),
+            )
+            .on_hover_text(t("Mensaje fijado"));
+        }
+        ui.add_space(ui.available_width());
+        draw_message_actions(ui, message, index, pinned, pending_actions);
+    });
+}
+
+fn draw_message_actions(
+    ui: &mut egui::Ui,
+    message: &ChatMessage,
+    index: usize,
+    pinned: bool,
+    pending_actions: &mut Vec<PendingChatAction>,
+) {
+    let enabled = !message.is_pending();
+
+    if message_action_button(ui, ICON_COPY, "Copiar mensaje al portapapeles", enabled).clicked() {
+        let text = message.combined_text();
+        ui.output_mut(|out| out.copied_text = text);
     }
 
-    ui.add_space(8.0);
-    let cards: Vec<RemoteModelCard> = {
-        let refs = state.resources.remote_catalog.filtered_cards(provider);
-        refs.into_iter().cloned().collect()
+    if message_action_button(ui, ICON_QUOTE, "Citar mensaje en el input", enabled).clicked() {
+        let mut quoted = message
+            .combined_text()
+            .lines()
+            .map(|line| format!("> {}", line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        quoted.push_str("\n\n");
+        pending_actions.push(PendingChatAction::Quote(quoted));
+    }
+
+    if message_action_button(ui, ICON_REPLY, "Responder a este mensaje", enabled).clicked() {
+        pending_actions.push(PendingChatAction::Reply(index));
+    }
+
+    if message_action_button(ui, ICON_REUSE, "Reutilizar este mensaje", enabled).clicked() {
+        pending_actions.push(PendingChatAction::Reuse(message.combined_text()));
+    }
+
+    let pin_tooltip = if pinned {
+        "Soltar mensaje"
+    } else {
+        "Fijar mensaje"
     };
-    if cards.is_empty() {
-        ui.colored_label(
-            theme::color_text_weak(),
-            "Ajusta los filtros o actualiza tus credenciales para mostrar modelos disponibles.",
+    if message_action_button(ui, ICON_PIN, pin_tooltip, enabled).clicked() {
+        pending_actions.push(PendingChatAction::TogglePin(index));
+    }
+
+    if message_action_button(ui, ICON_FORK, "Bifurcar la conversación aquí", enabled).clicked() {
+        pending_actions.push(PendingChatAction::Fork(index));
+    }
+
+    if message.sender == "User" {
+        if message_action_button(ui, ICON_EDIT, "Editar y reenviar desde aquí", enabled).clicked()
+        {
+            pending_actions.push(PendingChatAction::EditResend(index));
+        }
+        return;
+    }
+
+    if message.source_prompt.is_none() {
+        return;
+    }
+
+    let regenerable = message.is_regenerable();
+    if message_action_button(ui, ICON_REPEAT, "Regenerar respuesta", regenerable).clicked() {
+        pending_actions.push(PendingChatAction::Regenerate {
+            index,
+            provider: None,
+        });
+    }
+
+    ui.add_enabled_ui(regenerable, |ui| {
+        let response = ui.menu_button(
+            RichText::new(ICON_SHUFFLE)
+                .font(theme::icon_font(13.0))
+                .color(Color32::from_rgb(230, 230, 230)),
+            |ui| {
+                ui.label(
+                    RichText::new("Regenerar con…")
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                );
+                for provider in [
+                    RemoteProviderKind::Anthropic,
+                    RemoteProviderKind::OpenAi,
+                    RemoteProviderKind::Groq,
+                ] {
+                    let current = message.origin == Some(provider);
+                    if ui
+                        .selectable_label(current, provider.display_name())
+                        .clicked()
+                    {
+                        pending_actions.push(PendingChatAction::Regenerate {
+                            index,
+                            provider: Some(provider),
+                        });
+                        ui.close_menu();
+                    }
+                }
+            },
         );
-    } else {
+        theme::icon_button_label(response.response, t("Regenerar con otro proveedor"));
+    });
+}
+
+fn message_action_button(
+    ui: &mut egui::Ui,
+    icon: &str,
+    tooltip: &str,
+    enabled: bool,
+) -> egui::Response {
+    let button = egui::Button::new(
+        RichText::new(icon)
+            .font(theme::icon_font(13.0))
+            .color(Color32::from_rgb(230, 230, 230)),
+    )
+    .min_size(egui::vec2(30.0, 26.0))
+    .fill(Color32::from_rgb(44, 46, 54))
+    .rounding(egui::Rounding::same(6.0));
+
+    let response = ui.add_enabled(enabled, button);
+    theme::icon_button_label(response, t(tooltip))
+}
+
+fn draw_message_body(ui: &mut egui::Ui, message: &ChatMessage, accent: Color32) {
+    if message.is_pending() {
+        let pending_text = message.combined_text();
         ui.horizontal(|ui| {
-            ui.heading(
-                RichText::new(format!("{} resultados", cards.len()))
-                    .color(theme::color_text_primary())
-                    .size(16.0),
-            );
-            ui.add_space(ui.available_width());
+            ui.add(Spinner::new().size(18.0));
             ui.label(
-                RichText::new(
-                    "Utiliza 'Probar' para lanzar una solicitud con el prompt configurado.",
-                )
-                .color(theme::color_text_weak())
-                .size(11.0),
+                RichText::new(pending_text)
+                    .color(theme::color_text_weak())
+                    .italics()
+                    .size(14.0),
             );
         });
-        ui.add_space(8.0);
-        draw_remote_model_gallery(ui, state, &cards);
+        return;
     }
 
-    draw_remote_comparison(ui, state);
+    let display_text = message.combined_text();
+    let blocks = parse_markdown_blocks(&display_text);
+    if blocks.is_empty() {
+        render_formatted_text(ui, &display_text, theme::color_text_primary(), 15.0);
+    } else {
+        render_markdown_blocks(ui, &blocks, accent);
+    }
 }
 
-fn draw_remote_model_gallery(ui: &mut egui::Ui, state: &mut AppState, cards: &[RemoteModelCard]) {
-    let spacing = 18.0;
-    let min_card_width = 280.0;
+/// Respuesta JSON validada: árbol plegable con la opción de copiar el texto original.
+fn draw_structured_output(
+    ui: &mut egui::Ui,
+    value: &serde_json::Value,
+    raw: &str,
+    accent: Color32,
+) {
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(t("JSON válido según el esquema"))
+                .color(theme::color_success())
+                .size(12.0),
+        );
+        if ui.small_button(t("Copiar JSON")).clicked() {
+            let text = serde_json::to_string_pretty(value).unwrap_or_else(|_| raw.to_string());
+            ui.output_mut(|out| out.copied_text = text);
+        }
+    });
+    ui.add_space(4.0);
+    draw_json_node(ui, None, value, "$", accent);
+}
 
-    egui::ScrollArea::vertical()
-        .id_source("remote_models_gallery")
-        .max_height(420.0)
-        .auto_shrink([false, false])
-        .show(ui, |ui| {
-            let available_width = ui.available_width().max(min_card_width);
-            let mut columns =
-                ((available_width + spacing) / (min_card_width + spacing)).floor() as usize;
-            columns = columns.clamp(1, 3);
-            let card_width = ((available_width - spacing * ((columns as f32) - 1.0))
-                / columns as f32)
-                .max(min_card_width);
+fn draw_json_node(
+    ui: &mut egui::Ui,
+    key: Option<&str>,
+    value: &serde_json::Value,
+    path: &str,
+    accent: Color32,
+) {
+    use serde_json::Value;
 
-            for chunk in cards.chunks(columns) {
-                ui.horizontal(|ui| {
-                    ui.spacing_mut().item_spacing.x = spacing;
-                    for card in chunk {
-                        let (rect, _) = ui
-                            .allocate_at_least(egui::vec2(card_width, 240.0), egui::Sense::hover());
-                        let mut card_ui =
-                            ui.child_ui(rect, egui::Layout::top_down(egui::Align::LEFT));
-                        draw_remote_model_card(&mut card_ui, state, card);
-                    }
+    let prefix = key.map(|key| format!("{}: ", key)).unwrap_or_default();
+    match value {
+        Value::Object(object) => {
+            egui::CollapsingHeader::new(
+                RichText::new(format!("{}{{{}}}", prefix, object.len()))
+                    .color(accent)
+                    .monospace(),
+            )
+            .id_source(path)
+            .default_open(true)
+            .show(ui, |ui| {
+                for (child_key, child) in object {
+                    let child_path = format!("{}.{}", path, child_key);
+                    draw_json_node(ui, Some(child_key), child, &child_path, accent);
+                }
+            });
+        }
+        Value::Array(items) => {
+            egui::CollapsingHeader::new(
+                RichText::new(format!("{}[{}]", prefix, items.len()))
+                    .color(accent)
+                    .monospace(),
+            )
+            .id_source(path)
+            .default_open(items.len() <= 20)
+            .show(ui, |ui| {
+                for (index, item) in items.iter().enumerate() {
+                    let child_path = format!("{}[{}]", path, index);
+                    draw_json_node(ui, Some(&index.to_string()), item, &child_path, accent);
+                }
+            });
+        }
+        scalar => {
+            let color = match scalar {
+                Value::String(_) => theme::color_text_primary(),
+                Value::Null => theme::color_text_weak(),
+                _ => theme::color_primary(),
+            };
+            ui.horizontal_wrapped(|ui| {
+                ui.label(
+                    RichText::new(prefix)
+                        .color(theme::color_text_weak())
+                        .monospace(),
+                );
+                ui.label(RichText::new(scalar.to_string()).color(color).monospace());
+            });
+        }
+    }
+}
 
-                    if chunk.len() < columns {
-                        for _ in chunk.len()..columns {
-                            ui.add_space(card_width);
-                        }
+fn render_markdown_blocks(ui: &mut egui::Ui, blocks: &[MarkdownBlock], accent: Color32) {
+    let mut first = true;
+    for block in blocks {
+        if !first {
+            ui.add_space(6.0);
+        }
+        first = false;
+
+        match block {
+            MarkdownBlock::Heading { level, text } => {
+                let size = match level {
+                    1 => 20.0,
+                    2 => 18.0,
+                    3 => 16.0,
+                    _ => 15.0,
+                };
+                ui.label(RichText::new(text).color(accent).strong().size(size));
+            }
+            MarkdownBlock::Paragraph(text) => {
+                render_formatted_text(ui, text, theme::color_text_primary(), 15.0);
+            }
+            MarkdownBlock::BulletList(items) => {
+                ui.vertical(|ui| {
+                    for item in items {
+                        ui.horizontal(|ui| {
+                            ui.spacing_mut().item_spacing.x = 8.0;
+                            ui.label(RichText::new("•").color(accent).strong().size(16.0));
+                            render_formatted_text(ui, item, theme::color_text_primary(), 15.0);
+                        });
                     }
                 });
-                ui.add_space(spacing);
             }
-        });
+            MarkdownBlock::CodeBlock { language, code } => {
+                match data_table::parse_delimited(code, language) {
+                    Some((headers, rows)) => draw_markdown_table(ui, &headers, &rows),
+                    None => draw_code_block(ui, language, code),
+                }
+            }
+            MarkdownBlock::Table { headers, rows } => {
+                draw_markdown_table(ui, headers, rows);
+            }
+        }
+    }
 }
 
-fn draw_remote_model_card(ui: &mut egui::Ui, state: &mut AppState, card: &RemoteModelCard) {
-    let is_favorite = state.resources.remote_catalog.is_favorite(&card.key);
-    let in_comparison = state.resources.remote_catalog.in_comparison(&card.key);
-    let fill = if is_favorite {
-        Color32::from_rgb(44, 40, 60)
+fn draw_code_block(ui: &mut egui::Ui, language: &str, code: &str) {
+    let code_string = code.trim_end_matches('\n').to_string();
+    let header_label = if language.trim().is_empty() {
+        "Bloque de código".to_string()
     } else {
-        Color32::from_rgb(34, 38, 44)
+        format!("{}", language)
     };
 
-    egui::Frame::none()
-        .fill(fill)
-        .stroke(egui::Stroke::new(1.0, Color32::from_rgb(70, 80, 96)))
-        .rounding(egui::Rounding::same(12.0))
-        .inner_margin(egui::Margin::symmetric(16.0, 12.0))
-        .show(ui, |ui| {
-            ui.set_width(ui.available_width());
-            ui.vertical(|ui| {
+    egui::CollapsingHeader::new(
+        RichText::new(format!("{} {}", ICON_CODE, header_label))
+            .color(theme::color_text_primary())
+            .strong()
+            .size(13.0),
+    )
+    .default_open(true)
+    .show(ui, |ui| {
+        egui::Frame::none()
+            .fill(Color32::from_rgb(32, 34, 40))
+            .stroke(egui::Stroke::new(1.0, Color32::from_rgb(60, 72, 92)))
+            .rounding(egui::Rounding::same(10.0))
+            .inner_margin(egui::Margin::symmetric(14.0, 12.0))
+            .show(ui, |ui| {
+                ui.set_width(ui.available_width());
                 ui.horizontal(|ui| {
-                    ui.spacing_mut().item_spacing.x = 8.0;
-                    ui.label(
-                        RichText::new(&card.title)
-                            .strong()
-                            .color(theme::color_text_primary())
-                            .size(16.0),
-                    );
-                    let star_color = if is_favorite {
-                        Color32::from_rgb(255, 201, 71)
-                    } else {
-                        theme::color_text_weak()
-                    };
-                    let star = egui::Label::new(
-                        RichText::new(ICON_STAR)
-                            .font(theme::icon_font(14.0))
-                            .color(star_color),
-                    )
-                    .sense(egui::Sense::click());
-                    let star_response = ui.add(star).on_hover_text(if is_favorite {
-                        "Quitar de favoritos"
-                    } else {
-                        "Marcar como favorito"
-                    });
-                    if star_response.clicked() {
-                        let provider = card.key.provider;
-                        let key_clone = card.key.clone();
-                        let was_favorite = state.resources.remote_catalog.is_favorite(&key_clone);
-                        state
-                            .resources
-                            .remote_catalog
-                            .toggle_favorite(key_clone.clone());
-                        let favorites_snapshot = state.resources.remote_catalog.favorites.clone();
-                        {
-                            let cards = state.resources.remote_catalog.cards_for_mut(provider);
-                            cards.sort_by(|a, b| {
-                                let a_fav = favorites_snapshot.contains(&a.key);
-                                let b_fav = favorites_snapshot.contains(&b.key);
-                                b_fav.cmp(&a_fav).then_with(|| {
-                                    a.title.to_lowercase().cmp(&b.title.to_lowercase())
-                                })
-                            });
-                        }
-                        let message = if was_favorite {
-                            format!("{} eliminado de favoritos", card.title)
-                        } else {
-                            format!("{} añadido a favoritos", card.title)
-                        };
-                        state
-                            .resources
-                            .remote_catalog
-                            .update_status(Some(message.clone()));
-                        state.push_debug_event(
-                            DebugLogLevel::Info,
-                            format!("catalog::{}", provider.short_code()),
-                            message,
-                        );
-                    }
                     ui.add_space(ui.available_width());
-                    if card.multimodal {
-                        ui.label(
-                            RichText::new("Multimodal")
-                                .color(theme::color_primary())
-                                .size(11.0),
-                        );
+                    if code_copy_button(ui).clicked() {
+                        ui.output_mut(|out| out.copied_text = code_string.clone());
                     }
                 });
+                ui.add_space(6.0);
+                let mut code_buffer = code_string.clone();
+                let rows = code_buffer.lines().count().max(1);
+                ui.add(
+                    egui::TextEdit::multiline(&mut code_buffer)
+                        .font(egui::FontId::monospace(14.0))
+                        .desired_rows(rows)
+                        .frame(false)
+                        .interactive(false)
+                        .desired_width(f32::INFINITY),
+                );
+            });
+    });
+}
 
-                ui.add_space(4.0);
+fn code_copy_button(ui: &mut egui::Ui) -> egui::Response {
+    let button = egui::Button::new(
+        RichText::new(ICON_COPY)
+            .font(theme::icon_font(14.0))
+            .color(Color32::from_rgb(230, 230, 230)),
+    )
+    .min_size(egui::vec2(32.0, 26.0))
+    .fill(Color32::from_rgb(45, 47, 56))
+    .rounding(egui::Rounding::same(6.0));
+
+    theme::icon_button_label(ui.add(button), t("Copiar bloque de código"))
+}
+
+fn draw_markdown_table(ui: &mut egui::Ui, headers: &[String], rows: &[Vec<String>]) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(32, 34, 40))
+        .stroke(egui::Stroke::new(1.0, Color32::from_rgb(60, 72, 92)))
+        .rounding(egui::Rounding::same(10.0))
+        .inner_margin(egui::Margin::symmetric(12.0, 10.0))
+        .show(ui, |ui| {
+            ui.horizontal(|ui| {
                 ui.label(
-                    RichText::new(&card.description)
-                        .color(theme::color_text_weak())
-                        .size(12.0),
+                    RichText::new(format!("{} Tabla", ICON_TABLE))
+                        .color(theme::color_text_primary())
+                        .strong()
+                        .size(13.0),
                 );
+                ui.add_space(ui.available_width());
+                if code_copy_button(ui).clicked() {
+                    let mut buffer = String::new();
+                    buffer.push('|');
+                    buffer.push_str(&headers.join("|"));
+                    buffer.push('|');
+                    buffer.push('\n');
+                    buffer.push('|');
+                    buffer.push_str(&headers.iter().map(|_| "---").collect::<Vec<_>>().join("|"));
+                    buffer.push('|');
+                    buffer.push('\n');
+                    for row in rows {
+                        buffer.push('|');
+                        buffer.push_str(&row.join("|"));
+                        buffer.push('|');
+                        buffer.push('\n');
+                    }
+                    ui.output_mut(|out| out.copied_text = buffer);
+                }
+            });
 
-                ui.add_space(8.0);
-                ui.vertical(|ui| {
+            ui.add_space(6.0);
+            data_table::draw_data_table(ui, headers, rows);
+        });
+}
+
+fn draw_developer_artifacts(ui: &mut egui::Ui, message: &ChatMessage, tokens: &ThemeTokens) {
+    if message.sender == "User" || message.sender == "System" || message.is_pending() {
+        return;
+    }
+
+    let blocks = parse_markdown_blocks(&message.text);
+    let diff_block = extract_diff_block(&blocks);
+    let preview_block = extract_preview_block(&blocks);
+    let summary = extract_summary(&message.text);
+
+    if diff_block.is_none() && preview_block.is_none() && summary.is_none() {
+        return;
+    }
+
+    ui.add_space(10.0);
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 38, 44))
+        .stroke(theme::subtle_border(tokens))
+        .rounding(egui::Rounding::same(10.0))
+        .inner_margin(egui::Margin::symmetric(12.0, 10.0))
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new("Herramientas de desarrollo")
+                    .color(theme::color_text_primary())
+                    .strong()
+                    .size(13.0),
+            );
+
+            if let Some(summary) = summary {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
                     ui.label(
-                        RichText::new(format!(
-                            "Contexto: {} tokens · Salida máx: {} tokens",
-                            card.context_tokens, card.max_output_tokens
-                        ))
-                        .color(theme::color_text_primary())
-                        .size(11.0),
+                        RichText::new(ICON_QUOTE)
+                            .font(theme::icon_font(12.0))
+                            .color(theme::color_primary()),
                     );
                     ui.label(
-                        RichText::new(format!(
-                            "Coste entrada: {} · salida: {} · Latencia ≈ {} ms",
-                            format_cost_label(card.input_cost_per_million),
-                            format_cost_label(card.output_cost_per_million),
-                            card.latency_ms
-                        ))
-                        .color(theme::color_text_weak())
-                        .size(11.0),
+                        RichText::new(summary)
+                            .color(theme::color_text_primary())
+                            .size(12.0),
                     );
                 });
+            }
 
-                if !card.capabilities.is_empty() {
-                    ui.add_space(6.0);
-                    ui.horizontal_wrapped(|ui| {
-                        ui.spacing_mut().item_spacing.x = 6.0;
-                        for capability in &card.capabilities {
-                            ui.label(
-                                RichText::new(capability)
-                                    .color(theme::color_text_weak())
-                                    .size(11.0),
-                            );
+            if let Some(diff) = diff_block {
+                ui.add_space(6.0);
+                egui::CollapsingHeader::new(
+                    RichText::new(format!("{} Diferencias detectadas", ICON_COMPARE))
+                        .color(theme::color_text_primary())
+                        .strong()
+                        .size(12.0),
+                )
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add_space(ui.available_width());
+                        if code_copy_button(ui).clicked() {
+                            ui.output_mut(|out| out.copied_text = diff.clone());
                         }
                     });
-                }
+                    let preview: String = diff
+                        .lines()
+                        .take(20)
+                        .map(|line| line.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    ui.add(
+                        egui::TextEdit::multiline(&mut preview.clone())
+                            .font(egui::FontId::monospace(13.0))
+                            .desired_rows(6)
+                            .frame(false)
+                            .interactive(false)
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            }
 
-                if !card.tags.is_empty() {
-                    ui.add_space(6.0);
-                    ui.horizontal_wrapped(|ui| {
-                        ui.spacing_mut().item_spacing.x = 6.0;
-                        for tag in &card.tags {
-                            selectable_chip(ui, tag, false);
-                        }
-                    });
-                }
-
-                if !card.quick_actions.is_empty() {
-                    ui.add_space(6.0);
-                    ui.horizontal_wrapped(|ui| {
-                        ui.spacing_mut().item_spacing.x = 6.0;
-                        for action in &card.quick_actions {
-                            ui.label(
-                                RichText::new(action)
-                                    .color(theme::color_text_weak())
-                                    .size(11.0),
-                            );
+            if let Some((language, code)) = preview_block {
+                ui.add_space(6.0);
+                egui::CollapsingHeader::new(
+                    RichText::new(format!("{} Vista previa de {}", ICON_FILE_DOC, language))
+                        .color(theme::color_text_primary())
+                        .strong()
+                        .size(12.0),
+                )
+                .default_open(false)
+                .show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.add_space(ui.available_width());
+                        if code_copy_button(ui).clicked() {
+                            ui.output_mut(|out| out.copied_text = code.clone());
                         }
                     });
-                }
-
-                ui.add_space(8.0);
-                ui.label(
-                    RichText::new(&card.favorite_hint)
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                );
-
-                ui.add_space(10.0);
-                ui.horizontal(|ui| {
-                    let mut favorite_toggled = false;
-                    if selectable_chip(ui, "Favorito", is_favorite).clicked() {
-                        favorite_toggled = true;
-                    }
-                    if favorite_toggled {
-                        state
-                            .resources
-                            .remote_catalog
-                            .toggle_favorite(card.key.clone());
-                        let now_favorite = !is_favorite;
-                        let status = if now_favorite {
-                            format!("{} marcado como favorito.", card.title)
-                        } else {
-                            format!("{} eliminado de favoritos.", card.title)
-                        };
-                        state.resources.remote_catalog.update_status(Some(status));
-                    }
-
-                    if selectable_chip(ui, "Comparar", in_comparison).clicked() {
-                        state
-                            .resources
-                            .remote_catalog
-                            .toggle_comparison(card.key.clone());
-                        state.resources.remote_catalog.update_status(Some(format!(
-                            "{} {} en la tabla comparativa.",
-                            card.title,
-                            if in_comparison {
-                                "eliminado"
-                            } else {
-                                "añadido"
-                            }
-                        )));
-                    }
-
-                    ui.add_space(ui.available_width());
-
-                    let test_label = RichText::new(format!("{} Probar", ICON_LIGHTNING))
-                        .color(Color32::from_rgb(240, 240, 240));
-                    if ui
-                        .add(
-                            theme::primary_button(test_label, &state.theme)
-                                .min_size(egui::vec2(110.0, 32.0)),
-                        )
-                        .clicked()
-                    {
-                        let status = state.execute_remote_quick_test(card.key.clone());
-                        if let Some(status) = status {
-                            state.resources.remote_catalog.update_status(Some(status));
-                        }
-                    }
+                    let snippet: String = code
+                        .lines()
+                        .take(20)
+                        .map(|line| line.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    ui.add(
+                        egui::TextEdit::multiline(&mut snippet.clone())
+                            .font(egui::FontId::monospace(13.0))
+                            .desired_rows(6)
+                            .frame(false)
+                            .interactive(false)
+                            .desired_width(f32::INFINITY),
+                    );
                 });
-            });
+            }
         });
 }
 
-fn draw_remote_comparison(ui: &mut egui::Ui, state: &mut AppState) {
-    if state.resources.remote_catalog.comparison.is_empty() {
-        return;
-    }
-
-    ui.add_space(12.0);
-    ui.separator();
-    ui.add_space(6.0);
-    ui.heading(
-        RichText::new("Comparativa rápida")
-            .color(theme::color_text_primary())
-            .size(16.0)
-            .strong(),
-    );
-    ui.add_space(6.0);
-
-    ui.push_id("remote_comparison_grid", |ui| {
-        egui::Grid::new("remote_comparison")
-            .striped(true)
-            .spacing(egui::vec2(12.0, 6.0))
-            .show(ui, |ui| {
-                ui.label(RichText::new("Modelo").strong());
-                ui.label(RichText::new("Contexto").strong());
-                ui.label(RichText::new("Costos").strong());
-                ui.label(RichText::new("Proveedor").strong());
-                ui.label(RichText::new("Acciones").strong());
-                ui.end_row();
+fn render_formatted_text(ui: &mut egui::Ui, text: &str, color: Color32, size: f32) {
+    let segments = parse_inline_segments(text);
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for segment in segments {
+            if segment.text.is_empty() {
+                continue;
+            }
 
-                let mut removals = Vec::new();
-                for key in &state.resources.remote_catalog.comparison {
-                    if let Some(card) = remote_card_by_key(state, key) {
-                        ui.label(
-                            RichText::new(&card.title)
-                                .color(theme::color_text_primary())
-                                .size(12.0),
-                        );
-                        ui.label(
-                            RichText::new(format!("{} tokens", card.context_tokens))
-                                .color(theme::color_text_weak())
-                                .size(11.0),
-                        );
-                        ui.label(
-                            RichText::new(format!(
-                                "{} / {}",
-                                format_cost_label(card.input_cost_per_million),
-                                format_cost_label(card.output_cost_per_million)
-                            ))
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                        );
-                        ui.label(
-                            RichText::new(card.key.provider.display_name())
-                                .color(theme::color_text_weak())
-                                .size(11.0),
-                        );
-                        if ui.button(RichText::new("Quitar").size(11.0)).clicked() {
-                            removals.push(card.key.clone());
-                        }
-                        ui.end_row();
-                    }
-                }
+            let mut rich = RichText::new(segment.text).color(color).size(size);
+            if segment.bold {
+                rich = rich.strong();
+            }
+            if segment.italic {
+                rich = rich.italics();
+            }
+            if segment.code {
+                rich = rich
+                    .monospace()
+                    .background_color(Color32::from_rgb(40, 44, 54))
+                    .color(Color32::from_rgb(220, 220, 220));
+            }
 
-                for key in removals {
-                    state.resources.remote_catalog.toggle_comparison(key);
-                }
-            });
+            ui.label(rich);
+        }
     });
 }
 
-fn format_cost_label(value: f32) -> String {
-    if value < 1.0 {
-        format!("${:.3}", value)
-    } else {
-        format!("${:.2}", value)
-    }
-}
+fn parse_markdown_blocks(text: &str) -> Vec<MarkdownBlock> {
+    let mut blocks = Vec::new();
+    let mut paragraph: Vec<String> = Vec::new();
+    let mut list_items: Vec<String> = Vec::new();
+    let mut code_lines: Vec<String> = Vec::new();
+    let mut code_language = String::new();
+    let mut in_code_block = false;
+    let mut in_table = false;
+    let mut table_headers: Vec<String> = Vec::new();
+    let mut table_rows: Vec<Vec<String>> = Vec::new();
 
-fn remote_card_by_key<'a>(
-    state: &'a AppState,
-    key: &RemoteModelKey,
-) -> Option<&'a RemoteModelCard> {
-    state
-        .resources
-        .remote_catalog
-        .cards_for(key.provider)
-        .iter()
-        .find(|card| card.key == *key)
-}
+    let flush_paragraph = |blocks: &mut Vec<MarkdownBlock>, paragraph: &mut Vec<String>| {
+        if paragraph.is_empty() {
+            return;
+        }
+        let mut combined = String::new();
+        for (index, line) in paragraph.iter().enumerate() {
+            if index > 0 {
+                combined.push(' ');
+            }
+            combined.push_str(line);
+        }
+        paragraph.clear();
+        blocks.push(MarkdownBlock::Paragraph(combined));
+    };
 
-fn draw_system_github(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.label("Personal access token");
-    if ui.text_edit_singleline(&mut state.github_token).changed() {
-        state.persist_config();
-    }
+    let flush_list = |blocks: &mut Vec<MarkdownBlock>, list_items: &mut Vec<String>| {
+        if list_items.is_empty() {
+            return;
+        }
+        blocks.push(MarkdownBlock::BulletList(list_items.clone()));
+        list_items.clear();
+    };
 
-    if ui.button("Connect & sync").clicked() {
-        if state.github_token.trim().is_empty() {
-            state.github_username = None;
-            state.github_repositories.clear();
-            state.selected_github_repo = None;
-            state.github_connection_status =
-                Some("Please enter a valid GitHub token before syncing.".to_string());
-            state.refresh_personalization_resources();
-        } else {
-            match github::fetch_user_and_repositories(&state.github_token) {
-                Ok(data) => {
-                    state.github_username = Some(data.username.clone());
-                    state.github_repositories = data.repositories;
-                    state.selected_github_repo = None;
-                    state.github_connection_status =
-                        Some(format!("GitHub data loaded for {}.", data.username));
-                    state.refresh_personalization_resources();
-                }
-                Err(err) => {
-                    state.github_username = None;
-                    state.github_repositories.clear();
-                    state.selected_github_repo = None;
-                    state.github_connection_status =
-                        Some(format!("Failed to sync GitHub: {}", err));
-                    state.refresh_personalization_resources();
-                }
-            }
-        }
-    }
+    for line in text.lines() {
+        let trimmed_start = line.trim_start();
+        let trimmed = line.trim();
 
-    if let Some(username) = &state.github_username {
-        ui.colored_label(
-            ui.visuals().weak_text_color(),
-            format!("Authenticated as: {}", username),
-        );
-    }
+        let is_table_candidate =
+            trimmed.contains('|') && trimmed.chars().filter(|ch| *ch == '|').count() >= 2;
+        let is_table_separator = trimmed
+            .chars()
+            .all(|ch| matches!(ch, '|' | '-' | ':' | ' '));
 
-    let combo_label = state
-        .selected_github_repo
-        .and_then(|idx| state.github_repositories.get(idx))
-        .cloned()
-        .unwrap_or_else(|| "Choose a repository".to_string());
+        if in_code_block {
+            if trimmed_start.starts_with("```") {
+                let code = code_lines.join("\n");
+                blocks.push(MarkdownBlock::CodeBlock {
+                    language: code_language.clone(),
+                    code,
+                });
+                code_lines.clear();
+                code_language.clear();
+                in_code_block = false;
+            } else {
+                code_lines.push(line.to_string());
+            }
+            continue;
+        }
 
-    ui.add_enabled_ui(!state.github_repositories.is_empty(), |ui| {
-        egui::ComboBox::from_label("Select repository")
-            .selected_text(combo_label)
-            .show_ui(ui, |ui| {
-                for (idx, repo) in state.github_repositories.iter().enumerate() {
-                    ui.selectable_value(&mut state.selected_github_repo, Some(idx), repo);
-                }
-            });
-    });
+        if trimmed_start.starts_with("```") {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            flush_list(&mut blocks, &mut list_items);
+            flush_table_block(
+                &mut blocks,
+                &mut table_headers,
+                &mut table_rows,
+                &mut in_table,
+            );
+            code_language = trimmed_start[3..].trim().to_string();
+            in_code_block = true;
+            code_lines.clear();
+            continue;
+        }
 
-    if state.github_repositories.is_empty() {
-        ui.label("No repositories found yet. Connect with a token to load them.");
-    }
+        if in_table && (!is_table_candidate || trimmed.is_empty()) {
+            flush_table_block(
+                &mut blocks,
+                &mut table_headers,
+                &mut table_rows,
+                &mut in_table,
+            );
+        }
 
-    if ui.button("Sync repository").clicked() {
-        let message = match (
-            state.github_token.trim().is_empty(),
-            state.selected_github_repo,
-        ) {
-            (true, _) => "Cannot sync without a GitHub token.".to_string(),
-            (_, None) => "Please select a repository to sync.".to_string(),
-            (_, Some(idx)) => {
-                let repo = state.github_repositories[idx].clone();
-                format!("Repository '{}' scheduled for synchronization.", repo)
+        if is_table_candidate && !is_table_separator {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            flush_list(&mut blocks, &mut list_items);
+            let cells = parse_table_cells(trimmed_start);
+            if !in_table {
+                table_headers = cells;
+                in_table = true;
+            } else {
+                table_rows.push(cells);
             }
-        };
-        state.github_connection_status = Some(message);
-        state.persist_config();
-    }
-
-    if let Some(status) = &state.github_connection_status {
-        ui.add_space(8.0);
-        ui.colored_label(ui.visuals().weak_text_color(), status);
-    }
-}
+            continue;
+        }
 
-fn draw_system_cache(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.horizontal(|ui| {
-        ui.label("Cache directory");
-        if ui
-            .text_edit_singleline(&mut state.cache_directory)
-            .changed()
-        {
-            state.persist_config();
+        if in_table && is_table_separator {
+            continue;
         }
-    });
 
-    if ui
-        .add(
-            egui::Slider::new(&mut state.cache_size_limit_gb, 1.0..=256.0)
-                .text("Cache size limit (GB)"),
-        )
-        .changed()
-    {
-        state.persist_config();
-    }
+        if trimmed_start.starts_with("```") {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            flush_list(&mut blocks, &mut list_items);
+            code_language = trimmed_start[3..].trim().to_string();
+            in_code_block = true;
+            code_lines.clear();
+            continue;
+        }
 
-    if ui
-        .checkbox(&mut state.enable_auto_cleanup, "Enable automatic cleanup")
-        .changed()
-    {
-        state.persist_config();
-    }
+        if trimmed.is_empty() {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            flush_list(&mut blocks, &mut list_items);
+            flush_table_block(
+                &mut blocks,
+                &mut table_headers,
+                &mut table_rows,
+                &mut in_table,
+            );
+            continue;
+        }
 
-    if ui
-        .add(
-            egui::Slider::new(&mut state.cache_cleanup_interval_hours, 1..=168)
-                .text("Cleanup interval (hours)"),
-        )
-        .changed()
-    {
-        state.persist_config();
-    }
+        if trimmed_start.starts_with('#') {
+            let hash_count = trimmed_start
+                .chars()
+                .take_while(|ch| *ch == '#')
+                .count()
+                .max(1);
+            let content = trimmed_start[hash_count..].trim();
+            flush_paragraph(&mut blocks, &mut paragraph);
+            flush_list(&mut blocks, &mut list_items);
+            flush_table_block(
+                &mut blocks,
+                &mut table_headers,
+                &mut table_rows,
+                &mut in_table,
+            );
+            blocks.push(MarkdownBlock::Heading {
+                level: hash_count.min(6),
+                text: content.to_string(),
+            });
+            continue;
+        }
 
-    if ui.button("Run cleanup now").clicked() {
-        state.last_cache_cleanup = Some(format!(
-            "Manual cleanup triggered. Next automatic run in {} hours.",
-            state.cache_cleanup_interval_hours
-        ));
-        state.persist_config();
-    }
+        if let Some(stripped) = trimmed_start.strip_prefix("- ") {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            list_items.push(stripped.trim().to_string());
+            continue;
+        }
 
-    if let Some(status) = &state.last_cache_cleanup {
-        ui.add_space(8.0);
-        ui.colored_label(ui.visuals().weak_text_color(), status);
-    }
-}
+        if let Some(stripped) = trimmed_start.strip_prefix("* ") {
+            flush_paragraph(&mut blocks, &mut paragraph);
+            list_items.push(stripped.trim().to_string());
+            continue;
+        }
 
-fn draw_system_resources(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.label("Memory limit for cache");
-    if ui
-        .add(egui::Slider::new(&mut state.resource_memory_limit_gb, 1.0..=512.0).suffix(" GB"))
-        .changed()
-    {
-        state.persist_config();
+        flush_table_block(
+            &mut blocks,
+            &mut table_headers,
+            &mut table_rows,
+            &mut in_table,
+        );
+        paragraph.push(trimmed.to_string());
     }
 
-    ui.label("Disk limit for cache");
-    if ui
-        .add(egui::Slider::new(&mut state.resource_disk_limit_gb, 8.0..=4096.0).suffix(" GB"))
-        .changed()
-    {
-        state.persist_config();
+    if in_code_block {
+        let code = code_lines.join("\n");
+        blocks.push(MarkdownBlock::CodeBlock {
+            language: code_language,
+            code,
+        });
     }
 
-    ui.colored_label(
-        ui.visuals().weak_text_color(),
-        format!(
-            "Current limits: {:.1} GB memory · {:.1} GB disk",
-            state.resource_memory_limit_gb, state.resource_disk_limit_gb
-        ),
+    flush_paragraph(&mut blocks, &mut paragraph);
+    flush_list(&mut blocks, &mut list_items);
+    flush_table_block(
+        &mut blocks,
+        &mut table_headers,
+        &mut table_rows,
+        &mut in_table,
     );
+
+    blocks
 }
 
-fn draw_custom_commands_section(ui: &mut egui::Ui, state: &mut AppState, tab_index: usize) {
-    match tab_index {
-        0 => draw_custom_commands_configuration(ui, state),
-        1 => draw_custom_commands_documentation(ui, state),
-        2 => draw_custom_commands_activity(ui, state),
-        _ => draw_custom_commands_configuration(ui, state),
+fn flush_table_block(
+    blocks: &mut Vec<MarkdownBlock>,
+    headers: &mut Vec<String>,
+    rows: &mut Vec<Vec<String>>,
+    in_table: &mut bool,
+) {
+    if *in_table {
+        blocks.push(MarkdownBlock::Table {
+            headers: headers.clone(),
+            rows: rows.clone(),
+        });
+        headers.clear();
+        rows.clear();
+        *in_table = false;
     }
 }
 
-fn draw_custom_commands_configuration(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.heading("Command palette");
-    ui.label("Link slash commands with built-in automation functions.");
+fn parse_table_cells(line: &str) -> Vec<String> {
+    line.trim()
+        .trim_matches('|')
+        .split('|')
+        .map(|cell| cell.trim().to_string())
+        .collect()
+}
 
-    let mut remove_index = None;
-    for (idx, command) in state.chat.custom_commands.iter().enumerate() {
-        ui.group(|ui| {
-            ui.horizontal(|ui| {
-                ui.strong(&command.trigger);
-                ui.label(format!("→ {}", command.action.label()));
-                if ui.button(egui::RichText::new("Remove").small()).clicked() {
-                    remove_index = Some(idx);
-                }
-            });
-            ui.colored_label(ui.visuals().weak_text_color(), command.action.description());
-        });
-        ui.add_space(4.0);
+fn extract_diff_block(blocks: &[MarkdownBlock]) -> Option<String> {
+    for block in blocks {
+        if let MarkdownBlock::CodeBlock { language, code } = block {
+            if language.trim().eq_ignore_ascii_case("diff") {
+                return Some(code.clone());
+            }
+        }
     }
+    None
+}
 
-    if let Some(idx) = remove_index {
-        if let Some(command) = state.chat.custom_commands.get(idx).cloned() {
-            state.chat.custom_commands.remove(idx);
-            state.chat.command_feedback = Some(format!(
-                "Removed custom command '{}' ({})",
-                command.trigger,
-                command.action.label()
-            ));
-            state.persist_config();
+fn extract_preview_block(blocks: &[MarkdownBlock]) -> Option<(String, String)> {
+    for block in blocks {
+        if let MarkdownBlock::CodeBlock { language, code } = block {
+            if language.trim().eq_ignore_ascii_case("diff") {
+                continue;
+            }
+            if !code.trim().is_empty() {
+                return Some((language.clone(), code.clone()));
+            }
         }
     }
+    None
+}
 
-    ui.add_space(8.0);
-    ui.label("Create a new command");
-    ui.horizontal(|ui| {
-        ui.add(
-            egui::TextEdit::singleline(&mut state.chat.new_command)
-                .hint_text("Trigger (e.g. /time)"),
-        );
-
-        egui::ComboBox::from_id_source("new_custom_command_action")
-            .selected_text(state.chat.new_command_action.label())
-            .show_ui(ui, |ui| {
-                for action in state.command_registry.actions() {
-                    ui.selectable_value(
-                        &mut state.chat.new_command_action,
-                        *action,
-                        format!("{} — {}", action.label(), action.description()),
-                    );
+fn extract_summary(text: &str) -> Option<String> {
+    let mut lines = text.lines().peekable();
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim();
+        let lower = trimmed.to_lowercase();
+        if lower.starts_with("resumen")
+            || lower.contains("resumen semántico")
+            || lower.starts_with("summary")
+        {
+            let mut summary = String::new();
+            if let Some(index) = trimmed.find(':') {
+                let remainder = trimmed[index + 1..].trim();
+                if !remainder.is_empty() {
+                    summary.push_str(remainder);
                 }
-            });
-
-        if ui.button("Add").clicked() {
-            let trimmed = state.chat.new_command.trim();
-            if trimmed.is_empty() {
-                state.chat.command_feedback = Some("Command cannot be empty.".to_string());
-            } else {
-                let normalized = if trimmed.starts_with('/') {
-                    trimmed.to_string()
-                } else {
-                    format!("/{}", trimmed)
-                };
+            }
 
-                if state
-                    .chat
-                    .custom_commands
-                    .iter()
-                    .any(|cmd| cmd.trigger == normalized)
+            while let Some(peek) = lines.peek() {
+                if peek.trim().is_empty()
+                    || peek.trim_start().starts_with("```")
+                    || peek.trim_start().starts_with('#')
                 {
-                    state.chat.command_feedback =
-                        Some(format!("Command '{}' already exists.", normalized));
-                } else {
-                    let action = state.chat.new_command_action;
-                    state
-                        .chat
-                        .custom_commands
-                        .push(crate::state::CustomCommand {
-                            trigger: normalized.clone(),
-                            action,
-                        });
-                    state.chat.command_feedback = Some(format!(
-                        "Added '{}' linked to {}.",
-                        normalized,
-                        action.label()
-                    ));
-                    state.chat.new_command.clear();
-                    state.persist_config();
+                    break;
+                }
+                let next_line = lines.next().unwrap();
+                if !summary.is_empty() {
+                    summary.push(' ');
+                }
+                summary.push_str(next_line.trim());
+                if summary.len() > 320 {
+                    break;
                 }
             }
-        }
-    });
 
-    if let Some(feedback) = &state.chat.command_feedback {
-        ui.add_space(6.0);
-        ui.colored_label(ui.visuals().weak_text_color(), feedback);
-    }
+            if summary.is_empty() {
+                continue;
+            }
 
-    ui.add_space(8.0);
-    if ui
-        .button("Available functions")
-        .on_hover_text("Consulta documentación detallada y ejemplos")
-        .clicked()
-    {
-        state.chat.show_functions_modal = true;
+            return Some(summary);
+        }
     }
+
+    None
 }
 
-fn draw_custom_commands_documentation(ui: &mut egui::Ui, state: &AppState) {
-    ui.heading("Documentación de comandos personalizados");
-    ui.label(
-        RichText::new("Cada comando ejecuta una acción predefinida con parámetros opcionales.")
-            .color(theme::color_text_weak()),
-    );
+fn parse_inline_segments(text: &str) -> Vec<InlineSegment> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut bold = false;
+    let mut italic = false;
+    let mut code = false;
+    let mut index = 0;
+    let bytes = text.as_bytes();
 
-    ui.add_space(12.0);
-    for action in state.command_registry.actions() {
-        let action = *action;
-        let doc = action.documentation();
-        egui::Frame::none()
-            .fill(Color32::from_rgb(34, 36, 42))
-            .stroke(theme::subtle_border(&state.theme))
-            .rounding(egui::Rounding::same(12.0))
-            .inner_margin(egui::Margin::symmetric(14.0, 12.0))
-            .show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label(
-                        RichText::new(doc.signature)
-                            .monospace()
-                            .color(theme::color_text_primary())
-                            .strong(),
-                    );
-                    ui.add_space(ui.available_width());
-                    ui.label(
-                        RichText::new(action.label())
-                            .color(theme::color_text_weak())
-                            .monospace()
-                            .size(11.0),
-                    );
+    while index < bytes.len() {
+        if !code && text[index..].starts_with("**") {
+            if !current.is_empty() {
+                segments.push(InlineSegment {
+                    text: current.clone(),
+                    bold,
+                    italic,
+                    code,
                 });
-                ui.label(
-                    RichText::new(doc.summary)
-                        .color(theme::color_text_weak())
-                        .size(12.0),
-                );
+                current.clear();
+            }
+            bold = !bold;
+            index += 2;
+            continue;
+        }
 
-                if !doc.parameters.is_empty() {
-                    ui.add_space(6.0);
-                    ui.label(
-                        RichText::new("Parámetros disponibles")
-                            .color(theme::color_text_primary())
-                            .size(11.0)
-                            .strong(),
-                    );
-                    for parameter in doc.parameters {
-                        ui.label(
-                            RichText::new(format!("• {parameter}"))
-                                .color(theme::color_text_weak())
-                                .size(11.0),
-                        );
-                    }
-                }
+        if !code && text[index..].starts_with('*') {
+            if !current.is_empty() {
+                segments.push(InlineSegment {
+                    text: current.clone(),
+                    bold,
+                    italic,
+                    code,
+                });
+                current.clear();
+            }
+            italic = !italic;
+            index += 1;
+            continue;
+        }
 
-                if !doc.examples.is_empty() {
-                    ui.add_space(6.0);
-                    ui.label(
-                        RichText::new("Ejemplos")
-                            .color(theme::color_text_primary())
-                            .size(11.0)
-                            .strong(),
-                    );
-                    for example in doc.examples {
-                        ui.label(
-                            RichText::new(*example)
-                                .color(theme::color_text_weak())
-                                .monospace()
-                                .size(11.0),
-                        );
-                    }
-                }
-            });
-        ui.add_space(8.0);
-    }
+        if text[index..].starts_with('`') {
+            if !current.is_empty() {
+                segments.push(InlineSegment {
+                    text: current.clone(),
+                    bold,
+                    italic,
+                    code,
+                });
+                current.clear();
+            }
+            code = !code;
+            index += 1;
+            continue;
+        }
 
-    if state.chat.custom_commands.is_empty() {
-        ui.colored_label(
-            theme::color_text_weak(),
-            "Aún no hay comandos personalizados definidos.",
-        );
+        let ch = text[index..].chars().next().unwrap();
+        current.push(ch);
+        index += ch.len_utf8();
     }
-}
 
-fn draw_custom_commands_activity(ui: &mut egui::Ui, state: &AppState) {
-    ui.heading("Actividad de comandos");
-    if let Some(feedback) = &state.chat.command_feedback {
-        ui.label(
-            RichText::new(format!("Última acción: {feedback}"))
-                .color(theme::color_text_primary())
-                .size(12.0),
-        );
-    } else {
-        ui.label(
-            RichText::new("No hay actividad reciente registrada.")
-                .color(theme::color_text_weak())
-                .size(12.0),
-        );
-    }
-
-    ui.add_space(10.0);
-    if state.chat.custom_commands.is_empty() {
-        ui.colored_label(
-            theme::color_text_weak(),
-            "Agrega comandos personalizados para comenzar a registrar actividad.",
-        );
-        return;
+    if !current.is_empty() {
+        segments.push(InlineSegment {
+            text: current,
+            bold,
+            italic,
+            code,
+        });
     }
 
-    ui.label(
-        RichText::new("Comandos configurados actualmente")
-            .color(theme::color_text_primary())
-            .strong(),
-    );
-
-    for command in &state.chat.custom_commands {
-        egui::Frame::none()
-            .fill(Color32::from_rgb(34, 36, 42))
-            .stroke(theme::subtle_border(&state.theme))
-            .rounding(egui::Rounding::same(10.0))
-            .inner_margin(egui::Margin::symmetric(12.0, 10.0))
-            .show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    ui.label(
-                        RichText::new(&command.trigger)
-                            .color(theme::color_text_primary())
-                            .strong()
-                            .size(13.0)
-                            .monospace(),
-                    );
-                    ui.add_space(ui.available_width());
-                    ui.label(
-                        RichText::new(command.action.label())
-                            .color(theme::color_text_weak())
-                            .monospace()
-                            .size(11.0),
-                    );
-                });
-                ui.label(
-                    RichText::new(command.action.description())
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                );
-            });
-        ui.add_space(6.0);
-    }
+    segments
 }
 
-fn draw_customization_appearance(ui: &mut egui::Ui, state: &mut AppState) {
-    let tokens = state.theme.clone();
-    let info_frame = egui::Frame::none()
-        .fill(tokens.palette.panel_background)
-        .stroke(theme::subtle_border(&tokens))
-        .rounding(tokens.rounding.widget)
-        .inner_margin(egui::Margin {
-            left: 20.0,
-            right: 20.0,
-            top: 16.0,
-            bottom: 16.0,
-        });
-
-    info_frame.show(ui, |ui| {
-        ui.vertical(|ui| {
-            ui.label(
-                RichText::new("Tema de la interfaz")
-                    .color(tokens.palette.text_primary)
-                    .strong()
-                    .size(tokens.typography.title.size),
-            );
-            ui.add_space(tokens.spacing.item_spacing.y);
-            ui.label(
-                RichText::new(
-                    "Alterna entre presets claro y oscuro inspirados en los esquemas de VSCode.",
-                )
-                .color(tokens.palette.text_weak)
-                .size(tokens.typography.body.size),
-            );
-        });
-    });
-
-    ui.add_space(tokens.spacing.item_spacing.y * 2.0);
+#[derive(Clone)]
+struct InlineSegment {
+    text: String,
+    bold: bool,
+    italic: bool,
+    code: bool,
+}
 
-    ui.horizontal(|ui| {
-        ui.spacing_mut().item_spacing.x = tokens.spacing.item_spacing.x;
-        let options = [
-            (
-                ThemePreset::Dark,
-                "Tema oscuro",
-                "Contraste alto con paneles profundos y resaltes eléctricos.",
-            ),
-            (
-                ThemePreset::Light,
-                "Tema claro",
-                "Fondo luminoso con bordes suaves para entornos bien iluminados.",
-            ),
-        ];
+#[derive(Debug)]
+enum MarkdownBlock {
+    Heading {
+        level: usize,
+        text: String,
+    },
+    Paragraph(String),
+    BulletList(Vec<String>),
+    CodeBlock {
+        language: String,
+        code: String,
+    },
+    Table {
+        headers: Vec<String>,
+        rows: Vec<Vec<String>>,
+    },
+}
 
-        for (preset, title, description) in options {
-            let selected = state.config.theme == preset;
-            let response = theme_option_card(ui, &tokens, selected, title, description);
-            if response.clicked() {
-                state.set_theme_preset(preset);
+fn apply_pending_actions(state: &mut AppState, actions: Vec<PendingChatAction>) {
+    for action in actions {
+        match action {
+            PendingChatAction::Mention(tag) => insert_mention(state, &tag),
+            PendingChatAction::Quote(text) => {
+                if !state.chat.input.ends_with('\n') && !state.chat.input.is_empty() {
+                    state.chat.input.push('\n');
+                }
+                state.chat.input.push_str(&text);
+            }
+            PendingChatAction::Reuse(text) => state.chat.input = text,
+            PendingChatAction::Regenerate { index, provider } => {
+                let status = match state.regenerate_message(index, provider) {
+                    Ok(ProviderCallDispatch::Pending(ticket)) => {
+                        format!("Regenerando la respuesta con {}.", ticket.provider_name)
+                    }
+                    Ok(ProviderCallDispatch::Deferred { provider_name, .. }) => format!(
+                        "No se pudo regenerar: límite diario alcanzado para {}.",
+                        provider_name
+                    ),
+                    Ok(ProviderCallDispatch::MissingCredentials { provider_name, .. }) => {
+                        format!("Configura la API key de {} para regenerar.", provider_name)
+                    }
+                    Ok(ProviderCallDispatch::Queued {
+                        provider_name,
+                        position,
+                        ..
+                    }) => format!(
+                        "Sin conexión: la regeneración con {} quedó en cola (#{}).",
+                        provider_name, position
+                    ),
+                    Ok(ProviderCallDispatch::Offline { provider_name, .. }) => {
+                        format!("Sin conexión: no se puede regenerar con {}.", provider_name)
+                    }
+                    Ok(ProviderCallDispatch::BudgetExhausted {
+                        provider_name,
+                        period,
+                        ..
+                    }) => format!(
+                        "No se pudo regenerar: presupuesto {} agotado para {}.",
+                        period.label(),
+                        provider_name
+                    ),
+                    Ok(ProviderCallDispatch::LocalOnly { projects, .. }) => format!(
+                        "No se pudo regenerar: el contexto incluye {} (solo local).",
+                        projects.join(", ")
+                    ),
+                    Err(err) => err,
+                };
+                state.chat_routing.update_status(Some(status));
+            }
+            PendingChatAction::Reply(index) => {
+                state.begin_quote_reply(index);
+            }
+            PendingChatAction::TogglePin(index) => state.toggle_message_pin(index),
+            PendingChatAction::Fork(index) => {
+                if let Err(err) = state.fork_conversation(index) {
+                    state.threads.status = Some(err);
+                }
+            }
+            PendingChatAction::Focus(index) => state.focus_chat_message(Some(index)),
+            PendingChatAction::OpenPreference(panel) => {
+                state.activate_navigation_target(NavigationTarget::preference(panel));
+            }
+            PendingChatAction::OpenCodeAnchor(anchor) => {
+                if let Err(err) = state.open_code_anchor(&anchor) {
+                    state.chat_routing.update_status(Some(err));
+                }
+            }
+            PendingChatAction::ShowPatch { anchor, diff } => {
+                match state.open_code_anchor(&anchor) {
+                    Ok(()) => {
+                        state.code_viewer.patch =
+                            Some(code_viewer::PatchSuggestion { anchor, diff });
+                    }
+                    Err(err) => state.chat_routing.update_status(Some(err)),
+                }
+            }
+            PendingChatAction::TextAction { index, action } => {
+                let status = match state.apply_text_action(index, action) {
+                    Ok(label) => tf("Acción aplicada: {0}.", &[&label]),
+                    Err(err) => err,
+                };
+                state.chat_routing.update_status(Some(status));
+            }
+            PendingChatAction::FollowUp(text) => {
+                state.chat.input = text;
+                if state.config.follow_ups.send_immediately {
+                    submit_chat_message(state);
+                }
+            }
+            PendingChatAction::EditResend(index) => {
+                if state.begin_message_edit(index) {
+                    state.chat_routing.update_status(Some(
+                        "Edita el mensaje y envíalo: la conversación se recortará desde ese punto."
+                            .to_string(),
+                    ));
+                }
             }
         }
-    });
+    }
 }
 
-fn theme_option_card(
-    ui: &mut egui::Ui,
-    tokens: &ThemeTokens,
-    selected: bool,
-    title: &str,
-    description: &str,
-) -> egui::Response {
-    let desired = egui::vec2(240.0, 132.0);
-    let (rect, response) = ui.allocate_exact_size(desired, egui::Sense::click());
-
-    let (fill, border_color) = if selected {
-        (tokens.states.focus.background, tokens.states.focus.border)
-    } else if response.hovered() {
-        (tokens.states.hover.background, tokens.states.hover.border)
-    } else {
-        (tokens.palette.secondary_background, tokens.palette.border)
-    };
-
-    let painter = ui.painter_at(rect);
-    painter.rect(
-        rect,
-        tokens.rounding.widget,
-        fill,
-        egui::Stroke::new(1.0, border_color),
-    );
-
-    let mut content = ui.child_ui(
-        rect.shrink2(egui::vec2(18.0, 16.0)),
+fn draw_chat_input(ui: &mut egui::Ui, state: &mut AppState) {
+    let max_width = ui.available_width().min(580.0);
+    ui.allocate_ui_with_layout(
+        egui::vec2(max_width, 0.0),
         egui::Layout::top_down(egui::Align::LEFT),
-    );
+        |ui| {
+            ui.set_width(max_width);
+            egui::Frame::none()
+                .fill(Color32::from_rgb(24, 26, 32))
+                .stroke(theme::subtle_border(&state.theme))
+                .rounding(egui::Rounding::same(16.0))
+                .inner_margin(egui::Margin::symmetric(18.0, 14.0))
+                .show(ui, |ui| {
+                    let full_width = ui.available_width().min(560.0);
+                    ui.set_width(full_width);
+                    ui.vertical(|ui| {
+                        draw_model_routing_bar(ui, state);
+                        ui.add_space(6.0);
+                        ui.horizontal(|ui| {
+                            ui.spacing_mut().item_spacing.x = 8.0;
+                            if let Some(tag) = state.jarvis_mention_tag() {
+                                if quick_chip(ui, &tag).clicked() {
+                                    insert_mention(state, &tag);
+                                }
+                            }
+                            let model_tags: Vec<String> = state
+                                .config
+                                .jarvis
+                                .model_aliases
+                                .iter()
+                                .map(|entry| format!("@{}", entry.alias))
+                                .collect();
+                            for tag in model_tags {
+                                if quick_chip(ui, &tag).clicked() {
+                                    insert_mention(state, &tag);
+                                }
+                            }
 
-    content.label(
-        RichText::new(title)
-            .color(if selected {
-                tokens.states.focus.foreground
-            } else {
-                tokens.palette.text_primary
-            })
-            .size(tokens.typography.body.size)
-            .strong(),
-    );
-    content.add_space(tokens.spacing.item_spacing.y * 0.5);
-    content.label(
-        RichText::new(description)
-            .color(tokens.palette.text_weak)
-            .size(tokens.typography.body_small.size),
-    );
+                            for (mention, label) in QUICK_MENTIONS {
+                                if quick_chip(ui, label).clicked() {
+                                    insert_mention(state, mention);
+                                }
+                            }
 
-    response
-}
+                            ui.add_space(ui.available_width());
 
-fn draw_customization_memory(ui: &mut egui::Ui, state: &mut AppState) {
-    if ui
-        .checkbox(
-            &mut state.enable_memory_tracking,
-            "Enable contextual memory",
-        )
-        .changed()
-    {
-        state.persist_config();
-    }
-
-    if ui
-        .add(egui::Slider::new(&mut state.memory_retention_days, 1..=365).text("Retention (days)"))
-        .changed()
-    {
-        state.persist_config();
-    }
+                            if quick_chip_with_icon(ui, ICON_CODE, "Insertar bloque de código").clicked() {
+                                insert_code_template(state);
+                            }
+                        });
 
-    ui.colored_label(
-        ui.visuals().weak_text_color(),
-        format!(
-            "Memories older than {} days will be archived.",
-            state.memory_retention_days
-        ),
-    );
+                        ui.add_space(4.0);
+                        ui.horizontal_wrapped(|ui| {
+                            ui.spacing_mut().item_spacing.x = 8.0;
+                            for (command, label) in QUICK_COMMANDS {
+                                if quick_chip(ui, label).clicked() {
+                                    insert_quick_token(state, command);
+                                }
+                            }
+                            if state.config.response_cache.enabled {
+                                let label = if state.chat.bypass_response_cache {
+                                    format!("✓ {}", t("Sin caché"))
+                                } else {
+                                    t("Sin caché").to_string()
+                                };
+                                if quick_chip(ui, &label)
+                                    .on_hover_text(t("El próximo mensaje se enviará al proveedor aunque haya una respuesta en caché"))
+                                    .clicked()
+                                {
+                                    state.chat.bypass_response_cache =
+                                        !state.chat.bypass_response_cache;
+                                }
+                            }
+                            let label = if state.chat.structured.enabled {
+                                format!("✓ {}", t("JSON"))
+                            } else {
+                                t("JSON").to_string()
+                            };
+                            if quick_chip(ui, &label)
+                                .on_hover_text(t("Pide respuestas JSON que cumplan un esquema"))
+                                .clicked()
+                            {
+                                if state.chat.structured.schema_text.trim().is_empty() {
+                                    state.chat.structured.schema_text =
+                                        structured::EXAMPLE_SCHEMA.to_string();
+                                }
+                                state.chat.structured.show_editor = true;
+                            }
+                            if !state.composer.history.is_empty()
+                                && quick_chip(ui, t("Historial"))
+                                    .on_hover_text(t("Busca entre los mensajes enviados"))
+                                    .clicked()
+                            {
+                                state.composer.show_history = true;
+                            }
+                        });
 
-    ui.add_space(10.0);
-    let memory_cards = state.resources.personalization_resources.memories.clone();
-    draw_personalization_cards(
-        ui,
-        state,
-        &memory_cards,
-        "Aún no hay memorias configuradas.",
-    );
-}
+                        ui.add_space(12.0);
 
-fn draw_customization_profiles(ui: &mut egui::Ui, state: &mut AppState) {
-    let mut selected_profile = state.selected_profile;
-    egui::ComboBox::from_label("Active profile")
-        .selected_text(
-            state
-                .selected_profile
-                .and_then(|idx| state.profiles.get(idx))
-                .cloned()
-                .unwrap_or_else(|| "Choose a profile".to_string()),
-        )
-        .show_ui(ui, |ui| {
-            for (idx, profile) in state.profiles.iter().enumerate() {
-                ui.selectable_value(&mut selected_profile, Some(idx), profile);
-            }
-        });
+                        if let Some(index) = state.chat.editing_message {
+                            draw_message_edit_banner(ui, state, index);
+                            ui.add_space(8.0);
+                        }
 
-    if selected_profile != state.selected_profile {
-        state.selected_profile = selected_profile;
-        state.persist_config();
-    }
+                        if let Some(quote) = state.chat.pending_reply.clone() {
+                            draw_reply_banner(ui, state, &quote);
+                            ui.add_space(8.0);
+                        }
 
-    ui.add_space(6.0);
-    ui.horizontal(|ui| {
-        if ui.button("Duplicate profile").clicked() {
-            if let Some(idx) = state.selected_profile {
-                let new_profile = format!("{} (copy)", state.profiles[idx]);
-                state.profiles.push(new_profile);
-                state.selected_profile = Some(state.profiles.len() - 1);
-                state.persist_config();
-                state.refresh_personalization_resources();
-            }
-        }
-        if ui.button("Delete profile").clicked() {
-            if let Some(idx) = state.selected_profile {
-                if state.profiles.len() > 1 {
-                    state.profiles.remove(idx);
-                    if state.profiles.is_empty() {
-                        state.selected_profile = None;
-                    } else if idx >= state.profiles.len() {
-                        state.selected_profile = Some(state.profiles.len() - 1);
-                    }
-                    state.persist_config();
-                    state.refresh_personalization_resources();
-                }
-            }
-        }
-    });
+                        if let Some(key) = state.chat.model_override.clone() {
+                            draw_model_override_banner(ui, state, &key);
+                            ui.add_space(8.0);
+                        }
 
-    ui.colored_label(
-        ui.visuals().weak_text_color(),
-        "Profiles let you quickly change between workspace presets.",
-    );
+                        if let Some(total) = state
+                            .chat
+                            .cost_confirmation
+                            .as_ref()
+                            .filter(|confirmation| confirmation.input == state.chat.input.trim())
+                            .map(|confirmation| confirmation.total)
+                        {
+                            draw_cost_confirmation_banner(ui, state, total);
+                            ui.add_space(8.0);
+                        }
 
-    ui.add_space(10.0);
-    let profile_cards = state.resources.personalization_resources.profiles.clone();
-    draw_personalization_cards(
-        ui,
-        state,
-        &profile_cards,
-        "Crea tu primer perfil para empezar a personalizar respuestas.",
-    );
-}
+                        if let Some(projects) = state
+                            .chat
+                            .local_only_confirmation
+                            .as_ref()
+                            .filter(|confirmation| confirmation.input == state.chat.input.trim())
+                            .map(|confirmation| confirmation.projects.clone())
+                        {
+                            draw_local_only_banner(ui, state, &projects);
+                            ui.add_space(8.0);
+                        }
 
-fn draw_customization_projects(ui: &mut egui::Ui, state: &mut AppState) {
-    let mut selected_project = state.selected_project;
-    egui::ComboBox::from_label("Active project")
-        .selected_text(
-            state
-                .selected_project
-                .and_then(|idx| state.projects.get(idx))
-                .cloned()
-                .unwrap_or_else(|| "Choose a project".to_string()),
-        )
-        .show_ui(ui, |ui| {
-            for (idx, project) in state.projects.iter().enumerate() {
-                ui.selectable_value(&mut selected_project, Some(idx), project);
-            }
-        });
+                        if !state.attachments.items.is_empty()
+                            || !state.web_search.attached.is_empty()
+                        {
+                            draw_composer_attachments(ui, state);
+                            ui.add_space(8.0);
+                        }
 
-    if selected_project != state.selected_project {
-        state.selected_project = selected_project;
-        state.persist_config();
-    }
+                        draw_composer_redaction_notice(ui, state);
 
-    ui.add_space(6.0);
-    if ui.button("Create placeholder project").clicked() {
-        let new_project = format!("New Project {}", state.projects.len() + 1);
-        state.projects.push(new_project);
-        state.selected_project = Some(state.projects.len() - 1);
-        state.persist_config();
-        state.refresh_personalization_resources();
-    }
+                        let mut should_send = false;
 
-    ui.colored_label(
-        ui.visuals().weak_text_color(),
-        "Projects determine what repositories and documents are prioritised.",
-    );
+                        let text_height = 82.0;
+                        let enter_sends = state.config.keybindings.enter_sends;
+                        let enter_pressed = enter_sends
+                            && ui.input(|input| {
+                                input.key_pressed(egui::Key::Enter) && !input.modifiers.shift
+                            });
+                        let composer_hint = if enter_sends {
+                            t("Escribe tu mensaje o comando. Usa Shift+Enter para saltos de línea.")
+                                .to_string()
+                        } else {
+                            tf(
+                                "Escribe tu mensaje o comando. Envíalo con {0}.",
+                                &[&keybindings::binding(
+                                    &state.config.keybindings,
+                                    ShortcutAction::SendMessage,
+                                )
+                                .to_string()],
+                            )
+                        };
+                        let history_step = ui.input(|input| {
+                            if input.modifiers.any() {
+                                0
+                            } else if input.key_pressed(egui::Key::ArrowUp) {
+                                -1
+                            } else if input.key_pressed(egui::Key::ArrowDown) {
+                                1
+                            } else {
+                                0
+                            }
+                        });
 
-    ui.add_space(10.0);
-    let context_cards = state.resources.personalization_resources.contexts.clone();
-    draw_personalization_cards(
-        ui,
-        state,
-        &context_cards,
-        "Sin proyectos ni repos conectados. Sincroniza GitHub o agrega proyectos prioritarios.",
-    );
-}
+                        let text_response = ui
+                            .allocate_ui_with_layout(
+                                egui::vec2(ui.available_width(), text_height),
+                                egui::Layout::top_down(egui::Align::LEFT),
+                                |ui| {
+                                    let text_edit = egui::TextEdit::multiline(
+                                        &mut state.chat.input,
+                                    )
+                                    .desired_rows(3)
+                                    .hint_text(composer_hint)
+                                    .lock_focus(true)
+                                    .desired_width(f32::INFINITY)
+                                    .frame(false);
 
-fn draw_personalization_cards(
-    ui: &mut egui::Ui,
-    state: &mut AppState,
-    cards: &[KnowledgeResourceCard],
-    empty_message: &str,
-) {
-    if cards.is_empty() {
-        ui.colored_label(theme::color_text_weak(), empty_message);
-        return;
-    }
+                                    let text_frame = egui::Frame::none()
+                                        .fill(Color32::from_rgb(30, 32, 38))
+                                        .stroke(theme::subtle_border(&state.theme))
+                                        .rounding(egui::Rounding::same(12.0))
+                                        .inner_margin(egui::Margin::symmetric(14.0, 10.0));
 
-    for card in cards {
-        egui::Frame::none()
-            .fill(Color32::from_rgb(34, 38, 44))
-            .stroke(theme::subtle_border(&state.theme))
-            .rounding(egui::Rounding::same(12.0))
-            .inner_margin(egui::Margin::symmetric(14.0, 12.0))
-            .show(ui, |ui| {
-                ui.vertical(|ui| {
-                    ui.horizontal(|ui| {
-                        ui.label(
-                            RichText::new(&card.title)
-                                .color(theme::color_text_primary())
-                                .strong()
-                                .size(14.0),
-                        );
-                        ui.add_space(ui.available_width());
-                        ui.label(
-                            RichText::new(&card.resource_type)
-                                .color(theme::color_primary())
-                                .size(11.0),
-                        );
-                    });
-                    ui.label(
-                        RichText::new(&card.subtitle)
-                            .color(theme::color_text_weak())
-                            .size(11.0),
-                    );
-                    ui.label(
-                        RichText::new(format!("Última sincronización: {}", card.last_synced))
-                            .color(theme::color_text_weak())
-                            .size(10.0),
-                    );
+                                    text_frame
+                                        .show(ui, |ui| {
+                                            ui.set_height(text_height);
+                                            ui.spacing_mut().item_spacing.x = 12.0;
 
-                    if !card.tags.is_empty() {
-                        ui.add_space(6.0);
-                        ui.horizontal_wrapped(|ui| {
-                            ui.spacing_mut().item_spacing.x = 6.0;
-                            for tag in &card.tags {
-                                selectable_chip(ui, tag, false);
-                            }
-                        });
-                    }
+                                            ui.horizontal(|ui| {
+                                                let button_width = 34.0;
+                                                let available = ui.available_width();
+                                                let text_size = [
+                                                    (available - button_width).max(120.0),
+                                                    text_height - 20.0,
+                                                ];
+                                                let text_response =
+                                                    ui.add_sized(text_size, text_edit);
 
-                    ui.add_space(8.0);
-                    ui.horizontal(|ui| {
-                        if let Some(link) = &card.link {
-                            if ui
-                                .add(
-                                    egui::Button::new(
-                                        RichText::new(format!("{} Copiar", ICON_LINK))
-                                            .color(Color32::from_rgb(240, 240, 240))
-                                            .size(11.0),
-                                    )
-                                    .min_size(egui::vec2(80.0, 26.0))
-                                    .fill(Color32::from_rgb(44, 46, 54))
-                                    .rounding(egui::Rounding::same(8.0)),
-                                )
-                                .clicked()
-                            {
-                                ui.output_mut(|out| out.copied_text = link.clone());
-                                state.resources.personalization_feedback =
-                                    Some(format!("Enlace copiado: {}", card.title));
-                            }
-                        }
+                                                let (button_rect, send_response) = ui
+                                                    .allocate_exact_size(
+                                                        egui::vec2(
+                                                            button_width,
+                                                            text_response
+                                                                .rect
+                                                                .height()
+                                                                .max(28.0),
+                                                        ),
+                                                        egui::Sense::click(),
+                                                    );
+                                                let send_response = send_response
+                                                    .on_hover_text("Enviar mensaje")
+                                                    .on_hover_cursor(egui::CursorIcon::PointingHand);
+                                                let painter = ui.painter_at(button_rect);
+                                                painter.text(
+                                                    button_rect.center(),
+                                                    egui::Align2::CENTER_CENTER,
+                                                    ICON_SEND,
+                                                    theme::icon_font(20.0),
+                                                    Color32::from_rgb(240, 240, 240),
+                                                );
 
-                        if ui
-                            .add(
-                                egui::Button::new(
-                                    RichText::new(format!("{} Sincronizar", ICON_LIGHTNING))
-                                        .color(Color32::from_rgb(240, 240, 240))
-                                        .size(11.0),
-                                )
-                                .min_size(egui::vec2(110.0, 26.0))
-                                .fill(Color32::from_rgb(70, 80, 96))
-                                .rounding(egui::Rounding::same(8.0)),
+                                                (text_response, send_response)
+                                            })
+                                            .inner
+                                        })
+                                        .inner
+                                },
                             )
-                            .clicked()
+                            .inner;
+
+                        let (text_response, send_response) = text_response;
+
+                        if text_response.has_focus() && enter_pressed {
+                            should_send = true;
+                            ui.ctx()
+                                .memory_mut(|mem| mem.request_focus(text_response.id));
+                        }
+
+                        if send_response.clicked() {
+                            should_send = true;
+                        }
+
+                        // Las flechas recorren el historial cuando el editor está vacío o
+                        // muestra un mensaje ya recuperado.
+                        if text_response.has_focus()
+                            && history_step != 0
+                            && (state.chat.input.is_empty()
+                                || state.composer.is_browsing(&state.chat.input))
                         {
-                            state.resources.personalization_feedback = Some(format!(
-                                "{} marcado para sincronización contextual.",
-                                card.title
-                            ));
+                            let recalled = if history_step < 0 {
+                                state.composer.recall_previous(&mut state.chat.input)
+                            } else {
+                                state.composer.recall_next(&mut state.chat.input)
+                            };
+                            if recalled {
+                                move_cursor_to_end(
+                                    ui.ctx(),
+                                    text_response.id,
+                                    state.chat.input.chars().count(),
+                                );
+                            }
+                        }
+
+                        if should_send {
+                            submit_chat_message(state);
                         }
+
+                        ui.add_space(6.0);
+                        draw_context_meter(ui, state);
                     });
                 });
-            });
+        },
+    );
+}
 
-        ui.add_space(8.0);
+fn move_cursor_to_end(ctx: &egui::Context, id: egui::Id, chars: usize) {
+    if let Some(mut text_state) = egui::widgets::text_edit::TextEditState::load(ctx, id) {
+        text_state
+            .cursor
+            .set_char_range(Some(egui::text::CCursorRange::one(
+                egui::text::CCursor::new(chars),
+            )));
+        text_state.store(ctx, id);
     }
+}
 
-    if let Some(status) = &state.resources.personalization_feedback {
-        ui.colored_label(theme::color_text_weak(), status);
+fn draw_context_meter(ui: &mut egui::Ui, state: &AppState) {
+    let estimates = state.context_estimates(&state.chat.input);
+    let Some(tokens) = estimates.first().map(|estimate| estimate.tokens) else {
+        return;
+    };
+
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 10.0;
+        ui.label(
+            RichText::new(format!(
+                "≈ {} tokens · {}",
+                tokens,
+                state.config.context.strategy.label()
+            ))
+            .color(theme::color_text_weak())
+            .size(11.0),
+        );
+        for estimate in &estimates {
+            let cost = estimate
+                .estimated_cost
+                .map(|cost| format!("${:.4}", cost))
+                .unwrap_or_else(|| "coste n/d".to_string());
+            let color = if estimate.exceeds_context() {
+                theme::color_danger()
+            } else {
+                theme::color_text_weak()
+            };
+            ui.label(
+                RichText::new(format!("{} {}", estimate.provider.short_code(), cost))
+                    .color(color)
+                    .size(11.0),
+            )
+            .on_hover_text(format!(
+                "Modelo '{}' · contexto {}",
+                estimate.model,
+                estimate
+                    .context_limit
+                    .map(|limit| format!("{} tokens", limit))
+                    .unwrap_or_else(|| "desconocido".to_string())
+            ));
+        }
+        let send = state.send_estimates(&state.chat.input);
+        if !send.is_empty() {
+            let total: f32 = send
+                .iter()
+                .filter_map(|estimate| estimate.estimated_cost)
+                .sum();
+            let breakdown: Vec<String> = send
+                .iter()
+                .map(|estimate| {
+                    format!(
+                        "{} · {}: {} tokens + {} de respuesta{}",
+                        estimate.provider.display_name(),
+                        estimate.model,
+                        estimate.tokens,
+                        estimate.reply_tokens,
+                        estimate
+                            .estimated_cost
+                            .map(|cost| format!(" ≈ ${:.4}", cost))
+                            .unwrap_or_default()
+                    )
+                })
+                .collect();
+            let threshold = state.config.context.confirm_cost_above;
+            let color = if threshold > 0.0 && total > threshold {
+                theme::color_danger()
+            } else {
+                theme::color_primary()
+            };
+            ui.label(
+                RichText::new(tf("Envío ≈${0}", &[&format!("{:.4}", total)]))
+                    .color(color)
+                    .size(11.0),
+            )
+            .on_hover_text(breakdown.join("\n"));
+        }
+        if state.tokenizers.has_jarvis_tokenizer() && !state.chat.input.trim().is_empty() {
+            let counter = state.tokenizers.jarvis();
+            ui.label(
+                RichText::new(format!(
+                    "JV ≈{} tokens",
+                    counter.count(state.chat.input.trim())
+                ))
+                .color(theme::color_text_weak())
+                .size(11.0),
+            )
+            .on_hover_text(counter.name().to_string());
+        }
+    });
+
+    for estimate in estimates
+        .iter()
+        .filter(|estimate| estimate.exceeds_context())
+    {
+        ui.label(
+            RichText::new(format!(
+                "⚠ El prompt supera el contexto de {} ({} tokens). Reduce el historial o cambia de estrategia.",
+                estimate.model,
+                estimate.context_limit.unwrap_or_default()
+            ))
+            .color(theme::color_danger())
+            .size(11.0),
+        );
     }
 }
 
-fn draw_local_provider(ui: &mut egui::Ui, state: &mut AppState, provider: LocalModelProvider) {
-    let mut persist_changes = false;
-    let mut search_request: Option<(String, Option<String>)> = None;
-    let tokens = state.theme.clone();
+fn draw_message_edit_banner(ui: &mut egui::Ui, state: &mut AppState, index: usize) {
+    let discarded = state.chat.messages.len().saturating_sub(index);
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        ui.label(
+            RichText::new(ICON_EDIT)
+                .font(theme::icon_font(13.0))
+                .color(theme::color_primary()),
+        );
+        ui.label(
+            RichText::new(format!(
+                "Editando un mensaje anterior: al enviar se descartarán {} mensajes.",
+                discarded
+            ))
+            .color(theme::color_text_weak())
+            .size(12.0),
+        );
+        ui.add_space((ui.available_width() - 80.0).max(0.0));
+        if ui.small_button("Cancelar").clicked() {
+            state.cancel_message_edit();
+        }
+    });
+}
 
-    {
-        let provider_state = state.provider_state_mut(provider);
-        let token_label = provider.token_label();
-        ui.label(format!("{}", token_label));
-        ui.horizontal(|ui| {
-            let response = ui.text_edit_singleline(&mut provider_state.token_input);
-            if response.changed() {
-                // Do not persist immediately; wait for the save button.
+fn draw_reply_banner(ui: &mut egui::Ui, state: &mut AppState, quote: &MessageQuote) {
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        ui.label(
+            RichText::new(ICON_REPLY)
+                .font(theme::icon_font(13.0))
+                .color(theme::color_primary()),
+        );
+        ui.label(
+            RichText::new(format!(
+                "Respondiendo a {}: {}",
+                quote.sender,
+                quote.excerpt(48)
+            ))
+            .color(theme::color_text_weak())
+            .size(12.0),
+        )
+        .on_hover_text(&quote.text);
+        ui.add_space((ui.available_width() - 80.0).max(0.0));
+        if ui.small_button("Quitar cita").clicked() {
+            state.cancel_quote_reply();
+        }
+    });
+}
+
+fn draw_cost_confirmation_banner(ui: &mut egui::Ui, state: &mut AppState, total: f32) {
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        ui.label(
+            RichText::new(ICON_WARNING)
+                .font(theme::icon_font(13.0))
+                .color(theme::color_danger()),
+        );
+        ui.label(
+            RichText::new(tf(
+                "Este envío costará ≈${0} (umbral ${1}).",
+                &[
+                    &format!("{:.4}", total),
+                    &format!("{:.2}", state.config.context.confirm_cost_above),
+                ],
+            ))
+            .color(theme::color_text_weak())
+            .size(12.0),
+        );
+        ui.add_space((ui.available_width() - 180.0).max(0.0));
+        if ui.small_button(t("Enviar igualmente")).clicked() {
+            if let Some(confirmation) = state.chat.cost_confirmation.as_mut() {
+                confirmation.confirmed = true;
             }
+            submit_chat_message(state);
+        }
+        if ui.small_button(t("Cancelar")).clicked() {
+            state.chat.cost_confirmation = None;
+        }
+    });
+}
 
-            let save_label = RichText::new("Guardar").color(Color32::from_rgb(240, 240, 240));
-            let button = theme::primary_button(save_label, &tokens).min_size(egui::vec2(0.0, 28.0));
-            if ui.add_sized([110.0, 30.0], button).clicked() {
-                let trimmed = provider_state.token_input.trim();
-                if trimmed.is_empty() {
-                    provider_state.access_token = None;
-                    provider_state.token_input.clear();
-                } else {
-                    provider_state.access_token = Some(trimmed.to_string());
-                    provider_state.token_input = trimmed.to_string();
-                }
-                persist_changes = true;
+fn draw_local_only_banner(ui: &mut egui::Ui, state: &mut AppState, projects: &[String]) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        ui.label(
+            RichText::new(ICON_SHIELD)
+                .font(theme::icon_font(13.0))
+                .color(theme::color_danger()),
+        );
+        ui.label(
+            RichText::new(tf(
+                "El mensaje incluye contenido de {0}, marcado como solo local. Pregunta a Jarvis o confirma que quieres enviarlo a un proveedor remoto.",
+                &[&projects.join(", ")],
+            ))
+            .color(theme::color_text_weak())
+            .size(12.0),
+        );
+        if ui.small_button(t("Enviar a remoto igualmente")).clicked() {
+            if let Some(confirmation) = state.chat.local_only_confirmation.as_mut() {
+                confirmation.confirmed = true;
             }
-        });
+            submit_chat_message(state);
+        }
+        if ui.small_button(t("Cancelar")).clicked() {
+            state.chat.local_only_confirmation = None;
+        }
+    });
+}
 
-        if provider.requires_token() && provider_state.access_token.is_none() {
-            ui.colored_label(
-                Color32::from_rgb(255, 196, 96),
-                "Este proveedor requiere un token válido para listar modelos.",
-            );
+fn draw_model_override_banner(ui: &mut egui::Ui, state: &mut AppState, key: &RemoteModelKey) {
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        ui.label(
+            RichText::new(ICON_LIGHTNING)
+                .font(theme::icon_font(13.0))
+                .color(theme::color_primary()),
+        );
+        ui.label(
+            RichText::new(tf(
+                "El próximo mensaje a {0} usará {1}",
+                &[key.provider.display_name(), &key.id],
+            ))
+            .color(theme::color_text_weak())
+            .size(12.0),
+        );
+        ui.add_space((ui.available_width() - 120.0).max(0.0));
+        if ui.small_button(t("Usar modelo por defecto")).clicked() {
+            state.chat.model_override = None;
         }
+    });
+}
 
-        ui.add_space(6.0);
-        egui::Frame::none()
-            .fill(Color32::from_rgb(30, 32, 36))
-            .stroke(theme::subtle_border(&tokens))
-            .rounding(egui::Rounding::same(12.0))
-            .inner_margin(egui::Margin::symmetric(14.0, 12.0))
-            .show(ui, |ui| {
-                ui.horizontal(|ui| {
-                    let button_width = 120.0;
-                    let text_width = (ui.available_width() - button_width - 12.0).max(240.0);
-                    let search_edit = egui::TextEdit::singleline(&mut provider_state.search_query)
-                        .hint_text(provider.search_hint())
-                        .desired_width(f32::INFINITY);
-                    let response = ui.add_sized([text_width, 30.0], search_edit);
-                    if response.changed() {
-                        persist_changes = true;
-                    }
+/// Adjuntos del compositor. El menú contextual de cada uno ofrece describir las imágenes
+/// con un modelo con visión y transcribir los audios.
+/// Avisa de los fragmentos del borrador que se redactarán si van a un proveedor remoto y
+/// muestra la sustitución al pasar el ratón.
+fn draw_composer_redaction_notice(ui: &mut egui::Ui, state: &mut AppState) {
+    let input = state.chat.input.trim();
+    if input.is_empty() || !state.config.redaction.enabled {
+        return;
+    }
+    let config = state.effective_redaction_config();
+    let segments = state.redaction.preview(&config, input);
+    let redacted = segments
+        .iter()
+        .filter(|segment| matches!(segment, PreviewSegment::Redacted { .. }))
+        .count();
+    if redacted == 0 {
+        return;
+    }
+    ui.label(
+        RichText::new(format!(
+            "{} {}",
+            ICON_SHIELD,
+            tf(
+                "{0} fragmentos se redactarán al enviar a proveedores remotos",
+                &[&redacted.to_string()],
+            )
+        ))
+        .font(theme::icon_font(12.0))
+        .color(theme::color_text_weak()),
+    )
+    .on_hover_ui(|ui| draw_redaction_preview(ui, &segments));
+    ui.add_space(6.0);
+}
 
-                    let needs_token =
-                        provider.requires_token() && provider_state.access_token.is_none();
-                    let mut clicked = false;
-                    let search_label =
-                        RichText::new("Buscar").color(Color32::from_rgb(240, 240, 240));
-                    ui.add_enabled_ui(!needs_token, |ui| {
-                        if ui
-                            .add_sized(
-                                [button_width, 32.0],
-                                theme::primary_button(search_label.clone(), &tokens),
-                            )
-                            .clicked()
-                        {
-                            clicked = true;
-                        }
-                    });
+/// Texto con lo que se sustituye tachado en rojo y el reemplazo en verde.
+fn draw_redaction_preview(ui: &mut egui::Ui, segments: &[PreviewSegment]) {
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for segment in segments {
+            match segment {
+                PreviewSegment::Kept(text) => {
+                    ui.label(text);
+                }
+                PreviewSegment::Redacted {
+                    original,
+                    replacement,
+                    rule,
+                } => {
+                    ui.label(
+                        RichText::new(original)
+                            .strikethrough()
+                            .color(theme::color_danger()),
+                    )
+                    .on_hover_text(rule);
+                    ui.label(RichText::new(replacement).color(theme::color_success()));
+                }
+            }
+        }
+    });
+}
 
-                    if clicked {
-                        search_request = Some((
-                            provider_state.search_query.clone(),
-                            provider_state.access_token.clone(),
-                        ));
+fn draw_composer_attachments(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut describe = None;
+    let mut transcribe = None;
+    let mut recognize = None;
+    let mut remove = None;
+    let mut detach_result = None;
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        for result in &state.web_search.attached {
+            quick_chip(ui, &format!("{} {}", ICON_GLOBE, result.title))
+                .on_hover_text(&result.url)
+                .context_menu(|ui| {
+                    if ui.button(t("Quitar adjunto")).clicked() {
+                        detach_result = Some(result.url.clone());
+                        ui.close_menu();
                     }
                 });
-            });
+        }
+        for attachment in &state.attachments.items {
+            let label = format!("{} {}", attachment.kind.icon(), attachment.file_name());
+            let hover = match state.attachments.documents.get(&attachment.path) {
+                Some((_, chunks)) if attachment.kind == AttachmentKind::Image => format!(
+                    "{}\n{}",
+                    attachment.path.display(),
+                    tf(
+                        "Texto reconocido · {0} fragmentos",
+                        &[&chunks.len().to_string()]
+                    )
+                ),
+                None if state.ocr.is_running(&attachment.path) => format!(
+                    "{}\n{}",
+                    attachment.path.display(),
+                    t("Reconociendo texto…")
+                ),
+                Some((pages, chunks)) => format!(
+                    "{}\n{}",
+                    attachment.path.display(),
+                    tf(
+                        "{0} páginas · {1} fragmentos",
+                        &[&pages.to_string(), &chunks.len().to_string()]
+                    )
+                ),
+                None => attachment.path.display().to_string(),
+            };
+            quick_chip(ui, &label)
+                .on_hover_text(hover)
+                .context_menu(|ui| {
+                    match attachment.kind {
+                        AttachmentKind::Image => {
+                            if ui.button(t("Describir imagen")).clicked() {
+                                describe = Some(attachment.path.clone());
+                                ui.close_menu();
+                            }
+                            if ui.button(t("Reconocer texto (OCR)")).clicked() {
+                                recognize = Some(attachment.path.clone());
+                                ui.close_menu();
+                            }
+                        }
+                        AttachmentKind::Audio => {
+                            if ui.button(t("Transcribir")).clicked() {
+                                transcribe = Some(attachment.path.clone());
+                                ui.close_menu();
+                            }
+                        }
+                        AttachmentKind::Document | AttachmentKind::Other => {}
+                    }
+                    if ui.button(t("Quitar adjunto")).clicked() {
+                        remove = Some(attachment.path.clone());
+                        ui.close_menu();
+                    }
+                });
+        }
+    });
+
+    if let Some(path) = recognize {
+        state.recognize_image_text(path);
+    }
+    if let Some(path) = describe {
+        match state.describe_image(&path) {
+            Ok(provider) => state.toasts.push(
+                LogStatus::Running,
+                tf("Imagen enviada a {0}.", &[&provider]),
+            ),
+            Err(err) => state.toasts.push(LogStatus::Error, err),
+        }
+    }
+    if let Some(path) = transcribe {
+        if let Err(err) = state.start_transcription(path) {
+            state.toasts.push(LogStatus::Error, err);
+        }
     }
+    if let Some(path) = remove {
+        state.attachments.remove(&path);
+    }
+    if let Some(url) = detach_result {
+        state.web_search.toggle_attached(&url);
+    }
+}
 
-    if persist_changes {
-        state.persist_config();
+pub(crate) fn submit_chat_message(state: &mut AppState) {
+    let trimmed = state.chat.input.trim();
+    if trimmed.is_empty() {
+        state.chat.input.clear();
+        return;
     }
 
-    if let Some((query, token)) = search_request {
-        match search_models_for_provider(provider, &query, token.as_deref()) {
-            Ok(models) => {
-                let count = models.len();
-                let provider_state = state.provider_state_mut(provider);
-                provider_state.models = models;
-                provider_state.selected_model = None;
-                provider_state.install_status = Some(format!(
-                    "Se encontraron {} modelos para '{}'.",
-                    count, query
-                ));
-                state.persist_config();
-            }
-            Err(err) => {
-                let provider_state = state.provider_state_mut(provider);
-                provider_state.install_status = Some(format!("Fallo al buscar modelos: {}", err));
+    // La confirmación de solo local se conserva mientras se resuelve la de coste.
+    let local_only_confirmed = state
+        .chat
+        .local_only_confirmation
+        .as_ref()
+        .is_some_and(|confirmation| confirmation.confirmed && confirmation.input == trimmed);
+    if !local_only_confirmed {
+        let projects = state.local_only_conflict(trimmed);
+        if !projects.is_empty() {
+            state.chat.local_only_confirmation = Some(LocalOnlyConfirmation {
+                input: trimmed.to_string(),
+                projects,
+                confirmed: false,
+            });
+            return;
+        }
+    }
+
+    match state.chat.cost_confirmation.take() {
+        Some(confirmation) if confirmation.confirmed && confirmation.input == trimmed => {}
+        _ => {
+            if let Some(total) = state.send_cost_requiring_confirmation(trimmed) {
+                state.chat.cost_confirmation = Some(CostConfirmation {
+                    input: trimmed.to_string(),
+                    total,
+                    confirmed: false,
+                });
+                return;
             }
         }
     }
 
-    ui.add_space(12.0);
+    state.chat.local_only_confirmation = None;
+    state.chat.local_only_override = local_only_confirmed;
+    send_composer_input(state);
+    state.chat.local_only_override = false;
+}
 
-    let (models, selected_model) = {
-        let provider_state = state.provider_state(provider);
-        (provider_state.models.clone(), provider_state.selected_model)
-    };
+fn send_composer_input(state: &mut AppState) {
+    let mut input = state.chat.input.trim().to_string();
+    while input.ends_with('\n') {
+        input.pop();
+    }
+    state.chat.input.clear();
+    state.composer.record_sent(&input);
 
-    if models.is_empty() {
+    if let Some(index) = state.chat.editing_message.take() {
+        state.truncate_conversation(index);
+    }
+
+    let quote = state.chat.pending_reply.take();
+    state.dispatch_user_message(input, quote);
+}
+
+fn draw_selected_preference(ui: &mut egui::Ui, state: &mut AppState, tab_index: usize) {
+    match state.selected_preference {
+        PreferencePanel::SystemGithub => draw_system_github(ui, state),
+        PreferencePanel::SystemCache => draw_system_cache(ui, state),
+        PreferencePanel::SystemResources => draw_system_resources(ui, state),
+        PreferencePanel::SystemNetwork => draw_system_network(ui, state),
+        PreferencePanel::SystemJobQueue => draw_system_job_queue(ui, state),
+        PreferencePanel::SystemWebSearch => draw_system_web_search(ui, state),
+        PreferencePanel::SystemEmail => draw_system_email(ui, state),
+        PreferencePanel::SystemTelegram => draw_system_telegram(ui, state),
+        PreferencePanel::SystemMqtt => draw_system_mqtt(ui, state),
+        PreferencePanel::SystemRedaction => draw_system_redaction(ui, state),
+        PreferencePanel::SystemUsage => draw_system_usage(ui, state),
+        PreferencePanel::SystemNotifications => draw_system_notifications(ui, state),
+        PreferencePanel::SystemPermissions => draw_system_permissions(ui, state),
+        PreferencePanel::SystemStorage => draw_system_storage(ui, state),
+        PreferencePanel::CustomizationCommands => {
+            draw_custom_commands_section(ui, state, tab_index)
+        }
+        PreferencePanel::CustomizationAppearance => draw_customization_appearance(ui, state),
+        PreferencePanel::CustomizationAccessibility => draw_customization_accessibility(ui, state),
+        PreferencePanel::CustomizationKeybindings => draw_customization_keybindings(ui, state),
+        PreferencePanel::CustomizationVoice => draw_customization_voice(ui, state),
+        PreferencePanel::CustomizationDigest => draw_customization_digest(ui, state),
+        PreferencePanel::CustomizationTextActions => draw_customization_text_actions(ui, state),
+        PreferencePanel::CustomizationMemory => match tab_index {
+            1 => draw_customization_context(ui, state),
+            _ => draw_customization_memory(ui, state),
+        },
+        PreferencePanel::CustomizationProfiles => draw_customization_profiles(ui, state),
+        PreferencePanel::CustomizationProjects => draw_customization_projects(ui, state),
+        PreferencePanel::ProvidersAnthropic => draw_provider_anthropic(ui, state, tab_index),
+        PreferencePanel::ProvidersOpenAi => draw_provider_openai(ui, state, tab_index),
+        PreferencePanel::ProvidersGroq => draw_provider_groq(ui, state, tab_index),
+        PreferencePanel::LocalJarvis => match tab_index {
+            1 => draw_jarvis_performance(ui, state),
+            _ => draw_local_settings(ui, state),
+        },
+        PreferencePanel::LocalJarvisKnowledge => draw_local_knowledge(ui, state),
+        PreferencePanel::LocalJarvisSafety => draw_local_safety(ui, state),
+        PreferencePanel::LocalOcr => draw_local_ocr(ui, state),
+    }
+}
+
+fn draw_selected_resource(ui: &mut egui::Ui, state: &mut AppState, section: ResourceSection) {
+    match section {
+        ResourceSection::LocalCatalog(provider) => draw_local_provider(ui, state, provider),
+        ResourceSection::RemoteCatalog(kind) => draw_remote_provider_catalog(ui, state, kind),
+        ResourceSection::InstalledLocal => draw_local_library_overview(ui, state),
+        ResourceSection::ConnectedProjects => {
+            draw_project_resources(ui, state, ProjectResourceKind::LocalProject)
+        }
+        ResourceSection::GithubRepositories => {
+            draw_project_resources(ui, state, ProjectResourceKind::GithubRepository)
+        }
+        ResourceSection::Bookmarks => draw_bookmarks(ui, state),
+        ResourceSection::Reports => draw_reports(ui, state),
+        ResourceSection::Playbooks => draw_playbooks(ui, state),
+    }
+}
+
+fn draw_bookmarks(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.heading(
+        RichText::new(t("Marcadores"))
+            .color(theme::color_text_primary())
+            .strong()
+            .size(18.0),
+    );
+    ui.label(
+        RichText::new(t(
+            "Mensajes fijados en todas las conversaciones. Los de la conversación abierta te llevan de vuelta al historial.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    ui.add_space(10.0);
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.bookmarks.filter)
+                .hint_text(t("Filtrar marcadores"))
+                .desired_width(220.0),
+        );
+        ui.add(
+            egui::TextEdit::singleline(&mut state.bookmarks.export_path)
+                .hint_text("marcadores.md")
+                .desired_width(220.0),
+        );
+        let can_export = !state.bookmarks.entries.is_empty();
+        if ui
+            .add_enabled(can_export, egui::Button::new(t("Exportar a Markdown")))
+            .clicked()
+        {
+            state.bookmarks.status = Some(match state.export_bookmarks() {
+                Ok(count) => tf("{0} marcadores exportados.", &[&count.to_string()]),
+                Err(err) => err,
+            });
+        }
+    });
+    if let Some(status) = &state.bookmarks.status {
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+    ui.add_space(10.0);
+
+    if state.bookmarks.entries.is_empty() {
         ui.colored_label(
             theme::color_text_weak(),
-            "Busca un término para poblar la galería de modelos.",
+            t("Todavía no hay mensajes fijados. Usa el icono de chincheta de un mensaje para guardarlo aquí."),
         );
-    } else {
-        ui.horizontal(|ui| {
-            ui.heading(
-                RichText::new(format!("Galería de modelos ({} resultados)", models.len()))
-                    .color(theme::color_text_primary()),
-            );
-            ui.add_space(ui.available_width());
-            ui.label(
-                RichText::new("Clic en una tarjeta para seleccionarla o instalarla.")
-                    .color(theme::color_text_weak())
-                    .size(12.0),
-            );
-        });
-        ui.add_space(8.0);
-        draw_provider_gallery(ui, state, provider, &models, selected_model);
+        return;
     }
 
-    ui.add_space(12.0);
-    let installed: Vec<InstalledLocalModel> = state
-        .resources
-        .installed_local_models
-        .iter()
-        .cloned()
-        .filter(|model| model.identifier.provider == provider)
-        .collect();
+    let current = state.bookmarks.conversation.clone();
+    let mut open = None;
+    let mut remove = None;
+    for (conversation, entries) in state.bookmarks.grouped() {
+        let title = if conversation == current {
+            t("Conversación actual").to_string()
+        } else {
+            tf("Conversación del {0}", &[&conversation])
+        };
+        ui.add_space(6.0);
+        ui.label(
+            RichText::new(title)
+                .strong()
+                .color(theme::color_text_primary()),
+        );
+        for (index, bookmark) in entries {
+            egui::Frame::none()
+                .fill(Color32::from_rgb(34, 38, 44))
+                .rounding(egui::Rounding::same(10.0))
+                .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(format!("{} · {}", bookmark.sender, bookmark.timestamp))
+                                .strong()
+                                .size(12.0),
+                        );
+                        ui.label(
+                            RichText::new(tf("fijado el {0}", &[&bookmark.pinned_at]))
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button(t("Eliminar")).clicked() {
+                                remove = Some(index);
+                            }
+                            if conversation == current
+                                && ui.small_button(t("Ir al mensaje")).clicked()
+                            {
+                                open = Some(index);
+                            }
+                        });
+                    });
+                    let mut preview: String = bookmark.text.chars().take(400).collect();
+                    if preview.len() < bookmark.text.len() {
+                        preview.push('…');
+                    }
+                    ui.label(RichText::new(preview).color(theme::color_text_primary()));
+                });
+            ui.add_space(4.0);
+        }
+    }
 
-    if installed.is_empty() {
+    if let Some(index) = open {
+        state.open_bookmark(index);
+    }
+    if let Some(index) = remove {
+        state.remove_bookmark(index);
+    }
+}
+
+fn draw_reports(ui: &mut egui::Ui, state: &mut AppState) {
+    if !state.reports.loaded {
+        state.reports.refresh(&state.config.report_templates);
+    }
+    ui.heading(
+        RichText::new(t("Informes"))
+            .color(theme::color_text_primary())
+            .strong()
+            .size(18.0),
+    );
+    ui.label(
+        RichText::new(t(
+            "Las plantillas son Markdown: cada {{modelo: instrucción}} lo redacta el proveedor elegido. También puedes usar {{fecha}}, {{hora}} y {{plantilla}}.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    ui.add_space(10.0);
+
+    let mut edit = None;
+    let mut generate = None;
+    let mut remove = None;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(t("Plantillas")).strong());
+        if ui.small_button(t("Nueva plantilla")).clicked() {
+            state.reports.editing = None;
+            state.reports.draft = ReportTemplate {
+                body: reports::EXAMPLE_TEMPLATE.to_string(),
+                ..ReportTemplate::default()
+            };
+        }
+    });
+    if state.config.report_templates.is_empty() {
         ui.colored_label(
             theme::color_text_weak(),
-            "Todavía no hay modelos instalados desde este proveedor.",
+            t("Todavía no hay plantillas. Crea una a partir del ejemplo."),
         );
-    } else {
+    }
+    for (index, template) in state.config.report_templates.iter().enumerate() {
         ui.horizontal(|ui| {
-            ui.heading(
-                RichText::new("Modelos instalados")
-                    .color(theme::color_text_primary())
-                    .size(16.0),
-            );
-            ui.add_space(ui.available_width());
+            let selected = state.reports.editing == Some(index);
+            if ui.selectable_label(selected, &template.name).clicked() {
+                edit = Some(index);
+            }
+            let schedule = if template.cron_expression.is_empty() {
+                t("bajo demanda").to_string()
+            } else {
+                template.cron_expression.clone()
+            };
             ui.label(
-                RichText::new("Gestiona tus descargas locales y actívalas para Jarvis.")
+                RichText::new(schedule)
                     .color(theme::color_text_weak())
                     .size(11.0),
             );
-        });
-        ui.add_space(6.0);
-        egui::ScrollArea::vertical()
-            .id_source(("installed_models_scroll", provider.key()))
-            .max_height(240.0)
-            .auto_shrink([false, false])
-            .show(ui, |ui| {
-                for record in installed {
-                    draw_installed_model_card(ui, state, provider, &record);
-                    ui.add_space(10.0);
+            if let Some(last_run) = &template.last_run {
+                ui.label(
+                    RichText::new(tf("último: {0}", &[last_run]))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            }
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.small_button(t("Eliminar")).clicked() {
+                    remove = Some(index);
                 }
-            });
+                if ui
+                    .add_enabled(
+                        !state.reports.is_running(),
+                        egui::Button::new(t("Generar ahora")).small(),
+                    )
+                    .clicked()
+                {
+                    generate = Some(template.name.clone());
+                }
+            });
+        });
     }
-
-    if let Some(status) = state.provider_state(provider).install_status.clone() {
-        ui.add_space(10.0);
-        ui.colored_label(theme::color_text_weak(), status);
+    if let Some(index) = edit {
+        state.reports.editing = Some(index);
+        state.reports.draft = state.config.report_templates[index].clone();
     }
-}
-
-fn draw_provider_gallery(
-    ui: &mut egui::Ui,
-    state: &mut AppState,
-    provider: LocalModelProvider,
-    models: &[LocalModelCard],
-    selected_model: Option<usize>,
-) {
-    let spacing = 16.0;
-    let min_card_width = 280.0;
 
-    egui::ScrollArea::vertical()
-        .id_source(("provider_gallery_scroll", provider.key()))
-        .max_height(380.0)
-        .auto_shrink([false, false])
+    ui.add_space(8.0);
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 38, 44))
+        .rounding(egui::Rounding::same(10.0))
+        .inner_margin(egui::Margin::symmetric(12.0, 10.0))
         .show(ui, |ui| {
-            let available_width = ui.available_width().max(min_card_width);
-            let mut columns =
-                ((available_width + spacing) / (min_card_width + spacing)).floor() as usize;
-            columns = columns.clamp(1, 3);
-            let card_width = ((available_width - spacing * ((columns as f32) - 1.0))
-                / columns as f32)
-                .max(min_card_width);
+            ui.set_width(ui.available_width());
+            let draft = &mut state.reports.draft;
+            egui::Grid::new("report_template_editor")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label(t("Nombre"));
+                    ui.add(egui::TextEdit::singleline(&mut draft.name).desired_width(240.0));
+                    ui.end_row();
+
+                    ui.label(t("Redacta"));
+                    let selected = draft
+                        .provider
+                        .as_deref()
+                        .and_then(RemoteProviderKind::from_short_code)
+                        .map_or("Jarvis", RemoteProviderKind::display_name);
+                    egui::ComboBox::from_id_source("report_template_provider")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut draft.provider, None, "Jarvis");
+                            for provider in [
+                                RemoteProviderKind::Anthropic,
+                                RemoteProviderKind::OpenAi,
+                                RemoteProviderKind::Groq,
+                            ] {
+                                ui.selectable_value(
+                                    &mut draft.provider,
+                                    Some(provider.short_code().to_string()),
+                                    provider.display_name(),
+                                );
+                            }
+                        });
+                    ui.end_row();
 
-            let mut base_index = 0usize;
-            for chunk in models.chunks(columns) {
-                ui.horizontal(|ui| {
-                    ui.spacing_mut().item_spacing.x = spacing;
-                    for (offset, model) in chunk.iter().enumerate() {
-                        let index = base_index + offset;
-                        let (rect, response) = ui
-                            .allocate_at_least(egui::vec2(card_width, 190.0), egui::Sense::click());
-                        let mut card_ui =
-                            ui.child_ui(rect, egui::Layout::top_down(egui::Align::LEFT));
-                        draw_model_card(
-                            &mut card_ui,
-                            state,
-                            provider,
-                            model,
-                            index,
-                            selected_model == Some(index),
-                        );
+                    ui.label(t("Directorio de salida"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.output_directory)
+                            .hint_text(t("Carpeta de informes de la aplicación")),
+                    );
+                    ui.end_row();
 
-                        if response.clicked() {
-                            state.provider_state_mut(provider).selected_model = Some(index);
-                        }
+                    ui.label(t("Programación (cron)"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.cron_expression)
+                            .hint_text(t("Vacío: solo bajo demanda"))
+                            .desired_width(160.0),
+                    );
+                    ui.end_row();
+                });
+            ui.checkbox(
+                &mut draft.commit_to_git,
+                t("Confirmar cada informe en el repositorio Git del directorio"),
+            );
+            ui.add(
+                egui::TextEdit::multiline(&mut draft.body)
+                    .code_editor()
+                    .desired_rows(10)
+                    .desired_width(f32::INFINITY),
+            );
+        });
+    ui.horizontal(|ui| {
+        if ui.button(t("Guardar plantilla")).clicked() {
+            state.reports.status = Some(match state.save_report_template_draft() {
+                Ok(message) => message,
+                Err(err) => err,
+            });
+        }
+        if state.reports.is_running() {
+            ui.spinner();
+        }
+    });
+    if let Some(name) = generate {
+        if let Err(err) = state.generate_report(&name) {
+            state.reports.status = Some(err);
+        }
+    }
+    if let Some(index) = remove {
+        state.delete_report_template(index);
+    }
+    if let Some(status) = &state.reports.status {
+        ui.colored_label(theme::color_text_weak(), status.as_str());
+    }
 
-                        if response.double_clicked() {
-                            install_local_model(state, provider, index);
-                        }
-                    }
+    ui.add_space(12.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(t("Informes generados")).strong());
+        if ui.small_button(t("Actualizar")).clicked() {
+            state.reports.refresh(&state.config.report_templates);
+        }
+    });
+    if state.reports.reports.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Aún no se ha generado ningún informe."),
+        );
+        return;
+    }
 
-                    if chunk.len() < columns {
-                        for _ in chunk.len()..columns {
-                            ui.add_space(card_width);
-                        }
+    let mut select = None;
+    ui.columns(2, |columns| {
+        egui::ScrollArea::vertical()
+            .id_source("generated_reports")
+            .max_height(360.0)
+            .show(&mut columns[0], |ui| {
+                for report in &state.reports.reports {
+                    let name = report
+                        .path
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let selected = state.reports.selected.as_ref() == Some(&report.path);
+                    let label = format!(
+                        "{}\n{} · {} · {}",
+                        name,
+                        report.template,
+                        report.modified,
+                        format_bytes(report.size)
+                    );
+                    if ui.selectable_label(selected, label).clicked() {
+                        select = Some(report.path.clone());
                     }
-                });
-                ui.add_space(spacing);
-                base_index += chunk.len();
+                }
+            });
+        egui::ScrollArea::vertical()
+            .id_source("report_preview")
+            .max_height(360.0)
+            .show(&mut columns[1], |ui| {
+                if state.reports.selected.is_some() {
+                    ui.label(
+                        RichText::new(&state.reports.preview)
+                            .monospace()
+                            .color(theme::color_text_primary()),
+                    );
+                } else {
+                    ui.colored_label(
+                        theme::color_text_weak(),
+                        t("Selecciona un informe para verlo."),
+                    );
+                }
+            });
+    });
+    if let Some(path) = select {
+        state.reports.select(&path);
+    }
+    if let Some(path) = state.reports.selected.clone() {
+        if ui.button(t("Enviar por correo")).clicked() {
+            if let Err(err) = state.compose_report_email(&path) {
+                state.reports.status = Some(err);
             }
-        });
+        }
+    }
 }
 
-fn draw_model_card(
-    ui: &mut egui::Ui,
-    state: &mut AppState,
-    provider: LocalModelProvider,
-    model: &LocalModelCard,
-    index: usize,
-    is_selected: bool,
-) {
-    let premium = model.requires_token;
+fn draw_playbooks(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.heading(
+        RichText::new(t("Playbooks"))
+            .color(theme::color_text_primary())
+            .strong()
+            .size(18.0),
+    );
+    ui.label(
+        RichText::new(t(
+            "Cada paso es un prompt: {{campo}} se pide al lanzar, {{anterior}} es la respuesta del paso previo y {{paso: N}} la del paso N.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    let route = state
+        .effective_conversation_defaults()
+        .provider_kind()
+        .map_or("Jarvis", RemoteProviderKind::display_name);
+    ui.label(
+        RichText::new(tf(
+            "Los pasos se envían a {0}, con la persona y los parámetros de la conversación abierta.",
+            &[route],
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    ui.add_space(10.0);
 
-    let fill = if premium {
-        Color32::from_rgb(48, 36, 56)
-    } else {
-        Color32::from_rgb(34, 38, 44)
-    };
-    let incompatible = model.incompatible_reason.is_some();
-    let border = if is_selected {
-        theme::color_primary()
-    } else if premium {
-        Color32::from_rgb(182, 134, 242)
-    } else if incompatible {
-        theme::color_danger()
-    } else {
-        Color32::from_rgb(70, 80, 96)
-    };
+    let running = state.playbooks.is_running();
+    let mut edit = None;
+    let mut remove = None;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(t("Playbooks guardados")).strong());
+        if ui.small_button(t("Nuevo playbook")).clicked() {
+            state.playbooks.editing = None;
+            state.playbooks.draft = Playbook {
+                steps: vec![PlaybookStep::default()],
+                ..Playbook::default()
+            };
+        }
+    });
+    if state.config.playbooks.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Todavía no hay playbooks. Crea uno con sus pasos."),
+        );
+    }
+    for (index, playbook) in state.config.playbooks.iter().enumerate() {
+        ui.horizontal(|ui| {
+            let selected = state.playbooks.editing == Some(index);
+            if ui.selectable_label(selected, &playbook.name).clicked() {
+                edit = Some(index);
+            }
+            ui.label(
+                RichText::new(tf("{0} pasos", &[&playbook.steps.len().to_string()]))
+                    .color(theme::color_text_weak())
+                    .size(11.0),
+            );
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(!running, egui::Button::new(t("Eliminar")).small())
+                    .clicked()
+                {
+                    remove = Some(index);
+                }
+            });
+        });
+    }
+    if let Some(index) = edit {
+        state.playbooks.editing = Some(index);
+        state.playbooks.draft = state.config.playbooks[index].clone();
+    }
+    if let Some(index) = remove {
+        state.delete_playbook(index);
+    }
 
+    ui.add_space(8.0);
+    let mut remove_step = None;
     egui::Frame::none()
-        .fill(fill)
-        .stroke(egui::Stroke::new(
-            if is_selected { 2.0 } else { 1.0 },
-            border,
-        ))
-        .rounding(egui::Rounding::same(12.0))
-        .inner_margin(egui::Margin::symmetric(14.0, 12.0))
+        .fill(Color32::from_rgb(34, 38, 44))
+        .rounding(egui::Rounding::same(10.0))
+        .inner_margin(egui::Margin::symmetric(12.0, 10.0))
         .show(ui, |ui| {
             ui.set_width(ui.available_width());
-            ui.vertical(|ui| {
-                ui.horizontal(|ui| {
-                    ui.spacing_mut().item_spacing.x = 8.0;
-                    let badge_icon = if premium { ICON_PREMIUM } else { ICON_FREE };
-                    let badge_color = if premium {
-                        Color32::from_rgb(255, 214, 102)
-                    } else {
-                        Color32::from_rgb(108, 214, 148)
-                    };
-                    ui.label(
-                        RichText::new(badge_icon)
-                            .font(theme::icon_font(15.0))
-                            .color(badge_color),
+            let draft = &mut state.playbooks.draft;
+            egui::Grid::new("playbook_editor")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label(t("Nombre"));
+                    ui.add(egui::TextEdit::singleline(&mut draft.name).desired_width(240.0));
+                    ui.end_row();
+
+                    ui.label(t("Descripción"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.description)
+                            .desired_width(f32::INFINITY),
                     );
-                    ui.label(
-                        RichText::new(&model.id)
-                            .strong()
-                            .color(theme::color_text_primary()),
+                    ui.end_row();
+                });
+            for (index, step) in draft.steps.iter_mut().enumerate() {
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(tf("Paso {0}", &[&(index + 1).to_string()])).strong());
+                    ui.add(
+                        egui::TextEdit::singleline(&mut step.title)
+                            .hint_text(t("Título"))
+                            .desired_width(240.0),
                     );
+                    if ui
+                        .small_button(RichText::new(ICON_TRASH).font(theme::icon_font(12.0)))
+                        .on_hover_text(t("Eliminar paso"))
+                        .clicked()
+                    {
+                        remove_step = Some(index);
+                    }
                 });
+                ui.add(
+                    egui::TextEdit::multiline(&mut step.prompt)
+                        .code_editor()
+                        .desired_rows(3)
+                        .desired_width(f32::INFINITY),
+                );
+            }
+            ui.add_space(6.0);
+            if ui.small_button(t("Añadir paso")).clicked() {
+                draft.steps.push(PlaybookStep::default());
+            }
+        });
+    if let Some(index) = remove_step {
+        state.playbooks.draft.steps.remove(index);
+    }
+    if ui.button(t("Guardar playbook")).clicked() {
+        state.playbooks.status = Some(match state.save_playbook_draft() {
+            Ok(message) => message,
+            Err(err) => err,
+        });
+    }
 
-                if let Some(author) = &model.author {
-                    ui.label(
-                        RichText::new(format!("Autor: {}", author))
-                            .color(theme::color_text_weak())
-                            .size(12.0),
-                    );
+    // Solo se lanzan playbooks guardados, con los campos de su versión guardada.
+    if let Some((index, playbook)) = state
+        .playbooks
+        .editing
+        .and_then(|index| Some((index, state.config.playbooks.get(index)?.clone())))
+    {
+        ui.add_space(12.0);
+        ui.label(RichText::new(tf("Lanzar '{0}'", &[&playbook.name])).strong());
+        if !playbook.description.is_empty() {
+            ui.colored_label(theme::color_text_weak(), playbook.description.as_str());
+        }
+        for field in playbooks::fields(&playbook) {
+            ui.label(field.as_str());
+            ui.add(
+                egui::TextEdit::multiline(state.playbooks.values.entry(field).or_default())
+                    .desired_rows(2)
+                    .desired_width(f32::INFINITY),
+            );
+        }
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!running, egui::Button::new(t("Lanzar")))
+                .clicked()
+            {
+                if let Err(err) = state.launch_playbook(index) {
+                    state.playbooks.status = Some(err);
                 }
+            }
+            if running && ui.button(t("Cancelar")).clicked() {
+                state.cancel_playbook();
+            }
+        });
+    }
 
-                if let Some(pipeline) = &model.pipeline_tag {
-                    ui.label(
-                        RichText::new(format!("Pipeline: {}", pipeline))
-                            .color(theme::color_text_weak())
-                            .size(12.0),
+    if let Some(run) = &state.playbooks.run {
+        let done = run.results.len();
+        let total = run.playbook.steps.len();
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.add(
+                egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                    .desired_width(220.0)
+                    .text(tf(
+                        "{0}/{1} pasos",
+                        &[&done.to_string(), &total.to_string()],
+                    )),
+            );
+        });
+        if let Some(step) = run.playbook.steps.get(done) {
+            ui.colored_label(
+                theme::color_text_weak(),
+                tf("En curso: {0}", &[&step.title]),
+            );
+        }
+    }
+    if let Some(status) = &state.playbooks.status {
+        ui.colored_label(theme::color_text_weak(), status.as_str());
+    }
+
+    let Some(path) = &state.playbooks.document else {
+        return;
+    };
+    ui.add_space(12.0);
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(t("Documento de resultados")).strong());
+        ui.label(
+            RichText::new(path.display().to_string())
+                .color(theme::color_text_weak())
+                .size(11.0),
+        );
+    });
+    egui::ScrollArea::vertical()
+        .id_source("playbook_document")
+        .max_height(360.0)
+        .show(ui, |ui| {
+            ui.label(
+                RichText::new(&state.playbooks.preview)
+                    .monospace()
+                    .color(theme::color_text_primary()),
+            );
+        });
+}
+
+fn draw_project_resources(ui: &mut egui::Ui, state: &mut AppState, kind: ProjectResourceKind) {
+    let (title, subtitle) = match kind {
+        ProjectResourceKind::LocalProject => (
+            "Proyectos locales sincronizados",
+            "Explora carpetas conectadas al agente con estado de sincronización y README en vivo.",
+        ),
+        ProjectResourceKind::GithubRepository => (
+            "Repositorios GitHub enlazados",
+            "Consulta repositorios con sincronización bidireccional y acciones rápidas desde JungleMonkAI.",
+        ),
+    };
+
+    ui.heading(
+        RichText::new(title)
+            .color(theme::color_text_primary())
+            .strong()
+            .size(18.0),
+    );
+    ui.label(
+        RichText::new(subtitle)
+            .color(theme::color_text_weak())
+            .size(12.0),
+    );
+
+    ui.add_space(10.0);
+
+    let cards = state.resources.project_resources_by_kind(kind);
+    if cards.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "No hay recursos sincronizados en esta categoría todavía.",
+        );
+        return;
+    }
+
+    for card in cards {
+        draw_project_resource_card(ui, state, &card);
+        ui.add_space(12.0);
+    }
+}
+
+fn draw_project_resource_card(ui: &mut egui::Ui, state: &mut AppState, card: &ProjectResourceCard) {
+    egui::Frame::none()
+        .fill(Color32::from_rgb(34, 36, 42))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(16.0))
+        .inner_margin(egui::Margin {
+            left: 18.0,
+            right: 18.0,
+            top: 14.0,
+            bottom: 14.0,
+        })
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.heading(
+                        RichText::new(&card.name)
+                            .color(theme::color_text_primary())
+                            .size(16.0)
+                            .strong(),
                     );
-                }
+                    ui.add_space(ui.available_width());
+                    let status_color = sync_health_color(card.status.health);
+                    ui.label(
+                        RichText::new(card.status.label())
+                            .color(status_color)
+                            .monospace()
+                            .size(12.0),
+                    );
+                });
 
-                if !model.tags.is_empty() {
-                    let tags: Vec<&str> =
-                        model.tags.iter().take(3).map(|tag| tag.as_str()).collect();
+                ui.label(
+                    RichText::new(card.status.detail())
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                );
+
+                ui.add_space(8.0);
+                ui.horizontal(|ui| {
                     ui.label(
-                        RichText::new(format!("Etiquetas: {}", tags.join(", ")))
+                        RichText::new(format!("Ubicación: {}", card.location))
                             .color(theme::color_text_weak())
                             .size(11.0),
                     );
-                }
-
-                if let Some(description) = &model.description {
-                    ui.add_space(4.0);
+                    ui.add_space(18.0);
                     ui.label(
-                        RichText::new(description)
+                        RichText::new(format!("Última sincronización: {}", card.last_sync))
                             .color(theme::color_text_weak())
                             .size(11.0),
                     );
-                }
-
-                if let Some(reason) = &model.incompatible_reason {
-                    ui.add_space(6.0);
+                    ui.add_space(18.0);
                     ui.label(
-                        RichText::new(reason)
-                            .color(theme::color_danger())
-                            .italics()
+                        RichText::new(format!("Rama principal: {}", card.default_branch))
+                            .color(theme::color_text_weak())
                             .size(11.0),
                     );
-                }
+                });
 
-                let mut metrics = Vec::new();
-                if let Some(likes) = model.likes {
-                    metrics.push(format!("❤ {}", format_count(likes)));
+                if !card.tags.is_empty() {
+                    ui.add_space(6.0);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing.x = 6.0;
+                        ui.label(
+                            RichText::new("Tags")
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                        for tag in &card.tags {
+                            selectable_chip(ui, tag, false);
+                        }
+                    });
                 }
-                if let Some(downloads) = model.downloads {
-                    metrics.push(format!("⬇ {}", format_count(downloads)));
+
+                ui.add_space(8.0);
+                let mut local_only = state.is_project_local_only(&card.name);
+                if ui
+                    .checkbox(
+                        &mut local_only,
+                        RichText::new(format!("{} {}", ICON_SHIELD, t("Solo local")))
+                            .font(theme::icon_font(12.0)),
+                    )
+                    .on_hover_text(t(
+                        "El contenido de este proyecto solo podrá enviarse a Jarvis; los envíos a proveedores remotos que lo incluyan pedirán confirmación.",
+                    ))
+                    .changed()
+                {
+                    state.set_project_local_only(&card.name, local_only);
                 }
-                if !metrics.is_empty() {
-                    ui.add_space(4.0);
+
+                ui.add_space(10.0);
+                ui.label(
+                    RichText::new("README destacado")
+                        .color(theme::color_text_primary())
+                        .size(12.0)
+                        .strong(),
+                );
+                ui.add_space(4.0);
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(28, 30, 36))
+                    .stroke(theme::subtle_border(&state.theme))
+                    .rounding(egui::Rounding::same(12.0))
+                    .inner_margin(egui::Margin::same(12.0))
+                    .show(ui, |ui| {
+                        ui.label(
+                            RichText::new(&card.readme_preview)
+                                .color(theme::color_text_weak())
+                                .monospace()
+                                .size(12.0),
+                        );
+                    });
+
+                if !card.pending_actions.is_empty() {
+                    ui.add_space(8.0);
                     ui.label(
-                        RichText::new(metrics.join("  · "))
+                        RichText::new("Acciones sugeridas")
                             .color(theme::color_text_primary())
-                            .size(12.0),
+                            .size(12.0)
+                            .strong(),
                     );
+                    for action in &card.pending_actions {
+                        ui.label(
+                            RichText::new(format!("• {}", action))
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                    }
                 }
 
-                ui.add_space(8.0);
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if card.kind == ProjectResourceKind::LocalProject {
+                        let browse_button = theme::secondary_button(
+                            RichText::new(t("Explorar código"))
+                                .color(theme::color_text_primary())
+                                .strong(),
+                            &state.theme,
+                        )
+                        .min_size(egui::vec2(140.0, 30.0));
+                        if ui.add(browse_button).clicked() {
+                            if let Err(err) = state.open_code_viewer(&card.name) {
+                                state.push_activity_log(LogStatus::Error, "Recursos", err);
+                            }
+                        }
+                        ui.add_space(8.0);
+                    }
 
-                let button_label = if premium {
-                    format!("{} Instalar (token)", ICON_DOWNLOAD)
-                } else {
-                    format!("{} Instalar", ICON_DOWNLOAD)
-                };
-                let button_width = ui.available_width();
-                let response = ui.add_enabled(
-                    !incompatible,
-                    theme::primary_button(
-                        RichText::new(button_label).color(Color32::from_rgb(240, 240, 240)),
+                    let open_button = theme::secondary_button(
+                        RichText::new("Abrir README")
+                            .color(theme::color_text_primary())
+                            .strong(),
                         &state.theme,
                     )
-                    .min_size(egui::vec2(button_width, 30.0)),
-                );
+                    .min_size(egui::vec2(140.0, 30.0));
+                    if ui.add(open_button).clicked() {
+                        state.push_activity_log(
+                            LogStatus::Ok,
+                            "Recursos",
+                            format!("Abrió README de {}", card.name),
+                        );
+                        ui.output_mut(|out| out.copied_text = card.readme_preview.clone());
+                    }
 
-                if response.clicked() {
-                    install_local_model(state, provider, index);
-                }
+                    ui.add_space(8.0);
+                    let sync_button = theme::primary_button(
+                        RichText::new("Sincronizar ahora")
+                            .color(Color32::WHITE)
+                            .strong(),
+                        &state.theme,
+                    )
+                    .min_size(egui::vec2(150.0, 30.0));
+                    if ui.add(sync_button).clicked() {
+                        state.push_activity_log(
+                            LogStatus::Running,
+                            "Recursos",
+                            format!("Sincronización solicitada para {}", card.name),
+                        );
+                        state.push_debug_event(
+                            DebugLogLevel::Info,
+                            "resources::sync",
+                            format!("Marcado '{}' para sincronización manual", card.name),
+                        );
+                    }
+                });
             });
         });
 }
 
-fn draw_installed_model_card(
+fn sync_health_color(health: SyncHealth) -> Color32 {
+    match health {
+        SyncHealth::Healthy => theme::color_success(),
+        SyncHealth::Warning => Color32::from_rgb(255, 196, 0),
+        SyncHealth::Error => theme::color_danger(),
+    }
+}
+
+fn draw_remote_provider_catalog(
     ui: &mut egui::Ui,
     state: &mut AppState,
-    provider: LocalModelProvider,
-    record: &InstalledLocalModel,
+    provider: RemoteProviderKind,
 ) {
-    let is_active = state
-        .resources
-        .jarvis_active_model
-        .as_ref()
-        .map(|model| model == &record.identifier)
-        .unwrap_or(false);
-
-    let install_path = if record.install_path.trim().is_empty() {
-        Path::new(&state.resources.jarvis_install_dir)
-            .join(record.identifier.sanitized_dir_name())
-            .display()
-            .to_string()
-    } else {
-        record.install_path.clone()
-    };
+    match provider {
+        RemoteProviderKind::Anthropic => {
+            draw_claude_catalog(ui, state);
+            ui.add_space(18.0);
+            draw_remote_catalog_explorer(ui, state, provider);
+        }
+        RemoteProviderKind::OpenAi | RemoteProviderKind::Groq => {
+            draw_remote_catalog_explorer(ui, state, provider);
+        }
+    }
+}
 
-    let path_display = truncate_middle(&install_path, 72);
-    let subtitle = format!(
-        "{} • Instalado el {}",
-        format_bytes(record.size_bytes),
-        format_timestamp(record.installed_at)
+fn draw_remote_catalog_explorer(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    provider: RemoteProviderKind,
+) {
+    let provider_label = provider.display_name();
+    ui.heading(
+        RichText::new(format!("{} · Galería enriquecida", provider_label))
+            .color(theme::color_text_primary())
+            .strong()
+            .size(18.0),
+    );
+    ui.label(
+        RichText::new(
+            "Compara capacidades, costos y lanza pruebas rápidas directamente desde JungleMonkAI.",
+        )
+        .color(theme::color_text_weak())
+        .size(12.0),
     );
 
-    egui::Frame::none()
-        .fill(Color32::from_rgb(30, 32, 36))
-        .stroke(theme::subtle_border(&state.theme))
-        .rounding(egui::Rounding::same(12.0))
-        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
-        .show(ui, |ui| {
-            ui.set_width(ui.available_width());
-            ui.vertical(|ui| {
-                ui.horizontal(|ui| {
-                    ui.label(
-                        RichText::new(record.identifier.provider.display_name())
-                            .color(theme::color_text_weak())
-                            .size(12.0),
-                    );
-                    ui.add_space(6.0);
-                    ui.label(
-                        RichText::new(&record.identifier.model_id)
-                            .strong()
-                            .color(theme::color_text_primary()),
-                    );
-                    if is_active {
-                        ui.add_space(10.0);
-                        ui.label(
-                            RichText::new("Activo")
-                                .color(theme::color_success())
-                                .size(12.0),
-                        );
-                    }
-                });
+    ui.add_space(10.0);
+    let tags = state.resources.remote_catalog.all_tags(provider);
+    let mut reset_status = false;
 
-                ui.add_space(6.0);
-                ui.label(
-                    RichText::new(&subtitle)
-                        .color(theme::color_text_weak())
-                        .size(12.0),
-                );
+    {
+        let filters = state.resources.remote_catalog.filters_mut(provider);
 
-                ui.label(
-                    RichText::new(&path_display)
-                        .color(theme::color_text_weak())
-                        .size(11.0),
-                )
-                .on_hover_text(&install_path);
+        ui.horizontal(|ui| {
+            let search_width = (ui.available_width() - 140.0).max(200.0);
+            let search_response = ui.add_sized(
+                [search_width, 30.0],
+                egui::TextEdit::singleline(&mut filters.search)
+                    .hint_text("Buscar por nombre, tags o capacidades"),
+            );
+            if search_response.changed() {
+                reset_status = true;
+            }
 
-                ui.add_space(10.0);
+            if ui
+                .add_sized([120.0, 30.0], egui::Button::new("Limpiar filtros"))
+                .clicked()
+            {
+                *filters = Default::default();
+                reset_status = true;
+            }
+        });
 
-                if is_active {
-                    ui.add_enabled_ui(false, |ui| {
-                        ui.add_sized(
-                            [ui.available_width(), 30.0],
-                            theme::secondary_button(
-                                RichText::new("Activo en Jarvis")
-                                    .color(Color32::from_rgb(240, 240, 240)),
-                                &state.theme,
-                            ),
-                        );
-                    });
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            let mut cost_enabled = filters.max_cost.is_some();
+            if ui
+                .checkbox(&mut cost_enabled, "Coste ≤ USD / 1M tokens")
+                .changed()
+            {
+                if cost_enabled {
+                    filters.max_cost = Some(filters.max_cost.unwrap_or(15.0));
                 } else {
-                    let button = theme::primary_button(
-                        RichText::new(format!("{} Activar en Jarvis", ICON_SEND))
-                            .color(Color32::from_rgb(240, 240, 240)),
-                        &state.theme,
-                    );
-                    if ui.add_sized([ui.available_width(), 30.0], button).clicked() {
-                        let status = state.activate_jarvis_model(&record.identifier);
-                        state.provider_state_mut(provider).install_status = Some(status);
-                    }
+                    filters.max_cost = None;
                 }
-            });
-        });
-}
-
-fn install_local_model(state: &mut AppState, provider: LocalModelProvider, index: usize) {
-    let (model, token) = {
-        let provider_state = state.provider_state(provider);
-        if let Some(model) = provider_state.models.get(index).cloned() {
-            (model, provider_state.access_token.clone())
-        } else {
-            return;
-        }
-    };
-
-    debug_assert_eq!(model.provider, provider);
+            }
 
-    if let Some(reason) = &model.incompatible_reason {
-        let message = format!("'{}' no es compatible: {}", model.id, reason);
-        state.provider_state_mut(provider).install_status = Some(message.clone());
-        state.push_activity_log(LogStatus::Warning, "Jarvis", message);
-        return;
-    }
+            if cost_enabled {
+                let mut value = filters.max_cost.unwrap_or(15.0);
+                if ui
+                    .add(
+                        egui::Slider::new(&mut value, 0.5..=120.0)
+                            .logarithmic(true)
+                            .text("USD / 1M"),
+                    )
+                    .changed()
+                {
+                    filters.max_cost = Some(value);
+                }
+            }
 
-    if provider == LocalModelProvider::HuggingFace {
-        let started = state.queue_huggingface_install(model.clone(), token);
-        if started {
-            state.provider_state_mut(provider).selected_model = Some(index);
-        } else {
-            let warning = format!(
-                "Ya hay una descarga en curso para '{}'. Espera a que termine.",
-                model.id
-            );
+            let mut context_enabled = filters.min_context.is_some();
+            if ui
+                .checkbox(&mut context_enabled, "Contexto mínimo")
+                .changed()
             {
-                let provider_state = state.provider_state_mut(provider);
-                provider_state.selected_model = Some(index);
-                provider_state.install_status = Some(warning.clone());
+                if context_enabled {
+                    filters.min_context = Some(filters.min_context.unwrap_or(8192));
+                } else {
+                    filters.min_context = None;
+                }
             }
-            state.push_activity_log(LogStatus::Warning, "Jarvis", warning);
-        }
-        return;
-    }
 
-    let status = match provider {
-        LocalModelProvider::Ollama => {
-            match crate::api::ollama::pull_model(&model.id, token.as_deref()) {
-                Ok(()) => format!(
-                "Modelo '{}' preparado mediante Ollama. Usa el runtime de Ollama para servirlo.",
-                model.id
-            ),
-                Err(err) => format!("No se pudo preparar '{}' con Ollama: {}", model.id, err),
+            if context_enabled {
+                let mut value = filters.min_context.unwrap_or(8192) as f32;
+                if ui
+                    .add(
+                        egui::Slider::new(&mut value, 4096.0..=400_000.0)
+                            .logarithmic(true)
+                            .text("tokens"),
+                    )
+                    .changed()
+                {
+                    filters.min_context = Some(value.round() as u32);
+                }
             }
-        }
-        _ => format!(
-            "La instalación automática aún no está disponible para {}.",
-            provider.display_name()
-        ),
-    };
+        });
 
-    let provider_state = state.provider_state_mut(provider);
-    provider_state.selected_model = Some(index);
-    provider_state.install_status = Some(status);
-}
+        ui.add_space(4.0);
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut filters.favorites_only, "Solo favoritos");
+            ui.checkbox(&mut filters.multimodal_only, "Solo multimodal");
+        });
 
-fn search_models_for_provider(
-    provider: LocalModelProvider,
-    query: &str,
-    token: Option<&str>,
-) -> Result<Vec<LocalModelCard>> {
-    let trimmed = query.trim();
-    if trimmed.is_empty() {
-        return Ok(Vec::new());
+        if !tags.is_empty() {
+            ui.add_space(6.0);
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing.x = 6.0;
+                ui.label(
+                    RichText::new(format!("{} Tags", ICON_FILTER))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+                for tag in &tags {
+                    let selected = filters.tag_filters.contains(tag);
+                    if selectable_chip(ui, tag, selected).clicked() {
+                        if selected {
+                            filters.tag_filters.remove(tag);
+                        } else {
+                            filters.tag_filters.insert(tag.clone());
+                        }
+                        reset_status = true;
+                    }
+                }
+                if !filters.tag_filters.is_empty()
+                    && ui
+                        .button(RichText::new("Limpiar tags").size(11.0))
+                        .clicked()
+                {
+                    filters.tag_filters.clear();
+                    reset_status = true;
+                }
+            });
+        }
     }
 
-    match provider {
-        LocalModelProvider::HuggingFace => crate::api::huggingface::search_models(trimmed, token),
-        LocalModelProvider::Ollama => crate::api::ollama::search_models(trimmed, token),
-        LocalModelProvider::OpenRouter => crate::api::openrouter::search_models(trimmed),
-        _ => {
-            let lowercase = trimmed.to_lowercase();
-            let catalog = sample_catalog(provider);
-            let filtered = catalog
-                .into_iter()
-                .filter(|card| {
-                    card.id.to_lowercase().contains(&lowercase)
-                        || card
-                            .description
-                            .as_ref()
-                            .map(|desc| desc.to_lowercase().contains(&lowercase))
-                            .unwrap_or(false)
-                })
-                .collect();
-            Ok(filtered)
+    if reset_status {
+        state.resources.remote_catalog.update_status(None);
+    }
+
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        let prompt_width = (ui.available_width() - 140.0).max(200.0);
+        ui.add_sized(
+            [prompt_width, 30.0],
+            egui::TextEdit::singleline(&mut state.resources.remote_catalog.quick_test_prompt)
+                .hint_text("Prompt para 'Probar' (ej. Resume los últimos commits)"),
+        );
+        if ui
+            .add_sized([120.0, 30.0], egui::Button::new("Limpiar prompt"))
+            .clicked()
+        {
+            state.resources.remote_catalog.quick_test_prompt.clear();
         }
+    });
+
+    if let Some(status) = &state.resources.remote_catalog.last_status {
+        ui.add_space(6.0);
+        ui.colored_label(theme::color_text_weak(), status);
     }
-}
 
-fn sample_catalog(provider: LocalModelProvider) -> Vec<LocalModelCard> {
-    match provider {
-        LocalModelProvider::GithubModels => vec![
-            LocalModelCard {
-                provider,
-                id: "github/CodeLlama-34b".to_string(),
-                author: Some("GitHub".to_string()),
-                pipeline_tag: Some("text-generation".to_string()),
-                tags: vec!["code".to_string(), "llama".to_string()],
-                description: Some(
-                    "Modelos experimentales de GitHub Models listos para desplegar en contenedores.".
-                        to_string(),
-                ),
-                requires_token: true,
-                ..Default::default()
-            },
-            LocalModelCard {
-                provider,
-                id: "github/Phi-3-mini".to_string(),
-                author: Some("GitHub".to_string()),
-                pipeline_tag: Some("text-generation".to_string()),
-                tags: vec!["chat".to_string(), "preview".to_string()],
-                description: Some(
-                    "Inferencia hospedada en GitHub Models compatible con la API de OpenAI.".
-                        to_string(),
-                ),
-                requires_token: true,
-                ..Default::default()
-            },
-        ],
-        LocalModelProvider::Replicate => vec![
-            LocalModelCard {
-                provider,
-                id: "replicate/flux-dev".to_string(),
-                author: Some("Replicate".to_string()),
-                pipeline_tag: Some("image-to-image".to_string()),
-                tags: vec!["diffusion".to_string(), "vision".to_string()],
-                description: Some(
-                    "Modelos visuales populares de la comunidad de Replicate disponibles mediante API.".
-                        to_string(),
-                ),
-                requires_token: true,
-                ..Default::default()
-            },
-            LocalModelCard {
-                provider,
-                id: "replicate/llama-3-70b-instruct".to_string(),
-                author: Some("Replicate".to_string()),
-                pipeline_tag: Some("text-generation".to_string()),
-                tags: vec!["chat".to_string(), "meta".to_string()],
-                description: Some(
-                    "Versión alojada de Llama 3 para uso inmediato a través de Replicate API.".
-                        to_string(),
-                ),
-                requires_token: true,
-                ..Default::default()
-            },
-        ],
-        LocalModelProvider::Ollama => vec![
-            LocalModelCard {
-                provider,
-                id: "ollama/llama3".to_string(),
-                author: Some("Ollama".to_string()),
-                pipeline_tag: Some("text-generation".to_string()),
-                tags: vec!["local".to_string(), "chat".to_string()],
-                description: Some(
-                    "Modelos descargables mediante 'ollama pull' listos para ejecutarse en tu host.".
-                        to_string(),
-                ),
-                requires_token: false,
-                ..Default::default()
-            },
-            LocalModelCard {
-                provider,
-                id: "ollama/codellama".to_string(),
-                author: Some("Ollama".to_string()),
-                pipeline_tag: Some("code-generation".to_string()),
-                tags: vec!["code".to_string(), "local".to_string()],
-                description: Some(
-                    "Ejemplos de modelos que Ollama expone como imágenes portables para contenedores.".
-                        to_string(),
-                ),
-                requires_token: false,
-                ..Default::default()
-            },
-        ],
-        LocalModelProvider::OpenRouter => vec![
-            LocalModelCard {
-                provider,
-                id: "openrouter/google/gemini-pro".to_string(),
-                author: Some("OpenRouter".to_string()),
-                pipeline_tag: Some("text-generation".to_string()),
-                tags: vec!["router".to_string(), "gemini".to_string()],
-                description: Some(
-                    "Agrega modelos de múltiples proveedores con una única API compatible con OpenAI.".
-                        to_string(),
-                ),
-                requires_token: true,
-                ..Default::default()
-            },
-            LocalModelCard {
-                provider,
-                id: "openrouter/mistral/mixtral-8x7b".to_string(),
-                author: Some("OpenRouter".to_string()),
-                pipeline_tag: Some("text-generation".to_string()),
-                tags: vec!["mixture-of-experts".to_string()],
-                description: Some(
-                    "Modelos orquestados por OpenRouter listos para su consumo mediante claves personales.".
-                        to_string(),
-                ),
-                requires_token: true,
-                ..Default::default()
-            },
-        ],
-        LocalModelProvider::Modelscope => vec![
-            LocalModelCard {
-                provider,
-                id: "modelscope/Qwen1.5-14B-Chat".to_string(),
-                author: Some("ModelScope".to_string()),
-                pipeline_tag: Some("text-generation".to_string()),
-                tags: vec!["qwen".to_string(), "chat".to_string()],
-                description: Some(
-                    "Modelos del ecosistema ModelScope listos para descarga mediante su SDK oficial.".
-                        to_string(),
-                ),
-                requires_token: true,
-                ..Default::default()
-            },
-            LocalModelCard {
-                provider,
-                id: "modelscope/speech_paraformer".to_string(),
-                author: Some("ModelScope".to_string()),
-                pipeline_tag: Some("automatic-speech-recognition".to_string()),
-                tags: vec!["audio".to_string(), "asr".to_string()],
-                description: Some(
-                    "Ejemplos de pipelines de voz disponibles a través del hub de ModelScope.".
-                        to_string(),
-                ),
-                requires_token: true,
-                ..Default::default()
-            },
-        ],
-        LocalModelProvider::HuggingFace => Vec::new(),
+    ui.add_space(8.0);
+    let cards: Vec<RemoteModelCard> = {
+        let refs = state.resources.remote_catalog.filtered_cards(provider);
+        refs.into_iter().cloned().collect()
+    };
+    if cards.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "Ajusta los filtros o actualiza tus credenciales para mostrar modelos disponibles.",
+        );
+    } else {
+        ui.horizontal(|ui| {
+            ui.heading(
+                RichText::new(format!("{} resultados", cards.len()))
+                    .color(theme::color_text_primary())
+                    .size(16.0),
+            );
+            ui.add_space(ui.available_width());
+            ui.label(
+                RichText::new(
+                    "Utiliza 'Probar' para lanzar una solicitud con el prompt configurado.",
+                )
+                .color(theme::color_text_weak())
+                .size(11.0),
+            );
+        });
+        ui.add_space(8.0);
+        draw_remote_model_gallery(ui, state, &cards);
     }
+
+    draw_remote_comparison(ui, state);
+    draw_quick_test_harness(ui, state);
 }
 
-fn format_timestamp(timestamp: DateTime<Utc>) -> String {
-    let local: DateTime<Local> = DateTime::from(timestamp);
-    local.format("%Y-%m-%d %H:%M").to_string()
-}
+fn draw_remote_model_gallery(ui: &mut egui::Ui, state: &mut AppState, cards: &[RemoteModelCard]) {
+    let spacing = 18.0;
+    let min_card_width = 280.0;
+
+    egui::ScrollArea::vertical()
+        .id_source("remote_models_gallery")
+        .max_height(420.0)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            let available_width = ui.available_width().max(min_card_width);
+            let mut columns =
+                ((available_width + spacing) / (min_card_width + spacing)).floor() as usize;
+            columns = columns.clamp(1, 3);
+            let card_width = ((available_width - spacing * ((columns as f32) - 1.0))
+                / columns as f32)
+                .max(min_card_width);
+
+            for chunk in cards.chunks(columns) {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = spacing;
+                    for card in chunk {
+                        let (rect, _) = ui
+                            .allocate_at_least(egui::vec2(card_width, 240.0), egui::Sense::hover());
+                        let mut card_ui =
+                            ui.child_ui(rect, egui::Layout::top_down(egui::Align::LEFT));
+                        draw_remote_model_card(&mut card_ui, state, card);
+                    }
+
+                    if chunk.len() < columns {
+                        for _ in chunk.len()..columns {
+                            ui.add_space(card_width);
+                        }
+                    }
+                });
+                ui.add_space(spacing);
+            }
+        });
+}
+
+fn draw_remote_model_card(ui: &mut egui::Ui, state: &mut AppState, card: &RemoteModelCard) {
+    let is_favorite = state.resources.remote_catalog.is_favorite(&card.key);
+    let in_comparison = state.resources.remote_catalog.in_comparison(&card.key);
+    let fill = if is_favorite {
+        Color32::from_rgb(44, 40, 60)
+    } else {
+        Color32::from_rgb(34, 38, 44)
+    };
+
+    egui::Frame::none()
+        .fill(fill)
+        .stroke(egui::Stroke::new(1.0, Color32::from_rgb(70, 80, 96)))
+        .rounding(egui::Rounding::same(12.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 12.0))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 8.0;
+                    ui.label(
+                        RichText::new(&card.title)
+                            .strong()
+                            .color(theme::color_text_primary())
+                            .size(16.0),
+                    );
+                    let star_color = if is_favorite {
+                        Color32::from_rgb(255, 201, 71)
+                    } else {
+                        theme::color_text_weak()
+                    };
+                    let star = egui::Label::new(
+                        RichText::new(ICON_STAR)
+                            .font(theme::icon_font(14.0))
+                            .color(star_color),
+                    )
+                    .sense(egui::Sense::click());
+                    let star_response = ui.add(star).on_hover_text(if is_favorite {
+                        "Quitar de favoritos"
+                    } else {
+                        "Marcar como favorito"
+                    });
+                    if star_response.clicked() {
+                        let provider = card.key.provider;
+                        let key_clone = card.key.clone();
+                        let was_favorite = state.resources.remote_catalog.is_favorite(&key_clone);
+                        state
+                            .resources
+                            .remote_catalog
+                            .toggle_favorite(key_clone.clone());
+                        let favorites_snapshot = state.resources.remote_catalog.favorites.clone();
+                        {
+                            let cards = state.resources.remote_catalog.cards_for_mut(provider);
+                            cards.sort_by(|a, b| {
+                                let a_fav = favorites_snapshot.contains(&a.key);
+                                let b_fav = favorites_snapshot.contains(&b.key);
+                                b_fav.cmp(&a_fav).then_with(|| {
+                                    a.title.to_lowercase().cmp(&b.title.to_lowercase())
+                                })
+                            });
+                        }
+                        let message = if was_favorite {
+                            format!("{} eliminado de favoritos", card.title)
+                        } else {
+                            format!("{} añadido a favoritos", card.title)
+                        };
+                        state
+                            .resources
+                            .remote_catalog
+                            .update_status(Some(message.clone()));
+                        state.push_debug_event(
+                            DebugLogLevel::Info,
+                            format!("catalog::{}", provider.short_code()),
+                            message,
+                        );
+                    }
+                    ui.add_space(ui.available_width());
+                    if card.multimodal {
+                        ui.label(
+                            RichText::new("Multimodal")
+                                .color(theme::color_primary())
+                                .size(11.0),
+                        );
+                    }
+                });
+
+                ui.add_space(4.0);
+                ui.label(
+                    RichText::new(&card.description)
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                );
+
+                ui.add_space(8.0);
+                ui.vertical(|ui| {
+                    ui.label(
+                        RichText::new(format!(
+                            "Contexto: {} tokens · Salida máx: {} tokens",
+                            card.context_tokens, card.max_output_tokens
+                        ))
+                        .color(theme::color_text_primary())
+                        .size(11.0),
+                    );
+                    ui.label(
+                        RichText::new(format!(
+                            "Coste entrada: {} · salida: {} · Latencia ≈ {} ms",
+                            format_cost_label(card.input_cost_per_million),
+                            format_cost_label(card.output_cost_per_million),
+                            card.latency_ms
+                        ))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                    );
+                    let quick_prompt = state.resources.remote_catalog.quick_test_prompt.trim();
+                    if !quick_prompt.is_empty() {
+                        let quick_tokens = state
+                            .tokenizers
+                            .for_provider(card.key.provider)
+                            .count(quick_prompt);
+                        ui.label(
+                            RichText::new(format!(
+                                "Prueba rápida: ≈{} tokens · coste estimado ${:.5}",
+                                quick_tokens,
+                                quick_tokens as f32 / 1_000_000.0 * card.input_cost_per_million
+                            ))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                        );
+                    }
+                });
+
+                if !card.capabilities.is_empty() {
+                    ui.add_space(6.0);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing.x = 6.0;
+                        for capability in &card.capabilities {
+                            ui.label(
+                                RichText::new(capability)
+                                    .color(theme::color_text_weak())
+                                    .size(11.0),
+                            );
+                        }
+                    });
+                }
+
+                if !card.tags.is_empty() {
+                    ui.add_space(6.0);
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing.x = 6.0;
+                        for tag in &card.tags {
+                            selectable_chip(ui, tag, false);
+                        }
+                    });
+                }
+
+                let quick_actions = state.model_quick_actions(&card.key);
+                if !quick_actions.is_empty() {
+                    ui.add_space(6.0);
+                    let mut triggered = None;
+                    ui.horizontal_wrapped(|ui| {
+                        ui.spacing_mut().item_spacing.x = 6.0;
+                        for (label, template) in &quick_actions {
+                            if quick_chip(ui, label).on_hover_text(template).clicked() {
+                                triggered = Some((label, template));
+                            }
+                        }
+                    });
+                    if let Some((label, template)) = triggered {
+                        state.run_model_quick_action(card.key.clone(), label, template);
+                    }
+                }
+
+                ui.add_space(8.0);
+                ui.label(
+                    RichText::new(&card.favorite_hint)
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    let mut favorite_toggled = false;
+                    if selectable_chip(ui, "Favorito", is_favorite).clicked() {
+                        favorite_toggled = true;
+                    }
+                    if favorite_toggled {
+                        state
+                            .resources
+                            .remote_catalog
+                            .toggle_favorite(card.key.clone());
+                        let now_favorite = !is_favorite;
+                        let status = if now_favorite {
+                            format!("{} marcado como favorito.", card.title)
+                        } else {
+                            format!("{} eliminado de favoritos.", card.title)
+                        };
+                        state.resources.remote_catalog.update_status(Some(status));
+                    }
+
+                    if selectable_chip(ui, "Comparar", in_comparison).clicked() {
+                        state
+                            .resources
+                            .remote_catalog
+                            .toggle_comparison(card.key.clone());
+                        state.resources.remote_catalog.update_status(Some(format!(
+                            "{} {} en la tabla comparativa.",
+                            card.title,
+                            if in_comparison {
+                                "eliminado"
+                            } else {
+                                "añadido"
+                            }
+                        )));
+                    }
+
+                    ui.add_space(ui.available_width());
+
+                    let test_label = RichText::new(format!("{} Probar", ICON_LIGHTNING))
+                        .color(Color32::from_rgb(240, 240, 240));
+                    if ui
+                        .add(
+                            theme::primary_button(test_label, &state.theme)
+                                .min_size(egui::vec2(110.0, 32.0)),
+                        )
+                        .clicked()
+                    {
+                        let status = state.execute_remote_quick_test(card.key.clone());
+                        if let Some(status) = status {
+                            state.resources.remote_catalog.update_status(Some(status));
+                        }
+                    }
+                });
+            });
+        });
+}
+
+fn draw_remote_comparison(ui: &mut egui::Ui, state: &mut AppState) {
+    if state.resources.remote_catalog.comparison.is_empty() {
+        return;
+    }
+
+    ui.add_space(12.0);
+    ui.separator();
+    ui.add_space(6.0);
+    ui.heading(
+        RichText::new("Comparativa rápida")
+            .color(theme::color_text_primary())
+            .size(16.0)
+            .strong(),
+    );
+    ui.add_space(6.0);
+
+    ui.push_id("remote_comparison_grid", |ui| {
+        egui::Grid::new("remote_comparison")
+            .striped(true)
+            .spacing(egui::vec2(12.0, 6.0))
+            .show(ui, |ui| {
+                ui.label(RichText::new("Modelo").strong());
+                ui.label(RichText::new("Contexto").strong());
+                ui.label(RichText::new("Costos").strong());
+                ui.label(RichText::new("Proveedor").strong());
+                ui.label(RichText::new("Acciones").strong());
+                ui.end_row();
+
+                let mut removals = Vec::new();
+                for key in &state.resources.remote_catalog.comparison {
+                    if let Some(card) = remote_card_by_key(state, key) {
+                        ui.label(
+                            RichText::new(&card.title)
+                                .color(theme::color_text_primary())
+                                .size(12.0),
+                        );
+                        ui.label(
+                            RichText::new(format!("{} tokens", card.context_tokens))
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                        ui.label(
+                            RichText::new(format!(
+                                "{} / {}",
+                                format_cost_label(card.input_cost_per_million),
+                                format_cost_label(card.output_cost_per_million)
+                            ))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                        );
+                        ui.label(
+                            RichText::new(card.key.provider.display_name())
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                        if ui.button(RichText::new("Quitar").size(11.0)).clicked() {
+                            removals.push(card.key.clone());
+                        }
+                        ui.end_row();
+                    }
+                }
+
+                for key in removals {
+                    state.resources.remote_catalog.toggle_comparison(key);
+                }
+            });
+    });
+}
+
+fn draw_quick_test_harness(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.add_space(12.0);
+    ui.separator();
+    ui.add_space(6.0);
+    ui.heading(
+        RichText::new(t("Pruebas con aserciones"))
+            .color(theme::color_text_primary())
+            .size(16.0)
+            .strong(),
+    );
+    ui.label(
+        RichText::new(t(
+            "Comprueba que los modelos de la comparativa siguen respondiendo como esperas antes de hacerlos predeterminados.",
+        ))
+        .color(theme::color_text_weak())
+        .size(11.0),
+    );
+    ui.add_space(6.0);
+
+    let running = state.quick_tests.is_running();
+    let mut edit = None;
+    let mut remove = None;
+    for (index, case) in state.config.quick_tests.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&case.name).color(theme::color_text_primary()));
+            let kinds: Vec<String> = case
+                .assertions
+                .iter()
+                .map(|assertion| t(quick_tests::kind_label(assertion.kind)).to_string())
+                .collect();
+            ui.label(
+                RichText::new(kinds.join(" · "))
+                    .color(theme::color_text_weak())
+                    .size(11.0),
+            )
+            .on_hover_text(&case.prompt);
+            ui.add_enabled_ui(!running, |ui| {
+                if ui.small_button(t("Editar")).clicked() {
+                    edit = Some(index);
+                }
+                if ui.small_button(t("Eliminar")).clicked() {
+                    remove = Some(index);
+                }
+            });
+        });
+    }
+    if let Some(index) = edit {
+        state.quick_tests.draft = state.config.quick_tests[index].clone();
+        state.quick_tests.editing = Some(index);
+    }
+    if let Some(index) = remove {
+        state.remove_quick_test_case(index);
+    }
+
+    let form_title = if state.quick_tests.editing.is_some() {
+        t("Editar prueba")
+    } else {
+        t("Nueva prueba")
+    };
+    egui::CollapsingHeader::new(form_title)
+        .id_source("quick_test_form")
+        .open(state.quick_tests.editing.map(|_| true))
+        .show(ui, |ui| draw_quick_test_form(ui, state));
+
+    ui.add_space(8.0);
+    let targets = state.quick_test_targets();
+    ui.horizontal_wrapped(|ui| {
+        ui.label(t("Modelos"));
+        if targets.is_empty() {
+            ui.colored_label(
+                theme::color_text_weak(),
+                t("Añade modelos a la comparativa con el botón 'Comparar'."),
+            );
+        }
+        for target in &targets {
+            ui.label(RichText::new(target.label()).monospace().size(11.0));
+        }
+        ui.add_enabled(
+            !running,
+            egui::Checkbox::new(&mut state.quick_tests.include_jarvis, t("Incluir Jarvis")),
+        );
+    });
+    ui.horizontal(|ui| {
+        if running {
+            if ui
+                .add(theme::secondary_button(
+                    RichText::new(format!("{} {}", ICON_STOP, t("Cancelar")))
+                        .font(theme::icon_font(13.0)),
+                    &state.theme,
+                ))
+                .clicked()
+            {
+                state.cancel_quick_tests();
+            }
+            if let Some((done, total)) = state.quick_tests.progress() {
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / total.max(1) as f32)
+                        .desired_width(240.0)
+                        .text(format!("{}/{}", done, total)),
+                );
+            }
+        } else if ui
+            .add(theme::primary_button(
+                RichText::new(format!("{} {}", ICON_PLAY, t("Ejecutar pruebas")))
+                    .font(theme::icon_font(13.0)),
+                &state.theme,
+            ))
+            .clicked()
+        {
+            if let Err(err) = state.start_quick_tests() {
+                state.quick_tests.status = Some(err);
+            }
+        }
+
+        if !running && !state.quick_tests.history.is_empty() {
+            let history = &state.quick_tests.history;
+            let run_label = |index: usize| {
+                let run = &history[index];
+                format!(
+                    "{} · {}/{}",
+                    run.started_at,
+                    run.passed(),
+                    run.outcomes.len()
+                )
+            };
+            let mut selected = state.quick_tests.selected_run.min(history.len() - 1);
+            egui::ComboBox::from_id_source("quick_test_history")
+                .selected_text(run_label(selected))
+                .show_ui(ui, |ui| {
+                    for index in 0..history.len() {
+                        ui.selectable_value(&mut selected, index, run_label(index));
+                    }
+                });
+            state.quick_tests.selected_run = selected;
+        }
+    });
+    if let Some(status) = &state.quick_tests.status {
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+
+    draw_quick_test_results(ui, state);
+}
+
+fn draw_quick_test_form(ui: &mut egui::Ui, state: &mut AppState) {
+    let draft = &mut state.quick_tests.draft;
+    egui::Grid::new("quick_test_form_grid")
+        .num_columns(2)
+        .spacing(egui::vec2(12.0, 8.0))
+        .show(ui, |ui| {
+            ui.label(t("Nombre"));
+            ui.text_edit_singleline(&mut draft.name);
+            ui.end_row();
+
+            ui.label(t("Prompt"));
+            ui.add(
+                egui::TextEdit::multiline(&mut draft.prompt)
+                    .desired_rows(3)
+                    .desired_width(360.0),
+            );
+            ui.end_row();
+        });
+
+    ui.add_space(4.0);
+    ui.label(t("Aserciones"));
+    let mut removal = None;
+    for (index, assertion) in draft.assertions.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_source(("quick_test_assertion_kind", index))
+                .selected_text(t(quick_tests::kind_label(assertion.kind)))
+                .show_ui(ui, |ui| {
+                    for kind in [
+                        QuickTestAssertionKind::Contains,
+                        QuickTestAssertionKind::Regex,
+                        QuickTestAssertionKind::JsonSchema,
+                    ] {
+                        ui.selectable_value(
+                            &mut assertion.kind,
+                            kind,
+                            t(quick_tests::kind_label(kind)),
+                        );
+                    }
+                });
+            if assertion.kind == QuickTestAssertionKind::JsonSchema {
+                ui.add(
+                    egui::TextEdit::multiline(&mut assertion.value)
+                        .code_editor()
+                        .desired_rows(3)
+                        .desired_width(300.0)
+                        .hint_text(r#"{"type": "object", "required": ["campo"]}"#),
+                );
+            } else {
+                ui.add(egui::TextEdit::singleline(&mut assertion.value).desired_width(300.0));
+            }
+            if ui.small_button(t("Quitar")).clicked() {
+                removal = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removal {
+        draft.assertions.remove(index);
+    }
+    if ui.small_button(t("Añadir aserción")).clicked() {
+        draft.assertions.push(QuickTestAssertion::default());
+    }
+
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        if ui.button(t("Guardar prueba")).clicked() {
+            state.quick_tests.status = state.save_quick_test_case().err();
+        }
+        if ui.button(t("Descartar")).clicked() {
+            state.quick_tests.draft = Default::default();
+            state.quick_tests.editing = None;
+        }
+    });
+}
+
+/// Tabla de superadas y falladas: una fila por prueba y una columna por modelo.
+fn draw_quick_test_results(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(run) = state.quick_tests.shown_run().cloned() else {
+        return;
+    };
+    let running = state.quick_tests.is_running();
+    let mut promote = None;
+    ui.add_space(6.0);
+    egui::ScrollArea::horizontal()
+        .id_source("quick_test_results_scroll")
+        .show(ui, |ui| {
+            egui::Grid::new("quick_test_results")
+                .num_columns(run.targets.len() + 1)
+                .striped(true)
+                .spacing(egui::vec2(14.0, 6.0))
+                .show(ui, |ui| {
+                    ui.label(
+                        RichText::new(t("Prueba"))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                    for target in &run.targets {
+                        ui.label(
+                            RichText::new(target.label())
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                    }
+                    ui.end_row();
+
+                    for case in &run.cases {
+                        ui.label(RichText::new(case).color(theme::color_text_primary()));
+                        for target in 0..run.targets.len() {
+                            match run.outcome(case, target) {
+                                Some(outcome) => {
+                                    let (icon, color) = if outcome.passed {
+                                        ("✔", theme::color_success())
+                                    } else {
+                                        ("✘", theme::color_danger())
+                                    };
+                                    let mut hover = outcome.failures.join("\n");
+                                    if let Some(response) = &outcome.response {
+                                        if !hover.is_empty() {
+                                            hover.push_str("\n\n");
+                                        }
+                                        hover.push_str(response);
+                                    }
+                                    ui.label(
+                                        RichText::new(format!(
+                                            "{} {} ms",
+                                            icon, outcome.latency_ms
+                                        ))
+                                        .color(color),
+                                    )
+                                    .on_hover_text(hover);
+                                }
+                                None => {
+                                    ui.label(RichText::new("…").color(theme::color_text_weak()));
+                                }
+                            }
+                        }
+                        ui.end_row();
+                    }
+
+                    ui.label(
+                        RichText::new(t("Superadas"))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                    for (index, target) in run.targets.iter().enumerate() {
+                        let (passed, total) = run.score(index);
+                        ui.horizontal(|ui| {
+                            ui.label(format!("{}/{}", passed, total));
+                            let complete = total == run.cases.len() && passed == total;
+                            if let Some(key) = target.remote_key().filter(|_| complete && !running)
+                            {
+                                if ui
+                                    .small_button(t("Hacer predeterminado"))
+                                    .on_hover_text(t(
+                                        "Superó todas las pruebas: úsalo como modelo predeterminado del proveedor.",
+                                    ))
+                                    .clicked()
+                                {
+                                    promote = Some(key);
+                                }
+                            }
+                        });
+                    }
+                    ui.end_row();
+                });
+        });
+    if let Some(key) = promote {
+        state.use_remote_model_as_default(&key);
+        state.quick_tests.status = Some(tf(
+            "{0} es ahora el modelo predeterminado.",
+            &[&key.as_display()],
+        ));
+    }
+}
+
+fn format_cost_label(value: f32) -> String {
+    if value < 1.0 {
+        format!("${:.3}", value)
+    } else {
+        format!("${:.2}", value)
+    }
+}
+
+fn remote_card_by_key<'a>(
+    state: &'a AppState,
+    key: &RemoteModelKey,
+) -> Option<&'a RemoteModelCard> {
+    state
+        .resources
+        .remote_catalog
+        .cards_for(key.provider)
+        .iter()
+        .find(|card| card.key == *key)
+}
+
+fn draw_system_github(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.label("Personal access token");
+    if ui.text_edit_singleline(&mut state.github_token).changed() {
+        state.persist_config();
+    }
+
+    if ui.button("Connect & sync").clicked() {
+        if state.github_token.trim().is_empty() {
+            state.github_username = None;
+            state.github_repositories.clear();
+            state.selected_github_repo = None;
+            state.github_connection_status =
+                Some("Please enter a valid GitHub token before syncing.".to_string());
+            state.refresh_personalization_resources();
+        } else {
+            match github::fetch_user_and_repositories(&state.github_token) {
+                Ok(data) => {
+                    state.github_username = Some(data.username.clone());
+                    state.github_repositories = data.repositories;
+                    state.selected_github_repo = None;
+                    state.github_connection_status =
+                        Some(format!("GitHub data loaded for {}.", data.username));
+                    state.refresh_personalization_resources();
+                }
+                Err(err) => {
+                    state.github_username = None;
+                    state.github_repositories.clear();
+                    state.selected_github_repo = None;
+                    state.github_connection_status =
+                        Some(format!("Failed to sync GitHub: {}", err));
+                    state.refresh_personalization_resources();
+                    state.notify(
+                        NotificationSource::Integrations,
+                        LogStatus::Error,
+                        "GitHub",
+                        format!("No se pudo sincronizar GitHub: {}", err),
+                    );
+                }
+            }
+        }
+    }
+
+    if let Some(username) = &state.github_username {
+        ui.colored_label(
+            ui.visuals().weak_text_color(),
+            format!("Authenticated as: {}", username),
+        );
+    }
+
+    let combo_label = state
+        .selected_github_repo
+        .and_then(|idx| state.github_repositories.get(idx))
+        .cloned()
+        .unwrap_or_else(|| "Choose a repository".to_string());
+
+    ui.add_enabled_ui(!state.github_repositories.is_empty(), |ui| {
+        egui::ComboBox::from_label("Select repository")
+            .selected_text(combo_label)
+            .show_ui(ui, |ui| {
+                for (idx, repo) in state.github_repositories.iter().enumerate() {
+                    ui.selectable_value(&mut state.selected_github_repo, Some(idx), repo);
+                }
+            });
+    });
+
+    if state.github_repositories.is_empty() {
+        ui.label("No repositories found yet. Connect with a token to load them.");
+    }
+
+    if ui.button("Sync repository").clicked() {
+        let message = match (
+            state.github_token.trim().is_empty(),
+            state.selected_github_repo,
+        ) {
+            (true, _) => "Cannot sync without a GitHub token.".to_string(),
+            (_, None) => "Please select a repository to sync.".to_string(),
+            (_, Some(idx)) => {
+                let repo = state.github_repositories[idx].clone();
+                format!("Repository '{}' scheduled for synchronization.", repo)
+            }
+        };
+        state.github_connection_status = Some(message);
+        state.persist_config();
+    }
+
+    if let Some(status) = &state.github_connection_status {
+        ui.add_space(8.0);
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
+
+fn draw_system_cache(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Cache directory");
+        if ui
+            .text_edit_singleline(&mut state.cache_directory)
+            .changed()
+        {
+            state.persist_config();
+        }
+    });
+
+    if ui
+        .add(
+            egui::Slider::new(&mut state.cache_size_limit_gb, 1.0..=256.0)
+                .text("Cache size limit (GB)"),
+        )
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    if ui
+        .checkbox(&mut state.enable_auto_cleanup, "Enable automatic cleanup")
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    if ui
+        .add(
+            egui::Slider::new(&mut state.cache_cleanup_interval_hours, 1..=168)
+                .text("Cleanup interval (hours)"),
+        )
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    if ui.button("Run cleanup now").clicked() {
+        state.last_cache_cleanup = Some(format!(
+            "Manual cleanup triggered. Next automatic run in {} hours.",
+            state.cache_cleanup_interval_hours
+        ));
+        state.persist_config();
+    }
+
+    if let Some(status) = &state.last_cache_cleanup {
+        ui.add_space(8.0);
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+
+    ui.add_space(12.0);
+    ui.separator();
+    draw_response_cache_settings(ui, state);
+}
+
+fn draw_response_cache_settings(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.heading(t("Caché de respuestas"));
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        t("Las consultas idénticas a un proveedor (mismo modelo, prompt y parámetros) se sirven desde disco sin volver a llamar a la API."),
+    );
+    ui.add_space(6.0);
+
+    let mut changed = ui
+        .checkbox(
+            &mut state.config.response_cache.enabled,
+            t("Reutilizar respuestas en caché"),
+        )
+        .changed();
+    ui.add_enabled_ui(state.config.response_cache.enabled, |ui| {
+        egui::Grid::new("response_cache_grid")
+            .num_columns(2)
+            .spacing(egui::vec2(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.label(t("Caducidad"));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut state.config.response_cache.ttl_hours)
+                            .clamp_range(1..=720)
+                            .suffix(" h"),
+                    )
+                    .changed();
+                ui.end_row();
+
+                ui.label(t("Máximo de entradas"));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut state.config.response_cache.max_entries)
+                            .clamp_range(1..=100_000),
+                    )
+                    .changed();
+                ui.end_row();
+
+                ui.label(t("Tamaño máximo"));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut state.config.response_cache.max_size_mb)
+                            .clamp_range(1..=10_240)
+                            .suffix(" MB"),
+                    )
+                    .changed();
+                ui.end_row();
+            });
+    });
+    if changed {
+        state.persist_config();
+    }
+
+    ui.add_space(8.0);
+    let usage = state.response_cache_usage();
+    let hits = state.response_cache.stats.hits();
+    let misses = state.response_cache.stats.misses();
+    let lookups = hits + misses;
+    let hit_rate = if lookups == 0 {
+        0.0
+    } else {
+        hits as f32 * 100.0 / lookups as f32
+    };
+    ui.label(tf(
+        "{0} respuestas guardadas · {1}",
+        &[&usage.entries.to_string(), &format_bytes(usage.bytes)],
+    ));
+    ui.label(tf(
+        "Aciertos: {0} · Fallos: {1} · Tasa de acierto: {2}%",
+        &[
+            &hits.to_string(),
+            &misses.to_string(),
+            &format!("{:.0}", hit_rate),
+        ],
+    ));
+
+    ui.horizontal(|ui| {
+        if ui.button(t("Actualizar")).clicked() {
+            state.response_cache.usage = None;
+        }
+        if ui
+            .add_enabled(usage.entries > 0, egui::Button::new(t("Vaciar caché")))
+            .clicked()
+        {
+            state.clear_response_cache();
+        }
+    });
+
+    if let Some(status) = &state.response_cache.status {
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
+
+fn draw_system_resources(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.label("Memory limit for cache");
+    if ui
+        .add(egui::Slider::new(&mut state.resource_memory_limit_gb, 1.0..=512.0).suffix(" GB"))
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    ui.label("Disk limit for cache");
+    if ui
+        .add(egui::Slider::new(&mut state.resource_disk_limit_gb, 8.0..=4096.0).suffix(" GB"))
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        format!(
+            "Current limits: {:.1} GB memory · {:.1} GB disk",
+            state.resource_memory_limit_gb, state.resource_disk_limit_gb
+        ),
+    );
+}
+
+fn draw_system_redaction(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = ui
+        .checkbox(
+            &mut state.config.redaction.enabled,
+            t("Redactar los prompts enviados a proveedores remotos"),
+        )
+        .changed();
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Las reglas usan la sintaxis de expresiones regulares del crate regex. Jarvis y los modelos locales reciben el texto original."),
+    );
+    ui.add_space(8.0);
+
+    let mut remove = None;
+    ui.add_enabled_ui(state.config.redaction.enabled, |ui| {
+        egui::Grid::new("redaction_rules")
+            .num_columns(5)
+            .striped(true)
+            .spacing(egui::vec2(10.0, 6.0))
+            .show(ui, |ui| {
+                for header in ["", "Nombre", "Patrón", "Reemplazo", ""] {
+                    ui.label(
+                        RichText::new(t(header))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                }
+                ui.end_row();
+
+                for (index, rule) in state.config.redaction.rules.iter_mut().enumerate() {
+                    changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(&mut rule.name).desired_width(140.0))
+                        .lost_focus();
+                    let pattern = ui.add(
+                        egui::TextEdit::singleline(&mut rule.pattern)
+                            .code_editor()
+                            .desired_width(320.0),
+                    );
+                    changed |= pattern.lost_focus();
+                    if let Some(error) = state.redaction.errors.get(&index) {
+                        pattern.on_hover_text(error.as_str());
+                    }
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(&mut rule.replacement).desired_width(100.0))
+                        .lost_focus();
+                    if ui
+                        .small_button(RichText::new(ICON_TRASH).font(theme::icon_font(12.0)))
+                        .on_hover_text(t("Eliminar regla"))
+                        .clicked()
+                    {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(6.0);
+        if ui.button(t("Añadir regla")).clicked() {
+            state.config.redaction.rules.push(RedactionRule {
+                name: t("Nueva regla").to_string(),
+                replacement: "[REDACTADO]".to_string(),
+                enabled: true,
+                ..RedactionRule::default()
+            });
+            changed = true;
+        }
+    });
+    if let Some(index) = remove {
+        state.config.redaction.rules.remove(index);
+        changed = true;
+    }
+    if changed {
+        state.persist_config();
+    }
+
+    let sample = state.redaction.sample.clone();
+    let segments = state.redaction.preview(&state.config.redaction, &sample);
+    for (index, error) in &state.redaction.errors {
+        let name = state
+            .config
+            .redaction
+            .rules
+            .get(*index)
+            .map(|rule| rule.name.as_str())
+            .unwrap_or_default();
+        ui.colored_label(
+            theme::color_danger(),
+            tf("La regla '{0}' no es válida: {1}", &[name, error]),
+        );
+    }
+
+    ui.add_space(12.0);
+    ui.strong(t("Probar las reglas"));
+    ui.add(
+        egui::TextEdit::multiline(&mut state.redaction.sample)
+            .hint_text(t("Pega un prompt para ver qué se redactaría"))
+            .desired_rows(3)
+            .desired_width(f32::INFINITY),
+    );
+    if !sample.trim().is_empty() {
+        ui.add_space(4.0);
+        egui::Frame::none()
+            .fill(Color32::from_rgb(26, 28, 32))
+            .stroke(theme::subtle_border(&state.theme))
+            .inner_margin(egui::Margin::same(8.0))
+            .show(ui, |ui| draw_redaction_preview(ui, &segments));
+    }
+}
+
+/// Periodos que se pueden consultar en el panel de uso, en días.
+const USAGE_RANGES: [u32; 3] = [7, 30, 90];
+
+fn draw_system_permissions(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Antes de que un workflow o un listener borre ficheros, haga git push, llame a un servicio externo o ejecute un comando se pide permiso. Aquí quedan los que se concedieron para siempre."),
+    );
+    if !state.permissions.queue.is_empty() {
+        ui.colored_label(
+            theme::color_danger(),
+            tf(
+                "{0} automatizaciones esperan permiso.",
+                &[&state.permissions.queue.len().to_string()],
+            ),
+        );
+    }
+    ui.add_space(8.0);
+
+    if state.config.automation_permissions.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("No hay permisos recordados: cada efecto se pregunta al ejecutarse."),
+        );
+        return;
+    }
+    let mut revoke = None;
+    egui::Grid::new("automation_permissions")
+        .num_columns(4)
+        .striped(true)
+        .spacing(egui::vec2(12.0, 6.0))
+        .show(ui, |ui| {
+            for header in ["Efecto", "Automatización", "Concedido", ""] {
+                ui.label(
+                    RichText::new(t(header))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            }
+            ui.end_row();
+
+            for (index, grant) in state.config.automation_permissions.iter().enumerate() {
+                ui.label(t(permissions::effect_label(grant.effect)));
+                match &grant.automation {
+                    Some(automation) => ui.label(RichText::new(automation).monospace()),
+                    None => ui.label(t("Cualquier automatización")),
+                };
+                ui.label(
+                    RichText::new(&grant.granted_at)
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+                if ui.small_button(t("Retirar")).clicked() {
+                    revoke = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(index) = revoke {
+        state.revoke_automation_permission(index);
+    }
+}
+
+fn draw_system_storage(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Las conversaciones, los borradores, los marcadores y la sesión guardada se cifran con AES-256-GCM. Lo que ya estaba en claro se migra al activarlo y se sigue leyendo sin cambios hasta entonces."),
+    );
+    ui.add_space(8.0);
+
+    let encryption = state.config.storage_encryption.clone();
+    let locked = vault::is_locked();
+    let state_label = match (encryption.enabled, locked) {
+        (false, _) => t("Sin cifrar"),
+        (true, true) => t("Cifrado · bloqueado"),
+        (true, false) => t("Cifrado · desbloqueado"),
+    };
+    ui.horizontal(|ui| {
+        ui.label(t("Estado"));
+        ui.strong(state_label);
+    });
+    if encryption.enabled {
+        let source = match encryption.key_source {
+            StorageKeySource::Keychain => t("Llavero del sistema"),
+            StorageKeySource::Passphrase => t("Frase de paso"),
+        };
+        ui.colored_label(theme::color_text_weak(), tf("Clave: {0}", &[source]));
+    }
+    ui.add_space(8.0);
+
+    if locked {
+        if encryption.key_source == StorageKeySource::Passphrase {
+            ui.add(
+                egui::TextEdit::singleline(&mut state.storage_encryption.passphrase)
+                    .password(true)
+                    .hint_text(t("Frase de paso")),
+            );
+        }
+        if ui.button(t("Desbloquear")).clicked() {
+            state.storage_encryption.status = Some(match state.unlock_storage() {
+                Ok(()) => t("Historial desbloqueado.").to_string(),
+                Err(err) => err,
+            });
+        }
+    } else {
+        ui.strong(if encryption.enabled {
+            t("Cambiar la clave")
+        } else {
+            t("Activar el cifrado")
+        });
+        ui.radio_value(
+            &mut state.storage_encryption.key_source,
+            StorageKeySource::Keychain,
+            t("Secreto guardado en el llavero del sistema"),
+        );
+        ui.radio_value(
+            &mut state.storage_encryption.key_source,
+            StorageKeySource::Passphrase,
+            t("Frase de paso en cada arranque"),
+        );
+        if state.storage_encryption.key_source == StorageKeySource::Passphrase {
+            egui::Grid::new("storage_encryption_grid")
+                .num_columns(2)
+                .spacing(egui::vec2(12.0, 8.0))
+                .show(ui, |ui| {
+                    ui.label(t("Frase de paso"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut state.storage_encryption.passphrase)
+                            .password(true),
+                    );
+                    ui.end_row();
+
+                    ui.label(t("Confirmación"));
+                    ui.add(
+                        egui::TextEdit::singleline(
+                            &mut state.storage_encryption.passphrase_confirm,
+                        )
+                        .password(true),
+                    );
+                    ui.end_row();
+                });
+            ui.colored_label(
+                theme::color_danger(),
+                t("Si olvidas la frase de paso no podrás recuperar el historial."),
+            );
+        }
+        ui.horizontal(|ui| {
+            let label = if encryption.enabled {
+                t("Cifrar con la nueva clave")
+            } else {
+                t("Cifrar el historial")
+            };
+            if ui.button(label).clicked() {
+                state.storage_encryption.status = Some(match state.enable_storage_encryption() {
+                    Ok(count) => tf(
+                        "Historial cifrado; {0} archivos migrados.",
+                        &[&count.to_string()],
+                    ),
+                    Err(err) => err,
+                });
+            }
+            if encryption.enabled && ui.button(t("Desactivar el cifrado")).clicked() {
+                state.storage_encryption.status = Some(match state.disable_storage_encryption() {
+                    Ok(count) => tf(
+                        "Cifrado desactivado; {0} archivos guardados en claro.",
+                        &[&count.to_string()],
+                    ),
+                    Err(err) => err,
+                });
+            }
+        });
+    }
+    if let Some(status) = &state.storage_encryption.status {
+        ui.add_space(6.0);
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+}
+
+fn draw_system_notifications(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    let policy = &mut state.config.notifications;
+    changed |= ui
+        .checkbox(&mut policy.quiet_hours_enabled, t("Horas de silencio"))
+        .changed();
+    ui.add_enabled_ui(policy.quiet_hours_enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(t("Desde"));
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut policy.quiet_start).desired_width(60.0))
+                .changed();
+            ui.label(t("hasta"));
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut policy.quiet_end).desired_width(60.0))
+                .changed();
+        });
+        changed |= ui
+            .checkbox(
+                &mut policy.errors_break_quiet_hours,
+                t("Mostrar los errores en pantalla aunque sea hora de silencio"),
+            )
+            .changed();
+    });
+    let quiet_now = state.in_quiet_hours();
+    let policy = &mut state.config.notifications;
+    ui.colored_label(
+        theme::color_text_weak(),
+        if quiet_now {
+            t("Ahora mismo es hora de silencio: los avisos solo quedan en el registro.")
+        } else {
+            t("Todos los avisos quedan siempre en el registro de actividad.")
+        },
+    );
+    ui.add_space(8.0);
+
+    egui::Grid::new("notification_routes")
+        .num_columns(3)
+        .spacing([16.0, 6.0])
+        .show(ui, |ui| {
+            ui.strong(t("Origen"));
+            ui.strong(t("Canal"));
+            ui.strong(t("Gravedad mínima"));
+            ui.end_row();
+            for source in NotificationSource::ALL {
+                let route = source.route_mut(policy);
+                ui.label(t(source.label()));
+                egui::ComboBox::from_id_source(("notification_channel", source.label()))
+                    .selected_text(t(notifications::channel_label(route.channel)))
+                    .show_ui(ui, |ui| {
+                        for channel in [
+                            NotificationChannel::LogOnly,
+                            NotificationChannel::Toast,
+                            NotificationChannel::Desktop,
+                        ] {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut route.channel,
+                                    channel,
+                                    t(notifications::channel_label(channel)),
+                                )
+                                .changed();
+                        }
+                    });
+                egui::ComboBox::from_id_source(("notification_severity", source.label()))
+                    .selected_text(t(notifications::severity_label(route.min_severity)))
+                    .show_ui(ui, |ui| {
+                        for severity in [
+                            NotificationSeverity::Info,
+                            NotificationSeverity::Warning,
+                            NotificationSeverity::Error,
+                        ] {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut route.min_severity,
+                                    severity,
+                                    t(notifications::severity_label(severity)),
+                                )
+                                .changed();
+                        }
+                    });
+                ui.end_row();
+            }
+        });
+
+    if changed {
+        state.persist_config();
+    }
+}
+
+fn draw_system_usage(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut enabled = state.config.usage_metrics.enabled;
+    if ui
+        .checkbox(&mut enabled, t("Registrar mis métricas de uso"))
+        .changed()
+    {
+        state.set_usage_metrics_enabled(enabled);
+    }
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Las métricas se guardan solo en este equipo y nunca se envían a ningún servidor."),
+    );
+    ui.horizontal(|ui| {
+        ui.label(t("Conservar"));
+        if ui
+            .add(
+                egui::DragValue::new(&mut state.config.usage_metrics.retention_days)
+                    .clamp_range(7..=365)
+                    .suffix(t(" días")),
+            )
+            .changed()
+        {
+            state.persist_config();
+        }
+        if ui
+            .add_enabled(
+                !state.usage_metrics.is_empty(),
+                egui::Button::new(t("Borrar métricas")),
+            )
+            .clicked()
+        {
+            state.clear_usage_metrics();
+        }
+    });
+    ui.add_space(8.0);
+
+    if state.usage_metrics.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Aún no hay métricas registradas."),
+        );
+        return;
+    }
+
+    let range_id = ui.id().with("usage_range");
+    let mut range = ui.data_mut(|data| *data.get_temp_mut_or(range_id, USAGE_RANGES[1]));
+    ui.horizontal(|ui| {
+        for days in USAGE_RANGES {
+            ui.selectable_value(&mut range, days, tf("{0} días", &[&days.to_string()]));
+        }
+    });
+    ui.data_mut(|data| data.insert_temp(range_id, range));
+
+    let summary = state
+        .usage_metrics
+        .summary(Local::now().date_naive(), range);
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        for (label, value) in [
+            ("Llamadas", summary.total_calls()),
+            ("Tokens", summary.total_tokens()),
+            ("Comandos", summary.total_commands()),
+        ] {
+            ui.vertical(|ui| {
+                ui.label(RichText::new(t(label)).color(theme::color_text_weak()));
+                ui.label(
+                    RichText::new(value.to_string())
+                        .color(theme::color_text_primary())
+                        .size(18.0)
+                        .strong(),
+                );
+            });
+            ui.add_space(24.0);
+        }
+    });
+
+    let labels: Vec<String> = summary
+        .days
+        .iter()
+        .map(|(day, _, _)| day.format("%d/%m").to_string())
+        .collect();
+    ui.add_space(8.0);
+    ui.label(RichText::new(t("Llamadas por día")).color(theme::color_text_weak()));
+    data_table::draw_series_chart(
+        ui,
+        data_table::ChartKind::Bars,
+        &labels,
+        &[(
+            t("Llamadas").to_string(),
+            summary
+                .days
+                .iter()
+                .map(|(_, calls, _)| *calls as f64)
+                .collect(),
+        )],
+    );
+    ui.add_space(6.0);
+    ui.label(RichText::new(t("Tokens por día")).color(theme::color_text_weak()));
+    data_table::draw_series_chart(
+        ui,
+        data_table::ChartKind::Lines,
+        &labels,
+        &[(
+            "Tokens".to_string(),
+            summary
+                .days
+                .iter()
+                .map(|(_, _, tokens)| *tokens as f64)
+                .collect(),
+        )],
+    );
+
+    ui.add_space(12.0);
+    ui.label(
+        RichText::new(t("Proveedores"))
+            .color(theme::color_text_primary())
+            .strong(),
+    );
+    let latency = |value: Option<u32>| {
+        value
+            .map(|ms| format!("{} ms", ms))
+            .unwrap_or_else(|| "—".to_string())
+    };
+    egui::Grid::new("usage_providers")
+        .striped(true)
+        .spacing(egui::vec2(14.0, 4.0))
+        .show(ui, |ui| {
+            for header in [
+                "Proveedor",
+                "Llamadas",
+                "Errores",
+                "Prompt / respuesta",
+                "p50",
+                "p90",
+                "p99",
+            ] {
+                ui.label(
+                    RichText::new(t(header))
+                        .color(theme::color_text_primary())
+                        .strong(),
+                );
+            }
+            ui.end_row();
+            for provider in &summary.providers {
+                ui.label(&provider.name);
+                ui.label(provider.calls.to_string());
+                ui.label(provider.errors.to_string());
+                ui.label(format!(
+                    "{} / {}",
+                    provider.prompt_tokens, provider.response_tokens
+                ));
+                ui.label(latency(provider.p50_ms));
+                ui.label(latency(provider.p90_ms));
+                ui.label(latency(provider.p99_ms));
+                ui.end_row();
+            }
+        });
+
+    if !summary.commands.is_empty() {
+        ui.add_space(12.0);
+        ui.label(
+            RichText::new(t("Comandos más usados"))
+                .color(theme::color_text_primary())
+                .strong(),
+        );
+        let top: Vec<_> = summary.commands.iter().take(10).collect();
+        data_table::draw_series_chart(
+            ui,
+            data_table::ChartKind::Bars,
+            &top.iter()
+                .map(|(name, _)| format!("/{}", name))
+                .collect::<Vec<_>>(),
+            &[(
+                t("Ejecuciones").to_string(),
+                top.iter().map(|(_, count)| *count as f64).collect(),
+            )],
+        );
+    }
+}
+
+fn draw_system_network(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::Grid::new("system_network_grid")
+        .num_columns(2)
+        .spacing(egui::vec2(12.0, 8.0))
+        .show(ui, |ui| {
+            ui.label("Proxy");
+            ui.add(
+                egui::TextEdit::singleline(&mut state.network_draft.proxy_url)
+                    .hint_text("http://proxy.empresa.local:8080"),
+            );
+            ui.end_row();
+
+            ui.label("Excepciones");
+            ui.add(
+                egui::TextEdit::singleline(&mut state.network_draft.no_proxy)
+                    .hint_text("localhost,127.0.0.1,.intranet"),
+            );
+            ui.end_row();
+
+            ui.label("Bundle de CA (PEM)");
+            ui.add(
+                egui::TextEdit::singleline(&mut state.network_draft.ca_bundle_path)
+                    .hint_text("/etc/ssl/certs/empresa.pem"),
+            );
+            ui.end_row();
+
+            ui.label("Timeout");
+            ui.add(
+                egui::DragValue::new(&mut state.network_draft.request_timeout_secs)
+                    .clamp_range(0..=600)
+                    .suffix(" s"),
+            );
+            ui.end_row();
+        });
+
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        "Un timeout de 0 s conserva el valor por defecto de cada proveedor. El descargador de Hugging Face Hub solo hereda el proxy; los certificados adicionales se aplican al resto de clientes.",
+    );
+
+    ui.add_space(8.0);
+    let dirty = state.network_draft != state.config.network;
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(dirty, egui::Button::new("Aplicar"))
+            .clicked()
+        {
+            let _ = state.apply_network_settings();
+        }
+        if ui
+            .add_enabled(dirty, egui::Button::new("Descartar cambios"))
+            .clicked()
+        {
+            state.network_draft = state.config.network.clone();
+            state.network_status = None;
+        }
+    });
+
+    if let Some(status) = &state.network_status {
+        ui.add_space(8.0);
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+
+    ui.add_space(12.0);
+    ui.separator();
+    ui.heading("Modo sin conexión");
+
+    let mut offline_mode = state.config.network.offline_mode;
+    if ui
+        .checkbox(&mut offline_mode, "Forzar modo sin conexión")
+        .on_hover_text("Las menciones a proveedores remotos no saldrán de este equipo.")
+        .changed()
+    {
+        state.set_offline_mode(offline_mode);
+    }
+
+    let mut queue = state.config.network.queue_offline_requests;
+    if ui
+        .checkbox(
+            &mut queue,
+            "Poner en cola las solicitudes remotas sin conexión",
+        )
+        .on_hover_text("Si se desactiva, Jarvis responde localmente en su lugar.")
+        .changed()
+    {
+        state.set_offline_queueing(queue);
+    }
+
+    let detected = if state.connectivity.online {
+        "Conectividad detectada: en línea".to_string()
+    } else {
+        "Conectividad detectada: sin conexión".to_string()
+    };
+    let detected = match state.connectivity.last_change.as_deref() {
+        Some(changed) => format!("{} (último cambio a las {})", detected, changed),
+        None => detected,
+    };
+    ui.colored_label(ui.visuals().weak_text_color(), detected);
+
+    let queued = state.chat.offline_queue.len();
+    if queued > 0 {
+        ui.horizontal(|ui| {
+            ui.label(format!("{} solicitudes en cola", queued));
+            if ui
+                .add_enabled(!state.is_offline(), egui::Button::new("Reenviar ahora"))
+                .clicked()
+            {
+                state.replay_offline_queue();
+            }
+            if ui.button("Descartar cola").clicked() {
+                let discarded = state.discard_offline_queue();
+                state.network_status =
+                    Some(format!("Se descartaron {} solicitudes en cola.", discarded));
+            }
+        });
+    }
+
+    ui.add_space(12.0);
+    ui.separator();
+    ui.heading("Flujo de eventos (WebSocket)");
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        "Difunde mensajes del chat, cambios de workflows, registros e instalaciones de modelos como eventos JSON para paneles y plugins externos.",
+    );
+
+    ui.checkbox(
+        &mut state.event_stream_draft.enabled,
+        "Publicar eventos en tiempo real",
+    );
+    ui.horizontal(|ui| {
+        ui.label("Dirección");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.event_stream_draft.bind_address)
+                .hint_text("127.0.0.1:8765"),
+        );
+    });
+    ui.horizontal(|ui| {
+        ui.label("Token");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.event_stream_draft.token)
+                .password(true)
+                .hint_text("Opcional: ?token= o Authorization: Bearer"),
+        );
+    });
+
+    let dirty = state.event_stream_draft != state.config.event_stream;
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(dirty, egui::Button::new("Aplicar"))
+            .clicked()
+        {
+            let _ = state.apply_event_stream_settings();
+        }
+        if ui
+            .add_enabled(dirty, egui::Button::new("Descartar cambios"))
+            .clicked()
+        {
+            state.event_stream_draft = state.config.event_stream.clone();
+        }
+    });
+
+    if let Some(server) = &state.event_stream.server {
+        ui.colored_label(
+            ui.visuals().weak_text_color(),
+            format!("Observadores conectados: {}", server.client_count()),
+        );
+    }
+    if let Some(status) = &state.event_stream.status {
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
+
+fn draw_system_job_queue(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.label(RichText::new(t("Límites por carril")).strong());
+    let mut changed = false;
+    let mut remove = None;
+    egui::Grid::new("job_queue_limits")
+        .num_columns(4)
+        .striped(true)
+        .spacing(egui::vec2(18.0, 6.0))
+        .show(ui, |ui| {
+            for header in ["Carril", "Simultáneas", "Por minuto", ""] {
+                ui.label(
+                    RichText::new(t(header))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            }
+            ui.end_row();
+
+            for (index, limit) in state.config.job_queue.lanes.iter_mut().enumerate() {
+                ui.label(RichText::new(&limit.lane).monospace());
+                let response =
+                    ui.add(egui::DragValue::new(&mut limit.max_concurrent).clamp_range(1..=32));
+                changed |= response.drag_stopped() || response.lost_focus();
+                let response = ui.add(
+                    egui::DragValue::new(&mut limit.per_minute)
+                        .clamp_range(0..=600)
+                        .suffix("/min"),
+                );
+                changed |= response.drag_stopped() || response.lost_focus();
+                if ui.small_button(t("Quitar")).clicked() {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    if let Some(index) = remove {
+        state.config.job_queue.lanes.remove(index);
+        changed = true;
+    }
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.jobs.lane_draft)
+                .hint_text("github")
+                .desired_width(140.0),
+        );
+        if ui.button(t("Añadir carril")).clicked() {
+            let lane = state.jobs.lane_draft.trim().to_lowercase();
+            let exists = state
+                .config
+                .job_queue
+                .lanes
+                .iter()
+                .any(|limit| limit.lane == lane);
+            if !lane.is_empty() && !exists {
+                state.config.job_queue.lanes.push(JobLaneLimit {
+                    lane,
+                    max_concurrent: 2,
+                    per_minute: 0,
+                });
+                changed = true;
+            }
+            state.jobs.lane_draft.clear();
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label(t("Resto de carriles"));
+        let response = ui.add(
+            egui::DragValue::new(&mut state.config.job_queue.default_max_concurrent)
+                .clamp_range(1..=32),
+        );
+        changed |= response.drag_stopped() || response.lost_focus();
+        ui.label(
+            RichText::new(t("llamadas simultáneas, sin límite por minuto"))
+                .color(theme::color_text_weak())
+                .size(12.0),
+        );
+    });
+    ui.label(
+        RichText::new(t(
+            "Los carriles de proveedores se llaman anthropic, openai y groq; las integraciones usan webhooks y github. Un límite por minuto de 0 no limita.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    if changed {
+        state.apply_job_queue_limits();
+    }
+
+    ui.add_space(12.0);
+    ui.separator();
+    ui.heading(tf(
+        "Llamadas activas: {0} en curso · {1} en cola",
+        &[
+            &state.jobs.count(JobStatus::Running).to_string(),
+            &state.jobs.count(JobStatus::Pending).to_string(),
+        ],
+    ));
+    let jobs: Vec<JobInfo> = state.jobs.active().cloned().collect();
+    if jobs.is_empty() {
+        ui.colored_label(
+            ui.visuals().weak_text_color(),
+            t("No hay llamadas esperando ni en curso."),
+        );
+    } else {
+        let mut cancel = None;
+        draw_job_grid(ui, "job_queue_active", &jobs, Some(&mut cancel));
+        if let Some(job_id) = cancel {
+            state.cancel_job(job_id);
+        }
+    }
+
+    if !state.jobs.finished.is_empty() {
+        ui.add_space(8.0);
+        let finished: Vec<JobInfo> = state.jobs.finished.iter().cloned().collect();
+        egui::CollapsingHeader::new(t("Terminadas recientemente"))
+            .id_source("job_queue_finished")
+            .show(ui, |ui| {
+                draw_job_grid(ui, "job_queue_finished_grid", &finished, None)
+            });
+    }
+}
+
+fn draw_job_grid(
+    ui: &mut egui::Ui,
+    id: &str,
+    jobs: &[JobInfo],
+    mut cancel: Option<&mut Option<u64>>,
+) {
+    egui::Grid::new(id)
+        .num_columns(7)
+        .striped(true)
+        .spacing(egui::vec2(18.0, 6.0))
+        .show(ui, |ui| {
+            for header in [
+                "Llamada",
+                "Carril",
+                "Prioridad",
+                "Estado",
+                "Encolada",
+                "Espera",
+                "",
+            ] {
+                ui.label(
+                    RichText::new(t(header))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            }
+            ui.end_row();
+
+            for job in jobs {
+                ui.label(&job.label);
+                ui.label(RichText::new(&job.lane).monospace());
+                ui.label(t(job.priority.label()));
+                ui.label(t(job.status.label()));
+                ui.label(&job.queued_at);
+                ui.label(format!("{:.1} s", job.waited().as_secs_f32()));
+                match cancel.as_deref_mut() {
+                    Some(cancel) => {
+                        let hint = if job.can_cancel() {
+                            t("Descarta la llamada; si ya está en curso se ignora su respuesta")
+                        } else {
+                            t("Esta llamada ya está en curso y no se puede interrumpir")
+                        };
+                        if ui
+                            .add_enabled(job.can_cancel(), egui::Button::new(t("Cancelar")))
+                            .on_hover_text(hint)
+                            .on_disabled_hover_text(hint)
+                            .clicked()
+                        {
+                            *cancel = Some(job.id);
+                        }
+                    }
+                    None => {
+                        ui.label("");
+                    }
+                }
+                ui.end_row();
+            }
+        });
+}
+
+fn draw_system_web_search(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    {
+        let search = &mut state.config.web_search;
+        egui::ComboBox::from_label(t("Buscador"))
+            .selected_text(search.backend.label())
+            .show_ui(ui, |ui| {
+                for candidate in WebSearchBackend::ALL {
+                    changed |= ui
+                        .selectable_value(&mut search.backend, candidate, candidate.label())
+                        .changed();
+                }
+            });
+
+        egui::Grid::new("web_search_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                match search.backend {
+                    WebSearchBackend::SearXng => {
+                        ui.label(t("URL de la instancia"));
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut search.searxng_url)
+                                    .hint_text("https://searx.example.org"),
+                            )
+                            .lost_focus();
+                    }
+                    WebSearchBackend::Brave => {
+                        ui.label("API key");
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut search.brave_api_key)
+                                    .password(true),
+                            )
+                            .lost_focus();
+                    }
+                    WebSearchBackend::Bing => {
+                        ui.label("API key");
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut search.bing_api_key).password(true),
+                            )
+                            .lost_focus();
+                    }
+                }
+                ui.end_row();
+
+                ui.label(t("Resultados"));
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut search.max_results)
+                            .clamp_range(1..=web_search::MAX_RESULTS),
+                    )
+                    .changed();
+                ui.end_row();
+            });
+
+        changed |= ui
+            .checkbox(
+                &mut search.expose_to_providers,
+                t("Ofrecer la búsqueda a los proveedores remotos como herramienta"),
+            )
+            .on_hover_text(t(
+                "El proveedor puede pedir una búsqueda en lugar de responder; se le vuelve a llamar con los resultados.",
+            ))
+            .changed();
+    }
+    if changed {
+        state.persist_config();
+    }
+
+    if state.config.web_search.backend == WebSearchBackend::SearXng {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("La instancia debe tener habilitado el formato JSON (search.formats en settings.yml)."),
+        );
+    }
+    if let Err(err) = crate::api::web_search::validate(&state.config.web_search) {
+        ui.colored_label(theme::color_text_weak(), format!("{:#}", err));
+    }
+
+    ui.add_space(10.0);
+    ui.strong(t("Probar una búsqueda"));
+    let searching = state.web_search.is_searching();
+    ui.horizontal(|ui| {
+        let width = (ui.available_width() - 130.0).max(200.0);
+        ui.add_sized(
+            [width, 24.0],
+            egui::TextEdit::singleline(&mut state.web_search.draft).hint_text(t("Consulta")),
+        );
+        if searching {
+            ui.spinner();
+        } else if ui
+            .add_enabled(
+                !state.web_search.draft.trim().is_empty(),
+                egui::Button::new(t("Buscar")),
+            )
+            .clicked()
+        {
+            let query = state.web_search.draft.clone();
+            let _ = state.start_web_search(&query);
+        }
+    });
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Los resultados aparecen en el chat, donde puedes ampliarlos y adjuntarlos. También puedes usar /search <consulta>."),
+    );
+    if let Some(status) = &state.web_search.status {
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
+
+fn draw_system_email(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    {
+        let smtp = &mut state.config.smtp;
+        egui::Grid::new("smtp_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(t("Servidor"));
+                ui.horizontal(|ui| {
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut smtp.host)
+                                .hint_text("smtp.example.org")
+                                .desired_width(220.0),
+                        )
+                        .lost_focus();
+                    changed |= ui.add(egui::DragValue::new(&mut smtp.port)).changed();
+                });
+                ui.end_row();
+
+                ui.label(t("Cifrado"));
+                egui::ComboBox::from_id_source("smtp_security")
+                    .selected_text(t(smtp.security.label()))
+                    .show_ui(ui, |ui| {
+                        for candidate in SmtpSecurity::ALL {
+                            if ui
+                                .selectable_value(
+                                    &mut smtp.security,
+                                    candidate,
+                                    t(candidate.label()),
+                                )
+                                .changed()
+                            {
+                                smtp.port = candidate.default_port();
+                                changed = true;
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(t("Usuario"));
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut smtp.username))
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Contraseña"));
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut smtp.password).password(true))
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Remitente"));
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut smtp.from_address)
+                            .hint_text("yo@example.org"),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Destinatarios habituales"));
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut smtp.default_recipients)
+                            .hint_text(t("Separados por comas")),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Ejecutable"));
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut smtp.curl_command).hint_text("curl"))
+                    .lost_focus();
+                ui.end_row();
+            });
+
+        ui.add_space(8.0);
+        ui.label(RichText::new(t("Plantillas")).strong());
+        ui.label(
+            RichText::new(t(
+                "{titulo} es el nombre del informe o resumen, {fecha} la fecha del envío y {contenido} su texto.",
+            ))
+            .color(theme::color_text_weak())
+            .size(12.0),
+        );
+        changed |= ui
+            .add(
+                egui::TextEdit::singleline(&mut smtp.subject_template)
+                    .hint_text(t("Asunto"))
+                    .desired_width(f32::INFINITY),
+            )
+            .lost_focus();
+        changed |= ui
+            .add(
+                egui::TextEdit::multiline(&mut smtp.body_template)
+                    .hint_text(t("Cuerpo"))
+                    .desired_rows(5)
+                    .desired_width(f32::INFINITY),
+            )
+            .lost_focus();
+    }
+    if changed {
+        state.persist_config();
+    }
+
+    if let Err(err) = email::validate(&state.config.smtp) {
+        ui.colored_label(theme::color_text_weak(), err);
+    }
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("El envío usa curl, incluido en macOS, Windows 10 y la mayoría de distribuciones Linux."),
+    );
+
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        if state.email.is_sending() {
+            ui.spinner();
+        } else if ui.button(t("Enviar un correo de prueba")).clicked() {
+            state.compose_email(
+                "Correo de prueba",
+                "Si lees esto, JungleMonkAI puede enviar correos con tu servidor SMTP.",
+                Vec::new(),
+            );
+        }
+    });
+    if let Some(status) = &state.email.status {
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
+
+fn draw_system_telegram(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    let mut restart = false;
+    {
+        let telegram = &mut state.config.telegram;
+        restart |= ui
+            .checkbox(&mut telegram.enabled, t("Activar el puente con Telegram"))
+            .changed();
+        egui::Grid::new("telegram_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(t("Token del bot"));
+                restart |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut telegram.bot_token)
+                            .password(true)
+                            .hint_text("123456:ABC-DEF…")
+                            .desired_width(260.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Hilo de la conversación"));
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut telegram.thread_title)
+                            .hint_text("Telegram")
+                            .desired_width(260.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+            });
+    }
+    ui.label(
+        RichText::new(t(
+            "Crea el bot con @BotFather y pega aquí su token. Escribe al bot desde el móvil: si el chat no está autorizado, su identificador aparece en la actividad.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+
+    ui.add_space(8.0);
+    ui.label(RichText::new(t("Chats autorizados")).strong());
+    let mut remove = None;
+    for (index, chat_id) in state.config.telegram.allowed_chat_ids.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(chat_id.to_string()).monospace().size(12.0));
+            if ui.small_button(t("Quitar")).clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        state.config.telegram.allowed_chat_ids.remove(index);
+        changed = true;
+    }
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.telegram.chat_ids_draft)
+                .hint_text("123456789")
+                .desired_width(180.0),
+        );
+        if ui.button(t("Autorizar")).clicked() {
+            match telegram::parse_chat_ids(&state.telegram.chat_ids_draft) {
+                Ok(ids) => {
+                    let allowed = &mut state.config.telegram.allowed_chat_ids;
+                    for id in ids {
+                        if !allowed.contains(&id) {
+                            allowed.push(id);
+                        }
+                    }
+                    state.telegram.chat_ids_draft.clear();
+                    changed = true;
+                }
+                Err(err) => state.telegram.status = Some(err),
+            }
+        }
+    });
+
+    if restart {
+        state.restart_telegram_bridge();
+    }
+    if changed || restart {
+        state.persist_config();
+    }
+
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        if state.telegram.is_running() && state.telegram.bot_name.is_none() {
+            ui.spinner();
+        }
+        if state.config.telegram.enabled && ui.button(t("Reconectar")).clicked() {
+            state.restart_telegram_bridge();
+        }
+        let pending = state.telegram.inbox.len() + usize::from(state.telegram.relay.is_some());
+        if pending > 0 {
+            ui.label(tf("{0} mensajes en curso", &[&pending.to_string()]));
+        }
+    });
+    if let Some(status) = &state.telegram.status {
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
+
+fn draw_system_mqtt(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    let mut restart = false;
+    {
+        let mqtt = &mut state.config.mqtt;
+        restart |= ui
+            .checkbox(&mut mqtt.enabled, t("Conectar con el broker MQTT"))
+            .changed();
+        egui::Grid::new("mqtt_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(t("Broker"));
+                ui.horizontal(|ui| {
+                    restart |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut mqtt.host)
+                                .hint_text("homeassistant.local")
+                                .desired_width(200.0),
+                        )
+                        .lost_focus();
+                    let response = ui.add(egui::DragValue::new(&mut mqtt.port));
+                    restart |= response.drag_stopped() || response.lost_focus();
+                });
+                ui.end_row();
+
+                ui.label(t("Usuario"));
+                restart |= ui
+                    .add(egui::TextEdit::singleline(&mut mqtt.username).desired_width(260.0))
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Contraseña"));
+                restart |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut mqtt.password)
+                            .password(true)
+                            .desired_width(260.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Identificador de cliente"));
+                restart |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut mqtt.client_id)
+                            .hint_text("junglemonkai")
+                            .desired_width(260.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Keep-alive"));
+                let response = ui.add(
+                    egui::DragValue::new(&mut mqtt.keep_alive_secs)
+                        .clamp_range(5..=600)
+                        .suffix(" s"),
+                );
+                restart |= response.drag_stopped() || response.lost_focus();
+                ui.end_row();
+
+                ui.label(t("Tema de resultados"));
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut mqtt.results_topic)
+                            .hint_text("junglemonkai/workflows")
+                            .desired_width(260.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+            });
+    }
+    ui.label(
+        RichText::new(t(
+            "Cada workflow ejecutado publica un resumen JSON en el tema de resultados. Los mensajes de los temas suscritos disparan los listeners de tipo «Mensaje MQTT» cuyo filtro encaja.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+
+    ui.add_space(8.0);
+    ui.label(RichText::new(t("Suscripciones")).strong());
+    let mut remove = None;
+    for (index, filter) in state.config.mqtt.subscriptions.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(filter).monospace().size(12.0));
+            if ui.small_button(t("Quitar")).clicked() {
+                remove = Some(index);
+            }
+        });
+    }
+    if let Some(index) = remove {
+        state.config.mqtt.subscriptions.remove(index);
+        restart = true;
+    }
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.mqtt.subscription_draft)
+                .hint_text("homeassistant/+/anomalia/#")
+                .desired_width(220.0),
+        );
+        if ui.button(t("Suscribir")).clicked() {
+            let filter = state.mqtt.subscription_draft.trim().to_string();
+            if !filter.is_empty() && !state.config.mqtt.subscriptions.contains(&filter) {
+                state.config.mqtt.subscriptions.push(filter);
+                restart = true;
+            }
+            state.mqtt.subscription_draft.clear();
+        }
+    });
+
+    if restart {
+        state.restart_mqtt_client();
+    }
+    if changed || restart {
+        state.persist_config();
+    }
+
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        if state.mqtt.is_running() && !state.mqtt.connected {
+            ui.spinner();
+        }
+        if state.config.mqtt.enabled {
+            if ui.button(t("Reconectar")).clicked() {
+                state.restart_mqtt_client();
+            }
+            if ui.button(t("Publicar prueba")).clicked() {
+                state.publish_mqtt_test();
+            }
+        }
+    });
+    if let Some(status) = &state.mqtt.status {
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+
+    if !state.mqtt.recent.is_empty() {
+        ui.add_space(8.0);
+        ui.label(RichText::new(t("Mensajes recibidos")).strong());
+        egui::ScrollArea::vertical()
+            .id_source("mqtt_recent")
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for message in &state.mqtt.recent {
+                    ui.label(
+                        RichText::new(format!("{} · {}", message.timestamp, message.topic))
+                            .monospace()
+                            .size(12.0),
+                    );
+                    ui.label(RichText::new(&message.payload).size(12.0));
+                }
+            });
+        ui.label(
+            RichText::new(tf(
+                "Se conservan los últimos {0} mensajes.",
+                &[&mqtt::MAX_RECENT.to_string()],
+            ))
+            .color(theme::color_text_weak())
+            .size(12.0),
+        );
+    }
+}
+
+fn draw_custom_commands_section(ui: &mut egui::Ui, state: &mut AppState, tab_index: usize) {
+    match tab_index {
+        0 => draw_custom_commands_configuration(ui, state),
+        1 => draw_custom_commands_documentation(ui, state),
+        2 => draw_custom_commands_activity(ui, state),
+        _ => draw_custom_commands_configuration(ui, state),
+    }
+}
+
+fn draw_custom_commands_configuration(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.heading(t("Paleta de comandos"));
+    ui.label(t(
+        "Asocia comandos de barra a funciones predefinidas, plantillas de prompt, workflows o guiones de comandos.",
+    ));
+
+    let mut remove_index = None;
+    let mut edit_index = None;
+    for (idx, command) in state.chat.custom_commands.iter().enumerate() {
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.strong(&command.trigger);
+                ui.label(format!("→ {}", command.target_label()));
+                if ui.button(RichText::new(t("Editar")).small()).clicked() {
+                    edit_index = Some(idx);
+                }
+                if ui.button(RichText::new(t("Eliminar")).small()).clicked() {
+                    remove_index = Some(idx);
+                }
+            });
+            let detail = match &command.behavior {
+                CustomCommandBehavior::Action => command.action.description().to_string(),
+                CustomCommandBehavior::Prompt { template, .. } => template.clone(),
+                CustomCommandBehavior::Workflow { workflow_id } => state
+                    .automation
+                    .workflows
+                    .workflows
+                    .iter()
+                    .find(|workflow| workflow.id == *workflow_id)
+                    .map(|workflow| workflow.name.clone())
+                    .unwrap_or_else(|| t("Workflow inexistente").to_string()),
+                CustomCommandBehavior::Script { commands } => commands
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" · "),
+            };
+            ui.colored_label(ui.visuals().weak_text_color(), detail);
+        });
+        ui.add_space(4.0);
+    }
+
+    if let Some(idx) = edit_index {
+        if let Some(command) = state.chat.custom_commands.get(idx) {
+            state.chat.command_draft = CustomCommandDraft::from_command(idx, command);
+        }
+    }
+    if let Some(idx) = remove_index {
+        state.remove_custom_command(idx);
+    }
+
+    ui.add_space(8.0);
+    draw_custom_command_editor(ui, state);
+
+    if let Some(feedback) = &state.chat.command_feedback {
+        ui.add_space(6.0);
+        ui.colored_label(ui.visuals().weak_text_color(), feedback);
+    }
+
+    ui.add_space(8.0);
+    if ui
+        .button(t("Funciones disponibles"))
+        .on_hover_text("Consulta documentación detallada y ejemplos")
+        .clicked()
+    {
+        state.chat.show_functions_modal = true;
+    }
+}
+
+/// Formulario para crear o modificar un comando, con vista previa de la plantilla y prueba
+/// en vivo contra el estado actual.
+fn draw_custom_command_editor(ui: &mut egui::Ui, state: &mut AppState) {
+    let actions: Vec<CustomCommandAction> = state.command_registry.actions().to_vec();
+    let workflows: Vec<(u32, String)> = state
+        .automation
+        .workflows
+        .workflows
+        .iter()
+        .map(|workflow| (workflow.id, workflow.name.clone()))
+        .collect();
+
+    let mut save = false;
+    let mut test = false;
+    let mut cancel = false;
+    let draft = &mut state.chat.command_draft;
+    let title = if draft.editing.is_some() {
+        t("Editar comando")
+    } else {
+        t("Nuevo comando")
+    };
+
+    ui.group(|ui| {
+        ui.label(RichText::new(title).strong());
+        egui::Grid::new("custom_command_editor")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(t("Disparador"));
+                ui.add(egui::TextEdit::singleline(&mut draft.trigger).hint_text("/resumen"));
+                ui.end_row();
+
+                ui.label(t("Tipo"));
+                let current = draft.behavior.kind_label();
+                egui::ComboBox::from_id_source("custom_command_kind")
+                    .selected_text(t(current))
+                    .show_ui(ui, |ui| {
+                        let options = [
+                            CustomCommandBehavior::Action,
+                            CustomCommandBehavior::Prompt {
+                                provider: None,
+                                template: String::new(),
+                            },
+                            CustomCommandBehavior::Workflow {
+                                workflow_id: workflows.first().map_or(0, |(id, _)| *id),
+                            },
+                            CustomCommandBehavior::Script {
+                                commands: String::new(),
+                            },
+                        ];
+                        for option in options {
+                            let selected = option.kind_label() == current;
+                            if ui
+                                .selectable_label(selected, t(option.kind_label()))
+                                .clicked()
+                                && !selected
+                            {
+                                draft.behavior = option;
+                            }
+                        }
+                    });
+                ui.end_row();
+
+                match &mut draft.behavior {
+                    CustomCommandBehavior::Action => {
+                        ui.label(t("Función"));
+                        egui::ComboBox::from_id_source("new_custom_command_action")
+                            .selected_text(draft.action.label())
+                            .show_ui(ui, |ui| {
+                                for action in &actions {
+                                    ui.selectable_value(
+                                        &mut draft.action,
+                                        *action,
+                                        format!("{} — {}", action.label(), action.description()),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    }
+                    CustomCommandBehavior::Prompt { provider, template } => {
+                        ui.label(t("Proveedor"));
+                        let selected = provider
+                            .as_deref()
+                            .and_then(RemoteProviderKind::from_short_code)
+                            .map(|kind| kind.display_name())
+                            .unwrap_or("Jarvis (local)");
+                        egui::ComboBox::from_id_source("custom_command_provider")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(provider, None, "Jarvis (local)");
+                                for kind in [
+                                    RemoteProviderKind::Anthropic,
+                                    RemoteProviderKind::OpenAi,
+                                    RemoteProviderKind::Groq,
+                                ] {
+                                    ui.selectable_value(
+                                        provider,
+                                        Some(kind.short_code().to_string()),
+                                        kind.display_name(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(t("Plantilla"));
+                        ui.add(
+                            egui::TextEdit::multiline(template)
+                                .desired_rows(4)
+                                .hint_text("Resume {args} en tres viñetas."),
+                        );
+                        ui.end_row();
+                    }
+                    CustomCommandBehavior::Workflow { workflow_id } => {
+                        ui.label(t("Workflow"));
+                        let selected = workflows
+                            .iter()
+                            .find(|(id, _)| id == workflow_id)
+                            .map(|(id, name)| format!("#{} {}", id, name))
+                            .unwrap_or_else(|| t("Workflow inexistente").to_string());
+                        egui::ComboBox::from_id_source("custom_command_workflow")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                for (id, name) in &workflows {
+                                    ui.selectable_value(
+                                        workflow_id,
+                                        *id,
+                                        format!("#{} {}", id, name),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    }
+                    CustomCommandBehavior::Script { commands } => {
+                        ui.label(t("Comandos"));
+                        ui.add(
+                            egui::TextEdit::multiline(commands)
+                                .desired_rows(4)
+                                .code_editor()
+                                .hint_text("/models provider={0}\n/stats"),
+                        );
+                        ui.end_row();
+                    }
+                }
+            });
+
+        if !matches!(draft.behavior, CustomCommandBehavior::Action) {
+            ui.label(
+                RichText::new(t(
+                    "Marcadores: {args}, {0}, {1}…, {input} (salida de un pipeline) y {clave} para argumentos clave=valor.",
+                ))
+                .color(theme::color_text_weak())
+                .size(11.0),
+            );
+        }
+
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            ui.label(t("Argumentos de prueba"));
+            ui.add(
+                egui::TextEdit::singleline(&mut draft.test_arguments)
+                    .hint_text("texto clave=valor")
+                    .desired_width(220.0),
+            );
+            if ui.button(t("Probar")).clicked() {
+                test = true;
+            }
+        });
+
+        // La vista previa se recalcula en cada fotograma con los argumentos de prueba.
+        let template = match &draft.behavior {
+            CustomCommandBehavior::Prompt { template, .. } => Some(template),
+            CustomCommandBehavior::Script { commands } => Some(commands),
+            _ => None,
+        };
+        if let Some(template) = template.filter(|template| !template.trim().is_empty()) {
+            let invocation = CommandInvocation::parse(&format!("/_ {}", draft.test_arguments));
+            match fill_template(template, &invocation) {
+                Ok(preview) => {
+                    ui.label(RichText::new(t("Vista previa")).size(11.0).strong());
+                    ui.label(RichText::new(preview).monospace().size(11.0));
+                }
+                Err(missing) => {
+                    ui.colored_label(
+                        Color32::from_rgb(255, 196, 0),
+                        tf("Faltan valores para: {0}", &[&missing.join(", ")]),
+                    );
+                }
+            }
+        }
+
+        if !draft.test_output.is_empty() {
+            ui.add_space(4.0);
+            egui::Frame::none()
+                .fill(Color32::from_rgb(28, 30, 36))
+                .rounding(egui::Rounding::same(8.0))
+                .inner_margin(egui::Margin::symmetric(10.0, 8.0))
+                .show(ui, |ui| {
+                    for line in &draft.test_output {
+                        ui.label(RichText::new(line).monospace().size(11.0));
+                    }
+                });
+        }
+
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            let label = if draft.editing.is_some() {
+                t("Guardar cambios")
+            } else {
+                t("Añadir comando")
+            };
+            if ui.button(label).clicked() {
+                save = true;
+            }
+            if draft.editing.is_some() && ui.button(t("Cancelar")).clicked() {
+                cancel = true;
+            }
+        });
+    });
+
+    if test {
+        state.test_custom_command_draft();
+    }
+    if save {
+        if let Err(err) = state.save_custom_command_draft() {
+            state.chat.command_feedback = Some(err);
+        }
+    }
+    if cancel {
+        state.chat.command_draft = CustomCommandDraft::default();
+    }
+}
+
+fn draw_custom_commands_documentation(ui: &mut egui::Ui, state: &AppState) {
+    ui.heading("Documentación de comandos personalizados");
+    ui.label(
+        RichText::new("Cada comando ejecuta una acción predefinida con parámetros opcionales.")
+            .color(theme::color_text_weak()),
+    );
+
+    ui.add_space(12.0);
+    for action in state.command_registry.actions() {
+        let action = *action;
+        let doc = action.documentation();
+        egui::Frame::none()
+            .fill(Color32::from_rgb(34, 36, 42))
+            .stroke(theme::subtle_border(&state.theme))
+            .rounding(egui::Rounding::same(12.0))
+            .inner_margin(egui::Margin::symmetric(14.0, 12.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(doc.signature)
+                            .monospace()
+                            .color(theme::color_text_primary())
+                            .strong(),
+                    );
+                    ui.add_space(ui.available_width());
+                    ui.label(
+                        RichText::new(action.label())
+                            .color(theme::color_text_weak())
+                            .monospace()
+                            .size(11.0),
+                    );
+                });
+                ui.label(
+                    RichText::new(doc.summary)
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                );
+
+                if !doc.parameters.is_empty() {
+                    ui.add_space(6.0);
+                    ui.label(
+                        RichText::new("Parámetros disponibles")
+                            .color(theme::color_text_primary())
+                            .size(11.0)
+                            .strong(),
+                    );
+                    for parameter in doc.parameters {
+                        ui.label(
+                            RichText::new(format!("• {parameter}"))
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                    }
+                }
+
+                if !doc.examples.is_empty() {
+                    ui.add_space(6.0);
+                    ui.label(
+                        RichText::new("Ejemplos")
+                            .color(theme::color_text_primary())
+                            .size(11.0)
+                            .strong(),
+                    );
+                    for example in doc.examples {
+                        ui.label(
+                            RichText::new(*example)
+                                .color(theme::color_text_weak())
+                                .monospace()
+                                .size(11.0),
+                        );
+                    }
+                }
+            });
+        ui.add_space(8.0);
+    }
+
+    if state.chat.custom_commands.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "Aún no hay comandos personalizados definidos.",
+        );
+    }
+}
+
+fn draw_custom_commands_activity(ui: &mut egui::Ui, state: &AppState) {
+    ui.heading("Actividad de comandos");
+    if let Some(feedback) = &state.chat.command_feedback {
+        ui.label(
+            RichText::new(format!("Última acción: {feedback}"))
+                .color(theme::color_text_primary())
+                .size(12.0),
+        );
+    } else {
+        ui.label(
+            RichText::new("No hay actividad reciente registrada.")
+                .color(theme::color_text_weak())
+                .size(12.0),
+        );
+    }
+
+    ui.add_space(10.0);
+    if state.chat.custom_commands.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "Agrega comandos personalizados para comenzar a registrar actividad.",
+        );
+        return;
+    }
+
+    ui.label(
+        RichText::new("Comandos configurados actualmente")
+            .color(theme::color_text_primary())
+            .strong(),
+    );
+
+    for command in &state.chat.custom_commands {
+        egui::Frame::none()
+            .fill(Color32::from_rgb(34, 36, 42))
+            .stroke(theme::subtle_border(&state.theme))
+            .rounding(egui::Rounding::same(10.0))
+            .inner_margin(egui::Margin::symmetric(12.0, 10.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(&command.trigger)
+                            .color(theme::color_text_primary())
+                            .strong()
+                            .size(13.0)
+                            .monospace(),
+                    );
+                    ui.add_space(ui.available_width());
+                    ui.label(
+                        RichText::new(command.action.label())
+                            .color(theme::color_text_weak())
+                            .monospace()
+                            .size(11.0),
+                    );
+                });
+                ui.label(
+                    RichText::new(command.action.description())
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            });
+        ui.add_space(6.0);
+    }
+}
+
+const ACCESSIBILITY_SHORTCUTS: &[(&str, &str)] = &[
+    ("↑ / ↓", "Mensaje anterior o siguiente del historial"),
+    ("Inicio / Fin", "Primer o último mensaje del historial"),
+    ("Intro", "Responder al mensaje resaltado"),
+    ("Esc", "Quitar el resaltado del historial"),
+    ("Tab / Mayús+Tab", "Recorrer los controles de la vista"),
+    ("Ctrl + / Ctrl -", "Aumentar o reducir la escala"),
+];
+
+fn draw_customization_accessibility(ui: &mut egui::Ui, state: &mut AppState) {
+    let tokens = state.theme.clone();
+
+    let mut scale = state.config.accessibility.ui_scale;
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(t("Escala de la interfaz")).color(tokens.palette.text_primary));
+        let slider = ui.add(
+            egui::Slider::new(
+                &mut scale,
+                crate::state::UI_SCALE_MIN..=crate::state::UI_SCALE_MAX,
+            )
+            .step_by(0.05)
+            .custom_formatter(|value, _| format!("{:.0}%", value * 100.0)),
+        );
+        if slider.drag_stopped() || (slider.changed() && !slider.dragged()) {
+            state.set_ui_scale(scale);
+        }
+        if ui.button(t("Restablecer")).clicked() {
+            state.set_ui_scale(1.0);
+        }
+    });
+
+    ui.add_space(8.0);
+    let mut reduced_motion = state.config.accessibility.reduced_motion;
+    if ui
+        .checkbox(&mut reduced_motion, t("Reducir animaciones"))
+        .on_hover_text(t(
+            "Desactiva las transiciones de paneles, menús y desplazamientos.",
+        ))
+        .changed()
+    {
+        state.set_reduced_motion(reduced_motion);
+    }
+
+    let mut keyboard_navigation = state.config.accessibility.keyboard_navigation;
+    if ui
+        .checkbox(&mut keyboard_navigation, t("Navegación por teclado"))
+        .on_hover_text(t(
+            "Permite recorrer la barra lateral y el historial del chat con las flechas.",
+        ))
+        .changed()
+    {
+        state.set_keyboard_navigation(keyboard_navigation);
+    }
+
+    ui.add_space(12.0);
+    ui.label(
+        RichText::new(t("Atajos de teclado"))
+            .color(tokens.palette.text_primary)
+            .strong(),
+    );
+    egui::Grid::new("accessibility_shortcuts")
+        .num_columns(2)
+        .spacing(egui::vec2(16.0, 4.0))
+        .show(ui, |ui| {
+            for (keys, description) in ACCESSIBILITY_SHORTCUTS {
+                ui.label(
+                    RichText::new(*keys)
+                        .monospace()
+                        .color(tokens.palette.primary),
+                );
+                ui.label(RichText::new(t(description)).color(tokens.palette.text_weak));
+                ui.end_row();
+            }
+        });
+    ui.add_space(6.0);
+    if ui.button(t("Configurar atajos")).clicked() {
+        state.activate_navigation_target(NavigationTarget::preference(
+            PreferencePanel::CustomizationKeybindings,
+        ));
+    }
+}
+
+/// Tabla de acciones con su combinación, captura de nuevas combinaciones y avisos de
+/// conflicto con otras acciones o con los atajos fijos.
+fn draw_customization_keybindings(ui: &mut egui::Ui, state: &mut AppState) {
+    let tokens = state.theme.clone();
+    let warning = Color32::from_rgb(255, 196, 0);
+
+    let mut enter_sends = state.config.keybindings.enter_sends;
+    if ui
+        .checkbox(&mut enter_sends, t("Intro envía el mensaje"))
+        .on_hover_text(t(
+            "Si se desactiva, Intro añade un salto de línea y el mensaje se envía con el atajo de envío.",
+        ))
+        .changed()
+    {
+        state.set_enter_sends(enter_sends);
+    }
+    ui.add_space(8.0);
+
+    let conflicts = keybindings::conflicts(&state.config.keybindings);
+    let mut capture = None;
+    let mut restore = None;
+    egui::Grid::new("keybindings_grid")
+        .num_columns(4)
+        .striped(true)
+        .spacing(egui::vec2(16.0, 6.0))
+        .show(ui, |ui| {
+            for header in ["Acción", "Categoría", "Atajo", ""] {
+                ui.label(
+                    RichText::new(t(header))
+                        .color(tokens.palette.text_weak)
+                        .strong(),
+                );
+            }
+            ui.end_row();
+
+            for action in ShortcutAction::ALL {
+                let chord = keybindings::binding(&state.config.keybindings, action);
+                let conflict = conflicts
+                    .iter()
+                    .find(|conflict| conflict.actions.contains(&action));
+                ui.label(RichText::new(t(action.label())).color(tokens.palette.text_primary));
+                ui.label(RichText::new(t(action.category())).color(tokens.palette.text_weak));
+
+                let capturing = state.keybindings.capturing == Some(action);
+                let text = if capturing {
+                    t("Pulsa una combinación… (Esc cancela)").to_string()
+                } else {
+                    chord.to_string()
+                };
+                let mut label = RichText::new(text).monospace();
+                if conflict.is_some() {
+                    label = label.color(warning);
+                }
+                let response = ui.selectable_label(capturing, label);
+                let response = match conflict {
+                    Some(conflict) => {
+                        response.on_hover_text(conflict_description(conflict, action))
+                    }
+                    None => response.on_hover_text(t("Haz clic para reasignar")),
+                };
+                if response.clicked() {
+                    capture = Some(action);
+                }
+
+                if chord != action.default_chord() {
+                    if ui
+                        .small_button(t("Predeterminado"))
+                        .on_hover_text(action.default_chord().to_string())
+                        .clicked()
+                    {
+                        restore = Some(action);
+                    }
+                } else {
+                    ui.label("");
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(action) = capture {
+        state.keybindings.capturing = Some(action);
+        state.keybindings.status = None;
+    }
+    if let Some(action) = restore {
+        state.set_keybinding(action, action.default_chord());
+    }
+
+    ui.add_space(8.0);
+    if !conflicts.is_empty() {
+        ui.colored_label(
+            warning,
+            tf(
+                "{0} combinaciones en conflicto. Solo se ejecuta la primera acción de la lista.",
+                &[&conflicts.len().to_string()],
+            ),
+        );
+    }
+    ui.horizontal(|ui| {
+        if ui.button(t("Restablecer todos")).clicked() {
+            state.reset_keybindings();
+        }
+        if ui.button(t("Ver chuleta")).clicked() {
+            state.keybindings.show_cheatsheet = true;
+        }
+    });
+    if let Some(status) = &state.keybindings.status {
+        ui.colored_label(tokens.palette.text_weak, status);
+    }
+}
+
+fn conflict_description(
+    conflict: &keybindings::KeybindingConflict,
+    action: ShortcutAction,
+) -> String {
+    let others: Vec<&str> = conflict
+        .actions
+        .iter()
+        .filter(|other| **other != action)
+        .map(|other| t(other.label()))
+        .collect();
+    let mut parts = Vec::new();
+    if !others.is_empty() {
+        parts.push(tf("También asignado a: {0}", &[&others.join(", ")]));
+    }
+    if let Some(reserved) = conflict.reserved {
+        parts.push(tf("Coincide con un atajo fijo: {0}", &[t(reserved)]));
+    }
+    parts.join("\n")
+}
+
+fn draw_customization_appearance(ui: &mut egui::Ui, state: &mut AppState) {
+    let tokens = state.theme.clone();
+    let info_frame = egui::Frame::none()
+        .fill(tokens.palette.panel_background)
+        .stroke(theme::subtle_border(&tokens))
+        .rounding(tokens.rounding.widget)
+        .inner_margin(egui::Margin {
+            left: 20.0,
+            right: 20.0,
+            top: 16.0,
+            bottom: 16.0,
+        });
+
+    info_frame.show(ui, |ui| {
+        ui.vertical(|ui| {
+            ui.label(
+                RichText::new(t("Tema de la interfaz"))
+                    .color(tokens.palette.text_primary)
+                    .strong()
+                    .size(tokens.typography.title.size),
+            );
+            ui.add_space(tokens.spacing.item_spacing.y);
+            ui.label(
+                RichText::new(t(
+                    "Alterna entre presets claro y oscuro inspirados en los esquemas de VSCode.",
+                ))
+                .color(tokens.palette.text_weak)
+                .size(tokens.typography.body.size),
+            );
+        });
+    });
+
+    ui.add_space(tokens.spacing.item_spacing.y * 2.0);
+
+    ui.horizontal(|ui| {
+        ui.spacing_mut().item_spacing.x = tokens.spacing.item_spacing.x;
+        let options = [
+            (
+                ThemePreset::Dark,
+                "Tema oscuro",
+                "Contraste alto con paneles profundos y resaltes eléctricos.",
+            ),
+            (
+                ThemePreset::Light,
+                "Tema claro",
+                "Fondo luminoso con bordes suaves para entornos bien iluminados.",
+            ),
+        ];
+
+        for (preset, title, description) in options {
+            let selected = state.config.theme == preset;
+            let response = theme_option_card(ui, &tokens, selected, t(title), t(description));
+            if response.clicked() {
+                state.set_theme_preset(preset);
+            }
+        }
+    });
+
+    ui.add_space(tokens.spacing.item_spacing.y * 3.0);
+    draw_language_selector(ui, state, &tokens);
+
+    ui.add_space(tokens.spacing.item_spacing.y * 3.0);
+    draw_layout_presets(ui, state, &tokens);
+}
+
+fn draw_language_selector(ui: &mut egui::Ui, state: &mut AppState, tokens: &ThemeTokens) {
+    ui.label(
+        RichText::new(t("Idioma de la interfaz"))
+            .color(tokens.palette.text_primary)
+            .strong()
+            .size(tokens.typography.title.size),
+    );
+    ui.add_space(tokens.spacing.item_spacing.y);
+    ui.label(
+        RichText::new(t(
+            "El cambio se aplica al instante; los textos sin traducir se muestran en su idioma original.",
+        ))
+        .color(tokens.palette.text_weak)
+        .size(tokens.typography.body.size),
+    );
+    ui.add_space(tokens.spacing.item_spacing.y);
+
+    let mut selected = state.config.language;
+    egui::ComboBox::from_id_source("interface_language")
+        .selected_text(selected.native_name())
+        .show_ui(ui, |ui| {
+            for language in i18n::Language::ALL {
+                ui.selectable_value(
+                    &mut selected,
+                    language,
+                    format!("{} ({})", language.native_name(), language.code()),
+                );
+            }
+        });
+    if selected != state.config.language {
+        state.set_language(selected);
+    }
+}
+
+fn draw_layout_presets(ui: &mut egui::Ui, state: &mut AppState, tokens: &ThemeTokens) {
+    ui.label(
+        RichText::new(t("Distribución de paneles"))
+            .color(tokens.palette.text_primary)
+            .strong()
+            .size(tokens.typography.title.size),
+    );
+    ui.add_space(tokens.spacing.item_spacing.y);
+    ui.label(
+        RichText::new(t(
+            "Arrastra los bordes de los paneles para redimensionarlos; la distribución se restaura al volver a abrir la aplicación.",
+        ))
+        .color(tokens.palette.text_weak)
+        .size(tokens.typography.body.size),
+    );
+    ui.add_space(tokens.spacing.item_spacing.y * 2.0);
+
+    let current = state.layout_snapshot();
+    let mut apply = None;
+    let mut delete = None;
+    egui::Grid::new("layout_presets_grid")
+        .num_columns(3)
+        .spacing([16.0, 8.0])
+        .show(ui, |ui| {
+            for (index, preset) in state.config.layout.presets.iter().enumerate() {
+                let active = LayoutSnapshot {
+                    composer_height: preset.layout.composer_height,
+                    ..current.clone()
+                } == preset.layout;
+                let name = if active {
+                    RichText::new(format!("{} · {}", preset.name, t("activa"))).strong()
+                } else {
+                    RichText::new(&preset.name)
+                };
+                ui.label(name.color(tokens.palette.text_primary));
+                ui.label(
+                    RichText::new(layout_summary(&preset.layout))
+                        .color(tokens.palette.text_weak)
+                        .size(12.0),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button(t("Aplicar")).clicked() {
+                        apply = Some(index);
+                    }
+                    let delete_button =
+                        ui.button(RichText::new(ICON_TRASH).font(theme::icon_font(13.0)));
+                    if theme::icon_button_label(delete_button, t("Eliminar distribución")).clicked()
+                    {
+                        delete = Some(index);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+    if let Some(index) = apply {
+        state.apply_layout_preset(index);
+    }
+    if let Some(index) = delete {
+        state.delete_layout_preset(index);
+    }
+
+    ui.add_space(tokens.spacing.item_spacing.y * 2.0);
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut state.layout_preset_name)
+                .hint_text(t("Nombre de la distribución"))
+                .desired_width(220.0),
+        );
+        let can_save = !state.layout_preset_name.trim().is_empty();
+        if ui
+            .add_enabled(
+                can_save,
+                egui::Button::new(t("Guardar distribución actual")),
+            )
+            .clicked()
+        {
+            let name = state.layout_preset_name.clone();
+            if state.save_layout_preset(&name).is_ok() {
+                state.layout_preset_name.clear();
+            }
+        }
+        if ui
+            .button(t("Modo zen"))
+            .on_hover_text(t("Oculta cabecera y paneles laterales (F11)"))
+            .clicked()
+        {
+            state.toggle_zen_mode();
+        }
+    });
+}
+
+fn layout_summary(layout: &LayoutSnapshot) -> String {
+    let panel = |visible: bool, collapsed: bool, width: f32| {
+        if !visible {
+            t("oculto").to_string()
+        } else if collapsed {
+            t("plegado").to_string()
+        } else {
+            format!("{:.0}px", width)
+        }
+    };
+    let navigation = panel(
+        layout.show_navigation,
+        layout.navigation_collapsed,
+        layout.navigation_width,
+    );
+    let resources = panel(
+        layout.show_resource_panel,
+        layout.resource_collapsed,
+        layout.resource_width,
+    );
+    i18n::tf("Navegación {0} · Recursos {1}", &[&navigation, &resources])
+}
+
+fn theme_option_card(
+    ui: &mut egui::Ui,
+    tokens: &ThemeTokens,
+    selected: bool,
+    title: &str,
+    description: &str,
+) -> egui::Response {
+    let desired = egui::vec2(240.0, 132.0);
+    let (rect, response) = ui.allocate_exact_size(desired, egui::Sense::click());
+
+    let (fill, border_color) = if selected {
+        (tokens.states.focus.background, tokens.states.focus.border)
+    } else if response.hovered() {
+        (tokens.states.hover.background, tokens.states.hover.border)
+    } else {
+        (tokens.palette.secondary_background, tokens.palette.border)
+    };
+
+    let painter = ui.painter_at(rect);
+    painter.rect(
+        rect,
+        tokens.rounding.widget,
+        fill,
+        egui::Stroke::new(1.0, border_color),
+    );
+
+    let mut content = ui.child_ui(
+        rect.shrink2(egui::vec2(18.0, 16.0)),
+        egui::Layout::top_down(egui::Align::LEFT),
+    );
+
+    content.label(
+        RichText::new(title)
+            .color(if selected {
+                tokens.states.focus.foreground
+            } else {
+                tokens.palette.text_primary
+            })
+            .size(tokens.typography.body.size)
+            .strong(),
+    );
+    content.add_space(tokens.spacing.item_spacing.y * 0.5);
+    content.label(
+        RichText::new(description)
+            .color(tokens.palette.text_weak)
+            .size(tokens.typography.body_small.size),
+    );
+
+    response
+}
+
+fn draw_embedding_settings(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.strong(t("Modelo de embeddings"));
+    ui.label(
+        RichText::new(t(
+            "Los embeddings alimentan la búsqueda semántica de la cabecera y los recuerdos que se adjuntan a los prompts.",
+        ))
+        .color(theme::color_text_weak()),
+    );
+
+    let mut backend = state.config.embeddings.backend;
+    egui::ComboBox::from_label(t("Backend"))
+        .selected_text(t(backend.label()))
+        .show_ui(ui, |ui| {
+            for candidate in EmbeddingBackend::ALL {
+                ui.selectable_value(&mut backend, candidate, t(candidate.label()));
+            }
+        });
+    if backend != state.config.embeddings.backend {
+        state.set_embedding_backend(backend);
+    }
+
+    let mut changed = false;
+    if backend == EmbeddingBackend::OpenAi {
+        ui.horizontal(|ui| {
+            ui.label(t("Modelo"));
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(&mut state.config.embeddings.openai_model)
+                        .hint_text("text-embedding-3-small"),
+                )
+                .lost_focus();
+        });
+    }
+    changed |= ui
+        .checkbox(
+            &mut state.config.embeddings.semantic_search,
+            t("Búsqueda semántica en la conversación"),
+        )
+        .changed();
+    changed |= ui
+        .add(
+            egui::Slider::new(&mut state.config.embeddings.recall_messages, 0..=8)
+                .text(t("Recuerdos por prompt")),
+        )
+        .on_hover_text(t(
+            "Mensajes antiguos más parecidos al prompt que se envían como memoria. Requiere la memoria contextual activa; 0 la desactiva.",
+        ))
+        .changed();
+    if changed {
+        state.persist_config();
+    }
+
+    ui.horizontal(|ui| {
+        if ui.button(t("Probar embeddings")).clicked() {
+            state.test_embeddings();
+        }
+        ui.colored_label(
+            theme::color_text_weak(),
+            tf(
+                "{0} textos indexados",
+                &[&state.embeddings.index.len().to_string()],
+            ),
+        );
+    });
+    if let Some(status) = &state.embeddings.status {
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
+
+fn draw_customization_digest(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    {
+        let digest = &mut state.config.daily_digest;
+        changed |= ui
+            .checkbox(&mut digest.enabled, t("Generar el resumen cada día"))
+            .changed();
+
+        egui::Grid::new("daily_digest_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(t("Hora"));
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut digest.time)
+                            .hint_text("08:00")
+                            .desired_width(80.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Redacta"));
+                let selected = digest
+                    .provider
+                    .as_deref()
+                    .and_then(RemoteProviderKind::from_short_code)
+                    .map_or("Jarvis", RemoteProviderKind::display_name);
+                egui::ComboBox::from_id_source("daily_digest_provider")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        changed |= ui
+                            .selectable_value(&mut digest.provider, None, "Jarvis")
+                            .changed();
+                        for provider in [
+                            RemoteProviderKind::Anthropic,
+                            RemoteProviderKind::OpenAi,
+                            RemoteProviderKind::Groq,
+                        ] {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut digest.provider,
+                                    Some(provider.short_code().to_string()),
+                                    provider.display_name(),
+                                )
+                                .changed();
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(t("Calendario (.ics)"));
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut digest.calendar_file)
+                            .hint_text(t("Ruta del calendario exportado")),
+                    )
+                    .lost_focus();
+                ui.end_row();
+            });
+
+        ui.add_space(6.0);
+        ui.label(RichText::new(t("Incluir")).strong());
+        ui.horizontal_wrapped(|ui| {
+            changed |= ui
+                .checkbox(&mut digest.include_activity, t("Actividad"))
+                .changed();
+            changed |= ui
+                .checkbox(&mut digest.include_workflows, t("Workflows ejecutados"))
+                .changed();
+            changed |= ui
+                .checkbox(&mut digest.include_github, t("Notificaciones de GitHub"))
+                .changed();
+            changed |= ui
+                .checkbox(&mut digest.include_calendar, t("Agenda"))
+                .changed();
+        });
+        changed |= ui
+            .checkbox(
+                &mut digest.desktop_notification,
+                t("Mostrar también una notificación de escritorio"),
+            )
+            .changed();
+    }
+    if changed {
+        if let Err(err) = state.sync_daily_digest_task() {
+            state.digest.status = Some(err);
+        }
+    }
+
+    if let Some(last_run) = &state.config.daily_digest.last_run {
+        ui.colored_label(
+            theme::color_text_weak(),
+            tf("Último resumen: {0}", &[last_run]),
+        );
+    }
+
+    ui.add_space(10.0);
+    let mut generate = false;
+    let mut open = false;
+    let mut send = false;
+    ui.horizontal(|ui| {
+        if state.digest.is_running() {
+            ui.spinner();
+        } else {
+            generate = ui.button(t("Generar ahora")).clicked();
+        }
+        open = ui.button(t("Abrir conversación")).clicked();
+        if state.digest.last_digest.is_some() {
+            send = ui.button(t("Enviar por correo")).clicked();
+        }
+    });
+    if send {
+        if let Err(err) = state.compose_digest_email() {
+            state.digest.status = Some(err);
+        }
+    }
+    if generate {
+        if let Err(err) = state.start_daily_digest() {
+            state.digest.status = Some(err);
+        }
+    }
+    if open {
+        if let Err(err) = state.open_digest_thread() {
+            state.digest.status = Some(err);
+        }
+    }
+    if let Some(status) = &state.digest.status {
+        ui.colored_label(theme::color_text_weak(), status.as_str());
+    }
+}
+
+fn draw_customization_text_actions(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.label(
+        RichText::new(t(
+            "Haz clic derecho sobre un mensaje para aplicarle una de estas acciones. La respuesta llega como un mensaje nuevo del proveedor de la conversación.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    ui.add_space(8.0);
+
+    let mut changed = false;
+    let mut remove = None;
+    {
+        let config = &mut state.config.text_actions;
+        ui.horizontal(|ui| {
+            ui.label(t("Idioma de traducción"));
+            changed |= ui
+                .add(egui::TextEdit::singleline(&mut config.target_language).desired_width(160.0))
+                .lost_focus();
+        });
+        ui.add_space(6.0);
+
+        for (index, action) in config.actions.iter_mut().enumerate() {
+            egui::Frame::none()
+                .fill(Color32::from_rgb(34, 38, 44))
+                .rounding(egui::Rounding::same(10.0))
+                .inner_margin(egui::Margin::symmetric(12.0, 8.0))
+                .show(ui, |ui| {
+                    ui.set_width(ui.available_width());
+                    ui.horizontal(|ui| {
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut action.label)
+                                    .hint_text(t("Nombre"))
+                                    .desired_width(220.0),
+                            )
+                            .lost_focus();
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button(t("Eliminar")).clicked() {
+                                remove = Some(index);
+                            }
+                        });
+                    });
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::multiline(&mut action.prompt)
+                                .hint_text(t("Prompt con {texto} e {idioma}"))
+                                .desired_rows(3)
+                                .desired_width(f32::INFINITY),
+                        )
+                        .lost_focus();
+                });
+            ui.add_space(4.0);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button(t("Añadir acción")).clicked() {
+                config.actions.push(TextActionTemplate {
+                    label: t("Nueva acción").to_string(),
+                    prompt: "{texto}".to_string(),
+                });
+                changed = true;
+            }
+            if ui.button(t("Restablecer predeterminadas")).clicked() {
+                *config = TextActionsConfig::default();
+                changed = true;
+            }
+        });
+    }
+    if let Some(index) = remove {
+        state.config.text_actions.actions.remove(index);
+        changed = true;
+    }
+
+    ui.add_space(16.0);
+    ui.label(RichText::new(t("Sugerencias de seguimiento")).strong());
+    ui.label(
+        RichText::new(t(
+            "Preguntas propuestas bajo la última respuesta. Cada conversación puede desactivarlas desde sus ajustes.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    let settings = &mut state.config.follow_ups;
+    changed |= ui
+        .checkbox(&mut settings.enabled, t("Proponer sugerencias"))
+        .changed();
+    ui.add_enabled_ui(settings.enabled, |ui| {
+        egui::Grid::new("follow_up_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(t("Las propone"));
+                let selected = settings
+                    .provider
+                    .as_deref()
+                    .and_then(RemoteProviderKind::from_short_code)
+                    .map_or("Jarvis", RemoteProviderKind::display_name);
+                egui::ComboBox::from_id_source("follow_up_provider")
+                    .selected_text(selected)
+                    .show_ui(ui, |ui| {
+                        changed |= ui
+                            .selectable_value(&mut settings.provider, None, "Jarvis")
+                            .changed();
+                        for provider in [
+                            RemoteProviderKind::Anthropic,
+                            RemoteProviderKind::OpenAi,
+                            RemoteProviderKind::Groq,
+                        ] {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut settings.provider,
+                                    Some(provider.short_code().to_string()),
+                                    provider.display_name(),
+                                )
+                                .changed();
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(t("Modelo"));
+                changed |= ui
+                    .add_enabled(
+                        settings.provider.is_some(),
+                        egui::TextEdit::singleline(&mut settings.model)
+                            .hint_text(t("Predeterminado del proveedor"))
+                            .desired_width(200.0),
+                    )
+                    .lost_focus();
+                ui.end_row();
+
+                ui.label(t("Cantidad"));
+                changed |= ui
+                    .add(egui::Slider::new(
+                        &mut settings.count,
+                        2..=follow_ups::MAX_SUGGESTIONS,
+                    ))
+                    .changed();
+                ui.end_row();
+            });
+        changed |= ui
+            .checkbox(
+                &mut settings.send_immediately,
+                t("Enviar la sugerencia al pulsarla"),
+            )
+            .changed();
+        if settings.provider.is_none() {
+            ui.colored_label(
+                theme::color_text_weak(),
+                t("Jarvis solo las propone si ya tiene un modelo cargado."),
+            );
+        }
+    });
+
+    ui.add_space(16.0);
+    ui.label(RichText::new(t("Portapapeles")).strong());
+    ui.label(
+        RichText::new(t(
+            "Detecta trazas de error, código o URL al copiarlos y ofrece en un aviso acciones que los llevan a un mensaje nuevo.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    let mut restart = false;
+    let watcher = &mut state.config.clipboard_watcher;
+    restart |= ui
+        .checkbox(&mut watcher.enabled, t("Vigilar el portapapeles"))
+        .changed();
+    ui.add_enabled_ui(watcher.enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(t("Consultar cada"));
+            restart |= ui
+                .add(
+                    egui::DragValue::new(&mut watcher.poll_interval_secs)
+                        .clamp_range(1..=60)
+                        .suffix(" s"),
+                )
+                .changed();
+        });
+        changed |= ui
+            .checkbox(&mut watcher.detect_stack_traces, t("Trazas de error"))
+            .changed();
+        changed |= ui.checkbox(&mut watcher.detect_code, t("Código")).changed();
+        changed |= ui.checkbox(&mut watcher.detect_urls, t("URL")).changed();
+    });
+    if let Some(status) = &state.clipboard.status {
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+    if restart {
+        state.restart_clipboard_watcher();
+    }
+    if changed || restart {
+        state.persist_config();
+    }
+}
+
+fn draw_customization_voice(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    {
+        let voice = &mut state.config.voice_input;
+        egui::ComboBox::from_label(t("Backend de transcripción"))
+            .selected_text(t(voice.backend.label()))
+            .show_ui(ui, |ui| {
+                for candidate in TranscriptionBackend::ALL {
+                    changed |= ui
+                        .selectable_value(&mut voice.backend, candidate, t(candidate.label()))
+                        .changed();
+                }
+            });
+
+        egui::Grid::new("voice_input_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                match voice.backend {
+                    TranscriptionBackend::LocalWhisper => {
+                        ui.label(t("Ejecutable"));
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut voice.whisper_command)
+                                    .hint_text("whisper"),
+                            )
+                            .lost_focus();
+                        ui.end_row();
+                        ui.label(t("Modelo"));
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut voice.whisper_model)
+                                    .hint_text("small"),
+                            )
+                            .lost_focus();
+                        ui.end_row();
+                    }
+                    TranscriptionBackend::OpenAi => {
+                        ui.label(t("Modelo"));
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut voice.openai_model)
+                                    .hint_text("whisper-1"),
+                            )
+                            .lost_focus();
+                        ui.end_row();
+                    }
+                }
+                ui.label(t("Idioma"));
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut voice.language)
+                            .hint_text(t("es, en… vacío para detectarlo")),
+                    )
+                    .lost_focus();
+                ui.end_row();
+            });
+    }
+    if changed {
+        state.persist_config();
+    }
+
+    let backend = state.config.voice_input.backend;
+    let missing_key = state
+        .config
+        .openai
+        .api_key
+        .as_deref()
+        .is_none_or(|key| key.trim().is_empty());
+    if backend == TranscriptionBackend::OpenAi && missing_key {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Configura la API key de OpenAI en Proveedores › OpenAI para usar este backend."),
+        );
+    } else if backend == TranscriptionBackend::LocalWhisper {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Requiere la CLI de Whisper instalada (pip install openai-whisper). Si no la tienes, elige OpenAI."),
+        );
+    }
+
+    ui.add_space(10.0);
+    ui.strong(t("Transcribir un audio"));
+    let running = state.transcription.is_running();
+    ui.horizontal(|ui| {
+        let width = (ui.available_width() - 130.0).max(200.0);
+        ui.add_sized(
+            [width, 24.0],
+            egui::TextEdit::singleline(&mut state.transcription.audio_path)
+                .hint_text(t("Ruta de la nota de voz o del audio")),
+        );
+        if running {
+            ui.spinner();
+        } else if ui
+            .add_enabled(
+                !state.transcription.audio_path.trim().is_empty(),
+                egui::Button::new(t("Transcribir")),
+            )
+            .clicked()
+        {
+            let path = PathBuf::from(state.transcription.audio_path.trim());
+            let _ = state.start_transcription(path);
+        }
+    });
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("El texto se añade al compositor del chat. También puedes usar /transcribe <ruta>."),
+    );
+    if let Some(status) = &state.transcription.status {
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
+
+fn draw_customization_memory(ui: &mut egui::Ui, state: &mut AppState) {
+    if ui
+        .checkbox(
+            &mut state.enable_memory_tracking,
+            "Enable contextual memory",
+        )
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    if ui
+        .add(egui::Slider::new(&mut state.memory_retention_days, 1..=365).text("Retention (days)"))
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        format!(
+            "Memories older than {} days will be archived.",
+            state.memory_retention_days
+        ),
+    );
+
+    ui.add_space(10.0);
+    draw_embedding_settings(ui, state);
+
+    ui.add_space(10.0);
+    let memory_cards = state.resources.personalization_resources.memories.clone();
+    draw_personalization_cards(
+        ui,
+        state,
+        &memory_cards,
+        "Aún no hay memorias configuradas.",
+    );
+}
+
+fn draw_customization_context(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+
+    ui.label(
+        RichText::new("Estrategia de historial")
+            .color(theme::color_text_primary())
+            .strong(),
+    );
+    ui.add_space(4.0);
+    for strategy in ContextStrategy::ALL {
+        let selected = state.config.context.strategy == strategy;
+        if ui
+            .radio(selected, strategy.label())
+            .on_hover_text(strategy.description())
+            .clicked()
+            && !selected
+        {
+            state.config.context.strategy = strategy;
+            changed = true;
+        }
+    }
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        state.config.context.strategy.description(),
+    );
+    ui.add_space(10.0);
+
+    match state.config.context.strategy {
+        ContextStrategy::Disabled => {}
+        ContextStrategy::LastMessages => {
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut state.config.context.max_messages, 1..=50)
+                        .text("Mensajes adjuntos"),
+                )
+                .changed();
+        }
+        ContextStrategy::TokenBudget => {
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut state.config.context.token_budget, 256..=32_000)
+                        .logarithmic(true)
+                        .text("Presupuesto de tokens"),
+                )
+                .changed();
+        }
+        ContextStrategy::SummarizeThenInclude => {
+            let settings = &mut state.config.context;
+            changed |= ui
+                .add(
+                    egui::Slider::new(&mut settings.summary_recent_messages, 1..=20)
+                        .text("Mensajes recientes completos"),
+                )
+                .changed();
+            egui::Grid::new("context_summary_settings")
+                .num_columns(2)
+                .spacing([12.0, 6.0])
+                .show(ui, |ui| {
+                    ui.label(t("Los resume"));
+                    let selected = settings
+                        .summary_provider
+                        .as_deref()
+                        .and_then(RemoteProviderKind::from_short_code)
+                        .map_or(t("Proveedor de la conversación"), |provider| {
+                            provider.display_name()
+                        });
+                    egui::ComboBox::from_id_source("context_summary_provider")
+                        .selected_text(selected)
+                        .show_ui(ui, |ui| {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut settings.summary_provider,
+                                    None,
+                                    t("Proveedor de la conversación"),
+                                )
+                                .changed();
+                            for provider in [
+                                RemoteProviderKind::Anthropic,
+                                RemoteProviderKind::OpenAi,
+                                RemoteProviderKind::Groq,
+                            ] {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut settings.summary_provider,
+                                        Some(provider.short_code().to_string()),
+                                        provider.display_name(),
+                                    )
+                                    .changed();
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label(t("Modelo"));
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut settings.summary_model)
+                                .hint_text(t("Predeterminado del proveedor"))
+                                .desired_width(200.0),
+                        )
+                        .lost_focus();
+                    ui.end_row();
+                });
+            if state.context_summaries.is_pending() {
+                ui.colored_label(
+                    theme::color_text_weak(),
+                    t("Resumiendo mensajes antiguos; mientras tanto van completos."),
+                );
+            }
+        }
+    }
+
+    ui.add_space(10.0);
+    changed |= ui
+        .add(
+            egui::Slider::new(&mut state.config.context.confirm_cost_above, 0.0..=5.0)
+                .logarithmic(true)
+                .prefix("$")
+                .text(t("Confirmar envíos que cuesten más de")),
+        )
+        .on_hover_text(t("Con 0 los envíos nunca piden confirmación."))
+        .changed();
+
+    ui.add_space(10.0);
+    let counter = state.tokenizers.for_provider(RemoteProviderKind::OpenAi);
+    let window = crate::state::context::build_context_window(
+        &state.chat.messages,
+        &state.config.context,
+        counter,
+        false,
+        &state.context_summaries,
+    );
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        format!(
+            "Con la conversación actual se adjuntarían {} mensajes ({} omitidos, ≈{} tokens según {}).",
+            window.included_messages(),
+            window.omitted,
+            window.history_tokens(counter),
+            counter.name()
+        ),
+    );
+
+    if changed {
+        state.persist_config();
+    }
+}
+
+fn draw_customization_profiles(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut selected_profile = state.selected_profile;
+    egui::ComboBox::from_label("Active profile")
+        .selected_text(
+            state
+                .selected_profile
+                .and_then(|idx| state.profiles.get(idx))
+                .cloned()
+                .unwrap_or_else(|| "Choose a profile".to_string()),
+        )
+        .show_ui(ui, |ui| {
+            for (idx, profile) in state.profiles.iter().enumerate() {
+                ui.selectable_value(&mut selected_profile, Some(idx), profile);
+            }
+        });
+
+    if selected_profile != state.selected_profile {
+        state.selected_profile = selected_profile;
+        state.persist_config();
+    }
+
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        if ui.button("Duplicate profile").clicked() {
+            if let Some(idx) = state.selected_profile {
+                let new_profile = format!("{} (copy)", state.profiles[idx]);
+                state.profiles.push(new_profile);
+                state.selected_profile = Some(state.profiles.len() - 1);
+                state.persist_config();
+                state.refresh_personalization_resources();
+            }
+        }
+        if ui.button("Delete profile").clicked() {
+            if let Some(idx) = state.selected_profile {
+                if state.profiles.len() > 1 {
+                    state.profiles.remove(idx);
+                    if state.profiles.is_empty() {
+                        state.selected_profile = None;
+                    } else if idx >= state.profiles.len() {
+                        state.selected_profile = Some(state.profiles.len() - 1);
+                    }
+                    state.persist_config();
+                    state.refresh_personalization_resources();
+                }
+            }
+        }
+    });
+
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        "Profiles let you quickly change between workspace presets.",
+    );
+
+    ui.add_space(10.0);
+    let profile_cards = state.resources.personalization_resources.profiles.clone();
+    draw_personalization_cards(
+        ui,
+        state,
+        &profile_cards,
+        "Crea tu primer perfil para empezar a personalizar respuestas.",
+    );
+}
+
+fn draw_customization_projects(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut selected_project = state.selected_project;
+    egui::ComboBox::from_label("Active project")
+        .selected_text(
+            state
+                .selected_project
+                .and_then(|idx| state.projects.get(idx))
+                .cloned()
+                .unwrap_or_else(|| "Choose a project".to_string()),
+        )
+        .show_ui(ui, |ui| {
+            for (idx, project) in state.projects.iter().enumerate() {
+                ui.selectable_value(&mut selected_project, Some(idx), project);
+            }
+        });
+
+    state.select_project(selected_project);
+
+    ui.add_space(6.0);
+    if ui.button("Create placeholder project").clicked() {
+        let new_project = format!("New Project {}", state.projects.len() + 1);
+        state.projects.push(new_project);
+        state.selected_project = Some(state.projects.len() - 1);
+        state.persist_config();
+        state.refresh_personalization_resources();
+    }
+
+    ui.colored_label(
+        ui.visuals().weak_text_color(),
+        "Projects determine what repositories and documents are prioritised.",
+    );
+
+    if let Some(project) = state
+        .selected_project
+        .and_then(|idx| state.projects.get(idx))
+        .cloned()
+    {
+        ui.add_space(10.0);
+        draw_project_context_profile(ui, state, &project);
+    }
+
+    ui.add_space(10.0);
+    let context_cards = state.resources.personalization_resources.contexts.clone();
+    draw_personalization_cards(
+        ui,
+        state,
+        &context_cards,
+        "Sin proyectos ni repos conectados. Sincroniza GitHub o agrega proyectos prioritarios.",
+    );
+}
+
+/// Persona, proveedor, fuentes de conocimiento y reglas de redacción que el chat aplica
+/// mientras `project` está seleccionado.
+fn draw_project_context_profile(ui: &mut egui::Ui, state: &mut AppState, project: &str) {
+    let mut profile = state
+        .config
+        .project_profiles
+        .get(project)
+        .cloned()
+        .unwrap_or_default();
+    let original = profile.clone();
+
+    ui.label(
+        RichText::new(tf("Perfil de contexto de «{0}»", &[project]))
+            .color(theme::color_text_primary())
+            .strong(),
+    );
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Se aplica al seleccionar el proyecto. Los ajustes propios de cada conversación tienen prioridad."),
+    );
+    ui.add_space(6.0);
+
+    egui::Grid::new("project_profile_grid")
+        .num_columns(2)
+        .spacing([10.0, 6.0])
+        .show(ui, |ui| {
+            ui.label(t("Proveedor"));
+            let provider = profile
+                .provider
+                .as_deref()
+                .and_then(RemoteProviderKind::from_short_code);
+            egui::ComboBox::from_id_source("project_profile_provider")
+                .selected_text(provider.map_or_else(
+                    || t("Jarvis (local)").to_string(),
+                    |kind| kind.display_name().to_string(),
+                ))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut profile.provider, None, t("Jarvis (local)"));
+                    for kind in [
+                        RemoteProviderKind::Anthropic,
+                        RemoteProviderKind::OpenAi,
+                        RemoteProviderKind::Groq,
+                    ] {
+                        ui.selectable_value(
+                            &mut profile.provider,
+                            Some(kind.short_code().to_string()),
+                            kind.display_name(),
+                        );
+                    }
+                });
+            ui.end_row();
+
+            ui.label(t("Persona"));
+            ui.add(
+                egui::TextEdit::multiline(&mut profile.persona)
+                    .hint_text(t("Instrucciones de sistema para este proyecto"))
+                    .desired_rows(2)
+                    .desired_width(360.0),
+            );
+            ui.end_row();
+
+            ui.label(t("Fuentes de conocimiento"));
+            ui.vertical(|ui| {
+                if state.resources.project_resources.is_empty() {
+                    ui.colored_label(
+                        theme::color_text_weak(),
+                        t("Conecta proyectos o repositorios para usarlos como fuente."),
+                    );
+                }
+                for card in &state.resources.project_resources {
+                    let mut included = profile.knowledge_sources.contains(&card.name);
+                    if ui.checkbox(&mut included, &card.name).changed() {
+                        if included {
+                            profile.knowledge_sources.push(card.name.clone());
+                        } else {
+                            profile.knowledge_sources.retain(|name| name != &card.name);
+                        }
+                    }
+                }
+            });
+            ui.end_row();
+
+            ui.label(t("Redacción"));
+            ui.vertical(|ui| {
+                let mut inherit = profile.redaction_rules.is_none();
+                if ui
+                    .checkbox(&mut inherit, t("Usar las reglas de preferencias"))
+                    .changed()
+                {
+                    profile.redaction_rules = if inherit {
+                        None
+                    } else {
+                        Some(
+                            state
+                                .config
+                                .redaction
+                                .rules
+                                .iter()
+                                .filter(|rule| rule.enabled)
+                                .map(|rule| rule.name.clone())
+                                .collect(),
+                        )
+                    };
+                }
+                if let Some(included) = profile.redaction_rules.as_mut() {
+                    for rule in &state.config.redaction.rules {
+                        let mut checked = included.contains(&rule.name);
+                        if ui
+                            .add_enabled(
+                                rule.enabled,
+                                egui::Checkbox::new(&mut checked, &rule.name),
+                            )
+                            .changed()
+                        {
+                            if checked {
+                                included.push(rule.name.clone());
+                            } else {
+                                included.retain(|name| name != &rule.name);
+                            }
+                        }
+                    }
+                }
+            });
+            ui.end_row();
+        });
+
+    if profile != original {
+        if project_context::is_empty(&profile) {
+            state.config.project_profiles.remove(project);
+        } else {
+            state
+                .config
+                .project_profiles
+                .insert(project.to_string(), profile);
+        }
+        state.persist_config();
+    }
+}
+
+/// Aviso de la cabecera del chat con el perfil de contexto del proyecto seleccionado.
+fn draw_project_context_banner(ui: &mut egui::Ui, state: &mut AppState) {
+    if state.project_context.banner_dismissed {
+        return;
+    }
+    let Some((project, summary)) = state
+        .active_project_profile()
+        .map(|(project, profile)| (project.to_string(), project_context::summary(profile)))
+    else {
+        return;
+    };
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 6.0;
+        ui.label(
+            RichText::new(ICON_LIGHTNING)
+                .font(theme::icon_font(13.0))
+                .color(theme::color_primary()),
+        );
+        ui.label(
+            RichText::new(tf(
+                "Contexto de {0}: {1}",
+                &[&project, &summary.join(" · ")],
+            ))
+            .color(theme::color_text_weak())
+            .size(12.0),
+        );
+        if ui.small_button(t("Editar")).clicked() {
+            state.activate_navigation_target(NavigationTarget::preference(
+                PreferencePanel::CustomizationProjects,
+            ));
+        }
+        if ui.small_button(t("Ocultar")).clicked() {
+            state.project_context.banner_dismissed = true;
+        }
+    });
+    ui.add_space(4.0);
+}
+
+fn draw_personalization_cards(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    cards: &[KnowledgeResourceCard],
+    empty_message: &str,
+) {
+    if cards.is_empty() {
+        ui.colored_label(theme::color_text_weak(), empty_message);
+        return;
+    }
+
+    for card in cards {
+        egui::Frame::none()
+            .fill(Color32::from_rgb(34, 38, 44))
+            .stroke(theme::subtle_border(&state.theme))
+            .rounding(egui::Rounding::same(12.0))
+            .inner_margin(egui::Margin::symmetric(14.0, 12.0))
+            .show(ui, |ui| {
+                ui.vertical(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.label(
+                            RichText::new(&card.title)
+                                .color(theme::color_text_primary())
+                                .strong()
+                                .size(14.0),
+                        );
+                        ui.add_space(ui.available_width());
+                        ui.label(
+                            RichText::new(&card.resource_type)
+                                .color(theme::color_primary())
+                                .size(11.0),
+                        );
+                    });
+                    ui.label(
+                        RichText::new(&card.subtitle)
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                    ui.label(
+                        RichText::new(format!("Última sincronización: {}", card.last_synced))
+                            .color(theme::color_text_weak())
+                            .size(10.0),
+                    );
+
+                    if !card.tags.is_empty() {
+                        ui.add_space(6.0);
+                        ui.horizontal_wrapped(|ui| {
+                            ui.spacing_mut().item_spacing.x = 6.0;
+                            for tag in &card.tags {
+                                selectable_chip(ui, tag, false);
+                            }
+                        });
+                    }
+
+                    ui.add_space(8.0);
+                    ui.horizontal(|ui| {
+                        if let Some(link) = &card.link {
+                            if ui
+                                .add(
+                                    egui::Button::new(
+                                        RichText::new(format!("{} Copiar", ICON_LINK))
+                                            .color(Color32::from_rgb(240, 240, 240))
+                                            .size(11.0),
+                                    )
+                                    .min_size(egui::vec2(80.0, 26.0))
+                                    .fill(Color32::from_rgb(44, 46, 54))
+                                    .rounding(egui::Rounding::same(8.0)),
+                                )
+                                .clicked()
+                            {
+                                ui.output_mut(|out| out.copied_text = link.clone());
+                                state.resources.personalization_feedback =
+                                    Some(format!("Enlace copiado: {}", card.title));
+                            }
+                        }
+
+                        if ui
+                            .add(
+                                egui::Button::new(
+                                    RichText::new(format!("{} Sincronizar", ICON_LIGHTNING))
+                                        .color(Color32::from_rgb(240, 240, 240))
+                                        .size(11.0),
+                                )
+                                .min_size(egui::vec2(110.0, 26.0))
+                                .fill(Color32::from_rgb(70, 80, 96))
+                                .rounding(egui::Rounding::same(8.0)),
+                            )
+                            .clicked()
+                        {
+                            state.resources.personalization_feedback = Some(format!(
+                                "{} marcado para sincronización contextual.",
+                                card.title
+                            ));
+                        }
+                    });
+                });
+            });
+
+        ui.add_space(8.0);
+    }
+
+    if let Some(status) = &state.resources.personalization_feedback {
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+}
+
+fn draw_local_provider(ui: &mut egui::Ui, state: &mut AppState, provider: LocalModelProvider) {
+    let mut persist_changes = false;
+    let mut search_request: Option<(String, Option<String>)> = None;
+    let tokens = state.theme.clone();
+
+    {
+        let provider_state = state.provider_state_mut(provider);
+        let token_label = provider.token_label();
+        ui.label(format!("{}", token_label));
+        ui.horizontal(|ui| {
+            let response = ui.text_edit_singleline(&mut provider_state.token_input);
+            if response.changed() {
+                // Do not persist immediately; wait for the save button.
+            }
+
+            let save_label = RichText::new("Guardar").color(Color32::from_rgb(240, 240, 240));
+            let button = theme::primary_button(save_label, &tokens).min_size(egui::vec2(0.0, 28.0));
+            if ui.add_sized([110.0, 30.0], button).clicked() {
+                let trimmed = provider_state.token_input.trim();
+                if trimmed.is_empty() {
+                    provider_state.access_token = None;
+                    provider_state.token_input.clear();
+                } else {
+                    provider_state.access_token = Some(trimmed.to_string());
+                    provider_state.token_input = trimmed.to_string();
+                }
+                persist_changes = true;
+            }
+        });
+
+        if provider.requires_token() && provider_state.access_token.is_none() {
+            ui.colored_label(
+                Color32::from_rgb(255, 196, 96),
+                "Este proveedor requiere un token válido para listar modelos.",
+            );
+        }
+
+        ui.add_space(6.0);
+        egui::Frame::none()
+            .fill(Color32::from_rgb(30, 32, 36))
+            .stroke(theme::subtle_border(&tokens))
+            .rounding(egui::Rounding::same(12.0))
+            .inner_margin(egui::Margin::symmetric(14.0, 12.0))
+            .show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let button_width = 120.0;
+                    let text_width = (ui.available_width() - button_width - 12.0).max(240.0);
+                    let search_edit = egui::TextEdit::singleline(&mut provider_state.search_query)
+                        .hint_text(provider.search_hint())
+                        .desired_width(f32::INFINITY);
+                    let response = ui.add_sized([text_width, 30.0], search_edit);
+                    if response.changed() {
+                        persist_changes = true;
+                    }
+
+                    let needs_token =
+                        provider.requires_token() && provider_state.access_token.is_none();
+                    let mut clicked = false;
+                    let search_label =
+                        RichText::new("Buscar").color(Color32::from_rgb(240, 240, 240));
+                    ui.add_enabled_ui(!needs_token, |ui| {
+                        if ui
+                            .add_sized(
+                                [button_width, 32.0],
+                                theme::primary_button(search_label.clone(), &tokens),
+                            )
+                            .clicked()
+                        {
+                            clicked = true;
+                        }
+                    });
+
+                    if clicked {
+                        search_request = Some((
+                            provider_state.search_query.clone(),
+                            provider_state.access_token.clone(),
+                        ));
+                    }
+                });
+            });
+    }
+
+    if persist_changes {
+        state.persist_config();
+    }
+
+    if let Some((query, token)) = search_request {
+        match search_models_for_provider(provider, &query, token.as_deref()) {
+            Ok(models) => {
+                let count = models.len();
+                let provider_state = state.provider_state_mut(provider);
+                provider_state.models = models;
+                provider_state.selected_model = None;
+                provider_state.install_status = Some(format!(
+                    "Se encontraron {} modelos para '{}'.",
+                    count, query
+                ));
+                state.persist_config();
+            }
+            Err(err) => {
+                let provider_state = state.provider_state_mut(provider);
+                provider_state.install_status = Some(format!("Fallo al buscar modelos: {}", err));
+            }
+        }
+    }
+
+    ui.add_space(12.0);
+
+    let (models, selected_model) = {
+        let provider_state = state.provider_state(provider);
+        (provider_state.models.clone(), provider_state.selected_model)
+    };
+
+    if models.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "Busca un término para poblar la galería de modelos.",
+        );
+    } else {
+        ui.horizontal(|ui| {
+            ui.heading(
+                RichText::new(format!("Galería de modelos ({} resultados)", models.len()))
+                    .color(theme::color_text_primary()),
+            );
+            ui.add_space(ui.available_width());
+            ui.label(
+                RichText::new("Clic en una tarjeta para seleccionarla o instalarla.")
+                    .color(theme::color_text_weak())
+                    .size(12.0),
+            );
+        });
+        ui.add_space(8.0);
+        draw_provider_gallery(ui, state, provider, &models, selected_model);
+    }
+
+    ui.add_space(12.0);
+    let installed: Vec<InstalledLocalModel> = state
+        .resources
+        .installed_local_models
+        .iter()
+        .cloned()
+        .filter(|model| model.identifier.provider == provider)
+        .collect();
+
+    if installed.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "Todavía no hay modelos instalados desde este proveedor.",
+        );
+    } else {
+        ui.horizontal(|ui| {
+            ui.heading(
+                RichText::new("Modelos instalados")
+                    .color(theme::color_text_primary())
+                    .size(16.0),
+            );
+            ui.add_space(ui.available_width());
+            ui.label(
+                RichText::new("Gestiona tus descargas locales y actívalas para Jarvis.")
+                    .color(theme::color_text_weak())
+                    .size(11.0),
+            );
+        });
+        ui.add_space(6.0);
+        egui::ScrollArea::vertical()
+            .id_source(("installed_models_scroll", provider.key()))
+            .max_height(240.0)
+            .auto_shrink([false, false])
+            .show(ui, |ui| {
+                for record in installed {
+                    draw_installed_model_card(ui, state, provider, &record);
+                    ui.add_space(10.0);
+                }
+            });
+    }
+
+    if let Some(status) = state.provider_state(provider).install_status.clone() {
+        ui.add_space(10.0);
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+    draw_install_progress(ui, state, provider);
+}
+
+fn draw_install_progress(ui: &mut egui::Ui, state: &mut AppState, provider: LocalModelProvider) {
+    let mut cancel = None;
+    for install in state.install_progress(provider) {
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            let fraction = if install.total > 0 {
+                install.downloaded as f32 / install.total as f32
+            } else {
+                0.0
+            };
+            let text = if install.total > 0 {
+                tf(
+                    "{0}: {1} de {2} · {3}/s",
+                    &[
+                        &install.model_id,
+                        &format_bytes(install.downloaded),
+                        &format_bytes(install.total),
+                        &format_bytes(install.bytes_per_second),
+                    ],
+                )
+            } else {
+                tf("{0}: preparando la descarga…", &[&install.model_id])
+            };
+            ui.add(
+                egui::ProgressBar::new(fraction.min(1.0))
+                    .desired_width(ui.available_width() - 100.0)
+                    .text(text),
+            );
+            if ui
+                .add_enabled(!install.cancelled, egui::Button::new(t("Cancelar")))
+                .on_hover_text(t(
+                    "Lo ya descargado se conserva y se reanuda al volver a instalar el modelo.",
+                ))
+                .clicked()
+            {
+                cancel = Some(install.model_id.clone());
+            }
+        });
+    }
+    if let Some(model_id) = cancel {
+        state.cancel_local_install(provider, &model_id);
+    }
+}
+
+fn draw_provider_gallery(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    provider: LocalModelProvider,
+    models: &[LocalModelCard],
+    selected_model: Option<usize>,
+) {
+    let spacing = 16.0;
+    let min_card_width = 280.0;
+
+    egui::ScrollArea::vertical()
+        .id_source(("provider_gallery_scroll", provider.key()))
+        .max_height(380.0)
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            let available_width = ui.available_width().max(min_card_width);
+            let mut columns =
+                ((available_width + spacing) / (min_card_width + spacing)).floor() as usize;
+            columns = columns.clamp(1, 3);
+            let card_width = ((available_width - spacing * ((columns as f32) - 1.0))
+                / columns as f32)
+                .max(min_card_width);
+
+            let mut base_index = 0usize;
+            for chunk in models.chunks(columns) {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = spacing;
+                    for (offset, model) in chunk.iter().enumerate() {
+                        let index = base_index + offset;
+                        let (rect, response) = ui
+                            .allocate_at_least(egui::vec2(card_width, 190.0), egui::Sense::click());
+                        let mut card_ui =
+                            ui.child_ui(rect, egui::Layout::top_down(egui::Align::LEFT));
+                        draw_model_card(
+                            &mut card_ui,
+                            state,
+                            provider,
+                            model,
+                            index,
+                            selected_model == Some(index),
+                        );
+
+                        if response.clicked() {
+                            state.provider_state_mut(provider).selected_model = Some(index);
+                        }
+
+                        if response.double_clicked() {
+                            install_local_model(state, provider, index);
+                        }
+                    }
+
+                    if chunk.len() < columns {
+                        for _ in chunk.len()..columns {
+                            ui.add_space(card_width);
+                        }
+                    }
+                });
+                ui.add_space(spacing);
+                base_index += chunk.len();
+            }
+        });
+}
+
+fn draw_model_card(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    provider: LocalModelProvider,
+    model: &LocalModelCard,
+    index: usize,
+    is_selected: bool,
+) {
+    let premium = model.requires_token;
+
+    let fill = if premium {
+        Color32::from_rgb(48, 36, 56)
+    } else {
+        Color32::from_rgb(34, 38, 44)
+    };
+    let incompatible = model.incompatible_reason.is_some();
+    let border = if is_selected {
+        theme::color_primary()
+    } else if premium {
+        Color32::from_rgb(182, 134, 242)
+    } else if incompatible {
+        theme::color_danger()
+    } else {
+        Color32::from_rgb(70, 80, 96)
+    };
+
+    egui::Frame::none()
+        .fill(fill)
+        .stroke(egui::Stroke::new(
+            if is_selected { 2.0 } else { 1.0 },
+            border,
+        ))
+        .rounding(egui::Rounding::same(12.0))
+        .inner_margin(egui::Margin::symmetric(14.0, 12.0))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 8.0;
+                    let badge_icon = if premium { ICON_PREMIUM } else { ICON_FREE };
+                    let badge_color = if premium {
+                        Color32::from_rgb(255, 214, 102)
+                    } else {
+                        Color32::from_rgb(108, 214, 148)
+                    };
+                    ui.label(
+                        RichText::new(badge_icon)
+                            .font(theme::icon_font(15.0))
+                            .color(badge_color),
+                    );
+                    ui.label(
+                        RichText::new(&model.id)
+                            .strong()
+                            .color(theme::color_text_primary()),
+                    );
+                });
+
+                if let Some(author) = &model.author {
+                    ui.label(
+                        RichText::new(format!("Autor: {}", author))
+                            .color(theme::color_text_weak())
+                            .size(12.0),
+                    );
+                }
+
+                if let Some(pipeline) = &model.pipeline_tag {
+                    ui.label(
+                        RichText::new(format!("Pipeline: {}", pipeline))
+                            .color(theme::color_text_weak())
+                            .size(12.0),
+                    );
+                }
+
+                if !model.tags.is_empty() {
+                    let tags: Vec<&str> =
+                        model.tags.iter().take(3).map(|tag| tag.as_str()).collect();
+                    ui.label(
+                        RichText::new(format!("Etiquetas: {}", tags.join(", ")))
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                }
+
+                if let Some(description) = &model.description {
+                    ui.add_space(4.0);
+                    ui.label(
+                        RichText::new(description)
+                            .color(theme::color_text_weak())
+                            .size(11.0),
+                    );
+                }
+
+                if let Some(reason) = &model.incompatible_reason {
+                    ui.add_space(6.0);
+                    ui.label(
+                        RichText::new(reason)
+                            .color(theme::color_danger())
+                            .italics()
+                            .size(11.0),
+                    );
+                }
+
+                let mut metrics = Vec::new();
+                if let Some(likes) = model.likes {
+                    metrics.push(format!("❤ {}", format_count(likes)));
+                }
+                if let Some(downloads) = model.downloads {
+                    metrics.push(format!("⬇ {}", format_count(downloads)));
+                }
+                if !metrics.is_empty() {
+                    ui.add_space(4.0);
+                    ui.label(
+                        RichText::new(metrics.join("  · "))
+                            .color(theme::color_text_primary())
+                            .size(12.0),
+                    );
+                }
+
+                ui.add_space(8.0);
+
+                let button_label = if premium {
+                    format!("{} Instalar (token)", ICON_DOWNLOAD)
+                } else {
+                    format!("{} Instalar", ICON_DOWNLOAD)
+                };
+                let button_width = ui.available_width();
+                let response = ui.add_enabled(
+                    !incompatible,
+                    theme::primary_button(
+                        RichText::new(button_label).color(Color32::from_rgb(240, 240, 240)),
+                        &state.theme,
+                    )
+                    .min_size(egui::vec2(button_width, 30.0)),
+                );
+
+                if response.clicked() {
+                    install_local_model(state, provider, index);
+                }
+            });
+        });
+}
+
+fn draw_installed_model_card(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    provider: LocalModelProvider,
+    record: &InstalledLocalModel,
+) {
+    let is_active = state
+        .resources
+        .jarvis_active_model
+        .as_ref()
+        .map(|model| model == &record.identifier)
+        .unwrap_or(false);
+
+    let install_path = if record.install_path.trim().is_empty() {
+        Path::new(&state.resources.jarvis_install_dir)
+            .join(record.identifier.sanitized_dir_name())
+            .display()
+            .to_string()
+    } else {
+        record.install_path.clone()
+    };
+
+    let path_display = truncate_middle(&install_path, 72);
+    let subtitle = format!(
+        "{} • Instalado el {}",
+        format_bytes(record.size_bytes),
+        format_timestamp(record.installed_at)
+    );
+
+    egui::Frame::none()
+        .fill(Color32::from_rgb(30, 32, 36))
+        .stroke(theme::subtle_border(&state.theme))
+        .rounding(egui::Rounding::same(12.0))
+        .inner_margin(egui::Margin::symmetric(16.0, 14.0))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(record.identifier.provider.display_name())
+                            .color(theme::color_text_weak())
+                            .size(12.0),
+                    );
+                    ui.add_space(6.0);
+                    ui.label(
+                        RichText::new(&record.identifier.model_id)
+                            .strong()
+                            .color(theme::color_text_primary()),
+                    );
+                    if is_active {
+                        ui.add_space(10.0);
+                        ui.label(
+                            RichText::new("Activo")
+                                .color(theme::color_success())
+                                .size(12.0),
+                        );
+                    }
+                });
+
+                ui.add_space(6.0);
+                ui.label(
+                    RichText::new(&subtitle)
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                );
+
+                ui.label(
+                    RichText::new(&path_display)
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                )
+                .on_hover_text(&install_path);
+
+                ui.add_space(10.0);
+
+                if is_active {
+                    ui.add_enabled_ui(false, |ui| {
+                        ui.add_sized(
+                            [ui.available_width(), 30.0],
+                            theme::secondary_button(
+                                RichText::new("Activo en Jarvis")
+                                    .color(Color32::from_rgb(240, 240, 240)),
+                                &state.theme,
+                            ),
+                        );
+                    });
+                } else {
+                    let button = theme::primary_button(
+                        RichText::new(format!("{} Activar en Jarvis", ICON_SEND))
+                            .color(Color32::from_rgb(240, 240, 240)),
+                        &state.theme,
+                    );
+                    if ui.add_sized([ui.available_width(), 30.0], button).clicked() {
+                        let status = state.activate_jarvis_model(&record.identifier);
+                        state.provider_state_mut(provider).install_status = Some(status);
+                    }
+                }
+            });
+        });
+}
+
+fn install_local_model(state: &mut AppState, provider: LocalModelProvider, index: usize) {
+    let (model, token) = {
+        let provider_state = state.provider_state(provider);
+        if let Some(model) = provider_state.models.get(index).cloned() {
+            (model, provider_state.access_token.clone())
+        } else {
+            return;
+        }
+    };
+
+    debug_assert_eq!(model.provider, provider);
+
+    if let Some(reason) = &model.incompatible_reason {
+        let message = format!("'{}' no es compatible: {}", model.id, reason);
+        state.provider_state_mut(provider).install_status = Some(message.clone());
+        state.push_activity_log(LogStatus::Warning, "Jarvis", message);
+        return;
+    }
+
+    if provider == LocalModelProvider::HuggingFace {
+        let started = state.queue_huggingface_install(model.clone(), token);
+        if started {
+            state.provider_state_mut(provider).selected_model = Some(index);
+        } else {
+            let warning = format!(
+                "Ya hay una descarga en curso para '{}'. Espera a que termine.",
+                model.id
+            );
+            {
+                let provider_state = state.provider_state_mut(provider);
+                provider_state.selected_model = Some(index);
+                provider_state.install_status = Some(warning.clone());
+            }
+            state.push_activity_log(LogStatus::Warning, "Jarvis", warning);
+        }
+        return;
+    }
+
+    let status = match provider {
+        LocalModelProvider::Ollama => {
+            match crate::api::ollama::pull_model(&model.id, token.as_deref()) {
+                Ok(()) => format!(
+                "Modelo '{}' preparado mediante Ollama. Usa el runtime de Ollama para servirlo.",
+                model.id
+            ),
+                Err(err) => format!("No se pudo preparar '{}' con Ollama: {}", model.id, err),
+            }
+        }
+        _ => format!(
+            "La instalación automática aún no está disponible para {}.",
+            provider.display_name()
+        ),
+    };
+
+    let provider_state = state.provider_state_mut(provider);
+    provider_state.selected_model = Some(index);
+    provider_state.install_status = Some(status);
+}
+
+fn search_models_for_provider(
+    provider: LocalModelProvider,
+    query: &str,
+    token: Option<&str>,
+) -> Result<Vec<LocalModelCard>> {
+    let trimmed = query.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    match provider {
+        LocalModelProvider::HuggingFace => crate::api::huggingface::search_models(trimmed, token),
+        LocalModelProvider::Ollama => crate::api::ollama::search_models(trimmed, token),
+        LocalModelProvider::OpenRouter => crate::api::openrouter::search_models(trimmed),
+        _ => {
+            let lowercase = trimmed.to_lowercase();
+            let catalog = sample_catalog(provider);
+            let filtered = catalog
+                .into_iter()
+                .filter(|card| {
+                    card.id.to_lowercase().contains(&lowercase)
+                        || card
+                            .description
+                            .as_ref()
+                            .map(|desc| desc.to_lowercase().contains(&lowercase))
+                            .unwrap_or(false)
+                })
+                .collect();
+            Ok(filtered)
+        }
+    }
+}
+
+fn sample_catalog(provider: LocalModelProvider) -> Vec<LocalModelCard> {
+    match provider {
+        LocalModelProvider::GithubModels => vec![
+            LocalModelCard {
+                provider,
+                id: "github/CodeLlama-34b".to_string(),
+                author: Some("GitHub".to_string()),
+                pipeline_tag: Some("text-generation".to_string()),
+                tags: vec!["code".to_string(), "llama".to_string()],
+                description: Some(
+                    "Modelos experimentales de GitHub Models listos para desplegar en contenedores.".
+                        to_string(),
+                ),
+                requires_token: true,
+                ..Default::default()
+            },
+            LocalModelCard {
+                provider,
+                id: "github/Phi-3-mini".to_string(),
+                author: Some("GitHub".to_string()),
+                pipeline_tag: Some("text-generation".to_string()),
+                tags: vec!["chat".to_string(), "preview".to_string()],
+                description: Some(
+                    "Inferencia hospedada en GitHub Models compatible con la API de OpenAI.".
+                        to_string(),
+                ),
+                requires_token: true,
+                ..Default::default()
+            },
+        ],
+        LocalModelProvider::Replicate => vec![
+            LocalModelCard {
+                provider,
+                id: "replicate/flux-dev".to_string(),
+                author: Some("Replicate".to_string()),
+                pipeline_tag: Some("image-to-image".to_string()),
+                tags: vec!["diffusion".to_string(), "vision".to_string()],
+                description: Some(
+                    "Modelos visuales populares de la comunidad de Replicate disponibles mediante API.".
+                        to_string(),
+                ),
+                requires_token: true,
+                ..Default::default()
+            },
+            LocalModelCard {
+                provider,
+                id: "replicate/llama-3-70b-instruct".to_string(),
+                author: Some("Replicate".to_string()),
+                pipeline_tag: Some("text-generation".to_string()),
+                tags: vec!["chat".to_string(), "meta".to_string()],
+                description: Some(
+                    "Versión alojada de Llama 3 para uso inmediato a través de Replicate API.".
+                        to_string(),
+                ),
+                requires_token: true,
+                ..Default::default()
+            },
+        ],
+        LocalModelProvider::Ollama => vec![
+            LocalModelCard {
+                provider,
+                id: "ollama/llama3".to_string(),
+                author: Some("Ollama".to_string()),
+                pipeline_tag: Some("text-generation".to_string()),
+                tags: vec!["local".to_string(), "chat".to_string()],
+                description: Some(
+                    "Modelos descargables mediante 'ollama pull' listos para ejecutarse en tu host.".
+                        to_string(),
+                ),
+                requires_token: false,
+                ..Default::default()
+            },
+            LocalModelCard {
+                provider,
+                id: "ollama/codellama".to_string(),
+                author: Some("Ollama".to_string()),
+                pipeline_tag: Some("code-generation".to_string()),
+                tags: vec!["code".to_string(), "local".to_string()],
+                description: Some(
+                    "Ejemplos de modelos que Ollama expone como imágenes portables para contenedores.".
+                        to_string(),
+                ),
+                requires_token: false,
+                ..Default::default()
+            },
+        ],
+        LocalModelProvider::OpenRouter => vec![
+            LocalModelCard {
+                provider,
+                id: "openrouter/google/gemini-pro".to_string(),
+                author: Some("OpenRouter".to_string()),
+                pipeline_tag: Some("text-generation".to_string()),
+                tags: vec!["router".to_string(), "gemini".to_string()],
+                description: Some(
+                    "Agrega modelos de múltiples proveedores con una única API compatible con OpenAI.".
+                        to_string(),
+                ),
+                requires_token: true,
+                ..Default::default()
+            },
+            LocalModelCard {
+                provider,
+                id: "openrouter/mistral/mixtral-8x7b".to_string(),
+                author: Some("OpenRouter".to_string()),
+                pipeline_tag: Some("text-generation".to_string()),
+                tags: vec!["mixture-of-experts".to_string()],
+                description: Some(
+                    "Modelos orquestados por OpenRouter listos para su consumo mediante claves personales.".
+                        to_string(),
+                ),
+                requires_token: true,
+                ..Default::default()
+            },
+        ],
+        LocalModelProvider::Modelscope => vec![
+            LocalModelCard {
+                provider,
+                id: "modelscope/Qwen1.5-14B-Chat".to_string(),
+                author: Some("ModelScope".to_string()),
+                pipeline_tag: Some("text-generation".to_string()),
+                tags: vec!["qwen".to_string(), "chat".to_string()],
+                description: Some(
+                    "Modelos del ecosistema ModelScope listos para descarga mediante su SDK oficial.".
+                        to_string(),
+                ),
+                requires_token: true,
+                ..Default::default()
+            },
+            LocalModelCard {
+                provider,
+                id: "modelscope/speech_paraformer".to_string(),
+                author: Some("ModelScope".to_string()),
+                pipeline_tag: Some("automatic-speech-recognition".to_string()),
+                tags: vec!["audio".to_string(), "asr".to_string()],
+                description: Some(
+                    "Ejemplos de pipelines de voz disponibles a través del hub de ModelScope.".
+                        to_string(),
+                ),
+                requires_token: true,
+                ..Default::default()
+            },
+        ],
+        LocalModelProvider::HuggingFace => Vec::new(),
+    }
+}
+
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    let local: DateTime<Local> = DateTime::from(timestamp);
+    local.format("%Y-%m-%d %H:%M").to_string()
+}
+
+fn truncate_middle(value: &str, max_len: usize) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    if chars.len() <= max_len {
+        return value.to_string();
+    }
+
+    if max_len <= 3 {
+        return "…".to_string();
+    }
+
+    let keep = max_len.saturating_sub(3);
+    let front = keep / 2;
+    let back = keep - front;
+    let start: String = chars.iter().take(front).collect();
+    let end: String = chars
+        .iter()
+        .rev()
+        .take(back)
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    format!("{}…{}", start, end)
+}
+
+fn format_count(value: u64) -> String {
+    if value >= 1_000_000 {
+        let short = value as f64 / 1_000_000.0;
+        if short >= 10.0 {
+            format!("{:.0}M", short)
+        } else {
+            format!("{:.1}M", short)
+        }
+    } else if value >= 1_000 {
+        let short = value as f64 / 1_000.0;
+        if short >= 10.0 {
+            format!("{:.0}K", short)
+        } else {
+            format!("{:.1}K", short)
+        }
+    } else {
+        value.to_string()
+    }
+}
+
+fn quick_chip(ui: &mut egui::Ui, label: &str) -> egui::Response {
+    let button = egui::Button::new(
+        RichText::new(label)
+            .color(Color32::from_rgb(228, 228, 228))
+            .strong(),
+    )
+    .min_size(egui::vec2(0.0, 28.0))
+    .fill(Color32::from_rgb(36, 38, 46))
+    .rounding(egui::Rounding::same(10.0));
+    ui.add(button)
+}
+
+fn quick_chip_with_icon(ui: &mut egui::Ui, icon: &str, tooltip: &str) -> egui::Response {
+    let button = egui::Button::new(
+        RichText::new(icon)
+            .font(theme::icon_font(14.0))
+            .color(Color32::from_rgb(230, 230, 230)),
+    )
+    .min_size(egui::vec2(32.0, 28.0))
+    .fill(Color32::from_rgb(36, 38, 46))
+    .rounding(egui::Rounding::same(10.0));
+    let response = ui.add(button);
+    response.on_hover_text(tooltip)
+}
+
+fn selectable_chip(ui: &mut egui::Ui, label: &str, selected: bool) -> egui::Response {
+    let fill = if selected {
+        theme::color_primary()
+    } else {
+        Color32::from_rgb(44, 46, 54)
+    };
+    let text_color = if selected {
+        Color32::from_rgb(24, 28, 34)
+    } else {
+        Color32::from_rgb(240, 240, 240)
+    };
+
+    let button = egui::Button::new(RichText::new(label).color(text_color).size(11.0))
+        .min_size(egui::vec2(0.0, 24.0))
+        .fill(fill)
+        .rounding(egui::Rounding::same(10.0));
+
+    ui.add(button)
+}
+
+fn insert_mention(state: &mut AppState, mention: &str) {
+    let trimmed = state.chat.input.trim();
+    if trimmed.starts_with(mention) {
+        if !state.chat.input.ends_with(' ') {
+            state.chat.input.push(' ');
+        }
+        return;
+    }
+
+    if trimmed.is_empty() {
+        state.chat.input = format!("{} ", mention);
+    } else {
+        state.chat.input = format!("{} {}", mention, trimmed);
+    }
+}
+
+fn insert_code_template(state: &mut AppState) {
+    let template = "```language\n\n```";
+    if state.chat.input.trim().is_empty() {
+        state.chat.input = template.to_string();
+    } else {
+        if !state.chat.input.ends_with('\n') {
+            state.chat.input.push('\n');
+        }
+        state.chat.input.push_str(template);
+    }
+}
+
+fn draw_jarvis_performance(ui: &mut egui::Ui, state: &mut AppState) {
+    let performance = &mut state.resources.jarvis_performance;
+    if performance.calls.is_empty() && performance.loads.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Aún no hay mediciones. Envía mensajes a Jarvis para registrar su rendimiento."),
+        );
+        return;
+    }
+
+    let summaries = performance.summaries();
+    ui.label(
+        RichText::new(t("Comparativa por modelo"))
+            .color(theme::color_text_primary())
+            .strong(),
+    );
+    ui.add_space(4.0);
+    egui::Grid::new("jarvis_performance_summary")
+        .striped(true)
+        .spacing(egui::vec2(14.0, 4.0))
+        .show(ui, |ui| {
+            for header in [
+                "Modelo",
+                "Precisión",
+                "Llamadas",
+                "Carga",
+                "Latencia media",
+                "Tokens/s",
+                "Prompt / respuesta",
+                "Memoria máx.",
+            ] {
+                ui.label(
+                    RichText::new(t(header))
+                        .color(theme::color_text_primary())
+                        .strong(),
+                );
+            }
+            ui.end_row();
+            for summary in &summaries {
+                ui.label(&summary.model);
+                ui.label(summary.precision.as_deref().unwrap_or("—"));
+                ui.label(summary.calls.to_string());
+                ui.label(
+                    summary
+                        .last_load_ms
+                        .map(|ms| format!("{:.0} ms", ms))
+                        .unwrap_or_else(|| "—".to_string()),
+                );
+                ui.label(format!("{:.0} ms", summary.avg_duration_ms));
+                ui.label(format!("{:.1}", summary.avg_tokens_per_second));
+                ui.label(format!(
+                    "{:.0} / {:.0}",
+                    summary.avg_prompt_tokens, summary.avg_response_tokens
+                ));
+                ui.label(
+                    summary
+                        .peak_memory_bytes
+                        .map(format_bytes)
+                        .unwrap_or_else(|| "—".to_string()),
+                );
+                ui.end_row();
+            }
+        });
+
+    ui.add_space(12.0);
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(t("Historial"))
+                .color(theme::color_text_primary())
+                .strong(),
+        );
+        let selected = performance
+            .filter_model
+            .clone()
+            .unwrap_or_else(|| t("Todos los modelos").to_string());
+        egui::ComboBox::from_id_source("jarvis_performance_filter")
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut performance.filter_model, None, t("Todos los modelos"));
+                for summary in &summaries {
+                    ui.selectable_value(
+                        &mut performance.filter_model,
+                        Some(summary.model.clone()),
+                        &summary.model,
+                    );
+                }
+            });
+        if ui.small_button(t("Borrar mediciones")).clicked() {
+            performance.clear();
+        }
+    });
+
+    let calls: Vec<_> = performance.visible_calls().collect();
+    if calls.len() < 2 {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Se necesitan al menos dos llamadas para dibujar el historial."),
+        );
+        return;
+    }
+    let labels: Vec<String> = calls
+        .iter()
+        .map(|sample| {
+            sample
+                .timestamp
+                .split(' ')
+                .nth(1)
+                .unwrap_or(&sample.timestamp)
+                .to_string()
+        })
+        .collect();
+
+    ui.add_space(6.0);
+    ui.label(RichText::new(t("Velocidad (tokens/s)")).color(theme::color_text_weak()));
+    data_table::draw_series_chart(
+        ui,
+        data_table::ChartKind::Lines,
+        &labels,
+        &[(
+            "Tokens/s".to_string(),
+            calls
+                .iter()
+                .map(|sample| sample.tokens_per_second())
+                .collect(),
+        )],
+    );
+    ui.add_space(6.0);
+    ui.label(RichText::new(t("Latencia (ms)")).color(theme::color_text_weak()));
+    data_table::draw_series_chart(
+        ui,
+        data_table::ChartKind::Bars,
+        &labels,
+        &[(
+            "ms".to_string(),
+            calls.iter().map(|sample| sample.duration_ms).collect(),
+        )],
+    );
+    ui.add_space(6.0);
+    ui.label(RichText::new(t("Longitud en tokens")).color(theme::color_text_weak()));
+    data_table::draw_series_chart(
+        ui,
+        data_table::ChartKind::Lines,
+        &labels,
+        &[
+            (
+                "Prompt".to_string(),
+                calls
+                    .iter()
+                    .map(|sample| sample.prompt_tokens as f64)
+                    .collect(),
+            ),
+            (
+                t("Respuesta").to_string(),
+                calls
+                    .iter()
+                    .map(|sample| sample.response_tokens as f64)
+                    .collect(),
+            ),
+        ],
+    );
+    if calls.iter().any(|sample| sample.memory_bytes.is_some()) {
+        ui.add_space(6.0);
+        ui.label(RichText::new(t("Memoria residente (MB)")).color(theme::color_text_weak()));
+        data_table::draw_series_chart(
+            ui,
+            data_table::ChartKind::Lines,
+            &labels,
+            &[(
+                "MB".to_string(),
+                calls
+                    .iter()
+                    .map(|sample| {
+                        sample.memory_bytes.unwrap_or_default() as f64 / (1024.0 * 1024.0)
+                    })
+                    .collect(),
+            )],
+        );
+    }
+}
+
+fn draw_local_settings(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.label("Alias para mencionar a Jarvis en el chat");
+    if ui
+        .text_edit_singleline(&mut state.resources.jarvis_alias)
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    ui.label("Model path");
+    if ui
+        .text_edit_singleline(&mut state.resources.jarvis_model_path)
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    ui.label("Model install directory");
+    if ui
+        .text_edit_singleline(&mut state.resources.jarvis_install_dir)
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    if ui
+        .add(
+            egui::Slider::new(&mut state.config.jarvis.download_parallelism, 1..=8)
+                .text(t("Descargas simultáneas")),
+        )
+        .on_hover_text(
+            t("Archivos del modelo que se descargan a la vez. Las descargas interrumpidas se reanudan al reintentar la instalación."),
+        )
+        .changed()
+    {
+        state.persist_config();
+    }
+
+    if state.resources.installed_local_models.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            "Instala un modelo desde Hugging Face para habilitar Jarvis.",
+        );
+    } else {
+        let mut provider = state.resources.jarvis_selected_provider;
+        let mut available_providers: Vec<LocalModelProvider> = state
+            .resources
+            .installed_local_models
+            .iter()
+            .map(|model| model.identifier.provider)
+            .collect();
+        available_providers.sort();
+        available_providers.dedup();
+
+        if !available_providers.contains(&provider) {
+            provider = state
+                .resources
+                .installed_local_models
+                .first()
+                .map(|model| model.identifier.provider)
+                .unwrap_or(LocalModelProvider::HuggingFace);
+        }
+
+        egui::ComboBox::from_label("Proveedor local")
+            .selected_text(provider.display_name().to_string())
+            .show_ui(ui, |ui| {
+                for candidate in LocalModelProvider::ALL {
+                    if available_providers.contains(&candidate) {
+                        ui.selectable_value(&mut provider, candidate, candidate.display_name());
+                    }
+                }
+            });
+
+        if provider != state.resources.jarvis_selected_provider {
+            state.resources.jarvis_selected_provider = provider;
+            if state
+                .resources
+                .jarvis_active_model
+                .as_ref()
+                .map(|model| model.provider)
+                != Some(provider)
+            {
+                state.resources.jarvis_active_model = None;
+            }
+            state.persist_config();
+        }
 
-fn truncate_middle(value: &str, max_len: usize) -> String {
-    let chars: Vec<char> = value.chars().collect();
-    if chars.len() <= max_len {
-        return value.to_string();
-    }
+        let available_models: Vec<LocalModelIdentifier> = state
+            .resources
+            .installed_local_models
+            .iter()
+            .filter(|model| model.identifier.provider == provider)
+            .map(|model| model.identifier.clone())
+            .collect();
 
-    if max_len <= 3 {
-        return "…".to_string();
-    }
+        let mut selected_model = state
+            .resources
+            .jarvis_active_model
+            .as_ref()
+            .filter(|model| model.provider == provider)
+            .cloned();
 
-    let keep = max_len.saturating_sub(3);
-    let front = keep / 2;
-    let back = keep - front;
-    let start: String = chars.iter().take(front).collect();
-    let end: String = chars
-        .iter()
-        .rev()
-        .take(back)
-        .collect::<Vec<_>>()
-        .into_iter()
-        .rev()
-        .collect();
-    format!("{}…{}", start, end)
-}
+        let current_label = selected_model
+            .as_ref()
+            .map(|model| model.display_label())
+            .unwrap_or_else(|| "Selecciona un modelo instalado".to_string());
 
-fn format_count(value: u64) -> String {
-    if value >= 1_000_000 {
-        let short = value as f64 / 1_000_000.0;
-        if short >= 10.0 {
-            format!("{:.0}M", short)
-        } else {
-            format!("{:.1}M", short)
+        egui::ComboBox::from_label("Modelo local activo")
+            .selected_text(current_label)
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut selected_model, None, "— Sin modelo —");
+                for model in &available_models {
+                    ui.selectable_value(
+                        &mut selected_model,
+                        Some(model.clone()),
+                        model.display_label(),
+                    );
+                }
+            });
+
+        if selected_model != state.resources.jarvis_active_model {
+            if let Some(model) = selected_model.clone() {
+                let status = state.activate_jarvis_model(&model);
+                state.provider_state_mut(provider).install_status = Some(status);
+            } else {
+                let status = state.deactivate_jarvis_model();
+                state.provider_state_mut(provider).install_status = Some(status);
+            }
         }
-    } else if value >= 1_000 {
-        let short = value as f64 / 1_000.0;
-        if short >= 10.0 {
-            format!("{:.0}K", short)
+
+        if let Some(active) = state.resources.jarvis_active_model.clone() {
+            if let Some(record) = state.installed_model(&active) {
+                ui.add_space(10.0);
+                ui.heading(
+                    RichText::new("Resumen del modelo activo")
+                        .color(theme::color_text_primary())
+                        .size(16.0),
+                );
+                ui.add_space(6.0);
+                let install_path = if record.install_path.trim().is_empty() {
+                    Path::new(&state.resources.jarvis_install_dir)
+                        .join(record.identifier.sanitized_dir_name())
+                        .display()
+                        .to_string()
+                } else {
+                    record.install_path.clone()
+                };
+                let path_display = truncate_middle(&install_path, 80);
+
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(34, 38, 44))
+                    .stroke(theme::subtle_border(&state.theme))
+                    .rounding(egui::Rounding::same(12.0))
+                    .inner_margin(egui::Margin::symmetric(14.0, 12.0))
+                    .show(ui, |ui| {
+                        ui.vertical(|ui| {
+                            ui.label(
+                                RichText::new(active.display_label())
+                                    .strong()
+                                    .color(theme::color_text_primary()),
+                            );
+                            ui.add_space(4.0);
+                            ui.label(
+                                RichText::new(format!(
+                                    "{} · Instalado el {}",
+                                    format_bytes(record.size_bytes),
+                                    format_timestamp(record.installed_at)
+                                ))
+                                .color(theme::color_text_weak())
+                                .size(12.0),
+                            );
+                            ui.label(
+                                RichText::new(path_display)
+                                    .color(theme::color_text_weak())
+                                    .size(11.0),
+                            )
+                            .on_hover_text(install_path);
+                        });
+                    });
+            }
+        }
+    }
+
+    if ui
+        .checkbox(
+            &mut state.resources.jarvis_auto_start,
+            "Start Jarvis automatically",
+        )
+        .changed()
+    {
+        state.persist_config();
+        if state.resources.jarvis_auto_start {
+            match state.ensure_jarvis_runtime() {
+                Ok(runtime) => {
+                    state.resources.jarvis_status = Some(format!(
+                        "Jarvis se iniciará automáticamente con {}.",
+                        runtime.model_label()
+                    ));
+                }
+                Err(err) => {
+                    state.resources.jarvis_status =
+                        Some(format!("No se pudo preparar el autoarranque: {}", err));
+                }
+            }
         } else {
-            format!("{:.1}K", short)
+            state.resources.jarvis_status =
+                Some("El autoarranque de Jarvis ha sido desactivado.".to_string());
+            state.resources.jarvis_runtime = None;
         }
-    } else {
-        value.to_string()
     }
-}
 
-fn quick_chip(ui: &mut egui::Ui, label: &str) -> egui::Response {
-    let button = egui::Button::new(
-        RichText::new(label)
-            .color(Color32::from_rgb(228, 228, 228))
-            .strong(),
-    )
-    .min_size(egui::vec2(0.0, 28.0))
-    .fill(Color32::from_rgb(36, 38, 46))
-    .rounding(egui::Rounding::same(10.0));
-    ui.add(button)
-}
+    ui.add_space(8.0);
+    draw_lora_adapters(ui, state);
+    ui.add_space(8.0);
+    draw_model_aliases(ui, state);
+    ui.add_space(8.0);
 
-fn quick_chip_with_icon(ui: &mut egui::Ui, icon: &str, tooltip: &str) -> egui::Response {
-    let button = egui::Button::new(
-        RichText::new(icon)
-            .font(theme::icon_font(14.0))
-            .color(Color32::from_rgb(230, 230, 230)),
-    )
-    .min_size(egui::vec2(32.0, 28.0))
-    .fill(Color32::from_rgb(36, 38, 46))
-    .rounding(egui::Rounding::same(10.0));
-    let response = ui.add(button);
-    response.on_hover_text(tooltip)
-}
+    if ui.button("Apply settings").clicked() {
+        state.resources.jarvis_status = Some(format!(
+            "Jarvis will {} at startup with model at {}.",
+            if state.resources.jarvis_auto_start {
+                "start"
+            } else {
+                "remain stopped"
+            },
+            state.resources.jarvis_model_path
+        ));
+        state.persist_config();
+    }
 
-fn selectable_chip(ui: &mut egui::Ui, label: &str, selected: bool) -> egui::Response {
-    let fill = if selected {
-        theme::color_primary()
-    } else {
-        Color32::from_rgb(44, 46, 54)
-    };
-    let text_color = if selected {
-        Color32::from_rgb(24, 28, 34)
-    } else {
-        Color32::from_rgb(240, 240, 240)
-    };
+    if let Some(status) = &state.resources.jarvis_status {
+        ui.add_space(6.0);
+        ui.colored_label(ui.visuals().weak_text_color(), status);
+    }
+}
 
-    let button = egui::Button::new(RichText::new(label).color(text_color).size(11.0))
-        .min_size(egui::vec2(0.0, 24.0))
-        .fill(fill)
-        .rounding(egui::Rounding::same(10.0));
+/// Alias como `@coder` que responden con otro modelo instalado sin cambiar el activo.
+fn draw_model_aliases(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.strong(t("Modelos por alias"));
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Cada alias responde con su modelo; los menos usados se descargan para respetar el límite de memoria."),
+    );
 
-    ui.add(button)
-}
+    let mut removed = None;
+    for (index, entry) in state.config.jarvis.model_aliases.iter().enumerate() {
+        let model = LocalModelIdentifier::parse(&entry.model);
+        let loaded = state.resources.jarvis_pool.contains(&model);
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(format!("@{}", entry.alias))
+                    .strong()
+                    .color(theme::color_primary()),
+            );
+            ui.label(format!("→ {}", model.display_label()));
+            if loaded {
+                ui.colored_label(theme::color_success(), t("en memoria"));
+            } else if state.installed_model(&model).is_none() {
+                ui.colored_label(theme::color_danger(), t("no instalado"));
+            }
+            if ui
+                .small_button("✕")
+                .on_hover_text(t("Quitar alias"))
+                .clicked()
+            {
+                removed = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removed {
+        state.remove_model_alias(index);
+    }
 
-fn insert_mention(state: &mut AppState, mention: &str) {
-    let trimmed = state.chat.input.trim();
-    if trimmed.starts_with(mention) {
-        if !state.chat.input.ends_with(' ') {
-            state.chat.input.push(' ');
+    let alias_id = ui.id().with("model_alias_draft");
+    let model_id = ui.id().with("model_alias_model");
+    let mut alias = ui.data_mut(|data| data.get_temp::<String>(alias_id).unwrap_or_default());
+    let mut model = ui
+        .data_mut(|data| data.get_temp::<Option<LocalModelIdentifier>>(model_id))
+        .flatten();
+    let installed: Vec<LocalModelIdentifier> = state
+        .resources
+        .installed_local_models
+        .iter()
+        .map(|record| record.identifier.clone())
+        .collect();
+    let mut submitted = false;
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut alias)
+                .hint_text("@coder")
+                .desired_width(120.0),
+        );
+        egui::ComboBox::from_id_source("model_alias_model")
+            .selected_text(
+                model
+                    .as_ref()
+                    .map(|model| model.display_label())
+                    .unwrap_or_else(|| t("Selecciona un modelo instalado").to_string()),
+            )
+            .show_ui(ui, |ui| {
+                for candidate in &installed {
+                    ui.selectable_value(
+                        &mut model,
+                        Some(candidate.clone()),
+                        candidate.display_label(),
+                    );
+                }
+            });
+        submitted = ui
+            .add_enabled(
+                model.is_some() && !alias.trim().is_empty(),
+                egui::Button::new(t("Añadir alias")),
+            )
+            .clicked();
+    });
+    if let (true, Some(selected)) = (submitted, model.as_ref()) {
+        match state.add_model_alias(&alias, selected) {
+            Ok(message) => {
+                state.resources.jarvis_status = Some(message);
+                alias.clear();
+                model = None;
+            }
+            Err(err) => state.resources.jarvis_status = Some(err),
         }
-        return;
     }
+    ui.data_mut(|data| {
+        data.insert_temp(alias_id, alias);
+        data.insert_temp(model_id, model);
+    });
 
-    if trimmed.is_empty() {
-        state.chat.input = format!("{} ", mention);
-    } else {
-        state.chat.input = format!("{} {}", mention, trimmed);
+    let loaded = state.resources.jarvis_pool.loaded().len();
+    if loaded > 0 {
+        let used = format_bytes(state.resources.jarvis_pool.used_bytes());
+        ui.horizontal(|ui| {
+            ui.label(tf(
+                "{0} modelos por alias en memoria ({1}).",
+                &[&loaded.to_string(), &used],
+            ));
+            if ui.small_button(t("Descargar")).clicked() {
+                state.unload_alias_models();
+            }
+        });
     }
 }
 
-fn insert_code_template(state: &mut AppState) {
-    let template = "```language\n\n```";
-    if state.chat.input.trim().is_empty() {
-        state.chat.input = template.to_string();
+fn draw_lora_adapters(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.strong(t("Adaptadores LoRA"));
+        if ui.small_button(t("Buscar adaptadores")).clicked() {
+            state.refresh_lora_adapters();
+        }
+    });
+
+    if state.resources.jarvis_adapters.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Coloca adaptadores PEFT (adapter_config.json y adapter_model.safetensors) en la carpeta 'adapters' del modelo o en el directorio de instalación."),
+        );
     } else {
-        if !state.chat.input.ends_with('\n') {
-            state.chat.input.push('\n');
+        let active_model = state
+            .resources
+            .jarvis_active_model
+            .as_ref()
+            .map(|model| model.model_id.clone());
+        let mut toggled = None;
+        for adapter in &state.resources.jarvis_adapters {
+            let mut enabled = state.chat.lora_adapters.contains(&adapter.path);
+            let compatible = adapter.targets(active_model.as_deref());
+            ui.horizontal(|ui| {
+                let label = format!(
+                    "{} · r={} · α={}",
+                    adapter.name, adapter.rank, adapter.alpha
+                );
+                let response = ui.checkbox(&mut enabled, label).on_hover_text(
+                    adapter
+                        .base_model
+                        .as_deref()
+                        .map(|base| tf("Entrenado sobre {0}", &[base]))
+                        .unwrap_or_else(|| t("Sin modelo base declarado").to_string()),
+                );
+                if response.changed() {
+                    toggled = Some(adapter.path.clone());
+                }
+                if !compatible {
+                    ui.colored_label(theme::color_text_weak(), t("⚠ otro modelo base"));
+                }
+            });
         }
-        state.chat.input.push_str(template);
+        if let Some(path) = toggled {
+            state.toggle_lora_adapter(&path);
+        }
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("La selección es propia de esta conversación y se fusiona con los pesos al recargar el modelo."),
+        );
     }
+
+    let active = state
+        .resources
+        .jarvis_runtime
+        .as_ref()
+        .map(|runtime| runtime.active_adapters().join(", "))
+        .filter(|adapters| !adapters.is_empty());
+    ui.label(match active {
+        Some(adapters) => tf("Activos en el runtime: {0}", &[&adapters]),
+        None => t("Activos en el runtime: ninguno").to_string(),
+    });
 }
 
-fn draw_local_settings(ui: &mut egui::Ui, state: &mut AppState) {
-    ui.label("Alias para mencionar a Jarvis en el chat");
+/// Edita una lista de líneas en un único campo multilínea, conservando las líneas vacías
+/// mientras se escribe; se normalizan al aplicar.
+fn edit_line_list(ui: &mut egui::Ui, lines: &mut Vec<String>, hint: &str, rows: usize) {
+    let mut text = lines.join("\n");
     if ui
-        .text_edit_singleline(&mut state.resources.jarvis_alias)
+        .add(
+            egui::TextEdit::multiline(&mut text)
+                .desired_rows(rows)
+                .desired_width(f32::INFINITY)
+                .hint_text(hint),
+        )
         .changed()
     {
-        state.persist_config();
+        *lines = text.split('\n').map(str::to_string).collect();
     }
+}
 
-    ui.label("Model path");
-    if ui
-        .text_edit_singleline(&mut state.resources.jarvis_model_path)
-        .changed()
-    {
-        state.persist_config();
-    }
+/// Acciones que admite el clasificador, que no señala fragmentos que redactar.
+const CLASSIFIER_ACTIONS: [SafetyAction; 2] = [SafetyAction::Warn, SafetyAction::Block];
+const SAFETY_ACTIONS: [SafetyAction; 3] = [
+    SafetyAction::Warn,
+    SafetyAction::Redact,
+    SafetyAction::Block,
+];
 
-    ui.label("Model install directory");
-    if ui
-        .text_edit_singleline(&mut state.resources.jarvis_install_dir)
-        .changed()
-    {
-        state.persist_config();
+fn draw_safety_action_combo(
+    ui: &mut egui::Ui,
+    id: impl std::hash::Hash,
+    action: &mut SafetyAction,
+    options: &[SafetyAction],
+) -> bool {
+    let mut changed = false;
+    egui::ComboBox::from_id_source(id)
+        .selected_text(t(safety::action_label(*action)))
+        .width(110.0)
+        .show_ui(ui, |ui| {
+            for option in options {
+                changed |= ui
+                    .selectable_value(action, *option, t(safety::action_label(*option)))
+                    .changed();
+            }
+        });
+    changed
+}
+
+fn draw_local_safety(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = ui
+        .checkbox(
+            &mut state.config.safety_filters.enabled,
+            t("Filtrar los prompts y las respuestas de Jarvis y los modelos locales"),
+        )
+        .changed();
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Cada conversación puede activarlos o desactivarlos desde sus ajustes. Las palabras se separan por comas; las expresiones regulares usan la sintaxis del crate regex."),
+    );
+    ui.horizontal(|ui| {
+        ui.label(t("Texto de reemplazo"));
+        changed |= ui
+            .add(
+                egui::TextEdit::singleline(&mut state.config.safety_filters.replacement)
+                    .desired_width(140.0),
+            )
+            .lost_focus();
+    });
+    ui.add_space(8.0);
+
+    let mut remove = None;
+    egui::Grid::new("safety_rules")
+        .num_columns(6)
+        .striped(true)
+        .spacing(egui::vec2(10.0, 6.0))
+        .show(ui, |ui| {
+            for header in ["", "Nombre", "Regex", "Patrón", "Acción", ""] {
+                ui.label(
+                    RichText::new(t(header))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+            }
+            ui.end_row();
+
+            for (index, rule) in state.config.safety_filters.rules.iter_mut().enumerate() {
+                changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                changed |= ui
+                    .add(egui::TextEdit::singleline(&mut rule.name).desired_width(140.0))
+                    .lost_focus();
+                changed |= ui.checkbox(&mut rule.regex, "").changed();
+                let pattern = ui.add(
+                    egui::TextEdit::singleline(&mut rule.pattern)
+                        .code_editor()
+                        .desired_width(300.0),
+                );
+                changed |= pattern.lost_focus();
+                if let Some(error) = state.safety.errors.get(&index) {
+                    pattern.on_hover_text(error.as_str());
+                }
+                changed |= draw_safety_action_combo(
+                    ui,
+                    ("safety_rule_action", index),
+                    &mut rule.action,
+                    &SAFETY_ACTIONS,
+                );
+                if ui
+                    .small_button(RichText::new(ICON_TRASH).font(theme::icon_font(12.0)))
+                    .on_hover_text(t("Eliminar regla"))
+                    .clicked()
+                {
+                    remove = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+    ui.add_space(6.0);
+    if ui.button(t("Añadir regla")).clicked() {
+        state.config.safety_filters.rules.push(SafetyFilterRule {
+            name: t("Nueva regla").to_string(),
+            enabled: true,
+            ..SafetyFilterRule::default()
+        });
+        changed = true;
+    }
+    if let Some(index) = remove {
+        state.config.safety_filters.rules.remove(index);
+        changed = true;
     }
 
-    if state.resources.installed_local_models.is_empty() {
+    state.safety.validate(&state.config.safety_filters);
+    for (index, error) in &state.safety.errors {
+        let name = state
+            .config
+            .safety_filters
+            .rules
+            .get(*index)
+            .map(|rule| rule.name.as_str())
+            .unwrap_or_default();
         ui.colored_label(
-            theme::color_text_weak(),
-            "Instala un modelo desde Hugging Face para habilitar Jarvis.",
+            theme::color_danger(),
+            tf("La regla '{0}' no es válida: {1}", &[name, error]),
         );
-    } else {
-        let mut provider = state.resources.jarvis_selected_provider;
-        let mut available_providers: Vec<LocalModelProvider> = state
-            .resources
-            .installed_local_models
-            .iter()
-            .map(|model| model.identifier.provider)
-            .collect();
-        available_providers.sort();
-        available_providers.dedup();
-
-        if !available_providers.contains(&provider) {
-            provider = state
-                .resources
-                .installed_local_models
-                .first()
-                .map(|model| model.identifier.provider)
-                .unwrap_or(LocalModelProvider::HuggingFace);
-        }
+    }
 
-        egui::ComboBox::from_label("Proveedor local")
-            .selected_text(provider.display_name().to_string())
-            .show_ui(ui, |ui| {
-                for candidate in LocalModelProvider::ALL {
-                    if available_providers.contains(&candidate) {
-                        ui.selectable_value(&mut provider, candidate, candidate.display_name());
-                    }
+    ui.add_space(12.0);
+    let classifier = &mut state.config.safety_filters.classifier;
+    changed |= ui
+        .checkbox(
+            &mut classifier.enabled,
+            t("Clasificador por similitud con temas vetados"),
+        )
+        .changed();
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Usa el backend de embeddings configurado y compara cada texto con la descripción de cada tema."),
+    );
+    let mut remove_topic = None;
+    ui.add_enabled_ui(classifier.enabled, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(t("Similitud mínima"));
+            changed |= ui
+                .add(egui::Slider::new(&mut classifier.threshold, 0.3..=0.95))
+                .changed();
+            ui.label(t("Acción"));
+            changed |= draw_safety_action_combo(
+                ui,
+                "safety_classifier_action",
+                &mut classifier.action,
+                &CLASSIFIER_ACTIONS,
+            );
+        });
+        for (index, topic) in classifier.topics.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .add(egui::TextEdit::singleline(topic).desired_width(420.0))
+                    .lost_focus();
+                if ui
+                    .small_button(RichText::new(ICON_TRASH).font(theme::icon_font(12.0)))
+                    .on_hover_text(t("Eliminar tema"))
+                    .clicked()
+                {
+                    remove_topic = Some(index);
                 }
             });
+        }
+        if ui.button(t("Añadir tema")).clicked() {
+            classifier.topics.push(String::new());
+        }
+    });
+    if let Some(index) = remove_topic {
+        classifier.topics.remove(index);
+        changed = true;
+    }
+    if changed {
+        state.persist_config();
+    }
 
-        if provider != state.resources.jarvis_selected_provider {
-            state.resources.jarvis_selected_provider = provider;
-            if state
-                .resources
-                .jarvis_active_model
-                .as_ref()
-                .map(|model| model.provider)
-                != Some(provider)
-            {
-                state.resources.jarvis_active_model = None;
+    ui.add_space(12.0);
+    ui.strong(t("Probar las reglas"));
+    ui.add(
+        egui::TextEdit::multiline(&mut state.safety.sample)
+            .hint_text(t(
+                "Pega una respuesta para ver cómo quedaría (sin el clasificador)",
+            ))
+            .desired_rows(3)
+            .desired_width(f32::INFINITY),
+    );
+    let sample = state.safety.sample.clone();
+    if !sample.trim().is_empty() {
+        let outcome = state
+            .safety
+            .filter(&state.config.safety_filters, &sample, None);
+        match outcome.action() {
+            None => {
+                ui.colored_label(theme::color_success(), t("Ninguna regla coincide."));
+            }
+            Some(SafetyAction::Block) => {
+                ui.colored_label(
+                    theme::color_danger(),
+                    tf(
+                        "Bloqueado por '{0}'.",
+                        &[outcome.deciding_rule().unwrap_or_default()],
+                    ),
+                );
+            }
+            Some(action) => {
+                ui.label(
+                    RichText::new(tf(
+                        "{0}: {1} coincidencias.",
+                        &[
+                            t(safety::action_label(action)),
+                            &outcome.hits.len().to_string(),
+                        ],
+                    ))
+                    .color(theme::color_text_weak()),
+                );
+                egui::Frame::none()
+                    .fill(Color32::from_rgb(26, 28, 32))
+                    .stroke(theme::subtle_border(&state.theme))
+                    .inner_margin(egui::Margin::same(8.0))
+                    .show(ui, |ui| {
+                        ui.label(outcome.text.as_str());
+                    });
             }
-            state.persist_config();
         }
+    }
 
-        let available_models: Vec<LocalModelIdentifier> = state
-            .resources
-            .installed_local_models
-            .iter()
-            .filter(|model| model.identifier.provider == provider)
-            .map(|model| model.identifier.clone())
-            .collect();
-
-        let mut selected_model = state
-            .resources
-            .jarvis_active_model
-            .as_ref()
-            .filter(|model| model.provider == provider)
-            .cloned();
+    ui.add_space(12.0);
+    ui.horizontal(|ui| {
+        ui.strong(t("Coincidencias de la sesión"));
+        if !state.safety.log.is_empty() && ui.small_button(t("Vaciar")).clicked() {
+            state.safety.log.clear();
+        }
+    });
+    if state.safety.log.is_empty() {
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Sin coincidencias. También quedan en el registro de auditoría."),
+        );
+        return;
+    }
+    egui::ScrollArea::vertical()
+        .id_source("safety_log")
+        .max_height(220.0)
+        .show(ui, |ui| {
+            egui::Grid::new("safety_log_grid")
+                .num_columns(5)
+                .striped(true)
+                .spacing(egui::vec2(10.0, 4.0))
+                .show(ui, |ui| {
+                    for entry in &state.safety.log {
+                        ui.label(
+                            RichText::new(&entry.timestamp)
+                                .color(theme::color_text_weak())
+                                .size(11.0),
+                        );
+                        ui.label(t(entry.stage.label()));
+                        ui.label(&entry.rule);
+                        let color = match entry.action {
+                            SafetyAction::Block => theme::color_danger(),
+                            _ => theme::color_text_primary(),
+                        };
+                        ui.colored_label(color, t(safety::action_label(entry.action)));
+                        ui.label(RichText::new(&entry.excerpt).monospace().size(11.0))
+                            .on_hover_text(&entry.conversation);
+                        ui.end_row();
+                    }
+                });
+        });
+}
 
-        let current_label = selected_model
-            .as_ref()
-            .map(|model| model.display_label())
-            .unwrap_or_else(|| "Selecciona un modelo instalado".to_string());
+fn draw_local_ocr(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    let mut refresh = false;
+    {
+        let ocr = &mut state.config.ocr;
+        changed |= ui
+            .checkbox(
+                &mut ocr.enabled,
+                t("Reconocer el texto de las imágenes adjuntas"),
+            )
+            .changed();
+        egui::Grid::new("ocr_settings")
+            .num_columns(2)
+            .spacing([12.0, 6.0])
+            .show(ui, |ui| {
+                ui.label(t("Ejecutable"));
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(&mut ocr.tesseract_command)
+                            .hint_text("tesseract"),
+                    )
+                    .lost_focus();
+                ui.end_row();
 
-        egui::ComboBox::from_label("Modelo local activo")
-            .selected_text(current_label)
-            .show_ui(ui, |ui| {
-                ui.selectable_value(&mut selected_model, None, "— Sin modelo —");
-                for model in &available_models {
-                    ui.selectable_value(
-                        &mut selected_model,
-                        Some(model.clone()),
-                        model.display_label(),
+                ui.label(t("Idiomas"));
+                ui.horizontal_wrapped(|ui| {
+                    let mut remove = None;
+                    for (index, language) in ocr.languages.iter().enumerate() {
+                        if quick_chip(ui, &format!("{} ✕", language))
+                            .on_hover_text(t("Quitar"))
+                            .clicked()
+                        {
+                            remove = Some(index);
+                        }
+                    }
+                    if let Some(index) = remove {
+                        ocr.languages.remove(index);
+                        changed = true;
+                    }
+                    if ocr.languages.is_empty() {
+                        ui.colored_label(
+                            theme::color_text_weak(),
+                            t("Ninguno: Tesseract usará inglés."),
+                        );
+                    }
+                });
+                ui.end_row();
+
+                ui.label("");
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut state.ocr.language_draft)
+                            .hint_text("spa, eng, fra…")
+                            .desired_width(120.0),
                     );
-                }
+                    let code = state.ocr.language_draft.trim().to_string();
+                    if ui
+                        .add_enabled(!code.is_empty(), egui::Button::new(t("Añadir")))
+                        .clicked()
+                    {
+                        if !ocr.languages.contains(&code) {
+                            ocr.languages.push(code);
+                            changed = true;
+                        }
+                        state.ocr.language_draft.clear();
+                    }
+                    refresh = ui.button(t("Detectar paquetes instalados")).clicked();
+                });
+                ui.end_row();
             });
 
-        if selected_model != state.resources.jarvis_active_model {
-            if let Some(model) = selected_model.clone() {
-                let status = state.activate_jarvis_model(&model);
-                state.provider_state_mut(provider).install_status = Some(status);
-            } else {
-                let status = state.deactivate_jarvis_model();
-                state.provider_state_mut(provider).install_status = Some(status);
+        if !state.ocr.installed_languages.is_empty() {
+            ui.label(RichText::new(t("Paquetes instalados")).strong());
+            ui.horizontal_wrapped(|ui| {
+                for language in &state.ocr.installed_languages {
+                    let mut selected = ocr.languages.contains(language);
+                    if ui.checkbox(&mut selected, language).changed() {
+                        if selected {
+                            ocr.languages.push(language.clone());
+                        } else {
+                            ocr.languages.retain(|code| code != language);
+                        }
+                        changed = true;
+                    }
+                }
+            });
+            let missing: Vec<&str> = ocr
+                .languages
+                .iter()
+                .filter(|code| !state.ocr.installed_languages.contains(*code))
+                .map(String::as_str)
+                .collect();
+            if !missing.is_empty() {
+                ui.colored_label(
+                    theme::color_danger(),
+                    tf(
+                        "Sin instalar: {0}. Tesseract fallará hasta que instales sus paquetes.",
+                        &[&missing.join(", ")],
+                    ),
+                );
             }
         }
 
-        if let Some(active) = state.resources.jarvis_active_model.clone() {
-            if let Some(record) = state.installed_model(&active) {
-                ui.add_space(10.0);
-                ui.heading(
-                    RichText::new("Resumen del modelo activo")
-                        .color(theme::color_text_primary())
-                        .size(16.0),
+        ui.add_space(8.0);
+        changed |= ui
+            .checkbox(
+                &mut ocr.index_project_images,
+                t("Incluir las imágenes de los proyectos al reindexar la base de conocimiento"),
+            )
+            .changed();
+    }
+    if refresh {
+        let command = state.config.ocr.tesseract_command.clone();
+        state.ocr.refresh_languages(&command);
+    }
+    if changed {
+        state.persist_config();
+    }
+
+    if let Some(status) = &state.ocr.status {
+        ui.colored_label(theme::color_text_weak(), status);
+    }
+    ui.colored_label(
+        theme::color_text_weak(),
+        t("Requiere Tesseract instalado con los paquetes de idioma elegidos (p. ej. tesseract-ocr-spa). El texto reconocido acompaña a tus preguntas como el de los documentos adjuntos."),
+    );
+}
+
+fn draw_local_knowledge(ui: &mut egui::Ui, state: &mut AppState) {
+    let tokens = state.theme.clone();
+    ui.label(
+        RichText::new(t(
+            "Cada entrada agrupa frases de activación: Jarvis compara el prompt con su huella semántica y responde con la entrada más parecida.",
+        ))
+        .color(tokens.palette.text_weak),
+    );
+    ui.add_space(8.0);
+
+    let mut remove = None;
+    ui.horizontal(|ui| {
+        if ui.button(t("Añadir entrada")).clicked() {
+            state
+                .resources
+                .jarvis_knowledge
+                .push(JarvisKnowledgeEntry::new(t("Nueva entrada")));
+            state.resources.jarvis_knowledge_selected =
+                Some(state.resources.jarvis_knowledge.len() - 1);
+        }
+        if ui
+            .button(t("Restaurar predeterminadas"))
+            .on_hover_text(t("Sustituye las entradas por la base curada de Jarvis."))
+            .clicked()
+        {
+            state.resources.jarvis_knowledge = JarvisKnowledgeEntry::defaults();
+            state.resources.jarvis_knowledge_selected = None;
+        }
+    });
+
+    egui::ScrollArea::vertical()
+        .id_source("jarvis_knowledge_entries")
+        .max_height(180.0)
+        .show(ui, |ui| {
+            for (index, entry) in state.resources.jarvis_knowledge.iter().enumerate() {
+                let selected = state.resources.jarvis_knowledge_selected == Some(index);
+                let label = format!(
+                    "{} · {}",
+                    entry.label,
+                    tf(
+                        "{0} frases",
+                        &[&entry.normalized_triggers().len().to_string()]
+                    )
                 );
-                ui.add_space(6.0);
-                let install_path = if record.install_path.trim().is_empty() {
-                    Path::new(&state.resources.jarvis_install_dir)
-                        .join(record.identifier.sanitized_dir_name())
-                        .display()
-                        .to_string()
-                } else {
-                    record.install_path.clone()
-                };
-                let path_display = truncate_middle(&install_path, 80);
+                if ui.selectable_label(selected, label).clicked() {
+                    state.resources.jarvis_knowledge_selected = Some(index);
+                }
+            }
+        });
 
-                egui::Frame::none()
-                    .fill(Color32::from_rgb(34, 38, 44))
-                    .stroke(theme::subtle_border(&state.theme))
-                    .rounding(egui::Rounding::same(12.0))
-                    .inner_margin(egui::Margin::symmetric(14.0, 12.0))
-                    .show(ui, |ui| {
-                        ui.vertical(|ui| {
-                            ui.label(
-                                RichText::new(active.display_label())
-                                    .strong()
-                                    .color(theme::color_text_primary()),
-                            );
-                            ui.add_space(4.0);
-                            ui.label(
-                                RichText::new(format!(
-                                    "{} · Instalado el {}",
-                                    format_bytes(record.size_bytes),
-                                    format_timestamp(record.installed_at)
-                                ))
-                                .color(theme::color_text_weak())
-                                .size(12.0),
+    let selected = state
+        .resources
+        .jarvis_knowledge_selected
+        .filter(|index| *index < state.resources.jarvis_knowledge.len());
+    if let Some(index) = selected {
+        ui.separator();
+        let entry = &mut state.resources.jarvis_knowledge[index];
+        egui::Grid::new("jarvis_knowledge_entry")
+            .num_columns(2)
+            .spacing(egui::vec2(12.0, 8.0))
+            .show(ui, |ui| {
+                ui.label(t("Nombre"));
+                ui.text_edit_singleline(&mut entry.label);
+                ui.end_row();
+
+                ui.label(t("Persona integrada"));
+                let persona_label = entry.persona.clone().unwrap_or_else(|| t("Ninguna").into());
+                egui::ComboBox::from_id_source("jarvis_knowledge_persona")
+                    .selected_text(persona_label)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut entry.persona, None, t("Ninguna"));
+                        for persona in JarvisKnowledgeEntry::builtin_personas() {
+                            ui.selectable_value(
+                                &mut entry.persona,
+                                Some(persona.to_string()),
+                                persona,
                             );
-                            ui.label(
-                                RichText::new(path_display)
-                                    .color(theme::color_text_weak())
-                                    .size(11.0),
-                            )
-                            .on_hover_text(install_path);
-                        });
+                        }
                     });
-            }
+                ui.end_row();
+            });
+
+        ui.label(t("Frases de activación (una por línea)"));
+        edit_line_list(ui, &mut entry.triggers, t("hola jarvis"), 4);
+        ui.label(t("Respuesta"));
+        ui.add(
+            egui::TextEdit::multiline(&mut entry.response)
+                .desired_rows(4)
+                .desired_width(f32::INFINITY)
+                .hint_text(t("Vacía para usar la persona integrada")),
+        );
+        ui.label(t("Documentos fuente (una ruta por línea)"));
+        edit_line_list(ui, &mut entry.sources, "docs/guia.md", 2);
+
+        if ui.button(t("Eliminar entrada")).clicked() {
+            remove = Some(index);
         }
     }
+    if let Some(index) = remove {
+        state.resources.jarvis_knowledge.remove(index);
+        state.resources.jarvis_knowledge_selected = None;
+    }
 
-    if ui
-        .checkbox(
-            &mut state.resources.jarvis_auto_start,
-            "Start Jarvis automatically",
-        )
-        .changed()
-    {
-        state.persist_config();
-        if state.resources.jarvis_auto_start {
-            match state.ensure_jarvis_runtime() {
-                Ok(runtime) => {
-                    state.resources.jarvis_status = Some(format!(
-                        "Jarvis se iniciará automáticamente con {}.",
-                        runtime.model_label()
-                    ));
-                }
-                Err(err) => {
-                    state.resources.jarvis_status =
-                        Some(format!("No se pudo preparar el autoarranque: {}", err));
-                }
-            }
-        } else {
-            state.resources.jarvis_status =
-                Some("El autoarranque de Jarvis ha sido desactivado.".to_string());
-            state.resources.jarvis_runtime = None;
+    ui.separator();
+    ui.label(
+        RichText::new(t("Probar enrutado"))
+            .color(tokens.palette.text_primary)
+            .strong(),
+    );
+    ui.horizontal(|ui| {
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut state.resources.jarvis_knowledge_probe)
+                .hint_text(t("Escribe un prompt de prueba")),
+        );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if ui.button(t("Probar")).clicked() || submitted {
+            state.probe_jarvis_knowledge();
         }
+    });
+    if !state.resources.jarvis_knowledge_matches.is_empty() {
+        egui::Grid::new("jarvis_knowledge_matches")
+            .num_columns(2)
+            .spacing(egui::vec2(16.0, 4.0))
+            .show(ui, |ui| {
+                for (position, candidate) in state
+                    .resources
+                    .jarvis_knowledge_matches
+                    .iter()
+                    .take(5)
+                    .enumerate()
+                {
+                    let text = RichText::new(&candidate.label);
+                    if position == 0 && candidate.is_routed() {
+                        ui.label(text.strong().color(tokens.palette.primary));
+                    } else {
+                        ui.label(text);
+                    }
+                    ui.label(format!("{:.0}%", candidate.score * 100.0));
+                    ui.end_row();
+                }
+            });
     }
 
-    if ui.button("Apply settings").clicked() {
-        state.resources.jarvis_status = Some(format!(
-            "Jarvis will {} at startup with model at {}.",
-            if state.resources.jarvis_auto_start {
-                "start"
-            } else {
-                "remain stopped"
-            },
-            state.resources.jarvis_model_path
-        ));
-        state.persist_config();
-    }
+    ui.add_space(8.0);
+    let dirty = state.jarvis_knowledge_dirty();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(dirty, egui::Button::new(t("Aplicar y recargar")))
+            .clicked()
+        {
+            if let Err(err) = state.apply_jarvis_knowledge() {
+                state.resources.jarvis_knowledge_status = Some(err);
+            }
+        }
+        if ui
+            .add_enabled(dirty, egui::Button::new(t("Descartar cambios")))
+            .clicked()
+        {
+            state.discard_jarvis_knowledge_changes();
+        }
+    });
 
-    if let Some(status) = &state.resources.jarvis_status {
+    if let Some(status) = &state.resources.jarvis_knowledge_status {
         ui.add_space(6.0);
         ui.colored_label(ui.visuals().weak_text_color(), status);
     }
@@ -5919,10 +14913,56 @@ fn draw_provider_anthropic_configuration(ui: &mut egui::Ui, state: &mut AppState
     }
 
     ui.label("Default Claude model");
-    if ui
-        .text_edit_singleline(&mut state.resources.claude_default_model)
-        .changed()
-    {
+    let mut model_changed = false;
+    ui.horizontal(|ui| {
+        if state.resources.claude_available_models.is_empty() {
+            model_changed = ui
+                .text_edit_singleline(&mut state.resources.claude_default_model)
+                .changed();
+        } else {
+            let models = &state.resources.claude_available_models;
+            let selected = &mut state.resources.claude_default_model;
+            let selected_text = models
+                .iter()
+                .find(|model| model.id == *selected)
+                .map(|model| model.label().to_string())
+                .unwrap_or_else(|| selected.clone());
+            egui::ComboBox::from_id_source("claude_default_model")
+                .selected_text(selected_text)
+                .width(260.0)
+                .show_ui(ui, |ui| {
+                    for model in models {
+                        let label = match model.context_window {
+                            Some(context) => format!("{} · {} tokens", model.label(), context),
+                            None => model.label().to_string(),
+                        };
+                        if ui
+                            .selectable_label(*selected == model.id, label)
+                            .on_hover_text(&model.id)
+                            .clicked()
+                            && *selected != model.id
+                        {
+                            *selected = model.id.clone();
+                            model_changed = true;
+                        }
+                    }
+                });
+        }
+
+        let refreshing = state.resources.claude_models_rx.is_some();
+        if refreshing {
+            ui.spinner();
+        } else if ui
+            .button(t("Actualizar lista"))
+            .on_hover_text(t(
+                "Consulta los modelos disponibles en la API de Anthropic.",
+            ))
+            .clicked()
+        {
+            state.refresh_claude_models();
+        }
+    });
+    if model_changed {
         state.persist_config();
     }
 
@@ -5957,15 +14997,15 @@ fn draw_provider_anthropic_configuration(ui: &mut egui::Ui, state: &mut AppState
         ui.add_space(6.0);
         ui.colored_label(ui.visuals().weak_text_color(), status);
     }
+
+    draw_model_quick_actions(ui, state, RemoteProviderKind::Anthropic);
 }
 
 fn draw_claude_models_tab(ui: &mut egui::Ui, state: &mut AppState) {
-    let anthropic_key = state.config.anthropic.api_key.clone().unwrap_or_default();
-    let anthropic_key_trimmed = anthropic_key.trim().to_string();
-    draw_claude_catalog(ui, state, anthropic_key_trimmed.as_str());
+    draw_claude_catalog(ui, state);
 }
 
-fn draw_claude_catalog(ui: &mut egui::Ui, state: &mut AppState, anthropic_key: &str) {
+fn draw_claude_catalog(ui: &mut egui::Ui, state: &mut AppState) {
     ui.add_space(16.0);
     ui.separator();
     ui.add_space(10.0);
@@ -5984,42 +15024,23 @@ fn draw_claude_catalog(ui: &mut egui::Ui, state: &mut AppState, anthropic_key: &
     );
     ui.add_space(10.0);
 
-    let mut refresh_triggered = false;
-    if ui
-        .add_sized(
-            [180.0, 32.0],
+    let refreshing = state.resources.claude_models_rx.is_some();
+    ui.horizontal(|ui| {
+        let button = ui.add_enabled(
+            !refreshing,
             theme::primary_button(
                 RichText::new("Actualizar catálogo").color(Color32::from_rgb(240, 240, 240)),
                 &state.theme,
-            ),
-        )
-        .clicked()
-    {
-        refresh_triggered = true;
-    }
-
-    if refresh_triggered {
-        if anthropic_key.is_empty() {
-            state.resources.claude_models_status =
-                Some("Ingresa una API key válida antes de solicitar el catálogo.".to_string());
-        } else {
-            match crate::api::claude::list_models(anthropic_key) {
-                Ok(models) => {
-                    let count = models.len();
-                    state.resources.claude_available_models = models;
-                    state.resources.claude_models_status = Some(if count == 0 {
-                        "No se encontraron modelos disponibles para esta cuenta.".to_string()
-                    } else {
-                        format!("Se encontraron {count} modelos disponibles.")
-                    });
-                }
-                Err(err) => {
-                    state.resources.claude_models_status =
-                        Some(format!("No se pudo obtener el listado de modelos: {}", err));
-                }
-            }
+            )
+            .min_size(egui::vec2(180.0, 32.0)),
+        );
+        if button.clicked() {
+            state.refresh_claude_models();
         }
-    }
+        if refreshing {
+            ui.spinner();
+        }
+    });
 
     if let Some(status) = &state.resources.claude_models_status {
         ui.add_space(6.0);
@@ -6045,6 +15066,7 @@ fn draw_local_library_overview(ui: &mut egui::Ui, state: &mut AppState) {
             theme::color_text_weak(),
             "Aún no hay modelos instalados. Usa una galería local para descargar uno y aparecerá aquí.",
         );
+        draw_local_library_maintenance(ui, state);
         return;
     }
 
@@ -6082,11 +15104,47 @@ fn draw_local_library_overview(ui: &mut egui::Ui, state: &mut AppState) {
         }
     }
 
+    ui.add_space(6.0);
+    ui.horizontal(|ui| {
+        let checking = state.resources.model_updates.is_checking();
+        if ui
+            .add_enabled(!checking, egui::Button::new(t("Buscar actualizaciones")))
+            .on_hover_text(t("Compara la revisión instalada de cada modelo de Hugging Face con la última publicada."))
+            .clicked()
+        {
+            let count = state.check_local_model_updates();
+            if count == 0 {
+                state.resources.local_library.operation_feedback =
+                    Some(t("No hay modelos de Hugging Face que comprobar.").to_string());
+            }
+        }
+        if checking {
+            ui.spinner();
+        }
+        let updates = &state.resources.model_updates;
+        if let Some(last_checked) = &updates.last_checked {
+            let outdated = updates.outdated_count();
+            let summary = if outdated == 0 {
+                tf("Última comprobación: {0}", &[last_checked])
+            } else {
+                tf(
+                    "Última comprobación: {0} · {1} con actualizaciones",
+                    &[last_checked, &outdated.to_string()],
+                )
+            };
+            ui.label(
+                RichText::new(summary)
+                    .color(theme::color_text_weak())
+                    .size(11.0),
+            );
+        }
+    });
+
     ui.add_space(8.0);
     let filter_lower = state.resources.local_library.filter.to_lowercase();
     let show_only_ready = state.resources.local_library.show_only_ready;
     let installed = state.resources.installed_local_models.clone();
-    let mut removals: Vec<LocalModelIdentifier> = Vec::new();
+    let mut removals: Vec<(LocalModelIdentifier, UninstallMode)> = Vec::new();
     let mut pending_feedback: Option<String> = None;
 
     for record in installed.iter() {
@@ -6191,6 +15249,14 @@ fn draw_local_library_overview(ui: &mut egui::Ui, state: &mut AppState) {
                         );
                     }
 
+                    let update_status = state
+                        .resources
+                        .model_updates
+                        .status(&record.identifier)
+                        .cloned();
+                    draw_model_update_status(ui, record, update_status.as_ref());
+                    draw_model_quantization_status(ui, state, record);
+
                     ui.add_space(8.0);
                     ui.horizontal(|ui| {
                         if ui.button("Activar").clicked() {
@@ -6200,21 +15266,96 @@ fn draw_local_library_overview(ui: &mut egui::Ui, state: &mut AppState) {
                                 Some(record.identifier.clone());
                         }
 
-                        if ui.button("Actualizar").clicked() {
-                            if let Some(status) = state.mark_local_model_updated(&record.identifier)
-                            {
+                        let busy = update_status
+                            .as_ref()
+                            .is_some_and(ModelUpdateStatus::is_busy);
+                        let update_label =
+                            if matches!(update_status, Some(ModelUpdateStatus::Available { .. })) {
+                                RichText::new("Actualizar").color(theme::color_primary())
+                            } else {
+                                RichText::new("Actualizar")
+                            };
+                        if ui
+                            .add_enabled(!busy, egui::Button::new(update_label))
+                            .on_hover_text(t(
+                                "Descarga solo los archivos que cambiaron en la última revisión.",
+                            ))
+                            .clicked()
+                        {
+                            let status =
+                                if record.identifier.provider == LocalModelProvider::HuggingFace {
+                                    Some(state.queue_local_model_update(&record.identifier))
+                                } else {
+                                    state.mark_local_model_updated(&record.identifier)
+                                };
+                            if let Some(status) = status {
                                 pending_feedback = Some(status);
                             }
                             state.resources.local_library.selection =
                                 Some(record.identifier.clone());
                         }
 
-                        if ui
-                            .button(RichText::new("Eliminar").color(theme::color_danger()))
-                            .clicked()
-                        {
-                            removals.push(record.identifier.clone());
+                        if record.quantized_from.is_none() {
+                            let idle = state.resources.model_quantize.job.is_none();
+                            ui.add_enabled_ui(idle, |ui| {
+                                ui.menu_button(t("Cuantizar"), |ui| {
+                                    for level in quantize::QuantizationLevel::ALL {
+                                        if ui.button(t(level.label())).clicked() {
+                                            pending_feedback = Some(
+                                                match state.start_model_quantization(
+                                                    &record.identifier,
+                                                    level,
+                                                ) {
+                                                    Ok(status) | Err(status) => status,
+                                                },
+                                            );
+                                            ui.close_menu();
+                                        }
+                                    }
+                                })
+                                .response
+                                .on_hover_text(t(
+                                    "Crea una variante GGUF más ligera y la añade a la biblioteca.",
+                                ));
+                            });
                         }
+
+                        let retention_days = state.config.jarvis.trash_retention_days;
+                        ui.menu_button(
+                            RichText::new(t("Eliminar")).color(theme::color_danger()),
+                            |ui| {
+                                let trash_label = if retention_days > 0 {
+                                    tf(
+                                        "Mover a la papelera ({0} días)",
+                                        &[&retention_days.to_string()],
+                                    )
+                                } else {
+                                    t("Mover a la papelera").to_string()
+                                };
+                                if ui
+                                    .add_enabled(retention_days > 0, egui::Button::new(trash_label))
+                                    .on_disabled_hover_text(t(
+                                        "La papelera está desactivada (plazo de 0 días).",
+                                    ))
+                                    .clicked()
+                                {
+                                    removals
+                                        .push((record.identifier.clone(), UninstallMode::Trash));
+                                    ui.close_menu();
+                                }
+                                if ui
+                                    .button(
+                                        RichText::new(tf("Borrar del disco ({0})", &[&size_label]))
+                                            .color(theme::color_danger()),
+                                    )
+                                    .clicked()
+                                {
+                                    removals
+                                        .push((record.identifier.clone(), UninstallMode::Delete));
+                                    ui.close_menu();
+                                }
+                            },
+                        );
                     });
                 });
             });
@@ -6222,7 +15363,7 @@ fn draw_local_library_overview(ui: &mut egui::Ui, state: &mut AppState) {
         ui.add_space(10.0);
     }
 
-    for identifier in removals {
+    for (identifier, mode) in removals {
         let removed_selected = state
             .resources
             .local_library
@@ -6230,7 +15371,7 @@ fn draw_local_library_overview(ui: &mut egui::Ui, state: &mut AppState) {
             .as_ref()
             .map(|selected| selected == &identifier)
             .unwrap_or(false);
-        if let Some(status) = state.uninstall_local_model(&identifier) {
+        if let Some(status) = state.uninstall_local_model(&identifier, mode) {
             pending_feedback = Some(status);
         }
         if removed_selected {
@@ -6241,6 +15382,303 @@ fn draw_local_library_overview(ui: &mut egui::Ui, state: &mut AppState) {
     if let Some(feedback) = pending_feedback {
         state.resources.local_library.operation_feedback = Some(feedback);
     }
+
+    draw_local_library_maintenance(ui, state);
+}
+
+/// Origen y ahorro de una variante cuantizada, o el progreso si se está generando una.
+fn draw_model_quantization_status(
+    ui: &mut egui::Ui,
+    state: &AppState,
+    record: &InstalledLocalModel,
+) {
+    if let Some(source) = &record.quantized_from {
+        let original = state
+            .installed_model(source)
+            .map(|original| original.size_bytes)
+            .filter(|bytes| *bytes > 0);
+        let detail = match original {
+            Some(bytes) => tf(
+                "Variante cuantizada de {0}: {1}% del original ({2}).",
+                &[
+                    &source.display_label(),
+                    &(record.size_bytes * 100 / bytes).to_string(),
+                    &format_bytes(bytes),
+                ],
+            ),
+            None => tf("Variante cuantizada de {0}.", &[&source.display_label()]),
+        };
+        ui.label(
+            RichText::new(detail)
+                .color(theme::color_text_weak())
+                .size(11.0),
+        );
+    }
+
+    let Some(job) = state
+        .resources
+        .model_quantize
+        .job
+        .as_ref()
+        .filter(|job| job.source == record.identifier)
+    else {
+        return;
+    };
+    ui.add(
+        egui::ProgressBar::new(job.fraction())
+            .desired_width(240.0)
+            .text(tf(
+                "A {0}: {1}/{2} tensores",
+                &[
+                    t(job.level.label()),
+                    &job.done.to_string(),
+                    &job.total.to_string(),
+                ],
+            )),
+    );
+}
+
+/// Uso de disco, papelera de modelos desinstalados y carpetas en disco que no figuran en
+/// la biblioteca.
+fn draw_local_library_maintenance(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut feedback: Option<String> = None;
+
+    ui.add_space(8.0);
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(t("Uso de disco")).strong());
+        if ui.small_button(t("Medir")).clicked() {
+            state.refresh_model_disk_usage();
+        }
+        if ui
+            .small_button(t("Simular limpieza"))
+            .on_hover_text(t(
+                "Muestra qué descargas a medias y modelos caducados de la papelera se borrarían.",
+            ))
+            .clicked()
+        {
+            feedback = Some(state.collect_model_garbage(true));
+        }
+        if ui
+            .small_button(RichText::new(t("Limpiar")).color(theme::color_danger()))
+            .clicked()
+        {
+            feedback = Some(state.collect_model_garbage(false));
+        }
+    });
+    if let Some(usage) = &state.resources.model_disk_usage {
+        ui.label(
+            RichText::new(tf(
+                "{0} en total · instalados {1} · papelera {2} · sin registrar {3} · descargas a medias {4}",
+                &[
+                    &format_bytes(usage.total()),
+                    &format_bytes(usage.installed),
+                    &format_bytes(usage.trash),
+                    &format_bytes(usage.orphans),
+                    &format_bytes(usage.work_dirs),
+                ],
+            ))
+            .color(theme::color_text_weak())
+            .size(11.0),
+        );
+    }
+
+    ui.add_space(8.0);
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.label(RichText::new(t("Papelera")).strong());
+        ui.label(t("Conservar durante"));
+        if ui
+            .add(
+                egui::DragValue::new(&mut state.config.jarvis.trash_retention_days)
+                    .clamp_range(0..=90)
+                    .suffix(t(" días")),
+            )
+            .on_hover_text(t(
+                "Con 0 días los modelos se borran del disco al desinstalarlos.",
+            ))
+            .changed()
+        {
+            state.persist_config();
+        }
+        if !state.config.jarvis.trashed_models.is_empty()
+            && ui.button(t("Vaciar papelera")).clicked()
+        {
+            feedback = Some(state.empty_model_trash());
+        }
+    });
+
+    if state.config.jarvis.trashed_models.is_empty() {
+        ui.colored_label(theme::color_text_weak(), t("La papelera está vacía."));
+    }
+    let retention_days = i64::from(state.config.jarvis.trash_retention_days);
+    let mut restore = None;
+    let mut delete = None;
+    for (index, entry) in state.config.jarvis.trashed_models.iter().enumerate() {
+        let identifier = LocalModelIdentifier::parse(&entry.identifier);
+        let expires = (entry.trashed_at + chrono::Duration::days(retention_days))
+            .with_timezone(&Local)
+            .format("%Y-%m-%d")
+            .to_string();
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(identifier.display_label()).color(theme::color_text_primary()));
+            ui.label(
+                RichText::new(tf(
+                    "{0} · se borra el {1}",
+                    &[&format_bytes(entry.size_bytes), &expires],
+                ))
+                .color(theme::color_text_weak())
+                .size(11.0),
+            );
+            if ui.button(t("Restaurar")).clicked() {
+                restore = Some(index);
+            }
+            if ui
+                .button(RichText::new(t("Borrar")).color(theme::color_danger()))
+                .clicked()
+            {
+                delete = Some(index);
+            }
+        });
+    }
+    if let Some(index) = restore {
+        feedback = Some(state.restore_trashed_model(index));
+    } else if let Some(index) = delete {
+        feedback = Some(state.delete_trashed_model(index));
+    }
+
+    if !state.resources.orphan_model_dirs.is_empty() {
+        ui.add_space(8.0);
+        ui.separator();
+        ui.label(RichText::new(t("Carpetas sin registrar")).strong());
+        ui.colored_label(
+            theme::color_text_weak(),
+            t("Estas carpetas contienen modelos que no figuran en la biblioteca."),
+        );
+        let mut adopt = None;
+        let mut delete = None;
+        for (index, orphan) in state.resources.orphan_model_dirs.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let name = orphan
+                    .model_id
+                    .clone()
+                    .unwrap_or_else(|| orphan.path.display().to_string());
+                ui.label(RichText::new(name).color(theme::color_text_primary()))
+                    .on_hover_text(orphan.path.display().to_string());
+                ui.label(
+                    RichText::new(format_bytes(orphan.size_bytes))
+                        .color(theme::color_text_weak())
+                        .size(11.0),
+                );
+                if ui.button(t("Registrar")).clicked() {
+                    adopt = Some(index);
+                }
+                if ui
+                    .button(RichText::new(t("Borrar")).color(theme::color_danger()))
+                    .clicked()
+                {
+                    delete = Some(index);
+                }
+            });
+        }
+        if let Some(index) = adopt {
+            feedback = Some(state.adopt_orphan_model(index));
+        } else if let Some(index) = delete {
+            feedback = Some(state.delete_orphan_model(index));
+        }
+    }
+
+    if ui
+        .small_button(t("Buscar carpetas sin registrar"))
+        .clicked()
+    {
+        state.reconcile_model_directories();
+        if state.resources.orphan_model_dirs.is_empty() {
+            feedback = Some(t("Todas las carpetas de modelos están registradas.").to_string());
+        }
+    }
+
+    if let Some(feedback) = feedback {
+        state.resources.local_library.operation_feedback = Some(feedback);
+    }
+}
+
+fn draw_model_update_status(
+    ui: &mut egui::Ui,
+    record: &InstalledLocalModel,
+    status: Option<&ModelUpdateStatus>,
+) {
+    let revision = record
+        .revision
+        .as_deref()
+        .map(short_revision)
+        .unwrap_or("?");
+    match status {
+        Some(ModelUpdateStatus::Checking) => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(
+                    RichText::new(t("Comprobando revisión…"))
+                        .color(theme::color_text_weak())
+                        .size(10.0),
+                );
+            });
+        }
+        Some(ModelUpdateStatus::Updating) => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(
+                    RichText::new(t("Descargando archivos modificados…"))
+                        .color(theme::color_text_weak())
+                        .size(10.0),
+                );
+            });
+        }
+        Some(ModelUpdateStatus::UpToDate) => {
+            ui.label(
+                RichText::new(tf("Al día · revisión {0}", &[revision]))
+                    .color(theme::color_success())
+                    .size(10.0),
+            );
+        }
+        Some(ModelUpdateStatus::Available {
+            revision: latest,
+            last_modified,
+            changelog_url,
+        }) => {
+            ui.horizontal_wrapped(|ui| {
+                let mut text = tf(
+                    "Desactualizado · {0} → {1}",
+                    &[revision, short_revision(latest)],
+                );
+                if let Some(date) = last_modified {
+                    text.push_str(&format!(" ({})", date.get(..10).unwrap_or(date)));
+                }
+                ui.label(
+                    RichText::new(text)
+                        .color(Color32::from_rgb(255, 196, 0))
+                        .size(10.0),
+                );
+                ui.hyperlink_to(RichText::new(t("Ver cambios")).size(10.0), changelog_url);
+            });
+        }
+        Some(ModelUpdateStatus::Failed(error)) => {
+            ui.label(
+                RichText::new(tf("No se pudo comprobar: {0}", &[error]))
+                    .color(theme::color_danger())
+                    .size(10.0),
+            );
+        }
+        None => {
+            if record.revision.is_some() {
+                ui.label(
+                    RichText::new(tf("Revisión {0}", &[revision]))
+                        .color(theme::color_text_weak())
+                        .size(10.0),
+                );
+            }
+        }
+    }
 }
 
 fn draw_claude_models_gallery(ui: &mut egui::Ui, state: &mut AppState, models: &[AnthropicModel]) {
@@ -6333,6 +15771,9 @@ fn draw_claude_model_card(ui: &mut egui::Ui, state: &mut AppState, model: &Anthr
                 if let Some(limit) = model.output_token_limit {
                     metrics.push(format!("Salida máx: {}", limit));
                 }
+                if model.supports_vision {
+                    metrics.push("Visión".to_string());
+                }
                 if let Some(kind) = &model.r#type {
                     if !kind.trim().is_empty() {
                         metrics.push(format!("Tipo: {}", kind));
@@ -6466,6 +15907,8 @@ fn draw_provider_openai_configuration(ui: &mut egui::Ui, state: &mut AppState) {
         ui.add_space(6.0);
         ui.colored_label(ui.visuals().weak_text_color(), status);
     }
+
+    draw_model_quick_actions(ui, state, RemoteProviderKind::OpenAi);
 }
 
 fn draw_provider_groq(ui: &mut egui::Ui, state: &mut AppState, tab_index: usize) {
@@ -6534,6 +15977,104 @@ fn draw_provider_groq_configuration(ui: &mut egui::Ui, state: &mut AppState) {
         ui.add_space(6.0);
         ui.colored_label(ui.visuals().weak_text_color(), status);
     }
+
+    draw_model_quick_actions(ui, state, RemoteProviderKind::Groq);
+}
+
+fn draw_model_quick_actions(ui: &mut egui::Ui, state: &mut AppState, provider: RemoteProviderKind) {
+    ui.add_space(16.0);
+    ui.separator();
+    ui.add_space(10.0);
+    ui.heading(
+        RichText::new(t("Acciones rápidas personalizadas"))
+            .color(theme::color_text_primary())
+            .strong(),
+    );
+    ui.label(
+        RichText::new(t(
+            "Aparecen en la tarjeta del modelo dentro del catálogo y abren el chat con la plantilla dirigida a ese modelo.",
+        ))
+        .color(theme::color_text_weak())
+        .size(12.0),
+    );
+    ui.add_space(8.0);
+
+    let models: Vec<(String, String)> = state
+        .resources
+        .remote_catalog
+        .provider_cards
+        .get(&provider)
+        .map(|cards| {
+            cards
+                .iter()
+                .map(|card| (card.key.id.clone(), card.title.clone()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut remove = None;
+    let mut changed = false;
+    for (index, action) in state.config.model_quick_actions.iter_mut().enumerate() {
+        if RemoteProviderKind::from_short_code(&action.provider) != Some(provider) {
+            continue;
+        }
+        ui.push_id(("model_quick_action", index), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&action.model_id).strong());
+                changed |= ui.text_edit_singleline(&mut action.label).changed();
+                if ui.small_button(t("Eliminar")).clicked() {
+                    remove = Some(index);
+                }
+            });
+            changed |= ui
+                .add(
+                    egui::TextEdit::multiline(&mut action.template)
+                        .desired_rows(2)
+                        .desired_width(f32::INFINITY),
+                )
+                .changed();
+        });
+        ui.add_space(6.0);
+    }
+    if changed {
+        state.persist_config();
+    }
+    if let Some(index) = remove {
+        state.remove_model_quick_action(index);
+    }
+
+    let draft = &mut state.resources.quick_action_draft;
+    if !models.iter().any(|(id, _)| *id == draft.model_id) {
+        draft.model_id.clear();
+    }
+    ui.horizontal(|ui| {
+        let selected = models
+            .iter()
+            .find(|(id, _)| *id == draft.model_id)
+            .map(|(_, title)| title.clone())
+            .unwrap_or_else(|| t("Elige un modelo").to_string());
+        egui::ComboBox::from_id_source(("quick_action_model", provider.short_code()))
+            .selected_text(selected)
+            .show_ui(ui, |ui| {
+                for (id, title) in &models {
+                    ui.selectable_value(&mut draft.model_id, id.clone(), title);
+                }
+            });
+        ui.add(egui::TextEdit::singleline(&mut draft.label).hint_text(t("Nombre de la acción")));
+    });
+    ui.add(
+        egui::TextEdit::multiline(&mut draft.template)
+            .hint_text(t("Plantilla del prompt"))
+            .desired_rows(3)
+            .desired_width(f32::INFINITY),
+    );
+    if ui.button(t("Añadir acción")).clicked() {
+        let status = match state.add_model_quick_action(provider) {
+            Ok(()) => t("Acción rápida añadida.").to_string(),
+            Err(err) => err,
+        };
+        state.resources.remote_catalog.update_status(Some(status));
+    }
 }
 
 fn draw_provider_model_preview(ui: &mut egui::Ui, state: &AppState, provider: RemoteProviderKind) {
@@ -6613,7 +16154,11 @@ fn draw_provider_model_preview(ui: &mut egui::Ui, state: &AppState, provider: Re
     }
 }
 
-fn draw_provider_usage_overview(ui: &mut egui::Ui, state: &AppState, provider: RemoteProviderKind) {
+fn draw_provider_usage_overview(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    provider: RemoteProviderKind,
+) {
     let provider_name = provider.display_name();
     ui.heading(
         RichText::new(format!("Uso de {provider_name}"))
@@ -6661,6 +16206,26 @@ fn draw_provider_usage_overview(ui: &mut egui::Ui, state: &AppState, provider: R
         usage_chip(ui, ICON_COMPARE, "Comparador", comparisons, &state.theme);
     });
 
+    if let Some(usage) = state.resources.provider_usage.get(&provider) {
+        ui.add_space(6.0);
+        ui.horizontal(|ui| {
+            usage_chip(
+                ui,
+                ICON_DATABASE,
+                "Tokens enviados hoy",
+                usage.prompt_tokens_today as usize,
+                &state.theme,
+            );
+            usage_chip(
+                ui,
+                ICON_DATABASE,
+                "Tokens recibidos hoy",
+                usage.completion_tokens_today as usize,
+                &state.theme,
+            );
+        });
+    }
+
     ui.add_space(12.0);
     if let Some(status) = &state.resources.remote_catalog.last_status {
         ui.label(
@@ -6676,11 +16241,110 @@ fn draw_provider_usage_overview(ui: &mut egui::Ui, state: &AppState, provider: R
         );
     }
 
-    ui.add_space(10.0);
+    ui.add_space(14.0);
+    draw_provider_budget(ui, state, provider);
+}
+
+/// Porcentajes de aviso que se pueden activar para un presupuesto.
+const BUDGET_ALERT_OPTIONS: [u8; 6] = [25, 50, 75, 80, 90, 100];
+
+fn draw_provider_budget(ui: &mut egui::Ui, state: &mut AppState, provider: RemoteProviderKind) {
+    let config = state.remote_provider_config_mut(provider);
+    budget::roll_over(&mut config.spend);
+    let mut changed = false;
+
+    ui.label(
+        RichText::new(t("Presupuesto de gasto"))
+            .color(theme::color_text_primary())
+            .strong(),
+    );
     ui.colored_label(
         theme::color_text_weak(),
-        "Próximamente podrás definir límites de consumo y alertas personalizadas desde aquí.",
+        t("El gasto se estima con los precios del catálogo para cada respuesta recibida."),
     );
+    ui.add_space(6.0);
+
+    let periods = [
+        (
+            t("Diario"),
+            &mut config.budget.daily_usd,
+            config.spend.daily_usd,
+        ),
+        (
+            t("Mensual"),
+            &mut config.budget.monthly_usd,
+            config.spend.monthly_usd,
+        ),
+    ];
+    for (label, limit, spent) in periods {
+        ui.horizontal(|ui| {
+            let mut enabled = limit.is_some();
+            if ui.checkbox(&mut enabled, label).changed() {
+                *limit = enabled.then_some(10.0);
+                changed = true;
+            }
+            if let Some(value) = limit.as_mut() {
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(value)
+                            .prefix("$")
+                            .speed(0.5)
+                            .clamp_range(0.01..=100_000.0),
+                    )
+                    .changed();
+            }
+            let text = match limit {
+                Some(limit) => format!("${:.2} / ${:.2}", spent, limit),
+                None => format!("${:.2}", spent),
+            };
+            ui.label(
+                RichText::new(text)
+                    .color(theme::color_text_weak())
+                    .size(12.0),
+            );
+        });
+        if let Some(limit) = limit.filter(|limit| *limit > 0.0) {
+            let fraction = (spent / limit).clamp(0.0, 1.0);
+            let mut bar = egui::ProgressBar::new(fraction).desired_width(260.0);
+            if spent >= limit {
+                bar = bar.fill(theme::color_danger());
+            }
+            ui.add(bar);
+        }
+        ui.add_space(4.0);
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label(RichText::new(t("Avisar al")).color(theme::color_text_weak()));
+        for percent in BUDGET_ALERT_OPTIONS {
+            let mut active = config.budget.alert_percents.contains(&percent);
+            if ui
+                .toggle_value(&mut active, format!("{}%", percent))
+                .changed()
+            {
+                if active {
+                    config.budget.alert_percents.push(percent);
+                    config.budget.alert_percents.sort_unstable();
+                } else {
+                    config
+                        .budget
+                        .alert_percents
+                        .retain(|value| *value != percent);
+                }
+                changed = true;
+            }
+        }
+    });
+    changed |= ui
+        .checkbox(
+            &mut config.budget.hard_block,
+            t("Bloquear las llamadas cuando se agote el presupuesto"),
+        )
+        .changed();
+
+    if changed {
+        state.persist_config();
+    }
 }
 
 fn usage_chip(ui: &mut egui::Ui, icon: &str, label: &str, value: usize, tokens: &ThemeTokens) {
@@ -0,0 +1,399 @@
+use eframe::egui::{self, Color32, RichText};
+
+use crate::i18n::t;
+
+use super::theme;
+
+/// Filas a partir de las cuales no se ofrece el gráfico.
+const MAX_CHART_ROWS: usize = 200;
+/// Series numéricas que se dibujan como máximo en un mismo gráfico.
+const MAX_CHART_SERIES: usize = 4;
+const CHART_HEIGHT: f32 = 180.0;
+const SERIES_COLORS: [Color32; MAX_CHART_SERIES] = [
+    Color32::from_rgb(88, 166, 255),
+    Color32::from_rgb(255, 166, 87),
+    Color32::from_rgb(126, 231, 135),
+    Color32::from_rgb(210, 168, 255),
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChartKind {
+    #[default]
+    Bars,
+    Lines,
+}
+
+/// Estado de visualización de una tabla concreta, guardado en la memoria de egui.
+#[derive(Clone, Debug, Default)]
+struct TableView {
+    /// Columna de ordenación y si el orden es ascendente.
+    sort: Option<(usize, bool)>,
+    chart: Option<ChartKind>,
+}
+
+/// Convierte un bloque CSV o TSV en cabeceras y filas. Respeta comillas dobles.
+pub fn parse_delimited(text: &str, language: &str) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+    let delimiter = match language.trim().to_ascii_lowercase().as_str() {
+        "csv" => ',',
+        "tsv" => '\t',
+        _ => return None,
+    };
+
+    let mut lines = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| split_delimited(line, delimiter));
+    let headers = lines.next()?;
+    if headers.len() < 2 {
+        return None;
+    }
+    let rows = lines
+        .map(|mut row| {
+            row.resize(headers.len(), String::new());
+            row
+        })
+        .collect();
+    Some((headers, rows))
+}
+
+fn split_delimited(line: &str, delimiter: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ch if ch == delimiter && !quoted => {
+                cells.push(current.trim().to_string());
+                current.clear();
+            }
+            ch => current.push(ch),
+        }
+    }
+    cells.push(current.trim().to_string());
+    cells
+}
+
+/// Interpreta celdas como `1.234,5`, `12%`, `$30` o `1_000`.
+pub fn parse_number(cell: &str) -> Option<f64> {
+    let cleaned: String = cell
+        .trim()
+        .trim_end_matches('%')
+        .trim_start_matches(['$', '€', '£'])
+        .trim_end_matches(['€', '$'])
+        .chars()
+        .filter(|ch| !matches!(ch, ' ' | '_'))
+        .collect();
+    if cleaned.is_empty() {
+        return None;
+    }
+    let normalized = match (cleaned.rfind(','), cleaned.rfind('.')) {
+        (Some(comma), Some(dot)) if comma > dot => cleaned.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => cleaned.replace(',', ""),
+        (Some(_), None) => cleaned.replace(',', "."),
+        _ => cleaned,
+    };
+    normalized.parse().ok()
+}
+
+/// Columnas en las que todas las celdas con contenido son números.
+fn numeric_columns(headers: &[String], rows: &[Vec<String>]) -> Vec<usize> {
+    (0..headers.len())
+        .filter(|&column| {
+            let mut cells = rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .filter(|cell| !cell.trim().is_empty())
+                .peekable();
+            cells.peek().is_some() && cells.all(|cell| parse_number(cell).is_some())
+        })
+        .collect()
+}
+
+/// Tabla ordenable por columnas con un gráfico opcional para las columnas numéricas.
+pub fn draw_data_table(ui: &mut egui::Ui, headers: &[String], rows: &[Vec<String>]) {
+    let id = egui::Id::new(("data_table", headers, rows));
+    let mut view = ui
+        .data_mut(|data| data.get_temp::<TableView>(id))
+        .unwrap_or_default();
+    let numeric = numeric_columns(headers, rows);
+    let series: Vec<usize> = numeric
+        .iter()
+        .copied()
+        .filter(|&column| column > 0 || numeric.len() == headers.len())
+        .take(MAX_CHART_SERIES)
+        .collect();
+    let chartable = !series.is_empty() && rows.len() >= 2 && rows.len() <= MAX_CHART_ROWS;
+
+    if chartable {
+        ui.horizontal(|ui| {
+            let mut show_chart = view.chart.is_some();
+            if ui.toggle_value(&mut show_chart, t("Gráfico")).changed() {
+                view.chart = show_chart.then(ChartKind::default);
+            }
+            if let Some(kind) = view.chart.as_mut() {
+                ui.selectable_value(kind, ChartKind::Bars, t("Barras"));
+                ui.selectable_value(kind, ChartKind::Lines, t("Líneas"));
+            }
+        });
+        ui.add_space(4.0);
+    }
+
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    if let Some((column, ascending)) = view.sort {
+        let numeric_column = numeric.contains(&column);
+        order.sort_by(|&a, &b| {
+            let left = rows[a].get(column).map(String::as_str).unwrap_or("");
+            let right = rows[b].get(column).map(String::as_str).unwrap_or("");
+            let ordering = if numeric_column {
+                let left = parse_number(left).unwrap_or(f64::NEG_INFINITY);
+                let right = parse_number(right).unwrap_or(f64::NEG_INFINITY);
+                left.total_cmp(&right)
+            } else {
+                left.to_lowercase().cmp(&right.to_lowercase())
+            };
+            if ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+    }
+
+    match view.chart.filter(|_| chartable) {
+        Some(kind) => draw_chart(ui, kind, headers, rows, &order, &series),
+        None => {
+            egui::Grid::new(id.with("grid"))
+                .striped(true)
+                .spacing(egui::vec2(12.0, 4.0))
+                .show(ui, |ui| {
+                    for (column, header) in headers.iter().enumerate() {
+                        let arrow = match view.sort {
+                            Some((sorted, true)) if sorted == column => " ▲",
+                            Some((sorted, false)) if sorted == column => " ▼",
+                            _ => "",
+                        };
+                        let response = ui
+                            .add(
+                                egui::Label::new(
+                                    RichText::new(format!("{}{}", header, arrow))
+                                        .color(theme::color_text_primary())
+                                        .strong(),
+                                )
+                                .sense(egui::Sense::click()),
+                            )
+                            .on_hover_text(t("Ordenar por esta columna"));
+                        if response.clicked() {
+                            // Ascendente, descendente y de nuevo el orden original.
+                            view.sort = match view.sort {
+                                Some((sorted, true)) if sorted == column => Some((column, false)),
+                                Some((sorted, false)) if sorted == column => None,
+                                _ => Some((column, true)),
+                            };
+                        }
+                    }
+                    ui.end_row();
+
+                    for &index in &order {
+                        for (column, cell) in rows[index].iter().enumerate() {
+                            let text = RichText::new(cell)
+                                .color(theme::color_text_weak())
+                                .size(12.0);
+                            if numeric.contains(&column) {
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| ui.label(text),
+                                );
+                            } else {
+                                ui.label(text);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+
+    ui.data_mut(|data| data.insert_temp(id, view));
+}
+
+fn draw_chart(
+    ui: &mut egui::Ui,
+    kind: ChartKind,
+    headers: &[String],
+    rows: &[Vec<String>],
+    order: &[usize],
+    series: &[usize],
+) {
+    let labels: Vec<String> = order
+        .iter()
+        .map(|&index| rows[index].first().cloned().unwrap_or_default())
+        .collect();
+    let values: Vec<(String, Vec<f64>)> = series
+        .iter()
+        .map(|&column| {
+            let points = order
+                .iter()
+                .map(|&index| {
+                    rows[index]
+                        .get(column)
+                        .and_then(|cell| parse_number(cell))
+                        .unwrap_or(0.0)
+                })
+                .collect();
+            (headers[column].clone(), points)
+        })
+        .collect();
+    draw_series_chart(ui, kind, &labels, &values);
+}
+
+/// Gráfico de barras o líneas para hasta cuatro series que comparten las etiquetas del
+/// eje horizontal. Al pasar el puntero se muestran los valores de cada punto.
+pub fn draw_series_chart(
+    ui: &mut egui::Ui,
+    kind: ChartKind,
+    labels: &[String],
+    series: &[(String, Vec<f64>)],
+) {
+    let series = &series[..series.len().min(MAX_CHART_SERIES)];
+    let points = labels.len();
+    if points == 0 || series.is_empty() {
+        return;
+    }
+    let flat = series.iter().flat_map(|(_, values)| values.iter().copied());
+    let max = flat.clone().fold(0.0_f64, f64::max);
+    let min = flat.fold(0.0_f64, f64::min);
+    let span = (max - min).max(f64::EPSILON);
+
+    let width = ui.available_width().max(200.0);
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(width, CHART_HEIGHT), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let plot = egui::Rect::from_min_max(
+        rect.min + egui::vec2(44.0, 6.0),
+        rect.max - egui::vec2(6.0, 18.0),
+    );
+    let y_for = |value: f64| plot.bottom() - ((value - min) / span) as f32 * plot.height();
+    let axis = egui::Stroke::new(1.0, theme::color_text_weak());
+    let font = egui::FontId::proportional(11.0);
+
+    painter.line_segment([plot.left_top(), plot.left_bottom()], axis);
+    let baseline = y_for(0.0);
+    painter.line_segment(
+        [
+            egui::pos2(plot.left(), baseline),
+            egui::pos2(plot.right(), baseline),
+        ],
+        axis,
+    );
+    for value in [min, max] {
+        painter.text(
+            egui::pos2(plot.left() - 4.0, y_for(value)),
+            egui::Align2::RIGHT_CENTER,
+            format_value(value),
+            font.clone(),
+            theme::color_text_weak(),
+        );
+    }
+
+    let slot = plot.width() / points as f32;
+    for (position, label) in labels.iter().enumerate() {
+        // Con muchos puntos solo se rotulan algunos para que no se solapen.
+        if points <= 12 || position % (points / 12 + 1) == 0 {
+            painter.text(
+                egui::pos2(
+                    plot.left() + slot * (position as f32 + 0.5),
+                    plot.bottom() + 2.0,
+                ),
+                egui::Align2::CENTER_TOP,
+                truncate(label, (slot / 6.0).max(3.0) as usize),
+                font.clone(),
+                theme::color_text_weak(),
+            );
+        }
+    }
+
+    match kind {
+        ChartKind::Bars => {
+            let bar_width = (slot * 0.8) / series.len() as f32;
+            for (serie, (_, values)) in series.iter().enumerate() {
+                for (position, value) in values.iter().enumerate().take(points) {
+                    let left =
+                        plot.left() + slot * (position as f32 + 0.1) + bar_width * serie as f32;
+                    let top = y_for(*value).min(baseline);
+                    let bottom = y_for(*value).max(baseline);
+                    painter.rect_filled(
+                        egui::Rect::from_min_max(
+                            egui::pos2(left, top),
+                            egui::pos2(left + bar_width.max(1.0), bottom),
+                        ),
+                        1.0,
+                        SERIES_COLORS[serie],
+                    );
+                }
+            }
+        }
+        ChartKind::Lines => {
+            for (serie, (_, values)) in series.iter().enumerate() {
+                let line: Vec<egui::Pos2> = values
+                    .iter()
+                    .take(points)
+                    .enumerate()
+                    .map(|(position, value)| {
+                        egui::pos2(plot.left() + slot * (position as f32 + 0.5), y_for(*value))
+                    })
+                    .collect();
+                let stroke = egui::Stroke::new(2.0, SERIES_COLORS[serie]);
+                painter.add(egui::Shape::line(line.clone(), stroke));
+                for point in line {
+                    painter.circle_filled(point, 2.5, SERIES_COLORS[serie]);
+                }
+            }
+        }
+    }
+
+    if let Some(pointer) = response.hover_pos().filter(|pos| plot.contains(*pos)) {
+        let position = (((pointer.x - plot.left()) / slot) as usize).min(points - 1);
+        let mut lines = vec![labels[position].clone()];
+        for (name, values) in series {
+            if let Some(value) = values.get(position) {
+                lines.push(format!("{}: {}", name, format_value(*value)));
+            }
+        }
+        response.on_hover_text(lines.join("\n"));
+    }
+
+    ui.horizontal_wrapped(|ui| {
+        for (serie, (name, _)) in series.iter().enumerate() {
+            ui.label(RichText::new("■").color(SERIES_COLORS[serie]));
+            ui.label(
+                RichText::new(name)
+                    .color(theme::color_text_weak())
+                    .size(12.0),
+            );
+        }
+    });
+}
+
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e12 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        let mut truncated: String = text.chars().take(max_chars.saturating_sub(1)).collect();
+        truncated.push('…');
+        truncated
+    }
+}
@@ -3,7 +3,8 @@ use vscode_shell::components::{
     self, HeaderAction, HeaderModel, HeaderProps, SearchGroup, SearchResult,
 };
 
-use crate::state::{AppState, MainView};
+use crate::i18n::{t, tf};
+use crate::state::{keybindings, AppState, MainView, ShortcutAction};
 use crate::ui::layout_bridge::shell_theme;
 
 pub fn draw_header(ctx: &egui::Context, state: &mut AppState) {
@@ -18,17 +19,32 @@ struct AppHeader<'a> {
 
 impl AppHeader<'_> {
     fn active_view_subtitle(&self) -> Option<String> {
-        Some(
-            match self.state.active_main_view {
-                MainView::ChatMultimodal => "Conversación multimodal",
-                MainView::CronScheduler => "Planificador de tareas",
-                MainView::ActivityFeed => "Actividad reciente",
-                MainView::DebugConsole => "Consola de depuración",
-                MainView::Preferences => "Preferencias avanzadas",
-                MainView::ResourceBrowser => "Explorador de recursos",
-            }
-            .to_string(),
-        )
+        let view = t(match self.state.active_main_view {
+            MainView::ChatMultimodal => "Conversación multimodal",
+            MainView::CronScheduler => "Planificador de tareas",
+            MainView::ActivityFeed => "Actividad reciente",
+            MainView::DebugConsole => "Consola de depuración",
+            MainView::Preferences => "Preferencias avanzadas",
+            MainView::ResourceBrowser => "Explorador de recursos",
+            MainView::BatchEvaluation => "Evaluación por lotes",
+            MainView::Audit => "Auditoría",
+            MainView::CodeViewer => "Explorador de código",
+        });
+
+        if !self.state.is_offline() {
+            return Some(view.to_string());
+        }
+
+        let queued = self.state.chat.offline_queue.len();
+        let indicator = if queued > 0 {
+            format!(
+                "📴 {}",
+                tf("Sin conexión · {0} en cola", &[&queued.to_string()])
+            )
+        } else {
+            format!("📴 {}", t("Sin conexión"))
+        };
+        Some(format!("{} · {}", view, indicator))
     }
 
     fn parse_result_id(id: &str) -> Option<(usize, usize)> {
@@ -49,23 +65,79 @@ impl HeaderModel for AppHeader<'_> {
             title: "Jungle MonkAI".into(),
             subtitle: self.active_view_subtitle(),
             search_placeholder: Some(
-                "Cmd/Ctrl+K · Buscar modelos, conversaciones y documentos".into(),
+                t(if self.state.embeddings.semantic_mode {
+                    "Cmd/Ctrl+K · Buscar por significado en chats, README y conocimiento"
+                } else {
+                    "Cmd/Ctrl+K · Buscar modelos, conversaciones y documentos"
+                })
+                .into(),
             ),
             actions: vec![
                 HeaderAction {
                     id: "open_settings".into(),
-                    label: "Preferencias".into(),
+                    label: t("Preferencias").into(),
                     icon: Some("⚙️".into()),
                     shortcut: Some("Ctrl+,".into()),
                     enabled: true,
                 },
                 HeaderAction {
                     id: "open_functions".into(),
-                    label: "Funciones".into(),
+                    label: t("Funciones").into(),
                     icon: Some("🧰".into()),
                     shortcut: Some("Ctrl+Shift+F".into()),
                     enabled: true,
                 },
+                HeaderAction {
+                    id: "toggle_offline".into(),
+                    label: t(if self.state.config.network.offline_mode {
+                        "Volver a conectar"
+                    } else {
+                        "Modo sin conexión"
+                    })
+                    .into(),
+                    icon: Some(
+                        if self.state.is_offline() {
+                            "📴"
+                        } else {
+                            "🌐"
+                        }
+                        .into(),
+                    ),
+                    shortcut: None,
+                    enabled: true,
+                },
+                HeaderAction {
+                    id: "toggle_semantic_search".into(),
+                    label: t(if self.state.embeddings.semantic_mode {
+                        "Búsqueda por palabras"
+                    } else {
+                        "Búsqueda semántica"
+                    })
+                    .into(),
+                    icon: Some(
+                        if self.state.embeddings.semantic_mode {
+                            "🧠"
+                        } else {
+                            "🔤"
+                        }
+                        .into(),
+                    ),
+                    shortcut: None,
+                    enabled: true,
+                },
+                HeaderAction {
+                    id: "toggle_zen".into(),
+                    label: t("Modo zen").into(),
+                    icon: Some("🧘".into()),
+                    shortcut: Some(
+                        keybindings::binding(
+                            &self.state.config.keybindings,
+                            ShortcutAction::ToggleZen,
+                        )
+                        .to_string(),
+                    ),
+                    enabled: true,
+                },
             ],
             logo_acronym: Some("JM".into()),
         }
@@ -76,7 +148,7 @@ impl HeaderModel for AppHeader<'_> {
     }
 
     fn set_search_value(&mut self, value: String) {
-        self.state.search_buffer = value;
+        self.state.set_global_search_query(value);
     }
 
     fn search_palette(&self) -> Vec<SearchGroup> {
@@ -86,7 +158,7 @@ impl HeaderModel for AppHeader<'_> {
             .enumerate()
             .map(|(group_index, group)| SearchGroup {
                 id: format!("g{}", group_index),
-                title: group.title,
+                title: t(&group.title).to_string(),
                 results: group
                     .results
                     .into_iter()
@@ -95,7 +167,7 @@ impl HeaderModel for AppHeader<'_> {
                         id: format!("g{}:r{}", group_index, result_index),
                         title: result.title,
                         subtitle: result.subtitle,
-                        action_hint: Some(result.action_hint),
+                        action_hint: Some(t(&result.action_hint).to_string()),
                     })
                     .collect(),
             })
@@ -106,16 +178,24 @@ impl HeaderModel for AppHeader<'_> {
         if let Some((group_index, result_index)) = Self::parse_result_id(result_id) {
             if let Some(group) = self.state.global_search_groups().get(group_index) {
                 if let Some(result) = group.results.get(result_index) {
-                    self.state.search_buffer = result.title.clone();
+                    // Los resultados semánticos saltan a su origen y se recuerda la consulta.
+                    let recent = match &result.source {
+                        Some(source) => {
+                            self.state.open_semantic_source(source);
+                            self.state.search_buffer.trim().to_string()
+                        }
+                        None => {
+                            self.state.search_buffer = result.title.clone();
+                            result.title.clone()
+                        }
+                    };
                     if !self
                         .state
                         .global_search_recent
                         .iter()
-                        .any(|entry| entry == &result.title)
+                        .any(|entry| entry == &recent)
                     {
-                        self.state
-                            .global_search_recent
-                            .insert(0, result.title.clone());
+                        self.state.global_search_recent.insert(0, recent);
                         self.state.global_search_recent.truncate(10);
                     }
                 }
@@ -127,6 +207,12 @@ impl HeaderModel for AppHeader<'_> {
         match action_id {
             "open_settings" => self.state.show_settings_modal = true,
             "open_functions" => self.state.chat.show_functions_modal = true,
+            "toggle_offline" => {
+                let enabled = !self.state.config.network.offline_mode;
+                self.state.set_offline_mode(enabled);
+            }
+            "toggle_semantic_search" => self.state.toggle_semantic_search_mode(),
+            "toggle_zen" => self.state.toggle_zen_mode(),
             _ => {}
         }
     }
@@ -1,6 +1,7 @@
 use eframe::egui::{self, Color32, RichText};
 use egui_extras::{Column, TableBuilder};
 
+use crate::i18n::t;
 use crate::state::{AppState, LogStatus};
 
 use super::theme;
@@ -9,14 +10,13 @@ const ICON_LOGS: &str = "\u{f0f6}"; // file-lines
 const COLOR_WARNING: Color32 = Color32::from_rgb(255, 196, 0);
 const COLOR_RUNNING: Color32 = Color32::from_rgb(64, 172, 255);
 
-pub fn draw_logs_view(ui: &mut egui::Ui, state: &AppState) {
-    let tokens = &state.theme;
+pub fn draw_logs_view(ui: &mut egui::Ui, state: &mut AppState) {
     ui.set_width(ui.available_width());
     ui.set_min_height(ui.available_height());
 
     egui::Frame::none()
         .fill(Color32::from_rgb(26, 28, 32))
-        .stroke(theme::subtle_border(tokens))
+        .stroke(theme::subtle_border(&state.theme))
         .rounding(egui::Rounding::same(18.0))
         .inner_margin(egui::Margin {
             left: 20.0,
@@ -36,11 +36,21 @@ pub fn draw_logs_view(ui: &mut egui::Ui, state: &AppState) {
                         .color(theme::color_primary()),
                 );
                 ui.heading(
-                    RichText::new("Registros y tareas")
+                    RichText::new(t("Registros y tareas"))
                         .color(theme::color_text_primary())
                         .strong(),
                 );
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    draw_briefing_controls(ui, state);
+                });
             });
+            if let Some(status) = &state.briefing.status {
+                ui.label(
+                    RichText::new(status)
+                        .color(theme::color_text_weak())
+                        .size(12.0),
+                );
+            }
 
             ui.add_space(12.0);
 
@@ -55,6 +65,31 @@ pub fn draw_logs_view(ui: &mut egui::Ui, state: &AppState) {
         });
 }
 
+/// Botón para resumir en el chat la actividad desde la última puesta al día.
+fn draw_briefing_controls(ui: &mut egui::Ui, state: &mut AppState) {
+    let running = state.briefing.is_running();
+    if ui
+        .add_enabled(!running, egui::Button::new(t("Ponme al día")))
+        .on_hover_text(t(
+            "Resume en el chat la actividad desde la última puesta al día con el proveedor de la conversación",
+        ))
+        .clicked()
+    {
+        if let Err(err) = state.start_activity_briefing() {
+            state.briefing.status = Some(err);
+        }
+    }
+    if ui
+        .checkbox(&mut state.config.speak_briefings, t("Leer en voz alta"))
+        .changed()
+    {
+        state.persist_config();
+    }
+    if running {
+        ui.spinner();
+    }
+}
+
 fn draw_logs_table(ui: &mut egui::Ui, state: &AppState) {
     let header_bg = egui::Color32::from_rgb(42, 44, 50);
     let row_even = egui::Color32::from_rgb(34, 36, 42);
@@ -75,7 +110,7 @@ fn draw_logs_table(ui: &mut egui::Ui, state: &AppState) {
             header.col(|ui| {
                 header_cell(ui, header_bg, |ui| {
                     ui.label(
-                        RichText::new("Estado")
+                        RichText::new(t("Estado"))
                             .color(theme::color_text_weak())
                             .monospace(),
                     );
@@ -84,7 +119,7 @@ fn draw_logs_table(ui: &mut egui::Ui, state: &AppState) {
             header.col(|ui| {
                 header_cell(ui, header_bg, |ui| {
                     ui.label(
-                        RichText::new("Origen")
+                        RichText::new(t("Origen"))
                             .color(theme::color_text_weak())
                             .monospace(),
                     );
@@ -93,7 +128,7 @@ fn draw_logs_table(ui: &mut egui::Ui, state: &AppState) {
             header.col(|ui| {
                 header_cell(ui, header_bg, |ui| {
                     ui.label(
-                        RichText::new("Detalle")
+                        RichText::new(t("Detalle"))
                             .color(theme::color_text_weak())
                             .monospace(),
                     );
@@ -102,7 +137,7 @@ fn draw_logs_table(ui: &mut egui::Ui, state: &AppState) {
             header.col(|ui| {
                 header_cell(ui, header_bg, |ui| {
                     ui.label(
-                        RichText::new("Hora")
+                        RichText::new(t("Hora"))
                             .color(theme::color_text_weak())
                             .monospace(),
                     );
@@ -127,7 +162,7 @@ fn draw_logs_table(ui: &mut egui::Ui, state: &AppState) {
                     row.col(|ui| {
                         row_cell(ui, bg, |ui| {
                             ui.label(
-                                RichText::new(&entry.source)
+                                RichText::new(t(&entry.source))
                                     .color(theme::color_text_primary())
                                     .monospace(),
                             );
@@ -135,7 +170,9 @@ fn draw_logs_table(ui: &mut egui::Ui, state: &AppState) {
                     });
                     row.col(|ui| {
                         row_cell(ui, bg, |ui| {
-                            ui.label(RichText::new(&entry.message).color(theme::color_text_weak()));
+                            ui.label(
+                                RichText::new(t(&entry.message)).color(theme::color_text_weak()),
+                            );
                         });
                     });
                     row.col(|ui| {
@@ -1,12 +1,14 @@
-use crate::state::AppState;
+use crate::state::{AppState, LogStatus};
 use eframe::egui;
 
 pub mod chat;
+pub mod data_table;
 pub mod header;
 pub mod layout_bridge;
 pub mod logs;
 pub mod modals;
 pub mod resource_sidebar;
+pub mod shortcuts;
 pub mod sidebar;
 pub mod tabs;
 pub mod theme;
@@ -16,7 +18,21 @@ pub fn draw_ui(ctx: &egui::Context, state: &mut AppState) {
     if state.update_async_tasks() {
         ctx.request_repaint();
     }
+    let zen_before = state.is_zen_mode();
+    shortcuts::handle_shortcuts(ctx, state);
+    let dropped: Vec<_> = ctx.input(|input| {
+        input
+            .raw
+            .dropped_files
+            .iter()
+            .filter_map(|file| file.path.clone())
+            .collect()
+    });
+    for path in dropped {
+        state.attach_file(path);
+    }
     theme::apply(ctx, &state.theme);
+    apply_accessibility(ctx, state);
     state.sync_active_tab_from_view();
     ctx.style_mut(|style| {
         style.interaction.resize_grab_radius_side = 6.0;
@@ -27,6 +43,10 @@ pub fn draw_ui(ctx: &egui::Context, state: &mut AppState) {
     sidebar::draw_sidebar(ctx, state);
     resource_sidebar::draw_resource_sidebar(ctx, state);
     chat::draw_main_content(ctx, state);
+    chat::draw_popout_windows(ctx, state);
+    if state.is_zen_mode() {
+        draw_zen_exit_button(ctx, state);
+    }
 
     if state.layout.take_navigation_signal().is_some()
         || state.layout.take_resource_signal().is_some()
@@ -36,4 +56,124 @@ pub fn draw_ui(ctx: &egui::Context, state: &mut AppState) {
 
     modals::draw_settings_modal(ctx, state);
     modals::draw_functions_modal(ctx, state);
+    modals::draw_share_modal(ctx, state);
+    modals::draw_email_modal(ctx, state);
+    modals::draw_input_history_modal(ctx, state);
+    modals::draw_structured_schema_modal(ctx, state);
+    modals::draw_shortcuts_cheatsheet(ctx, state);
+    modals::draw_storage_unlock_modal(ctx, state);
+    modals::draw_session_recovery_modal(ctx, state);
+    modals::draw_config_conflict_modal(ctx, state);
+    modals::draw_automation_permission_modal(ctx, state);
+    draw_toasts(ctx, state);
+
+    if zen_before != state.is_zen_mode() {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(state.is_zen_mode()));
+    }
+    if !ctx.input(|input| input.pointer.any_down()) {
+        state.sync_layout_persistence();
+    }
+    if ctx.input(|input| input.viewport().close_requested()) {
+        state.flush_config();
+        state.shutdown_session();
+    }
+}
+
+/// Aplica la escala y la reducción de animaciones configuradas. Los cambios de zoom
+/// hechos con Ctrl +/- se adoptan como nueva escala persistida.
+fn apply_accessibility(ctx: &egui::Context, state: &mut AppState) {
+    let configured = state.config.accessibility.ui_scale;
+    match state.applied_ui_scale {
+        Some(applied) if (applied - configured).abs() > f32::EPSILON => {
+            ctx.set_zoom_factor(configured);
+            state.applied_ui_scale = Some(configured);
+        }
+        Some(applied) if (ctx.zoom_factor() - applied).abs() > f32::EPSILON => {
+            state.set_ui_scale(ctx.zoom_factor());
+            let scale = state.config.accessibility.ui_scale;
+            ctx.set_zoom_factor(scale);
+            state.applied_ui_scale = Some(scale);
+        }
+        Some(_) => {}
+        None => {
+            ctx.set_zoom_factor(configured);
+            state.applied_ui_scale = Some(configured);
+        }
+    }
+
+    let reduced_motion = state.config.accessibility.reduced_motion;
+    ctx.style_mut(|style| {
+        style.animation_time = if reduced_motion {
+            0.0
+        } else {
+            egui::Style::default().animation_time
+        };
+    });
+}
+
+fn draw_toasts(ctx: &egui::Context, state: &mut AppState) {
+    if !state.toasts.prune() {
+        return;
+    }
+    // Los avisos caducan solos: hay que volver a pintar aunque no haya interacción.
+    ctx.request_repaint_after(std::time::Duration::from_millis(500));
+
+    let mut dismissed = None;
+    let mut chosen = None;
+    egui::Area::new(egui::Id::new("toasts"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -16.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            for (index, toast) in state.toasts.items.iter().enumerate() {
+                let accent = match toast.status {
+                    LogStatus::Error => theme::color_danger(),
+                    LogStatus::Warning => egui::Color32::from_rgb(230, 180, 80),
+                    LogStatus::Ok => theme::color_success(),
+                    LogStatus::Running => theme::color_primary(),
+                };
+                egui::Frame::popup(ui.style())
+                    .stroke(egui::Stroke::new(1.0, accent))
+                    .show(ui, |ui| {
+                        ui.set_max_width(320.0);
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(&toast.message).color(accent));
+                            if ui.small_button("✕").clicked() {
+                                dismissed = Some(index);
+                            }
+                        });
+                        if !toast.actions.is_empty() {
+                            ui.horizontal_wrapped(|ui| {
+                                for (label, action) in &toast.actions {
+                                    if ui.small_button(crate::i18n::t(label)).clicked() {
+                                        dismissed = Some(index);
+                                        chosen = Some(*action);
+                                    }
+                                }
+                            });
+                        }
+                    });
+                ui.add_space(6.0);
+            }
+        });
+    if let Some(index) = dismissed {
+        state.toasts.dismiss(index);
+    }
+    if let Some(action) = chosen {
+        state.run_toast_action(action);
+    }
+}
+
+fn draw_zen_exit_button(ctx: &egui::Context, state: &mut AppState) {
+    egui::Area::new(egui::Id::new("zen_exit_button"))
+        .anchor(egui::Align2::RIGHT_TOP, egui::vec2(-16.0, 12.0))
+        .order(egui::Order::Foreground)
+        .show(ctx, |ui| {
+            if ui
+                .button(crate::i18n::t("Salir del modo zen"))
+                .on_hover_text("F11")
+                .clicked()
+            {
+                state.toggle_zen_mode();
+            }
+        });
 }
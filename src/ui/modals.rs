@@ -1,20 +1,26 @@
-use crate::state::AppState;
+use crate::config::StorageKeySource;
+use crate::i18n::{t, tf};
+use crate::state::{
+    keybindings, permissions, share, structured, vault, AppState, LogStatus, NavigationTarget,
+    PreferencePanel, ShortcutAction,
+};
 use eframe::egui;
 
 pub fn draw_settings_modal(ctx: &egui::Context, state: &mut AppState) {
     let mut is_open = state.show_settings_modal;
 
-    egui::Window::new("Settings")
+    egui::Window::new(t("Settings"))
+        .id(egui::Id::new("settings_modal"))
         .collapsible(false)
         .resizable(false)
         .open(&mut is_open)
         .show(ctx, |ui| {
-            ui.heading("API Configuration");
+            ui.heading(t("API Configuration"));
             ui.separator();
-            ui.label("Configura aquí tus claves de API y otros ajustes.");
+            ui.label(t("Configura aquí tus claves de API y otros ajustes."));
             // TODO: Añadir campos para las claves de API (OpenAI, Claude, etc.)
 
-            if ui.button("Close").clicked() {
+            if ui.button(t("Close")).clicked() {
                 // The window will be closed by the .open() method when the user clicks the 'x' button or if `is_open` is set to false elsewhere.
                 // No need to explicitly set is_open = false here.
             }
@@ -23,43 +29,360 @@ pub fn draw_settings_modal(ctx: &egui::Context, state: &mut AppState) {
     state.show_settings_modal = is_open;
 }
 
+pub fn draw_input_history_modal(ctx: &egui::Context, state: &mut AppState) {
+    if !state.composer.show_history {
+        return;
+    }
+
+    let mut is_open = true;
+    let mut selected = None;
+    egui::Window::new(t("Historial de mensajes"))
+        .id(egui::Id::new("input_history_modal"))
+        .collapsible(false)
+        .default_width(520.0)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+            let search = ui.add(
+                egui::TextEdit::singleline(&mut state.composer.history_query)
+                    .hint_text(t("Buscar en el historial"))
+                    .desired_width(f32::INFINITY),
+            );
+            if ui.memory(|mem| mem.focused().is_none()) {
+                search.request_focus();
+            }
+            ui.separator();
+
+            let matches = state.composer.search(&state.composer.history_query);
+            if matches.is_empty() {
+                ui.weak(t("Ningún mensaje coincide con la búsqueda."));
+            }
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .show(ui, |ui| {
+                    for entry in matches {
+                        let preview: String = entry.chars().take(160).collect();
+                        if ui
+                            .selectable_label(false, preview)
+                            .on_hover_text(entry)
+                            .clicked()
+                        {
+                            selected = Some(entry.to_string());
+                        }
+                    }
+                });
+            if search.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter)) {
+                selected = state
+                    .composer
+                    .search(&state.composer.history_query)
+                    .first()
+                    .map(|entry| entry.to_string());
+            }
+        });
+
+    if let Some(entry) = selected {
+        state.chat.input = entry;
+        is_open = false;
+    }
+    if !is_open {
+        state.composer.show_history = false;
+        state.composer.history_query.clear();
+    }
+}
+
+pub fn draw_structured_schema_modal(ctx: &egui::Context, state: &mut AppState) {
+    if !state.chat.structured.show_editor {
+        return;
+    }
+
+    let mut is_open = true;
+    let mut apply = false;
+    egui::Window::new(t("Salida JSON estructurada"))
+        .id(egui::Id::new("structured_schema_modal"))
+        .collapsible(false)
+        .default_width(480.0)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+            ui.label(t(
+                "Los mensajes enviados a un proveedor incluyen este JSON Schema. La respuesta se valida y, si no lo cumple, se pide al modelo que la corrija.",
+            ));
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut state.chat.structured.schema_text)
+                            .code_editor()
+                            .desired_rows(14)
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+            if let Some(error) = &state.chat.structured.schema_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                if ui.button(t("Activar")).clicked() {
+                    apply = true;
+                }
+                if state.chat.structured.enabled && ui.button(t("Desactivar")).clicked() {
+                    state.chat.structured.enabled = false;
+                }
+                if ui.button(t("Usar ejemplo")).clicked() {
+                    state.chat.structured.schema_text = structured::EXAMPLE_SCHEMA.to_string();
+                    state.chat.structured.schema_error = None;
+                }
+            });
+        });
+
+    if apply && state.chat.structured.check_schema() {
+        state.chat.structured.enabled = true;
+        is_open = false;
+    }
+    state.chat.structured.show_editor = is_open;
+}
+
+pub fn draw_share_modal(ctx: &egui::Context, state: &mut AppState) {
+    if !state.share.show_modal {
+        return;
+    }
+
+    let mut is_open = state.share.show_modal;
+    egui::Window::new(t("Compartir conversación"))
+        .id(egui::Id::new("share_modal"))
+        .collapsible(false)
+        .resizable(false)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+            ui.label(t(
+                "Cifra la conversación con una frase de paso para enviarla a otra instancia de JungleMonkAI sin pasar por un servidor.",
+            ));
+            ui.separator();
+
+            egui::Grid::new("share_modal_grid")
+                .num_columns(2)
+                .spacing(egui::vec2(12.0, 8.0))
+                .show(ui, |ui| {
+                    ui.label(t("Frase de paso"));
+                    ui.add(egui::TextEdit::singleline(&mut state.share.passphrase).password(true));
+                    ui.end_row();
+
+                    ui.label(t("Confirmación"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut state.share.passphrase_confirm)
+                            .password(true)
+                            .hint_text(t("Solo al exportar")),
+                    );
+                    ui.end_row();
+                });
+
+            ui.add_space(8.0);
+            ui.strong(t("Exportar"));
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.share.export_path)
+                        .hint_text(format!("conversacion.{}", share::SHARE_EXTENSION)),
+                );
+                let ready = state.share.passphrase.chars().count() >= share::MIN_PASSPHRASE_LEN;
+                if ui
+                    .add_enabled(ready, egui::Button::new(t("Exportar cifrada")))
+                    .on_disabled_hover_text(tf(
+                        "La frase de paso necesita al menos {0} caracteres.",
+                        &[&share::MIN_PASSPHRASE_LEN.to_string()],
+                    ))
+                    .clicked()
+                {
+                    state.share.status = Some(match state.export_shared_conversation() {
+                        Ok(count) => tf("{0} mensajes exportados.", &[&count.to_string()]),
+                        Err(err) => err,
+                    });
+                }
+                if ui
+                    .button(t("Enviar por correo"))
+                    .on_hover_text(t("Adjunta el archivo exportado; la frase de paso no se envía."))
+                    .clicked()
+                {
+                    if let Err(err) = state.compose_shared_conversation_email() {
+                        state.share.status = Some(err);
+                    }
+                }
+            });
+
+            ui.add_space(8.0);
+            ui.strong(t("Importar"));
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut state.share.import_path)
+                        .hint_text(format!("conversacion.{}", share::SHARE_EXTENSION)),
+                );
+                if ui
+                    .add_enabled(
+                        !state.share.passphrase.is_empty(),
+                        egui::Button::new(t("Importar")),
+                    )
+                    .clicked()
+                {
+                    state.share.status = Some(match state.import_shared_conversation() {
+                        Ok(count) => tf("{0} mensajes añadidos al historial.", &[&count.to_string()]),
+                        Err(err) => err,
+                    });
+                }
+            });
+
+            if let Some(status) = &state.share.status {
+                ui.add_space(8.0);
+                ui.colored_label(ui.visuals().weak_text_color(), status);
+            }
+        });
+
+    if !is_open {
+        state.share.passphrase.clear();
+        state.share.passphrase_confirm.clear();
+    }
+    state.share.show_modal = is_open;
+}
+
+pub fn draw_email_modal(ctx: &egui::Context, state: &mut AppState) {
+    let Some(draft) = state.email.draft.as_mut() else {
+        return;
+    };
+
+    let mut is_open = true;
+    let mut send = false;
+    egui::Window::new(t("Enviar por correo"))
+        .id(egui::Id::new("email_modal"))
+        .collapsible(false)
+        .default_width(520.0)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+            egui::Grid::new("email_modal_grid")
+                .num_columns(2)
+                .spacing(egui::vec2(12.0, 8.0))
+                .show(ui, |ui| {
+                    ui.label(t("Para"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.to)
+                            .hint_text(t("Separados por comas"))
+                            .desired_width(f32::INFINITY),
+                    );
+                    ui.end_row();
+
+                    ui.label(t("Asunto"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut draft.subject).desired_width(f32::INFINITY),
+                    );
+                    ui.end_row();
+                });
+            egui::ScrollArea::vertical()
+                .max_height(280.0)
+                .show(ui, |ui| {
+                    ui.add(
+                        egui::TextEdit::multiline(&mut draft.body)
+                            .desired_rows(10)
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+
+            let mut detach = None;
+            for (index, path) in draft.attachments.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("📎 {}", path.display()));
+                    if ui
+                        .small_button("✕")
+                        .on_hover_text(t("Quitar adjunto"))
+                        .clicked()
+                    {
+                        detach = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = detach {
+                draft.attachments.remove(index);
+            }
+
+            ui.add_space(6.0);
+            ui.horizontal(|ui| {
+                send = ui
+                    .add_enabled(!draft.to.trim().is_empty(), egui::Button::new(t("Enviar")))
+                    .clicked();
+                if let Some(status) = &state.email.status {
+                    ui.colored_label(ui.visuals().weak_text_color(), status);
+                }
+            });
+        });
+
+    if send {
+        if let Err(err) = state.send_email_draft() {
+            state.email.status = Some(err);
+        }
+    } else if !is_open {
+        state.email.draft = None;
+    }
+}
+
 pub fn draw_functions_modal(ctx: &egui::Context, state: &mut AppState) {
     if !state.chat.show_functions_modal {
         return;
     }
 
     let mut is_open = state.chat.show_functions_modal;
-    egui::Window::new("Available Functions")
+    egui::Window::new(t("Available Functions"))
+        .id(egui::Id::new("functions_modal"))
         .collapsible(false)
         .resizable(true)
         .min_size(egui::vec2(540.0, 420.0))
         .open(&mut is_open)
         .show(ctx, |ui| {
-            ui.label("Consulta la documentación ampliada de cada comando y función disponible.");
+            ui.label(t(
+                "Consulta la documentación ampliada de cada comando y función disponible.",
+            ));
             ui.separator();
 
             egui::ScrollArea::vertical()
                 .id_source("functions_modal_scroll")
                 .show(ui, |ui| {
-                    ui.heading("Comandos integrados");
+                    ui.heading(t("Comandos integrados"));
                     ui.add_space(6.0);
 
-                    for (signature, summary, examples) in builtin_documentation() {
-                        ui.group(|ui| {
-                            ui.strong(signature);
-                            ui.label(summary);
-                            if !examples.is_empty() {
-                                ui.label("Ejemplos:");
-                                for example in examples.iter() {
-                                    ui.monospace(*example);
+                    let commands: Vec<_> = state
+                        .command_engine
+                        .special_builtins()
+                        .chain(state.command_engine.extension_commands())
+                        .filter(|command| command.action().is_none())
+                        .cloned()
+                        .collect();
+                    for command in commands {
+                        ui.group(|ui| match command.documentation() {
+                            Some(doc) => {
+                                ui.strong(doc.signature);
+                                ui.label(t(doc.summary));
+                                if !doc.parameters.is_empty() {
+                                    ui.add_space(4.0);
+                                    ui.label(t("Parámetros:"));
+                                    for parameter in doc.parameters {
+                                        ui.horizontal(|ui| {
+                                            ui.label("•");
+                                            ui.label(*parameter);
+                                        });
+                                    }
                                 }
+                                if !doc.examples.is_empty() {
+                                    ui.add_space(4.0);
+                                    ui.label(t("Ejemplos:"));
+                                    for example in doc.examples.iter() {
+                                        ui.monospace(*example);
+                                    }
+                                }
+                            }
+                            None => {
+                                ui.strong(command.name());
+                                ui.label(command.summary());
                             }
                         });
                         ui.add_space(10.0);
                     }
 
                     ui.separator();
-                    ui.heading("Funciones personalizables");
+                    ui.heading(t("Funciones personalizables"));
                     ui.add_space(6.0);
 
                     for action in state.command_registry.actions() {
@@ -70,7 +393,7 @@ pub fn draw_functions_modal(ctx: &egui::Context, state: &mut AppState) {
                             ui.label(doc.summary);
                             if !doc.parameters.is_empty() {
                                 ui.add_space(4.0);
-                                ui.label("Parámetros:");
+                                ui.label(t("Parámetros:"));
                                 for parameter in doc.parameters {
                                     ui.horizontal(|ui| {
                                         ui.label("•");
@@ -80,7 +403,7 @@ pub fn draw_functions_modal(ctx: &egui::Context, state: &mut AppState) {
                             }
                             if !doc.examples.is_empty() {
                                 ui.add_space(4.0);
-                                ui.label("Ejemplos:");
+                                ui.label(t("Ejemplos:"));
                                 for example in doc.examples.iter() {
                                     ui.monospace(*example);
                                 }
@@ -94,17 +417,244 @@ pub fn draw_functions_modal(ctx: &egui::Context, state: &mut AppState) {
     state.chat.show_functions_modal = is_open;
 }
 
-fn builtin_documentation() -> Vec<(&'static str, &'static str, &'static [&'static str])> {
-    vec![
-        (
-            "/if <condición> then <cmd> [else <cmd>]",
-            "Ejecuta comandos condicionalmente evaluando campos del sistema (por ejemplo memory.enabled o projects.count).",
-            &["/if memory.enabled == true then /status", "/if projects.count > 2 then /models else /help"],
-        ),
-        (
-            "/reload [--force]",
-            "Sincroniza la configuración y admite la bandera --force para reiniciar credenciales.",
-            &["/reload", "/reload --force"],
-        ),
-    ]
+/// Chuleta superpuesta con los atajos configurables y los fijos.
+pub fn draw_shortcuts_cheatsheet(ctx: &egui::Context, state: &mut AppState) {
+    if !state.keybindings.show_cheatsheet {
+        return;
+    }
+
+    let mut is_open = true;
+    let mut configure = false;
+    egui::Window::new(t("Atajos de teclado"))
+        .id(egui::Id::new("shortcuts_cheatsheet"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .open(&mut is_open)
+        .show(ctx, |ui| {
+            for category in ["Chat", "Navegación", "Vista"] {
+                ui.strong(t(category));
+                egui::Grid::new(("shortcuts_cheatsheet", category))
+                    .num_columns(2)
+                    .spacing(egui::vec2(16.0, 4.0))
+                    .show(ui, |ui| {
+                        for action in ShortcutAction::ALL
+                            .into_iter()
+                            .filter(|action| action.category() == category)
+                        {
+                            let chord = keybindings::binding(&state.config.keybindings, action);
+                            ui.monospace(chord.to_string());
+                            ui.label(t(action.label()));
+                            ui.end_row();
+                        }
+                    });
+                ui.add_space(6.0);
+            }
+
+            ui.strong(t("Atajos fijos"));
+            egui::Grid::new("shortcuts_cheatsheet_reserved")
+                .num_columns(2)
+                .spacing(egui::vec2(16.0, 4.0))
+                .show(ui, |ui| {
+                    for (keys, description) in keybindings::RESERVED_SHORTCUTS {
+                        ui.monospace(*keys);
+                        ui.label(t(description));
+                        ui.end_row();
+                    }
+                });
+
+            ui.add_space(8.0);
+            if ui.button(t("Configurar atajos")).clicked() {
+                configure = true;
+            }
+        });
+
+    if configure {
+        is_open = false;
+        state.activate_navigation_target(NavigationTarget::preference(
+            PreferencePanel::CustomizationKeybindings,
+        ));
+    }
+    state.keybindings.show_cheatsheet = is_open;
+}
+
+/// Ofrece restaurar la sesión anterior cuando la aplicación no se cerró limpiamente.
+pub fn draw_session_recovery_modal(ctx: &egui::Context, state: &mut AppState) {
+    let Some(snapshot) = state.session.recovery.as_ref() else {
+        return;
+    };
+    let summary = tf(
+        "Última copia: {0} · {1} mensajes.",
+        &[&snapshot.saved_at, &snapshot.message_count().to_string()],
+    );
+
+    let mut restore = false;
+    let mut discard = false;
+    egui::Window::new(t("Recuperar la sesión anterior"))
+        .id(egui::Id::new("session_recovery_modal"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(t(
+                "JungleMonkAI no se cerró correctamente. Puedes recuperar la conversación, el borrador y la vista en la que estabas.",
+            ));
+            ui.label(summary);
+            if let Some(report) = &state.session.crash_report {
+                ui.add_space(4.0);
+                ui.label(tf(
+                    "Informe del fallo: {0}",
+                    &[&report.display().to_string()],
+                ));
+            }
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                restore = ui.button(t("Restaurar")).clicked();
+                discard = ui.button(t("Descartar")).clicked();
+            });
+        });
+
+    if restore {
+        if let Some(count) = state.restore_previous_session() {
+            state.toasts.push(
+                LogStatus::Ok,
+                tf("Sesión restaurada con {0} mensajes.", &[&count.to_string()]),
+            );
+        }
+    } else if discard {
+        state.discard_previous_session();
+    }
+}
+
+/// Pregunta qué versión conservar cuando `config.json` y la aplicación cambiaron las mismas
+/// secciones.
+pub fn draw_config_conflict_modal(ctx: &egui::Context, state: &mut AppState) {
+    let Some(conflict) = state.config_saver.conflict.as_ref() else {
+        return;
+    };
+    let sections = conflict.sections.join(", ");
+
+    let mut choice = None;
+    egui::Window::new(t("Conflicto en la configuración"))
+        .id(egui::Id::new("config_conflict_modal"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(t(
+                "config.json se editó fuera de la aplicación y estas secciones también cambiaron aquí. Los guardados quedan en pausa hasta que elijas.",
+            ));
+            ui.label(tf("Secciones: {0}", &[&sections]));
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button(t("Usar el fichero")).clicked() {
+                    choice = Some(false);
+                }
+                if ui.button(t("Conservar los de la aplicación")).clicked() {
+                    choice = Some(true);
+                }
+            });
+        });
+
+    if let Some(keep_local) = choice {
+        state.resolve_config_conflict(keep_local);
+    }
+}
+
+/// Pide permiso para los efectos de la automatización más antigua que espera.
+pub fn draw_automation_permission_modal(ctx: &egui::Context, state: &mut AppState) {
+    let Some(request) = state.permissions.queue.front() else {
+        return;
+    };
+    let name = request.name.clone();
+    let effects: Vec<&'static str> = request
+        .effects
+        .iter()
+        .map(|effect| permissions::effect_label(*effect))
+        .collect();
+    let waiting = state.permissions.queue.len() - 1;
+
+    let mut choice = None;
+    egui::Window::new(t("Permiso de automatización"))
+        .id(egui::Id::new("automation_permission_modal"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(tf("'{0}' quiere:", &[&name]));
+            for effect in &effects {
+                ui.label(format!("• {}", t(effect)));
+            }
+            ui.add_space(8.0);
+            for scope in permissions::PermissionScope::ALL {
+                ui.radio_value(&mut state.permissions.scope, scope, t(scope.label()));
+            }
+            if waiting > 0 {
+                ui.label(tf(
+                    "{0} automatizaciones más esperan después.",
+                    &[&waiting.to_string()],
+                ));
+            }
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button(t("Permitir")).clicked() {
+                    choice = Some(true);
+                }
+                if ui.button(t("Denegar")).clicked() {
+                    choice = Some(false);
+                }
+            });
+        });
+
+    if let Some(allow) = choice {
+        state.resolve_automation_permission(allow);
+    }
+}
+
+/// Pide la frase de paso o reintenta el llavero cuando el historial cifrado sigue bloqueado.
+pub fn draw_storage_unlock_modal(ctx: &egui::Context, state: &mut AppState) {
+    if !vault::is_locked() {
+        return;
+    }
+    let key_source = state.config.storage_encryption.key_source;
+
+    let mut unlock = false;
+    egui::Window::new(t("Historial cifrado"))
+        .id(egui::Id::new("storage_unlock_modal"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            match key_source {
+                StorageKeySource::Passphrase => {
+                    ui.label(t(
+                        "Escribe la frase de paso para cargar las conversaciones y la sesión guardadas.",
+                    ));
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut state.storage_encryption.passphrase)
+                            .password(true),
+                    );
+                    unlock |= response.lost_focus()
+                        && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                }
+                StorageKeySource::Keychain => {
+                    ui.label(t(
+                        "No se pudo leer la clave del llavero del sistema. Desbloquéalo y vuelve a intentarlo.",
+                    ));
+                }
+            }
+            if let Some(status) = &state.storage_encryption.status {
+                ui.colored_label(ui.visuals().weak_text_color(), status);
+            }
+            ui.add_space(8.0);
+            let label = match key_source {
+                StorageKeySource::Passphrase => t("Desbloquear"),
+                StorageKeySource::Keychain => t("Reintentar"),
+            };
+            unlock |= ui.button(label).clicked();
+        });
+
+    if unlock {
+        state.storage_encryption.status = state.unlock_storage().err();
+    }
 }
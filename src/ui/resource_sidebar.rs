@@ -3,7 +3,11 @@ use vscode_shell::components::{
     self, ResourceItem, ResourcePanelModel, ResourcePanelProps, ResourceSectionProps,
 };
 
-use crate::state::{AppState, ChatMessage, SECTION_RESOURCES_REMOTE};
+use crate::i18n::{t, t_path, tf};
+use std::path::Path;
+
+use crate::state::system_monitor::SAMPLE_INTERVAL;
+use crate::state::{format_bytes, AppState, ChatMessage, LoadLevel, SECTION_RESOURCES_REMOTE};
 use crate::ui::layout_bridge::shell_theme;
 
 pub fn draw_resource_sidebar(ctx: &egui::Context, state: &mut AppState) {
@@ -31,36 +35,36 @@ impl AppResourcePanel<'_> {
             .resources
             .jarvis_status
             .clone()
-            .unwrap_or_else(|| "Jarvis listo para iniciar".to_string());
+            .unwrap_or_else(|| t("Jarvis listo para iniciar").to_string());
         let active_model = self
             .state
             .resources
             .jarvis_active_model
             .as_ref()
             .map(|model| model.display_label())
-            .unwrap_or_else(|| "Sin modelo seleccionado".to_string());
+            .unwrap_or_else(|| t("Sin modelo seleccionado").to_string());
 
         vec![ResourceSectionProps {
             id: "status".into(),
-            title: "Jarvis runtime".into(),
-            description: Some("Resumen del entorno local".into()),
+            title: t("Jarvis runtime").into(),
+            description: Some(t("Resumen del entorno local").into()),
             items: vec![
                 ResourceItem {
                     id: "status:jarvis".into(),
                     title: jarvis_status,
-                    subtitle: Some(format!(
-                        "Inicio automático: {}",
-                        if self.state.resources.jarvis_auto_start {
+                    subtitle: Some(tf(
+                        "Inicio automático: {0}",
+                        &[t(if self.state.resources.jarvis_auto_start {
                             "activado"
                         } else {
                             "manual"
-                        }
+                        })],
                     )),
                     selected: false,
                 },
                 ResourceItem {
                     id: "status:model".into(),
-                    title: format!("Modelo configurado: {}", active_model),
+                    title: tf("Modelo configurado: {0}", &[&active_model]),
                     subtitle: Some(format!("Alias: {}", self.state.resources.jarvis_alias)),
                     selected: false,
                 },
@@ -68,20 +72,152 @@ impl AppResourcePanel<'_> {
         }]
     }
 
+    /// Indicadores de CPU, memoria, GPU y disco con los datos del monitor de recursos.
+    fn system_section(&self) -> ResourceSectionProps {
+        let monitor = &self.state.resources.system_monitor;
+        let mut items = Vec::new();
+        match monitor.latest() {
+            Some(sample) => {
+                let average_cpu = monitor
+                    .history
+                    .iter()
+                    .map(|sample| sample.cpu_percent)
+                    .sum::<f32>()
+                    / monitor.history.len() as f32;
+                items.push(ResourceItem {
+                    id: "system:cpu".into(),
+                    title: format!(
+                        "{} CPU {:.0} %",
+                        led(Some(LoadLevel::from_percent(sample.cpu_percent))),
+                        sample.cpu_percent
+                    ),
+                    subtitle: Some(tf(
+                        "Media de los últimos {0} s: {1} %",
+                        &[
+                            &(monitor.history.len() as u64 * SAMPLE_INTERVAL.as_secs()).to_string(),
+                            &format!("{:.0}", average_cpu),
+                        ],
+                    )),
+                    selected: false,
+                });
+
+                let limit_bytes =
+                    (f64::from(self.state.resource_memory_limit_gb) * 1024.0 * 1024.0 * 1024.0)
+                        as u64;
+                let memory_level = if monitor.generation_paused {
+                    LoadLevel::Critical
+                } else {
+                    LoadLevel::from_percent(sample.memory_percent())
+                };
+                items.push(ResourceItem {
+                    id: "system:memory".into(),
+                    title: format!(
+                        "{} RAM {} / {}",
+                        led(Some(memory_level)),
+                        format_bytes(sample.memory_used_bytes),
+                        format_bytes(sample.memory_total_bytes)
+                    ),
+                    subtitle: Some(tf(
+                        "JungleMonkAI: {0} de {1} permitidos",
+                        &[
+                            &format_bytes(sample.process_memory_bytes),
+                            &format_bytes(limit_bytes),
+                        ],
+                    )),
+                    selected: false,
+                });
+
+                items.push(match &sample.gpu {
+                    Some(gpu) => ResourceItem {
+                        id: "system:gpu".into(),
+                        title: format!(
+                            "{} GPU {:.0} %",
+                            led(Some(LoadLevel::from_percent(gpu.utilization_percent))),
+                            gpu.utilization_percent
+                        ),
+                        subtitle: Some(tf(
+                            "VRAM {0} / {1}",
+                            &[
+                                &format_bytes(gpu.memory_used_bytes),
+                                &format_bytes(gpu.memory_total_bytes),
+                            ],
+                        )),
+                        selected: false,
+                    },
+                    None => ResourceItem {
+                        id: "system:gpu".into(),
+                        title: format!("{} GPU", led(None)),
+                        subtitle: Some(t("Sin datos: no se encontró nvidia-smi").into()),
+                        selected: false,
+                    },
+                });
+
+                let install_dir = Path::new(&self.state.resources.jarvis_install_dir);
+                if let Some(disk) = sample.disk_for(install_dir) {
+                    items.push(ResourceItem {
+                        id: "system:disk".into(),
+                        title: tf(
+                            "{0} Disco {1} libres",
+                            &[
+                                led(Some(LoadLevel::from_percent(disk.used_percent()))),
+                                &format_bytes(disk.available_bytes),
+                            ],
+                        ),
+                        subtitle: Some(tf(
+                            "{0} de {1} en {2}",
+                            &[
+                                &format_bytes(
+                                    disk.total_bytes.saturating_sub(disk.available_bytes),
+                                ),
+                                &format_bytes(disk.total_bytes),
+                                &disk.mount_point.display().to_string(),
+                            ],
+                        )),
+                        selected: false,
+                    });
+                }
+
+                if monitor.generation_paused {
+                    items.push(ResourceItem {
+                        id: "system:paused".into(),
+                        title: t("⏸ Generación local en pausa").into(),
+                        subtitle: Some(
+                            t("Se reanudará cuando la memoria baje del límite configurado").into(),
+                        ),
+                        selected: false,
+                    });
+                }
+            }
+            None => items.push(ResourceItem {
+                id: "system:pending".into(),
+                title: format!("{} {}", led(None), t("Midiendo recursos…")),
+                subtitle: None,
+                selected: false,
+            }),
+        }
+
+        ResourceSectionProps {
+            id: "system".into(),
+            title: t("Sistema").into(),
+            description: Some(t("Uso de recursos en tiempo real").into()),
+            items,
+        }
+    }
+
     fn quick_actions(&self) -> ResourceSectionProps {
         let mut items = vec![
             ResourceItem {
                 id: "action:open_settings".into(),
-                title: "Abrir preferencias".into(),
-                subtitle: Some("Configura proveedores y automatizaciones".into()),
+                title: t("Abrir preferencias").into(),
+                subtitle: Some(t("Configura proveedores y automatizaciones").into()),
                 selected: false,
             },
             ResourceItem {
                 id: "action:open_functions".into(),
-                title: "Explorar funciones".into(),
-                subtitle: Some(format!(
-                    "{} funciones personalizables",
-                    self.state.command_registry.actions().len()
+                title: t("Explorar funciones").into(),
+                subtitle: Some(tf(
+                    "{0} funciones personalizables",
+                    &[&self.state.command_registry.actions().len().to_string()],
                 )),
                 selected: false,
             },
@@ -90,16 +226,22 @@ impl AppResourcePanel<'_> {
         if !self.state.chat.messages.is_empty() {
             items.push(ResourceItem {
                 id: "action:copy_conversation".into(),
-                title: "Copiar conversación".into(),
-                subtitle: Some("Guarda el historial actual en el portapapeles".into()),
+                title: t("Copiar conversación").into(),
+                subtitle: Some(t("Guarda el historial actual en el portapapeles").into()),
                 selected: false,
             });
         }
+        items.push(ResourceItem {
+            id: "action:share_conversation".into(),
+            title: t("Compartir conversación").into(),
+            subtitle: Some(t("Exporta o importa un archivo cifrado con frase de paso").into()),
+            selected: false,
+        });
 
         ResourceSectionProps {
             id: "quick-actions".into(),
-            title: "Acciones rápidas".into(),
-            description: Some("Atajos frecuentes durante la sesión".into()),
+            title: t("Acciones rápidas").into(),
+            description: Some(t("Atajos frecuentes durante la sesión").into()),
             items,
         }
     }
@@ -113,20 +255,32 @@ impl AppResourcePanel<'_> {
         {
             items.push(ResourceItem {
                 id: node.id,
-                title: node.label,
-                subtitle: node.description,
+                title: t_path(&node.label),
+                subtitle: node
+                    .description
+                    .map(|description| t(&description).to_string()),
                 selected: self.state.is_navigation_target_active(node.target),
             });
         }
         ResourceSectionProps {
             id: "resource-nav".into(),
-            title: "Catálogos destacados".into(),
-            description: Some("Explora proveedores conectados".into()),
+            title: t("Catálogos destacados").into(),
+            description: Some(t("Explora proveedores conectados").into()),
             items,
         }
     }
 }
 
+/// Indicador de color según la carga; gris cuando no hay datos.
+fn led(level: Option<LoadLevel>) -> &'static str {
+    match level {
+        Some(LoadLevel::Normal) => "🟢",
+        Some(LoadLevel::High) => "🟡",
+        Some(LoadLevel::Critical) => "🔴",
+        None => "⚪",
+    }
+}
+
 fn copy_conversation_to_clipboard(ctx: &egui::Context, messages: &[ChatMessage]) {
     if messages.is_empty() {
         return;
@@ -158,13 +312,14 @@ impl ResourcePanelModel for AppResourcePanel<'_> {
 
     fn props(&self) -> ResourcePanelProps {
         let mut sections = self.status_sections();
+        sections.push(self.system_section());
         sections.push(self.quick_actions());
         sections.push(self.resource_navigation());
 
         ResourcePanelProps {
-            title: Some("Recursos".into()),
+            title: Some(t("Recursos").into()),
             sections,
-            collapse_button_tooltip: Some("Ocultar panel de recursos".into()),
+            collapse_button_tooltip: Some(t("Ocultar panel de recursos").into()),
         }
     }
 
@@ -173,6 +328,8 @@ impl ResourcePanelModel for AppResourcePanel<'_> {
             "action:open_settings" => self.state.show_settings_modal = true,
             "action:open_functions" => self.state.chat.show_functions_modal = true,
             "action:copy_conversation" => self.state.chat.pending_copy_conversation = true,
+            "action:share_conversation" => self.state.share.show_modal = true,
+            id if id.starts_with("system:") => {}
             _ => {
                 let _ = self.state.activate_navigation_node(item_id);
             }
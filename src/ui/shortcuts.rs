@@ -0,0 +1,113 @@
+use eframe::egui;
+use vscode_shell::components;
+
+use crate::i18n::t;
+use crate::state::{
+    keybindings, AppState, KeyChord, LogStatus, MainTab, MainView, PreferencePanel, ShortcutAction,
+};
+
+use super::chat;
+
+/// Ejecuta el atajo pulsado en este frame. La pulsación se retira de la entrada para que
+/// los controles no la procesen también; las acciones que no aplican en la vista actual
+/// la dejan pasar. Mientras se reasigna un atajo, la pulsación pasa a ser su combinación.
+pub fn handle_shortcuts(ctx: &egui::Context, state: &mut AppState) {
+    let editing = state.active_main_view == MainView::Preferences
+        && state.selected_preference == PreferencePanel::CustomizationKeybindings;
+    if !editing {
+        state.keybindings.capturing = None;
+    }
+    if let Some(action) = state.keybindings.capturing {
+        if let Some(chord) = take_key_press(ctx, Some) {
+            state.keybindings.capturing = None;
+            if chord == KeyChord::plain(egui::Key::Escape) {
+                state.keybindings.status = Some(t("Reasignación cancelada.").to_string());
+            } else {
+                state.set_keybinding(action, chord);
+            }
+        }
+        return;
+    }
+
+    let pressed = take_key_press(ctx, |chord| {
+        keybindings::action_for(&state.config.keybindings, chord)
+            .filter(|action| is_available(state, *action))
+    });
+    if let Some(action) = pressed {
+        run_action(ctx, state, action);
+    }
+}
+
+fn take_key_press<T>(
+    ctx: &egui::Context,
+    mut matcher: impl FnMut(KeyChord) -> Option<T>,
+) -> Option<T> {
+    ctx.input_mut(|input| {
+        let mut found = None;
+        input.events.retain(|event| match event {
+            egui::Event::Key {
+                key,
+                pressed: true,
+                modifiers,
+                ..
+            } if found.is_none() => match matcher(KeyChord::from_input(*key, *modifiers)) {
+                Some(value) => {
+                    found = Some(value);
+                    false
+                }
+                None => true,
+            },
+            _ => true,
+        });
+        found
+    })
+}
+
+fn is_available(state: &AppState, action: ShortcutAction) -> bool {
+    let in_chat = state.active_main_view == MainView::ChatMultimodal;
+    match action {
+        ShortcutAction::SendMessage => in_chat && !state.chat.input.trim().is_empty(),
+        ShortcutAction::PreviousMessage | ShortcutAction::NextMessage => {
+            in_chat && !state.chat.messages.is_empty()
+        }
+        ShortcutAction::CancelPendingCall => !state.chat.pending_provider_calls.is_empty(),
+        ShortcutAction::PreviousNavigation | ShortcutAction::NextNavigation => {
+            state.config.accessibility.keyboard_navigation
+        }
+        ShortcutAction::OpenPalette
+        | ShortcutAction::ShowChatTab
+        | ShortcutAction::ShowCronTab
+        | ShortcutAction::ShowActivityTab
+        | ShortcutAction::ShowDebugTab
+        | ShortcutAction::ToggleZen
+        | ShortcutAction::ShowCheatSheet => true,
+    }
+}
+
+fn run_action(ctx: &egui::Context, state: &mut AppState, action: ShortcutAction) {
+    match action {
+        ShortcutAction::SendMessage => chat::submit_chat_message(state),
+        ShortcutAction::OpenPalette => {
+            ctx.memory_mut(|memory| memory.request_focus(components::header_search_id()));
+        }
+        ShortcutAction::PreviousMessage => state.step_focused_message(-1),
+        ShortcutAction::NextMessage => state.step_focused_message(1),
+        ShortcutAction::ShowChatTab => state.set_active_tab(MainTab::Chat),
+        ShortcutAction::ShowCronTab => state.set_active_tab(MainTab::Cron),
+        ShortcutAction::ShowActivityTab => state.set_active_tab(MainTab::Activity),
+        ShortcutAction::ShowDebugTab => state.set_active_tab(MainTab::DebugConsole),
+        ShortcutAction::CancelPendingCall => {
+            if state.cancel_latest_provider_call() {
+                state
+                    .toasts
+                    .push(LogStatus::Warning, t("Llamada al proveedor cancelada."));
+            }
+        }
+        ShortcutAction::PreviousNavigation => state.step_navigation(-1),
+        ShortcutAction::NextNavigation => state.step_navigation(1),
+        ShortcutAction::ToggleZen => state.toggle_zen_mode(),
+        ShortcutAction::ShowCheatSheet => {
+            state.keybindings.show_cheatsheet = !state.keybindings.show_cheatsheet;
+        }
+    }
+}
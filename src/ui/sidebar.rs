@@ -1,6 +1,7 @@
 use eframe::egui;
 use vscode_shell::components::{self, NavigationModel, SidebarItem, SidebarProps, SidebarSection};
 
+use crate::i18n::{t, t_path};
 use crate::state::{AppState, NavigationNode};
 use crate::ui::layout_bridge::shell_theme;
 
@@ -25,7 +26,7 @@ impl AppSidebar<'_> {
             .into_iter()
             .map(|(section, nodes)| SidebarSection {
                 id: section.id,
-                title: section.title,
+                title: t_path(&section.title),
                 items: nodes
                     .into_iter()
                     .map(|node| self.sidebar_item(node))
@@ -38,8 +39,10 @@ impl AppSidebar<'_> {
         let selected = self.state.is_navigation_target_active(node.target);
         SidebarItem {
             id: node.id,
-            label: node.label,
-            description: node.description,
+            label: t_path(&node.label),
+            description: node
+                .description
+                .map(|description| t(&description).to_string()),
             icon: node.icon,
             badge: node.badge,
             selected,
@@ -54,9 +57,9 @@ impl NavigationModel for AppSidebar<'_> {
 
     fn props(&self) -> SidebarProps {
         SidebarProps {
-            title: Some("Navegación".into()),
+            title: Some(t("Navegación").into()),
             sections: self.sections(),
-            collapse_button_tooltip: Some("Ocultar navegación".into()),
+            collapse_button_tooltip: Some(t("Ocultar navegación").into()),
         }
     }
 
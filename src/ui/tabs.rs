@@ -1,5 +1,6 @@
 use eframe::egui::{self, Margin, RichText, Sense, Stroke};
 
+use crate::i18n::t;
 use crate::state::MainTab;
 
 use super::theme::{self, ThemeTokens};
@@ -97,7 +98,8 @@ fn draw_tab_button<T: Copy + PartialEq>(
         tokens.palette.border
     };
 
-    let galley = egui::WidgetText::from(definition.label).into_galley(
+    let label = t(definition.label);
+    let galley = egui::WidgetText::from(label).into_galley(
         ui,
         Some(false),
         f32::INFINITY,
@@ -121,12 +123,7 @@ fn draw_tab_button<T: Copy + PartialEq>(
         label_ui.add_space(6.0);
     }
 
-    label_ui.label(
-        RichText::new(definition.label)
-            .color(text_color)
-            .strong()
-            .size(14.0),
-    );
+    label_ui.label(RichText::new(label).color(text_color).strong().size(14.0));
 
     if is_active {
         painter.line_segment(
@@ -141,7 +138,7 @@ fn draw_tab_button<T: Copy + PartialEq>(
     let was_clicked = response.clicked();
 
     if !definition.tooltip.is_empty() {
-        response.on_hover_text(definition.tooltip);
+        response.on_hover_text(t(definition.tooltip));
     }
 
     was_clicked
@@ -513,6 +513,12 @@ pub fn secondary_button<'a>(
     egui::Button::new(text).fill(tokens.palette.secondary_background)
 }
 
+/// Da un nombre accesible y un tooltip a un botón que solo muestra un icono.
+pub fn icon_button_label(response: egui::Response, label: &str) -> egui::Response {
+    response.widget_info(|| egui::WidgetInfo::labeled(egui::WidgetType::Button, label));
+    response.on_hover_text(label)
+}
+
 pub fn subtle_border(tokens: &ThemeTokens) -> Stroke {
     Stroke::new(1.0, tokens.palette.border)
 }
@@ -570,9 +576,8 @@ fn icon_font_bytes() -> Option<&'static Vec<u8>> {
 }
 
 fn fetch_icon_font() -> Option<Vec<u8>> {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(Duration::from_secs(10))
-        .build()
+    let client = crate::api::network::client_builder(Duration::from_secs(10))
+        .and_then(|builder| builder.build().map_err(anyhow::Error::from))
         .map_err(|err| eprintln!("No se pudo crear el cliente HTTP para la fuente: {err}"))
         .ok()?;
 
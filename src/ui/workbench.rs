@@ -1,3 +1,4 @@
+use crate::i18n::t;
 use crate::state::AppState;
 use eframe::egui;
 use vscode_shell::components::{MainContentAction, MainContentTab};
@@ -56,21 +57,23 @@ pub fn default_layout_actions(state: &AppState) -> Vec<MainContentAction> {
     vec![
         MainContentAction {
             id: "toggle-navigation".into(),
-            label: if state.layout.navigation_collapsed() {
-                "Mostrar navegación".into()
+            label: t(if state.layout.navigation_collapsed() {
+                "Mostrar navegación"
             } else {
-                "Ocultar navegación".into()
-            },
+                "Ocultar navegación"
+            })
+            .into(),
             icon: Some("📂".into()),
             enabled: true,
         },
         MainContentAction {
             id: "toggle-resources".into(),
-            label: if state.layout.resource_collapsed() {
-                "Mostrar recursos".into()
+            label: t(if state.layout.resource_collapsed() {
+                "Mostrar recursos"
             } else {
-                "Ocultar recursos".into()
-            },
+                "Ocultar recursos"
+            })
+            .into(),
             icon: Some("📚".into()),
             enabled: true,
         },
@@ -69,6 +69,11 @@ pub trait HeaderModel {
     fn on_action(&mut self, action_id: &str);
 }
 
+/// Id of the header search field, so the host app can focus it from a shortcut.
+pub fn header_search_id() -> Id {
+    Id::new("shell_header_search")
+}
+
 pub fn draw_header(ctx: &egui::Context, layout: &LayoutConfig, model: &mut dyn HeaderModel) {
     if !layout.show_header {
         return;
@@ -183,6 +188,7 @@ fn draw_search(
                 let response = ui.add_sized(
                     [ui.available_width().max(160.0), 24.0],
                     egui::TextEdit::singleline(&mut query)
+                        .id(header_search_id())
                         .hint_text(placeholder)
                         .frame(false),
                 );
@@ -12,7 +12,7 @@ pub mod tree_view;
 pub use command_palette::{
     draw_command_palette, Command, CommandPaletteModel, CommandPaletteProps,
 };
-pub use header::{draw_header, HeaderAction, HeaderModel, HeaderProps, SearchGroup, SearchResult};
+pub use header::{draw_header, header_search_id, HeaderAction, HeaderModel, HeaderProps, SearchGroup, SearchResult};
 pub use main_content::{
     draw_main_content, MainContentAction, MainContentModel, MainContentProps, MainContentTab,
 };
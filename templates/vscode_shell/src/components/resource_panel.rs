@@ -1,6 +1,6 @@
 use eframe::egui::{self, Color32, Frame, Margin, RichText, Rounding, Sense, Stroke};
 
-use crate::layout::{LayoutConfig, ShellTheme};
+use crate::layout::{LayoutConfig, ShellTheme, RESOURCE_WIDTH_RANGE};
 
 #[derive(Clone, Debug)]
 pub struct ResourcePanelProps {
@@ -64,16 +64,26 @@ pub fn draw_resource_panel(
                     .inner_margin(Margin::same(6.0)),
             )
             .show(ctx, |ui| {
-                if ui.button("◀").on_hover_text("Expandir recursos").clicked() {
+                let response = ui.button("◀");
+                response.widget_info(|| {
+                    egui::WidgetInfo::labeled(egui::WidgetType::Button, "Expandir recursos")
+                });
+                if response.on_hover_text("Expandir recursos").clicked() {
                     layout.emit_resource_signal(false);
                 }
             });
         return;
     }
 
-    egui::SidePanel::right("resource_panel")
-        .resizable(false)
-        .exact_width(layout.resource_width)
+    let mut panel = egui::SidePanel::right("resource_panel")
+        .resizable(true)
+        .width_range(RESOURCE_WIDTH_RANGE)
+        .default_width(layout.resource_width);
+    if layout.take_resource_width_sync() {
+        panel = panel.exact_width(layout.resource_width);
+    }
+
+    let response = panel
         .frame(
             Frame::none()
                 .fill(theme.surface_background)
@@ -95,9 +105,14 @@ pub fn draw_resource_panel(
                 ui.add_space(ui.available_width());
                 let button = egui::Button::new("▶").min_size(egui::vec2(24.0, 24.0));
                 let mut response = ui.add(button);
-                if let Some(tooltip) = props.collapse_button_tooltip.as_ref() {
-                    response = response.on_hover_text(tooltip);
-                }
+                let tooltip = props
+                    .collapse_button_tooltip
+                    .clone()
+                    .unwrap_or_else(|| "Contraer recursos".to_string());
+                response.widget_info(|| {
+                    egui::WidgetInfo::labeled(egui::WidgetType::Button, tooltip.as_str())
+                });
+                response = response.on_hover_text(tooltip);
                 if response.clicked() {
                     layout.emit_resource_signal(true);
                 }
@@ -128,6 +143,8 @@ pub fn draw_resource_panel(
                     }
                 });
         });
+
+    layout.resource_width = response.response.rect.width();
 }
 
 fn resource_entry(ui: &mut egui::Ui, theme: &ShellTheme, item: &ResourceItem) -> egui::Response {
@@ -1,6 +1,6 @@
 use eframe::egui::{self, Margin, RichText, Rounding};
 
-use crate::layout::{LayoutConfig, ShellTheme};
+use crate::layout::{LayoutConfig, ShellTheme, NAVIGATION_WIDTH_RANGE};
 
 #[derive(Clone, Debug)]
 pub struct SidebarProps {
@@ -65,16 +65,26 @@ pub fn draw_sidebar(
                     .inner_margin(Margin::same(6.0)),
             )
             .show(ctx, |ui| {
-                if ui.button("▶").on_hover_text("Expandir panel").clicked() {
+                let response = ui.button("▶");
+                response.widget_info(|| {
+                    egui::WidgetInfo::labeled(egui::WidgetType::Button, "Expandir panel")
+                });
+                if response.on_hover_text("Expandir panel").clicked() {
                     layout.emit_navigation_signal(false);
                 }
             });
         return;
     }
 
-    egui::SidePanel::left("navigation_panel")
-        .resizable(false)
-        .exact_width(layout.navigation_width)
+    let mut panel = egui::SidePanel::left("navigation_panel")
+        .resizable(true)
+        .width_range(NAVIGATION_WIDTH_RANGE)
+        .default_width(layout.navigation_width);
+    if layout.take_navigation_width_sync() {
+        panel = panel.exact_width(layout.navigation_width);
+    }
+
+    let response = panel
         .frame(
             egui::Frame::none()
                 .fill(theme.surface_background)
@@ -97,9 +107,14 @@ pub fn draw_sidebar(
                 ui.add_space(ui.available_width());
                 let button = egui::Button::new("◀").min_size(egui::vec2(24.0, 24.0));
                 let mut response = ui.add(button);
-                if let Some(tooltip) = props.collapse_button_tooltip.as_ref() {
-                    response = response.on_hover_text(tooltip);
-                }
+                let tooltip = props
+                    .collapse_button_tooltip
+                    .clone()
+                    .unwrap_or_else(|| "Contraer panel".to_string());
+                response.widget_info(|| {
+                    egui::WidgetInfo::labeled(egui::WidgetType::Button, tooltip.as_str())
+                });
+                response = response.on_hover_text(tooltip);
                 if response.clicked() {
                     layout.emit_navigation_signal(true);
                 }
@@ -127,6 +142,8 @@ pub fn draw_sidebar(
                     }
                 });
         });
+
+    layout.navigation_width = response.response.rect.width();
 }
 
 fn nav_entry(ui: &mut egui::Ui, theme: &ShellTheme, item: &SidebarItem) -> egui::Response {
@@ -147,6 +164,13 @@ fn nav_entry(ui: &mut egui::Ui, theme: &ShellTheme, item: &SidebarItem) -> egui:
     .min_size(egui::vec2(0.0, 32.0));
 
     let mut response = ui.add(button);
+    response.widget_info(|| {
+        egui::WidgetInfo::selected(
+            egui::WidgetType::SelectableLabel,
+            item.selected,
+            item.label.as_str(),
+        )
+    });
     if let Some(description) = &item.description {
         response = response.on_hover_text(description);
     }
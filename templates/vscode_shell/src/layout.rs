@@ -28,6 +28,11 @@ impl Default for ShellTheme {
     }
 }
 
+/// Rango de anchos admitido al redimensionar el panel de navegación.
+pub const NAVIGATION_WIDTH_RANGE: std::ops::RangeInclusive<f32> = 200.0..=480.0;
+/// Rango de anchos admitido al redimensionar el panel de recursos.
+pub const RESOURCE_WIDTH_RANGE: std::ops::RangeInclusive<f32> = 240.0..=560.0;
+
 /// Controla la visibilidad y el ancho de los paneles principales del layout.
 #[derive(Clone, Debug)]
 pub struct LayoutConfig {
@@ -40,6 +45,8 @@ pub struct LayoutConfig {
     resource_collapsed: bool,
     navigation_signal: Option<bool>,
     resource_signal: Option<bool>,
+    navigation_width_sync: bool,
+    resource_width_sync: bool,
 }
 
 impl Default for LayoutConfig {
@@ -54,6 +61,8 @@ impl Default for LayoutConfig {
             resource_collapsed: false,
             navigation_signal: None,
             resource_signal: None,
+            navigation_width_sync: false,
+            resource_width_sync: false,
         }
     }
 }
@@ -92,6 +101,21 @@ impl LayoutConfig {
     pub fn take_resource_signal(&mut self) -> Option<bool> {
         self.resource_signal.take()
     }
+
+    /// Fuerza a los paneles laterales a adoptar `navigation_width` y `resource_width`
+    /// en el siguiente frame, descartando el ancho recordado por egui.
+    pub fn request_width_sync(&mut self) {
+        self.navigation_width_sync = true;
+        self.resource_width_sync = true;
+    }
+
+    pub(crate) fn take_navigation_width_sync(&mut self) -> bool {
+        std::mem::take(&mut self.navigation_width_sync)
+    }
+
+    pub(crate) fn take_resource_width_sync(&mut self) -> bool {
+        std::mem::take(&mut self.resource_width_sync)
+    }
 }
 
 /// Envoltorio utilitario que pinta un panel principal centralizado.